@@ -0,0 +1,249 @@
+//! Débit du mix : `cargo bench -p troubadour-core`.
+//!
+//! 16 entrées (le Mic avec ses effets) routées vers un bus, en blocs de
+//! 512 frames, par le même chemin que le callback (`render_offline`).
+//! Criterion rapporte des blocs par seconde : à comparer d'une branche
+//! à l'autre avant de toucher au chemin chaud.
+//!
+//! `64ch_x_256` est une session pleine (`DEFAULT_MAX_CHANNELS`) : 62
+//! entrées vers 2 bus, en blocs de 256 frames. À 48 kHz, un bloc doit
+//! être mixé en moins de 5,3 ms, soit au moins 188 blocs par seconde,
+//! sans compter le reste du callback.
+//!
+//! `verbose_logging` refait `16ch_x_512` sous un subscriber `tracing`
+//! au niveau TRACE, le pire cas quand on active les logs pour chercher
+//! des coupures : le chemin audio n'appelant pas `tracing`, les deux
+//! débits doivent être du même ordre.
+//!
+//! `effects_chain` mesure la chaîne du Mic seule : la boucle nue sur
+//! `process_sample`, puis `process_block` profilage coupé et activé.
+//! Coupé, le profilage ne doit rien coûter face à la boucle nue.
+//!
+//! `true_peak` mesure un bloc stéréo de 512 frames (4 phases × 12
+//! coefficients par sample), à comparer aux 10,7 ms du bloc à 48 kHz.
+//!
+//! `meter_frame` encode un tick de 64 canaux en trame binaire puis en
+//! JSON (ce que reçoit une UI web).
+//!
+//! `preset_listing` résume 500 presets générés, cache vide (chaque
+//! fichier parsé) puis cache chaud (seuls les mtimes sont lus).
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, SystemTime};
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+use troubadour_core::dsp::EffectsChain;
+use troubadour_core::dsp::true_peak::TruePeakMeter;
+use troubadour_core::render::{RenderSettings, render_offline};
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::dsp::EffectsPreset;
+use troubadour_shared::meter_frame::{MeterFrameEncoder, MeterLayout};
+use troubadour_shared::mixer::{
+    ChannelConfig, ChannelLevel, DEFAULT_MAX_CHANNELS, MixerConfig, Route,
+};
+use troubadour_shared::preset::EffectsPresetManager;
+use troubadour_shared::profile::Profile;
+
+const CHANNELS: usize = 16;
+const BLOCK_FRAMES: usize = 512;
+const BLOCKS: usize = 100;
+
+/// Bus de la session pleine ; les autres canaux sont des entrées.
+const FULL_SESSION_BUSES: usize = 2;
+const FULL_SESSION_FRAMES: usize = 256;
+
+/// `inputs` entrées routées tour à tour vers `buses` bus.
+fn profile(inputs: usize, buses: usize) -> Profile {
+    let mut mixer = MixerConfig::default();
+    for id in 0..inputs {
+        mixer
+            .channels
+            .push(ChannelConfig::input(id, format!("In {id}")));
+        mixer
+            .routes
+            .push(Route::new(ChannelId(id), ChannelId(inputs + id % buses)));
+    }
+    for bus in inputs..inputs + buses {
+        mixer
+            .channels
+            .push(ChannelConfig::output(bus, format!("Bus {bus}")));
+    }
+    Profile {
+        mixer,
+        effects: EffectsPreset::default_preset(),
+        ..Profile::default_profile()
+    }
+}
+
+/// Un sinus différent par entrée, sur `frames` frames.
+fn signals(inputs: usize, frames: usize) -> HashMap<ChannelId, Vec<f32>> {
+    (0..inputs)
+        .map(|id| {
+            let signal = (0..frames)
+                .map(|n| 0.3 * (n as f32 * 0.01 * (id + 1) as f32).sin())
+                .collect();
+            (ChannelId(id), signal)
+        })
+        .collect()
+}
+
+fn mix(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mix");
+    group.throughput(Throughput::Elements(BLOCKS as u64));
+    let full_session = DEFAULT_MAX_CHANNELS - FULL_SESSION_BUSES;
+    for (name, inputs, buses, block_frames) in [
+        ("16ch_x_512", CHANNELS, 1, BLOCK_FRAMES),
+        (
+            "64ch_x_256",
+            full_session,
+            FULL_SESSION_BUSES,
+            FULL_SESSION_FRAMES,
+        ),
+    ] {
+        let profile = profile(inputs, buses);
+        let inputs = signals(inputs, BLOCKS * block_frames);
+        let settings = RenderSettings {
+            block_frames,
+            ..RenderSettings::default()
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| render_offline(black_box(&profile), black_box(&inputs), BLOCKS, settings))
+        });
+    }
+    group.finish();
+}
+
+fn verbose_logging(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verbose_logging");
+    group.throughput(Throughput::Elements(BLOCKS as u64));
+    let profile = profile(CHANNELS, 1);
+    let inputs = signals(CHANNELS, BLOCKS * BLOCK_FRAMES);
+    let settings = RenderSettings {
+        block_frames: BLOCK_FRAMES,
+        ..RenderSettings::default()
+    };
+    let mut render = || render_offline(black_box(&profile), black_box(&inputs), BLOCKS, settings);
+    group.bench_function("16ch_x_512_quiet", |b| b.iter(&mut render));
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .with_writer(std::io::sink)
+        .finish();
+    tracing::subscriber::with_default(subscriber, || {
+        group.bench_function("16ch_x_512_trace", |b| b.iter(&mut render));
+    });
+    group.finish();
+}
+
+fn effects_chain(c: &mut Criterion) {
+    let block: Vec<f32> = (0..BLOCK_FRAMES)
+        .map(|n| 0.5 * (n as f32 * 0.05).sin())
+        .collect();
+    let mut group = c.benchmark_group("effects_chain");
+    group.throughput(Throughput::Elements(1));
+    let mut chain = EffectsChain::from_preset(&EffectsPreset::default_preset());
+    let mut buffer = block.clone();
+    group.bench_function("mic_x_512_bare", |b| {
+        b.iter(|| {
+            buffer.copy_from_slice(&block);
+            for sample in buffer.iter_mut() {
+                *sample = chain.process_sample(*sample);
+            }
+            black_box(&buffer);
+        })
+    });
+    for (name, profiling) in [("mic_x_512", false), ("mic_x_512_profiled", true)] {
+        let mut chain = EffectsChain::from_preset(&EffectsPreset::default_preset());
+        chain.set_profiling(profiling);
+        let mut buffer = block.clone();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                buffer.copy_from_slice(&block);
+                chain.process_block(black_box(&mut buffer))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn true_peak(c: &mut Criterion) {
+    let block: Vec<f32> = (0..BLOCK_FRAMES * 2)
+        .map(|n| 0.5 * ((n / 2) as f32 * 2.0 * std::f32::consts::PI / 48.0).sin())
+        .collect();
+    let mut meter = TruePeakMeter::new(2);
+    let mut group = c.benchmark_group("true_peak");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("stereo_x_512", |b| {
+        b.iter(|| meter.process(black_box(&block)))
+    });
+    group.finish();
+}
+
+fn meter_frame(c: &mut Criterion) {
+    const TARGETS: usize = 64;
+    let levels: Vec<ChannelLevel> = (0..TARGETS)
+        .map(|id| ChannelLevel {
+            channel: ChannelId(id),
+            rms: id as f32 / 64.0,
+            peak: id as f32 / 32.0,
+            rms_position: 0.5,
+            peak_position: 0.75,
+            loudness: None,
+            true_peak: None,
+            clipped: false,
+        })
+        .collect();
+    let targets = levels.iter().map(|l| l.channel).collect();
+    let mut encoder = MeterFrameEncoder::new(MeterLayout::new(1, targets));
+    let mut frame = Vec::new();
+    let mut group = c.benchmark_group("meter_frame");
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("binary_64ch", |b| {
+        b.iter(|| encoder.encode_into(black_box(&levels), &mut frame))
+    });
+    group.bench_function("json_64ch", |b| {
+        b.iter(|| serde_json::to_vec(black_box(&levels)).unwrap())
+    });
+    group.finish();
+}
+
+fn preset_listing(c: &mut Criterion) {
+    const PRESETS: usize = 500;
+    let dir = std::env::temp_dir().join(format!("troubadour-bench-presets-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let manager = EffectsPresetManager::new(&dir);
+    for n in 0..PRESETS {
+        let mut preset = EffectsPreset::streaming();
+        preset.compressor.ratio = 1.0 + (n % 19) as f32;
+        let name = format!("Preset {n:03}");
+        manager.save(&name, &preset, false).unwrap();
+        // Écritures anciennes : le cache peut leur faire confiance
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join(format!("{name}.toml")))
+            .and_then(|file| file.set_modified(SystemTime::now() - Duration::from_secs(60)))
+            .unwrap();
+    }
+
+    let mut group = c.benchmark_group("preset_listing");
+    group.throughput(Throughput::Elements(PRESETS as u64));
+    group.bench_function("500_cold", |b| {
+        b.iter(|| EffectsPresetManager::new(black_box(&dir)).summaries())
+    });
+    manager.summaries();
+    group.bench_function("500_cached", |b| b.iter(|| black_box(&manager).summaries()));
+    group.finish();
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+criterion_group!(
+    benches,
+    mix,
+    verbose_logging,
+    effects_chain,
+    true_peak,
+    meter_frame,
+    preset_listing
+);
+criterion_main!(benches);
@@ -0,0 +1,200 @@
+//! Réglage guidé des niveaux : l'utilisateur parle dans chaque micro à
+//! tour de rôle, le moteur relève la crête de chacun pendant la fenêtre
+//! demandée, puis corrige son volume pour amener cette crête sur la
+//! cible.
+//!
+//! La mesure tourne sur la boucle de contrôle, sans la bloquer : chaque
+//! tick lui passe la crête relevée depuis le précédent, celle qui
+//! alimente aussi l'historique des niveaux (`SharedLevels::take_peak`).
+//! Le premier tick d'un canal ne fait qu'ouvrir sa fenêtre : la crête
+//! accumulée avant date d'avant la demande.
+//!
+//! Le meter mesure après le volume : la correction multiplie le volume
+//! en place. Un canal resté muet, ou qu'il faudrait monter de plus de
+//! `MAX_BOOST_DB`, garde son volume (voir `AutoLevelStatus`).
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::mixer::{AutoLevelResult, AutoLevelStatus};
+
+/// Montée maximale acceptée : au-delà, le micro est sans doute mal
+/// branché ou trop loin, monter le volume ne ferait que du bruit.
+pub const MAX_BOOST_DB: f32 = 18.0;
+
+/// Crête sous laquelle un canal compte comme muet pendant la mesure.
+pub const SILENCE_DB: f32 = -60.0;
+
+/// Plus haut volume du mixer (+6 dB).
+const MAX_VOLUME: f32 = 2.0;
+
+/// Correction d'un canal dont la crête mesurée est `measured_peak`
+/// (linéaire, après `volume`).
+pub fn level_channel(
+    channel: ChannelId,
+    measured_peak: f32,
+    volume: f32,
+    target_peak_db: f32,
+) -> AutoLevelResult {
+    let peak = if measured_peak.is_finite() {
+        measured_peak.abs()
+    } else {
+        0.0
+    };
+    let peak_db = 20.0 * peak.log10();
+    let unchanged = |status, measured_peak_db, needed_gain_db| AutoLevelResult {
+        channel,
+        measured_peak_db,
+        needed_gain_db,
+        applied_gain_db: 0.0,
+        volume,
+        status,
+    };
+    if peak_db < SILENCE_DB || volume <= 0.0 {
+        return unchanged(AutoLevelStatus::Silent, None, 0.0);
+    }
+    let needed = target_peak_db - peak_db;
+    if needed > MAX_BOOST_DB {
+        return unchanged(AutoLevelStatus::BoostRefused, Some(peak_db), needed);
+    }
+    let new_volume = (volume * 10f32.powf(needed / 20.0)).min(MAX_VOLUME);
+    AutoLevelResult {
+        channel,
+        measured_peak_db: Some(peak_db),
+        needed_gain_db: needed,
+        applied_gain_db: 20.0 * (new_volume / volume).log10(),
+        volume: new_volume,
+        status: AutoLevelStatus::Applied,
+    }
+}
+
+/// Mesure en cours : les canaux un par un, chacun sur sa fenêtre.
+#[derive(Debug, Clone)]
+pub struct AutoLevel {
+    channels: Vec<ChannelId>,
+    target_peak_db: f32,
+    duration_ms: u64,
+    /// Canal mesuré (index dans `channels`)
+    index: usize,
+    /// Début de sa fenêtre (`None` : pas encore ouverte)
+    started_ms: Option<u64>,
+    peak: f32,
+    results: Vec<AutoLevelResult>,
+}
+
+impl AutoLevel {
+    pub fn new(channels: Vec<ChannelId>, target_peak_db: f32, duration_ms: u64) -> Self {
+        Self {
+            channels,
+            target_peak_db,
+            duration_ms,
+            index: 0,
+            started_ms: None,
+            peak: 0.0,
+            results: Vec::new(),
+        }
+    }
+
+    pub fn target_peak_db(&self) -> f32 {
+        self.target_peak_db
+    }
+
+    /// Canal à mesurer maintenant (`None` : tous mesurés).
+    pub fn current(&self) -> Option<ChannelId> {
+        self.channels.get(self.index).copied()
+    }
+
+    /// `(index du canal en cours, nombre de canaux)`.
+    pub fn position(&self) -> (usize, usize) {
+        (self.index, self.channels.len())
+    }
+
+    /// Crête du canal en cours relevée à `now_ms`. Quand sa fenêtre se
+    /// termine, retourne le canal et sa crête, et passe au suivant.
+    pub fn observe(&mut self, now_ms: u64, peak: f32) -> Option<(ChannelId, f32)> {
+        let channel = self.current()?;
+        let Some(started_ms) = self.started_ms else {
+            self.started_ms = Some(now_ms);
+            return None;
+        };
+        if peak.is_finite() {
+            self.peak = self.peak.max(peak.abs());
+        }
+        if now_ms.saturating_sub(started_ms) < self.duration_ms {
+            return None;
+        }
+        let measured = std::mem::take(&mut self.peak);
+        self.index += 1;
+        self.started_ms = None;
+        Some((channel, measured))
+    }
+
+    pub fn push_result(&mut self, result: AutoLevelResult) {
+        self.results.push(result);
+    }
+
+    pub fn results(&self) -> &[AutoLevelResult] {
+        &self.results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(level: f32) -> f32 {
+        20.0 * level.log10()
+    }
+
+    #[test]
+    fn gains_bring_the_peak_to_the_target_within_the_cap() {
+        // -24 dBFS mesurés, cible -12 : +12 dB
+        let result = level_channel(ChannelId(0), 10f32.powf(-24.0 / 20.0), 0.5, -12.0);
+        assert_eq!(result.status, AutoLevelStatus::Applied);
+        assert!((result.needed_gain_db - 12.0).abs() < 1e-3);
+        assert!((result.applied_gain_db - 12.0).abs() < 1e-3);
+        assert!((db(result.volume / 0.5) - 12.0).abs() < 1e-3);
+
+        // Trop fort : on baisse
+        let result = level_channel(ChannelId(0), 1.0, 1.0, -6.0);
+        assert!((result.volume - 0.5012).abs() < 1e-3);
+
+        // Volume déjà haut : borné à 2.0, la correction réelle est moindre
+        let result = level_channel(ChannelId(0), 10f32.powf(-18.0 / 20.0), 1.0, -6.0);
+        assert_eq!(result.status, AutoLevelStatus::Applied);
+        assert_eq!(result.volume, 2.0);
+        assert!((result.applied_gain_db - db(2.0)).abs() < 1e-3);
+        assert!(result.needed_gain_db > result.applied_gain_db);
+    }
+
+    #[test]
+    fn silence_and_excessive_boosts_leave_the_volume_alone() {
+        let silent = level_channel(ChannelId(1), 0.0001, 0.8, -12.0);
+        assert_eq!(silent.status, AutoLevelStatus::Silent);
+        assert_eq!((silent.measured_peak_db, silent.volume), (None, 0.8));
+        assert_eq!(
+            level_channel(ChannelId(1), f32::NAN, 0.8, -12.0).status,
+            AutoLevelStatus::Silent
+        );
+
+        // -40 dBFS pour -12 : +28 dB, au-delà de +18
+        let far = level_channel(ChannelId(2), 0.01, 1.0, -12.0);
+        assert_eq!(far.status, AutoLevelStatus::BoostRefused);
+        assert_eq!((far.volume, far.applied_gain_db), (1.0, 0.0));
+        assert!((far.needed_gain_db - 28.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn channels_are_measured_one_window_after_the_other() {
+        let mut auto = AutoLevel::new(vec![ChannelId(0), ChannelId(1)], -12.0, 1_000);
+        assert_eq!(auto.position(), (0, 2));
+        // Le premier tick ouvre la fenêtre : sa crête est ancienne
+        assert_eq!(auto.observe(5_000, 0.9), None);
+        assert_eq!(auto.observe(5_500, 0.3), None);
+        assert_eq!(auto.observe(6_000, 0.2), Some((ChannelId(0), 0.3)));
+
+        assert_eq!(auto.current(), Some(ChannelId(1)));
+        assert_eq!(auto.observe(6_100, 0.5), None);
+        assert_eq!(auto.observe(7_100, 0.0), Some((ChannelId(1), 0.0)));
+        assert_eq!(auto.current(), None);
+        assert_eq!(auto.observe(8_000, 0.5), None);
+    }
+}
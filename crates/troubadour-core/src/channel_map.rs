@@ -0,0 +1,403 @@
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use troubadour_shared::audio::{ChannelCount, ChannelId};
+use troubadour_shared::error::{MixerError, MixerResult};
+use troubadour_shared::mixer::ChannelConfig;
+
+/// Tranche de canaux d'un device multicanal lue par un canal du mixer.
+///
+/// # Pourquoi ?
+/// Une interface audio 8 entrées se présente comme UN seul device à
+/// 8 canaux. Les samples arrivent entrelacés :
+///
+/// ```text
+///   [in1 in2 in3 in4 in5 in6 in7 in8] [in1 in2 ...] ...
+///    └──────────── frame 0 ────────┘   └ frame 1 ...
+/// ```
+///
+/// Le micro branché sur les entrées 3-4 correspond à la tranche
+/// `offset = 2, count = 2`. Plusieurs canaux du mixer peuvent lire des
+/// tranches différentes du MÊME stream : on n'ouvre le device qu'une fois.
+///
+/// `count = 0` signifie "tous les canaux du device" (comportement
+/// historique : downmix de tout le device).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ChannelSlice {
+    /// Premier canal du device (0 = entrée 1)
+    pub offset: usize,
+    /// Nombre de canaux lus (0 = tout le device)
+    pub count: usize,
+}
+
+impl ChannelSlice {
+    pub fn new(offset: usize, count: usize) -> Self {
+        Self { offset, count }
+    }
+
+    /// Tranche configurée pour un canal du mixer.
+    pub fn from_config(config: &ChannelConfig) -> Self {
+        Self::new(
+            config.device_channel_offset as usize,
+            config.device_channel_count as usize,
+        )
+    }
+
+    /// Borne la tranche aux canaux réellement disponibles sur le device.
+    ///
+    /// Une tranche hors du device (config d'une autre interface,
+    /// device débranché puis remplacé) retombe sur le device entier
+    /// plutôt que de produire du silence sans explication.
+    pub fn resolve(self, device_channels: usize) -> Self {
+        if device_channels == 0 {
+            return Self::new(0, 0);
+        }
+        if self.count == 0 || self.offset >= device_channels {
+            return Self::new(0, device_channels);
+        }
+        let count = self.count.min(device_channels - self.offset);
+        Self::new(self.offset, count)
+    }
+
+    /// Copie la tranche de chaque frame dans `out` (toujours entrelacé,
+    /// `count` canaux par frame). `out` est vidé d'abord ; sa capacité
+    /// est réutilisée d'un appel à l'autre.
+    pub fn extract(self, data: &[f32], device_channels: usize, out: &mut Vec<f32>) {
+        out.clear();
+        let slice = self.resolve(device_channels);
+        if slice.count == 0 {
+            return;
+        }
+        for frame in data.chunks_exact(device_channels) {
+            out.extend_from_slice(&frame[slice.offset..slice.offset + slice.count]);
+        }
+    }
+
+    /// Moyenne mono de la tranche dans une frame du device.
+    ///
+    /// Variante sans buffer intermédiaire de `extract`, pour le callback
+    /// audio qui traite frame par frame.
+    pub fn downmix(self, frame: &[f32]) -> f32 {
+        let slice = self.resolve(frame.len());
+        if slice.count == 0 {
+            return 0.0;
+        }
+        frame[slice.offset..slice.offset + slice.count]
+            .iter()
+            .sum::<f32>()
+            / slice.count as f32
+    }
+}
+
+/// Place d'un bus dans les canaux d'un device de sortie multicanal.
+///
+/// Le pendant de `ChannelSlice` côté sortie : le bus A1 sur les
+/// sorties 3-4 d'une interface 8 canaux est `offset = 2, count = 2`.
+/// Plusieurs bus peuvent se partager le MÊME stream, chacun sur sa
+/// tranche, tant que les tranches ne se chevauchent pas.
+///
+/// ```text
+///   [out1 out2 out3 out4 out5 out6 out7 out8]
+///    └─ Speakers ┘└ A1 (L R)┘
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputSlot {
+    /// Premier canal du device (0 = sortie 1)
+    pub offset: usize,
+    /// Canaux du bus (1 mono, 2 stéréo)
+    pub count: usize,
+}
+
+impl OutputSlot {
+    pub fn new(offset: usize, count: usize) -> Self {
+        Self { offset, count }
+    }
+
+    /// Place configurée d'un bus (`None` : pas de tranche imposée).
+    pub fn from_config(config: &ChannelConfig) -> Option<Self> {
+        config
+            .output_channel_offset
+            .map(|offset| Self::new(offset as usize, config.channel_count.channels() as usize))
+    }
+
+    /// Canaux du device nécessaires pour l'écrire.
+    pub fn end(self) -> usize {
+        self.offset + self.count
+    }
+
+    pub fn overlaps(self, other: Self) -> bool {
+        self.offset < other.end() && other.offset < self.end()
+    }
+
+    /// Écrit un bloc stéréo (déjà replié pour un bus mono) à sa place
+    /// dans un buffer entrelacé. Les autres canaux ne sont pas touchés :
+    /// chaque bus du stream écrit sa tranche à son tour.
+    ///
+    /// Un bloc de longueur impaire finit par une frame mono, écrite sur
+    /// toute la tranche.
+    pub fn write(self, stereo: &[f32], output: &mut [f32], device_channels: usize) {
+        self.write_lane(stereo, 0, 1, output, device_channels);
+    }
+
+    /// `write` pour la voie `lane` d'un bloc de `lanes` voies stéréo :
+    /// chaque frame du bloc porte la paire de chaque bus, dans l'ordre.
+    pub fn write_lane(
+        self,
+        block: &[f32],
+        lane: usize,
+        lanes: usize,
+        output: &mut [f32],
+        device_channels: usize,
+    ) {
+        if self.end() > device_channels {
+            return;
+        }
+        for (frame, lanes_frame) in output
+            .chunks_exact_mut(device_channels)
+            .zip(block.chunks(2 * lanes))
+        {
+            let lr = lane_pair(lanes_frame, lane);
+            if lr.is_empty() {
+                continue;
+            }
+            for (ch, sample) in frame[self.offset..self.end()].iter_mut().enumerate() {
+                *sample = lr[ch % lr.len()];
+            }
+        }
+    }
+}
+
+/// Paire de la voie `lane` dans une frame (une seule valeur pour la
+/// frame mono finale d'un bloc impair, rien si la frame est tronquée).
+fn lane_pair(frame: &[f32], lane: usize) -> &[f32] {
+    frame
+        .get(2 * lane..(2 * lane + 2).min(frame.len()))
+        .unwrap_or_default()
+}
+
+/// Bus écrits chacun sur sa tranche d'un même device de sortie, dans
+/// l'ordre des voies du bloc rendu : le bus joué (le premier de
+/// `buses`), puis les autres bus qui ont une tranche.
+///
+/// Vide si le bus joué n'en a pas : son stéréo est alors recopié sur
+/// toutes les paires, et il est seul sur le device.
+pub fn device_lanes(buses: &[&ChannelConfig]) -> Vec<(ChannelId, OutputSlot)> {
+    let Some((played, others)) = buses.split_first() else {
+        return Vec::new();
+    };
+    let Some(slot) = OutputSlot::from_config(played) else {
+        return Vec::new();
+    };
+    let mut lanes = vec![(played.id, slot)];
+    for config in others {
+        if let Some(slot) = OutputSlot::from_config(config)
+            && lanes.iter().all(|(_, taken)| !taken.overlaps(slot))
+        {
+            lanes.push((config.id, slot));
+        }
+    }
+    lanes
+}
+
+/// Vérifie les places des bus d'un même device : pas de chevauchement,
+/// et toutes dans le device (`device_channels` : `None` si inconnu).
+pub fn check_output_slots(
+    slots: &[(ChannelId, OutputSlot)],
+    device_channels: Option<usize>,
+) -> MixerResult<()> {
+    for (i, &(bus, slot)) in slots.iter().enumerate() {
+        if let Some(channels) = device_channels
+            && slot.end() > channels
+        {
+            return Err(MixerError::InvalidParameter(format!(
+                "bus {} needs outputs {}-{}, the device has {channels}",
+                bus.0,
+                slot.offset + 1,
+                slot.end()
+            )));
+        }
+        if let Some(&(other, _)) = slots[i + 1..].iter().find(|(_, s)| s.overlaps(slot)) {
+            return Err(MixerError::InvalidParameter(format!(
+                "buses {} and {} overlap on the same outputs",
+                bus.0, other.0
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Recopie un bloc stéréo sur toutes les paires du device (L sur les
+/// canaux pairs, R sur les impairs) : sortie d'un bus sans tranche.
+/// Une frame mono finale (bloc impair) va sur tous les canaux.
+pub fn spread_stereo(stereo: &[f32], output: &mut [f32], device_channels: usize) {
+    spread_lane(stereo, 0, 1, output, device_channels);
+}
+
+/// `spread_stereo` pour la voie `lane` d'un bloc de `lanes` voies
+/// (voir `OutputSlot::write_lane`).
+pub fn spread_lane(
+    block: &[f32],
+    lane: usize,
+    lanes: usize,
+    output: &mut [f32],
+    device_channels: usize,
+) {
+    for (frame, lanes_frame) in output
+        .chunks_exact_mut(device_channels)
+        .zip(block.chunks(2 * lanes))
+    {
+        let lr = lane_pair(lanes_frame, lane);
+        if lr.is_empty() {
+            continue;
+        }
+        for (ch, sample) in frame.iter_mut().enumerate() {
+            *sample = lr[ch % lr.len()];
+        }
+    }
+}
+
+/// Adapte un bloc stéréo entrelacé au nombre de canaux d'un bus.
+///
+/// Le pipeline reste stéréo de bout en bout ; un bus mono reçoit
+/// `(L + R) × 0,707` sur ses deux côtés (loi de pan à -3 dB). Avec le
+/// pan à puissance constante, un canal centré (L = R = 0,707) revient
+/// à son niveau d'origine, et un canal tout à gauche perd 3 dB au lieu
+/// de disparaître d'un côté.
+///
+/// Un bloc de longueur impaire finit par une frame mono : elle est déjà
+/// repliée et reste telle quelle.
+pub fn fold_to_bus(block: &mut [f32], count: ChannelCount) {
+    if count != ChannelCount::Mono {
+        return;
+    }
+    for frame in block.chunks_exact_mut(2) {
+        let mono = (frame[0] + frame[1]) * FRAC_1_SQRT_2;
+        frame[0] = mono;
+        frame[1] = mono;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Buffer entrelacé d'un device 8 canaux : la valeur encode
+    /// `frame * 10 + canal`, pour retrouver l'origine de chaque sample.
+    fn mock_8ch_buffer(frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|f| (0..8).map(move |ch| (f * 10 + ch) as f32))
+            .collect()
+    }
+
+    #[test]
+    fn two_channels_extract_different_pairs_from_same_stream() {
+        let data = mock_8ch_buffer(3);
+
+        // Mic sur les entrées 3-4, guitare sur les entrées 7-8
+        let mic = ChannelSlice::new(2, 2);
+        let guitar = ChannelSlice::new(6, 2);
+
+        let mut mic_out = Vec::new();
+        let mut guitar_out = Vec::new();
+        mic.extract(&data, 8, &mut mic_out);
+        guitar.extract(&data, 8, &mut guitar_out);
+
+        assert_eq!(mic_out, vec![2.0, 3.0, 12.0, 13.0, 22.0, 23.0]);
+        assert_eq!(guitar_out, vec![6.0, 7.0, 16.0, 17.0, 26.0, 27.0]);
+    }
+
+    #[test]
+    fn downmix_uses_only_the_slice() {
+        let frame = [0.0, 0.0, 0.4, 0.8, 1.0, 1.0, 1.0, 1.0];
+        assert!((ChannelSlice::new(2, 2).downmix(&frame) - 0.6).abs() < 1e-6);
+        // Tout le device (historique)
+        assert!((ChannelSlice::default().downmix(&frame) - 5.2 / 8.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn out_of_range_slice_is_clamped() {
+        // Tranche partiellement hors du device → tronquée
+        assert_eq!(ChannelSlice::new(6, 4).resolve(8), ChannelSlice::new(6, 2));
+        // Tranche entièrement hors du device → device entier
+        assert_eq!(ChannelSlice::new(8, 2).resolve(2), ChannelSlice::new(0, 2));
+        assert_eq!(ChannelSlice::new(0, 0).resolve(8), ChannelSlice::new(0, 8));
+    }
+
+    #[test]
+    fn two_buses_land_on_their_own_pairs_of_one_8ch_frame() {
+        // Deux frames stéréo par bus : la valeur encode bus, frame et côté
+        let speakers = [1.0, -1.0, 1.5, -1.5];
+        let a1 = [3.0, -3.0, 3.5, -3.5];
+        let mono_cue = [7.0, 7.0, 7.5, 7.5];
+        let mut output = vec![0.0; 2 * 8];
+
+        OutputSlot::new(0, 2).write(&speakers, &mut output, 8);
+        OutputSlot::new(2, 2).write(&a1, &mut output, 8);
+        OutputSlot::new(6, 1).write(&mono_cue, &mut output, 8);
+
+        assert_eq!(
+            output,
+            vec![
+                1.0, -1.0, 3.0, -3.0, 0.0, 0.0, 7.0, 0.0, //
+                1.5, -1.5, 3.5, -3.5, 0.0, 0.0, 7.5, 0.0,
+            ]
+        );
+
+        // Sans tranche : le stéréo sur toutes les paires
+        let mut spread = vec![0.0; 2 * 4];
+        spread_stereo(&speakers, &mut spread, 4);
+        assert_eq!(spread, vec![1.0, -1.0, 1.0, -1.0, 1.5, -1.5, 1.5, -1.5]);
+    }
+
+    #[test]
+    fn overlapping_or_out_of_device_slots_are_refused() {
+        let (a, b) = (ChannelId(3), ChannelId(4));
+        let ok = [(a, OutputSlot::new(0, 2)), (b, OutputSlot::new(2, 2))];
+        assert!(check_output_slots(&ok, Some(4)).is_ok());
+        assert!(check_output_slots(&ok, None).is_ok());
+        // Sorties 3-4 demandées sur un device stéréo
+        assert!(check_output_slots(&ok, Some(2)).is_err());
+        let overlap = [(a, OutputSlot::new(0, 2)), (b, OutputSlot::new(1, 2))];
+        assert!(check_output_slots(&overlap, Some(8)).is_err());
+        // Un bus mono à côté d'un stéréo : pas de chevauchement
+        let mono = [(a, OutputSlot::new(0, 2)), (b, OutputSlot::new(2, 1))];
+        assert!(check_output_slots(&mono, Some(3)).is_ok());
+    }
+
+    #[test]
+    fn mono_bus_folds_with_minus_3_db_law() {
+        let center = FRAC_1_SQRT_2 * 0.5;
+        let mut block = vec![0.5, 0.0, center, center];
+        fold_to_bus(&mut block, ChannelCount::Mono);
+        // Tout à gauche : -3 dB, sur les deux côtés
+        assert!((block[0] - 0.5 * FRAC_1_SQRT_2).abs() < 1e-6);
+        assert_eq!(block[0], block[1]);
+        // Centré : niveau d'origine
+        assert!((block[2] - 0.5).abs() < 1e-6);
+
+        let mut stereo = vec![0.5, 0.0];
+        fold_to_bus(&mut stereo, ChannelCount::Stereo);
+        assert_eq!(stereo, vec![0.5, 0.0]);
+    }
+
+    #[test]
+    fn odd_blocks_end_with_a_mono_frame() {
+        for len in [0_usize, 1, 3, 511, 512, 513, 4096] {
+            let frames = len.div_ceil(2);
+            let mut block: Vec<f32> = (0..len).map(|i| i as f32).collect();
+            fold_to_bus(&mut block, ChannelCount::Mono);
+            assert_eq!(block.len(), len);
+
+            let mut spread = vec![-1.0; frames * 4];
+            spread_stereo(&block, &mut spread, 4);
+            let mut output = vec![-1.0; frames * 4];
+            OutputSlot::new(2, 2).write(&block, &mut output, 4);
+            // Chaque frame du bloc arrive, la dernière comprise
+            assert!(spread.iter().all(|s| *s >= 0.0), "block of {len}");
+            if len % 2 == 1 {
+                let last = block[len - 1];
+                assert_eq!(spread[spread.len() - 4..], [last; 4]);
+                assert_eq!(output[output.len() - 4..], [-1.0, -1.0, last, last]);
+            }
+        }
+    }
+}
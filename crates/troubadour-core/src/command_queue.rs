@@ -0,0 +1,137 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use troubadour_shared::audio::ChannelId;
+
+use crate::dsp::EffectsChain;
+use crate::dsp::ramp::BusFade;
+use crate::mixer::Mixer;
+use crate::snapshot::MixerRuntimeState;
+
+/// Places de la file entre le thread de contrôle et le thread audio.
+///
+/// Le callback la vide à chaque bloc (~5 ms) : 64 commandes en attente,
+/// c'est un callback arrêté, pas une UI trop rapide.
+pub const ENGINE_QUEUE_CAPACITY: usize = 64;
+
+/// Travail confié au thread audio par le thread de contrôle.
+///
+/// # Une file bornée, dans l'ordre
+/// Les commandes passent par un channel crossbeam borné (voir
+/// `SharedMixerState::send`), vidé en tête de chaque bloc, dans l'ordre
+/// d'envoi : ce qui est envoyé pendant un bloc s'applique au suivant.
+///
+/// File pleine → `TroubadourError::CommandQueueFull`, tout de suite :
+/// le thread de contrôle n'attend jamais le callback, et l'appelant sait
+/// que sa modification n'est pas partie.
+///
+/// # Fin ou gros grain
+/// `SetGain` ne touche qu'un canal (un glissé de fader en envoie des
+/// dizaines), `StartRamp` lance le fondu d'un bus, `SwapEffectsProcessor`
+/// remplace la chaîne d'effets du canal traité. Tout le reste (mute,
+/// solo, routing...) republie l'état complet : il remplace tout ce qui
+/// le précède dans la file.
+#[derive(Debug, Clone)]
+pub enum EngineCommand {
+    /// Gain effectif (volume × pan) et volume du fader d'un canal. Le
+    /// fondu d'un bus suit : reprendre le fader l'annule.
+    SetGain {
+        channel: ChannelId,
+        gain: (f32, f32),
+        volume: f32,
+        fade: Option<BusFade>,
+    },
+    /// Fondu d'un bus vers `volume` (gain effectif `gain`), en
+    /// `fade.duration_ms`, avancé bloc par bloc par le callback.
+    StartRamp {
+        channel: ChannelId,
+        gain: (f32, f32),
+        volume: f32,
+        fade: BusFade,
+    },
+    /// Chaîne d'effets de rechange du canal traité (voir `EffectsSwap`).
+    SwapEffectsProcessor(Arc<EffectsSwap>),
+    /// Nouvel état runtime complet
+    ReplaceRuntimeState(Arc<MixerRuntimeState>),
+}
+
+impl EngineCommand {
+    /// Gain actuel d'un canal du mixer (`None` s'il n'existe pas).
+    pub fn gain_of(mixer: &Mixer, channel: ChannelId) -> Option<Self> {
+        let volume = mixer.channel(channel)?.volume;
+        Some(Self::SetGain {
+            channel,
+            gain: mixer.effective_gain(channel),
+            volume,
+            fade: mixer.bus_fade(channel),
+        })
+    }
+
+    /// Fondu en cours d'un bus (`None` : pas de fondu, ou pas un bus).
+    pub fn ramp_of(mixer: &Mixer, bus: ChannelId) -> Option<Self> {
+        Some(Self::StartRamp {
+            channel: bus,
+            gain: mixer.effective_gain(bus),
+            volume: mixer.channel(bus)?.volume,
+            fade: mixer.bus_fade(bus)?,
+        })
+    }
+
+    /// État runtime complet du mixer.
+    pub fn state_of(mixer: &Mixer) -> Self {
+        Self::ReplaceRuntimeState(Arc::new(MixerRuntimeState::from_mixer(mixer)))
+    }
+}
+
+/// Chaîne d'effets construite par le thread de contrôle, hors de tout
+/// lock, pour remplacer celle du canal traité.
+///
+/// # Une seule installation
+/// La chaîne en place est partagée par les pipelines vivants : le
+/// premier qui voit la rechange (un lecteur en tête de bloc, ou le
+/// thread de contrôle avant de toucher la chaîne) l'échange contre la
+/// sienne, sous le lock de la chaîne. Les suivants n'y touchent plus.
+///
+/// L'ancienne chaîne reste ici : c'est le thread de contrôle, qui garde
+/// une référence jusqu'à l'installation, qui la libère (jamais le
+/// callback).
+pub struct EffectsSwap {
+    chain: Mutex<EffectsChain>,
+    installed: AtomicBool,
+}
+
+impl EffectsSwap {
+    pub fn new(chain: EffectsChain) -> Arc<Self> {
+        Arc::new(Self {
+            chain: Mutex::new(chain),
+            installed: AtomicBool::new(false),
+        })
+    }
+
+    /// Échange la rechange contre `current` (la chaîne en place, lock
+    /// tenu), si personne ne l'a encore fait.
+    pub fn install(&self, current: &mut EffectsChain) {
+        if self.installed.load(Ordering::Acquire) {
+            return;
+        }
+        // Le lock de la chaîne en place sérialise les installations :
+        // celui-ci n'est jamais disputé
+        if let Ok(mut chain) = self.chain.try_lock() {
+            std::mem::swap(&mut *chain, current);
+            self.installed.store(true, Ordering::Release);
+        }
+    }
+
+    pub fn is_installed(&self) -> bool {
+        self.installed.load(Ordering::Acquire)
+    }
+}
+
+impl fmt::Debug for EffectsSwap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EffectsSwap")
+            .field("installed", &self.is_installed())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,253 @@
+//! Compatibilité d'un preset avec le matériel de cette machine.
+//!
+//! Un preset fait sur une autre machine nomme des devices qui n'existent
+//! pas ici, et on ne le découvrait qu'au démarrage de l'audio. Le
+//! rapport se fait avant le chargement : chaque device référencé (par
+//! les canaux, puis par le profil) est cherché parmi les devices
+//! énumérés :
+//!
+//! 1. le nom exact (c'est lui qui identifie un device) ; plusieurs
+//!    devices de ce nom rendent le choix ambigu ;
+//! 2. sinon le nom le plus proche (`import::resolve_device`), avec la
+//!    ressemblance des noms comme confiance ;
+//! 3. sinon le device est introuvable.
+//!
+//! S'y ajoutent les sample rates que les devices trouvés ne supportent
+//! pas et les canaux au-delà de la capacité du mixer. La fonction est
+//! pure : la liste des devices est passée en paramètre.
+
+use troubadour_shared::audio::{DeviceInfo, SampleRate};
+use troubadour_shared::compat::{CompatibilityReport, DeviceCheck, DeviceMatch};
+use troubadour_shared::mixer::ChannelKind;
+use troubadour_shared::profile::Profile;
+
+use crate::import::{name_similarity, resolve_device};
+use crate::setup::DeviceInventory;
+
+/// Vérifie `profile` contre les devices de la machine, au sample rate
+/// du moteur et pour un mixer de `max_channels` canaux.
+pub fn check_profile(
+    profile: &Profile,
+    devices: &DeviceInventory,
+    sample_rate: SampleRate,
+    max_channels: usize,
+) -> CompatibilityReport {
+    let mut wanted: Vec<(String, &str, bool)> = profile
+        .mixer
+        .channels
+        .iter()
+        .filter(|c| c.kind != ChannelKind::Submix)
+        .filter_map(|c| {
+            let device = c.device_name.specific()?;
+            Some((c.name.clone(), device, c.kind == ChannelKind::Input))
+        })
+        .collect();
+    if let Some(device) = &profile.input_device {
+        wanted.push(("profile input".to_string(), device, true));
+    }
+    if let Some(device) = &profile.output_device {
+        wanted.push(("profile output".to_string(), device, false));
+    }
+
+    let mut issues = Vec::new();
+    let checks = wanted
+        .into_iter()
+        .map(|(role, device, is_input)| {
+            let list = if is_input {
+                &devices.inputs
+            } else {
+                &devices.outputs
+            };
+            let result = resolve(device, list);
+            if let Some(found) = result.device()
+                && let Some(info) = list.iter().find(|d| d.name == found)
+                && !info.supported_sample_rates.is_empty()
+                && !info.supported_sample_rates.contains(&sample_rate)
+            {
+                issues.push(format!(
+                    "{role}: {found} does not support {} Hz",
+                    sample_rate.as_hz()
+                ));
+            }
+            DeviceCheck {
+                role,
+                wanted: device.to_string(),
+                is_input,
+                result,
+            }
+        })
+        .collect();
+
+    let channels = profile.mixer.channels.len();
+    if channels > max_channels {
+        issues.push(format!(
+            "{channels} channels for at most {max_channels}: {} will not be loaded",
+            channels - max_channels
+        ));
+    }
+
+    CompatibilityReport {
+        preset: profile.name.clone(),
+        sample_rate,
+        devices: checks,
+        issues,
+    }
+}
+
+/// Ce que devient `wanted` parmi `devices`.
+fn resolve(wanted: &str, devices: &[DeviceInfo]) -> DeviceMatch {
+    match devices.iter().filter(|d| d.name == wanted).count() {
+        0 => {}
+        1 => {
+            return DeviceMatch::Exact {
+                device: wanted.to_string(),
+            };
+        }
+        count => {
+            return DeviceMatch::Ambiguous {
+                device: wanted.to_string(),
+                count,
+            };
+        }
+    }
+    let mut names: Vec<String> = devices.iter().map(|d| d.name.clone()).collect();
+    names.dedup();
+    match resolve_device(wanted, &names) {
+        Some(device) => DeviceMatch::Fuzzy {
+            confidence: name_similarity(wanted, &device),
+            device,
+        },
+        None => DeviceMatch::Missing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::mixer::{ChannelConfig, DeviceAssignment, MixerConfig};
+
+    fn device(name: &str, is_input: bool, rates: &[SampleRate]) -> DeviceInfo {
+        DeviceInfo::new(name, is_input, 2, rates.to_vec())
+    }
+
+    /// Preset de la machine d'origine : un micro USB et un casque.
+    fn preset() -> Profile {
+        let mut profile = Profile::default_profile();
+        profile.name = "Stream".to_string();
+        profile.mixer.channels[0].device_name = DeviceAssignment::Specific("Blue Yeti".into());
+        let headphones = profile
+            .mixer
+            .channels
+            .iter_mut()
+            .find(|c| c.kind == ChannelKind::Output)
+            .unwrap();
+        headphones.device_name = DeviceAssignment::Specific("Studio Headphones".into());
+        profile.output_device = Some("Studio Headphones".into());
+        profile
+    }
+
+    fn inventory(inputs: Vec<DeviceInfo>, outputs: Vec<DeviceInfo>) -> DeviceInventory {
+        DeviceInventory {
+            inputs,
+            outputs,
+            ..DeviceInventory::default()
+        }
+    }
+
+    #[test]
+    fn same_hardware_is_clean() {
+        let devices = inventory(
+            vec![device("Blue Yeti", true, &SampleRate::ALL)],
+            vec![device("Studio Headphones", false, &[])],
+        );
+        let report = check_profile(&preset(), &devices, SampleRate::Hz48000, 32);
+        assert!(report.is_clean(), "{report:?}");
+        assert_eq!(report.preset, "Stream");
+        // Le Mic, le bus du casque, la sortie du profil
+        assert_eq!(report.devices.len(), 3);
+        assert_eq!(report.devices[0].summary(), "Mic: Blue Yeti");
+    }
+
+    #[test]
+    fn renamed_devices_match_with_a_confidence() {
+        let devices = inventory(
+            vec![
+                device("Built-in Microphone", true, &[]),
+                device("Yeti Stereo Microphone (Blue Yeti)", true, &[]),
+            ],
+            vec![device("Studio Headphones", false, &[])],
+        );
+        let report = check_profile(&preset(), &devices, SampleRate::Hz48000, 32);
+        let DeviceMatch::Fuzzy { device, confidence } = &report.devices[0].result else {
+            panic!("{:?}", report.devices[0]);
+        };
+        assert_eq!(device, "Yeti Stereo Microphone (Blue Yeti)");
+        assert!(*confidence > 0.5 && *confidence < 1.0, "{confidence}");
+        assert!(!report.is_clean());
+        assert_eq!(
+            report.devices[0].summary(),
+            "Mic: Blue Yeti → Yeti Stereo Microphone (Blue Yeti) (57 %)"
+        );
+    }
+
+    #[test]
+    fn vanished_devices_are_missing() {
+        // Le micro a disparu ; une entrée du même nom ne vaut pas une sortie
+        let devices = inventory(
+            vec![
+                device("Webcam", true, &[]),
+                device("Studio Headphones", true, &[]),
+            ],
+            vec![device("HDMI Output", false, &[])],
+        );
+        let report = check_profile(&preset(), &devices, SampleRate::Hz48000, 32);
+        let missing: Vec<_> = report.missing().map(|d| d.role.as_str()).collect();
+        assert_eq!(missing, ["Mic", "Headphones", "profile output"]);
+        assert_eq!(report.devices[0].summary(), "Mic: Blue Yeti not found");
+    }
+
+    #[test]
+    fn duplicate_names_are_ambiguous() {
+        let devices = inventory(
+            vec![
+                device("Blue Yeti", true, &[]),
+                device("Blue Yeti", true, &[]),
+            ],
+            vec![device("Studio Headphones", false, &[])],
+        );
+        let report = check_profile(&preset(), &devices, SampleRate::Hz48000, 32);
+        assert_eq!(
+            report.devices[0].result,
+            DeviceMatch::Ambiguous {
+                device: "Blue Yeti".into(),
+                count: 2
+            }
+        );
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn rates_and_channel_count_are_reported() {
+        let devices = inventory(
+            vec![device("Blue Yeti", true, &[SampleRate::Hz44100])],
+            vec![device("Studio Headphones", false, &SampleRate::ALL)],
+        );
+        let mut profile = preset();
+        let mut mixer = MixerConfig::default_setup();
+        mixer.channels.push(ChannelConfig::input(9, "Guest"));
+        profile.mixer.channels = mixer.channels;
+        profile.mixer.channels[0].device_name = DeviceAssignment::Specific("Blue Yeti".into());
+
+        let report = check_profile(&profile, &devices, SampleRate::Hz48000, 4);
+        assert_eq!(
+            report.issues,
+            [
+                "Mic: Blue Yeti does not support 48000 Hz",
+                "6 channels for at most 4: 2 will not be loaded"
+            ]
+        );
+        // Au rate que le device supporte, seul le nombre de canaux reste
+        let report = check_profile(&profile, &devices, SampleRate::Hz44100, 4);
+        assert_eq!(report.issues.len(), 1);
+    }
+}
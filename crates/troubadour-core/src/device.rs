@@ -1,6 +1,7 @@
 use cpal::traits::{DeviceTrait, HostTrait};
-use troubadour_shared::audio::DeviceInfo;
-use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::audio::{DeviceInfo, SampleRate};
+use troubadour_shared::error::{StreamErrorKind, TroubadourError, TroubadourResult};
+use troubadour_shared::mixer::DeviceAssignment;
 
 /// Gestionnaire de périphériques audio.
 ///
@@ -30,6 +31,24 @@ impl DeviceManager {
         }
     }
 
+    /// DeviceManager sur un backend précis ("ALSA", "JACK", "WASAPI"...),
+    /// insensible à la casse. Erreur si le backend n'est pas compilé
+    /// dans cpal ou indisponible sur ce système.
+    pub fn with_host(name: &str) -> TroubadourResult<Self> {
+        let id = cpal::available_hosts()
+            .into_iter()
+            .find(|id| id.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| TroubadourError::DeviceNotFound(format!("Audio host {name}")))?;
+        let host =
+            cpal::host_from_id(id).map_err(|e| TroubadourError::StreamError(e.to_string()))?;
+        Ok(Self { host })
+    }
+
+    /// Nom du backend audio utilisé.
+    pub fn host_name(&self) -> &'static str {
+        self.host.id().name()
+    }
+
     /// Liste tous les périphériques d'entrée (microphones, etc.)
     ///
     /// # Iterators — le coeur de Rust idiomatique
@@ -127,12 +146,166 @@ impl DeviceManager {
             device.default_output_config().ok()?
         };
 
+        let rates = if is_input {
+            supported_rates(device.supported_input_configs().into_iter().flatten())
+        } else {
+            supported_rates(device.supported_output_configs().into_iter().flatten())
+        };
         Some(DeviceInfo {
-            name,
-            is_input,
-            channels: config.channels(),
-            supported_sample_rates: vec![], // TODO: enumerate supported rates
+            default_sample_rate: Some(config.sample_rate().0),
+            ..DeviceInfo::new(name, is_input, config.channels(), rates)
+        })
+    }
+}
+
+/// Rates du moteur qu'au moins une config du device accepte.
+///
+/// Un device qui n'en propose aucun reste utilisable : le stream s'ouvre
+/// à son rate natif et le pont de sortie convertit.
+pub fn supported_rates(
+    ranges: impl IntoIterator<Item = cpal::SupportedStreamConfigRange>,
+) -> Vec<SampleRate> {
+    let ranges: Vec<_> = ranges.into_iter().collect();
+    SampleRate::ALL
+        .into_iter()
+        .filter(|rate| {
+            ranges.iter().any(|range| {
+                (range.min_sample_rate().0..=range.max_sample_rate().0).contains(&rate.as_hz())
+            })
         })
+        .collect()
+}
+
+/// Erreur d'ouverture d'un stream sur `device`, classée (voir
+/// `StreamErrorKind`). `is_input` : sens du stream ; `requested` : le
+/// sample rate demandé ; `supported` : ceux du device au moment de l'échec.
+pub fn build_stream_error(
+    device: &str,
+    is_input: bool,
+    err: &cpal::BuildStreamError,
+    requested: u32,
+    supported: &[SampleRate],
+) -> TroubadourError {
+    let kind = match err {
+        cpal::BuildStreamError::DeviceNotAvailable => StreamErrorKind::DeviceDisconnected,
+        cpal::BuildStreamError::StreamConfigNotSupported
+        | cpal::BuildStreamError::InvalidArgument => StreamErrorKind::FormatNotSupported {
+            requested,
+            supported: supported.iter().map(|rate| rate.as_hz()).collect(),
+        },
+        cpal::BuildStreamError::StreamIdOverflow => StreamErrorKind::Other,
+        cpal::BuildStreamError::BackendSpecific { err } => {
+            classify_backend(&err.description, is_input)
+        }
+    };
+    TroubadourError::StreamOpen {
+        device: device.to_string(),
+        kind,
+        message: err.to_string(),
+    }
+}
+
+/// Erreur au démarrage d'un stream ouvert sur `device`, classée.
+pub fn play_stream_error(
+    device: &str,
+    is_input: bool,
+    err: &cpal::PlayStreamError,
+) -> TroubadourError {
+    let kind = match err {
+        cpal::PlayStreamError::DeviceNotAvailable => StreamErrorKind::DeviceDisconnected,
+        cpal::PlayStreamError::BackendSpecific { err } => {
+            classify_backend(&err.description, is_input)
+        }
+    };
+    TroubadourError::StreamOpen {
+        device: device.to_string(),
+        kind,
+        message: err.to_string(),
+    }
+}
+
+/// Classe un message d'erreur du backend d'après ses mots : codes
+/// WASAPI (`AUDCLNT_E_DEVICE_IN_USE`), `errno` d'ALSA ("Device or
+/// resource busy"), textes de CoreAudio. `is_input` choisit le conseil
+/// d'un refus d'accès (micro ou sortie).
+fn classify_backend(description: &str, is_input: bool) -> StreamErrorKind {
+    let description = description.to_lowercase();
+    let says = |words: &[&str]| words.iter().any(|word| description.contains(word));
+    if says(&["exclusive"]) {
+        StreamErrorKind::ExclusiveModeDenied
+    } else if says(&["in use", "in_use", "busy"]) {
+        StreamErrorKind::DeviceInUse
+    } else if says(&[
+        "permission",
+        "access denied",
+        "accessdenied",
+        "not permitted",
+    ]) {
+        StreamErrorKind::PermissionDenied { input: is_input }
+    } else if says(&[
+        "disconnected",
+        "invalidated",
+        "no such device",
+        "not available",
+    ]) {
+        StreamErrorKind::DeviceDisconnected
+    } else {
+        StreamErrorKind::Other
+    }
+}
+
+/// Source des devices par défaut du système.
+///
+/// # Pourquoi un trait ?
+/// Le défaut change sous nos pieds (l'utilisateur passe des enceintes
+/// au casque dans les réglages de l'OS) : le moteur relit cette source
+/// régulièrement. Les tests la remplacent par un faux dont le défaut
+/// change en cours de test, sans device réel.
+pub trait DefaultDeviceSource: Send {
+    fn default_input_name(&self) -> Option<String>;
+    fn default_output_name(&self) -> Option<String>;
+
+    /// Les deux défauts, lus ensemble.
+    fn system_defaults(&self) -> SystemDefaults {
+        SystemDefaults {
+            input: self.default_input_name(),
+            output: self.default_output_name(),
+        }
+    }
+
+    /// Le device est-il branché ? `None` : la source n'en sait rien, le
+    /// moteur interroge les devices réels.
+    fn device_present(&self, _name: &str, _is_input: bool) -> Option<bool> {
+        None
+    }
+}
+
+impl DefaultDeviceSource for DeviceManager {
+    fn default_input_name(&self) -> Option<String> {
+        DeviceManager::default_input_name(self)
+    }
+
+    fn default_output_name(&self) -> Option<String> {
+        DeviceManager::default_output_name(self)
+    }
+}
+
+/// Devices par défaut du système à un instant donné.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemDefaults {
+    pub input: Option<String>,
+    pub output: Option<String>,
+}
+
+impl SystemDefaults {
+    /// Device réel d'une assignation de canal, dans le sens donné.
+    pub fn resolve<'a>(
+        &'a self,
+        assignment: &'a DeviceAssignment,
+        is_input: bool,
+    ) -> Option<&'a str> {
+        let default = if is_input { &self.input } else { &self.output };
+        assignment.resolve(default.as_deref())
     }
 }
 
@@ -187,4 +360,108 @@ mod tests {
         let result = manager.find_input_device("Ce Device N'Existe Pas 12345");
         assert!(result.is_err());
     }
+
+    fn kind(err: TroubadourError) -> StreamErrorKind {
+        match err {
+            TroubadourError::StreamOpen { kind, .. } => kind,
+            other => panic!("not a stream open error: {other}"),
+        }
+    }
+
+    fn backend(description: &str) -> cpal::BackendSpecificError {
+        cpal::BackendSpecificError {
+            description: description.into(),
+        }
+    }
+
+    #[test]
+    fn build_errors_are_classified() {
+        let supported = [SampleRate::Hz44100, SampleRate::Hz48000];
+        let build = |err: cpal::BuildStreamError| {
+            kind(build_stream_error("Yeti", true, &err, 96_000, &supported))
+        };
+        let unsupported = StreamErrorKind::FormatNotSupported {
+            requested: 96_000,
+            supported: vec![44_100, 48_000],
+        };
+
+        assert_eq!(
+            build(cpal::BuildStreamError::DeviceNotAvailable),
+            StreamErrorKind::DeviceDisconnected
+        );
+        assert_eq!(
+            build(cpal::BuildStreamError::StreamConfigNotSupported),
+            unsupported
+        );
+        assert_eq!(build(cpal::BuildStreamError::InvalidArgument), unsupported);
+        assert_eq!(
+            build(cpal::BuildStreamError::StreamIdOverflow),
+            StreamErrorKind::Other
+        );
+
+        for (description, expected) in [
+            ("AUDCLNT_E_DEVICE_IN_USE", StreamErrorKind::DeviceInUse),
+            (
+                "ALSA function 'snd_pcm_open' failed with error 'EBUSY: Device or resource busy'",
+                StreamErrorKind::DeviceInUse,
+            ),
+            (
+                "AUDCLNT_E_EXCLUSIVE_MODE_NOT_ALLOWED",
+                StreamErrorKind::ExclusiveModeDenied,
+            ),
+            (
+                "Permission denied",
+                StreamErrorKind::PermissionDenied { input: true },
+            ),
+            (
+                "E_ACCESSDENIED",
+                StreamErrorKind::PermissionDenied { input: true },
+            ),
+            (
+                "AUDCLNT_E_DEVICE_INVALIDATED",
+                StreamErrorKind::DeviceDisconnected,
+            ),
+            ("something else entirely", StreamErrorKind::Other),
+        ] {
+            let err = cpal::BuildStreamError::BackendSpecific {
+                err: backend(description),
+            };
+            assert_eq!(build(err), expected, "{description}");
+        }
+    }
+
+    #[test]
+    fn play_errors_are_classified() {
+        let play = |err: cpal::PlayStreamError| kind(play_stream_error("Yeti", true, &err));
+        assert_eq!(
+            play(cpal::PlayStreamError::DeviceNotAvailable),
+            StreamErrorKind::DeviceDisconnected
+        );
+        assert_eq!(
+            play(cpal::PlayStreamError::BackendSpecific {
+                err: backend("device busy")
+            }),
+            StreamErrorKind::DeviceInUse
+        );
+
+        let err = play_stream_error("Yeti", true, &cpal::PlayStreamError::DeviceNotAvailable);
+        assert!(err.to_string().contains("Yeti"));
+        assert!(err.hint().unwrap().contains("unplugged"));
+    }
+
+    #[test]
+    fn permission_hint_follows_the_stream_direction() {
+        let denied = cpal::PlayStreamError::BackendSpecific {
+            err: backend("Permission denied"),
+        };
+        let input = play_stream_error("Yeti", true, &denied);
+        assert!(input.hint().unwrap().contains("microphone"));
+
+        let output = play_stream_error("Speakers", false, &denied);
+        assert!(!output.hint().unwrap().contains("microphone"));
+        assert_eq!(
+            kind(output),
+            StreamErrorKind::PermissionDenied { input: false }
+        );
+    }
 }
@@ -0,0 +1,514 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use troubadour_shared::audio::DeviceInfo;
+use troubadour_shared::config::{AppConfig, ensure_writable};
+use troubadour_shared::diagnostics::{DiagnosticsReport, ProbeResult, ProbeStatus};
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::mixer::{ChannelKind, MixerConfig};
+
+use crate::device::{DeviceManager, build_stream_error, play_stream_error, supported_rates};
+use crate::journal::now_ms;
+
+/// Temps maximum accordé à chaque sonde.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Accès au système audio utilisé par le diagnostic.
+///
+/// # Pourquoi un trait ?
+/// Les vraies sondes passent par cpal, donc par le matériel de la
+/// machine : intestable en CI. Derrière ce trait, les tests branchent
+/// un faux système audio (devices absents, driver qui bloque...).
+///
+/// `Send + Sync` : chaque sonde tourne dans son propre thread.
+pub trait AudioProbe: Send + Sync {
+    fn input_devices(&self) -> TroubadourResult<Vec<DeviceInfo>>;
+    fn output_devices(&self) -> TroubadourResult<Vec<DeviceInfo>>;
+    /// Ouvre un stream sur le device puis le referme aussitôt.
+    fn open_stream(&self, device: &str, is_input: bool) -> TroubadourResult<()>;
+}
+
+/// Sondes réelles, via cpal.
+///
+/// Un `DeviceManager` neuf par appel : rien de cpal ne traverse les
+/// threads des sondes (certains backends n'aiment pas ça).
+pub struct CpalProbe;
+
+impl AudioProbe for CpalProbe {
+    fn input_devices(&self) -> TroubadourResult<Vec<DeviceInfo>> {
+        DeviceManager::new().list_input_devices()
+    }
+
+    fn output_devices(&self) -> TroubadourResult<Vec<DeviceInfo>> {
+        DeviceManager::new().list_output_devices()
+    }
+
+    fn open_stream(&self, name: &str, is_input: bool) -> TroubadourResult<()> {
+        let manager = DeviceManager::new();
+        let stream_err = |e: &dyn std::fmt::Display| TroubadourError::StreamError(e.to_string());
+
+        let stream = if is_input {
+            let device = manager.find_input_device(name)?;
+            let config = device.default_input_config().map_err(|e| stream_err(&e))?;
+            if config.sample_format() != cpal::SampleFormat::F32 {
+                return Err(TroubadourError::StreamError(format!(
+                    "Unsupported format: {:?}. Only F32 supported.",
+                    config.sample_format()
+                )));
+            }
+            device
+                .build_input_stream(
+                    &config.config(),
+                    |_: &[f32], _: &cpal::InputCallbackInfo| {},
+                    |_| {},
+                    None,
+                )
+                .map_err(|e| {
+                    let rates =
+                        supported_rates(device.supported_input_configs().into_iter().flatten());
+                    build_stream_error(name, is_input, &e, config.sample_rate().0, &rates)
+                })?
+        } else {
+            let device = manager.find_output_device(name)?;
+            let config = device.default_output_config().map_err(|e| stream_err(&e))?;
+            device
+                .build_output_stream(
+                    &config.config(),
+                    |out: &mut [f32], _: &cpal::OutputCallbackInfo| out.fill(0.0),
+                    |_| {},
+                    None,
+                )
+                .map_err(|e| {
+                    let rates =
+                        supported_rates(device.supported_output_configs().into_iter().flatten());
+                    build_stream_error(name, is_input, &e, config.sample_rate().0, &rates)
+                })?
+        };
+        stream
+            .play()
+            .map_err(|e| play_stream_error(name, is_input, &e))?;
+        // `drop` ferme le stream
+        drop(stream);
+        Ok(())
+    }
+}
+
+/// État du moteur au moment du diagnostic.
+#[derive(Debug, Clone, Default)]
+pub struct EngineHealth {
+    pub running: bool,
+    /// Nombre de fois où le device de sortie a manqué d'audio
+    pub underruns: u64,
+    /// Description des streams ouverts ("Blue Yeti (1 ch @ 48000 Hz)")
+    pub active_streams: Vec<String>,
+    /// Streams dont le callback ne tourne plus (watchdog)
+    pub stalled_streams: Vec<String>,
+}
+
+/// Tout ce que le diagnostic doit vérifier, rassemblé par l'appelant.
+pub struct DiagnosticsContext {
+    pub config_path: PathBuf,
+    pub preset_dir: PathBuf,
+    pub mixer: MixerConfig,
+    /// Devices sélectionnés pour le pipeline
+    pub input_device: Option<String>,
+    pub output_device: Option<String>,
+    pub engine: EngineHealth,
+    pub timeout: Duration,
+}
+
+/// Exécute `f` dans un thread séparé, avec une limite de temps.
+///
+/// # Pourquoi un thread ?
+/// Un driver audio bloqué ne rend jamais la main. Dans un thread à part,
+/// on arrête simplement d'attendre après `timeout` : le thread reste
+/// coincé (on ne peut pas le tuer), mais le diagnostic, lui, continue.
+fn time_boxed<T: Send + 'static>(
+    name: String,
+    timeout: Duration,
+    f: impl FnOnce() -> TroubadourResult<T> + Send + 'static,
+) -> (ProbeResult, Option<T>) {
+    let start = Instant::now();
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    let spawned = std::thread::Builder::new()
+        .name(format!("diag-{name}"))
+        .spawn(move || {
+            let _ = tx.send(f());
+        });
+
+    let (status, detail, value) = match spawned {
+        Err(e) => (
+            ProbeStatus::Failed,
+            format!("Cannot spawn probe: {e}"),
+            None,
+        ),
+        Ok(_) => match rx.recv_timeout(timeout) {
+            Ok(Ok(value)) => (ProbeStatus::Ok, String::new(), Some(value)),
+            Ok(Err(e)) => (ProbeStatus::Failed, e.to_string(), None),
+            Err(_) => (
+                ProbeStatus::TimedOut,
+                format!("No answer after {} ms", timeout.as_millis()),
+                None,
+            ),
+        },
+    };
+
+    let result = ProbeResult {
+        name,
+        status,
+        detail,
+        duration_ms: start.elapsed().as_millis() as u64,
+    };
+    (result, value)
+}
+
+/// Lance toutes les sondes et assemble le rapport.
+pub fn run_diagnostics(probe: Arc<dyn AudioProbe>, ctx: &DiagnosticsContext) -> DiagnosticsReport {
+    let mut report = DiagnosticsReport {
+        generated_at_ms: now_ms(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        ..Default::default()
+    };
+
+    // 1. Énumération des devices
+    let p = probe.clone();
+    let (mut result, inputs) = time_boxed("enumerate_inputs".into(), ctx.timeout, move || {
+        p.input_devices()
+    });
+    if let Some(devices) = &inputs {
+        result.detail = format!("{} device(s)", devices.len());
+    }
+    report.probes.push(result);
+
+    let p = probe.clone();
+    let (mut result, outputs) = time_boxed("enumerate_outputs".into(), ctx.timeout, move || {
+        p.output_devices()
+    });
+    if let Some(devices) = &outputs {
+        result.detail = format!("{} device(s)", devices.len());
+    }
+    report.probes.push(result);
+
+    // 2. Devices référencés (pipeline + canaux)
+    let mut wanted: BTreeSet<(bool, String)> = BTreeSet::new();
+    wanted.extend(ctx.input_device.clone().map(|n| (true, n)));
+    wanted.extend(ctx.output_device.clone().map(|n| (false, n)));
+    for ch in &ctx.mixer.channels {
+        if let Some(name) = ch.device_name.specific() {
+            wanted.insert((ch.kind == ChannelKind::Input, name.to_string()));
+        }
+    }
+
+    let known = |is_input: bool, name: &str| {
+        let list = if is_input { &inputs } else { &outputs };
+        // Énumération ratée → on ne peut rien affirmer : on tente l'ouverture
+        list.as_ref()
+            .is_none_or(|l| l.iter().any(|d| d.name == name))
+    };
+
+    let missing: Vec<String> = ctx
+        .mixer
+        .channels
+        .iter()
+        .filter_map(|ch| {
+            let name = ch.device_name.specific()?;
+            (!known(ch.kind == ChannelKind::Input, name)).then(|| format!("{} → {name}", ch.name))
+        })
+        .collect();
+    report.probes.push(ProbeResult {
+        name: "channel_devices".into(),
+        status: if missing.is_empty() {
+            ProbeStatus::Ok
+        } else {
+            ProbeStatus::Warning
+        },
+        detail: if missing.is_empty() {
+            String::new()
+        } else {
+            format!("Channels reference missing devices: {}", missing.join(", "))
+        },
+        duration_ms: 0,
+    });
+
+    // 3. Ouverture d'un stream court sur chaque device présent
+    if wanted.is_empty() {
+        report
+            .probes
+            .push(skipped("open_streams", "No device configured"));
+    }
+    for (is_input, name) in wanted {
+        let probe_name = format!(
+            "{}:{name}",
+            if is_input {
+                "open_input"
+            } else {
+                "open_output"
+            }
+        );
+        if !known(is_input, &name) {
+            report.missing_devices.push(name);
+            report
+                .probes
+                .push(skipped(&probe_name, "Device not present"));
+            continue;
+        }
+        let p = probe.clone();
+        let (result, _) = time_boxed(probe_name, ctx.timeout, move || {
+            p.open_stream(&name, is_input)
+        });
+        report.probes.push(result);
+    }
+
+    // 4. Fichiers
+    report.probes.push(check_config(&ctx.config_path));
+    report.probes.push(check_writable(&ctx.preset_dir));
+
+    // 5. Moteur
+    report.probes.push(check_engine(&ctx.engine));
+
+    report.input_devices = inputs.unwrap_or_default();
+    report.output_devices = outputs.unwrap_or_default();
+    report
+}
+
+fn skipped(name: &str, detail: &str) -> ProbeResult {
+    ProbeResult {
+        name: name.into(),
+        status: ProbeStatus::Skipped,
+        detail: detail.into(),
+        duration_ms: 0,
+    }
+}
+
+/// Le fichier de config existe-t-il, et se parse-t-il ?
+fn check_config(path: &Path) -> ProbeResult {
+    let start = Instant::now();
+    let (status, detail) = if !path.exists() {
+        (
+            ProbeStatus::Skipped,
+            format!("{} not found, defaults in use", path.display()),
+        )
+    } else {
+        match AppConfig::load(path) {
+            Ok(_) => (ProbeStatus::Ok, path.display().to_string()),
+            Err(e) => (ProbeStatus::Failed, format!("{}: {e}", path.display())),
+        }
+    };
+    ProbeResult {
+        name: "config_parse".into(),
+        status,
+        detail,
+        duration_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+/// Peut-on créer et supprimer un fichier dans le dossier des presets ?
+fn check_writable(dir: &Path) -> ProbeResult {
+    let start = Instant::now();
+    let (status, detail) = match ensure_writable(dir) {
+        Ok(()) => (ProbeStatus::Ok, dir.display().to_string()),
+        Err(e) => (ProbeStatus::Failed, format!("{}: {e}", dir.display())),
+    };
+    ProbeResult {
+        name: "preset_dir_writable".into(),
+        status,
+        detail,
+        duration_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+fn check_engine(engine: &EngineHealth) -> ProbeResult {
+    let (status, detail) = if !engine.running {
+        (ProbeStatus::Warning, "Engine stopped".to_string())
+    } else {
+        let streams = if engine.active_streams.is_empty() {
+            "no stream".to_string()
+        } else {
+            engine.active_streams.join(", ")
+        };
+        let status = if !engine.stalled_streams.is_empty() {
+            ProbeStatus::Failed
+        } else if engine.underruns > 0 {
+            ProbeStatus::Warning
+        } else {
+            ProbeStatus::Ok
+        };
+        let mut detail = format!("Running: {streams}; {} underrun(s)", engine.underruns);
+        if !engine.stalled_streams.is_empty() {
+            detail.push_str(&format!("; stalled: {}", engine.stalled_streams.join(", ")));
+        }
+        (status, detail)
+    };
+    ProbeResult {
+        name: "engine".into(),
+        status,
+        detail,
+        duration_ms: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::mixer::{ChannelConfig, DeviceAssignment};
+
+    /// Faux système audio : listes fixes, devices en panne, driver bloqué.
+    struct MockProbe {
+        inputs: Vec<&'static str>,
+        outputs: Vec<&'static str>,
+        broken: Vec<&'static str>,
+        hanging: Vec<&'static str>,
+    }
+
+    fn info(name: &str, is_input: bool) -> DeviceInfo {
+        DeviceInfo::new(name, is_input, 2, Vec::new())
+    }
+
+    impl AudioProbe for MockProbe {
+        fn input_devices(&self) -> TroubadourResult<Vec<DeviceInfo>> {
+            Ok(self.inputs.iter().map(|n| info(n, true)).collect())
+        }
+
+        fn output_devices(&self) -> TroubadourResult<Vec<DeviceInfo>> {
+            Ok(self.outputs.iter().map(|n| info(n, false)).collect())
+        }
+
+        fn open_stream(&self, device: &str, _is_input: bool) -> TroubadourResult<()> {
+            if self.hanging.contains(&device) {
+                std::thread::sleep(Duration::from_secs(5));
+            }
+            if self.broken.contains(&device) {
+                return Err(TroubadourError::StreamError("device busy".into()));
+            }
+            Ok(())
+        }
+    }
+
+    fn context(tag: &str) -> (DiagnosticsContext, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-diag-{tag}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut mic = ChannelConfig::input(0, "Mic");
+        mic.device_name = DeviceAssignment::Specific("Blue Yeti".into());
+        let mut phones = ChannelConfig::output(1, "Phones");
+        phones.device_name = DeviceAssignment::Specific("Old Headset".into());
+
+        let ctx = DiagnosticsContext {
+            config_path: dir.join("config.toml"),
+            preset_dir: dir.join("presets"),
+            mixer: MixerConfig {
+                channels: vec![mic, phones],
+                routes: vec![],
+                crossfades: vec![],
+                links: vec![],
+                bus_follows: vec![],
+            },
+            input_device: Some("Blue Yeti".into()),
+            output_device: Some("Speakers".into()),
+            engine: EngineHealth {
+                running: true,
+                underruns: 0,
+                active_streams: vec!["Blue Yeti (1 ch @ 48000 Hz)".into()],
+                stalled_streams: Vec::new(),
+            },
+            timeout: Duration::from_millis(200),
+        };
+        (ctx, dir)
+    }
+
+    fn probe() -> MockProbe {
+        MockProbe {
+            inputs: vec!["Blue Yeti"],
+            outputs: vec!["Speakers"],
+            broken: vec![],
+            hanging: vec![],
+        }
+    }
+
+    #[test]
+    fn reports_missing_devices_and_healthy_probes() {
+        let (ctx, dir) = context("missing");
+        let report = run_diagnostics(Arc::new(probe()), &ctx);
+
+        assert_eq!(report.input_devices.len(), 1);
+        assert_eq!(report.missing_devices, vec!["Old Headset"]);
+        assert_eq!(
+            report.probe("channel_devices").unwrap().status,
+            ProbeStatus::Warning
+        );
+        assert_eq!(
+            report.probe("open_input:Blue Yeti").unwrap().status,
+            ProbeStatus::Ok
+        );
+        assert_eq!(
+            report.probe("open_output:Old Headset").unwrap().status,
+            ProbeStatus::Skipped
+        );
+        // Pas de fichier de config : défauts, pas une erreur
+        assert_eq!(
+            report.probe("config_parse").unwrap().status,
+            ProbeStatus::Skipped
+        );
+        assert_eq!(
+            report.probe("preset_dir_writable").unwrap().status,
+            ProbeStatus::Ok
+        );
+        assert!(report.is_healthy());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn hanging_driver_is_time_boxed() {
+        let (ctx, dir) = context("hang");
+        let mut mock = probe();
+        mock.hanging = vec!["Speakers"];
+        mock.broken = vec!["Blue Yeti"];
+
+        let start = Instant::now();
+        let report = run_diagnostics(Arc::new(mock), &ctx);
+
+        // Le driver bloque 5 s, le diagnostic n'attend que 200 ms
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(
+            report.probe("open_output:Speakers").unwrap().status,
+            ProbeStatus::TimedOut
+        );
+        let broken = report.probe("open_input:Blue Yeti").unwrap();
+        assert_eq!(broken.status, ProbeStatus::Failed);
+        assert!(broken.detail.contains("device busy"));
+        assert!(!report.is_healthy());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn invalid_config_and_underruns_are_reported() {
+        let (mut ctx, dir) = context("config");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&ctx.config_path, "[audio\nbroken = ").unwrap();
+        ctx.engine.underruns = 12;
+
+        let report = run_diagnostics(Arc::new(probe()), &ctx);
+        assert_eq!(
+            report.probe("config_parse").unwrap().status,
+            ProbeStatus::Failed
+        );
+        let engine = report.probe("engine").unwrap();
+        assert_eq!(engine.status, ProbeStatus::Warning);
+        assert!(engine.detail.contains("12 underrun"));
+
+        // Un stream bloqué est plus grave que des underruns
+        ctx.engine.stalled_streams = vec!["Blue Yeti".into()];
+        let report = run_diagnostics(Arc::new(probe()), &ctx);
+        let engine = report.probe("engine").unwrap();
+        assert_eq!(engine.status, ProbeStatus::Failed);
+        assert!(engine.detail.contains("stalled: Blue Yeti"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,382 @@
+use super::Processor;
+
+/// Auto Gain (AGC) — ajuste lentement le gain pour viser un niveau cible.
+///
+/// # Comment ça marche ?
+/// L'AGC mesure le niveau RMS du signal sur une longue fenêtre
+/// (plusieurs secondes) et calcule le gain qui amènerait ce niveau
+/// sur la cible (ex: -18 dBFS). Le gain ne saute pas : il glisse
+/// vers cette valeur.
+///
+/// Un invité qui parle de plus en plus bas → le gain remonte doucement.
+/// Un invité qui se met à crier → le gain redescend vite.
+///
+/// # Asymétrie descente rapide / montée lente
+/// - Descendre vite (`fall_time`) protège contre les saturations
+/// - Monter lentement (`rise_time`) évite l'effet "pompage" audible
+///
+/// # Gel sous le seuil
+/// Pendant les silences, le niveau mesuré s'effondre et un AGC naïf
+/// remonterait le gain à fond → le bruit de fond devient énorme.
+/// Un détecteur rapide décide si "quelqu'un parle" : sous
+/// `freeze_threshold_db`, la mesure ET le gain sont gelés.
+///
+/// # Paramètres
+/// Contrairement aux autres processeurs (coefficients par sample),
+/// l'AGC travaille sur des durées de plusieurs secondes : les paramètres
+/// sont en dB et en secondes, convertis en coefficients via le sample rate.
+pub struct AutoGain {
+    target_db: f32,
+    max_gain_db: f32,
+    freeze_threshold_db: f32,
+    window: f32,
+    rise_time: f32,
+    fall_time: f32,
+    sample_rate: f32,
+    /// Coefficients de lissage (recalculés quand un paramètre change)
+    slow_coeff: f32,
+    fast_coeff: f32,
+    rise_coeff: f32,
+    fall_coeff: f32,
+    /// Moyenne quadratique long terme (niveau "programme")
+    slow_ms: f32,
+    /// Moyenne quadratique court terme (détection de présence)
+    fast_ms: f32,
+    /// Gain courant en dB
+    gain_db: f32,
+    bypassed: bool,
+}
+
+/// Constante de temps du détecteur de présence (secondes).
+const PRESENCE_TIME: f32 = 0.02;
+
+/// Nombre de constantes de temps pour atteindre ~99% de l'écart.
+/// `rise_time`/`fall_time` sont exprimés en "temps pour converger",
+/// pas en constante de temps brute : e^-4.6 ≈ 1%.
+const SETTLE_TAUS: f32 = 4.6;
+
+impl AutoGain {
+    pub fn new() -> Self {
+        let mut agc = Self {
+            target_db: -18.0,
+            max_gain_db: 12.0,
+            freeze_threshold_db: -50.0,
+            window: 3.0,
+            rise_time: 3.0,
+            fall_time: 0.5,
+            sample_rate: 48_000.0,
+            slow_coeff: 0.0,
+            fast_coeff: 0.0,
+            rise_coeff: 0.0,
+            fall_coeff: 0.0,
+            slow_ms: 0.0,
+            fast_ms: 0.0,
+            gain_db: 0.0,
+            bypassed: true, // OFF par defaut, comme le gate
+        };
+        agc.update_coefficients();
+        agc
+    }
+
+    /// Niveau cible en dBFS (-40 → 0).
+    pub fn set_target_db(&mut self, target_db: f32) {
+        self.target_db = target_db.clamp(-40.0, 0.0);
+    }
+
+    /// Gain maximum (en boost comme en coupe), en dB (0 → 30).
+    pub fn set_max_gain_db(&mut self, max_gain_db: f32) {
+        self.max_gain_db = max_gain_db.clamp(0.0, 30.0);
+        self.gain_db = self.gain_db.clamp(-self.max_gain_db, self.max_gain_db);
+    }
+
+    /// Seuil de gel en dBFS (-90 → -20).
+    pub fn set_freeze_threshold_db(&mut self, threshold_db: f32) {
+        self.freeze_threshold_db = threshold_db.clamp(-90.0, -20.0);
+    }
+
+    /// Fenêtre de mesure du RMS long terme, en secondes (0.5 → 10).
+    pub fn set_window(&mut self, seconds: f32) {
+        self.window = seconds.clamp(0.5, 10.0);
+        self.update_coefficients();
+    }
+
+    /// Temps de montée du gain, en secondes (0.5 → 30).
+    pub fn set_rise_time(&mut self, seconds: f32) {
+        self.rise_time = seconds.clamp(0.5, 30.0);
+        self.update_coefficients();
+    }
+
+    /// Temps de descente du gain, en secondes (0.05 → 10).
+    pub fn set_fall_time(&mut self, seconds: f32) {
+        self.fall_time = seconds.clamp(0.05, 10.0);
+        self.update_coefficients();
+    }
+
+    pub fn target_db(&self) -> f32 {
+        self.target_db
+    }
+
+    pub fn max_gain_db(&self) -> f32 {
+        self.max_gain_db
+    }
+
+    pub fn freeze_threshold_db(&self) -> f32 {
+        self.freeze_threshold_db
+    }
+
+    pub fn window(&self) -> f32 {
+        self.window
+    }
+
+    pub fn rise_time(&self) -> f32 {
+        self.rise_time
+    }
+
+    pub fn fall_time(&self) -> f32 {
+        self.fall_time
+    }
+
+    /// Gain appliqué actuellement, en dB (pour l'UI).
+    pub fn current_gain_db(&self) -> f32 {
+        self.gain_db
+    }
+
+    /// Convertit une durée en coefficient de lissage exponentiel par sample.
+    fn coeff(&self, seconds: f32) -> f32 {
+        1.0 - (-1.0 / (seconds * self.sample_rate)).exp()
+    }
+
+    fn update_coefficients(&mut self) {
+        self.slow_coeff = self.coeff(self.window);
+        self.fast_coeff = self.coeff(PRESENCE_TIME);
+        self.rise_coeff = self.coeff(self.rise_time / SETTLE_TAUS);
+        self.fall_coeff = self.coeff(self.fall_time / SETTLE_TAUS);
+    }
+}
+
+impl Default for AutoGain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Puissance (moyenne quadratique) → dB. Plancher à -120 dB.
+fn power_to_db(ms: f32) -> f32 {
+    10.0 * ms.max(1e-12).log10()
+}
+
+impl Processor for AutoGain {
+    fn name(&self) -> &'static str {
+        "AutoGain"
+    }
+
+    fn process_sample(&mut self, sample: f32) -> f32 {
+        if self.bypassed {
+            return sample;
+        }
+
+        let sq = sample * sample;
+
+        // 1. Détecteur de présence (rapide)
+        self.fast_ms += self.fast_coeff * (sq - self.fast_ms);
+        let present = power_to_db(self.fast_ms) >= self.freeze_threshold_db;
+
+        // 2. Sous le seuil → tout est gelé (mesure ET gain)
+        if present {
+            self.slow_ms += self.slow_coeff * (sq - self.slow_ms);
+
+            let level_db = power_to_db(self.slow_ms);
+            let desired_db = (self.target_db - level_db).clamp(-self.max_gain_db, self.max_gain_db);
+
+            // Descente rapide, montée lente
+            let coeff = if desired_db < self.gain_db {
+                self.fall_coeff
+            } else {
+                self.rise_coeff
+            };
+            self.gain_db += coeff * (desired_db - self.gain_db);
+        }
+
+        sample * 10.0_f32.powf(self.gain_db / 20.0)
+    }
+
+    fn reset(&mut self) {
+        self.slow_ms = 0.0;
+        self.fast_ms = 0.0;
+        self.gain_db = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(1.0);
+        self.update_coefficients();
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "target_db" => self.set_target_db(value),
+            "max_gain_db" => self.set_max_gain_db(value),
+            "freeze_threshold_db" => self.set_freeze_threshold_db(value),
+            "window" => self.set_window(value),
+            "rise_time" => self.set_rise_time(value),
+            "fall_time" => self.set_fall_time(value),
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_bypass(&mut self, bypass: bool) {
+        self.bypassed = bypass;
+    }
+
+    fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48_000.0;
+
+    /// Génère `seconds` de "parole" synthétique : rafales de 250 ms
+    /// d'une sinusoïde à `rms_db` dBFS, séparées de 50 ms de silence.
+    fn speech_bursts(rms_db: f32, seconds: f32) -> Vec<f32> {
+        let amplitude = 10.0_f32.powf(rms_db / 20.0) * std::f32::consts::SQRT_2;
+        let total = (seconds * SR) as usize;
+        let period = (0.3 * SR) as usize;
+        let on = (0.25 * SR) as usize;
+        (0..total)
+            .map(|i| {
+                if i % period < on {
+                    amplitude * (2.0 * std::f32::consts::PI * 220.0 * i as f32 / SR).sin()
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+
+    fn enabled_agc() -> AutoGain {
+        let mut agc = AutoGain::new();
+        agc.set_bypass(false);
+        agc.set_target_db(-18.0);
+        agc.set_max_gain_db(20.0);
+        agc.set_window(1.0);
+        agc.set_rise_time(3.0);
+        agc
+    }
+
+    /// RMS en dBFS des portions non silencieuses.
+    fn active_rms_db(samples: &[f32]) -> f32 {
+        let active: Vec<f32> = samples.iter().copied().filter(|s| *s != 0.0).collect();
+        let ms = active.iter().map(|s| s * s).sum::<f32>() / active.len() as f32;
+        power_to_db(ms)
+    }
+
+    #[test]
+    fn agc_converges_to_target() {
+        let mut agc = enabled_agc();
+
+        // Fenêtre de mesure + temps de montée configurés
+        let input = speech_bursts(-30.0, 1.0 + 3.0);
+        let output: Vec<f32> = input.iter().map(|&s| agc.process_sample(s)).collect();
+
+        // Dernière seconde : le niveau doit être à ±2 dB de la cible
+        let tail = &output[output.len() - SR as usize..];
+        let level = active_rms_db(tail);
+        assert!(
+            (level - -18.0).abs() < 2.0,
+            "Output level should converge to -18 dBFS, got {level}"
+        );
+    }
+
+    #[test]
+    fn agc_respects_max_gain() {
+        let mut agc = enabled_agc();
+        agc.set_max_gain_db(6.0);
+
+        for &s in &speech_bursts(-40.0, 6.0) {
+            agc.process_sample(s);
+        }
+        assert!(
+            agc.current_gain_db() <= 6.0 + 1e-3,
+            "Gain should be capped at 6 dB, got {}",
+            agc.current_gain_db()
+        );
+    }
+
+    #[test]
+    fn agc_silence_does_not_increase_gain() {
+        let mut agc = enabled_agc();
+        for &s in &speech_bursts(-24.0, 5.0) {
+            agc.process_sample(s);
+        }
+        let gain_before = agc.current_gain_db();
+
+        // 10 secondes de silence (avec un bruit de fond très faible)
+        for i in 0..(10.0 * SR) as usize {
+            let noise = if i % 2 == 0 { 1e-4 } else { -1e-4 }; // -80 dBFS
+            agc.process_sample(noise);
+        }
+
+        assert!(
+            agc.current_gain_db() <= gain_before + 0.1,
+            "Gain rose during silence: {gain_before} -> {}",
+            agc.current_gain_db()
+        );
+    }
+
+    #[test]
+    fn agc_falls_faster_than_it_rises() {
+        let mut agc = enabled_agc();
+        agc.set_fall_time(0.5);
+
+        // Converger sur un signal faible (gain élevé)...
+        for &s in &speech_bursts(-30.0, 6.0) {
+            agc.process_sample(s);
+        }
+        let high_gain = agc.current_gain_db();
+
+        // ...puis un signal très fort : le gain doit chuter en < 1 s
+        for &s in &speech_bursts(-6.0, 1.0) {
+            agc.process_sample(s);
+        }
+        assert!(
+            agc.current_gain_db() < high_gain - 10.0,
+            "Gain should drop quickly: {high_gain} -> {}",
+            agc.current_gain_db()
+        );
+    }
+
+    #[test]
+    fn agc_bypass() {
+        let mut agc = AutoGain::new();
+        // Bypassé par défaut
+        assert!(agc.is_bypassed());
+        assert_eq!(agc.process_sample(0.3), 0.3);
+    }
+
+    #[test]
+    fn agc_reset() {
+        let mut agc = enabled_agc();
+        for &s in &speech_bursts(-30.0, 2.0) {
+            agc.process_sample(s);
+        }
+        assert!(agc.current_gain_db() > 0.0);
+
+        agc.reset();
+        assert_eq!(agc.current_gain_db(), 0.0);
+    }
+
+    #[test]
+    fn agc_parameter_clamping() {
+        let mut agc = AutoGain::new();
+        agc.set_target_db(10.0);
+        assert_eq!(agc.target_db(), 0.0);
+        agc.set_max_gain_db(100.0);
+        assert_eq!(agc.max_gain_db(), 30.0);
+        agc.set_rise_time(0.0);
+        assert_eq!(agc.rise_time(), 0.5);
+    }
+}
@@ -0,0 +1,106 @@
+/// Rampe linéaire d'un paramètre d'effet ("automation").
+///
+/// # Par blocs, pas par sample
+/// Changer un seuil ou un gain d'EQ recalcule des coefficients (un
+/// biquad, un `powf`) : le faire à chaque sample coûterait cher pour
+/// rien. La valeur avance une fois par bloc audio (~5 ms à 256 frames),
+/// assez fin pour qu'une rampe de plusieurs secondes soit continue à
+/// l'oreille.
+///
+/// Comme `GainRamp`, la durée est comptée en frames : c'est le callback
+/// qui fait avancer la rampe, quel que soit le rythme de l'UI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamRamp {
+    /// Position de l'effet dans la chaîne
+    pub effect_index: usize,
+    /// Nom du paramètre (voir `EffectsPreset::param_mut`)
+    pub param: String,
+    start: f32,
+    target: f32,
+    total_frames: usize,
+    elapsed_frames: usize,
+}
+
+impl ParamRamp {
+    pub fn new(
+        effect_index: usize,
+        param: impl Into<String>,
+        start: f32,
+        target: f32,
+        duration_ms: u32,
+        sample_rate: f32,
+    ) -> Self {
+        Self {
+            effect_index,
+            param: param.into(),
+            start,
+            target,
+            total_frames: (duration_ms as f32 / 1000.0 * sample_rate).round() as usize,
+            elapsed_frames: 0,
+        }
+    }
+
+    /// La même rampe, partant de `value` (relais d'une rampe en cours).
+    pub fn starting_at(mut self, value: f32) -> Self {
+        self.start = value;
+        self
+    }
+
+    /// Vise le même paramètre que `other`.
+    pub fn same_param(&self, other: &ParamRamp) -> bool {
+        self.effect_index == other.effect_index && self.param == other.param
+    }
+
+    /// Valeur atteinte à la fin du dernier bloc.
+    pub fn current(&self) -> f32 {
+        if self.elapsed_frames >= self.total_frames {
+            return self.target;
+        }
+        let t = self.elapsed_frames as f32 / self.total_frames as f32;
+        self.start + (self.target - self.start) * t
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// La cible est atteinte (et appliquée).
+    pub fn is_finished(&self) -> bool {
+        self.elapsed_frames >= self.total_frames
+    }
+
+    /// Avance de `frames` et retourne la nouvelle valeur. La dernière
+    /// valeur est exactement la cible, sans erreur d'arrondi.
+    pub fn advance(&mut self, frames: usize) -> f32 {
+        self.elapsed_frames = (self.elapsed_frames + frames).min(self.total_frames);
+        self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramp_interpolates_linearly_across_blocks() {
+        // 0 → -12 dB en 1 s à 48 kHz, blocs de 480 frames (10 ms)
+        let mut ramp = ParamRamp::new(2, "band2_gain_db", 0.0, -12.0, 1000, 48_000.0);
+        let values: Vec<f32> = (0..100).map(|_| ramp.advance(480)).collect();
+
+        for (i, value) in values.iter().enumerate() {
+            let expected = -12.0 * (i + 1) as f32 / 100.0;
+            assert!((value - expected).abs() < 1e-4, "block {i}: {value}");
+        }
+        assert_eq!(values[99], -12.0);
+        assert!(ramp.is_finished());
+        // Au-delà de la durée, la valeur reste la cible
+        assert_eq!(ramp.advance(480), -12.0);
+    }
+
+    #[test]
+    fn zero_duration_lands_on_target_at_next_block() {
+        let mut ramp = ParamRamp::new(3, "ratio", 4.0, 8.0, 0, 48_000.0);
+        assert!(ramp.is_finished());
+        assert_eq!(ramp.advance(256), 8.0);
+    }
+}
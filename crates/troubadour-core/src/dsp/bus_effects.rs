@@ -0,0 +1,267 @@
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::dsp::{EffectsPreset, InsertPoint};
+
+use super::{EffectPanic, EffectsChain, Processor};
+
+/// Capacité initiale du buffer de désentrelacement (frames par bloc) :
+/// au-delà, le premier bloc plus long réalloue une fois.
+const SCRATCH_FRAMES: usize = 4096;
+
+/// Effets du bus joué, de part et d'autre de son fader.
+///
+/// Les `EffectsChain` sont mono : chaque côté du fader a une chaîne par
+/// canal (gauche, droite), construites depuis le preset du bus avec les
+/// effets de l'autre côté coupés. Un côté sans effet actif n'a pas de
+/// chaîne et ne coûte rien.
+///
+/// Partagé avec le callback derrière un `Mutex`, comme la chaîne du Mic :
+/// le thread de contrôle construit les chaînes hors du lock et les
+/// échange, le callback fait un `try_lock`.
+pub struct BusEffects {
+    bus: Option<ChannelId>,
+    pre_fader: Option<[EffectsChain; 2]>,
+    post_fader: Option<[EffectsChain; 2]>,
+    /// Un canal désentrelacé, réutilisé d'un bloc à l'autre
+    scratch: Vec<f32>,
+    /// Chaînes chronométrées (voir `EffectsChain::set_profiling`)
+    profiling: bool,
+}
+
+impl BusEffects {
+    pub fn new() -> Self {
+        Self {
+            bus: None,
+            pre_fader: None,
+            post_fader: None,
+            scratch: Vec::with_capacity(SCRATCH_FRAMES),
+            profiling: false,
+        }
+    }
+
+    /// Bus dont les effets tournent (`None` : pas de bus joué).
+    pub fn bus(&self) -> Option<ChannelId> {
+        self.bus
+    }
+
+    /// Remplace les chaînes par celles du preset de `bus` (`None` : bus
+    /// sans effets). Alloue : à appeler hors du callback audio.
+    pub fn load(
+        &mut self,
+        bus: Option<ChannelId>,
+        preset: Option<&EffectsPreset>,
+        sample_rate: f32,
+    ) {
+        let profiling = self.profiling;
+        let chains = |point| {
+            let preset = preset?.at_insert_point(point)?;
+            Some([(); 2].map(|_| {
+                let mut chain = EffectsChain::from_preset(&preset);
+                chain.set_sample_rate(sample_rate);
+                chain.set_profiling(profiling);
+                chain
+            }))
+        };
+        self.bus = bus;
+        self.pre_fader = chains(InsertPoint::PreFader);
+        self.post_fader = chains(InsertPoint::PostFader);
+    }
+
+    /// Traite un bloc stéréo entrelacé avec les effets placés à `point`.
+    ///
+    /// Un bloc de longueur impaire (fin tronquée d'un fichier) se termine
+    /// par une frame mono : son sample sort de la chaîne de gauche, et
+    /// passe aussi dans celle de droite pour que les deux côtés restent
+    /// au même instant (enveloppes, tenue du gate).
+    ///
+    /// Un étage qui panique est mis à l'écart par sa chaîne (voir
+    /// `EffectsChain::process_block`) et retourné pour être signalé.
+    pub fn process(&mut self, point: InsertPoint, interleaved: &mut [f32]) -> Option<EffectPanic> {
+        let chains = match point {
+            InsertPoint::PreFader => self.pre_fader.as_mut()?,
+            InsertPoint::PostFader => self.post_fader.as_mut()?,
+        };
+        // Frame mono finale, lue avant que la gauche ne la remplace
+        let trailing = interleaved.chunks_exact(2).remainder().first().copied();
+        let mut panicked = None;
+        for (side, chain) in chains.iter_mut().enumerate() {
+            self.scratch.clear();
+            self.scratch
+                .extend(interleaved.iter().skip(side).step_by(2).copied());
+            if side == 1 {
+                self.scratch.extend(trailing);
+            }
+            panicked = panicked.or(chain.process_block(&mut self.scratch));
+            for (sample, &processed) in interleaved
+                .iter_mut()
+                .skip(side)
+                .step_by(2)
+                .zip(&self.scratch)
+            {
+                *sample = processed;
+            }
+        }
+        panicked
+    }
+
+    /// Chronomètre les étages des quatre chaînes, y compris celles du
+    /// prochain `load`. Alloue à l'activation : hors du callback audio.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        self.profiling = enabled;
+        for chain in self.chains_mut() {
+            chain.set_profiling(enabled);
+        }
+    }
+
+    pub fn profiling(&self) -> bool {
+        self.profiling
+    }
+
+    /// Temps moyen de chaque étage, en µs par bloc, ses chaînes
+    /// (gauche et droite, avant et après le fader) additionnées : une
+    /// entrée par effet du preset, avec son nom. Vide si le profilage
+    /// est coupé ou si le bus n'a pas d'effet actif.
+    pub fn stage_micros(&self) -> Vec<(&'static str, f32)> {
+        let chains: Vec<&EffectsChain> = self
+            .pre_fader
+            .iter()
+            .chain(&self.post_fader)
+            .flatten()
+            .filter(|chain| chain.profiling())
+            .collect();
+        let Some(first) = chains.first() else {
+            return Vec::new();
+        };
+        first
+            .processors()
+            .enumerate()
+            .map(|(i, proc)| {
+                let micros = chains
+                    .iter()
+                    .filter_map(|chain| chain.stage_micros()?.get(i))
+                    .sum();
+                (proc.name(), micros)
+            })
+            .collect()
+    }
+
+    /// Frames du dernier bloc profilé (0 : aucun).
+    pub fn profiled_frames(&self) -> usize {
+        self.pre_fader
+            .iter()
+            .chain(&self.post_fader)
+            .flatten()
+            .map(EffectsChain::profiled_frames)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn chains_mut(&mut self) -> impl Iterator<Item = &mut EffectsChain> {
+        self.pre_fader
+            .iter_mut()
+            .chain(&mut self.post_fader)
+            .flatten()
+    }
+
+    /// Étage `index` du canal gauche, dans la chaîne de son point
+    /// d'insertion (`None` : aucun effet actif de ce côté du fader).
+    pub fn stage(&self, index: usize, point: InsertPoint) -> Option<&dyn Processor> {
+        let chains = match point {
+            InsertPoint::PreFader => self.pre_fader.as_ref()?,
+            InsertPoint::PostFader => self.post_fader.as_ref()?,
+        };
+        chains[0].processors().nth(index)
+    }
+}
+
+impl Default for BusEffects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_run_only_on_their_side_of_the_fader() {
+        let mut preset = EffectsPreset::clean();
+        preset.compressor.enabled = true;
+        preset.compressor.threshold = 0.1;
+        preset.set_insert_point(3, InsertPoint::PostFader);
+        let mut effects = BusEffects::new();
+        effects.load(Some(ChannelId(3)), Some(&preset), 48_000.0);
+
+        let mut block = vec![0.5_f32; 512];
+        effects.process(InsertPoint::PreFader, &mut block);
+        // Avant le fader : seul le limiter, sous son plafond
+        assert!(block.iter().all(|&s| s == 0.5));
+        assert!(
+            effects
+                .stage(3, InsertPoint::PreFader)
+                .unwrap()
+                .is_bypassed()
+        );
+
+        effects.process(InsertPoint::PostFader, &mut block);
+        assert!(block[511] < 0.5);
+        assert!(
+            effects
+                .stage(3, InsertPoint::PostFader)
+                .unwrap()
+                .gain_reduction()
+                > Some(0.0)
+        );
+
+        effects.load(Some(ChannelId(3)), None, 48_000.0);
+        assert!(effects.stage(3, InsertPoint::PreFader).is_none());
+    }
+
+    #[test]
+    fn profiling_adds_up_every_chain_of_an_effect() {
+        let mut preset = EffectsPreset::clean();
+        preset.compressor.enabled = true;
+        preset.set_insert_point(3, InsertPoint::PostFader);
+        let mut effects = BusEffects::new();
+        effects.set_profiling(true);
+        // Le réglage passe aux chaînes chargées ensuite
+        effects.load(Some(ChannelId(3)), Some(&preset), 48_000.0);
+        assert!(effects.profiling());
+
+        let mut block = vec![0.5_f32; 512];
+        effects.process(InsertPoint::PreFader, &mut block);
+        effects.process(InsertPoint::PostFader, &mut block);
+        assert_eq!(effects.profiled_frames(), 256);
+        let stages = effects.stage_micros();
+        assert_eq!(stages.len(), 6);
+        assert_eq!(stages[3].0, "Compressor");
+        assert!(stages.iter().all(|&(_, micros)| micros > 0.0));
+
+        effects.set_profiling(false);
+        assert!(effects.stage_micros().is_empty());
+    }
+
+    #[test]
+    fn odd_blocks_keep_both_sides_in_step() {
+        let mut preset = EffectsPreset::clean();
+        preset.compressor.enabled = true;
+        preset.compressor.threshold = 0.1;
+        let mut effects = BusEffects::new();
+        effects.load(Some(ChannelId(3)), Some(&preset), 48_000.0);
+
+        // Même signal des deux côtés, en blocs de toutes les tailles
+        let mut t = 0;
+        for len in crate::dsp::tests::BLOCK_LENGTHS {
+            let mut block: Vec<f32> = (0..len)
+                .map(|i| ((t + i / 2) as f32 * 0.01).sin() * 0.8)
+                .collect();
+            t += len.div_ceil(2);
+            effects.process(InsertPoint::PreFader, &mut block);
+            assert_eq!(block.len(), len);
+            assert!(block.iter().all(|s| s.is_finite()));
+            for frame in block.chunks_exact(2) {
+                assert_eq!(frame[0], frame[1], "block of {len}");
+            }
+        }
+    }
+}
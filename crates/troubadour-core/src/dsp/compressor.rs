@@ -1,4 +1,4 @@
-use super::Processor;
+use super::{Processor, REFERENCE_SAMPLE_RATE, rescale_coefficient};
 
 /// Compresseur dynamique — réduit la plage dynamique du signal.
 ///
@@ -13,6 +13,9 @@ use super::Processor;
 /// - `threshold` : au-dessus de ce seuil, le compresseur s'active (0.0-1.0)
 /// - `ratio` : facteur de compression (2.0 = pour 2dB au-dessus du seuil,
 ///   seulement 1dB passe. 10.0 = quasi-limiter)
+/// - `knee_db` : largeur du coude autour du seuil. Dans le coude, la
+///   compression arrive progressivement au lieu de tomber d'un coup
+///   (0 = coude dur, plus sec sur la voix)
 /// - `attack` : vitesse de réaction quand le signal monte (lent = laisse passer
 ///   les transitoires/attaques, rapide = compresse tout)
 /// - `release` : vitesse de relâchement quand le signal descend
@@ -24,45 +27,95 @@ use super::Processor;
 pub struct Compressor {
     threshold: f32,
     ratio: f32,
+    knee_db: f32,
     attack: f32,
     release: f32,
     makeup_gain: f32,
+    /// Coefficients effectifs, adaptés au sample rate courant
+    attack_coeff: f32,
+    release_coeff: f32,
+    /// Bords du coude (linéaires) et réduction de la courbe dure au bord
+    /// haut, en dB : recalculés avec les réglages, pas à chaque sample
+    knee_lower: f32,
+    knee_upper: f32,
+    knee_lower_db: f32,
+    knee_upper_reduction_db: f32,
+    sample_rate: f32,
     envelope: f32,
     /// Le gain reduction actuel (0.0 = pas de compression, négatif = compression)
     gain_reduction: f32,
+    /// Écoute du détecteur (transitoire, jamais sauvée)
+    listen: bool,
     bypassed: bool,
 }
 
 impl Compressor {
     pub fn new() -> Self {
-        Self {
+        let mut comp = Self {
             threshold: 0.4,   // Seuil plus haut - comprime seulement les vrais pics
             ratio: 3.0,       // 3:1 = compression douce
+            knee_db: 6.0,     // Coude doux : pas de cassure au seuil
             attack: 0.005,    // Tres rapide
             release: 0.02,    // Release doux
             makeup_gain: 1.2, // Makeup leger pour ne pas amplifier le bruit
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            knee_lower: 0.0,
+            knee_upper: 0.0,
+            knee_lower_db: 0.0,
+            knee_upper_reduction_db: 0.0,
+            sample_rate: REFERENCE_SAMPLE_RATE,
             envelope: 0.0,
             gain_reduction: 0.0,
+            listen: false,
             bypassed: false,
-        }
+        };
+        comp.update_coefficients();
+        comp.update_knee();
+        comp
+    }
+
+    fn update_coefficients(&mut self) {
+        self.attack_coeff = rescale_coefficient(self.attack, self.sample_rate);
+        self.release_coeff = rescale_coefficient(self.release, self.sample_rate);
+    }
+
+    /// Bords du coude : `knee_db / 2` de part et d'autre du seuil.
+    fn update_knee(&mut self) {
+        let threshold_db = 20.0 * self.threshold.log10();
+        self.knee_lower_db = threshold_db - self.knee_db / 2.0;
+        self.knee_lower = 10f32.powf(self.knee_lower_db / 20.0);
+        self.knee_upper = 10f32.powf((threshold_db + self.knee_db / 2.0) / 20.0);
+        self.knee_upper_reduction_db = 20.0 * self.hard_knee_gain(self.knee_upper).log10();
     }
 
     pub fn set_threshold(&mut self, threshold: f32) {
         self.threshold = threshold.clamp(0.01, 1.0);
+        self.update_knee();
     }
 
     /// Ratio de compression.
     /// 1.0 = pas de compression, 2.0 = 2:1, 10.0 = quasi-limiter.
     pub fn set_ratio(&mut self, ratio: f32) {
         self.ratio = ratio.clamp(1.0, 20.0);
+        self.update_knee();
+    }
+
+    /// Largeur du coude en dB (0 à 24). 0 = coude dur : pleine
+    /// compression dès le seuil.
+    pub fn set_knee_db(&mut self, knee_db: f32) {
+        self.knee_db = knee_db.clamp(0.0, 24.0);
+        self.update_knee();
     }
 
     pub fn set_attack(&mut self, attack: f32) {
         self.attack = attack.clamp(0.001, 0.5);
+        self.update_coefficients();
     }
 
     pub fn set_release(&mut self, release: f32) {
         self.release = release.clamp(0.001, 0.5);
+        self.update_coefficients();
     }
 
     /// Makeup gain : compense la perte de volume due à la compression.
@@ -79,6 +132,10 @@ impl Compressor {
         self.ratio
     }
 
+    pub fn knee_db(&self) -> f32 {
+        self.knee_db
+    }
+
     pub fn attack(&self) -> f32 {
         self.attack
     }
@@ -91,6 +148,42 @@ impl Compressor {
         self.makeup_gain
     }
 
+    /// Gain appliqué à une enveloppe stable `envelope` (courbe de
+    /// transfert statique, sans attack ni release).
+    ///
+    /// Sous le coude : 1. Au-dessus : la courbe dure. Dans le coude, la
+    /// réduction en dB croît comme le carré de la distance au bord bas
+    /// et rejoint la courbe dure au bord haut ; au seuil, elle vaut le
+    /// quart de celle du bord haut (interpolation quadratique classique).
+    pub fn static_gain(&self, envelope: f32) -> f32 {
+        if self.knee_db <= 0.0 || envelope >= self.knee_upper {
+            return self.hard_knee_gain(envelope);
+        }
+        if envelope <= self.knee_lower {
+            return 1.0;
+        }
+        let position = (20.0 * envelope.log10() - self.knee_lower_db) / self.knee_db;
+        10f32.powf(self.knee_upper_reduction_db * position * position / 20.0)
+    }
+
+    /// Courbe à coude dur : pleine compression dès le seuil.
+    fn hard_knee_gain(&self, envelope: f32) -> f32 {
+        if envelope > self.threshold {
+            // Formule : gain = threshold + (envelope - threshold) / ratio
+            // Normalisé : gain = résultat / envelope
+            //
+            // Exemple avec ratio 4:1, threshold 0.3, envelope 0.7 :
+            //   target = 0.3 + (0.7 - 0.3) / 4 = 0.3 + 0.1 = 0.4
+            //   gain = 0.4 / 0.7 ≈ 0.57
+            //   → le signal est réduit à 57% de sa valeur originale
+            let target = self.threshold + (envelope - self.threshold) / self.ratio;
+            target / envelope.max(0.0001) // .max pour éviter division par 0
+        } else {
+            // Sous le seuil : pas de compression
+            1.0
+        }
+    }
+
     /// Retourne le gain reduction actuel (pour l'UI).
     /// Valeur entre 0.0 (pas de compression) et 1.0 (compression max).
     pub fn current_gain_reduction(&self) -> f32 {
@@ -105,6 +198,14 @@ impl Default for Compressor {
 }
 
 impl Processor for Compressor {
+    fn name(&self) -> &'static str {
+        "Compressor"
+    }
+
+    fn gain_reduction(&self) -> Option<f32> {
+        Some(self.gain_reduction)
+    }
+
     fn process_sample(&mut self, sample: f32) -> f32 {
         if self.bypassed {
             return sample;
@@ -113,33 +214,25 @@ impl Processor for Compressor {
         // 1. Envelope follower (comme le noise gate)
         let abs_sample = sample.abs();
         let coeff = if abs_sample > self.envelope {
-            self.attack
+            self.attack_coeff
         } else {
-            self.release
+            self.release_coeff
         };
         self.envelope += coeff * (abs_sample - self.envelope);
 
-        // 2. Calculer le gain
-        let gain = if self.envelope > self.threshold {
-            // Au-dessus du seuil : comprimer
-            //
-            // Formule : gain = threshold + (envelope - threshold) / ratio
-            // Normalisé : gain = résultat / envelope
-            //
-            // Exemple avec ratio 4:1, threshold 0.3, envelope 0.7 :
-            //   target = 0.3 + (0.7 - 0.3) / 4 = 0.3 + 0.1 = 0.4
-            //   gain = 0.4 / 0.7 ≈ 0.57
-            //   → le signal est réduit à 57% de sa valeur originale
-            let target = self.threshold + (self.envelope - self.threshold) / self.ratio;
-            target / self.envelope.max(0.0001) // .max pour éviter division par 0
-        } else {
-            // Sous le seuil : pas de compression
-            1.0
-        };
+        // 2. Calculer le gain (coude compris)
+        let gain = self.static_gain(self.envelope);
 
         // Stocker le gain reduction pour l'UI
         self.gain_reduction = 1.0 - gain;
 
+        // Écoute du détecteur : seulement ce que la compression retire
+        // ("delta"). Silence tant que l'enveloppe reste sous le seuil,
+        // d'autant plus fort que la réduction est grande.
+        if self.listen {
+            return sample * self.gain_reduction;
+        }
+
         // 3. Appliquer le gain + makeup
         sample * gain * self.makeup_gain
     }
@@ -149,6 +242,29 @@ impl Processor for Compressor {
         self.gain_reduction = 0.0;
     }
 
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(1.0);
+        self.update_coefficients();
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "threshold" => self.set_threshold(value),
+            "ratio" => self.set_ratio(value),
+            "knee_db" => self.set_knee_db(value),
+            "attack" => self.set_attack(value),
+            "release" => self.set_release(value),
+            "makeup_gain" => self.set_makeup_gain(value),
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_detector_listen(&mut self, listen: bool) -> bool {
+        self.listen = listen;
+        true
+    }
+
     fn set_bypass(&mut self, bypass: bool) {
         self.bypassed = bypass;
     }
@@ -260,4 +376,118 @@ mod tests {
         comp.set_ratio(100.0);
         assert_eq!(comp.ratio(), 20.0);
     }
+
+    #[test]
+    fn detector_listen_follows_gain_reduction_not_program() {
+        let mut comp = Compressor::new();
+        let mut normal = Compressor::new();
+        comp.set_detector_listen(true);
+
+        // Sous le seuil : le programme passe, l'écoute est muette
+        for _ in 0..1000 {
+            assert!(normal.process_sample(0.2) > 0.2);
+            assert_eq!(comp.process_sample(0.2), 0.0);
+        }
+        // Au-dessus : l'écoute suit la réduction de gain
+        let mut out = 0.0;
+        for _ in 0..2000 {
+            out = comp.process_sample(0.8);
+            normal.process_sample(0.8);
+        }
+        assert!(out > 0.0);
+        assert!((out - 0.8 * comp.current_gain_reduction()).abs() < 1e-6);
+
+        comp.set_detector_listen(false);
+        for _ in 0..100 {
+            assert_eq!(comp.process_sample(0.8), normal.process_sample(0.8));
+        }
+    }
+
+    /// Courbe dure de référence, telle que `process_sample` la calculait
+    /// avant le coude.
+    fn hard_knee_reference(threshold: f32, ratio: f32, envelope: f32) -> f32 {
+        if envelope > threshold {
+            (threshold + (envelope - threshold) / ratio) / envelope.max(0.0001)
+        } else {
+            1.0
+        }
+    }
+
+    fn db(gain: f32) -> f32 {
+        20.0 * gain.log10()
+    }
+
+    #[test]
+    fn soft_knee_transfer_curve_at_its_edges_and_threshold() {
+        let mut comp = Compressor::new();
+        comp.set_threshold(0.25);
+        comp.set_ratio(4.0);
+        comp.set_knee_db(12.0);
+        let threshold_db = db(0.25);
+        let at = |offset_db: f32| 10f32.powf((threshold_db + offset_db) / 20.0);
+
+        // Bord haut : la courbe dure, pleine compression
+        let upper = at(6.0);
+        let full = db(hard_knee_reference(0.25, 4.0, upper));
+        assert!((db(comp.static_gain(upper)) - full).abs() < 1e-3);
+        assert!(full < -1.0);
+        // Au seuil : le quart de la réduction du bord haut
+        assert!((db(comp.static_gain(0.25)) - full / 4.0).abs() < 1e-3);
+        // Bord bas et en dessous : rien
+        assert_eq!(comp.static_gain(at(-6.0)), 1.0);
+        assert_eq!(comp.static_gain(at(-20.0)), 1.0);
+        // Juste dans le coude : réduction minime, continue
+        assert!(db(comp.static_gain(at(-5.9))) > -0.01);
+        // Au-delà : la courbe dure
+        let loud = at(15.0);
+        assert_eq!(comp.static_gain(loud), hard_knee_reference(0.25, 4.0, loud));
+    }
+
+    #[test]
+    fn zero_knee_reproduces_the_hard_knee_exactly() {
+        let mut comp = Compressor::new();
+        comp.set_knee_db(0.0);
+        for (threshold, ratio) in [(0.4, 3.0), (0.2, 4.0), (0.05, 20.0)] {
+            comp.set_threshold(threshold);
+            comp.set_ratio(ratio);
+            for step in 0..=200 {
+                let envelope = step as f32 / 200.0;
+                assert_eq!(
+                    comp.static_gain(envelope),
+                    hard_knee_reference(threshold, ratio, envelope),
+                    "threshold {threshold}, ratio {ratio}, envelope {envelope}"
+                );
+            }
+        }
+
+        // Et en traitement : même sortie, sample par sample
+        let mut hard = Compressor::new();
+        hard.set_knee_db(0.0);
+        hard.set_threshold(0.2);
+        hard.set_ratio(4.0);
+        let mut envelope = 0.0f32;
+        let attack = rescale_coefficient(hard.attack(), REFERENCE_SAMPLE_RATE);
+        let release = rescale_coefficient(hard.release(), REFERENCE_SAMPLE_RATE);
+        for n in 0..500 {
+            let sample = (n as f32 * 0.05).sin() * 0.8;
+            let coeff = if sample.abs() > envelope {
+                attack
+            } else {
+                release
+            };
+            envelope += coeff * (sample.abs() - envelope);
+            let expected = sample * hard_knee_reference(0.2, 4.0, envelope) * hard.makeup_gain();
+            assert_eq!(hard.process_sample(sample), expected);
+        }
+    }
+
+    #[test]
+    fn knee_width_is_clamped_and_automatable() {
+        let mut comp = Compressor::new();
+        assert_eq!(comp.knee_db(), 6.0);
+        comp.set_knee_db(-3.0);
+        assert_eq!(comp.knee_db(), 0.0);
+        assert!(comp.set_param("knee_db", 48.0));
+        assert_eq!(comp.knee_db(), 24.0);
+    }
 }
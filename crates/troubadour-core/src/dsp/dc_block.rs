@@ -0,0 +1,144 @@
+/// Fréquence de coupure du bloqueur de DC.
+pub const DC_BLOCK_HZ: f32 = 5.0;
+
+/// Bloqueur de DC — passe-haut du premier ordre à ~5 Hz.
+///
+/// # Le DC, c'est quoi ?
+/// Une interface bon marché ajoute parfois une tension constante au
+/// signal : la forme d'onde ne tourne plus autour de zéro mais de 0.1,
+/// par exemple. Inaudible en soi, mais ça mange de la marge (le signal
+/// clippe plus tôt d'un côté) et ça fausse gate et compresseur, qui
+/// voient un niveau permanent.
+///
+/// # Le filtre
+/// ```text
+///   y[n] = x[n] - x[n-1] + R · y[n-1]      R = e^(-2π·fc/fs)
+/// ```
+/// `x[n] - x[n-1]` supprime tout ce qui ne bouge pas ; `R` proche de 1
+/// laisse passer tout ce qui bouge plus vite que `fc`. À 5 Hz, la voix
+/// la plus grave (~80 Hz) n'est pas touchée, et un DC de 0.1 tombe sous
+/// 0.001 en ~150 ms.
+pub struct DcBlocker {
+    coeff: f32,
+    prev_input: f32,
+    prev_output: f32,
+}
+
+impl DcBlocker {
+    pub fn new(sample_rate: f32) -> Self {
+        let mut blocker = Self {
+            coeff: 0.0,
+            prev_input: 0.0,
+            prev_output: 0.0,
+        };
+        blocker.set_sample_rate(sample_rate);
+        blocker
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.coeff = (-std::f32::consts::TAU * DC_BLOCK_HZ / sample_rate.max(1.0)).exp();
+    }
+
+    pub fn process_sample(&mut self, sample: f32) -> f32 {
+        let output = sample - self.prev_input + self.coeff * self.prev_output;
+        self.prev_input = sample;
+        self.prev_output = output;
+        output
+    }
+
+    /// Oublie l'historique : le prochain sample repart de zéro.
+    pub fn reset(&mut self) {
+        self.prev_input = 0.0;
+        self.prev_output = 0.0;
+    }
+}
+
+/// Conditionnement d'une entrée, avant tout effet : inversion de
+/// polarité puis bloqueur de DC (`ChannelConfig::phase_invert` /
+/// `dc_block`).
+///
+/// # Pourquoi remettre le filtre à zéro ?
+/// L'historique d'un filtre coupé date d'avant sa coupure : le rallumer
+/// avec ces vieilles valeurs ferait un saut. Chaque bascule de l'option
+/// repart donc d'un état vierge.
+pub struct InputConditioner {
+    dc: DcBlocker,
+    dc_enabled: bool,
+}
+
+impl InputConditioner {
+    pub fn new(sample_rate: f32) -> Self {
+        Self {
+            dc: DcBlocker::new(sample_rate),
+            dc_enabled: false,
+        }
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.dc.set_sample_rate(sample_rate);
+    }
+
+    /// Options du canal, à relire à chaque bloc (snapshot du mixer).
+    pub fn set_options(&mut self, dc_block: bool) {
+        if dc_block != self.dc_enabled {
+            self.dc.reset();
+            self.dc_enabled = dc_block;
+        }
+    }
+
+    pub fn process_sample(&mut self, sample: f32, phase_invert: bool) -> f32 {
+        let sample = if phase_invert { -sample } else { sample };
+        if self.dc_enabled {
+            self.dc.process_sample(sample)
+        } else {
+            sample
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_offset_decays_within_200_ms() {
+        let sample_rate = 48_000.0;
+        let mut conditioner = InputConditioner::new(sample_rate);
+        conditioner.set_options(true);
+        let frames = (sample_rate * 0.2) as usize;
+        let last = (0..frames)
+            .map(|_| conditioner.process_sample(0.1, false))
+            .last()
+            .unwrap();
+        assert!(last.abs() < 0.001, "DC left after 200 ms: {last}");
+
+        // Option coupée : le DC repasse tel quel
+        conditioner.set_options(false);
+        assert_eq!(conditioner.process_sample(0.1, false), 0.1);
+    }
+
+    #[test]
+    fn phase_invert_negates_exactly_and_commutes_with_gain() {
+        let mut conditioner = InputConditioner::new(48_000.0);
+        for sample in [0.5, -0.25, 0.0, 1.0, -1.0, 1e-6] {
+            let inverted = conditioner.process_sample(sample, true);
+            assert_eq!(inverted, -sample);
+            // Le gain après inversion = l'inversion du signal amplifié
+            let gain = 0.7;
+            assert_eq!(inverted * gain, -(sample * gain));
+        }
+    }
+
+    #[test]
+    fn toggling_the_blocker_starts_from_a_clean_state() {
+        let mut conditioner = InputConditioner::new(48_000.0);
+        conditioner.set_options(true);
+        for _ in 0..100 {
+            conditioner.process_sample(0.5, false);
+        }
+        conditioner.set_options(false);
+        conditioner.set_options(true);
+        // Premier sample après réarmement : pas d'historique, il passe
+        assert_eq!(conditioner.process_sample(0.2, false), 0.2);
+    }
+}
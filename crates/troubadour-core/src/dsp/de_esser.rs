@@ -0,0 +1,294 @@
+use super::eq::{EqBand, FilterType};
+use super::{Processor, REFERENCE_SAMPLE_RATE, rescale_coefficient};
+
+/// De-esser — atténue les sifflantes ("s", "ch") d'une voix.
+///
+/// # Un compresseur qui n'écoute que les aigus
+/// Les micros à condensateur accentuent les sifflantes, entre 4 et
+/// 10 kHz. Un compresseur classique ne les voit pas : elles pèsent peu
+/// dans le niveau global. Le de-esser est un compresseur dont le
+/// détecteur (la "sidechain") passe d'abord par un filtre passe-bande
+/// centré sur les sifflantes :
+///
+/// ```text
+/// entrée ──┬──────────────────────► × gain ──► sortie
+///          └─► passe-bande ─► enveloppe ─► gain (seuil, ratio)
+/// ```
+///
+/// Une voix grave, même forte, ne traverse pas le filtre : aucune
+/// réduction. Un "s" au-dessus du seuil fait baisser le gain.
+///
+/// # Large bande
+/// Le gain est appliqué à tout le signal (pas seulement aux aigus) :
+/// simple et sans déphasage, au prix d'un léger creux de la voix
+/// pendant la sifflante.
+///
+/// # Paramètres
+/// - `frequency` / `q` : centre et largeur de la bande écoutée
+/// - `threshold`, `ratio`, `attack`, `release` : comme le compresseur
+pub struct DeEsser {
+    frequency: f32,
+    q: f32,
+    threshold: f32,
+    ratio: f32,
+    attack: f32,
+    release: f32,
+    /// Coefficients effectifs, adaptés au sample rate courant
+    attack_coeff: f32,
+    release_coeff: f32,
+    sample_rate: f32,
+    /// Filtre de la sidechain (n'agit que sur la détection)
+    sidechain: EqBand,
+    envelope: f32,
+    /// Le gain reduction actuel (0.0 = rien, 1.0 = silence)
+    gain_reduction: f32,
+    /// Écoute du détecteur (transitoire, jamais sauvée)
+    listen: bool,
+    bypassed: bool,
+}
+
+impl DeEsser {
+    pub fn new() -> Self {
+        let mut de_esser = Self {
+            frequency: 6000.0,
+            q: 2.0,
+            threshold: 0.1,
+            ratio: 4.0,
+            attack: 0.05,   // Rapide : une sifflante dure quelques dizaines de ms
+            release: 0.005, // Relâche plus doucement, sans pomper
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            sample_rate: REFERENCE_SAMPLE_RATE,
+            sidechain: EqBand::new(FilterType::BandPass, 6000.0, 0.0, 2.0),
+            envelope: 0.0,
+            gain_reduction: 0.0,
+            listen: false,
+            bypassed: true, // OFF par defaut
+        };
+        de_esser.update_coefficients();
+        de_esser
+    }
+
+    fn update_coefficients(&mut self) {
+        self.attack_coeff = rescale_coefficient(self.attack, self.sample_rate);
+        self.release_coeff = rescale_coefficient(self.release, self.sample_rate);
+    }
+
+    /// Recalcule le passe-bande. Sous Nyquist quel que soit le sample
+    /// rate : 12 kHz n'existe pas dans un flux à 16 kHz.
+    fn update_sidechain(&mut self) {
+        self.sidechain.frequency = self.frequency.min(self.sample_rate * 0.45);
+        self.sidechain.q = self.q;
+        self.sidechain.compute_coefficients(self.sample_rate);
+    }
+
+    /// Centre de la bande détectée, en Hz.
+    pub fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = frequency.clamp(2000.0, 16_000.0);
+        self.update_sidechain();
+    }
+
+    /// Largeur de la bande : 1.0 = large, 4.0 = étroite.
+    pub fn set_q(&mut self, q: f32) {
+        self.q = q.clamp(0.5, 10.0);
+        self.update_sidechain();
+    }
+
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold.clamp(0.01, 1.0);
+    }
+
+    pub fn set_ratio(&mut self, ratio: f32) {
+        self.ratio = ratio.clamp(1.0, 20.0);
+    }
+
+    pub fn set_attack(&mut self, attack: f32) {
+        self.attack = attack.clamp(0.001, 0.5);
+        self.update_coefficients();
+    }
+
+    pub fn set_release(&mut self, release: f32) {
+        self.release = release.clamp(0.001, 0.5);
+        self.update_coefficients();
+    }
+
+    pub fn frequency(&self) -> f32 {
+        self.frequency
+    }
+
+    pub fn q(&self) -> f32 {
+        self.q
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    pub fn ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Retourne le gain reduction actuel (pour l'UI).
+    /// Valeur entre 0.0 (pas de réduction) et 1.0 (réduction max).
+    pub fn current_gain_reduction(&self) -> f32 {
+        self.gain_reduction
+    }
+}
+
+impl Default for DeEsser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Processor for DeEsser {
+    fn name(&self) -> &'static str {
+        "DeEsser"
+    }
+
+    fn gain_reduction(&self) -> Option<f32> {
+        Some(self.gain_reduction)
+    }
+
+    fn process_sample(&mut self, sample: f32) -> f32 {
+        if self.bypassed {
+            return sample;
+        }
+
+        // 1. Sidechain : seules les sifflantes atteignent le détecteur
+        let detected = self.sidechain.process(sample);
+
+        // 2. Envelope follower sur la bande (comme le compresseur)
+        let abs_detected = detected.abs();
+        let coeff = if abs_detected > self.envelope {
+            self.attack_coeff
+        } else {
+            self.release_coeff
+        };
+        self.envelope += coeff * (abs_detected - self.envelope);
+
+        // 3. Gain du compresseur, calculé sur la bande seule
+        let gain = if self.envelope > self.threshold {
+            let target = self.threshold + (self.envelope - self.threshold) / self.ratio;
+            target / self.envelope.max(0.0001)
+        } else {
+            1.0
+        };
+        self.gain_reduction = 1.0 - gain;
+
+        // Écoute du détecteur : la bande filtrée, pour caler la
+        // fréquence sur les sifflantes de la voix
+        if self.listen {
+            return detected;
+        }
+
+        // 4. Appliqué à tout le signal
+        sample * gain
+    }
+
+    fn reset(&mut self) {
+        self.sidechain.reset();
+        self.envelope = 0.0;
+        self.gain_reduction = 0.0;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(1.0);
+        self.update_coefficients();
+        self.update_sidechain();
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "frequency" => self.set_frequency(value),
+            "q" => self.set_q(value),
+            "threshold" => self.set_threshold(value),
+            "ratio" => self.set_ratio(value),
+            "attack" => self.set_attack(value),
+            "release" => self.set_release(value),
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_detector_listen(&mut self, listen: bool) -> bool {
+        self.listen = listen;
+        true
+    }
+
+    fn set_bypass(&mut self, bypass: bool) {
+        self.bypassed = bypass;
+    }
+
+    fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .map(|n| amplitude * (std::f32::consts::TAU * frequency * n as f32 / 48_000.0).sin())
+            .collect()
+    }
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    /// Rapport de RMS sortie / entrée, une fois l'enveloppe stabilisée.
+    fn steady_gain(de_esser: &mut DeEsser, input: &[f32]) -> f32 {
+        let output: Vec<f32> = input.iter().map(|&s| de_esser.process_sample(s)).collect();
+        let tail = input.len() / 2;
+        rms(&output[tail..]) / rms(&input[tail..])
+    }
+
+    fn slow_de_esser() -> DeEsser {
+        let mut de_esser = DeEsser::new();
+        de_esser.set_bypass(false);
+        // Enveloppe lente : elle se pose sur la moyenne du redressé
+        de_esser.set_attack(0.001);
+        de_esser.set_release(0.001);
+        de_esser
+    }
+
+    #[test]
+    fn sibilance_is_reduced_by_the_expected_amount() {
+        let mut de_esser = slow_de_esser();
+        let input = sine(6000.0, 0.5, 48_000);
+        let gain = steady_gain(&mut de_esser, &input);
+
+        // Au centre de la bande, le passe-bande laisse tout passer :
+        // l'enveloppe vaut la moyenne d'un sinus redressé, 2/π × 0.5
+        let envelope = 0.5 * std::f32::consts::FRAC_2_PI;
+        let expected = (0.1 + (envelope - 0.1) / 4.0) / envelope;
+        assert!((gain - expected).abs() < 0.03, "{gain} vs {expected}");
+        assert!(de_esser.current_gain_reduction() > 0.4);
+    }
+
+    #[test]
+    fn low_voice_at_the_same_level_passes() {
+        let mut de_esser = slow_de_esser();
+        let input = sine(200.0, 0.5, 48_000);
+        let gain = steady_gain(&mut de_esser, &input);
+        assert!(gain > 0.99, "{gain}");
+        assert_eq!(de_esser.current_gain_reduction(), 0.0);
+    }
+
+    #[test]
+    fn de_esser_parameter_clamping() {
+        let mut de_esser = DeEsser::new();
+        de_esser.set_frequency(50.0);
+        de_esser.set_q(100.0);
+        de_esser.set_ratio(0.5);
+        assert_eq!(de_esser.frequency(), 2000.0);
+        assert_eq!(de_esser.q(), 10.0);
+        assert_eq!(de_esser.ratio(), 1.0);
+        assert!(de_esser.set_param("threshold", 0.0));
+        assert_eq!(de_esser.threshold(), 0.01);
+        assert!(!de_esser.set_param("makeup_gain", 1.0));
+    }
+}
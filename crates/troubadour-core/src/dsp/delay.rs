@@ -0,0 +1,245 @@
+use super::Processor;
+
+/// Délai maximum d'un canal, en millisecondes.
+pub const MAX_DELAY_MS: f32 = 1000.0;
+
+/// Délai maximum de la sortie d'un bus, en millisecondes.
+pub const MAX_OUTPUT_DELAY_MS: f32 = 500.0;
+
+/// Durée du crossfade quand le délai change, en millisecondes.
+const CROSSFADE_MS: f32 = 10.0;
+
+/// Ligne de retard — décale un canal dans le temps (lip-sync).
+///
+/// # Pourquoi retarder l'audio ?
+/// Une webcam ajoute souvent 100-150 ms de latence vidéo.
+/// Sans correction, la voix arrive AVANT les lèvres à l'écran.
+/// On retarde donc le micro du même temps.
+///
+/// # Buffer circulaire pré-alloué
+/// Le buffer est dimensionné une fois pour le délai MAX (1 s) au
+/// sample rate du moteur. Changer le délai ne fait que déplacer
+/// la tête de lecture : zéro allocation dans le callback audio.
+///
+/// ```text
+///   buffer : [ . . . . R . . . . . W . . . ]
+///                      ^ lecture    ^ écriture
+///            distance W - R = délai en samples
+/// ```
+///
+/// # Crossfade au changement de délai
+/// Sauter brutalement la tête de lecture crée un clic (discontinuité).
+/// La faire glisser change la hauteur du son (effet "bande magnétique").
+/// On lit donc les DEUX positions (ancienne et nouvelle) pendant 10 ms
+/// et on fond de l'une à l'autre.
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    write_pos: usize,
+    sample_rate: f32,
+    delay_ms: f32,
+    delay_samples: usize,
+    /// Délai avant le dernier changement (lu pendant le crossfade)
+    old_delay_samples: usize,
+    fade_pos: usize,
+    fade_len: usize,
+    bypassed: bool,
+}
+
+impl DelayLine {
+    /// Crée une ligne de retard pour un sample rate donné (délai initial : 0).
+    pub fn new(sample_rate: f32) -> Self {
+        Self::with_delay_ms(sample_rate, 0.0)
+    }
+
+    /// Crée une ligne déjà réglée sur `delay_ms`, sans le fondu d'un
+    /// changement de délai.
+    pub fn with_delay_ms(sample_rate: f32, delay_ms: f32) -> Self {
+        let mut delay = Self {
+            buffer: Vec::new(),
+            write_pos: 0,
+            sample_rate: 48_000.0,
+            delay_ms: delay_ms.clamp(0.0, MAX_DELAY_MS),
+            delay_samples: 0,
+            old_delay_samples: 0,
+            fade_pos: 0,
+            fade_len: 0,
+            bypassed: false,
+        };
+        delay.set_sample_rate(sample_rate);
+        delay
+    }
+
+    /// Change le délai (0 → 1000 ms), avec crossfade.
+    pub fn set_delay_ms(&mut self, delay_ms: f32) {
+        self.delay_ms = delay_ms.clamp(0.0, MAX_DELAY_MS);
+        let samples = self.ms_to_samples(self.delay_ms);
+        if samples == self.delay_samples {
+            return;
+        }
+        // Si un crossfade est en cours, on repart de la position cible
+        // précédente : un léger saut de fondu, jamais un clic.
+        self.old_delay_samples = self.delay_samples;
+        self.delay_samples = samples;
+        self.fade_pos = 0;
+    }
+
+    pub fn delay_ms(&self) -> f32 {
+        self.delay_ms
+    }
+
+    /// Délai actuel en samples.
+    pub fn delay_samples(&self) -> usize {
+        self.delay_samples
+    }
+
+    fn ms_to_samples(&self, ms: f32) -> usize {
+        (ms * self.sample_rate / 1000.0).round() as usize
+    }
+
+    fn read(&self, delay: usize) -> f32 {
+        let len = self.buffer.len();
+        self.buffer[(self.write_pos + len - delay) % len]
+    }
+}
+
+impl Processor for DelayLine {
+    fn name(&self) -> &'static str {
+        "DelayLine"
+    }
+
+    fn latency_samples(&self) -> u32 {
+        self.delay_samples as u32
+    }
+
+    fn process_sample(&mut self, sample: f32) -> f32 {
+        if self.bypassed {
+            return sample;
+        }
+
+        // Écrire d'abord : un délai de 0 relit le sample courant
+        self.buffer[self.write_pos] = sample;
+
+        let current = self.read(self.delay_samples);
+        let out = if self.fade_pos < self.fade_len {
+            let t = self.fade_pos as f32 / self.fade_len as f32;
+            self.fade_pos += 1;
+            self.read(self.old_delay_samples) * (1.0 - t) + current * t
+        } else {
+            current
+        };
+
+        self.write_pos = (self.write_pos + 1) % self.buffer.len();
+        out
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.write_pos = 0;
+        self.fade_pos = self.fade_len;
+    }
+
+    /// Redimensionne le buffer pour un nouveau sample rate.
+    ///
+    /// Alloue : à appeler hors du callback audio (à la reconstruction
+    /// du pipeline). Le contenu est perdu, le délai en ms est conservé.
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(1.0);
+        let len = self.ms_to_samples(MAX_DELAY_MS) + 1;
+        self.buffer = vec![0.0; len];
+        self.write_pos = 0;
+        self.delay_samples = self.ms_to_samples(self.delay_ms);
+        self.old_delay_samples = self.delay_samples;
+        self.fade_len = self.ms_to_samples(CROSSFADE_MS).max(1);
+        self.fade_pos = self.fade_len;
+    }
+
+    fn set_bypass(&mut self, bypass: bool) {
+        self.bypassed = bypass;
+    }
+
+    fn is_bypassed(&self) -> bool {
+        self.bypassed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impulse_delayed_by_10ms_at_48k() {
+        let mut delay = DelayLine::new(48_000.0);
+        delay.set_delay_ms(10.0);
+        // Laisser le crossfade initial se terminer sur du silence
+        for _ in 0..1000 {
+            delay.process_sample(0.0);
+        }
+
+        let output: Vec<f32> = (0..1000)
+            .map(|i| delay.process_sample(if i == 0 { 1.0 } else { 0.0 }))
+            .collect();
+
+        let peak_index = output
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i)
+            .unwrap();
+        assert!(
+            peak_index.abs_diff(480) <= 1,
+            "Impulse should emerge at 480 samples, got {peak_index}"
+        );
+        assert!((output[peak_index] - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn zero_delay_is_passthrough() {
+        let mut delay = DelayLine::new(48_000.0);
+        assert_eq!(delay.process_sample(0.42), 0.42);
+        assert_eq!(delay.process_sample(-0.1), -0.1);
+    }
+
+    #[test]
+    fn delay_is_clamped() {
+        let mut delay = DelayLine::new(48_000.0);
+        delay.set_delay_ms(5000.0);
+        assert_eq!(delay.delay_ms(), MAX_DELAY_MS);
+        assert_eq!(delay.delay_samples(), 48_000);
+        // Le délai max tient dans le buffer sans paniquer
+        for _ in 0..100 {
+            delay.process_sample(0.5);
+        }
+    }
+
+    #[test]
+    fn delay_change_is_click_free() {
+        let mut delay = DelayLine::new(48_000.0);
+        let sine =
+            |i: usize| 0.5 * (2.0 * std::f32::consts::PI * 100.0 * i as f32 / 48_000.0).sin();
+
+        let mut prev = 0.0;
+        let mut max_step = 0.0_f32;
+        for i in 0..4800 {
+            // Changement de délai en plein signal : 0 → 2.5 ms (quart de période)
+            if i == 2400 {
+                delay.set_delay_ms(2.5);
+            }
+            let out = delay.process_sample(sine(i));
+            if i > 0 {
+                max_step = max_step.max((out - prev).abs());
+            }
+            prev = out;
+        }
+        // Sans crossfade, le saut de phase créerait une marche de ~0.35
+        assert!(max_step < 0.02, "Discontinuity detected: {max_step}");
+    }
+
+    #[test]
+    fn sample_rate_change_keeps_delay_ms() {
+        let mut delay = DelayLine::new(48_000.0);
+        delay.set_delay_ms(10.0);
+        delay.set_sample_rate(96_000.0);
+        assert_eq!(delay.delay_ms(), 10.0);
+        assert_eq!(delay.delay_samples(), 960);
+    }
+}
@@ -0,0 +1,153 @@
+use troubadour_shared::audio::DitherMode;
+
+/// Pleine échelle d'un sample 16 bits.
+const I16_SCALE: f32 = 32767.0;
+
+/// Conversion f32 → i16 avec dither, pour les devices de sortie 16 bits.
+///
+/// # TPDF
+/// Le bruit est la somme de deux bruits uniformes de ±0.5 LSB : sa
+/// densité est triangulaire (Triangular Probability Density Function).
+/// C'est le plus petit bruit qui rend l'erreur d'arrondi indépendante
+/// du signal (ni distorsion, ni "pompage" du souffle). Moyenne nulle :
+/// pas d'offset DC.
+///
+/// # Mise en forme (noise shaping)
+/// L'erreur d'arrondi du sample précédent est soustraite du suivant :
+/// `q[n] = x[n] + e[n] - e[n-1]`. L'erreur passe dans un passe-haut du
+/// premier ordre : moins de souffle dans les médiums, là où l'oreille
+/// est la plus sensible, un peu plus dans l'extrême aigu.
+pub struct Ditherer {
+    mode: DitherMode,
+    /// Générateur xorshift32 : déterministe (graine fixée pour les tests),
+    /// sans allocation ni lock, utilisable dans le callback audio.
+    rng: u32,
+    /// Erreur d'arrondi précédente, par canal (mise en forme)
+    errors: Vec<f32>,
+}
+
+impl Ditherer {
+    /// `channels` : nombre de canaux entrelacés du device. `seed` ne
+    /// doit pas être nul (remplacé par une constante sinon).
+    pub fn new(mode: DitherMode, channels: usize, seed: u32) -> Self {
+        Self {
+            mode,
+            rng: if seed == 0 { 0x9E37_79B9 } else { seed },
+            errors: vec![0.0; channels.max(1)],
+        }
+    }
+
+    pub fn mode(&self) -> DitherMode {
+        self.mode
+    }
+
+    /// Bruit uniforme dans [-0.5, 0.5] LSB.
+    fn uniform(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng as f32 / u32::MAX as f32 - 0.5
+    }
+
+    /// Convertit un bloc entrelacé. `input` et `output` ont la même longueur.
+    pub fn convert_i16(&mut self, input: &[f32], output: &mut [i16]) {
+        let channels = self.errors.len();
+        for (index, (sample, out)) in input.iter().zip(output.iter_mut()).enumerate() {
+            let scaled = sample.clamp(-1.0, 1.0) * I16_SCALE;
+            *out = match self.mode {
+                DitherMode::Off => scaled.round() as i16,
+                DitherMode::Tpdf => {
+                    let noise = self.uniform() + self.uniform();
+                    (scaled + noise).round().clamp(-32768.0, I16_SCALE) as i16
+                }
+                DitherMode::TpdfShaped => {
+                    let slot = index % channels;
+                    let shaped = scaled - self.errors[slot];
+                    let noise = self.uniform() + self.uniform();
+                    let quantized = (shaped + noise).round().clamp(-32768.0, I16_SCALE);
+                    // Borné : un clipping ne doit pas s'accumuler dans la boucle
+                    self.errors[slot] = (quantized - shaped).clamp(-2.0, 2.0);
+                    quantized as i16
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+
+    use super::*;
+
+    const SR: usize = 48_000;
+    const FREQ: usize = 1_000;
+
+    /// Sinus à -80 dBFS, nombre entier de périodes sur 1 s.
+    fn quiet_sine() -> Vec<f32> {
+        let amplitude = 10.0_f32.powf(-80.0 / 20.0);
+        (0..SR)
+            .map(|n| amplitude * (TAU * FREQ as f32 * n as f32 / SR as f32).sin())
+            .collect()
+    }
+
+    fn convert(mode: DitherMode, input: &[f32]) -> Vec<f32> {
+        let mut output = vec![0_i16; input.len()];
+        Ditherer::new(mode, 1, 1234).convert_i16(input, &mut output);
+        output.iter().map(|&s| s as f32 / I16_SCALE).collect()
+    }
+
+    /// Puissance du signal à une fréquence exacte (un bin de la DFT).
+    fn bin_power(signal: &[f32], freq: usize) -> f64 {
+        let (mut re, mut im) = (0.0_f64, 0.0_f64);
+        for (n, &x) in signal.iter().enumerate() {
+            let phase = std::f64::consts::TAU * (freq * n % SR) as f64 / SR as f64;
+            re += x as f64 * phase.cos();
+            im -= x as f64 * phase.sin();
+        }
+        re * re + im * im
+    }
+
+    /// Distorsion harmonique : harmoniques 2 à 10 / fondamentale.
+    fn thd(signal: &[f32]) -> f64 {
+        let harmonics: f64 = (2..=10).map(|h| bin_power(signal, FREQ * h)).sum();
+        harmonics / bin_power(signal, FREQ)
+    }
+
+    #[test]
+    fn dither_lowers_harmonic_distortion_of_quiet_sine() {
+        let sine = quiet_sine();
+        let truncated = thd(&convert(DitherMode::Off, &sine));
+        let dithered = thd(&convert(DitherMode::Tpdf, &sine));
+        let shaped = thd(&convert(DitherMode::TpdfShaped, &sine));
+
+        // Arrondi simple : harmoniques nettement audibles
+        assert!(truncated > 1e-3, "truncated THD {truncated}");
+        assert!(dithered < truncated / 100.0, "{dithered} vs {truncated}");
+        assert!(shaped < truncated / 10.0, "{shaped} vs {truncated}");
+    }
+
+    #[test]
+    fn dither_adds_no_dc_offset() {
+        let silence = vec![0.0; SR];
+        for mode in [DitherMode::Tpdf, DitherMode::TpdfShaped] {
+            let output = convert(mode, &silence);
+            let mean_lsb = output.iter().sum::<f32>() / output.len() as f32 * I16_SCALE;
+            assert!(mean_lsb.abs() < 0.02, "{mode:?}: DC {mean_lsb} LSB");
+            // Jamais plus d'un LSB (TPDF) ou deux (mise en forme) de souffle
+            assert!(output.iter().all(|s| (s * I16_SCALE).abs() <= 2.0));
+        }
+    }
+
+    #[test]
+    fn same_seed_same_output() {
+        let sine = quiet_sine();
+        assert_eq!(
+            convert(DitherMode::Tpdf, &sine),
+            convert(DitherMode::Tpdf, &sine)
+        );
+        let mut output = [0_i16; 4];
+        Ditherer::new(DitherMode::Off, 2, 1).convert_i16(&[1.5, -1.5, 0.5, 0.0], &mut output);
+        assert_eq!(output, [32767, -32767, 16384, 0]);
+    }
+}
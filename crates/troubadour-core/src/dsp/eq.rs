@@ -1,4 +1,5 @@
-use super::Processor;
+use super::{Processor, REFERENCE_SAMPLE_RATE};
+use troubadour_shared::dsp::parse_band_param;
 
 /// Type de filtre EQ.
 ///
@@ -9,11 +10,15 @@ use super::Processor;
 ///   (ex: -5dB à 3kHz = réduit la zone nasale de la voix)
 /// - **HighShelf** : booste/coupe les fréquences AU-DESSUS d'une fréquence
 ///   (ex: +2dB au-dessus de 8kHz = plus d'air/brillance)
+///
+/// Et un passe-bande, hors de l'EQ : il ne garde QUE la bande autour de
+/// la fréquence (gain ignoré). Sert de détecteur au de-esser.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FilterType {
     LowShelf,
     Peaking,
     HighShelf,
+    BandPass,
 }
 
 /// Une bande d'EQ paramétrique.
@@ -75,7 +80,7 @@ impl EqBand {
             y2: 0.0,
             enabled: true,
         };
-        band.compute_coefficients(48000.0);
+        band.compute_coefficients(REFERENCE_SAMPLE_RATE);
         band
     }
 
@@ -88,6 +93,12 @@ impl EqBand {
     /// Les formules dépendent du type de filtre mais partagent des
     /// variables intermédiaires : omega, sin, cos, alpha, A.
     pub fn compute_coefficients(&mut self, sample_rate: f32) {
+        [self.b0, self.b1, self.b2, self.a1, self.a2] = self.coefficients(sample_rate);
+    }
+
+    /// Coefficients `[b0, b1, b2, a1, a2]` normalisés par a0, pour
+    /// `sample_rate` (voir `compute_coefficients`).
+    fn coefficients(&self, sample_rate: f32) -> [f32; 5] {
         let a = 10.0_f32.powf(self.gain_db / 40.0); // Amplitude from dB
         let omega = 2.0 * std::f32::consts::PI * self.frequency / sample_rate;
         let sin_w = omega.sin();
@@ -124,14 +135,41 @@ impl EqBand {
                 let a2 = (a + 1.0) - (a - 1.0) * cos_w - two_sqrt_a_alpha;
                 (b0, b1, b2, a0, a1, a2)
             }
+            // Gain de 0 dB au centre, quel que soit Q
+            FilterType::BandPass => (alpha, 0.0, -alpha, 1.0 + alpha, -2.0 * cos_w, 1.0 - alpha),
         };
 
         // Normaliser par a0
-        self.b0 = b0 / a0;
-        self.b1 = b1 / a0;
-        self.b2 = b2 / a0;
-        self.a1 = a1 / a0;
-        self.a2 = a2 / a0;
+        [b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0]
+    }
+
+    /// Gain de la bande (dB) à chaque fréquence, lu sur la fonction de
+    /// transfert au lieu de traiter du signal :
+    ///
+    /// H(z) = (b0 + b1·z⁻¹ + b2·z⁻²) / (1 + a1·z⁻¹ + a2·z⁻²), z = e^(jω)
+    ///
+    /// Une bande désactivée vaut 0 dB partout.
+    pub fn frequency_response(&self, frequencies: &[f32], sample_rate: f32) -> Vec<f32> {
+        if !self.enabled {
+            return vec![0.0; frequencies.len()];
+        }
+        let [b0, b1, b2, a1, a2] = self.coefficients(sample_rate).map(f64::from);
+        frequencies
+            .iter()
+            .map(|&frequency| {
+                let omega = std::f64::consts::TAU * f64::from(frequency) / f64::from(sample_rate);
+                // z⁻¹ = cos ω - j sin ω, z⁻² = cos 2ω - j sin 2ω
+                let (c1, s1, c2, s2) = (
+                    omega.cos(),
+                    omega.sin(),
+                    (2.0 * omega).cos(),
+                    (2.0 * omega).sin(),
+                );
+                let num = (b0 + b1 * c1 + b2 * c2).hypot(b1 * s1 + b2 * s2);
+                let den = (1.0 + a1 * c1 + a2 * c2).hypot(a1 * s1 + a2 * s2);
+                (20.0 * (num / den).log10()) as f32
+            })
+            .collect()
     }
 
     /// Traite un sample avec le filtre biquad.
@@ -178,6 +216,8 @@ impl EqBand {
 /// - Bande 5 : High Shelf (aigus)
 pub struct ParametricEq {
     bands: Vec<EqBand>,
+    /// Sample rate des coefficients actuels des bandes
+    sample_rate: f32,
     bypassed: bool,
 }
 
@@ -185,6 +225,7 @@ impl ParametricEq {
     pub fn new() -> Self {
         Self {
             bands: Vec::new(),
+            sample_rate: REFERENCE_SAMPLE_RATE,
             bypassed: false,
         }
     }
@@ -197,6 +238,7 @@ impl ParametricEq {
                 EqBand::new(FilterType::Peaking, 1000.0, 0.0, 1.0),
                 EqBand::new(FilterType::HighShelf, 8000.0, 0.0, 0.7),
             ],
+            sample_rate: REFERENCE_SAMPLE_RATE,
             bypassed: false,
         }
     }
@@ -233,6 +275,33 @@ impl ParametricEq {
         }
     }
 
+    /// Courbe de l'EQ sur `n_points` fréquences (voir `log_frequencies`),
+    /// au sample rate de ses coefficients : la somme des bandes actives,
+    /// 0 dB partout si l'EQ est bypassé.
+    pub fn response_curve(&self, n_points: usize) -> (Vec<f32>, Vec<f32>) {
+        let frequencies = log_frequencies(n_points);
+        let gains = self.gains_db(&frequencies, self.sample_rate);
+        (frequencies, gains)
+    }
+
+    /// Gain de l'EQ (dB) à chaque fréquence : les bandes sont en série,
+    /// leurs gains en dB s'additionnent.
+    fn gains_db(&self, frequencies: &[f32], sample_rate: f32) -> Vec<f32> {
+        let mut gains = vec![0.0; frequencies.len()];
+        if self.bypassed {
+            return gains;
+        }
+        for band in &self.bands {
+            for (gain, band_gain) in gains
+                .iter_mut()
+                .zip(band.frequency_response(frequencies, sample_rate))
+            {
+                *gain += band_gain;
+            }
+        }
+        gains
+    }
+
     /// Réinitialise toutes les bandes.
     pub fn reset_all(&mut self) {
         for band in &mut self.bands {
@@ -248,6 +317,10 @@ impl Default for ParametricEq {
 }
 
 impl Processor for ParametricEq {
+    fn name(&self) -> &'static str {
+        "ParametricEq"
+    }
+
     fn process_sample(&mut self, sample: f32) -> f32 {
         if self.bypassed {
             return sample;
@@ -264,6 +337,35 @@ impl Processor for ParametricEq {
         self.reset_all();
     }
 
+    /// Recalcule les biquads : sans ça, un low-shelf réglé à 200 Hz
+    /// pour 48 kHz tomberait à 100 Hz une fois joué à 96 kHz.
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(1.0);
+        for band in &mut self.bands {
+            band.compute_coefficients(self.sample_rate);
+        }
+    }
+
+    /// `band<N>_frequency`, `band<N>_gain_db` ou `band<N>_q` : seuls
+    /// les coefficients de la bande visée sont recalculés.
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        let Some((index, field)) = parse_band_param(name) else {
+            return false;
+        };
+        let Some(band) = self.bands.get(index) else {
+            return false;
+        };
+        let (mut frequency, mut gain_db, mut q) = (band.frequency, band.gain_db, band.q);
+        match field {
+            "frequency" => frequency = value,
+            "gain_db" => gain_db = value,
+            "q" => q = value,
+            _ => return false,
+        }
+        self.set_band(index, frequency, gain_db, q, self.sample_rate);
+        true
+    }
+
     fn set_bypass(&mut self, bypass: bool) {
         self.bypassed = bypass;
     }
@@ -271,6 +373,20 @@ impl Processor for ParametricEq {
     fn is_bypassed(&self) -> bool {
         self.bypassed
     }
+
+    fn frequency_response(&self, frequencies: &[f32], sample_rate: f32) -> Option<Vec<f32>> {
+        Some(self.gains_db(frequencies, sample_rate))
+    }
+}
+
+/// `n_points` fréquences (au moins 2) espacées en log de 20 Hz à 20 kHz :
+/// autant de points par octave, comme l'axe d'une courbe d'EQ.
+pub fn log_frequencies(n_points: usize) -> Vec<f32> {
+    let n_points = n_points.max(2);
+    let (low, high) = (20.0_f32.ln(), 20_000.0_f32.ln());
+    (0..n_points)
+        .map(|i| (low + (high - low) * i as f32 / (n_points - 1) as f32).exp())
+        .collect()
 }
 
 #[cfg(test)]
@@ -382,4 +498,67 @@ mod tests {
         assert_eq!(band.x1, 0.0);
         assert_eq!(band.y1, 0.0);
     }
+
+    /// Gain mesuré (dB) d'une sinusoïde à `freq` Hz jouée à `sample_rate`.
+    fn measured_gain_db(eq: &mut ParametricEq, freq: f32, sample_rate: f32) -> f32 {
+        eq.reset();
+        let len = sample_rate as usize; // 1 s
+        let mut peak = 0.0_f32;
+        for i in 0..len {
+            let x = 0.5 * (2.0 * std::f32::consts::PI * freq * i as f32 / sample_rate).sin();
+            let y = eq.process_sample(x);
+            // On ignore la première moitié (régime transitoire du filtre)
+            if i > len / 2 {
+                peak = peak.max(y.abs());
+            }
+        }
+        20.0 * (peak / 0.5).log10()
+    }
+
+    #[test]
+    fn low_shelf_corner_survives_sample_rate_change() {
+        // Low-shelf +12 dB à 200 Hz : au coin, le gain vaut la moitié (+6 dB)
+        let mut eq = ParametricEq::default_3band();
+        eq.set_band(0, 200.0, 12.0, 0.7, 48_000.0);
+        let at_48k = measured_gain_db(&mut eq, 200.0, 48_000.0);
+        assert!((at_48k - 6.0).abs() < 0.5, "48 kHz: {at_48k} dB");
+
+        eq.set_sample_rate(96_000.0);
+        let at_96k = measured_gain_db(&mut eq, 200.0, 96_000.0);
+        assert!((at_96k - 6.0).abs() < 0.5, "96 kHz: {at_96k} dB");
+
+        // Les réglages n'ont pas bougé
+        let band = eq.band(0).unwrap();
+        assert_eq!(band.frequency, 200.0);
+        assert_eq!(band.gain_db, 12.0);
+    }
+
+    #[test]
+    fn low_shelf_response_matches_the_analog_prototype() {
+        // Low-shelf +6 dB à 200 Hz : ~+6 dB bien en dessous, +3 dB au
+        // coin, ~0 dB une décade au-dessus
+        let band = EqBand::new(FilterType::LowShelf, 200.0, 6.0, 0.707);
+        let response = band.frequency_response(&[50.0, 200.0, 2000.0], 48_000.0);
+        for (gain, expected) in response.iter().zip([5.97, 3.0, 0.0]) {
+            assert!((gain - expected).abs() < 0.5, "{response:?}");
+        }
+
+        // Même résultat que le signal traité, bande désactivée à plat
+        let mut eq = ParametricEq::default_3band();
+        eq.set_band(1, 1000.0, -8.0, 2.0, 48_000.0);
+        let (frequencies, gains) = eq.response_curve(64);
+        assert_eq!(
+            (frequencies.len(), frequencies[0], gains.len()),
+            (64, 20.0, 64)
+        );
+        assert!((frequencies[63] - 20_000.0).abs() < 1.0);
+        let predicted = eq.gains_db(&[1000.0], 48_000.0)[0];
+        let measured = measured_gain_db(&mut eq, 1000.0, 48_000.0);
+        assert!(
+            (predicted - measured).abs() < 0.2,
+            "{predicted} vs {measured}"
+        );
+        eq.band_mut(1).unwrap().enabled = false;
+        assert!(eq.gains_db(&[1000.0], 48_000.0)[0].abs() < 1e-3);
+    }
 }
@@ -1,4 +1,4 @@
-use super::Processor;
+use super::{Processor, REFERENCE_SAMPLE_RATE, rescale_coefficient};
 
 /// Limiter — empêche le signal de dépasser un plafond.
 ///
@@ -21,6 +21,9 @@ pub struct Limiter {
     /// 0.95 par défaut (un peu de marge avant le vrai 1.0)
     ceiling: f32,
     release: f32,
+    /// Coefficient de release effectif, adapté au sample rate courant
+    release_coeff: f32,
+    sample_rate: f32,
     /// Le gain appliqué (descend quand le signal approche le ceiling)
     gain: f32,
     bypassed: bool,
@@ -31,6 +34,8 @@ impl Limiter {
         Self {
             ceiling: 0.95,
             release: 0.01,
+            release_coeff: 0.01,
+            sample_rate: REFERENCE_SAMPLE_RATE,
             gain: 1.0,
             bypassed: false,
         }
@@ -43,6 +48,7 @@ impl Limiter {
 
     pub fn set_release(&mut self, release: f32) {
         self.release = release.clamp(0.001, 0.5);
+        self.release_coeff = rescale_coefficient(self.release, self.sample_rate);
     }
 
     pub fn ceiling(&self) -> f32 {
@@ -67,6 +73,10 @@ impl Default for Limiter {
 }
 
 impl Processor for Limiter {
+    fn name(&self) -> &'static str {
+        "Limiter"
+    }
+
     fn process_sample(&mut self, sample: f32) -> f32 {
         if self.bypassed {
             return sample;
@@ -82,7 +92,7 @@ impl Processor for Limiter {
         } else {
             // Le signal est sous le ceiling → relâcher le gain doucement
             // vers 1.0 (pas de limiting).
-            self.gain += self.release * (1.0 - self.gain);
+            self.gain += self.release_coeff * (1.0 - self.gain);
         }
 
         sample * self.gain
@@ -92,6 +102,20 @@ impl Processor for Limiter {
         self.gain = 1.0;
     }
 
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(1.0);
+        self.release_coeff = rescale_coefficient(self.release, self.sample_rate);
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "ceiling" => self.set_ceiling(value),
+            "release" => self.set_release(value),
+            _ => return false,
+        }
+        true
+    }
+
     fn set_bypass(&mut self, bypass: bool) {
         self.bypassed = bypass;
     }
@@ -0,0 +1,422 @@
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::mixer::Loudness;
+
+/// Durée d'un sous-bloc de mesure (ms). Les fenêtres momentanée (400 ms)
+/// et court terme (3 s) sont des multiples : on les glisse par 100 ms,
+/// soit le recouvrement de 75 % demandé par la norme.
+const SUB_BLOCK_MS: u32 = 100;
+/// Fenêtre momentanée : 4 sous-blocs = 400 ms.
+const MOMENTARY_BLOCKS: usize = 4;
+/// Fenêtre court terme : 30 sous-blocs = 3 s.
+const SHORT_TERM_BLOCKS: usize = 30;
+
+/// Porte absolue : les blocs sous -70 LUFS (silence) ne comptent pas.
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Porte relative : 10 LU sous la moyenne des blocs retenus.
+const RELATIVE_GATE_LU: f64 = -10.0;
+/// Résolution de l'histogramme de l'intégrée (LU).
+const HISTOGRAM_STEP: f64 = 0.1;
+/// -70 → +10 LUFS par pas de 0.1 LU.
+const HISTOGRAM_BINS: usize = 800;
+
+/// Filtre biquad en f64 (forme directe I).
+///
+/// # Pourquoi f64 ici, alors que l'EQ est en f32 ?
+/// Le filtre passe-haut RLB coupe à 38 Hz : à 48 kHz ses pôles sont
+/// tout près du cercle unité, et l'arrondi f32 décale la réponse dans
+/// les graves. Pour une mesure (pas un effet), on paie le f64.
+#[derive(Debug, Clone, Copy, Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    x1: f64,
+    x2: f64,
+    y1: f64,
+    y2: f64,
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.b1 * self.x1 + self.b2 * self.x2
+            - self.a1 * self.y1
+            - self.a2 * self.y2;
+        self.x2 = self.x1;
+        self.x1 = x;
+        self.y2 = self.y1;
+        self.y1 = y;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.x1 = 0.0;
+        self.x2 = 0.0;
+        self.y1 = 0.0;
+        self.y2 = 0.0;
+    }
+}
+
+/// Pondération K (ITU-R BS.1770) : les deux biquads, calculés pour
+/// `sample_rate`.
+///
+/// # Pourquoi recalculer ?
+/// La norme ne donne les coefficients qu'à 48 kHz. On part donc des
+/// filtres analogiques qu'ils discrétisent (shelf +4 dB vers 1.7 kHz
+/// pour la tête, passe-haut "RLB" à 38 Hz) et on applique la
+/// transformée bilinéaire au rate voulu : à 48 kHz, on retrouve les
+/// valeurs du tableau de la norme.
+fn k_weighting(sample_rate: f64) -> [Biquad; 2] {
+    // Étage 1 : shelf haut qui modélise l'effet acoustique de la tête
+    let f0 = 1_681.974_450_955_533;
+    let gain_db = 3.999_843_853_973_347;
+    let q = 0.707_175_236_955_419_6;
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b0: (vh + vb * k / q + k * k) / a0,
+        b1: 2.0 * (k * k - vh) / a0,
+        b2: (vh - vb * k / q + k * k) / a0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        ..Biquad::default()
+    };
+
+    // Étage 2 : passe-haut RLB (les graves pèsent peu dans la sonie)
+    let f0 = 38.135_470_876_024_44;
+    let q = 0.500_327_037_323_877_3;
+    let k = (std::f64::consts::PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b0: 1.0,
+        b1: -2.0,
+        b2: 1.0,
+        a1: 2.0 * (k * k - 1.0) / a0,
+        a2: (1.0 - k / q + k * k) / a0,
+        ..Biquad::default()
+    };
+
+    [shelf, high_pass]
+}
+
+/// Puissance pondérée → LUFS (`None` pour un silence numérique).
+fn to_lufs(power: f64) -> Option<f32> {
+    (power > 0.0).then(|| (-0.691 + 10.0 * power.log10()) as f32)
+}
+
+fn to_power(lufs: f64) -> f64 {
+    10f64.powf((lufs + 0.691) / 10.0)
+}
+
+/// Mesure de sonie EBU R128 d'un signal stéréo.
+///
+/// # LUFS vs peak
+/// Le peak dit si le signal sature, pas s'il est FORT : une voix
+/// compressée et un piano aux mêmes crêtes ne sonnent pas pareil. Les
+/// plateformes de streaming normalisent la sonie (~-14 LUFS) ; la
+/// mesurer évite qu'elles baissent un stream trop fort.
+///
+/// - **Momentanée** : 400 ms, la "réactivité" d'un VU-meter
+/// - **Court terme** : 3 s, ce qu'on regarde pendant un stream
+/// - **Intégrée** : toute la durée, avec deux portes (absolue à
+///   -70 LUFS, puis relative à -10 LU) pour que les silences et les
+///   passages très calmes ne tirent pas la moyenne vers le bas
+///
+/// # Temps réel
+/// Tout est alloué à la construction : l'intégrée garde un histogramme
+/// (pas de 0.1 LU) des blocs de 400 ms au lieu de leur liste, sa
+/// mémoire ne grandit pas avec la durée du stream.
+pub struct LoudnessMeter {
+    /// [canal][étage]
+    filters: [[Biquad; 2]; 2],
+    sub_block_frames: usize,
+    sub_block_pos: usize,
+    sub_block_sum: f64,
+    /// Puissance des derniers sous-blocs (ring)
+    ring: [f64; SHORT_TERM_BLOCKS],
+    ring_pos: usize,
+    ring_filled: usize,
+    histogram: Box<[u32; HISTOGRAM_BINS]>,
+    loudness: Loudness,
+}
+
+impl LoudnessMeter {
+    pub fn new(sample_rate: f32) -> Self {
+        let weighting = k_weighting(f64::from(sample_rate));
+        Self {
+            filters: [weighting; 2],
+            sub_block_frames: ((sample_rate as u32 * SUB_BLOCK_MS / 1000) as usize).max(1),
+            sub_block_pos: 0,
+            sub_block_sum: 0.0,
+            ring: [0.0; SHORT_TERM_BLOCKS],
+            ring_pos: 0,
+            ring_filled: 0,
+            histogram: Box::new([0; HISTOGRAM_BINS]),
+            loudness: Loudness::default(),
+        }
+    }
+
+    /// Mesure un bloc stéréo entrelacé.
+    pub fn process(&mut self, stereo: &[f32]) {
+        for frame in stereo.chunks_exact(2) {
+            let mut power = 0.0;
+            for (filters, &sample) in self.filters.iter_mut().zip(frame) {
+                let [shelf, high_pass] = filters;
+                let weighted = high_pass.process(shelf.process(f64::from(sample)));
+                power += weighted * weighted;
+            }
+            self.sub_block_sum += power;
+            self.sub_block_pos += 1;
+            if self.sub_block_pos == self.sub_block_frames {
+                self.finish_sub_block();
+            }
+        }
+    }
+
+    fn finish_sub_block(&mut self) {
+        self.ring[self.ring_pos] = self.sub_block_sum / self.sub_block_frames as f64;
+        self.ring_pos = (self.ring_pos + 1) % SHORT_TERM_BLOCKS;
+        self.ring_filled = (self.ring_filled + 1).min(SHORT_TERM_BLOCKS);
+        self.sub_block_pos = 0;
+        self.sub_block_sum = 0.0;
+
+        if self.ring_filled >= MOMENTARY_BLOCKS {
+            let momentary = self.window_power(MOMENTARY_BLOCKS);
+            self.loudness.momentary = to_lufs(momentary);
+            // Chaque fenêtre de 400 ms est un bloc de l'intégrée
+            if let Some(lufs) = self.loudness.momentary
+                && f64::from(lufs) > ABSOLUTE_GATE_LUFS
+            {
+                let bin = ((f64::from(lufs) - ABSOLUTE_GATE_LUFS) / HISTOGRAM_STEP) as usize;
+                self.histogram[bin.min(HISTOGRAM_BINS - 1)] += 1;
+                self.loudness.integrated = self.integrated();
+            }
+        }
+        if self.ring_filled == SHORT_TERM_BLOCKS {
+            self.loudness.short_term = to_lufs(self.window_power(SHORT_TERM_BLOCKS));
+        }
+    }
+
+    /// Puissance moyenne des `blocks` derniers sous-blocs.
+    fn window_power(&self, blocks: usize) -> f64 {
+        let sum: f64 = (1..=blocks)
+            .map(|back| self.ring[(self.ring_pos + SHORT_TERM_BLOCKS - back) % SHORT_TERM_BLOCKS])
+            .sum();
+        sum / blocks as f64
+    }
+
+    /// Intégrée : moyenne des blocs au-dessus des deux portes.
+    fn integrated(&self) -> Option<f32> {
+        let bin_power =
+            |bin: usize| to_power(ABSOLUTE_GATE_LUFS + (bin as f64 + 0.5) * HISTOGRAM_STEP);
+        let gated_mean = |from: usize| {
+            let (count, power) = self.histogram[from..].iter().enumerate().fold(
+                (0_u64, 0.0),
+                |(count, power), (i, &n)| {
+                    (
+                        count + u64::from(n),
+                        power + f64::from(n) * bin_power(from + i),
+                    )
+                },
+            );
+            (count > 0).then(|| power / count as f64)
+        };
+
+        // Porte absolue : déjà appliquée en remplissant l'histogramme
+        let ungated = gated_mean(0)?;
+        let relative_gate = to_lufs(ungated)? as f64 + RELATIVE_GATE_LU;
+        let first_bin = ((relative_gate - ABSOLUTE_GATE_LUFS) / HISTOGRAM_STEP)
+            .ceil()
+            .max(0.0) as usize;
+        gated_mean(first_bin.min(HISTOGRAM_BINS - 1)).and_then(to_lufs)
+    }
+
+    /// Valeurs actuelles (`None` tant qu'une fenêtre n'est pas pleine).
+    pub fn loudness(&self) -> Loudness {
+        self.loudness
+    }
+
+    /// Repart de zéro (l'intégrée recommence).
+    pub fn reset(&mut self) {
+        for filter in self.filters.iter_mut().flatten() {
+            filter.reset();
+        }
+        self.sub_block_pos = 0;
+        self.sub_block_sum = 0.0;
+        self.ring_filled = 0;
+        self.histogram.fill(0);
+        self.loudness = Loudness::default();
+    }
+}
+
+/// Dernière mesure publiée par le callback audio pour le thread de
+/// contrôle (atomiques : pas de lock côté audio). Un seul bus est joué
+/// par le pipeline, donc mesuré à la fois.
+#[derive(Debug)]
+pub struct SharedLoudness {
+    /// Bus mesuré (`usize::MAX` : aucun)
+    bus: AtomicUsize,
+    /// Génération de remise à zéro de la mesure publiée
+    generation: AtomicU64,
+    /// Valeurs en bits f32, NaN = `None`
+    momentary: AtomicU32,
+    short_term: AtomicU32,
+    integrated: AtomicU32,
+}
+
+impl SharedLoudness {
+    pub fn new() -> Self {
+        Self {
+            bus: AtomicUsize::new(usize::MAX),
+            generation: AtomicU64::new(0),
+            momentary: AtomicU32::new(f32::NAN.to_bits()),
+            short_term: AtomicU32::new(f32::NAN.to_bits()),
+            integrated: AtomicU32::new(f32::NAN.to_bits()),
+        }
+    }
+
+    pub fn store(&self, bus: ChannelId, generation: u64, loudness: Loudness) {
+        let bits = |v: Option<f32>| v.unwrap_or(f32::NAN).to_bits();
+        self.momentary
+            .store(bits(loudness.momentary), Ordering::Relaxed);
+        self.short_term
+            .store(bits(loudness.short_term), Ordering::Relaxed);
+        self.integrated
+            .store(bits(loudness.integrated), Ordering::Relaxed);
+        self.generation.store(generation, Ordering::Relaxed);
+        self.bus.store(bus.0, Ordering::Release);
+    }
+
+    /// Dernière mesure de `bus` pour cette génération (`None` : le bus
+    /// n'a pas été mesuré depuis, par exemple parce qu'il n'est pas joué).
+    pub fn load(&self, bus: ChannelId, generation: u64) -> Option<Loudness> {
+        if self.bus.load(Ordering::Acquire) != bus.0
+            || self.generation.load(Ordering::Relaxed) != generation
+        {
+            return None;
+        }
+        let value =
+            |v: &AtomicU32| Some(f32::from_bits(v.load(Ordering::Relaxed))).filter(|v| !v.is_nan());
+        Some(Loudness {
+            momentary: value(&self.momentary),
+            short_term: value(&self.short_term),
+            integrated: value(&self.integrated),
+        })
+    }
+}
+
+impl Default for SharedLoudness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sinus stéréo de 1 kHz à `dbfs` (amplitude crête), `seconds` durant.
+    fn sine(meter: &mut LoudnessMeter, rate: f32, dbfs: f32, seconds: f32) {
+        let amplitude = 10f32.powf(dbfs / 20.0);
+        let frames = (rate * seconds) as usize;
+        // Par blocs de 480 frames, comme un callback
+        let mut block = Vec::with_capacity(960);
+        let mut phase = 0.0_f64;
+        let step = 2.0 * std::f64::consts::PI * 1000.0 / f64::from(rate);
+        for _ in 0..frames.div_ceil(480) {
+            block.clear();
+            for _ in 0..480 {
+                let s = amplitude * phase.sin() as f32;
+                block.extend([s, s]);
+                phase = (phase + step) % (2.0 * std::f64::consts::PI);
+            }
+            meter.process(&block);
+        }
+    }
+
+    fn assert_lufs(value: Option<f32>, expected: f32) {
+        let value = value.expect("no loudness yet");
+        assert!(
+            (value - expected).abs() <= 0.1,
+            "{value} LUFS, expected {expected} ± 0.1"
+        );
+    }
+
+    #[test]
+    fn k_weighting_matches_bs1770_table_at_48k() {
+        let [shelf, high_pass] = k_weighting(48_000.0);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-8;
+        assert!(close(shelf.b0, 1.535_124_859_586_97));
+        assert!(close(shelf.b1, -2.691_696_189_406_38));
+        assert!(close(shelf.b2, 1.198_392_810_852_85));
+        assert!(close(shelf.a1, -1.690_659_293_182_41));
+        assert!(close(shelf.a2, 0.732_480_774_215_85));
+        assert!(close(high_pass.a1, -1.990_047_454_833_98));
+        assert!(close(high_pass.a2, 0.990_072_250_366_21));
+    }
+
+    #[test]
+    fn reference_tone_reads_minus_23_lufs() {
+        // EBU Tech 3341, cas 1 : sinus 1 kHz à -23 dBFS sur les deux canaux
+        for rate in [44_100.0, 48_000.0, 96_000.0] {
+            let mut meter = LoudnessMeter::new(rate);
+            sine(&mut meter, rate, -23.0, 20.0);
+            let loudness = meter.loudness();
+            assert_lufs(loudness.momentary, -23.0);
+            assert_lufs(loudness.short_term, -23.0);
+            assert_lufs(loudness.integrated, -23.0);
+        }
+    }
+
+    #[test]
+    fn relative_gate_ignores_quiet_passages() {
+        // EBU Tech 3341, cas 3 : -36 / -23 / -36 dBFS (10 s, 60 s, 10 s)
+        let mut meter = LoudnessMeter::new(48_000.0);
+        sine(&mut meter, 48_000.0, -36.0, 10.0);
+        sine(&mut meter, 48_000.0, -23.0, 60.0);
+        sine(&mut meter, 48_000.0, -36.0, 10.0);
+        let loudness = meter.loudness();
+        assert_lufs(loudness.integrated, -23.0);
+        assert_lufs(loudness.short_term, -36.0);
+    }
+
+    #[test]
+    fn silence_is_gated_out_and_reset_starts_over() {
+        let mut meter = LoudnessMeter::new(48_000.0);
+        assert_eq!(meter.loudness(), Loudness::default());
+
+        meter.process(&vec![0.0; 48_000 * 2 * 5]);
+        assert_eq!(meter.loudness().integrated, None);
+
+        sine(&mut meter, 48_000.0, -20.0, 20.0);
+        meter.process(&vec![0.0; 48_000 * 2 * 5]);
+        // La fin silencieuse ne tire pas l'intégrée vers le bas
+        assert_lufs(meter.loudness().integrated, -20.0);
+        assert!(meter.loudness().momentary.is_none_or(|m| m < -70.0));
+
+        meter.reset();
+        assert_eq!(meter.loudness(), Loudness::default());
+    }
+
+    #[test]
+    fn shared_loudness_is_scoped_to_bus_and_generation() {
+        let shared = SharedLoudness::new();
+        assert_eq!(shared.load(ChannelId(3), 0), None);
+
+        let loudness = Loudness {
+            momentary: Some(-14.2),
+            short_term: Some(-15.0),
+            integrated: None,
+        };
+        shared.store(ChannelId(3), 1, loudness);
+        assert_eq!(shared.load(ChannelId(3), 1), Some(loudness));
+        assert_eq!(shared.load(ChannelId(4), 1), None);
+        // Remise à zéro demandée depuis : l'ancienne mesure ne compte plus
+        assert_eq!(shared.load(ChannelId(3), 2), None);
+    }
+}
@@ -5,7 +5,7 @@
 //! Les processeurs sont chaînés dans un `EffectsChain` :
 //!
 //! ```text
-//! audio in → [NoiseGate] → [Compressor] → [EQ] → [Limiter] → audio out
+//! audio in → [NoiseGate] → [AutoGain] → [EQ] → [Compressor] → [DeEsser] → [Limiter] → audio out
 //! ```
 //!
 //! # Traitement sample-par-sample vs buffer
@@ -17,10 +17,28 @@
 //! En production audio pro, on traiterait par blocs SIMD pour gagner 4-8x,
 //! mais pour un mixer avec < 10 canaux, c'est overkill.
 
+pub mod auto_gain;
+pub mod automation;
+pub mod bus_effects;
 pub mod compressor;
+pub mod dc_block;
+pub mod de_esser;
+pub mod delay;
+pub mod dither;
 pub mod eq;
 pub mod limiter;
+pub mod loudness;
 pub mod noise_gate;
+pub mod ramp;
+pub mod true_peak;
+
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::time::Instant;
+
+use automation::ParamRamp;
+use troubadour_shared::dsp::FrequencyResponse;
+
+use crate::fault::panic_message;
 
 /// Trait commun à tous les processeurs DSP.
 ///
@@ -35,6 +53,7 @@ pub mod noise_gate;
 /// 1. Traiter un sample audio (`process_sample`)
 /// 2. Être réinitialisé (`reset`)
 /// 3. Être bypassé (`set_bypass` / `is_bypassed`)
+/// 4. Suivre un changement de sample rate (`set_sample_rate`, optionnel)
 pub trait Processor: Send {
     /// Traite un seul sample audio et retourne le sample traité.
     ///
@@ -53,6 +72,100 @@ pub trait Processor: Send {
 
     /// Retourne `true` si le processeur est bypassé.
     fn is_bypassed(&self) -> bool;
+
+    /// Nom du type de processeur ("NoiseGate", "Compressor"...),
+    /// pour inspecter une chaîne sans downcast.
+    fn name(&self) -> &'static str;
+
+    /// Adapte les coefficients internes à un nouveau sample rate.
+    ///
+    /// Les paramètres réglés (fréquences, temps, seuils) ne changent pas :
+    /// seul ce qui en est dérivé est recalculé. Par défaut : rien à faire
+    /// (un processeur sans notion de temps, comme un gain).
+    fn set_sample_rate(&mut self, _sample_rate: f32) {}
+
+    /// Change un paramètre continu par son nom (voir
+    /// `EffectsPreset::param_mut`), sans reconstruire le processeur ni
+    /// perdre son état. Retourne `false` si le nom est inconnu.
+    ///
+    /// Appelé depuis le callback audio (automation) : pas d'allocation.
+    fn set_param(&mut self, _name: &str, _value: f32) -> bool {
+        false
+    }
+
+    /// Écoute du détecteur : la sortie devient ce que le détecteur
+    /// "entend" (voir le gate et le compresseur), pour placer le seuil
+    /// à l'oreille. Retourne `false` si le processeur n'a pas de détecteur.
+    fn set_detector_listen(&mut self, _listen: bool) -> bool {
+        false
+    }
+
+    /// Retard que le processeur ajoute au signal, en samples
+    /// (lookahead, ligne de retard). Par défaut : aucun.
+    fn latency_samples(&self) -> u32 {
+        0
+    }
+
+    /// Réduction de gain actuelle, de 0.0 à 1.0, pour les processeurs
+    /// de dynamique. `None` : le processeur n'en fait pas.
+    fn gain_reduction(&self) -> Option<f32> {
+        None
+    }
+
+    /// Gain (dB) à chaque fréquence, calculé depuis les coefficients
+    /// (voir `EffectsChain::estimate_response`). `None` : la réponse
+    /// dépend du signal (gate, compresseur...), elle n'a pas de courbe.
+    fn frequency_response(&self, _frequencies: &[f32], _sample_rate: f32) -> Option<Vec<f32>> {
+        None
+    }
+}
+
+/// Un étage a paniqué pendant `EffectsChain::process_block`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectPanic {
+    /// Position de l'étage dans la chaîne
+    pub index: usize,
+    pub name: &'static str,
+    pub message: String,
+}
+
+/// Crêtes du dernier bloc avant et après un étage de la chaîne.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StagePeak {
+    pub input: f32,
+    pub output: f32,
+}
+
+/// Poids d'un nouveau bloc dans le temps moyen d'un étage profilé :
+/// à 512 frames et 48 kHz, la moyenne suit sur ~100 ms (10 blocs).
+pub const PROFILE_SMOOTHING: f32 = 0.1;
+
+/// Moyenne glissante (EWMA) du temps d'un étage, en µs : `previous` à
+/// `None` au premier bloc mesuré, qui est pris tel quel.
+pub fn smooth_micros(previous: Option<f32>, measured: f32) -> f32 {
+    match previous {
+        Some(previous) => previous + PROFILE_SMOOTHING * (measured - previous),
+        None => measured,
+    }
+}
+
+/// Sample rate auquel les coefficients "par sample" des presets sont réglés.
+///
+/// Les `attack` / `release` du gate, du compresseur et du limiter sont des
+/// coefficients de lissage appliqués à chaque sample, calibrés à 48 kHz.
+pub const REFERENCE_SAMPLE_RATE: f32 = 48_000.0;
+
+/// Convertit un coefficient de lissage calibré à `REFERENCE_SAMPLE_RATE`
+/// pour qu'il garde la même constante de temps à `sample_rate`.
+///
+/// # Pourquoi une puissance ?
+/// `env += c * (x - env)` laisse `(1 - c)` de l'écart à chaque sample.
+/// Pour la même durée, il y a `ratio = sr / 48k` fois plus de samples :
+/// il faut `(1 - c')^ratio = (1 - c)`, soit `c' = 1 - (1 - c)^(1 / ratio)`.
+/// À 96 kHz, le coefficient est donc plus petit (≈ moitié pour un petit `c`).
+pub fn rescale_coefficient(coeff: f32, sample_rate: f32) -> f32 {
+    let exponent = REFERENCE_SAMPLE_RATE / sample_rate.max(1.0);
+    1.0 - (1.0 - coeff.clamp(0.0, 1.0)).powf(exponent)
 }
 
 /// Chaîne d'effets — applique une série de processeurs en séquence.
@@ -74,12 +187,35 @@ pub trait Processor: Send {
 /// ça oblige à lister tous les processeurs dans l'enum. Moins flexible.
 pub struct EffectsChain {
     processors: Vec<Box<dyn Processor>>,
+    /// Rampes de paramètres en cours (ou terminées, pas encore relevées)
+    automation: Vec<ParamRamp>,
+    /// Crêtes par étage, une entrée par processeur ; `None` quand la
+    /// métrologie détaillée est coupée (le chemin normal ne mesure rien)
+    stage_peaks: Option<Vec<StagePeak>>,
+    /// Temps de chaque étage, en µs par bloc (moyenne glissante) ;
+    /// `None` quand le profilage est coupé (l'horloge n'est pas lue)
+    stage_micros: Option<Vec<f32>>,
+    /// Blocs profilés depuis l'activation (le premier amorce la moyenne)
+    profiled_blocks: u64,
+    /// Frames du dernier bloc profilé
+    profiled_frames: usize,
+    /// Étages qui ont paniqué, sautés désormais (un par processeur)
+    poisoned: Vec<bool>,
+    /// Étage en cours : celui à mettre à l'écart si un panic survient
+    running: usize,
 }
 
 impl EffectsChain {
     pub fn new() -> Self {
         Self {
             processors: Vec::new(),
+            automation: Vec::new(),
+            stage_peaks: None,
+            stage_micros: None,
+            profiled_blocks: 0,
+            profiled_frames: 0,
+            poisoned: Vec::new(),
+            running: 0,
         }
     }
 
@@ -101,20 +237,196 @@ impl EffectsChain {
     /// Ajoute un processeur à la fin de la chaîne.
     pub fn add(&mut self, processor: Box<dyn Processor>) {
         self.processors.push(processor);
+        self.poisoned.push(false);
+        if let Some(peaks) = &mut self.stage_peaks {
+            peaks.push(StagePeak::default());
+        }
+        if let Some(micros) = &mut self.stage_micros {
+            micros.push(0.0);
+        }
     }
 
     /// Traite un sample à travers toute la chaîne.
     ///
     /// Chaque processeur reçoit le résultat du précédent.
-    /// Les processeurs bypassés sont skippés.
+    /// Les processeurs bypassés sont skippés, ceux qui ont paniqué aussi.
     pub fn process_sample(&mut self, sample: f32) -> f32 {
         let mut s = sample;
-        for proc in &mut self.processors {
-            s = proc.process_sample(s);
+        let stages = self.processors.iter_mut().zip(&self.poisoned).enumerate();
+        match &mut self.stage_peaks {
+            None => {
+                for (i, (proc, &poisoned)) in stages {
+                    if !poisoned {
+                        self.running = i;
+                        s = proc.process_sample(s);
+                    }
+                }
+            }
+            // Métrologie détaillée : une prise de crête de chaque côté
+            // de chaque étage
+            Some(peaks) => {
+                for ((i, (proc, &poisoned)), peak) in stages.zip(peaks.iter_mut()) {
+                    peak.input = peak.input.max(s.abs());
+                    if !poisoned {
+                        self.running = i;
+                        s = proc.process_sample(s);
+                    }
+                    peak.output = peak.output.max(s.abs());
+                }
+            }
         }
         s
     }
 
+    /// Traite un bloc mono en place, à l'abri des panics.
+    ///
+    /// # Taille du bloc
+    /// N'importe laquelle : les étages avancent sample par sample, sans
+    /// hypothèse de parité ni de taille maximale (rien sur la pile). Un
+    /// bloc vide ne touche à rien ; couper un signal en blocs de tailles
+    /// quelconques donne le même résultat qu'un seul bloc.
+    ///
+    /// # Un étage qui panique
+    /// Le panic est attrapé ici, au lieu de traverser le callback audio :
+    /// l'étage fautif est mis à l'écart (sauté pour tous les blocs
+    /// suivants, voir `is_poisoned`), le bloc en cours devient du
+    /// silence (à moitié traité, il ne veut rien dire) et l'étage est
+    /// retourné pour être signalé. Les autres étages continuent.
+    ///
+    /// # `AssertUnwindSafe`
+    /// Les processeurs ne sont pas `UnwindSafe` (état mutable). Seul
+    /// l'étage qui a paniqué a pu être laissé à moitié modifié, et il
+    /// n'est plus jamais appelé : les autres ont fini leur sample.
+    ///
+    /// Le chemin normal n'alloue rien : `catch_unwind` ne coûte qu'en
+    /// cas de panic.
+    ///
+    /// # Profilage
+    /// Coupé, il coûte un test par bloc. Activé (`set_profiling`), le
+    /// bloc passe étage par étage au lieu de sample par sample, pour
+    /// deux lectures d'horloge par étage et par bloc : chaque étage ne
+    /// dépend que de ce qu'il reçoit, la sortie est la même.
+    pub fn process_block(&mut self, block: &mut [f32]) -> Option<EffectPanic> {
+        if block.is_empty() {
+            return None;
+        }
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            if self.stage_micros.is_some() {
+                self.process_block_profiled(block);
+            } else {
+                for sample in block.iter_mut() {
+                    *sample = self.process_sample(*sample);
+                }
+            }
+        }));
+        let payload = result.err()?;
+        block.fill(0.0);
+        let index = self.running;
+        self.poisoned[index] = true;
+        Some(EffectPanic {
+            index,
+            name: self.processors[index].name(),
+            message: panic_message(payload),
+        })
+    }
+
+    /// `process_block` étage par étage, chacun chronométré.
+    fn process_block_profiled(&mut self, block: &mut [f32]) {
+        let Some(micros) = self.stage_micros.as_mut() else {
+            return;
+        };
+        let seeded = self.profiled_blocks > 0;
+        let stages = self.processors.iter_mut().zip(&self.poisoned).enumerate();
+        for (i, (proc, &poisoned)) in stages {
+            let started = Instant::now();
+            let mut peak = self.stage_peaks.as_mut().map(|peaks| &mut peaks[i]);
+            for sample in block.iter_mut() {
+                if let Some(peak) = peak.as_mut() {
+                    peak.input = peak.input.max(sample.abs());
+                }
+                if !poisoned {
+                    self.running = i;
+                    *sample = proc.process_sample(*sample);
+                }
+                if let Some(peak) = peak.as_mut() {
+                    peak.output = peak.output.max(sample.abs());
+                }
+            }
+            let elapsed = started.elapsed().as_secs_f32() * 1e6;
+            micros[i] = smooth_micros(seeded.then_some(micros[i]), elapsed);
+        }
+        self.profiled_blocks += 1;
+        self.profiled_frames = block.len();
+    }
+
+    /// L'étage `index` a paniqué et n'est plus appelé. Il revient avec
+    /// une chaîne reconstruite (`from_preset`), pas avec `reset`.
+    pub fn is_poisoned(&self, index: usize) -> bool {
+        self.poisoned.get(index).copied().unwrap_or(false)
+    }
+
+    /// Active ou coupe la mesure des crêtes par étage.
+    ///
+    /// Alloue à l'activation : à appeler hors du callback audio.
+    pub fn set_detailed_metering(&mut self, enabled: bool) {
+        if enabled == self.stage_peaks.is_some() {
+            return;
+        }
+        self.stage_peaks = enabled.then(|| vec![StagePeak::default(); self.processors.len()]);
+    }
+
+    pub fn detailed_metering(&self) -> bool {
+        self.stage_peaks.is_some()
+    }
+
+    /// Active ou coupe le chronométrage de chaque étage ; les moyennes
+    /// repartent de zéro à l'activation.
+    ///
+    /// Alloue à l'activation : à appeler hors du callback audio.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        if enabled == self.stage_micros.is_some() {
+            return;
+        }
+        self.stage_micros = enabled.then(|| vec![0.0; self.processors.len()]);
+        self.profiled_blocks = 0;
+        self.profiled_frames = 0;
+    }
+
+    pub fn profiling(&self) -> bool {
+        self.stage_micros.is_some()
+    }
+
+    /// Temps moyen de chaque étage, en µs par bloc (`None` : profilage
+    /// coupé). Tout à zéro tant qu'aucun bloc n'est passé.
+    pub fn stage_micros(&self) -> Option<&[f32]> {
+        self.stage_micros.as_deref()
+    }
+
+    /// Frames du dernier bloc profilé : la durée audio dont les étages
+    /// disposaient (0 : aucun bloc depuis l'activation).
+    pub fn profiled_frames(&self) -> usize {
+        self.profiled_frames
+    }
+
+    /// Début d'un bloc audio : les crêtes repartent de zéro, pour que
+    /// `stage_peaks` décrive le dernier bloc traité.
+    pub fn begin_block(&mut self) {
+        if let Some(peaks) = &mut self.stage_peaks {
+            peaks.fill(StagePeak::default());
+        }
+    }
+
+    /// Crêtes du dernier bloc, étage par étage (`None` : métrologie
+    /// détaillée coupée).
+    pub fn stage_peaks(&self) -> Option<&[StagePeak]> {
+        self.stage_peaks.as_deref()
+    }
+
+    /// Les processeurs, dans l'ordre de traitement (pour les inspecter).
+    pub fn processors(&self) -> impl Iterator<Item = &dyn Processor> {
+        self.processors.iter().map(|p| p.as_ref())
+    }
+
     /// Réinitialise tous les processeurs.
     pub fn reset(&mut self) {
         for proc in &mut self.processors {
@@ -122,6 +434,16 @@ impl EffectsChain {
         }
     }
 
+    /// Propage un nouveau sample rate à tous les processeurs.
+    ///
+    /// Les réglages sont conservés : pas besoin de reconstruire la
+    /// chaîne depuis le preset.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        for proc in &mut self.processors {
+            proc.set_sample_rate(sample_rate);
+        }
+    }
+
     /// Reconstruit la chaîne depuis un preset sérialisé.
     ///
     /// # Pourquoi reconstruire au lieu de modifier ?
@@ -140,6 +462,18 @@ impl EffectsChain {
         gate.set_bypass(!preset.noise_gate.enabled);
         chain.add(Box::new(gate));
 
+        // AGC : après le gate (le bruit coupé ne fausse pas la mesure),
+        // avant le compresseur (qui travaille alors sur un niveau stable)
+        let mut agc = auto_gain::AutoGain::new();
+        agc.set_target_db(preset.auto_gain.target_db);
+        agc.set_max_gain_db(preset.auto_gain.max_gain_db);
+        agc.set_freeze_threshold_db(preset.auto_gain.freeze_threshold_db);
+        agc.set_window(preset.auto_gain.window);
+        agc.set_rise_time(preset.auto_gain.rise_time);
+        agc.set_fall_time(preset.auto_gain.fall_time);
+        agc.set_bypass(!preset.auto_gain.enabled);
+        chain.add(Box::new(agc));
+
         // EQ
         let mut eq = eq::ParametricEq::default_3band();
         if preset.eq.bands.len() >= 3 {
@@ -172,12 +506,25 @@ impl EffectsChain {
         let mut comp = compressor::Compressor::new();
         comp.set_threshold(preset.compressor.threshold);
         comp.set_ratio(preset.compressor.ratio);
+        comp.set_knee_db(preset.compressor.knee_db);
         comp.set_attack(preset.compressor.attack);
         comp.set_release(preset.compressor.release);
         comp.set_makeup_gain(preset.compressor.makeup_gain);
         comp.set_bypass(!preset.compressor.enabled);
         chain.add(Box::new(comp));
 
+        // De-esser : après le compresseur, dont le makeup gain remonte
+        // aussi les sifflantes ; avant le limiter, qui reste le dernier
+        let mut de_esser = de_esser::DeEsser::new();
+        de_esser.set_frequency(preset.de_esser.frequency);
+        de_esser.set_q(preset.de_esser.q);
+        de_esser.set_threshold(preset.de_esser.threshold);
+        de_esser.set_ratio(preset.de_esser.ratio);
+        de_esser.set_attack(preset.de_esser.attack);
+        de_esser.set_release(preset.de_esser.release);
+        de_esser.set_bypass(!preset.de_esser.enabled);
+        chain.add(Box::new(de_esser));
+
         // Limiter
         let mut lim = limiter::Limiter::new();
         lim.set_ceiling(preset.limiter.ceiling);
@@ -188,6 +535,125 @@ impl EffectsChain {
         chain
     }
 
+    /// Lance une rampe sur un paramètre d'un processeur de la chaîne.
+    ///
+    /// Une rampe déjà en cours sur le même paramètre est remplacée et la
+    /// nouvelle repart de la valeur atteinte (pas de saut). Retourne
+    /// `false` si le processeur ou le paramètre n'existe pas.
+    pub fn automate(&mut self, mut ramp: ParamRamp) -> bool {
+        if let Some(i) = self.automation.iter().position(|r| r.same_param(&ramp)) {
+            ramp = ramp.starting_at(self.automation.remove(i).current());
+        }
+        self.resume(ramp)
+    }
+
+    /// Reprend une rampe (déjà avancée ou non) : le paramètre est mis à
+    /// sa valeur actuelle, puis la rampe continue à chaque bloc.
+    pub fn resume(&mut self, ramp: ParamRamp) -> bool {
+        let Some(processor) = self.processors.get_mut(ramp.effect_index) else {
+            return false;
+        };
+        if !processor.set_param(&ramp.param, ramp.current()) {
+            return false;
+        }
+        self.automation.push(ramp);
+        true
+    }
+
+    /// Fait avancer les rampes d'un bloc de `frames` frames ; retourne
+    /// combien ont atteint leur cible pendant ce bloc.
+    ///
+    /// Appelé par le callback audio avant de traiter le bloc : ni
+    /// allocation ni lock, une mise à jour de paramètre par rampe.
+    pub fn advance_automation(&mut self, frames: usize) -> usize {
+        let mut finished = 0;
+        for ramp in &mut self.automation {
+            if ramp.is_finished() {
+                continue;
+            }
+            let value = ramp.advance(frames);
+            if let Some(processor) = self.processors.get_mut(ramp.effect_index) {
+                processor.set_param(&ramp.param, value);
+            }
+            if ramp.is_finished() {
+                finished += 1;
+            }
+        }
+        finished
+    }
+
+    /// Retire et retourne les rampes arrivées à leur cible.
+    pub fn take_finished_automation(&mut self) -> Vec<ParamRamp> {
+        let (finished, running) = std::mem::take(&mut self.automation)
+            .into_iter()
+            .partition(ParamRamp::is_finished);
+        self.automation = running;
+        finished
+    }
+
+    /// Retire et retourne toutes les rampes (avant de remplacer la chaîne).
+    pub fn take_automation(&mut self) -> Vec<ParamRamp> {
+        std::mem::take(&mut self.automation)
+    }
+
+    /// Rampes en cours ou non encore relevées.
+    pub fn automation(&self) -> &[ParamRamp] {
+        &self.automation
+    }
+
+    /// Active l'écoute du détecteur d'un processeur de la chaîne.
+    /// Retourne `false` si le processeur n'existe pas ou n'a pas de détecteur.
+    pub fn set_detector_listen(&mut self, effect_index: usize, listen: bool) -> bool {
+        self.processors
+            .get_mut(effect_index)
+            .is_some_and(|p| p.set_detector_listen(listen))
+    }
+
+    /// Coupe toutes les écoutes de détecteur (arrêt du moteur).
+    pub fn clear_detector_listen(&mut self) {
+        for proc in &mut self.processors {
+            proc.set_detector_listen(false);
+        }
+    }
+
+    /// Réponse prédite de la chaîne sur `n_points` fréquences, sans
+    /// traiter d'audio : les courbes des étages linéaires (EQ)
+    /// s'additionnent en dB. Un étage non linéaire actif compte pour
+    /// 0 dB et est nommé dans `nonlinear_stages` ; un étage bypassé ou
+    /// mis à l'écart ne compte pas.
+    pub fn estimate_response(&self, sample_rate: f32, n_points: usize) -> FrequencyResponse {
+        let frequencies = eq::log_frequencies(n_points);
+        let mut gains_db = vec![0.0; frequencies.len()];
+        let mut nonlinear_stages = Vec::new();
+        for (i, proc) in self.processors.iter().enumerate() {
+            if proc.is_bypassed() || self.is_poisoned(i) {
+                continue;
+            }
+            match proc.frequency_response(&frequencies, sample_rate) {
+                Some(stage) => {
+                    for (gain, stage_gain) in gains_db.iter_mut().zip(stage) {
+                        *gain += stage_gain;
+                    }
+                }
+                None => nonlinear_stages.push(proc.name().to_string()),
+            }
+        }
+        FrequencyResponse {
+            frequencies,
+            gains_db,
+            nonlinear_stages,
+        }
+    }
+
+    /// Noms des processeurs actifs (non bypassés), dans l'ordre de traitement.
+    pub fn active_processors(&self) -> Vec<&'static str> {
+        self.processors
+            .iter()
+            .filter(|p| !p.is_bypassed())
+            .map(|p| p.name())
+            .collect()
+    }
+
     /// Nombre de processeurs dans la chaîne.
     pub fn len(&self) -> usize {
         self.processors.len()
@@ -225,6 +691,10 @@ mod tests {
     }
 
     impl Processor for Gain {
+        fn name(&self) -> &'static str {
+            "Gain"
+        }
+
         fn process_sample(&mut self, sample: f32) -> f32 {
             if self.bypassed {
                 return sample;
@@ -234,6 +704,14 @@ mod tests {
 
         fn reset(&mut self) {}
 
+        fn set_param(&mut self, name: &str, value: f32) -> bool {
+            if name != "factor" {
+                return false;
+            }
+            self.factor = value;
+            true
+        }
+
         fn set_bypass(&mut self, bypass: bool) {
             self.bypassed = bypass;
         }
@@ -243,6 +721,56 @@ mod tests {
         }
     }
 
+    /// Processeur bogué : panique sur un sample trop fort (un index
+    /// hors borne dans un vrai effet).
+    struct Exploding;
+
+    impl Processor for Exploding {
+        fn name(&self) -> &'static str {
+            "Exploding"
+        }
+
+        fn process_sample(&mut self, sample: f32) -> f32 {
+            let table = [sample; 1];
+            table[(sample.abs() * 2.0) as usize]
+        }
+
+        fn reset(&mut self) {}
+
+        fn set_bypass(&mut self, _bypass: bool) {}
+
+        fn is_bypassed(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn panicking_stage_is_skipped_while_the_chain_keeps_running() {
+        let mut chain = EffectsChain::new();
+        chain.add(Box::new(Gain::new(2.0)));
+        chain.add(Box::new(Exploding));
+        chain.add(Box::new(Gain::new(0.5)));
+
+        let mut block = [0.1, 0.2, 0.1];
+        assert_eq!(chain.process_block(&mut block), None);
+        assert_eq!(block, [0.1, 0.2, 0.1]);
+
+        // 0.3 × 2 = 0.6 : index 1 d'un tableau d'un élément
+        let mut block = [0.1, 0.3, 0.1];
+        let panic = chain.process_block(&mut block).unwrap();
+        assert_eq!((panic.index, panic.name), (1, "Exploding"));
+        assert!(panic.message.contains("index out of bounds"));
+        assert_eq!(block, [0.0; 3]);
+        assert!(chain.is_poisoned(1) && !chain.is_poisoned(0));
+
+        // Blocs suivants : l'étage est sauté, les autres traitent
+        let mut block = [0.1, 0.3, 0.1];
+        assert_eq!(chain.process_block(&mut block), None);
+        assert_eq!(block, [0.1, 0.3, 0.1]);
+        chain.reset();
+        assert!(chain.is_poisoned(1));
+    }
+
     #[test]
     fn empty_chain_passthrough() {
         let mut chain = EffectsChain::new();
@@ -268,9 +796,344 @@ mod tests {
         assert_eq!(chain.process_sample(0.5), 0.5);
     }
 
+    #[test]
+    fn concurrent_ramps_advance_each_block_until_taken() {
+        let mut chain = EffectsChain::new();
+        chain.add(Box::new(Gain::new(1.0)));
+        chain.add(Box::new(Gain::new(1.0)));
+        // 1 s et 0.5 s à 1 kHz : 1000 et 500 frames
+        assert!(chain.automate(ParamRamp::new(0, "factor", 1.0, 0.0, 1000, 1000.0)));
+        assert!(chain.automate(ParamRamp::new(1, "factor", 1.0, 3.0, 500, 1000.0)));
+        assert!(!chain.automate(ParamRamp::new(1, "ratio", 1.0, 3.0, 500, 1000.0)));
+        assert!(!chain.automate(ParamRamp::new(2, "factor", 1.0, 3.0, 500, 1000.0)));
+
+        chain.advance_automation(250);
+        // 0.75 × 2.0
+        assert!((chain.process_sample(1.0) - 1.5).abs() < 1e-6);
+        assert!(chain.take_finished_automation().is_empty());
+
+        assert_eq!(chain.advance_automation(250), 1);
+        let finished = chain.take_finished_automation();
+        assert_eq!(finished.len(), 1);
+        assert_eq!((finished[0].effect_index, finished[0].target()), (1, 3.0));
+        // 0.5 × 3.0
+        assert!((chain.process_sample(1.0) - 1.5).abs() < 1e-6);
+
+        // Nouvelle rampe sur le même paramètre : repart de 0.5, pas de 1.0
+        chain.automate(ParamRamp::new(0, "factor", 1.0, 1.0, 1000, 1000.0));
+        assert_eq!(chain.automation().len(), 1);
+        assert!((chain.process_sample(1.0) - 1.5).abs() < 1e-6);
+        chain.advance_automation(1000);
+        assert_eq!(chain.process_sample(1.0), 3.0);
+        assert_eq!(chain.take_finished_automation().len(), 1);
+    }
+
+    #[test]
+    fn stage_peaks_follow_the_chain_order() {
+        let mut chain = EffectsChain::new();
+        chain.add(Box::new(Gain::new(2.0)));
+        chain.add(Box::new(Gain::new(0.25)));
+        chain.process_sample(0.5);
+        assert_eq!(chain.stage_peaks(), None);
+
+        chain.set_detailed_metering(true);
+        chain.begin_block();
+        for sample in [0.1, -0.4, 0.2] {
+            chain.process_sample(sample);
+        }
+        let peaks = chain.stage_peaks().unwrap();
+        assert_eq!(
+            peaks[0],
+            StagePeak {
+                input: 0.4,
+                output: 0.8
+            }
+        );
+        // L'entrée d'un étage est la sortie du précédent
+        assert_eq!(
+            peaks[1],
+            StagePeak {
+                input: 0.8,
+                output: 0.2
+            }
+        );
+
+        // Nouveau bloc : les crêtes repartent de zéro
+        chain.begin_block();
+        chain.process_sample(0.1);
+        assert_eq!(chain.stage_peaks().unwrap()[0].input, 0.1);
+
+        chain.set_detailed_metering(false);
+        chain.process_sample(0.9);
+        assert_eq!(chain.stage_peaks(), None);
+    }
+
+    /// Processeur lent : attend `micros` µs à chaque sample.
+    struct Busy {
+        micros: f32,
+    }
+
+    impl Processor for Busy {
+        fn name(&self) -> &'static str {
+            "Busy"
+        }
+
+        fn process_sample(&mut self, sample: f32) -> f32 {
+            let started = Instant::now();
+            while started.elapsed().as_secs_f32() * 1e6 < self.micros {
+                std::hint::spin_loop();
+            }
+            sample
+        }
+
+        fn reset(&mut self) {}
+
+        fn set_param(&mut self, name: &str, value: f32) -> bool {
+            self.micros = value;
+            name == "micros"
+        }
+
+        fn set_bypass(&mut self, _bypass: bool) {}
+
+        fn is_bypassed(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn profiling_charges_the_time_to_the_slow_stage() {
+        let mut chain = EffectsChain::new();
+        chain.add(Box::new(Gain::new(2.0)));
+        chain.add(Box::new(Busy { micros: 20.0 }));
+        chain.add(Box::new(Gain::new(0.5)));
+        let mut block = [0.25; 50];
+        chain.process_block(&mut block);
+        assert_eq!(chain.stage_micros(), None);
+
+        chain.set_profiling(true);
+        assert_eq!(chain.stage_micros(), Some(&[0.0; 3][..]));
+        assert!(chain.process_block(&mut block).is_none());
+        assert_eq!(block, [0.25; 50]);
+        assert_eq!(chain.profiled_frames(), 50);
+        // 50 samples × 20 µs : au moins 1 ms, les gains presque rien
+        let micros = chain.stage_micros().unwrap().to_vec();
+        assert!(micros[1] >= 1000.0, "{micros:?}");
+        assert!(micros[0] < micros[1] / 10.0 && micros[2] < micros[1] / 10.0);
+
+        // L'étage ne coûte plus rien : la moyenne ne perd que 10 %
+        chain.processors[1].set_param("micros", 0.0);
+        chain.process_block(&mut block);
+        let smoothed = chain.stage_micros().unwrap()[1];
+        let expected = (1.0 - PROFILE_SMOOTHING) * micros[1];
+        assert!(smoothed >= expected && smoothed < expected + 0.05 * micros[1]);
+
+        // Réactivé : la moyenne repart du bloc suivant
+        chain.set_profiling(false);
+        chain.set_profiling(true);
+        chain.process_block(&mut block);
+        assert!(chain.stage_micros().unwrap()[1] < micros[1] / 10.0);
+    }
+
+    #[test]
+    fn profiled_blocks_sound_the_same() {
+        let signal: Vec<f32> = (0..2048)
+            .map(|i| (i as f32 * 0.05).sin() * if i < 1000 { 0.8 } else { 0.01 })
+            .collect();
+        let mut plain = EffectsChain::default_mic_chain();
+        let mut profiled = EffectsChain::default_mic_chain();
+        plain.set_detailed_metering(true);
+        profiled.set_detailed_metering(true);
+        profiled.set_profiling(true);
+        for chunk in signal.chunks(512) {
+            let (mut a, mut b) = (chunk.to_vec(), chunk.to_vec());
+            plain.begin_block();
+            profiled.begin_block();
+            plain.process_block(&mut a);
+            profiled.process_block(&mut b);
+            assert_eq!(a, b);
+            assert_eq!(plain.stage_peaks(), profiled.stage_peaks());
+        }
+        assert!(profiled.stage_micros().unwrap().iter().all(|&us| us > 0.0));
+    }
+
+    #[test]
+    fn smoothing_seeds_then_follows_each_block() {
+        let mut average = smooth_micros(None, 100.0);
+        assert_eq!(average, 100.0);
+        average = smooth_micros(Some(average), 200.0);
+        assert!((average - 110.0).abs() < 1e-4);
+        for _ in 0..100 {
+            average = smooth_micros(Some(average), 200.0);
+        }
+        assert!((average - 200.0).abs() < 0.01);
+    }
+
     #[test]
     fn default_mic_chain_has_four_processors() {
         let chain = EffectsChain::default_mic_chain();
         assert_eq!(chain.len(), 4); // gate + eq + compressor + limiter
     }
+
+    #[test]
+    fn rescaled_coefficient_keeps_time_constant() {
+        assert!((rescale_coefficient(0.01, REFERENCE_SAMPLE_RATE) - 0.01).abs() < 1e-7);
+
+        // Lissage de 1.0 vers 0.0 pendant 10 ms, à 48 kHz puis à 96 kHz
+        let settle = |coeff: f32, samples: usize| {
+            let mut env = 1.0_f32;
+            for _ in 0..samples {
+                env += coeff * (0.0 - env);
+            }
+            env
+        };
+        let at_48k = settle(0.01, 480);
+        let at_96k = settle(rescale_coefficient(0.01, 96_000.0), 960);
+        assert!((at_48k - at_96k).abs() < 1e-4, "{at_48k} vs {at_96k}");
+    }
+
+    #[test]
+    fn every_effect_behaves_the_same_at_44_1_khz() {
+        let mut preset = troubadour_shared::dsp::EffectsPreset::streaming();
+        preset.noise_gate.enabled = true;
+        preset.auto_gain.enabled = true;
+        preset.compressor.enabled = true;
+        preset.de_esser.enabled = true;
+        preset.limiter.enabled = true;
+
+        // Niveau (dB) par tranche de 10 ms d'une voix simulée : silence,
+        // 300 Hz, sifflante à 7 kHz, puis 300 Hz plus fort. Les tranches
+        // courtes suivent les attaques et relâchements, pas seulement le
+        // régime établi.
+        let levels = |rate: f32| {
+            let mut chain = EffectsChain::from_preset(&preset);
+            chain.set_sample_rate(rate);
+            let window = (rate / 100.0) as usize;
+            (0..300)
+                .map(|w| {
+                    let (freq, amplitude) = match w {
+                        0..50 => (300.0, 0.0),
+                        50..150 => (300.0, 0.1),
+                        150..200 => (7000.0, 0.2),
+                        _ => (300.0, 0.5),
+                    };
+                    let energy: f32 = (0..window)
+                        .map(|i| {
+                            let t = (w * window + i) as f32 / rate;
+                            let x = amplitude * (std::f32::consts::TAU * freq * t).sin();
+                            chain.process_sample(x).powi(2)
+                        })
+                        .sum();
+                    10.0 * (energy / window as f32).max(1e-12).log10()
+                })
+                .collect::<Vec<f32>>()
+        };
+        let (at_44k, at_48k) = (levels(44_100.0), levels(48_000.0));
+        for (w, (a, b)) in at_44k.iter().zip(&at_48k).enumerate().skip(50) {
+            assert!(
+                (a - b).abs() < 0.5,
+                "window {w}: {a} dB at 44.1 kHz vs {b} dB"
+            );
+        }
+    }
+
+    #[test]
+    fn from_preset_includes_auto_gain() {
+        let preset = troubadour_shared::dsp::EffectsPreset::default_preset();
+        let mut chain = EffectsChain::from_preset(&preset);
+        assert_eq!(chain.len(), 6); // gate + agc + eq + compressor + de-esser + limiter
+
+        // AGC désactivé par défaut : un sample faible n'est pas amplifié
+        // au-delà de ce que fait le compresseur (makeup gain).
+        let out = chain.process_sample(0.01);
+        assert!(out.abs() < 0.02);
+    }
+
+    #[test]
+    fn estimated_response_adds_linear_stages_and_flags_the_others() {
+        let mut preset = troubadour_shared::dsp::EffectsPreset::default_preset();
+        preset.eq.enabled = true;
+        preset.eq.bands[0].frequency = 200.0;
+        preset.eq.bands[0].gain_db = 6.0;
+        preset.eq.bands[0].q = 0.707;
+        preset.compressor.enabled = true;
+        preset.noise_gate.enabled = false;
+        let chain = EffectsChain::from_preset(&preset);
+        let response = chain.estimate_response(48_000.0, 32);
+        assert_eq!(response.frequencies.len(), 32);
+        assert!(
+            (response.gains_db[0] - 6.0).abs() < 0.5,
+            "{:?}",
+            response.gains_db
+        );
+        assert!(response.gains_db[31].abs() < 0.5);
+        assert!(
+            response
+                .nonlinear_stages
+                .contains(&"Compressor".to_string())
+        );
+        assert!(!response.nonlinear_stages.contains(&"NoiseGate".to_string()));
+
+        // Sans EQ : plat
+        preset.eq.enabled = false;
+        let flat = EffectsChain::from_preset(&preset).estimate_response(48_000.0, 8);
+        assert!(flat.gains_db.iter().all(|g| *g == 0.0));
+    }
+
+    /// Tailles de bloc des tests de robustesse : vide, impaires, autour
+    /// d'un buffer usuel, grande.
+    pub(crate) const BLOCK_LENGTHS: [usize; 7] = [0, 1, 3, 511, 512, 513, 4096];
+
+    /// Chaque effet, seul dans sa chaîne et réglé pour travailler.
+    fn each_effect() -> Vec<Box<dyn Processor>> {
+        let mut gate = noise_gate::NoiseGate::new();
+        gate.set_threshold(0.2);
+        let mut compressor = compressor::Compressor::new();
+        compressor.set_threshold(0.1);
+        let mut de_esser = de_esser::DeEsser::new();
+        de_esser.set_threshold(0.05);
+        let mut limiter = limiter::Limiter::new();
+        limiter.set_ceiling(0.3);
+        let mut delay = delay::DelayLine::new(48_000.0);
+        delay.set_delay_ms(5.0);
+        vec![
+            Box::new(gate),
+            Box::new(auto_gain::AutoGain::new()),
+            Box::new(eq::ParametricEq::default_3band()),
+            Box::new(compressor),
+            Box::new(de_esser),
+            Box::new(limiter),
+            Box::new(delay),
+        ]
+    }
+
+    #[test]
+    fn any_block_length_gives_the_same_output() {
+        let signal: Vec<f32> = (0..4096 * 2)
+            .map(|i| (i as f32 * 0.05).sin() * if (i / 700) % 2 == 0 { 0.8 } else { 0.01 })
+            .collect();
+        for index in 0..each_effect().len() {
+            let single = || {
+                let mut chain = EffectsChain::new();
+                chain.add(each_effect().swap_remove(index));
+                chain
+            };
+            let name = single().processors().next().unwrap().name();
+            for len in BLOCK_LENGTHS {
+                // Référence : tout le signal d'un seul bloc
+                let mut reference = signal.clone();
+                assert!(single().process_block(&mut reference).is_none());
+
+                // Le même signal en blocs de `len` (le dernier plus
+                // court), un bloc vide entre chaque
+                let mut chain = single();
+                let mut split = signal.clone();
+                let block_len = if len == 0 { split.len() } else { len };
+                for block in split.chunks_mut(block_len) {
+                    assert!(chain.process_block(&mut []).is_none());
+                    assert!(chain.process_block(block).is_none());
+                }
+                assert_eq!(split, reference, "{name}, blocks of {len}");
+            }
+        }
+    }
 }
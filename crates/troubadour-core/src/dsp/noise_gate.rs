@@ -1,4 +1,7 @@
-use super::Processor;
+use super::{Processor, REFERENCE_SAMPLE_RATE, rescale_coefficient};
+
+/// Lissage du gain d'ouverture/fermeture (à 48 kHz).
+const GAIN_SMOOTHING: f32 = 0.05;
 
 /// Noise Gate — coupe le son en dessous d'un seuil.
 ///
@@ -25,23 +28,43 @@ pub struct NoiseGate {
     threshold: f32,
     attack: f32,
     release: f32,
+    /// Coefficients effectifs, adaptés au sample rate courant
+    attack_coeff: f32,
+    release_coeff: f32,
+    gain_coeff: f32,
+    sample_rate: f32,
     /// L'enveloppe lissée du signal (0.0 → 1.0+)
     envelope: f32,
     /// Le gain appliqué (0.0 = fermé, 1.0 = ouvert)
     gain: f32,
+    /// Écoute du détecteur (transitoire, jamais sauvée)
+    listen: bool,
     bypassed: bool,
 }
 
 impl NoiseGate {
     pub fn new() -> Self {
-        Self {
+        let mut gate = Self {
             threshold: 0.005,
             attack: 0.3,
             release: 0.002,
+            attack_coeff: 0.0,
+            release_coeff: 0.0,
+            gain_coeff: 0.0,
+            sample_rate: REFERENCE_SAMPLE_RATE,
             envelope: 0.0,
             gain: 0.0,
+            listen: false,
             bypassed: true, // OFF par defaut — l'utilisateur l'active quand il veut
-        }
+        };
+        gate.update_coefficients();
+        gate
+    }
+
+    fn update_coefficients(&mut self) {
+        self.attack_coeff = rescale_coefficient(self.attack, self.sample_rate);
+        self.release_coeff = rescale_coefficient(self.release, self.sample_rate);
+        self.gain_coeff = rescale_coefficient(GAIN_SMOOTHING, self.sample_rate);
     }
 
     /// Configure le seuil du gate.
@@ -53,11 +76,13 @@ impl NoiseGate {
     /// Configure la vitesse d'ouverture (0.001 lent → 0.5 rapide).
     pub fn set_attack(&mut self, attack: f32) {
         self.attack = attack.clamp(0.001, 0.5);
+        self.update_coefficients();
     }
 
     /// Configure la vitesse de fermeture (0.001 lent → 0.5 rapide).
     pub fn set_release(&mut self, release: f32) {
         self.release = release.clamp(0.001, 0.5);
+        self.update_coefficients();
     }
 
     pub fn threshold(&self) -> f32 {
@@ -86,6 +111,10 @@ impl Default for NoiseGate {
 }
 
 impl Processor for NoiseGate {
+    fn name(&self) -> &'static str {
+        "NoiseGate"
+    }
+
     fn process_sample(&mut self, sample: f32) -> f32 {
         if self.bypassed {
             return sample;
@@ -96,9 +125,9 @@ impl Processor for NoiseGate {
         //    C'est comme un VU-meter très rapide.
         let abs_sample = sample.abs();
         let coeff = if abs_sample > self.envelope {
-            self.attack // Monte vite
+            self.attack_coeff // Monte vite
         } else {
-            self.release // Descend lentement
+            self.release_coeff // Descend lentement
         };
         self.envelope += coeff * (abs_sample - self.envelope);
 
@@ -113,8 +142,14 @@ impl Processor for NoiseGate {
 
         // Smoothing du gain pour éviter les clics
         // Plus rapide que l'envelope car on veut une transition clean
-        self.gain += 0.05 * (target_gain - self.gain);
+        self.gain += self.gain_coeff * (target_gain - self.gain);
 
+        // Écoute du détecteur : la décision brute, sans lissage. On
+        // entend exactement les passages qui ouvrent la porte. L'état
+        // normal continue d'évoluer : couper l'écoute ne laisse rien.
+        if self.listen {
+            return sample * target_gain;
+        }
         sample * self.gain
     }
 
@@ -123,6 +158,26 @@ impl Processor for NoiseGate {
         self.gain = 0.0;
     }
 
+    fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.sample_rate = sample_rate.max(1.0);
+        self.update_coefficients();
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "threshold" => self.set_threshold(value),
+            "attack" => self.set_attack(value),
+            "release" => self.set_release(value),
+            _ => return false,
+        }
+        true
+    }
+
+    fn set_detector_listen(&mut self, listen: bool) -> bool {
+        self.listen = listen;
+        true
+    }
+
     fn set_bypass(&mut self, bypass: bool) {
         self.bypassed = bypass;
     }
@@ -224,4 +279,34 @@ mod tests {
         gate.reset();
         assert_eq!(gate.current_gain(), 0.0);
     }
+
+    #[test]
+    fn detector_listen_plays_the_raw_gate_decision() {
+        let mut gate = NoiseGate::new();
+        gate.set_bypass(false);
+        gate.set_threshold(0.1);
+        let mut normal = NoiseGate::new();
+        normal.set_bypass(false);
+        normal.set_threshold(0.1);
+        assert!(gate.set_detector_listen(true));
+
+        // Premier sample fort : la porte lissée est encore presque
+        // fermée, le détecteur a déjà décidé d'ouvrir
+        let first = gate.process_sample(0.8);
+        assert_eq!(first, 0.8);
+        assert!(normal.process_sample(0.8) < 0.1);
+
+        // Sous le seuil, le détecteur ferme d'un coup
+        for _ in 0..48_000 {
+            gate.process_sample(0.01);
+            normal.process_sample(0.01);
+        }
+        assert_eq!(gate.process_sample(0.01), 0.0);
+
+        // Écoute coupée : exactement le traitement normal
+        gate.set_detector_listen(false);
+        for _ in 0..500 {
+            assert_eq!(gate.process_sample(0.5), normal.process_sample(0.5));
+        }
+    }
 }
@@ -0,0 +1,219 @@
+/// Durée du lissage d'un simple changement de volume de bus.
+///
+/// Un saut de gain instantané au milieu d'une forme d'onde crée une
+/// discontinuité audible ("zipper noise", clic). 10 ms suffisent à la
+/// gommer sans que le fader paraisse mou.
+pub const SMOOTHING_MS: f32 = 10.0;
+
+/// Rampe de gain linéaire, avancée par blocs audio.
+///
+/// # Pourquoi compter en samples ?
+/// Le callback audio est la seule horloge fiable : l'UI peut rafraîchir
+/// à 30 ou 60 fps, ou geler une seconde, la rampe dure quand même
+/// exactement `duration_ms` de son. À l'intérieur d'un bloc, le gain
+/// est interpolé frame par frame (pas de marche d'escalier).
+#[derive(Debug, Clone)]
+pub struct GainRamp {
+    current: f32,
+    target: f32,
+    /// Incrément par frame
+    step: f32,
+    /// Frames restantes avant d'atteindre la cible
+    remaining: usize,
+    sample_rate: f32,
+}
+
+impl GainRamp {
+    pub fn new(gain: f32, sample_rate: f32) -> Self {
+        Self {
+            current: gain,
+            target: gain,
+            step: 0.0,
+            remaining: 0,
+            sample_rate,
+        }
+    }
+
+    /// Démarre une rampe depuis le gain courant (même en pleine rampe :
+    /// on repart d'où on en est, sans saut).
+    pub fn ramp_to(&mut self, target: f32, duration_ms: f32) {
+        let frames = (duration_ms.max(0.0) / 1000.0 * self.sample_rate).round() as usize;
+        self.target = target;
+        if frames == 0 {
+            self.current = target;
+            self.remaining = 0;
+        } else {
+            self.step = (target - self.current) / frames as f32;
+            self.remaining = frames;
+        }
+    }
+
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target
+    }
+
+    pub fn is_ramping(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Applique le gain à un bloc entrelacé (`channels` samples par frame).
+    ///
+    /// Retourne `true` si la rampe a atteint sa cible pendant ce bloc.
+    pub fn apply(&mut self, buffer: &mut [f32], channels: usize) -> bool {
+        let was_ramping = self.is_ramping();
+        for frame in buffer.chunks_mut(channels.max(1)) {
+            if self.remaining > 0 {
+                self.remaining -= 1;
+                // Dernière frame : la cible exacte (pas d'erreur d'arrondi cumulée)
+                self.current = if self.remaining == 0 {
+                    self.target
+                } else {
+                    self.current + self.step
+                };
+            }
+            for sample in frame {
+                *sample *= self.current;
+            }
+        }
+        was_ramping && !self.is_ramping()
+    }
+}
+
+/// Fondu demandé sur un bus (`Command::FadeBusVolume`).
+///
+/// `id` distingue deux fondus successifs vers la même cible : le
+/// callback ne compare que des snapshots, il doit savoir qu'un NOUVEAU
+/// fondu a été demandé.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BusFade {
+    pub id: u64,
+    pub duration_ms: f32,
+}
+
+/// Gain d'un bus côté callback : suit le volume publié dans le snapshot.
+///
+/// - volume changé, pas de fondu → lissage de `SMOOTHING_MS`
+/// - nouveau fondu → rampe de `duration_ms`
+/// - fondu retiré (fader repris en main) → le fondu est abandonné et
+///   le gain rejoint le nouveau volume en `SMOOTHING_MS`
+pub struct BusFader {
+    ramp: GainRamp,
+    fade: Option<BusFade>,
+}
+
+impl BusFader {
+    pub fn new(gain: f32, fade: Option<BusFade>, sample_rate: f32) -> Self {
+        // Un fondu déjà en place au démarrage du stream est considéré
+        // comme terminé : le volume publié est déjà sa cible.
+        Self {
+            ramp: GainRamp::new(gain, sample_rate),
+            fade,
+        }
+    }
+
+    /// Prend en compte le volume et le fondu du dernier snapshot.
+    pub fn update(&mut self, target: f32, fade: Option<BusFade>) {
+        if fade != self.fade {
+            self.fade = fade;
+            let duration = fade.map_or(SMOOTHING_MS, |f| f.duration_ms);
+            self.ramp.ramp_to(target, duration);
+        } else if target != self.ramp.target() {
+            self.ramp.ramp_to(target, SMOOTHING_MS);
+        }
+    }
+
+    /// Applique le gain du bus. Retourne `true` quand un fondu se termine
+    /// (pas pour un simple lissage).
+    pub fn process(&mut self, buffer: &mut [f32], channels: usize) -> bool {
+        let fading = self.fade.is_some() && self.ramp.is_ramping();
+        self.ramp.apply(buffer, channels) && fading
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.ramp.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48_000.0;
+    const BLOCK: usize = 480; // 10 ms
+
+    /// Gain appliqué à chaque frame d'un bloc mono de 1.0.
+    fn run_block(fader: &mut BusFader) -> (Vec<f32>, bool) {
+        let mut block = vec![1.0; BLOCK];
+        let done = fader.process(&mut block, 1);
+        (block, done)
+    }
+
+    #[test]
+    fn fade_is_monotonic_and_ends_on_time() {
+        let fade = BusFade {
+            id: 1,
+            duration_ms: 500.0,
+        };
+        let mut fader = BusFader::new(1.0, None, SR);
+        fader.update(0.0, Some(fade));
+
+        let mut trajectory = Vec::new();
+        let mut completed_at = None;
+        for block in 0..100 {
+            let (gains, done) = run_block(&mut fader);
+            trajectory.extend(gains);
+            if done {
+                completed_at = Some(block);
+                break;
+            }
+        }
+        assert!(trajectory.windows(2).all(|w| w[1] <= w[0]));
+        assert_eq!(*trajectory.last().unwrap(), 0.0);
+
+        // 500 ms = 24 000 frames = 50 blocs : fin dans le 50e (index 49)
+        let expected = (0.5 * SR) as usize / BLOCK;
+        let block = completed_at.expect("fade never completed");
+        assert!(block + 1 >= expected && block < expected + 1);
+    }
+
+    #[test]
+    fn new_volume_cancels_fade() {
+        let mut fader = BusFader::new(1.0, None, SR);
+        fader.update(
+            0.0,
+            Some(BusFade {
+                id: 1,
+                duration_ms: 1000.0,
+            }),
+        );
+        for _ in 0..10 {
+            run_block(&mut fader);
+        }
+        let mid_fade = fader.gain();
+        assert!(mid_fade < 1.0 && mid_fade > 0.5);
+
+        // Le fader est repris : le fondu disparaît du snapshot
+        fader.update(0.8, None);
+        let mut completed = false;
+        for _ in 0..10 {
+            completed |= run_block(&mut fader).1;
+        }
+        assert_eq!(fader.gain(), 0.8);
+        assert!(!completed, "a cancelled fade must not report completion");
+    }
+
+    #[test]
+    fn plain_volume_change_is_smoothed() {
+        let mut fader = BusFader::new(1.0, None, SR);
+        fader.update(0.0, None);
+        let (gains, done) = run_block(&mut fader);
+        // Pas de saut : la première frame est encore proche de 1
+        assert!(gains[0] > 0.99);
+        assert_eq!(gains[BLOCK - 1], 0.0);
+        assert!(!done);
+    }
+}
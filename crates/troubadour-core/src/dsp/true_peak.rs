@@ -0,0 +1,143 @@
+/// Facteur de suréchantillonnage de la crête vraie.
+const OVERSAMPLING: usize = 4;
+/// Coefficients par phase du filtre d'interpolation (48 au total,
+/// comme le filtre d'exemple de l'ITU-R BS.1770).
+const TAPS_PER_PHASE: usize = 12;
+
+/// Crête vraie (ITU-R BS.1770) : la crête du signal reconstruit ENTRE
+/// les samples.
+///
+/// # Pourquoi les samples ne suffisent pas ?
+/// Un sinus à 8 kHz échantillonné à 48 kHz n'a que 6 samples par
+/// période : aucun ne tombe forcément sur le sommet. Les samples
+/// plafonnent à -1.25 dB alors que le convertisseur, lui, reconstruit
+/// une onde qui touche 0 dB... et clippe.
+///
+/// # Le filtre
+/// On insère 3 points entre chaque paire de samples avec un sinc
+/// fenêtré (Blackman), découpé en 4 phases : la phase `p` calcule le
+/// point à `n + p/4`, à partir des 12 derniers samples. Les
+/// coefficients sont calculés une fois, à la création ; `process`
+/// n'alloue rien et peut tourner dans le callback audio.
+pub struct TruePeakMeter {
+    phases: [[f32; TAPS_PER_PHASE]; OVERSAMPLING],
+    /// 12 derniers samples de chaque canal, le plus récent en tête
+    history: Vec<[f32; TAPS_PER_PHASE]>,
+}
+
+impl TruePeakMeter {
+    pub fn new(channels: usize) -> Self {
+        let taps = OVERSAMPLING * TAPS_PER_PHASE;
+        let center = (taps - 1) as f64 / 2.0;
+        let mut phases = [[0.0; TAPS_PER_PHASE]; OVERSAMPLING];
+        for (p, phase) in phases.iter_mut().enumerate() {
+            let mut sum = 0.0;
+            let mut coeffs = [0.0_f64; TAPS_PER_PHASE];
+            for (k, coeff) in coeffs.iter_mut().enumerate() {
+                let i = p + OVERSAMPLING * k;
+                // Coupure à la fréquence de Nyquist du signal d'origine
+                let x = std::f64::consts::PI * (i as f64 - center) / OVERSAMPLING as f64;
+                let sinc = if x == 0.0 { 1.0 } else { x.sin() / x };
+                let w = std::f64::consts::TAU * i as f64 / (taps - 1) as f64;
+                let window = 0.42 - 0.5 * w.cos() + 0.08 * (2.0 * w).cos();
+                *coeff = sinc * window;
+                sum += *coeff;
+            }
+            // Chaque phase laisse passer le continu tel quel
+            for (out, coeff) in phase.iter_mut().zip(coeffs) {
+                *out = (coeff / sum) as f32;
+            }
+        }
+        Self {
+            phases,
+            history: vec![[0.0; TAPS_PER_PHASE]; channels.max(1)],
+        }
+    }
+
+    /// Crête vraie (linéaire) d'un bloc entrelacé, tous canaux
+    /// confondus. L'historique passe d'un bloc au suivant : une crête à
+    /// cheval sur deux blocs est vue.
+    pub fn process(&mut self, interleaved: &[f32]) -> f32 {
+        let channels = self.history.len();
+        let mut peak = 0.0_f32;
+        for frame in interleaved.chunks_exact(channels) {
+            for (history, &sample) in self.history.iter_mut().zip(frame) {
+                history.copy_within(..TAPS_PER_PHASE - 1, 1);
+                history[0] = sample;
+                for phase in &self.phases {
+                    let value: f32 = phase.iter().zip(history.iter()).map(|(h, x)| h * x).sum();
+                    peak = peak.max(value.abs());
+                }
+                peak = peak.max(sample.abs());
+            }
+        }
+        peak
+    }
+
+    /// Oublie l'historique (mesure réactivée : pas de reste de l'ancien
+    /// signal dans le premier bloc).
+    pub fn reset(&mut self) {
+        self.history.fill([0.0; TAPS_PER_PHASE]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sinus stéréo entrelacé : `cycles_per_sample` = f / fs.
+    fn sine(amplitude: f32, cycles_per_sample: f64, phase: f64, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|n| {
+                let value = amplitude as f64
+                    * (std::f64::consts::TAU * cycles_per_sample * n as f64 + phase).sin();
+                [value as f32; 2]
+            })
+            .collect()
+    }
+
+    fn db(level: f32) -> f32 {
+        20.0 * level.log10()
+    }
+
+    #[test]
+    fn quarter_rate_sine_at_45_degrees_overshoots_by_3_db() {
+        // fs/4 déphasé de 45° : les samples valent ±sin(45°) de la
+        // crête. Amplitude √2 → samples à 0 dBFS, onde à +3 dBTP.
+        let block = sine(
+            std::f32::consts::SQRT_2,
+            0.25,
+            std::f64::consts::FRAC_PI_4,
+            512,
+        );
+        let sample_peak = block.iter().fold(0.0_f32, |p, s| p.max(s.abs()));
+        assert!(db(sample_peak).abs() < 0.01);
+
+        let mut meter = TruePeakMeter::new(2);
+        meter.process(&block);
+        // Régime établi : le démarrage brutal n'est plus dans l'historique
+        let true_peak = db(meter.process(&block));
+        assert!((true_peak - 3.01).abs() < 0.2, "{true_peak} dBTP");
+    }
+
+    #[test]
+    fn slow_signals_read_their_sample_peak() {
+        // 4 périodes entières : le bloc se reboucle sans saut
+        let block = sine(0.5, 100.0 / 48_000.0, 0.0, 1_920);
+        let mut meter = TruePeakMeter::new(2);
+        meter.process(&block);
+        let true_peak = meter.process(&block);
+        assert!((db(true_peak) - db(0.5)).abs() < 0.05);
+    }
+
+    #[test]
+    fn reset_forgets_the_previous_block() {
+        let mut meter = TruePeakMeter::new(1);
+        meter.process(&[0.0, 1.0]);
+        // Le sinc de l'impulsion déborde sur les points suivants
+        assert!(meter.process(&[0.0; 4]) > 0.0);
+        meter.process(&[0.0, 1.0]);
+        meter.reset();
+        assert_eq!(meter.process(&[0.0; 4]), 0.0);
+    }
+}
@@ -1,18 +1,109 @@
-use std::sync::{Arc, Mutex};
+use std::collections::{BTreeMap, HashMap};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LockResult, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
 
 use cpal::traits::{DeviceTrait, StreamTrait};
-use cpal::{SampleFormat, Stream};
+use cpal::{SampleFormat, Stream, SupportedStreamConfig, SupportedStreamConfigRange};
 use crossbeam_channel::{Receiver, Sender};
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
-use troubadour_shared::audio::ChannelId;
-use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::action::{ActionSet, DailyTrigger, ScheduleConfig};
+use troubadour_shared::audio::{
+    AudioStats, BufferSize, ChannelCount, ChannelId, DeviceInfo, DitherMode, PreviewStopReason,
+    RouteActivity, SampleRate, SessionStats, StreamHealth, channel_slice_label,
+};
+use troubadour_shared::compat::CompatibilityReport;
+use troubadour_shared::config::{
+    AppConfig, AudioConfig, AutoSnapshotConfig, ControlsConfig, DevicePreviewConfig, JournalConfig,
+    LevelHistoryConfig, RecoveryConfig, ReplayConfig, WatchdogConfig, ensure_writable,
+};
+use troubadour_shared::config_layers::ConfigEntry;
+use troubadour_shared::control::{ControlProfile, ControlProfileManager, ControlSource};
+use troubadour_shared::device_cache::{DeviceCache, StreamSettings};
+use troubadour_shared::diff::{ConfigDiff, DiffEntry};
+use troubadour_shared::dsp::{
+    DspProfile, EffectStageInfo, EffectTiming, EffectsPreset, FrequencyResponse, InsertPoint,
+    ParamPolicy, ParamViolation,
+};
+use troubadour_shared::error::{MixerError, MixerResult, TroubadourError, TroubadourResult};
+use troubadour_shared::graph::{GraphEdge, GraphNode, NodeKind, SignalGraph};
+use troubadour_shared::import::{ImportFormat, ImportReport};
+use troubadour_shared::journal::ChangeEntry;
+use troubadour_shared::locale::{self, Locale};
+use troubadour_shared::machine::{MachineProfile, SetupProposal};
 use troubadour_shared::messages::{Command, Event};
-use troubadour_shared::mixer::{ChannelLevel, MixerConfig};
+use troubadour_shared::meter_frame::{MeterFrameEncoder, MeterLayout, MeterTransport};
+use troubadour_shared::mixer::{
+    AutoLevelStatus, ChannelConfig, ChannelDependencies, ChannelHealth, ChannelKind, ChannelLevel,
+    DeviceAssignment, FaderTaper, InputFallback, InputSource, LevelPoint, Loudness, MeterScale,
+    MixerConfig, PREVIEW_CHANNEL, Route, RoutingSnapshot, SignalPresenceConfig, SignalState,
+};
+use troubadour_shared::network::{NetworkReceiveConfig, NetworkSendConfig, NetworkStats};
+use troubadour_shared::preset::{
+    EffectsPresetManager, FavoritePresets, LEGACY_PRESET_DIR, sanitize_preset_name,
+};
+use troubadour_shared::profile::Profile;
+use troubadour_shared::profile_json;
+use troubadour_shared::recording::ReplayInfo;
+use troubadour_shared::recovery::{AutoSnapshotInfo, RecoverySnapshot};
+use troubadour_shared::script::ScriptingConfig;
+use troubadour_shared::tap::{TapConfig, TapInfo};
+use troubadour_shared::template::ChannelTemplate;
+use troubadour_shared::ui_state::UiState;
 
-use crate::device::DeviceManager;
-use crate::dsp::EffectsChain;
-use crate::mixer::Mixer;
+use crate::auto_level::{self, AutoLevel};
+use crate::channel_map::{OutputSlot, check_output_slots, device_lanes, fold_to_bus, spread_lane};
+use crate::command_queue::{EffectsSwap, EngineCommand};
+use crate::compat;
+use crate::device::{
+    DefaultDeviceSource, DeviceManager, SystemDefaults, build_stream_error, play_stream_error,
+    supported_rates,
+};
+use crate::diagnostics::{self, CpalProbe, DiagnosticsContext, EngineHealth};
+use crate::dsp::automation::ParamRamp;
+use crate::dsp::bus_effects::BusEffects;
+use crate::dsp::dc_block::InputConditioner;
+use crate::dsp::delay::DelayLine;
+use crate::dsp::dither::Ditherer;
+use crate::dsp::loudness::{LoudnessMeter, SharedLoudness};
+use crate::dsp::ramp::BusFader;
+use crate::dsp::true_peak::TruePeakMeter;
+use crate::dsp::{EffectsChain, Processor};
+use crate::fallback::{self, ClockedInput, LoopedAudio, ResolvedInput};
+use crate::fault::{ChannelFaults, panic_message};
+use crate::handover::{HANDOVER_TIMEOUT_MS, HandoverFader, HandoverGate};
+use crate::idle::{IdleDetector, InputActivity};
+use crate::import;
+use crate::journal::{self, ChangeJournal};
+use crate::latency::{LatencyCounters, LatencyGovernor};
+use crate::level_history::LevelHistory;
+use crate::levels::SharedLevels;
+use crate::meter_gate::{EmissionRate, MeterGate, MeterSender, MeterSubscription};
+use crate::mixer::{self, Mixer};
+use crate::network::{NetworkCounters, NetworkPorts, NetworkSink, NetworkSource, PacketFormat};
+use crate::presence::{PresenceDetector, SignalStates};
+use crate::preview::{DevicePreview, PREVIEW_LEVEL_DB, PreviewFeed};
+use crate::recording;
+use crate::recovery::{AutoSnapshots, RecoveryWriter};
+use crate::replay::{REPLAY_TAP_BLOCKS, ReplayBuffer};
+use crate::resampler::CallbackResampler;
+use crate::route_meter::{RouteMeter, RouteSampler};
+use crate::rt_log::{RtEvent, RtLog, RtLogger};
+use crate::scheduler::{self, Scheduler};
+#[cfg(feature = "scripting")]
+use crate::script::{MixerView, ScriptHost};
+use crate::session::{self, SessionCounters, SessionTracker};
+use crate::setup::{self, DeviceInventory};
+use crate::snapshot::{MixerRuntimeState, RuntimeStateReader};
+use crate::streams::{StreamChanges, StreamKey, StreamSet, desired_streams};
+use crate::tap::{TapReader, TapServer, Taps};
+use crate::taper::VolumeDecibels;
+use crate::template::ChannelTemplateRegistry;
+use crate::watchdog::{self, Heartbeat, Watchdog, WatchdogAction};
+use crate::wav;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EngineState {
@@ -20,59 +111,63 @@ pub enum EngineState {
     Running,
 }
 
-/// Paramètres audio lus par le callback audio.
-///
-/// # Pourquoi une struct séparée ?
-/// Le callback audio tourne sur un thread OS haute priorité.
-/// Il ne peut PAS :
-/// - Allouer de la mémoire (malloc peut bloquer)
-/// - Prendre un Mutex qui pourrait être contesté longtemps
-/// - Faire de l'I/O
-///
-/// On utilise `try_lock()` (non-bloquant) : si le lock est pris,
-/// on garde les anciens paramètres. Ça skip UN frame audio (~5ms)
-/// → imperceptible à l'oreille.
+/// L'état partagé avec le callback audio vit dans `snapshot` ;
+/// ré-exporté ici car l'UI le récupère via `Engine::shared_mixer_state`.
+pub use crate::snapshot::SharedMixerState;
+
+/// Un stream audio réellement ouvert par le moteur.
 ///
-/// Les paramètres sont des f32 simples, pas de Vec ni String.
-/// Copie rapide, pas d'allocation.
-#[derive(Clone)]
-pub struct SharedMixerState {
-    /// Gain gauche/droite du canal d'entrée principal
-    gain: Arc<Mutex<(f32, f32)>>,
-    /// Mute global
-    muted: Arc<Mutex<bool>>,
+/// La config dit quel device l'utilisateur VEUT ; cette struct dit
+/// ce que le moteur a VRAIMENT ouvert (device par défaut, format négocié).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveStream {
+    pub device_name: String,
+    pub is_input: bool,
+    pub channels: u16,
+    pub sample_rate: u32,
+    /// Canaux du mixer servis par ce stream
+    pub target_channels: Vec<ChannelId>,
 }
 
-impl SharedMixerState {
-    fn new() -> Self {
-        // Gain par défaut : unity gain au centre (constant power pan)
-        // cos(π/4) = sin(π/4) = √2/2 ≈ 0.707
-        let default_gain = std::f32::consts::FRAC_PI_4;
-        Self {
-            gain: Arc::new(Mutex::new((default_gain.cos(), default_gain.sin()))),
-            muted: Arc::new(Mutex::new(false)),
-        }
-    }
-
-    /// Met à jour les gains depuis le mixer.
-    pub fn update_from_mixer(&self, mixer: &Mixer) {
-        // Prendre le gain effectif du premier canal d'entrée (Mic = ChannelId(0))
-        let (l, r) = mixer.effective_gain(ChannelId(0));
-        if let Ok(mut gain) = self.gain.lock() {
-            *gain = (l, r);
-        }
-        // Vérifier si tous les canaux sont muted
-        let all_muted = mixer.inputs().iter().all(|ch| ch.muted);
-        if let Ok(mut muted) = self.muted.lock() {
-            *muted = all_muted;
-        }
-    }
+/// Résultat de l'application d'un profil.
+#[derive(Debug, Default, PartialEq)]
+pub struct ProfileApplied {
+    /// Canaux écartés par la limite du mixer
+    pub skipped: Vec<ChannelId>,
+    /// Streams rouverts pour suivre les devices du profil (vide : aucun)
+    pub streams: StreamChanges,
 }
 
+/// Canal alimenté par le stream d'entrée du pipeline actuel.
+pub(crate) const PIPELINE_INPUT_CHANNEL: ChannelId = ChannelId(0);
+
+/// Réponse aux commandes de scripts d'un build sans la feature `scripting`.
+#[cfg(not(feature = "scripting"))]
+const SCRIPTING_UNAVAILABLE: &str = "scripting is not available in this build";
+
+/// Nombre d'ids de canal suivis par la détection de silence.
+const MAX_SIGNAL_CHANNELS: usize = 64;
+
+/// Blocs audio en transit entre le callback d'entrée et celui de sortie.
+const AUDIO_QUEUE: usize = 32;
+
+/// Points au plus d'une courbe de réponse (`RequestEqResponse`).
+const MAX_RESPONSE_POINTS: usize = 2048;
+
+/// `LevelUpdate` et trames binaires rendus par l'UI en attente de
+/// réutilisation.
+const METER_POOL: usize = 8;
+
 #[derive(Clone)]
 pub struct EngineChannels {
     pub command_tx: Sender<Command>,
     pub event_rx: Receiver<Event>,
+    /// Rend au callback un `Event::LevelUpdate` lu, qu'il réécrit au
+    /// lieu d'en allouer un. Facultatif : sans retour, chaque envoi
+    /// alloue.
+    pub level_update_tx: Sender<Vec<ChannelLevel>>,
+    /// Idem pour les trames `Event::MeterFrame`
+    pub meter_frame_tx: Sender<Vec<u8>>,
 }
 
 /// Le moteur audio principal.
@@ -96,6 +191,17 @@ pub struct EngineChannels {
 /// ```
 pub struct Engine {
     device_manager: DeviceManager,
+    /// Source des devices par défaut du système (`None` : le host du
+    /// `device_manager`). Remplaçable pour les tests.
+    default_source: Option<Box<dyn DefaultDeviceSource>>,
+    /// Devices simulés ouverts par le pipeline à la place des devices
+    /// cpal (`None` : les vrais)
+    #[cfg(test)]
+    fake_devices: Option<tests::FakeDevices>,
+    /// Défauts du système auxquels les streams ouverts ont été résolus.
+    system_defaults: SystemDefaults,
+    /// Prochaine relecture des défauts du système (horloge du watchdog).
+    next_default_check_ms: u64,
     command_rx: Receiver<Command>,
     event_tx: Sender<Event>,
     state: EngineState,
@@ -104,13 +210,181 @@ pub struct Engine {
     /// Chaîne DSP partagée avec le callback audio.
     /// `Arc<Mutex>` car le callback doit appeler `process_sample` (mutable).
     dsp_chain: Arc<Mutex<EffectsChain>>,
-    _streams: Vec<Stream>,
+    /// Chaînes de rechange envoyées au callback, gardées jusqu'à ce
+    /// qu'elles soient installées (voir `EffectsSwap`)
+    effects_swaps: Vec<Arc<EffectsSwap>>,
+    /// Effets du bus joué, avant et après son fader (même partage)
+    bus_effects: Arc<Mutex<BusEffects>>,
+    /// Config de la chaîne d'effets de chaque canal (absent = preset par défaut).
+    channel_effects: HashMap<ChannelId, EffectsPreset>,
+    /// Paramètres hors plage venus des commandes (les fichiers, eux,
+    /// sont toujours corrigés)
+    effect_param_policy: ParamPolicy,
+    /// État de l'UI (`[ui]` de la config), opaque pour le moteur
+    ui_state: UiState,
+    /// Langue des noms d'usine servis à l'UI
+    locale: Locale,
+    /// Origine des réglages surchargeables (`config_layers::resolve`)
+    effective_config: Vec<ConfigEntry>,
+    /// Bibliothèque de presets d'effets (`presets/effects/`).
+    effects_presets: EffectsPresetManager,
+    /// Templates de canaux (intégrés + `templates/`).
+    channel_templates: ChannelTemplateRegistry,
+    /// Profils de surfaces de contrôle (intégrés + `profiles/controls/`).
+    control_profiles: ControlProfileManager,
+    /// Liaisons MIDI/OSC en vigueur (un seul profil à la fois).
+    control_profile: Option<ControlProfile>,
+    /// Entrée de la file de commandes : une valeur de contrôle y est
+    /// remise, traduite, et suit le même chemin qu'une commande de l'UI.
+    control_tx: Sender<Command>,
+    /// Dossier des profils sauvés (`DiffPreset`, `ApplyPresetSelection`).
+    profiles_dir: PathBuf,
+    /// Journal des modifications (audit trail), alimenté par `process_commands`.
+    journal: ChangeJournal,
+    /// Courbe des faders de volume (`SetVolumeFader`).
+    fader_taper: FaderTaper,
+    /// Bus de repli du passthrough d'urgence (`None` : premier bus)
+    passthrough_bus: Option<ChannelId>,
+    /// Détection de silence par défaut (surchargeable par canal).
+    signal_presence: SignalPresenceConfig,
+    /// État de présence de chaque canal, écrit par le callback audio.
+    signal_states: Arc<SignalStates>,
+    /// Canaux dont le traitement a paniqué, écrit par le callback audio
+    channel_faults: Arc<ChannelFaults>,
+    /// Streams ouverts : handles et description (diagnostic, graphe).
+    streams: StreamSet<PipelineStream>,
+    /// Repli joué par l'entrée du pipeline faute de son device
+    /// (`None` : l'entrée est sur son device)
+    input_fallback: Option<ActiveFallback>,
+    /// Porte de bascule du pipeline ouvert (voir `handover`)
+    pipeline_gate: Arc<HandoverGate>,
+    /// File entre les deux streams du pipeline ouvert
+    pipeline_link: Option<PipelineLink>,
+    /// Tranches des bus que rend son entrée (`bus_lanes`)
+    pipeline_lanes: Vec<(ChannelId, OutputSlot)>,
+    /// Blocs pleins que le nouveau pipeline doit livrer avant la bascule
+    /// (0 : fermer l'ancien d'abord, sans pré-roll)
+    preroll_blocks: u32,
+    /// Ancien pipeline encore ouvert pendant une bascule
+    handover: Option<PendingHandover>,
+    /// Envoi et réception d'audio par le réseau, branchés sur le pipeline
+    network: Arc<Mutex<NetworkPorts>>,
+    /// Compteurs du réseau, lus sans le lock de `network`
+    network_counters: Arc<NetworkCounters>,
+    /// Copies des bus pour d'autres consommateurs, remplies par le pipeline
+    taps: Arc<Mutex<Taps>>,
+    /// Taps servis en TCP local, un thread chacun
+    tap_servers: Vec<TapServer>,
+    /// Dernières secondes des bus gardées en mémoire, par id de bus
+    replays: Vec<ReplayBuffer>,
+    replay_config: ReplayConfig,
+    /// Blocs et crête livrés par l'entrée, relevés à chaque tick
+    input_activity: Arc<InputActivity>,
+    /// Veille de la boucle de contrôle quand rien ne joue
+    idle: IdleDetector,
+    /// Callbacks de sortie servis (en partie) en silence faute d'audio.
+    underruns: Arc<AtomicU64>,
+    /// Réserve visée devant la sortie, en blocs du device.
+    target_latency_blocks: u32,
+    /// Réserve et corrections du régulateur de latence de sortie.
+    latency: Arc<LatencyCounters>,
+    /// Dernière sonie mesurée par le callback sur le bus joué.
+    loudness: Arc<SharedLoudness>,
+    /// Derniers niveaux des canaux traités par le callback (scripts).
+    levels: Arc<SharedLevels>,
+    /// Historique des niveaux des canaux suivis (`SetLevelHistory`)
+    level_history: HashMap<ChannelId, LevelHistory>,
+    level_history_config: LevelHistoryConfig,
+    /// Réglage guidé des niveaux en cours (`auto_level_channels`)
+    auto_level: Option<AutoLevel>,
+    /// Écoute d'un device avant son assignation (`start_device_preview`)
+    preview: DevicePreview<PipelineStream>,
+    /// Signal de l'écoute, mélangé par le pipeline au bus d'écoute
+    preview_feed: Arc<Mutex<Option<PreviewFeed>>>,
+    /// Bus d'écoute par son nom (`None` : le bus joué)
+    preview_bus: Option<String>,
+    /// Niveaux en trames binaires plutôt qu'en `LevelUpdate`, lu par le
+    /// callback (`SetMeterTransport`)
+    binary_meters: Arc<AtomicBool>,
+    /// Canaux affichés par l'UI et pause des meters, lus par le callback
+    meter_subscription: Arc<MeterSubscription>,
+    /// Niveaux envoyés par seconde (`audio_stats`)
+    meter_rate: EmissionRate,
+    /// Canaux des trames binaires du dernier pipeline démarré
+    meter_layout: Option<MeterLayout>,
+    next_meter_layout: u32,
+    /// Bus joué par le pipeline en cours (`None` moteur arrêté)
+    played_bus: Option<ChannelId>,
+    /// Niveaux et trames rendus par l'UI (`EngineChannels`)
+    level_update_pool: Receiver<Vec<ChannelLevel>>,
+    meter_frame_pool: Receiver<Vec<u8>>,
+    /// Instantané de secours de l'état non sauvé (désactivé par défaut).
+    recovery: RecoveryWriter,
+    /// Instantanés pris avant de remplacer l'état (désactivés par défaut).
+    auto_snapshots: AutoSnapshots,
+    /// Sample rate demandé pour les streams (si le device le supporte).
+    sample_rate: SampleRate,
+    /// `true` si l'utilisateur a choisi `sample_rate` pendant la session
+    /// (`Command::SetSampleRate`) : il passe alors avant le cache des
+    /// devices. Sinon c'est le défaut du moteur, après le cache.
+    sample_rate_explicit: bool,
+    /// Taille de buffer demandée aux streams (si le device l'accepte).
+    buffer_size: BufferSize,
+    /// `true` si l'utilisateur a choisi `buffer_size` pendant la session
+    /// (`Command::SetBufferSize`), comme `sample_rate_explicit`.
+    buffer_size_explicit: bool,
+    /// Réglages de stream appris par device (`device_cache.toml`).
+    device_cache: DeviceCache,
+    /// Fichier du cache (`None` : gardé en mémoire seulement).
+    device_cache_path: Option<PathBuf>,
+    /// Sample rate auquel tournent réellement les effets (celui de l'entrée).
+    processing_rate: f32,
+    /// Dither de la conversion vers une sortie 16 bits.
+    dither: DitherMode,
+    /// Machine active (`None` : profils machine non configurés, les
+    /// devices restent dans les profils de mixer).
+    machine: Option<String>,
+    /// Devices de chaque machine connue (`[profiles."<hostname>"]`).
+    machine_profiles: BTreeMap<String, MachineProfile>,
+    /// Événements des callbacks audio, formatés dans `tracing` par
+    /// `process_commands` (jamais depuis le thread audio).
+    rt_log: RtLog,
+    /// Rampes d'automation terminées par le callback, pas encore relevées
+    /// (le thread de contrôle ne prend le lock des effets que si > 0).
+    finished_ramps: Arc<AtomicUsize>,
+    /// Ensembles d'actions nommés ("BRB", heures calmes).
+    action_sets: BTreeMap<String, ActionSet>,
+    /// Routages nommés (`[routing_snapshots]`), routes seules
+    routing_snapshots: BTreeMap<String, RoutingSnapshot>,
+    /// Presets d'effets favoris (`favorites`), dans leur ordre
+    favorite_presets: FavoritePresets,
+    /// Effets chronométrés dans le callback (`SetDspProfiling`), repris
+    /// par chaque chaîne reconstruite
+    dsp_profiling: bool,
+    /// Déclencheurs quotidiens des ensembles d'actions.
+    scheduler: Scheduler,
+    /// Réglages des scripts (`[scripting]`) d'un build sans la feature
+    /// `scripting`, gardés tels quels pour la sauvegarde
+    #[cfg(not(feature = "scripting"))]
+    scripting: ScriptingConfig,
+    /// Scripts d'automatisation actifs, exécutés à la fin de
+    /// `process_commands`
+    #[cfg(feature = "scripting")]
+    scripts: ScriptHost,
+    /// Détection des callbacks audio bloqués (heartbeats des streams).
+    watchdog: Watchdog,
+    /// Durée, charge et crêtes de la session (`RequestSessionStats`).
+    session: SessionTracker,
+    /// Activité des routes, si activée (`SetRouteMetering`).
+    route_meter: Arc<RouteMeter>,
 }
 
 impl Engine {
     pub fn new() -> (Self, EngineChannels) {
         let (command_tx, command_rx) = crossbeam_channel::bounded(64);
         let (event_tx, event_rx) = crossbeam_channel::bounded(256);
+        let (level_update_tx, level_update_pool) = crossbeam_channel::bounded(METER_POOL);
+        let (meter_frame_tx, meter_frame_pool) = crossbeam_channel::bounded(METER_POOL);
 
         let mixer = Mixer::from_config(MixerConfig::default_setup());
         let shared_state = SharedMixerState::new();
@@ -121,18 +395,106 @@ impl Engine {
 
         let engine = Self {
             device_manager: DeviceManager::new(),
+            default_source: None,
+            #[cfg(test)]
+            fake_devices: None,
+            system_defaults: SystemDefaults::default(),
+            next_default_check_ms: 0,
             command_rx,
             event_tx,
             state: EngineState::Stopped,
             mixer,
             shared_state,
             dsp_chain,
-            _streams: Vec::new(),
+            effects_swaps: Vec::new(),
+            bus_effects: Arc::new(Mutex::new(BusEffects::new())),
+            channel_effects: HashMap::new(),
+            effect_param_policy: ParamPolicy::Reject,
+            ui_state: UiState::default(),
+            locale: Locale::default(),
+            effective_config: Vec::new(),
+            effects_presets: EffectsPresetManager::default(),
+            control_profiles: ControlProfileManager::default(),
+            control_profile: None,
+            control_tx: command_tx.clone(),
+            channel_templates: ChannelTemplateRegistry::default(),
+            profiles_dir: Profile::profiles_dir(),
+            journal: ChangeJournal::from_config(&JournalConfig::default()),
+            fader_taper: FaderTaper::default(),
+            passthrough_bus: None,
+            signal_presence: SignalPresenceConfig::default(),
+            signal_states: Arc::new(SignalStates::new(MAX_SIGNAL_CHANNELS)),
+            channel_faults: Arc::new(ChannelFaults::new(MAX_SIGNAL_CHANNELS)),
+            streams: StreamSet::new(),
+            input_fallback: None,
+            pipeline_gate: HandoverGate::active(),
+            pipeline_link: None,
+            pipeline_lanes: Vec::new(),
+            preroll_blocks: AudioConfig::default().preroll_blocks,
+            handover: None,
+            network: Arc::new(Mutex::new(NetworkPorts::default())),
+            network_counters: Arc::new(NetworkCounters::default()),
+            taps: Arc::new(Mutex::new(Taps::default())),
+            tap_servers: Vec::new(),
+            replays: Vec::new(),
+            replay_config: ReplayConfig::default(),
+            input_activity: Arc::default(),
+            idle: IdleDetector::new(
+                AudioConfig::default().idle_after_seconds,
+                AudioConfig::default().idle_threshold_db,
+            ),
+            underruns: Arc::new(AtomicU64::new(0)),
+            target_latency_blocks: AudioConfig::default().target_output_latency_blocks,
+            latency: Arc::new(LatencyCounters::default()),
+            loudness: Arc::new(SharedLoudness::new()),
+            levels: Arc::new(SharedLevels::new(MAX_SIGNAL_CHANNELS)),
+            level_history: HashMap::new(),
+            level_history_config: LevelHistoryConfig::default(),
+            auto_level: None,
+            preview: DevicePreview::default(),
+            preview_feed: Arc::new(Mutex::new(None)),
+            preview_bus: None,
+            binary_meters: Arc::new(AtomicBool::new(false)),
+            meter_subscription: Arc::new(MeterSubscription::new(MAX_SIGNAL_CHANNELS)),
+            meter_rate: EmissionRate::default(),
+            meter_layout: None,
+            next_meter_layout: 0,
+            played_bus: None,
+            level_update_pool,
+            meter_frame_pool,
+            recovery: RecoveryWriter::disabled(),
+            auto_snapshots: AutoSnapshots::disabled(),
+            sample_rate: SampleRate::default(),
+            sample_rate_explicit: false,
+            buffer_size: BufferSize::default(),
+            buffer_size_explicit: false,
+            device_cache: DeviceCache::default(),
+            device_cache_path: None,
+            processing_rate: SampleRate::default().as_hz() as f32,
+            dither: DitherMode::default(),
+            machine: None,
+            machine_profiles: BTreeMap::new(),
+            rt_log: RtLog::default(),
+            finished_ramps: Arc::new(AtomicUsize::new(0)),
+            action_sets: BTreeMap::new(),
+            routing_snapshots: BTreeMap::new(),
+            favorite_presets: FavoritePresets::default(),
+            dsp_profiling: false,
+            scheduler: Scheduler::default(),
+            #[cfg(not(feature = "scripting"))]
+            scripting: ScriptingConfig::default(),
+            #[cfg(feature = "scripting")]
+            scripts: ScriptHost::new(ScriptingConfig::default_dir(), &ScriptingConfig::default()),
+            watchdog: Watchdog::new(WatchdogConfig::default()),
+            session: SessionTracker::new(MAX_SIGNAL_CHANNELS),
+            route_meter: Arc::new(RouteMeter::new(MAX_SIGNAL_CHANNELS)),
         };
 
         let channels = EngineChannels {
             command_tx,
             event_rx,
+            level_update_tx,
+            meter_frame_tx,
         };
 
         (engine, channels)
@@ -146,544 +508,12573 @@ impl Engine {
 
         info!("Starting audio engine...");
 
-        let input_device = self
-            .device_manager
-            .default_input_name()
+        let (preferred_input, preferred_output) = self
+            .active_machine_profile()
+            .map(|p| (p.input_device.clone(), p.output_device.clone()))
+            .unwrap_or_default();
+
+        // Un sens qui suit le défaut du système ignore le device préféré
+        let defaults = self.current_system_defaults();
+        let primary_input = preferred_input.filter(|_| !self.follows_system_default(true));
+        let input = self
+            .resolve_pipeline_input(primary_input.as_deref(), &defaults)
             .ok_or_else(|| TroubadourError::DeviceNotFound("No default input device".into()))?;
 
-        let output_device = self
-            .device_manager
-            .default_output_name()
+        let output_device = preferred_output
+            .filter(|_| !self.follows_system_default(false))
+            .filter(|name| self.device_available(name, false))
+            .or_else(|| defaults.output.clone())
             .ok_or_else(|| TroubadourError::DeviceNotFound("No default output device".into()))?;
+        let previous = std::mem::replace(&mut self.system_defaults, defaults);
 
-        info!("Input: {input_device}, Output: {output_device}");
+        info!("Input: {}, Output: {output_device}", input.source);
 
         self.shared_state.update_from_mixer(&self.mixer);
-        self.start_audio_pipeline(&input_device, &output_device)?;
+        if let Err(e) =
+            self.start_audio_pipeline(&input.source, &output_device, HandoverGate::active())
+        {
+            self.system_defaults = previous;
+            return Err(e);
+        }
+        self.record_pipeline_input(primary_input, input);
 
         self.state = EngineState::Running;
+        self.session.engine_started(Instant::now());
         let _ = self.event_tx.try_send(Event::EngineStarted);
         info!("Audio engine started");
 
         Ok(())
     }
 
+    /// Devices dont `start` a besoin et qui manquent : le device préféré
+    /// du profil machine (sauf dans un sens qui suit le défaut du
+    /// système), sinon le défaut du système. Vide : `start` peut ouvrir
+    /// les streams.
+    ///
+    /// `start` se rabat sur le défaut du système quand le device préféré
+    /// manque ; un service qui ne doit pas jouer ailleurs vérifie avant.
+    /// Une entrée avec un repli explicite (`InputFallback`) ne manque pas :
+    /// l'utilisateur a dit quoi jouer à sa place.
+    pub fn missing_devices(&self) -> Vec<String> {
+        let (preferred_input, preferred_output) = self
+            .active_machine_profile()
+            .map(|p| (p.input_device.clone(), p.output_device.clone()))
+            .unwrap_or_default();
+        let defaults = self.current_system_defaults();
+        let mut missing = Vec::new();
+        for (is_input, preferred, default) in [
+            (true, preferred_input, defaults.input),
+            (false, preferred_output, defaults.output),
+        ] {
+            if is_input && !self.pipeline_input_policy().is_system_default() {
+                continue;
+            }
+            let found = |name: &str| self.device_present(name, is_input);
+            match preferred.filter(|_| !self.follows_system_default(is_input)) {
+                Some(name) if !found(&name) => missing.push(name),
+                Some(_) => {}
+                None if default.is_none() => missing.push(
+                    match is_input {
+                        true => "default input device",
+                        false => "default output device",
+                    }
+                    .to_string(),
+                ),
+                None => {}
+            }
+        }
+        missing
+    }
+
     /// Construit le pipeline audio complet.
     ///
     /// # Le flux audio
     /// 1. cpal capture le micro (peut être mono ou stéréo)
     /// 2. On convertit en stéréo si nécessaire
-    /// 3. On applique le gain (volume × pan) depuis SharedMixerState
+    /// 3. On applique le gain (volume × pan) depuis le snapshot runtime
     /// 4. On envoie le résultat au output stream
     /// 5. On calcule les niveaux pour le VU-meter
+    ///
+    /// `gate` : porte de bascule du pipeline (`HandoverGate::standby`
+    /// pour un pré-roll à côté du pipeline en place).
     fn start_audio_pipeline(
         &mut self,
-        input_name: &str,
+        input: &InputSource,
         output_name: &str,
+        gate: Arc<HandoverGate>,
     ) -> TroubadourResult<()> {
-        let input_device = self.device_manager.find_input_device(input_name)?;
-        let output_device = self.device_manager.find_output_device(output_name)?;
+        self.pipeline_gate = gate;
+        // Channel pour transférer l'audio traité de l'input vers l'output.
+        // Toujours stéréo après traitement (2 f32 par frame). Les buffers
+        // consommés par la sortie reviennent par `recycle` : une fois le
+        // pipeline chaud, plus aucune allocation d'un callback à l'autre.
+        let link = PipelineLink::new();
 
-        let input_config = input_device
-            .default_input_config()
-            .map_err(|e| TroubadourError::StreamError(e.to_string()))?;
+        let mut input = self.open_pipeline_input(input, output_name, &link)?;
+        let mut output = self.open_pipeline_output(output_name, input.info.sample_rate, &link)?;
 
-        let input_channels = input_config.channels() as usize;
+        // Démarrer les streams
+        input
+            .play()
+            .inspect_err(|e| self.report_stream_failure(e))?;
+        output
+            .play()
+            .inspect_err(|e| self.report_stream_failure(e))?;
 
-        info!(
-            "Input: {} ch, {} Hz, {:?}",
-            input_channels,
-            input_config.sample_rate().0,
-            input_config.sample_format()
+        self.watchdog.watch(
+            vec![input.watched(), output.watched()],
+            watchdog::monotonic_ms(),
         );
+        self.pipeline_link = Some(link);
+        self.streams.push(input.info, input.stream);
+        self.streams.push(output.info, output.stream);
 
-        // Channel pour transférer l'audio traité de l'input vers l'output.
-        // Toujours stéréo après traitement (2 f32 par frame).
-        let (audio_tx, audio_rx) = crossbeam_channel::bounded::<Vec<f32>>(32);
+        Ok(())
+    }
 
-        let event_tx = self.event_tx.clone();
-        let shared = self.shared_state.clone();
-        let dsp = self.dsp_chain.clone();
+    /// Construit (sans le démarrer) le stream d'entrée du pipeline, qui
+    /// traite `input` pour les bus joués par `output_name` et livre le
+    /// résultat dans `link`.
+    fn open_pipeline_input(
+        &mut self,
+        input: &InputSource,
+        output_name: &str,
+        link: &PipelineLink,
+    ) -> TroubadourResult<OpenedStream> {
+        let input_name = input.to_string();
+        let feed = match input {
+            InputSource::Device(name) => {
+                let device = self.pipeline_device(name, true)?;
+                let config = device.default_config()?;
+                let preferred = self.preferred_rate(name, true);
+                let config = config_at_rate(config, device.supported_configs(), preferred);
+                if config.sample_rate().0 != preferred {
+                    info!(
+                        "Input {name} does not support {preferred} Hz: opened at {} Hz",
+                        config.sample_rate().0
+                    );
+                }
+                InputFeed::Device(device, config)
+            }
+            InputSource::File(_) | InputSource::Silence => self.looped_feed(input),
+        };
 
-        // ── INPUT STREAM ──
-        let input_stream = match input_config.sample_format() {
-            SampleFormat::F32 => {
-                let config: cpal::StreamConfig = input_config.into();
-                input_device
-                    .build_input_stream(
-                        &config,
-                        move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                            if data.is_empty() {
-                                return;
-                            }
+        // Le device est ouvert avec TOUS ses canaux : chaque canal du
+        // mixer y lit ensuite sa propre tranche (`device_channel_offset`).
+        let (channels, sample_rate) = match &feed {
+            InputFeed::Device(_, config) => (config.channels(), config.sample_rate().0),
+            InputFeed::Looped(audio, rate) => (audio.channels(), *rate),
+        };
+        let input_channels = channels as usize;
+        let input_stream_info = ActiveStream {
+            device_name: input_name.clone(),
+            is_input: true,
+            channels,
+            sample_rate,
+            target_channels: vec![PIPELINE_INPUT_CHANNEL],
+        };
 
-                            // Lire les gains (non-bloquant).
-                            // Si le lock est pris → on garde les gains du frame précédent.
-                            // C'est la magie du `try_lock` : JAMAIS de blocage dans le
-                            // callback audio. Pire cas = un frame avec les anciens gains.
-                            let (gain_l, gain_r) =
-                                shared.gain.try_lock().map(|g| *g).unwrap_or((0.707, 0.707));
-
-                            let muted = shared.muted.try_lock().map(|m| *m).unwrap_or(false);
-
-                            // Construire la sortie stéréo avec gain appliqué.
-                            // Pré-allouer pour éviter les réallocations.
-                            let frame_count = data.len() / input_channels;
-                            let mut output = Vec::with_capacity(frame_count * 2);
-
-                            if muted {
-                                output.resize(frame_count * 2, 0.0);
-                            } else {
-                                // Pipeline audio v0.3 :
-                                // 1. Downmix vers mono
-                                // 2. DSP chain (gate → compressor → limiter)
-                                // 3. Appliquer gain L/R (volume × pan)
-
-                                // try_lock sur la chaîne DSP (non-bloquant)
-                                let mut dsp_guard = dsp.try_lock().ok();
-
-                                for frame in data.chunks(input_channels) {
-                                    // 1. Downmix vers mono
-                                    let mut mono: f32 =
-                                        frame.iter().sum::<f32>() / input_channels as f32;
-
-                                    // 2. DSP processing
-                                    if let Some(ref mut chain) = dsp_guard {
-                                        mono = chain.process_sample(mono);
-                                    }
-
-                                    // 3. Appliquer volume + pan
-                                    output.push(mono * gain_l);
-                                    output.push(mono * gain_r);
-                                }
-                            }
+        info!("Input: {input_name}, {input_channels} ch, {sample_rate} Hz");
 
-                            // VU-meter : calculer RMS et peak sur le signal traité
-                            let rms = (output.iter().map(|&s| s * s).sum::<f32>()
-                                / output.len().max(1) as f32)
-                                .sqrt();
-                            let peak = output.iter().map(|s| s.abs()).fold(0.0_f32, f32::max);
+        // Les effets tournent au rate de l'entrée : coefficients recalculés
+        // AVANT que le callback ne traite le premier sample.
+        self.set_processing_rate(input_stream_info.sample_rate as f32);
 
-                            let _ = event_tx.try_send(Event::LevelUpdate(vec![ChannelLevel {
-                                channel: ChannelId(0),
-                                rms,
-                                peak,
-                            }]));
+        let mut processor = self.input_processor(
+            input_channels,
+            input_stream_info.sample_rate as f32,
+            output_name,
+            link.audio_tx.clone(),
+            link.recycle_rx.clone(),
+        );
+        self.pipeline_lanes = bus_lanes(&self.mixer, &processor.output_buses);
+        let encoder = self.new_meter_layout(processor.output_bus);
+        let mut meters = MeterSender::new(
+            self.binary_meters.clone(),
+            self.meter_subscription.clone(),
+            self.event_tx.clone(),
+            encoder,
+            self.level_update_pool.clone(),
+            self.meter_frame_pool.clone(),
+        );
+        // Un battement par callback : le watchdog voit un callback figé
+        let beat = Heartbeat::default();
+        let heartbeat = beat.clone();
 
-                            let _ = audio_tx.try_send(output);
-                        },
-                        move |err| error!("Input stream error: {err}"),
-                        None,
-                    )
-                    .map_err(|e| TroubadourError::StreamError(e.to_string()))?
+        let on_block = move |data: &[f32]| {
+            heartbeat.beat();
+            let level = processor.process_block_guarded(data);
+            if data.is_empty() {
+                return;
             }
-            format => {
-                return Err(TroubadourError::StreamError(format!(
-                    "Unsupported format: {format:?}. Only F32 supported."
-                )));
+            meters.send(level.into_iter().chain(processor.take_bus_level()));
+            processor.send_block();
+        };
+
+        // ── INPUT STREAM ──
+        let stream = match feed {
+            InputFeed::Device(input_device, input_config) => {
+                let buffer = self.preferred_buffer(&input_name, true, &input_config);
+                let input_settings = stream_settings(&input_config, buffer);
+                let stream = match input_config.sample_format() {
+                    SampleFormat::F32 => {
+                        input_device.build_input(&stream_config(input_config, buffer), on_block)
+                    }
+                    format => {
+                        return Err(TroubadourError::StreamError(format!(
+                            "Unsupported format: {format:?}. Only F32 supported."
+                        )));
+                    }
+                };
+                self.remember_stream_open(
+                    &input_name,
+                    true,
+                    input_settings,
+                    || supported_rates(input_device.supported_configs().into_iter().flatten()),
+                    stream,
+                )?
             }
+            InputFeed::Looped(audio, rate) => PipelineStream::Clocked(ClockedInput::new(
+                audio,
+                rate,
+                BufferSize::default().as_frames() as usize,
+                on_block,
+            )),
         };
+        Ok(OpenedStream {
+            info: input_stream_info,
+            stream,
+            beat,
+        })
+    }
 
-        // ── OUTPUT STREAM ──
-        let output_config = output_device
-            .default_output_config()
-            .map_err(|e| TroubadourError::StreamError(e.to_string()))?;
+    /// Construit (sans le démarrer) le stream de sortie du pipeline, qui
+    /// lit dans `link` l'audio traité au rate `input_rate`.
+    fn open_pipeline_output(
+        &mut self,
+        output_name: &str,
+        input_rate: u32,
+        link: &PipelineLink,
+    ) -> TroubadourResult<OpenedStream> {
+        let output_device = self.pipeline_device(output_name, false)?;
+        let output_config = output_device.default_config()?;
+        let preferred = self.preferred_rate(output_name, false);
+        let output_config =
+            config_at_rate(output_config, output_device.supported_configs(), preferred);
+        if output_config.sample_rate().0 != preferred {
+            info!(
+                "Output {output_name} does not support {preferred} Hz: opened at {} Hz",
+                output_config.sample_rate().0
+            );
+        }
+        // Bus mono : un stream mono si le device en propose un ; bus
+        // placés : assez de canaux pour atteindre la plus haute tranche
+        let output_buses = stream_channels(&self.mixer, output_name, false, &self.system_defaults);
+        let bus_config = output_buses
+            .first()
+            .and_then(|&bus| self.mixer.channel(bus));
+        let bus_count = bus_config.map_or_else(ChannelCount::default, |c| c.channel_count);
+        let lanes = bus_lanes(&self.mixer, &output_buses);
+        let output_config = config_with_channels(
+            output_config,
+            output_device.supported_configs(),
+            bus_count,
+            lanes
+                .iter()
+                .map(|&(_, slot)| slot)
+                .max_by_key(|slot| slot.end()),
+        );
 
         let out_channels = output_config.channels() as usize;
+        for (i, &(bus, slot)) in lanes.iter().enumerate() {
+            if slot.end() > out_channels {
+                let fallback = if i == 0 {
+                    "copied to every pair"
+                } else {
+                    "not played"
+                };
+                warn!(
+                    "Output {output_name} has {out_channels} ch, bus {} needs {}: {fallback}",
+                    bus.0,
+                    slot.end()
+                );
+            }
+        }
+        let output_stream_info = ActiveStream {
+            device_name: output_name.to_string(),
+            is_input: false,
+            channels: output_config.channels(),
+            sample_rate: output_config.sample_rate().0,
+            target_channels: output_buses.clone(),
+        };
         info!(
             "Output: {} ch, {} Hz",
             out_channels,
             output_config.sample_rate().0
         );
 
-        let output_stream = output_device
-            .build_output_stream(
-                &output_config.into(),
-                move |output: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                    match audio_rx.try_recv() {
-                        Ok(stereo_data) => {
-                            // stereo_data est toujours [L, R, L, R, ...]
-                            let in_frames = stereo_data.len() / 2;
-                            let out_frames = output.len() / out_channels;
-                            let frames = in_frames.min(out_frames);
-
-                            for f in 0..frames {
-                                let l = stereo_data[f * 2];
-                                let r = stereo_data[f * 2 + 1];
-
-                                // Mapper stéréo vers N canaux de sortie
-                                for ch in 0..out_channels {
-                                    output[f * out_channels + ch] = if ch % 2 == 0 { l } else { r };
-                                }
-                            }
-                            // Remplir le reste avec du silence
-                            let written = frames * out_channels;
-                            for s in &mut output[written..] {
-                                *s = 0.0;
-                            }
-                        }
-                        Err(_) => output.fill(0.0),
-                    }
-                },
-                move |err| error!("Output stream error: {err}"),
-                None,
-            )
-            .map_err(|e| TroubadourError::StreamError(e.to_string()))?;
-
-        // Démarrer les streams
-        input_stream
-            .play()
-            .map_err(|e| TroubadourError::StreamError(e.to_string()))?;
-        output_stream
-            .play()
-            .map_err(|e| TroubadourError::StreamError(e.to_string()))?;
-
-        self._streams.push(input_stream);
-        self._streams.push(output_stream);
-
-        Ok(())
-    }
-
-    /// Traite les commandes de l'UI.
-    pub fn process_commands(&mut self) {
-        let mut changed = false;
-
-        while let Ok(cmd) = self.command_rx.try_recv() {
-            match cmd {
-                Command::SetVolume { channel, level } => {
-                    self.mixer.set_volume(channel, level);
-                    changed = true;
-                }
-                Command::SetMute { channel, muted } => {
-                    self.mixer.set_mute(channel, muted);
-                    changed = true;
-                }
-                Command::SetSolo { channel, solo } => {
-                    self.mixer.set_solo(channel, solo);
-                    changed = true;
-                }
-                Command::SetPan { channel, pan } => {
-                    self.mixer.set_pan(channel, pan);
-                    changed = true;
-                }
-                Command::AddRoute { from, to } => {
-                    self.mixer.add_route(from, to);
-                    changed = true;
-                }
-                Command::RemoveRoute { from, to } => {
-                    self.mixer.remove_route(from, to);
-                    changed = true;
-                }
-                Command::RequestDeviceList => {
-                    self.send_device_list();
-                }
-                Command::Shutdown => {
-                    self.stop();
-                    return;
-                }
-                _ => {
-                    warn!("Unhandled command: {cmd:?}");
+        // Pont input → output : accumule, convertit le sample rate si les
+        // deux devices diffèrent, et livre exactement ce que le device demande.
+        let bridge = CallbackResampler::new(
+            input_rate,
+            output_stream_info.sample_rate,
+            2 * lanes.len().max(1),
+            BufferSize::default().as_frames() as usize,
+        )?;
+        if !bridge.is_passthrough() {
+            info!(
+                "Resampling {} Hz → {} Hz",
+                input_rate, output_stream_info.sample_rate
+            );
+        }
+        // Nouveau pipeline : un bus réduit au silence rejoue
+        if let Some(bus) = bus_config {
+            self.channel_faults.set(bus.id, ChannelHealth::Ok);
+        }
+        let mut renderer = OutputRenderer {
+            audio_rx: link.audio_rx.clone(),
+            recycle_tx: link.recycle_tx.clone(),
+            bridge,
+            out_channels,
+            // Le bus joué hors du device : son stéréo sur toutes les paires
+            slots: match lanes.first() {
+                Some(&(_, slot)) if slot.end() <= out_channels => {
+                    lanes.iter().map(|&(_, slot)| slot).collect()
                 }
+                _ => Vec::new(),
+            },
+            lanes: lanes.len().max(1),
+            block: Vec::new(),
+            underruns: self.underruns.clone(),
+            governor: LatencyGovernor::new(self.target_latency_blocks, self.latency.clone()),
+            rt_log: self.rt_log.logger(),
+            handover: HandoverFader::new(
+                self.pipeline_gate.clone(),
+                output_stream_info.sample_rate as f32,
+            ),
+            bus: bus_config.map(|c| c.id),
+            faults: self.channel_faults.clone(),
+            event_tx: self.event_tx.clone(),
+            failed: false,
+        };
+
+        let buffer = self.preferred_buffer(output_name, false, &output_config);
+        let output_settings = stream_settings(&output_config, buffer);
+        let sample_format = output_config.sample_format();
+        let stream_config = stream_config(output_config, buffer);
+        let beat = Heartbeat::default();
+        let heartbeat = beat.clone();
+        let output_stream = match sample_format {
+            SampleFormat::F32 => {
+                output_device.build_output(&stream_config, move |output: &mut [f32]| {
+                    heartbeat.beat();
+                    renderer.render_guarded(output);
+                })
             }
-        }
+            SampleFormat::I16 => {
+                // Rendu en f32, puis conversion avec dither (buffer réutilisé)
+                let mut ditherer = Ditherer::new(self.dither, out_channels, 0x5EED_D17E);
+                let mut mix: Vec<f32> = Vec::new();
+                info!("Output is 16-bit, dither: {:?}", ditherer.mode());
+                output_device.build_output(&stream_config, move |output: &mut [i16]| {
+                    heartbeat.beat();
+                    mix.resize(output.len(), 0.0);
+                    renderer.render_guarded(&mut mix);
+                    ditherer.convert_i16(&mix, output);
+                })
+            }
+            format => {
+                return Err(TroubadourError::StreamError(format!(
+                    "Unsupported output format: {format:?}. Only F32 and I16 supported."
+                )));
+            }
+        };
+        let stream = self.remember_stream_open(
+            output_name,
+            false,
+            output_settings,
+            || supported_rates(output_device.supported_configs().into_iter().flatten()),
+            output_stream,
+        )?;
+        Ok(OpenedStream {
+            info: output_stream_info,
+            stream,
+            beat,
+        })
+    }
 
-        if changed {
-            self.shared_state.update_from_mixer(&self.mixer);
+    /// Device d'un stream du pipeline, trouvé par son nom.
+    fn pipeline_device(&self, name: &str, is_input: bool) -> TroubadourResult<PipelineDevice> {
+        #[cfg(test)]
+        if let Some(fake) = &self.fake_devices {
+            return fake.device(name, is_input).map(PipelineDevice::Fake);
         }
+        let device = match is_input {
+            true => self.device_manager.find_input_device(name)?,
+            false => self.device_manager.find_output_device(name)?,
+        };
+        Ok(PipelineDevice::Cpal { device, is_input })
     }
 
-    fn send_device_list(&self) {
-        let inputs = self
-            .device_manager
-            .list_input_devices()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|d| d.name)
-            .collect();
-
-        let outputs = self
-            .device_manager
-            .list_output_devices()
-            .unwrap_or_default()
-            .into_iter()
-            .map(|d| d.name)
-            .collect();
-
-        let _ = self
-            .event_tx
-            .try_send(Event::DeviceList { inputs, outputs });
+    /// Sample rate à demander à un device : choix explicite de
+    /// l'utilisateur, sinon ce qui a marché la dernière fois, sinon le
+    /// défaut du moteur.
+    fn preferred_rate(&self, device: &str, is_input: bool) -> u32 {
+        let explicit = self.sample_rate_explicit.then_some(self.sample_rate);
+        self.device_cache
+            .preferred_rate(device, is_input, explicit, self.sample_rate)
     }
 
-    pub fn stop(&mut self) {
-        if self.state == EngineState::Stopped {
-            return;
+    /// Taille de buffer à imposer au stream d'un device, dans le même
+    /// ordre que le sample rate. `None` si le device ne l'accepte pas :
+    /// le driver choisit alors la sienne.
+    fn preferred_buffer(
+        &self,
+        device: &str,
+        is_input: bool,
+        config: &SupportedStreamConfig,
+    ) -> Option<u32> {
+        let explicit = self.buffer_size_explicit.then_some(self.buffer_size);
+        let frames =
+            self.device_cache
+                .preferred_buffer_frames(device, is_input, explicit, self.buffer_size);
+        match config.buffer_size() {
+            cpal::SupportedBufferSize::Range { min, max } if (*min..=*max).contains(&frames) => {
+                Some(frames)
+            }
+            _ => {
+                info!("{device} does not accept {frames} frames per buffer: driver default");
+                None
+            }
         }
-        info!("Stopping audio engine...");
-        self._streams.clear();
-        self.state = EngineState::Stopped;
-        let _ = self.event_tx.try_send(Event::EngineStopped);
-        info!("Audio engine stopped");
-    }
-
-    pub fn take_command_receiver(&mut self) -> Receiver<Command> {
-        self.command_rx.clone()
     }
 
-    pub fn take_event_sender(&mut self) -> Sender<Event> {
-        self.event_tx.clone()
+    /// Note dans le cache des devices l'ouverture (réussie ou non) d'un
+    /// stream, puis retourne le stream.
+    fn remember_stream_open<S>(
+        &mut self,
+        device: &str,
+        is_input: bool,
+        settings: StreamSettings,
+        supported: impl FnOnce() -> Vec<SampleRate>,
+        result: Result<S, cpal::BuildStreamError>,
+    ) -> TroubadourResult<S> {
+        let requested = settings.sample_rate;
+        match &result {
+            Ok(_) => self.device_cache.record_success(device, is_input, settings),
+            Err(e) => self
+                .device_cache
+                .record_failure(device, is_input, requested, &e.to_string()),
+        }
+        self.save_device_cache();
+        result.map_err(|e| {
+            let err = build_stream_error(device, is_input, &e, requested, &supported());
+            self.report_stream_failure(&err);
+            err
+        })
     }
 
-    pub fn state(&self) -> EngineState {
-        self.state
+    /// Signale à l'UI un stream refusé, avec sa cause et le conseil à
+    /// afficher (`Event::StreamFailed`).
+    fn report_stream_failure(&self, err: &TroubadourError) {
+        if let TroubadourError::StreamOpen { device, kind, .. } = err {
+            let _ = self.event_tx.try_send(Event::StreamFailed {
+                device_name: device.clone(),
+                kind: kind.clone(),
+                hint: err.hint().map(str::to_string),
+            });
+        }
     }
 
-    pub fn mixer(&self) -> &Mixer {
-        &self.mixer
+    fn save_device_cache(&self) {
+        if let Some(path) = &self.device_cache_path
+            && let Err(e) = self.device_cache.save(path)
+        {
+            warn!("Cannot save device cache to {}: {e}", path.display());
+        }
     }
 
-    pub fn shared_mixer_state(&self) -> SharedMixerState {
-        self.shared_state.clone()
+    /// Charge le cache des devices depuis `path`, où il sera réécrit à
+    /// chaque ouverture de stream. Un fichier illisible repart de zéro.
+    pub fn configure_device_cache(&mut self, path: PathBuf) {
+        self.device_cache = DeviceCache::load(&path).unwrap_or_else(|e| {
+            warn!("Ignoring device cache {}: {e}", path.display());
+            DeviceCache::default()
+        });
+        self.device_cache_path = Some(path);
     }
 
-    /// Retourne un handle vers la chaîne DSP partagée.
-    pub fn shared_dsp_chain(&self) -> Arc<Mutex<EffectsChain>> {
-        self.dsp_chain.clone()
+    pub fn device_cache(&self) -> &DeviceCache {
+        &self.device_cache
     }
-}
 
-impl Drop for Engine {
-    fn drop(&mut self) {
-        self.stop();
+    /// Oublie tout ce qui a été appris sur les devices.
+    pub fn clear_device_cache(&mut self) {
+        self.device_cache = DeviceCache::default();
+        self.save_device_cache();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Traitement du Mic pour le callback d'entrée, branché sur les bus
+    /// joués par `output_name`.
+    fn input_processor(
+        &self,
+        input_channels: usize,
+        sample_rate: f32,
+        output_name: &str,
+        audio_tx: Sender<Vec<f32>>,
+        recycle_rx: Receiver<Vec<f32>>,
+    ) -> InputProcessor {
+        let mut runtime = self.shared_state.reader();
 
-    #[test]
-    fn engine_starts_stopped() {
-        let (engine, _channels) = Engine::new();
-        assert_eq!(engine.state(), EngineState::Stopped);
-    }
+        // Ligne de retard du Mic : allouée ICI (1 s au sample rate de
+        // l'entrée), puis déplacée dans le callback. Un changement de
+        // sample rate reconstruit le pipeline, donc une nouvelle ligne.
+        let mut delay_line = DelayLine::new(sample_rate);
+        delay_line.set_delay_ms(runtime.current().delay_ms(PIPELINE_INPUT_CHANNEL));
+        let mut conditioner = InputConditioner::new(sample_rate);
+        conditioner.set_options(runtime.current().input_options(PIPELINE_INPUT_CHANNEL).1);
 
-    #[test]
-    fn engine_has_default_mixer() {
-        let (engine, _channels) = Engine::new();
-        assert_eq!(engine.mixer().channel_count(), 5);
-    }
+        // Détection de silence du Mic (réglages globaux, sauf surcharge du canal)
+        let global_presence = self.signal_presence;
+        let presence = PresenceDetector::new(
+            runtime
+                .current()
+                .signal_presence(PIPELINE_INPUT_CHANNEL)
+                .unwrap_or(global_presence),
+            sample_rate,
+        );
+        self.signal_states
+            .set(PIPELINE_INPUT_CHANNEL, presence.state());
+        // Nouveau pipeline : un canal réduit au silence rejoue
+        self.channel_faults
+            .set(PIPELINE_INPUT_CHANNEL, ChannelHealth::Ok);
 
-    #[test]
-    fn engine_processes_volume_command() {
-        let (mut engine, channels) = Engine::new();
-        channels
-            .command_tx
-            .send(Command::SetVolume {
-                channel: ChannelId(0),
-                level: 0.5,
-            })
-            .unwrap();
-        engine.process_commands();
-        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.5);
-    }
+        // Bus(es) joués par le device de sortie : s'ils ne sont pas
+        // dans le solo de bus en cours, le device reçoit du silence.
+        let output_buses = stream_channels(&self.mixer, output_name, false, &self.system_defaults);
+        let output_bus = output_buses.first().copied();
 
-    #[test]
-    fn engine_volume_updates_shared_state() {
-        let (mut engine, channels) = Engine::new();
+        // Gain du bus joué : lissé, et rampe des fondus. Avancé par le
+        // callback (horloge audio), pas par le thread de contrôle.
+        let bus_fader = output_bus.map(|bus| {
+            let state = runtime.current();
+            BusFader::new(state.bus_gain(bus), state.bus_fade(bus), sample_rate)
+        });
+        // Retard de sortie du bus joué : alloué ici comme celui du Mic,
+        // déjà réglé (pas de fondu depuis 0 à l'ouverture)
+        let output_delay_ms = output_bus.map_or(0.0, |bus| runtime.current().output_delay_ms(bus));
+        let output_delay = [(); 2].map(|()| DelayLine::with_delay_ms(sample_rate, output_delay_ms));
+        let mut bus_effects = BusEffects::new();
+        bus_effects.set_profiling(self.dsp_profiling);
+        bus_effects.load(
+            output_bus,
+            output_bus.and_then(|bus| self.channel_effects.get(&bus)),
+            sample_rate,
+        );
+        if let Ok(mut current) = self.bus_effects.lock() {
+            *current = bus_effects;
+        }
+        // Autres bus placés sur le même device : rendus à côté du bus
+        // joué, chacun pour sa tranche du stream
+        let device_buses = bus_lanes(&self.mixer, &output_buses)
+            .into_iter()
+            .skip(1)
+            .map(|(bus, _)| DeviceBus::new(bus, runtime.current(), sample_rate))
+            .collect();
 
-        // Volume 0 → gain doit être (0, 0)
-        channels
-            .command_tx
-            .send(Command::SetVolume {
-                channel: ChannelId(0),
-                level: 0.0,
-            })
-            .unwrap();
-        engine.process_commands();
+        // Une rechange en attente dans la file d'un pipeline arrêté
+        // serait perdue pour celui-ci : installée avant qu'il démarre
+        drop(self.lock_dsp_chain());
+        InputProcessor {
+            runtime,
+            dsp: self.dsp_chain.clone(),
+            effects_swap: None,
+            bus_effects: self.bus_effects.clone(),
+            gate: self.pipeline_gate.clone(),
+            delay_line,
+            conditioner,
+            presence,
+            global_presence,
+            signal_states: self.signal_states.clone(),
+            faults: self.channel_faults.clone(),
+            silenced: false,
+            event_tx: self.event_tx.clone(),
+            input_channels: input_channels.max(1),
+            output_buses,
+            output_bus,
+            bus_fader,
+            device_buses,
+            output: Vec::new(),
+            delayed: Vec::new(),
+            monitor_block: Vec::new(),
+            diff_block: Vec::new(),
+            loudness: LoudnessMeter::new(sample_rate),
+            loudness_generation: None,
+            shared_loudness: self.loudness.clone(),
+            levels: self.levels.clone(),
+            true_peak: TruePeakMeter::new(2),
+            true_peak_on: false,
+            bus_level: None,
+            meters: MeterGate::new(self.meter_subscription.clone()),
+            audio_tx,
+            recycle_rx,
+            spare: None,
+            rt_log: self.rt_log.logger(),
+            finished_ramps: self.finished_ramps.clone(),
+            sample_rate,
+            session: self.session.counters(),
+            route_meter: self.route_meter.clone(),
+            route_sampler: RouteSampler::default(),
+            network: self.network.clone(),
+            network_block: Vec::new(),
+            taps: self.taps.clone(),
+            tap_misses: self
+                .taps
+                .lock()
+                .map(|t| t.miss_counter())
+                .unwrap_or_default(),
+            output_delay,
+            preview: self.preview_feed.clone(),
+            activity: self.input_activity.clone(),
+        }
+    }
 
-        let (l, r) = *engine.shared_state.gain.lock().unwrap();
-        assert_eq!(l, 0.0);
-        assert_eq!(r, 0.0);
+    /// Formate dans `tracing` les événements laissés par les callbacks
+    /// audio. Appelé par `process_commands`.
+    pub fn drain_rt_log(&self) {
+        self.rt_log.drain();
     }
 
-    #[test]
-    fn engine_mute_updates_shared_state() {
-        let (mut engine, channels) = Engine::new();
+    /// Traite les commandes de l'UI.
+    ///
+    /// Chaque mutation est aussi enregistrée dans le journal des
+    /// modifications, avec la valeur avant/après quand elle est
+    /// peu coûteuse à lire.
+    pub fn process_commands(&mut self) {
+        self.drain_rt_log();
+        let busy = !self.command_rx.is_empty();
+        self.update_idle(busy, Instant::now());
+        self.observe_levels(journal::now_ms());
+        self.send_route_activity();
+        self.expire_device_preview(journal::now_ms());
+        self.meter_rate
+            .update(journal::now_ms(), self.meter_subscription.emitted());
+        self.collect_finished_automation();
+        let mut changed = false;
 
-        // Muter tous les inputs
-        for id in [0, 1, 2] {
-            channels
-                .command_tx
+        while let Ok(cmd) = self.command_rx.try_recv() {
+            match cmd {
+                Command::SetVolume { channel, level } => {
+                    if !self.engine_queue_has_room() {
+                        continue;
+                    }
+                    let before = self
+                        .mixer
+                        .channel(channel)
+                        .map(|c| format!("{:.2}", c.volume));
+                    self.mixer.set_volume(channel, level);
+                    let after = self
+                        .mixer
+                        .channel(channel)
+                        .map(|c| format!("{:.2}", c.volume));
+                    self.journal_change("SetVolume", Some(channel), before, after);
+                    self.queue_linked_gain(channel);
+                }
+                Command::SetVolumeFader { channel, position } => {
+                    if !self.engine_queue_has_room() {
+                        continue;
+                    }
+                    let before = self
+                        .mixer
+                        .channel(channel)
+                        .map(|c| format!("{:.2}", c.volume));
+                    self.mixer
+                        .set_volume_fader(channel, position, self.fader_taper);
+                    let after = self
+                        .mixer
+                        .channel(channel)
+                        .map(|c| format!("{:.2}", c.volume));
+                    // Même action que SetVolume : un glissé de fader est
+                    // regroupé en une seule entrée du journal
+                    self.journal_change("SetVolume", Some(channel), before, after);
+                    self.queue_linked_gain(channel);
+                }
+                Command::RequestVolumeFader { channel } => {
+                    if let Some(position) = self.mixer.volume_fader(channel, self.fader_taper) {
+                        let _ = self
+                            .event_tx
+                            .try_send(Event::VolumeFader { channel, position });
+                    }
+                }
+                Command::SetFaderTaper(taper) => {
+                    self.fader_taper = taper;
+                }
+                Command::SetMeterScale { min_db, warn_db } => {
+                    self.set_meter_scale(MeterScale::new(min_db, warn_db));
+                    self.send_meter_scale();
+                }
+                Command::RequestMeterScale => {
+                    self.send_meter_scale();
+                }
+                Command::SetUiState { json } => match UiState::from_json(&json) {
+                    // Écrit avec l'instantané de secours (au plus un par
+                    // intervalle), puis dans la config à l'arrêt
+                    Ok(state) => {
+                        self.ui_state = state;
+                        self.recovery.mark_dirty();
+                    }
+                    Err(e) => self.send_error(format!("Cannot store UI state: {e}")),
+                },
+                Command::RequestUiState => {
+                    let json = self.ui_state.to_json();
+                    let _ = self.event_tx.try_send(Event::UiState { json });
+                }
+                Command::SetLocale { locale } => match self.set_locale(&locale) {
+                    Ok(()) => {
+                        let _ = self.event_tx.try_send(Event::LocaleChanged { locale });
+                    }
+                    Err(e) => self.send_error(format!("Cannot switch language: {e}")),
+                },
+                Command::SetPresetDir { path } => match self.set_preset_dir(path) {
+                    Ok(()) => self.send_preset_dir(),
+                    Err(e) => self.send_error(format!("Cannot use preset folder: {e}")),
+                },
+                Command::RequestPresetDir => self.send_preset_dir(),
+                Command::RequestEffectiveConfig => {
+                    let entries = self.effective_config.clone();
+                    let _ = self.event_tx.try_send(Event::EffectiveConfig { entries });
+                }
+                Command::EnableNetworkSend(config) => match self.enable_network_send(&config) {
+                    Ok(()) => self.send_network_stats(),
+                    Err(e) => self.send_error(format!("Cannot send to {}: {e}", config.host)),
+                },
+                Command::DisableNetworkSend => {
+                    self.disable_network_send();
+                    self.send_network_stats();
+                }
+                Command::EnableNetworkReceive(config) => {
+                    match self.enable_network_receive(&config) {
+                        Ok(()) => self.send_network_stats(),
+                        Err(e) => {
+                            self.send_error(format!("Cannot receive on port {}: {e}", config.port))
+                        }
+                    }
+                }
+                Command::DisableNetworkReceive => {
+                    self.disable_network_receive();
+                    self.send_network_stats();
+                }
+                Command::RequestNetworkStats => {
+                    self.send_network_stats();
+                }
+                Command::CreateTap(config) => match self.serve_tap(&config) {
+                    Ok(_) => self.send_tap_list(),
+                    Err(e) => self.send_error(format!("Cannot open tap: {e}")),
+                },
+                Command::DestroyTap { id } => {
+                    if !self.destroy_tap(id) {
+                        self.send_error(format!("Cannot close tap: no tap {id}"));
+                    }
+                    self.send_tap_list();
+                }
+                Command::ListTaps => {
+                    self.send_tap_list();
+                }
+                Command::SetMute { channel, muted } => {
+                    if !self.engine_queue_has_room() {
+                        continue;
+                    }
+                    let before = self.mixer.channel(channel).map(|c| c.muted.to_string());
+                    self.mixer.set_mute(channel, muted);
+                    let after = self.mixer.channel(channel).map(|c| c.muted.to_string());
+                    self.journal_change("SetMute", Some(channel), before, after);
+                    self.queue_engine_command(EngineCommand::state_of(&self.mixer));
+                }
+                Command::SetSolo { channel, solo } => {
+                    let before = self.mixer.channel(channel).map(|c| c.solo.to_string());
+                    self.mixer.set_solo(channel, solo);
+                    let after = self.mixer.channel(channel).map(|c| c.solo.to_string());
+                    self.journal_change("SetSolo", Some(channel), before, after);
+                    changed = true;
+                }
+                Command::SetPan { channel, pan } => {
+                    if !self.engine_queue_has_room() {
+                        continue;
+                    }
+                    let before = self.mixer.channel(channel).map(|c| format!("{:.2}", c.pan));
+                    self.mixer.set_pan(channel, pan);
+                    let after = self.mixer.channel(channel).map(|c| format!("{:.2}", c.pan));
+                    self.journal_change("SetPan", Some(channel), before, after);
+                    self.queue_gain(channel);
+                }
+                Command::SetDelay { channel, delay_ms } => {
+                    let before = self
+                        .mixer
+                        .channel(channel)
+                        .map(|c| format!("{:.1}", c.delay_ms));
+                    self.mixer.set_delay(channel, delay_ms);
+                    let after = self
+                        .mixer
+                        .channel(channel)
+                        .map(|c| format!("{:.1}", c.delay_ms));
+                    self.journal_change("SetDelay", Some(channel), before, after);
+                    changed = true;
+                }
+                Command::SetChannelInputOptions {
+                    channel,
+                    phase_invert,
+                    dc_block,
+                } => {
+                    let describe = |c: &ChannelConfig| {
+                        format!("phase_invert={} dc_block={}", c.phase_invert, c.dc_block)
+                    };
+                    let before = self.mixer.channel(channel).map(describe);
+                    if let Err(e) = self
+                        .mixer
+                        .set_input_options(channel, phase_invert, dc_block)
+                    {
+                        self.send_error(format!("Cannot set input options: {e}"));
+                        continue;
+                    }
+                    let after = self.mixer.channel(channel).map(describe);
+                    self.journal_change("SetChannelInputOptions", Some(channel), before, after);
+                    changed = true;
+                }
+                Command::SetDeviceChannels {
+                    channel,
+                    offset,
+                    count,
+                } => {
+                    // Le callback ne lit la tranche que de l'entrée du
+                    // pipeline : ailleurs, le réglage n'aurait aucun effet
+                    if channel != PIPELINE_INPUT_CHANNEL {
+                        self.send_error(format!(
+                            "Cannot pick device channels of channel {}: only the pipeline input (channel {}) reads a slice of its device",
+                            channel.0, PIPELINE_INPUT_CHANNEL.0
+                        ));
+                        continue;
+                    }
+                    let describe = |c: &ChannelConfig| {
+                        channel_slice_label(c.device_channel_offset, c.device_channel_count)
+                    };
+                    let before = self.mixer.channel(channel).map(describe);
+                    self.mixer.set_device_channels(channel, offset, count);
+                    let after = self.mixer.channel(channel).map(describe);
+                    self.journal_change("SetDeviceChannels", Some(channel), before, after);
+                    changed = true;
+                }
+                Command::SetChannelDevice { channel, device } => {
+                    if let Err(e) = self.reassign_channel_device(channel, device) {
+                        self.send_error(format!(
+                            "Cannot change device of channel {}: {e}",
+                            channel.0
+                        ));
+                    }
+                }
+                Command::SetInputFallback { channel, fallback } => {
+                    if let Err(e) = self.set_input_fallback(channel, fallback) {
+                        self.send_error(format!(
+                            "Cannot set input fallback of channel {}: {e}",
+                            channel.0
+                        ));
+                    }
+                }
+                Command::SetChannelMonitor {
+                    channel,
+                    bus,
+                    level_db,
+                } => {
+                    let describe = |c: &ChannelConfig| match c.monitor_bus {
+                        Some(bus) => format!("-> {} @ {:.1} dB", bus.0, c.monitor_level_db),
+                        None => "off".to_string(),
+                    };
+                    let before = self.mixer.channel(channel).map(describe);
+                    if self.mixer.set_monitor(channel, bus, level_db) {
+                        let after = self.mixer.channel(channel).map(describe);
+                        self.journal_change("SetChannelMonitor", Some(channel), before, after);
+                        changed = true;
+                    } else {
+                        self.send_error(format!(
+                            "Cannot set monitor send of channel {}: needs an input and an output bus",
+                            channel.0
+                        ));
+                    }
+                }
+                Command::RequestSignalState { channel } => {
+                    if self.mixer.channel(channel).is_some() {
+                        let state = self.signal_state(channel);
+                        let _ = self
+                            .event_tx
+                            .try_send(Event::SignalState { channel, state });
+                    } else {
+                        self.send_error(format!("Channel {} not found", channel.0));
+                    }
+                }
+                Command::SetSignalPresence { channel, config } => {
+                    self.mixer.set_signal_presence(channel, config);
+                    changed = true;
+                }
+                Command::AddChannel(config) => {
+                    let channel = config.id;
+                    let name = config.name.clone();
+                    match self.mixer.add_channel(config) {
+                        Ok(()) => {
+                            self.journal_change("AddChannel", Some(channel), None, Some(name));
+                            let _ = self.event_tx.try_send(Event::ChannelAdded {
+                                channel,
+                                template: None,
+                            });
+                            changed = true;
+                        }
+                        Err(e) => self.send_error(format!("Cannot add channel: {e}")),
+                    }
+                }
+                Command::AddChannelFromTemplate {
+                    channel,
+                    name,
+                    template,
+                } => match self.add_channel_from_template(channel, &name, &template) {
+                    Ok(()) => {
+                        let _ = self.event_tx.try_send(Event::ChannelAdded {
+                            channel,
+                            template: Some(template),
+                        });
+                    }
+                    Err(e) => self.send_error(format!("Cannot add channel: {e}")),
+                },
+                Command::RequestChannelDependencies { channel } => {
+                    match self.channel_dependencies(channel) {
+                        Ok(report) => {
+                            let _ = self.event_tx.try_send(Event::ChannelDependencies(report));
+                        }
+                        Err(e) => self.send_error(format!("Cannot inspect channel: {e}")),
+                    }
+                }
+                Command::RemoveChannel { channel } => match self.remove_channel(channel) {
+                    Ok((removed, cleaned)) => {
+                        self.journal_change(
+                            "RemoveChannel",
+                            Some(channel),
+                            Some(removed.name),
+                            Some("removed".into()),
+                        );
+                        let _ = self.event_tx.try_send(Event::ChannelRemoved(cleaned));
+                        changed = true;
+                    }
+                    Err(e) => self.send_error(format!("Cannot remove channel: {e}")),
+                },
+                Command::FindChannels(query) => {
+                    let channels = self
+                        .mixer
+                        .find_channels(&query)
+                        .iter()
+                        .map(|channel| self.locale.localized(channel))
+                        .collect();
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::ChannelsFound { query, channels });
+                }
+                Command::RequestChannelCountSummary => {
+                    let summary = self.mixer.channel_count_summary();
+                    let _ = self.event_tx.try_send(Event::ChannelCountSummary(summary));
+                }
+                Command::ListChannelTemplates => {
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::ChannelTemplateList(self.channel_templates.names()));
+                }
+                Command::FadeBusVolume {
+                    bus,
+                    target_db,
+                    duration_ms,
+                } => {
+                    let before = self.mixer.channel(bus).map(|c| format!("{:.2}", c.volume));
+                    if self
+                        .mixer
+                        .fade_bus_volume(bus, VolumeDecibels(target_db), duration_ms)
+                    {
+                        let after = self.mixer.channel(bus).map(|c| format!("{:.2}", c.volume));
+                        self.journal_change("FadeBusVolume", Some(bus), before, after);
+                        // Le bus et ceux qui le suivent partent en fondu
+                        for bus in std::iter::once(bus).chain(self.mixer.bus_followers(bus)) {
+                            if let Some(ramp) = EngineCommand::ramp_of(&self.mixer, bus) {
+                                self.queue_engine_command(ramp);
+                            }
+                        }
+                    } else {
+                        self.send_error(format!("Cannot fade channel {}: not a bus", bus.0));
+                    }
+                }
+                Command::ToggleBusSolo { bus } => {
+                    // Transitoire : ni journalisé, ni sauvé dans les profils
+                    if let Some(solo) = self.mixer.toggle_bus_solo(bus) {
+                        let _ = self.event_tx.try_send(Event::BusSolo { bus, solo });
+                        changed = true;
+                    }
+                }
+                Command::SetBusChannelCount { bus, count } => {
+                    match self.set_bus_channel_count(bus, count) {
+                        Ok(()) => changed = true,
+                        Err(e) => self
+                            .send_error(format!("Cannot set channel count of bus {}: {e}", bus.0)),
+                    }
+                }
+                Command::SetBusOutputMapping { bus, offset } => {
+                    match self.set_bus_output_mapping(bus, offset) {
+                        Ok(()) => changed = true,
+                        Err(e) => {
+                            self.send_error(format!("Cannot map outputs of bus {}: {e}", bus.0))
+                        }
+                    }
+                }
+                Command::SetEmergencyPassthrough { enabled } => {
+                    match self.set_emergency_passthrough(enabled) {
+                        Ok(_) => changed = true,
+                        Err(e) => self.send_error(format!("Cannot switch passthrough: {e}")),
+                    }
+                }
+                Command::SetLoudnessMeter { bus, enabled } => {
+                    // Transitoire, comme le solo de bus
+                    match self.mixer.set_loudness_meter(bus, enabled) {
+                        Ok(()) => changed = true,
+                        Err(e) => self.send_error(format!("Cannot meter loudness: {e}")),
+                    }
+                }
+                Command::SetTruePeakMeter { bus, enabled } => {
+                    let before = self
+                        .mixer
+                        .channel(bus)
+                        .map(|c| c.true_peak_meter.to_string());
+                    if let Err(e) = self.mixer.set_true_peak_meter(bus, enabled) {
+                        self.send_error(format!("Cannot meter true peak: {e}"));
+                        continue;
+                    }
+                    self.journal_change(
+                        "SetTruePeakMeter",
+                        Some(bus),
+                        before,
+                        Some(enabled.to_string()),
+                    );
+                    changed = true;
+                }
+                Command::SetBusSumPriority { bus, priority } => {
+                    let listed = |engine: &Self| {
+                        engine.mixer.channel(bus).map(|c| {
+                            c.sum_priority
+                                .iter()
+                                .map(|id| id.0.to_string())
+                                .collect::<Vec<_>>()
+                                .join(",")
+                        })
+                    };
+                    let before = listed(self);
+                    if let Err(e) = self.mixer.set_sum_priority(bus, &priority) {
+                        self.send_error(format!("Cannot set bus sum priority: {e}"));
+                        continue;
+                    }
+                    let after = listed(self);
+                    self.journal_change("SetBusSumPriority", Some(bus), before, after);
+                    changed = true;
+                }
+                Command::StartDevicePreview { device } => {
+                    if let Err(e) = self.start_device_preview(&device) {
+                        self.send_error(format!("Cannot preview {device}: {e}"));
+                    }
+                }
+                Command::StopDevicePreview => self.stop_device_preview(),
+                Command::SetMeterSubscription { targets, active } => {
+                    self.set_meter_subscription(&targets, active);
+                }
+                Command::SetMetersPaused { paused } => self.set_meters_paused(paused),
+                Command::SetOutputDelay { bus, delay_ms } => {
+                    let before = self
+                        .mixer
+                        .channel(bus)
+                        .map(|c| format!("{:.1}", c.output_delay_ms));
+                    if let Err(e) = self.mixer.set_output_delay(bus, delay_ms) {
+                        self.send_error(format!("Cannot delay bus output: {e}"));
+                        continue;
+                    }
+                    let after = self
+                        .mixer
+                        .channel(bus)
+                        .map(|c| format!("{:.1}", c.output_delay_ms));
+                    self.journal_change("SetOutputDelay", Some(bus), before, after);
+                    changed = true;
+                }
+                Command::RequestLoudness { bus, reset } => {
+                    let loudness = self.loudness(bus);
+                    let _ = self.event_tx.try_send(Event::Loudness { bus, loudness });
+                    if reset && self.mixer.reset_loudness_meter(bus).is_ok() {
+                        changed = true;
+                    }
+                }
+                Command::AddRoute { from, to } => {
+                    if !self.engine_queue_has_room() {
+                        continue;
+                    }
+                    if self.mixer.add_route(from, to) {
+                        self.journal_route("AddRoute", from, to);
+                        self.queue_engine_command(EngineCommand::state_of(&self.mixer));
+                    }
+                }
+                Command::RemoveRoute { from, to } => {
+                    if !self.engine_queue_has_room() {
+                        continue;
+                    }
+                    if self.mixer.has_route(from, to) {
+                        self.mixer.remove_route(from, to);
+                        self.journal_route("RemoveRoute", from, to);
+                        self.queue_engine_command(EngineCommand::state_of(&self.mixer));
+                    }
+                }
+                Command::CreateCrossfade {
+                    id,
+                    channel_a,
+                    channel_b,
+                    curve,
+                } => match self.mixer.create_crossfade(id, channel_a, channel_b, curve) {
+                    Ok(()) => {
+                        self.journal_change(
+                            "CreateCrossfade",
+                            Some(channel_a),
+                            None,
+                            Some(format!("#{id}: {} / {}", channel_a.0, channel_b.0)),
+                        );
+                        changed = true;
+                    }
+                    Err(e) => self.send_error(format!("Cannot create crossfade {id}: {e}")),
+                },
+                Command::SetCrossfadePosition { id, position } => {
+                    if !self.engine_queue_has_room() {
+                        continue;
+                    }
+                    let Some(before) = self.mixer.crossfade(id).cloned() else {
+                        self.send_error(format!("Crossfade {id} not found"));
+                        continue;
+                    };
+                    let _ = self.mixer.set_crossfade_position(id, position);
+                    let after = self
+                        .mixer
+                        .crossfade(id)
+                        .map(|x| format!("{:.2}", x.position));
+                    // Journalisé sous le canal A : un glissé du crossfader
+                    // est regroupé comme celui d'un fader
+                    self.journal_change(
+                        "SetCrossfadePosition",
+                        Some(before.channel_a),
+                        Some(format!("{:.2}", before.position)),
+                        after,
+                    );
+                    // Seuls les gains des deux côtés changent
+                    self.queue_gain(before.channel_a);
+                    self.queue_gain(before.channel_b);
+                }
+                Command::RemoveCrossfade { id } => match self.mixer.remove_crossfade(id) {
+                    Ok(removed) => {
+                        self.journal_change(
+                            "RemoveCrossfade",
+                            Some(removed.channel_a),
+                            Some(format!(
+                                "#{id}: {} / {}",
+                                removed.channel_a.0, removed.channel_b.0
+                            )),
+                            Some("removed".into()),
+                        );
+                        changed = true;
+                    }
+                    Err(e) => self.send_error(format!("Cannot remove crossfade {id}: {e}")),
+                },
+                Command::LinkChannels { left, right } => {
+                    match self.mixer.link_channels(left, right) {
+                        Ok(()) => {
+                            // Les effets suivent aussi la gauche
+                            self.set_channel_effects(right, self.channel_effects(left));
+                            self.journal_change(
+                                "LinkChannels",
+                                Some(left),
+                                None,
+                                Some(format!("{} + {}", left.0, right.0)),
+                            );
+                            self.send_channel_links();
+                            changed = true;
+                        }
+                        Err(e) => self.send_error(format!(
+                            "Cannot link channels {} and {}: {e}",
+                            left.0, right.0
+                        )),
+                    }
+                }
+                Command::UnlinkChannels { channel } => match self.mixer.unlink_channels(channel) {
+                    Ok(removed) => {
+                        self.journal_change(
+                            "UnlinkChannels",
+                            Some(removed.left),
+                            Some(format!("{} + {}", removed.left.0, removed.right.0)),
+                            Some("unlinked".into()),
+                        );
+                        self.send_channel_links();
+                        changed = true;
+                    }
+                    Err(e) => self.send_error(format!("Cannot unlink channel {}: {e}", channel.0)),
+                },
+                Command::ListChannelLinks => {
+                    self.send_channel_links();
+                }
+                Command::FollowBus(follow) => match self.mixer.follow_bus(follow) {
+                    Ok(()) => {
+                        self.journal_change(
+                            "FollowBus",
+                            Some(follow.follower),
+                            None,
+                            Some(format!("{} {:+.1} dB", follow.leader.0, follow.offset_db)),
+                        );
+                        self.send_bus_follows();
+                        changed = true;
+                    }
+                    Err(e) => self.send_error(format!(
+                        "Cannot make bus {} follow bus {}: {e}",
+                        follow.follower.0, follow.leader.0
+                    )),
+                },
+                Command::UnfollowBus { follower } => match self.mixer.unfollow_bus(follower) {
+                    Ok(removed) => {
+                        self.journal_change(
+                            "UnfollowBus",
+                            Some(follower),
+                            Some(format!("{} {:+.1} dB", removed.leader.0, removed.offset_db)),
+                            None,
+                        );
+                        self.send_bus_follows();
+                    }
+                    Err(e) => self.send_error(format!("Cannot unfollow bus {}: {e}", follower.0)),
+                },
+                Command::ListBusFollows => {
+                    self.send_bus_follows();
+                }
+                Command::SaveActionSet { name, set } => match self.save_action_set(&name, set) {
+                    Ok(()) => self.send_action_set_list(),
+                    Err(e) => self.send_error(format!("Cannot save action set '{name}': {e}")),
+                },
+                Command::DeleteActionSet { name } => {
+                    if self.action_sets.remove(&name).is_some() {
+                        // Un déclencheur vers un ensemble disparu échouerait chaque jour
+                        for trigger in self.scheduler.remove_triggers_of(&name) {
+                            info!("Trigger at {} removed with action set '{name}'", trigger.at);
+                        }
+                        self.send_action_set_list();
+                    } else {
+                        self.send_error(format!("Action set '{name}' not found"));
+                    }
+                }
+                Command::ExecuteActionSet { name } => {
+                    self.run_action_set(&name, false);
+                }
+                Command::ListActionSets => {
+                    self.send_action_set_list();
+                }
+                Command::SaveRoutingSnapshot { name } => match self.save_routing_snapshot(&name) {
+                    Ok(()) => self.send_routing_snapshot_list(),
+                    Err(e) => self.send_error(format!("Cannot save routing snapshot: {e}")),
+                },
+                Command::ApplyRoutingSnapshot { name } => {
+                    match self.apply_routing_snapshot(&name) {
+                        Ok(()) => {
+                            let _ = self
+                                .event_tx
+                                .try_send(Event::RoutingSnapshotApplied { name });
+                        }
+                        Err(e) => {
+                            self.send_error(format!("Cannot apply routing snapshot '{name}': {e}"))
+                        }
+                    }
+                }
+                Command::DeleteRoutingSnapshot { name } => {
+                    if self.routing_snapshots.remove(&name).is_some() {
+                        self.send_routing_snapshot_list();
+                    } else {
+                        self.send_error(format!("Routing snapshot '{name}' not found"));
+                    }
+                }
+                Command::ListRoutingSnapshots => self.send_routing_snapshot_list(),
+                Command::ListScripts => self.send_script_list(),
+                Command::EnableScript { name } => self.enable_script(&name),
+                Command::DisableScript { name } => self.disable_script(&name),
+                Command::ListCrossfades => {
+                    let crossfades = self.mixer.crossfades().to_vec();
+                    let _ = self.event_tx.try_send(Event::CrossfadeList(crossfades));
+                }
+                Command::RequestDeviceList => {
+                    self.send_device_list();
+                }
+                Command::RequestDeviceCache => {
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::DeviceCache(self.device_cache.clone()));
+                }
+                Command::ClearDeviceCache => {
+                    self.clear_device_cache();
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::DeviceCache(self.device_cache.clone()));
+                }
+                Command::SelectMachineProfile { name } => {
+                    self.select_machine_profile(&name);
+                    self.send_machine_profile();
+                }
+                Command::RequestMachineProfile => {
+                    self.send_machine_profile();
+                }
+                Command::RequestSetupProposal => {
+                    let proposal = self.setup_proposal();
+                    let _ = self.event_tx.try_send(Event::SetupProposal(proposal));
+                }
+                Command::ApplySetupProposal(proposal) => {
+                    match self.apply_setup_proposal(&proposal) {
+                        Ok(()) => changed = true,
+                        Err(e) => self.send_error(format!("Cannot apply setup proposal: {e}")),
+                    }
+                }
+                Command::SetChannelEffects { channel, preset } => {
+                    let preset = match preset.checked(self.effect_param_policy) {
+                        Ok(preset) => preset,
+                        Err(violations) => {
+                            self.reject_effect_params(channel, violations);
+                            continue;
+                        }
+                    };
+                    // Une paire stéréo partage sa chaîne d'effets
+                    if let Some(partner) = self.mixer.link_partner(channel) {
+                        self.set_channel_effects(partner, preset.clone());
+                    }
+                    self.set_channel_effects(channel, preset);
+                }
+                Command::SetEffectInsertPoint {
+                    bus,
+                    effect_index,
+                    point,
+                } => {
+                    let partner = self.mixer.link_partner(bus);
+                    let result = self
+                        .set_effect_insert_point(bus, effect_index, point)
+                        .and_then(|()| match partner {
+                            Some(partner) => {
+                                self.set_effect_insert_point(partner, effect_index, point)
+                            }
+                            None => Ok(()),
+                        });
+                    if let Err(e) = result {
+                        self.send_error(format!("Cannot move effect: {e}"));
+                    }
+                }
+                Command::SetEffectParamPolicy(policy) => {
+                    self.effect_param_policy = policy;
+                }
+                Command::AutomateEffectParam {
+                    channel,
+                    effect_index,
+                    param,
+                    target,
+                    duration_ms,
+                } => {
+                    let target =
+                        match self.checked_effect_target(channel, effect_index, &param, target) {
+                            Ok(target) => target,
+                            Err(violation) => {
+                                self.reject_effect_params(channel, vec![violation]);
+                                continue;
+                            }
+                        };
+                    if let Err(e) = self.automate_effect_param(
+                        channel,
+                        effect_index,
+                        &param,
+                        target,
+                        duration_ms,
+                    ) {
+                        self.send_error(format!("Cannot automate {param}: {e}"));
+                    }
+                }
+                Command::SetDetectorListen {
+                    channel,
+                    effect_index,
+                    listen,
+                } => {
+                    if let Err(e) = self.set_detector_listen(channel, effect_index, listen) {
+                        self.send_error(format!("Cannot listen to detector: {e}"));
+                    }
+                }
+                Command::SetEffectsDiffMonitor { channel, enabled } => {
+                    match self.set_effects_diff_monitor(channel, enabled) {
+                        Ok(()) => changed = true,
+                        Err(e) => {
+                            self.send_error(format!("Cannot monitor effects difference: {e}"))
+                        }
+                    }
+                }
+                Command::RequestChannelEffects { channel } => {
+                    match self.channel_effect_stages(channel) {
+                        Ok(stages) => {
+                            let _ = self
+                                .event_tx
+                                .try_send(Event::ChannelEffects { channel, stages });
+                        }
+                        Err(e) => self.send_error(format!("Cannot read effects: {e}")),
+                    }
+                }
+                Command::RequestEqResponse { channel, points } => {
+                    match self.channel_eq_response(channel, points as usize) {
+                        Ok(response) => {
+                            let _ = self
+                                .event_tx
+                                .try_send(Event::EqResponse { channel, response });
+                        }
+                        Err(e) => self.send_error(format!("Cannot compute EQ response: {e}")),
+                    }
+                }
+                Command::SetDetailedEffectMetering { channel, enabled } => {
+                    if let Err(e) = self.set_detailed_effect_metering(channel, enabled) {
+                        self.send_error(format!("Cannot change effect metering: {e}"));
+                    }
+                }
+                Command::SetDspProfiling { enabled } => self.set_dsp_profiling(enabled),
+                Command::RequestDspProfile => {
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::DspProfile(self.dsp_profile()));
+                }
+                Command::SaveEffectsPreset {
+                    channel,
+                    name,
+                    overwrite,
+                } => {
+                    let preset = self.channel_effects(channel);
+                    match self.effects_presets.save(&name, &preset, overwrite) {
+                        Ok(()) => self.send_effects_preset_list(),
+                        Err(e)
+                            if matches!(
+                                e.downcast_ref::<TroubadourError>(),
+                                Some(TroubadourError::PresetExists(_))
+                            ) =>
+                        {
+                            let _ = self.event_tx.try_send(Event::EffectsPresetExists { name });
+                        }
+                        Err(e) => self.send_error(format!("Cannot save effects preset: {e}")),
+                    }
+                }
+                Command::ApplyEffectsPreset { channel, name } => {
+                    self.apply_effects_preset(channel, name);
+                }
+                Command::DeleteEffectsPreset { name } => match self.effects_presets.delete(&name) {
+                    Ok(()) => {
+                        self.send_effects_preset_list();
+                        if self.favorite_presets.contains(&name) {
+                            self.favorite_presets.preset_deleted(&name);
+                            self.send_favorite_presets();
+                        }
+                    }
+                    Err(e) => self.send_error(format!("Cannot delete effects preset: {e}")),
+                },
+                Command::ListEffectsPresets => {
+                    self.send_effects_preset_list();
+                }
+                Command::ListEffectsPresetSummaries => {
+                    let summaries = self.effects_presets.summaries();
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::EffectsPresetSummaries(summaries));
+                }
+                Command::RefreshPresetCache => {
+                    self.effects_presets.refresh_cache();
+                    self.send_preset_cache_stats();
+                }
+                Command::RequestPresetCacheStats => self.send_preset_cache_stats(),
+                Command::RenameEffectsPreset { from, to } => {
+                    match self.effects_presets.rename(&from, &to) {
+                        Ok(()) => {
+                            self.send_effects_preset_list();
+                            if self.favorite_presets.contains(&from) {
+                                self.favorite_presets.preset_renamed(&from, &to);
+                                self.send_favorite_presets();
+                            }
+                        }
+                        Err(e) => self.send_error(format!("Cannot rename effects preset: {e}")),
+                    }
+                }
+                Command::AddFavoritePreset { name, position } => {
+                    match self.add_favorite_preset(&name, position) {
+                        Ok(()) => self.send_favorite_presets(),
+                        Err(e) => self.send_error(format!("Cannot add favorite preset: {e}")),
+                    }
+                }
+                Command::RemoveFavoritePreset { name } => {
+                    if self.favorite_presets.remove(&name) {
+                        self.send_favorite_presets();
+                    } else {
+                        self.send_error(format!("Preset {name} is not a favorite"));
+                    }
+                }
+                Command::ReorderFavoritePresets { names } => {
+                    match self.favorite_presets.reorder(&names) {
+                        Ok(()) => self.send_favorite_presets(),
+                        Err(e) => self.send_error(format!("Cannot reorder favorite presets: {e}")),
+                    }
+                }
+                Command::ListFavoritePresets => self.send_favorite_presets(),
+                Command::ApplyFavoritePreset { channel, position } => {
+                    match self.favorite_presets.get(position) {
+                        Some(name) => self.apply_effects_preset(channel, name.to_string()),
+                        None => self
+                            .send_error(format!("No favorite preset at position {}", position + 1)),
+                    }
+                }
+                Command::ListControlProfiles => {
+                    let _ = self.event_tx.try_send(Event::ControlProfiles {
+                        names: self.control_profiles.list(),
+                        active: self.active_control_profile().map(str::to_string),
+                    });
+                }
+                Command::ActivateControlProfile { name } => {
+                    match self.activate_control_profile(&name) {
+                        Ok(()) => {
+                            let name = self.active_control_profile().unwrap_or(&name).to_string();
+                            let _ = self
+                                .event_tx
+                                .try_send(Event::ControlProfileActivated { name });
+                        }
+                        Err(e) => self.send_error(format!("Cannot activate control profile: {e}")),
+                    }
+                }
+                Command::ExportControlProfile { name, path } => {
+                    match self.control_profiles.export(&name, &path) {
+                        Ok(()) => {
+                            let _ = self
+                                .event_tx
+                                .try_send(Event::ControlProfileExported { name, path });
+                        }
+                        Err(e) => self.send_error(format!("Cannot export control profile: {e}")),
+                    }
+                }
+                Command::ControlInput { source, value } => {
+                    self.control_input(&source, value);
+                }
+                Command::DiffPreset { name } => match self.find_profile(&name) {
+                    Ok(profile) => {
+                        let diff = self.diff_profile(&profile);
+                        let _ = self.event_tx.try_send(Event::PresetDiff { name, diff });
+                    }
+                    Err(e) => self.send_error(format!("Cannot diff preset: {e}")),
+                },
+                Command::CheckPresetCompatibility { name } => {
+                    match self.check_preset_compatibility(&name) {
+                        Ok(report) => {
+                            let _ = self.event_tx.try_send(Event::PresetCompatibility(report));
+                        }
+                        Err(e) => self.send_error(format!("Cannot check preset: {e}")),
+                    }
+                }
+                Command::ApplyPresetSelection { name, selection } => {
+                    match self.find_profile(&name) {
+                        Ok(profile) => match self.apply_profile_selection(&profile, &selection) {
+                            Ok(applied) => {
+                                let _ = self
+                                    .event_tx
+                                    .try_send(Event::PresetSelectionApplied { name, applied });
+                            }
+                            Err(e) => {
+                                self.send_error(format!("Cannot apply preset selection: {e}"))
+                            }
+                        },
+                        Err(e) => self.send_error(format!("Cannot apply preset selection: {e}")),
+                    }
+                }
+                Command::ImportExternalConfig { path, format } => {
+                    match self.import_external_config(&path, format) {
+                        Ok(report) => {
+                            let _ = self
+                                .event_tx
+                                .try_send(Event::ExternalConfigImported(report));
+                        }
+                        Err(e) => self.send_error(format!("Cannot import {}: {e}", format.label())),
+                    }
+                }
+                Command::LoadPresetFile { path } => match self.load_profile_file(&path) {
+                    Ok((name, warnings)) => {
+                        let _ = self
+                            .event_tx
+                            .try_send(Event::PresetFileLoaded { name, warnings });
+                    }
+                    Err(e) => self.send_error(format!("Cannot load preset file: {e}")),
+                },
+                Command::SaveDroppedPreset {
+                    path,
+                    name,
+                    overwrite,
+                } => match self.save_dropped_profile(&path, name.as_deref(), overwrite) {
+                    Ok(name) => {
+                        let _ = self.event_tx.try_send(Event::DroppedPresetSaved { name });
+                    }
+                    Err(e) => self.send_error(format!("Cannot save preset file: {e}")),
+                },
+                Command::ExportPresetJson { name } => match self.export_profile_json(&name) {
+                    Ok(json) => {
+                        let _ = self.event_tx.try_send(Event::PresetJson { name, json });
+                    }
+                    Err(e) => self.send_error(format!("Cannot export preset: {e}")),
+                },
+                Command::ImportPresetJson {
+                    name,
+                    json,
+                    overwrite,
+                } => match self.import_profile_json(&name, &json, overwrite) {
+                    Ok((name, warnings)) => {
+                        let _ = self
+                            .event_tx
+                            .try_send(Event::PresetJsonImported { name, warnings });
+                    }
+                    Err(e) => self.send_error(format!("Cannot import preset: {e}")),
+                },
+                Command::RequestChangeLog { limit } => {
+                    let entries = self.journal.recent(limit);
+                    let _ = self.event_tx.try_send(Event::ChangeLog(entries));
+                }
+                Command::ClearChangeLog => {
+                    self.journal.clear();
+                }
+                Command::RequestSessionStats { reset } => {
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::SessionStats(self.session_stats()));
+                    if reset {
+                        self.reset_session_stats();
+                    }
+                }
+                Command::SetRouteMetering { enabled } => {
+                    self.set_route_metering(enabled);
+                }
+                Command::AutoLevelChannels {
+                    channels,
+                    target_peak_db,
+                    duration_ms,
+                } => {
+                    if let Err(e) = self.auto_level_channels(channels, target_peak_db, duration_ms)
+                    {
+                        self.send_error(format!("Cannot start auto level: {e}"));
+                    }
+                }
+                Command::CancelAutoLevel => self.cancel_auto_level(),
+                Command::SetLevelHistory { channel, enabled } => {
+                    if let Err(e) = self.set_level_history(channel, enabled) {
+                        self.send_error(format!("Cannot record level history: {e}"));
+                    }
+                }
+                Command::RequestLevelHistory { channel, seconds } => {
+                    match self.level_history(channel, seconds) {
+                        Some(points) => {
+                            let _ = self
+                                .event_tx
+                                .try_send(Event::LevelHistory { channel, points });
+                        }
+                        None => self.send_error(format!(
+                            "No level history for channel {}: enable it with SetLevelHistory",
+                            channel.0
+                        )),
+                    }
+                }
+                Command::SetMeterTransport(transport) => {
+                    self.set_meter_transport(transport);
+                }
+                Command::RequestMeterLayout => match self.meter_layout.clone() {
+                    Some(layout) => {
+                        let _ = self.event_tx.try_send(Event::MeterLayout(layout));
+                    }
+                    None => self.send_error("No meter layout yet: start the engine first".into()),
+                },
+                Command::RequestRouteActivity => {
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::RouteActivity(self.get_route_activity()));
+                }
+                Command::RequestAudioStats => {
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::AudioStats(self.audio_stats()));
+                    let _ = self
+                        .event_tx
+                        .try_send(Event::StreamHealth(self.stream_health()));
+                }
+                Command::RunDiagnostics => {
+                    self.spawn_diagnostics(None);
+                }
+                Command::ExportDiagnostics { path } => {
+                    self.spawn_diagnostics(Some(path));
+                }
+                Command::AnalyzeRecording { path } => {
+                    let file = path.clone();
+                    self.spawn_recording_task(path, move |progress| {
+                        let report = recording::analyze_recording(&file, progress)?;
+                        Ok(Event::RecordingAnalyzed { path: file, report })
+                    });
+                }
+                Command::NormalizeRecording { src, dst, target } => {
+                    self.spawn_recording_task(src.clone(), move |progress| {
+                        let outcome = recording::normalize_recording(&src, &dst, target, progress)?;
+                        Ok(Event::RecordingNormalized { path: dst, outcome })
+                    });
+                }
+                Command::EnableReplay { bus, seconds } => match self.enable_replay(bus, seconds) {
+                    Ok(_) => self.send_replay_status(),
+                    Err(e) => {
+                        self.send_error(format!("Cannot keep a replay of bus {}: {e}", bus.0))
+                    }
+                },
+                Command::DisableReplay { bus } => {
+                    self.disable_replay(bus);
+                    self.send_replay_status();
+                }
+                Command::RequestReplayStatus => self.send_replay_status(),
+                Command::SaveReplay { bus, path, seconds } => {
+                    // Sans bus : chaque replay, chacun dans son fichier
+                    let buses: Vec<ChannelId> = match (bus, &path) {
+                        (Some(bus), _) => vec![bus],
+                        (None, None) => self.replays.iter().map(ReplayBuffer::bus).collect(),
+                        (None, Some(_)) => {
+                            self.send_error("A replay path needs a bus".to_string());
+                            Vec::new()
+                        }
+                    };
+                    if buses.is_empty() && path.is_none() {
+                        self.send_error("No replay to save".to_string());
+                    }
+                    for bus in buses {
+                        if let Err(e) = self.save_replay(bus, path.clone(), seconds) {
+                            self.send_error(format!("Cannot save replay of bus {}: {e}", bus.0));
+                        }
+                    }
+                }
+                Command::ExportSignalGraph { format } => {
+                    let event = match self.export_signal_graph().render(format) {
+                        Ok(content) => Event::SignalGraph { format, content },
+                        Err(e) => Event::Error(format!("Signal graph export failed: {e}")),
+                    };
+                    let _ = self.event_tx.try_send(event);
+                }
+                Command::SetBufferSize(size) => {
+                    // Choix de l'utilisateur : passe avant le cache des devices
+                    self.buffer_size_explicit = true;
+                    if let Err(e) = self.set_buffer_size(size) {
+                        self.send_error(format!("Cannot change buffer size: {e}"));
+                    }
+                }
+                Command::SetSampleRate(rate) => {
+                    // Choix de l'utilisateur : passe avant le cache des devices
+                    self.sample_rate_explicit = true;
+                    if let Err(e) = self.set_sample_rate(rate) {
+                        self.send_error(format!("Cannot change sample rate: {e}"));
+                    }
+                }
+                Command::RestoreRecoverySnapshot => match self.restore_recovery_snapshot() {
+                    Ok(saved_at_ms) => {
+                        let _ = self
+                            .event_tx
+                            .try_send(Event::RecoveryRestored { saved_at_ms });
+                    }
+                    Err(e) => self.send_error(format!("Cannot restore unsaved session: {e}")),
+                },
+                Command::ListAutoSnapshots => {
+                    let list = self.auto_snapshot_list();
+                    let _ = self.event_tx.try_send(Event::AutoSnapshotList(list));
+                }
+                Command::RestoreAutoSnapshot { id } => match self.restore_auto_snapshot(id) {
+                    Ok(info) => {
+                        let _ = self.event_tx.try_send(Event::AutoSnapshotRestored(info));
+                    }
+                    Err(e) => self.send_error(format!("Cannot restore auto snapshot: {e}")),
+                },
+                Command::DiscardRecoverySnapshot => match self.discard_recovery_snapshot() {
+                    Ok(()) => {
+                        let _ = self.event_tx.try_send(Event::RecoveryDiscarded);
+                    }
+                    Err(e) => self.send_error(format!("Cannot discard unsaved session: {e}")),
+                },
+                Command::Shutdown => {
+                    // Arrêt propre : l'instantané de secours n'a plus lieu d'être
+                    if let Err(e) = self.recovery.discard() {
+                        warn!("Cannot remove recovery snapshot: {e}");
+                    }
+                    self.stop();
+                    return;
+                }
+                _ => {
+                    warn!("Unhandled command: {cmd:?}");
+                }
+            }
+        }
+
+        if changed {
+            self.shared_state.update_from_mixer(&self.mixer);
+            self.recovery.mark_dirty();
+        }
+        self.collect_effects_swaps();
+        self.write_recovery_snapshot(Instant::now());
+        self.check_watchdog(watchdog::monotonic_ms());
+        self.check_default_devices(watchdog::monotonic_ms());
+        self.check_handover(watchdog::monotonic_ms());
+        self.run_scheduled_actions(SystemTime::now());
+        self.run_scripts(watchdog::monotonic_ms());
+    }
+
+    /// Exécute les ensembles d'actions dont l'heure vient de passer.
+    fn run_scheduled_actions(&mut self, now: SystemTime) {
+        let minute = self.scheduler.local_minute(now);
+        for name in self.scheduler.due(minute) {
+            info!("Scheduled action set '{name}'");
+            self.run_action_set(&name, true);
+        }
+    }
+
+    /// Exécute un ensemble d'actions et prévient l'UI (succès ou erreur).
+    fn run_action_set(&mut self, name: &str, scheduled: bool) {
+        match self.execute_action_set(name) {
+            Ok(()) => {
+                let _ = self.event_tx.try_send(Event::ActionSetExecuted {
+                    name: name.to_string(),
+                    scheduled,
+                });
+            }
+            Err(e) => self.send_error(format!("Cannot run action set '{name}': {e}")),
+        }
+    }
+
+    /// Applique un ensemble d'actions, tout ou rien (voir
+    /// `scheduler::apply_action_set`), en une seule publication vers le
+    /// thread audio.
+    pub fn execute_action_set(&mut self, name: &str) -> TroubadourResult<()> {
+        let set = self.action_sets.get(name).cloned().ok_or_else(|| {
+            MixerError::InvalidParameter(format!("action set '{name}' not found"))
+        })?;
+        let passthrough = self.mixer.emergency_passthrough();
+        scheduler::apply_action_set(&mut self.mixer, &set, &self.routing_snapshots)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        let bus = self.mixer.emergency_passthrough();
+        if bus != passthrough {
+            let _ = self
+                .event_tx
+                .try_send(Event::EmergencyPassthroughChanged { bus });
+        }
+        self.recovery.mark_dirty();
+        self.journal_change("ExecuteActionSet", None, None, Some(name.to_string()));
+        Ok(())
+    }
+
+    /// Enregistre (ou remplace) un ensemble d'actions. Refusé s'il vise
+    /// un canal absent du mixer ou un routage nommé inconnu.
+    pub fn save_action_set(&mut self, name: &str, set: ActionSet) -> MixerResult<()> {
+        if let Some(id) = set.unknown_channel(&self.mixer.to_config()) {
+            return Err(MixerError::ChannelNotFound(id.0));
+        }
+        if let Some(snapshot) = set.unknown_routing_snapshot(&self.routing_snapshots) {
+            return Err(MixerError::InvalidParameter(format!(
+                "routing snapshot '{snapshot}' not found"
+            )));
+        }
+        self.action_sets.insert(name.to_string(), set);
+        Ok(())
+    }
+
+    /// État de l'UI lu dans la config.
+    pub fn configure_ui_state(&mut self, state: UiState) {
+        self.ui_state = state;
+    }
+
+    /// État de l'UI à réécrire dans la config.
+    pub fn ui_state(&self) -> &UiState {
+        &self.ui_state
+    }
+
+    /// Langue des noms d'usine ; une langue inconnue garde la courante.
+    pub fn set_locale(&mut self, code: &str) -> TroubadourResult<()> {
+        self.locale = Locale::new(code).ok_or_else(|| {
+            TroubadourError::ConfigError(format!(
+                "unknown language '{code}' (available: {})",
+                locale::available().join(", ")
+            ))
+        })?;
+        Ok(())
+    }
+
+    pub fn locale(&self) -> &Locale {
+        &self.locale
+    }
+
+    /// Origine des réglages de démarrage, pour `RequestEffectiveConfig`.
+    pub fn configure_effective_config(&mut self, entries: Vec<ConfigEntry>) {
+        self.effective_config = entries;
+    }
+
+    /// Branche l'envoi et la réception réseau activés dans la config.
+    /// À appeler une fois le profil chargé (bus et canal sont cherchés
+    /// par leur nom) et le moteur démarré (seul le bus joué part). Un
+    /// échec est journalisé, le moteur continue sans.
+    pub fn configure_network(
+        &mut self,
+        send: Option<&NetworkSendConfig>,
+        receive: Option<&NetworkReceiveConfig>,
+    ) {
+        if let Some(config) = send.filter(|c| c.enabled)
+            && let Err(e) = self.enable_network_send(config)
+        {
+            warn!(
+                "Network send to {}:{} not started: {e}",
+                config.host, config.port
+            );
+        }
+        if let Some(config) = receive.filter(|c| c.enabled)
+            && let Err(e) = self.enable_network_receive(config)
+        {
+            warn!("Network receive on port {} not started: {e}", config.port);
+        }
+    }
+
+    /// Envoie le bus `config.bus` à une autre machine (remplace un
+    /// envoi en cours). Seul le bus joué par le device de sortie passe
+    /// par le pipeline : moteur arrêté ou autre bus, l'envoi est refusé.
+    pub fn enable_network_send(&mut self, config: &NetworkSendConfig) -> TroubadourResult<()> {
+        let bus = self.channel_named(&config.bus, ChannelKind::Output)?;
+        if self.state != EngineState::Running {
+            return Err(TroubadourError::Network(format!(
+                "Cannot send bus '{}': the engine is not running",
+                config.bus
+            )));
+        }
+        if self.played_bus != Some(bus) {
+            return Err(TroubadourError::Network(format!(
+                "Cannot send bus '{}': only the bus played by the output device goes through the pipeline",
+                config.bus
+            )));
+        }
+        let format = if config.int16 {
+            PacketFormat::I16
+        } else {
+            PacketFormat::F32
+        };
+        let sink = NetworkSink::connect(
+            &config.host,
+            config.port,
+            bus,
+            format,
+            self.network_counters.clone(),
+        )?;
+        if let Ok(mut network) = self.network.lock() {
+            network.sink = Some(sink);
+        }
+        self.network_counters.set_sending(true);
+        Ok(())
+    }
+
+    pub fn disable_network_send(&mut self) {
+        if let Ok(mut network) = self.network.lock() {
+            network.sink = None;
+        }
+        self.network_counters.set_sending(false);
+    }
+
+    /// Joue un flux réseau sur le canal d'entrée `config.channel`
+    /// (remplace une réception en cours). Le canal suit ses routes vers
+    /// le bus joué, avec son volume, son pan et son mute.
+    pub fn enable_network_receive(
+        &mut self,
+        config: &NetworkReceiveConfig,
+    ) -> TroubadourResult<()> {
+        let channel = self.channel_named(&config.channel, ChannelKind::Input)?;
+        // L'ancienne réception libère le port avant la nouvelle
+        self.disable_network_receive();
+        self.network_counters.set_receiving(true);
+        let source = NetworkSource::bind(
+            &config.bind,
+            config.port,
+            channel,
+            config.jitter_packets,
+            self.network_counters.clone(),
+        );
+        let source = match source {
+            Ok(source) => source,
+            Err(e) => {
+                self.network_counters.set_receiving(false);
+                return Err(e);
+            }
+        };
+        if let Ok(mut network) = self.network.lock() {
+            network.source = Some(source);
+        }
+        Ok(())
+    }
+
+    pub fn disable_network_receive(&mut self) {
+        let stopped = self.network.lock().ok().and_then(|mut n| n.source.take());
+        // Thread de réception arrêté hors du lock
+        drop(stopped);
+        self.network_counters.set_receiving(false);
+    }
+
+    /// Compteurs du transport, lus sans bloquer le callback.
+    pub fn network_stats(&self) -> NetworkStats {
+        self.network_counters.stats()
+    }
+
+    fn send_network_stats(&self) {
+        let _ = self
+            .event_tx
+            .try_send(Event::NetworkStats(self.network_stats()));
+    }
+
+    /// Ouvre les taps activés dans la config (`[[taps]]`). Comme le
+    /// réseau : à appeler une fois le profil chargé, un échec est
+    /// journalisé et le moteur continue sans.
+    pub fn configure_taps(&mut self, taps: &[TapConfig]) {
+        for config in taps.iter().filter(|c| c.enabled) {
+            if let Err(e) = self.serve_tap(config) {
+                warn!(
+                    "Tap of {} on port {} not opened: {e}",
+                    config.bus, config.port
+                );
+            }
+        }
+    }
+
+    /// Ouvre un tap sur `bus` : le lecteur reçoit une copie de chaque
+    /// bloc du bus, post-fader, dans une file de `capacity_blocks` blocs
+    /// qui jette les plus anciens quand il ne suit pas. Le tap se ferme
+    /// quand le lecteur est droppé, ou avec `destroy_tap`.
+    ///
+    /// Comme l'envoi réseau, seul le bus joué par le device de sortie
+    /// passe par le pipeline : moteur arrêté ou autre bus, le tap
+    /// resterait vide et il est refusé.
+    pub fn create_tap(
+        &mut self,
+        bus: ChannelId,
+        capacity_blocks: usize,
+    ) -> TroubadourResult<TapReader> {
+        let config = self
+            .mixer
+            .channel(bus)
+            .ok_or(MixerError::ChannelNotFound(bus.0))?;
+        if config.kind != ChannelKind::Output {
+            return Err(MixerError::BusNotFound(bus.0).into());
+        }
+        if self.played_bus != Some(bus) {
+            return Err(TroubadourError::StreamError(format!(
+                "Cannot tap bus {}: only the bus played by the running pipeline is rendered",
+                bus.0
+            )));
+        }
+        let mut taps = self
+            .taps
+            .lock()
+            .map_err(|_| TroubadourError::StreamError("tap registry poisoned".into()))?;
+        Ok(taps.open(bus, capacity_blocks))
+    }
+
+    /// Ouvre un tap sur le bus `config.bus` et le sert en TCP sur
+    /// `127.0.0.1:config.port`, en trames (voir `tap`).
+    pub fn serve_tap(&mut self, config: &TapConfig) -> TroubadourResult<TapInfo> {
+        let bus = self.channel_named(&config.bus, ChannelKind::Output)?;
+        let reader = self.create_tap(bus, config.capacity_blocks)?;
+        let id = reader.id();
+        // Port pris : le tap est refermé avec son lecteur
+        let server = TapServer::bind(reader, config.port)?;
+        self.tap_servers.push(server);
+        self.taps()
+            .into_iter()
+            .find(|tap| tap.id == id)
+            .ok_or_else(|| TroubadourError::StreamError(format!("tap {id} closed")))
+    }
+
+    /// Ferme le tap `id` (et son serveur TCP). `false` : pas de tap `id`.
+    /// Ferme le tap `id`. Le tap d'un replay n'en est pas un pour
+    /// l'utilisateur : refusé, il se ferme avec `disable_replay`.
+    pub fn destroy_tap(&mut self, id: u32) -> bool {
+        if self.is_replay_tap(id) {
+            return false;
+        }
+        let closed = self.taps.lock().is_ok_and(|mut taps| taps.close(id));
+        // Le thread du serveur voit la fin du flux et s'arrête
+        self.tap_servers.retain(|server| server.id() != id);
+        closed
+    }
+
+    /// Taps ouverts avec leurs compteurs, par id croissant. Ceux des
+    /// replays n'y sont pas (voir `replays`).
+    pub fn taps(&self) -> Vec<TapInfo> {
+        let mut taps = self
+            .taps
+            .lock()
+            .map(|mut taps| taps.infos())
+            .unwrap_or_default();
+        taps.retain(|tap| !self.is_replay_tap(tap.id));
+        for tap in &mut taps {
+            tap.address = self
+                .tap_servers
+                .iter()
+                .find(|server| server.id() == tap.id)
+                .map(|server| server.address().to_string());
+        }
+        taps
+    }
+
+    fn is_replay_tap(&self, id: u32) -> bool {
+        self.replays.iter().any(|replay| replay.tap_id() == id)
+    }
+
+    fn send_tap_list(&self) {
+        let _ = self.event_tx.try_send(Event::TapList(self.taps()));
+    }
+
+    /// Replays ouverts au démarrage (`[replay]`). Comme les taps : à
+    /// appeler une fois le profil chargé, un bus introuvable est
+    /// journalisé et le moteur continue sans.
+    pub fn configure_replay(&mut self, config: &ReplayConfig) {
+        self.replay_config = config.clone();
+        for name in &config.buses {
+            let kept = self
+                .channel_named(name, ChannelKind::Output)
+                .and_then(|bus| self.enable_replay(bus, None));
+            if let Err(e) = kept {
+                warn!("Replay of {name} not kept: {e}");
+            }
+        }
+    }
+
+    /// Garde en mémoire les `seconds` dernières secondes de `bus` (la
+    /// durée de la config par défaut, voir `replay`). Remplace le replay
+    /// du bus, et ce qu'il gardait.
+    ///
+    /// Le replay est un tap : seul le bus joué par le device de sortie
+    /// se remplit.
+    pub fn enable_replay(
+        &mut self,
+        bus: ChannelId,
+        seconds: Option<u32>,
+    ) -> TroubadourResult<ReplayInfo> {
+        self.disable_replay(bus);
+        let reader = self.create_tap(bus, REPLAY_TAP_BLOCKS)?;
+        let seconds = seconds.unwrap_or(self.replay_config.seconds);
+        let replay = ReplayBuffer::start(reader, seconds, self.sample_rate.as_hz())?;
+        let info = replay.info();
+        self.replays.push(replay);
+        self.replays.sort_by_key(|replay| replay.bus().0);
+        Ok(info)
+    }
+
+    /// Libère le replay de `bus` (son thread s'arrête, son tap se
+    /// ferme). `false` : pas de replay sur ce bus.
+    pub fn disable_replay(&mut self, bus: ChannelId) -> bool {
+        let before = self.replays.len();
+        self.replays.retain(|replay| replay.bus() != bus);
+        self.replays.len() != before
+    }
+
+    /// Replays actifs, par id de bus.
+    pub fn replays(&self) -> Vec<ReplayInfo> {
+        self.replays.iter().map(ReplayBuffer::info).collect()
+    }
+
+    fn send_replay_status(&self) {
+        let _ = self.event_tx.try_send(Event::ReplayStatus(self.replays()));
+    }
+
+    /// Sauve en WAV la fin du replay de `bus` : `seconds` (tout ce qui
+    /// est gardé par défaut), dans `path` ou un fichier horodaté du
+    /// dossier des replays. La fin est copiée tout de suite ; le fichier
+    /// est écrit dans le thread des enregistrements → `Event::ReplaySaved`.
+    ///
+    /// Retourne le fichier et la durée copiée.
+    pub fn save_replay(
+        &self,
+        bus: ChannelId,
+        path: Option<PathBuf>,
+        seconds: Option<f32>,
+    ) -> TroubadourResult<(PathBuf, f32)> {
+        let replay = self
+            .replays
+            .iter()
+            .find(|replay| replay.bus() == bus)
+            .ok_or_else(|| TroubadourError::ConfigError(format!("no replay on bus {}", bus.0)))?;
+        let (samples, sample_rate) = replay.latest(seconds.unwrap_or(replay.info().seconds as f32));
+        if samples.is_empty() {
+            return Err(TroubadourError::AudioFile(format!(
+                "replay of bus {} is still empty",
+                bus.0
+            )));
+        }
+        let seconds = (samples.len() / 2) as f32 / sample_rate as f32;
+        let path = path.unwrap_or_else(|| {
+            let name: String = self
+                .mixer
+                .channel(bus)
+                .map(|c| c.name.clone())
+                .unwrap_or_default()
+                .chars()
+                .filter(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+                .collect();
+            self.replay_config
+                .dir
+                .clone()
+                .unwrap_or_else(ReplayConfig::default_dir)
+                .join(format!("Replay {name} {}.wav", journal::now_ms()))
+        });
+        let file = path.clone();
+        self.spawn_recording_task(path.clone(), move |progress| {
+            if let Some(dir) = file.parent() {
+                std::fs::create_dir_all(dir)
+                    .map_err(|e| TroubadourError::AudioFile(format!("{}: {e}", dir.display())))?;
+            }
+            wav::write_wav(&file, sample_rate, 2, &samples)?;
+            progress(1.0);
+            Ok(Event::ReplaySaved {
+                bus,
+                path: file,
+                seconds,
+            })
+        });
+        Ok((path, seconds))
+    }
+
+    /// Canal désigné par son nom dans la config (sans tenir compte de la casse).
+    fn channel_named(&self, name: &str, kind: ChannelKind) -> TroubadourResult<ChannelId> {
+        let channels = match kind {
+            ChannelKind::Input => self.mixer.inputs(),
+            ChannelKind::Output => self.mixer.outputs(),
+            ChannelKind::Submix => self.mixer.submixes(),
+        };
+        channels
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .map(|c| c.id)
+            .ok_or_else(|| {
+                let kind = match kind {
+                    ChannelKind::Input => "input channel",
+                    ChannelKind::Output => "bus",
+                    ChannelKind::Submix => "submix",
+                };
+                MixerError::InvalidParameter(format!("no {kind} named \"{name}\"")).into()
+            })
+    }
+
+    /// Charge les ensembles d'actions et leurs déclencheurs (`[schedule]`).
+    ///
+    /// Contrairement à `save_action_set`, rien n'est refusé ici : un
+    /// ensemble chargé avant son preset peut viser des canaux pas encore
+    /// créés ; l'erreur viendra à l'exécution.
+    pub fn configure_schedule(&mut self, config: &ScheduleConfig) {
+        self.action_sets = config.action_sets.clone();
+        self.scheduler.configure(config);
+    }
+
+    pub fn action_sets(&self) -> &BTreeMap<String, ActionSet> {
+        &self.action_sets
+    }
+
+    /// Déclencheurs quotidiens en place (sans ceux des ensembles supprimés).
+    pub fn schedule_triggers(&self) -> &[DailyTrigger] {
+        self.scheduler.triggers()
+    }
+
+    /// Charge les routages nommés de la config.
+    pub fn configure_routing_snapshots(&mut self, snapshots: BTreeMap<String, RoutingSnapshot>) {
+        self.routing_snapshots = snapshots;
+    }
+
+    /// Routages nommés, à réécrire dans la config.
+    pub fn routing_snapshots(&self) -> &BTreeMap<String, RoutingSnapshot> {
+        &self.routing_snapshots
+    }
+
+    /// Enregistre (ou remplace) les routes actuelles sous `name`.
+    pub fn save_routing_snapshot(&mut self, name: &str) -> MixerResult<()> {
+        if name.trim().is_empty() {
+            return Err(MixerError::InvalidParameter(
+                "routing snapshot name is empty".to_string(),
+            ));
+        }
+        let snapshot = RoutingSnapshot {
+            routes: self.mixer.routes().to_vec(),
+        };
+        self.routing_snapshots.insert(name.to_string(), snapshot);
+        Ok(())
+    }
+
+    /// Remplace toutes les routes par celles de `name`, tout ou rien
+    /// (voir `Mixer::replace_routes`), en une seule publication : le
+    /// callback passe d'un routage à l'autre entre deux blocs. Volumes,
+    /// effets et devices ne bougent pas ; les routes remplacées restent
+    /// dans un instantané automatique.
+    pub fn apply_routing_snapshot(&mut self, name: &str) -> TroubadourResult<()> {
+        let routes = self
+            .routing_snapshots
+            .get(name)
+            .map(|snapshot| snapshot.routes.clone())
+            .ok_or_else(|| {
+                MixerError::InvalidParameter(format!("routing snapshot '{name}' not found"))
+            })?;
+        self.auto_snapshot("ApplyRoutingSnapshot")?;
+        self.mixer.replace_routes(&routes)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        self.recovery.mark_dirty();
+        self.journal_change("ApplyRoutingSnapshot", None, None, Some(name.to_string()));
+        Ok(())
+    }
+
+    fn send_routing_snapshot_list(&self) {
+        let names = self.routing_snapshots.keys().cloned().collect();
+        let _ = self.event_tx.try_send(Event::RoutingSnapshotList(names));
+    }
+
+    /// Règle les scripts (`[scripting]`, dossier `dir`) et active ceux
+    /// de la config ; un script qui ne compile pas est signalé par
+    /// `Event::ScriptError`, les autres tournent.
+    pub fn configure_scripting(&mut self, config: &ScriptingConfig, dir: PathBuf) {
+        #[cfg(feature = "scripting")]
+        {
+            self.scripts = ScriptHost::new(dir, config);
+            for (name, message) in self.scripts.enable_configured(watchdog::monotonic_ms()) {
+                self.send_script_error(name, message);
+            }
+        }
+        #[cfg(not(feature = "scripting"))]
+        {
+            if !config.enabled.is_empty() {
+                warn!(
+                    "Scripts in {} ignored: {SCRIPTING_UNAVAILABLE}",
+                    dir.display()
+                );
+            }
+            self.scripting = config.clone();
+        }
+    }
+
+    /// Réglages des scripts, `enabled` à jour (pour la sauvegarde).
+    pub fn scripting_config(&self) -> ScriptingConfig {
+        #[cfg(feature = "scripting")]
+        return self.scripts.config();
+        #[cfg(not(feature = "scripting"))]
+        self.scripting.clone()
+    }
+
+    #[cfg(feature = "scripting")]
+    fn send_script_list(&self) {
+        let _ = self
+            .event_tx
+            .try_send(Event::ScriptList(self.scripts.list()));
+    }
+
+    #[cfg(feature = "scripting")]
+    fn enable_script(&mut self, name: &str) {
+        match self.scripts.enable(name, watchdog::monotonic_ms()) {
+            Ok(()) => self.send_script_list(),
+            Err(e) => self.send_error(format!("Cannot enable script '{name}': {e}")),
+        }
+    }
+
+    #[cfg(feature = "scripting")]
+    fn disable_script(&mut self, name: &str) {
+        if self.scripts.disable(name) {
+            info!("Script '{name}' disabled");
+            self.send_script_list();
+        } else {
+            self.send_error(format!("Script '{name}' is not enabled"));
+        }
+    }
+
+    /// Tick des scripts, si dû : leurs commandes repassent par la file
+    /// de commandes (traitées au prochain `process_commands`, comme
+    /// celles de l'UI), leurs erreurs partent vers l'UI.
+    #[cfg(feature = "scripting")]
+    fn run_scripts(&mut self, now_ms: u64) {
+        if !self.scripts.is_due(now_ms) {
+            return;
+        }
+        let view = MixerView::from_mixer(&self.mixer, &self.levels);
+        let output = self.scripts.tick(view, now_ms);
+        for command in output.commands {
+            if self.control_tx.try_send(command).is_err() {
+                warn!("Command queue full: script command dropped");
+            }
+        }
+        for (name, message) in output.errors {
+            self.send_script_error(name, message);
+        }
+    }
+
+    #[cfg(feature = "scripting")]
+    fn send_script_error(&self, name: String, message: String) {
+        warn!("Script '{name}': {message}");
+        let _ = self.event_tx.try_send(Event::ScriptError { name, message });
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn send_script_list(&self) {
+        self.send_error(SCRIPTING_UNAVAILABLE.to_string());
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn enable_script(&mut self, name: &str) {
+        self.send_error(format!(
+            "Cannot enable script '{name}': {SCRIPTING_UNAVAILABLE}"
+        ));
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn disable_script(&mut self, name: &str) {
+        self.send_error(format!(
+            "Cannot disable script '{name}': {SCRIPTING_UNAVAILABLE}"
+        ));
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    fn run_scripts(&mut self, _now_ms: u64) {}
+
+    /// Relit les devices par défaut du système (au plus une fois par
+    /// seconde). Si un sens du pipeline les suit et que son défaut a
+    /// changé, la paire de streams est rouverte sur le nouveau device ;
+    /// sinon aucun stream n'est touché.
+    fn check_default_devices(&mut self, now_ms: u64) {
+        if self.state != EngineState::Running || now_ms < self.next_default_check_ms {
+            return;
+        }
+        self.next_default_check_ms = now_ms + watchdog::CHECK_INTERVAL_MS;
+        self.check_input_fallback();
+
+        let defaults = self.current_system_defaults();
+        if defaults == self.system_defaults {
+            return;
+        }
+        let (Some(input), Some(output)) = (self.stream_device(true), self.stream_device(false))
+        else {
+            return;
+        };
+        let followed = self.followed_devices(&defaults, &input, &output);
+        // Les streams s'ouvrent sur les nouveaux défauts ; repris si la
+        // réouverture échoue, pour que le prochain passage réessaie
+        let previous = std::mem::replace(&mut self.system_defaults, defaults);
+        let Some((new_input, new_output)) = followed else {
+            return;
+        };
+        // Une entrée sur son repli le garde : `check_input_fallback` la
+        // ramène sur un device
+        let new_input = match self.pipeline_input() {
+            Some(source) if new_input == input => source,
+            _ => InputSource::Device(new_input),
+        };
+
+        info!("System default device changed, reopening streams on {new_input} → {new_output}");
+        match self.reconnect_pipeline(&new_input, &new_output) {
+            // L'UI met à jour le device marqué comme résolu
+            Ok(()) => self.send_device_list(),
+            Err(_) => self.system_defaults = previous,
+        }
+    }
+
+    /// Source de l'entrée du pipeline pour le device `primary` (`None` :
+    /// le défaut du système), selon le repli du Mic.
+    fn resolve_pipeline_input(
+        &self,
+        primary: Option<&str>,
+        defaults: &SystemDefaults,
+    ) -> Option<ResolvedInput> {
+        let resolved = fallback::resolve_input(
+            primary,
+            defaults.input.as_deref(),
+            &self.pipeline_input_policy(),
+            |name| self.device_present(name, true),
+        )?;
+        if resolved.fallback {
+            warn!(
+                "Input device {} not found, using {}",
+                primary.unwrap_or("(system default)"),
+                resolved.source
+            );
+        }
+        Some(resolved)
+    }
+
+    /// Repli de l'entrée du pipeline (celui du Mic).
+    fn pipeline_input_policy(&self) -> InputFallback {
+        self.mixer
+            .channel(PIPELINE_INPUT_CHANNEL)
+            .map(|c| c.input_fallback.clone())
+            .unwrap_or_default()
+    }
+
+    /// Note la source que le pipeline vient d'ouvrir pour son entrée, et
+    /// signale l'entrée ou la sortie d'un repli.
+    fn record_pipeline_input(&mut self, primary: Option<String>, input: ResolvedInput) {
+        let active = input.fallback.then_some(ActiveFallback {
+            primary,
+            source: input.source,
+        });
+        if active == self.input_fallback {
+            return;
+        }
+        match &active {
+            Some(active) => info!("Input on fallback: {}", active.source),
+            None => info!("Input back on its device"),
+        }
+        let _ = self.event_tx.try_send(Event::InputFallback {
+            channel: PIPELINE_INPUT_CHANNEL,
+            source: active.as_ref().map(|a| a.source.clone()),
+        });
+        self.input_fallback = active;
+    }
+
+    /// Source vers laquelle quitter le repli en cours : le device revenu,
+    /// ou un meilleur repli. `None` : rester.
+    fn input_fallback_switch(&self) -> Option<ResolvedInput> {
+        let active = self.input_fallback.as_ref()?;
+        let defaults = self.current_system_defaults();
+        let policy = self.pipeline_input_policy();
+        let resolved = fallback::resolve_input(
+            active.primary.as_deref(),
+            defaults.input.as_deref(),
+            &policy,
+            |name| self.device_present(name, true),
+        )?;
+        (resolved.source != active.source).then_some(resolved)
+    }
+
+    /// Quitte le repli de l'entrée si son device est revenu, par une
+    /// bascule sans trou (`switch_pipeline`).
+    fn check_input_fallback(&mut self) {
+        let Some(resolved) = self.input_fallback_switch() else {
+            return;
+        };
+        let Some(output) = self.stream_device(false) else {
+            return;
+        };
+        let primary = self.input_fallback.as_ref().and_then(|a| a.primary.clone());
+        info!("Input moving from its fallback to {}", resolved.source);
+        if self.reconnect_pipeline(&resolved.source, &output).is_ok() {
+            self.record_pipeline_input(primary, resolved);
+        }
+    }
+
+    /// Paire (entrée, sortie) sur laquelle rouvrir le pipeline ouvert sur
+    /// `input` → `output` pour suivre `defaults`. `None` : aucun sens qui
+    /// suit le défaut du système n'a changé de device.
+    fn followed_devices(
+        &self,
+        defaults: &SystemDefaults,
+        input: &str,
+        output: &str,
+    ) -> Option<(String, String)> {
+        let follow = |is_input: bool, current: &str| {
+            let default = if is_input {
+                &defaults.input
+            } else {
+                &defaults.output
+            };
+            default
+                .clone()
+                .filter(|d| self.follows_system_default(is_input) && d != current)
+        };
+        let new_input = follow(true, input);
+        let new_output = follow(false, output);
+        if new_input.is_none() && new_output.is_none() {
+            return None;
+        }
+        Some((
+            new_input.unwrap_or_else(|| input.to_string()),
+            new_output.unwrap_or_else(|| output.to_string()),
+        ))
+    }
+
+    /// Le pipeline suit le défaut du système dans ce sens : le Mic pour
+    /// l'entrée, un bus au moins pour la sortie.
+    fn follows_system_default(&self, is_input: bool) -> bool {
+        if is_input {
+            self.mixer
+                .channel(PIPELINE_INPUT_CHANNEL)
+                .is_some_and(|c| c.device_name.follows_system_default())
+        } else {
+            self.mixer
+                .outputs()
+                .iter()
+                .any(|c| c.device_name.follows_system_default())
+        }
+    }
+
+    /// Devices par défaut actuels du système.
+    fn current_system_defaults(&self) -> SystemDefaults {
+        match &self.default_source {
+            Some(source) => source.system_defaults(),
+            None => self.device_manager.system_defaults(),
+        }
+    }
+
+    /// Remplace la source des devices par défaut du système.
+    pub fn set_default_device_source(&mut self, source: Box<dyn DefaultDeviceSource>) {
+        self.default_source = Some(source);
+    }
+
+    /// Vérifie les heartbeats des callbacks (au plus une fois par seconde)
+    /// et rouvre le pipeline si un stream est bloqué.
+    fn check_watchdog(&mut self, now_ms: u64) {
+        if self.state != EngineState::Running {
+            return;
+        }
+        let mut restart = false;
+        for action in self.watchdog.check(now_ms) {
+            match action {
+                WatchdogAction::Stalled {
+                    device_name,
+                    is_input,
+                    silent_ms,
+                } => {
+                    warn!("Stream {device_name} stalled: no callback for {silent_ms} ms");
+                    let _ = self.event_tx.try_send(Event::StreamStalled {
+                        device_name,
+                        is_input,
+                        silent_ms,
+                    });
+                }
+                WatchdogAction::Restart {
+                    device_name,
+                    attempt,
+                    ..
+                } => {
+                    info!("Restarting streams for {device_name} (attempt {attempt})");
+                    restart = true;
+                }
+                WatchdogAction::GaveUp { device_name, .. } => {
+                    self.send_error(format!(
+                        "Stream {device_name} is still stalled after {} restart(s)",
+                        self.watchdog.config().max_restarts
+                    ));
+                }
+                WatchdogAction::Recovered {
+                    device_name,
+                    is_input,
+                } => {
+                    info!("Stream {device_name} recovered");
+                    let _ = self.event_tx.try_send(Event::StreamRecovered {
+                        device_name,
+                        is_input,
+                    });
+                }
+            }
+        }
+
+        // Entrée et sortie forment un seul pipeline : une réouverture
+        // suffit, même si les deux streams sont bloqués
+        if restart
+            && let (Some(input), Some(output)) = (self.pipeline_input(), self.stream_device(false))
+            && let Err(e) = self.reconnect_pipeline(&input, &output)
+        {
+            self.send_error(format!("Cannot restart stalled streams: {e}"));
+        }
+    }
+
+    /// `switch_pipeline` à l'initiative du moteur (stream bloqué, défaut
+    /// du système changé, device revenu) : comptée dans les
+    /// reconnexions de la session si elle réussit.
+    fn reconnect_pipeline(&mut self, input: &InputSource, output: &str) -> TroubadourResult<()> {
+        self.switch_pipeline(input, output)?;
+        self.session.record_reconnect();
+        Ok(())
+    }
+
+    /// Écrit l'instantané de secours si l'état a changé depuis la
+    /// dernière écriture (au plus une fois par intervalle configuré).
+    fn write_recovery_snapshot(&mut self, now: Instant) {
+        if !self.recovery.is_due(now) {
+            return;
+        }
+        let snapshot = RecoverySnapshot {
+            saved_at_ms: journal::now_ms(),
+            profile: self.to_profile("Recovered session"),
+            ui: self.ui_state.clone(),
+        };
+        if let Err(e) = self.recovery.write(now, &snapshot) {
+            warn!("Cannot write recovery snapshot: {e}");
+        }
+    }
+
+    /// Enregistre une modification de paramètre de canal dans le journal.
+    fn journal_change(
+        &mut self,
+        action: &str,
+        channel: Option<ChannelId>,
+        before: Option<String>,
+        after: Option<String>,
+    ) {
+        // Canal inexistant → rien n'a changé, rien à journaliser
+        if after.is_none() {
+            return;
+        }
+        self.journal.record(ChangeEntry {
+            timestamp_ms: journal::now_ms(),
+            action: action.to_string(),
+            channel,
+            before,
+            after,
+        });
+    }
+
+    /// Enregistre un ajout/suppression de route dans le journal.
+    fn journal_route(&mut self, action: &str, from: ChannelId, to: ChannelId) {
+        self.journal.record(ChangeEntry {
+            timestamp_ms: journal::now_ms(),
+            action: action.to_string(),
+            channel: Some(from),
+            before: None,
+            after: Some(format!("-> {}", to.0)),
+        });
+    }
+
+    /// Config actuelle de la chaîne d'effets d'un canal.
+    pub fn channel_effects(&self, channel: ChannelId) -> EffectsPreset {
+        self.channel_effects
+            .get(&channel)
+            .cloned()
+            .unwrap_or_else(EffectsPreset::default_preset)
+    }
+
+    /// Remplace la chaîne d'effets d'un canal.
+    ///
+    /// Pour le canal traité par le pipeline, la nouvelle chaîne est
+    /// construite HORS du lock, puis échangée : le callback ne voit
+    /// qu'un swap de pointeur, jamais un moteur redémarré.
+    ///
+    /// Les automations en cours passent sur la nouvelle chaîne, sauf
+    /// celles dont le paramètre change ici : réglé à la main, il n'est
+    /// plus automatisé (`EffectAutomationCancelled`).
+    ///
+    /// Les valeurs hors plage (preset ou profil édité à la main) sont
+    /// ramenées à leur borne : la config gardée est celle qu'on entend.
+    fn set_channel_effects(&mut self, channel: ChannelId, mut preset: EffectsPreset) {
+        preset.clamp();
+        if channel == PIPELINE_INPUT_CHANNEL {
+            self.collect_finished_automation();
+            let mut chain = EffectsChain::from_preset(&preset);
+            chain.set_sample_rate(self.processing_rate);
+            chain.set_profiling(self.dsp_profiling);
+            let mut previous = self.channel_effects(channel);
+            let mut next = preset.clone();
+            let mut cancelled = Vec::new();
+            if let Ok(mut current) = self.lock_dsp_chain() {
+                chain.set_detailed_metering(current.detailed_metering());
+                for ramp in current.take_automation() {
+                    let before = previous.param_mut(ramp.effect_index, &ramp.param).copied();
+                    let after = next.param_mut(ramp.effect_index, &ramp.param).copied();
+                    if before != after || !chain.resume(ramp.clone()) {
+                        cancelled.push(ramp);
+                    }
+                }
+            }
+            self.swap_dsp_chain(chain);
+            for ramp in cancelled {
+                let _ = self.event_tx.try_send(Event::EffectAutomationCancelled {
+                    channel,
+                    effect_index: ramp.effect_index,
+                    param: ramp.param,
+                });
+            }
+        }
+        self.channel_effects.insert(channel, preset);
+        self.reload_bus_effects(channel);
+        self.recovery.mark_dirty();
+    }
+
+    /// Lock de la chaîne du canal traité, rechange en attente installée
+    /// d'abord : le thread de contrôle ne lit ni ne règle jamais une
+    /// chaîne déjà remplacée.
+    fn lock_dsp_chain(&self) -> LockResult<MutexGuard<'_, EffectsChain>> {
+        let mut chain = self.dsp_chain.lock()?;
+        for swap in &self.effects_swaps {
+            swap.install(&mut chain);
+        }
+        Ok(chain)
+    }
+
+    /// Remplace la chaîne du canal traité : par la file de commandes
+    /// quand un pipeline tourne (installée en tête de bloc, sans que le
+    /// callback n'attende le lock), directement sinon.
+    fn swap_dsp_chain(&mut self, chain: EffectsChain) {
+        // Une seule rechange en attente : une plus ancienne, installée
+        // après celle-ci, remettrait l'ancienne chaîne
+        drop(self.lock_dsp_chain());
+        self.collect_effects_swaps();
+        let swap = EffectsSwap::new(chain);
+        let queued = self.shared_state.has_readers()
+            && self
+                .shared_state
+                .send(EngineCommand::SwapEffectsProcessor(swap.clone()))
+                .is_ok();
+        if !queued && let Ok(mut current) = self.dsp_chain.lock() {
+            swap.install(&mut current);
+        }
+        self.effects_swaps.push(swap);
+    }
+
+    /// Libère les chaînes remplacées, ici plutôt que dans le callback.
+    /// Une rechange que plus aucun pipeline ne verra (arrêté entre-temps)
+    /// est installée maintenant.
+    fn collect_effects_swaps(&mut self) {
+        let orphaned = |s: &Arc<EffectsSwap>| !s.is_installed() && Arc::strong_count(s) == 1;
+        if self.effects_swaps.iter().any(orphaned) {
+            drop(self.lock_dsp_chain());
+        }
+        self.effects_swaps
+            .retain(|s| !s.is_installed() || Arc::strong_count(s) > 1);
+    }
+
+    /// Reconstruit les effets du bus joué si c'est `bus` : chaînes
+    /// construites hors du lock, puis échangées.
+    fn reload_bus_effects(&mut self, bus: ChannelId) {
+        if !self.bus_effects.lock().is_ok_and(|e| e.bus() == Some(bus)) {
+            return;
+        }
+        let mut effects = BusEffects::new();
+        effects.set_profiling(self.dsp_profiling);
+        effects.load(
+            Some(bus),
+            self.channel_effects.get(&bus),
+            self.processing_rate,
+        );
+        if let Ok(mut current) = self.bus_effects.lock() {
+            *current = effects;
+        }
+    }
+
+    /// Fait glisser un paramètre d'effet de sa valeur actuelle vers
+    /// `target` en `duration_ms`, bloc par bloc dans le callback audio.
+    ///
+    /// Plusieurs automations coexistent tant qu'elles visent des
+    /// paramètres différents ; une nouvelle sur le même paramètre
+    /// remplace l'ancienne et repart de la valeur atteinte. La valeur
+    /// finale n'entre dans la config du canal (profils, recovery) qu'à
+    /// la fin de la rampe (`EffectAutomationCompleted`).
+    ///
+    /// Seul le canal traité par le pipeline a une chaîne qui tourne.
+    pub fn automate_effect_param(
+        &mut self,
+        channel: ChannelId,
+        effect_index: usize,
+        param: &str,
+        target: f32,
+        duration_ms: u32,
+    ) -> TroubadourResult<()> {
+        if self.mixer.channel(channel).is_none() {
+            return Err(MixerError::ChannelNotFound(channel.0).into());
+        }
+        if channel != PIPELINE_INPUT_CHANNEL {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} has no running effects chain",
+                channel.0
+            ))
+            .into());
+        }
+        // Les index d'effets suivent la chaîne d'un preset : la chaîne
+        // de démarrage (`default_mic_chain`, sans AGC) est d'abord
+        // remplacée par celle du preset par défaut
+        if !self.channel_effects.contains_key(&channel) {
+            self.set_channel_effects(channel, EffectsPreset::default_preset());
+        }
+        let start = self
+            .channel_effects(channel)
+            .param_mut(effect_index, param)
+            .copied()
+            .ok_or_else(|| {
+                MixerError::InvalidParameter(format!(
+                    "no parameter {param} on effect {effect_index}"
+                ))
+            })?;
+
+        let ramp = ParamRamp::new(
+            effect_index,
+            param,
+            start,
+            target,
+            duration_ms,
+            self.processing_rate,
+        );
+        let mut finished = Vec::new();
+        if let Ok(mut chain) = self.lock_dsp_chain() {
+            // Le preset peut décrire plus de bandes d'EQ que la chaîne n'en joue
+            if !chain.automate(ramp) {
+                return Err(MixerError::InvalidParameter(format!(
+                    "effect {effect_index} cannot automate {param}"
+                ))
+                .into());
+            }
+            // Durée nulle : déjà à la cible, rien à attendre du callback
+            finished = chain.take_finished_automation();
+        }
+        self.report_finished_automation(finished);
+        Ok(())
+    }
+
+    /// Écoute du détecteur d'un effet de la chaîne (mêmes index que
+    /// `automate_effect_param`).
+    ///
+    /// Réglage de l'instant, pas du preset : rien n'est journalisé ni
+    /// sauvé, et l'arrêt du moteur coupe toutes les écoutes.
+    ///
+    /// Un effet bypassé ne traite rien : on ne l'écoute pas (le couper
+    /// reste possible).
+    pub fn set_detector_listen(
+        &mut self,
+        channel: ChannelId,
+        effect_index: usize,
+        listen: bool,
+    ) -> TroubadourResult<()> {
+        if self.mixer.channel(channel).is_none() {
+            return Err(MixerError::ChannelNotFound(channel.0).into());
+        }
+        if channel != PIPELINE_INPUT_CHANNEL {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} has no running effects chain",
+                channel.0
+            ))
+            .into());
+        }
+        if !self.channel_effects.contains_key(&channel) {
+            self.set_channel_effects(channel, EffectsPreset::default_preset());
+        }
+        let mut chain = self
+            .lock_dsp_chain()
+            .map_err(|_| TroubadourError::LockPoisoned("effects chain"))?;
+        if listen
+            && chain
+                .processors()
+                .nth(effect_index)
+                .is_some_and(|p| p.is_bypassed())
+        {
+            return Err(MixerError::InvalidParameter(format!(
+                "effect {effect_index} is bypassed: enable it to listen to its detector"
+            ))
+            .into());
+        }
+        if !chain.set_detector_listen(effect_index, listen) {
+            return Err(MixerError::InvalidParameter(format!(
+                "effect {effect_index} has no detector"
+            ))
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Écoute de la différence sec − traité d'une entrée sur son retour
+    /// (voir `Mixer::set_effects_diff_monitor`).
+    ///
+    /// Comme l'écoute du détecteur : ni journalisée ni sauvée, coupée à
+    /// l'arrêt du moteur. Seule la chaîne du Mic tourne en direct.
+    pub fn set_effects_diff_monitor(
+        &mut self,
+        channel: ChannelId,
+        enabled: bool,
+    ) -> TroubadourResult<()> {
+        if enabled && self.mixer.channel(channel).is_some() && channel != PIPELINE_INPUT_CHANNEL {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} has no running effects chain",
+                channel.0
+            ))
+            .into());
+        }
+        self.mixer.set_effects_diff_monitor(channel, enabled)?;
+        let _ = self
+            .event_tx
+            .try_send(Event::EffectsDiffMonitor { channel, enabled });
+        Ok(())
+    }
+
+    /// Chaîne d'effets d'un canal, étage par étage, dans l'ordre de
+    /// traitement.
+    ///
+    /// Canal du pipeline : la chaîne qui tourne, avec réduction de gain
+    /// et crêtes du dernier bloc. Autres canaux : la chaîne que leur
+    /// preset construirait, sans mesures. Les paramètres viennent du
+    /// preset du canal ; la chaîne de démarrage (`default_mic_chain`,
+    /// sans preset) n'en a pas. Bus joué : ses chaînes, de part et
+    /// d'autre du fader, avec leur réduction de gain.
+    pub fn channel_effect_stages(
+        &self,
+        channel: ChannelId,
+    ) -> TroubadourResult<Vec<EffectStageInfo>> {
+        if self.mixer.channel(channel).is_none() {
+            return Err(MixerError::ChannelNotFound(channel.0).into());
+        }
+        let preset = self.channel_effects.get(&channel);
+        let is_bus = self
+            .mixer
+            .channel(channel)
+            .is_some_and(|c| c.kind == ChannelKind::Output);
+        let insert_point =
+            |i| is_bus.then(|| preset.map(|p| p.insert_point(i)).unwrap_or_default());
+        let stages = |chain: &EffectsChain, live: bool| {
+            let peaks = chain.stage_peaks().filter(|_| live);
+            chain
+                .processors()
+                .enumerate()
+                .map(|(i, proc)| {
+                    let peak = peaks.and_then(|p| p.get(i));
+                    EffectStageInfo {
+                        name: proc.name().to_string(),
+                        params: preset.map(|p| p.params(i)).unwrap_or_default(),
+                        bypassed: proc.is_bypassed(),
+                        poisoned: chain.is_poisoned(i),
+                        latency_samples: proc.latency_samples(),
+                        gain_reduction: proc.gain_reduction().filter(|_| live),
+                        input_peak: peak.map(|p| p.input),
+                        output_peak: peak.map(|p| p.output),
+                        insert_point: insert_point(i),
+                    }
+                })
+                .collect::<Vec<_>>()
+        };
+        if channel == PIPELINE_INPUT_CHANNEL {
+            let chain = self.lock_dsp_chain().map_err(|_| {
+                MixerError::InvalidParameter("effects chain unavailable".to_string())
+            })?;
+            return Ok(stages(&chain, true));
+        }
+        let mut listed = stages(
+            &EffectsChain::from_preset(&self.channel_effects(channel)),
+            false,
+        );
+        if let Ok(effects) = self.bus_effects.lock()
+            && effects.bus() == Some(channel)
+        {
+            for (i, stage) in listed.iter_mut().enumerate() {
+                let live = stage.insert_point.and_then(|point| effects.stage(i, point));
+                stage.gain_reduction = live.and_then(|p| p.gain_reduction());
+            }
+        }
+        Ok(listed)
+    }
+
+    /// Courbe prédite de la chaîne d'effets de `channel` au rate de
+    /// traitement (voir `EffectsChain::estimate_response`) ; `points`
+    /// est ramené à `MAX_RESPONSE_POINTS`.
+    pub fn channel_eq_response(
+        &self,
+        channel: ChannelId,
+        points: usize,
+    ) -> TroubadourResult<FrequencyResponse> {
+        if self.mixer.channel(channel).is_none() {
+            return Err(MixerError::ChannelNotFound(channel.0).into());
+        }
+        let points = points.min(MAX_RESPONSE_POINTS);
+        if channel == PIPELINE_INPUT_CHANNEL {
+            let chain = self.lock_dsp_chain().map_err(|_| {
+                MixerError::InvalidParameter("effects chain unavailable".to_string())
+            })?;
+            return Ok(chain.estimate_response(self.processing_rate, points));
+        }
+        let mut chain = EffectsChain::from_preset(&self.channel_effects(channel));
+        chain.set_sample_rate(self.processing_rate);
+        Ok(chain.estimate_response(self.processing_rate, points))
+    }
+
+    /// Place un effet d'un bus avant ou après son fader (mêmes index
+    /// que `automate_effect_param`). Le réglage est gardé dans le preset
+    /// du bus : profils, recovery et presets sauvés le suivent.
+    pub fn set_effect_insert_point(
+        &mut self,
+        bus: ChannelId,
+        effect_index: usize,
+        point: InsertPoint,
+    ) -> TroubadourResult<()> {
+        let config = self
+            .mixer
+            .channel(bus)
+            .ok_or(MixerError::ChannelNotFound(bus.0))?;
+        if config.kind != ChannelKind::Output {
+            return Err(MixerError::BusNotFound(bus.0).into());
+        }
+        let mut preset = self.channel_effects(bus);
+        if preset.insert_point(effect_index) == point {
+            return Ok(());
+        }
+        if !preset.set_insert_point(effect_index, point) {
+            return Err(MixerError::InvalidParameter(format!("no effect {effect_index}")).into());
+        }
+        self.set_channel_effects(bus, preset);
+        Ok(())
+    }
+
+    /// Mesure des crêtes avant/après chaque effet d'un canal.
+    ///
+    /// Comme l'écoute du détecteur, un réglage de l'instant : rien n'est
+    /// sauvé. Il survit en revanche à un changement de preset (la
+    /// nouvelle chaîne reprend le réglage de l'ancienne).
+    pub fn set_detailed_effect_metering(
+        &mut self,
+        channel: ChannelId,
+        enabled: bool,
+    ) -> TroubadourResult<()> {
+        if self.mixer.channel(channel).is_none() {
+            return Err(MixerError::ChannelNotFound(channel.0).into());
+        }
+        if channel != PIPELINE_INPUT_CHANNEL {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} has no running effects chain",
+                channel.0
+            ))
+            .into());
+        }
+        if let Ok(mut chain) = self.lock_dsp_chain() {
+            chain.set_detailed_metering(enabled);
+        }
+        Ok(())
+    }
+
+    /// Active ou coupe le chronométrage des effets qui tournent : la
+    /// chaîne du Mic et celles du bus joué. Les chaînes reconstruites
+    /// ensuite (preset, redémarrage) reprennent le réglage ;
+    /// `[audio] dsp_profiling` l'active au lancement.
+    pub fn set_dsp_profiling(&mut self, enabled: bool) {
+        self.dsp_profiling = enabled;
+        if let Ok(mut chain) = self.lock_dsp_chain() {
+            chain.set_profiling(enabled);
+        }
+        if let Ok(mut effects) = self.bus_effects.lock() {
+            effects.set_profiling(enabled);
+        }
+    }
+
+    pub fn dsp_profiling(&self) -> bool {
+        self.dsp_profiling
+    }
+
+    /// Temps moyen de chaque effet qui tourne, lu dans les chaînes : le
+    /// callback n'écrit que dans leurs tables, allouées à l'activation.
+    /// Vide tant que le profilage est coupé.
+    pub fn dsp_profile(&self) -> DspProfile {
+        let budget = |frames: usize| frames as f32 / self.processing_rate * 1e6;
+        let mut block_budget_us = 0.0_f32;
+        let mut stages = Vec::new();
+        if let Ok(chain) = self.lock_dsp_chain()
+            && let Some(micros) = chain.stage_micros()
+        {
+            let budget = budget(chain.profiled_frames());
+            block_budget_us = block_budget_us.max(budget);
+            for (index, (proc, &micros)) in chain.processors().zip(micros).enumerate() {
+                stages.push(EffectTiming::new(
+                    PIPELINE_INPUT_CHANNEL,
+                    index,
+                    proc.name(),
+                    micros,
+                    budget,
+                ));
+            }
+        }
+        if let Ok(effects) = self.bus_effects.lock()
+            && let Some(bus) = effects.bus()
+        {
+            let budget = budget(effects.profiled_frames());
+            block_budget_us = block_budget_us.max(budget);
+            for (index, (name, micros)) in effects.stage_micros().into_iter().enumerate() {
+                stages.push(EffectTiming::new(bus, index, name, micros, budget));
+            }
+        }
+        DspProfile {
+            enabled: self.dsp_profiling,
+            block_budget_us,
+            stages,
+        }
+    }
+
+    /// Relève les automations terminées par le callback.
+    fn collect_finished_automation(&mut self) {
+        if self.finished_ramps.swap(0, Ordering::Relaxed) == 0 {
+            return;
+        }
+        let finished = match self.lock_dsp_chain() {
+            Ok(mut chain) => chain.take_finished_automation(),
+            Err(_) => return,
+        };
+        self.report_finished_automation(finished);
+    }
+
+    /// Cible d'automation selon la politique des commandes : ramenée
+    /// dans la plage du paramètre (`Clamp`) ou refusée (`Reject`).
+    /// Un paramètre inconnu passe : `automate_effect_param` le refuse.
+    fn checked_effect_target(
+        &self,
+        channel: ChannelId,
+        effect_index: usize,
+        param: &str,
+        target: f32,
+    ) -> Result<f32, ParamViolation> {
+        let Some(range) = self
+            .channel_effects(channel)
+            .param_range(effect_index, param)
+        else {
+            return Ok(target);
+        };
+        match self.effect_param_policy {
+            ParamPolicy::Clamp => Ok(range.clamp(target)),
+            ParamPolicy::Reject if range.contains(target) => Ok(target),
+            ParamPolicy::Reject => Err(ParamViolation {
+                field: format!("{}.{param}", EffectsPreset::EFFECT_NAMES[effect_index]),
+                value: target,
+                range,
+            }),
+        }
+    }
+
+    fn reject_effect_params(&self, channel: ChannelId, violations: Vec<ParamViolation>) {
+        let fields: Vec<String> = violations.iter().map(ToString::to_string).collect();
+        warn!("Effect parameters rejected: {}", fields.join(", "));
+        let _ = self.event_tx.try_send(Event::EffectParamsRejected {
+            channel,
+            violations,
+        });
+    }
+
+    /// Reporte la valeur finale des rampes dans la config du canal.
+    fn report_finished_automation(&mut self, finished: Vec<ParamRamp>) {
+        if finished.is_empty() {
+            return;
+        }
+        let channel = PIPELINE_INPUT_CHANNEL;
+        let mut preset = self.channel_effects(channel);
+        for ramp in finished {
+            if let Some(value) = preset.param_mut(ramp.effect_index, &ramp.param) {
+                *value = ramp.target();
+            }
+            let _ = self.event_tx.try_send(Event::EffectAutomationCompleted {
+                channel,
+                effect_index: ramp.effect_index,
+                value: ramp.target(),
+                param: ramp.param,
+            });
+        }
+        self.channel_effects.insert(channel, preset);
+        self.recovery.mark_dirty();
+    }
+
+    /// Change le sample rate sans redémarrer tout le moteur.
+    ///
+    /// Moteur démarré : les streams sont fermés (le callback s'arrête,
+    /// plus rien ne touche aux effets), puis rouverts sur les mêmes
+    /// devices au nouveau rate. Le pipeline reconstruit le pont de
+    /// resampling, la ligne de retard et la détection de silence ; la
+    /// chaîne d'effets garde ses réglages et recalcule ses coefficients.
+    ///
+    /// Moteur arrêté : le rate est retenu pour le prochain démarrage.
+    ///
+    /// Un device qui ne supporte pas ce rate reste à son rate par défaut
+    /// (le resampler absorbe la différence) : `Event::SampleRateChanged`
+    /// annonce le rate réellement obtenu.
+    pub fn set_sample_rate(&mut self, rate: SampleRate) -> TroubadourResult<()> {
+        self.sample_rate = rate;
+
+        if self.state != EngineState::Running {
+            self.set_processing_rate(rate.as_hz() as f32);
+        } else {
+            let (Some(input), Some(output)) = (self.pipeline_input(), self.stream_device(false))
+            else {
+                return Err(TroubadourError::StreamError("No active streams".into()));
+            };
+
+            info!("Switching sample rate to {} Hz", rate.as_hz());
+            self.reopen_pipeline(&input, &output)?;
+        }
+
+        let _ = self.event_tx.try_send(Event::SampleRateChanged {
+            sample_rate: self.processing_rate as u32,
+        });
+        Ok(())
+    }
+
+    /// Change la taille de buffer des streams ; moteur en marche, le
+    /// pipeline est rouvert pour l'appliquer.
+    pub fn set_buffer_size(&mut self, size: BufferSize) -> TroubadourResult<()> {
+        self.buffer_size = size;
+        if self.state != EngineState::Running {
+            return Ok(());
+        }
+        let (Some(input), Some(output)) = (self.pipeline_input(), self.stream_device(false)) else {
+            return Err(TroubadourError::StreamError("No active streams".into()));
+        };
+        info!("Switching buffer size to {} frames", size.as_frames());
+        self.reopen_pipeline(&input, &output)
+    }
+
+    /// Device du stream ouvert dans le sens donné.
+    fn stream_device(&self, is_input: bool) -> Option<String> {
+        self.streams
+            .infos()
+            .iter()
+            .find(|s| s.is_input == is_input)
+            .map(|s| s.device_name.clone())
+    }
+
+    /// Boucle jouée par un repli fichier ou silence. Un fichier devenu
+    /// illisible (effacé, remplacé) est joué comme du silence : le
+    /// pipeline démarre quand même, et une bascule ne l'arrête pas.
+    fn looped_feed(&self, input: &InputSource) -> InputFeed {
+        if let InputSource::File(path) = input {
+            match wav::read_wav(path) {
+                Ok(audio) => {
+                    let rate = audio.sample_rate;
+                    return InputFeed::Looped(LoopedAudio::from_wav(audio), rate);
+                }
+                Err(e) => self.send_error(format!(
+                    "Cannot play the input fallback ({e}), playing silence instead"
+                )),
+            }
+        }
+        InputFeed::Looped(LoopedAudio::silence(1), self.sample_rate.as_hz())
+    }
+
+    /// Source ouverte pour l'entrée du pipeline : son device, ou son repli.
+    fn pipeline_input(&self) -> Option<InputSource> {
+        match &self.input_fallback {
+            Some(active) => Some(active.source.clone()),
+            None => self.stream_device(true).map(InputSource::Device),
+        }
+    }
+
+    /// Device voulu pour l'entrée du pipeline : celui ouvert, ou celui
+    /// que son repli remplace.
+    fn input_device(&self) -> Option<String> {
+        match &self.input_fallback {
+            Some(active) => active.primary.clone(),
+            None => self.stream_device(true),
+        }
+    }
+
+    /// Ferme puis rouvre le pipeline sur `input` → `output`.
+    /// En cas d'échec, le moteur s'arrête (plus aucun stream ouvert).
+    fn reopen_pipeline(&mut self, input: &InputSource, output: &str) -> TroubadourResult<()> {
+        self.handover = None;
+        self.streams.clear();
+        if let Err(e) = self.start_audio_pipeline(input, output, HandoverGate::active()) {
+            error!("Cannot reopen streams on {input} → {output}: {e}");
+            self.streams.clear();
+            self.watchdog.clear();
+            self.state = EngineState::Stopped;
+            let _ = self.event_tx.try_send(Event::EngineStopped);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Remplace le pipeline par un autre sur `input` → `output`, sans
+    /// trou : le nouveau démarre à côté de l'ancien (pré-roll), puis
+    /// `check_handover` fait la bascule (voir `handover`). Chaque
+    /// pipeline a son lecteur d'état : les commandes envoyées pendant la
+    /// bascule arrivent aux deux.
+    ///
+    /// Si le nouveau ne s'ouvre pas à côté de l'ancien (device en mode
+    /// exclusif), l'ancien est fermé d'abord : `reopen_pipeline`.
+    ///
+    /// Pendant un pré-roll, c'est l'ancien pipeline qui est entendu : il
+    /// reste en place, et le pipeline en attente est remplacé par le
+    /// nouveau. Une fois la bascule faite, l'ancien (déjà en fondu de
+    /// sortie) est fermé.
+    fn switch_pipeline(&mut self, input: &InputSource, output: &str) -> TroubadourResult<()> {
+        if self.preroll_blocks == 0 || (self.streams.is_empty() && self.handover.is_none()) {
+            return self.reopen_pipeline(input, output);
+        }
+        let (old_streams, old_gate) = match self.handover.take() {
+            Some(pending) if pending.switched_ms.is_none() => {
+                drop(std::mem::take(&mut self.streams));
+                (pending.old_streams, pending.old_gate)
+            }
+            finishing => {
+                drop(finishing);
+                (
+                    std::mem::take(&mut self.streams),
+                    self.pipeline_gate.clone(),
+                )
+            }
+        };
+        let new_gate = HandoverGate::standby();
+        match self.start_audio_pipeline(input, output, new_gate.clone()) {
+            Ok(()) => {
+                debug!("Pre-rolling streams on {input} → {output}");
+                self.handover = Some(PendingHandover {
+                    old_streams,
+                    old_gate,
+                    new_gate,
+                    started_ms: watchdog::monotonic_ms(),
+                    switched_ms: None,
+                });
+                Ok(())
+            }
+            Err(e) => {
+                warn!("Cannot pre-roll {input} → {output} ({e}), reopening instead");
+                drop(old_streams);
+                self.reopen_pipeline(input, output)
+            }
+        }
+    }
+
+    /// Fait avancer la bascule en cours : le nouveau pipeline est
+    /// entendu après son pré-roll, l'ancien fermé une fois muet.
+    ///
+    /// Un pipeline qui ne livre pas (device muet, callback bloqué) ne
+    /// retient pas la bascule plus de `HANDOVER_TIMEOUT_MS`.
+    fn check_handover(&mut self, now_ms: u64) {
+        let Some(pending) = &mut self.handover else {
+            return;
+        };
+        match pending.switched_ms {
+            None => {
+                let ready = pending.new_gate.delivered_blocks() >= self.preroll_blocks;
+                let late = now_ms.saturating_sub(pending.started_ms) >= HANDOVER_TIMEOUT_MS;
+                if ready || late {
+                    if !ready {
+                        warn!("New streams still not delivering, switching anyway");
+                    }
+                    pending.new_gate.activate();
+                    pending.old_gate.deactivate();
+                    pending.switched_ms = Some(now_ms);
+                }
+            }
+            Some(switched) => {
+                if pending.old_gate.is_silent()
+                    || now_ms.saturating_sub(switched) >= HANDOVER_TIMEOUT_MS
+                {
+                    let done = self.handover.take().map(|p| p.old_streams);
+                    // Dropper les handles ferme les anciens streams
+                    drop(done);
+                    debug!("Handover complete");
+                }
+            }
+        }
+    }
+
+    /// Blocs pleins que le nouveau pipeline doit livrer avant de
+    /// remplacer l'ancien (0 : pas de pré-roll).
+    pub fn set_preroll_blocks(&mut self, blocks: u32) {
+        self.preroll_blocks = blocks;
+    }
+
+    /// Veille de la boucle de contrôle (voir `idle`) : après
+    /// `after_seconds` d'entrée sous `threshold_db` (0 : jamais).
+    pub fn set_idle_detection(&mut self, after_seconds: u32, threshold_db: f32) {
+        self.idle = IdleDetector::new(after_seconds, threshold_db);
+    }
+
+    /// La boucle de contrôle est en veille : rien ne joue.
+    pub fn is_idle(&self) -> bool {
+        self.idle.is_idle()
+    }
+
+    /// Attente conseillée entre deux `process_commands` : `active` en
+    /// marche normale, `idle::IDLE_TICK` en veille. L'audio, cadencé
+    /// par les devices, n'en dépend pas.
+    pub fn loop_tick(&self, active: Duration) -> Duration {
+        self.idle.tick(active)
+    }
+
+    /// Relève l'activité de l'entrée et bascule la veille si besoin.
+    /// Un tap ouvert ou un flux réseau reçu gardent la boucle éveillée.
+    fn update_idle(&mut self, busy: bool, now: Instant) {
+        let busy = busy
+            || self.taps.lock().is_ok_and(|taps| !taps.is_empty())
+            || self.network.lock().is_ok_and(|n| n.source.is_some());
+        let activity = self.input_activity.take();
+        if let Some(idle) = self.idle.observe(activity, busy, now) {
+            info!("Control loop {}", if idle { "idle" } else { "awake" });
+            let _ = self.event_tx.try_send(Event::IdleChanged { idle });
+        }
+    }
+
+    /// Tout ce que la suppression du canal retirerait : le mixer (routes,
+    /// paires, suivis...) et ce que le moteur y accroche (ensembles
+    /// d'actions, taps, réseau, pipeline).
+    pub fn channel_dependencies(&self, id: ChannelId) -> TroubadourResult<ChannelDependencies> {
+        let mut report = self.mixer.channel_dependencies(id)?;
+        report.action_sets = self
+            .action_sets
+            .iter()
+            .filter(|(_, set)| set.actions.iter().any(|a| a.channels().contains(&id)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        report.passthrough_bus = self.passthrough_bus == Some(id);
+        report.taps = self
+            .taps()
+            .iter()
+            .filter(|tap| tap.bus == id)
+            .map(|tap| tap.id)
+            .collect();
+        report.replay = self.replays.iter().any(|replay| replay.bus() == id);
+        if let Ok(network) = self.network.lock() {
+            report.network_send = network.sink.as_ref().is_some_and(|sink| sink.bus() == id);
+            report.network_receive = network
+                .source
+                .as_ref()
+                .is_some_and(|source| source.channel() == id);
+        }
+        report.played = self.streams.serving(id, false).is_some();
+        report.pipeline_input = id == PIPELINE_INPUT_CHANNEL;
+        Ok(report)
+    }
+
+    /// Supprime un canal et tout ce qui le référence
+    /// (`channel_dependencies`) ; retourne sa config et ce qui a été
+    /// nettoyé. Un bus joué sort du pipeline, rouvert sans lui.
+    pub fn remove_channel(
+        &mut self,
+        id: ChannelId,
+    ) -> TroubadourResult<(ChannelConfig, ChannelDependencies)> {
+        let cleaned = self.channel_dependencies(id)?;
+        let (removed, _) = self.mixer.remove_channel(id)?;
+        self.channel_effects.remove(&id);
+        self.level_history.remove(&id);
+        for name in &cleaned.action_sets {
+            if let Some(set) = self.action_sets.get_mut(name) {
+                set.actions
+                    .retain(|action| !action.channels().contains(&id));
+            }
+        }
+        if cleaned.passthrough_bus {
+            self.passthrough_bus = None;
+        }
+        for &tap in &cleaned.taps {
+            self.destroy_tap(tap);
+        }
+        if cleaned.replay {
+            self.disable_replay(id);
+        }
+        if cleaned.network_send {
+            self.disable_network_send();
+        }
+        if cleaned.network_receive {
+            self.disable_network_receive();
+        }
+        if cleaned.played {
+            self.reconcile_with_mixer();
+        }
+        Ok((removed, cleaned))
+    }
+
+    /// Change le device d'un canal sans redémarrer les streams qui ne
+    /// le concernent pas.
+    ///
+    /// Le pipeline actuel est une paire de streams (entrée du Mic →
+    /// bus du device de sortie) reliée par une même file : seuls les
+    /// changements qui la touchent la rouvrent (`reconcile`) —
+    /// - le Mic change de device : seule l'entrée est rouverte dessus,
+    /// - le bus joué change de device : seule la sortie le suit,
+    /// - un bus quitte ou rejoint le device de sortie ouvert : la paire
+    ///   est remplacée pour jouer le nouvel ensemble de bus.
+    ///
+    /// Pour tout autre canal, seule l'assignation change : aucun stream
+    /// n'est fermé ni ouvert, les autres canaux ne coupent pas.
+    ///
+    /// `DeviceAssignment::SystemDefault` est résolu vers le device par
+    /// défaut actuel du système, comme à l'ouverture des streams.
+    pub fn reassign_channel_device(
+        &mut self,
+        channel: ChannelId,
+        device: DeviceAssignment,
+    ) -> TroubadourResult<()> {
+        let config = self
+            .mixer
+            .channel(channel)
+            .ok_or(MixerError::ChannelNotFound(channel.0))?;
+        if config.device_name == device {
+            return Ok(());
+        }
+        if config.kind == ChannelKind::Submix && !device.is_none() {
+            return Err(MixerError::InvalidParameter(format!(
+                "Channel {} is a submix: it has no device",
+                channel.0
+            ))
+            .into());
+        }
+        let before = config.device_name.to_string();
+
+        if let Some(config) = self.mixer.channel_mut(channel) {
+            config.device_name = device.clone();
+        }
+        self.journal_change(
+            "SetChannelDevice",
+            Some(channel),
+            Some(before),
+            Some(device.to_string()),
+        );
+        self.recovery.mark_dirty();
+
+        // Device écouté assigné à l'entrée du pipeline : l'écoute ferme
+        // son stream avant que le pipeline n'ouvre le sien
+        // (seul le Mic ouvre un stream : un autre canal garde l'écoute)
+        if channel == PIPELINE_INPUT_CHANNEL
+            && let Some(previewed) = self.preview.device().map(str::to_string)
+        {
+            let defaults = self.current_system_defaults();
+            if defaults.resolve(&device, true) == Some(previewed.as_str())
+                && self.preview.hand_over(&previewed)
+            {
+                self.device_preview_ended(previewed, PreviewStopReason::AssignedToChannel);
+            }
+        }
+
+        let topology = self.stream_topology();
+        if self.reconcile(&topology)?.is_empty() {
+            debug!("Channel {} moved to {device}, no stream touched", channel.0);
+        }
+        Ok(())
+    }
+
+    /// Streams que demandent les devices du mixer en place
+    /// (`streams::desired_streams`).
+    pub fn stream_topology(&self) -> BTreeMap<StreamKey, Vec<ChannelId>> {
+        desired_streams(&self.mixer.to_config(), &self.current_system_defaults())
+    }
+
+    /// Aligne le pipeline ouvert sur `topology` en une seule fois :
+    /// l'entrée suit le stream du Mic, la sortie celui du bus joué, et
+    /// joue les bus que la topologie y place.
+    ///
+    /// Seul le stream dont la clé change est rouvert
+    /// (`StreamSet::refresh`), l'autre continue sur la même file. Le
+    /// pipeline entier est remplacé (`switch_pipeline`) quand son
+    /// traitement change : autres bus joués, entrée et sortie déplacées
+    /// ensemble, nouvelle entrée à un autre sample rate, ou bascule en
+    /// cours.
+    ///
+    /// Moteur arrêté, rien à faire : `start` ouvrira la bonne paire.
+    /// Retourne les streams fermés et ouverts.
+    pub fn reconcile(
+        &mut self,
+        topology: &BTreeMap<StreamKey, Vec<ChannelId>>,
+    ) -> TroubadourResult<StreamChanges> {
+        if self.state != EngineState::Running {
+            return Ok(StreamChanges::default());
+        }
+        let (Some(input), Some(output)) = (self.pipeline_input(), self.stream_device(false)) else {
+            return Ok(StreamChanges::default());
+        };
+        let defaults = self.current_system_defaults();
+        let wanted_input = topology
+            .iter()
+            .find(|(key, channels)| key.is_input && channels.contains(&PIPELINE_INPUT_CHANNEL))
+            .map(|(key, _)| key.device_name.clone())
+            .filter(|device| self.input_device().as_ref() != Some(device));
+        let new_input = wanted_input
+            .as_deref()
+            .and_then(|device| self.resolve_pipeline_input(Some(device), &defaults));
+        let source = new_input
+            .as_ref()
+            .map_or(input.clone(), |n| n.source.clone());
+        // La sortie suit le bus joué ; sans device pour lui, elle reste
+        let wanted_output = self
+            .played_bus
+            .and_then(|bus| {
+                topology
+                    .iter()
+                    .find(|(key, channels)| !key.is_input && channels.contains(&bus))
+            })
+            .map_or_else(|| output.clone(), |(key, _)| key.device_name.clone());
+
+        // Aucun bus sur la sortie : le plus petit id la joue (comme à
+        // l'ouverture)
+        let sorted = |mut buses: Vec<ChannelId>| {
+            buses.sort_by_key(|c| c.0);
+            buses
+        };
+        let wanted_buses = sorted(
+            topology
+                .get(&StreamKey::new(wanted_output.clone(), false))
+                .cloned()
+                .unwrap_or_else(|| {
+                    self.mixer
+                        .outputs()
+                        .iter()
+                        .map(|c| c.id)
+                        .min_by_key(|id| id.0)
+                        .into_iter()
+                        .collect()
+                }),
+        );
+        let open_buses = self
+            .streams
+            .infos()
+            .iter()
+            .find(|s| !s.is_input)
+            .map(|s| sorted(s.target_channels.clone()))
+            .unwrap_or_default();
+        let input_moves = source.to_string() != input.to_string();
+        let output_moves = wanted_output != output;
+        if !input_moves && !output_moves && wanted_buses == open_buses {
+            if let Some(new_input) = new_input {
+                self.record_pipeline_input(wanted_input, new_input);
+            }
+            return Ok(StreamChanges::default());
+        }
+
+        info!("Reconciling streams with the new device assignments");
+        let closed: Vec<StreamKey> = self.streams.infos().iter().map(ActiveStream::key).collect();
+        let same_processing = wanted_buses == open_buses
+            && bus_lanes(&self.mixer, &wanted_buses) == self.pipeline_lanes;
+        let refreshed = match same_processing && !(input_moves && output_moves) {
+            true if self.handover.is_none() => {
+                self.refresh_pipeline(&source, &wanted_output, wanted_buses)?
+            }
+            _ => None,
+        };
+        let changes = match refreshed {
+            Some(changes) => changes,
+            None => {
+                self.switch_pipeline(&source, &wanted_output)?;
+                StreamChanges {
+                    opened: self.streams.infos().iter().map(ActiveStream::key).collect(),
+                    closed,
+                }
+            }
+        };
+        if let Some(new_input) = new_input {
+            self.record_pipeline_input(wanted_input, new_input);
+        }
+        Ok(changes)
+    }
+
+    /// Rouvre le seul stream du pipeline dont la clé change : l'autre
+    /// reste ouvert, et le nouveau prend sa place sur la même file
+    /// (`PipelineLink`).
+    ///
+    /// `None` : la nouvelle entrée ne tourne pas au sample rate de
+    /// l'ancienne (le pont de la sortie n'irait plus), le pipeline entier
+    /// doit être remplacé.
+    fn refresh_pipeline(
+        &mut self,
+        input: &InputSource,
+        output: &str,
+        buses: Vec<ChannelId>,
+    ) -> TroubadourResult<Option<StreamChanges>> {
+        let (Some(link), Some(input_rate)) = (
+            self.pipeline_link.clone(),
+            self.streams
+                .infos()
+                .iter()
+                .find(|s| s.is_input)
+                .map(|s| s.sample_rate),
+        ) else {
+            return Ok(None);
+        };
+        let desired = BTreeMap::from([
+            (
+                StreamKey::new(input.to_string(), true),
+                vec![PIPELINE_INPUT_CHANNEL],
+            ),
+            (StreamKey::new(output, false), buses),
+        ]);
+        let mut rate_changed = false;
+        let mut streams = std::mem::take(&mut self.streams);
+        let changes: TroubadourResult<_> = streams.refresh(&desired, |key| {
+            let mut opened = match key.is_input {
+                true => self.open_pipeline_input(input, output, &link)?,
+                false => self.open_pipeline_output(output, input_rate, &link)?,
+            };
+            if key.is_input && opened.info.sample_rate != input_rate {
+                info!(
+                    "{input} opens at {} Hz, not {input_rate} Hz: reopening both streams",
+                    opened.info.sample_rate
+                );
+                rate_changed = true;
+                return Ok(None);
+            }
+            opened
+                .play()
+                .inspect_err(|e| self.report_stream_failure(e))?;
+            let (device_name, is_input, beat) = opened.watched();
+            self.watchdog
+                .rewatch(device_name, is_input, beat, watchdog::monotonic_ms());
+            Ok(Some((opened.info, opened.stream)))
+        });
+        self.streams = streams;
+        let changes = changes?;
+        Ok((!rate_changed).then_some(changes))
+    }
+
+    /// `reconcile` sur la topologie du mixer en place ; un échec est
+    /// signalé (le mixer, lui, reste remplacé).
+    fn reconcile_with_mixer(&mut self) -> StreamChanges {
+        let topology = self.stream_topology();
+        self.reconcile(&topology).unwrap_or_else(|e| {
+            self.send_error(format!("Cannot reopen streams for the new devices: {e}"));
+            StreamChanges::default()
+        })
+    }
+
+    /// Règle le repli d'une entrée quand son device manque.
+    ///
+    /// Seul le Mic alimente le pipeline : s'il joue déjà un repli, le
+    /// nouveau est appliqué tout de suite (bascule sans trou). Un
+    /// fichier est décodé d'abord : illisible, il est refusé.
+    pub fn set_input_fallback(
+        &mut self,
+        channel: ChannelId,
+        fallback: InputFallback,
+    ) -> TroubadourResult<()> {
+        if let InputFallback::File(path) = &fallback {
+            wav::read_wav(path)?;
+        }
+        let config = self
+            .mixer
+            .channel_mut(channel)
+            .ok_or(MixerError::ChannelNotFound(channel.0))?;
+        if config.kind != ChannelKind::Input {
+            return Err(MixerError::InvalidParameter(format!(
+                "Channel {} is not an input",
+                channel.0
+            ))
+            .into());
+        }
+        if config.input_fallback == fallback {
+            return Ok(());
+        }
+        let before = std::mem::replace(&mut config.input_fallback, fallback.clone());
+        self.journal_change(
+            "SetInputFallback",
+            Some(channel),
+            Some(format!("{before:?}")),
+            Some(format!("{fallback:?}")),
+        );
+        self.recovery.mark_dirty();
+
+        if self.state == EngineState::Running && channel == PIPELINE_INPUT_CHANNEL {
+            self.check_input_fallback();
+        }
+        Ok(())
+    }
+
+    /// Repli joué par l'entrée du pipeline (`None` : elle est sur son
+    /// device, ou le moteur est arrêté).
+    pub fn input_fallback(&self) -> Option<&InputSource> {
+        self.input_fallback.as_ref().map(|active| &active.source)
+    }
+
+    /// Passe un bus en mono ou en stéréo.
+    ///
+    /// # Validation
+    /// Le device du bus (celui qui lui est assigné, sinon la sortie
+    /// ouverte qui le joue) doit avoir assez de canaux. Un device absent
+    /// de la liste n'est pas vérifié ici : il le sera à l'ouverture.
+    ///
+    /// Si le bus joue sur la sortie ouverte, la paire est rouverte : le
+    /// stream prend le nombre de canaux du bus quand le device le permet.
+    pub fn set_bus_channel_count(
+        &mut self,
+        bus: ChannelId,
+        count: ChannelCount,
+    ) -> TroubadourResult<()> {
+        let config = self
+            .mixer
+            .channel(bus)
+            .ok_or(MixerError::ChannelNotFound(bus.0))?;
+        if config.kind != ChannelKind::Output {
+            return Err(MixerError::BusNotFound(bus.0).into());
+        }
+        let before = config.channel_count;
+        if before == count {
+            return Ok(());
+        }
+
+        let playing = self.streams.serving(bus, false).is_some();
+        let device = self
+            .system_defaults
+            .resolve(&config.device_name, false)
+            .map(str::to_string)
+            .or_else(|| self.stream_device(false).filter(|_| playing));
+        if let Some(name) = device {
+            let devices = self
+                .device_manager
+                .list_output_devices()
+                .unwrap_or_default();
+            if let Some(info) = devices.iter().find(|d| d.name == name)
+                && info.channels < count.channels()
+            {
+                return Err(MixerError::InvalidParameter(format!(
+                    "{name} has {} channel(s), a {count} bus needs {}",
+                    info.channels,
+                    count.channels()
+                ))
+                .into());
+            }
+        }
+
+        self.mixer.set_bus_channel_count(bus, count)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        self.journal_change(
+            "SetBusChannelCount",
+            Some(bus),
+            Some(before.to_string()),
+            Some(count.to_string()),
+        );
+        self.recovery.mark_dirty();
+
+        if self.state != EngineState::Running || !playing {
+            return Ok(());
+        }
+        let (Some(input), Some(output)) = (self.pipeline_input(), self.stream_device(false)) else {
+            return Ok(());
+        };
+        info!("Reopening pipeline for bus {} ({count})", bus.0);
+        self.switch_pipeline(&input, &output)
+    }
+
+    /// Place un bus sur les canaux `offset..` de son device de sortie.
+    ///
+    /// # Validation
+    /// Pas de chevauchement avec un autre bus du même device (mixer), et
+    /// la tranche doit tenir dans le device du bus, vérifié comme pour
+    /// `set_bus_channel_count`. Si le bus joue, la paire est rouverte :
+    /// le stream prend assez de canaux pour atteindre la tranche.
+    pub fn set_bus_output_mapping(
+        &mut self,
+        bus: ChannelId,
+        offset: Option<u16>,
+    ) -> TroubadourResult<()> {
+        let config = self
+            .mixer
+            .channel(bus)
+            .ok_or(MixerError::ChannelNotFound(bus.0))?;
+        if config.kind != ChannelKind::Output {
+            return Err(MixerError::BusNotFound(bus.0).into());
+        }
+        let before = config.output_channel_offset;
+        if before == offset {
+            return Ok(());
+        }
+
+        let playing = self.streams.serving(bus, false).is_some();
+        let device = self
+            .system_defaults
+            .resolve(&config.device_name, false)
+            .map(str::to_string)
+            .or_else(|| self.stream_device(false).filter(|_| playing));
+        let slot =
+            offset.map(|o| OutputSlot::new(o as usize, config.channel_count.channels() as usize));
+        if let (Some(name), Some(slot)) = (device, slot) {
+            let devices = self
+                .device_manager
+                .list_output_devices()
+                .unwrap_or_default();
+            if let Some(info) = devices.iter().find(|d| d.name == name) {
+                check_output_slots(&[(bus, slot)], Some(info.channels as usize))?;
+            }
+        }
+
+        self.mixer.set_bus_output_mapping(bus, offset)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        self.journal_change(
+            "SetBusOutputMapping",
+            Some(bus),
+            before.map(|o| o.to_string()),
+            offset.map(|o| o.to_string()),
+        );
+        self.recovery.mark_dirty();
+
+        if self.state != EngineState::Running || !playing {
+            return Ok(());
+        }
+        let (Some(input), Some(output)) = (self.pipeline_input(), self.stream_device(false)) else {
+            return Ok(());
+        };
+        info!("Reopening pipeline for bus {} outputs", bus.0);
+        self.switch_pipeline(&input, &output)
+    }
+
+    /// Sample rate demandé.
+    pub fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    pub fn buffer_size(&self) -> BufferSize {
+        self.buffer_size
+    }
+
+    /// Choisit le dither des sorties 16 bits (pris en compte à la
+    /// prochaine ouverture des streams).
+    pub fn set_dither(&mut self, mode: DitherMode) {
+        self.dither = mode;
+    }
+
+    pub fn dither(&self) -> DitherMode {
+        self.dither
+    }
+
+    /// Sample rate auquel les effets calculent leurs coefficients.
+    pub fn processing_rate(&self) -> f32 {
+        self.processing_rate
+    }
+
+    /// Propage le rate de traitement à la chaîne d'effets en place.
+    fn set_processing_rate(&mut self, sample_rate: f32) {
+        self.processing_rate = sample_rate;
+        if let Ok(mut chain) = self.lock_dsp_chain() {
+            chain.set_sample_rate(sample_rate);
+        }
+    }
+
+    /// Applique un profil complet : mixer et effets du Mic.
+    ///
+    /// Le mixer est remplacé en bloc puis publié ; la chaîne d'effets
+    /// est échangée à chaud. Les devices du profil ne sont pas rouverts
+    /// ici (c'est au démarrage du moteur de les choisir). Les devices de
+    /// la machine active l'emportent sur ceux du profil.
+    ///
+    /// Au-delà de `max_channels`, les canaux d'id les plus élevés sont
+    /// écartés : ils sont retournés et signalés par `Event::Error`.
+    ///
+    /// Moteur lancé, les streams suivent ensuite les devices du profil
+    /// en une fois (`reconcile`), une fois le mixer entier remplacé.
+    ///
+    /// L'état remplacé est d'abord sauvé (`auto_snapshot`).
+    pub fn apply_profile(&mut self, profile: &Profile) -> TroubadourResult<ProfileApplied> {
+        self.auto_snapshot("ApplyProfile")?;
+        Ok(self.replace_with_profile(profile))
+    }
+
+    /// `apply_profile` sans instantané.
+    fn replace_with_profile(&mut self, profile: &Profile) -> ProfileApplied {
+        let applied = self.install_profile(profile);
+        self.report_skipped_channels(&profile.name, &applied.skipped);
+        applied
+    }
+
+    /// `Event::Error` pour les canaux d'un preset écartés au-delà de
+    /// `max_channels` (rien si aucun).
+    fn report_skipped_channels(&self, preset: &str, skipped: &[ChannelId]) {
+        if skipped.is_empty() {
+            return;
+        }
+        let ids: Vec<String> = skipped.iter().map(|id| id.0.to_string()).collect();
+        self.send_error(format!(
+            "Preset \"{preset}\": channel(s) {} skipped, the mixer is limited to {} channels",
+            ids.join(", "),
+            self.mixer.max_channels()
+        ));
+    }
+
+    /// Applique un profil lu hors du dossier des profils (fichier glissé
+    /// sur la fenêtre) comme un profil nommé.
+    ///
+    /// Retourne son nom et ce qui n'a pas été repris : canaux au-delà de
+    /// la limite du mixer, effets d'un bus absent, clés inconnues. Un fichier refusé
+    /// (`InvalidPresetFile`) ou illisible (`PresetParse`) ne change rien.
+    pub fn load_profile_file(&mut self, path: &Path) -> TroubadourResult<(String, Vec<String>)> {
+        let (profile, unknown_keys) = Profile::load_dropped(path)?;
+        self.auto_snapshot("LoadPresetFile")?;
+        let skipped = self.install_profile(&profile).skipped;
+        let max_channels = self.mixer.max_channels();
+        let mut warnings: Vec<String> = profile
+            .mixer
+            .channels
+            .iter()
+            .filter(|c| skipped.contains(&c.id))
+            .map(|c| {
+                format!(
+                    "channel {} \"{}\" skipped: the mixer is limited to {max_channels} channels",
+                    c.id.0, c.name
+                )
+            })
+            .collect();
+        warnings.extend(
+            profile
+                .bus_effects
+                .keys()
+                .filter(|name| !self.mixer.outputs().iter().any(|bus| &bus.name == *name))
+                .map(|name| format!("effects of bus \"{name}\" ignored: no such bus")),
+        );
+        warnings.extend(unknown_keys);
+        Ok((profile.name, warnings))
+    }
+
+    /// Recopie un fichier de profil dans le dossier des profils, sous
+    /// `name` (par défaut : le nom du fichier), sans l'appliquer.
+    ///
+    /// Le nom est assaini (`sanitize_preset_name`). Un profil intégré
+    /// n'est jamais remplacé ; un profil sauvé seulement avec
+    /// `overwrite`, en gardant l'ancien dans `<nom>.toml.bak`.
+    /// Retourne le nom retenu.
+    pub fn save_dropped_profile(
+        &mut self,
+        path: &Path,
+        name: Option<&str>,
+        overwrite: bool,
+    ) -> TroubadourResult<String> {
+        let (profile, _) = Profile::load_dropped(path)?;
+        let raw = name.map_or_else(
+            || {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            },
+            str::to_string,
+        );
+        self.store_profile(profile, &raw, overwrite)
+    }
+
+    /// Profil intégré ou sauvé, écrit en JSON (`profile_json`).
+    pub fn export_profile_json(&self, name: &str) -> TroubadourResult<String> {
+        profile_json::profile_to_json(&self.find_profile(name)?)
+    }
+
+    /// Sauve un profil JSON dans le dossier des profils sous `name`,
+    /// sans l'appliquer, comme `save_dropped_profile`.
+    ///
+    /// Retourne le nom retenu et les clés inconnues du JSON, ignorées.
+    pub fn import_profile_json(
+        &mut self,
+        name: &str,
+        json: &str,
+        overwrite: bool,
+    ) -> TroubadourResult<(String, Vec<String>)> {
+        let (profile, warnings) = profile_json::preset_from_json(json)?;
+        Ok((self.store_profile(profile, name, overwrite)?, warnings))
+    }
+
+    /// Écrit un profil dans le dossier des profils sous le nom `raw`
+    /// assaini ; voir `save_dropped_profile`.
+    fn store_profile(
+        &mut self,
+        mut profile: Profile,
+        raw: &str,
+        overwrite: bool,
+    ) -> TroubadourResult<String> {
+        let name = sanitize_preset_name(raw)
+            .ok_or_else(|| TroubadourError::InvalidPresetName(format!("{raw:?}")))?;
+        let builtin = Profile::builtin_profiles()
+            .iter()
+            .any(|p| p.name.eq_ignore_ascii_case(&name));
+        let target = self.profiles_dir.join(format!("{name}.toml"));
+        if builtin || (target.exists() && !overwrite) {
+            return Err(TroubadourError::PresetExists(name));
+        }
+        let config_error = |e: &dyn std::fmt::Display| {
+            TroubadourError::ConfigError(format!("Preset \"{name}\": {e}"))
+        };
+        if target.exists() {
+            std::fs::copy(&target, self.profiles_dir.join(format!("{name}.toml.bak")))
+                .map_err(|e| config_error(&e))?;
+        }
+        profile.name = name.clone();
+        profile.save(&target).map_err(|e| config_error(&e))?;
+        Ok(name)
+    }
+
+    /// Remplace le mixer et les effets par ceux d'un profil, puis aligne
+    /// les streams sur ses devices.
+    fn install_profile(&mut self, profile: &Profile) -> ProfileApplied {
+        let (mut mixer, skipped) =
+            Mixer::from_config_limited(self.profile_mixer(profile), self.mixer.max_channels());
+        self.keep_transient_state(&mut mixer);
+        self.mixer = mixer;
+        self.set_channel_effects(PIPELINE_INPUT_CHANNEL, profile.effects.clone());
+        let buses: Vec<(ChannelId, String)> = self
+            .mixer
+            .outputs()
+            .iter()
+            .map(|c| (c.id, c.name.clone()))
+            .collect();
+        for (bus, name) in buses {
+            match profile.bus_effects.get(&name) {
+                Some(preset) => self.set_channel_effects(bus, preset.clone()),
+                None => {
+                    self.channel_effects.remove(&bus);
+                    self.reload_bus_effects(bus);
+                }
+            }
+        }
+        self.shared_state.update_from_mixer(&self.mixer);
+        self.recovery.mark_dirty();
+        self.journal.record(ChangeEntry {
+            timestamp_ms: journal::now_ms(),
+            action: "ApplyProfile".to_string(),
+            channel: None,
+            before: None,
+            after: Some(profile.name.clone()),
+        });
+        ProfileApplied {
+            skipped,
+            streams: self.reconcile_with_mixer(),
+        }
+    }
+
+    /// Reporte sur un mixer qui remplace le nôtre ce qui n'est pas dans
+    /// sa config : échelle des meters, passthrough d'urgence (un preset
+    /// chargé en pleine panne ne doit pas relancer les effets).
+    fn keep_transient_state(&self, mixer: &mut Mixer) {
+        mixer.set_meter_scale(self.mixer.meter_scale());
+        let passthrough = self.mixer.emergency_passthrough();
+        // Bus de repli absent du nouveau mixer : l'override tombe
+        if mixer.set_emergency_passthrough(passthrough).is_err() {
+            let _ = self
+                .event_tx
+                .try_send(Event::EmergencyPassthroughChanged { bus: None });
+        }
+    }
+
+    /// Mixer d'un profil tel qu'il serait appliqué : avec les devices
+    /// de la machine active.
+    fn profile_mixer(&self, profile: &Profile) -> MixerConfig {
+        let mut config = profile.mixer.clone();
+        if let Some(machine) = self.active_machine_profile() {
+            machine.apply_to(&mut config);
+        }
+        config
+    }
+
+    /// Ce que changerait `apply_profile` : canaux et bus ajoutés ou
+    /// retirés, réglages modifiés (devices compris), routes.
+    pub fn diff_profile(&self, profile: &Profile) -> ConfigDiff {
+        self.mixer.to_config().diff(&self.profile_mixer(profile))
+    }
+
+    /// Applique seulement les entrées choisies du diff d'un profil
+    /// (`diff_profile`). Les effets du profil ne sont pas touchés.
+    /// Retourne le nombre d'entrées appliquées ; les canaux au-delà de
+    /// `max_channels` sont écartés et signalés comme par `apply_profile`.
+    pub fn apply_profile_selection(
+        &mut self,
+        profile: &Profile,
+        selection: &[DiffEntry],
+    ) -> TroubadourResult<usize> {
+        let mut config = self.mixer.to_config();
+        let applied = config.apply_entries(&self.profile_mixer(profile), selection);
+        if applied > 0 {
+            self.auto_snapshot("ApplyPresetSelection")?;
+            let (mut mixer, skipped) =
+                Mixer::from_config_limited(config, self.mixer.max_channels());
+            self.report_skipped_channels(&profile.name, &skipped);
+            self.keep_transient_state(&mut mixer);
+            self.mixer = mixer;
+            self.shared_state.update_from_mixer(&self.mixer);
+            self.recovery.mark_dirty();
+            self.journal_change(
+                "ApplyPresetSelection",
+                None,
+                None,
+                Some(format!("{} ({applied} changes)", profile.name)),
+            );
+            self.reconcile_with_mixer();
+        }
+        Ok(applied)
+    }
+
+    /// Crée un profil depuis une config OBS ou VoiceMeeter et le sauve
+    /// dans le dossier des profils, sans l'appliquer : l'utilisateur
+    /// le relit (`DiffPreset`) avant de basculer.
+    ///
+    /// Le profil s'appelle "Imported OBS" (ou "Imported OBS 2"… si le
+    /// nom est pris) ; les devices sont résolus parmi ceux de la machine.
+    pub fn import_external_config(
+        &mut self,
+        path: &Path,
+        format: ImportFormat,
+    ) -> TroubadourResult<ImportReport> {
+        let base = format!("Imported {}", format.label());
+        let taken = Profile::available_names(&self.profiles_dir);
+        let name = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{base} {n}"),
+            })
+            .find(|name| !taken.iter().any(|t| t.eq_ignore_ascii_case(name)))
+            .unwrap_or(base);
+
+        let names = |devices: Vec<DeviceInfo>| devices.into_iter().map(|d| d.name).collect();
+        let inputs: Vec<String> =
+            names(self.device_manager.list_input_devices().unwrap_or_default());
+        let outputs: Vec<String> = names(
+            self.device_manager
+                .list_output_devices()
+                .unwrap_or_default(),
+        );
+        let (profile, report) = import::import_external(path, format, &name, &inputs, &outputs)?;
+        profile
+            .save(&self.profiles_dir.join(format!("{name}.toml")))
+            .map_err(|e| TroubadourError::ConfigError(format!("Preset \"{name}\": {e}")))?;
+        Ok(report)
+    }
+
+    /// Résout les devices d'un profil (intégré ou sauvé) parmi ceux de
+    /// cette machine, sans l'appliquer (voir `compat::check_profile`).
+    /// Les devices de la machine active remplacent ceux du profil, comme
+    /// au chargement.
+    pub fn check_preset_compatibility(&self, name: &str) -> TroubadourResult<CompatibilityReport> {
+        let mut profile = self.find_profile(name)?;
+        profile.mixer = self.profile_mixer(&profile);
+        // Une énumération en échec n'est pas "aucun device" : tout
+        // paraîtrait introuvable
+        let devices = DeviceInventory {
+            inputs: self.device_manager.list_input_devices()?,
+            outputs: self.device_manager.list_output_devices()?,
+            ..DeviceInventory::default()
+        };
+        Ok(compat::check_profile(
+            &profile,
+            &devices,
+            self.sample_rate,
+            self.mixer.max_channels(),
+        ))
+    }
+
+    /// Profil intégré ou sauvé dans le dossier des profils ; ses clés
+    /// inconnues sont signalées (`Event::PresetWarnings`).
+    fn find_profile(&self, name: &str) -> TroubadourResult<Profile> {
+        let (profile, warnings) = Profile::find(name, &self.profiles_dir).map_err(|e| {
+            match e.downcast::<TroubadourError>() {
+                Ok(e) => *e,
+                Err(e) => TroubadourError::ConfigError(format!("Preset \"{name}\": {e}")),
+            }
+        })?;
+        self.report_preset_warnings(name, warnings);
+        Ok(profile)
+    }
+
+    /// Signale les clés ignorées d'un preset chargé hors du moteur (TUI,
+    /// CLI) ; rien si la liste est vide.
+    pub fn report_preset_warnings(&self, name: &str, warnings: Vec<String>) {
+        if warnings.is_empty() {
+            return;
+        }
+        let _ = self.event_tx.try_send(Event::PresetWarnings {
+            name: name.to_string(),
+            warnings,
+        });
+    }
+
+    /// Change le dossier des profils sauvés (tests, autre emplacement).
+    pub fn set_profiles_dir(&mut self, dir: PathBuf) {
+        self.profiles_dir = dir;
+    }
+
+    /// Capture l'état actuel sous forme de profil.
+    ///
+    /// Avec des profils machine, le profil ne garde aucun device nommé :
+    /// ils appartiennent à la machine (`machine_profiles`), le profil
+    /// reste partageable entre machines. Suivre le défaut du système, en
+    /// revanche, vaut sur toutes les machines et reste dans le profil.
+    pub fn to_profile(&self, name: &str) -> Profile {
+        let mut mixer = self.mixer.to_config();
+        let machine_scoped = self.machine.is_some();
+        if machine_scoped {
+            for channel in &mut mixer.channels {
+                if channel.device_name.specific().is_some() {
+                    channel.device_name = DeviceAssignment::None;
+                }
+            }
+        }
+        Profile {
+            name: name.to_string(),
+            mixer,
+            effects: self.channel_effects(PIPELINE_INPUT_CHANNEL),
+            bus_effects: self
+                .mixer
+                .outputs()
+                .into_iter()
+                .filter_map(|bus| {
+                    let preset = self.channel_effects.get(&bus.id)?;
+                    Some((bus.name.clone(), preset.clone()))
+                })
+                .collect(),
+            input_device: self.input_device().filter(|_| !machine_scoped),
+            output_device: self.stream_device(false).filter(|_| !machine_scoped),
+        }
+    }
+
+    /// Installe les profils machine de la config et active `active`.
+    ///
+    /// Les devices déjà présents dans le mixer (ancien profil "à plat")
+    /// et absents du profil machine sont conservés : ils rejoignent le
+    /// profil de cette machine à la prochaine sauvegarde (migration).
+    pub fn configure_machine_profiles(
+        &mut self,
+        profiles: BTreeMap<String, MachineProfile>,
+        active: &str,
+    ) {
+        self.machine_profiles = profiles;
+        self.machine = None;
+        self.select_machine_profile(active);
+    }
+
+    /// Active le profil de devices d'une machine.
+    ///
+    /// Les devices de la machine quittée sont relevés dans son profil,
+    /// puis retirés des canaux : ceux de la nouvelle machine les
+    /// remplacent. Les canaux qui suivent le défaut du système le suivent
+    /// toujours. Le backend audio et les devices d'entrée/sortie
+    /// s'appliquent au prochain démarrage du moteur.
+    pub fn select_machine_profile(&mut self, name: &str) {
+        if self.machine.is_some() {
+            self.capture_machine_devices();
+            let ids: Vec<ChannelId> = self
+                .mixer
+                .to_config()
+                .channels
+                .iter()
+                .map(|c| c.id)
+                .collect();
+            for id in ids {
+                if let Some(channel) = self.mixer.channel_mut(id)
+                    && channel.device_name.specific().is_some()
+                {
+                    channel.device_name = DeviceAssignment::None;
+                }
+            }
+        }
+
+        let profile = self
+            .machine_profiles
+            .entry(name.to_string())
+            .or_default()
+            .clone();
+        for assignment in &profile.channel_devices {
+            if let Some(channel) = self.mixer.channel_mut(assignment.channel) {
+                channel.device_name = DeviceAssignment::Specific(assignment.device.clone());
+            }
+        }
+        self.device_manager = match profile.audio_host.as_deref() {
+            Some(host) => DeviceManager::with_host(host).unwrap_or_else(|e| {
+                warn!("{e}, using the default audio host");
+                DeviceManager::new()
+            }),
+            None => DeviceManager::new(),
+        };
+
+        let before = self.machine.replace(name.to_string());
+        self.shared_state.update_from_mixer(&self.mixer);
+        self.recovery.mark_dirty();
+        self.journal_change("SelectMachineProfile", None, before, Some(name.to_string()));
+    }
+
+    /// Devices proposés pour un premier lancement, d'après les devices
+    /// branchés (voir `setup::propose_initial_config`).
+    pub fn setup_proposal(&self) -> SetupProposal {
+        let defaults = self.current_system_defaults();
+        let devices = DeviceInventory {
+            inputs: self.device_manager.list_input_devices().unwrap_or_default(),
+            outputs: self
+                .device_manager
+                .list_output_devices()
+                .unwrap_or_default(),
+            default_input: defaults.input,
+            default_output: defaults.output,
+        };
+        setup::propose_initial_config(&self.mixer.to_config(), &devices)
+    }
+
+    /// Applique une proposition de premier lancement : devices des
+    /// canaux, et devices d'entrée/sortie du profil de la machine active.
+    /// Les streams déjà ouverts ne changent qu'au prochain démarrage.
+    pub fn apply_setup_proposal(&mut self, proposal: &SetupProposal) -> TroubadourResult<()> {
+        self.auto_snapshot("ApplySetupProposal")?;
+        for assignment in &proposal.profile.channel_devices {
+            if let Some(channel) = self.mixer.channel_mut(assignment.channel) {
+                channel.device_name = DeviceAssignment::Specific(assignment.device.clone());
+            }
+        }
+        if let Some(machine) = &self.machine {
+            let profile = self.machine_profiles.entry(machine.clone()).or_default();
+            setup::merge_into(profile, proposal);
+        }
+        self.shared_state.update_from_mixer(&self.mixer);
+        self.recovery.mark_dirty();
+        self.journal_change(
+            "ApplySetupProposal",
+            None,
+            None,
+            Some(format!(
+                "{} device(s)",
+                proposal.profile.channel_devices.len()
+            )),
+        );
+        Ok(())
+    }
+
+    /// Machine active, si les profils machine sont configurés.
+    pub fn machine(&self) -> Option<&str> {
+        self.machine.as_deref()
+    }
+
+    /// Profils machine à sauver dans la config : celui de la machine
+    /// active est mis à jour avec les devices actuels des canaux, les
+    /// autres sont rendus tels qu'ils ont été chargés.
+    pub fn machine_profiles(&self) -> BTreeMap<String, MachineProfile> {
+        let mut profiles = self.machine_profiles.clone();
+        if let Some(machine) = &self.machine {
+            profiles
+                .entry(machine.clone())
+                .or_default()
+                .capture_from(&self.mixer.to_config());
+        }
+        profiles
+    }
+
+    fn active_machine_profile(&self) -> Option<&MachineProfile> {
+        self.machine
+            .as_ref()
+            .and_then(|m| self.machine_profiles.get(m))
+    }
+
+    /// Relève les devices des canaux dans le profil de la machine active.
+    fn capture_machine_devices(&mut self) {
+        if let Some(machine) = &self.machine {
+            let config = self.mixer.to_config();
+            self.machine_profiles
+                .entry(machine.clone())
+                .or_default()
+                .capture_from(&config);
+        }
+    }
+
+    /// Le device est-il branché ? La source des défauts répond si elle
+    /// le sait (tests), sinon les devices réels.
+    fn device_present(&self, name: &str, is_input: bool) -> bool {
+        if let Some(present) = self
+            .default_source
+            .as_ref()
+            .and_then(|source| source.device_present(name, is_input))
+        {
+            return present;
+        }
+        if is_input {
+            self.device_manager.find_input_device(name).is_ok()
+        } else {
+            self.device_manager.find_output_device(name).is_ok()
+        }
+    }
+
+    /// Le device préféré d'un profil machine est-il branché ?
+    fn device_available(&self, name: &str, is_input: bool) -> bool {
+        let found = self.device_present(name, is_input);
+        if !found {
+            warn!("Device \"{name}\" not found, using the system default");
+        }
+        found
+    }
+
+    fn send_machine_profile(&self) {
+        let Some(name) = self.machine.clone() else {
+            self.send_error("Machine profiles are not configured".to_string());
+            return;
+        };
+        let profiles = self.machine_profiles();
+        let _ = self.event_tx.try_send(Event::MachineProfile {
+            profile: profiles.get(&name).cloned().unwrap_or_default(),
+            available: profiles.keys().cloned().collect(),
+            name,
+        });
+    }
+
+    /// Crée un canal d'entrée depuis un template.
+    ///
+    /// `template` est cherché parmi les templates de canaux, puis parmi
+    /// les presets d'effets sauvés (volume nominal, tous les bus).
+    /// Le canal est routé vers les bus existants au moment de l'appel :
+    /// un bus ajouté plus tard ne le reçoit pas automatiquement.
+    pub fn add_channel_from_template(
+        &mut self,
+        channel: ChannelId,
+        name: &str,
+        template: &str,
+    ) -> TroubadourResult<()> {
+        let template = match self.channel_templates.get(template) {
+            Some(found) => found.clone(),
+            None => self
+                .effects_presets
+                .load(template)
+                .map(|(preset, warnings)| {
+                    self.report_preset_warnings(template, warnings);
+                    ChannelTemplate::from_effects_preset(preset)
+                })
+                .map_err(|_| TroubadourError::TemplateNotFound(template.to_string()))?,
+        };
+
+        let mut config = ChannelConfig::new(channel, name, ChannelKind::Input);
+        config.volume = template.volume;
+        self.mixer.add_channel(config)?;
+
+        let mut buses: Vec<ChannelId> = self
+            .mixer
+            .outputs()
+            .into_iter()
+            .filter(|bus| template.buses.includes(&bus.name))
+            .map(|bus| bus.id)
+            .collect();
+        buses.sort_by_key(|bus| bus.0);
+        for bus in buses {
+            self.mixer.add_route(channel, bus);
+        }
+
+        self.set_channel_effects(channel, template.effects);
+        self.shared_state.update_from_mixer(&self.mixer);
+        self.journal_change(
+            "AddChannelFromTemplate",
+            Some(channel),
+            None,
+            Some(template.name),
+        );
+        Ok(())
+    }
+
+    /// Remplace les templates de canaux (autre dossier, tests).
+    pub fn set_channel_templates(&mut self, registry: ChannelTemplateRegistry) {
+        self.channel_templates = registry;
+    }
+
+    /// Remplace la bibliothèque de presets d'effets (autre dossier).
+    pub fn set_effects_preset_manager(&mut self, manager: EffectsPresetManager) {
+        self.effects_presets = manager;
+    }
+
+    /// Dossier des presets d'effets lu dans la config (`None` : celui
+    /// par défaut), puis migration de l'ancien dossier relatif
+    /// (`LEGACY_PRESET_DIR`). Un échec de migration est journalisé, le
+    /// moteur démarre quand même.
+    pub fn configure_presets(&mut self, dir: Option<&Path>) {
+        self.effects_presets = dir.map_or_else(EffectsPresetManager::default, |dir| {
+            EffectsPresetManager::new(dir)
+        });
+        match self
+            .effects_presets
+            .migrate_from(Path::new(LEGACY_PRESET_DIR))
+        {
+            Ok(report) if report.is_empty() => {}
+            Ok(report) => info!(
+                "Legacy presets migrated to {}: {report}",
+                self.effects_presets.dir().display()
+            ),
+            Err(e) => warn!("Legacy presets not migrated: {e}"),
+        }
+    }
+
+    /// Dossier des presets d'effets en usage.
+    pub fn preset_dir(&self) -> &Path {
+        self.effects_presets.dir()
+    }
+
+    /// Change le dossier des presets d'effets. Refusé si on ne peut pas
+    /// y écrire : l'erreur arriverait sinon à la première sauvegarde,
+    /// loin du réglage fautif.
+    pub fn set_preset_dir(&mut self, path: PathBuf) -> TroubadourResult<()> {
+        ensure_writable(&path).map_err(|e| {
+            TroubadourError::ConfigError(format!("{} is not writable: {e}", path.display()))
+        })?;
+        let before = self.effects_presets.dir().display().to_string();
+        self.journal_change(
+            "SetPresetDir",
+            None,
+            Some(before),
+            Some(path.display().to_string()),
+        );
+        self.effects_presets = EffectsPresetManager::new(path);
+        Ok(())
+    }
+
+    fn send_preset_dir(&self) {
+        let path = self.effects_presets.dir().to_path_buf();
+        let _ = self.event_tx.try_send(Event::PresetDir { path });
+    }
+
+    /// Remplace la bibliothèque de profils de surfaces (autre dossier).
+    pub fn set_control_profile_manager(&mut self, manager: ControlProfileManager) {
+        self.control_profiles = manager;
+    }
+
+    /// Active un profil de surface de contrôle (intégré ou sauvé).
+    ///
+    /// Les liaisons sont remplacées d'un bloc : la valeur suivante d'un
+    /// contrôle est déjà traduite selon le nouveau profil. En cas
+    /// d'erreur, l'ancien profil reste actif.
+    pub fn activate_control_profile(&mut self, name: &str) -> TroubadourResult<()> {
+        let profile = self.control_profiles.load(name)?;
+        info!(
+            "Control profile {} active ({} bindings)",
+            profile.name,
+            profile.bindings.len()
+        );
+        self.control_profile = Some(profile);
+        Ok(())
+    }
+
+    /// Nom du profil de surface actif.
+    pub fn active_control_profile(&self) -> Option<&str> {
+        self.control_profile.as_ref().map(|p| p.name.as_str())
+    }
+
+    /// Traduit la valeur d'un contrôle selon le profil actif et la remet
+    /// dans la file de commandes (traitée dans ce même `process_commands`).
+    /// Retourne `false` si le contrôle n'est pas lié, si la valeur ne
+    /// déclenche rien (bouton relâché) ou si la file est pleine.
+    pub fn control_input(&self, source: &ControlSource, value: f32) -> bool {
+        let Some(binding) = self
+            .control_profile
+            .as_ref()
+            .and_then(|p| p.binding_for(source))
+        else {
+            return false;
+        };
+        binding
+            .command(value)
+            .is_some_and(|command| self.control_tx.try_send(command).is_ok())
+    }
+
+    fn send_effects_preset_list(&self) {
+        let _ = self
+            .event_tx
+            .try_send(Event::EffectsPresetList(self.effects_presets.list()));
+    }
+
+    fn send_preset_cache_stats(&self) {
+        let _ = self
+            .event_tx
+            .try_send(Event::PresetCacheStats(self.effects_presets.cache_stats()));
+    }
+
+    /// Charge un preset d'effets sauvé sur `channel`.
+    fn apply_effects_preset(&mut self, channel: ChannelId, name: String) {
+        match self.effects_presets.load(&name) {
+            Ok((preset, warnings)) => {
+                self.report_preset_warnings(&name, warnings);
+                let before = Some(self.channel_effects(channel).name);
+                self.set_channel_effects(channel, preset);
+                self.journal_change(
+                    "ApplyEffectsPreset",
+                    Some(channel),
+                    before,
+                    Some(name.clone()),
+                );
+                let _ = self
+                    .event_tx
+                    .try_send(Event::EffectsPresetApplied { channel, name });
+            }
+            Err(e) => self.send_error(format!("Cannot apply effects preset: {e}")),
+        }
+    }
+
+    /// Charge les favoris de la config.
+    pub fn configure_favorite_presets(&mut self, favorites: FavoritePresets) {
+        self.favorite_presets = favorites;
+    }
+
+    /// Favoris, à réécrire dans la config.
+    pub fn favorite_presets(&self) -> &FavoritePresets {
+        &self.favorite_presets
+    }
+
+    /// Seul un preset de la bibliothèque devient favori ; il peut
+    /// disparaître ensuite (signalé par `Event::FavoritePresets`).
+    fn add_favorite_preset(&mut self, name: &str, position: Option<usize>) -> TroubadourResult<()> {
+        if !self.effects_presets.path_for(name)?.exists() {
+            return Err(TroubadourError::PresetNotFound(name.to_string()));
+        }
+        self.favorite_presets.add(name, position);
+        Ok(())
+    }
+
+    fn send_favorite_presets(&self) {
+        let favorites = self.effects_presets.favorites(&self.favorite_presets);
+        let _ = self.event_tx.try_send(Event::FavoritePresets(favorites));
+    }
+
+    fn send_error(&self, message: String) {
+        warn!("{message}");
+        let _ = self.event_tx.try_send(Event::Error(message));
+    }
+
+    /// Vérifié AVANT de toucher au mixer : une modification qui ne peut
+    /// pas partir vers le thread audio est refusée en entier, sinon le
+    /// mixer et ce qu'on entend divergeraient.
+    fn engine_queue_has_room(&self) -> bool {
+        if self.shared_state.is_queue_full() {
+            let depth = self.shared_state.queue_depth();
+            self.send_error(TroubadourError::CommandQueueFull(depth).to_string());
+            return false;
+        }
+        true
+    }
+
+    /// Transmet une modification déjà faite sur le mixer au thread audio,
+    /// par la file de commandes (moteur arrêté : au prochain pipeline).
+    fn queue_engine_command(&mut self, command: EngineCommand) {
+        if let Err(e) = self.shared_state.send(command) {
+            self.send_error(e.to_string());
+        }
+        self.recovery.mark_dirty();
+    }
+
+    /// Volume ou pan d'un canal : seul son gain part, pas tout l'état.
+    fn queue_gain(&mut self, channel: ChannelId) {
+        if let Some(command) = EngineCommand::gain_of(&self.mixer, channel) {
+            self.queue_engine_command(command);
+        }
+    }
+
+    /// `queue_gain` du canal, de son partenaire stéréo et des bus qui
+    /// suivent son volume.
+    fn queue_linked_gain(&mut self, channel: ChannelId) {
+        self.queue_gain(channel);
+        if let Some(partner) = self.mixer.link_partner(channel) {
+            self.queue_gain(partner);
+        }
+        for follower in self.mixer.bus_followers(channel) {
+            self.queue_gain(follower);
+        }
+    }
+
+    fn send_channel_links(&self) {
+        let links = self.mixer.links().to_vec();
+        let _ = self.event_tx.try_send(Event::ChannelLinkList(links));
+    }
+
+    fn send_bus_follows(&self) {
+        let follows = self.mixer.bus_follows().to_vec();
+        let _ = self.event_tx.try_send(Event::BusFollowList(follows));
+    }
+
+    fn send_action_set_list(&self) {
+        let names = self.action_sets.keys().cloned().collect();
+        let _ = self.event_tx.try_send(Event::ActionSetList(names));
+    }
+
+    fn send_device_list(&self) {
+        let input_infos = self.device_manager.list_input_devices().unwrap_or_default();
+        let output_infos = self
+            .device_manager
+            .list_output_devices()
+            .unwrap_or_default();
+
+        let inputs = input_infos.iter().map(|d| d.name.clone()).collect();
+        let outputs = output_infos.iter().map(|d| d.name.clone()).collect();
+
+        let defaults = self.current_system_defaults();
+        let _ = self.event_tx.try_send(Event::DeviceList {
+            inputs,
+            outputs,
+            default_input: defaults.input,
+            default_output: defaults.output,
+        });
+        let _ = self.event_tx.try_send(Event::DeviceDetails {
+            inputs: input_infos,
+            outputs: output_infos,
+        });
+    }
+
+    pub fn stop(&mut self) {
+        if self.state == EngineState::Stopped {
+            return;
+        }
+        info!("Stopping audio engine...");
+        if let Some(device) = self.preview.stop() {
+            self.device_preview_ended(device, PreviewStopReason::EngineStopped);
+        }
+        self.handover = None;
+        self.streams.clear();
+        self.pipeline_link = None;
+        self.played_bus = None;
+        self.input_fallback = None;
+        self.watchdog.clear();
+        if let Ok(mut chain) = self.lock_dsp_chain() {
+            chain.clear_detector_listen();
+        }
+        let cleared = self.mixer.clear_effects_diff_monitor();
+        if !cleared.is_empty() {
+            self.shared_state.update_from_mixer(&self.mixer);
+            for channel in cleared {
+                let _ = self.event_tx.try_send(Event::EffectsDiffMonitor {
+                    channel,
+                    enabled: false,
+                });
+            }
+        }
+        info!("{}", session::summary(&self.session_stats()));
+        self.session.engine_stopped(Instant::now());
+        self.state = EngineState::Stopped;
+        let _ = self.event_tx.try_send(Event::EngineStopped);
+        info!("Audio engine stopped");
+    }
+
+    pub fn take_command_receiver(&mut self) -> Receiver<Command> {
+        self.command_rx.clone()
+    }
+
+    pub fn take_event_sender(&mut self) -> Sender<Event> {
+        self.event_tx.clone()
+    }
+
+    /// Exporte le graphe du flux de signal.
+    ///
+    /// Moteur arrêté → graphe de la config du mixer.
+    /// Moteur démarré → on y fusionne les streams réellement ouverts :
+    /// un device configuré mais jamais ouvert reste `active: false`,
+    /// un device par défaut ouvert sans être configuré apparaît quand même.
+    pub fn export_signal_graph(&self) -> SignalGraph {
+        let mut graph = self.mixer.export_signal_graph();
+        if self.state == EngineState::Running {
+            merge_active_streams(
+                &mut graph,
+                &self.mixer,
+                self.streams.infos(),
+                &self.system_defaults,
+            );
+        }
+        graph
+    }
+
+    /// Nombre d'underruns de sortie depuis la création du moteur.
+    pub fn underrun_count(&self) -> u64 {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Active la mesure de sonie (LUFS) d'un bus. Seul le bus joué par
+    /// le device de sortie ouvert produit des valeurs.
+    pub fn enable_loudness_meter(&mut self, bus: ChannelId) -> MixerResult<()> {
+        self.mixer.set_loudness_meter(bus, true)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        Ok(())
+    }
+
+    pub fn disable_loudness_meter(&mut self, bus: ChannelId) -> MixerResult<()> {
+        self.mixer.set_loudness_meter(bus, false)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        Ok(())
+    }
+
+    /// Dernière sonie mesurée sur un bus (`None` : meter non activé ;
+    /// valeurs vides tant que le bus n'a rien joué depuis l'activation
+    /// ou la dernière remise à zéro).
+    pub fn loudness(&self, bus: ChannelId) -> Option<Loudness> {
+        let generation = self.mixer.loudness_meter(bus)?;
+        Some(self.loudness.load(bus, generation).unwrap_or_default())
+    }
+
+    /// Remet à zéro la mesure de sonie d'un bus.
+    pub fn reset_loudness(&mut self, bus: ChannelId) -> MixerResult<()> {
+        self.mixer.reset_loudness_meter(bus)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        Ok(())
+    }
+
+    /// Limite le nombre de canaux du mixer (`audio.max_channels`).
+    pub fn set_max_channels(&mut self, max_channels: usize) {
+        self.mixer.set_max_channels(max_channels);
+    }
+
+    /// Choisit la réserve visée devant la sortie, en blocs du device
+    /// (prise en compte à la prochaine ouverture des streams).
+    pub fn set_target_output_latency_blocks(&mut self, blocks: u32) {
+        self.target_latency_blocks = blocks.max(1);
+    }
+
+    /// Santé de la sortie : underruns, réserve actuelle, dérive
+    /// d'horloge et corrections du régulateur de latence.
+    pub fn audio_stats(&self) -> AudioStats {
+        AudioStats {
+            underruns: self.underrun_count(),
+            output_depth_frames: self.latency.depth_frames(),
+            target_depth_frames: self.latency.target_frames(),
+            frames_skipped: self.latency.frames_skipped(),
+            frames_inserted: self.latency.frames_inserted(),
+            clock_drift_ppm: self.latency.drift_ppm(),
+            ratio_correction_ppm: self.latency.correction_ppm(),
+            stream_stalls: self.watchdog.stall_count(),
+            stream_restarts: self.watchdog.restart_count(),
+            command_queue_depth: self.shared_state.queue_depth() as u32,
+            tap_overflows: self.taps.lock().map(|t| t.overflows()).unwrap_or(0),
+            idle: self.idle.is_idle(),
+            device_preview: self.preview.status(journal::now_ms()),
+            meter_subscription: self.meter_subscription.size().map(|n| n as u32),
+            meters_paused: self.meter_subscription.paused(),
+            meter_levels_per_second: self.meter_rate.per_second(),
+        }
+    }
+
+    /// Statistiques de la session : durée de fonctionnement, underruns,
+    /// pire charge DSP, crête de chaque bus et reconnexions.
+    pub fn session_stats(&self) -> SessionStats {
+        self.session.stats(Instant::now(), self.underrun_count())
+    }
+
+    /// Active ou coupe la mesure d'activité des routes. Coupée, le
+    /// callback ne fait qu'un test de booléen par bloc.
+    pub fn set_route_metering(&mut self, enabled: bool) {
+        self.route_meter.set_enabled(enabled);
+    }
+
+    /// Routes ayant porté du signal depuis l'activation de la mesure,
+    /// avec leur dernière crête et l'heure où elle a été vue. Vide si
+    /// la mesure est coupée.
+    pub fn get_route_activity(&self) -> Vec<RouteActivity> {
+        self.route_meter.activity(self.mixer.routes())
+    }
+
+    /// Envoie l'activité des routes si le callback en a mesuré depuis
+    /// le dernier tick : lue ici plutôt que dans le callback, qui n'a
+    /// ainsi rien à allouer.
+    fn send_route_activity(&self) {
+        if self.route_meter.take_measured() {
+            let _ = self
+                .event_tx
+                .try_send(Event::RouteActivity(self.get_route_activity()));
+        }
+    }
+
+    /// Délai et bus de l'écoute des devices.
+    pub fn configure_device_preview(&mut self, config: &DevicePreviewConfig) {
+        self.preview.set_timeout(config.timeout_seconds);
+        self.preview_bus = config.monitor_bus.clone();
+    }
+
+    /// Écoute un device d'entrée sans l'assigner (voir `preview`) : un
+    /// stream temporaire, converti au rate du mixer, mélangé au bus
+    /// d'écoute à `PREVIEW_LEVEL_DB`. Remplace l'écoute en cours.
+    ///
+    /// Refusé moteur arrêté, pour un device déjà ouvert par le pipeline,
+    /// ou qui ne s'ouvre pas (l'écoute en cours continue alors).
+    pub fn start_device_preview(&mut self, device: &str) -> TroubadourResult<()> {
+        if self.state != EngineState::Running {
+            return Err(TroubadourError::StreamError(
+                "the engine is not running".to_string(),
+            ));
+        }
+        if self.stream_device(true).as_deref() == Some(device) {
+            return Err(TroubadourError::StreamError(format!(
+                "{device} already feeds the mixer"
+            )));
+        }
+        let bus = self.preview_monitor_bus();
+        let stream = self.open_preview_stream(device, bus)?;
+        let replaced = self.preview.start(device, bus, journal::now_ms(), || {
+            Ok::<_, TroubadourError>(stream)
+        })?;
+        if let Some(replaced) = replaced {
+            let _ = self.event_tx.try_send(Event::DevicePreviewStopped {
+                device: replaced,
+                reason: PreviewStopReason::Replaced,
+            });
+        }
+        info!("Previewing {device}");
+        let _ = self.event_tx.try_send(Event::DevicePreviewStarted {
+            device: device.to_string(),
+            bus,
+        });
+        Ok(())
+    }
+
+    /// Arrête l'écoute en cours (sans effet s'il n'y en a pas).
+    pub fn stop_device_preview(&mut self) {
+        if let Some(device) = self.preview.stop() {
+            self.device_preview_ended(device, PreviewStopReason::Requested);
+        }
+    }
+
+    /// Arrête l'écoute dont le délai est écoulé.
+    fn expire_device_preview(&mut self, now_ms: u64) {
+        if let Some(device) = self.preview.expire(now_ms) {
+            self.device_preview_ended(device, PreviewStopReason::TimedOut);
+        }
+    }
+
+    /// Après l'arrêt d'une écoute : plus rien à mélanger, l'UI est prévenue.
+    fn device_preview_ended(&self, device: String, reason: PreviewStopReason) {
+        if let Ok(mut feed) = self.preview_feed.lock() {
+            *feed = None;
+        }
+        info!("Preview of {device} ended: {reason:?}");
+        let _ = self
+            .event_tx
+            .try_send(Event::DevicePreviewStopped { device, reason });
+    }
+
+    /// Bus qui entend l'écoute : celui de la config, sinon le bus joué.
+    fn preview_monitor_bus(&self) -> Option<ChannelId> {
+        match &self.preview_bus {
+            Some(name) => self
+                .mixer
+                .outputs()
+                .iter()
+                .find(|bus| bus.name.eq_ignore_ascii_case(name))
+                .map(|bus| bus.id),
+            None => self.stream_device(false).and_then(|output| {
+                stream_channels(&self.mixer, &output, false, &self.system_defaults)
+                    .first()
+                    .copied()
+            }),
+        }
+    }
+
+    /// Ouvre et démarre le stream d'une écoute. Son callback downmixe
+    /// le device en mono, le baisse à `PREVIEW_LEVEL_DB`, le convertit au
+    /// rate du mixer, le confie au pipeline (`PreviewFeed`) et envoie ses
+    /// niveaux.
+    ///
+    /// La file n'est remplacée qu'une fois le stream construit : un
+    /// échec laisse celle de l'écoute en cours.
+    fn open_preview_stream(
+        &mut self,
+        name: &str,
+        bus: Option<ChannelId>,
+    ) -> TroubadourResult<PipelineStream> {
+        let device = self.device_manager.find_input_device(name)?;
+        let config = device
+            .default_input_config()
+            .map_err(|e| TroubadourError::StreamError(e.to_string()))?;
+        let rate = self.processing_rate as u32;
+        let config = config_at_rate(config, device.supported_input_configs().ok(), rate);
+        if config.sample_format() != SampleFormat::F32 {
+            return Err(TroubadourError::StreamError(format!(
+                "Unsupported format: {:?}. Only F32 supported.",
+                config.sample_format()
+            )));
+        }
+        let device_rate = config.sample_rate().0;
+        if device_rate != rate {
+            info!("Preview of {name} resampled {device_rate} Hz → {rate} Hz");
+        }
+        let mut callback = PreviewCallback::new(
+            self.preview_feed.clone(),
+            self.event_tx.clone(),
+            MeterGate::new(self.meter_subscription.clone()),
+            // Échelle prise à l'ouverture, comme le bus d'écoute : un
+            // stream temporaire n'a pas de lecteur d'état à lui
+            self.mixer.meter_scale(),
+            usize::from(config.channels()),
+            CallbackResampler::new(
+                device_rate,
+                rate,
+                1,
+                BufferSize::default().as_frames() as usize,
+            )?,
+        );
+        let stream = device
+            .build_input_stream(
+                &config.into(),
+                move |data: &[f32], _: &cpal::InputCallbackInfo| callback.process(data),
+                move |err| error!("Preview stream error: {err}"),
+                None,
+            )
+            .map_err(|e| {
+                let supported =
+                    supported_rates(device.supported_input_configs().into_iter().flatten());
+                build_stream_error(name, true, &e, device_rate, &supported)
+            })?;
+        if let Ok(mut feed) = self.preview_feed.lock() {
+            *feed = Some(PreviewFeed::new(bus, rate as f32));
+        }
+        let mut stream = PipelineStream::Device(stream);
+        if let Err(e) = stream.play(name, true) {
+            if let Ok(mut feed) = self.preview_feed.lock() {
+                *feed = None;
+            }
+            return Err(e);
+        }
+        Ok(stream)
+    }
+
+    /// Résolution et durée de l'historique des niveaux. Les historiques
+    /// en cours repartent de zéro.
+    pub fn configure_level_history(&mut self, config: &LevelHistoryConfig) {
+        self.level_history_config = *config;
+        for history in self.level_history.values_mut() {
+            *history = LevelHistory::new(config);
+        }
+    }
+
+    /// Commence ou arrête l'historique des niveaux d'un canal (arrêté,
+    /// sa mémoire est rendue).
+    pub fn set_level_history(&mut self, channel: ChannelId, enabled: bool) -> MixerResult<()> {
+        if !enabled {
+            self.level_history.remove(&channel);
+            return Ok(());
+        }
+        if self.mixer.channel(channel).is_none() {
+            return Err(MixerError::ChannelNotFound(channel.0));
+        }
+        self.level_history
+            .entry(channel)
+            .or_insert_with(|| LevelHistory::new(&self.level_history_config));
+        Ok(())
+    }
+
+    /// Les `seconds` dernières secondes de l'historique d'un canal
+    /// (`None` : historique non activé).
+    pub fn level_history(&self, channel: ChannelId, seconds: u32) -> Option<Vec<LevelPoint>> {
+        self.level_history
+            .get(&channel)
+            .map(|history| history.points(seconds))
+    }
+
+    /// Relève les niveaux du tick : ajoutés à l'historique des canaux
+    /// suivis, et à la mesure du réglage guidé. La crête de chaque canal
+    /// n'est prise qu'une fois.
+    fn observe_levels(&mut self, now_ms: u64) {
+        let measured = self.auto_level.as_ref().and_then(AutoLevel::current);
+        let mut measured_peak = None;
+        for (&channel, history) in &mut self.level_history {
+            let rms = self.levels.load(channel).map_or(0.0, |(rms, _)| rms);
+            let peak = self.levels.take_peak(channel);
+            history.record(now_ms, rms, peak);
+            if measured == Some(channel) {
+                measured_peak = Some(peak);
+            }
+        }
+        if let Some(channel) = measured {
+            let peak = measured_peak.unwrap_or_else(|| self.levels.take_peak(channel));
+            self.advance_auto_level(now_ms, peak);
+        }
+    }
+
+    /// Réglage guidé des niveaux (voir `auto_level`) : mesure la crête de
+    /// chaque canal de `channels`, l'un après l'autre pendant
+    /// `duration_ms`, et règle son volume pour qu'elle atteigne
+    /// `target_peak_db`.
+    ///
+    /// La mesure avance à chaque `process_commands` :
+    /// `Event::AutoLevelProgress` au début de chaque canal,
+    /// `Event::AutoLevelChannel` à sa fin, puis `Event::AutoLevelFinished`.
+    /// Une nouvelle demande remplace celle en cours. Refusé moteur
+    /// arrêté, ou pour un canal que le pipeline ne mesure pas (seuls
+    /// l'entrée et le bus joué le sont) : sa crête resterait muette.
+    pub fn auto_level_channels(
+        &mut self,
+        channels: Vec<ChannelId>,
+        target_peak_db: f32,
+        duration_ms: u64,
+    ) -> MixerResult<()> {
+        if let Some(&missing) = channels.iter().find(|&&c| self.mixer.channel(c).is_none()) {
+            return Err(MixerError::ChannelNotFound(missing.0));
+        }
+        let Some(&first) = channels.first() else {
+            return Err(MixerError::InvalidParameter("no channel to level".into()));
+        };
+        if self.state != EngineState::Running {
+            return Err(MixerError::InvalidParameter(
+                "the engine is not running".into(),
+            ));
+        }
+        let metered = self.meter_layout.as_ref().map_or(&[][..], |l| &l.targets);
+        if let Some(&unmetered) = channels.iter().find(|c| !metered.contains(c)) {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} is not metered: only the pipeline input and the played bus are",
+                unmetered.0
+            )));
+        }
+        if !(auto_level::SILENCE_DB..=0.0).contains(&target_peak_db) {
+            return Err(MixerError::InvalidParameter(format!(
+                "target peak {target_peak_db} dBFS outside {}..0",
+                auto_level::SILENCE_DB
+            )));
+        }
+        if duration_ms == 0 {
+            return Err(MixerError::InvalidParameter(
+                "measurement window is empty".into(),
+            ));
+        }
+        info!("Auto level of {} channel(s)", channels.len());
+        let total = channels.len();
+        self.auto_level = Some(AutoLevel::new(channels, target_peak_db, duration_ms));
+        let _ = self.event_tx.try_send(Event::AutoLevelProgress {
+            channel: first,
+            index: 0,
+            total,
+        });
+        Ok(())
+    }
+
+    /// Abandonne le réglage guidé : les canaux déjà réglés le restent.
+    pub fn cancel_auto_level(&mut self) {
+        if self.auto_level.take().is_some() {
+            info!("Auto level cancelled");
+        }
+    }
+
+    /// Ajoute au réglage guidé la crête du tick ; règle le canal dont la
+    /// fenêtre se termine.
+    fn advance_auto_level(&mut self, now_ms: u64, peak: f32) {
+        let Some(auto) = &mut self.auto_level else {
+            return;
+        };
+        let Some((channel, measured)) = auto.observe(now_ms, peak) else {
+            return;
+        };
+        let target = auto.target_peak_db();
+        // Canal retiré pendant la mesure : compté comme muet
+        let volume = self.mixer.channel(channel).map_or(0.0, |c| c.volume);
+        let result = auto_level::level_channel(channel, measured, volume, target);
+        if result.status == AutoLevelStatus::Applied {
+            self.mixer.set_volume(channel, result.volume);
+            self.queue_linked_gain(channel);
+            self.shared_state.update_from_mixer(&self.mixer);
+            self.recovery.mark_dirty();
+            self.journal_change(
+                "AutoLevel",
+                Some(channel),
+                Some(format!("{volume:.2}")),
+                Some(format!("{:.2}", result.volume)),
+            );
+        }
+        let _ = self.event_tx.try_send(Event::AutoLevelChannel(result));
+
+        let Some(auto) = &mut self.auto_level else {
+            return;
+        };
+        auto.push_result(result);
+        match auto.current() {
+            Some(next) => {
+                let (index, total) = auto.position();
+                let _ = self.event_tx.try_send(Event::AutoLevelProgress {
+                    channel: next,
+                    index,
+                    total,
+                });
+            }
+            None => {
+                let results = auto.results().to_vec();
+                self.auto_level = None;
+                let _ = self.event_tx.try_send(Event::AutoLevelFinished(results));
+            }
+        }
+    }
+
+    /// Forme des niveaux envoyés à l'UI. En binaire, le layout du
+    /// pipeline en place est annoncé (`Event::MeterLayout`) avant la
+    /// première trame.
+    pub fn set_meter_transport(&mut self, transport: MeterTransport) {
+        let binary = transport == MeterTransport::Binary;
+        if binary && let Some(layout) = &self.meter_layout {
+            let _ = self.event_tx.try_send(Event::MeterLayout(layout.clone()));
+        }
+        self.binary_meters.store(binary, Ordering::Relaxed);
+    }
+
+    /// Canaux que l'UI affiche : le callback ne mesure qu'eux pour elle
+    /// (liste vide : aucun). `active: false` revient à tout mesurer.
+    pub fn set_meter_subscription(&mut self, targets: &[ChannelId], active: bool) {
+        self.meter_subscription.subscribe(targets, active);
+    }
+
+    /// Suspend l'envoi des meters (fenêtre réduite), crêtes tenues.
+    pub fn set_meters_paused(&mut self, paused: bool) {
+        self.meter_subscription.set_paused(paused);
+    }
+
+    pub fn meter_transport(&self) -> MeterTransport {
+        if self.binary_meters.load(Ordering::Relaxed) {
+            MeterTransport::Binary
+        } else {
+            MeterTransport::Json
+        }
+    }
+
+    /// Canaux des trames binaires du dernier pipeline démarré.
+    pub fn meter_layout(&self) -> Option<&MeterLayout> {
+        self.meter_layout.as_ref()
+    }
+
+    /// Layout d'un nouveau pipeline : l'entrée, puis le bus joué (mesuré
+    /// seulement avec la sonie ou le true peak). Annoncé tout de suite
+    /// en binaire.
+    fn new_meter_layout(&mut self, output_bus: Option<ChannelId>) -> MeterFrameEncoder {
+        self.next_meter_layout = self.next_meter_layout.wrapping_add(1);
+        self.played_bus = output_bus;
+        let targets = std::iter::once(PIPELINE_INPUT_CHANNEL)
+            .chain(output_bus)
+            .collect();
+        let layout = MeterLayout::new(self.next_meter_layout, targets);
+        self.meter_layout = Some(layout.clone());
+        if self.binary_meters.load(Ordering::Relaxed) {
+            let _ = self.event_tx.try_send(Event::MeterLayout(layout.clone()));
+        }
+        MeterFrameEncoder::new(layout)
+    }
+
+    /// Fait repartir la session de zéro (durée, maximums, compteurs).
+    pub fn reset_session_stats(&mut self) {
+        self.session.reset(Instant::now(), self.underrun_count());
+    }
+
+    /// État de chaque stream ouvert selon le watchdog.
+    pub fn stream_health(&self) -> Vec<StreamHealth> {
+        self.watchdog.health(watchdog::monotonic_ms())
+    }
+
+    /// Rassemble ce que le diagnostic doit vérifier.
+    pub fn diagnostics_context(&self) -> DiagnosticsContext {
+        let defaults = self.current_system_defaults();
+        DiagnosticsContext {
+            config_path: AppConfig::default_path(),
+            preset_dir: self.effects_presets.dir().to_path_buf(),
+            mixer: self.mixer.to_config(),
+            input_device: self.input_device().or(defaults.input),
+            output_device: self.stream_device(false).or(defaults.output),
+            engine: EngineHealth {
+                running: self.state == EngineState::Running,
+                underruns: self.underrun_count(),
+                stalled_streams: self
+                    .stream_health()
+                    .into_iter()
+                    .filter(|s| !s.healthy)
+                    .map(|s| s.device_name)
+                    .collect(),
+                active_streams: self
+                    .streams
+                    .infos()
+                    .iter()
+                    .map(|s| {
+                        format!(
+                            "{} ({} ch @ {} Hz)",
+                            s.device_name, s.channels, s.sample_rate
+                        )
+                    })
+                    .collect(),
+            },
+            timeout: diagnostics::PROBE_TIMEOUT,
+        }
+    }
+
+    /// Lance le diagnostic dans un thread : les sondes peuvent prendre
+    /// plusieurs secondes (drivers lents), le traitement des commandes
+    /// ne doit pas les attendre. Le rapport arrive en `Event::Diagnostics`
+    /// (et est écrit dans `export_path` si fourni).
+    fn spawn_diagnostics(&self, export_path: Option<PathBuf>) {
+        let ctx = self.diagnostics_context();
+        let event_tx = self.event_tx.clone();
+        let spawned = std::thread::Builder::new()
+            .name("diagnostics".into())
+            .spawn(move || {
+                let report = diagnostics::run_diagnostics(Arc::new(CpalProbe), &ctx);
+                if let Some(path) = export_path {
+                    match report.save(&path) {
+                        Ok(()) => {
+                            let _ = event_tx.send(Event::DiagnosticsExported { path });
+                        }
+                        Err(e) => {
+                            let _ = event_tx.send(Event::Error(format!(
+                                "Cannot write diagnostics to {}: {e}",
+                                path.display()
+                            )));
+                        }
+                    }
+                }
+                let _ = event_tx.send(Event::Diagnostics(Box::new(report)));
+            });
+        if let Err(e) = spawned {
+            self.send_error(format!("Cannot start diagnostics: {e}"));
+        }
+    }
+
+    /// Analyse ou normalisation d'un fichier dans un thread, comme le
+    /// diagnostic : un enregistrement de plusieurs heures se lit en
+    /// plusieurs secondes. L'avancement part en `Event::RecordingProgress`
+    /// (par pas d'au moins 1 %, perdu si la file d'événements est pleine),
+    /// le résultat en l'événement rendu par `task`.
+    fn spawn_recording_task(
+        &self,
+        path: PathBuf,
+        task: impl FnOnce(&mut dyn FnMut(f32)) -> TroubadourResult<Event> + Send + 'static,
+    ) {
+        let event_tx = self.event_tx.clone();
+        let spawned = std::thread::Builder::new()
+            .name("recording".into())
+            .spawn(move || {
+                let mut reported = 0.0;
+                let mut progress = |value: f32| {
+                    if value - reported >= 0.01 || (value >= 1.0 && reported < 1.0) {
+                        reported = value;
+                        let _ = event_tx.try_send(Event::RecordingProgress {
+                            path: path.clone(),
+                            progress: value,
+                        });
+                    }
+                };
+                let event = task(&mut progress)
+                    .unwrap_or_else(|e| Event::Error(format!("Recording {}: {e}", path.display())));
+                let _ = event_tx.send(event);
+            });
+        if let Err(e) = spawned {
+            self.send_error(format!("Cannot start recording analysis: {e}"));
+        }
+    }
+
+    /// Streams audio actuellement ouverts par le moteur.
+    pub fn active_streams(&self) -> &[ActiveStream] {
+        self.streams.infos()
+    }
+
+    /// Remplace le journal des modifications selon une nouvelle config.
+    pub fn configure_journal(&mut self, config: &JournalConfig) {
+        self.journal = ChangeJournal::from_config(config);
+    }
+
+    /// Applique les réglages des surfaces de contrôle (courbe des faders).
+    pub fn configure_controls(&mut self, config: &ControlsConfig) {
+        self.fader_taper = config.fader_taper;
+        self.passthrough_bus = config.passthrough_bus;
+        self.set_meter_scale(config.meter_scale());
+        if let Some(name) = &config.control_profile
+            && let Err(e) = self.activate_control_profile(name)
+        {
+            warn!("Control profile {name} not activated: {e}");
+        }
+    }
+
+    /// Change l'échelle des meters : les niveaux suivants (plancher,
+    /// positions des `LevelUpdate`) la suivent aussitôt.
+    pub fn set_meter_scale(&mut self, scale: MeterScale) {
+        self.mixer.set_meter_scale(scale);
+        self.shared_state.update_from_mixer(&self.mixer);
+    }
+
+    fn send_meter_scale(&self) {
+        let scale = self.mixer.meter_scale();
+        let _ = self.event_tx.try_send(Event::MeterScale {
+            scale,
+            ticks: scale.ticks(),
+        });
+    }
+
+    /// Bouton panique : effets contournés et gain unité pour chaque
+    /// canal routé vers le bus de repli (`ControlsConfig::
+    /// passthrough_bus`, sinon le premier bus). Retourne le bus joué.
+    ///
+    /// Override de traitement, pas un réglage : rien n'est modifié dans
+    /// les canaux ni sauvé, désactiver rend le mix d'avant.
+    pub fn set_emergency_passthrough(&mut self, enabled: bool) -> MixerResult<Option<ChannelId>> {
+        let before = self.mixer.emergency_passthrough();
+        let bus = if enabled {
+            let bus = self
+                .passthrough_bus
+                .or_else(|| self.mixer.default_passthrough_bus())
+                .ok_or_else(|| MixerError::InvalidParameter("no output bus".to_string()))?;
+            Some(bus)
+        } else {
+            None
+        };
+        if bus == before {
+            return Ok(bus);
+        }
+        self.mixer.set_emergency_passthrough(bus)?;
+        self.shared_state.update_from_mixer(&self.mixer);
+        match bus {
+            Some(bus) => warn!("Emergency passthrough enabled on bus {}", bus.0),
+            None => info!("Emergency passthrough disabled"),
+        }
+        self.journal_change(
+            "SetEmergencyPassthrough",
+            bus.or(before),
+            Some(before.is_some().to_string()),
+            Some(bus.is_some().to_string()),
+        );
+        let _ = self
+            .event_tx
+            .try_send(Event::EmergencyPassthroughChanged { bus });
+        Ok(bus)
+    }
+
+    /// Bus joué par le passthrough d'urgence, s'il est actif.
+    pub fn emergency_passthrough(&self) -> Option<ChannelId> {
+        self.mixer.emergency_passthrough()
+    }
+
+    /// Courbe des faders de volume utilisée par `SetVolumeFader`.
+    pub fn fader_taper(&self) -> FaderTaper {
+        self.fader_taper
+    }
+
+    /// Change les réglages globaux de détection de silence.
+    /// Pris en compte au prochain démarrage du pipeline ; les surcharges
+    /// par canal (`SetSignalPresence`) s'appliquent à chaud.
+    pub fn configure_signal_presence(&mut self, config: &SignalPresenceConfig) {
+        self.signal_presence = *config;
+    }
+
+    /// Active l'instantané de secours, écrit dans `path` (en pratique
+    /// `RecoverySnapshot::default_path()`).
+    pub fn configure_recovery(&mut self, config: &RecoveryConfig, path: PathBuf) {
+        self.recovery = RecoveryWriter::from_config(config, path);
+    }
+
+    /// Un instantané de secours attend que l'utilisateur choisisse de le
+    /// restaurer ou de l'ignorer : rien n'est écrit par-dessus d'ici là
+    /// (le profil de départ modifie déjà l'état).
+    pub fn hold_recovery_snapshot(&mut self) {
+        self.recovery.hold();
+    }
+
+    /// Règle le watchdog des streams (timeout, réouverture automatique).
+    pub fn configure_watchdog(&mut self, config: &WatchdogConfig) {
+        self.watchdog.configure(*config);
+    }
+
+    /// Applique l'instantané de secours. Retourne sa date de prise.
+    ///
+    /// Le fichier est gardé : l'état restauré n'est toujours pas sauvé,
+    /// il sera réécrit au prochain passage. Réussie ou non, la question
+    /// est tranchée : l'écriture reprend.
+    pub fn restore_recovery_snapshot(&mut self) -> Result<u64, Box<dyn std::error::Error>> {
+        let path = self
+            .recovery
+            .path()
+            .ok_or("recovery snapshots are disabled")?;
+        let loaded = RecoverySnapshot::load(path);
+        self.recovery.release();
+        let snapshot = loaded?;
+        self.apply_profile(&snapshot.profile)?;
+        if !snapshot.ui.is_empty() {
+            self.ui_state = snapshot.ui;
+        }
+        Ok(snapshot.saved_at_ms)
+    }
+
+    /// Supprime l'instantané de secours (arrêt propre, ou "ignorer").
+    pub fn discard_recovery_snapshot(&mut self) -> std::io::Result<()> {
+        self.recovery.discard()
+    }
+
+    /// Active les instantanés automatiques, écrits dans `dir` (en
+    /// pratique `AutoSnapshot::default_dir()`).
+    ///
+    /// À appeler après le chargement du profil de départ : l'état par
+    /// défaut qu'il remplace ne mérite pas d'instantané.
+    pub fn configure_auto_snapshots(&mut self, config: &AutoSnapshotConfig, dir: PathBuf) {
+        self.auto_snapshots = AutoSnapshots::from_config(config, dir);
+    }
+
+    /// Sauve l'état avant une opération qui le remplace (`reason`).
+    ///
+    /// Un échec d'écriture est signalé sans bloquer l'opération, sauf
+    /// en mode strict : l'erreur est alors retournée et l'appelant
+    /// renonce.
+    fn auto_snapshot(&mut self, reason: &str) -> TroubadourResult<()> {
+        if !self.auto_snapshots.is_enabled() {
+            return Ok(());
+        }
+        let profile = self.to_profile("Auto snapshot");
+        let Err(e) = self
+            .auto_snapshots
+            .take(reason, &profile, journal::now_ms())
+        else {
+            return Ok(());
+        };
+        let message = format!("Cannot save auto snapshot before {reason}: {e}");
+        if self.auto_snapshots.is_strict() {
+            return Err(TroubadourError::ConfigError(message));
+        }
+        self.send_error(message);
+        Ok(())
+    }
+
+    /// Instantanés automatiques, du plus récent au plus ancien.
+    pub fn auto_snapshot_list(&self) -> Vec<AutoSnapshotInfo> {
+        self.auto_snapshots.list()
+    }
+
+    /// Revient à un instantané automatique. L'état actuel est sauvé
+    /// avant, comme pour un chargement de preset : la restauration se
+    /// défait de la même façon.
+    pub fn restore_auto_snapshot(&mut self, id: u64) -> TroubadourResult<AutoSnapshotInfo> {
+        let snapshot = self
+            .auto_snapshots
+            .load(id)
+            .map_err(|e| TroubadourError::ConfigError(format!("Auto snapshot {id}: {e}")))?;
+        self.auto_snapshot("RestoreAutoSnapshot")?;
+        self.replace_with_profile(&snapshot.profile);
+        Ok(AutoSnapshotInfo {
+            id,
+            taken_at_ms: snapshot.taken_at_ms,
+            reason: snapshot.reason,
+        })
+    }
+
+    /// Dernier état de présence de signal connu d'un canal.
+    pub fn signal_state(&self, channel: ChannelId) -> SignalState {
+        self.signal_states.get(channel)
+    }
+
+    /// Le traitement du canal a-t-il paniqué (étage sauté, canal muet) ?
+    pub fn channel_health(&self, channel: ChannelId) -> ChannelHealth {
+        self.channel_faults.get(channel)
+    }
+
+    /// Accès en lecture au journal des modifications.
+    pub fn journal(&self) -> &ChangeJournal {
+        &self.journal
+    }
+
+    pub fn state(&self) -> EngineState {
+        self.state
+    }
+
+    pub fn mixer(&self) -> &Mixer {
+        &self.mixer
+    }
+
+    pub fn shared_mixer_state(&self) -> SharedMixerState {
+        self.shared_state.clone()
+    }
+
+    /// Retourne un handle vers la chaîne DSP partagée.
+    pub fn shared_dsp_chain(&self) -> Arc<Mutex<EffectsChain>> {
+        self.dsp_chain.clone()
+    }
+}
+
+/// Réglages d'un stream tels que notés dans le cache des devices.
+/// `buffer_frames` : taille imposée au stream (`None` : celle du driver).
+fn stream_settings(config: &SupportedStreamConfig, buffer_frames: Option<u32>) -> StreamSettings {
+    StreamSettings {
+        sample_rate: config.sample_rate().0,
+        buffer_frames,
+        channels: config.channels(),
+        format: format!("{:?}", config.sample_format()),
+    }
+}
+
+/// Config à passer à cpal, avec la taille de buffer imposée s'il y en a une.
+fn stream_config(config: SupportedStreamConfig, buffer_frames: Option<u32>) -> cpal::StreamConfig {
+    let mut stream_config: cpal::StreamConfig = config.into();
+    if let Some(frames) = buffer_frames {
+        stream_config.buffer_size = cpal::BufferSize::Fixed(frames);
+    }
+    stream_config
+}
+
+/// Config du device au sample rate demandé, s'il le supporte avec le
+/// même nombre de canaux et le même format ; sinon sa config par défaut,
+/// à son rate natif : le stream s'ouvre quand même, et le pont entre
+/// entrée et sortie (`CallbackResampler`) convertit vers le rate de
+/// la sortie.
+fn config_at_rate(
+    default: SupportedStreamConfig,
+    supported: Option<impl Iterator<Item = SupportedStreamConfigRange>>,
+    hz: u32,
+) -> SupportedStreamConfig {
+    if default.sample_rate().0 == hz {
+        return default;
+    }
+    supported
+        .into_iter()
+        .flatten()
+        .filter(|range| {
+            range.channels() == default.channels()
+                && range.sample_format() == default.sample_format()
+        })
+        .find_map(|range| range.try_with_sample_rate(cpal::SampleRate(hz)))
+        .unwrap_or(default)
+}
+
+/// Config du device avec le nombre de canaux d'un bus mono, au même
+/// sample rate et dans le même format. Un bus stéréo garde la config
+/// telle quelle : le stéréo est recopié sur tous les canaux du device.
+///
+/// Un bus placé (`slot`) demande au moins `slot.end()` canaux : la plus
+/// petite config qui les offre, sinon celle par défaut (le renderer
+/// retombe alors sur la recopie).
+fn config_with_channels(
+    config: SupportedStreamConfig,
+    supported: Option<impl Iterator<Item = SupportedStreamConfigRange>>,
+    count: ChannelCount,
+    slot: Option<OutputSlot>,
+) -> SupportedStreamConfig {
+    if let Some(slot) = slot {
+        if config.channels() as usize >= slot.end() {
+            return config;
+        }
+        return supported
+            .into_iter()
+            .flatten()
+            .filter(|range| {
+                range.channels() as usize >= slot.end()
+                    && range.sample_format() == config.sample_format()
+            })
+            .filter_map(|range| range.try_with_sample_rate(config.sample_rate()))
+            .min_by_key(|c| c.channels())
+            .unwrap_or(config);
+    }
+    if count != ChannelCount::Mono || config.channels() == count.channels() {
+        return config;
+    }
+    supported
+        .into_iter()
+        .flatten()
+        .filter(|range| {
+            range.channels() == count.channels() && range.sample_format() == config.sample_format()
+        })
+        .find_map(|range| range.try_with_sample_rate(config.sample_rate()))
+        .unwrap_or(config)
+}
+
+/// Côté entrée du pipeline : traitement du Mic, état déplacé dans le
+/// callback du device.
+///
+/// # Temps réel
+/// Une fois les buffers à leur taille (premiers blocs), `process_block`
+/// et `send_block` n'allouent plus rien, ne libèrent rien et ne
+/// prennent aucun lock bloquant. Ce qui mérite un log passe par
+/// `RtLogger` (voir `rt_log`), jamais par `tracing`.
+struct InputProcessor {
+    runtime: RuntimeStateReader,
+    dsp: Arc<Mutex<EffectsChain>>,
+    /// Chaîne de rechange reçue, installée dès que la chaîne est à nous
+    effects_swap: Option<Arc<EffectsSwap>>,
+    bus_effects: Arc<Mutex<BusEffects>>,
+    /// Porte de bascule du pipeline : seul le pipeline actif publie
+    /// (niveaux, événements, taps, réseau, écoute) ; les effets tournent
+    /// tant qu'il est entendu, fondu de sortie compris
+    gate: Arc<HandoverGate>,
+    delay_line: DelayLine,
+    /// Polarité et bloqueur de DC du Mic, avant les effets
+    conditioner: InputConditioner,
+    presence: PresenceDetector,
+    global_presence: SignalPresenceConfig,
+    signal_states: Arc<SignalStates>,
+    faults: Arc<ChannelFaults>,
+    /// Le traitement a paniqué hors des effets : silence jusqu'à la
+    /// réouverture du pipeline
+    silenced: bool,
+    event_tx: Sender<Event>,
+    /// Canaux entrelacés du device d'entrée (tous ouverts)
+    input_channels: usize,
+    output_buses: Vec<ChannelId>,
+    output_bus: Option<ChannelId>,
+    bus_fader: Option<BusFader>,
+    /// Autres bus du device, chacun sur sa tranche (vide : le bus joué
+    /// est seul sur le stream)
+    device_buses: Vec<DeviceBus>,
+    /// Bloc stéréo traité, réutilisé d'un callback à l'autre
+    output: Vec<f32>,
+    /// Mic mono après retard, avant volume et pan : la source des
+    /// `device_buses`. Réutilisé d'un callback à l'autre.
+    delayed: Vec<f32>,
+    /// Signal du Mic après effets, avant retard et fader : source du
+    /// retour pré-fader. Réutilisé d'un callback à l'autre.
+    monitor_block: Vec<f32>,
+    /// Écoute de la différence active : copie sèche du bloc avant les
+    /// effets, puis sec − traité, envoyée au retour à la place de
+    /// `monitor_block`. Vide sinon.
+    diff_block: Vec<f32>,
+    /// Sonie du bus joué (si son meter est activé), post-fader
+    loudness: LoudnessMeter,
+    /// Génération de mesure vue en dernier (`None` : meter coupé)
+    loudness_generation: Option<u64>,
+    shared_loudness: Arc<SharedLoudness>,
+    /// Derniers niveaux du Mic et du bus joué, pour le thread de contrôle
+    levels: Arc<SharedLevels>,
+    /// Crête vraie du bus joué (si sa mesure est activée), post-fader
+    true_peak: TruePeakMeter,
+    /// La crête vraie était mesurée au bloc précédent
+    true_peak_on: bool,
+    /// Niveau du bus mesuré, à joindre au prochain `LevelUpdate`
+    bus_level: Option<ChannelLevel>,
+    /// Niveaux construits seulement pour les canaux affichés par l'UI
+    meters: MeterGate,
+    audio_tx: Sender<Vec<f32>>,
+    /// Buffers rendus par la sortie, réutilisés pour les blocs suivants
+    recycle_rx: Receiver<Vec<f32>>,
+    /// Buffer d'un bloc refusé (file pleine), gardé plutôt que libéré ici
+    spare: Option<Vec<f32>>,
+    rt_log: RtLogger,
+    finished_ramps: Arc<AtomicUsize>,
+    /// Sample rate de l'entrée (charge DSP : durée audio d'un bloc)
+    sample_rate: f32,
+    /// Charge DSP et crêtes des bus de la session
+    session: Arc<SessionCounters>,
+    /// Activité des routes (voir `route_meter`), mesurée tous les N blocs
+    route_meter: Arc<RouteMeter>,
+    route_sampler: RouteSampler,
+    /// Flux réseau reçu (mixé au bus joué) et envoyé (le bus joué)
+    network: Arc<Mutex<NetworkPorts>>,
+    /// Bloc stéréo du flux reçu, réutilisé d'un callback à l'autre
+    network_block: Vec<f32>,
+    /// Copies du bus joué pour d'autres consommateurs
+    taps: Arc<Mutex<Taps>>,
+    /// Blocs que `try_lock` sur `taps` a fait perdre (`Taps::miss_counter`)
+    tap_misses: Arc<AtomicU64>,
+    /// Retard de la sortie du bus joué (gauche, droite), après les taps
+    output_delay: [DelayLine; 2],
+    /// Écoute d'un device, mélangée au bus joué s'il est son bus d'écoute
+    preview: Arc<Mutex<Option<PreviewFeed>>>,
+    /// Ce que l'entrée livre, pour la veille de la boucle de contrôle
+    activity: Arc<InputActivity>,
+}
+
+impl InputProcessor {
+    /// `process_block` à l'abri des panics : appelé par le callback.
+    ///
+    /// Un panic hors de la chaîne d'effets (qui isole déjà les siens)
+    /// laisse le traitement dans un état inconnu : le canal est réduit
+    /// au silence pour de bon, mais le pipeline continue de livrer des
+    /// blocs (silencieux) à la sortie, et le moteur tourne.
+    fn process_block_guarded(&mut self, data: &[f32]) -> Option<ChannelLevel> {
+        self.activity
+            .record(data.iter().fold(0.0_f32, |peak, s| peak.max(s.abs())));
+        if !self.silenced {
+            match catch_unwind(AssertUnwindSafe(|| self.process_block(data))) {
+                Ok(level) => return level,
+                Err(payload) => {
+                    self.silenced = true;
+                    // Le lock de la chaîne était peut-être tenu : la
+                    // chaîne elle-même est intacte (ses panics sont
+                    // attrapés plus bas), le moteur doit pouvoir la relire
+                    self.dsp.clear_poison();
+                    self.bus_effects.clear_poison();
+                    Self::report_panic(
+                        &self.faults,
+                        &self.rt_log,
+                        &self.event_tx,
+                        PIPELINE_INPUT_CHANNEL,
+                        None,
+                        panic_message(payload),
+                    );
+                }
+            }
+        }
+        if data.is_empty() {
+            return None;
+        }
+        self.output.clear();
+        self.output
+            .resize(data.len().div_ceil(self.input_channels.max(1)) * 2, 0.0);
+        // Les autres bus du device se taisent aussi (`send_block` comble)
+        for device_bus in &mut self.device_buses {
+            device_bus.block.clear();
+        }
+        if !self.gate.is_active() {
+            return None;
+        }
+        let scale = self.runtime.current().meter_scale;
+        self.meters
+            .level(&scale, PIPELINE_INPUT_CHANNEL, 0.0, 0.0, None, None)
+    }
+
+    /// Signale un panic attrapé : santé du canal, log temps réel et
+    /// événement pour l'UI.
+    ///
+    /// L'événement alloue (nom, message) : le panic a déjà alloué bien
+    /// plus, et il n'arrive qu'une fois par étage ou canal mis à l'écart.
+    /// Champs passés un à un : appelé pendant que le snapshot emprunte
+    /// `self`.
+    fn report_panic(
+        faults: &ChannelFaults,
+        rt_log: &RtLogger,
+        event_tx: &Sender<Event>,
+        channel: ChannelId,
+        effect: Option<&'static str>,
+        message: String,
+    ) {
+        let health = match effect {
+            Some(_) => ChannelHealth::EffectBypassed,
+            None => ChannelHealth::Silenced,
+        };
+        faults.set(channel, health);
+        rt_log.record(RtEvent::ProcessingPanicked { channel, effect });
+        let sent = event_tx.try_send(Event::ProcessingPanicked {
+            channel,
+            effect: effect.map(str::to_string),
+            message,
+        });
+        if sent.is_err() {
+            rt_log.record(RtEvent::EventDropped);
+        }
+    }
+
+    /// Effets du bus joué placés à `point`, sur `self.output`.
+    fn process_bus_effects(
+        effects: &mut Option<(ChannelId, MutexGuard<'_, BusEffects>)>,
+        point: InsertPoint,
+        output: &mut [f32],
+        faults: &ChannelFaults,
+        rt_log: &RtLogger,
+        event_tx: &Sender<Event>,
+    ) {
+        if let Some((bus, effects)) = effects
+            && let Some(panic) = effects.process(point, output)
+        {
+            Self::report_panic(
+                faults,
+                rt_log,
+                event_tx,
+                *bus,
+                Some(panic.name),
+                panic.message,
+            );
+        }
+    }
+
+    /// Traite un bloc entrelacé du device dans `self.output` (stéréo).
+    ///
+    /// Retourne le niveau du canal pour les VU-meters, mesuré après
+    /// fader (et les sous-mix vers le bus joué) et avant le retour
+    /// pré-fader et le volume du bus. `None`
+    /// pour un bloc vide, ou si l'UI n'en veut pas (voir `meter_gate`).
+    fn process_block(&mut self, data: &[f32]) -> Option<ChannelLevel> {
+        if data.is_empty() {
+            self.output.clear();
+            return None;
+        }
+        let started = Instant::now();
+
+        // Lire le dernier snapshot publié (lock-free).
+        // Pas de nouveau snapshot → on garde le précédent.
+        // JAMAIS de blocage dans le callback audio.
+        let (state, swap) = self.runtime.current_with_effects_swap();
+        if swap.is_some() {
+            self.effects_swap = swap;
+        }
+        // Pendant une bascule, seul le pipeline actif publie : l'autre
+        // (pré-roll, fondu de sortie) ne fait que rendre son audio
+        let publishing = self.gate.is_active();
+        // Gain jusqu'au bus joué, sous-mix traversés compris ; sans bus,
+        // le seul fader du canal
+        let (gain_l, gain_r) = match self.output_bus {
+            Some(bus) => state.routed_gain(PIPELINE_INPUT_CHANNEL, bus),
+            None => state.gain(PIPELINE_INPUT_CHANNEL),
+        };
+        let muted = state.all_inputs_muted;
+        let slice = state.device_slice(PIPELINE_INPUT_CHANNEL);
+
+        // Nouveau délai → la ligne gère le crossfade elle-même
+        self.delay_line
+            .set_delay_ms(state.delay_ms(PIPELINE_INPUT_CHANNEL));
+        let (phase_invert, dc_block) = state.input_options(PIPELINE_INPUT_CHANNEL);
+        self.conditioner.set_options(dc_block);
+
+        // Buffers réutilisés : `clear` garde la capacité. Une frame
+        // tronquée en fin de bloc compte : elle est downmixée avec les
+        // canaux présents, comme toutes les autres
+        let frame_count = data.len().div_ceil(self.input_channels);
+        self.output.clear();
+        self.delayed.clear();
+        self.monitor_block.clear();
+        self.diff_block.clear();
+        let diff_monitor = state.effects_diff_monitor(PIPELINE_INPUT_CHANNEL);
+
+        if muted {
+            self.output.resize(frame_count * 2, 0.0);
+            self.delayed.resize(frame_count, 0.0);
+            // Vider la ligne de retard : au unmute, on ne
+            // veut pas réentendre l'audio d'avant le mute.
+            for _ in 0..frame_count {
+                self.delay_line.process_sample(0.0);
+            }
+        } else {
+            // Pipeline audio v0.3 :
+            // 1. Downmix vers mono (des seuls canaux du
+            //    device assignés au Mic, ex: entrées 3-4),
+            //    polarité et bloqueur de DC
+            // 2. DSP chain (gate → compressor → limiter)
+            // 3. Appliquer gain L/R (volume × pan)
+
+            // try_lock sur la chaîne DSP (non-bloquant)
+            // La chaîne (partagée) tourne tant que le pipeline est entendu,
+            // fondu de sortie compris : le fondu croisé porte le signal
+            // traité. Le temps du fondu, les deux pipelines se la
+            // partagent ; en pré-roll, le nouveau passe le signal sec.
+            // Passthrough d'urgence : la chaîne n'est pas touchée du tout
+            let active = self.gate.is_audible() && !state.passthrough();
+            let mut dsp_guard = active.then(|| self.dsp.try_lock().ok()).flatten();
+            match dsp_guard {
+                // Automation : les paramètres avancent d'un bloc
+                Some(ref mut chain) => {
+                    if let Some(swap) = self.effects_swap.take() {
+                        swap.install(chain);
+                    }
+                    chain.begin_block();
+                    let finished = chain.advance_automation(frame_count);
+                    if finished > 0 {
+                        self.finished_ramps.fetch_add(finished, Ordering::Relaxed);
+                    }
+                }
+                None if !active => {}
+                None => self.rt_log.record(RtEvent::EffectsBypassed {
+                    channel: PIPELINE_INPUT_CHANNEL,
+                    frames: frame_count as u32,
+                }),
+            }
+            // 1. Downmix de la tranche vers mono. Énergie avant effets,
+            //    pour la détection de silence : un gate fermé ou un
+            //    expander ne font pas croire à un micro débranché
+            let mut sum_sq = 0.0_f32;
+            for frame in data.chunks(self.input_channels) {
+                let mono = self
+                    .conditioner
+                    .process_sample(slice.downmix(frame), phase_invert);
+                sum_sq += mono * mono;
+                self.monitor_block.push(mono);
+            }
+            if diff_monitor {
+                self.diff_block.extend_from_slice(&self.monitor_block);
+            }
+
+            // 2. DSP processing, un étage qui panique est mis à l'écart
+            if let Some(ref mut chain) = dsp_guard
+                && let Some(panic) = chain.process_block(&mut self.monitor_block)
+            {
+                Self::report_panic(
+                    &self.faults,
+                    &self.rt_log,
+                    &self.event_tx,
+                    PIPELINE_INPUT_CHANNEL,
+                    Some(panic.name),
+                    panic.message,
+                );
+            }
+            drop(dsp_guard);
+            // Chaîne sautée (lock pris, passthrough) : sec = traité, silence
+            for (diff, wet) in self.diff_block.iter_mut().zip(&self.monitor_block) {
+                *diff -= wet;
+            }
+
+            for &processed in &self.monitor_block {
+                // 2b. Retard (lip-sync), avant le routing
+                let mono = self.delay_line.process_sample(processed);
+                self.delayed.push(mono);
+
+                // 3. Appliquer volume + pan
+                self.output.push(mono * gain_l);
+                self.output.push(mono * gain_r);
+            }
+
+            // Présence de signal (un canal muted n'est
+            // pas "silencieux" : on ne le mesure pas)
+            self.presence.set_config(
+                state
+                    .signal_presence(PIPELINE_INPUT_CHANNEL)
+                    .unwrap_or(self.global_presence),
+            );
+            let block_rms = (sum_sq / frame_count.max(1) as f32).sqrt();
+            if let Some(new_state) = self.presence.process_block(block_rms, frame_count)
+                && publishing
+            {
+                self.signal_states.set(PIPELINE_INPUT_CHANNEL, new_state);
+                let sent = self.event_tx.try_send(Event::SignalStateChanged {
+                    channel: PIPELINE_INPUT_CHANNEL,
+                    state: new_state,
+                    timestamp_ms: journal::now_ms(),
+                });
+                if sent.is_err() {
+                    self.rt_log.record(RtEvent::EventDropped);
+                }
+            }
+        }
+
+        // VU-meter : calculer RMS et peak sur le signal traité
+        let (rms, peak) = block_levels(&self.output);
+        if publishing {
+            self.levels.store(PIPELINE_INPUT_CHANNEL, rms, peak);
+        }
+
+        // Activité de la route Mic → bus joué : la crête du canal,
+        // déjà calculée. Mesure coupée → un seul test de booléen.
+        if publishing
+            && self.route_sampler.tick(&self.route_meter)
+            && let Some(bus) = self.output_bus
+        {
+            if state
+                .routes
+                .contains(&Route::new(PIPELINE_INPUT_CHANNEL, bus))
+            {
+                self.route_meter
+                    .record(PIPELINE_INPUT_CHANNEL, bus, peak, journal::now_ms());
+            }
+            self.route_meter.mark_measured();
+        }
+
+        // Second passage : retour pré-fader vers le bus joué
+        // (monitoring direct, absent des meters du canal), ou la
+        // différence sec − traité quand elle est écoutée
+        if let Some(bus) = self.output_bus {
+            let send = state.monitor_gain(PIPELINE_INPUT_CHANNEL, bus);
+            let source = if diff_monitor {
+                &self.diff_block
+            } else {
+                &self.monitor_block
+            };
+            if send > 0.0 {
+                for (frame, mono) in self.output.chunks_exact_mut(2).zip(source) {
+                    frame[0] += mono * send;
+                    frame[1] += mono * send;
+                }
+            }
+        }
+
+        // Flux réseau reçu : une entrée de plus, routée vers le bus joué
+        // comme un canal (volume, pan, mute, route). Lu même coupé, pour
+        // ne pas accumuler de retard ; lu par le seul pipeline actif,
+        // pour ne pas partager ses blocs. `try_lock` : le moteur ne tient
+        // le lock que pour (dé)brancher un flux ou lire ses compteurs.
+        let mut network = self.network.try_lock().ok();
+        let mut received = None;
+        if let Some(bus) = self.output_bus
+            && publishing
+            && let Some(source) = network.as_mut().and_then(|n| n.source.as_mut())
+        {
+            self.network_block.resize(self.output.len(), 0.0);
+            source.read(&mut self.network_block);
+            received = Some(source.channel());
+            let (send_l, send_r) = state.bus_send(source.channel(), bus);
+            if send_l > 0.0 || send_r > 0.0 {
+                for (frame, received) in self
+                    .output
+                    .chunks_exact_mut(2)
+                    .zip(self.network_block.chunks_exact(2))
+                {
+                    frame[0] += received[0] * send_l;
+                    frame[1] += received[1] * send_r;
+                }
+            }
+        }
+
+        // Bus mono : repli L+R une fois tout sommé, avant le fader
+        if let Some(bus) = self.output_bus {
+            fold_to_bus(&mut self.output, state.bus_channel_count(bus));
+        }
+
+        // Effets du bus, de part et d'autre de son fader. Comme la
+        // chaîne du Mic : laissés au pipeline entendu, sautés en
+        // passthrough, et un bloc passe sec si le moteur tient le lock.
+        let mut bus_effects = match self.output_bus {
+            Some(bus) if self.gate.is_audible() && !state.passthrough() => {
+                let guard = self.bus_effects.try_lock().ok();
+                if guard.is_none() {
+                    self.rt_log.record(RtEvent::EffectsBypassed {
+                        channel: bus,
+                        frames: frame_count as u32,
+                    });
+                }
+                guard.map(|effects| (bus, effects))
+            }
+            _ => None,
+        };
+        Self::process_bus_effects(
+            &mut bus_effects,
+            InsertPoint::PreFader,
+            &mut self.output,
+            &self.faults,
+            &self.rt_log,
+            &self.event_tx,
+        );
+
+        // Volume du bus, après les meters (qui montrent le
+        // signal du canal) et avant le dispatch
+        if let (Some(bus), Some(fader)) = (self.output_bus, self.bus_fader.as_mut()) {
+            fader.update(state.bus_gain(bus), state.bus_fade(bus));
+            if fader.process(&mut self.output, 2)
+                && publishing
+                && self
+                    .event_tx
+                    .try_send(Event::BusFadeCompleted { bus })
+                    .is_err()
+            {
+                self.rt_log.record(RtEvent::EventDropped);
+            }
+        }
+        Self::process_bus_effects(
+            &mut bus_effects,
+            InsertPoint::PostFader,
+            &mut self.output,
+            &self.faults,
+            &self.rt_log,
+            &self.event_tx,
+        );
+        drop(bus_effects);
+
+        // Sonie et crête vraie du bus, post-fader. Mesurées ici plutôt
+        // que sur un thread à part : filtres et mémoire fixes, sans
+        // allocation ni lock.
+        let loudness = match self
+            .output_bus
+            .and_then(|bus| Some((bus, state.loudness_meter(bus)?)))
+        {
+            Some((bus, generation)) => {
+                if self.loudness_generation != Some(generation) {
+                    self.loudness.reset();
+                    self.loudness_generation = Some(generation);
+                }
+                self.loudness.process(&self.output);
+                let loudness = self.loudness.loudness();
+                if publishing {
+                    self.shared_loudness.store(bus, generation, loudness);
+                }
+                Some(loudness)
+            }
+            None => {
+                self.loudness_generation = None;
+                None
+            }
+        };
+        let true_peak_on = self
+            .output_bus
+            .is_some_and(|bus| state.true_peak_meter(bus));
+        if true_peak_on && !self.true_peak_on {
+            self.true_peak.reset();
+        }
+        self.true_peak_on = true_peak_on;
+        let true_peak = true_peak_on.then(|| self.true_peak.process(&self.output));
+        if let Some(bus) = self.output_bus
+            && publishing
+        {
+            let (bus_rms, bus_peak) = block_levels(&self.output);
+            self.levels.store(bus, bus_rms, bus_peak);
+            // Crête de la session, post-fader comme les meters du bus
+            self.session.record_bus_peak(bus, bus_peak);
+            if loudness.is_some() || true_peak.is_some() {
+                self.bus_level = self.meters.level(
+                    &state.meter_scale,
+                    bus,
+                    bus_rms,
+                    bus_peak,
+                    loudness,
+                    true_peak,
+                );
+            }
+        }
+
+        // Envoi réseau du bus joué, post-fader comme sa sonie
+        if let Some(bus) = self.output_bus
+            && publishing
+            && let Some(sink) = network.as_mut().and_then(|n| n.sink.as_mut())
+            && sink.bus() == bus
+        {
+            sink.write(&self.output, self.sample_rate as u32);
+        }
+        drop(network);
+
+        // Taps du bus joué, au même point. Un tap plein jette son bloc
+        // le plus ancien : un lecteur lent ne retient jamais le callback.
+        // Lock tenu par le moteur : le bloc perdu est compté, le lecteur
+        // voit le trou.
+        if let Some(bus) = self.output_bus
+            && publishing
+        {
+            match self.taps.try_lock() {
+                Ok(mut taps) => taps.write(bus, &self.output, self.sample_rate as u32),
+                Err(_) => {
+                    self.tap_misses.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        // Retard de sortie (casque sans fil) : seul le device l'entend
+        if let Some(bus) = self.output_bus {
+            let delay_ms = state.output_delay_ms(bus);
+            let [left, right] = &mut self.output_delay;
+            left.set_delay_ms(delay_ms);
+            right.set_delay_ms(delay_ms);
+            for frame in self.output.chunks_exact_mut(2) {
+                frame[0] = left.process_sample(frame[0]);
+                frame[1] = right.process_sample(frame[1]);
+            }
+        }
+
+        // Écoute d'un device : après les meters et les taps, elle n'est
+        // ni mesurée ni enregistrée avec le bus ; après le retard de
+        // sortie, elle s'entend sans attendre. Comme le flux réseau, lue
+        // par le seul pipeline actif.
+        if let Some(bus) = self.output_bus
+            && publishing
+            && let Ok(mut preview) = self.preview.try_lock()
+            && let Some(feed) = preview.as_mut()
+            && feed.bus() == Some(bus)
+        {
+            feed.mix_into(&mut self.output);
+        }
+
+        // Autres bus du device : le Mic et le flux reçu selon leurs
+        // propres routes, chacun pour sa tranche du stream
+        let monitor = if diff_monitor {
+            &self.diff_block
+        } else {
+            &self.monitor_block
+        };
+        let received = received.map(|channel| (channel, &self.network_block[..]));
+        for device_bus in &mut self.device_buses {
+            let bus = device_bus.bus;
+            if device_bus.render(state, &self.delayed, monitor, received)
+                && publishing
+                && self
+                    .event_tx
+                    .try_send(Event::BusFadeCompleted { bus })
+                    .is_err()
+            {
+                self.rt_log.record(RtEvent::EventDropped);
+            }
+            if publishing {
+                let (bus_rms, bus_peak) = block_levels(&device_bus.block);
+                self.levels.store(bus, bus_rms, bus_peak);
+                self.session.record_bus_peak(bus, bus_peak);
+            }
+            if publishing
+                && let Ok(mut preview) = self.preview.try_lock()
+                && let Some(feed) = preview.as_mut()
+                && feed.bus() == Some(bus)
+            {
+                feed.mix_into(&mut device_bus.block);
+            }
+            state.dispatch_to_device(&[bus], &mut device_bus.block);
+        }
+
+        // Dispatch vers le device, APRÈS les meters :
+        // un bus hors solo se tait, ses niveaux restent réels. Seul sur
+        // le stream, le bus joué parle pour tous ceux du device.
+        let played = if self.device_buses.is_empty() {
+            &self.output_buses[..]
+        } else {
+            &self.output_buses[..1]
+        };
+        state.dispatch_to_device(played, &mut self.output);
+
+        if !publishing {
+            return None;
+        }
+        // Charge DSP : temps passé ici / durée audio du bloc
+        let block_seconds = frame_count as f32 / self.sample_rate;
+        self.session
+            .record_load(started.elapsed().as_secs_f32() / block_seconds);
+
+        self.meters.level(
+            &state.meter_scale,
+            PIPELINE_INPUT_CHANNEL,
+            rms,
+            peak,
+            None,
+            None,
+        )
+    }
+
+    /// Niveau et sonie du bus mesuré pendant le dernier bloc.
+    fn take_bus_level(&mut self) -> Option<ChannelLevel> {
+        self.bus_level.take()
+    }
+
+    /// Confie le dernier bloc traité au callback de sortie, dans un
+    /// buffer recyclé (alloué seulement pendant le remplissage initial).
+    fn send_block(&mut self) {
+        let mut block = self
+            .spare
+            .take()
+            .or_else(|| self.recycle_rx.try_recv().ok())
+            .unwrap_or_default();
+        block.clear();
+        if self.device_buses.is_empty() {
+            block.extend_from_slice(&self.output);
+        } else {
+            // Une voie stéréo par bus et par frame, le bus joué d'abord
+            for (i, lr) in self.output.chunks_exact(2).enumerate() {
+                block.extend_from_slice(lr);
+                for device_bus in &self.device_buses {
+                    block.extend_from_slice(
+                        device_bus.block.get(2 * i..2 * i + 2).unwrap_or(&[0.0; 2]),
+                    );
+                }
+            }
+        }
+        if let Err(e) = self.audio_tx.try_send(block) {
+            self.rt_log.record(RtEvent::AudioBlockDropped {
+                frames: (self.output.len() / 2) as u32,
+            });
+            self.spare = Some(e.into_inner());
+        }
+    }
+}
+
+/// Autre bus d'un device de sortie partagé, rendu par le pipeline à côté
+/// du bus joué pour sa propre tranche (`channel_map::device_lanes`).
+///
+/// Il reçoit le Mic et le flux réseau selon ses routes, son repli mono,
+/// son volume et son retard de sortie. Les effets de bus, la sonie, les
+/// taps et l'envoi réseau restent ceux du seul bus joué.
+struct DeviceBus {
+    bus: ChannelId,
+    fader: BusFader,
+    /// Retard de sortie (gauche, droite), alloué à l'ouverture
+    delay: [DelayLine; 2],
+    /// Bloc stéréo rendu, réutilisé d'un callback à l'autre
+    block: Vec<f32>,
+}
+
+impl DeviceBus {
+    fn new(bus: ChannelId, state: &MixerRuntimeState, sample_rate: f32) -> Self {
+        let delay_ms = state.output_delay_ms(bus);
+        Self {
+            bus,
+            fader: BusFader::new(state.bus_gain(bus), state.bus_fade(bus), sample_rate),
+            delay: [(); 2].map(|()| DelayLine::with_delay_ms(sample_rate, delay_ms)),
+            block: Vec::new(),
+        }
+    }
+
+    /// Rend le bloc du bus à partir du Mic retardé (`mic`), du retour
+    /// pré-fader (`monitor`) et du flux reçu. `true` : un fondu du bus
+    /// vient de finir.
+    fn render(
+        &mut self,
+        state: &MixerRuntimeState,
+        mic: &[f32],
+        monitor: &[f32],
+        received: Option<(ChannelId, &[f32])>,
+    ) -> bool {
+        let bus = self.bus;
+        let (gain_l, gain_r) = state.routed_gain(PIPELINE_INPUT_CHANNEL, bus);
+        self.block.clear();
+        for &mono in mic {
+            self.block.push(mono * gain_l);
+            self.block.push(mono * gain_r);
+        }
+        let send = state.monitor_gain(PIPELINE_INPUT_CHANNEL, bus);
+        if send > 0.0 {
+            for (frame, mono) in self.block.chunks_exact_mut(2).zip(monitor) {
+                frame[0] += mono * send;
+                frame[1] += mono * send;
+            }
+        }
+        if let Some((channel, received)) = received {
+            let (send_l, send_r) = state.bus_send(channel, bus);
+            if send_l > 0.0 || send_r > 0.0 {
+                for (frame, received) in
+                    self.block.chunks_exact_mut(2).zip(received.chunks_exact(2))
+                {
+                    frame[0] += received[0] * send_l;
+                    frame[1] += received[1] * send_r;
+                }
+            }
+        }
+        fold_to_bus(&mut self.block, state.bus_channel_count(bus));
+        self.fader.update(state.bus_gain(bus), state.bus_fade(bus));
+        let faded = self.fader.process(&mut self.block, 2);
+        let delay_ms = state.output_delay_ms(bus);
+        let [left, right] = &mut self.delay;
+        left.set_delay_ms(delay_ms);
+        right.set_delay_ms(delay_ms);
+        for frame in self.block.chunks_exact_mut(2) {
+            frame[0] = left.process_sample(frame[0]);
+            frame[1] = right.process_sample(frame[1]);
+        }
+        faded
+    }
+}
+
+/// Côté sortie du pipeline : état déplacé dans le callback du device.
+///
+/// Séparé du callback pour servir les devices f32 comme i16 (ces
+/// derniers reçoivent ensuite la conversion avec dither).
+struct OutputRenderer {
+    audio_rx: Receiver<Vec<f32>>,
+    /// Buffers vidés, rendus à l'entrée pour être réutilisés
+    recycle_tx: Sender<Vec<f32>>,
+    /// Pont input → output : accumule, convertit le sample rate si les
+    /// deux devices diffèrent, et livre exactement ce que le device demande.
+    bridge: CallbackResampler,
+    out_channels: usize,
+    /// Tranche de chaque voie du bloc dans les canaux du device (vide :
+    /// le stéréo du bus joué recopié sur toutes les paires)
+    slots: Vec<OutputSlot>,
+    /// Voies stéréo par frame du bloc : le bus joué, puis les autres bus
+    /// placés sur le device (`InputProcessor::device_buses`)
+    lanes: usize,
+    /// Buffer entrelacé réutilisé d'un callback à l'autre
+    /// (ne réalloue que si le device demande plus que d'habitude).
+    block: Vec<f32>,
+    /// Underruns : le device demande plus que ce que le pont a en
+    /// réserve. La constitution de la réserve (démarrage, reprise après
+    /// un underrun) n'en est pas un.
+    underruns: Arc<AtomicU64>,
+    /// Garde la réserve du pont autour de la cible malgré la dérive
+    /// entre les horloges d'entrée et de sortie.
+    governor: LatencyGovernor,
+    rt_log: RtLogger,
+    /// Gain de bascule entre pipelines (pré-roll, fondu croisé)
+    handover: HandoverFader,
+    /// Bus joué, pour signaler un panic du rendu
+    bus: Option<ChannelId>,
+    faults: Arc<ChannelFaults>,
+    event_tx: Sender<Event>,
+    /// Le rendu a paniqué : le device ne reçoit plus que du silence
+    failed: bool,
+}
+
+impl OutputRenderer {
+    /// `render` à l'abri des panics : appelé par le callback de sortie.
+    ///
+    /// Comme pour l'entrée (`process_block_guarded`), un panic laisse
+    /// le rendu dans un état inconnu : le device reçoit du silence
+    /// jusqu'à la réouverture du pipeline, le moteur tourne.
+    fn render_guarded(&mut self, output: &mut [f32]) {
+        if !self.failed {
+            match catch_unwind(AssertUnwindSafe(|| self.render(output))) {
+                Ok(()) => return,
+                Err(payload) => {
+                    self.failed = true;
+                    let message = panic_message(payload);
+                    match self.bus {
+                        Some(bus) => InputProcessor::report_panic(
+                            &self.faults,
+                            &self.rt_log,
+                            &self.event_tx,
+                            bus,
+                            None,
+                            message,
+                        ),
+                        None => self.rt_log.record(RtEvent::OutputPanicked),
+                    }
+                }
+            }
+        }
+        output.fill(0.0);
+    }
+
+    fn render(&mut self, output: &mut [f32]) {
+        // Vider tout ce que l'input a produit depuis le dernier appel.
+        // Une erreur de resampling = un chunk perdu, pas un crash.
+        while let Ok(lanes_data) = self.audio_rx.try_recv() {
+            let _ = self.bridge.push(&lanes_data);
+            let _ = self.recycle_tx.try_send(lanes_data);
+        }
+
+        let out_channels = self.out_channels;
+        let out_frames = output.len() / out_channels;
+        let width = 2 * self.lanes;
+        self.block.resize(out_frames * width, 0.0);
+        let mut delivered = false;
+        if self.governor.before_fill(&mut self.bridge, out_frames) {
+            // `fill` borne à [-1, 1] et met à zéro ce qui manque
+            let filled = self.bridge.fill(&mut self.block);
+            delivered = filled == self.block.len();
+            if !delivered {
+                self.governor.underrun();
+                self.underruns.fetch_add(1, Ordering::Relaxed);
+                self.rt_log.record(RtEvent::OutputUnderrun {
+                    missing_frames: ((self.block.len() - filled) / width) as u32,
+                });
+            }
+        } else {
+            // Réserve en cours de constitution
+            self.block.fill(0.0);
+        }
+        self.handover.apply(&mut self.block, width, delivered);
+
+        // Mapper les voies stéréo vers N canaux de sortie
+        if self.slots.is_empty() {
+            spread_lane(&self.block, 0, self.lanes, output, out_channels);
+        } else {
+            output.fill(0.0);
+            for (lane, slot) in self.slots.iter().enumerate() {
+                slot.write_lane(&self.block, lane, self.lanes, output, out_channels);
+            }
+        }
+        // Samples orphelins (buffer non multiple du nb de canaux)
+        let written = out_frames * out_channels;
+        output[written..].fill(0.0);
+    }
+}
+
+/// Callback du stream d'une écoute : downmixe le device en mono, le
+/// baisse à `PREVIEW_LEVEL_DB`, le confie au pipeline (`PreviewFeed`)
+/// et envoie ses niveaux selon l'abonnement des meters.
+struct PreviewCallback {
+    feed: Arc<Mutex<Option<PreviewFeed>>>,
+    event_tx: Sender<Event>,
+    meters: MeterGate,
+    scale: MeterScale,
+    /// Canaux entrelacés du device
+    channels: usize,
+    /// `PREVIEW_LEVEL_DB`, linéaire
+    gain: f32,
+    mono: Vec<f32>,
+    /// Rate du device → rate du mixer (passthrough s'ils sont égaux)
+    bridge: CallbackResampler,
+    /// Mono converti, prêt pour la file
+    resampled: Vec<f32>,
+}
+
+impl PreviewCallback {
+    fn new(
+        feed: Arc<Mutex<Option<PreviewFeed>>>,
+        event_tx: Sender<Event>,
+        meters: MeterGate,
+        scale: MeterScale,
+        channels: usize,
+        bridge: CallbackResampler,
+    ) -> Self {
+        Self {
+            feed,
+            event_tx,
+            meters,
+            scale,
+            channels: channels.max(1),
+            gain: VolumeDecibels(PREVIEW_LEVEL_DB).to_linear(),
+            mono: Vec::new(),
+            resampled: Vec::with_capacity(bridge.capacity_frames()),
+            bridge,
+        }
+    }
+
+    fn process(&mut self, data: &[f32]) {
+        let gain = self.gain;
+        self.mono.clear();
+        self.mono.extend(
+            data.chunks(self.channels)
+                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32 * gain),
+        );
+        if self.bridge.push(&self.mono).is_ok() {
+            self.resampled.resize(self.bridge.available(), 0.0);
+            let written = self.bridge.fill(&mut self.resampled);
+            self.resampled.truncate(written);
+        }
+        if let Ok(mut feed) = self.feed.try_lock()
+            && let Some(feed) = feed.as_mut()
+        {
+            feed.write(self.resampled.iter().copied());
+        }
+        let (rms, peak) = block_levels(&self.mono);
+        if let Some(level) = self
+            .meters
+            .level(&self.scale, PREVIEW_CHANNEL, rms, peak, None, None)
+        {
+            self.meters.subscription().record_emission(1);
+            let _ = self.event_tx.try_send(Event::LevelUpdate(vec![level]));
+        }
+    }
+}
+
+/// Handle d'un stream du pipeline : un stream cpal, ou l'entrée cadencée
+/// qui le remplace quand le device de l'entrée manque (voir `fallback`).
+enum PipelineStream {
+    Device(Stream),
+    Clocked(ClockedInput),
+    #[cfg(test)]
+    Fake(tests::FakeStream),
+}
+
+impl PipelineStream {
+    /// `device` : nom du device, pour l'erreur ; `is_input` : sens du
+    /// stream, pour son conseil.
+    fn play(&mut self, device: &str, is_input: bool) -> TroubadourResult<()> {
+        match self {
+            Self::Device(stream) => stream
+                .play()
+                .map_err(|e| play_stream_error(device, is_input, &e)),
+            Self::Clocked(input) => input.play(),
+            #[cfg(test)]
+            Self::Fake(_) => Ok(()),
+        }
+    }
+}
+
+/// Stream du pipeline construit, pas encore démarré.
+struct OpenedStream {
+    info: ActiveStream,
+    stream: PipelineStream,
+    /// Battu par son callback (watchdog)
+    beat: Heartbeat,
+}
+
+impl OpenedStream {
+    fn play(&mut self) -> TroubadourResult<()> {
+        self.stream.play(&self.info.device_name, self.info.is_input)
+    }
+
+    /// Entrée de `Watchdog::watch` pour ce stream.
+    fn watched(&self) -> (String, bool, Heartbeat) {
+        (
+            self.info.device_name.clone(),
+            self.info.is_input,
+            self.beat.clone(),
+        )
+    }
+}
+
+/// File entre l'entrée et la sortie d'un pipeline. Gardée par le
+/// moteur : l'un des deux streams peut être rouvert sans l'autre.
+#[derive(Clone)]
+struct PipelineLink {
+    audio_tx: Sender<Vec<f32>>,
+    audio_rx: Receiver<Vec<f32>>,
+    recycle_tx: Sender<Vec<f32>>,
+    recycle_rx: Receiver<Vec<f32>>,
+}
+
+impl PipelineLink {
+    fn new() -> Self {
+        let (audio_tx, audio_rx) = crossbeam_channel::bounded::<Vec<f32>>(AUDIO_QUEUE);
+        let (recycle_tx, recycle_rx) = crossbeam_channel::bounded::<Vec<f32>>(AUDIO_QUEUE);
+        Self {
+            audio_tx,
+            audio_rx,
+            recycle_tx,
+            recycle_rx,
+        }
+    }
+}
+
+/// Device ouvert par le pipeline, dans un sens.
+enum PipelineDevice {
+    Cpal {
+        device: cpal::Device,
+        is_input: bool,
+    },
+    #[cfg(test)]
+    Fake(tests::FakeDevice),
+}
+
+impl PipelineDevice {
+    fn default_config(&self) -> TroubadourResult<SupportedStreamConfig> {
+        let config = match self {
+            Self::Cpal {
+                device,
+                is_input: true,
+            } => device.default_input_config(),
+            Self::Cpal { device, .. } => device.default_output_config(),
+            #[cfg(test)]
+            Self::Fake(fake) => return Ok(fake.config()),
+        };
+        config.map_err(|e| TroubadourError::StreamError(e.to_string()))
+    }
+
+    /// Configs proposées (`None` : le device ne sait pas les lister).
+    fn supported_configs(&self) -> Option<std::vec::IntoIter<SupportedStreamConfigRange>> {
+        let configs: Vec<_> = match self {
+            Self::Cpal {
+                device,
+                is_input: true,
+            } => device.supported_input_configs().ok()?.collect(),
+            Self::Cpal { device, .. } => device.supported_output_configs().ok()?.collect(),
+            #[cfg(test)]
+            Self::Fake(_) => return None,
+        };
+        Some(configs.into_iter())
+    }
+
+    fn build_input(
+        &self,
+        config: &cpal::StreamConfig,
+        mut on_block: impl FnMut(&[f32]) + Send + 'static,
+    ) -> Result<PipelineStream, cpal::BuildStreamError> {
+        match self {
+            Self::Cpal { device, .. } => device
+                .build_input_stream(
+                    config,
+                    move |data: &[f32], _: &cpal::InputCallbackInfo| on_block(data),
+                    move |err| error!("Input stream error: {err}"),
+                    None,
+                )
+                .map(PipelineStream::Device),
+            #[cfg(test)]
+            Self::Fake(fake) => Ok(PipelineStream::Fake(fake.input_stream(on_block))),
+        }
+    }
+
+    fn build_output<T: cpal::SizedSample + 'static>(
+        &self,
+        config: &cpal::StreamConfig,
+        mut render: impl FnMut(&mut [T]) + Send + 'static,
+    ) -> Result<PipelineStream, cpal::BuildStreamError> {
+        match self {
+            Self::Cpal { device, .. } => device
+                .build_output_stream(
+                    config,
+                    move |output: &mut [T], _: &cpal::OutputCallbackInfo| render(output),
+                    move |err| error!("Output stream error: {err}"),
+                    None,
+                )
+                .map(PipelineStream::Device),
+            #[cfg(test)]
+            Self::Fake(fake) => Ok(PipelineStream::Fake(fake.output_stream(render))),
+        }
+    }
+}
+
+/// Ce qui alimente l'entrée du pipeline, avant l'ouverture du stream.
+enum InputFeed {
+    Device(PipelineDevice, SupportedStreamConfig),
+    /// Audio rejoué à son sample rate
+    Looped(LoopedAudio, u32),
+}
+
+/// Repli en cours sur l'entrée du pipeline.
+#[derive(Debug, Clone, PartialEq)]
+struct ActiveFallback {
+    /// Device remplacé (`None` : le défaut du système, absent)
+    primary: Option<String>,
+    source: InputSource,
+}
+
+/// Pipeline remplacé, gardé ouvert le temps de la bascule.
+struct PendingHandover {
+    old_streams: StreamSet<PipelineStream>,
+    old_gate: Arc<HandoverGate>,
+    new_gate: Arc<HandoverGate>,
+    started_ms: u64,
+    /// Moment où le nouveau pipeline est devenu audible
+    switched_ms: Option<u64>,
+}
+
+/// RMS et peak d'un bloc (pour les VU-meters).
+fn block_levels(block: &[f32]) -> (f32, f32) {
+    let rms = (block.iter().map(|&s| s * s).sum::<f32>() / block.len().max(1) as f32).sqrt();
+    let peak = block.iter().map(|s| s.abs()).fold(0.0_f32, f32::max);
+    (rms, peak)
+}
+
+/// Tranches des bus d'un stream de sortie (`channel_map::device_lanes`),
+/// `buses` venant de `stream_channels`.
+fn bus_lanes(mixer: &Mixer, buses: &[ChannelId]) -> Vec<(ChannelId, OutputSlot)> {
+    let configs: Vec<&ChannelConfig> = buses.iter().filter_map(|&id| mixer.channel(id)).collect();
+    device_lanes(&configs)
+}
+
+/// Canaux du mixer alimentés par (ou alimentant) un stream ouvert.
+///
+/// Les canaux dont `device_name` correspond au device, par id (le bus
+/// joué est le premier) ; à défaut, le canal d'entrée du pipeline ou la
+/// première sortie.
+fn stream_channels(
+    mixer: &Mixer,
+    device_name: &str,
+    is_input: bool,
+    defaults: &SystemDefaults,
+) -> Vec<ChannelId> {
+    let candidates = if is_input {
+        mixer.inputs()
+    } else {
+        mixer.outputs()
+    };
+    let mut bound: Vec<ChannelId> = candidates
+        .iter()
+        .filter(|c| defaults.resolve(&c.device_name, is_input) == Some(device_name))
+        .map(|c| c.id)
+        .collect();
+    bound.sort_by_key(|id| id.0);
+    if bound.is_empty() {
+        let fallback = if is_input {
+            mixer.channel(PIPELINE_INPUT_CHANNEL).map(|c| c.id)
+        } else {
+            mixer.outputs().iter().map(|c| c.id).min_by_key(|id| id.0)
+        };
+        bound.extend(fallback);
+    }
+    bound
+}
+
+/// Ajoute les streams ouverts au graphe et les relie aux canaux qu'ils servent.
+///
+/// Un stream est relié aux canaux dont `device_name` correspond.
+/// Sinon, il est relié au canal que le pipeline alimente réellement :
+/// le Mic pour l'entrée, la première sortie pour la lecture.
+fn merge_active_streams(
+    graph: &mut SignalGraph,
+    mixer: &Mixer,
+    streams: &[ActiveStream],
+    defaults: &SystemDefaults,
+) {
+    for stream in streams {
+        let kind = if stream.is_input {
+            NodeKind::InputDevice
+        } else {
+            NodeKind::OutputDevice
+        };
+        let device_id = mixer::device_node_id(kind, &stream.device_name);
+        let label = format!(
+            "{} ({} ch @ {} Hz)",
+            stream.device_name, stream.channels, stream.sample_rate
+        );
+        let node = graph.upsert_node(GraphNode::device(&device_id, kind, &stream.device_name));
+        node.label = label;
+        node.active = Some(true);
+
+        for id in stream_channels(mixer, &stream.device_name, stream.is_input, defaults) {
+            let muted = mixer.channel(id).is_none_or(|c| c.muted);
+            let channel_id = mixer::channel_node_id(id);
+            let (from, to) = if stream.is_input {
+                (device_id.clone(), channel_id)
+            } else {
+                (channel_id, device_id.clone())
+            };
+            graph.add_edge(GraphEdge {
+                from,
+                to,
+                gain: 1.0,
+                enabled: !muted,
+            });
+        }
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel_map::ChannelSlice;
+    use crate::command_queue::ENGINE_QUEUE_CAPACITY;
+    use crate::dsp::delay::MAX_OUTPUT_DELAY_MS;
+    use crate::route_meter::ROUTE_METER_INTERVAL;
+    use std::f32::consts::FRAC_1_SQRT_2;
+    use std::time::UNIX_EPOCH;
+    use troubadour_shared::action::{Action, TimeOfDay};
+    use troubadour_shared::dsp::EqBandConfig;
+    use troubadour_shared::error::StreamErrorKind;
+    use troubadour_shared::graph::GraphFormat;
+    use troubadour_shared::machine::ChannelDevice;
+    use troubadour_shared::mixer::{ChannelLink, ChannelQuery, CrossfadeCurve};
+
+    use crate::alloc_count::count_allocations;
+
+    /// Pipeline d'entrée hors device, avec la sortie simulée : chaque
+    /// bloc envoyé est aussitôt consommé et rendu pour recyclage.
+    struct InputHarness {
+        processor: InputProcessor,
+        audio_rx: Receiver<Vec<f32>>,
+        recycle_tx: Sender<Vec<f32>>,
+        block: Vec<f32>,
+    }
+
+    impl InputHarness {
+        fn new(engine: &Engine) -> Self {
+            let (audio_tx, audio_rx) = crossbeam_channel::bounded(AUDIO_QUEUE);
+            let (recycle_tx, recycle_rx) = crossbeam_channel::bounded(AUDIO_QUEUE);
+            // 256 frames stéréo de sinus
+            let block = (0..512).map(|n| 0.3 * (n as f32 * 0.05).sin()).collect();
+            Self {
+                processor: engine.input_processor(2, 48_000.0, "", audio_tx, recycle_rx),
+                audio_rx,
+                recycle_tx,
+                block,
+            }
+        }
+
+        fn run_block(&mut self) {
+            self.processor.process_block_guarded(&self.block);
+            self.processor.send_block();
+            if let Ok(done) = self.audio_rx.try_recv() {
+                let _ = self.recycle_tx.try_send(done);
+            }
+        }
+    }
+
+    /// Devices simulés (`Engine::fake_devices`) : le pipeline les ouvre
+    /// comme des devices cpal, le test appelle leurs callbacks à la main.
+    #[derive(Default)]
+    pub(super) struct FakeDevices {
+        /// Streams ouverts et fermés, dans l'ordre ("open out Card")
+        log: Arc<Mutex<Vec<String>>>,
+        /// Sample rate d'un device (48 kHz sinon)
+        rates: HashMap<String, u32>,
+        /// Devices débranchés : introuvables
+        unplugged: Vec<String>,
+    }
+
+    impl FakeDevices {
+        pub(super) fn device(&self, name: &str, is_input: bool) -> TroubadourResult<FakeDevice> {
+            if self.unplugged.iter().any(|d| d == name) {
+                return Err(TroubadourError::DeviceNotFound(name.to_string()));
+            }
+            let direction = if is_input { "in" } else { "out" };
+            Ok(FakeDevice {
+                label: format!("{direction} {name}"),
+                rate: self.rates.get(name).copied().unwrap_or(48_000),
+                log: self.log.clone(),
+            })
+        }
+    }
+
+    pub(super) struct FakeDevice {
+        label: String,
+        rate: u32,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl FakeDevice {
+        pub(super) fn config(&self) -> SupportedStreamConfig {
+            SupportedStreamConfig::new(
+                2,
+                cpal::SampleRate(self.rate),
+                cpal::SupportedBufferSize::Unknown,
+                SampleFormat::F32,
+            )
+        }
+
+        pub(super) fn input_stream(
+            &self,
+            on_block: impl FnMut(&[f32]) + Send + 'static,
+        ) -> FakeStream {
+            self.open(FakeCallback::Input(Box::new(on_block)))
+        }
+
+        pub(super) fn output_stream<T: 'static>(
+            &self,
+            render: impl FnMut(&mut [T]) + Send + 'static,
+        ) -> FakeStream {
+            let render: FakeRender<T> = Box::new(render);
+            self.open(FakeCallback::Output(Box::new(render)))
+        }
+
+        fn open(&self, callback: FakeCallback) -> FakeStream {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("open {}", self.label));
+            FakeStream {
+                label: self.label.clone(),
+                log: self.log.clone(),
+                callback: Mutex::new(callback),
+            }
+        }
+    }
+
+    type FakeBlock = Box<dyn FnMut(&[f32]) + Send>;
+    type FakeRender<T> = Box<dyn FnMut(&mut [T]) + Send>;
+
+    enum FakeCallback {
+        Input(FakeBlock),
+        /// `FakeRender` du format de sortie
+        Output(Box<dyn std::any::Any + Send>),
+    }
+
+    /// Stream d'un device simulé ; sa fermeture est notée dans le journal.
+    pub(super) struct FakeStream {
+        label: String,
+        log: Arc<Mutex<Vec<String>>>,
+        callback: Mutex<FakeCallback>,
+    }
+
+    impl FakeStream {
+        /// Appelle le callback d'entrée avec `data`.
+        fn feed(&self, data: &[f32]) {
+            if let FakeCallback::Input(on_block) = &mut *self.callback.lock().unwrap() {
+                on_block(data);
+            }
+        }
+
+        /// Appelle le callback de sortie (f32) sur `output`.
+        fn render(&self, output: &mut [f32]) {
+            if let FakeCallback::Output(render) = &mut *self.callback.lock().unwrap()
+                && let Some(render) = render.downcast_mut::<FakeRender<f32>>()
+            {
+                render(output);
+            }
+        }
+    }
+
+    impl Drop for FakeStream {
+        fn drop(&mut self) {
+            if let Ok(mut log) = self.log.lock() {
+                log.push(format!("close {}", self.label));
+            }
+        }
+    }
+
+    /// Moteur sur devices simulés, démarré sur `input` → `output` comme
+    /// par `start` ; retourne le journal des streams.
+    fn start_on_fake_devices(
+        engine: &mut Engine,
+        input: &str,
+        output: &str,
+    ) -> Arc<Mutex<Vec<String>>> {
+        let devices = FakeDevices::default();
+        let log = devices.log.clone();
+        engine.fake_devices = Some(devices);
+        engine
+            .start_audio_pipeline(
+                &InputSource::Device(input.into()),
+                output,
+                HandoverGate::active(),
+            )
+            .unwrap();
+        engine.state = EngineState::Running;
+        log.lock().unwrap().clear();
+        log
+    }
+
+    /// Stream simulé ouvert sur `device` parmi `streams`.
+    fn fake_stream<'a>(
+        streams: &'a StreamSet<PipelineStream>,
+        device: &str,
+        is_input: bool,
+    ) -> &'a FakeStream {
+        match streams.handle(&StreamKey::new(device, is_input)) {
+            Some(PipelineStream::Fake(stream)) => stream,
+            _ => panic!("no fake stream on {device}"),
+        }
+    }
+
+    /// Une période des streams simulés d'un pipeline : l'entrée "Mic" lit
+    /// un bloc, la sortie sur `output` en rend un. Retourne la crête rendue.
+    fn run_fake_period(streams: &StreamSet<PipelineStream>, output: &str) -> f32 {
+        let block: Vec<f32> = (0..512).map(|n| 0.3 * (n as f32 * 0.05).sin()).collect();
+        fake_stream(streams, "Mic", true).feed(&block);
+        let mut rendered = vec![0.0; 512];
+        fake_stream(streams, output, false).render(&mut rendered);
+        rendered.iter().fold(0.0, |peak, s| peak.max(s.abs()))
+    }
+
+    /// Niveaux envoyés à l'UI depuis le dernier appel.
+    fn level_updates(channels: &EngineChannels) -> usize {
+        channels
+            .event_rx
+            .try_iter()
+            .filter(|e| matches!(e, Event::LevelUpdate(_) | Event::MeterFrame(_)))
+            .count()
+    }
+
+    /// Fait comme si un pipeline démarré jouait `bus`.
+    fn play_bus(engine: &mut Engine, bus: ChannelId) {
+        engine.state = EngineState::Running;
+        drop(engine.new_meter_layout(Some(bus)));
+    }
+
+    #[test]
+    fn input_options_are_stored_and_refused_on_buses() {
+        let (mut engine, channels) = Engine::new();
+        for channel in [ChannelId(0), ChannelId(3)] {
+            channels
+                .command_tx
+                .send(Command::SetChannelInputOptions {
+                    channel,
+                    phase_invert: true,
+                    dc_block: true,
+                })
+                .unwrap();
+        }
+        engine.process_commands();
+
+        let mic = engine.mixer().channel(ChannelId(0)).unwrap();
+        assert!(mic.phase_invert && mic.dc_block);
+        assert_eq!(
+            engine.shared_state.snapshot().input_options(ChannelId(0)),
+            (true, true)
+        );
+        assert!(!engine.mixer().channel(ChannelId(3)).unwrap().phase_invert);
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("input options")))
+        );
+        assert_eq!(
+            engine.journal().recent(1)[0].action,
+            "SetChannelInputOptions"
+        );
+
+        // Le pipeline les applique : le Mic sort inversé (chaque rendu
+        // sur un moteur neuf, les effets gardent un état d'un bloc à l'autre)
+        let render = |phase_invert: bool| {
+            let (mut engine, _channels) = Engine::new();
+            engine
+                .mixer
+                .set_input_options(ChannelId(0), phase_invert, false)
+                .unwrap();
+            engine.shared_state.update_from_mixer(&engine.mixer);
+            let mut harness = InputHarness::new(&engine);
+            harness.run_block();
+            harness.processor.output.clone()
+        };
+        let (inverted, upright) = (render(true), render(false));
+        assert!(inverted.iter().any(|&s| s != 0.0));
+        for (a, b) in inverted.iter().zip(&upright) {
+            assert!((a + b).abs() < 1e-6, "{a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn warmed_up_input_path_does_not_allocate() {
+        let (mut engine, _channels) = Engine::new();
+        // Chemin complet : effets, retard, retour pré-fader, fondu de bus,
+        // mesure de sonie
+        engine.mixer.set_delay(ChannelId(0), 20.0);
+        engine.mixer.set_loudness_meter(ChannelId(3), true).unwrap();
+        engine
+            .mixer
+            .set_monitor(ChannelId(0), Some(ChannelId(3)), -6.0);
+        engine
+            .mixer
+            .set_effects_diff_monitor(ChannelId(0), true)
+            .unwrap();
+        engine
+            .mixer
+            .fade_bus_volume(ChannelId(3), VolumeDecibels(-12.0), 50.0);
+        engine.shared_state.update_from_mixer(&engine.mixer);
+
+        // Le compteur voit bien les allocations de ce thread
+        assert!(count_allocations(|| drop(std::hint::black_box(vec![0_u8; 64]))) > 0);
+
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..8 {
+            harness.run_block();
+        }
+
+        let allocations = count_allocations(|| {
+            for _ in 0..200 {
+                harness.run_block();
+            }
+        });
+        assert_eq!(allocations, 0);
+    }
+
+    #[test]
+    fn commands_sent_between_blocks_apply_to_the_next_block() {
+        let (mut engine, channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        harness.run_block();
+        assert!(harness.processor.output.iter().any(|&s| s != 0.0));
+
+        // Le fader descend pendant que le bloc précédent est joué
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: ChannelId(0),
+                level: 0.0,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.audio_stats().command_queue_depth, 1);
+
+        harness.run_block();
+        assert!(harness.processor.output.iter().all(|&s| s == 0.0));
+        assert_eq!(engine.audio_stats().command_queue_depth, 0);
+    }
+
+    #[test]
+    fn meters_follow_the_subscription_and_hold_peaks_through_a_pause() {
+        let (mut engine, channels) = Engine::new();
+        *engine.dsp_chain.lock().unwrap() = EffectsChain::new();
+        let mut harness = InputHarness::new(&engine);
+        channels
+            .command_tx
+            .send(Command::SetMeterSubscription {
+                targets: vec![ChannelId(3)],
+                active: true,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(harness.processor.process_block(&harness.block).is_none());
+        assert_eq!(harness.processor.meters.computed(), 0);
+        assert_eq!(engine.audio_stats().meter_subscription, Some(1));
+
+        engine.set_meter_subscription(&[PIPELINE_INPUT_CHANNEL], true);
+        channels
+            .command_tx
+            .send(Command::SetMetersPaused { paused: true })
+            .unwrap();
+        engine.process_commands();
+        assert!(engine.audio_stats().meters_paused);
+        // Un pic pendant la pause, puis un signal plus faible
+        harness.block.fill(0.1);
+        harness.block[0] = 0.9;
+        assert!(harness.processor.process_block(&harness.block).is_none());
+        harness.block[0] = 0.1;
+        assert!(harness.processor.process_block(&harness.block).is_none());
+        assert_eq!(harness.processor.meters.computed(), 0);
+
+        engine.set_meters_paused(false);
+        let held = harness.processor.process_block(&harness.block).unwrap();
+        let level = harness.processor.process_block(&harness.block).unwrap();
+        assert!(held.peak > 3.0 * level.peak);
+        assert_eq!(harness.processor.meters.computed(), 2);
+    }
+
+    #[test]
+    fn meter_scale_change_applies_to_the_next_level_update() {
+        let (mut engine, channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        let level = harness.processor.process_block(&harness.block).unwrap();
+        let default = MeterScale::default();
+        assert_eq!(level.peak_position, default.position(level.peak));
+
+        channels
+            .command_tx
+            .send(Command::SetMeterScale {
+                min_db: Some(-24.0),
+                warn_db: Some(-9.0),
+            })
+            .unwrap();
+        engine.process_commands();
+        let level = harness.processor.process_block(&harness.block).unwrap();
+        let short = MeterScale::new(Some(-24.0), Some(-9.0));
+        assert_eq!(level.peak_position, short.position(level.peak));
+        assert!(level.peak_position < default.position(level.peak));
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::MeterScale { scale, ticks } if scale == short && ticks[0] == -24.0
+        )));
+    }
+
+    #[test]
+    fn full_command_queue_refuses_changes_instead_of_blocking() {
+        let (mut engine, channels) = Engine::new();
+        // Un pipeline ouvert dont le callback ne tourne pas
+        let mut harness = InputHarness::new(&engine);
+        for i in 0..=ENGINE_QUEUE_CAPACITY {
+            channels
+                .command_tx
+                .send(Command::SetVolume {
+                    channel: ChannelId(0),
+                    level: i as f32 / 100.0,
+                })
+                .unwrap();
+            engine.process_commands();
+        }
+
+        let errors: Vec<String> = channels
+            .event_rx
+            .try_iter()
+            .filter_map(|e| match e {
+                Event::Error(message) => Some(message),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("queue is full"), "{}", errors[0]);
+        // Le refus n'a pas touché au mixer : il reste sur le dernier envoyé
+        let last_sent = (ENGINE_QUEUE_CAPACITY - 1) as f32 / 100.0;
+        assert_eq!(
+            engine.mixer.channel(ChannelId(0)).unwrap().volume,
+            last_sent
+        );
+        assert_eq!(
+            engine.audio_stats().command_queue_depth,
+            ENGINE_QUEUE_CAPACITY as u32
+        );
+
+        // Le callback repart : tout est appliqué, dans l'ordre
+        harness.run_block();
+        assert_eq!(engine.audio_stats().command_queue_depth, 0);
+        assert_eq!(
+            harness.processor.runtime.current(),
+            &*engine.shared_state.snapshot()
+        );
+    }
+
+    #[test]
+    fn control_loop_idles_on_silence_and_wakes_without_losing_blocks() {
+        fn play(harness: &mut InputHarness, block: &[f32]) -> usize {
+            harness.processor.process_block_guarded(block);
+            harness.processor.send_block();
+            let done = harness.audio_rx.try_recv().unwrap();
+            let samples = done.len();
+            let _ = harness.recycle_tx.try_send(done);
+            samples
+        }
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_idle_detection(1, -60.0);
+        let mut harness = InputHarness::new(&engine);
+        let signal = harness.block.clone();
+        let silence = vec![0.0; signal.len()];
+        let start = Instant::now();
+        let mut delivered = 0;
+        let mut blocks = 0;
+        // Un relevé toutes les 100 ms, 4 blocs entre deux relevés
+        let script = [(&silence, 11), (&signal, 1), (&silence, 12)];
+        let mut tick = 0;
+        for (block, ticks) in script {
+            for _ in 0..ticks {
+                for _ in 0..4 {
+                    delivered += play(&mut harness, block);
+                    blocks += 1;
+                }
+                engine.update_idle(false, start + Duration::from_millis(tick * 100));
+                tick += 1;
+            }
+        }
+        let transitions: Vec<bool> = channels
+            .event_rx
+            .try_iter()
+            .filter_map(|event| match event {
+                Event::IdleChanged { idle } => Some(idle),
+                _ => None,
+            })
+            .collect();
+        // Veille après 1 s de silence, réveil au premier bloc de
+        // signal, veille à nouveau une seconde plus tard
+        assert_eq!(transitions, [true, false, true]);
+        assert!(engine.audio_stats().idle);
+        assert_eq!(
+            engine.loop_tick(Duration::from_millis(16)),
+            crate::idle::IDLE_TICK
+        );
+        // Le callback n'a jamais cessé de livrer : aucun bloc perdu
+        assert_eq!(delivered, blocks * signal.len());
+
+        // Une commande réveille la boucle
+        channels
+            .command_tx
+            .send(Command::RequestAudioStats)
+            .unwrap();
+        engine.process_commands();
+        assert!(!engine.is_idle());
+    }
+
+    #[test]
+    fn output_delay_reaches_the_device_but_not_the_taps() {
+        let (mut engine, channels) = Engine::new();
+        *engine.dsp_chain.lock().unwrap() = EffectsChain::new();
+        for (bus, delay_ms) in [(ChannelId(3), 100.0), (ChannelId(4), 900.0)] {
+            channels
+                .command_tx
+                .send(Command::SetOutputDelay { bus, delay_ms })
+                .unwrap();
+        }
+        channels
+            .command_tx
+            .send(Command::SetOutputDelay {
+                bus: ChannelId(0),
+                delay_ms: 100.0,
+            })
+            .unwrap();
+        engine.process_commands();
+        let delay = |id| {
+            engine
+                .mixer()
+                .channel(ChannelId(id))
+                .unwrap()
+                .output_delay_ms
+        };
+        assert_eq!((delay(3), delay(4)), (100.0, MAX_OUTPUT_DELAY_MS));
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("delay bus output")))
+        );
+        assert_eq!(engine.journal().recent(1)[0].action, "SetOutputDelay");
+
+        // Impulsion dans le Mic, puis du silence : le tap la voit tout
+        // de suite, le device 100 ms (4800 frames à 48 kHz) plus tard
+        play_bus(&mut engine, ChannelId(3));
+        let tap = engine.create_tap(ChannelId(3), 64).unwrap();
+        let mut harness = InputHarness::new(&engine);
+        let (mut tapped, mut played) = (Vec::new(), Vec::new());
+        for n in 0..20 {
+            harness.block.fill(0.0);
+            if n == 0 {
+                harness.block[0] = 1.0;
+            }
+            harness.run_block();
+            played.extend_from_slice(&harness.processor.output);
+            tapped.extend(tap.try_read().unwrap().samples);
+        }
+        let first = |samples: &[f32]| samples.iter().position(|s| s.abs() > 1e-3).map(|i| i / 2);
+        assert_eq!(first(&tapped), Some(0));
+        assert_eq!(first(&played), Some(4800));
+        assert_eq!(played[4800 * 2], tapped[0]);
+    }
+
+    /// Écoute installée sans device réel : un stream cadencé sur du silence.
+    fn fake_preview(engine: &mut Engine, device: &str, now_ms: u64) {
+        let stream = PipelineStream::Clocked(ClockedInput::new(
+            LoopedAudio::silence(1),
+            48_000,
+            256,
+            |_| {},
+        ));
+        engine
+            .preview
+            .start(device, Some(ChannelId(3)), now_ms, || {
+                Ok::<_, TroubadourError>(stream)
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn device_preview_is_refused_when_stopped_and_times_out() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::StartDevicePreview {
+                device: "USB Mic".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("Cannot preview USB Mic")))
+        );
+        assert!(engine.audio_stats().device_preview.is_none());
+
+        // Démarrée à 0 ms : le délai est écoulé au prochain passage
+        engine.configure_device_preview(&DevicePreviewConfig {
+            timeout_seconds: 1,
+            monitor_bus: None,
+        });
+        fake_preview(&mut engine, "USB Mic", 0);
+        let status = engine.audio_stats().device_preview.unwrap();
+        assert_eq!(
+            (status.device.as_str(), status.bus),
+            ("USB Mic", Some(ChannelId(3)))
+        );
+        engine.process_commands();
+        assert!(engine.audio_stats().device_preview.is_none());
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::DevicePreviewStopped { device, reason: PreviewStopReason::TimedOut }
+                if device == "USB Mic"
+        )));
+    }
+
+    #[test]
+    fn assigning_the_previewed_device_ends_the_preview() {
+        let (mut engine, channels) = Engine::new();
+        fake_preview(&mut engine, "USB Mic", journal::now_ms());
+        // Desktop n'ouvre pas de stream : l'écoute continue
+        channels
+            .command_tx
+            .send(Command::SetChannelDevice {
+                channel: ChannelId(1),
+                device: DeviceAssignment::Specific("USB Mic".into()),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.preview.device(), Some("USB Mic"));
+        channels
+            .command_tx
+            .send(Command::SetChannelDevice {
+                channel: ChannelId(0),
+                device: DeviceAssignment::Specific("USB Mic".into()),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.preview.device(), None);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::DevicePreviewStopped {
+                reason: PreviewStopReason::AssignedToChannel,
+                ..
+            }
+        )));
+
+        // Un autre device reste écouté ; le stop explicite le ferme
+        fake_preview(&mut engine, "Webcam", journal::now_ms());
+        channels
+            .command_tx
+            .send(Command::SetChannelDevice {
+                channel: ChannelId(0),
+                device: DeviceAssignment::Specific("USB Mic".into()),
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::StopDevicePreview)
+            .unwrap();
+        engine.process_commands();
+        let stopped: Vec<_> = channels
+            .event_rx
+            .try_iter()
+            .filter_map(|e| match e {
+                Event::DevicePreviewStopped { device, reason } => Some((device, reason)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            stopped,
+            [("Webcam".to_string(), PreviewStopReason::Requested)]
+        );
+    }
+
+    #[test]
+    fn input_blocks_of_any_length_give_whole_stereo_frames() {
+        let (mut engine, channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        for muted in [false, true] {
+            if muted {
+                for id in [0, 1, 2] {
+                    channels
+                        .command_tx
+                        .send(Command::SetMute {
+                            channel: ChannelId(id),
+                            muted: true,
+                        })
+                        .unwrap();
+                }
+                engine.process_commands();
+            }
+            for len in [0_usize, 1, 3, 511, 512, 513, 4096] {
+                harness.block = (0..len).map(|n| 0.3 * (n as f32 * 0.05).sin()).collect();
+                harness.processor.process_block_guarded(&harness.block);
+                // Une frame tronquée (un seul canal sur deux) en est une
+                assert_eq!(
+                    harness.processor.output.len(),
+                    len.div_ceil(2) * 2,
+                    "block of {len}, muted: {muted}"
+                );
+                assert!(harness.processor.output.iter().all(|s| s.is_finite()));
+            }
+        }
+    }
+
+    /// Callback d'une écoute stéréo à `device_rate`, vers un mixer à 48 kHz.
+    fn preview_callback(engine: &Engine, device_rate: u32) -> PreviewCallback {
+        PreviewCallback::new(
+            engine.preview_feed.clone(),
+            engine.event_tx.clone(),
+            MeterGate::new(engine.meter_subscription.clone()),
+            MeterScale::default(),
+            2,
+            CallbackResampler::new(device_rate, 48_000, 1, 256).unwrap(),
+        )
+    }
+
+    #[test]
+    fn preview_at_another_rate_reaches_the_feed_at_the_mixer_rate() {
+        let (engine, _channels) = Engine::new();
+        *engine.preview_feed.lock().unwrap() = Some(PreviewFeed::new(Some(ChannelId(3)), 48_000.0));
+        for (device_rate, expected) in [(48_000, 4_800..=4_800), (44_100, 4_500..=4_800)] {
+            let mut callback = preview_callback(&engine, device_rate);
+            // 100 ms du device en blocs de 10 ms
+            let block = vec![0.5; device_rate as usize / 100 * 2];
+            let mut produced = 0;
+            for _ in 0..10 {
+                callback.process(&block);
+                produced += callback.resampled.len();
+            }
+            assert!(
+                expected.contains(&produced),
+                "{device_rate} Hz gave {produced} samples"
+            );
+        }
+        let mut mixed = vec![0.0; 64];
+        engine
+            .preview_feed
+            .lock()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .mix_into(&mut mixed);
+        let level = VolumeDecibels(PREVIEW_LEVEL_DB).to_linear() * 0.5;
+        assert!(mixed.iter().all(|&s| (s - level).abs() < 1e-3));
+    }
+
+    #[test]
+    fn failed_preview_start_keeps_the_current_preview_and_its_feed() {
+        let (mut engine, _channels) = Engine::new();
+        assert!(engine.start_device_preview("USB Mic").is_err());
+        engine.state = EngineState::Running;
+        fake_preview(&mut engine, "Webcam", journal::now_ms());
+        *engine.preview_feed.lock().unwrap() = Some(PreviewFeed::new(Some(ChannelId(3)), 48_000.0));
+
+        assert!(engine.start_device_preview("No Such Device").is_err());
+        assert_eq!(engine.preview.device(), Some("Webcam"));
+        let feed = engine.preview_feed.lock().unwrap();
+        assert_eq!(feed.as_ref().and_then(PreviewFeed::bus), Some(ChannelId(3)));
+    }
+
+    #[test]
+    fn preview_levels_follow_the_meter_subscription() {
+        let (mut engine, channels) = Engine::new();
+        let mut callback = preview_callback(&engine, 48_000);
+        let preview_levels = || {
+            channels
+                .event_rx
+                .try_iter()
+                .filter(|e| {
+                    matches!(e, Event::LevelUpdate(levels) if levels[0].channel == PREVIEW_CHANNEL)
+                })
+                .count()
+        };
+        callback.process(&[0.5; 64]);
+        assert_eq!(preview_levels(), 1);
+
+        // L'UI n'affiche que le Mic : l'écoute n'envoie plus rien
+        engine.set_meter_subscription(&[PIPELINE_INPUT_CHANNEL], true);
+        callback.process(&[0.5; 64]);
+        assert_eq!(preview_levels(), 0);
+
+        engine.set_meter_subscription(&[PREVIEW_CHANNEL], true);
+        callback.process(&[0.5; 64]);
+        assert_eq!(preview_levels(), 1);
+        assert_eq!(engine.meter_subscription.emitted(), 2);
+    }
+
+    #[test]
+    fn preview_is_mixed_into_its_bus_after_the_taps() {
+        let (mut engine, _channels) = Engine::new();
+        *engine.dsp_chain.lock().unwrap() = EffectsChain::new();
+        // Le retard de sortie du bus ne retarde pas l'écoute
+        engine.mixer.set_output_delay(ChannelId(3), 100.0).unwrap();
+        engine.shared_state.update_from_mixer(&engine.mixer);
+        play_bus(&mut engine, ChannelId(3));
+        let tap = engine.create_tap(ChannelId(3), 64).unwrap();
+        let mut harness = InputHarness::new(&engine);
+        harness.block.fill(0.0);
+
+        *engine.preview_feed.lock().unwrap() = Some(PreviewFeed::new(Some(ChannelId(3)), 48_000.0));
+        engine
+            .preview_feed
+            .lock()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .write([0.25; 256]);
+        harness.run_block();
+        assert!(harness.processor.output.iter().all(|&s| s == 0.25));
+        assert!(tap.try_read().unwrap().samples.iter().all(|&s| s == 0.0));
+
+        // Un autre bus d'écoute : le bus joué ne l'entend pas
+        *engine.preview_feed.lock().unwrap() = Some(PreviewFeed::new(Some(ChannelId(4)), 48_000.0));
+        engine
+            .preview_feed
+            .lock()
+            .unwrap()
+            .as_mut()
+            .unwrap()
+            .write([0.25; 256]);
+        harness.run_block();
+        assert!(harness.processor.output.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn taps_copy_the_played_bus_and_count_overflows() {
+        let (mut engine, channels) = Engine::new();
+        assert!(engine.create_tap(ChannelId(0), 4).is_err());
+        // Moteur arrêté, puis bus que le pipeline ne rend pas
+        assert!(engine.create_tap(ChannelId(3), 2).is_err());
+        play_bus(&mut engine, ChannelId(3));
+        assert!(engine.create_tap(ChannelId(4), 2).is_err());
+        let tap = engine.create_tap(ChannelId(3), 2).unwrap();
+
+        let mut harness = InputHarness::new(&engine);
+        let mut played = Vec::new();
+        for _ in 0..5 {
+            harness.run_block();
+            played.push(harness.processor.output.clone());
+        }
+        // File de 2 blocs : les 3 premiers ont été jetés
+        for (sequence, output) in played.iter().enumerate().skip(3) {
+            let block = tap.try_read().unwrap();
+            assert_eq!(block.sequence, sequence as u64);
+            assert_eq!(block.sample_rate, 48_000);
+            assert_eq!(&block.samples, output);
+        }
+        assert_eq!(engine.audio_stats().tap_overflows, 3);
+
+        channels.command_tx.send(Command::ListTaps).unwrap();
+        engine.process_commands();
+        let listed = channels
+            .event_rx
+            .try_iter()
+            .find_map(|event| match event {
+                Event::TapList(taps) => Some(taps),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!((listed[0].bus, listed[0].blocks_written), (ChannelId(3), 5));
+        assert_eq!(listed[0].address, None);
+
+        // Lock tenu par le moteur pendant un bloc : le trou est compté
+        let held = engine.taps.lock().unwrap();
+        harness.run_block();
+        drop(held);
+        harness.run_block();
+        assert_eq!(tap.try_read().unwrap().sequence, 6);
+        assert_eq!(engine.audio_stats().tap_overflows, 4);
+
+        assert!(engine.destroy_tap(tap.id()));
+        assert!(tap.try_read().is_none());
+        assert!(engine.taps().is_empty());
+        // Les débordements d'un tap fermé restent comptés
+        assert_eq!(engine.audio_stats().tap_overflows, 4);
+
+        let config = |bus: &str| TapConfig {
+            bus: bus.into(),
+            port: 0,
+            capacity_blocks: 4,
+            enabled: true,
+        };
+        assert!(engine.serve_tap(&config("Mic")).is_err());
+        let served = engine.serve_tap(&config("headphones")).unwrap();
+        assert!(served.address.unwrap().starts_with("127.0.0.1:"));
+        assert!(engine.destroy_tap(served.id));
+        assert!(!engine.destroy_tap(served.id));
+    }
+
+    #[test]
+    fn replay_keeps_the_played_bus_and_saves_its_end() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-engine-replay-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (mut engine, channels) = Engine::new();
+        play_bus(&mut engine, ChannelId(3));
+        assert!(engine.enable_replay(ChannelId(0), None).is_err());
+        engine.configure_replay(&ReplayConfig {
+            seconds: 1,
+            buses: vec!["headphones".into(), "Nowhere".into()],
+            dir: Some(dir.clone()),
+        });
+        assert_eq!(engine.replays().len(), 1);
+        assert!(engine.save_replay(ChannelId(3), None, None).is_err());
+        // Le tap du replay n'est ni listé, ni fermable par `DestroyTap`
+        let replay_tap = engine.replays[0].tap_id();
+        assert!(engine.taps().is_empty());
+        assert!(!engine.destroy_tap(replay_tap));
+
+        // Moins de blocs que la file du tap : aucun n'est jeté
+        let mut harness = InputHarness::new(&engine);
+        let mut played = Vec::new();
+        for _ in 0..40 {
+            harness.run_block();
+            played.extend_from_slice(&harness.processor.output);
+        }
+        let expected = (played.len() / 2) as f32 / 48_000.0;
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while engine.replays()[0].buffered_seconds < expected {
+            assert!(std::time::Instant::now() < deadline);
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+
+        let send = |cmd| channels.command_tx.send(cmd).unwrap();
+        send(Command::SaveReplay {
+            bus: None,
+            path: Some(dir.join("clip.wav")),
+            seconds: None,
+        });
+        send(Command::SaveReplay {
+            bus: None,
+            path: None,
+            seconds: Some(0.1),
+        });
+        engine.process_commands();
+        let (mut errors, mut saved) = (0, None);
+        while saved.is_none() {
+            match channels
+                .event_rx
+                .recv_timeout(std::time::Duration::from_secs(10))
+                .expect("replay saved")
+            {
+                Event::Error(_) => errors += 1,
+                Event::ReplaySaved { bus, path, seconds } => saved = Some((bus, path, seconds)),
+                _ => {}
+            }
+        }
+        // Un chemin sans bus est refusé
+        assert_eq!(errors, 1);
+        let (bus, path, seconds) = saved.unwrap();
+        assert_eq!((bus, seconds), (ChannelId(3), 0.1));
+        assert!(path.starts_with(&dir));
+        let clip = crate::wav::read_wav(&path).unwrap();
+        assert_eq!((clip.sample_rate, clip.channels), (48_000, 2));
+        assert_eq!(clip.samples, played[played.len() - 9_600..]);
+
+        send(Command::DisableReplay { bus: ChannelId(3) });
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::ReplayStatus(ref replays) if replays.is_empty()))
+        );
+        assert!(engine.taps.lock().unwrap().infos().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn network_stream_plays_through_its_channel_and_the_bus_is_sent() {
+        use crate::network::{PACKET_FRAMES, decode_packet};
+        use std::net::UdpSocket;
+
+        let (mut engine, _channels) = Engine::new();
+        // Mic coupé : le bus ne joue que le flux reçu sur Desktop
+        engine.mixer.set_mute(ChannelId(0), true);
+        engine.shared_state.update_from_mixer(&engine.mixer);
+        let receive = |channel: &str| NetworkReceiveConfig {
+            channel: channel.into(),
+            bind: "127.0.0.1".into(),
+            port: 0,
+            jitter_packets: 1,
+            enabled: true,
+        };
+        assert!(engine.enable_network_receive(&receive("Nope")).is_err());
+        engine.enable_network_receive(&receive("desktop")).unwrap();
+        let listener = UdpSocket::bind("127.0.0.1:0").unwrap();
+        listener
+            .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+            .unwrap();
+        let send = |bus: &str| NetworkSendConfig {
+            bus: bus.into(),
+            host: "127.0.0.1".into(),
+            port: listener.local_addr().unwrap().port(),
+            int16: false,
+            enabled: true,
+        };
+        // Moteur arrêté, puis bus que le pipeline ne joue pas : refusés
+        assert!(engine.enable_network_send(&send("Headphones")).is_err());
+        play_bus(&mut engine, ChannelId(3));
+        assert!(engine.enable_network_send(&send("Speakers")).is_err());
+        assert!(!engine.network_stats().sending);
+        engine.enable_network_send(&send("Headphones")).unwrap();
+
+        // L'autre machine envoie 0.5 pendant un bloc entier
+        let source_addr = engine
+            .network
+            .lock()
+            .unwrap()
+            .source
+            .as_ref()
+            .unwrap()
+            .local_addr();
+        let mut remote = NetworkSink::connect(
+            "127.0.0.1",
+            source_addr.port(),
+            ChannelId(3),
+            PacketFormat::F32,
+            Arc::new(NetworkCounters::default()),
+        )
+        .unwrap();
+        remote.write(&[0.5; 512], 48_000);
+        let deadline = Instant::now() + std::time::Duration::from_secs(5);
+        while engine.network_stats().packets_received < 2 {
+            assert!(Instant::now() < deadline, "stream never arrived");
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        let mut harness = InputHarness::new(&engine);
+        harness.processor.process_block(&harness.block).unwrap();
+        let (gain_l, gain_r) = engine.shared_state.snapshot().gain(ChannelId(1));
+        let output = harness.processor.output.clone();
+        assert!((output[0] - 0.5 * gain_l).abs() < 1e-6);
+        assert!((output[1] - 0.5 * gain_r).abs() < 1e-6);
+
+        // Le bus joué part tel quel, en paquets de 128 frames
+        let mut packet = vec![0u8; 2048];
+        let len = listener.recv(&mut packet).unwrap();
+        let mut sent = vec![0.0; PACKET_FRAMES * 2];
+        assert_eq!(
+            decode_packet(&packet[..len], &mut sent).unwrap().sequence,
+            0
+        );
+        assert_eq!(sent[..], output[..PACKET_FRAMES * 2]);
+
+        let stats = engine.network_stats();
+        assert!(stats.sending && stats.receiving);
+        assert_eq!(stats.packets_sent, 2);
+        engine.disable_network_receive();
+        engine.disable_network_send();
+        assert_eq!(engine.network_stats(), NetworkStats::default());
+    }
+
+    #[test]
+    fn true_peak_of_played_bus_is_measured_and_drives_the_clip_flag() {
+        let (mut engine, channels) = Engine::new();
+        for bus in [ChannelId(0), ChannelId(3)] {
+            channels
+                .command_tx
+                .send(Command::SetTruePeakMeter { bus, enabled: true })
+                .unwrap();
+        }
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(m) if m.starts_with("Cannot meter true peak")))
+        );
+        assert!(
+            engine
+                .mixer()
+                .channel(ChannelId(3))
+                .unwrap()
+                .true_peak_meter
+        );
+        assert!(engine.shared_state.snapshot().true_peak_meter(ChannelId(3)));
+
+        // fs/4 déphasé de 45° : la crête vraie est 3 dB au-dessus des samples
+        let mut harness = InputHarness::new(&engine);
+        harness.block = (0..256)
+            .flat_map(|n| {
+                let phase = std::f32::consts::FRAC_PI_2 * n as f32 + std::f32::consts::FRAC_PI_4;
+                [0.5 * phase.sin(); 2]
+            })
+            .collect();
+        for _ in 0..20 {
+            harness.run_block();
+        }
+        let level = harness.processor.take_bus_level().unwrap();
+        assert_eq!(level.channel, ChannelId(3));
+        assert_eq!(level.loudness, None);
+        let true_peak = level.true_peak.unwrap();
+        let overshoot = 20.0 * (true_peak / level.peak).log10();
+        assert!((overshoot - 3.01).abs() < 0.3, "{overshoot} dB");
+        assert_eq!(level.clipped, MeterScale::default().clips(true_peak));
+
+        channels
+            .command_tx
+            .send(Command::SetTruePeakMeter {
+                bus: ChannelId(3),
+                enabled: false,
+            })
+            .unwrap();
+        engine.process_commands();
+        harness.run_block();
+        assert!(harness.processor.take_bus_level().is_none());
+    }
+
+    #[cfg(feature = "scripting")]
+    #[test]
+    fn scripts_read_callback_levels_and_act_through_the_command_queue() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-engine-scripts-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("duck.rhai"),
+            r#"fn tick(mixer) { if mixer.level("Mic") > -20.0 { mixer.set_volume("Desktop", -12.0); } }"#,
+        )
+        .unwrap();
+        let (mut engine, channels) = Engine::new();
+        engine.configure_scripting(
+            &ScriptingConfig {
+                enabled: vec!["duck".into()],
+                tick_ms: 1,
+                ..ScriptingConfig::default()
+            },
+            dir.clone(),
+        );
+        let desktop = |engine: &Engine| engine.mixer().channel(ChannelId(1)).unwrap().volume;
+
+        // Le Mic n'a encore rien mesuré : niveau à -inf
+        engine.process_commands();
+        engine.process_commands();
+        assert_eq!(desktop(&engine), 1.0);
+
+        let mut harness = InputHarness::new(&engine);
+        harness.block = vec![0.5; 512];
+        harness.run_block();
+        std::thread::sleep(Duration::from_millis(2));
+        // Tick : la commande part dans la file...
+        engine.process_commands();
+        assert_eq!(desktop(&engine), 1.0);
+        // ... et s'applique au passage suivant, comme celles de l'UI
+        engine.process_commands();
+        assert!((VolumeDecibels::from_linear(desktop(&engine)).0 + 12.0).abs() < 0.01);
+
+        channels
+            .command_tx
+            .send(Command::DisableScript {
+                name: "duck".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::ScriptList(list) if list.len() == 1 && !list[0].enabled
+        )));
+        assert!(engine.scripting_config().enabled.is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    #[test]
+    fn script_commands_are_refused_without_the_feature() {
+        let (mut engine, channels) = Engine::new();
+        let config = ScriptingConfig {
+            enabled: vec!["duck".into()],
+            ..ScriptingConfig::default()
+        };
+        engine.configure_scripting(&config, PathBuf::from("scripts"));
+        channels
+            .command_tx
+            .send(Command::EnableScript {
+                name: "duck".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("not available in this build")))
+        );
+        // Sans la feature, la section est sauvée telle qu'elle a été lue
+        assert_eq!(engine.scripting_config(), config);
+    }
+
+    #[test]
+    fn bus_compressor_pre_fader_ignores_the_bus_volume() {
+        let (mut engine, channels) = Engine::new();
+        let bus = ChannelId(3);
+        let mut preset = EffectsPreset::clean();
+        preset.compressor.enabled = true;
+        preset.compressor.threshold = 0.05;
+        preset.set_insert_point(3, InsertPoint::PostFader);
+        channels
+            .command_tx
+            .send(Command::SetChannelEffects {
+                channel: bus,
+                preset,
+            })
+            .unwrap();
+        engine.process_commands();
+        let reduction = |engine: &mut Engine| {
+            let mut harness = InputHarness::new(engine);
+            for _ in 0..8 {
+                harness.run_block();
+            }
+            let stages = engine.channel_effect_stages(bus).unwrap();
+            (stages[3].insert_point, stages[3].gain_reduction.unwrap())
+        };
+
+        // Bus à 0 dB : le compresseur voit le signal entier
+        let (_, unattenuated) = reduction(&mut engine);
+        assert!(unattenuated > 0.1, "{unattenuated}");
+
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: bus,
+                level: VolumeDecibels(-20.0).to_linear(),
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::SetEffectInsertPoint {
+                bus,
+                effect_index: 3,
+                point: InsertPoint::PreFader,
+            })
+            .unwrap();
+        engine.process_commands();
+        let (point, pre_fader) = reduction(&mut engine);
+        assert_eq!(point, Some(InsertPoint::PreFader));
+        assert!((pre_fader - unattenuated).abs() < 1e-4);
+
+        // Après le fader, -20 dB passent sous le seuil
+        engine
+            .set_effect_insert_point(bus, 3, InsertPoint::PostFader)
+            .unwrap();
+        let (point, post_fader) = reduction(&mut engine);
+        assert_eq!(point, Some(InsertPoint::PostFader));
+        assert_eq!(post_fader, 0.0);
+        // Le preset du bus voyage avec le profil
+        let (mut restored, _channels) = Engine::new();
+        restored.apply_profile(&engine.to_profile("Bus")).unwrap();
+        assert_eq!(
+            restored.channel_effects(bus).insert_point(3),
+            InsertPoint::PostFader
+        );
+
+        // Un canal d'entrée n'a pas de fader de bus
+        assert!(
+            engine
+                .set_effect_insert_point(ChannelId(0), 3, InsertPoint::PostFader)
+                .is_err()
+        );
+        assert_eq!(
+            engine.channel_effect_stages(ChannelId(0)).unwrap()[0].insert_point,
+            None
+        );
+    }
+
+    #[test]
+    fn level_history_is_recorded_only_for_enabled_channels() {
+        let (mut engine, channels) = Engine::new();
+        let bus = ChannelId(3);
+        engine.configure_level_history(&LevelHistoryConfig {
+            resolution_ms: 100,
+            window_seconds: 1,
+        });
+        assert!(engine.set_level_history(ChannelId(42), true).is_err());
+        engine.set_level_history(bus, true).unwrap();
+
+        // Deux blocs entre deux ticks : la crête la plus haute est gardée
+        for tick in 0..15u64 {
+            engine.levels.store(bus, 0.2, 0.8);
+            engine.levels.store(bus, 0.2, 0.4);
+            engine.observe_levels(10_000 + tick * 100);
+        }
+        let points = engine.level_history(bus, 60).unwrap();
+        assert_eq!(points.len(), 10);
+        assert_eq!(points[9].timestamp_ms, 11_400);
+        assert!(points.iter().all(|p| p.peak == 0.8 && p.rms == 0.2));
+        assert_eq!(engine.level_history(ChannelId(4), 60), None);
+
+        channels
+            .command_tx
+            .send(Command::RequestLevelHistory {
+                channel: bus,
+                seconds: 1,
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::SetLevelHistory {
+                channel: bus,
+                enabled: false,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::LevelHistory { channel, points } if channel == bus && points.len() == 10
+        )));
+        assert_eq!(engine.level_history(bus, 60), None);
+    }
+
+    #[test]
+    fn binary_meters_announce_their_layout_first() {
+        let (mut engine, channels) = Engine::new();
+        assert_eq!(engine.meter_transport(), MeterTransport::Json);
+        // Pas de pipeline : rien à annoncer
+        channels
+            .command_tx
+            .send(Command::SetMeterTransport(MeterTransport::Binary))
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.meter_transport(), MeterTransport::Binary);
+        assert!(
+            !channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::MeterLayout(_)))
+        );
+
+        // Un pipeline démarre : layout annoncé, repris par ses trames
+        let mut encoder = engine.new_meter_layout(Some(ChannelId(3)));
+        let announced: Vec<MeterLayout> = channels
+            .event_rx
+            .try_iter()
+            .filter_map(|e| match e {
+                Event::MeterLayout(layout) => Some(layout),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(announced.len(), 1);
+        assert_eq!(announced[0].targets, [PIPELINE_INPUT_CHANNEL, ChannelId(3)]);
+        let frame = encoder.encode(&[]);
+        assert_eq!(frame[8..12], announced[0].id.to_le_bytes());
+
+        // Le pipeline suivant a un autre id
+        let next = engine.new_meter_layout(None);
+        assert_ne!(next.layout().id, announced[0].id);
+        assert_eq!(engine.meter_layout(), Some(next.layout()));
+        assert_eq!(channels.event_rx.try_iter().count(), 1);
+
+        // Annonce perdue : l'UI la redemande
+        channels
+            .command_tx
+            .send(Command::RequestMeterLayout)
+            .unwrap();
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(
+            |e| matches!(e, Event::MeterLayout(layout) if Some(&layout) == engine.meter_layout())
+        ));
+
+        engine.set_meter_transport(MeterTransport::Json);
+        assert_eq!(engine.meter_transport(), MeterTransport::Json);
+        drop(engine.new_meter_layout(None));
+        assert!(
+            !channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::MeterLayout(_)))
+        );
+    }
+
+    #[test]
+    fn auto_level_measures_each_channel_in_turn() {
+        let (mut engine, channels) = Engine::new();
+        assert!(matches!(
+            engine.auto_level_channels(vec![ChannelId(0), ChannelId(42)], -12.0, 1_000),
+            Err(MixerError::ChannelNotFound(42))
+        ));
+        assert!(engine.auto_level_channels(vec![], -12.0, 1_000).is_err());
+        assert_eq!(
+            engine.auto_level_channels(vec![ChannelId(0)], -12.0, 1_000),
+            Err(MixerError::InvalidParameter(
+                "the engine is not running".into()
+            ))
+        );
+
+        // Pipeline en place : l'entrée et le bus 3 sont mesurés, pas ch1
+        engine.state = EngineState::Running;
+        drop(engine.new_meter_layout(Some(ChannelId(3))));
+        assert!(matches!(
+            engine.auto_level_channels(vec![ChannelId(0), ChannelId(1)], -12.0, 1_000),
+            Err(MixerError::InvalidParameter(msg)) if msg.starts_with("channel 1 is not metered")
+        ));
+        let volume_3 = engine.mixer().channel(ChannelId(3)).unwrap().volume;
+        engine
+            .auto_level_channels(vec![ChannelId(0), ChannelId(3), ChannelId(0)], -12.0, 1_000)
+            .unwrap();
+
+        // Mic à -6 dBFS puis muet, bus à -40 dBFS : un tick toutes les 100 ms
+        for tick in 0..=32u64 {
+            let mic_peak = if tick < 15 { 0.5 } else { 0.0 };
+            engine.levels.store(ChannelId(0), 0.2, mic_peak);
+            engine.levels.store(ChannelId(3), 0.005, 0.01);
+            engine.observe_levels(10_000 + tick * 100);
+        }
+
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        let progress: Vec<ChannelId> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::AutoLevelProgress { channel, total, .. } if *total == 3 => Some(*channel),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress, [ChannelId(0), ChannelId(3), ChannelId(0)]);
+        let Some(Event::AutoLevelFinished(results)) = events
+            .iter()
+            .find(|e| matches!(e, Event::AutoLevelFinished(_)))
+        else {
+            panic!("auto level not finished: {events:?}");
+        };
+        let statuses: Vec<AutoLevelStatus> = results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            [
+                AutoLevelStatus::Applied,
+                AutoLevelStatus::BoostRefused,
+                AutoLevelStatus::Silent
+            ]
+        );
+        // -6 dBFS → -12 dBFS : le volume est divisé par deux
+        assert!((results[0].measured_peak_db.unwrap() + 6.02).abs() < 0.01);
+        assert!((results[0].applied_gain_db + 5.98).abs() < 0.01);
+        let mic = engine.mixer().channel(ChannelId(0)).unwrap().volume;
+        assert!((mic - 0.5024).abs() < 1e-3);
+        assert_eq!(
+            engine.mixer().channel(ChannelId(3)).unwrap().volume,
+            volume_3
+        );
+        assert_eq!(engine.journal().recent(1)[0].action, "AutoLevel");
+        assert!(engine.auto_level.is_none());
+
+        // Annulée en route : rien n'est réglé
+        engine
+            .auto_level_channels(vec![ChannelId(3)], -30.0, 1_000)
+            .unwrap();
+        channels.command_tx.send(Command::CancelAutoLevel).unwrap();
+        engine.process_commands();
+        assert!(engine.auto_level.is_none());
+    }
+
+    #[test]
+    fn bus_fader_at_bottom_is_exact_silence() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetVolumeFader {
+                channel: ChannelId(3),
+                position: 0.0,
+            })
+            .unwrap();
+        engine.process_commands();
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..4 {
+            harness.run_block();
+        }
+        assert_eq!(engine.levels.load(ChannelId(3)), Some((0.0, 0.0)));
+
+        // -60 dB (presets existants) : très bas, mais audible
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: ChannelId(3),
+                level: VolumeDecibels(-60.0).to_linear(),
+            })
+            .unwrap();
+        engine.process_commands();
+        for _ in 0..4 {
+            harness.run_block();
+        }
+        let (_, peak) = engine.levels.load(ChannelId(3)).unwrap();
+        assert!(peak > 0.0);
+    }
+
+    #[test]
+    fn live_input_reaches_the_bus_through_its_submixes() {
+        let (mut engine, _channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..4 {
+            harness.run_block();
+        }
+        let (_, direct) = engine.levels.load(PIPELINE_INPUT_CHANNEL).unwrap();
+
+        // Mic → Voices (-6 dB) → bus, plus de route directe
+        engine
+            .mixer
+            .add_channel(ChannelConfig::submix(5, "Voices"))
+            .unwrap();
+        engine.mixer.remove_route(ChannelId(0), ChannelId(3));
+        assert!(engine.mixer.add_route(ChannelId(0), ChannelId(5)));
+        assert!(engine.mixer.add_route(ChannelId(5), ChannelId(3)));
+        engine.mixer.set_volume(ChannelId(5), 0.5);
+        engine.shared_state.update_from_mixer(&engine.mixer);
+        for _ in 0..4 {
+            harness.run_block();
+        }
+        let (_, through) = engine.levels.load(PIPELINE_INPUT_CHANNEL).unwrap();
+        let (voices, _) = engine.mixer.effective_gain(ChannelId(5));
+        assert!(
+            (through - direct * voices).abs() < 1e-4,
+            "{through} vs {direct}"
+        );
+
+        // Plus aucun chemin vers le bus joué : le Mic ne s'y entend plus
+        engine.mixer.remove_route(ChannelId(5), ChannelId(3));
+        engine.shared_state.update_from_mixer(&engine.mixer);
+        for _ in 0..4 {
+            harness.run_block();
+        }
+        assert_eq!(engine.levels.load(ChannelId(3)), Some((0.0, 0.0)));
+    }
+
+    #[test]
+    fn loudness_of_played_bus_is_measured_and_reset() {
+        let (mut engine, channels) = Engine::new();
+        assert_eq!(engine.loudness(ChannelId(3)), None);
+        assert_eq!(
+            engine.enable_loudness_meter(ChannelId(0)), // une entrée
+            Err(MixerError::BusNotFound(0))
+        );
+        engine.enable_loudness_meter(ChannelId(3)).unwrap();
+        assert_eq!(engine.loudness(ChannelId(3)), Some(Loudness::default()));
+
+        // ~0.5 s de sinus sur le bus joué (Headphones, premier bus)
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..100 {
+            harness.run_block();
+        }
+        let level = harness.processor.take_bus_level().unwrap();
+        assert_eq!(level.channel, ChannelId(3));
+        assert!(level.peak > 0.0);
+        let measured = engine.loudness(ChannelId(3)).unwrap();
+        assert!(measured.momentary.is_some_and(|m| m > -30.0 && m < 0.0));
+        assert_eq!(level.loudness, Some(measured));
+
+        channels
+            .command_tx
+            .send(Command::RequestLoudness {
+                bus: ChannelId(3),
+                reset: true,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::Loudness { bus: ChannelId(3), loudness: Some(l) } if l == measured
+        )));
+        // Remise à zéro : plus de valeur tant que le callback n'a rien remesuré
+        assert_eq!(engine.loudness(ChannelId(3)), Some(Loudness::default()));
+
+        engine.disable_loudness_meter(ChannelId(3)).unwrap();
+        harness.run_block();
+        assert!(harness.processor.take_bus_level().is_none());
+    }
+
+    #[test]
+    fn effects_chain_swap_goes_through_the_command_queue() {
+        let (mut engine, _channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        let active = |chain: &EffectsChain| chain.active_processors().contains(&"NoiseGate");
+        let before = active(&engine.dsp_chain.lock().unwrap());
+
+        let mut preset = engine.channel_effects(ChannelId(0));
+        preset.noise_gate.enabled = !before;
+        engine.set_channel_effects(ChannelId(0), preset);
+        // En file : le callback n'a pas encore pris la nouvelle chaîne,
+        // mais le thread de contrôle ne voit déjà plus l'ancienne
+        assert_eq!(active(&engine.dsp_chain.lock().unwrap()), before);
+        assert_eq!(engine.effects_swaps.len(), 1);
+        harness.run_block();
+        assert_eq!(active(&engine.dsp_chain.lock().unwrap()), !before);
+
+        // Installée : l'ancienne chaîne est libérée côté contrôle
+        engine.process_commands();
+        assert!(engine.effects_swaps.is_empty());
+
+        // Sans pipeline, la chaîne est remplacée tout de suite
+        drop(harness);
+        let mut preset = engine.channel_effects(ChannelId(0));
+        preset.noise_gate.enabled = before;
+        engine.set_channel_effects(ChannelId(0), preset);
+        assert_eq!(active(&engine.dsp_chain.lock().unwrap()), before);
+    }
+
+    #[test]
+    fn effect_automation_ramps_per_block_and_yields_to_manual_changes() {
+        let (mut engine, channels) = Engine::new();
+        engine.processing_rate = 48_000.0;
+        let ramps = |engine: &Engine| -> Vec<(usize, f32)> {
+            let chain = engine.lock_dsp_chain().unwrap();
+            chain
+                .automation()
+                .iter()
+                .map(|r| (r.effect_index, r.current()))
+                .collect()
+        };
+
+        // EQ aigus 0 → -12 dB en 1 s, ratio du compresseur 3 → 8 en 0.5 s
+        engine
+            .automate_effect_param(ChannelId(0), 2, "band2_gain_db", -12.0, 1000)
+            .unwrap();
+        engine
+            .automate_effect_param(ChannelId(0), 3, "ratio", 8.0, 500)
+            .unwrap();
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..75 {
+            harness.run_block(); // 256 frames
+        }
+        let elapsed = 75.0 * 256.0 / 48_000.0;
+        let running = ramps(&engine);
+        assert!((running[0].1 - -12.0 * elapsed).abs() < 1e-3);
+        assert!((running[1].1 - (3.0 + 5.0 * elapsed / 0.5)).abs() < 1e-3);
+
+        // Le ratio atteint sa cible : config mise à jour, événement
+        for _ in 0..30 {
+            harness.run_block();
+        }
+        engine.process_commands();
+        assert_eq!(engine.channel_effects(ChannelId(0)).compressor.ratio, 8.0);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::EffectAutomationCompleted { effect_index: 3, param, value, .. }
+                if param == "ratio" && value == 8.0
+        )));
+        assert_eq!(ramps(&engine).len(), 1);
+
+        // Réglage manuel du gate : l'EQ continue. Réglage de l'EQ : annulée.
+        let mut preset = engine.channel_effects(ChannelId(0));
+        preset.noise_gate.threshold = 0.02;
+        engine.set_channel_effects(ChannelId(0), preset.clone());
+        assert_eq!(ramps(&engine).len(), 1);
+        preset.eq.bands[2].gain_db = 3.0;
+        engine.set_channel_effects(ChannelId(0), preset);
+        assert!(ramps(&engine).is_empty());
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::EffectAutomationCancelled { effect_index: 2, param, .. } if param == "band2_gain_db"
+        )));
+        assert_eq!(
+            engine.channel_effects(ChannelId(0)).eq.bands[2].gain_db,
+            3.0
+        );
+
+        // Canal sans chaîne qui tourne, paramètre inconnu
+        assert!(
+            engine
+                .automate_effect_param(ChannelId(1), 3, "ratio", 2.0, 100)
+                .is_err()
+        );
+        assert!(matches!(
+            engine.automate_effect_param(ChannelId(0), 5, "ratio", 2.0, 100),
+            Err(TroubadourError::Mixer(MixerError::InvalidParameter(_)))
+        ));
+    }
+
+    #[test]
+    fn busy_effects_chain_is_reported_through_rt_log() {
+        let (engine, _channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        {
+            let _held = engine.dsp_chain.lock().unwrap();
+            harness.run_block();
+        }
+        let mut events = Vec::new();
+        engine.rt_log.drain_with(|e| events.push(e));
+        assert_eq!(
+            events,
+            [RtEvent::EffectsBypassed {
+                channel: ChannelId(0),
+                frames: 256,
+            }]
+        );
+    }
+
+    #[test]
+    fn engine_starts_stopped() {
+        let (engine, _channels) = Engine::new();
+        assert_eq!(engine.state(), EngineState::Stopped);
+    }
+
+    #[test]
+    fn engine_has_default_mixer() {
+        let (engine, _channels) = Engine::new();
+        assert_eq!(engine.mixer().channel_count(), 5);
+    }
+
+    #[test]
+    fn engine_processes_volume_command() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: ChannelId(0),
+                level: 0.5,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.5);
+    }
+
+    #[test]
+    fn engine_volume_updates_shared_state() {
+        let (mut engine, channels) = Engine::new();
+
+        // Volume 0 → gain doit être (0, 0)
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: ChannelId(0),
+                level: 0.0,
+            })
+            .unwrap();
+        engine.process_commands();
+
+        let (l, r) = engine.shared_state.snapshot().gain(ChannelId(0));
+        assert_eq!(l, 0.0);
+        assert_eq!(r, 0.0);
+    }
+
+    #[test]
+    fn engine_mute_updates_shared_state() {
+        let (mut engine, channels) = Engine::new();
+
+        // Muter tous les inputs
+        for id in [0, 1, 2] {
+            channels
+                .command_tx
                 .send(Command::SetMute {
                     channel: ChannelId(id),
                     muted: true,
                 })
-                .unwrap();
-        }
+                .unwrap();
+        }
+        engine.process_commands();
+
+        // Le gain du canal 0 doit être 0 (muted)
+        let (l, r) = engine.shared_state.snapshot().gain(ChannelId(0));
+        assert_eq!(l, 0.0);
+        assert_eq!(r, 0.0);
+    }
+
+    #[test]
+    fn crossfade_commands_move_gain_between_channels() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::CreateCrossfade {
+                id: 1,
+                channel_a: ChannelId(0),
+                channel_b: ChannelId(1),
+                curve: CrossfadeCurve::ConstantPower,
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::SetCrossfadePosition {
+                id: 1,
+                position: 1.0,
+            })
+            .unwrap();
+        channels.command_tx.send(Command::ListCrossfades).unwrap();
+        engine.process_commands();
+
+        let snapshot = engine.shared_state.snapshot();
+        assert!(snapshot.gain(ChannelId(0)).0.abs() < 1e-6);
+        assert!((snapshot.gain(ChannelId(1)).0 - FRAC_1_SQRT_2).abs() < 1e-4);
+        // Le fader du canal A n'a pas bougé
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 1.0);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::CrossfadeList(list) if list.len() == 1 && list[0].position == 1.0
+        )));
+
+        channels
+            .command_tx
+            .send(Command::RemoveCrossfade { id: 1 })
+            .unwrap();
+        engine.process_commands();
+        let snapshot = engine.shared_state.snapshot();
+        assert!((snapshot.gain(ChannelId(0)).0 - FRAC_1_SQRT_2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn remove_channel_reports_what_it_cleaned_up() {
+        let (mut engine, channels) = Engine::new();
+        let send = |cmd| channels.command_tx.send(cmd).unwrap();
+        send(Command::LinkChannels {
+            left: ChannelId(1),
+            right: ChannelId(2),
+        });
+        send(Command::RequestChannelDependencies {
+            channel: ChannelId(1),
+        });
+        engine.process_commands();
+        let report = channels
+            .event_rx
+            .try_iter()
+            .find_map(|e| match e {
+                Event::ChannelDependencies(report) => Some(report),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(report.links.len(), 1);
+        assert!(!report.routes.is_empty());
+
+        send(Command::RemoveChannel {
+            channel: ChannelId(1),
+        });
+        engine.process_commands();
+        assert!(engine.mixer().channel(ChannelId(1)).is_none());
+        assert!(engine.mixer().links().is_empty());
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::ChannelRemoved(cleaned) if cleaned == report
+        )));
+        assert!(!report.pipeline_input && !report.played);
+
+        send(Command::RemoveChannel {
+            channel: ChannelId(1),
+        });
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(_)))
+        );
+    }
+
+    #[test]
+    fn remove_channel_cleans_up_what_the_engine_hangs_on_it() {
+        let (mut engine, _channels) = Engine::new();
+        let headphones = ChannelId(3);
+        engine.passthrough_bus = Some(headphones);
+        let brb = ActionSet {
+            actions: vec![
+                Action::SetMute {
+                    channel: headphones,
+                    muted: true,
+                },
+                Action::SetMute {
+                    channel: ChannelId(0),
+                    muted: true,
+                },
+            ],
+        };
+        engine.action_sets.insert("brb".into(), brb);
+        play_bus(&mut engine, headphones);
+        let tap = engine.create_tap(headphones, 4).unwrap();
+
+        let report = engine.channel_dependencies(headphones).unwrap();
+        assert_eq!(report.action_sets, ["brb"]);
+        assert!(report.passthrough_bus);
+        assert_eq!(report.taps, [tap.id()]);
+        assert!(!report.pipeline_input);
+
+        let (_, cleaned) = engine.remove_channel(headphones).unwrap();
+        assert_eq!(cleaned, report);
+        assert_eq!(engine.passthrough_bus, None);
+        assert!(engine.taps().is_empty());
+        assert_eq!(
+            engine.action_sets()["brb"].actions,
+            [Action::SetMute {
+                channel: ChannelId(0),
+                muted: true,
+            }]
+        );
+
+        // Le Mic alimente le pipeline : signalé avant confirmation
+        let report = engine.channel_dependencies(PIPELINE_INPUT_CHANNEL).unwrap();
+        assert!(report.pipeline_input && !report.is_empty());
+    }
+
+    #[test]
+    fn linked_channels_share_volume_and_effects() {
+        let (mut engine, channels) = Engine::new();
+        let send = |cmd| channels.command_tx.send(cmd).unwrap();
+        send(Command::LinkChannels {
+            left: ChannelId(1),
+            right: ChannelId(2),
+        });
+        send(Command::SetVolume {
+            channel: ChannelId(1),
+            level: 0.5,
+        });
+        let mut preset = EffectsPreset::default_preset();
+        preset.name = "Linked".into();
+        send(Command::SetChannelEffects {
+            channel: ChannelId(2),
+            preset,
+        });
+        send(Command::ListChannelLinks);
+        engine.process_commands();
+
+        assert_eq!(engine.mixer().channel(ChannelId(2)).unwrap().volume, 0.5);
+        let snapshot = engine.shared_state.snapshot();
+        assert_eq!(snapshot.gain(ChannelId(2)), snapshot.gain(ChannelId(1)));
+        assert_eq!(engine.channel_effects(ChannelId(1)).name, "Linked");
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::ChannelLinkList(links) if links == [ChannelLink::new(ChannelId(1), ChannelId(2))]
+        )));
+
+        // Un canal déjà lié est refusé
+        send(Command::LinkChannels {
+            left: ChannelId(0),
+            right: ChannelId(2),
+        });
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(_)))
+        );
+    }
+
+    #[test]
+    fn engine_pan_updates_shared_state() {
+        let (mut engine, channels) = Engine::new();
+
+        // Pan tout à gauche
+        channels
+            .command_tx
+            .send(Command::SetPan {
+                channel: ChannelId(0),
+                pan: -1.0,
+            })
+            .unwrap();
+        engine.process_commands();
+
+        let (l, r) = engine.shared_state.snapshot().gain(ChannelId(0));
+        assert!(l > 0.9, "Left gain should be ~1.0, got {l}");
+        assert!(r < 0.01, "Right gain should be ~0.0, got {r}");
+    }
+
+    #[test]
+    fn engine_clamps_volume() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: ChannelId(0),
+                level: 5.0,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 2.0);
+    }
+
+    #[test]
+    fn engine_processes_mute_command() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetMute {
+                channel: ChannelId(0),
+                muted: true,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(engine.mixer().channel(ChannelId(0)).unwrap().muted);
+    }
+
+    #[test]
+    fn engine_processes_solo_command() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetSolo {
+                channel: ChannelId(0),
+                solo: true,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(engine.mixer().channel(ChannelId(0)).unwrap().solo);
+    }
+
+    #[test]
+    fn engine_processes_pan_command() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetPan {
+                channel: ChannelId(0),
+                pan: -0.5,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().pan, -0.5);
+    }
+
+    #[test]
+    fn engine_processes_route_commands() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::AddRoute {
+                from: ChannelId(1),
+                to: ChannelId(4),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(engine.mixer().has_route(ChannelId(1), ChannelId(4)));
+
+        channels
+            .command_tx
+            .send(Command::RemoveRoute {
+                from: ChannelId(1),
+                to: ChannelId(4),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(!engine.mixer().has_route(ChannelId(1), ChannelId(4)));
+    }
+
+    #[test]
+    fn engine_processes_device_list_request() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::RequestDeviceList)
+            .unwrap();
+        engine.process_commands();
+
+        match channels.event_rx.try_recv() {
+            Ok(Event::DeviceList { .. }) => {}
+            other => println!("Received: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn engine_processes_shutdown() {
+        let (mut engine, channels) = Engine::new();
+        channels.command_tx.send(Command::Shutdown).unwrap();
+        engine.process_commands();
+        assert_eq!(engine.state(), EngineState::Stopped);
+    }
+
+    #[test]
+    fn engine_handles_multiple_commands() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: ChannelId(0),
+                level: 0.8,
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::SetMute {
+                channel: ChannelId(0),
+                muted: true,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.8);
+        assert!(engine.mixer().channel(ChannelId(0)).unwrap().muted);
+    }
+
+    #[test]
+    fn engine_channels_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Sender<Command>>();
+        assert_send::<Receiver<Event>>();
+    }
+
+    #[test]
+    fn engine_journals_mutations() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetMute {
+                channel: ChannelId(0),
+                muted: true,
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::AddRoute {
+                from: ChannelId(1),
+                to: ChannelId(4),
+            })
+            .unwrap();
+        engine.process_commands();
+
+        let log = engine.journal().recent(10);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].action, "SetMute");
+        assert_eq!(log[0].before.as_deref(), Some("false"));
+        assert_eq!(log[0].after.as_deref(), Some("true"));
+        assert_eq!(log[1].action, "AddRoute");
+    }
+
+    #[test]
+    fn engine_coalesces_volume_drags() {
+        let (mut engine, channels) = Engine::new();
+        for level in [0.9, 0.8, 0.7, 0.6] {
+            channels
+                .command_tx
+                .send(Command::SetVolume {
+                    channel: ChannelId(0),
+                    level,
+                })
+                .unwrap();
+        }
+        engine.process_commands();
+
+        let log = engine.journal().recent(10);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].before.as_deref(), Some("1.00"));
+        assert_eq!(log[0].after.as_deref(), Some("0.60"));
+    }
+
+    #[test]
+    fn engine_change_log_request_and_clear() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetSolo {
+                channel: ChannelId(2),
+                solo: true,
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::RequestChangeLog { limit: 5 })
+            .unwrap();
+        engine.process_commands();
+
+        match channels.event_rx.try_recv() {
+            Ok(Event::ChangeLog(entries)) => assert_eq!(entries.len(), 1),
+            other => panic!("Expected ChangeLog, got {other:?}"),
+        }
+
+        channels.command_tx.send(Command::ClearChangeLog).unwrap();
+        engine.process_commands();
+        assert!(engine.journal().is_empty());
+    }
+
+    #[test]
+    fn engine_stop_is_idempotent() {
+        let (mut engine, _channels) = Engine::new();
+        engine.stop();
+        engine.stop();
+        assert_eq!(engine.state(), EngineState::Stopped);
+    }
+
+    #[test]
+    fn signal_graph_merges_active_streams_when_running() {
+        let (engine, _channels) = Engine::new();
+
+        // Arrêté : aucun device (rien n'est configuré par défaut)
+        let graph = engine.export_signal_graph();
+        assert!(graph.nodes.iter().all(|n| n.kind != NodeKind::InputDevice));
+
+        // Simuler des streams ouverts par `start()` (les handles cpal
+        // exigent un vrai device : on fusionne les descriptions seules)
+        let streams = [
+            ActiveStream {
+                device_name: "Komplete Audio 2".into(),
+                is_input: true,
+                channels: 2,
+                sample_rate: 48_000,
+                target_channels: vec![PIPELINE_INPUT_CHANNEL],
+            },
+            ActiveStream {
+                device_name: "soundcore Q45".into(),
+                is_input: false,
+                channels: 2,
+                sample_rate: 48_000,
+                target_channels: vec![ChannelId(3)],
+            },
+        ];
+        let mut graph = engine.mixer.export_signal_graph();
+        merge_active_streams(
+            &mut graph,
+            &engine.mixer,
+            &streams,
+            &SystemDefaults::default(),
+        );
+        let input = graph.node("in:Komplete Audio 2").unwrap();
+        assert_eq!(input.active, Some(true));
+        assert_eq!(input.label, "Komplete Audio 2 (2 ch @ 48000 Hz)");
+        assert!(graph.node("out:soundcore Q45").is_some());
+
+        // Entrée → Mic, première sortie (Headphones) → device de sortie
+        assert!(
+            graph
+                .edges
+                .iter()
+                .any(|e| e.from == "in:Komplete Audio 2" && e.to == "ch0")
+        );
+        assert!(
+            graph
+                .edges
+                .iter()
+                .any(|e| e.from == "ch3" && e.to == "out:soundcore Q45")
+        );
+    }
+
+    #[test]
+    fn engine_export_signal_graph_command() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::ExportSignalGraph {
+                format: GraphFormat::Dot,
+            })
+            .unwrap();
+        engine.process_commands();
+
+        match channels.event_rx.try_recv().unwrap() {
+            Event::SignalGraph { format, content } => {
+                assert_eq!(format, GraphFormat::Dot);
+                assert!(content.contains("\"ch0\" -> \"ch3\""));
+            }
+            other => panic!("Expected SignalGraph, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn engine_delay_command_updates_shared_state() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetDelay {
+                channel: ChannelId(0),
+                delay_ms: 120.0,
+            })
+            .unwrap();
+        engine.process_commands();
+
+        assert_eq!(
+            engine.mixer().channel(ChannelId(0)).unwrap().delay_ms,
+            120.0
+        );
+        assert_eq!(engine.shared_state.snapshot().delay_ms(ChannelId(0)), 120.0);
+        assert_eq!(engine.journal().recent(1)[0].action, "SetDelay");
+    }
+
+    #[test]
+    fn control_profiles_switch_bindings_without_restart() {
+        let (mut engine, channels) = Engine::new();
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-engine-controls-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        engine.set_control_profile_manager(ControlProfileManager::new(&dir));
+        let send = |command| channels.command_tx.send(command).unwrap();
+        let cc = |cc| ControlSource::MidiCc { channel: 1, cc };
+
+        // Aucun profil : les contrôles ne font rien
+        assert!(!engine.control_input(&cc(7), 0.0));
+
+        engine.configure_controls(&ControlsConfig {
+            control_profile: Some("Generic 8-fader".into()),
+            ..ControlsConfig::default()
+        });
+        send(Command::ControlInput {
+            source: cc(7),
+            value: 0.0,
+        });
+        engine.process_commands();
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.0);
+
+        // Autre contrôleur : CC 7 n'est plus lié, CC 48 mute le Mic
+        send(Command::ActivateControlProfile {
+            name: "nanokontrol2".into(),
+        });
+        send(Command::ControlInput {
+            source: cc(7),
+            value: 1.0,
+        });
+        send(Command::ControlInput {
+            source: cc(48),
+            value: 1.0,
+        });
+        send(Command::ListControlProfiles);
+        engine.process_commands();
+        let mic = engine.mixer().channel(ChannelId(0)).unwrap();
+        assert_eq!(mic.volume, 0.0);
+        assert!(mic.muted);
+        assert_eq!(engine.active_control_profile(), Some("nanoKONTROL2"));
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::ControlProfiles { names, active: Some(active) }
+                if names.len() == 2 && active == "nanoKONTROL2"
+        )));
+
+        // Profil inconnu : l'actif reste en place
+        send(Command::ActivateControlProfile {
+            name: "Missing".into(),
+        });
+        let path = dir.join("export").join("nano.toml");
+        send(Command::ExportControlProfile {
+            name: "nanoKONTROL2".into(),
+            path: path.clone(),
+        });
+        engine.process_commands();
+        assert_eq!(engine.active_control_profile(), Some("nanoKONTROL2"));
+        let exported = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            ControlProfile::parse(&exported).unwrap(),
+            ControlProfile::nano_kontrol2()
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn volume_fader_uses_configured_taper() {
+        let (mut engine, channels) = Engine::new();
+        engine.configure_controls(&ControlsConfig {
+            fader_taper: FaderTaper::LinearDb,
+            ..ControlsConfig::default()
+        });
+        // Linéaire en dB : milieu de course = -27 dB
+        channels
+            .command_tx
+            .send(Command::SetVolumeFader {
+                channel: ChannelId(0),
+                position: 0.5,
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::RequestVolumeFader {
+                channel: ChannelId(0),
+            })
+            .unwrap();
+        engine.process_commands();
+
+        let volume = engine.mixer().channel(ChannelId(0)).unwrap().volume;
+        assert!((volume - 10.0_f32.powf(-27.0 / 20.0)).abs() < 1e-5);
+        assert_eq!(engine.journal().recent(1)[0].action, "SetVolume");
+
+        let position = channels
+            .event_rx
+            .try_iter()
+            .find_map(|e| match e {
+                Event::VolumeFader { position, .. } => Some(position),
+                _ => None,
+            })
+            .expect("VolumeFader event");
+        assert!((position - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn signal_presence_override_reaches_snapshot() {
+        let (mut engine, channels) = Engine::new();
+        assert_eq!(engine.signal_state(ChannelId(0)), SignalState::Active);
+
+        let config = SignalPresenceConfig {
+            threshold_db: -45.0,
+            silence_seconds: 5.0,
+            hysteresis_db: 3.0,
+        };
+        channels
+            .command_tx
+            .send(Command::SetSignalPresence {
+                channel: ChannelId(0),
+                config: Some(config),
+            })
+            .unwrap();
+        engine.process_commands();
+
+        assert_eq!(
+            engine
+                .mixer()
+                .channel(ChannelId(0))
+                .unwrap()
+                .signal_presence,
+            Some(config)
+        );
+        assert_eq!(
+            engine.shared_state.snapshot().signal_presence(ChannelId(0)),
+            Some(config)
+        );
+    }
+
+    #[test]
+    fn presence_is_measured_before_the_effects() {
+        let (mut engine, channels) = Engine::new();
+        engine.configure_signal_presence(&SignalPresenceConfig {
+            threshold_db: -40.0,
+            silence_seconds: 0.01,
+            hysteresis_db: 3.0,
+        });
+        // Gate fermé sur tout le signal : le canal traité est muet
+        let mut preset = engine.channel_effects(ChannelId(0));
+        preset.noise_gate.enabled = true;
+        preset.noise_gate.threshold = 1.0;
+        engine.set_channel_effects(ChannelId(0), preset);
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..8 {
+            harness.run_block();
+        }
+        let (processed_rms, _) = engine.levels.load(ChannelId(0)).unwrap();
+        assert!(processed_rms < 0.001, "{processed_rms}");
+        let request = || {
+            channels
+                .command_tx
+                .send(Command::RequestSignalState {
+                    channel: ChannelId(0),
+                })
+                .unwrap();
+        };
+        request();
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::SignalState {
+                channel: ChannelId(0),
+                state: SignalState::Active
+            }
+        )));
+
+        // Micro muet en entrée : silence signalé, et relu sur demande
+        harness.block.fill(0.0);
+        for _ in 0..8 {
+            harness.run_block();
+        }
+        request();
+        engine.process_commands();
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::SignalStateChanged {
+                state: SignalState::Silent,
+                ..
+            }
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::SignalState {
+                channel: ChannelId(0),
+                state: SignalState::Silent
+            }
+        )));
+
+        channels
+            .command_tx
+            .send(Command::RequestSignalState {
+                channel: ChannelId(42),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("42")))
+        );
+    }
+
+    #[test]
+    fn device_limited_to_44_1_khz_opens_natively_and_is_converted() {
+        use cpal::SupportedBufferSize;
+
+        let native = SupportedStreamConfig::new(
+            2,
+            cpal::SampleRate(44_100),
+            SupportedBufferSize::Unknown,
+            SampleFormat::F32,
+        );
+        let only_44_1 = || {
+            [SupportedStreamConfigRange::new(
+                2,
+                cpal::SampleRate(44_100),
+                cpal::SampleRate(44_100),
+                SupportedBufferSize::Unknown,
+                SampleFormat::F32,
+            )]
+            .into_iter()
+        };
+        assert_eq!(supported_rates(only_44_1()), [SampleRate::Hz44100]);
+        let config = config_at_rate(native, Some(only_44_1()), 48_000);
+        assert_eq!(config.sample_rate().0, 44_100);
+
+        // Une seconde au rate du device devient une seconde au rate de
+        // l'autre côté, en entrée (44.1 → 48) comme en sortie (48 → 44.1)
+        let block = BufferSize::default().as_frames() as usize;
+        for (from, to) in [(44_100, 48_000), (48_000, 44_100)] {
+            let mut bridge = CallbackResampler::new(from, to, 2, block).unwrap();
+            assert!(!bridge.is_passthrough());
+            let chunk = vec![0.25_f32; from as usize / 100 * 2];
+            let mut out = vec![0.0; 2048];
+            let mut delivered = 0;
+            for _ in 0..100 {
+                bridge.push(&chunk).unwrap();
+                while bridge.available() > 0 {
+                    let written = bridge.fill(&mut out);
+                    assert_eq!(written % 2, 0, "whole frames only");
+                    delivered += written / 2;
+                }
+            }
+            let missing = to as usize - delivered;
+            // Seule la latence interne du resampler manque encore
+            assert!(missing < 2 * block, "{from} → {to}: {delivered} frames");
+        }
+    }
+
+    #[test]
+    fn config_at_rate_prefers_supported_rate() {
+        use cpal::SupportedBufferSize;
+
+        let default = SupportedStreamConfig::new(
+            2,
+            cpal::SampleRate(48_000),
+            SupportedBufferSize::Unknown,
+            SampleFormat::F32,
+        );
+        let ranges = || {
+            vec![
+                SupportedStreamConfigRange::new(
+                    1,
+                    cpal::SampleRate(8_000),
+                    cpal::SampleRate(192_000),
+                    SupportedBufferSize::Unknown,
+                    SampleFormat::F32,
+                ),
+                SupportedStreamConfigRange::new(
+                    2,
+                    cpal::SampleRate(44_100),
+                    cpal::SampleRate(96_000),
+                    SupportedBufferSize::Unknown,
+                    SampleFormat::F32,
+                ),
+            ]
+            .into_iter()
+        };
+
+        let config = config_at_rate(default.clone(), Some(ranges()), 96_000);
+        assert_eq!(config.sample_rate().0, 96_000);
+        assert_eq!(config.channels(), 2);
+
+        // 192 kHz n'existe qu'en mono : on garde la config par défaut
+        let config = config_at_rate(default.clone(), Some(ranges()), 192_000);
+        assert_eq!(config.sample_rate().0, 48_000);
+        assert_eq!(config.channels(), 2);
+
+        // Bus mono : stream mono au même rate ; bus stéréo : inchangé
+        let mono = config_with_channels(default.clone(), Some(ranges()), ChannelCount::Mono, None);
+        assert_eq!(mono.channels(), 1);
+        assert_eq!(mono.sample_rate().0, 48_000);
+        let stereo =
+            config_with_channels(default.clone(), Some(ranges()), ChannelCount::Stereo, None);
+        assert_eq!(stereo.channels(), 2);
+
+        // Bus sur les sorties 5-6 : la plus petite config qui les atteint
+        let wide = ranges().chain([8, 6].map(|channels| {
+            SupportedStreamConfigRange::new(
+                channels,
+                cpal::SampleRate(44_100),
+                cpal::SampleRate(96_000),
+                SupportedBufferSize::Unknown,
+                SampleFormat::F32,
+            )
+        }));
+        let slot = Some(OutputSlot::new(4, 2));
+        let placed = config_with_channels(default.clone(), Some(wide), ChannelCount::Stereo, slot);
+        assert_eq!(placed.channels(), 6);
+        assert_eq!(placed.sample_rate().0, 48_000);
+        // Device stéréo : config inchangée, le renderer recopie
+        let fallback = config_with_channels(default, Some(ranges()), ChannelCount::Stereo, slot);
+        assert_eq!(fallback.channels(), 2);
+    }
+
+    #[test]
+    fn bus_output_mapping_command_is_saved_and_refuses_overlaps() {
+        let (mut engine, channels) = Engine::new();
+        for (bus, offset) in [(ChannelId(3), Some(2)), (ChannelId(4), Some(3))] {
+            channels
+                .command_tx
+                .send(Command::SetBusOutputMapping { bus, offset })
+                .unwrap();
+        }
+        engine.process_commands();
+
+        let config = engine.mixer().to_config();
+        let offset = |id| {
+            config
+                .channels
+                .iter()
+                .find(|c| c.id == ChannelId(id))
+                .unwrap()
+                .output_channel_offset
+        };
+        assert_eq!(offset(3), Some(2));
+        // Sorties 4-5 : chevauche Headphones, refusé
+        assert_eq!(offset(4), None);
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(_)))
+        );
+        let entry = &engine.journal().recent(1)[0];
+        assert_eq!(entry.action, "SetBusOutputMapping");
+        assert_eq!(entry.after.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn bus_channel_count_command_updates_runtime_and_journal() {
+        let (mut engine, channels) = Engine::new();
+        for bus in [ChannelId(4), ChannelId(0)] {
+            channels
+                .command_tx
+                .send(Command::SetBusChannelCount {
+                    bus,
+                    count: ChannelCount::Mono,
+                })
+                .unwrap();
+        }
+        engine.process_commands();
+
+        let speakers = engine.mixer().channel(ChannelId(4)).unwrap();
+        assert_eq!(speakers.channel_count, ChannelCount::Mono);
+        assert_eq!(
+            engine
+                .shared_state
+                .snapshot()
+                .bus_channel_count(ChannelId(4)),
+            ChannelCount::Mono
+        );
+        let entry = &engine.journal().recent(1)[0];
+        assert_eq!(entry.action, "SetBusChannelCount");
+        assert_eq!(entry.after.as_deref(), Some("mono"));
+        // Une entrée n'est pas un bus
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(msg) if msg.contains("bus 0")))
+        );
+    }
+
+    #[test]
+    fn sample_rate_change_while_stopped_updates_effects() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetSampleRate(SampleRate::Hz96000))
+            .unwrap();
+        engine.process_commands();
+
+        assert_eq!(engine.sample_rate(), SampleRate::Hz96000);
+        assert_eq!(engine.processing_rate(), 96_000.0);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::SampleRateChanged {
+                sample_rate: 96_000
+            }
+        )));
+    }
+
+    #[test]
+    fn eq_response_is_computed_without_audio() {
+        let (mut engine, channels) = Engine::new();
+        let mut preset = EffectsPreset::default_preset();
+        preset.eq.enabled = true;
+        preset.eq.bands[2].gain_db = -6.0;
+        channels
+            .command_tx
+            .send(Command::SetChannelEffects {
+                channel: ChannelId(1),
+                preset,
+            })
+            .unwrap();
+        for channel in [ChannelId(1), ChannelId(42)] {
+            channels
+                .command_tx
+                .send(Command::RequestEqResponse {
+                    channel,
+                    points: 100_000,
+                })
+                .unwrap();
+        }
+        engine.process_commands();
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        let response = events
+            .iter()
+            .find_map(|e| match e {
+                Event::EqResponse { channel, response } if *channel == ChannelId(1) => {
+                    Some(response)
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(response.frequencies.len(), MAX_RESPONSE_POINTS);
+        assert!(response.gains_db.last().unwrap() < &-3.0);
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("Cannot compute EQ response")))
+        );
+    }
+
+    #[test]
+    fn routing_snapshots_swap_only_the_routes() {
+        let dir = auto_snapshot_dir("routing");
+        let (mut engine, channels) = Engine::new();
+        engine.configure_auto_snapshots(&AutoSnapshotConfig::default(), dir.clone());
+        let send = |cmd| channels.command_tx.send(cmd).unwrap();
+        send(Command::SaveRoutingSnapshot {
+            name: "interview".into(),
+        });
+        send(Command::RemoveRoute {
+            from: ChannelId(1),
+            to: ChannelId(3),
+        });
+        send(Command::AddRoute {
+            from: ChannelId(2),
+            to: ChannelId(4),
+        });
+        send(Command::SaveRoutingSnapshot {
+            name: "music".into(),
+        });
+        send(Command::SetVolume {
+            channel: ChannelId(0),
+            level: 0.25,
+        });
+        send(Command::ApplyRoutingSnapshot {
+            name: "interview".into(),
+        });
+        engine.process_commands();
+
+        let routes = MixerConfig::default_setup().routes;
+        assert_eq!(engine.mixer().routes(), routes);
+        assert_eq!(engine.shared_state.snapshot().routes, routes);
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.25);
+        assert_eq!(
+            engine.routing_snapshots().keys().collect::<Vec<_>>(),
+            ["interview", "music"]
+        );
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::RoutingSnapshotApplied { name } if name == "interview"
+        )));
+        // Les routes remplacées restent récupérables
+        let list = engine.auto_snapshot_list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].reason, "ApplyRoutingSnapshot");
+
+        // Canal supprimé depuis : refusé, routes inchangées
+        send(Command::RemoveChannel {
+            channel: ChannelId(2),
+        });
+        send(Command::ApplyRoutingSnapshot {
+            name: "music".into(),
+        });
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::Error(m) if m.contains("'music'") && m.contains("deleted channels: 2")
+        )));
+        assert!(engine.mixer().has_route(ChannelId(1), ChannelId(3)));
+
+        send(Command::DeleteRoutingSnapshot {
+            name: "music".into(),
+        });
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::RoutingSnapshotList(names) if names == ["interview"]
+        )));
+
+        // Un ensemble d'actions ne peut pas viser un routage inconnu
+        let set = |name: &str| ActionSet {
+            actions: vec![Action::ApplyRoutingSnapshot { name: name.into() }],
+        };
+        assert!(matches!(
+            engine.save_action_set("music", set("music")),
+            Err(MixerError::InvalidParameter(m)) if m.contains("'music' not found")
+        ));
+        engine.save_action_set("talk", set("interview")).unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn action_sets_are_validated_run_on_demand_and_on_schedule() {
+        let (mut engine, channels) = Engine::new();
+        let brb = ActionSet {
+            actions: vec![
+                Action::SetMute {
+                    channel: ChannelId(0),
+                    muted: true,
+                },
+                Action::SetMute {
+                    channel: ChannelId(1),
+                    muted: false,
+                },
+            ],
+        };
+        let mut broken = brb.clone();
+        broken.actions.push(Action::SetSolo {
+            channel: ChannelId(42),
+            solo: true,
+        });
+        for (name, set) in [("brb", brb), ("broken", broken)] {
+            channels
+                .command_tx
+                .send(Command::SaveActionSet {
+                    name: name.into(),
+                    set,
+                })
+                .unwrap();
+        }
+        channels
+            .command_tx
+            .send(Command::ExecuteActionSet { name: "brb".into() })
+            .unwrap();
+        engine.process_commands();
+
+        assert_eq!(engine.action_sets().keys().collect::<Vec<_>>(), ["brb"]);
+        assert!(engine.shared_state.snapshot().gain(ChannelId(0)) == (0.0, 0.0));
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, Event::Error(msg) if msg.contains("broken")))
+        );
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::ActionSetExecuted { name, scheduled: false } if name == "brb"
+        )));
+
+        // Déclencheur à 22:30 (UTC), horloge simulée
+        let mut schedule = ScheduleConfig {
+            action_sets: engine.action_sets().clone(),
+            utc_offset_minutes: Some(0),
+            ..Default::default()
+        };
+        schedule.action_sets.insert(
+            "back".into(),
+            ActionSet {
+                actions: vec![Action::SetMute {
+                    channel: ChannelId(0),
+                    muted: false,
+                }],
+            },
+        );
+        schedule.triggers.push(DailyTrigger {
+            at: TimeOfDay::new(22, 30).unwrap(),
+            action_set: "back".into(),
+        });
+        engine.configure_schedule(&schedule);
+        let clock = |h: u64, m: u64| UNIX_EPOCH + Duration::from_secs((h * 60 + m) * 60);
+        engine.run_scheduled_actions(clock(22, 29));
+        assert!(engine.mixer().channel(ChannelId(0)).unwrap().muted);
+        engine.run_scheduled_actions(clock(22, 30));
+        assert!(!engine.mixer().channel(ChannelId(0)).unwrap().muted);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::ActionSetExecuted { name, scheduled: true } if name == "back"
+        )));
+
+        // Supprimer l'ensemble retire aussi son déclencheur
+        channels
+            .command_tx
+            .send(Command::DeleteActionSet {
+                name: "back".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(engine.schedule_triggers().is_empty());
+    }
+
+    #[test]
+    fn stream_opens_are_learned_and_preferred_next_time() {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-engine-device-cache-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("device_cache.toml");
+        let (mut engine, channels) = Engine::new();
+        engine.configure_device_cache(path.clone());
+        let settings = |hz| StreamSettings {
+            sample_rate: hz,
+            buffer_frames: None,
+            channels: 2,
+            format: "F32".into(),
+        };
+
+        // Défaut du moteur tant que rien n'est appris
+        assert_eq!(engine.preferred_rate("Yeti", true), 48_000);
+        let failed: Result<(), _> = Err(cpal::BuildStreamError::StreamConfigNotSupported);
+        let only_44_1 = || vec![SampleRate::Hz44100];
+        let err = engine
+            .remember_stream_open("Yeti", true, settings(96_000), only_44_1, failed)
+            .unwrap_err();
+        // Le rate refusé et ceux que le device accepte vraiment
+        let unsupported = StreamErrorKind::FormatNotSupported {
+            requested: 96_000,
+            supported: vec![44_100],
+        };
+        assert!(matches!(
+            &err,
+            TroubadourError::StreamOpen { device, kind, .. } if device == "Yeti" && *kind == unsupported
+        ));
+        let reported = channels.event_rx.try_iter().find_map(|event| match event {
+            Event::StreamFailed { kind, hint, .. } => Some((kind, hint)),
+            _ => None,
+        });
+        assert_eq!(
+            reported,
+            Some((unsupported, err.hint().map(str::to_string)))
+        );
+        engine
+            .remember_stream_open("Yeti", true, settings(44_100), only_44_1, Ok(()))
+            .unwrap();
+        assert_eq!(engine.preferred_rate("Yeti", true), 44_100);
+
+        // Relu au prochain lancement, échec compris
+        let (mut next, _next_channels) = Engine::new();
+        next.configure_device_cache(path.clone());
+        let entry = next.device_cache().entry("Yeti", true).unwrap();
+        assert_eq!(entry.failures[0].sample_rate, 96_000);
+        assert_eq!(next.preferred_rate("Yeti", true), 44_100);
+
+        // Un rate choisi par l'utilisateur passe avant le cache
+        channels
+            .command_tx
+            .send(Command::SetSampleRate(SampleRate::Hz96000))
+            .unwrap();
+        channels.command_tx.send(Command::ClearDeviceCache).unwrap();
+        engine.process_commands();
+        assert_eq!(engine.preferred_rate("Yeti", true), 96_000);
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::DeviceCache(cache) if cache.inputs.is_empty()))
+        );
+        assert_eq!(DeviceCache::load(&path).unwrap(), DeviceCache::default());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cached_buffer_size_is_requested_and_recorded() {
+        use cpal::SupportedBufferSize;
+
+        let (mut engine, channels) = Engine::new();
+        let config = |min, max| {
+            SupportedStreamConfig::new(
+                2,
+                cpal::SampleRate(48_000),
+                SupportedBufferSize::Range { min, max },
+                SampleFormat::F32,
+            )
+        };
+        let device = config(32, 2048);
+
+        // Défaut du moteur, imposé au stream et noté dans le cache
+        let buffer = engine.preferred_buffer("Yeti", true, &device);
+        assert_eq!(buffer, Some(256));
+        let config_for_cpal = stream_config(device.clone(), buffer);
+        assert_eq!(config_for_cpal.buffer_size, cpal::BufferSize::Fixed(256));
+        let mut settings = stream_settings(&device, buffer);
+        settings.buffer_frames = Some(1024);
+        engine
+            .remember_stream_open("Yeti", true, settings, Vec::new, Ok(()))
+            .unwrap();
+        // La taille qui a marché est reprise à la prochaine ouverture
+        assert_eq!(engine.preferred_buffer("Yeti", true, &device), Some(1024));
+        // Hors des limites du device : le driver choisit
+        assert_eq!(
+            engine.preferred_buffer("Yeti", true, &config(32, 512)),
+            None
+        );
+        assert_eq!(
+            stream_config(device.clone(), None).buffer_size,
+            cpal::BufferSize::Default
+        );
+
+        // Le choix de l'utilisateur passe avant le cache
+        channels
+            .command_tx
+            .send(Command::SetBufferSize(BufferSize::Samples128))
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.buffer_size(), BufferSize::Samples128);
+        assert_eq!(engine.preferred_buffer("Yeti", true, &device), Some(128));
+    }
+
+    #[test]
+    fn channel_device_change_while_stopped_only_updates_assignment() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetChannelDevice {
+                channel: ChannelId(1),
+                device: DeviceAssignment::Specific("Loopback".into()),
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::SetChannelDevice {
+                channel: ChannelId(42),
+                device: DeviceAssignment::None,
+            })
+            .unwrap();
+        engine.process_commands();
+
+        let desktop = engine.mixer().channel(ChannelId(1)).unwrap();
+        assert_eq!(desktop.device_name.specific(), Some("Loopback"));
+        assert!(engine.active_streams().is_empty());
+        let entry = &engine.journal().recent(1)[0];
+        assert_eq!(entry.action, "SetChannelDevice");
+        assert_eq!(entry.after.as_deref(), Some("Loopback"));
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(msg) if msg.contains("channel 42")))
+        );
+    }
+
+    /// Défauts du système que le test change à la main.
+    struct MockDefaults(Arc<Mutex<SystemDefaults>>);
+
+    impl DefaultDeviceSource for MockDefaults {
+        fn default_input_name(&self) -> Option<String> {
+            self.0.lock().unwrap().input.clone()
+        }
+
+        fn default_output_name(&self) -> Option<String> {
+            self.0.lock().unwrap().output.clone()
+        }
+    }
+
+    #[test]
+    fn missing_devices_name_what_start_needs() {
+        let (mut engine, _channels) = Engine::new();
+        let defaults = Arc::new(Mutex::new(SystemDefaults::default()));
+        engine.set_default_device_source(Box::new(MockDefaults(defaults.clone())));
+        assert_eq!(
+            engine.missing_devices(),
+            ["default input device", "default output device"]
+        );
+
+        defaults.lock().unwrap().input = Some("Built-in Mic".into());
+        assert_eq!(engine.missing_devices(), ["default output device"]);
+    }
+
+    /// Devices branchés simulés, sans défaut de sortie.
+    struct MockDevices {
+        default_input: String,
+        plugged: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl DefaultDeviceSource for MockDevices {
+        fn default_input_name(&self) -> Option<String> {
+            Some(self.default_input.clone())
+        }
+
+        fn default_output_name(&self) -> Option<String> {
+            None
+        }
+
+        fn device_present(&self, name: &str, _is_input: bool) -> Option<bool> {
+            Some(self.plugged.lock().unwrap().iter().any(|d| d == name))
+        }
+    }
+
+    #[test]
+    fn failed_reopen_on_a_new_default_is_retried_at_the_next_poll() {
+        let (mut engine, _channels) = Engine::new();
+        let defaults = Arc::new(Mutex::new(SystemDefaults {
+            input: Some("Mic".into()),
+            output: Some("Card A".into()),
+        }));
+        engine.set_default_device_source(Box::new(MockDefaults(defaults.clone())));
+        for bus in [ChannelId(3), ChannelId(4)] {
+            engine.mixer.channel_mut(bus).unwrap().device_name = DeviceAssignment::SystemDefault;
+        }
+        engine.system_defaults = engine.current_system_defaults();
+        let log = start_on_fake_devices(&mut engine, "Mic", "Card A");
+        let unplug = |engine: &mut Engine, devices: Vec<String>| {
+            engine.fake_devices.as_mut().unwrap().unplugged = devices;
+        };
+
+        // Le nouveau défaut est annoncé avant que son device ne s'ouvre
+        unplug(&mut engine, vec!["Card B".into()]);
+        defaults.lock().unwrap().output = Some("Card B".into());
+        engine.check_default_devices(0);
+        assert_eq!(engine.system_defaults.output.as_deref(), Some("Card A"));
+
+        // Redémarré, le moteur voit encore le changement et le suit
+        unplug(&mut engine, Vec::new());
+        engine
+            .start_audio_pipeline(
+                &InputSource::Device("Mic".into()),
+                "Card A",
+                HandoverGate::active(),
+            )
+            .unwrap();
+        engine.state = EngineState::Running;
+        log.lock().unwrap().clear();
+        engine.check_default_devices(watchdog::CHECK_INTERVAL_MS);
+        assert_eq!(engine.stream_device(false).as_deref(), Some("Card B"));
+        assert_eq!(engine.system_defaults.output.as_deref(), Some("Card B"));
+        assert!(log.lock().unwrap().contains(&"open out Card B".to_string()));
+    }
+
+    #[test]
+    fn input_fallback_leaves_when_the_device_returns() {
+        let path =
+            std::env::temp_dir().join(format!("troubadour-engine-hold-{}.wav", std::process::id()));
+        crate::wav::write_wav(&path, 48_000, 1, &[0.25; 480]).unwrap();
+        let (mut engine, channels) = Engine::new();
+        let plugged = Arc::new(Mutex::new(vec!["Built-in Mic".to_string()]));
+        engine.set_default_device_source(Box::new(MockDevices {
+            default_input: "Built-in Mic".into(),
+            plugged: plugged.clone(),
+        }));
+        let hold = InputSource::File(path.clone());
+        assert!(
+            engine
+                .set_input_fallback(ChannelId(3), InputFallback::Silence)
+                .is_err()
+        );
+        engine
+            .set_input_fallback(PIPELINE_INPUT_CHANNEL, InputFallback::File(path.clone()))
+            .unwrap();
+        // Un repli explicite couvre l'entrée absente
+        assert_eq!(engine.missing_devices(), ["default output device"]);
+
+        let dock = Some("Dock Interface".to_string());
+        let defaults = engine.current_system_defaults();
+        let resolved = engine
+            .resolve_pipeline_input(dock.as_deref(), &defaults)
+            .unwrap();
+        assert_eq!(resolved.source, hold);
+        engine.record_pipeline_input(dock.clone(), resolved);
+        assert_eq!(engine.input_fallback(), Some(&hold));
+        assert_eq!(engine.input_device(), dock);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::InputFallback { source: Some(source), .. } if source == hold
+        )));
+
+        // Toujours absent : le fichier continue
+        assert_eq!(engine.input_fallback_switch(), None);
+
+        plugged.lock().unwrap().push("Dock Interface".into());
+        let back = engine.input_fallback_switch().unwrap();
+        assert_eq!(
+            back,
+            ResolvedInput {
+                source: InputSource::Device("Dock Interface".into()),
+                fallback: false,
+            }
+        );
+        engine.record_pipeline_input(dock, back);
+        assert_eq!(engine.input_fallback(), None);
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::InputFallback { source: None, .. }))
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn unreadable_fallback_file_is_refused_and_played_as_silence() {
+        let (mut engine, channels) = Engine::new();
+        let missing = std::env::temp_dir().join("troubadour-no-such-hold.wav");
+        assert!(
+            engine
+                .set_input_fallback(PIPELINE_INPUT_CHANNEL, InputFallback::File(missing.clone()))
+                .is_err()
+        );
+        assert_eq!(engine.pipeline_input_policy(), InputFallback::default());
+
+        // Effacé après coup (ou venu d'un profil) : le pipeline s'ouvre
+        // sur du silence au lieu d'échouer
+        let InputFeed::Looped(mut audio, rate) = engine.looped_feed(&InputSource::File(missing))
+        else {
+            panic!("a file fallback is looped");
+        };
+        assert_eq!(rate, engine.sample_rate.as_hz());
+        let mut block = [1.0; 64];
+        audio.fill(&mut block);
+        assert!(block.iter().all(|&s| s == 0.0));
+        assert!(channels.event_rx.try_iter().any(
+            |e| matches!(e, Event::Error(m) if m.starts_with("Cannot play the input fallback"))
+        ));
+    }
+
+    #[test]
+    fn following_channels_move_with_the_system_default() {
+        let (mut engine, channels) = Engine::new();
+        let defaults = Arc::new(Mutex::new(SystemDefaults {
+            input: Some("Built-in Mic".into()),
+            output: Some("Speakers".into()),
+        }));
+        engine.set_default_device_source(Box::new(MockDefaults(defaults.clone())));
+        engine
+            .reassign_channel_device(PIPELINE_INPUT_CHANNEL, DeviceAssignment::SystemDefault)
+            .unwrap();
+        let entry = &engine.journal().recent(1)[0];
+        assert_eq!(entry.after.as_deref(), Some("system default"));
+
+        let current = engine.current_system_defaults();
+        assert_eq!(
+            engine.followed_devices(&current, "Built-in Mic", "Speakers"),
+            None
+        );
+
+        // Seul le défaut de sortie change : aucun bus ne le suit
+        defaults.lock().unwrap().output = Some("Headset".into());
+        let current = engine.current_system_defaults();
+        assert_eq!(
+            engine.followed_devices(&current, "Built-in Mic", "Speakers"),
+            None
+        );
+
+        // Le défaut d'entrée change : le Mic le suit, la sortie reste
+        defaults.lock().unwrap().input = Some("Blue Yeti".into());
+        let current = engine.current_system_defaults();
+        assert_eq!(
+            engine.followed_devices(&current, "Built-in Mic", "Speakers"),
+            Some(("Blue Yeti".to_string(), "Speakers".to_string()))
+        );
+
+        // La liste des devices marque ceux vers lesquels se résout le défaut
+        channels
+            .command_tx
+            .send(Command::RequestDeviceList)
+            .unwrap();
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::DeviceList { default_input: Some(input), default_output: Some(output), .. }
+                if input == "Blue Yeti" && output == "Headset"
+        )));
+    }
+
+    #[test]
+    fn find_channels_command_returns_matches() {
+        let (mut engine, channels) = Engine::new();
+        let query = ChannelQuery {
+            name: Some("mic".into()),
+            ..Default::default()
+        };
+        channels
+            .command_tx
+            .send(Command::FindChannels(query.clone()))
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::RequestChannelCountSummary)
+            .unwrap();
+        engine.process_commands();
+
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::ChannelsFound { query: q, channels }
+                if *q == query && channels.len() == 1 && channels[0].name == "Mic"
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::ChannelCountSummary(summary) if summary.inputs == 3 && summary.outputs == 2
+        )));
+    }
+
+    #[test]
+    fn switching_locale_renames_later_channel_listings() {
+        let (mut engine, channels) = Engine::new();
+        let names = |engine: &mut Engine| {
+            channels
+                .command_tx
+                .send(Command::FindChannels(ChannelQuery::default()))
+                .unwrap();
+            engine.process_commands();
+            channels
+                .event_rx
+                .try_iter()
+                .find_map(|e| match e {
+                    Event::ChannelsFound { channels, .. } => {
+                        Some(channels.into_iter().map(|c| c.name).collect::<Vec<_>>())
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+        assert_eq!(names(&mut engine)[0], "Mic");
+
+        channels
+            .command_tx
+            .send(Command::SetLocale {
+                locale: "fr".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(matches!(
+            channels.event_rx.try_recv(),
+            Ok(Event::LocaleChanged { locale }) if locale == "fr"
+        ));
+        assert_eq!(names(&mut engine)[..2], ["Micro", "Bureau"]);
+        // Le mixer garde les noms stockés
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().name, "Mic");
+
+        // Langue inconnue : erreur, le français reste
+        channels
+            .command_tx
+            .send(Command::SetLocale {
+                locale: "xx".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert!(matches!(channels.event_rx.try_recv(), Ok(Event::Error(_))));
+        assert_eq!(engine.locale().code(), "fr");
+    }
+
+    #[test]
+    fn session_stats_collect_callback_maximums_until_reset() {
+        let (mut engine, channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..4 {
+            harness.run_block();
+        }
+
+        let request = |reset| {
+            channels
+                .command_tx
+                .send(Command::RequestSessionStats { reset })
+                .unwrap();
+        };
+        let received = |engine: &mut Engine| {
+            engine.process_commands();
+            channels
+                .event_rx
+                .try_iter()
+                .find_map(|e| match e {
+                    Event::SessionStats(stats) => Some(stats),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        request(true);
+        let stats = received(&mut engine);
+        // Le Mic joue sur Headphones (bus par défaut du device de sortie)
+        assert_eq!(stats.bus_peaks.len(), 1);
+        assert_eq!(stats.bus_peaks[0].bus, ChannelId(3));
+        assert!(stats.bus_peaks[0].peak > 0.0);
+        assert!(stats.peak_dsp_load > 0.0);
+        assert_eq!((stats.uptime_seconds, stats.reconnects), (0, 0));
+
+        // Remis à zéro après l'envoi
+        request(false);
+        let stats = received(&mut engine);
+        assert!(stats.bus_peaks.is_empty());
+        assert_eq!(stats.peak_dsp_load, 0.0);
+    }
+
+    #[test]
+    fn stalled_stream_is_reported_and_counted() {
+        let (mut engine, channels) = Engine::new();
+        engine.configure_watchdog(&WatchdogConfig {
+            auto_restart: false,
+            ..WatchdogConfig::default()
+        });
+        // Heartbeat simulé : aucun device réel dans les tests
+        let heartbeat = Heartbeat::default();
+        engine.state = EngineState::Running;
+        engine
+            .watchdog
+            .watch(vec![("Blue Yeti".into(), true, heartbeat.clone())], 0);
+
+        heartbeat.beat_at(500);
+        engine.check_watchdog(1000);
+        engine.check_watchdog(2000);
+        assert!(channels.event_rx.try_iter().next().is_none());
+
+        engine.check_watchdog(3000);
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(matches!(
+            events.as_slice(),
+            [Event::StreamStalled { device_name, is_input: true, silent_ms: 2500 }]
+                if device_name == "Blue Yeti"
+        ));
+        assert_eq!(engine.audio_stats().stream_stalls, 1);
+        assert_eq!(engine.audio_stats().stream_restarts, 0);
+        assert!(!engine.watchdog.health(3000)[0].healthy);
+
+        heartbeat.beat_at(4200);
+        engine.check_watchdog(5000);
+        assert!(matches!(
+            channels.event_rx.try_recv(),
+            Ok(Event::StreamRecovered { is_input: true, .. })
+        ));
+
+        // Moteur arrêté : plus rien à surveiller
+        engine.stop();
+        assert!(engine.stream_health().is_empty());
+    }
+
+    #[test]
+    fn handover_switches_after_preroll_then_closes_the_old_pipeline() {
+        let (mut engine, channels) = Engine::new();
+        engine.set_preroll_blocks(4);
+        let log = start_on_fake_devices(&mut engine, "Mic", "Card A");
+        // Réserve de l'ancien constituée : il est entendu
+        for _ in 0..4 {
+            run_fake_period(&engine.streams, "Card A");
+        }
+        let old_gate = engine.pipeline_gate.clone();
+        let mic = InputSource::Device("Mic".into());
+        engine.switch_pipeline(&mic, "Card B").unwrap();
+        fn old_streams(engine: &Engine) -> &StreamSet<PipelineStream> {
+            &engine.handover.as_ref().unwrap().old_streams
+        }
+        assert!(run_fake_period(old_streams(&engine), "Card A") > 0.01);
+
+        // Nouvelle bascule pendant le pré-roll : l'ancien, entendu, reste
+        // en place ; seul le pipeline en attente est remplacé
+        engine.switch_pipeline(&mic, "Card C").unwrap();
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "open in Mic",
+                "open out Card B",
+                "close in Mic",
+                "close out Card B",
+                "open in Mic",
+                "open out Card C",
+            ]
+        );
+        assert!(Arc::ptr_eq(
+            &engine.handover.as_ref().unwrap().old_gate,
+            &old_gate
+        ));
+        let new_gate = engine.pipeline_gate.clone();
+        log.lock().unwrap().clear();
+        channels.event_rx.try_iter().count();
+
+        // Pré-roll : l'ancien seul entendu ; un seul pipeline publie
+        let mut periods = 0;
+        while !new_gate.is_active() {
+            assert!(run_fake_period(old_streams(&engine), "Card A") > 0.01);
+            assert_eq!(run_fake_period(&engine.streams, "Card C"), 0.0);
+            assert_eq!(level_updates(&channels), 1);
+            engine.check_handover(watchdog::monotonic_ms());
+            periods += 1;
+            assert!(periods < 20, "pre-roll never completed");
+        }
+        assert!(!old_gate.is_active());
+
+        // Fondu croisé, puis l'ancien fermé une fois muet
+        while engine.handover.is_some() {
+            run_fake_period(old_streams(&engine), "Card A");
+            run_fake_period(&engine.streams, "Card C");
+            assert_eq!(level_updates(&channels), 1);
+            engine.check_handover(watchdog::monotonic_ms());
+            periods += 1;
+            assert!(periods < 30, "old pipeline never went silent");
+        }
+        assert_eq!(*log.lock().unwrap(), ["close in Mic", "close out Card A"]);
+        assert!(run_fake_period(&engine.streams, "Card C") > 0.01);
+    }
+
+    #[test]
+    fn handover_is_forced_when_a_pipeline_does_not_deliver() {
+        let (mut engine, _channels) = Engine::new();
+        engine.set_preroll_blocks(4);
+        start_on_fake_devices(&mut engine, "Mic", "Card A");
+        engine
+            .switch_pipeline(&InputSource::Device("Mic".into()), "Card B")
+            .unwrap();
+        // Aucun callback appelé : les délais tranchent
+        let started = engine.handover.as_ref().unwrap().started_ms;
+        engine.check_handover(started + HANDOVER_TIMEOUT_MS);
+        assert!(engine.pipeline_gate.is_active());
+        engine.check_handover(started + 2 * HANDOVER_TIMEOUT_MS);
+        assert!(engine.handover.is_none());
+    }
+
+    #[test]
+    fn commands_during_a_handover_reach_both_pipelines() {
+        let (mut engine, channels) = Engine::new();
+        engine.set_preroll_blocks(4);
+        start_on_fake_devices(&mut engine, "Mic", "Card A");
+        let mut old = InputHarness::new(&engine);
+        let send = |command| {
+            channels.command_tx.send(command).unwrap();
+        };
+        // Pas encore lue par l'ancien pipeline quand le nouveau s'ouvre
+        send(Command::SetVolume {
+            channel: ChannelId(0),
+            level: 0.5,
+        });
+        engine.process_commands();
+
+        engine
+            .switch_pipeline(&InputSource::Device("Mic".into()), "Card B")
+            .unwrap();
+        let mut new = InputHarness::new(&engine);
+        send(Command::SetMute {
+            channel: ChannelId(0),
+            muted: true,
+        });
+        engine.process_commands();
+        // Pré-roll du nouveau : ses blocs livrés ouvrent la bascule
+        let new_gate = engine.pipeline_gate.clone();
+        for _ in 0..20 {
+            if new_gate.is_active() {
+                break;
+            }
+            old.run_block();
+            new.run_block();
+            run_fake_period(&engine.streams, "Card B");
+            engine.check_handover(watchdog::monotonic_ms());
+        }
+        assert!(new_gate.is_active());
+        send(Command::SetDelay {
+            channel: ChannelId(0),
+            delay_ms: 20.0,
+        });
+        engine.process_commands();
+
+        let expected = engine.shared_state.snapshot();
+        for harness in [&mut old, &mut new] {
+            let state = harness.processor.runtime.current();
+            let mic = state.channel(ChannelId(0)).unwrap();
+            assert!(mic.muted);
+            assert_eq!(mic.volume, 0.5);
+            assert_eq!(mic.delay_ms, 20.0);
+            assert_eq!(state.gain(ChannelId(0)), expected.gain(ChannelId(0)));
+        }
+    }
+
+    #[test]
+    fn audio_stats_are_reported_before_any_stream_opens() {
+        let (mut engine, channels) = Engine::new();
+        engine.set_target_output_latency_blocks(0);
+        assert_eq!(engine.target_latency_blocks, 1);
+
+        channels
+            .command_tx
+            .send(Command::RequestAudioStats)
+            .unwrap();
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::AudioStats(stats) if stats == AudioStats::default()))
+        );
+    }
+
+    #[test]
+    fn recovery_snapshot_survives_crash_and_restores() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-engine-recovery-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let recovery_path = dir.join("recovery.toml");
+        let config_path = dir.join("config.toml");
+        let config = RecoveryConfig {
+            enabled: true,
+            interval_seconds: 0,
+        };
+
+        // Session 1 : une modification, puis "crash" (pas de Shutdown)
+        {
+            let (mut engine, channels) = Engine::new();
+            engine.configure_recovery(&config, recovery_path.clone());
+            channels
+                .command_tx
+                .send(Command::SetVolume {
+                    channel: ChannelId(1),
+                    level: 0.25,
+                })
+                .unwrap();
+            engine.process_commands();
+        }
+
+        // Session 2 : le démarrage détecte l'instantané
+        let loaded = AppConfig::load_with_recovery(&config_path, &recovery_path).unwrap();
+        assert!(loaded.recovery_pending);
+
+        let (mut engine, channels) = Engine::new();
+        engine.configure_recovery(&loaded.config.recovery, recovery_path.clone());
+        assert_eq!(engine.mixer().channel(ChannelId(1)).unwrap().volume, 1.0);
+
+        channels
+            .command_tx
+            .send(Command::RestoreRecoverySnapshot)
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.mixer().channel(ChannelId(1)).unwrap().volume, 0.25);
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::RecoveryRestored { .. }))
+        );
+
+        // Arrêt propre → plus rien à proposer au prochain démarrage
+        channels.command_tx.send(Command::Shutdown).unwrap();
+        engine.process_commands();
+        assert!(!recovery_path.exists());
+        let loaded = AppConfig::load_with_recovery(&config_path, &recovery_path).unwrap();
+        assert!(!loaded.recovery_pending);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn recording_analysis_runs_off_the_command_thread() {
+        let path = std::env::temp_dir().join(format!(
+            "troubadour-engine-analysis-{}.wav",
+            std::process::id()
+        ));
+        crate::wav::write_wav(&path, 48_000, 1, &[0.5; 48_000]).unwrap();
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::AnalyzeRecording { path: path.clone() })
+            .unwrap();
+        engine.process_commands();
+
+        let mut progress = Vec::new();
+        let report = loop {
+            match channels
+                .event_rx
+                .recv_timeout(std::time::Duration::from_secs(10))
+                .expect("analysis finished")
+            {
+                Event::RecordingProgress { progress: p, .. } => progress.push(p),
+                Event::RecordingAnalyzed { report, .. } => break report,
+                _ => {}
+            }
+        };
+        assert_eq!(report.frames, 48_000);
+        assert!((report.sample_peak_dbfs.unwrap() + 6.02).abs() < 0.01);
+        assert_eq!(progress.last(), Some(&1.0));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn ui_state_goes_through_the_recovery_snapshot() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-engine-ui-state-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let recovery_path = dir.join("recovery.toml");
+        let config = RecoveryConfig {
+            enabled: true,
+            interval_seconds: 0,
+        };
+        // Clés triées : c'est l'ordre dans lequel l'état est rendu
+        let panel = r#"{"collapsed":[1,2],"panel":"effects"}"#;
+
+        {
+            let (mut engine, channels) = Engine::new();
+            engine.configure_recovery(&config, recovery_path.clone());
+            let too_deep = format!("{}0{}", r#"{"a":"#.repeat(40), "}".repeat(40));
+            for json in [panel.to_string(), too_deep] {
+                channels
+                    .command_tx
+                    .send(Command::SetUiState { json })
+                    .unwrap();
+            }
+            channels.command_tx.send(Command::RequestUiState).unwrap();
+            engine.process_commands();
+            let events: Vec<Event> = channels.event_rx.try_iter().collect();
+            // Le second état est refusé, le premier reste
+            assert!(
+                events
+                    .iter()
+                    .any(|e| matches!(e, Event::Error(m) if m.contains("UI state")))
+            );
+            assert!(
+                events
+                    .iter()
+                    .any(|e| matches!(e, Event::UiState { json } if json == panel))
+            );
+        }
+
+        // Crash : l'état de l'UI revient avec l'instantané
+        let (mut engine, _channels) = Engine::new();
+        engine.configure_recovery(&config, recovery_path);
+        assert!(engine.ui_state().is_empty());
+        engine.restore_recovery_snapshot().unwrap();
+        assert_eq!(engine.ui_state().to_json(), panel);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn restore_without_snapshot_reports_error() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::RestoreRecoverySnapshot)
+            .unwrap();
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(_)))
+        );
+    }
+
+    #[test]
+    fn apply_profile_replaces_mixer_and_effects() {
+        let (mut engine, _channels) = Engine::new();
+        let mut profile = Profile::streaming();
+        profile.mixer.channels[0].volume = 0.3;
+
+        engine.apply_profile(&profile).unwrap();
+
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.3);
+        assert!(engine.channel_effects(ChannelId(0)).noise_gate.enabled);
+        assert_eq!(engine.journal().recent(1)[0].action, "ApplyProfile");
+
+        let captured = engine.to_profile("Captured");
+        assert_eq!(captured.name, "Captured");
+        assert_eq!(captured.mixer.channels.len(), profile.mixer.channels.len());
+    }
+
+    fn auto_snapshot_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-engine-auto-snapshots-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn preset_load_is_undone_by_restoring_its_auto_snapshot() {
+        let dir = auto_snapshot_dir("restore");
+        let (mut engine, channels) = Engine::new();
+        engine.configure_auto_snapshots(&AutoSnapshotConfig::default(), dir.clone());
+        for command in [
+            Command::SetVolume {
+                channel: ChannelId(0),
+                level: 0.42,
+            },
+            Command::SetMute {
+                channel: ChannelId(1),
+                muted: true,
+            },
+        ] {
+            channels.command_tx.send(command).unwrap();
+        }
+        engine.process_commands();
+        let before = engine.mixer().to_config();
+
+        engine.apply_profile(&Profile::streaming()).unwrap();
+        assert!(!before.diff(&engine.mixer().to_config()).is_empty());
+
+        channels
+            .command_tx
+            .send(Command::ListAutoSnapshots)
+            .unwrap();
+        engine.process_commands();
+        let list = channels
+            .event_rx
+            .try_iter()
+            .find_map(|e| match e {
+                Event::AutoSnapshotList(list) => Some(list),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].reason, "ApplyProfile");
+
+        channels
+            .command_tx
+            .send(Command::RestoreAutoSnapshot { id: list[0].id })
+            .unwrap();
+        engine.process_commands();
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::AutoSnapshotRestored(info) if info == list[0]))
+        );
+        assert!(before.diff(&engine.mixer().to_config()).is_empty());
+        // Le preset remplacé par la restauration reste récupérable
+        let list = engine.auto_snapshot_list();
+        assert_eq!(list[0].reason, "RestoreAutoSnapshot");
+        assert_eq!(list.len(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_auto_snapshot_blocks_the_load_only_in_strict_mode() {
+        let dir = auto_snapshot_dir("strict");
+        std::fs::create_dir_all(&dir).unwrap();
+        // Un fichier à la place du dossier : l'écriture échoue
+        let blocked = dir.join("file");
+        std::fs::write(&blocked, "").unwrap();
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetVolume {
+                channel: ChannelId(0),
+                level: 0.42,
+            })
+            .unwrap();
+        engine.process_commands();
+        let before = engine.mixer().to_config();
+
+        let strict = AutoSnapshotConfig {
+            strict: true,
+            ..AutoSnapshotConfig::default()
+        };
+        engine.configure_auto_snapshots(&strict, blocked.join("autosnapshots"));
+        assert!(engine.apply_profile(&Profile::streaming()).is_err());
+        assert!(
+            engine
+                .apply_setup_proposal(&engine.setup_proposal())
+                .is_err()
+        );
+        assert!(before.diff(&engine.mixer().to_config()).is_empty());
+
+        engine.configure_auto_snapshots(
+            &AutoSnapshotConfig::default(),
+            blocked.join("autosnapshots"),
+        );
+        engine.apply_profile(&Profile::streaming()).unwrap();
+        assert!(!before.diff(&engine.mixer().to_config()).is_empty());
+        assert!(channels.event_rx.try_iter().any(
+            |e| matches!(e, Event::Error(message) if message.contains("Cannot save auto snapshot"))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn laptop_profiles() -> BTreeMap<String, MachineProfile> {
+        let laptop = MachineProfile {
+            audio_host: Some("NoSuchHost".into()),
+            channel_devices: vec![ChannelDevice {
+                channel: ChannelId(0),
+                device: "Built-in Mic".into(),
+            }],
+            ..MachineProfile::default()
+        };
+        BTreeMap::from([("laptop".to_string(), laptop)])
+    }
+
+    #[test]
+    fn machine_profile_devices_win_and_stay_out_of_mixer_profiles() {
+        let (mut engine, _channels) = Engine::new();
+        engine.configure_machine_profiles(laptop_profiles(), "laptop");
+
+        let mut profile = Profile::streaming();
+        profile.mixer.channels[0].device_name =
+            DeviceAssignment::Specific("Desktop Interface".into());
+        profile.input_device = Some("Desktop Interface".into());
+        engine.apply_profile(&profile).unwrap();
+        assert_eq!(
+            engine
+                .mixer()
+                .channel(ChannelId(0))
+                .unwrap()
+                .device_name
+                .specific(),
+            Some("Built-in Mic")
+        );
+
+        let captured = engine.to_profile("Shared");
+        assert!(
+            captured
+                .mixer
+                .channels
+                .iter()
+                .all(|c| c.device_name.is_none())
+        );
+        assert!(captured.input_device.is_none());
+    }
+
+    #[test]
+    fn saving_touches_only_the_active_machine_profile() {
+        let (mut engine, channels) = Engine::new();
+        let profiles = laptop_profiles();
+        engine.configure_machine_profiles(profiles.clone(), "desktop");
+        engine.mixer.channel_mut(ChannelId(4)).unwrap().device_name =
+            DeviceAssignment::Specific("HD 600".into());
+
+        let saved = engine.machine_profiles();
+        assert_eq!(saved["laptop"], profiles["laptop"]);
+        assert_eq!(
+            saved["desktop"].channel_devices,
+            [ChannelDevice {
+                channel: ChannelId(4),
+                device: "HD 600".into(),
+            }]
+        );
+
+        // Bascule vers le portable : ses devices remplacent ceux du bureau
+        channels
+            .command_tx
+            .send(Command::SelectMachineProfile {
+                name: "laptop".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(engine.machine(), Some("laptop"));
+        assert!(
+            engine
+                .mixer()
+                .channel(ChannelId(4))
+                .unwrap()
+                .device_name
+                .is_none()
+        );
+        assert_eq!(engine.journal().recent(1)[0].action, "SelectMachineProfile");
+        match channels.event_rx.try_recv() {
+            Ok(Event::MachineProfile {
+                name,
+                profile,
+                available,
+            }) => {
+                assert_eq!(name, "laptop");
+                assert_eq!(profile, profiles["laptop"]);
+                assert_eq!(available, ["desktop", "laptop"]);
+            }
+            other => panic!("expected MachineProfile, got {other:?}"),
+        }
+        // Le bureau a gardé ses devices
+        assert_eq!(engine.machine_profiles()["desktop"], saved["desktop"]);
+    }
+
+    #[test]
+    fn legacy_mixer_devices_migrate_into_machine_profile() {
+        let (mut engine, _channels) = Engine::new();
+        let mut legacy = Profile::default_profile();
+        legacy.mixer.channels[0].device_name = DeviceAssignment::Specific("Blue Yeti".into());
+        engine.apply_profile(&legacy).unwrap();
+
+        engine.configure_machine_profiles(BTreeMap::new(), "desktop");
+        let saved = engine.machine_profiles();
+        assert_eq!(saved["desktop"].channel_devices[0].device, "Blue Yeti");
+    }
+
+    #[test]
+    fn preset_devices_form_one_topology_applied_after_the_swap() {
+        let (mut engine, _channels) = Engine::new();
+        let mut profile = Profile::default_profile();
+        profile.mixer.channels[1].device_name = DeviceAssignment::Specific("USB Mic".into());
+        profile.mixer.channels[3].device_name = DeviceAssignment::Specific("Headset".into());
+
+        // Arrêté : le mixer change, aucun stream n'est ouvert
+        let applied = engine.apply_profile(&profile).unwrap();
+        assert!(applied.streams.is_empty());
+        let topology = engine.stream_topology();
+        assert_eq!(
+            topology.keys().cloned().collect::<Vec<_>>(),
+            [
+                StreamKey::new("Headset", false),
+                StreamKey::new("USB Mic", true)
+            ]
+        );
+        assert_eq!(topology[&StreamKey::new("USB Mic", true)], [ChannelId(1)]);
+        assert!(engine.reconcile(&topology).unwrap().is_empty());
+    }
+
+    #[test]
+    fn preset_reopens_only_the_output_its_played_bus_moves_to() {
+        let (mut engine, _channels) = Engine::new();
+        let mut profile = Profile::default_profile();
+        let assign = |profile: &mut Profile, channel: usize, device: &str| {
+            profile.mixer.channels[channel].device_name = DeviceAssignment::Specific(device.into());
+        };
+        assign(&mut profile, 0, "Mic");
+        assign(&mut profile, 1, "Line In");
+        assign(&mut profile, 3, "Card A");
+        assign(&mut profile, 4, "Speakers");
+        engine.set_default_device_source(Box::new(MockDevices {
+            default_input: "Mic".into(),
+            plugged: Arc::new(Mutex::new(vec!["Mic".into(), "Desk Mic".into()])),
+        }));
+        engine.apply_profile(&profile).unwrap();
+        let log = start_on_fake_devices(&mut engine, "Mic", "Card A");
+
+        // ch1 hors du pipeline : aucun stream touché
+        assign(&mut profile, 1, "USB Mic");
+        assert!(engine.apply_profile(&profile).unwrap().streams.is_empty());
+        assert!(log.lock().unwrap().is_empty());
+
+        // A1 change de device : sa sortie suit, l'entrée reste ouverte
+        assign(&mut profile, 1, "Line In");
+        assign(&mut profile, 3, "Card B");
+        let applied = engine.apply_profile(&profile).unwrap();
+        assert_eq!(
+            applied.streams,
+            StreamChanges {
+                opened: vec![StreamKey::new("Card B", false)],
+                closed: vec![StreamKey::new("Card A", false)],
+            }
+        );
+        assert_eq!(
+            *log.lock().unwrap(),
+            ["close out Card A", "open out Card B"]
+        );
+        // Réserve de la nouvelle sortie constituée : l'entrée gardée s'y entend
+        for _ in 0..3 {
+            run_fake_period(&engine.streams, "Card B");
+        }
+        assert!(run_fake_period(&engine.streams, "Card B") > 0.01);
+        assert_eq!(engine.stream_device(false).as_deref(), Some("Card B"));
+
+        // Le Mic change de device : seule l'entrée est rouverte
+        log.lock().unwrap().clear();
+        assign(&mut profile, 0, "Desk Mic");
+        let applied = engine.apply_profile(&profile).unwrap();
+        assert_eq!(
+            applied.streams,
+            StreamChanges {
+                opened: vec![StreamKey::new("Desk Mic", true)],
+                closed: vec![StreamKey::new("Mic", true)],
+            }
+        );
+        assert_eq!(*log.lock().unwrap(), ["close in Mic", "open in Desk Mic"]);
+    }
+
+    #[test]
+    fn preset_over_the_channel_limit_reports_skipped_channels() {
+        let (mut engine, channels) = Engine::new();
+        engine.set_max_channels(4);
+        let applied = engine.apply_profile(&Profile::default_profile()).unwrap();
+
+        assert_eq!(applied.skipped, [ChannelId(4)]);
+        assert_eq!(engine.mixer().channel_count(), 4);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::Error(msg) if msg.contains("channel(s) 4 skipped")
+        )));
+
+        // Mixer plein : un ajout est refusé
+        channels
+            .command_tx
+            .send(Command::AddChannel(ChannelConfig::input(7, "Guitar")))
+            .unwrap();
+        engine.process_commands();
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::Error(msg) if msg.contains("limited to 4")
+        )));
+
+        // Tout le diff d'un preset plus large : les canaux en trop aussi
+        let mut wide = Profile::default_profile();
+        wide.name = "Wide".into();
+        wide.mixer.channels.push(ChannelConfig::input(9, "Guest"));
+        let diff = engine.diff_profile(&wide);
+        assert!(
+            engine
+                .apply_profile_selection(&wide, &diff.entries)
+                .unwrap()
+                > 0
+        );
+        assert_eq!(engine.mixer().channel_count(), 4);
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::Error(msg) if msg.starts_with("Preset \"Wide\": channel(s) 4, 9 skipped")
+        )));
+    }
+
+    #[test]
+    fn setup_proposal_fills_the_machine_profile() {
+        let (mut engine, channels) = Engine::new();
+        engine.configure_machine_profiles(BTreeMap::new(), "desktop");
+        let proposal = SetupProposal {
+            profile: MachineProfile {
+                input_device: Some("Blue Yeti".into()),
+                output_device: Some("HD 600".into()),
+                channel_devices: vec![
+                    ChannelDevice {
+                        channel: ChannelId(0),
+                        device: "Blue Yeti".into(),
+                    },
+                    ChannelDevice {
+                        channel: ChannelId(3),
+                        device: "HD 600".into(),
+                    },
+                ],
+                ..MachineProfile::default()
+            },
+            summary: Vec::new(),
+        };
+        channels
+            .command_tx
+            .send(Command::ApplySetupProposal(proposal.clone()))
+            .unwrap();
+        engine.process_commands();
+
+        let mic = engine.mixer().channel(ChannelId(0)).unwrap();
+        assert_eq!(mic.device_name.specific(), Some("Blue Yeti"));
+        let saved = &engine.machine_profiles()["desktop"];
+        assert_eq!(saved.input_device.as_deref(), Some("Blue Yeti"));
+        assert_eq!(saved.output_device.as_deref(), Some("HD 600"));
+        assert_eq!(saved.channel_devices, proposal.profile.channel_devices);
+        assert_eq!(engine.journal().recent(1)[0].action, "ApplySetupProposal");
+    }
+
+    #[test]
+    fn external_config_import_saves_a_new_profile_each_time() {
+        let dir = std::env::temp_dir().join(format!("troubadour-import-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/import/voicemeeter.xml");
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_profiles_dir(dir.clone());
+        for _ in 0..2 {
+            channels
+                .command_tx
+                .send(Command::ImportExternalConfig {
+                    path: fixture.clone(),
+                    format: ImportFormat::VoiceMeeter,
+                })
+                .unwrap();
+        }
+        channels
+            .command_tx
+            .send(Command::ImportExternalConfig {
+                path: fixture.clone(),
+                format: ImportFormat::Obs,
+            })
+            .unwrap();
+        engine.process_commands();
+
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        let presets: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::ExternalConfigImported(report) => Some(report.preset.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(presets, ["Imported VoiceMeeter", "Imported VoiceMeeter 2"]);
+        // Du XML lu comme une scène OBS
+        assert!(matches!(events.last(), Some(Event::Error(msg)) if msg.contains("OBS")));
+
+        let imported = engine.find_profile("Imported VoiceMeeter 2").unwrap();
+        assert_eq!(imported.mixer.channels.len(), 6);
+        // Sauvé, pas appliqué
+        assert_eq!(engine.mixer().channel_count(), 5);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dropped_preset_files_load_and_save_like_named_presets() {
+        let dir = std::env::temp_dir().join(format!("troubadour-drop-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dropped = dir.join("downloads/late.show.toml");
+        let mut profile = Profile::music();
+        profile.name = "Late Show".into();
+        profile
+            .mixer
+            .channels
+            .push(ChannelConfig::input(9, "Guitar"));
+        profile
+            .bus_effects
+            .insert("Stream".into(), EffectsPreset::clean());
+        profile.save(&dropped).unwrap();
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_profiles_dir(dir.join("profiles"));
+        engine.set_max_channels(5);
+        let malformed = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../troubadour-shared/fixtures/presets/malformed.toml");
+        for path in [dropped.clone(), malformed] {
+            channels
+                .command_tx
+                .send(Command::LoadPresetFile { path })
+                .unwrap();
+        }
+        engine.process_commands();
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        let Some(Event::PresetFileLoaded { name, warnings }) = events
+            .iter()
+            .find(|e| matches!(e, Event::PresetFileLoaded { .. }))
+        else {
+            panic!("no PresetFileLoaded in {events:?}");
+        };
+        assert_eq!(name, "Late Show");
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings[0].contains("\"Guitar\" skipped"));
+        assert!(warnings[1].contains("\"Stream\""));
+        assert_eq!(engine.mixer().channel_count(), 5);
+        assert!(matches!(events.last(), Some(Event::Error(msg)) if msg.contains("parse")));
+
+        let saved = engine.save_dropped_profile(&dropped, None, false).unwrap();
+        assert_eq!(saved, "late_show");
+        assert_eq!(
+            engine
+                .find_profile("late_show")
+                .unwrap()
+                .mixer
+                .channels
+                .len(),
+            6
+        );
+        for (name, overwrite) in [(None, false), (Some("gaming"), true)] {
+            assert!(matches!(
+                engine.save_dropped_profile(&dropped, name, overwrite),
+                Err(TroubadourError::PresetExists(_))
+            ));
+        }
+        assert!(engine.save_dropped_profile(&dropped, None, true).is_ok());
+        assert!(dir.join("profiles/late_show.toml.bak").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failures_return_specific_errors() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-bad-preset-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("Broken.toml"), "name = ").unwrap();
+
+        let (mut engine, _channels) = Engine::new();
+        engine.set_profiles_dir(dir.clone());
+        assert!(matches!(
+            engine.reassign_channel_device(ChannelId(9), DeviceAssignment::None),
+            Err(TroubadourError::Mixer(MixerError::ChannelNotFound(9)))
+        ));
+        assert_eq!(
+            engine.reset_loudness(ChannelId(4)),
+            Err(MixerError::InvalidParameter(
+                "loudness of bus 4 is not metered".into()
+            ))
+        );
+        assert!(matches!(
+            engine.find_profile("Missing"),
+            Err(TroubadourError::PresetNotFound(name)) if name == "Missing"
+        ));
+        assert!(matches!(
+            engine.find_profile("Broken"),
+            Err(TroubadourError::ConfigError(msg)) if msg.starts_with("Preset \"Broken\"")
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unknown_preset_keys_are_reported() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-typo-preset-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("Typo.toml");
+        Profile::music().save(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, format!("volumne = 3\n{text}")).unwrap();
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_profiles_dir(dir.clone());
+        engine.find_profile("Typo").unwrap();
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::PresetWarnings { name, warnings }
+                if name == "Typo" && warnings == &["unknown key volumne ignored"]
+        )));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn diff_and_apply_preset_selection_by_name() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-preset-diff-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut saved = Profile::default_profile();
+        saved.name = "Quiet".into();
+        saved.mixer.channels[0].volume = 0.25;
+        saved.mixer.channels[1].muted = true;
+        saved.save(&dir.join("Quiet.toml")).unwrap();
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_profiles_dir(dir.clone());
+        let send = |cmd| {
+            channels.command_tx.send(cmd).unwrap();
+        };
+
+        send(Command::DiffPreset {
+            name: "Quiet".into(),
+        });
+        engine.process_commands();
+        let diff = match channels.event_rx.try_recv() {
+            Ok(Event::PresetDiff { name, diff }) => {
+                assert_eq!(name, "Quiet");
+                diff
+            }
+            other => panic!("expected PresetDiff, got {other:?}"),
+        };
+        assert_eq!(diff.len(), 2);
+
+        // Seul le volume est retenu
+        send(Command::ApplyPresetSelection {
+            name: "Quiet".into(),
+            selection: vec![diff.entries[0].clone()],
+        });
+        engine.process_commands();
+        assert!(matches!(
+            channels.event_rx.try_recv(),
+            Ok(Event::PresetSelectionApplied { applied: 1, .. })
+        ));
+        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.25);
+        assert!(!engine.mixer().channel(ChannelId(1)).unwrap().muted);
+        assert_eq!(engine.journal().recent(1)[0].action, "ApplyPresetSelection");
+        assert_eq!(engine.diff_profile(&saved).len(), 1);
+
+        send(Command::DiffPreset {
+            name: "Missing".into(),
+        });
+        engine.process_commands();
+        assert!(matches!(channels.event_rx.try_recv(), Ok(Event::Error(_))));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn preset_compatibility_is_checked_without_loading() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-preset-compat-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut saved = Profile::default_profile();
+        saved.name = "Elsewhere".into();
+        saved.mixer.channels[0].device_name =
+            DeviceAssignment::Specific("Troubadour Test Mic 1195".into());
+        saved.save(&dir.join("Elsewhere.toml")).unwrap();
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_profiles_dir(dir.clone());
+        channels
+            .command_tx
+            .send(Command::CheckPresetCompatibility {
+                name: "Elsewhere".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+        let report = match channels.event_rx.try_recv() {
+            Ok(Event::PresetCompatibility(report)) => report,
+            other => panic!("expected PresetCompatibility, got {other:?}"),
+        };
+        assert_eq!(report.preset, "Elsewhere");
+        let missing: Vec<_> = report.missing().map(|d| d.wanted.as_str()).collect();
+        assert_eq!(missing, ["Troubadour Test Mic 1195"]);
+        // Rien n'a été chargé
+        assert_eq!(
+            engine.mixer().channel(ChannelId(0)).unwrap().device_name,
+            DeviceAssignment::None
+        );
+        assert!(engine.check_preset_compatibility("Missing").is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn set_device_channels_reaches_snapshot() {
+        let (mut engine, channels) = Engine::new();
+        channels
+            .command_tx
+            .send(Command::SetDeviceChannels {
+                channel: ChannelId(0),
+                offset: 2,
+                count: 2,
+            })
+            .unwrap();
+        engine.process_commands();
+
+        let mic = engine.mixer().channel(ChannelId(0)).unwrap();
+        assert_eq!(
+            (mic.device_channel_offset, mic.device_channel_count),
+            (2, 2)
+        );
+        assert_eq!(
+            engine.shared_state.snapshot().device_slice(ChannelId(0)),
+            ChannelSlice::new(2, 2)
+        );
+        let entry = &engine.journal().recent(1)[0];
+        assert_eq!(entry.action, "SetDeviceChannels");
+        assert_eq!(entry.after.as_deref(), Some("Inputs 3-4"));
+
+        // Une autre entrée : refusé, rien ne change
+        channels
+            .command_tx
+            .send(Command::SetDeviceChannels {
+                channel: ChannelId(1),
+                offset: 4,
+                count: 2,
+            })
+            .unwrap();
+        engine.process_commands();
+        assert_eq!(
+            engine
+                .mixer()
+                .channel(ChannelId(1))
+                .unwrap()
+                .device_channel_count,
+            0
+        );
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("only the pipeline input")))
+        );
+    }
+
+    #[test]
+    fn effects_preset_applies_to_running_chain() {
+        let dir = std::env::temp_dir().join(format!("troubadour-engine-fx-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_effects_preset_manager(EffectsPresetManager::new(&dir));
+        let chain_handle = engine.shared_dsp_chain();
+        assert_eq!(chain_handle.lock().unwrap().len(), 4); // default_mic_chain
+
+        // Capturer la chaîne du Mic puis la sauver
+        let mut vocal = EffectsPreset::streaming();
+        vocal.compressor.ratio = 6.0;
+        channels
+            .command_tx
+            .send(Command::SetChannelEffects {
+                channel: ChannelId(0),
+                preset: vocal,
+            })
+            .unwrap();
+        // Deux fois : la seconde, sans `overwrite`, demande confirmation
+        for _ in 0..2 {
+            channels
+                .command_tx
+                .send(Command::SaveEffectsPreset {
+                    channel: ChannelId(0),
+                    name: "My Vocal Chain".into(),
+                    overwrite: false,
+                })
+                .unwrap();
+        }
+        // Revenir à une chaîne clean, puis réappliquer le preset
+        channels
+            .command_tx
+            .send(Command::SetChannelEffects {
+                channel: ChannelId(0),
+                preset: EffectsPreset::clean(),
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::ApplyEffectsPreset {
+                channel: ChannelId(0),
+                name: "My Vocal Chain".into(),
+            })
+            .unwrap();
+        engine.process_commands();
+
+        // Même Arc (pas de redémarrage), contenu remplacé
+        assert!(Arc::ptr_eq(&chain_handle, &engine.shared_dsp_chain()));
+        assert_eq!(chain_handle.lock().unwrap().len(), 6); // from_preset
+        assert_eq!(engine.channel_effects(ChannelId(0)).compressor.ratio, 6.0);
+
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::EffectsPresetList(names) if names == &vec!["My Vocal Chain".to_string()]
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::EffectsPresetApplied { channel: ChannelId(0), name } if name == "My Vocal Chain"
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::EffectsPresetExists { name } if name == "My Vocal Chain"
+        )));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn out_of_range_effect_commands_are_rejected_unless_clamping() {
+        let (mut engine, channels) = Engine::new();
+        let mut slow = EffectsPreset::default_preset();
+        slow.compressor.attack = 5.0;
+        let send = |cmd| channels.command_tx.send(cmd).unwrap();
+        send(Command::SetChannelEffects {
+            channel: ChannelId(0),
+            preset: slow.clone(),
+        });
+        send(Command::AutomateEffectParam {
+            channel: ChannelId(0),
+            effect_index: 5,
+            param: "ceiling".into(),
+            target: 2.0,
+            duration_ms: 100,
+        });
+        engine.process_commands();
+
+        // Rien d'appliqué, chaque refus nomme son champ
+        assert_eq!(
+            engine.channel_effects(ChannelId(0)).compressor.attack,
+            0.005
+        );
+        let rejected: Vec<String> = channels
+            .event_rx
+            .try_iter()
+            .filter_map(|e| match e {
+                Event::EffectParamsRejected {
+                    channel: ChannelId(0),
+                    violations,
+                } => Some(violations[0].field.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rejected, ["compressor.attack", "limiter.ceiling"]);
+
+        send(Command::SetEffectParamPolicy(ParamPolicy::Clamp));
+        send(Command::SetChannelEffects {
+            channel: ChannelId(0),
+            preset: slow,
+        });
+        engine.process_commands();
+        assert_eq!(engine.channel_effects(ChannelId(0)).compressor.attack, 0.5);
+        assert!(
+            !channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::EffectParamsRejected { .. }))
+        );
+    }
+
+    #[test]
+    fn preset_summaries_come_from_a_refreshable_cache() {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-engine-fx-summaries-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let manager = EffectsPresetManager::new(&dir);
+        manager
+            .save("Voice", &EffectsPreset::streaming(), false)
+            .unwrap();
+        manager
+            .save("Clean", &EffectsPreset::clean(), false)
+            .unwrap();
+        let (mut engine, channels) = Engine::new();
+        engine.set_effects_preset_manager(manager);
+
+        for command in [
+            Command::ListEffectsPresetSummaries,
+            Command::RequestPresetCacheStats,
+            Command::RefreshPresetCache,
+        ] {
+            channels.command_tx.send(command).unwrap();
+        }
+        engine.process_commands();
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::EffectsPresetSummaries(summaries)
+                if summaries.iter().map(|s| s.name.as_str()).eq(["Clean", "Voice"])
+        )));
+        let stats: Vec<(usize, u64)> = events
+            .iter()
+            .filter_map(|e| match e {
+                Event::PresetCacheStats(stats) => Some((stats.entries, stats.misses)),
+                _ => None,
+            })
+            .collect();
+        // Vidé par `RefreshPresetCache`, compteurs gardés
+        assert_eq!(stats, [(2, 2), (0, 2)]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn favorite_presets_follow_renames_and_deletes() {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-engine-fx-favorites-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let manager = EffectsPresetManager::new(&dir);
+        manager
+            .save("Voice", &EffectsPreset::streaming(), false)
+            .unwrap();
+        manager
+            .save("Music", &EffectsPreset::clean(), false)
+            .unwrap();
+
+        let (mut engine, channels) = Engine::new();
+        engine.set_effects_preset_manager(EffectsPresetManager::new(&dir));
+        engine.configure_favorite_presets(FavoritePresets::new(["Gone".to_string()]));
+        let send = |command| channels.command_tx.send(command).unwrap();
+        let favorites = |engine: &mut Engine| {
+            engine.process_commands();
+            channels
+                .event_rx
+                .try_iter()
+                .filter_map(|e| match e {
+                    Event::FavoritePresets(list) => Some(
+                        list.into_iter()
+                            .map(|f| format!("{}{}", f.name, if f.missing { "?" } else { "" }))
+                            .collect::<Vec<_>>(),
+                    ),
+                    _ => None,
+                })
+                .last()
+        };
+
+        send(Command::AddFavoritePreset {
+            name: "Voice".into(),
+            position: None,
+        });
+        send(Command::AddFavoritePreset {
+            name: "Music".into(),
+            position: Some(0),
+        });
+        // Pas dans la bibliothèque : refusé
+        send(Command::AddFavoritePreset {
+            name: "Nope".into(),
+            position: None,
+        });
+        assert_eq!(favorites(&mut engine).unwrap(), ["Music", "Gone?", "Voice"]);
+
+        // La touche 3 : le troisième favori sur le Mic
+        send(Command::ApplyFavoritePreset {
+            channel: ChannelId(0),
+            position: 2,
+        });
+        send(Command::ApplyFavoritePreset {
+            channel: ChannelId(0),
+            position: 7,
+        });
         engine.process_commands();
+        assert_eq!(engine.channel_effects(ChannelId(0)).name, "Voice");
+        let errors: Vec<String> = channels
+            .event_rx
+            .try_iter()
+            .filter_map(|e| match e {
+                Event::Error(message) => Some(message),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(errors, ["No favorite preset at position 8"]);
 
-        // Le gain du canal 0 doit être 0 (muted)
-        let (l, r) = *engine.shared_state.gain.lock().unwrap();
-        assert_eq!(l, 0.0);
-        assert_eq!(r, 0.0);
+        send(Command::RenameEffectsPreset {
+            from: "Voice".into(),
+            to: "Live Voice".into(),
+        });
+        send(Command::DeleteEffectsPreset {
+            name: "Music".into(),
+        });
+        send(Command::ReorderFavoritePresets {
+            names: vec!["Live Voice".into(), "Gone".into()],
+        });
+        assert_eq!(favorites(&mut engine).unwrap(), ["Live Voice", "Gone?"]);
+
+        send(Command::RemoveFavoritePreset {
+            name: "Gone".into(),
+        });
+        send(Command::ListFavoritePresets);
+        assert_eq!(favorites(&mut engine).unwrap(), ["Live Voice"]);
+        assert_eq!(engine.favorite_presets().names(), ["Live Voice"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 
     #[test]
-    fn engine_pan_updates_shared_state() {
+    fn applying_unknown_effects_preset_reports_error() {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-engine-fx-missing-{}",
+            std::process::id()
+        ));
         let (mut engine, channels) = Engine::new();
-
-        // Pan tout à gauche
+        engine.set_effects_preset_manager(EffectsPresetManager::new(&dir));
         channels
             .command_tx
-            .send(Command::SetPan {
+            .send(Command::ApplyEffectsPreset {
                 channel: ChannelId(0),
-                pan: -1.0,
+                name: "../nope".into(),
             })
             .unwrap();
         engine.process_commands();
+        assert!(matches!(channels.event_rx.try_recv(), Ok(Event::Error(_))));
+        assert_eq!(engine.shared_dsp_chain().lock().unwrap().len(), 4);
+    }
 
-        let (l, r) = *engine.shared_state.gain.lock().unwrap();
-        assert!(l > 0.9, "Left gain should be ~1.0, got {l}");
-        assert!(r < 0.01, "Right gain should be ~0.0, got {r}");
+    #[test]
+    fn detailed_metering_shows_each_effect_stage_in_order() {
+        let (mut engine, channels) = Engine::new();
+        let mut preset = EffectsPreset::default_preset();
+        preset.eq.bands[1].gain_db = 12.0;
+        let send = |cmd| channels.command_tx.send(cmd).unwrap();
+        send(Command::SetChannelEffects {
+            channel: ChannelId(0),
+            preset,
+        });
+        send(Command::SetDetailedEffectMetering {
+            channel: ChannelId(0),
+            enabled: true,
+        });
+        // Le bus n'a pas de chaîne qui tourne
+        send(Command::SetDetailedEffectMetering {
+            channel: ChannelId(3),
+            enabled: true,
+        });
+        engine.process_commands();
+        let errors: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(
+            matches!(errors.as_slice(), [Event::Error(e)] if e.contains("no running effects chain"))
+        );
+
+        // Un bloc de sinus à 1 kHz, la fréquence de la bande boostée
+        let block = |engine: &Engine| {
+            let mut chain = engine.dsp_chain.lock().unwrap();
+            chain.begin_block();
+            for i in 0..4800 {
+                let t = i as f32 / engine.processing_rate();
+                chain.process_sample(0.1 * (std::f32::consts::TAU * 1000.0 * t).sin());
+            }
+        };
+        block(&engine);
+        let stages = engine.channel_effect_stages(ChannelId(0)).unwrap();
+        let names: Vec<&str> = stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "NoiseGate",
+                "AutoGain",
+                "ParametricEq",
+                "Compressor",
+                "DeEsser",
+                "Limiter"
+            ]
+        );
+        let eq = &stages[2];
+        assert!(eq.params.contains(&("band1_gain_db".to_string(), 12.0)));
+        assert!(eq.output_peak.unwrap() > 2.0 * eq.input_peak.unwrap());
+        // Chaque étage reçoit ce que le précédent a rendu
+        for pair in stages.windows(2) {
+            assert_eq!(pair[0].output_peak, pair[1].input_peak);
+        }
+        assert!(stages[3].gain_reduction.is_some());
+        assert_eq!(stages[2].gain_reduction, None);
+
+        // Changer de preset garde la mesure ; la couper retire les crêtes
+        engine.set_channel_effects(ChannelId(0), EffectsPreset::default_preset());
+        assert!(engine.dsp_chain.lock().unwrap().detailed_metering());
+        send(Command::SetDetailedEffectMetering {
+            channel: ChannelId(0),
+            enabled: false,
+        });
+        send(Command::RequestChannelEffects {
+            channel: ChannelId(0),
+        });
+        engine.process_commands();
+        block(&engine);
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        let [Event::ChannelEffects { stages, .. }] = events.as_slice() else {
+            panic!("unexpected events: {events:?}");
+        };
+        assert_eq!(stages.len(), 6);
+        assert!(stages.iter().all(|s| s.input_peak.is_none()));
+        assert!(engine.dsp_chain.lock().unwrap().stage_peaks().is_none());
     }
 
     #[test]
-    fn engine_clamps_volume() {
+    fn dsp_profile_times_each_running_effect() {
         let (mut engine, channels) = Engine::new();
-        channels
-            .command_tx
-            .send(Command::SetVolume {
-                channel: ChannelId(0),
-                level: 5.0,
-            })
-            .unwrap();
+        let send = |cmd| channels.command_tx.send(cmd).unwrap();
+        send(Command::SetDspProfiling { enabled: true });
+        send(Command::RequestDspProfile);
         engine.process_commands();
-        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 2.0);
+        // Aucun bloc encore : des zéros, sans budget
+        let Ok(Event::DspProfile(profile)) = channels.event_rx.try_recv() else {
+            panic!("no profile");
+        };
+        assert!(profile.enabled);
+        assert_eq!(profile.block_budget_us, 0.0);
+        assert!(profile.stages.iter().all(|s| s.micros == 0.0));
+
+        // Un preset chargé garde le profilage ; un bloc de 10 ms
+        engine.set_channel_effects(ChannelId(0), EffectsPreset::default_preset());
+        let frames = (engine.processing_rate() / 100.0) as usize;
+        let mut block = vec![0.1_f32; frames];
+        assert!(
+            engine
+                .dsp_chain
+                .lock()
+                .unwrap()
+                .process_block(&mut block)
+                .is_none()
+        );
+        let profile = engine.dsp_profile();
+        assert!((profile.block_budget_us - 10_000.0).abs() < 1.0);
+        let names: Vec<&str> = profile.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "NoiseGate",
+                "AutoGain",
+                "ParametricEq",
+                "Compressor",
+                "DeEsser",
+                "Limiter"
+            ]
+        );
+        for (index, stage) in profile.stages.iter().enumerate() {
+            assert_eq!((stage.channel, stage.effect_index), (ChannelId(0), index));
+            assert!(stage.micros > 0.0);
+            assert!((stage.budget_percent - stage.micros / 100.0).abs() < 1e-3);
+        }
+
+        engine.set_dsp_profiling(false);
+        let profile = engine.dsp_profile();
+        assert!(!profile.enabled && profile.stages.is_empty());
+        assert!(!engine.dsp_chain.lock().unwrap().profiling());
     }
 
     #[test]
-    fn engine_processes_mute_command() {
+    fn mic_template_adds_gate_then_compressor_routed_to_all_buses() {
         let (mut engine, channels) = Engine::new();
         channels
             .command_tx
-            .send(Command::SetMute {
-                channel: ChannelId(0),
-                muted: true,
+            .send(Command::AddChannelFromTemplate {
+                channel: ChannelId(5),
+                name: "Guest Mic".into(),
+                template: "Mic".into(),
             })
             .unwrap();
         engine.process_commands();
-        assert!(engine.mixer().channel(ChannelId(0)).unwrap().muted);
+
+        let channel = engine.mixer().channel(ChannelId(5)).unwrap();
+        assert_eq!(channel.name, "Guest Mic");
+        assert_eq!(channel.kind, ChannelKind::Input);
+
+        let chain = EffectsChain::from_preset(&engine.channel_effects(ChannelId(5)));
+        let active = chain.active_processors();
+        let position = |name: &str| active.iter().position(|p| *p == name).unwrap();
+        assert!(position("NoiseGate") < position("Compressor"));
+        assert!(position("Compressor") < position("DeEsser"));
+        assert!(position("DeEsser") < position("Limiter"));
+
+        for bus in engine.mixer().outputs() {
+            assert!(
+                engine.mixer().has_route(ChannelId(5), bus.id),
+                "{}",
+                bus.name
+            );
+        }
+        assert!(matches!(
+            channels.event_rx.try_recv(),
+            Ok(Event::ChannelAdded { channel: ChannelId(5), template: Some(template) })
+                if template == "Mic"
+        ));
+        assert_eq!(
+            engine.journal().recent(1)[0].action,
+            "AddChannelFromTemplate"
+        );
     }
 
     #[test]
-    fn engine_processes_solo_command() {
+    fn channel_template_falls_back_to_effects_preset() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-engine-template-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let presets = EffectsPresetManager::new(&dir);
+        presets
+            .save("Radio Voice", &EffectsPreset::streaming(), false)
+            .unwrap();
+
+        let (mut engine, _channels) = Engine::new();
+        engine.set_effects_preset_manager(presets);
+        engine
+            .add_channel_from_template(ChannelId(5), "Host", "Radio Voice")
+            .unwrap();
+        assert_eq!(engine.channel_effects(ChannelId(5)).name, "Radio Voice");
+        assert!(engine.mixer().has_route(ChannelId(5), ChannelId(4)));
+
+        // Canal déjà pris, template inconnu : rien n'est créé
+        assert!(matches!(
+            engine.add_channel_from_template(ChannelId(5), "Again", "Mic"),
+            Err(TroubadourError::Mixer(MixerError::DuplicateId(5)))
+        ));
+        assert!(matches!(
+            engine.add_channel_from_template(ChannelId(6), "Nope", "Kazoo"),
+            Err(TroubadourError::TemplateNotFound(_))
+        ));
+        assert!(engine.mixer().channel(ChannelId(6)).is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn add_channel_command_rejects_duplicate_id() {
         let (mut engine, channels) = Engine::new();
-        channels
-            .command_tx
-            .send(Command::SetSolo {
-                channel: ChannelId(0),
-                solo: true,
+        let send = |config| {
+            channels
+                .command_tx
+                .send(Command::AddChannel(config))
+                .unwrap()
+        };
+        send(ChannelConfig::input(1, "Input 1"));
+        send(ChannelConfig::input(6, "Input 6"));
+        engine.process_commands();
+
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(
+            matches!(&events[0], Event::Error(message) if message.contains("1 already exists"))
+        );
+        assert!(matches!(
+            events[1],
+            Event::ChannelAdded {
+                channel: ChannelId(6),
+                template: None
+            }
+        ));
+        assert_eq!(
+            engine.mixer().channel(ChannelId(1)).unwrap().name,
+            "Desktop"
+        );
+        assert_eq!(engine.mixer().channel_count(), 6);
+    }
+
+    /// Énergie de `signal` à `frequency` (Goertzel) : une raie du spectre,
+    /// sans FFT complète.
+    fn tone_energy(signal: &[f32], frequency: f32, sample_rate: f32) -> f32 {
+        let coeff = 2.0 * (std::f32::consts::TAU * frequency / sample_rate).cos();
+        let (mut s1, mut s2) = (0.0_f32, 0.0_f32);
+        for &x in signal {
+            let s0 = x + coeff * s1 - s2;
+            s2 = s1;
+            s1 = s0;
+        }
+        s1 * s1 + s2 * s2 - coeff * s1 * s2
+    }
+
+    #[test]
+    fn effects_diff_monitor_plays_only_what_the_chain_changes() {
+        // 187,5 Hz et 4,5 kHz : un nombre entier de périodes par bloc de
+        // 256 frames, le bloc répété reste continu
+        let (low, high) = (187.5, 4500.0);
+        let block: Vec<f32> = (0..256)
+            .flat_map(|n| {
+                let t = n as f32 / 48_000.0;
+                let sample = 0.2 * (std::f32::consts::TAU * low * t).sin()
+                    + 0.2 * (std::f32::consts::TAU * high * t).sin();
+                [sample, sample]
             })
-            .unwrap();
+            .collect();
+        let mut bypassed = EffectsPreset::clean();
+        bypassed.limiter.enabled = false;
+        let mut shelf = bypassed.clone();
+        shelf.eq.enabled = true;
+        shelf.eq.bands = vec![EqBandConfig {
+            filter_type: "low_shelf".to_string(),
+            frequency: 400.0,
+            gain_db: 6.0,
+            q: 0.7,
+            enabled: true,
+        }];
+        // Gauche du bus joué, 4 blocs après 4 de mise en route. `faded` :
+        // fader du Mic en bas, seul son retour (pré-fader) s'entend
+        let render = |preset: &EffectsPreset, faded: bool, diff: Option<bool>| {
+            let (mut engine, _channels) = Engine::new();
+            engine.set_channel_effects(ChannelId(0), preset.clone());
+            if faded {
+                engine.mixer.set_volume(ChannelId(0), 0.0);
+            }
+            if let Some(diff) = diff {
+                engine
+                    .mixer
+                    .set_monitor(ChannelId(0), Some(ChannelId(3)), 0.0);
+                engine.set_effects_diff_monitor(ChannelId(0), diff).unwrap();
+            }
+            engine.shared_state.update_from_mixer(&engine.mixer);
+            let mut harness = InputHarness::new(&engine);
+            harness.block = block.clone();
+            let mut left = Vec::new();
+            for n in 0..8 {
+                harness.run_block();
+                if n >= 4 {
+                    left.extend(harness.processor.output.iter().step_by(2));
+                }
+            }
+            left
+        };
+
+        // Chaîne sautée : la différence est nulle, le mix principal intact
+        assert_eq!(
+            render(&bypassed, false, Some(true)),
+            render(&bypassed, false, None)
+        );
+        assert!(
+            render(&bypassed, true, Some(true))
+                .iter()
+                .all(|&s| s == 0.0)
+        );
+
+        // Low shelf +6 dB : la différence est dans les graves
+        let diff = render(&shelf, true, Some(true));
+        let (low_energy, high_energy) = (
+            tone_energy(&diff, low, 48_000.0),
+            tone_energy(&diff, high, 48_000.0),
+        );
+        assert!(
+            low_energy > 100.0 * high_energy,
+            "{low_energy} vs {high_energy}"
+        );
+        // Retour normal : les deux raies y sont
+        let wet = render(&shelf, true, Some(false));
+        assert!(tone_energy(&wet, high, 48_000.0) > 100.0 * high_energy);
+    }
+
+    #[test]
+    fn effects_diff_monitor_is_cleared_on_stop() {
+        let (mut engine, channels) = Engine::new();
+        let send = |command| channels.command_tx.send(command).unwrap();
+        // Pas de retour : rien où faire entendre la différence
+        send(Command::SetEffectsDiffMonitor {
+            channel: ChannelId(0),
+            enabled: true,
+        });
+        send(Command::SetChannelMonitor {
+            channel: ChannelId(0),
+            bus: Some(ChannelId(3)),
+            level_db: 0.0,
+        });
+        send(Command::SetEffectsDiffMonitor {
+            channel: ChannelId(0),
+            enabled: true,
+        });
         engine.process_commands();
-        assert!(engine.mixer().channel(ChannelId(0)).unwrap().solo);
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(
+            events
+                .iter()
+                .any(|e| matches!(e, Event::Error(m) if m.contains("no monitor bus")))
+        );
+        assert!(events.iter().any(|e| matches!(
+            e,
+            Event::EffectsDiffMonitor {
+                channel: ChannelId(0),
+                enabled: true
+            }
+        )));
+        assert!(
+            engine
+                .shared_state
+                .snapshot()
+                .effects_diff_monitor(ChannelId(0))
+        );
+        assert!(engine.set_effects_diff_monitor(ChannelId(1), true).is_err());
+
+        engine.state = EngineState::Running;
+        engine.stop();
+        assert!(!engine.mixer().effects_diff_monitor(ChannelId(0)));
+        assert!(
+            !engine
+                .shared_state
+                .snapshot()
+                .effects_diff_monitor(ChannelId(0))
+        );
+        assert!(
+            channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::EffectsDiffMonitor { enabled: false, .. }))
+        );
     }
 
     #[test]
-    fn engine_processes_pan_command() {
+    fn detector_listen_is_cleared_on_stop() {
+        let (mut engine, channels) = Engine::new();
+        let send = |command| channels.command_tx.send(command).unwrap();
+        // Index 3 : le compresseur de la chaîne du preset par défaut
+        send(Command::SetDetectorListen {
+            channel: ChannelId(0),
+            effect_index: 3,
+            listen: true,
+        });
+        // L'EQ n'a pas de détecteur
+        send(Command::SetDetectorListen {
+            channel: ChannelId(0),
+            effect_index: 2,
+            listen: true,
+        });
+        engine.process_commands();
+        let errors: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(matches!(errors.as_slice(), [Event::Error(e)] if e.contains("no detector")));
+
+        // Signal calme, sous le seuil : le compresseur ne retire rien
+        let quiet = |engine: &Engine| {
+            let mut chain = engine.dsp_chain.lock().unwrap();
+            (0..256).map(|_| chain.process_sample(0.05)).last().unwrap()
+        };
+        assert_eq!(quiet(&engine), 0.0);
+
+        engine.state = EngineState::Running;
+        engine.stop();
+        assert!(quiet(&engine) > 0.0);
+        assert!(engine.set_detector_listen(ChannelId(1), 3, true).is_err());
+
+        // Compresseur bypassé : pas d'écoute, mais on peut la couper
+        let mut preset = engine.channel_effects(ChannelId(0));
+        preset.compressor.enabled = false;
+        engine.set_channel_effects(ChannelId(0), preset);
+        let err = engine
+            .set_detector_listen(ChannelId(0), 3, true)
+            .unwrap_err();
+        assert!(err.to_string().contains("bypassed"), "{err}");
+        engine.set_detector_listen(ChannelId(0), 3, false).unwrap();
+
+        // Chaîne empoisonnée par un panic : erreur, pas un faux succès
+        let dsp = engine.dsp_chain.clone();
+        let _ = std::thread::spawn(move || {
+            let _held = dsp.lock().unwrap();
+            panic!("poisoning the effects chain");
+        })
+        .join();
+        assert!(matches!(
+            engine.set_detector_listen(ChannelId(0), 0, true),
+            Err(TroubadourError::LockPoisoned(_))
+        ));
+    }
+
+    #[test]
+    fn route_activity_is_measured_only_when_enabled() {
         let (mut engine, channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        for _ in 0..32 {
+            harness.run_block();
+        }
+        // Coupée par défaut : le callback ne compte même pas les blocs
+        assert_eq!(harness.processor.route_sampler.pending_blocks(), 0);
+        assert!(!engine.route_meter.take_measured());
+        assert!(engine.get_route_activity().is_empty());
+
         channels
             .command_tx
-            .send(Command::SetPan {
-                channel: ChannelId(0),
-                pan: -0.5,
-            })
+            .send(Command::SetRouteMetering { enabled: true })
             .unwrap();
         engine.process_commands();
-        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().pan, -0.5);
+        // Le callback mesure sans allouer ; le tick suivant du moteur
+        // envoie l'activité
+        let allocations = count_allocations(|| {
+            for _ in 0..ROUTE_METER_INTERVAL {
+                harness.processor.process_block_guarded(&harness.block);
+            }
+        });
+        assert_eq!(allocations, 0);
+        engine.process_commands();
+        let Some(Event::RouteActivity(routes)) = channels
+            .event_rx
+            .try_iter()
+            .find(|e| matches!(e, Event::RouteActivity(_)))
+        else {
+            panic!("no route activity sent");
+        };
+        assert_eq!(routes.len(), 1);
+        assert_eq!((routes[0].from, routes[0].to), (ChannelId(0), ChannelId(3)));
+        assert!(routes[0].peak > 0.0);
+        engine.process_commands();
+        assert!(
+            !channels
+                .event_rx
+                .try_iter()
+                .any(|e| matches!(e, Event::RouteActivity(_)))
+        );
+
+        channels
+            .command_tx
+            .send(Command::RequestRouteActivity)
+            .unwrap();
+        engine.process_commands();
+        assert!(matches!(
+            channels.event_rx.try_iter().last(),
+            Some(Event::RouteActivity(r)) if r == routes
+        ));
     }
 
     #[test]
-    fn engine_processes_route_commands() {
+    fn fade_bus_command_publishes_target_and_fade() {
         let (mut engine, channels) = Engine::new();
         channels
             .command_tx
-            .send(Command::AddRoute {
-                from: ChannelId(1),
-                to: ChannelId(4),
+            .send(Command::FadeBusVolume {
+                bus: ChannelId(3),
+                target_db: f32::NEG_INFINITY,
+                duration_ms: 2000.0,
+            })
+            .unwrap();
+        channels
+            .command_tx
+            .send(Command::FadeBusVolume {
+                bus: ChannelId(0),
+                target_db: -20.0,
+                duration_ms: 100.0,
             })
             .unwrap();
         engine.process_commands();
-        assert!(engine.mixer().has_route(ChannelId(1), ChannelId(4)));
 
+        let snapshot = engine.shared_state.snapshot();
+        assert_eq!(snapshot.bus_gain(ChannelId(3)), 0.0);
+        assert_eq!(snapshot.bus_fade(ChannelId(3)).unwrap().duration_ms, 2000.0);
+        assert!(matches!(channels.event_rx.try_recv(), Ok(Event::Error(_))));
+        assert_eq!(engine.journal().recent(1)[0].action, "FadeBusVolume");
+
+        // Reprendre le fader annule le fondu
         channels
             .command_tx
-            .send(Command::RemoveRoute {
-                from: ChannelId(1),
-                to: ChannelId(4),
+            .send(Command::SetVolume {
+                channel: ChannelId(3),
+                level: 0.7,
             })
             .unwrap();
         engine.process_commands();
-        assert!(!engine.mixer().has_route(ChannelId(1), ChannelId(4)));
+        assert_eq!(engine.shared_state.snapshot().bus_fade(ChannelId(3)), None);
     }
 
     #[test]
-    fn engine_processes_device_list_request() {
+    fn monitor_send_bypasses_channel_fader() {
         let (mut engine, channels) = Engine::new();
-        channels
-            .command_tx
-            .send(Command::RequestDeviceList)
+        let send = |command| channels.command_tx.send(command).unwrap();
+        // Mic routé vers Headphones (3) ; retour vers Speakers (4) à -6 dB
+        send(Command::SetVolume {
+            channel: ChannelId(0),
+            level: VolumeDecibels(-60.0).to_linear(),
+        });
+        send(Command::SetChannelMonitor {
+            channel: ChannelId(0),
+            bus: Some(ChannelId(4)),
+            level_db: -6.0,
+        });
+        engine.process_commands();
+
+        let state = engine.shared_state.snapshot();
+        let (main_l, main_r) = state.bus_send(ChannelId(0), ChannelId(3));
+        assert!(main_l <= 0.001 && main_r <= 0.001);
+        let (mon_l, mon_r) = state.bus_send(ChannelId(0), ChannelId(4));
+        let expected = VolumeDecibels(-6.0).to_linear();
+        assert!((mon_l - expected).abs() < 1e-6 && (mon_r - expected).abs() < 1e-6);
+
+        let saved = engine.to_profile("p").mixer;
+        let mic = saved
+            .channels
+            .iter()
+            .find(|c| c.id == ChannelId(0))
             .unwrap();
+        assert_eq!(mic.monitor_bus, Some(ChannelId(4)));
+        assert_eq!(mic.monitor_level_db, -6.0);
+
+        // Un retour vers une entrée est refusé
+        send(Command::SetChannelMonitor {
+            channel: ChannelId(0),
+            bus: Some(ChannelId(1)),
+            level_db: 0.0,
+        });
         engine.process_commands();
+        assert!(matches!(channels.event_rx.try_recv(), Ok(Event::Error(_))));
+    }
 
-        match channels.event_rx.try_recv() {
-            Ok(Event::DeviceList { .. }) => {}
-            other => println!("Received: {other:?}"),
+    struct Fragile;
+
+    impl Processor for Fragile {
+        fn name(&self) -> &'static str {
+            "Fragile"
+        }
+
+        fn process_sample(&mut self, _sample: f32) -> f32 {
+            panic!("fragile stage")
+        }
+
+        fn reset(&mut self) {}
+
+        fn set_bypass(&mut self, _bypass: bool) {}
+
+        fn is_bypassed(&self) -> bool {
+            false
         }
     }
 
     #[test]
-    fn engine_processes_shutdown() {
-        let (mut engine, channels) = Engine::new();
-        channels.command_tx.send(Command::Shutdown).unwrap();
-        engine.process_commands();
-        assert_eq!(engine.state(), EngineState::Stopped);
+    fn processing_panics_bypass_the_stage_then_silence_the_channel() {
+        let (engine, channels) = Engine::new();
+        let mut harness = InputHarness::new(&engine);
+        {
+            let mut chain = engine.dsp_chain.lock().unwrap();
+            *chain = EffectsChain::new();
+            chain.add(Box::new(Fragile));
+        }
+        let panics = |rx: &Receiver<Event>| -> Vec<Option<String>> {
+            rx.try_iter()
+                .filter_map(|e| match e {
+                    Event::ProcessingPanicked { effect, .. } => Some(effect),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // Le bloc fautif est rendu muet, l'étage mis à l'écart
+        harness.run_block();
+        assert!(harness.processor.output.iter().all(|&s| s == 0.0));
+        assert_eq!(panics(&channels.event_rx), [Some("Fragile".to_string())]);
+        assert_eq!(
+            engine.channel_health(PIPELINE_INPUT_CHANNEL),
+            ChannelHealth::EffectBypassed
+        );
+        let stages = engine
+            .channel_effect_stages(PIPELINE_INPUT_CHANNEL)
+            .unwrap();
+        assert!(stages[0].poisoned);
+
+        // Bloc suivant : le signal passe, sans nouvel événement
+        harness.run_block();
+        assert!(harness.processor.output.iter().any(|&s| s != 0.0));
+        assert!(panics(&channels.event_rx).is_empty());
+
+        // Panic hors de la chaîne (`chunks(0)` au downmix) : le
+        // canal se tait, mais continue de livrer des blocs
+        harness.processor.input_channels = 0;
+        harness.run_block();
+        assert_eq!(panics(&channels.event_rx), [None]);
+        assert_eq!(
+            engine.channel_health(PIPELINE_INPUT_CHANNEL),
+            ChannelHealth::Silenced
+        );
+        harness.processor.input_channels = 2;
+        harness.run_block();
+        assert_eq!(harness.processor.output.len(), 512);
+        assert!(harness.processor.output.iter().all(|&s| s == 0.0));
+        // Le lock tenu pendant le panic reste utilisable
+        assert!(engine.dsp_chain.lock().is_ok());
+    }
+
+    /// Rendu de sortie sans device : `audio_tx` remplace l'entrée.
+    fn test_renderer(engine: &Engine, out_channels: usize) -> (OutputRenderer, Sender<Vec<f32>>) {
+        let (audio_tx, audio_rx) = crossbeam_channel::bounded(8);
+        let (recycle_tx, _) = crossbeam_channel::bounded(8);
+        let renderer = OutputRenderer {
+            audio_rx,
+            recycle_tx,
+            bridge: CallbackResampler::new(48_000, 48_000, 2, 256).unwrap(),
+            out_channels,
+            slots: Vec::new(),
+            lanes: 1,
+            block: Vec::new(),
+            underruns: engine.underruns.clone(),
+            governor: LatencyGovernor::new(engine.target_latency_blocks, engine.latency.clone()),
+            rt_log: engine.rt_log.logger(),
+            handover: HandoverFader::new(engine.pipeline_gate.clone(), 48_000.0),
+            bus: Some(ChannelId(3)),
+            faults: engine.channel_faults.clone(),
+            event_tx: engine.event_tx.clone(),
+            failed: false,
+        };
+        (renderer, audio_tx)
     }
 
     #[test]
-    fn engine_handles_multiple_commands() {
+    fn output_panics_silence_the_device_and_are_reported() {
+        let (engine, channels) = Engine::new();
+        let (mut renderer, _audio_tx) = test_renderer(&engine, 2);
+        let mut output = vec![0.5; 512];
+        renderer.render_guarded(&mut output);
+        assert!(!renderer.failed);
+
+        // Division par zéro au calcul des frames : le callback survit
+        renderer.out_channels = 0;
+        output.fill(0.5);
+        renderer.render_guarded(&mut output);
+        assert!(renderer.failed);
+        assert!(output.iter().all(|&s| s == 0.0));
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::ProcessingPanicked {
+                channel: ChannelId(3),
+                effect: None,
+                ..
+            }
+        )));
+        assert_eq!(engine.channel_health(ChannelId(3)), ChannelHealth::Silenced);
+
+        // Plus de rendu jusqu'à la réouverture, même réparé
+        renderer.out_channels = 2;
+        output.fill(0.5);
+        renderer.render_guarded(&mut output);
+        assert!(output.iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn buses_sharing_a_device_each_reach_their_slot_through_the_output_callback() {
         let (mut engine, channels) = Engine::new();
-        channels
-            .command_tx
-            .send(Command::SetVolume {
-                channel: ChannelId(0),
-                level: 0.8,
-            })
-            .unwrap();
-        channels
-            .command_tx
-            .send(Command::SetMute {
-                channel: ChannelId(0),
-                muted: true,
-            })
-            .unwrap();
+        // Headphones sur les sorties 1-2, Speakers sur 3-4 de la même
+        // interface 8 canaux ; le Mic va aux deux, Speakers à moitié
+        let card = DeviceAssignment::Specific("Card".into());
+        let commands = [
+            Command::SetChannelDevice {
+                channel: ChannelId(3),
+                device: card.clone(),
+            },
+            Command::SetChannelDevice {
+                channel: ChannelId(4),
+                device: card,
+            },
+            Command::SetBusOutputMapping {
+                bus: ChannelId(3),
+                offset: Some(0),
+            },
+            Command::SetBusOutputMapping {
+                bus: ChannelId(4),
+                offset: Some(2),
+            },
+            Command::AddRoute {
+                from: ChannelId(0),
+                to: ChannelId(4),
+            },
+            Command::SetVolume {
+                channel: ChannelId(4),
+                level: 0.5,
+            },
+        ];
+        for command in commands {
+            channels.command_tx.send(command).unwrap();
+        }
         engine.process_commands();
-        assert_eq!(engine.mixer().channel(ChannelId(0)).unwrap().volume, 0.8);
-        assert!(engine.mixer().channel(ChannelId(0)).unwrap().muted);
+
+        let (audio_tx, audio_rx) = crossbeam_channel::bounded(AUDIO_QUEUE);
+        let (_recycle_tx, recycle_rx) = crossbeam_channel::bounded(AUDIO_QUEUE);
+        let mut processor = engine.input_processor(2, 48_000.0, "Card", audio_tx, recycle_rx);
+        assert_eq!(processor.output_bus, Some(ChannelId(3)));
+        let (mut renderer, lanes_tx) = test_renderer(&engine, 8);
+        renderer.slots = vec![OutputSlot::new(0, 2), OutputSlot::new(2, 2)];
+        renderer.lanes = 2;
+        renderer.bridge = CallbackResampler::new(48_000, 48_000, 4, 256).unwrap();
+
+        // Assez de blocs pour constituer la réserve du pont
+        let block: Vec<f32> = (0..512).map(|n| 0.3 * (n as f32 * 0.05).sin()).collect();
+        let mut output = vec![0.0; 64 * 8];
+        let mut rendered = false;
+        for _ in 0..16 {
+            processor.process_block_guarded(&block);
+            processor.send_block();
+            for lanes in audio_rx.try_iter() {
+                lanes_tx.send(lanes).unwrap();
+            }
+            renderer.render_guarded(&mut output);
+            if output.iter().any(|&s| s != 0.0) {
+                rendered = true;
+                break;
+            }
+        }
+        assert!(rendered);
+
+        for frame in output.chunks_exact(8) {
+            // Chaque bus sur sa paire, Speakers à la moitié de Headphones
+            assert!((frame[2] - 0.5 * frame[0]).abs() < 1e-5);
+            assert!((frame[3] - 0.5 * frame[1]).abs() < 1e-5);
+            assert!(frame[4..].iter().all(|&s| s == 0.0));
+        }
+        assert!(output.chunks_exact(8).any(|frame| frame[0].abs() > 0.01));
     }
 
     #[test]
-    fn engine_channels_are_send() {
-        fn assert_send<T: Send>() {}
-        assert_send::<Sender<Command>>();
-        assert_send::<Receiver<Event>>();
+    fn emergency_passthrough_bypasses_effects_without_touching_channels() {
+        let (mut engine, channels) = Engine::new();
+        let send = |command| channels.command_tx.send(command).unwrap();
+        send(Command::SetVolume {
+            channel: PIPELINE_INPUT_CHANNEL,
+            level: 0.2,
+        });
+        send(Command::SetMute {
+            channel: PIPELINE_INPUT_CHANNEL,
+            muted: true,
+        });
+        engine.process_commands();
+        let before = engine.mixer.to_config().channels;
+        let mut harness = InputHarness::new(&engine);
+        // Même signal à gauche et à droite : le downmix le rend tel quel
+        harness.block = (0..256)
+            .flat_map(|n| [0.3 * (n as f32 * 0.05).sin(); 2])
+            .collect();
+        // Une chaîne qui panique si on la fait tourner
+        engine.dsp_chain.lock().unwrap().add(Box::new(Fragile));
+        let _ = channels.event_rx.try_iter().count();
+
+        send(Command::SetEmergencyPassthrough { enabled: true });
+        engine.process_commands();
+        assert_eq!(engine.emergency_passthrough(), Some(ChannelId(3)));
+        assert!(channels.event_rx.try_iter().any(|e| matches!(
+            e,
+            Event::EmergencyPassthroughChanged {
+                bus: Some(ChannelId(3))
+            }
+        )));
+
+        // Sortie = entrée, à l'unité, malgré volume, mute et effets
+        harness.run_block();
+        for (frame, input) in harness
+            .processor
+            .output
+            .chunks_exact(2)
+            .zip(harness.block.chunks_exact(2))
+        {
+            assert_eq!(frame, input);
+        }
+        assert_eq!(engine.mixer.to_config().channels, before);
+
+        send(Command::SetEmergencyPassthrough { enabled: false });
+        engine.process_commands();
+        assert_eq!(engine.emergency_passthrough(), None);
+        harness.run_block();
+        assert!(harness.processor.output.iter().all(|&s| s == 0.0));
     }
 
     #[test]
-    fn engine_stop_is_idempotent() {
-        let (mut engine, _channels) = Engine::new();
-        engine.stop();
-        engine.stop();
-        assert_eq!(engine.state(), EngineState::Stopped);
+    fn preset_dir_must_be_writable() {
+        let (mut engine, channels) = Engine::new();
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-preset-dir-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        // Un fichier à la place du dossier : refusé, rien ne change
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("not-a-dir");
+        std::fs::write(&file, b"x").unwrap();
+        let before = engine.preset_dir().to_path_buf();
+        let send = |command| channels.command_tx.send(command).unwrap();
+        send(Command::SetPresetDir { path: file });
+        engine.process_commands();
+        assert!(
+            matches!(channels.event_rx.try_recv(), Ok(Event::Error(e)) if e.contains("not writable"))
+        );
+        assert_eq!(engine.preset_dir(), before);
+
+        let presets = dir.join("presets");
+        send(Command::SetPresetDir {
+            path: presets.clone(),
+        });
+        send(Command::RequestPresetDir);
+        engine.process_commands();
+        let events: Vec<Event> = channels.event_rx.try_iter().collect();
+        assert!(matches!(
+            events.as_slice(),
+            [Event::PresetDir { path: a }, Event::PresetDir { path: b }] if *a == presets && *b == presets
+        ));
+        assert!(presets.is_dir());
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
 }
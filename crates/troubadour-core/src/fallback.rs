@@ -0,0 +1,305 @@
+//! Repli d'une entrée dont le device manque.
+//!
+//! Un laptop sorti du dock démarre sans son interface : plutôt que de
+//! prendre le micro intégré (le défaut du système), une entrée peut
+//! rester muette, essayer d'autres devices dans l'ordre, ou jouer un
+//! fichier en boucle (`InputFallback`).
+//!
+//! Un fichier ou le silence n'ont pas de callback cpal pour les cadencer :
+//! `ClockedInput` les livre bloc par bloc depuis un thread, au rythme du
+//! sample rate, au même traitement qu'un stream d'entrée. Le moteur revient
+//! sur le device dès que la détection des devices le revoit.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::mixer::{InputFallback, InputSource};
+
+use crate::wav::WavAudio;
+
+/// Retard (en blocs) au-delà duquel l'horloge repart de maintenant au
+/// lieu de rattraper : après une mise en veille, pas de rafale de blocs.
+const MAX_LATE_BLOCKS: u32 = 4;
+
+/// Source choisie pour une entrée.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedInput {
+    pub source: InputSource,
+    /// `source` n'est pas le device de l'entrée : à signaler, et à
+    /// quitter quand le device revient
+    pub fallback: bool,
+}
+
+/// Choisit la source d'une entrée.
+///
+/// `primary` : le device voulu (`None` : celui par défaut du système,
+/// qui n'est alors pas un repli). `present` dit si un device est branché.
+/// `None` : rien à ouvrir (aucun device et repli sur le défaut du système).
+pub fn resolve_input(
+    primary: Option<&str>,
+    system_default: Option<&str>,
+    policy: &InputFallback,
+    present: impl Fn(&str) -> bool,
+) -> Option<ResolvedInput> {
+    let wanted = primary.or(system_default);
+    if let Some(device) = wanted.filter(|name| present(name)) {
+        return Some(ResolvedInput {
+            source: InputSource::Device(device.to_string()),
+            fallback: false,
+        });
+    }
+    let source = match policy {
+        InputFallback::SystemDefault => InputSource::Device(
+            system_default
+                .filter(|name| Some(*name) != wanted && present(name))?
+                .to_string(),
+        ),
+        InputFallback::Silence => InputSource::Silence,
+        InputFallback::Devices(devices) => devices
+            .iter()
+            .find(|name| present(name))
+            .map_or(InputSource::Silence, |name| {
+                InputSource::Device(name.clone())
+            }),
+        InputFallback::File(path) => InputSource::File(path.clone()),
+    };
+    Some(ResolvedInput {
+        source,
+        fallback: true,
+    })
+}
+
+/// Audio rejoué en boucle : un fichier, ou du silence.
+#[derive(Debug, Clone)]
+pub struct LoopedAudio {
+    /// Entrelacé ; vide pour le silence
+    samples: Vec<f32>,
+    channels: u16,
+    cursor: usize,
+}
+
+impl LoopedAudio {
+    pub fn silence(channels: u16) -> Self {
+        Self {
+            samples: Vec::new(),
+            channels: channels.max(1),
+            cursor: 0,
+        }
+    }
+
+    /// Un fichier vide se joue comme du silence.
+    pub fn from_wav(audio: WavAudio) -> Self {
+        Self {
+            samples: audio.samples,
+            channels: audio.channels.max(1),
+            cursor: 0,
+        }
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Remplit `out` (entrelacé) en reprenant au début à la fin du fichier.
+    pub fn fill(&mut self, out: &mut [f32]) {
+        if self.samples.is_empty() {
+            out.fill(0.0);
+            return;
+        }
+        let mut written = 0;
+        while written < out.len() {
+            let take = (self.samples.len() - self.cursor).min(out.len() - written);
+            out[written..written + take]
+                .copy_from_slice(&self.samples[self.cursor..self.cursor + take]);
+            self.cursor = (self.cursor + take) % self.samples.len();
+            written += take;
+        }
+    }
+}
+
+type BlockCallback = Box<dyn FnMut(&[f32]) + Send>;
+
+/// Entrée cadencée par un thread : appelle `callback` avec un bloc de
+/// `block_frames` frames toutes les `block_frames / sample_rate`
+/// secondes, comme le ferait le callback d'un stream d'entrée.
+///
+/// Le thread démarre avec `play` et s'arrête quand le handle est droppé.
+pub struct ClockedInput {
+    pending: Option<(LoopedAudio, BlockCallback)>,
+    sample_rate: u32,
+    block_frames: usize,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ClockedInput {
+    pub fn new(
+        audio: LoopedAudio,
+        sample_rate: u32,
+        block_frames: usize,
+        callback: impl FnMut(&[f32]) + Send + 'static,
+    ) -> Self {
+        Self {
+            pending: Some((audio, Box::new(callback))),
+            sample_rate: sample_rate.max(1),
+            block_frames: block_frames.max(1),
+            running: Arc::new(AtomicBool::new(true)),
+            thread: None,
+        }
+    }
+
+    /// Lance le thread (sans effet s'il tourne déjà).
+    pub fn play(&mut self) -> TroubadourResult<()> {
+        let Some((mut audio, mut callback)) = self.pending.take() else {
+            return Ok(());
+        };
+        let running = self.running.clone();
+        let period =
+            Duration::from_secs_f64(self.block_frames as f64 / f64::from(self.sample_rate));
+        let mut block = vec![0.0; self.block_frames * audio.channels() as usize];
+        let thread = std::thread::Builder::new()
+            .name("clocked-input".into())
+            .spawn(move || {
+                let mut next = Instant::now();
+                while running.load(Ordering::Relaxed) {
+                    audio.fill(&mut block);
+                    callback(&block);
+                    next += period;
+                    let now = Instant::now();
+                    if next > now {
+                        std::thread::sleep(next - now);
+                    } else if now - next > period * MAX_LATE_BLOCKS {
+                        next = now;
+                    }
+                }
+            })
+            .map_err(|e| TroubadourError::StreamError(format!("Cannot start input clock: {e}")))?;
+        self.thread = Some(thread);
+        Ok(())
+    }
+}
+
+impl Drop for ClockedInput {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::AtomicUsize;
+
+    fn resolve(primary: Option<&str>, policy: &InputFallback) -> Option<ResolvedInput> {
+        let plugged = ["Built-in Mic", "USB Mic"];
+        resolve_input(primary, Some("Built-in Mic"), policy, |name| {
+            plugged.contains(&name)
+        })
+    }
+
+    fn fallback(source: InputSource) -> Option<ResolvedInput> {
+        Some(ResolvedInput {
+            source,
+            fallback: true,
+        })
+    }
+
+    #[test]
+    fn present_device_is_not_a_fallback() {
+        for policy in [InputFallback::SystemDefault, InputFallback::Silence] {
+            assert_eq!(
+                resolve(Some("USB Mic"), &policy),
+                Some(ResolvedInput {
+                    source: InputSource::Device("USB Mic".into()),
+                    fallback: false,
+                })
+            );
+        }
+        // Pas de device voulu : le défaut du système, sans repli
+        assert!(!resolve(None, &InputFallback::Silence).unwrap().fallback);
+    }
+
+    #[test]
+    fn each_policy_replaces_a_missing_device() {
+        let docked = Some("Dock Interface");
+        assert_eq!(
+            resolve(docked, &InputFallback::SystemDefault),
+            fallback(InputSource::Device("Built-in Mic".into()))
+        );
+        assert_eq!(
+            resolve(docked, &InputFallback::Silence),
+            fallback(InputSource::Silence)
+        );
+        let devices = InputFallback::Devices(vec!["Headset".into(), "USB Mic".into()]);
+        assert_eq!(
+            resolve(docked, &devices),
+            fallback(InputSource::Device("USB Mic".into()))
+        );
+        let unplugged = InputFallback::Devices(vec!["Headset".into()]);
+        assert_eq!(resolve(docked, &unplugged), fallback(InputSource::Silence));
+        let file = InputFallback::File(PathBuf::from("hold.wav"));
+        assert_eq!(
+            resolve(docked, &file),
+            fallback(InputSource::File("hold.wav".into()))
+        );
+
+        // Aucun device du tout : seul le défaut du système ne peut rien
+        let nothing = |policy: &InputFallback| resolve_input(None, None, policy, |_| false);
+        assert_eq!(nothing(&InputFallback::SystemDefault), None);
+        assert_eq!(
+            nothing(&InputFallback::Silence),
+            fallback(InputSource::Silence)
+        );
+    }
+
+    #[test]
+    fn looped_audio_wraps_around() {
+        let mut audio = LoopedAudio::from_wav(WavAudio {
+            sample_rate: 48_000,
+            channels: 1,
+            samples: vec![1.0, 2.0, 3.0],
+        });
+        let mut block = [0.0; 7];
+        audio.fill(&mut block);
+        assert_eq!(block, [1.0, 2.0, 3.0, 1.0, 2.0, 3.0, 1.0]);
+        audio.fill(&mut block[..2]);
+        assert_eq!(block[..2], [2.0, 3.0]);
+
+        let mut silence = LoopedAudio::silence(2);
+        let mut block = [1.0; 4];
+        silence.fill(&mut block);
+        assert_eq!(block, [0.0; 4]);
+    }
+
+    #[test]
+    fn clocked_input_paces_blocks_until_dropped() {
+        let blocks = Arc::new(AtomicUsize::new(0));
+        let counted = blocks.clone();
+        // 480 frames à 48 kHz : un bloc toutes les 10 ms
+        let mut input = ClockedInput::new(LoopedAudio::silence(1), 48_000, 480, move |block| {
+            assert_eq!(block.len(), 480);
+            counted.fetch_add(1, Ordering::Relaxed);
+        });
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(blocks.load(Ordering::Relaxed), 0, "not started before play");
+
+        input.play().unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        drop(input);
+        let delivered = blocks.load(Ordering::Relaxed);
+        assert!(
+            (5..=40).contains(&delivered),
+            "{delivered} blocks in 200 ms"
+        );
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(blocks.load(Ordering::Relaxed), delivered);
+    }
+}
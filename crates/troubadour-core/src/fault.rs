@@ -0,0 +1,76 @@
+//! Isolation des panics du traitement audio.
+//!
+//! Un panic qui remonte jusqu'au callback cpal n'a personne pour
+//! l'attraper : selon la plateforme, le stream meurt en silence ou
+//! l'application entière s'arrête. Le traitement est donc exécuté sous
+//! `catch_unwind`, à deux niveaux :
+//!
+//! ```text
+//!  callback ─► catch_unwind ─► InputProcessor ─► EffectsChain::process_block
+//!              (canal réduit                       └─ catch_unwind
+//!               au silence)                           (étage fautif sauté)
+//! ```
+//!
+//! Le callback de sortie (`OutputRenderer`) est gardé de la même façon :
+//! un panic du rendu rend le device muet, le bus joué passe `Silenced`.
+//!
+//! Un étage ou un canal qui a paniqué est mis à l'écart pour de bon :
+//! son état interne est peut-être incohérent, et il paniquerait
+//! probablement au bloc suivant. L'étage revient quand la chaîne est
+//! reconstruite (preset rechargé), le canal quand le pipeline est
+//! rouvert.
+
+use std::any::Any;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::mixer::ChannelHealth;
+
+/// Message d'un panic (`panic!("...")` ou `panic!("{x}")`).
+pub fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "unknown panic".to_string(), |s| s.to_string()),
+    }
+}
+
+/// État de santé de chaque canal, lisible sans lock.
+///
+/// Même principe que `SignalStates` : le callback écrit, l'engine lit,
+/// un atomique par id de canal.
+pub struct ChannelFaults {
+    health: Vec<AtomicU8>,
+}
+
+impl ChannelFaults {
+    pub fn new(max_channels: usize) -> Self {
+        Self {
+            health: (0..max_channels).map(|_| AtomicU8::new(0)).collect(),
+        }
+    }
+
+    pub fn set(&self, channel: ChannelId, health: ChannelHealth) {
+        if let Some(slot) = self.health.get(channel.0) {
+            let value = match health {
+                ChannelHealth::Ok => 0,
+                ChannelHealth::EffectBypassed => 1,
+                ChannelHealth::Silenced => 2,
+            };
+            slot.store(value, Ordering::Relaxed);
+        }
+    }
+
+    pub fn get(&self, channel: ChannelId) -> ChannelHealth {
+        match self
+            .health
+            .get(channel.0)
+            .map(|s| s.load(Ordering::Relaxed))
+        {
+            Some(1) => ChannelHealth::EffectBypassed,
+            Some(2) => ChannelHealth::Silenced,
+            _ => ChannelHealth::Ok,
+        }
+    }
+}
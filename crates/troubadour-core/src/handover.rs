@@ -0,0 +1,230 @@
+//! Bascule d'un pipeline audio à un autre sans trou : "make-before-break".
+//!
+//! Rouvrir les streams (changement de device, défaut du système qui
+//! change, stream bloqué) laisse un silence de quelques dizaines de ms :
+//! un stream cpal tout juste créé ne livre pas tout de suite. Ici, le
+//! nouveau pipeline démarre À CÔTÉ de l'ancien, muet :
+//!
+//! ```text
+//!  ancien  ████████████████████▓▓▒▒░░            (fermé une fois muet)
+//!  nouveau ░░░░░░░░░░░░░░░░░░░░░░▒▒▓▓████████████
+//!          │◄── pré-roll : N blocs ──►│◄ fondu ►│
+//!              pleins livrés
+//! ```
+//!
+//! 1. Le nouveau pipeline tourne en attente (gain 0) et compte les blocs
+//!    que sa réserve a pu remplir en entier.
+//! 2. Après `N` blocs, le moteur inverse les deux portes : l'ancien
+//!    s'éteint pendant que le nouveau monte (fondu croisé, pas de saut
+//!    de phase audible).
+//! 3. Une fois l'ancien muet, ses streams sont fermés.
+//!
+//! Les portes sont des atomiques : le moteur les bascule, chaque
+//! callback de sortie applique son gain avec un `HandoverFader`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Durée du fondu croisé de la bascule.
+pub const HANDOVER_FADE_MS: f32 = 10.0;
+
+/// Délai après lequel la bascule se fait même si le nouveau pipeline
+/// n'a pas fini son pré-roll (device muet, callback bloqué) : on ne
+/// garde pas deux pipelines ouverts indéfiniment.
+pub const HANDOVER_TIMEOUT_MS: u64 = 500;
+
+/// État d'un pipeline vu par la bascule, partagé moteur ↔ callback.
+#[derive(Debug)]
+pub struct HandoverGate {
+    /// Le pipeline doit être entendu (sinon : fondu vers le silence)
+    active: AtomicBool,
+    /// Blocs livrés en entier par la réserve de sortie
+    delivered: AtomicU32,
+    /// Inactif et arrivé au silence : ses streams peuvent être fermés
+    silent: AtomicBool,
+}
+
+impl HandoverGate {
+    /// Porte d'un pipeline entendu dès son premier bloc (démarrage).
+    pub fn active() -> Arc<Self> {
+        Arc::new(Self::new(true))
+    }
+
+    /// Porte d'un pipeline en pré-roll : il tourne, mais muet.
+    pub fn standby() -> Arc<Self> {
+        Arc::new(Self::new(false))
+    }
+
+    fn new(active: bool) -> Self {
+        Self {
+            active: AtomicBool::new(active),
+            delivered: AtomicU32::new(0),
+            silent: AtomicBool::new(!active),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    pub fn activate(&self) {
+        self.silent.store(false, Ordering::Relaxed);
+        self.active.store(true, Ordering::Relaxed);
+    }
+
+    pub fn deactivate(&self) {
+        self.active.store(false, Ordering::Relaxed);
+    }
+
+    /// Entendu, même en partie : actif, ou en fondu de sortie. Son
+    /// signal doit alors être traité comme celui d'un pipeline actif.
+    pub fn is_audible(&self) -> bool {
+        self.is_active() || !self.silent.load(Ordering::Relaxed)
+    }
+
+    /// Blocs pleins livrés depuis l'ouverture.
+    pub fn delivered_blocks(&self) -> u32 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    /// Inactif et fondu terminé.
+    pub fn is_silent(&self) -> bool {
+        !self.is_active() && self.silent.load(Ordering::Relaxed)
+    }
+}
+
+/// Gain de bascule appliqué par un callback de sortie.
+///
+/// Le gain glisse vers 1 (porte active) ou 0 (inactive) d'un pas par
+/// frame : un fondu de `HANDOVER_FADE_MS`, sans allocation.
+pub struct HandoverFader {
+    gate: Arc<HandoverGate>,
+    gain: f32,
+    step: f32,
+}
+
+impl HandoverFader {
+    pub fn new(gate: Arc<HandoverGate>, sample_rate: f32) -> Self {
+        let fade_frames = (sample_rate * HANDOVER_FADE_MS / 1000.0).max(1.0);
+        let gain = if gate.is_active() { 1.0 } else { 0.0 };
+        Self {
+            gate,
+            gain,
+            step: 1.0 / fade_frames,
+        }
+    }
+
+    /// Applique le gain à un bloc entrelacé de `channels` canaux (le
+    /// stéréo de chaque bus rendu par le device).
+    ///
+    /// `delivered` : la réserve a rempli tout le bloc (ni constitution,
+    /// ni underrun), compté pour le pré-roll.
+    pub fn apply(&mut self, block: &mut [f32], channels: usize, delivered: bool) {
+        if delivered {
+            self.gate.delivered.fetch_add(1, Ordering::Relaxed);
+        }
+        let target = if self.gate.is_active() { 1.0 } else { 0.0 };
+        if self.gain == target {
+            if target == 0.0 {
+                block.fill(0.0);
+                self.gate.silent.store(true, Ordering::Relaxed);
+            }
+            return;
+        }
+        // Une frame tronquée (bloc impair) suit le fondu elle aussi
+        for frame in block.chunks_mut(channels.max(1)) {
+            self.gain = if target > self.gain {
+                (self.gain + self.step).min(target)
+            } else {
+                (self.gain - self.step).max(target)
+            };
+            frame.iter_mut().for_each(|s| *s *= self.gain);
+        }
+        if self.gain == 0.0 {
+            self.gate.silent.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Faux pipeline : une réserve qui livre un signal constant une fois
+    /// `warmup` blocs passés (un stream cpal qui démarre).
+    struct MockPipeline {
+        fader: HandoverFader,
+        warmup: usize,
+        rendered: usize,
+    }
+
+    impl MockPipeline {
+        fn new(gate: Arc<HandoverGate>, warmup: usize) -> Self {
+            Self {
+                fader: HandoverFader::new(gate, 48_000.0),
+                warmup,
+                rendered: 0,
+            }
+        }
+
+        fn render(&mut self, block: &mut [f32]) {
+            let delivered = self.rendered >= self.warmup;
+            block.fill(if delivered { 0.5 } else { 0.0 });
+            self.fader.apply(block, 2, delivered);
+            self.rendered += 1;
+        }
+    }
+
+    #[test]
+    fn no_block_goes_silent_while_switching_between_healthy_pipelines() {
+        const PREROLL: u32 = 3;
+        let old_gate = HandoverGate::active();
+        let mut old = MockPipeline::new(old_gate.clone(), 0);
+        let new_gate = HandoverGate::standby();
+        // Le nouveau stream met 5 blocs à livrer
+        let mut new = MockPipeline::new(new_gate.clone(), 5);
+
+        let mut old_block = vec![0.0; 256];
+        let mut new_block = vec![0.0; 256];
+        let mut old_open = true;
+        let mut blocks = 0;
+        while old_open {
+            blocks += 1;
+            assert!(blocks < 100, "handover never finished");
+            old.render(&mut old_block);
+            new.render(&mut new_block);
+            // Le device mélange les deux streams
+            let quietest = old_block
+                .iter()
+                .zip(&new_block)
+                .map(|(a, b)| a + b)
+                .fold(f32::MAX, f32::min);
+            assert!(quietest > 0.4, "block {blocks} dropped to {quietest}");
+
+            // Tour du moteur entre deux blocs
+            if !new_gate.is_active() && new_gate.delivered_blocks() >= PREROLL {
+                new_gate.activate();
+                old_gate.deactivate();
+            }
+            old_open = !old_gate.is_silent();
+        }
+        // Pré-roll (5 + 3 blocs) puis un fondu de 480 frames (4 blocs de 128)
+        assert_eq!(blocks, 12);
+        new.render(&mut new_block);
+        assert!(new_block.iter().all(|&s| s == 0.5));
+    }
+
+    #[test]
+    fn standby_pipeline_stays_muted_until_activated() {
+        let gate = HandoverGate::standby();
+        let mut pipeline = MockPipeline::new(gate.clone(), 0);
+        let mut block = vec![0.0; 64];
+        pipeline.render(&mut block);
+        assert!(block.iter().all(|&s| s == 0.0));
+        assert_eq!(gate.delivered_blocks(), 1);
+        // Il n'a jamais joué : le fermer ne coupe rien
+        assert!(gate.is_silent());
+        gate.activate();
+        assert!(!gate.is_silent());
+    }
+}
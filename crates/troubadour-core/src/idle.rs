@@ -0,0 +1,213 @@
+//! Mise en veille de la boucle de contrôle quand rien ne joue.
+//!
+//! L'audio est cadencé par les callbacks des devices : il ne s'arrête
+//! jamais, et aucun sample n'est perdu au réveil. Ce qui dort, c'est la
+//! boucle qui appelle `Engine::process_commands` : quand l'entrée ne
+//! livre que du silence depuis un moment (et qu'aucun tap ne lit le
+//! bus), elle passe d'un tick par frame à `IDLE_TICK`, le temps de voir
+//! revenir le signal. Les meters de l'UI, eux, continuent d'être
+//! alimentés par le callback et de redescendre.
+//!
+//! ```text
+//!            silence pendant `idle_after`
+//!  Actif ─────────────────────────────────► Veille
+//!    ▲                                        │
+//!    └────── signal ≥ seuil + 6 dB, ──────────┘
+//!            commande, tap ouvert
+//! ```
+//!
+//! Le seuil de réveil est plus haut que celui du silence : un bruit de
+//! fond qui oscille autour du seuil ne fait pas basculer la boucle à
+//! chaque tick.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Tick de la boucle de contrôle en veille (10 Hz).
+pub const IDLE_TICK: Duration = Duration::from_millis(100);
+
+/// Écart entre le seuil de silence et celui du réveil (+6 dB).
+const WAKE_RATIO: f32 = 2.0;
+
+/// Ce que l'entrée a livré depuis le dernier relevé, écrit par le
+/// callback sans lock ni allocation.
+#[derive(Debug, Default)]
+pub struct InputActivity {
+    blocks: AtomicU64,
+    /// Crête max (bits d'un `f32` positif : l'ordre des bits est celui
+    /// des valeurs, `fetch_max` suffit)
+    peak: AtomicU32,
+}
+
+/// Relevé de `InputActivity`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ActivitySample {
+    /// Blocs livrés par l'entrée
+    pub blocks: u64,
+    /// Crête la plus haute de ces blocs
+    pub peak: f32,
+}
+
+impl InputActivity {
+    pub fn record(&self, peak: f32) {
+        self.blocks.fetch_add(1, Ordering::Relaxed);
+        let peak = if peak.is_finite() { peak.abs() } else { 0.0 };
+        self.peak.fetch_max(peak.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Activité depuis le relevé précédent (remise à zéro).
+    pub fn take(&self) -> ActivitySample {
+        ActivitySample {
+            blocks: self.blocks.swap(0, Ordering::Relaxed),
+            peak: f32::from_bits(self.peak.swap(0, Ordering::Relaxed)),
+        }
+    }
+}
+
+/// Passage de la boucle en veille et retour.
+#[derive(Debug, Clone)]
+pub struct IdleDetector {
+    /// Silence avant la veille (`None` : jamais de veille)
+    idle_after: Option<Duration>,
+    /// Crête linéaire sous laquelle un bloc compte comme silence
+    threshold: f32,
+    quiet_since: Option<Instant>,
+    idle: bool,
+}
+
+impl IdleDetector {
+    /// `idle_after_seconds` à 0 : la boucle ne se met jamais en veille.
+    pub fn new(idle_after_seconds: u32, threshold_db: f32) -> Self {
+        Self {
+            idle_after: (idle_after_seconds > 0)
+                .then(|| Duration::from_secs(u64::from(idle_after_seconds))),
+            threshold: 10f32.powf(threshold_db / 20.0),
+            quiet_since: None,
+            idle: false,
+        }
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle
+    }
+
+    /// Tient compte de l'activité relevée à `now`. `busy` : quelque
+    /// chose d'autre que l'entrée demande la boucle (commande reçue,
+    /// tap ouvert).
+    ///
+    /// Retourne le nouvel état s'il vient de changer.
+    pub fn observe(&mut self, activity: ActivitySample, busy: bool, now: Instant) -> Option<bool> {
+        let threshold = if self.idle {
+            self.threshold * WAKE_RATIO
+        } else {
+            self.threshold
+        };
+        let signal = activity.blocks > 0 && activity.peak >= threshold;
+        let Some(idle_after) = self.idle_after.filter(|_| !busy && !signal) else {
+            self.quiet_since = None;
+            return std::mem::take(&mut self.idle).then_some(false);
+        };
+        let quiet_since = *self.quiet_since.get_or_insert(now);
+        if !self.idle && now.duration_since(quiet_since) >= idle_after {
+            self.idle = true;
+            return Some(true);
+        }
+        None
+    }
+
+    /// Attente de la boucle de contrôle : `active` en marche normale,
+    /// au moins `IDLE_TICK` en veille.
+    pub fn tick(&self, active: Duration) -> Duration {
+        if self.idle {
+            active.max(IDLE_TICK)
+        } else {
+            active
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(peak: f32) -> ActivitySample {
+        ActivitySample { blocks: 10, peak }
+    }
+
+    #[test]
+    fn silence_then_signal_then_silence() {
+        // -60 dB : 0.001 ; réveil à 0.002
+        let mut idle = IdleDetector::new(2, -60.0);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        assert_eq!(idle.observe(blocks(0.0005), false, at(0)), None);
+        assert_eq!(idle.observe(blocks(0.0005), false, at(1_000)), None);
+        // Un bloc au-dessus du seuil relance l'attente
+        assert_eq!(idle.observe(blocks(0.5), false, at(1_500)), None);
+        assert_eq!(idle.observe(blocks(0.0), false, at(3_000)), None);
+        assert_eq!(idle.observe(blocks(0.0), false, at(5_000)), Some(true));
+        assert!(idle.is_idle());
+        assert_eq!(idle.tick(Duration::from_millis(16)), IDLE_TICK);
+
+        // Entre les deux seuils : reste en veille
+        assert_eq!(idle.observe(blocks(0.0015), false, at(5_100)), None);
+        // Au-dessus du seuil de réveil : réveil immédiat
+        assert_eq!(idle.observe(blocks(0.01), false, at(5_200)), Some(false));
+        assert_eq!(
+            idle.tick(Duration::from_millis(16)),
+            Duration::from_millis(16)
+        );
+
+        // Éveillée, le même niveau intermédiaire compte comme du signal
+        assert_eq!(idle.observe(blocks(0.0015), false, at(7_300)), None);
+        assert_eq!(idle.observe(blocks(0.0), false, at(7_400)), None);
+        assert_eq!(idle.observe(blocks(0.0), false, at(9_400)), Some(true));
+    }
+
+    #[test]
+    fn no_input_counts_as_silence_but_taps_and_commands_keep_it_awake() {
+        let mut idle = IdleDetector::new(1, -60.0);
+        let start = Instant::now();
+        let nothing = ActivitySample::default();
+        idle.observe(nothing, false, start);
+        assert_eq!(
+            idle.observe(nothing, true, start + Duration::from_secs(5)),
+            None
+        );
+        idle.observe(nothing, false, start + Duration::from_secs(6));
+        assert_eq!(
+            idle.observe(nothing, false, start + Duration::from_secs(7)),
+            Some(true)
+        );
+        assert_eq!(
+            idle.observe(nothing, true, start + Duration::from_secs(8)),
+            Some(false)
+        );
+
+        // Désactivée : jamais de veille
+        let mut never = IdleDetector::new(0, -60.0);
+        for s in 0..100 {
+            assert_eq!(
+                never.observe(nothing, false, start + Duration::from_secs(s)),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn activity_is_taken_once() {
+        let activity = InputActivity::default();
+        activity.record(0.25);
+        activity.record(0.5);
+        activity.record(f32::NAN);
+        assert_eq!(
+            activity.take(),
+            ActivitySample {
+                blocks: 3,
+                peak: 0.5
+            }
+        );
+        assert_eq!(activity.take(), ActivitySample::default());
+    }
+}
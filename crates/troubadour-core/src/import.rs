@@ -0,0 +1,479 @@
+//! Import d'une configuration audio OBS Studio ou VoiceMeeter.
+//!
+//! # Principe
+//! Les parseurs sont des fonctions pures (texte → `ExternalSource`) et
+//! la résolution des devices reçoit la liste des devices de la machine
+//! en paramètre : tout se teste sans fichier système ni carte son.
+//!
+//! ```text
+//! scènes OBS (.json) ─┐
+//!                     ├→ Vec<ExternalSource> → build_profile(devices) → Profile + ImportReport
+//! VoiceMeeter (.xml) ─┘
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::dsp::EffectsPreset;
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::import::{ImportFormat, ImportReport};
+use troubadour_shared::mixer::{ChannelConfig, MixerConfig, Route};
+use troubadour_shared::profile::Profile;
+
+use crate::taper::VolumeDecibels;
+
+/// Une source (ou un bus) lue dans une config externe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalSource {
+    pub name: String,
+    /// Device tel que l'autre logiciel le nomme (`None` : device par
+    /// défaut du système, ou source sans device)
+    pub device: Option<String>,
+    /// Gain linéaire (1.0 = 0 dB)
+    pub gain: f32,
+    pub muted: bool,
+    /// `false` pour un bus de sortie (VoiceMeeter A1, A2…)
+    pub is_input: bool,
+}
+
+/// Sources d'une config externe, et ce qui a été ignoré.
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedConfig {
+    pub sources: Vec<ExternalSource>,
+    pub skipped: Vec<String>,
+}
+
+/// Clés des devices audio globaux d'une collection de scènes OBS.
+const OBS_GLOBAL_AUDIO: [&str; 6] = [
+    "DesktopAudioDevice1",
+    "DesktopAudioDevice2",
+    "AuxAudioDevice1",
+    "AuxAudioDevice2",
+    "AuxAudioDevice3",
+    "AuxAudioDevice4",
+];
+
+/// Lit les sources audio d'une collection de scènes OBS.
+///
+/// Sont repris : les devices audio globaux (`DesktopAudioDevice1`,
+/// `AuxAudioDevice1`…) et les sources de capture audio de la liste
+/// `sources` (`*_input_capture`, `*_output_capture`). Le volume OBS est
+/// déjà un gain linéaire. Une source présente deux fois (même nom) n'est
+/// gardée qu'une fois.
+pub fn parse_obs(json: &str) -> Result<ParsedConfig, String> {
+    let root: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let root = root.as_object().ok_or("not a scene collection")?;
+
+    let globals = OBS_GLOBAL_AUDIO.iter().filter_map(|key| root.get(*key));
+    let listed = root
+        .get("sources")
+        .and_then(|s| s.as_array())
+        .into_iter()
+        .flatten();
+
+    let mut parsed = ParsedConfig::default();
+    for source in globals.chain(listed) {
+        let name = source["name"].as_str().unwrap_or_default().to_string();
+        let kind = source["id"].as_str().unwrap_or_default();
+        if !is_obs_audio_capture(kind) {
+            continue;
+        }
+        if parsed.sources.iter().any(|s| s.name == name) {
+            parsed.skipped.push(format!("{name}: duplicate source"));
+            continue;
+        }
+        // Capture d'application : pas de device à reprendre
+        let device = source["settings"]["device_id"]
+            .as_str()
+            .filter(|id| !id.is_empty() && *id != "default")
+            .map(str::to_string);
+        parsed.sources.push(ExternalSource {
+            name,
+            device,
+            gain: source["volume"].as_f64().unwrap_or(1.0) as f32,
+            muted: source["muted"].as_bool().unwrap_or(false),
+            is_input: true,
+        });
+    }
+    Ok(parsed)
+}
+
+fn is_obs_audio_capture(kind: &str) -> bool {
+    kind.ends_with("_input_capture") || kind.ends_with("_output_capture")
+}
+
+/// Lit les strips (entrées) et bus (sorties) d'un export VoiceMeeter.
+///
+/// Seules les balises `<Strip>` et `<Bus>` sont lues, avec leurs
+/// attributs `label`, `device`, `gain` (dB) et `mute`. Un strip ou bus
+/// sans label ni device est inutilisé : il est ignoré. Sans label, un
+/// strip prend le nom de son device, un bus celui de VoiceMeeter
+/// (`A1`, `A2`…).
+pub fn parse_voicemeeter(xml: &str) -> Result<ParsedConfig, String> {
+    if !xml.contains("<VBAudioVoicemeeterSettings") {
+        return Err("not a VoiceMeeter settings export".into());
+    }
+    let mut parsed = ParsedConfig::default();
+    for (tag, attrs) in xml_elements(xml) {
+        let is_input = match tag {
+            "Strip" => true,
+            "Bus" => false,
+            _ => continue,
+        };
+        let attr = |key: &str| {
+            attrs
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.trim())
+                .filter(|v| !v.is_empty())
+        };
+        let index: usize = attr("index").and_then(|i| i.parse().ok()).unwrap_or(0);
+        let device = attr("device").map(str::to_string);
+        let name = match (attr("label"), &device) {
+            (Some(label), _) => label.to_string(),
+            (None, Some(device)) if is_input => device.clone(),
+            (None, Some(_)) => format!("A{}", index + 1),
+            (None, None) => continue,
+        };
+        let gain_db = match attr("gain").map(str::parse::<f32>) {
+            Some(Ok(db)) => db,
+            Some(Err(_)) => {
+                parsed
+                    .skipped
+                    .push(format!("{name}: unreadable gain, 0 dB used"));
+                0.0
+            }
+            None => 0.0,
+        };
+        parsed.sources.push(ExternalSource {
+            name,
+            device,
+            gain: VolumeDecibels(gain_db).to_linear(),
+            muted: attr("mute") == Some("1"),
+            is_input,
+        });
+    }
+    Ok(parsed)
+}
+
+/// Balises ouvrantes d'un document XML simple : nom et attributs
+/// (entités de base décodées). Pas un parseur XML complet : pas de
+/// CDATA ni de namespaces, ce qu'un export VoiceMeeter n'utilise pas.
+fn xml_elements(xml: &str) -> Vec<(&str, Vec<(&str, String)>)> {
+    let mut elements = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('>') else { break };
+        let body = &rest[..end];
+        rest = &rest[end + 1..];
+        if body.starts_with(['/', '?', '!']) {
+            continue;
+        }
+        let body = body.trim_end_matches('/');
+        let (tag, mut attrs_text) = body.split_once(char::is_whitespace).unwrap_or((body, ""));
+        let mut attrs = Vec::new();
+        while let Some(eq) = attrs_text.find('=') {
+            let key = attrs_text[..eq].trim();
+            let value_text = attrs_text[eq + 1..].trim_start();
+            let Some(quote) = value_text
+                .chars()
+                .next()
+                .filter(|c| *c == '"' || *c == '\'')
+            else {
+                break;
+            };
+            let Some(close) = value_text[1..].find(quote) else {
+                break;
+            };
+            attrs.push((key, unescape_xml(&value_text[1..1 + close])));
+            attrs_text = &value_text[close + 2..];
+        }
+        elements.push((tag, attrs));
+    }
+    elements
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Mots d'un nom de device, en minuscules ("alsa_input.usb-Blue_Yeti"
+/// → alsa, input, usb, blue, yeti).
+fn device_words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Ressemblance de deux noms de device, de 0 à 1 : part des mots en
+/// commun sur l'ensemble des mots des deux noms (coefficient de Dice).
+pub fn name_similarity(a: &str, b: &str) -> f32 {
+    let (a, b) = (device_words(a), device_words(b));
+    let common = a.iter().filter(|w| b.contains(w)).count();
+    ((2 * common) as f32 / (a.len() + b.len()).max(1) as f32).min(1.0)
+}
+
+/// Device de cette machine qui correspond le mieux à `wanted`.
+///
+/// # Pourquoi une correspondance approximative ?
+/// Chaque logiciel nomme les devices à sa façon : OBS sous Linux garde
+/// l'identifiant PulseAudio (`alsa_input.usb-Blue_Microphones_Yeti_…`),
+/// VoiceMeeter le nom Windows (`Microphone (Yeti Stereo Microphone)`).
+/// On compare les mots : un device est candidat si au moins 60 % de ses
+/// mots apparaissent dans le nom cherché, et le candidat qui partage le
+/// plus de mots l'emporte (à égalité, le plus couvert).
+pub fn resolve_device(wanted: &str, candidates: &[String]) -> Option<String> {
+    if let Some(exact) = candidates.iter().find(|c| c.eq_ignore_ascii_case(wanted)) {
+        return Some(exact.clone());
+    }
+    let wanted = device_words(wanted);
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let words = device_words(candidate);
+            let common = words.iter().filter(|w| wanted.contains(w)).count();
+            let coverage = common as f32 / words.len().max(1) as f32;
+            (coverage >= 0.6).then_some((candidate, common, coverage))
+        })
+        .max_by(|a, b| a.1.cmp(&b.1).then(a.2.total_cmp(&b.2)))
+        .map(|(candidate, _, _)| candidate.clone())
+}
+
+/// Construit le profil importé.
+///
+/// Une entrée par source, puis les bus lus (ou, sans bus, ceux de la
+/// config par défaut). Chaque entrée est routée vers le premier bus
+/// (A1). Les devices sont résolus parmi `inputs` / `outputs` ; ceux
+/// qui n'existent pas ici sont reportés et le canal reste sans device.
+pub fn build_profile(
+    name: &str,
+    parsed: ParsedConfig,
+    inputs: &[String],
+    outputs: &[String],
+) -> (Profile, ImportReport) {
+    let mut report = ImportReport {
+        preset: name.to_string(),
+        skipped: parsed.skipped,
+        ..ImportReport::default()
+    };
+    let (sources, buses): (Vec<_>, Vec<_>) = parsed.sources.into_iter().partition(|s| s.is_input);
+
+    let mut channels = Vec::new();
+    for source in &sources {
+        channels.push(imported_channel(
+            ChannelConfig::input(channels.len(), source.name.clone()),
+            source,
+            inputs,
+            &mut report,
+        ));
+    }
+    if buses.is_empty() {
+        for bus in MixerConfig::default_setup().outputs() {
+            channels.push(ChannelConfig::output(channels.len(), bus.name.clone()));
+        }
+    }
+    for bus in &buses {
+        channels.push(imported_channel(
+            ChannelConfig::output(channels.len(), bus.name.clone()),
+            bus,
+            outputs,
+            &mut report,
+        ));
+    }
+
+    let first_bus = ChannelId(sources.len());
+    let routes = (0..sources.len())
+        .map(|i| Route::new(ChannelId(i), first_bus))
+        .collect();
+    let device_of = |id: usize| {
+        channels
+            .get(id)
+            .and_then(|c| c.device_name.specific().map(str::to_string))
+    };
+    let profile = Profile {
+        name: name.to_string(),
+        input_device: device_of(0),
+        output_device: device_of(first_bus.0),
+        mixer: MixerConfig {
+            channels,
+            routes,
+            crossfades: Vec::new(),
+            links: Vec::new(),
+            bus_follows: Vec::new(),
+        },
+        effects: EffectsPreset::default_preset(),
+        bus_effects: BTreeMap::new(),
+    };
+    report.channels = profile.mixer.channels.len();
+    (profile, report)
+}
+
+fn imported_channel(
+    mut channel: ChannelConfig,
+    source: &ExternalSource,
+    devices: &[String],
+    report: &mut ImportReport,
+) -> ChannelConfig {
+    channel.volume = source.gain.clamp(0.0, 2.0);
+    channel.muted = source.muted;
+    if let Some(wanted) = &source.device {
+        channel.device_name = resolve_device(wanted, devices).into();
+        if channel.device_name.is_none() {
+            report
+                .unresolved_devices
+                .push(format!("{}: {wanted}", source.name));
+        }
+    }
+    channel
+}
+
+/// Lit et convertit le fichier `path`, sans rien sauver.
+pub fn import_external(
+    path: &Path,
+    format: ImportFormat,
+    name: &str,
+    inputs: &[String],
+    outputs: &[String],
+) -> TroubadourResult<(Profile, ImportReport)> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| TroubadourError::ConfigError(format!("{}: {e}", path.display())))?;
+    let parsed = match format {
+        ImportFormat::Obs => parse_obs(&text),
+        ImportFormat::VoiceMeeter => parse_voicemeeter(&text),
+    }
+    .map_err(|e| TroubadourError::ConfigError(format!("{} import: {e}", format.label())))?;
+    Ok(build_profile(name, parsed, inputs, outputs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OBS: &str = include_str!("../fixtures/import/obs_scenes.json");
+    const VOICEMEETER: &str = include_str!("../fixtures/import/voicemeeter.xml");
+
+    fn devices(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn obs_audio_sources_are_read_from_globals_and_source_list() {
+        let parsed = parse_obs(OBS).unwrap();
+        let names: Vec<&str> = parsed.sources.iter().map(|s| s.name.as_str()).collect();
+        // L'image n'est pas une source audio ; "Mic/Aux" est listé deux fois
+        assert_eq!(names, ["Desktop Audio", "Mic/Aux", "Guest Mic", "Game"]);
+        assert_eq!(parsed.skipped, ["Mic/Aux: duplicate source"]);
+
+        let desktop = &parsed.sources[0];
+        assert_eq!((desktop.device.as_deref(), desktop.gain), (None, 0.5));
+        let guest = &parsed.sources[2];
+        assert!(guest.muted);
+        assert_eq!(guest.gain, 0.25);
+        assert!(parsed.sources[3].device.is_none()); // capture d'application
+
+        assert!(parse_obs("[1, 2]").is_err());
+        assert!(parse_obs("{ not json").is_err());
+    }
+
+    #[test]
+    fn voicemeeter_strips_and_buses_are_read() {
+        let parsed = parse_voicemeeter(VOICEMEETER).unwrap();
+        let names: Vec<(&str, bool)> = parsed
+            .sources
+            .iter()
+            .map(|s| (s.name.as_str(), s.is_input))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("Mic", true),
+                ("Music & Alerts", true),
+                ("Desktop", true),
+                ("Guest", true),
+                ("A1", false),
+                ("Stream", false),
+            ]
+        );
+        let music = &parsed.sources[1];
+        assert!(music.muted);
+        assert!((VolumeDecibels::from_linear(music.gain).0 - -12.5).abs() < 1e-3);
+        assert!((parsed.sources[3].gain - 1.995).abs() < 1e-3); // +6 dB
+
+        assert!(parse_voicemeeter("<html></html>").is_err());
+    }
+
+    #[test]
+    fn devices_are_matched_by_shared_words() {
+        let inputs = devices(&["Built-in Microphone", "Yeti Stereo Microphone", "Webcam"]);
+        assert_eq!(
+            resolve_device(
+                "alsa_input.usb-Blue_Microphones_Yeti_Stereo_Microphone_REV8-00.analog-stereo",
+                &inputs
+            )
+            .as_deref(),
+            Some("Yeti Stereo Microphone")
+        );
+        assert_eq!(
+            resolve_device("Microphone (Yeti Stereo Microphone)", &inputs).as_deref(),
+            Some("Yeti Stereo Microphone")
+        );
+        assert_eq!(resolve_device("webcam", &inputs).as_deref(), Some("Webcam"));
+        assert_eq!(resolve_device("Elgato Wave:3", &inputs), None);
+        assert_eq!(resolve_device("{0.0.1.00000000}.{6f1b2c3d}", &inputs), None);
+    }
+
+    #[test]
+    fn obs_import_builds_a_profile_routed_to_a1() {
+        let inputs = devices(&["Yeti Stereo Microphone"]);
+        let (profile, report) =
+            build_profile("Imported OBS", parse_obs(OBS).unwrap(), &inputs, &[]);
+
+        let mixer = &profile.mixer;
+        assert_eq!(mixer.inputs().len(), 4);
+        let buses: Vec<&str> = mixer.outputs().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(buses, ["Headphones", "Speakers"]);
+        assert!(mixer.routes.iter().all(|r| r.to == ChannelId(4)));
+        assert_eq!(mixer.routes.len(), 4);
+
+        let mic = &mixer.channels[1];
+        assert_eq!(mic.device_name.specific(), Some("Yeti Stereo Microphone"));
+        assert!((mic.volume - 1.4125).abs() < 1e-3); // +3 dB
+        assert_eq!(report.channels, 6);
+        assert_eq!(report.unresolved_devices.len(), 1);
+        assert!(report.unresolved_devices[0].starts_with("Guest Mic: {0.0.1"));
+    }
+
+    #[test]
+    fn voicemeeter_import_keeps_its_buses_and_reports_missing_devices() {
+        let inputs = devices(&["Yeti Stereo Microphone"]);
+        let outputs = devices(&["Realtek High Definition Audio"]);
+        let (profile, report) = build_profile(
+            "Imported VoiceMeeter",
+            parse_voicemeeter(VOICEMEETER).unwrap(),
+            &inputs,
+            &outputs,
+        );
+
+        let a1 = &profile.mixer.channels[4];
+        assert_eq!(a1.name, "A1");
+        assert_eq!(
+            a1.device_name.specific(),
+            Some("Realtek High Definition Audio")
+        );
+        assert_eq!(profile.output_device.as_deref(), a1.device_name.specific());
+        assert_eq!(
+            profile.input_device.as_deref(),
+            Some("Yeti Stereo Microphone")
+        );
+        assert!(profile.mixer.routes.iter().all(|r| r.to == ChannelId(4)));
+        assert_eq!(report.unresolved_devices, ["Guest: Elgato Wave:3"]);
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::VecDeque;
+use std::io::Write;
+use std::path::PathBuf;
+
+use tracing::warn;
+use troubadour_shared::config::{JournalConfig, config_dir};
+use troubadour_shared::journal::ChangeEntry;
+
+/// Fenêtre de coalescence des changements continus (volume, pan), en ms.
+///
+/// Un fader déplacé à la souris envoie des dizaines de `SetVolume`
+/// par seconde. On garde UNE entrée par canal et par seconde,
+/// avec la valeur finale.
+pub const COALESCE_WINDOW_MS: u64 = 1000;
+
+/// Actions "continues" (sliders) dont les entrées sont fusionnées.
+const COALESCED_ACTIONS: &[&str] = &["SetVolume", "SetPan", "SetDelay", "SetCrossfadePosition"];
+
+/// Une entrée du ring + son état d'écriture dans le fichier de log.
+struct Slot {
+    entry: ChangeEntry,
+    logged: bool,
+}
+
+/// Journal des modifications du mixer (audit trail).
+///
+/// # Où vit le journal ?
+/// Dans la couche de traitement des commandes (`Engine::process_commands`),
+/// JAMAIS dans le callback audio. Écrire dans un fichier ou allouer
+/// une `String` depuis le thread audio causerait des dropouts.
+///
+/// # Ring buffer borné
+/// `VecDeque` avec une capacité max : quand elle est atteinte,
+/// l'entrée la plus ancienne est évincée. La mémoire reste bornée
+/// même pendant un live de 10 heures.
+///
+/// # Fichier de log
+/// Une entrée coalescée peut encore changer pendant sa fenêtre.
+/// On ne l'écrit donc dans le fichier qu'une fois "scellée"
+/// (fenêtre expirée), en respectant l'ordre chronologique.
+pub struct ChangeJournal {
+    slots: VecDeque<Slot>,
+    capacity: usize,
+    log_path: Option<PathBuf>,
+}
+
+impl ChangeJournal {
+    /// Crée un journal en mémoire seulement.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            slots: VecDeque::with_capacity(capacity.min(1024)),
+            capacity: capacity.max(1),
+            log_path: None,
+        }
+    }
+
+    /// Crée un journal depuis la config (`session.log` dans le dossier de config).
+    pub fn from_config(config: &JournalConfig) -> Self {
+        let journal = Self::new(config.capacity);
+        if config.log_to_file {
+            journal.with_log_file(config_dir().join("session.log"))
+        } else {
+            journal
+        }
+    }
+
+    /// Active l'écriture des entrées dans un fichier (mode append).
+    pub fn with_log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_path = Some(path.into());
+        self
+    }
+
+    /// Enregistre une modification.
+    ///
+    /// Les actions continues (`SetVolume`, `SetPan`) sont fusionnées avec
+    /// l'entrée du même canal si elle a moins de `COALESCE_WINDOW_MS` :
+    /// on garde le `before` d'origine et on met à jour `after`.
+    pub fn record(&mut self, entry: ChangeEntry) {
+        let now = entry.timestamp_ms;
+
+        if COALESCED_ACTIONS.contains(&entry.action.as_str())
+            && let Some(slot) = self.slots.iter_mut().rev().find(|s| {
+                !s.logged
+                    && s.entry.action == entry.action
+                    && s.entry.channel == entry.channel
+                    && now < s.entry.timestamp_ms + COALESCE_WINDOW_MS
+            })
+        {
+            slot.entry.after = entry.after;
+            self.flush_sealed(now);
+            return;
+        }
+
+        self.slots.push_back(Slot {
+            entry,
+            logged: false,
+        });
+
+        while self.slots.len() > self.capacity {
+            if let Some(evicted) = self.slots.pop_front()
+                && !evicted.logged
+            {
+                self.write_lines(std::slice::from_ref(&evicted.entry));
+            }
+        }
+
+        self.flush_sealed(now);
+    }
+
+    /// Retourne les `limit` dernières entrées (la plus ancienne en premier).
+    pub fn recent(&self, limit: usize) -> Vec<ChangeEntry> {
+        let skip = self.slots.len().saturating_sub(limit);
+        self.slots
+            .iter()
+            .skip(skip)
+            .map(|s| s.entry.clone())
+            .collect()
+    }
+
+    /// Vide le journal (les entrées pas encore écrites sont d'abord flushées).
+    pub fn clear(&mut self) {
+        self.flush();
+        self.slots.clear();
+    }
+
+    /// Écrit toutes les entrées en attente dans le fichier, même si
+    /// leur fenêtre de coalescence n'est pas terminée.
+    pub fn flush(&mut self) {
+        if self.log_path.is_none() {
+            return;
+        }
+        let pending: Vec<ChangeEntry> = self
+            .slots
+            .iter_mut()
+            .filter(|s| !s.logged)
+            .map(|s| {
+                s.logged = true;
+                s.entry.clone()
+            })
+            .collect();
+        self.write_lines(&pending);
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Écrit les entrées scellées, dans l'ordre, jusqu'à la première
+    /// entrée encore ouverte à la coalescence.
+    fn flush_sealed(&mut self, now: u64) {
+        if self.log_path.is_none() {
+            return;
+        }
+
+        let mut sealed = Vec::new();
+        for slot in self.slots.iter_mut().filter(|s| !s.logged) {
+            let open = COALESCED_ACTIONS.contains(&slot.entry.action.as_str())
+                && now < slot.entry.timestamp_ms + COALESCE_WINDOW_MS;
+            if open {
+                break;
+            }
+            slot.logged = true;
+            sealed.push(slot.entry.clone());
+        }
+        self.write_lines(&sealed);
+    }
+
+    fn write_lines(&self, entries: &[ChangeEntry]) {
+        let Some(path) = &self.log_path else {
+            return;
+        };
+        if entries.is_empty() {
+            return;
+        }
+
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            for entry in entries {
+                writeln!(file, "{}", entry.to_log_line())?;
+            }
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            warn!("Failed to write session log {}: {e}", path.display());
+        }
+    }
+}
+
+impl Drop for ChangeJournal {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Horodatage courant en millisecondes depuis l'epoch Unix.
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::audio::ChannelId;
+
+    fn entry(ts: u64, action: &str, channel: usize, before: &str, after: &str) -> ChangeEntry {
+        ChangeEntry {
+            timestamp_ms: ts,
+            action: action.to_string(),
+            channel: Some(ChannelId(channel)),
+            before: Some(before.to_string()),
+            after: Some(after.to_string()),
+        }
+    }
+
+    #[test]
+    fn volume_changes_within_a_second_are_coalesced() {
+        let mut journal = ChangeJournal::new(100);
+        journal.record(entry(1000, "SetVolume", 0, "1.00", "0.90"));
+        journal.record(entry(1300, "SetVolume", 0, "0.90", "0.70"));
+        journal.record(entry(1900, "SetVolume", 0, "0.70", "0.50"));
+
+        let log = journal.recent(10);
+        assert_eq!(log.len(), 1);
+        // Le `before` d'origine est conservé, le `after` est la valeur finale
+        assert_eq!(log[0].before.as_deref(), Some("1.00"));
+        assert_eq!(log[0].after.as_deref(), Some("0.50"));
+    }
+
+    #[test]
+    fn volume_changes_after_window_create_new_entry() {
+        let mut journal = ChangeJournal::new(100);
+        journal.record(entry(1000, "SetVolume", 0, "1.00", "0.90"));
+        journal.record(entry(2000, "SetVolume", 0, "0.90", "0.80"));
+        assert_eq!(journal.len(), 2);
+    }
+
+    #[test]
+    fn coalescing_is_per_channel() {
+        let mut journal = ChangeJournal::new(100);
+        journal.record(entry(1000, "SetVolume", 0, "1.00", "0.90"));
+        journal.record(entry(1100, "SetVolume", 1, "1.00", "0.40"));
+        journal.record(entry(1200, "SetVolume", 0, "0.90", "0.80"));
+
+        let log = journal.recent(10);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].after.as_deref(), Some("0.80"));
+        assert_eq!(log[1].after.as_deref(), Some("0.40"));
+    }
+
+    #[test]
+    fn discrete_actions_are_never_coalesced() {
+        let mut journal = ChangeJournal::new(100);
+        journal.record(entry(1000, "SetMute", 0, "false", "true"));
+        journal.record(entry(1001, "SetMute", 0, "true", "false"));
+        assert_eq!(journal.len(), 2);
+    }
+
+    #[test]
+    fn ring_evicts_oldest_entries() {
+        let mut journal = ChangeJournal::new(3);
+        for i in 0..5 {
+            journal.record(entry(i * 10, "SetMute", i as usize, "false", "true"));
+        }
+
+        let log = journal.recent(10);
+        assert_eq!(log.len(), 3);
+        // Les entrées 0 et 1 ont été évincées
+        assert_eq!(log[0].channel, Some(ChannelId(2)));
+        assert_eq!(log[2].channel, Some(ChannelId(4)));
+    }
+
+    #[test]
+    fn recent_returns_last_entries_in_order() {
+        let mut journal = ChangeJournal::new(10);
+        for i in 0..5 {
+            journal.record(entry(i * 10, "SetSolo", i as usize, "false", "true"));
+        }
+        let log = journal.recent(2);
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].channel, Some(ChannelId(3)));
+        assert_eq!(log[1].channel, Some(ChannelId(4)));
+    }
+
+    #[test]
+    fn clear_empties_journal() {
+        let mut journal = ChangeJournal::new(10);
+        journal.record(entry(0, "SetMute", 0, "false", "true"));
+        journal.clear();
+        assert!(journal.is_empty());
+    }
+
+    #[test]
+    fn log_file_receives_sealed_entries_in_order() {
+        let dir = std::env::temp_dir().join(format!("troubadour-journal-{}", std::process::id()));
+        let path = dir.join("session.log");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        {
+            let mut journal = ChangeJournal::new(10).with_log_file(&path);
+            journal.record(entry(1000, "SetVolume", 0, "1.00", "0.90"));
+            journal.record(entry(1500, "SetVolume", 0, "0.90", "0.60"));
+            journal.record(entry(1600, "SetMute", 1, "false", "true"));
+
+            // Le volume est encore dans sa fenêtre → rien d'écrit
+            // (le mute attend derrière pour respecter l'ordre).
+            let content = std::fs::read_to_string(&path).unwrap_or_default();
+            assert!(content.is_empty());
+
+            journal.record(entry(2500, "SetSolo", 1, "false", "true"));
+            let content = std::fs::read_to_string(&path).unwrap();
+            let lines: Vec<_> = content.lines().collect();
+            assert_eq!(lines.len(), 3);
+            assert_eq!(lines[0], "1000 SetVolume ch=0 1.00 -> 0.60");
+            assert_eq!(lines[1], "1600 SetMute ch=1 false -> true");
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
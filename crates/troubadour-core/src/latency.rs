@@ -0,0 +1,335 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::resampler::{CallbackResampler, MAX_RATIO_CORRECTION};
+
+/// Poids d'une nouvelle mesure dans la moyenne glissante de la réserve.
+/// 0.02 ≈ une constante de temps de 50 callbacks (~0.3 s à 256 frames) :
+/// assez lent pour ignorer la gigue d'un callback, assez rapide pour
+/// suivre une dérive d'horloge.
+const SMOOTHING: f32 = 0.02;
+
+/// Gain proportionnel du régulateur : correction de ratio par bloc
+/// d'écart à la cible (1 bloc → 1000 ppm).
+const KP: f64 = 1e-3;
+
+/// Gain intégral, par bloc d'écart et par callback. Bien plus lent que
+/// le proportionnel (~100 s à 256 frames) : les blocs d'entrée arrivent
+/// tantôt juste avant, tantôt juste après la lecture, et la réserve
+/// mesurée fait une dent de scie d'un bloc que l'intégrale doit moyenner
+/// pour retrouver l'écart des horloges.
+const KI: f64 = 5e-8;
+
+/// Compteurs du régulateur, écrits par le callback de sortie et lus
+/// par le thread de contrôle (atomiques : aucun lock côté audio).
+#[derive(Debug, Default)]
+pub struct LatencyCounters {
+    depth_frames: AtomicU32,
+    target_frames: AtomicU32,
+    skipped: AtomicU64,
+    inserted: AtomicU64,
+    /// `f32` en bits (pas d'`AtomicF32`)
+    drift_ppm: AtomicU32,
+    correction_ppm: AtomicU32,
+}
+
+impl LatencyCounters {
+    /// Réserve moyenne devant la sortie, en frames.
+    pub fn depth_frames(&self) -> u32 {
+        self.depth_frames.load(Ordering::Relaxed)
+    }
+
+    /// Réserve visée, en frames (0 tant que la sortie n'a pas démarré).
+    pub fn target_frames(&self) -> u32 {
+        self.target_frames.load(Ordering::Relaxed)
+    }
+
+    /// Frames jetées depuis la création du moteur.
+    pub fn frames_skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    /// Frames répétées depuis la création du moteur.
+    pub fn frames_inserted(&self) -> u64 {
+        self.inserted.load(Ordering::Relaxed)
+    }
+
+    /// Écart estimé entre l'horloge de l'entrée et celle de la sortie,
+    /// en ppm (positif : l'entrée va plus vite).
+    pub fn drift_ppm(&self) -> f32 {
+        f32::from_bits(self.drift_ppm.load(Ordering::Relaxed))
+    }
+
+    /// Correction appliquée au ratio du pont, en ppm.
+    pub fn correction_ppm(&self) -> f32 {
+        f32::from_bits(self.correction_ppm.load(Ordering::Relaxed))
+    }
+}
+
+/// Régulateur de latence de sortie.
+///
+/// # Le problème
+/// Les devices d'entrée et de sortie ont chacun leur horloge. Même
+/// réglées sur 48 kHz, elles diffèrent de quelques centaines de ppm :
+/// 200 ppm = 9.6 frames par seconde, soit ~0.6 s de latence en plus
+/// (ou une coupure) au bout d'une heure. Jusqu'ici, seule la borne du
+/// pont (`MAX_READY_CHUNKS`) limitait la dérive, en jetant d'un coup
+/// des centaines de frames : une latence élevée et un clic.
+///
+/// # La solution
+/// Avant chaque callback de sortie, on mesure la réserve du pont (ce
+/// qui restera après ce callback) et on la lisse. Un régulateur PI
+/// transforme l'écart à la cible en correction du ratio du pont
+/// (`CallbackResampler::set_ratio_correction`) : le terme intégral
+/// converge vers l'écart entre les deux horloges (`drift_ppm`), et la
+/// réserve reste sur sa cible sans jamais jeter de frame.
+///
+/// En secours, si la moyenne s'écarte de plus d'un bloc de la cible
+/// (saut de l'horloge, écart au-delà de `MAX_RATIO_CORRECTION`), on
+/// jette UNE frame ou on en répète une, comme avant le régulateur.
+///
+/// Au démarrage (et après un underrun), on sort du silence tant que la
+/// réserve n'a pas atteint la cible : la marge est reconstituée d'un
+/// coup plutôt qu'à coups d'underruns.
+pub struct LatencyGovernor {
+    target_blocks: u32,
+    average: f32,
+    primed: bool,
+    /// Terme intégral : estimation de l'écart d'horloge (ratio relatif).
+    /// Gardé après un underrun : les horloges n'ont pas changé.
+    integral: f64,
+    counters: Arc<LatencyCounters>,
+}
+
+impl LatencyGovernor {
+    pub fn new(target_blocks: u32, counters: Arc<LatencyCounters>) -> Self {
+        Self {
+            target_blocks: target_blocks.max(1),
+            average: 0.0,
+            primed: false,
+            integral: 0.0,
+            counters,
+        }
+    }
+
+    /// À appeler avant `bridge.fill` pour un bloc de `block_frames`.
+    ///
+    /// Retourne `false` tant que la réserve se constitue : le callback
+    /// doit alors sortir du silence sans puiser dans le pont.
+    pub fn before_fill(&mut self, bridge: &mut CallbackResampler, block_frames: usize) -> bool {
+        // Une cible au-delà de ce que le pont garde ne serait jamais
+        // atteinte (on garde un bloc de marge sous sa borne)
+        let ceiling = bridge.capacity_frames().saturating_sub(2 * block_frames);
+        let target = (self.target_blocks as usize * block_frames).min(ceiling);
+        self.counters
+            .target_frames
+            .store(target as u32, Ordering::Relaxed);
+
+        // Réserve = ce qui reste une fois ce bloc servi. Un bloc produit
+        // en retard la fait baisser d'un bloc : c'est la marge qui évite
+        // l'underrun.
+        let available = bridge.available_frames();
+        let depth = available.saturating_sub(block_frames);
+        if !self.primed {
+            self.counters
+                .depth_frames
+                .store(depth as u32, Ordering::Relaxed);
+            if available < target + block_frames {
+                return false;
+            }
+            self.primed = true;
+            self.average = depth as f32;
+        }
+
+        self.average += SMOOTHING * (depth as f32 - self.average);
+        let error = f64::from(self.average - target as f32) / block_frames.max(1) as f64;
+        self.integral =
+            (self.integral + KI * error).clamp(-MAX_RATIO_CORRECTION, MAX_RATIO_CORRECTION);
+        bridge.set_ratio_correction(KP * error + self.integral);
+        let ppm = |ratio: f64| ((ratio * 1e6) as f32).to_bits();
+        self.counters
+            .drift_ppm
+            .store(ppm(self.integral), Ordering::Relaxed);
+        self.counters
+            .correction_ppm
+            .store(ppm(bridge.ratio_correction()), Ordering::Relaxed);
+
+        // Secours. Une frame change la réserve d'une frame : la moyenne
+        // aussi, sinon elle continuerait de corriger pendant qu'elle rattrape.
+        let tolerance = block_frames as f32;
+        if self.average > target as f32 + tolerance {
+            if bridge.skip_frames(1) == 1 {
+                self.average -= 1.0;
+                self.counters.skipped.fetch_add(1, Ordering::Relaxed);
+            }
+        } else if self.average < target as f32 - tolerance && bridge.repeat_frame() {
+            self.average += 1.0;
+            self.counters.inserted.fetch_add(1, Ordering::Relaxed);
+        }
+        self.counters
+            .depth_frames
+            .store(self.average.round() as u32, Ordering::Relaxed);
+        true
+    }
+
+    /// Le pont n'a pas pu remplir le bloc : on reconstitue la réserve.
+    pub fn underrun(&mut self) {
+        self.primed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: f64 = 48_000.0;
+    const BLOCK: usize = 256;
+    const TARGET_BLOCKS: u32 = 2;
+
+    struct SkewRun {
+        counters: Arc<LatencyCounters>,
+        min_depth: u32,
+        max_depth: u32,
+        underruns_after_priming: u32,
+    }
+
+    /// Simule `seconds` de production à `ppm` d'écart d'horloge avec une
+    /// gigue déterministe (< 30 % d'une période) sur chaque bloc produit.
+    /// Les deux callbacks sont joués dans l'ordre de leurs instants.
+    fn run_with_skew(ppm: f64, seconds: f64) -> SkewRun {
+        run_with_skew_from(RATE as u32, ppm, seconds)
+    }
+
+    /// `run_with_skew` avec une entrée à `input_rate` (convertie vers 48 kHz).
+    fn run_with_skew_from(input_rate: u32, ppm: f64, seconds: f64) -> SkewRun {
+        let counters = Arc::new(LatencyCounters::default());
+        let mut governor = LatencyGovernor::new(TARGET_BLOCKS, counters.clone());
+        let mut bridge = CallbackResampler::new(input_rate, RATE as u32, 2, BLOCK).unwrap();
+
+        let consumer_period = BLOCK as f64 / RATE;
+        let producer_period = BLOCK as f64 / f64::from(input_rate) / (1.0 + ppm * 1e-6);
+        let block = vec![0.1_f32; BLOCK * 2];
+        let mut out = vec![0.0_f32; BLOCK * 2];
+
+        let mut rng: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut jitter = || {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            (rng % 1000) as f64 / 1000.0 * 0.3
+        };
+
+        let mut run = SkewRun {
+            counters: counters.clone(),
+            min_depth: u32::MAX,
+            max_depth: 0,
+            underruns_after_priming: 0,
+        };
+        let (mut produced, mut consumed) = (0_u64, 0_u64);
+        let mut next_push = jitter() * producer_period;
+        let mut primed_once = false;
+        while (consumed as f64) * consumer_period < seconds {
+            let next_pull = consumed as f64 * consumer_period;
+            if next_push <= next_pull {
+                bridge.push(&block).unwrap();
+                produced += 1;
+                next_push = (produced as f64 + jitter()) * producer_period;
+                continue;
+            }
+            consumed += 1;
+            if !governor.before_fill(&mut bridge, BLOCK) {
+                continue;
+            }
+            if bridge.fill(&mut out) < out.len() {
+                governor.underrun();
+                if primed_once {
+                    run.underruns_after_priming += 1;
+                }
+                continue;
+            }
+            // Laisser la moyenne s'établir avant de mesurer
+            if consumed as f64 * consumer_period > 2.0 {
+                primed_once = true;
+                let depth = counters.depth_frames();
+                run.min_depth = run.min_depth.min(depth);
+                run.max_depth = run.max_depth.max(depth);
+            }
+        }
+        run
+    }
+
+    fn assert_depth_held(run: &SkewRun) {
+        let target = run.counters.target_frames();
+        assert_eq!(target, TARGET_BLOCKS * BLOCK as u32);
+        assert_eq!(run.underruns_after_priming, 0);
+        assert!(
+            run.min_depth + BLOCK as u32 >= target && run.max_depth <= target + BLOCK as u32,
+            "depth {}..={} outside {target} ± {BLOCK}",
+            run.min_depth,
+            run.max_depth
+        );
+    }
+
+    /// Le régulateur absorbe l'écart sans jeter ni répéter de frame, et
+    /// son terme intégral retrouve l'écart des horloges.
+    fn assert_drift_absorbed(run: &SkewRun, ppm: f32) {
+        assert_depth_held(run);
+        assert_eq!(run.counters.frames_skipped(), 0);
+        assert_eq!(run.counters.frames_inserted(), 0);
+        let drift = run.counters.drift_ppm();
+        assert!((drift - ppm).abs() < ppm.abs() * 0.15, "drift {drift} ppm");
+    }
+
+    #[test]
+    fn fast_input_clock_is_absorbed_by_the_ratio_correction() {
+        assert_drift_absorbed(&run_with_skew(200.0, 600.0), 200.0);
+    }
+
+    #[test]
+    fn slow_input_clock_is_absorbed_by_the_ratio_correction() {
+        assert_drift_absorbed(&run_with_skew(-200.0, 600.0), -200.0);
+    }
+
+    #[test]
+    fn an_hour_of_clock_offset_keeps_the_reserve_bounded() {
+        // 100 ppm pendant une heure : 17 280 frames d'écart cumulé, que
+        // la réserve ne doit jamais accumuler
+        let run = run_with_skew(100.0, 3600.0);
+        assert_drift_absorbed(&run, 100.0);
+        assert!(run.max_depth - run.min_depth < BLOCK as u32);
+    }
+
+    #[test]
+    fn resampled_input_drift_is_absorbed_too() {
+        // 44.1 kHz → 48 kHz : le pont livre des chunks de rubato, pas des
+        // blocs d'entrée, mais la réserve reste sur sa cible
+        let run = run_with_skew_from(44_100, 100.0, 120.0);
+        let target = run.counters.target_frames();
+        assert_eq!(run.underruns_after_priming, 0);
+        assert_eq!(run.counters.frames_skipped(), 0);
+        assert!(
+            run.min_depth + 2 * BLOCK as u32 >= target
+                && run.max_depth <= target + 2 * BLOCK as u32,
+            "depth {}..={} around {target}",
+            run.min_depth,
+            run.max_depth
+        );
+    }
+
+    #[test]
+    fn target_is_capped_by_bridge_capacity() {
+        let counters = Arc::new(LatencyCounters::default());
+        let mut governor = LatencyGovernor::new(50, counters.clone());
+        let mut bridge = CallbackResampler::new(48_000, 48_000, 2, BLOCK).unwrap();
+        bridge
+            .push(&vec![0.0; bridge.capacity_frames() * 2])
+            .unwrap();
+
+        // Sans plafond, la réserve n'atteindrait jamais 50 blocs : silence éternel
+        assert!(governor.before_fill(&mut bridge, BLOCK));
+        assert_eq!(
+            counters.target_frames() as usize,
+            bridge.capacity_frames() - 2 * BLOCK
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Historique des niveaux d'un canal, pour les graphes défilants de l'UI.
+//!
+//! L'UI ne reçoit les `LevelUpdate` que quand sa fenêtre est ouverte :
+//! le moteur garde donc lui-même, pour les canaux demandés, une case de
+//! crête et de RMS par tranche de `resolution_ms`. Les cases vivent dans
+//! un anneau de taille fixe, alloué une fois : la mémoire ne dépend que
+//! de la config (voir `LevelHistoryConfig`), pas de la durée de session.
+//!
+//! Le thread de contrôle alimente l'historique à chaque tick depuis
+//! `SharedLevels` : RMS du dernier bloc, crête la plus haute depuis le
+//! tick précédent. Une tranche sans mesure (moteur arrêté, boucle en
+//! veille plus longue qu'une case) compte comme du silence.
+
+use std::collections::VecDeque;
+
+use troubadour_shared::config::LevelHistoryConfig;
+use troubadour_shared::mixer::LevelPoint;
+
+/// Case en cours de remplissage.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    start_ms: u64,
+    peak: f32,
+    sum_squares: f32,
+    count: u32,
+}
+
+impl Bucket {
+    fn new(start_ms: u64) -> Self {
+        Self {
+            start_ms,
+            peak: 0.0,
+            sum_squares: 0.0,
+            count: 0,
+        }
+    }
+
+    fn add(&mut self, rms: f32, peak: f32) {
+        self.peak = self.peak.max(peak);
+        self.sum_squares += rms * rms;
+        self.count += 1;
+    }
+
+    /// `[peak, rms]`
+    fn close(&self) -> [f32; 2] {
+        let rms = if self.count == 0 {
+            0.0
+        } else {
+            (self.sum_squares / self.count as f32).sqrt()
+        };
+        [self.peak, rms]
+    }
+}
+
+/// Anneau de cases de niveaux d'un canal.
+#[derive(Debug, Clone)]
+pub struct LevelHistory {
+    resolution_ms: u64,
+    capacity: usize,
+    /// `[peak, rms]` des cases terminées, la plus ancienne en tête
+    closed: VecDeque<[f32; 2]>,
+    current: Option<Bucket>,
+}
+
+impl LevelHistory {
+    pub fn new(config: &LevelHistoryConfig) -> Self {
+        let resolution_ms = config.resolution_ms.clamp(10, 1000);
+        let window_ms = u64::from(config.window_seconds.clamp(1, 600)) * 1000;
+        let capacity = (window_ms / resolution_ms) as usize;
+        Self {
+            resolution_ms,
+            capacity,
+            closed: VecDeque::with_capacity(capacity),
+            current: None,
+        }
+    }
+
+    /// Cases gardées au plus (case en cours comprise).
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Ajoute une mesure prise à `now_ms`.
+    pub fn record(&mut self, now_ms: u64, rms: f32, peak: f32) {
+        let start_ms = now_ms - now_ms % self.resolution_ms;
+        let (rms, peak) = (finite_or_zero(rms), finite_or_zero(peak));
+        match &mut self.current {
+            // Une horloge qui recule reste dans la case en cours
+            Some(bucket) if start_ms <= bucket.start_ms => bucket.add(rms, peak),
+            Some(bucket) => {
+                let done = *bucket;
+                self.push(done.close());
+                let gaps = (start_ms - done.start_ms) / self.resolution_ms - 1;
+                for _ in 0..gaps.min(self.capacity as u64) {
+                    self.push([0.0, 0.0]);
+                }
+                self.start(start_ms, rms, peak);
+            }
+            None => self.start(start_ms, rms, peak),
+        }
+    }
+
+    /// Les cases des `seconds` dernières secondes, de la plus ancienne à
+    /// la plus récente (la case en cours, incomplète, en dernier).
+    pub fn points(&self, seconds: u32) -> Vec<LevelPoint> {
+        let Some(current) = self.current else {
+            return Vec::new();
+        };
+        let wanted = (u64::from(seconds) * 1000 / self.resolution_ms).max(1) as usize;
+        let closed = wanted.saturating_sub(1).min(self.closed.len());
+        let first_ms = current.start_ms - closed as u64 * self.resolution_ms;
+        self.closed
+            .iter()
+            .skip(self.closed.len() - closed)
+            .chain(std::iter::once(&current.close()))
+            .enumerate()
+            .map(|(i, &[peak, rms])| LevelPoint {
+                timestamp_ms: first_ms + i as u64 * self.resolution_ms,
+                peak,
+                rms,
+            })
+            .collect()
+    }
+
+    fn start(&mut self, start_ms: u64, rms: f32, peak: f32) {
+        let mut bucket = Bucket::new(start_ms);
+        bucket.add(rms, peak);
+        self.current = Some(bucket);
+    }
+
+    /// La case en cours compte dans la capacité : l'anneau en garde une
+    /// de moins.
+    fn push(&mut self, point: [f32; 2]) {
+        if self.closed.len() + 1 >= self.capacity {
+            self.closed.pop_front();
+        }
+        if self.capacity > 1 {
+            self.closed.push_back(point);
+        }
+    }
+}
+
+fn finite_or_zero(level: f32) -> f32 {
+    if level.is_finite() { level.abs() } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(resolution_ms: u64, window_seconds: u32) -> LevelHistory {
+        LevelHistory::new(&LevelHistoryConfig {
+            resolution_ms,
+            window_seconds,
+        })
+    }
+
+    #[test]
+    fn buckets_keep_the_peak_and_the_quadratic_mean() {
+        let mut history = history(100, 60);
+        assert_eq!(history.capacity(), 600);
+        assert!(history.points(60).is_empty());
+
+        // Case 1000–1100 : crête max, RMS quadratique des trois mesures
+        history.record(1_000, 0.3, 0.5);
+        history.record(1_050, 0.4, 0.9);
+        history.record(1_099, 0.4, 0.2);
+        history.record(1_100, 0.1, 0.1);
+
+        let points = history.points(60);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp_ms, 1_000);
+        assert_eq!(points[0].peak, 0.9);
+        let expected = ((0.09 + 0.16 + 0.16) / 3.0f32).sqrt();
+        assert!((points[0].rms - expected).abs() < 1e-6);
+        assert_eq!(
+            points[1],
+            LevelPoint {
+                timestamp_ms: 1_100,
+                peak: 0.1,
+                rms: 0.1
+            }
+        );
+
+        // Trou de 300 ms : deux cases de silence
+        history.record(1_400, 0.2, 0.2);
+        let points = history.points(60);
+        let times: Vec<u64> = points.iter().map(|p| p.timestamp_ms).collect();
+        assert_eq!(times, [1_000, 1_100, 1_200, 1_300, 1_400]);
+        assert_eq!(points[2].peak, 0.0);
+        // Une demande plus courte : les dernières cases seulement
+        assert_eq!(history.points(0).len(), 1);
+    }
+
+    #[test]
+    fn the_ring_evicts_the_oldest_buckets() {
+        // 1 s à 100 ms : 10 cases
+        let mut history = history(100, 1);
+        for i in 0..25u64 {
+            history.record(i * 100, i as f32 / 100.0, i as f32 / 100.0);
+        }
+        let points = history.points(60);
+        assert_eq!(points.len(), 10);
+        assert_eq!(points[0].timestamp_ms, 1_500);
+        assert_eq!(points[9].timestamp_ms, 2_400);
+        assert!((points[0].peak - 0.15).abs() < 1e-6);
+        assert!(history.closed.capacity() <= 16, "no growth past the window");
+
+        // Un long silence ne pousse pas plus que la capacité
+        history.record(1_000_000, 0.5, 0.5);
+        let points = history.points(60);
+        assert_eq!(points.len(), 10);
+        assert!(points[..9].iter().all(|p| p.peak == 0.0));
+        assert_eq!(points[9].timestamp_ms, 1_000_000);
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use troubadour_shared::audio::ChannelId;
+
+/// Derniers niveaux mesurés par le callback audio, lisibles par le
+/// thread de contrôle (les scripts les lisent à chaque tick).
+///
+/// Les `LevelUpdate` partent du callback directement vers l'UI : le
+/// moteur ne les voit pas passer. Le callback écrit donc aussi ici, une
+/// case atomique par canal (ids inférieurs à la capacité), sans lock ni
+/// allocation. RMS et peak sont écrits séparément : une lecture peut
+/// mélanger deux blocs voisins, sans conséquence à l'échelle d'un tick.
+pub struct SharedLevels {
+    /// RMS et peak linéaires (bits d'un `f32`), NaN = jamais mesuré
+    levels: Vec<[AtomicU32; 2]>,
+    /// Crête max depuis le dernier `take_peak` (bits d'un `f32` positif :
+    /// `fetch_max` suffit), pour l'historique des niveaux
+    peaks: Vec<AtomicU32>,
+}
+
+impl SharedLevels {
+    pub fn new(max_channels: usize) -> Self {
+        Self {
+            levels: (0..max_channels)
+                .map(|_| {
+                    [
+                        AtomicU32::new(f32::NAN.to_bits()),
+                        AtomicU32::new(f32::NAN.to_bits()),
+                    ]
+                })
+                .collect(),
+            peaks: (0..max_channels).map(|_| AtomicU32::new(0)).collect(),
+        }
+    }
+
+    pub fn store(&self, channel: ChannelId, rms: f32, peak: f32) {
+        if let Some([r, p]) = self.levels.get(channel.0) {
+            r.store(rms.to_bits(), Ordering::Relaxed);
+            p.store(peak.to_bits(), Ordering::Relaxed);
+        }
+        if let Some(max) = self.peaks.get(channel.0) {
+            let peak = if peak.is_finite() { peak.abs() } else { 0.0 };
+            max.fetch_max(peak.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// `(rms, peak)` du dernier bloc de `channel` (`None` : jamais
+    /// mesuré, par exemple un canal que le pipeline ne traite pas).
+    pub fn load(&self, channel: ChannelId) -> Option<(f32, f32)> {
+        let [r, p] = self.levels.get(channel.0)?;
+        let rms = f32::from_bits(r.load(Ordering::Relaxed));
+        let peak = f32::from_bits(p.load(Ordering::Relaxed));
+        (!rms.is_nan()).then_some((rms, peak))
+    }
+
+    /// Crête la plus haute de `channel` depuis l'appel précédent (remise
+    /// à zéro).
+    pub fn take_peak(&self, channel: ChannelId) -> f32 {
+        self.peaks
+            .get(channel.0)
+            .map_or(0.0, |max| f32::from_bits(max.swap(0, Ordering::Relaxed)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unmeasured_channels_have_no_level() {
+        let levels = SharedLevels::new(4);
+        assert_eq!(levels.load(ChannelId(1)), None);
+
+        levels.store(ChannelId(1), 0.25, 0.5);
+        assert_eq!(levels.load(ChannelId(1)), Some((0.25, 0.5)));
+        levels.store(ChannelId(1), 0.1, 0.2);
+        assert_eq!(levels.take_peak(ChannelId(1)), 0.5);
+        assert_eq!(levels.take_peak(ChannelId(1)), 0.0);
+        // Hors capacité : ignoré
+        levels.store(ChannelId(9), 0.25, 0.5);
+        assert_eq!(levels.load(ChannelId(9)), None);
+    }
+}
@@ -1,5 +1,43 @@
+#[cfg(test)]
+mod alloc_count;
+pub mod auto_level;
+pub mod channel_map;
+pub mod command_queue;
+pub mod compat;
 pub mod device;
+pub mod diagnostics;
 pub mod dsp;
 pub mod engine;
+pub mod fallback;
+pub mod fault;
+pub mod handover;
+pub mod idle;
+pub mod import;
+pub mod journal;
+pub mod latency;
+pub mod level_history;
+pub mod levels;
+pub mod meter_gate;
 pub mod mixer;
+pub mod network;
+pub mod presence;
+pub mod preview;
+pub mod recording;
+pub mod recovery;
+pub mod render;
+pub mod replay;
 pub mod resampler;
+pub mod route_meter;
+pub mod rt_log;
+pub mod scheduler;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod session;
+pub mod setup;
+pub mod snapshot;
+pub mod streams;
+pub mod tap;
+pub mod taper;
+pub mod template;
+pub mod watchdog;
+pub mod wav;
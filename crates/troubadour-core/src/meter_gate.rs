@@ -0,0 +1,411 @@
+//! Meters à la demande de l'UI.
+//!
+//! Fenêtre réduite, panneau replié : l'UI ne lit plus les `LevelUpdate`,
+//! mais le callback continuait de les construire et de les envoyer.
+//! L'UI déclare donc ce qu'elle affiche (`MeterSubscription`) :
+//!
+//! - abonnement actif : seuls les canaux listés sont mesurés pour l'UI
+//!   (liste vide : aucun) ; sans abonnement, tout est mesuré ;
+//! - pause (fenêtre réduite) : plus aucun envoi, mais la crête de
+//!   chaque canal abonné reste tenue (`MeterGate`) et part avec le
+//!   premier niveau après la reprise : un clip pendant la pause se voit.
+//!
+//! Seuls les niveaux destinés à l'UI sont concernés : les niveaux
+//! partagés (`SharedLevels`), l'historique et la session continuent.
+//!
+//! `MeterSender` les envoie sans allouer une fois chaud : les
+//! `LevelUpdate` et trames binaires lus par l'UI lui reviennent par
+//! les files de recyclage d'`EngineChannels`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crossbeam_channel::{Receiver, Sender};
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::messages::Event;
+use troubadour_shared::meter_frame::MeterFrameEncoder;
+use troubadour_shared::mixer::{ChannelLevel, Loudness, MeterScale, PREVIEW_CHANNEL};
+
+/// Ce que l'UI affiche, écrit par le moteur et lu par le callback sans
+/// lock : une case par canal (ids inférieurs à la capacité), comme
+/// `SharedLevels`.
+pub struct MeterSubscription {
+    /// Un abonnement est déclaré : les autres canaux ne sont pas mesurés
+    filtered: AtomicBool,
+    targets: Vec<AtomicBool>,
+    /// L'écoute d'un device (`PREVIEW_CHANNEL`) est abonnée : son id
+    /// n'a pas de case
+    preview: AtomicBool,
+    paused: AtomicBool,
+    /// Niveaux envoyés à l'UI depuis la création
+    emitted: AtomicU64,
+}
+
+impl MeterSubscription {
+    pub fn new(max_channels: usize) -> Self {
+        Self {
+            filtered: AtomicBool::new(false),
+            targets: (0..max_channels).map(|_| AtomicBool::new(false)).collect(),
+            preview: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+            emitted: AtomicU64::new(0),
+        }
+    }
+
+    /// Remplace l'abonnement. `active: false` revient à tout mesurer.
+    pub fn subscribe(&self, targets: &[ChannelId], active: bool) {
+        for (id, slot) in self.targets.iter().enumerate() {
+            slot.store(
+                active && targets.contains(&ChannelId(id)),
+                Ordering::Relaxed,
+            );
+        }
+        self.preview.store(
+            active && targets.contains(&PREVIEW_CHANNEL),
+            Ordering::Relaxed,
+        );
+        self.filtered.store(active, Ordering::Relaxed);
+    }
+
+    /// L'UI veut les niveaux de `channel`.
+    pub fn wants(&self, channel: ChannelId) -> bool {
+        if channel == PREVIEW_CHANNEL {
+            return !self.filtered.load(Ordering::Relaxed) || self.preview.load(Ordering::Relaxed);
+        }
+        !self.filtered.load(Ordering::Relaxed)
+            || self
+                .targets
+                .get(channel.0)
+                .is_some_and(|slot| slot.load(Ordering::Relaxed))
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Nombre de canaux abonnés (`None` : pas d'abonnement, tout est mesuré).
+    pub fn size(&self) -> Option<usize> {
+        self.filtered.load(Ordering::Relaxed).then(|| {
+            self.targets
+                .iter()
+                .chain([&self.preview])
+                .filter(|slot| slot.load(Ordering::Relaxed))
+                .count()
+        })
+    }
+
+    /// Compte un envoi de `count` niveaux.
+    pub fn record_emission(&self, count: usize) {
+        self.emitted.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn emitted(&self) -> u64 {
+        self.emitted.load(Ordering::Relaxed)
+    }
+}
+
+/// Côté callback : construit les niveaux des canaux abonnés et tient
+/// les crêtes pendant la pause.
+pub struct MeterGate {
+    subscription: Arc<MeterSubscription>,
+    /// Crête la plus haute de chaque canal depuis le début de la pause
+    /// (alloué d'avance : le callback mesure au plus deux canaux)
+    held: Vec<(ChannelId, f32)>,
+    /// Niveaux construits (les tests vérifient ce qui n'est pas mesuré)
+    #[cfg(test)]
+    computed: u64,
+}
+
+impl MeterGate {
+    pub fn new(subscription: Arc<MeterSubscription>) -> Self {
+        Self {
+            subscription,
+            held: Vec::with_capacity(4),
+            #[cfg(test)]
+            computed: 0,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn computed(&self) -> u64 {
+        self.computed
+    }
+
+    pub fn subscription(&self) -> &MeterSubscription {
+        &self.subscription
+    }
+
+    /// Niveau de `channel` pour l'UI : `None` hors abonnement (rien
+    /// n'est calculé) ou en pause (la crête est tenue). À la reprise,
+    /// la crête tenue remplace celle du bloc si elle est plus haute.
+    pub fn level(
+        &mut self,
+        scale: &MeterScale,
+        channel: ChannelId,
+        rms: f32,
+        peak: f32,
+        loudness: Option<Loudness>,
+        true_peak: Option<f32>,
+    ) -> Option<ChannelLevel> {
+        if !self.subscription.wants(channel) {
+            return None;
+        }
+        if self.subscription.paused() {
+            match self.held.iter_mut().find(|(id, _)| *id == channel) {
+                Some((_, held)) => *held = held.max(peak),
+                None => self.held.push((channel, peak)),
+            }
+            return None;
+        }
+        let peak = match self.held.iter().position(|(id, _)| *id == channel) {
+            Some(index) => self.held.swap_remove(index).1.max(peak),
+            None => peak,
+        };
+        #[cfg(test)]
+        {
+            self.computed += 1;
+        }
+        Some(ChannelLevel {
+            channel,
+            rms,
+            peak,
+            rms_position: scale.position(rms),
+            peak_position: scale.position(peak),
+            loudness,
+            true_peak,
+            // Crête vraie ≥ crête des samples : elle clippe plus tôt
+            clipped: scale.clips(true_peak.unwrap_or(peak)),
+        })
+    }
+}
+
+/// Côté callback : envoie à l'UI les niveaux d'un bloc, en
+/// `LevelUpdate` ou en trame binaire selon le transport choisi.
+pub struct MeterSender {
+    binary: Arc<AtomicBool>,
+    subscription: Arc<MeterSubscription>,
+    event_tx: Sender<Event>,
+    encoder: MeterFrameEncoder,
+    /// `LevelUpdate` et trames rendus par l'UI, réécrits avant d'en
+    /// allouer d'autres
+    level_pool: Receiver<Vec<ChannelLevel>>,
+    frame_pool: Receiver<Vec<u8>>,
+    /// Niveaux du bloc à encoder (alloué d'avance : au plus deux)
+    scratch: Vec<ChannelLevel>,
+}
+
+impl MeterSender {
+    pub fn new(
+        binary: Arc<AtomicBool>,
+        subscription: Arc<MeterSubscription>,
+        event_tx: Sender<Event>,
+        encoder: MeterFrameEncoder,
+        level_pool: Receiver<Vec<ChannelLevel>>,
+        frame_pool: Receiver<Vec<u8>>,
+    ) -> Self {
+        Self {
+            binary,
+            subscription,
+            event_tx,
+            encoder,
+            level_pool,
+            frame_pool,
+            scratch: Vec::with_capacity(2),
+        }
+    }
+
+    /// Envoie les niveaux mesurés (rien si aucun : canaux hors
+    /// abonnement ou meters en pause).
+    pub fn send(&mut self, levels: impl IntoIterator<Item = ChannelLevel>) {
+        self.scratch.clear();
+        self.scratch.extend(levels);
+        if self.scratch.is_empty() {
+            return;
+        }
+        self.subscription.record_emission(self.scratch.len());
+        let event = if self.binary.load(Ordering::Relaxed) {
+            let mut frame = self.frame_pool.try_recv().unwrap_or_default();
+            self.encoder.encode_into(&self.scratch, &mut frame);
+            Event::MeterFrame(frame)
+        } else {
+            let mut update = self.level_pool.try_recv().unwrap_or_default();
+            update.clear();
+            update.extend_from_slice(&self.scratch);
+            Event::LevelUpdate(update)
+        };
+        let _ = self.event_tx.try_send(event);
+    }
+}
+
+/// Débit des niveaux envoyés, recalculé chaque seconde par le moteur.
+#[derive(Debug, Default)]
+pub struct EmissionRate {
+    last: Option<(u64, u64)>,
+    per_second: f32,
+}
+
+impl EmissionRate {
+    /// `emitted` : compteur de `MeterSubscription` lu à `now_ms`.
+    pub fn update(&mut self, now_ms: u64, emitted: u64) {
+        match self.last {
+            Some((since_ms, count)) if now_ms >= since_ms + 1000 => {
+                let seconds = (now_ms - since_ms) as f32 / 1000.0;
+                self.per_second = emitted.saturating_sub(count) as f32 / seconds;
+                self.last = Some((now_ms, emitted));
+            }
+            Some(_) => {}
+            None => self.last = Some((now_ms, emitted)),
+        }
+    }
+
+    /// Niveaux envoyés par seconde, sur la dernière seconde mesurée.
+    pub fn per_second(&self) -> f32 {
+        self.per_second
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc_count::count_allocations;
+    use troubadour_shared::meter_frame::MeterLayout;
+
+    #[test]
+    fn returned_levels_and_frames_are_sent_without_allocating() {
+        let subscription = Arc::new(MeterSubscription::new(8));
+        let binary = Arc::new(AtomicBool::new(false));
+        let (event_tx, event_rx) = crossbeam_channel::bounded(4);
+        let (level_tx, level_pool) = crossbeam_channel::bounded(4);
+        let (frame_tx, frame_pool) = crossbeam_channel::bounded(4);
+        let layout = MeterLayout::new(1, vec![ChannelId(0), ChannelId(3)]);
+        let mut sender = MeterSender::new(
+            binary.clone(),
+            subscription.clone(),
+            event_tx,
+            MeterFrameEncoder::new(layout),
+            level_pool,
+            frame_pool,
+        );
+        let mut gate = MeterGate::new(subscription.clone());
+        let scale = MeterScale::default();
+        let mut levels =
+            |peak| [0, 3].map(|id| gate.level(&scale, ChannelId(id), 0.1, peak, None, None));
+
+        // Rien de mesuré : rien d'envoyé
+        sender.send(None);
+        assert!(event_rx.is_empty());
+
+        // Premiers envois : alloués, puis rendus par l'UI
+        sender.send(levels(0.5).into_iter().flatten());
+        binary.store(true, Ordering::Relaxed);
+        sender.send(levels(0.5).into_iter().flatten());
+        for event in event_rx.try_iter() {
+            match event {
+                Event::LevelUpdate(update) => level_tx.send(update).unwrap(),
+                Event::MeterFrame(frame) => frame_tx.send(frame).unwrap(),
+                other => panic!("unexpected {other:?}"),
+            }
+        }
+
+        let measured = levels(0.25);
+        let allocations = count_allocations(|| {
+            sender.send(measured.into_iter().flatten());
+            binary.store(false, Ordering::Relaxed);
+            sender.send(measured.into_iter().flatten());
+        });
+        assert_eq!(allocations, 0);
+        let events: Vec<Event> = event_rx.try_iter().collect();
+        let [Event::MeterFrame(frame), Event::LevelUpdate(update)] = &events[..] else {
+            panic!("unexpected {events:?}");
+        };
+        // Crête du bus, dernier champ de la trame
+        assert_eq!(frame[28..], 0.25_f32.to_le_bytes());
+        assert_eq!(update.len(), 2);
+        assert_eq!(subscription.emitted(), 8);
+    }
+
+    #[test]
+    fn only_subscribed_channels_are_measured() {
+        let subscription = Arc::new(MeterSubscription::new(8));
+        let mut gate = MeterGate::new(subscription.clone());
+        let scale = MeterScale::default();
+        assert_eq!(subscription.size(), None);
+        assert!(
+            gate.level(&scale, ChannelId(5), 0.1, 0.2, None, None)
+                .is_some()
+        );
+
+        subscription.subscribe(&[ChannelId(0), ChannelId(42)], true);
+        assert_eq!(subscription.size(), Some(1));
+        assert!(
+            gate.level(&scale, ChannelId(5), 0.1, 0.2, None, None)
+                .is_none()
+        );
+        assert!(
+            gate.level(&scale, ChannelId(0), 0.1, 0.2, None, None)
+                .is_some()
+        );
+        assert_eq!(gate.computed(), 2);
+
+        // Liste vide : plus rien ; inactif : tout à nouveau
+        subscription.subscribe(&[], true);
+        assert!(
+            gate.level(&scale, ChannelId(0), 0.1, 0.2, None, None)
+                .is_none()
+        );
+        subscription.subscribe(&[], false);
+        assert!(
+            gate.level(&scale, ChannelId(5), 0.1, 0.2, None, None)
+                .is_some()
+        );
+
+        // L'écoute d'un device s'abonne comme un canal
+        assert!(subscription.wants(PREVIEW_CHANNEL));
+        subscription.subscribe(&[ChannelId(0)], true);
+        assert!(!subscription.wants(PREVIEW_CHANNEL));
+        subscription.subscribe(&[PREVIEW_CHANNEL], true);
+        assert!(subscription.wants(PREVIEW_CHANNEL));
+        assert_eq!(subscription.size(), Some(1));
+    }
+
+    #[test]
+    fn peaks_are_held_through_a_pause() {
+        let subscription = Arc::new(MeterSubscription::new(8));
+        let mut gate = MeterGate::new(subscription.clone());
+        let scale = MeterScale::default();
+        subscription.set_paused(true);
+        for peak in [0.2, 1.5, 0.3] {
+            assert!(
+                gate.level(&scale, ChannelId(0), 0.1, peak, None, None)
+                    .is_none()
+            );
+        }
+        assert_eq!(gate.computed(), 0);
+
+        subscription.set_paused(false);
+        let level = gate
+            .level(&scale, ChannelId(0), 0.1, 0.4, None, None)
+            .unwrap();
+        assert_eq!(level.peak, 1.5);
+        assert!(level.clipped);
+        // Tenue une seule fois
+        let level = gate
+            .level(&scale, ChannelId(0), 0.1, 0.4, None, None)
+            .unwrap();
+        assert_eq!(level.peak, 0.4);
+    }
+
+    #[test]
+    fn emission_rate_per_second() {
+        let mut rate = EmissionRate::default();
+        rate.update(0, 0);
+        rate.update(500, 40);
+        assert_eq!(rate.per_second(), 0.0);
+        rate.update(2000, 200);
+        assert_eq!(rate.per_second(), 100.0);
+    }
+}
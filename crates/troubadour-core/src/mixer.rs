@@ -1,7 +1,18 @@
-use std::collections::HashMap;
-
-use troubadour_shared::audio::ChannelId;
-use troubadour_shared::mixer::{ChannelConfig, ChannelKind, ChannelLevel, MixerConfig, Route};
+use std::collections::{HashMap, HashSet};
+
+use troubadour_shared::audio::{ChannelCount, ChannelId};
+use troubadour_shared::error::{MixerError, MixerResult};
+use troubadour_shared::graph::{GraphEdge, GraphNode, NodeKind, SignalGraph};
+use troubadour_shared::mixer::{
+    BusFollow, ChannelConfig, ChannelCountSummary, ChannelDependencies, ChannelKind, ChannelLevel,
+    ChannelLink, ChannelQuery, Crossfade, CrossfadeCurve, DEFAULT_MAX_CHANNELS, FaderTaper,
+    FollowOverride, MeterScale, MixerConfig, Route, SignalPresenceConfig,
+};
+
+use crate::channel_map::{OutputSlot, check_output_slots};
+use crate::dsp::delay::{MAX_DELAY_MS, MAX_OUTPUT_DELAY_MS};
+use crate::dsp::ramp::BusFade;
+use crate::taper::{FADER_MAX_DB, VolumeDecibels};
 
 /// État runtime d'un canal (données qui changent chaque frame audio).
 ///
@@ -53,6 +64,33 @@ pub struct Mixer {
     channels: HashMap<ChannelId, ChannelConfig>,
     states: HashMap<ChannelId, ChannelState>,
     routes: Vec<Route>,
+    /// Crossfaders A/B, triés par id
+    crossfades: Vec<Crossfade>,
+    /// Paires stéréo, triées par canal de gauche
+    links: Vec<ChannelLink>,
+    /// Bus qui suivent le volume d'un autre, triés par suiveur
+    follows: Vec<BusFollow>,
+    /// Bus de sortie en solo. Transitoire : absent de `MixerConfig`,
+    /// donc jamais sauvé dans un profil.
+    bus_solo: HashSet<ChannelId>,
+    /// Dernier fondu demandé par bus (transitoire, comme le solo de bus).
+    bus_fades: HashMap<ChannelId, BusFade>,
+    /// Bus dont la sonie (LUFS) est mesurée, avec leur génération de
+    /// remise à zéro (transitoire, comme le solo de bus).
+    loudness_meters: HashMap<ChannelId, u64>,
+    /// Entrées dont le retour porte la différence sec − traité (voir
+    /// `set_effects_diff_monitor` ; transitoire, comme le solo de bus)
+    effects_diff_monitor: HashSet<ChannelId>,
+    /// Bus de repli du passthrough d'urgence, quand il est actif
+    /// (transitoire, comme le solo de bus)
+    emergency_passthrough: Option<ChannelId>,
+    /// Numéro du prochain fondu
+    next_fade_id: u64,
+    /// Au-delà, `add_channel` refuse
+    max_channels: usize,
+    /// Échelle des meters : plancher des niveaux et positions envoyées
+    /// à l'UI (réglage de l'application, absent de `MixerConfig`)
+    meter_scale: MeterScale,
 }
 
 impl Mixer {
@@ -62,6 +100,17 @@ impl Mixer {
             channels: HashMap::new(),
             states: HashMap::new(),
             routes: Vec::new(),
+            crossfades: Vec::new(),
+            links: Vec::new(),
+            follows: Vec::new(),
+            bus_solo: HashSet::new(),
+            bus_fades: HashMap::new(),
+            loudness_meters: HashMap::new(),
+            effects_diff_monitor: HashSet::new(),
+            emergency_passthrough: None,
+            next_fade_id: 1,
+            max_channels: DEFAULT_MAX_CHANNELS,
+            meter_scale: MeterScale::default(),
         }
     }
 
@@ -74,27 +123,185 @@ impl Mixer {
     pub fn from_config(config: MixerConfig) -> Self {
         let mut mixer = Self::new();
 
+        // Chargement : un id répété dans le fichier remplace le précédent
         for channel in config.channels {
-            mixer.states.insert(channel.id, ChannelState::default());
-            mixer.channels.insert(channel.id, channel);
+            mixer.add_or_replace_channel(channel);
         }
 
         mixer.routes = config.routes;
+        // Un crossfader dont un côté a disparu du fichier est écarté
+        for crossfade in config.crossfades {
+            let _ = mixer.insert_crossfade(crossfade);
+        }
+        // Idem pour un lien incomplet ou qui réutilise un canal déjà lié
+        for link in config.links {
+            let _ = mixer.link_channels(link.left, link.right);
+        }
+        // Idem pour un suivi vers un bus disparu ou qui bouclerait
+        for follow in config.bus_follows {
+            let _ = mixer.follow_bus(follow);
+        }
         mixer
     }
 
+    /// Comme `from_config`, limité à `max_channels` canaux : les canaux
+    /// d'id les plus élevés sont écartés (avec leurs routes) et retournés.
+    pub fn from_config_limited(config: MixerConfig, max_channels: usize) -> (Self, Vec<ChannelId>) {
+        let mut mixer = Self::from_config(config);
+        mixer.max_channels = max_channels;
+        let mut ids: Vec<ChannelId> = mixer.channels.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        let skipped = ids.split_off(max_channels.min(ids.len()));
+        for &id in &skipped {
+            let _ = mixer.remove_channel(id);
+        }
+        (mixer, skipped)
+    }
+
+    pub fn max_channels(&self) -> usize {
+        self.max_channels
+    }
+
+    pub fn meter_scale(&self) -> MeterScale {
+        self.meter_scale
+    }
+
+    /// Change l'échelle des meters. Les niveaux déjà sous le nouveau
+    /// plancher tombent à zéro tout de suite.
+    pub fn set_meter_scale(&mut self, scale: MeterScale) {
+        self.meter_scale = scale;
+        let floor = scale.floor();
+        for state in self.states.values_mut() {
+            for level in [&mut state.rms, &mut state.peak, &mut state.peak_hold] {
+                if *level < floor {
+                    *level = 0.0;
+                }
+            }
+        }
+    }
+
+    /// Change la limite de canaux. Les canaux déjà présents au-delà
+    /// restent : seuls les ajouts suivants sont refusés.
+    pub fn set_max_channels(&mut self, max_channels: usize) {
+        self.max_channels = max_channels;
+    }
+
     /// Ajoute un canal au mixer.
-    pub fn add_channel(&mut self, config: ChannelConfig) {
+    ///
+    /// Refuse un id déjà pris : `HashMap::insert` écraserait l'ancien
+    /// canal (et ses réglages) sans rien dire. Refuse aussi un canal de
+    /// trop (`max_channels`).
+    pub fn add_channel(&mut self, config: ChannelConfig) -> MixerResult<()> {
+        if self.channels.contains_key(&config.id) {
+            return Err(MixerError::DuplicateId(config.id.0));
+        }
+        if self.channels.len() >= self.max_channels {
+            return Err(MixerError::TooManyChannels(self.max_channels));
+        }
+        self.add_or_replace_channel(config);
+        Ok(())
+    }
+
+    /// Ajoute un canal, ou remplace celui qui a le même id (chargement
+    /// d'une config ou d'un profil). Retourne le canal remplacé.
+    ///
+    /// Les routes du canal remplacé sont gardées : elles référencent
+    /// l'id, pas l'ancienne config. Un retard de sortie hors bornes (fichier
+    /// édité à la main) est ramené dans celles de `set_output_delay`.
+    pub fn add_or_replace_channel(&mut self, mut config: ChannelConfig) -> Option<ChannelConfig> {
+        config.output_delay_ms = clamp_output_delay(config.output_delay_ms);
         self.states.insert(config.id, ChannelState::default());
-        self.channels.insert(config.id, config);
+        self.channels.insert(config.id, config)
     }
 
-    /// Supprime un canal et toutes ses routes.
-    pub fn remove_channel(&mut self, id: ChannelId) {
-        self.channels.remove(&id);
+    /// Ce que la suppression du canal retirerait dans le mixer (voir
+    /// `remove_channel`) ; le moteur complète le reste (taps, réseau,
+    /// pipeline : voir `Engine::channel_dependencies`).
+    pub fn channel_dependencies(&self, id: ChannelId) -> MixerResult<ChannelDependencies> {
+        if !self.channels.contains_key(&id) {
+            return Err(MixerError::ChannelNotFound(id.0));
+        }
+        let mut monitored_by: Vec<ChannelId> = self
+            .channels
+            .values()
+            .filter(|ch| ch.monitor_bus == Some(id))
+            .map(|ch| ch.id)
+            .collect();
+        monitored_by.sort_by_key(|ch| ch.0);
+        Ok(ChannelDependencies {
+            channel: id,
+            routes: self
+                .routes
+                .iter()
+                .filter(|r| r.from == id || r.to == id)
+                .copied()
+                .collect(),
+            monitored_by,
+            crossfades: self
+                .crossfades
+                .iter()
+                .filter(|x| x.involves(id))
+                .cloned()
+                .collect(),
+            links: self
+                .links
+                .iter()
+                .filter(|l| l.involves(id))
+                .copied()
+                .collect(),
+            follows: self
+                .follows
+                .iter()
+                .filter(|f| f.follower == id || f.leader == id)
+                .copied()
+                .collect(),
+            bus_solo: self.bus_solo.contains(&id),
+            emergency_passthrough: self.emergency_passthrough == Some(id),
+            action_sets: Vec::new(),
+            passthrough_bus: false,
+            taps: Vec::new(),
+            replay: false,
+            network_send: false,
+            network_receive: false,
+            played: false,
+            pipeline_input: false,
+        })
+    }
+
+    /// Supprime un canal et tout ce qui le référence ; retourne sa config
+    /// et ce qui a été nettoyé.
+    pub fn remove_channel(
+        &mut self,
+        id: ChannelId,
+    ) -> MixerResult<(ChannelConfig, ChannelDependencies)> {
+        let cleaned = self.channel_dependencies(id)?;
+        let removed = self
+            .channels
+            .remove(&id)
+            .ok_or(MixerError::ChannelNotFound(id.0))?;
         self.states.remove(&id);
+        self.bus_solo.remove(&id);
+        self.bus_fades.remove(&id);
+        self.effects_diff_monitor.remove(&id);
+        if self.emergency_passthrough == Some(id) {
+            self.emergency_passthrough = None;
+        }
+        // Un retour vers ce bus n'a plus de destination
+        for ch in self.channels.values_mut() {
+            if ch.monitor_bus == Some(id) {
+                ch.monitor_bus = None;
+            }
+            ch.sum_priority.retain(|&listed| listed != id);
+        }
         // Supprimer toutes les routes qui référencent ce canal
         self.routes.retain(|r| r.from != id && r.to != id);
+        // Un crossfader sans l'un de ses côtés n'a plus de sens
+        self.crossfades.retain(|x| !x.involves(id));
+        // Son partenaire redevient un canal mono indépendant
+        self.links.retain(|l| !l.involves(id));
+        // Ses suiveurs gardent leur volume actuel
+        self.follows.retain(|f| f.follower != id && f.leader != id);
+        Ok((removed, cleaned))
     }
 
     /// Retourne la config d'un canal.
@@ -108,23 +315,93 @@ impl Mixer {
     }
 
     /// Change le volume d'un canal (clampé entre 0.0 et 2.0).
+    ///
+    /// Sur un bus en plein fondu, le fondu est abandonné : l'utilisateur
+    /// a repris le fader en main. Les bus qui suivent celui-ci prennent
+    /// leur nouveau volume (voir `follow_bus`).
     pub fn set_volume(&mut self, id: ChannelId, volume: f32) {
-        if let Some(ch) = self.channels.get_mut(&id) {
-            ch.volume = volume.clamp(0.0, 2.0);
+        for id in self.with_partner(id) {
+            if let Some(ch) = self.channels.get_mut(&id) {
+                ch.volume = volume.clamp(0.0, 2.0);
+                self.bus_fades.remove(&id);
+            }
         }
+        self.override_follow(id);
+        self.propagate_follow(id, None);
     }
 
-    /// Mute/unmute un canal.
+    /// Lance un fondu d'un bus vers `target` sur `duration_ms`.
+    ///
+    /// Le volume sauvé devient tout de suite la cible ; c'est le callback
+    /// audio qui fait glisser le gain réel (voir `dsp::ramp::BusFader`).
+    /// Retourne `false` si `id` n'est pas un bus.
+    pub fn fade_bus_volume(
+        &mut self,
+        id: ChannelId,
+        target: VolumeDecibels,
+        duration_ms: f32,
+    ) -> bool {
+        let Some(ch) = self
+            .channels
+            .get_mut(&id)
+            .filter(|c| c.kind == ChannelKind::Output)
+        else {
+            return false;
+        };
+        ch.volume = target.to_linear().clamp(0.0, 2.0);
+        self.start_bus_fade(id, duration_ms);
+        self.override_follow(id);
+        self.propagate_follow(id, Some(duration_ms));
+        true
+    }
+
+    fn start_bus_fade(&mut self, id: ChannelId, duration_ms: f32) {
+        self.bus_fades.insert(
+            id,
+            BusFade {
+                id: self.next_fade_id,
+                duration_ms: duration_ms.max(0.0),
+            },
+        );
+        self.next_fade_id += 1;
+    }
+
+    /// Dernier fondu demandé sur un bus (`None` si repris à la main).
+    pub fn bus_fade(&self, id: ChannelId) -> Option<BusFade> {
+        self.bus_fades.get(&id).copied()
+    }
+
+    /// Règle le volume d'un canal depuis une position de fader (0.0 → 1.0).
+    ///
+    /// La courbe convertit la position en dB, puis en gain linéaire :
+    /// `set_volume` (gain brut) reste inchangé pour les appelants existants.
+    pub fn set_volume_fader(&mut self, id: ChannelId, position: f32, taper: FaderTaper) {
+        let gain = VolumeDecibels::from_fader_position(position, taper).to_linear();
+        self.set_volume(id, gain);
+    }
+
+    /// Position de fader correspondant au volume actuel d'un canal.
+    pub fn volume_fader(&self, id: ChannelId, taper: FaderTaper) -> Option<f32> {
+        self.channels
+            .get(&id)
+            .map(|ch| VolumeDecibels::from_linear(ch.volume).to_fader_position(taper))
+    }
+
+    /// Mute/unmute un canal (et son partenaire stéréo).
     pub fn set_mute(&mut self, id: ChannelId, muted: bool) {
-        if let Some(ch) = self.channels.get_mut(&id) {
-            ch.muted = muted;
+        for id in self.with_partner(id) {
+            if let Some(ch) = self.channels.get_mut(&id) {
+                ch.muted = muted;
+            }
         }
     }
 
-    /// Active/désactive le solo sur un canal.
+    /// Active/désactive le solo sur un canal (et son partenaire stéréo).
     pub fn set_solo(&mut self, id: ChannelId, solo: bool) {
-        if let Some(ch) = self.channels.get_mut(&id) {
-            ch.solo = solo;
+        for id in self.with_partner(id) {
+            if let Some(ch) = self.channels.get_mut(&id) {
+                ch.solo = solo;
+            }
         }
     }
 
@@ -135,7 +412,132 @@ impl Mixer {
         }
     }
 
+    /// Change le retard d'un canal (clampé entre 0 et `MAX_DELAY_MS`).
+    pub fn set_delay(&mut self, id: ChannelId, delay_ms: f32) {
+        if let Some(ch) = self.channels.get_mut(&id) {
+            ch.delay_ms = delay_ms.clamp(0.0, MAX_DELAY_MS);
+        }
+    }
+
+    /// Inversion de polarité et bloqueur de DC d'une entrée (un bus n'a
+    /// pas de signal d'entrée à conditionner).
+    pub fn set_input_options(
+        &mut self,
+        id: ChannelId,
+        phase_invert: bool,
+        dc_block: bool,
+    ) -> MixerResult<()> {
+        let ch = self
+            .channels
+            .get_mut(&id)
+            .ok_or(MixerError::ChannelNotFound(id.0))?;
+        if ch.kind != ChannelKind::Input {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} is a bus, input options only apply to inputs",
+                id.0
+            )));
+        }
+        ch.phase_invert = phase_invert;
+        ch.dc_block = dc_block;
+        Ok(())
+    }
+
+    /// Choisit la tranche de canaux du device lue par un canal.
+    /// `count = 0` revient au comportement par défaut (tout le device).
+    pub fn set_device_channels(&mut self, id: ChannelId, offset: u16, count: u16) {
+        if let Some(ch) = self.channels.get_mut(&id) {
+            ch.device_channel_offset = offset;
+            ch.device_channel_count = count;
+        }
+    }
+
+    /// Surcharge (ou rétablit, avec `None`) la détection de silence d'un canal.
+    pub fn set_signal_presence(&mut self, id: ChannelId, config: Option<SignalPresenceConfig>) {
+        if let Some(ch) = self.channels.get_mut(&id) {
+            ch.signal_presence = config;
+        }
+    }
+
+    /// Règle le retour (monitoring direct) d'une entrée vers un bus.
+    ///
+    /// `bus = None` coupe le retour. Le niveau est borné au maximum du
+    /// fader (+6 dB) ; `-inf` est accepté. Retourne `false` si `id`
+    /// n'est pas une entrée ou `bus` pas une sortie.
+    pub fn set_monitor(&mut self, id: ChannelId, bus: Option<ChannelId>, level_db: f32) -> bool {
+        let is_kind = |id: ChannelId, kind: ChannelKind| {
+            self.channels.get(&id).is_some_and(|c| c.kind == kind)
+        };
+        if !is_kind(id, ChannelKind::Input) || bus.is_some_and(|b| !is_kind(b, ChannelKind::Output))
+        {
+            return false;
+        }
+        if let Some(ch) = self.channels.get_mut(&id) {
+            ch.monitor_bus = bus;
+            ch.monitor_level_db = level_db.min(FADER_MAX_DB);
+        }
+        true
+    }
+
+    /// Gain du retour d'un canal vers un bus : le niveau du retour si
+    /// ce bus est le sien, 0 sinon (ou si le canal est muted).
+    ///
+    /// Le fader et le solo des entrées sont ignorés : c'est tout
+    /// l'intérêt d'un retour "pré-fader".
+    pub fn monitor_gain(&self, id: ChannelId, bus: ChannelId) -> f32 {
+        match self.channels.get(&id) {
+            Some(ch) if ch.monitor_bus == Some(bus) && !ch.muted => {
+                VolumeDecibels(ch.monitor_level_db).to_linear()
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Fait porter au retour d'une entrée la différence entre son
+    /// signal sec et sa sortie d'effets (test de nulle) : on entend
+    /// seulement ce que la chaîne ajoute ou retire. Le mix principal
+    /// ne change pas.
+    ///
+    /// Réglage de l'instant : jamais sauvé. Échoue si le canal n'est pas
+    /// une entrée, ou n'a pas de retour où faire entendre la différence.
+    pub fn set_effects_diff_monitor(&mut self, id: ChannelId, enabled: bool) -> MixerResult<()> {
+        let channel = self
+            .channels
+            .get(&id)
+            .ok_or(MixerError::ChannelNotFound(id.0))?;
+        if channel.kind != ChannelKind::Input {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} is not an input",
+                id.0
+            )));
+        }
+        if !enabled {
+            self.effects_diff_monitor.remove(&id);
+        } else if channel.monitor_bus.is_none() {
+            return Err(MixerError::InvalidParameter(format!(
+                "channel {} has no monitor bus",
+                id.0
+            )));
+        } else {
+            self.effects_diff_monitor.insert(id);
+        }
+        Ok(())
+    }
+
+    pub fn effects_diff_monitor(&self, id: ChannelId) -> bool {
+        self.effects_diff_monitor.contains(&id)
+    }
+
+    /// Coupe toutes les écoutes de différence ; retourne les canaux concernés.
+    pub fn clear_effects_diff_monitor(&mut self) -> Vec<ChannelId> {
+        let mut cleared: Vec<ChannelId> = self.effects_diff_monitor.drain().collect();
+        cleared.sort_by_key(|id| id.0);
+        cleared
+    }
+
     /// Ajoute une route (si elle n'existe pas déjà).
+    ///
+    /// Refusée si elle fermerait une boucle (sous-mix qui se renverrait
+    /// à lui-même) : le signal tournerait sans fin.
     pub fn add_route(&mut self, from: ChannelId, to: ChannelId) -> bool {
         let route = Route::new(from, to);
         if self.routes.contains(&route) {
@@ -145,10 +547,62 @@ impl Mixer {
         if !self.channels.contains_key(&from) || !self.channels.contains_key(&to) {
             return false;
         }
+        if from == to || self.reaches(to, from) {
+            return false;
+        }
         self.routes.push(route);
         true
     }
 
+    /// `to` reçoit-il le signal de `from`, directement ou par des sous-mix ?
+    fn reaches(&self, from: ChannelId, to: ChannelId) -> bool {
+        let mut pending = vec![from];
+        let mut seen = Vec::new();
+        while let Some(id) = pending.pop() {
+            if id == to {
+                return true;
+            }
+            if seen.contains(&id) {
+                continue;
+            }
+            seen.push(id);
+            pending.extend(self.routes.iter().filter(|r| r.from == id).map(|r| r.to));
+        }
+        false
+    }
+
+    /// Remplace toutes les routes d'un coup (routage nommé), tout ou
+    /// rien : refusé si une route cite un canal absent (tous listés) ou
+    /// fermerait une boucle. Les doublons sont ignorés.
+    pub fn replace_routes(&mut self, routes: &[Route]) -> MixerResult<()> {
+        let mut missing: Vec<usize> = routes
+            .iter()
+            .flat_map(|route| [route.from, route.to])
+            .filter(|id| !self.channels.contains_key(id))
+            .map(|id| id.0)
+            .collect();
+        if !missing.is_empty() {
+            missing.sort_unstable();
+            missing.dedup();
+            let list: Vec<String> = missing.iter().map(usize::to_string).collect();
+            return Err(MixerError::InvalidParameter(format!(
+                "routes reference deleted channels: {}",
+                list.join(", ")
+            )));
+        }
+        let previous = std::mem::take(&mut self.routes);
+        for route in routes {
+            if !self.add_route(route.from, route.to) && !self.routes.contains(route) {
+                self.routes = previous;
+                return Err(MixerError::InvalidParameter(format!(
+                    "route {} -> {} would create a loop",
+                    route.from.0, route.to.0
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Supprime une route.
     pub fn remove_route(&mut self, from: ChannelId, to: ChannelId) {
         self.routes.retain(|r| !(r.from == from && r.to == to));
@@ -164,6 +618,510 @@ impl Mixer {
         &self.routes
     }
 
+    /// Crée un crossfader entre deux entrées, au centre.
+    ///
+    /// Échoue si l'id est déjà pris, si un canal n'existe pas, n'est pas
+    /// une entrée, ou si A et B sont le même canal.
+    pub fn create_crossfade(
+        &mut self,
+        id: u32,
+        channel_a: ChannelId,
+        channel_b: ChannelId,
+        curve: CrossfadeCurve,
+    ) -> MixerResult<()> {
+        self.insert_crossfade(Crossfade::new(id, channel_a, channel_b, curve))
+    }
+
+    fn insert_crossfade(&mut self, crossfade: Crossfade) -> MixerResult<()> {
+        if self.crossfade(crossfade.id).is_some() {
+            return Err(MixerError::InvalidParameter(format!(
+                "crossfade {} already exists",
+                crossfade.id
+            )));
+        }
+        if crossfade.channel_a == crossfade.channel_b {
+            return Err(MixerError::InvalidParameter(
+                "a crossfade needs two different channels".into(),
+            ));
+        }
+        for id in [crossfade.channel_a, crossfade.channel_b] {
+            match self.channels.get(&id) {
+                Some(c) if c.kind == ChannelKind::Input => {}
+                Some(_) => {
+                    return Err(MixerError::InvalidParameter(format!(
+                        "channel {} is not an input",
+                        id.0
+                    )));
+                }
+                None => return Err(MixerError::ChannelNotFound(id.0)),
+            }
+        }
+        let at = self.crossfades.partition_point(|x| x.id < crossfade.id);
+        self.crossfades.insert(at, crossfade);
+        Ok(())
+    }
+
+    /// Déplace un crossfader (position clampée entre 0.0 et 1.0).
+    pub fn set_crossfade_position(&mut self, id: u32, position: f32) -> MixerResult<()> {
+        let crossfade = self
+            .crossfades
+            .iter_mut()
+            .find(|x| x.id == id)
+            .ok_or_else(|| MixerError::InvalidParameter(format!("crossfade {id} not found")))?;
+        crossfade.position = position.clamp(0.0, 1.0);
+        Ok(())
+    }
+
+    /// Supprime un crossfader ; ses deux canaux retrouvent leur seul fader.
+    pub fn remove_crossfade(&mut self, id: u32) -> MixerResult<Crossfade> {
+        let at = self
+            .crossfades
+            .iter()
+            .position(|x| x.id == id)
+            .ok_or_else(|| MixerError::InvalidParameter(format!("crossfade {id} not found")))?;
+        Ok(self.crossfades.remove(at))
+    }
+
+    pub fn crossfade(&self, id: u32) -> Option<&Crossfade> {
+        self.crossfades.iter().find(|x| x.id == id)
+    }
+
+    pub fn crossfades(&self) -> &[Crossfade] {
+        &self.crossfades
+    }
+
+    /// Lie deux entrées mono en une paire stéréo (L = `left`, R = `right`).
+    ///
+    /// Un canal n'appartient qu'à un lien. `right` prend tout de suite
+    /// le volume, le mute et le solo de `left`, qui fait foi. Le mix de
+    /// la paire en L/R n'existe que hors ligne (`render_offline`).
+    pub fn link_channels(&mut self, left: ChannelId, right: ChannelId) -> MixerResult<()> {
+        if left == right {
+            return Err(MixerError::InvalidParameter(
+                "a stereo link needs two different channels".into(),
+            ));
+        }
+        for id in [left, right] {
+            match self.channels.get(&id) {
+                Some(c) if c.kind == ChannelKind::Input => {}
+                Some(_) => {
+                    return Err(MixerError::InvalidParameter(format!(
+                        "channel {} is not an input",
+                        id.0
+                    )));
+                }
+                None => return Err(MixerError::ChannelNotFound(id.0)),
+            }
+            if self.link_of(id).is_some() {
+                return Err(MixerError::InvalidParameter(format!(
+                    "channel {} is already linked",
+                    id.0
+                )));
+            }
+        }
+        let source = self.channels[&left].clone();
+        if let Some(ch) = self.channels.get_mut(&right) {
+            ch.volume = source.volume;
+            ch.muted = source.muted;
+            ch.solo = source.solo;
+        }
+        let at = self.links.partition_point(|l| l.left.0 < left.0);
+        self.links.insert(at, ChannelLink::new(left, right));
+        Ok(())
+    }
+
+    /// Défait le lien dont `id` est l'un des côtés ; les deux canaux
+    /// gardent leurs réglages actuels.
+    pub fn unlink_channels(&mut self, id: ChannelId) -> MixerResult<ChannelLink> {
+        let at = self
+            .links
+            .iter()
+            .position(|l| l.involves(id))
+            .ok_or_else(|| {
+                MixerError::InvalidParameter(format!("channel {} is not linked", id.0))
+            })?;
+        Ok(self.links.remove(at))
+    }
+
+    /// Lien dont `id` est l'un des côtés.
+    pub fn link_of(&self, id: ChannelId) -> Option<ChannelLink> {
+        self.links.iter().find(|l| l.involves(id)).copied()
+    }
+
+    /// Partenaire stéréo de `id`.
+    pub fn link_partner(&self, id: ChannelId) -> Option<ChannelId> {
+        self.link_of(id).and_then(|l| l.partner(id))
+    }
+
+    pub fn links(&self) -> &[ChannelLink] {
+        &self.links
+    }
+
+    /// `id` suivi de son partenaire stéréo, s'il en a un.
+    fn with_partner(&self, id: ChannelId) -> Vec<ChannelId> {
+        std::iter::once(id).chain(self.link_partner(id)).collect()
+    }
+
+    /// Fait suivre au bus `follow.follower` le volume de `follow.leader`,
+    /// à `offset_db` près ; le suiveur prend tout de suite son volume.
+    ///
+    /// Un suivi existant du même suiveur est remplacé. Refusé si l'un
+    /// des deux n'est pas un bus, ou si le leader suit déjà (de proche
+    /// en proche) le suiveur : les volumes se courraient après.
+    pub fn follow_bus(&mut self, follow: BusFollow) -> MixerResult<()> {
+        self.bus(follow.follower)?;
+        self.bus(follow.leader)?;
+        if !follow.offset_db.is_finite() {
+            return Err(MixerError::InvalidParameter(format!(
+                "invalid offset {} dB",
+                follow.offset_db
+            )));
+        }
+        // Le partenaire stéréo du suiveur bouge avec lui : il compte
+        // dans la boucle
+        let mut chain = self.with_partner(follow.follower);
+        chain.extend(self.bus_followers(follow.follower));
+        if chain.contains(&follow.leader) {
+            return Err(MixerError::InvalidParameter(format!(
+                "bus {} cannot follow bus {}: it would follow itself",
+                follow.follower.0, follow.leader.0
+            )));
+        }
+        self.follows.retain(|f| f.follower != follow.follower);
+        let at = self
+            .follows
+            .partition_point(|f| f.follower.0 < follow.follower.0);
+        self.follows.insert(at, follow);
+        self.propagate_follow(follow.leader, None);
+        Ok(())
+    }
+
+    /// Défait le suivi de `follower` ; il garde son volume actuel.
+    pub fn unfollow_bus(&mut self, follower: ChannelId) -> MixerResult<BusFollow> {
+        let at = self
+            .follows
+            .iter()
+            .position(|f| f.follower == follower)
+            .ok_or_else(|| {
+                MixerError::InvalidParameter(format!("bus {} follows no bus", follower.0))
+            })?;
+        Ok(self.follows.remove(at))
+    }
+
+    /// Suivi dont `id` est le suiveur.
+    pub fn bus_follow(&self, id: ChannelId) -> Option<&BusFollow> {
+        self.follows.iter().find(|f| f.follower == id)
+    }
+
+    pub fn bus_follows(&self) -> &[BusFollow] {
+        &self.follows
+    }
+
+    /// Bus qui suivent `id` (ou son partenaire stéréo), directement ou
+    /// de proche en proche, avec leurs partenaires stéréo.
+    pub fn bus_followers(&self, id: ChannelId) -> Vec<ChannelId> {
+        let mut followers = Vec::new();
+        let mut pending = self.with_partner(id);
+        let mut seen = pending.clone();
+        while let Some(leader) = pending.pop() {
+            for follow in self.follows.iter().filter(|f| f.leader == leader) {
+                for bus in self.with_partner(follow.follower) {
+                    if !seen.contains(&bus) {
+                        seen.push(bus);
+                        followers.push(bus);
+                        pending.push(bus);
+                    }
+                }
+            }
+        }
+        followers
+    }
+
+    /// Volume de `id` réglé à la main alors qu'il suit un bus : le
+    /// suivi est défait, ou son écart recalé (`FollowOverride`).
+    fn override_follow(&mut self, id: ChannelId) {
+        let Some(at) = self.follows.iter().position(|f| f.follower == id) else {
+            return;
+        };
+        match self.follows[at].on_override {
+            FollowOverride::Unlink => {
+                self.follows.remove(at);
+            }
+            FollowOverride::AdjustOffset => {
+                let db = |id| {
+                    self.channels
+                        .get(&id)
+                        .map(|c| VolumeDecibels::from_linear(c.volume))
+                        .filter(|db| !db.is_silent())
+                };
+                // Écart indéfini si l'un des deux est muet : gardé
+                if let (Some(follower), Some(leader)) = (db(id), db(self.follows[at].leader)) {
+                    self.follows[at].offset_db = follower.0 - leader.0;
+                }
+            }
+        }
+    }
+
+    /// Recale le volume des bus qui suivent `leader` (ou son partenaire
+    /// stéréo), de proche en proche ; le partenaire stéréo d'un suiveur
+    /// bouge avec lui. Un fondu du leader (`duration_ms`) est repris.
+    fn propagate_follow(&mut self, leader: ChannelId, duration_ms: Option<f32>) {
+        let mut pending = vec![leader];
+        // Un lien stéréo posé après coup peut refermer une boucle
+        let mut seen = self.with_partner(leader);
+        while let Some(leader) = pending.pop() {
+            let Some(volume) = self.channels.get(&leader).map(|c| c.volume) else {
+                continue;
+            };
+            let leader_db = VolumeDecibels::from_linear(volume).0;
+            let leaders = self.with_partner(leader);
+            let follows: Vec<BusFollow> = self
+                .follows
+                .iter()
+                .filter(|f| leaders.contains(&f.leader))
+                .copied()
+                .collect();
+            for follow in follows {
+                if seen.contains(&follow.follower) {
+                    continue;
+                }
+                let volume = VolumeDecibels(leader_db + follow.offset_db)
+                    .to_linear()
+                    .clamp(0.0, 2.0);
+                for bus in self.with_partner(follow.follower) {
+                    let Some(ch) = self.channels.get_mut(&bus) else {
+                        continue;
+                    };
+                    ch.volume = volume;
+                    match duration_ms {
+                        Some(duration_ms) => self.start_bus_fade(bus, duration_ms),
+                        None => {
+                            self.bus_fades.remove(&bus);
+                        }
+                    }
+                    seen.push(bus);
+                }
+                pending.push(follow.follower);
+            }
+        }
+    }
+
+    /// Gain des crossfaders sur un canal (produit, 1.0 s'il n'en a pas).
+    pub fn crossfade_gain(&self, id: ChannelId) -> f32 {
+        self.crossfades.iter().map(|x| x.gain(id)).product()
+    }
+
+    /// Active/désactive le solo d'un bus de sortie.
+    ///
+    /// # Solo de bus vs solo de canal
+    /// Le solo de canal choisit quelles ENTRÉES on entend dans le mix.
+    /// Le solo de bus choisit quelles SORTIES jouent sur leur device :
+    /// "soloer A2" = écouter exactement le retour stream. Comme le solo
+    /// de canal, il est additif : plusieurs bus peuvent être en solo.
+    ///
+    /// Échoue si le canal n'existe pas ou n'est pas une sortie.
+    pub fn set_bus_solo(&mut self, id: ChannelId, solo: bool) -> MixerResult<()> {
+        self.bus(id)?;
+        if solo {
+            self.bus_solo.insert(id);
+        } else {
+            self.bus_solo.remove(&id);
+        }
+        Ok(())
+    }
+
+    /// Change le nombre de canaux d'un bus de sortie.
+    ///
+    /// Échoue si le canal n'existe pas ou n'est pas une sortie.
+    pub fn set_bus_channel_count(&mut self, id: ChannelId, count: ChannelCount) -> MixerResult<()> {
+        let mut updated = self.bus(id)?.clone();
+        updated.channel_count = count;
+        // Un bus placé qui passe en stéréo prend un canal de plus
+        self.check_output_slot(&updated)?;
+        if let Some(config) = self.channels.get_mut(&id) {
+            config.channel_count = count;
+        }
+        Ok(())
+    }
+
+    /// Place un bus sur les canaux `offset..` de son device de sortie
+    /// (`None` : le stéréo recopié sur toutes les paires).
+    ///
+    /// Échoue si le canal n'est pas une sortie, ou si la tranche
+    /// chevauche celle d'un autre bus du même device. Le nombre de
+    /// canaux du device est vérifié par le moteur, qui le connaît.
+    pub fn set_bus_output_mapping(
+        &mut self,
+        id: ChannelId,
+        offset: Option<u16>,
+    ) -> MixerResult<()> {
+        let mut updated = self.bus(id)?.clone();
+        updated.output_channel_offset = offset;
+        self.check_output_slot(&updated)?;
+        if let Some(config) = self.channels.get_mut(&id) {
+            config.output_channel_offset = offset;
+        }
+        Ok(())
+    }
+
+    /// Active ou coupe la mesure de crête vraie d'un bus (sauvée avec
+    /// sa config).
+    ///
+    /// Échoue si le canal n'existe pas ou n'est pas une sortie.
+    pub fn set_true_peak_meter(&mut self, id: ChannelId, enabled: bool) -> MixerResult<()> {
+        self.bus(id)?;
+        if let Some(config) = self.channels.get_mut(&id) {
+            config.true_peak_meter = enabled;
+        }
+        Ok(())
+    }
+
+    /// Canaux que `bus` somme en premier (sauvé avec sa config). Un id
+    /// répété n'est gardé qu'une fois.
+    ///
+    /// Échoue si le bus n'en est pas un, ou si un canal listé n'existe
+    /// pas ou est lui-même un bus.
+    pub fn set_sum_priority(&mut self, bus: ChannelId, priority: &[ChannelId]) -> MixerResult<()> {
+        self.bus(bus)?;
+        let mut listed = Vec::with_capacity(priority.len());
+        for &id in priority {
+            match self.channels.get(&id) {
+                None => return Err(MixerError::ChannelNotFound(id.0)),
+                Some(c) if c.kind == ChannelKind::Output => {
+                    return Err(MixerError::InvalidParameter(format!(
+                        "bus {} cannot be summed into bus {}",
+                        id.0, bus.0
+                    )));
+                }
+                Some(_) if listed.contains(&id) => {}
+                Some(_) => listed.push(id),
+            }
+        }
+        if let Some(config) = self.channels.get_mut(&bus) {
+            config.sum_priority = listed;
+        }
+        Ok(())
+    }
+
+    /// Retarde la sortie d'un bus vers son device, borné à
+    /// `MAX_OUTPUT_DELAY_MS` (sauvé avec sa config).
+    ///
+    /// Échoue si le canal n'existe pas ou n'est pas une sortie.
+    pub fn set_output_delay(&mut self, id: ChannelId, delay_ms: f32) -> MixerResult<()> {
+        self.bus(id)?;
+        if let Some(config) = self.channels.get_mut(&id) {
+            config.output_delay_ms = clamp_output_delay(delay_ms);
+        }
+        Ok(())
+    }
+
+    /// Vérifie la tranche d'un bus (config à venir) face aux autres bus
+    /// placés sur le même device.
+    fn check_output_slot(&self, updated: &ChannelConfig) -> MixerResult<()> {
+        let Some(slot) = OutputSlot::from_config(updated) else {
+            return Ok(());
+        };
+        let mut slots = vec![(updated.id, slot)];
+        slots.extend(
+            self.channels
+                .values()
+                .filter(|c| {
+                    c.id != updated.id
+                        && c.kind == ChannelKind::Output
+                        && c.device_name == updated.device_name
+                })
+                .filter_map(|c| OutputSlot::from_config(c).map(|s| (c.id, s))),
+        );
+        check_output_slots(&slots, None)
+    }
+
+    /// Config d'un bus de sortie.
+    fn bus(&self, id: ChannelId) -> MixerResult<&ChannelConfig> {
+        match self.channels.get(&id) {
+            Some(c) if c.kind == ChannelKind::Output => Ok(c),
+            Some(_) => Err(MixerError::BusNotFound(id.0)),
+            None => Err(MixerError::ChannelNotFound(id.0)),
+        }
+    }
+
+    /// Active ou coupe la mesure de sonie (LUFS) d'un bus.
+    ///
+    /// Échoue si le canal n'existe pas ou n'est pas une sortie.
+    pub fn set_loudness_meter(&mut self, id: ChannelId, enabled: bool) -> MixerResult<()> {
+        self.bus(id)?;
+        if enabled {
+            self.loudness_meters.entry(id).or_insert(0);
+        } else {
+            self.loudness_meters.remove(&id);
+        }
+        Ok(())
+    }
+
+    /// Génération de la mesure de sonie d'un bus (`None` : pas mesuré).
+    /// Elle change à chaque remise à zéro : le callback repart de zéro
+    /// quand il la voit changer.
+    pub fn loudness_meter(&self, id: ChannelId) -> Option<u64> {
+        self.loudness_meters.get(&id).copied()
+    }
+
+    /// Remet à zéro la mesure de sonie d'un bus (l'intégrée recommence).
+    ///
+    /// Échoue si le bus n'existe pas ou n'est pas mesuré.
+    pub fn reset_loudness_meter(&mut self, id: ChannelId) -> MixerResult<()> {
+        self.bus(id)?;
+        let generation = self.loudness_meters.get_mut(&id).ok_or_else(|| {
+            MixerError::InvalidParameter(format!("loudness of bus {} is not metered", id.0))
+        })?;
+        *generation += 1;
+        Ok(())
+    }
+
+    /// Inverse le solo d'un bus ; retourne le nouvel état.
+    pub fn toggle_bus_solo(&mut self, id: ChannelId) -> Option<bool> {
+        let solo = !self.is_bus_soloed(id);
+        self.set_bus_solo(id, solo).ok().map(|()| solo)
+    }
+
+    pub fn is_bus_soloed(&self, id: ChannelId) -> bool {
+        self.bus_solo.contains(&id)
+    }
+
+    /// `true` si un bus doit envoyer son audio à son device :
+    /// aucun solo de bus actif, ou ce bus est en solo. Le bus de repli
+    /// du passthrough d'urgence joue toujours.
+    pub fn bus_dispatches(&self, id: ChannelId) -> bool {
+        self.bus_solo.is_empty()
+            || self.bus_solo.contains(&id)
+            || self.emergency_passthrough == Some(id)
+    }
+
+    /// Active (`Some(bus)`) ou coupe le passthrough d'urgence.
+    ///
+    /// # Un override, pas un réglage
+    /// Rien n'est écrit dans les canaux : le snapshot runtime ignore
+    /// effets, volumes et mutes des canaux routés vers `bus` tant que
+    /// l'override est actif (voir `MixerRuntimeState::gain`). Le couper
+    /// rend le mix d'avant, intact.
+    ///
+    /// Échoue si `bus` n'existe pas ou n'est pas une sortie.
+    pub fn set_emergency_passthrough(&mut self, bus: Option<ChannelId>) -> MixerResult<()> {
+        if let Some(bus) = bus {
+            self.bus(bus)?;
+        }
+        self.emergency_passthrough = bus;
+        Ok(())
+    }
+
+    pub fn emergency_passthrough(&self) -> Option<ChannelId> {
+        self.emergency_passthrough
+    }
+
+    /// Bus de repli par défaut : le premier bus de sortie (le mix du
+    /// stream dans la configuration par défaut).
+    pub fn default_passthrough_bus(&self) -> Option<ChannelId> {
+        self.outputs().iter().map(|c| c.id).min_by_key(|id| id.0)
+    }
+
     /// Calcule le gain effectif d'un canal, en tenant compte de mute et solo.
     ///
     /// # La logique Solo
@@ -192,17 +1150,23 @@ impl Mixer {
             return (0.0, 0.0);
         }
 
-        // Solo logic
-        let any_solo = self.channels.values().any(|c| c.solo);
-        if any_solo && !ch.solo {
+        // Solo logic (entre entrées : le solo de bus agit à la sortie).
+        // Un sous-mix laisse passer l'entrée en solo qu'il transporte.
+        let any_solo = self
+            .channels
+            .values()
+            .any(|c| c.kind == ChannelKind::Input && c.solo);
+        if any_solo && !ch.solo && ch.kind != ChannelKind::Submix {
             return (0.0, 0.0);
         }
 
         // Constant power pan law
         // Angle de 0 (gauche) à π/2 (droite)
         let angle = (ch.pan + 1.0) * 0.5 * std::f32::consts::FRAC_PI_2;
-        let gain_left = ch.volume * angle.cos();
-        let gain_right = ch.volume * angle.sin();
+        // Le crossfader est un facteur à part : le volume reste celui du fader
+        let volume = ch.volume * self.crossfade_gain(id);
+        let gain_left = volume * angle.cos();
+        let gain_right = volume * angle.sin();
 
         (gain_left, gain_right)
     }
@@ -261,6 +1225,15 @@ impl Mixer {
             // Decay lent du peak hold
             state.peak_hold *= 0.95;
         }
+
+        // Sous le plancher de l'échelle, le meter est à zéro : la
+        // retombée s'arrête au lieu de tendre vers 0 sans l'atteindre
+        let floor = self.meter_scale.floor();
+        for level in [&mut state.rms, &mut state.peak, &mut state.peak_hold] {
+            if *level < floor {
+                *level = 0.0;
+            }
+        }
     }
 
     /// Retourne les niveaux actuels de tous les canaux (pour l'UI).
@@ -271,6 +1244,11 @@ impl Mixer {
                 channel: id,
                 rms: state.rms,
                 peak: state.peak,
+                rms_position: self.meter_scale.position(state.rms),
+                peak_position: self.meter_scale.position(state.peak),
+                loudness: None,
+                true_peak: None,
+                clipped: self.meter_scale.clips(state.peak),
             })
             .collect()
     }
@@ -291,18 +1269,182 @@ impl Mixer {
             .collect()
     }
 
+    /// Retourne les sous-mix.
+    pub fn submixes(&self) -> Vec<&ChannelConfig> {
+        self.channels
+            .values()
+            .filter(|c| c.kind == ChannelKind::Submix)
+            .collect()
+    }
+
     /// Nombre total de canaux.
     pub fn channel_count(&self) -> usize {
         self.channels.len()
     }
 
+    /// Solo tel que l'affiche la tranche : solo du canal pour une
+    /// entrée, solo de bus pour une sortie.
+    fn is_soloed(&self, channel: &ChannelConfig) -> bool {
+        match channel.kind {
+            ChannelKind::Input | ChannelKind::Submix => channel.solo,
+            ChannelKind::Output => self.is_bus_soloed(channel.id),
+        }
+    }
+
+    /// Canaux qui satisfont TOUS les critères de `query`, dans l'ordre
+    /// d'affichage : entrées puis bus, chacun par id.
+    ///
+    /// # Casse et Unicode
+    /// `to_lowercase` suit les règles Unicode ("ÉCRAN" trouve "écran"),
+    /// contrairement à `to_ascii_lowercase` qui ignore les accents.
+    pub fn find_channels(&self, query: &ChannelQuery) -> Vec<ChannelConfig> {
+        let needle = query.name.as_deref().map(str::to_lowercase);
+        let mut found: Vec<ChannelConfig> = self
+            .channels
+            .values()
+            .filter(|c| {
+                needle
+                    .as_deref()
+                    .is_none_or(|n| c.name.to_lowercase().contains(n))
+                    && query.kind.is_none_or(|k| c.kind == k)
+                    && query.muted.is_none_or(|m| c.muted == m)
+                    && query.soloed.is_none_or(|s| self.is_soloed(c) == s)
+                    && query.bus.is_none_or(|b| self.has_route(c.id, b))
+                    && query
+                        .device
+                        .as_deref()
+                        .is_none_or(|d| c.device_name.specific() == Some(d))
+            })
+            .cloned()
+            .collect();
+        found.sort_by_key(|c| (c.kind == ChannelKind::Output, c.id.0));
+        found
+    }
+
+    /// Totaux par état pour les badges de l'UI.
+    pub fn channel_count_summary(&self) -> ChannelCountSummary {
+        let mut summary = ChannelCountSummary::default();
+        for channel in self.channels.values() {
+            summary.total += 1;
+            match channel.kind {
+                ChannelKind::Input => summary.inputs += 1,
+                ChannelKind::Output => summary.outputs += 1,
+                ChannelKind::Submix => summary.submixes += 1,
+            }
+            summary.muted += usize::from(channel.muted);
+            summary.soloed += usize::from(self.is_soloed(channel));
+            summary.unassigned += usize::from(channel.device_name.is_none());
+        }
+        summary
+    }
+
     /// Exporte la config actuelle (pour sauvegarde).
+    ///
+    /// Canaux triés par id : l'ordre d'une `HashMap` change d'un
+    /// lancement à l'autre, le fichier sauvé ne doit pas.
     pub fn to_config(&self) -> MixerConfig {
+        let mut channels: Vec<ChannelConfig> = self.channels.values().cloned().collect();
+        channels.sort_by_key(|c| c.id.0);
         MixerConfig {
-            channels: self.channels.values().cloned().collect(),
+            channels,
             routes: self.routes.clone(),
+            crossfades: self.crossfades.clone(),
+            links: self.links.clone(),
+            bus_follows: self.follows.clone(),
         }
     }
+
+    /// Exporte le flux de signal configuré sous forme de graphe.
+    ///
+    /// - Nœuds : canaux (volume, pan, mute, solo) + devices assignés
+    /// - Arêtes : device → entrée, entrée → sortie (routes), sortie → device
+    ///
+    /// Les devices apparaissent avec `active: false` : seul le moteur
+    /// sait quels streams sont réellement ouverts
+    /// (voir `Engine::export_signal_graph`).
+    pub fn export_signal_graph(&self) -> SignalGraph {
+        let mut graph = SignalGraph::default();
+
+        // Ordre déterministe (le HashMap n'en a pas)
+        let mut channels: Vec<&ChannelConfig> = self.channels.values().collect();
+        channels.sort_by_key(|c| c.id.0);
+
+        for ch in &channels {
+            let (kind, device_kind) = match ch.kind {
+                ChannelKind::Input => (NodeKind::Input, Some(NodeKind::InputDevice)),
+                ChannelKind::Output => (NodeKind::Output, Some(NodeKind::OutputDevice)),
+                // Un sous-mix n'a jamais de device
+                ChannelKind::Submix => (NodeKind::Submix, None),
+            };
+            graph.upsert_node(GraphNode {
+                id: channel_node_id(ch.id),
+                kind,
+                label: ch.name.clone(),
+                volume: Some(ch.volume),
+                pan: Some(ch.pan),
+                muted: Some(ch.muted),
+                solo: Some(self.is_soloed(ch)),
+                active: None,
+            });
+
+            if let Some(device_kind) = device_kind
+                && let Some(device) = ch.device_name.specific()
+            {
+                let device_id = device_node_id(device_kind, device);
+                graph.upsert_node(GraphNode::device(&device_id, device_kind, device));
+                let (from, to) = if ch.kind == ChannelKind::Input {
+                    (device_id, channel_node_id(ch.id))
+                } else {
+                    (channel_node_id(ch.id), device_id)
+                };
+                graph.add_edge(GraphEdge {
+                    from,
+                    to,
+                    gain: 1.0,
+                    enabled: !ch.muted && self.bus_dispatches(ch.id),
+                });
+            }
+        }
+
+        for route in &self.routes {
+            let (l, r) = self.effective_gain(route.from);
+            let dest_muted = self.channels.get(&route.to).is_none_or(|c| c.muted);
+            graph.add_edge(GraphEdge {
+                from: channel_node_id(route.from),
+                to: channel_node_id(route.to),
+                gain: self.channels.get(&route.from).map_or(0.0, |c| c.volume),
+                enabled: (l > 0.0 || r > 0.0) && !dest_muted,
+            });
+        }
+
+        graph
+    }
+}
+
+/// Retard de sortie borné à `[0, MAX_OUTPUT_DELAY_MS]` ; une valeur
+/// illisible (NaN) compte pour aucun retard.
+fn clamp_output_delay(delay_ms: f32) -> f32 {
+    if delay_ms.is_nan() {
+        0.0
+    } else {
+        delay_ms.clamp(0.0, MAX_OUTPUT_DELAY_MS)
+    }
+}
+
+/// Identifiant d'un canal dans le graphe de signal.
+pub fn channel_node_id(id: ChannelId) -> String {
+    format!("ch{}", id.0)
+}
+
+/// Identifiant d'un device dans le graphe de signal.
+///
+/// Préfixé par la direction : un même nom peut désigner
+/// l'entrée ET la sortie d'une interface audio.
+pub fn device_node_id(kind: NodeKind, name: &str) -> String {
+    match kind {
+        NodeKind::InputDevice => format!("in:{name}"),
+        _ => format!("out:{name}"),
+    }
 }
 
 impl Default for Mixer {
@@ -314,11 +1456,646 @@ impl Default for Mixer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::snapshot::MixerRuntimeState;
+    use troubadour_shared::mixer::DeviceAssignment;
 
     fn setup_mixer() -> Mixer {
         Mixer::from_config(MixerConfig::default_setup())
     }
 
+    /// Session de test : 3 entrées (dont des noms accentués), 2 bus.
+    fn search_mixer() -> Mixer {
+        let mut mic = ChannelConfig::input(0, "Micro Studio");
+        mic.device_name = DeviceAssignment::Specific("Scarlett 2i2".into());
+        let mut desktop = ChannelConfig::input(1, "Écran partagé");
+        desktop.muted = true;
+        let mut chat = ChannelConfig::input(4, "Discord 🎧");
+        chat.solo = true;
+        chat.device_name = DeviceAssignment::Specific("Scarlett 2i2".into());
+        let mut headphones = ChannelConfig::output(2, "Casque");
+        headphones.device_name = DeviceAssignment::Specific("HD 600".into());
+        let mut stream = ChannelConfig::output(3, "Stream Mix");
+        stream.muted = true;
+        let mut mixer = Mixer::from_config(MixerConfig {
+            // Ordre volontairement mélangé : le résultat ne doit pas en dépendre
+            channels: vec![stream, chat, headphones, desktop, mic],
+            routes: vec![],
+            crossfades: vec![],
+            links: vec![],
+            bus_follows: vec![],
+        });
+        mixer.add_route(ChannelId(0), ChannelId(2));
+        mixer.add_route(ChannelId(0), ChannelId(3));
+        mixer.add_route(ChannelId(4), ChannelId(2));
+        mixer.set_bus_solo(ChannelId(3), true).unwrap();
+        mixer
+    }
+
+    fn found_ids(mixer: &Mixer, query: ChannelQuery) -> Vec<usize> {
+        mixer.find_channels(&query).iter().map(|c| c.id.0).collect()
+    }
+
+    #[test]
+    fn empty_query_returns_everything_in_display_order() {
+        let mixer = search_mixer();
+        // Entrées par id, puis bus par id
+        assert_eq!(found_ids(&mixer, ChannelQuery::default()), [0, 1, 4, 2, 3]);
+        // Un nom vide ne filtre rien non plus
+        let query = ChannelQuery {
+            name: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(found_ids(&mixer, query), [0, 1, 4, 2, 3]);
+        assert!(
+            Mixer::new()
+                .find_channels(&ChannelQuery::default())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn name_search_ignores_case_including_unicode() {
+        let mixer = search_mixer();
+        let by_name = |name: &str| {
+            found_ids(
+                &mixer,
+                ChannelQuery {
+                    name: Some(name.into()),
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(by_name("MIC"), [0]);
+        assert_eq!(by_name("écran"), [1]);
+        assert_eq!(by_name("ÉCRAN PARTAGÉ"), [1]);
+        assert_eq!(by_name("🎧"), [4]);
+        assert_eq!(by_name("s"), [0, 4, 2, 3]);
+        // Sans accent, pas de correspondance : on ne replie pas les diacritiques
+        assert!(by_name("ecran").is_empty());
+        assert!(by_name("guitar").is_empty());
+    }
+
+    #[test]
+    fn each_criterion_filters_on_its_own() {
+        let mixer = search_mixer();
+        let q = ChannelQuery::default;
+        let kind = |k| ChannelQuery {
+            kind: Some(k),
+            ..q()
+        };
+        assert_eq!(found_ids(&mixer, kind(ChannelKind::Input)), [0, 1, 4]);
+        assert_eq!(found_ids(&mixer, kind(ChannelKind::Output)), [2, 3]);
+
+        let muted = |m| ChannelQuery {
+            muted: Some(m),
+            ..q()
+        };
+        assert_eq!(found_ids(&mixer, muted(true)), [1, 3]);
+        assert_eq!(found_ids(&mixer, muted(false)), [0, 4, 2]);
+
+        // Solo d'entrée et solo de bus
+        let soloed = |s| ChannelQuery {
+            soloed: Some(s),
+            ..q()
+        };
+        assert_eq!(found_ids(&mixer, soloed(true)), [4, 3]);
+        assert_eq!(found_ids(&mixer, soloed(false)), [0, 1, 2]);
+
+        let bus = |b| ChannelQuery {
+            bus: Some(ChannelId(b)),
+            ..q()
+        };
+        assert_eq!(found_ids(&mixer, bus(2)), [0, 4]);
+        assert_eq!(found_ids(&mixer, bus(3)), [0]);
+        assert!(found_ids(&mixer, bus(99)).is_empty());
+
+        let device = |d: &str| ChannelQuery {
+            device: Some(d.into()),
+            ..q()
+        };
+        assert_eq!(found_ids(&mixer, device("Scarlett 2i2")), [0, 4]);
+        assert_eq!(found_ids(&mixer, device("HD 600")), [2]);
+        // Nom exact : pas de sous-chaîne pour un device
+        assert!(found_ids(&mixer, device("Scarlett")).is_empty());
+    }
+
+    #[test]
+    fn criteria_combine_with_and() {
+        let mixer = search_mixer();
+        let query = ChannelQuery {
+            device: Some("Scarlett 2i2".into()),
+            bus: Some(ChannelId(2)),
+            soloed: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(found_ids(&mixer, query), [0]);
+
+        let query = ChannelQuery {
+            name: Some("micro".into()),
+            muted: Some(true),
+            ..Default::default()
+        };
+        assert!(found_ids(&mixer, query).is_empty());
+    }
+
+    #[test]
+    fn count_summary_matches_flags() {
+        let mixer = search_mixer();
+        assert_eq!(
+            mixer.channel_count_summary(),
+            ChannelCountSummary {
+                total: 5,
+                inputs: 3,
+                outputs: 2,
+                submixes: 0,
+                muted: 2,
+                soloed: 2,
+                unassigned: 2,
+            }
+        );
+        assert_eq!(
+            Mixer::new().channel_count_summary(),
+            ChannelCountSummary::default()
+        );
+    }
+
+    #[test]
+    fn duplicate_channel_id_is_rejected() {
+        let mut mixer = setup_mixer();
+        mixer.set_volume(ChannelId(1), 0.3);
+
+        let result = mixer.add_channel(ChannelConfig::input(1, "Input 1"));
+        assert_eq!(result, Err(MixerError::DuplicateId(1)));
+        // L'ancien canal et ses réglages sont intacts
+        let kept = mixer.channel(ChannelId(1)).unwrap();
+        assert_eq!(kept.name, "Desktop");
+        assert_eq!(kept.volume, 0.3);
+
+        mixer
+            .add_channel(ChannelConfig::input(7, "Input 7"))
+            .unwrap();
+        let ids: Vec<usize> = mixer.to_config().channels.iter().map(|c| c.id.0).collect();
+        assert_eq!(ids, [0, 1, 2, 3, 4, 7]);
+    }
+
+    #[test]
+    fn loading_over_existing_channels_replaces_them() {
+        let mut config = MixerConfig::default_setup();
+        config.channels.push(ChannelConfig::input(0, "Mic (USB)"));
+        let mixer = Mixer::from_config(config);
+        assert_eq!(mixer.channel_count(), 5);
+        assert_eq!(mixer.channel(ChannelId(0)).unwrap().name, "Mic (USB)");
+
+        let mut mixer = setup_mixer();
+        let replaced = mixer.add_or_replace_channel(ChannelConfig::input(0, "Voice"));
+        assert_eq!(replaced.unwrap().name, "Mic");
+        assert!(mixer.has_route(ChannelId(0), ChannelId(3)));
+    }
+
+    #[test]
+    fn fader_move_cancels_bus_fade() {
+        let mut mixer = setup_mixer();
+        assert!(!mixer.fade_bus_volume(ChannelId(0), VolumeDecibels::SILENCE, 500.0));
+
+        assert!(mixer.fade_bus_volume(ChannelId(4), VolumeDecibels::SILENCE, 500.0));
+        assert_eq!(mixer.channel(ChannelId(4)).unwrap().volume, 0.0);
+        let first = mixer.bus_fade(ChannelId(4)).unwrap();
+        assert_eq!(first.duration_ms, 500.0);
+
+        // Même cible, nouveau fondu : nouvel id
+        mixer.fade_bus_volume(ChannelId(4), VolumeDecibels::SILENCE, 500.0);
+        assert_ne!(mixer.bus_fade(ChannelId(4)).unwrap().id, first.id);
+
+        mixer.set_volume(ChannelId(4), 0.5);
+        assert_eq!(mixer.bus_fade(ChannelId(4)), None);
+    }
+
+    #[test]
+    fn bus_solo_is_transient_and_output_only() {
+        let mut mixer = setup_mixer();
+        // Entrée → refusé
+        assert_eq!(mixer.toggle_bus_solo(ChannelId(0)), None);
+        assert_eq!(
+            mixer.set_bus_solo(ChannelId(0), true),
+            Err(MixerError::BusNotFound(0))
+        );
+        assert_eq!(
+            mixer.set_bus_solo(ChannelId(9), true),
+            Err(MixerError::ChannelNotFound(9))
+        );
+
+        assert_eq!(mixer.toggle_bus_solo(ChannelId(4)), Some(true));
+        assert!(!mixer.bus_dispatches(ChannelId(3)));
+        assert!(mixer.bus_dispatches(ChannelId(4)));
+        // Le solo de bus ne coupe pas les entrées (meters, enregistrement)
+        assert!(mixer.effective_gain(ChannelId(0)).0 > 0.0);
+        // Jamais sauvé
+        assert!(mixer.to_config().channels.iter().all(|c| !c.solo));
+
+        assert_eq!(mixer.toggle_bus_solo(ChannelId(4)), Some(false));
+        assert!(mixer.bus_dispatches(ChannelId(3)));
+    }
+
+    #[test]
+    fn effects_diff_monitor_needs_an_input_with_a_monitor_bus() {
+        let mut mixer = setup_mixer();
+        assert!(matches!(
+            mixer.set_effects_diff_monitor(ChannelId(0), true),
+            Err(MixerError::InvalidParameter(m)) if m.contains("no monitor bus")
+        ));
+        assert!(mixer.set_effects_diff_monitor(ChannelId(3), true).is_err());
+        assert_eq!(
+            mixer.set_effects_diff_monitor(ChannelId(9), true),
+            Err(MixerError::ChannelNotFound(9))
+        );
+
+        assert!(mixer.set_monitor(ChannelId(0), Some(ChannelId(3)), 0.0));
+        mixer.set_effects_diff_monitor(ChannelId(0), true).unwrap();
+        assert!(mixer.effects_diff_monitor(ChannelId(0)));
+        assert!(MixerRuntimeState::from_mixer(&mixer).effects_diff_monitor(ChannelId(0)));
+        // Jamais sauvé : un mixer rechargé ne l'a plus
+        assert!(!Mixer::from_config(mixer.to_config()).effects_diff_monitor(ChannelId(0)));
+        assert_eq!(mixer.clear_effects_diff_monitor(), [ChannelId(0)]);
+        assert!(!mixer.effects_diff_monitor(ChannelId(0)));
+    }
+
+    #[test]
+    fn emergency_passthrough_is_transient_and_keeps_the_fallback_bus_playing() {
+        let mut mixer = setup_mixer();
+        assert_eq!(mixer.default_passthrough_bus(), Some(ChannelId(3)));
+        assert_eq!(
+            mixer.set_emergency_passthrough(Some(ChannelId(0))),
+            Err(MixerError::BusNotFound(0))
+        );
+
+        mixer.set_bus_solo(ChannelId(4), true).unwrap();
+        let before = mixer.to_config().channels;
+        mixer.set_emergency_passthrough(Some(ChannelId(3))).unwrap();
+        assert!(mixer.bus_dispatches(ChannelId(3)));
+        assert_eq!(mixer.to_config().channels, before);
+
+        // Le bus supprimé emporte l'override
+        mixer.remove_channel(ChannelId(3)).unwrap();
+        assert_eq!(mixer.emergency_passthrough(), None);
+    }
+
+    #[test]
+    fn channel_limit_refuses_additions_and_trims_loaded_configs() {
+        let mut mixer = setup_mixer();
+        mixer.set_max_channels(6);
+        mixer
+            .add_channel(ChannelConfig::input(10, "Guitar"))
+            .unwrap();
+        assert_eq!(
+            mixer.add_channel(ChannelConfig::input(11, "Synth")),
+            Err(MixerError::TooManyChannels(6))
+        );
+
+        let mut config = MixerConfig::default_setup();
+        config.channels.push(ChannelConfig::input(7, "Guitar"));
+        config.routes.push(Route::new(ChannelId(7), ChannelId(3)));
+        let (mixer, skipped) = Mixer::from_config_limited(config, 4);
+        assert_eq!(skipped, [ChannelId(4), ChannelId(7)]);
+        assert_eq!(mixer.channel_count(), 4);
+        assert!(mixer.routes().iter().all(|r| r.from != ChannelId(7)));
+        assert_eq!(mixer.max_channels(), 4);
+    }
+
+    #[test]
+    fn bus_channel_count_is_output_only_and_saved() {
+        let mut mixer = setup_mixer();
+        assert_eq!(
+            mixer.set_bus_channel_count(ChannelId(0), ChannelCount::Mono),
+            Err(MixerError::BusNotFound(0))
+        );
+        assert_eq!(
+            mixer.set_bus_channel_count(ChannelId(9), ChannelCount::Mono),
+            Err(MixerError::ChannelNotFound(9))
+        );
+
+        mixer
+            .set_bus_channel_count(ChannelId(4), ChannelCount::Mono)
+            .unwrap();
+        let config = mixer.to_config();
+        let bus = config.channels.iter().find(|c| c.id == ChannelId(4));
+        assert_eq!(bus.unwrap().channel_count, ChannelCount::Mono);
+    }
+
+    #[test]
+    fn bus_output_mapping_refuses_overlaps_on_the_same_device() {
+        let mut mixer = setup_mixer();
+        assert_eq!(
+            mixer.set_bus_output_mapping(ChannelId(0), Some(0)),
+            Err(MixerError::BusNotFound(0))
+        );
+        // Headphones sur les sorties 3-4 du device par défaut
+        mixer.set_bus_output_mapping(ChannelId(3), Some(2)).unwrap();
+        // Même device : sorties 4-5 chevauchent 3-4, 1-2 non
+        assert!(mixer.set_bus_output_mapping(ChannelId(4), Some(3)).is_err());
+        mixer
+            .set_bus_output_mapping(ChannelId(4), Some(1))
+            .unwrap_err();
+        // En mono, Speakers tient sur la sortie 2
+        mixer
+            .set_bus_channel_count(ChannelId(4), ChannelCount::Mono)
+            .unwrap();
+        mixer.set_bus_output_mapping(ChannelId(4), Some(1)).unwrap();
+        // ... et ne peut plus repasser en stéréo à cette place
+        assert!(
+            mixer
+                .set_bus_channel_count(ChannelId(4), ChannelCount::Stereo)
+                .is_err()
+        );
+
+        let config = mixer.to_config();
+        let bus = config.channels.iter().find(|c| c.id == ChannelId(3));
+        assert_eq!(bus.unwrap().output_channel_offset, Some(2));
+        mixer.set_bus_output_mapping(ChannelId(3), None).unwrap();
+        assert_eq!(
+            mixer.channel(ChannelId(3)).unwrap().output_channel_offset,
+            None
+        );
+    }
+
+    #[test]
+    fn volume_fader_roundtrip() {
+        let mut mixer = setup_mixer();
+        mixer.set_volume_fader(ChannelId(0), 0.75, FaderTaper::Audio);
+        // 0 dB aux 3/4 de la course
+        assert!((mixer.channel(ChannelId(0)).unwrap().volume - 1.0).abs() < 1e-5);
+        let pos = mixer.volume_fader(ChannelId(0), FaderTaper::Audio).unwrap();
+        assert!((pos - 0.75).abs() < 1e-4);
+
+        mixer.set_volume_fader(ChannelId(0), 0.0, FaderTaper::Audio);
+        assert_eq!(mixer.channel(ChannelId(0)).unwrap().volume, 0.0);
+        assert_eq!(mixer.effective_gain(ChannelId(0)), (0.0, 0.0));
+        // Un preset à -60 dB passe encore du signal
+        mixer.set_volume(ChannelId(0), VolumeDecibels(-60.0).to_linear());
+        let (left, right) = mixer.effective_gain(ChannelId(0));
+        assert!(left > 0.0 && right > 0.0);
+        assert!(
+            mixer
+                .volume_fader(ChannelId(99), FaderTaper::Audio)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn crossfade_centre_attenuates_both_sides_by_3_db() {
+        let mut mixer = setup_mixer();
+        mixer
+            .create_crossfade(1, ChannelId(1), ChannelId(2), CrossfadeCurve::ConstantPower)
+            .unwrap();
+        for id in [ChannelId(1), ChannelId(2)] {
+            let db = VolumeDecibels::from_linear(mixer.crossfade_gain(id)).0;
+            assert!((db + 3.01).abs() < 0.05, "{db} dB");
+        }
+        // Le fader garde son volume, seul le gain effectif baisse
+        assert_eq!(mixer.channel(ChannelId(1)).unwrap().volume, 1.0);
+        let (l, _) = mixer.effective_gain(ChannelId(1));
+        assert!((l - 0.5).abs() < 1e-4);
+
+        mixer.set_crossfade_position(1, 0.0).unwrap();
+        assert_eq!(mixer.crossfade_gain(ChannelId(1)), 1.0);
+        assert!(mixer.crossfade_gain(ChannelId(2)).abs() < 1e-6);
+        assert_eq!(mixer.crossfade_gain(ChannelId(0)), 1.0);
+
+        // Persisté dans la config, position comprise
+        let restored = Mixer::from_config(mixer.to_config());
+        assert_eq!(restored.crossfades(), mixer.crossfades());
+    }
+
+    #[test]
+    fn crossfade_is_validated_and_dies_with_its_channels() {
+        let mut mixer = setup_mixer();
+        let curve = CrossfadeCurve::Linear;
+        assert!(
+            mixer
+                .create_crossfade(1, ChannelId(1), ChannelId(1), curve)
+                .is_err()
+        );
+        assert!(
+            mixer
+                .create_crossfade(1, ChannelId(1), ChannelId(3), curve)
+                .is_err()
+        );
+        assert_eq!(
+            mixer.create_crossfade(1, ChannelId(1), ChannelId(9), curve),
+            Err(MixerError::ChannelNotFound(9))
+        );
+        mixer
+            .create_crossfade(1, ChannelId(1), ChannelId(2), curve)
+            .unwrap();
+        assert!(
+            mixer
+                .create_crossfade(1, ChannelId(0), ChannelId(2), curve)
+                .is_err()
+        );
+
+        mixer.remove_channel(ChannelId(2)).unwrap();
+        assert!(mixer.crossfades().is_empty());
+        assert_eq!(mixer.crossfade_gain(ChannelId(1)), 1.0);
+        assert!(mixer.set_crossfade_position(1, 0.3).is_err());
+    }
+
+    #[test]
+    fn levels_below_the_meter_floor_fall_to_zero() {
+        let mut mixer = setup_mixer();
+        mixer.set_meter_scale(MeterScale::new(Some(-40.0), None));
+        // -46 dB : sous le plancher dès la mesure
+        mixer.update_levels(ChannelId(0), &[0.005; 64]);
+        let level = |mixer: &Mixer| {
+            mixer
+                .get_levels()
+                .into_iter()
+                .find(|l| l.channel == ChannelId(0))
+                .unwrap()
+        };
+        assert_eq!(level(&mixer).peak, 0.0);
+
+        mixer.update_levels(ChannelId(0), &[0.5; 64]);
+        let loud = level(&mixer);
+        assert!(loud.peak > 0.0);
+        assert_eq!(loud.peak_position, mixer.meter_scale().position(loud.peak));
+        // La retombée s'arrête net au plancher
+        for _ in 0..200 {
+            mixer.update_levels(ChannelId(0), &[0.0; 64]);
+        }
+        assert_eq!(level(&mixer).peak, 0.0);
+        assert_eq!(level(&mixer).rms_position, 0.0);
+    }
+
+    #[test]
+    fn linked_channels_follow_the_left_side() {
+        let mut mixer = setup_mixer();
+        mixer.set_volume(ChannelId(1), 0.5);
+        mixer.link_channels(ChannelId(1), ChannelId(2)).unwrap();
+        // La droite prend tout de suite les réglages de la gauche
+        assert_eq!(mixer.channel(ChannelId(2)).unwrap().volume, 0.5);
+
+        mixer.set_volume(ChannelId(1), 0.8);
+        mixer.set_mute(ChannelId(2), true);
+        mixer.set_solo(ChannelId(1), true);
+        for id in [ChannelId(1), ChannelId(2)] {
+            let ch = mixer.channel(id).unwrap();
+            assert_eq!((ch.volume, ch.muted, ch.solo), (0.8, true, true));
+        }
+        // Un canal hors du lien n'est pas touché
+        assert_eq!(mixer.channel(ChannelId(0)).unwrap().volume, 1.0);
+
+        let restored = Mixer::from_config(mixer.to_config());
+        assert_eq!(restored.link_partner(ChannelId(2)), Some(ChannelId(1)));
+
+        assert_eq!(
+            mixer.unlink_channels(ChannelId(2)),
+            Ok(ChannelLink::new(ChannelId(1), ChannelId(2)))
+        );
+        mixer.set_volume(ChannelId(1), 1.0);
+        assert_eq!(mixer.channel(ChannelId(2)).unwrap().volume, 0.8);
+    }
+
+    #[test]
+    fn a_channel_belongs_to_one_link_at_most() {
+        let mut mixer = setup_mixer();
+        assert!(mixer.link_channels(ChannelId(1), ChannelId(1)).is_err());
+        // Un bus ne se lie pas
+        assert!(mixer.link_channels(ChannelId(1), ChannelId(3)).is_err());
+        assert_eq!(
+            mixer.link_channels(ChannelId(1), ChannelId(9)),
+            Err(MixerError::ChannelNotFound(9))
+        );
+        mixer.link_channels(ChannelId(1), ChannelId(2)).unwrap();
+        assert!(mixer.link_channels(ChannelId(0), ChannelId(2)).is_err());
+        assert!(mixer.link_channels(ChannelId(1), ChannelId(0)).is_err());
+        assert!(mixer.unlink_channels(ChannelId(0)).is_err());
+
+        mixer.remove_channel(ChannelId(2)).unwrap();
+        assert!(mixer.links().is_empty());
+        assert_eq!(mixer.link_partner(ChannelId(1)), None);
+    }
+
+    /// Casque (3) qui suit le Stream (5) à +3 dB.
+    fn follow_mixer(on_override: FollowOverride) -> Mixer {
+        let mut mixer = setup_mixer();
+        mixer
+            .add_channel(ChannelConfig::output(5, "Stream"))
+            .unwrap();
+        mixer
+            .follow_bus(BusFollow {
+                follower: ChannelId(3),
+                leader: ChannelId(5),
+                offset_db: 3.0,
+                on_override,
+            })
+            .unwrap();
+        mixer
+    }
+
+    fn volume_db(mixer: &Mixer, id: usize) -> f32 {
+        VolumeDecibels::from_linear(mixer.channel(ChannelId(id)).unwrap().volume).0
+    }
+
+    #[test]
+    fn follower_bus_keeps_its_offset_and_clamps() {
+        let mut mixer = follow_mixer(FollowOverride::Unlink);
+        mixer.set_volume(ChannelId(5), VolumeDecibels(-6.0).to_linear());
+        assert!((volume_db(&mixer, 3) - -3.0).abs() < 1e-3);
+        mixer.set_volume(ChannelId(5), VolumeDecibels(-3.0).to_linear());
+        assert!((volume_db(&mixer, 3) - 0.0).abs() < 1e-3);
+
+        // Le suiveur bute sur le maximum du fader (+6 dB)
+        mixer.set_volume(ChannelId(5), VolumeDecibels(5.0).to_linear());
+        assert_eq!(mixer.channel(ChannelId(3)).unwrap().volume, 2.0);
+        mixer.set_volume(ChannelId(5), 0.0);
+        assert_eq!(mixer.channel(ChannelId(3)).unwrap().volume, 0.0);
+
+        // Un fondu du leader est repris par le suiveur
+        mixer.fade_bus_volume(ChannelId(5), VolumeDecibels(-12.0), 200.0);
+        assert!((volume_db(&mixer, 3) - -9.0).abs() < 1e-3);
+        assert_eq!(mixer.bus_fade(ChannelId(3)).unwrap().duration_ms, 200.0);
+
+        let restored = Mixer::from_config(mixer.to_config());
+        assert_eq!(restored.bus_follows(), mixer.bus_follows());
+    }
+
+    #[test]
+    fn touching_the_follower_unlinks_or_adjusts_the_offset() {
+        let mut mixer = follow_mixer(FollowOverride::Unlink);
+        mixer.set_volume(ChannelId(3), 0.5);
+        assert!(mixer.bus_follow(ChannelId(3)).is_none());
+        mixer.set_volume(ChannelId(5), 0.1);
+        assert_eq!(mixer.channel(ChannelId(3)).unwrap().volume, 0.5);
+
+        let mut mixer = follow_mixer(FollowOverride::AdjustOffset);
+        mixer.set_volume(ChannelId(5), VolumeDecibels(-10.0).to_linear());
+        mixer.set_volume(ChannelId(3), VolumeDecibels(-4.0).to_linear());
+        let offset = mixer.bus_follow(ChannelId(3)).unwrap().offset_db;
+        assert!((offset - 6.0).abs() < 1e-3);
+        mixer.set_volume(ChannelId(5), VolumeDecibels(-20.0).to_linear());
+        assert!((volume_db(&mixer, 3) - -14.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bus_follow_chains_cannot_loop() {
+        let mut mixer = follow_mixer(FollowOverride::Unlink);
+        // Speakers (4) suit le casque, qui suit le Stream
+        let follow = |follower, leader| BusFollow {
+            follower: ChannelId(follower),
+            leader: ChannelId(leader),
+            offset_db: -2.0,
+            on_override: FollowOverride::Unlink,
+        };
+        mixer.follow_bus(follow(4, 3)).unwrap();
+        mixer.set_volume(ChannelId(5), VolumeDecibels(-6.0).to_linear());
+        assert!((volume_db(&mixer, 4) - -5.0).abs() < 1e-3);
+        assert_eq!(
+            mixer.bus_followers(ChannelId(5)),
+            [ChannelId(3), ChannelId(4)]
+        );
+
+        for (follower, leader) in [(5, 4), (5, 3), (3, 3)] {
+            assert!(mixer.follow_bus(follow(follower, leader)).is_err());
+        }
+        // Une entrée ne suit ni n'est suivie
+        assert!(mixer.follow_bus(follow(0, 3)).is_err());
+        assert!(mixer.follow_bus(follow(4, 0)).is_err());
+
+        mixer.remove_channel(ChannelId(3)).unwrap();
+        assert!(mixer.bus_follows().is_empty());
+    }
+
+    #[test]
+    fn follower_stereo_partner_moves_with_it() {
+        let mut mixer = follow_mixer(FollowOverride::Unlink);
+        // Speakers (4) forme une paire stéréo avec le casque (3) ; posée
+        // à la main, `link_channels` ne liant que des entrées
+        mixer.links.push(ChannelLink {
+            left: ChannelId(3),
+            right: ChannelId(4),
+        });
+        mixer.set_volume(ChannelId(5), VolumeDecibels(-6.0).to_linear());
+        assert!((volume_db(&mixer, 3) - -3.0).abs() < 1e-3);
+        assert!((volume_db(&mixer, 4) - -3.0).abs() < 1e-3);
+
+        mixer.fade_bus_volume(ChannelId(5), VolumeDecibels(-12.0), 200.0);
+        assert!((volume_db(&mixer, 4) - -9.0).abs() < 1e-3);
+        assert_eq!(mixer.bus_fade(ChannelId(4)).unwrap().duration_ms, 200.0);
+        assert_eq!(
+            mixer.bus_followers(ChannelId(5)),
+            [ChannelId(3), ChannelId(4)]
+        );
+
+        // Le leader ne peut pas suivre le partenaire de son suiveur
+        let follow = BusFollow {
+            follower: ChannelId(5),
+            leader: ChannelId(4),
+            offset_db: 0.0,
+            on_override: FollowOverride::Unlink,
+        };
+        assert!(mixer.follow_bus(follow).is_err());
+        assert!(mixer.unfollow_bus(ChannelId(4)).is_err());
+    }
+
     #[test]
     fn mixer_from_config() {
         let mixer = setup_mixer();
@@ -461,13 +2238,180 @@ mod tests {
         assert!(!mixer.has_route(ChannelId(0), ChannelId(3)));
     }
 
+    #[test]
+    fn sum_priority_lists_existing_sources_once() {
+        let mut mixer = setup_mixer();
+        let headphones = ChannelId(3);
+        let priority = [ChannelId(2), ChannelId(0), ChannelId(2)];
+        mixer.set_sum_priority(headphones, &priority).unwrap();
+        let bus = mixer.channel(headphones).unwrap();
+        assert_eq!(bus.sum_priority, [ChannelId(2), ChannelId(0)]);
+        assert_eq!(
+            bus.sum_order(&[ChannelId(0), ChannelId(1), ChannelId(2)]),
+            [2, 0, 1].map(ChannelId)
+        );
+
+        assert_eq!(
+            mixer.set_sum_priority(ChannelId(0), &[]),
+            Err(MixerError::BusNotFound(0))
+        );
+        assert_eq!(
+            mixer.set_sum_priority(headphones, &[ChannelId(9)]),
+            Err(MixerError::ChannelNotFound(9))
+        );
+        assert!(mixer.set_sum_priority(headphones, &[ChannelId(4)]).is_err());
+        // Refusé : rien n'a changé ; un canal supprimé quitte la liste
+        mixer.remove_channel(ChannelId(2)).unwrap();
+        assert_eq!(
+            mixer.channel(headphones).unwrap().sum_priority,
+            [ChannelId(0)]
+        );
+    }
+
     #[test]
     fn remove_channel_removes_routes() {
         let mut mixer = setup_mixer();
         assert!(mixer.has_route(ChannelId(0), ChannelId(3)));
-        mixer.remove_channel(ChannelId(0));
+        let (removed, _) = mixer.remove_channel(ChannelId(0)).unwrap();
+        assert_eq!(removed.name, "Mic");
         assert!(!mixer.has_route(ChannelId(0), ChannelId(3)));
         assert!(mixer.channel(ChannelId(0)).is_none());
+
+        assert_eq!(
+            mixer.remove_channel(ChannelId(0)),
+            Err(MixerError::ChannelNotFound(0))
+        );
+    }
+
+    #[test]
+    fn loaded_output_delay_stays_in_bounds() {
+        let mut config = MixerConfig::default_setup();
+        config.channels[3].output_delay_ms = 5_000.0;
+        config.channels[4].output_delay_ms = f32::NAN;
+        let (headphones, speakers) = (config.channels[3].id, config.channels[4].id);
+        let mixer = Mixer::from_config(config);
+        let delay = |id| mixer.channel(id).unwrap().output_delay_ms;
+        assert_eq!(delay(headphones), MAX_OUTPUT_DELAY_MS);
+        assert_eq!(delay(speakers), 0.0);
+    }
+
+    #[test]
+    fn channel_dependencies_list_everything_removal_cleans_up() {
+        let mut mixer = setup_mixer();
+        let headphones = ChannelId(3);
+        assert!(mixer.set_monitor(ChannelId(0), Some(headphones), -6.0));
+        mixer
+            .follow_bus(BusFollow {
+                follower: ChannelId(4),
+                leader: headphones,
+                offset_db: 0.0,
+                on_override: FollowOverride::default(),
+            })
+            .unwrap();
+        mixer.set_bus_solo(headphones, true).unwrap();
+        mixer.set_emergency_passthrough(Some(headphones)).unwrap();
+
+        let report = mixer.channel_dependencies(headphones).unwrap();
+        assert_eq!(report.routes.len(), 3);
+        assert_eq!(report.monitored_by, vec![ChannelId(0)]);
+        assert_eq!(report.follows.len(), 1);
+        assert!(report.bus_solo && report.emergency_passthrough);
+
+        let (removed, cleaned) = mixer.remove_channel(headphones).unwrap();
+        assert_eq!(removed.name, "Headphones");
+        assert_eq!(cleaned, report);
+        assert!(mixer.routes().iter().all(|r| r.to != headphones));
+        assert_eq!(mixer.channel(ChannelId(0)).unwrap().monitor_bus, None);
+        assert!(mixer.bus_follows().is_empty());
+        assert_eq!(mixer.emergency_passthrough(), None);
+
+        // Côté entrée : crossfader et paire stéréo
+        mixer
+            .create_crossfade(1, ChannelId(0), ChannelId(1), CrossfadeCurve::Linear)
+            .unwrap();
+        mixer.link_channels(ChannelId(0), ChannelId(2)).unwrap();
+        let report = mixer.channel_dependencies(ChannelId(0)).unwrap();
+        assert_eq!(report.crossfades.len(), 1);
+        assert_eq!(report.links.len(), 1);
+        assert!(report.routes.is_empty() && !report.bus_solo);
+        let (_, cleaned) = mixer.remove_channel(ChannelId(0)).unwrap();
+        assert_eq!(cleaned, report);
+        assert!(mixer.crossfades().is_empty() && mixer.links().is_empty());
+
+        // Un canal isolé n'entraîne rien
+        assert!(mixer.channel_dependencies(ChannelId(1)).unwrap().is_empty());
+        assert_eq!(
+            mixer.channel_dependencies(ChannelId(0)),
+            Err(MixerError::ChannelNotFound(0))
+        );
+    }
+
+    #[test]
+    fn submix_routes_carry_gain_and_refuse_loops() {
+        let mut mixer = setup_mixer();
+        mixer
+            .add_channel(ChannelConfig::submix(5, "Voices"))
+            .unwrap();
+        mixer
+            .add_channel(ChannelConfig::submix(6, "Stream"))
+            .unwrap();
+        mixer.remove_route(ChannelId(0), ChannelId(3));
+        assert!(mixer.add_route(ChannelId(0), ChannelId(5)));
+        assert!(mixer.add_route(ChannelId(5), ChannelId(6)));
+        assert!(mixer.add_route(ChannelId(6), ChannelId(3)));
+        // Boucle : Stream → Voices → Stream
+        assert!(!mixer.add_route(ChannelId(6), ChannelId(5)));
+        assert!(!mixer.add_route(ChannelId(5), ChannelId(5)));
+        mixer.set_volume(ChannelId(5), 0.5);
+        mixer.set_pan(ChannelId(6), -1.0);
+
+        let state = MixerRuntimeState::from_mixer(&mixer);
+        let gain = |id| mixer.effective_gain(ChannelId(id));
+        let ((mic_l, mic_r), (voices_l, voices_r)) = (gain(0), gain(5));
+        let (stream_l, stream_r) = gain(6);
+        let (l, r) = state.routed_gain(ChannelId(0), ChannelId(3));
+        assert!((l - mic_l * voices_l * stream_l).abs() < 1e-6);
+        assert!((r - mic_r * voices_r * stream_r).abs() < 1e-6);
+        assert!(r.abs() < 1e-6, "Stream panned hard left");
+
+        // Le solo d'une entrée ne coupe pas le sous-mix qui la porte
+        mixer.set_solo(ChannelId(0), true);
+        assert_ne!(mixer.effective_gain(ChannelId(5)), (0.0, 0.0));
+        mixer.set_mute(ChannelId(5), true);
+        let state = MixerRuntimeState::from_mixer(&mixer);
+        assert_eq!(state.routed_gain(ChannelId(0), ChannelId(3)), (0.0, 0.0));
+        assert_eq!(mixer.channel_count_summary().submixes, 2);
+    }
+
+    #[test]
+    fn replacing_routes_is_all_or_nothing() {
+        let mut mixer = setup_mixer();
+        mixer
+            .add_channel(ChannelConfig::submix(5, "Voices"))
+            .unwrap();
+        let before = mixer.routes().to_vec();
+
+        let err = mixer
+            .replace_routes(&[
+                Route::new(ChannelId(0), ChannelId(9)),
+                Route::new(ChannelId(7), ChannelId(3)),
+            ])
+            .unwrap_err();
+        assert!(err.to_string().contains("deleted channels: 7, 9"), "{err}");
+        let looping = [
+            Route::new(ChannelId(0), ChannelId(5)),
+            Route::new(ChannelId(5), ChannelId(5)),
+        ];
+        assert!(mixer.replace_routes(&looping).is_err());
+        assert_eq!(mixer.routes(), before);
+
+        let routes = [
+            Route::new(ChannelId(0), ChannelId(5)),
+            Route::new(ChannelId(5), ChannelId(4)),
+            Route::new(ChannelId(0), ChannelId(5)),
+        ];
+        mixer.replace_routes(&routes).unwrap();
+        assert_eq!(mixer.routes(), &routes[..2]);
     }
 
     #[test]
@@ -543,4 +2487,58 @@ mod tests {
         assert_eq!(l, 0.0);
         assert_eq!(r, 0.0);
     }
+
+    #[test]
+    fn signal_graph_json_structure() {
+        // Setup connu : 3 entrées, 2 sorties, 3 routes vers Headphones
+        let mut mixer = setup_mixer();
+        mixer.set_mute(ChannelId(2), true);
+        mixer.channel_mut(ChannelId(0)).unwrap().device_name =
+            DeviceAssignment::Specific("Yeti".into());
+
+        let json = mixer.export_signal_graph().to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let nodes = value["nodes"].as_array().unwrap();
+        let edges = value["edges"].as_array().unwrap();
+        // 5 canaux + 1 device
+        assert_eq!(nodes.len(), 6);
+        // 3 routes + device → Mic
+        assert_eq!(edges.len(), 4);
+
+        assert_eq!(nodes[0]["id"], "ch0");
+        assert_eq!(nodes[0]["kind"], "input");
+        assert_eq!(nodes[0]["label"], "Mic");
+        assert_eq!(nodes[0]["volume"], 1.0);
+        assert!(
+            nodes.iter().any(|n| n["id"] == "in:Yeti"
+                && n["kind"] == "input_device"
+                && n["active"] == false)
+        );
+        assert_eq!(nodes.iter().filter(|n| n["kind"] == "output").count(), 2);
+
+        // Browser est muted → sa route est désactivée
+        let browser = edges
+            .iter()
+            .find(|e| e["from"] == "ch2" && e["to"] == "ch3")
+            .unwrap();
+        assert_eq!(browser["enabled"], false);
+        let mic = edges
+            .iter()
+            .find(|e| e["from"] == "ch0" && e["to"] == "ch3")
+            .unwrap();
+        assert_eq!(mic["enabled"], true);
+        assert_eq!(mic["gain"], 1.0);
+    }
+
+    #[test]
+    fn set_delay_clamps() {
+        let mut mixer = setup_mixer();
+        mixer.set_delay(ChannelId(0), 120.0);
+        assert_eq!(mixer.channel(ChannelId(0)).unwrap().delay_ms, 120.0);
+        mixer.set_delay(ChannelId(0), 5000.0);
+        assert_eq!(mixer.channel(ChannelId(0)).unwrap().delay_ms, 1000.0);
+        mixer.set_delay(ChannelId(0), -3.0);
+        assert_eq!(mixer.channel(ChannelId(0)).unwrap().delay_ms, 0.0);
+    }
 }
@@ -0,0 +1,761 @@
+//! Audio d'une instance de Troubadour à une autre : PCM brut sur UDP.
+//!
+//! Pour un réseau local (un second PC qui encode le stream), pas pour
+//! Internet : ni chiffrement, ni correction d'erreur. Un paquet perdu
+//! devient 128 frames de silence, un paquet en retard est jeté.
+//!
+//! ```text
+//!  bus joué ──► NetworkSink ──UDP──► NetworkSource ──► canal d'entrée
+//!               paquets de            thread de réception
+//!               128 frames            → tampon anti-gigue
+//!                                     → lu par le callback
+//! ```
+//!
+//! # Format d'un paquet
+//! En-tête de 16 octets, little-endian, puis les samples entrelacés :
+//!
+//! | octets | contenu                                  |
+//! |--------|------------------------------------------|
+//! | 0..4   | `TRBD`                                   |
+//! | 4      | version (1)                              |
+//! | 5      | format : 0 = f32, 1 = i16                |
+//! | 6      | canaux (1 ou 2)                          |
+//! | 7      | réservé (0)                              |
+//! | 8..12  | numéro de séquence (u32, repart à 0)     |
+//! | 12..16 | sample rate de l'émetteur (u32)          |
+//!
+//! Taille fixe : 128 frames stéréo = 1 040 octets en f32, sous la MTU
+//! d'Ethernet (pas de fragmentation IP).
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::network::NetworkStats;
+
+/// Début de chaque paquet : un autre programme qui parle sur le même
+/// port est ignoré au lieu d'être joué.
+pub const PACKET_MAGIC: [u8; 4] = *b"TRBD";
+
+/// Version du format ; un paquet d'une autre version est ignoré.
+pub const PACKET_VERSION: u8 = 1;
+
+/// Frames par paquet (2.7 ms à 48 kHz).
+pub const PACKET_FRAMES: usize = 128;
+
+const HEADER_LEN: usize = 16;
+
+/// Samples stéréo d'un paquet décodé.
+const PACKET_SAMPLES: usize = PACKET_FRAMES * 2;
+
+/// Plus grand paquet valide (f32 stéréo).
+const MAX_PACKET_LEN: usize = HEADER_LEN + PACKET_SAMPLES * 4;
+
+/// Borne du tampon anti-gigue, en paquets (~170 ms à 48 kHz).
+pub const MAX_JITTER_PACKETS: usize = 64;
+
+/// Attente maximale du thread de réception entre deux vérifications
+/// de l'arrêt.
+const RECEIVE_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Encodage des samples dans un paquet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PacketFormat {
+    F32,
+    /// Deux fois moins de débit, bruit de quantification à -96 dB
+    I16,
+}
+
+impl PacketFormat {
+    fn code(self) -> u8 {
+        match self {
+            Self::F32 => 0,
+            Self::I16 => 1,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::F32),
+            1 => Some(Self::I16),
+            _ => None,
+        }
+    }
+
+    fn sample_len(self) -> usize {
+        match self {
+            Self::F32 => 4,
+            Self::I16 => 2,
+        }
+    }
+}
+
+/// En-tête lu d'un paquet valide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PacketHeader {
+    pub sequence: u32,
+    pub sample_rate: u32,
+}
+
+/// Écrit un paquet de `PACKET_FRAMES` frames stéréo dans `packet`
+/// (vidé d'abord ; pas d'allocation si sa capacité suffit).
+pub fn encode_packet(
+    packet: &mut Vec<u8>,
+    header: PacketHeader,
+    format: PacketFormat,
+    stereo: &[f32],
+) {
+    packet.clear();
+    packet.extend_from_slice(&PACKET_MAGIC);
+    packet.extend_from_slice(&[PACKET_VERSION, format.code(), 2, 0]);
+    packet.extend_from_slice(&header.sequence.to_le_bytes());
+    packet.extend_from_slice(&header.sample_rate.to_le_bytes());
+    for &sample in &stereo[..PACKET_SAMPLES] {
+        match format {
+            PacketFormat::F32 => packet.extend_from_slice(&sample.to_le_bytes()),
+            PacketFormat::I16 => {
+                let quantized = (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+                packet.extend_from_slice(&quantized.to_le_bytes());
+            }
+        }
+    }
+}
+
+/// Lit un paquet dans `stereo` (`PACKET_FRAMES` frames ; un flux mono
+/// est joué des deux côtés). `None` si ce n'est pas un paquet valide.
+pub fn decode_packet(packet: &[u8], stereo: &mut [f32]) -> Option<PacketHeader> {
+    let header = packet.get(..HEADER_LEN)?;
+    if header[..4] != PACKET_MAGIC || header[4] != PACKET_VERSION {
+        return None;
+    }
+    let format = PacketFormat::from_code(header[5])?;
+    let channels = header[6] as usize;
+    if !(1..=2).contains(&channels)
+        || packet.len() != HEADER_LEN + PACKET_FRAMES * channels * format.sample_len()
+    {
+        return None;
+    }
+    let word = |at: usize| {
+        u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+
+    let payload = &packet[HEADER_LEN..];
+    let sample = |i: usize| match format {
+        PacketFormat::F32 => f32::from_le_bytes([
+            payload[i * 4],
+            payload[i * 4 + 1],
+            payload[i * 4 + 2],
+            payload[i * 4 + 3],
+        ]),
+        PacketFormat::I16 => {
+            i16::from_le_bytes([payload[i * 2], payload[i * 2 + 1]]) as f32 / i16::MAX as f32
+        }
+    };
+    for (frame, out) in stereo[..PACKET_SAMPLES].chunks_exact_mut(2).enumerate() {
+        if channels == 2 {
+            out[0] = sample(frame * 2);
+            out[1] = sample(frame * 2 + 1);
+        } else {
+            out.fill(sample(frame));
+        }
+    }
+    Some(PacketHeader {
+        sequence: word(8),
+        sample_rate: word(12),
+    })
+}
+
+fn network_error(context: &str, e: impl std::fmt::Display) -> TroubadourError {
+    TroubadourError::Network(format!("{context}: {e}"))
+}
+
+/// Envoi d'un bus sur le réseau, appelé par le callback audio.
+///
+/// Le socket est non bloquant : un buffer d'envoi plein fait perdre
+/// un paquet (compté) au lieu de faire attendre le callback.
+pub struct NetworkSink {
+    socket: UdpSocket,
+    target: SocketAddr,
+    bus: ChannelId,
+    format: PacketFormat,
+    /// Frames en attente d'un paquet complet
+    staging: Vec<f32>,
+    packet: Vec<u8>,
+    sequence: u32,
+    counters: Arc<NetworkCounters>,
+}
+
+impl NetworkSink {
+    /// Prépare l'envoi de `bus` vers `host:port`, compté dans `counters`.
+    pub fn connect(
+        host: &str,
+        port: u16,
+        bus: ChannelId,
+        format: PacketFormat,
+        counters: Arc<NetworkCounters>,
+    ) -> TroubadourResult<Self> {
+        let target = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| network_error(host, e))?
+            .next()
+            .ok_or_else(|| TroubadourError::Network(format!("{host}: no address")))?;
+        let local: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local).map_err(|e| network_error("bind", e))?;
+        socket
+            .set_nonblocking(true)
+            .map_err(|e| network_error("bind", e))?;
+        Ok(Self {
+            socket,
+            target,
+            bus,
+            format,
+            staging: Vec::with_capacity(PACKET_SAMPLES),
+            packet: Vec::with_capacity(MAX_PACKET_LEN),
+            sequence: 0,
+            counters,
+        })
+    }
+
+    pub fn bus(&self) -> ChannelId {
+        self.bus
+    }
+
+    /// Ajoute un bloc stéréo entrelacé ; chaque paquet complet part
+    /// aussitôt.
+    pub fn write(&mut self, stereo: &[f32], sample_rate: u32) {
+        let mut rest = stereo;
+        while !rest.is_empty() {
+            let take = (PACKET_SAMPLES - self.staging.len()).min(rest.len());
+            self.staging.extend_from_slice(&rest[..take]);
+            rest = &rest[take..];
+            if self.staging.len() == PACKET_SAMPLES {
+                let header = PacketHeader {
+                    sequence: self.sequence,
+                    sample_rate,
+                };
+                encode_packet(&mut self.packet, header, self.format, &self.staging);
+                let counter = match self.socket.send_to(&self.packet, self.target) {
+                    Ok(_) => &self.counters.packets_sent,
+                    Err(_) => &self.counters.send_errors,
+                };
+                counter.fetch_add(1, Ordering::Relaxed);
+                self.sequence = self.sequence.wrapping_add(1);
+                self.staging.clear();
+            }
+        }
+    }
+}
+
+/// Un paquet reçu, rangé à sa place dans le tampon.
+struct Slot {
+    sequence: Option<u32>,
+    samples: Vec<f32>,
+}
+
+/// Tampon anti-gigue : remet les paquets dans l'ordre et absorbe les
+/// écarts d'arrivée.
+///
+/// La lecture attend `target` paquets d'avance avant de jouer (et de
+/// nouveau après s'être vidé). Un paquet manquant alors que les
+/// suivants sont là est perdu : il est joué en silence et la lecture
+/// continue, à la même place dans le temps.
+pub struct JitterBuffer {
+    slots: Vec<Slot>,
+    target: usize,
+    /// Prochain paquet à jouer (`None` avant le premier reçu)
+    next: Option<u32>,
+    playing: bool,
+    received: u64,
+    lost: u64,
+    dropped: u64,
+    underruns: u64,
+}
+
+impl JitterBuffer {
+    /// `target` est ramené entre 1 et `MAX_JITTER_PACKETS / 2` : il faut
+    /// de la place derrière pour ranger les paquets en avance.
+    pub fn new(target: usize) -> Self {
+        Self {
+            slots: (0..MAX_JITTER_PACKETS)
+                .map(|_| Slot {
+                    sequence: None,
+                    samples: vec![0.0; PACKET_SAMPLES],
+                })
+                .collect(),
+            target: target.clamp(1, MAX_JITTER_PACKETS / 2),
+            next: None,
+            playing: false,
+            received: 0,
+            lost: 0,
+            dropped: 0,
+            underruns: 0,
+        }
+    }
+
+    /// Paquets en attente.
+    pub fn depth(&self) -> usize {
+        self.slots.iter().filter(|s| s.sequence.is_some()).count()
+    }
+
+    /// Range un paquet décodé.
+    ///
+    /// Un paquet déjà dépassé est jeté. Un saut de plus d'un tampon
+    /// (émetteur redémarré, longue coupure) repart de ce paquet.
+    pub fn push(&mut self, sequence: u32, stereo: &[f32]) {
+        let capacity = self.slots.len() as i64;
+        let next = *self.next.get_or_insert(sequence);
+        let ahead = sequence.wrapping_sub(next) as i32 as i64;
+        if ahead < -capacity || ahead >= capacity {
+            self.restart(sequence);
+        } else if ahead < 0 {
+            self.dropped += 1;
+            return;
+        }
+        let index = sequence as usize % self.slots.len();
+        let slot = &mut self.slots[index];
+        slot.sequence = Some(sequence);
+        slot.samples.copy_from_slice(&stereo[..PACKET_SAMPLES]);
+        self.received += 1;
+    }
+
+    fn restart(&mut self, sequence: u32) {
+        for slot in &mut self.slots {
+            slot.sequence = None;
+        }
+        self.next = Some(sequence);
+        self.playing = false;
+    }
+
+    /// Paquet illisible reçu (autre programme, autre version).
+    pub fn reject(&mut self) {
+        self.dropped += 1;
+    }
+
+    /// Joue le prochain paquet dans `stereo` (`PACKET_FRAMES` frames),
+    /// ou du silence.
+    pub fn pop(&mut self, stereo: &mut [f32]) {
+        let stereo = &mut stereo[..PACKET_SAMPLES];
+        if !self.playing {
+            if self.depth() < self.target {
+                stereo.fill(0.0);
+                return;
+            }
+            self.playing = true;
+        }
+        let Some(next) = self.next else {
+            stereo.fill(0.0);
+            return;
+        };
+        let index = next as usize % self.slots.len();
+        if self.slots[index].sequence == Some(next) {
+            let slot = &mut self.slots[index];
+            stereo.copy_from_slice(&slot.samples);
+            slot.sequence = None;
+        } else if self.depth() > 0 {
+            // Les suivants sont arrivés : celui-ci est perdu
+            self.lost += 1;
+            stereo.fill(0.0);
+        } else {
+            // Plus rien à jouer : silence, puis on attend de nouveau
+            // `target` paquets
+            self.underruns += 1;
+            self.playing = false;
+            stereo.fill(0.0);
+            return;
+        }
+        self.next = Some(next.wrapping_add(1));
+    }
+
+    /// Recopie les compteurs du tampon dans `counters`.
+    fn publish(&self, counters: &NetworkCounters) {
+        counters
+            .packets_received
+            .store(self.received, Ordering::Relaxed);
+        counters.packets_lost.store(self.lost, Ordering::Relaxed);
+        counters
+            .packets_dropped
+            .store(self.dropped, Ordering::Relaxed);
+        counters.jitter_depth.store(self.depth(), Ordering::Relaxed);
+        counters.underruns.store(self.underruns, Ordering::Relaxed);
+    }
+}
+
+/// Flux réseau joué par un canal d'entrée.
+///
+/// Un thread reçoit les paquets et les range dans le tampon ; le
+/// callback audio les lit avec `read`, par `try_lock` (le thread ne
+/// tient le lock que le temps de ranger un paquet).
+pub struct NetworkSource {
+    channel: ChannelId,
+    buffer: Arc<Mutex<JitterBuffer>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    local_addr: SocketAddr,
+    counters: Arc<NetworkCounters>,
+    /// Paquet en cours de lecture
+    current: Vec<f32>,
+    cursor: usize,
+}
+
+impl NetworkSource {
+    /// Écoute `bind:port` et joue le flux reçu sur `channel`, compté
+    /// dans `counters`.
+    pub fn bind(
+        bind: &str,
+        port: u16,
+        channel: ChannelId,
+        jitter_packets: usize,
+        counters: Arc<NetworkCounters>,
+    ) -> TroubadourResult<Self> {
+        let socket = UdpSocket::bind((bind, port)).map_err(|e| network_error("bind", e))?;
+        socket
+            .set_read_timeout(Some(RECEIVE_TIMEOUT))
+            .map_err(|e| network_error("bind", e))?;
+        let local_addr = socket.local_addr().map_err(|e| network_error("bind", e))?;
+
+        let buffer = Arc::new(Mutex::new(JitterBuffer::new(jitter_packets)));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let buffer = buffer.clone();
+            let running = running.clone();
+            let counters = counters.clone();
+            std::thread::Builder::new()
+                .name("network-receive".into())
+                .spawn(move || {
+                    let mut packet = vec![0u8; MAX_PACKET_LEN + 1];
+                    let mut stereo = vec![0.0; PACKET_SAMPLES];
+                    while running.load(Ordering::Relaxed) {
+                        // Délai dépassé : on revérifie l'arrêt
+                        let Ok(len) = socket.recv(&mut packet) else {
+                            continue;
+                        };
+                        let decoded = decode_packet(&packet[..len], &mut stereo);
+                        let Ok(mut buffer) = buffer.lock() else {
+                            return;
+                        };
+                        match decoded {
+                            Some(header) => {
+                                counters
+                                    .remote_sample_rate
+                                    .store(header.sample_rate, Ordering::Relaxed);
+                                buffer.push(header.sequence, &stereo);
+                            }
+                            None => buffer.reject(),
+                        }
+                        buffer.publish(&counters);
+                    }
+                })
+                .map_err(|e| network_error("receive thread", e))?
+        };
+
+        Ok(Self {
+            channel,
+            buffer,
+            running,
+            thread: Some(thread),
+            local_addr,
+            counters,
+            current: vec![0.0; PACKET_SAMPLES],
+            cursor: PACKET_SAMPLES,
+        })
+    }
+
+    pub fn channel(&self) -> ChannelId {
+        self.channel
+    }
+
+    /// Adresse d'écoute effective (port choisi par le système si 0).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Remplit un bloc stéréo entrelacé avec le flux reçu.
+    ///
+    /// Tampon occupé par le thread de réception : le reste du bloc est
+    /// du silence, le flux reprend au bloc suivant là où il en était.
+    pub fn read(&mut self, stereo: &mut [f32]) {
+        let mut written = 0;
+        while written < stereo.len() {
+            if self.cursor == PACKET_SAMPLES {
+                let Ok(mut buffer) = self.buffer.try_lock() else {
+                    stereo[written..].fill(0.0);
+                    return;
+                };
+                buffer.pop(&mut self.current);
+                buffer.publish(&self.counters);
+                self.cursor = 0;
+            }
+            let take = (PACKET_SAMPLES - self.cursor).min(stereo.len() - written);
+            stereo[written..written + take]
+                .copy_from_slice(&self.current[self.cursor..self.cursor + take]);
+            self.cursor += take;
+            written += take;
+        }
+    }
+}
+
+impl Drop for NetworkSource {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Envoi et réception branchés sur le pipeline, partagés moteur ↔
+/// callback (le moteur ne prend le lock que pour les (dé)brancher).
+#[derive(Default)]
+pub struct NetworkPorts {
+    pub sink: Option<NetworkSink>,
+    pub source: Option<NetworkSource>,
+}
+
+/// Compteurs du transport, écrits par le callback et le thread de
+/// réception. Le moteur les lit sans prendre le lock de
+/// `NetworkPorts` : le callback, qui n'y fait que `try_lock`, perdrait
+/// le flux d'un bloc à chaque lecture.
+#[derive(Debug, Default)]
+pub struct NetworkCounters {
+    sending: AtomicBool,
+    packets_sent: AtomicU64,
+    send_errors: AtomicU64,
+    receiving: AtomicBool,
+    packets_received: AtomicU64,
+    packets_lost: AtomicU64,
+    packets_dropped: AtomicU64,
+    jitter_depth: AtomicUsize,
+    underruns: AtomicU64,
+    /// Sample rate annoncé par l'émetteur (0 avant le premier paquet)
+    remote_sample_rate: AtomicU32,
+}
+
+impl NetworkCounters {
+    /// Un envoi commence (compteurs à zéro) ou s'arrête.
+    pub fn set_sending(&self, sending: bool) {
+        self.packets_sent.store(0, Ordering::Relaxed);
+        self.send_errors.store(0, Ordering::Relaxed);
+        self.sending.store(sending, Ordering::Relaxed);
+    }
+
+    /// Une réception commence (compteurs à zéro) ou s'arrête.
+    pub fn set_receiving(&self, receiving: bool) {
+        for counter in [
+            &self.packets_received,
+            &self.packets_lost,
+            &self.packets_dropped,
+            &self.underruns,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.jitter_depth.store(0, Ordering::Relaxed);
+        self.remote_sample_rate.store(0, Ordering::Relaxed);
+        self.receiving.store(receiving, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> NetworkStats {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        NetworkStats {
+            sending: self.sending.load(Ordering::Relaxed),
+            packets_sent: load(&self.packets_sent),
+            send_errors: load(&self.send_errors),
+            receiving: self.receiving.load(Ordering::Relaxed),
+            packets_received: load(&self.packets_received),
+            packets_lost: load(&self.packets_lost),
+            packets_dropped: load(&self.packets_dropped),
+            jitter_depth: self.jitter_depth.load(Ordering::Relaxed),
+            underruns: load(&self.underruns),
+            remote_sample_rate: Some(self.remote_sample_rate.load(Ordering::Relaxed))
+                .filter(|&rate| rate > 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn packet_of(value: f32) -> Vec<f32> {
+        vec![value; PACKET_SAMPLES]
+    }
+
+    #[test]
+    fn jitter_buffer_reorders_and_fills_gaps_with_silence() {
+        let mut buffer = JitterBuffer::new(2);
+        let mut out = packet_of(9.0);
+        // Pas encore `target` paquets : silence
+        buffer.push(10, &packet_of(0.1));
+        buffer.pop(&mut out);
+        assert!(out.iter().all(|&s| s == 0.0));
+
+        // Arrivés dans le désordre, 12 jamais arrivé
+        buffer.push(13, &packet_of(0.4));
+        buffer.push(11, &packet_of(0.2));
+        let mut played = Vec::new();
+        for _ in 0..4 {
+            buffer.pop(&mut out);
+            played.push(out[0]);
+        }
+        assert_eq!(played, [0.1, 0.2, 0.0, 0.4]);
+
+        // 12 arrive trop tard : jeté
+        buffer.push(12, &packet_of(0.3));
+        let counters = NetworkCounters::default();
+        buffer.publish(&counters);
+        let stats = counters.stats();
+        assert_eq!((stats.packets_lost, stats.packets_dropped), (1, 1));
+
+        // Vide : un underrun, puis on réattend deux paquets
+        buffer.pop(&mut out);
+        buffer.push(14, &packet_of(0.5));
+        buffer.pop(&mut out);
+        assert_eq!(out[0], 0.0);
+        buffer.push(15, &packet_of(0.6));
+        buffer.pop(&mut out);
+        assert_eq!(out[0], 0.5);
+        buffer.publish(&counters);
+        assert_eq!(counters.stats().underruns, 1);
+
+        // Longue coupure : on repart du premier paquet reçu après
+        buffer.push(5_000, &packet_of(0.7));
+        buffer.push(5_001, &packet_of(0.8));
+        buffer.pop(&mut out);
+        assert_eq!(out[0], 0.7);
+    }
+
+    #[test]
+    fn foreign_packets_are_ignored() {
+        let mut stereo = packet_of(0.0);
+        let mut packet = Vec::new();
+        let header = PacketHeader {
+            sequence: 7,
+            sample_rate: 48_000,
+        };
+        encode_packet(&mut packet, header, PacketFormat::I16, &packet_of(0.5));
+        assert_eq!(decode_packet(&packet, &mut stereo), Some(header));
+        assert!((stereo[0] - 0.5).abs() < 1e-4);
+
+        let mut wrong_version = packet.clone();
+        wrong_version[4] = 2;
+        assert_eq!(decode_packet(&wrong_version, &mut stereo), None);
+        assert_eq!(decode_packet(b"GET / HTTP/1.1\r\n", &mut stereo), None);
+        assert_eq!(
+            decode_packet(&packet[..packet.len() - 1], &mut stereo),
+            None
+        );
+    }
+
+    /// Attend que la source ait reçu `count` paquets.
+    fn wait_received(source: &NetworkSource, count: u64) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while source.buffer.lock().unwrap().received < count {
+            assert!(Instant::now() < deadline, "packet {count} never arrived");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    fn tone_survives_a_lossy_loopback(format: PacketFormat, tolerance: f32) {
+        const PACKETS: usize = 400;
+        const JITTER: usize = 4;
+        let tone: Vec<f32> = (0..PACKETS * PACKET_FRAMES)
+            .flat_map(|n| {
+                let s = 0.5 * (std::f32::consts::TAU * 440.0 * n as f32 / 48_000.0).sin();
+                [s, -s]
+            })
+            .collect();
+
+        let counters = Arc::new(NetworkCounters::default());
+        let mut source =
+            NetworkSource::bind("127.0.0.1", 0, ChannelId(5), JITTER, counters.clone()).unwrap();
+        // Relais entre les deux : perd un paquet sur cent
+        let relay = UdpSocket::bind("127.0.0.1:0").unwrap();
+        relay
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let relay_port = relay.local_addr().unwrap().port();
+        let mut sink = NetworkSink::connect(
+            "127.0.0.1",
+            relay_port,
+            ChannelId(3),
+            format,
+            counters.clone(),
+        )
+        .unwrap();
+
+        let mut dropped = Vec::new();
+        let mut forwarded = 0;
+        let mut received = vec![0.0; tone.len()];
+        let mut packet = vec![0u8; MAX_PACKET_LEN];
+        let mut read_packets = 0;
+        for (index, chunk) in tone.chunks(PACKET_SAMPLES).enumerate() {
+            sink.write(chunk, 48_000);
+            let len = relay.recv(&mut packet).unwrap();
+            if index % 100 == 37 {
+                dropped.push(index);
+            } else {
+                relay.send_to(&packet[..len], source.local_addr()).unwrap();
+                forwarded += 1;
+                wait_received(&source, forwarded);
+            }
+            // La lecture suit à `JITTER` paquets derrière l'envoi
+            if index >= JITTER {
+                let at = read_packets * PACKET_SAMPLES;
+                source.read(&mut received[at..at + PACKET_SAMPLES]);
+                read_packets += 1;
+            }
+        }
+        let at = read_packets * PACKET_SAMPLES;
+        source.read(&mut received[at..]);
+
+        assert_eq!(dropped.len(), 4);
+        for (index, (got, sent)) in received
+            .chunks(PACKET_SAMPLES)
+            .zip(tone.chunks(PACKET_SAMPLES))
+            .enumerate()
+        {
+            if dropped.contains(&index) {
+                assert!(got.iter().all(|&s| s == 0.0), "packet {index} not silent");
+            } else {
+                // À sa place exacte : aucun décalage après une perte
+                let error = got
+                    .iter()
+                    .zip(sent)
+                    .map(|(a, b)| (a - b).abs())
+                    .fold(0.0, f32::max);
+                assert!(error <= tolerance, "packet {index} off by {error}");
+            }
+        }
+
+        // Lus sans prendre le lock que le callback tiendrait
+        let ports = Mutex::new(NetworkPorts {
+            sink: Some(sink),
+            source: Some(source),
+        });
+        let _callback = ports.lock().unwrap();
+        let stats = counters.stats();
+        assert_eq!(stats.packets_sent, PACKETS as u64);
+        assert_eq!(stats.packets_received, PACKETS as u64 - 4);
+        assert_eq!(stats.packets_lost, 4);
+        assert_eq!(stats.underruns, 0);
+        assert_eq!(stats.remote_sample_rate, Some(48_000));
+    }
+
+    #[test]
+    fn tone_crosses_the_loopback_despite_one_percent_loss() {
+        tone_survives_a_lossy_loopback(PacketFormat::F32, 0.0);
+    }
+
+    #[test]
+    fn int16_packets_carry_the_tone_within_one_lsb() {
+        tone_survives_a_lossy_loopback(PacketFormat::I16, 1.0 / i16::MAX as f32);
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::mixer::{SignalPresenceConfig, SignalState};
+
+/// Détecteur de présence de signal sur un canal.
+///
+/// Alimenté bloc par bloc depuis le callback audio avec le RMS du bloc :
+/// pas d'allocation, quelques comparaisons par buffer.
+///
+/// ```text
+///  niveau
+///    │   ┌──┐                         seuil + hystérésis ─ ─ ─
+///    │───┘  └──┐ ┌┐  ┌┐                seuil ──────────────
+///    │         └─┘└──┘└──────────────────────
+///    └──────────┬──────────────────────┬────────► temps
+///               sous le seuil     silence_seconds écoulées
+///                                      → Silent
+/// ```
+///
+/// En `Active`, un seul bloc au-dessus du seuil remet le compteur à
+/// zéro : une parole hachée n'est jamais déclarée silencieuse.
+/// En `Silent`, il faut dépasser seuil + hystérésis pour revenir.
+pub struct PresenceDetector {
+    config: SignalPresenceConfig,
+    sample_rate: f32,
+    state: SignalState,
+    /// Samples consécutifs passés sous le seuil
+    below_samples: u64,
+}
+
+impl PresenceDetector {
+    pub fn new(config: SignalPresenceConfig, sample_rate: f32) -> Self {
+        Self {
+            config,
+            sample_rate: sample_rate.max(1.0),
+            state: SignalState::Active,
+            below_samples: 0,
+        }
+    }
+
+    /// Change les réglages sans perdre l'état courant.
+    pub fn set_config(&mut self, config: SignalPresenceConfig) {
+        self.config = config;
+    }
+
+    pub fn config(&self) -> SignalPresenceConfig {
+        self.config
+    }
+
+    pub fn state(&self) -> SignalState {
+        self.state
+    }
+
+    /// Traite un bloc de `frames` samples de niveau RMS `rms` (linéaire).
+    ///
+    /// Retourne `Some(nouvel_état)` uniquement lors d'une transition.
+    pub fn process_block(&mut self, rms: f32, frames: usize) -> Option<SignalState> {
+        let level_db = 20.0 * rms.max(1e-9).log10();
+
+        match self.state {
+            SignalState::Active => {
+                if level_db < self.config.threshold_db {
+                    self.below_samples += frames as u64;
+                    let hold = (self.config.silence_seconds.max(0.0) * self.sample_rate) as u64;
+                    if self.below_samples >= hold {
+                        self.state = SignalState::Silent;
+                        return Some(SignalState::Silent);
+                    }
+                } else {
+                    self.below_samples = 0;
+                }
+            }
+            SignalState::Silent => {
+                if level_db >= self.config.threshold_db + self.config.hysteresis_db {
+                    self.state = SignalState::Active;
+                    self.below_samples = 0;
+                    return Some(SignalState::Active);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Dernier état de présence de chaque canal, lisible sans lock.
+///
+/// Le callback audio écrit, l'UI / l'engine lisent. Un `AtomicBool`
+/// par id de canal (`true` = silencieux), dimensionné à la création :
+/// les ids au-delà sont simplement toujours `Active`.
+pub struct SignalStates {
+    silent: Vec<AtomicBool>,
+}
+
+impl SignalStates {
+    pub fn new(max_channels: usize) -> Self {
+        Self {
+            silent: (0..max_channels).map(|_| AtomicBool::new(false)).collect(),
+        }
+    }
+
+    pub fn set(&self, channel: ChannelId, state: SignalState) {
+        if let Some(flag) = self.silent.get(channel.0) {
+            flag.store(state == SignalState::Silent, Ordering::Relaxed);
+        }
+    }
+
+    pub fn get(&self, channel: ChannelId) -> SignalState {
+        match self.silent.get(channel.0) {
+            Some(flag) if flag.load(Ordering::Relaxed) => SignalState::Silent,
+            _ => SignalState::Active,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SR: f32 = 48_000.0;
+    const BLOCK: usize = 480; // 10 ms
+
+    fn config() -> SignalPresenceConfig {
+        SignalPresenceConfig {
+            threshold_db: -50.0,
+            silence_seconds: 1.0,
+            hysteresis_db: 6.0,
+        }
+    }
+
+    fn db(level_db: f32) -> f32 {
+        10.0_f32.powf(level_db / 20.0)
+    }
+
+    /// Pousse `seconds` de blocs à un niveau donné, retourne les transitions.
+    fn feed(det: &mut PresenceDetector, level_db: f32, seconds: f32) -> Vec<SignalState> {
+        let blocks = (seconds * SR) as usize / BLOCK;
+        (0..blocks)
+            .filter_map(|_| det.process_block(db(level_db), BLOCK))
+            .collect()
+    }
+
+    #[test]
+    fn silence_after_configured_duration() {
+        let mut det = PresenceDetector::new(config(), SR);
+        assert!(feed(&mut det, -20.0, 2.0).is_empty());
+
+        // 0.9 s de silence : pas encore
+        assert!(feed(&mut det, -80.0, 0.9).is_empty());
+        // On franchit 1 s
+        assert_eq!(feed(&mut det, -80.0, 0.2), vec![SignalState::Silent]);
+        assert_eq!(det.state(), SignalState::Silent);
+
+        // Retour du signal
+        assert_eq!(feed(&mut det, -20.0, 0.1), vec![SignalState::Active]);
+    }
+
+    #[test]
+    fn short_dips_do_not_trigger() {
+        let mut det = PresenceDetector::new(config(), SR);
+        // Parole hachée : 0.5 s de silence, 0.1 s de voix, en boucle
+        for _ in 0..10 {
+            assert!(feed(&mut det, -80.0, 0.5).is_empty());
+            assert!(feed(&mut det, -30.0, 0.1).is_empty());
+        }
+        assert_eq!(det.state(), SignalState::Active);
+    }
+
+    #[test]
+    fn hysteresis_prevents_flapping_at_threshold() {
+        let mut det = PresenceDetector::new(config(), SR);
+        feed(&mut det, -80.0, 1.5);
+        assert_eq!(det.state(), SignalState::Silent);
+
+        // Le niveau oscille de part et d'autre du seuil (-50 dB),
+        // mais reste sous seuil + hystérésis (-44 dB) : on reste Silent.
+        let mut transitions = Vec::new();
+        for i in 0..500 {
+            let level = if i % 2 == 0 { -51.0 } else { -47.0 };
+            transitions.extend(det.process_block(db(level), BLOCK));
+        }
+        assert!(transitions.is_empty(), "Flapping: {transitions:?}");
+        assert_eq!(det.state(), SignalState::Silent);
+
+        // Franchir franchement seuil + hystérésis → Active, une seule fois
+        let transitions = feed(&mut det, -40.0, 1.0);
+        assert_eq!(transitions, vec![SignalState::Active]);
+    }
+
+    #[test]
+    fn signal_states_are_shared() {
+        let states = SignalStates::new(4);
+        assert_eq!(states.get(ChannelId(1)), SignalState::Active);
+        states.set(ChannelId(1), SignalState::Silent);
+        assert_eq!(states.get(ChannelId(1)), SignalState::Silent);
+        // Hors capacité : ignoré, toujours Active
+        states.set(ChannelId(99), SignalState::Silent);
+        assert_eq!(states.get(ChannelId(99)), SignalState::Active);
+    }
+}
@@ -0,0 +1,310 @@
+//! Écoute d'un device d'entrée avant de l'assigner à un canal.
+//!
+//! Parmi cinq entrées aux noms voisins, l'utilisateur en écoute une :
+//! un stream temporaire s'ouvre sur le device, hors de tout canal. Son
+//! signal (downmixé en mono, à `PREVIEW_LEVEL_DB`) passe par une file
+//! courte, que le pipeline mélange au bus d'écoute s'il est le bus joué.
+//! Ses niveaux arrivent dans `LevelUpdate` sous `PREVIEW_CHANNEL`.
+//!
+//! ```text
+//!  device écouté ──► callback ──► PreviewFeed ──► bus joué (pipeline)
+//!                       └──► LevelUpdate (PREVIEW_CHANNEL)
+//! ```
+//!
+//! Une seule écoute à la fois. Elle s'arrête seule au bout de son délai,
+//! à l'arrêt du moteur, et quand le device est assigné à une entrée :
+//! le stream de l'écoute est fermé avant que le pipeline n'ouvre le
+//! sien, jamais deux streams sur le même device.
+
+use troubadour_shared::audio::{ChannelId, DevicePreviewStatus};
+
+/// Niveau fixe de l'écoute : assez bas pour qu'un device inconnu ne
+/// fasse pas sauter le casque.
+pub const PREVIEW_LEVEL_DB: f32 = -12.0;
+
+/// Durée du signal gardé d'avance dans la file, en secondes : au-delà,
+/// les samples les plus anciens sont jetés.
+const FEED_SECONDS: f32 = 0.25;
+
+/// Écoute en cours, avec le handle qui garde son stream ouvert.
+struct ActivePreview<S> {
+    device: String,
+    bus: Option<ChannelId>,
+    started_ms: u64,
+    /// Fermer le stream = dropper le handle
+    _handle: S,
+}
+
+/// Cycle de vie de l'écoute d'un device.
+///
+/// Générique sur `S` (le handle du stream) comme `StreamSet`, pour
+/// tester le cycle sans device.
+pub struct DevicePreview<S> {
+    active: Option<ActivePreview<S>>,
+    timeout_ms: u64,
+}
+
+impl<S> DevicePreview<S> {
+    pub fn new(timeout_seconds: u32) -> Self {
+        Self {
+            active: None,
+            timeout_ms: u64::from(timeout_seconds.max(1)) * 1000,
+        }
+    }
+
+    /// Délai avant l'arrêt automatique (prend effet sur l'écoute en cours).
+    pub fn set_timeout(&mut self, timeout_seconds: u32) {
+        self.timeout_ms = u64::from(timeout_seconds.max(1)) * 1000;
+    }
+
+    /// Device écouté.
+    pub fn device(&self) -> Option<&str> {
+        self.active.as_ref().map(|p| p.device.as_str())
+    }
+
+    /// Démarre l'écoute de `device` vers `bus`. `open` ouvre le stream ;
+    /// s'il échoue, l'écoute en cours continue.
+    ///
+    /// Retourne le device dont l'écoute a été remplacée.
+    pub fn start<E>(
+        &mut self,
+        device: &str,
+        bus: Option<ChannelId>,
+        now_ms: u64,
+        open: impl FnOnce() -> Result<S, E>,
+    ) -> Result<Option<String>, E> {
+        // L'ancien stream d'abord, si c'est le même device : jamais deux
+        // streams ouverts dessus
+        let mut replaced = None;
+        if self.device() == Some(device) {
+            replaced = self.stop();
+        }
+        let handle = open()?;
+        replaced = replaced.or_else(|| self.stop());
+        self.active = Some(ActivePreview {
+            device: device.to_string(),
+            bus,
+            started_ms: now_ms,
+            _handle: handle,
+        });
+        Ok(replaced)
+    }
+
+    /// Arrête l'écoute ; retourne le device qui était écouté.
+    pub fn stop(&mut self) -> Option<String> {
+        self.active.take().map(|p| p.device)
+    }
+
+    /// Arrête l'écoute si son délai est écoulé.
+    pub fn expire(&mut self, now_ms: u64) -> Option<String> {
+        let started = self.active.as_ref()?.started_ms;
+        (now_ms.saturating_sub(started) >= self.timeout_ms)
+            .then(|| self.stop())
+            .flatten()
+    }
+
+    /// Arrête l'écoute si elle porte sur `device`, qui va être ouvert
+    /// pour un canal.
+    pub fn hand_over(&mut self, device: &str) -> bool {
+        self.device() == Some(device) && self.stop().is_some()
+    }
+
+    /// État de l'écoute pour `AudioStats`.
+    pub fn status(&self, now_ms: u64) -> Option<DevicePreviewStatus> {
+        self.active.as_ref().map(|p| DevicePreviewStatus {
+            device: p.device.clone(),
+            bus: p.bus,
+            remaining_seconds: ((p.started_ms + self.timeout_ms).saturating_sub(now_ms) / 1000)
+                as u32,
+        })
+    }
+}
+
+impl<S> Default for DevicePreview<S> {
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+/// Signal de l'écoute en attente du pipeline : file circulaire mono
+/// allouée à l'ouverture, écrite par le callback du device écouté et
+/// lue par celui de l'entrée (`try_lock` des deux côtés).
+#[derive(Debug)]
+pub struct PreviewFeed {
+    bus: Option<ChannelId>,
+    ring: Vec<f32>,
+    /// Prochain sample à lire
+    read: usize,
+    len: usize,
+}
+
+impl PreviewFeed {
+    /// File de `FEED_SECONDS` au sample rate du pipeline.
+    pub fn new(bus: Option<ChannelId>, sample_rate: f32) -> Self {
+        let capacity = ((sample_rate * FEED_SECONDS) as usize).max(1);
+        Self {
+            bus,
+            ring: vec![0.0; capacity],
+            read: 0,
+            len: 0,
+        }
+    }
+
+    /// Bus d'écoute (`None` : aucun bus de sortie).
+    pub fn bus(&self) -> Option<ChannelId> {
+        self.bus
+    }
+
+    /// Ajoute des samples mono ; une file pleine jette les plus anciens.
+    pub fn write(&mut self, samples: impl IntoIterator<Item = f32>) {
+        let capacity = self.ring.len();
+        for sample in samples {
+            self.ring[(self.read + self.len) % capacity] = sample;
+            if self.len == capacity {
+                self.read = (self.read + 1) % capacity;
+            } else {
+                self.len += 1;
+            }
+        }
+    }
+
+    /// Mélange ce qui est disponible à un bloc stéréo entrelacé ; un
+    /// device écouté en retard laisse la fin du bloc intacte. La frame
+    /// mono d'un bloc impair reçoit un sample comme les autres.
+    pub fn mix_into(&mut self, stereo: &mut [f32]) {
+        let capacity = self.ring.len();
+        for frame in stereo.chunks_mut(2) {
+            if self.len == 0 {
+                break;
+            }
+            let sample = self.ring[self.read];
+            self.read = (self.read + 1) % capacity;
+            self.len -= 1;
+            frame.iter_mut().for_each(|s| *s += sample);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Faux stream : journalise sa fermeture (drop).
+    struct MockStream {
+        device: &'static str,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Drop for MockStream {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push(format!("stop {}", self.device));
+        }
+    }
+
+    fn open(
+        device: &'static str,
+        log: &Rc<RefCell<Vec<String>>>,
+    ) -> impl FnOnce() -> Result<MockStream, String> {
+        let log = log.clone();
+        move || {
+            log.borrow_mut().push(format!("start {device}"));
+            Ok(MockStream { device, log })
+        }
+    }
+
+    #[test]
+    fn one_preview_at_a_time() {
+        let log = Rc::default();
+        let mut preview = DevicePreview::new(60);
+        let bus = Some(ChannelId(3));
+        assert_eq!(
+            preview.start("USB Mic", bus, 0, open("USB Mic", &log)),
+            Ok(None)
+        );
+        assert_eq!(preview.device(), Some("USB Mic"));
+
+        // Le nouveau stream s'ouvre avant que l'ancien ne ferme
+        let replaced = preview.start("Webcam", bus, 10, open("Webcam", &log));
+        assert_eq!(replaced, Ok(Some("USB Mic".to_string())));
+        // Même device : l'ancien stream ferme d'abord
+        preview
+            .start("Webcam", bus, 20, open("Webcam", &log))
+            .unwrap();
+        // Ouverture refusée : l'écoute en cours continue
+        let failed = preview.start("Broken", bus, 30, || Err("busy".to_string()));
+        assert_eq!(failed.err().as_deref(), Some("busy"));
+        assert_eq!(preview.device(), Some("Webcam"));
+
+        assert_eq!(preview.stop(), Some("Webcam".to_string()));
+        assert_eq!(preview.stop(), None);
+        assert_eq!(
+            *log.borrow(),
+            [
+                "start USB Mic",
+                "start Webcam",
+                "stop USB Mic",
+                "stop Webcam",
+                "start Webcam",
+                "stop Webcam"
+            ]
+        );
+    }
+
+    #[test]
+    fn preview_times_out() {
+        let log = Rc::default();
+        let mut preview = DevicePreview::new(60);
+        preview
+            .start("USB Mic", None, 1_000, open("USB Mic", &log))
+            .unwrap();
+        let status = preview.status(31_000).unwrap();
+        assert_eq!(
+            (status.device.as_str(), status.remaining_seconds),
+            ("USB Mic", 30)
+        );
+        assert_eq!(preview.expire(60_999), None);
+        assert_eq!(preview.expire(61_000), Some("USB Mic".to_string()));
+        assert!(preview.status(61_000).is_none());
+        assert_eq!(log.borrow().last().unwrap(), "stop USB Mic");
+
+        // Délai raccourci : l'écoute en cours en tient compte
+        preview
+            .start("USB Mic", None, 0, open("USB Mic", &log))
+            .unwrap();
+        preview.set_timeout(5);
+        assert_eq!(preview.expire(5_000), Some("USB Mic".to_string()));
+    }
+
+    #[test]
+    fn assigning_the_device_hands_it_over() {
+        let log = Rc::default();
+        let mut preview = DevicePreview::new(60);
+        preview
+            .start("USB Mic", None, 0, open("USB Mic", &log))
+            .unwrap();
+        assert!(!preview.hand_over("Webcam"));
+        assert!(preview.hand_over("USB Mic"));
+        assert_eq!(preview.device(), None);
+        assert_eq!(*log.borrow(), ["start USB Mic", "stop USB Mic"]);
+    }
+
+    #[test]
+    fn feed_keeps_the_latest_samples() {
+        let mut feed = PreviewFeed::new(Some(ChannelId(3)), 16.0);
+        // 4 samples de capacité : les 2 plus anciens sont jetés
+        feed.write([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let mut stereo = vec![0.5; 10];
+        feed.mix_into(&mut stereo);
+        assert_eq!(stereo, [3.5, 3.5, 4.5, 4.5, 5.5, 5.5, 6.5, 6.5, 0.5, 0.5]);
+        feed.mix_into(&mut stereo[..2]);
+        assert_eq!(stereo[..2], [3.5, 3.5]);
+
+        // Bloc impair : la frame mono finale est servie
+        feed.write([1.0, 2.0]);
+        let mut odd = vec![0.0; 3];
+        feed.mix_into(&mut odd);
+        assert_eq!(odd, [1.0, 1.0, 2.0]);
+    }
+}
@@ -0,0 +1,256 @@
+//! Analyse et normalisation d'un enregistrement, après coup.
+//!
+//! ```text
+//!  fichier.wav ──► WavReader (morceaux) ──┬─► crête, RMS
+//!                                         ├─► crête vraie (x4)
+//!                                         └─► LoudnessMeter (R128)
+//!                                                   │
+//!  copie.wav ◄── WavWriter ◄── × gain ◄── cible ────┘
+//! ```
+//!
+//! Le fichier est lu par morceaux : la mémoire ne dépend pas de sa
+//! durée. La normalisation le lit deux fois (mesure, puis copie).
+
+use std::path::Path;
+
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::recording::{
+    NormalizeOutcome, NormalizeTarget, RecordingReport, TRUE_PEAK_CEILING_DBTP,
+};
+
+use crate::dsp::loudness::LoudnessMeter;
+use crate::dsp::true_peak::TruePeakMeter;
+use crate::wav::{WavReader, WavWriter};
+
+/// Frames lues à la fois.
+const CHUNK_FRAMES: usize = 8_192;
+
+/// Amplitude → dB (`None` pour un silence numérique).
+fn to_db(amplitude: f64) -> Option<f32> {
+    (amplitude > 0.0).then(|| (20.0 * amplitude.log10()) as f32)
+}
+
+/// Mesure un fichier WAV : crêtes, RMS et sonie intégrée.
+///
+/// `progress` reçoit l'avancement (0 → 1) après chaque morceau lu.
+///
+/// La sonie est mesurée sur les deux premiers canaux ; un fichier mono
+/// passe par le seul canal gauche, comme le prévoit la norme (un canal
+/// mono n'est pas compté deux fois).
+pub fn analyze_recording(
+    path: &Path,
+    mut progress: impl FnMut(f32),
+) -> TroubadourResult<RecordingReport> {
+    let mut reader = WavReader::open(path)?;
+    let channels = reader.channels() as usize;
+    let total = reader.frames();
+    let mut true_peak_meter = TruePeakMeter::new(channels);
+    let mut loudness = LoudnessMeter::new(reader.sample_rate() as f32);
+
+    let (mut chunk, mut stereo) = (Vec::new(), Vec::new());
+    let (mut sample_peak, mut true_peak) = (0.0_f32, 0.0_f32);
+    let (mut sum_squares, mut read) = (0.0_f64, 0_u64);
+    loop {
+        let frames = reader.read_frames(CHUNK_FRAMES, &mut chunk)?;
+        if frames == 0 {
+            break;
+        }
+        for &sample in &chunk {
+            sample_peak = sample_peak.max(sample.abs());
+            sum_squares += f64::from(sample) * f64::from(sample);
+        }
+        true_peak = true_peak.max(true_peak_meter.process(&chunk));
+
+        stereo.clear();
+        stereo.extend(chunk.chunks_exact(channels).flat_map(|frame| {
+            let right = if channels > 1 { frame[1] } else { 0.0 };
+            [frame[0], right]
+        }));
+        loudness.process(&stereo);
+
+        read += frames as u64;
+        progress(read as f32 / total.max(1) as f32);
+    }
+
+    let samples = read * channels as u64;
+    Ok(RecordingReport {
+        sample_rate: reader.sample_rate(),
+        channels: reader.channels(),
+        frames: read,
+        sample_peak_dbfs: to_db(f64::from(sample_peak)),
+        true_peak_dbtp: to_db(f64::from(true_peak)),
+        rms_dbfs: to_db((sum_squares / samples.max(1) as f64).sqrt()),
+        integrated_lufs: loudness.loudness().integrated,
+    })
+}
+
+/// Écrit dans `dst` une copie de `src` amenée à `target` (WAV flottant
+/// 32 bits, même format sinon).
+///
+/// # Jamais de clipping
+/// Le gain est plafonné pour que la crête vraie de la copie reste sous
+/// `TRUE_PEAK_CEILING_DBTP` : un enregistrement très dynamique visé à
+/// -14 LUFS peut donc rester en dessous (`NormalizeOutcome::limited`).
+/// Rien n'est compressé ni limité : la copie est l'original × un gain.
+///
+/// `progress` va de 0 à 0.5 pendant la mesure, de 0.5 à 1 pendant la
+/// copie.
+pub fn normalize_recording(
+    src: &Path,
+    dst: &Path,
+    target: NormalizeTarget,
+    mut progress: impl FnMut(f32),
+) -> TroubadourResult<NormalizeOutcome> {
+    if dst.exists() && src.canonicalize().ok() == dst.canonicalize().ok() {
+        return Err(TroubadourError::AudioFile(format!(
+            "{}: cannot normalize a file onto itself",
+            dst.display()
+        )));
+    }
+    let report = analyze_recording(src, |p| progress(p * 0.5))?;
+    let silent =
+        || TroubadourError::AudioFile(format!("{}: silent, nothing to normalize", src.display()));
+    let true_peak = report.true_peak_dbtp.ok_or_else(silent)?;
+    let wanted = match target {
+        NormalizeTarget::Lufs(lufs) => lufs - report.integrated_lufs.ok_or_else(silent)?,
+        NormalizeTarget::Peak(dbtp) => dbtp - true_peak,
+    };
+    let ceiling = TRUE_PEAK_CEILING_DBTP - true_peak;
+    let outcome = NormalizeOutcome {
+        gain_db: wanted.min(ceiling),
+        limited: wanted > ceiling,
+    };
+
+    let gain = 10f32.powf(outcome.gain_db / 20.0);
+    let mut reader = WavReader::open(src)?;
+    let mut writer = WavWriter::create(dst, reader.sample_rate(), reader.channels())?;
+    let mut chunk = Vec::new();
+    let mut written = 0_u64;
+    loop {
+        let frames = reader.read_frames(CHUNK_FRAMES, &mut chunk)?;
+        if frames == 0 {
+            break;
+        }
+        for sample in &mut chunk {
+            *sample *= gain;
+        }
+        writer.write(&chunk)?;
+        written += frames as u64;
+        progress(0.5 + 0.5 * written as f32 / report.frames.max(1) as f32);
+    }
+    writer.finish()?;
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wav::write_wav;
+
+    fn temp_wav(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "troubadour-recording-{name}-{}.wav",
+            std::process::id()
+        ))
+    }
+
+    /// Sinus stéréo de `freq` Hz, crête à `dbfs`, `seconds` à 48 kHz.
+    ///
+    /// Il monte en 10 ms : un départ brutal fait sonner le filtre
+    /// d'interpolation, une vraie crête mais pas celle du sinus.
+    fn sine_wav(name: &str, freq: f64, dbfs: f32, seconds: f32) -> std::path::PathBuf {
+        let amplitude = 10f64.powf(f64::from(dbfs) / 20.0);
+        let frames = (48_000.0 * seconds) as usize;
+        let samples: Vec<f32> = (0..frames)
+            .flat_map(|n| {
+                let fade = (n as f64 / 480.0).min(1.0);
+                let value =
+                    fade * amplitude * (std::f64::consts::TAU * freq * n as f64 / 48_000.0).sin();
+                [value as f32; 2]
+            })
+            .collect();
+        let path = temp_wav(name);
+        write_wav(&path, 48_000, 2, &samples).unwrap();
+        path
+    }
+
+    fn assert_close(value: Option<f32>, expected: f32, tolerance: f32) {
+        let value = value.expect("level measured");
+        assert!(
+            (value - expected).abs() <= tolerance,
+            "{value} instead of {expected}"
+        );
+    }
+
+    #[test]
+    fn known_sine_levels_are_measured() {
+        let path = sine_wav("sine", 1_000.0, -20.0, 3.0);
+        let mut steps = Vec::new();
+        let report = analyze_recording(&path, |p| steps.push(p)).unwrap();
+        assert_eq!((report.sample_rate, report.channels), (48_000, 2));
+        assert_eq!(report.frames, 144_000);
+        assert_close(report.sample_peak_dbfs, -20.0, 0.01);
+        assert_close(report.true_peak_dbtp, -20.0, 0.05);
+        // RMS d'un sinus : 3 dB sous sa crête
+        assert_close(report.rms_dbfs, -23.01, 0.01);
+        // 1 kHz stéréo : la sonie vaut le niveau crête
+        assert_close(report.integrated_lufs, -20.0, 0.1);
+        assert!(steps.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(steps.last(), Some(&1.0));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn true_peak_catches_the_crest_between_samples() {
+        // 6 samples par période, aucun sur le sommet : ils plafonnent
+        // à sin(60°) = -1.25 dB
+        let path = sine_wav("inter-sample", 8_000.0, 0.0, 1.0);
+        let report = analyze_recording(&path, |_| {}).unwrap();
+        assert_close(report.sample_peak_dbfs, -1.25, 0.01);
+        assert_close(report.true_peak_dbtp, 0.0, 0.2);
+
+        let silence = temp_wav("silence");
+        write_wav(&silence, 48_000, 1, &[0.0; 4_800]).unwrap();
+        let report = analyze_recording(&silence, |_| {}).unwrap();
+        assert_eq!(report.sample_peak_dbfs, None);
+        assert_eq!(report.integrated_lufs, None);
+        assert!(
+            normalize_recording(&silence, &path, NormalizeTarget::Lufs(-16.0), |_| {}).is_err()
+        );
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&silence);
+    }
+
+    #[test]
+    fn normalized_copy_hits_the_target_without_passing_the_ceiling() {
+        let src = sine_wav("normalize-src", 1_000.0, -20.0, 3.0);
+        let dst = temp_wav("normalize-dst");
+
+        let outcome =
+            normalize_recording(&src, &dst, NormalizeTarget::Lufs(-16.0), |_| {}).unwrap();
+        assert!(!outcome.limited);
+        assert!((outcome.gain_db - 4.0).abs() < 0.1);
+        let copy = analyze_recording(&dst, |_| {}).unwrap();
+        // L'intégrée est rangée par pas de 0.1 LU
+        assert_close(copy.integrated_lufs, -16.0, 0.1);
+        assert_eq!(copy.frames, 144_000);
+
+        // 0 LUFS demandé : la crête s'arrête à -1 dBTP
+        let outcome = normalize_recording(&src, &dst, NormalizeTarget::Lufs(0.0), |_| {}).unwrap();
+        assert!(outcome.limited);
+        let copy = analyze_recording(&dst, |_| {}).unwrap();
+        assert_close(copy.true_peak_dbtp, TRUE_PEAK_CEILING_DBTP, 0.01);
+
+        let outcome = normalize_recording(&src, &dst, NormalizeTarget::Peak(-3.0), |_| {}).unwrap();
+        assert!(!outcome.limited);
+        let copy = analyze_recording(&dst, |_| {}).unwrap();
+        assert_close(copy.true_peak_dbtp, -3.0, 0.01);
+
+        assert!(normalize_recording(&src, &src, NormalizeTarget::Peak(-3.0), |_| {}).is_err());
+
+        let _ = std::fs::remove_file(&src);
+        let _ = std::fs::remove_file(&dst);
+    }
+}
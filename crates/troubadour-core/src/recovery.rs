@@ -0,0 +1,343 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use tracing::warn;
+use troubadour_shared::config::{AutoSnapshotConfig, RecoveryConfig};
+use troubadour_shared::profile::Profile;
+use troubadour_shared::recovery::{AutoSnapshot, AutoSnapshotInfo, RecoverySnapshot};
+
+/// Suivi "modifié depuis la dernière sauvegarde" + écriture limitée
+/// de l'instantané de secours.
+///
+/// Appelé depuis le thread des commandes, jamais depuis le callback
+/// audio : une écriture disque n'a rien à faire en temps réel.
+pub struct RecoveryWriter {
+    /// `None` = désactivé (tests, config `enabled = false`)
+    path: Option<PathBuf>,
+    interval: Duration,
+    dirty: bool,
+    last_write: Option<Instant>,
+    /// L'instantané de la session précédente attend la réponse de
+    /// l'utilisateur : ne pas l'écraser
+    held: bool,
+}
+
+impl RecoveryWriter {
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            interval: Duration::ZERO,
+            dirty: false,
+            last_write: None,
+            held: false,
+        }
+    }
+
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        Self {
+            path: Some(path),
+            interval,
+            dirty: false,
+            last_write: None,
+            held: false,
+        }
+    }
+
+    pub fn from_config(config: &RecoveryConfig, path: PathBuf) -> Self {
+        if config.enabled {
+            Self::new(path, Duration::from_secs(config.interval_seconds))
+        } else {
+            Self::disabled()
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Suspend l'écriture jusqu'à `release` ou `discard` : l'instantané
+    /// sur disque reste celui de la session précédente.
+    pub fn hold(&mut self) {
+        self.held = true;
+    }
+
+    /// Reprend l'écriture ; ce qui a changé entre-temps sera écrit.
+    pub fn release(&mut self) {
+        self.held = false;
+    }
+
+    /// `true` si l'état est modifié et que l'intervalle minimum depuis
+    /// la dernière écriture est écoulé.
+    pub fn is_due(&self, now: Instant) -> bool {
+        if self.path.is_none() || !self.dirty || self.held {
+            return false;
+        }
+        match self.last_write {
+            Some(last) => now.duration_since(last) >= self.interval,
+            None => true,
+        }
+    }
+
+    /// Écrit l'instantané et remet l'état à "propre".
+    pub fn write(
+        &mut self,
+        now: Instant,
+        snapshot: &RecoverySnapshot,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        // Même en cas d'échec, on attend l'intervalle avant de réessayer :
+        // un disque plein ne doit pas être martelé à chaque commande.
+        self.last_write = Some(now);
+        snapshot.save(path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Supprime l'instantané (arrêt propre ou "ignorer" demandé par l'utilisateur).
+    pub fn discard(&mut self) -> std::io::Result<()> {
+        self.dirty = false;
+        self.held = false;
+        match &self.path {
+            Some(path) => RecoverySnapshot::discard(path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Instantanés automatiques : un fichier `<id>.toml` par instantané.
+///
+/// L'id est l'horodatage de la prise en millisecondes, forcé croissant :
+/// trier les ids, c'est trier par date, sans relire les fichiers.
+pub struct AutoSnapshots {
+    /// `None` = désactivé (tests, config `enabled = false`)
+    dir: Option<PathBuf>,
+    keep: usize,
+    strict: bool,
+}
+
+impl AutoSnapshots {
+    pub fn disabled() -> Self {
+        Self {
+            dir: None,
+            keep: 0,
+            strict: false,
+        }
+    }
+
+    /// `keep` vaut au moins 1 : l'instantané qu'on vient de prendre.
+    pub fn from_config(config: &AutoSnapshotConfig, dir: PathBuf) -> Self {
+        Self {
+            dir: config.enabled.then_some(dir),
+            keep: config.keep.max(1),
+            strict: config.strict,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Un échec d'écriture doit-il annuler l'opération ?
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Écrit un instantané de `profile`, puis supprime les plus anciens
+    /// au-delà de `keep`.
+    pub fn take(
+        &self,
+        reason: &str,
+        profile: &Profile,
+        now_ms: u64,
+    ) -> Result<AutoSnapshotInfo, Box<dyn std::error::Error>> {
+        let Some(dir) = &self.dir else {
+            return Err("auto snapshots are disabled".into());
+        };
+        let mut ids = self.ids();
+        // Deux prises dans la même milliseconde, ou horloge qui recule
+        let id = ids.last().map_or(now_ms, |last| now_ms.max(last + 1));
+        let snapshot = AutoSnapshot {
+            taken_at_ms: now_ms,
+            reason: reason.to_string(),
+            profile: profile.clone(),
+        };
+        snapshot.save(&dir.join(format!("{id}.toml")))?;
+        ids.push(id);
+        let expired = ids.len().saturating_sub(self.keep);
+        for old in &ids[..expired] {
+            let path = dir.join(format!("{old}.toml"));
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!("Cannot remove auto snapshot {}: {e}", path.display());
+            }
+        }
+        Ok(AutoSnapshotInfo {
+            id,
+            taken_at_ms: now_ms,
+            reason: snapshot.reason,
+        })
+    }
+
+    /// Instantanés sur disque, du plus récent au plus ancien. Un fichier
+    /// illisible est ignoré.
+    pub fn list(&self) -> Vec<AutoSnapshotInfo> {
+        self.ids()
+            .into_iter()
+            .rev()
+            .filter_map(|id| {
+                let snapshot = self.load(id).ok()?;
+                Some(AutoSnapshotInfo {
+                    id,
+                    taken_at_ms: snapshot.taken_at_ms,
+                    reason: snapshot.reason,
+                })
+            })
+            .collect()
+    }
+
+    pub fn load(&self, id: u64) -> Result<AutoSnapshot, Box<dyn std::error::Error>> {
+        let dir = self.dir.as_ref().ok_or("auto snapshots are disabled")?;
+        AutoSnapshot::load(&dir.join(format!("{id}.toml")))
+    }
+
+    /// Ids présents dans le dossier, croissants.
+    fn ids(&self) -> Vec<u64> {
+        let Some(entries) = self
+            .dir
+            .as_ref()
+            .and_then(|dir| std::fs::read_dir(dir).ok())
+        else {
+            return Vec::new();
+        };
+        let mut ids: Vec<u64> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                if path.extension()? != "toml" {
+                    return None;
+                }
+                path.file_stem()?.to_str()?.parse().ok()
+            })
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> RecoverySnapshot {
+        RecoverySnapshot {
+            saved_at_ms: 0,
+            profile: Profile::default_profile(),
+            ui: Default::default(),
+        }
+    }
+
+    #[test]
+    fn writes_at_most_once_per_interval() {
+        let path = std::env::temp_dir().join(format!(
+            "troubadour-recovery-writer-{}.toml",
+            std::process::id()
+        ));
+        let mut writer = RecoveryWriter::new(path.clone(), Duration::from_secs(10));
+        let t0 = Instant::now();
+
+        // Rien de modifié → rien d'écrit
+        assert!(!writer.is_due(t0));
+
+        writer.mark_dirty();
+        assert!(writer.is_due(t0));
+        writer.write(t0, &snapshot()).unwrap();
+        assert!(path.exists());
+        assert!(!writer.is_dirty());
+
+        // Nouvelle modification 2 s plus tard : on attend l'intervalle
+        writer.mark_dirty();
+        assert!(!writer.is_due(t0 + Duration::from_secs(2)));
+        assert!(writer.is_due(t0 + Duration::from_secs(10)));
+
+        writer.discard().unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn disabled_writer_never_writes() {
+        let mut writer = RecoveryWriter::from_config(
+            &RecoveryConfig {
+                enabled: false,
+                interval_seconds: 0,
+            },
+            std::env::temp_dir().join("never-written.toml"),
+        );
+        writer.mark_dirty();
+        assert!(!writer.is_due(Instant::now()));
+        assert!(writer.path().is_none());
+    }
+
+    fn snapshots(name: &str, keep: usize) -> (AutoSnapshots, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-auto-snapshots-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = AutoSnapshotConfig {
+            keep,
+            ..AutoSnapshotConfig::default()
+        };
+        (AutoSnapshots::from_config(&config, dir.clone()), dir)
+    }
+
+    #[test]
+    fn auto_snapshots_rotate_oldest_first() {
+        let (snapshots, dir) = snapshots("rotate", 3);
+        let profile = Profile::default_profile();
+        for (n, at) in [1_000, 2_000, 3_000].into_iter().enumerate() {
+            let info = snapshots.take(&format!("op {n}"), &profile, at).unwrap();
+            assert_eq!(info.id, at);
+        }
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 3);
+
+        // N+1 : le plus ancien part
+        snapshots.take("op 3", &profile, 4_000).unwrap();
+        let ids: Vec<u64> = snapshots.list().iter().map(|s| s.id).collect();
+        assert_eq!(ids, [4_000, 3_000, 2_000]);
+        assert!(!dir.join("1000.toml").exists());
+        assert_eq!(snapshots.list()[0].reason, "op 3");
+
+        // Même milliseconde (ou horloge en arrière) : id suivant
+        assert_eq!(snapshots.take("again", &profile, 4_000).unwrap().id, 4_001);
+        assert_eq!(snapshots.take("late", &profile, 10).unwrap().id, 4_002);
+        assert_eq!(snapshots.list().len(), 3);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn disabled_auto_snapshots_write_nothing() {
+        let dir = std::env::temp_dir().join("troubadour-auto-snapshots-never");
+        let config = AutoSnapshotConfig {
+            enabled: false,
+            ..AutoSnapshotConfig::default()
+        };
+        let snapshots = AutoSnapshots::from_config(&config, dir.clone());
+        assert!(!snapshots.is_enabled());
+        assert!(
+            snapshots
+                .take("op", &Profile::default_profile(), 1)
+                .is_err()
+        );
+        assert!(snapshots.list().is_empty());
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,876 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use tracing::warn;
+
+use troubadour_shared::audio::{BufferSize, ChannelCount, ChannelId};
+use troubadour_shared::dsp::{DspProfile, EffectTiming, InsertPoint};
+use troubadour_shared::error::{MixerError, TroubadourError, TroubadourResult};
+use troubadour_shared::mixer::{ChannelConfig, ChannelKind};
+use troubadour_shared::profile::Profile;
+
+use crate::channel_map::fold_to_bus;
+use crate::dsp::bus_effects::BusEffects;
+use crate::dsp::dc_block::InputConditioner;
+use crate::dsp::delay::DelayLine;
+use crate::dsp::{EffectsChain, Processor};
+use crate::engine::PIPELINE_INPUT_CHANNEL;
+use crate::mixer::Mixer;
+use crate::route_meter::{RouteMeter, RouteSampler};
+use crate::snapshot::MixerRuntimeState;
+use crate::wav;
+
+/// Réglages d'un rendu hors ligne.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    pub sample_rate: u32,
+    /// Taille des blocs, comme celle d'un callback du device
+    pub block_frames: usize,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48_000,
+            block_frames: BufferSize::default().as_frames() as usize,
+        }
+    }
+}
+
+/// Joue un profil hors ligne, sans device ni thread, plus vite que le
+/// temps réel.
+///
+/// `inputs` donne le signal mono de chaque canal d'entrée (un canal
+/// absent est silencieux, un signal trop court est complété de silence).
+/// Retourne, pour chaque bus, `blocks × block_frames` frames stéréo
+/// entrelacées (L = R pour un bus mono).
+///
+/// # Le même chemin que le callback
+/// Chaque canal passe par sa chaîne d'effets (celle du profil, sur le
+/// Mic comme dans le moteur), sa ligne de retard, son gain (volume × pan,
+/// mute et solo) puis est sommé dans les bus où il est routé, avec son
+/// retour pré-fader. Le bus replie en mono s'il l'est, passe ses effets
+/// pré-fader (`bus_effects` du profil), applique son volume puis ses
+/// effets post-fader, et enfin le solo de bus. Comme en direct, le
+/// passthrough d'urgence saute tous les effets.
+///
+/// Seul le retard de sortie n'est pas rendu : il compense la latence
+/// d'un device (casque sans fil), il n'appartient pas au mix.
+///
+/// # Déterministe
+/// Aucune horloge, aucun thread, aucun `try_lock` qui pourrait rater :
+/// le même profil et la même entrée donnent le même résultat au bit
+/// près. Chaque bus somme ses canaux dans un ordre fixe (sa priorité,
+/// puis les ids : `ChannelConfig::sum_order`). C'est ce qui permet de
+/// figer un rendu dans un test ("golden").
+pub fn render_offline(
+    profile: &Profile,
+    inputs: &HashMap<ChannelId, Vec<f32>>,
+    blocks: usize,
+    settings: RenderSettings,
+) -> HashMap<ChannelId, Vec<f32>> {
+    render_offline_metered(profile, inputs, blocks, settings, &RouteMeter::new(0))
+}
+
+/// `render_offline`, en relevant l'activité des routes dans `routes`
+/// (s'il est activé) tous les `ROUTE_METER_INTERVAL` blocs, comme le
+/// callback. L'heure d'une activité est sa position dans le rendu (ms
+/// depuis le début) : le rendu reste déterministe.
+pub fn render_offline_metered(
+    profile: &Profile,
+    inputs: &HashMap<ChannelId, Vec<f32>>,
+    blocks: usize,
+    settings: RenderSettings,
+    routes: &RouteMeter,
+) -> HashMap<ChannelId, Vec<f32>> {
+    let mixer = Mixer::from_config(profile.mixer.clone());
+    let state = MixerRuntimeState::from_mixer(&mixer);
+    let sample_rate = settings.sample_rate as f32;
+    let frames = settings.block_frames.max(1);
+
+    let mut buses: Vec<ChannelId> = mixer.outputs().iter().map(|c| c.id).collect();
+    buses.sort_by_key(|id| id.0);
+    // Effets de chaque bus, rangés comme `buses`
+    let mut bus_effects: Vec<Option<BusEffects>> = buses
+        .iter()
+        .map(|&bus| {
+            let preset = profile.bus_effects.get(&mixer.channel(bus)?.name)?;
+            if state.passthrough() {
+                return None;
+            }
+            let mut effects = BusEffects::new();
+            effects.load(Some(bus), Some(preset), sample_rate);
+            Some(effects)
+        })
+        .collect();
+
+    /// Un côté d'une tranche : son signal mono et son traitement.
+    struct Side<'a> {
+        signal: &'a [f32],
+        conditioner: InputConditioner,
+        phase_invert: bool,
+        effects: Option<EffectsChain>,
+        delay: DelayLine,
+    }
+    /// Une tranche mono (un côté, envoyé à gauche et à droite) ou une
+    /// paire stéréo liée (L = canal de gauche, R = canal de droite).
+    struct Strip<'a> {
+        id: ChannelId,
+        sides: Vec<Side<'a>>,
+        /// Bloc en cours de chaque côté, avant et après le retard
+        pre_fader: Vec<Vec<f32>>,
+        post_delay: Vec<Vec<f32>>,
+    }
+    let mut strips: Vec<Strip> = mixer
+        .inputs()
+        .iter()
+        // Le côté droit d'un lien est joué par la tranche de sa gauche
+        .filter(|c| mixer.link_of(c.id).is_none_or(|l| l.left == c.id))
+        .map(|c| {
+            // Toute la paire suit la gauche : effets, retard, routes, gain
+            let side = |id: ChannelId| {
+                // Passthrough d'urgence : rendu sans effets, comme en direct
+                let effects = (c.id == PIPELINE_INPUT_CHANNEL && !state.passthrough()).then(|| {
+                    let mut chain = EffectsChain::from_preset(&profile.effects);
+                    chain.set_sample_rate(sample_rate);
+                    chain
+                });
+                let mut delay = DelayLine::new(sample_rate);
+                delay.set_delay_ms(state.delay_ms(c.id));
+                // Polarité et DC : propres à chaque micro, même lié
+                let (phase_invert, dc_block) = state.input_options(id);
+                let mut conditioner = InputConditioner::new(sample_rate);
+                conditioner.set_options(dc_block);
+                Side {
+                    signal: inputs.get(&id).map_or(&[], Vec::as_slice),
+                    conditioner,
+                    phase_invert,
+                    effects,
+                    delay,
+                }
+            };
+            let sides: Vec<Side> = std::iter::once(c.id)
+                .chain(mixer.link_partner(c.id))
+                .map(side)
+                .collect();
+            let count = sides.len();
+            Strip {
+                id: c.id,
+                sides,
+                pre_fader: vec![vec![0.0; frames]; count],
+                post_delay: vec![vec![0.0; frames]; count],
+            }
+        })
+        .collect();
+    strips.sort_by_key(|s| s.id.0);
+
+    /// Envoi d'une tranche dans un bus : (index de la tranche, gain L,
+    /// gain R, retour pré-fader).
+    type BusSend = (usize, f32, f32, f32);
+    // Calculés une fois, rangés dans l'ordre de somme de chaque bus
+    // (`ChannelConfig::sum_order`) : l'état ne bouge pas en cours de
+    // rendu, et la boucle par bloc ne cherche plus les routes.
+    let ids: Vec<ChannelId> = strips.iter().map(|s| s.id).collect();
+    let sends: Vec<Vec<BusSend>> = buses
+        .iter()
+        .map(|&bus| {
+            let order = mixer
+                .channel(bus)
+                .map_or_else(|| ids.clone(), |config| config.sum_order(&ids));
+            order
+                .iter()
+                .filter_map(|id| {
+                    let index = ids.iter().position(|s| s == id)?;
+                    // Route directe ou à travers des sous-mix
+                    let (gain_l, gain_r) = state.routed_gain(*id, bus);
+                    let monitor = state.monitor_gain(*id, bus);
+                    let silent = gain_l == 0.0 && gain_r == 0.0 && monitor == 0.0;
+                    (!silent).then_some((index, gain_l, gain_r, monitor))
+                })
+                .collect()
+        })
+        .collect();
+
+    // Sorties rangées comme `buses`, à leur taille finale dès le départ
+    let mut outputs: Vec<Vec<f32>> = vec![vec![0.0; blocks * frames * 2]; buses.len()];
+    let mut sampler = RouteSampler::default();
+
+    for block in 0..blocks {
+        let start = block * frames;
+        let range = start * 2..(start + frames) * 2;
+        let measured = sampler.tick(routes);
+        let block_end_ms = ((start + frames) as u64 * 1000) / settings.sample_rate.max(1) as u64;
+        for strip in &mut strips {
+            let sides = strip
+                .sides
+                .iter_mut()
+                .zip(&mut strip.pre_fader)
+                .zip(&mut strip.post_delay);
+            for ((side, block), post_delay) in sides {
+                for (i, sample) in block.iter_mut().enumerate() {
+                    let raw = side.signal.get(start + i).copied().unwrap_or(0.0);
+                    *sample = side.conditioner.process_sample(raw, side.phase_invert);
+                }
+                // Comme en direct : un étage qui panique est sauté
+                if let Some(chain) = side.effects.as_mut()
+                    && let Some(panic) = chain.process_block(block)
+                {
+                    warn!(
+                        "Effect '{}' panicked while rendering {:?}: {}",
+                        panic.name, strip.id, panic.message
+                    );
+                }
+                for (out, &sample) in post_delay.iter_mut().zip(block.iter()) {
+                    *out = side.delay.process_sample(sample);
+                }
+            }
+        }
+
+        for ((&bus, output), sends) in buses.iter().zip(&mut outputs).zip(&sends) {
+            let output = &mut output[range.clone()];
+            for &(index, gain_l, gain_r, monitor) in sends {
+                let strip = &strips[index];
+                // Mono : le même côté des deux côtés
+                let (l, r) = (0, strip.sides.len() - 1);
+                let (pre_fader, post_delay) = (&strip.pre_fader, &strip.post_delay);
+                let mut contribution = 0.0_f32;
+                for (i, frame) in output.chunks_exact_mut(2).enumerate() {
+                    let left = post_delay[l][i] * gain_l + pre_fader[l][i] * monitor;
+                    let right = post_delay[r][i] * gain_r + pre_fader[r][i] * monitor;
+                    frame[0] += left;
+                    frame[1] += right;
+                    if measured {
+                        contribution = contribution.max(left.abs()).max(right.abs());
+                    }
+                }
+                if measured {
+                    routes.record(strip.id, bus, contribution, block_end_ms);
+                }
+            }
+        }
+
+        for ((&bus, output), effects) in buses.iter().zip(&mut outputs).zip(&mut bus_effects) {
+            let bus_block = &mut output[range.clone()];
+            fold_to_bus(bus_block, state.bus_channel_count(bus));
+            let mut process = |point, block: &mut [f32]| {
+                if let Some(panic) = effects.as_mut().and_then(|e| e.process(point, block)) {
+                    warn!(
+                        "Effect '{}' panicked while rendering bus {:?}: {}",
+                        panic.name, bus, panic.message
+                    );
+                }
+            };
+            process(InsertPoint::PreFader, bus_block);
+            let gain = state.bus_gain(bus);
+            bus_block.iter_mut().for_each(|s| *s *= gain);
+            process(InsertPoint::PostFader, bus_block);
+            state.dispatch_to_device(&[bus], bus_block);
+        }
+    }
+    buses.into_iter().zip(outputs).collect()
+}
+
+/// Temps CPU des effets d'un profil, hors ligne : `troubadour
+/// dsp-profile`.
+///
+/// Les chaînes du Mic et de chaque bus à effets traitent `blocks` blocs
+/// d'une voix de synthèse (sinus et bruit, avec un passage calme pour
+/// le gate), chronométrées comme dans le callback. Contrairement au
+/// rendu, le résultat dépend de la machine et de sa charge.
+pub fn profile_effects(profile: &Profile, settings: RenderSettings, blocks: usize) -> DspProfile {
+    let sample_rate = settings.sample_rate as f32;
+    let frames = settings.block_frames.max(1);
+    let block_budget_us = frames as f32 / sample_rate.max(1.0) * 1e6;
+    // Voix de synthèse : deux sinus et un bruit (LCG), calme un bloc sur 4
+    let mut seed = 0x2545_f491_u32;
+    let mut voice = |n: usize| {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let noise = (seed >> 8) as f32 / (1 << 24) as f32 - 0.5;
+        let t = n as f32 / sample_rate;
+        let level = if (n / frames) % 4 == 3 { 0.002 } else { 0.3 };
+        level
+            * ((std::f32::consts::TAU * 220.0 * t).sin()
+                + 0.5 * (std::f32::consts::TAU * 3_300.0 * t).sin()
+                + 0.3 * noise)
+    };
+
+    let mut stages = Vec::new();
+    let mut mic = EffectsChain::from_preset(&profile.effects);
+    mic.set_sample_rate(sample_rate);
+    mic.set_profiling(true);
+    let mut buses: Vec<(ChannelId, BusEffects)> = profile
+        .mixer
+        .channels
+        .iter()
+        .filter(|c| c.kind == ChannelKind::Output)
+        .filter_map(|bus| {
+            let preset = profile.bus_effects.get(&bus.name)?;
+            let mut effects = BusEffects::new();
+            effects.set_profiling(true);
+            effects.load(Some(bus.id), Some(preset), sample_rate);
+            Some((bus.id, effects))
+        })
+        .collect();
+
+    let mut mono = vec![0.0; frames];
+    let mut stereo = vec![0.0; frames * 2];
+    for block in 0..blocks {
+        for (i, sample) in mono.iter_mut().enumerate() {
+            *sample = voice(block * frames + i);
+        }
+        mic.process_block(&mut mono);
+        for (_, effects) in &mut buses {
+            for (frame, &sample) in stereo.chunks_exact_mut(2).zip(&mono) {
+                frame.fill(sample);
+            }
+            effects.process(InsertPoint::PreFader, &mut stereo);
+            effects.process(InsertPoint::PostFader, &mut stereo);
+        }
+    }
+
+    if let Some(micros) = mic.stage_micros() {
+        for (index, (proc, &micros)) in mic.processors().zip(micros).enumerate() {
+            stages.push(EffectTiming::new(
+                PIPELINE_INPUT_CHANNEL,
+                index,
+                proc.name(),
+                micros,
+                block_budget_us,
+            ));
+        }
+    }
+    for (bus, effects) in &buses {
+        for (index, (name, micros)) in effects.stage_micros().into_iter().enumerate() {
+            stages.push(EffectTiming::new(
+                *bus,
+                index,
+                name,
+                micros,
+                block_budget_us,
+            ));
+        }
+    }
+    DspProfile {
+        enabled: true,
+        block_budget_us,
+        stages,
+    }
+}
+
+/// Canal désigné par son nom (sans tenir compte de la casse) ou son id.
+fn find_channel<'a>(profile: &'a Profile, key: &str) -> Option<&'a ChannelConfig> {
+    let channels = &profile.mixer.channels;
+    channels
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(key))
+        .or_else(|| {
+            let id = key.parse().ok()?;
+            channels.iter().find(|c| c.id == ChannelId(id))
+        })
+}
+
+fn resolve(profile: &Profile, key: &str, kind: ChannelKind) -> TroubadourResult<ChannelId> {
+    let channel = find_channel(profile, key).ok_or_else(|| {
+        MixerError::InvalidParameter(format!("no channel named \"{key}\" in {}", profile.name))
+    })?;
+    match (channel.kind, kind) {
+        (ChannelKind::Input, ChannelKind::Input) | (ChannelKind::Output, ChannelKind::Output) => {
+            Ok(channel.id)
+        }
+        (_, ChannelKind::Output) => Err(MixerError::BusNotFound(channel.id.0).into()),
+        _ => Err(MixerError::InvalidParameter(format!("\"{key}\" is not an input channel")).into()),
+    }
+}
+
+/// Rendu de fichiers WAV : `troubadour render`.
+///
+/// `inputs` et `outputs` associent un canal (nom ou id) à un fichier.
+/// Les entrées sont mixées en mono et doivent partager le même sample
+/// rate, qui devient celui du rendu. Chaque bus demandé est écrit avec
+/// son nombre de canaux (mono ou stéréo), à la longueur de l'entrée la
+/// plus longue. Retourne le nombre de frames rendues.
+pub fn render_files(
+    profile: &Profile,
+    inputs: &[(String, PathBuf)],
+    outputs: &[(String, PathBuf)],
+) -> TroubadourResult<usize> {
+    let mut settings = RenderSettings::default();
+    let mut signals = HashMap::new();
+    for (index, (key, path)) in inputs.iter().enumerate() {
+        let id = resolve(profile, key, ChannelKind::Input)?;
+        let audio = wav::read_wav(path)?;
+        if index == 0 {
+            settings.sample_rate = audio.sample_rate;
+        } else if audio.sample_rate != settings.sample_rate {
+            return Err(TroubadourError::AudioFile(format!(
+                "{}: {} Hz, expected {} Hz like the other inputs",
+                path.display(),
+                audio.sample_rate,
+                settings.sample_rate
+            )));
+        }
+        signals.insert(id, audio.to_mono());
+    }
+    let targets = outputs
+        .iter()
+        .map(|(key, path)| {
+            let bus = resolve(profile, key, ChannelKind::Output)?;
+            let count =
+                find_channel(profile, key).map_or_else(Default::default, |c| c.channel_count);
+            Ok((bus, count, path))
+        })
+        .collect::<TroubadourResult<Vec<_>>>()?;
+
+    let frames = signals.values().map(Vec::len).max().unwrap_or(0);
+    let blocks = frames.div_ceil(settings.block_frames);
+    let rendered = render_offline(profile, &signals, blocks, settings);
+    for (bus, count, path) in targets {
+        let samples = &rendered[&bus][..frames * 2];
+        match count {
+            // L = R après le repli : un seul côté suffit
+            ChannelCount::Mono => {
+                let mono: Vec<f32> = samples.iter().step_by(2).copied().collect();
+                wav::write_wav(path, settings.sample_rate, 1, &mono)?;
+            }
+            ChannelCount::Stereo => wav::write_wav(path, settings.sample_rate, 2, samples)?,
+        }
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alloc_count::count_allocations;
+    use crate::route_meter::ROUTE_METER_INTERVAL;
+    use troubadour_shared::dsp::EffectsPreset;
+    use troubadour_shared::mixer::{ChannelLink, Route};
+
+    /// Voix de test : deux sinus et un bruit pseudo-aléatoire (LCG), avec
+    /// un passage silencieux pour faire travailler le gate.
+    fn test_signal(frames: usize) -> Vec<f32> {
+        let mut seed: u32 = 0x1234_5678;
+        (0..frames)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let noise = (seed >> 8) as f32 / (1u32 << 24) as f32 - 0.5;
+                let t = i as f32 / 48_000.0;
+                let tone = 0.4 * (std::f32::consts::TAU * 220.0 * t).sin()
+                    + 0.2 * (std::f32::consts::TAU * 3_000.0 * t).sin();
+                if (i / 4_800) % 4 == 3 {
+                    0.001 * noise
+                } else {
+                    tone + 0.05 * noise
+                }
+            })
+            .collect()
+    }
+
+    /// Empreinte FNV-1a des samples quantifiés en 16 bits : un écart
+    /// sous le LSB (ordre des opérations flottantes d'une plateforme à
+    /// l'autre) ne casse pas le test, un vrai changement du DSP si.
+    fn fingerprint(samples: &[f32]) -> u64 {
+        samples.iter().fold(0xcbf2_9ce4_8422_2325, |hash, s| {
+            let q = (s.clamp(-1.0, 1.0) * 32_767.0).round() as i16;
+            q.to_le_bytes()
+                .iter()
+                .fold(hash, |h, b| (h ^ *b as u64).wrapping_mul(0x0100_0000_01b3))
+        })
+    }
+
+    #[test]
+    fn golden_render_of_default_profile() {
+        let mut profile = Profile::default_profile();
+        profile.effects = EffectsPreset::default_preset();
+        let inputs = HashMap::from([
+            (ChannelId(0), test_signal(48_000)),
+            (ChannelId(1), test_signal(24_000)),
+        ]);
+
+        let rendered = render_offline(&profile, &inputs, 188, RenderSettings::default());
+        let headphones = &rendered[&ChannelId(3)];
+        assert_eq!(headphones.len(), 188 * 256 * 2);
+        assert!(headphones.iter().any(|s| s.abs() > 0.1));
+        // Speakers : rien n'y est routé
+        assert!(rendered[&ChannelId(4)].iter().all(|s| *s == 0.0));
+
+        // Si ce test échoue après un changement VOULU du DSP, réécouter
+        // le rendu (`troubadour render`) puis mettre l'empreinte à jour.
+        assert_eq!(fingerprint(headphones), GOLDEN_HEADPHONES);
+    }
+
+    const GOLDEN_HEADPHONES: u64 = 14_790_547_190_681_335_985;
+
+    #[test]
+    fn render_is_deterministic_and_follows_the_mix() {
+        let mut profile = Profile::default_profile();
+        let inputs = HashMap::from([(ChannelId(1), vec![0.5; 1024])]);
+        let first = render_offline(&profile, &inputs, 4, RenderSettings::default());
+        assert_eq!(
+            first,
+            render_offline(&profile, &inputs, 4, RenderSettings::default())
+        );
+
+        // Desktop (sans effets) → Headphones, pan au centre
+        let (gain_l, gain_r) =
+            Mixer::from_config(profile.mixer.clone()).effective_gain(ChannelId(1));
+        let frame = &first[&ChannelId(3)][200..202];
+        assert!((frame[0] - 0.5 * gain_l).abs() < 1e-6);
+        assert!((frame[1] - 0.5 * gain_r).abs() < 1e-6);
+
+        // Bus muted → silence
+        profile
+            .mixer
+            .channels
+            .iter_mut()
+            .find(|c| c.id == ChannelId(3))
+            .unwrap()
+            .muted = true;
+        let muted = render_offline(&profile, &inputs, 4, RenderSettings::default());
+        assert!(muted[&ChannelId(3)].iter().all(|s| *s == 0.0));
+    }
+
+    /// Headphones : un canal fort (Browser) entre deux canaux si faibles
+    /// que chacun, seul, disparaît dans l'arrondi de la somme.
+    fn rounding_mix(priority: &[ChannelId]) -> (Profile, HashMap<ChannelId, Vec<f32>>) {
+        let mut profile = Profile::default_profile();
+        profile
+            .mixer
+            .channels
+            .push(ChannelConfig::input(5, "Guest"));
+        profile
+            .mixer
+            .routes
+            .push(Route::new(ChannelId(5), ChannelId(3)));
+        let mut mixer = Mixer::from_config(profile.mixer.clone());
+        mixer.set_sum_priority(ChannelId(3), priority).unwrap();
+        profile.mixer = mixer.to_config();
+
+        let (gain, _) = mixer.effective_gain(ChannelId(2));
+        let loud = 0.5 * gain;
+        let ulp = f32::from_bits(loud.to_bits() + 1) - loud;
+        // 0.4 ULP : absorbé seul, deux ensemble font monter la somme
+        let quiet = 0.4 * ulp / gain;
+        let inputs = HashMap::from([
+            (ChannelId(1), vec![quiet; 512]),
+            (ChannelId(2), vec![0.5; 512]),
+            (ChannelId(5), vec![quiet; 512]),
+        ]);
+        (profile, inputs)
+    }
+
+    #[test]
+    fn buses_sum_in_a_fixed_order_led_by_their_priority() {
+        let left = |priority: &[ChannelId]| {
+            let (profile, inputs) = rounding_mix(priority);
+            let first = render_offline(&profile, &inputs, 2, RenderSettings::default());
+            // Deux rendus, même résultat au bit près
+            assert_eq!(
+                first,
+                render_offline(&profile, &inputs, 2, RenderSettings::default())
+            );
+            first[&ChannelId(3)][200]
+        };
+        let (profile, mut alone) = rounding_mix(&[]);
+        alone.retain(|id, _| *id == ChannelId(2));
+        let rendered = render_offline(&profile, &alone, 2, RenderSettings::default());
+        let loud = rendered[&ChannelId(3)][200];
+
+        // Ordre d'affichage (1, 2, 5) : chaque canal faible arrive seul
+        // sur le fort et se perd ; le fort en premier, pareil
+        assert_eq!(left(&[]), loud);
+        assert_eq!(left(&[ChannelId(2)]), loud);
+        // Les faibles d'abord : leur somme passe dans le bus
+        assert_eq!(
+            left(&[ChannelId(1), ChannelId(5)]),
+            f32::from_bits(loud.to_bits() + 1)
+        );
+    }
+
+    #[test]
+    fn inputs_of_any_length_are_summed_in_full() {
+        let profile = Profile::default_profile();
+        let (gain_l, _) = Mixer::from_config(profile.mixer.clone()).effective_gain(ChannelId(1));
+        for block_frames in [1, 3, 511, 512, 513, 4096] {
+            for len in [0, 1, 3, 511, 512, 513, 4096] {
+                // Deux entrées de longueurs différentes vers le même bus
+                let inputs = HashMap::from([
+                    (ChannelId(0), test_signal(len / 3)),
+                    (ChannelId(1), vec![0.5; len]),
+                ]);
+                let blocks = len.div_ceil(block_frames);
+                let settings = RenderSettings {
+                    block_frames,
+                    ..RenderSettings::default()
+                };
+                let rendered = render_offline(&profile, &inputs, blocks, settings);
+                for output in rendered.values() {
+                    assert_eq!(output.len(), blocks * block_frames * 2);
+                }
+                // La dernière frame de l'entrée la plus longue est là
+                if len > 0 {
+                    let last = rendered[&ChannelId(3)][(len - 1) * 2];
+                    assert!(
+                        (last - 0.5 * gain_l).abs() < 1e-6,
+                        "{len} frames in blocks of {block_frames}: {last}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hard_left_channel_reaches_mono_bus_at_minus_3_db() {
+        let mut profile = Profile::default_profile();
+        for channel in &mut profile.mixer.channels {
+            match channel.id {
+                ChannelId(1) => channel.pan = -1.0,
+                ChannelId(3) => channel.channel_count = ChannelCount::Mono,
+                _ => {}
+            }
+        }
+        let inputs = HashMap::from([(ChannelId(1), vec![0.5; 1024])]);
+        let rendered = render_offline(&profile, &inputs, 4, RenderSettings::default());
+
+        let (gain_l, gain_r) =
+            Mixer::from_config(profile.mixer.clone()).effective_gain(ChannelId(1));
+        assert_eq!(gain_r, 0.0);
+        let expected = 0.5 * gain_l * std::f32::consts::FRAC_1_SQRT_2;
+        let frame = &rendered[&ChannelId(3)][200..202];
+        assert!((frame[0] - expected).abs() < 1e-6);
+        assert_eq!(frame[0], frame[1]);
+    }
+
+    #[test]
+    fn linked_pair_plays_left_in_l_and_right_in_r() {
+        let mut profile = Profile::default_profile();
+        profile
+            .mixer
+            .links
+            .push(ChannelLink::new(ChannelId(1), ChannelId(2)));
+        // Desktop (L) et Browser (R) routés vers Headphones
+        let inputs = HashMap::from([
+            (ChannelId(1), vec![0.5; 1024]),
+            (ChannelId(2), vec![-0.25; 1024]),
+        ]);
+        let rendered = render_offline(&profile, &inputs, 4, RenderSettings::default());
+
+        let (gain_l, gain_r) =
+            Mixer::from_config(profile.mixer.clone()).effective_gain(ChannelId(1));
+        let frame = &rendered[&ChannelId(3)][200..202];
+        // Chaque côté ne reçoit que son canal, et une seule fois
+        assert!((frame[0] - 0.5 * gain_l).abs() < 1e-6);
+        assert!((frame[1] + 0.25 * gain_r).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bus_effects_are_rendered_like_the_callback() {
+        let mut profile = Profile::default_profile();
+        let mut gate = troubadour_shared::dsp::EffectsPreset::clean();
+        gate.noise_gate.enabled = true;
+        gate.noise_gate.threshold = 1.0;
+        profile.bus_effects.insert("Headphones".into(), gate);
+        let inputs = HashMap::from([(ChannelId(1), vec![0.1; 48_000])]);
+
+        let dry = render_offline(&Profile::default_profile(), &inputs, 40, Default::default());
+        assert!(dry[&ChannelId(3)].last().unwrap().abs() > 1e-3);
+        // Gate du bus fermé : Headphones se tait
+        let rendered = render_offline(&profile, &inputs, 40, RenderSettings::default());
+        let last = rendered[&ChannelId(3)].last().copied().unwrap();
+        assert!(last.abs() < 1e-4, "{last}");
+    }
+
+    #[test]
+    fn input_options_invert_and_remove_dc_before_the_fader() {
+        let mut profile = Profile::default_profile();
+        for channel in &mut profile.mixer.channels {
+            match channel.id.0 {
+                1 => channel.phase_invert = true,
+                2 => channel.dc_block = true,
+                _ => {}
+            }
+        }
+        // Desktop : signal inversé ; Browser : un DC pur, qui disparaît
+        let frames = 48_000 / 4;
+        let inputs = HashMap::from([
+            (ChannelId(1), vec![0.5; frames]),
+            (ChannelId(2), vec![0.1; frames]),
+        ]);
+        let settings = RenderSettings::default();
+        let blocks = frames / settings.block_frames;
+        let rendered = render_offline(&profile, &inputs, blocks, settings);
+
+        let (gain_l, _) = Mixer::from_config(profile.mixer.clone()).effective_gain(ChannelId(1));
+        let out = &rendered[&ChannelId(3)];
+        let last = out[out.len() - 2];
+        assert!((last + 0.5 * gain_l).abs() < 1e-3, "got {last}");
+    }
+
+    #[test]
+    fn muted_channel_shows_no_route_activity() {
+        let mut profile = Profile::default_profile();
+        for channel in &mut profile.mixer.channels {
+            if channel.id == ChannelId(1) {
+                channel.muted = true;
+            }
+        }
+        // Desktop (muted) et Browser, routés tous deux vers Headphones
+        let inputs = HashMap::from([
+            (ChannelId(1), test_signal(4096)),
+            (ChannelId(2), test_signal(4096)),
+        ]);
+        let routes = RouteMeter::new(8);
+        routes.set_enabled(true);
+        let blocks = 2 * ROUTE_METER_INTERVAL as usize;
+        render_offline_metered(
+            &profile,
+            &inputs,
+            blocks,
+            RenderSettings::default(),
+            &routes,
+        );
+
+        let activity = routes.activity(&profile.mixer.routes);
+        assert_eq!(activity.len(), 1);
+        assert_eq!(
+            (activity[0].from, activity[0].to),
+            (ChannelId(2), ChannelId(3))
+        );
+        assert!(activity[0].peak > 0.0);
+        // Deuxième mesure : fin du 16e bloc de 256 frames à 48 kHz
+        assert_eq!(activity[0].last_seen_ms, 85);
+
+        // Mesure coupée : rien n'est relevé
+        let routes = RouteMeter::new(8);
+        render_offline_metered(
+            &profile,
+            &inputs,
+            blocks,
+            RenderSettings::default(),
+            &routes,
+        );
+        assert!(routes.activity(&profile.mixer.routes).is_empty());
+    }
+
+    /// Session pleine (`DEFAULT_MAX_CHANNELS`) : 62 entrées vers 2 bus.
+    /// C'est le cas que la boucle par bloc doit tenir en temps réel
+    /// (5,3 ms par bloc de 256 frames à 48 kHz).
+    #[test]
+    fn full_session_sums_every_channel() {
+        use troubadour_shared::mixer::{DEFAULT_MAX_CHANNELS, MixerConfig};
+
+        let buses = [ChannelId(62), ChannelId(63)];
+        let mut mixer = MixerConfig::default();
+        for id in 0..DEFAULT_MAX_CHANNELS - buses.len() {
+            mixer
+                .channels
+                .push(ChannelConfig::input(id, format!("In {id}")));
+            mixer.routes.push(Route::new(ChannelId(id), buses[id % 2]));
+        }
+        for bus in buses {
+            mixer
+                .channels
+                .push(ChannelConfig::output(bus.0, format!("Bus {}", bus.0)));
+        }
+        let profile = Profile {
+            mixer,
+            ..Profile::default_profile()
+        };
+        let inputs = (1..DEFAULT_MAX_CHANNELS - buses.len())
+            .map(|id| (ChannelId(id), vec![0.01; 512]))
+            .collect();
+        let rendered = render_offline(&profile, &inputs, 2, RenderSettings::default());
+
+        let (gain_l, _) = Mixer::from_config(profile.mixer.clone()).effective_gain(ChannelId(1));
+        // Bus 63 : les 31 entrées impaires, toutes alimentées
+        let expected = 31.0 * 0.01 * gain_l;
+        assert!((rendered[&ChannelId(63)][100] - expected).abs() < 1e-4);
+        assert_eq!(rendered.len(), 2);
+    }
+
+    #[test]
+    fn inputs_reach_a_bus_through_a_submix() {
+        let mut profile = Profile::default_profile();
+        let mixer = &mut profile.mixer;
+        let mut submix = ChannelConfig::submix(5, "Music");
+        submix.volume = 0.5;
+        mixer.channels.push(submix);
+        mixer.routes.retain(|r| r.from != ChannelId(1));
+        mixer.routes.push(Route::new(ChannelId(1), ChannelId(5)));
+        mixer.routes.push(Route::new(ChannelId(5), ChannelId(3)));
+        let inputs = HashMap::from([(ChannelId(1), vec![0.5; 1024])]);
+        let rendered = render_offline(&profile, &inputs, 2, RenderSettings::default());
+
+        let mixer = Mixer::from_config(profile.mixer.clone());
+        let (gain_l, _) = mixer.effective_gain(ChannelId(1));
+        let (submix_l, _) = mixer.effective_gain(ChannelId(5));
+        let expected = 0.5 * gain_l * submix_l;
+        assert!((rendered[&ChannelId(3)][100] - expected).abs() < 1e-6);
+        // Le sous-mix n'est pas un bus : pas de sortie à lui
+        assert!(!rendered.contains_key(&ChannelId(5)));
+    }
+
+    #[test]
+    fn offline_mix_allocates_nothing_per_block() {
+        use troubadour_shared::mixer::MixerConfig;
+
+        let mut mixer = MixerConfig::default();
+        for id in 0..16 {
+            mixer
+                .channels
+                .push(ChannelConfig::input(id, format!("In {id}")));
+            mixer.routes.push(Route::new(ChannelId(id), ChannelId(16)));
+        }
+        mixer.channels.push(ChannelConfig::output(16, "Bus"));
+        let profile = Profile {
+            mixer,
+            effects: EffectsPreset::default_preset(),
+            ..Profile::default_profile()
+        };
+        let inputs = (0..16)
+            .map(|id| (ChannelId(id), test_signal(64 * 512)))
+            .collect();
+        let settings = RenderSettings {
+            block_frames: 512,
+            ..RenderSettings::default()
+        };
+        let render = |blocks| {
+            count_allocations(|| drop(render_offline(&profile, &inputs, blocks, settings)))
+        };
+        render(1);
+        // Préparation identique, quel que soit le nombre de blocs
+        assert_eq!(render(8), render(64));
+    }
+
+    #[test]
+    fn render_files_writes_requested_buses() {
+        let dir = std::env::temp_dir().join(format!("troubadour-render-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("voice.wav");
+        let output = dir.join("out.wav");
+        wav::write_wav(&input, 44_100, 1, &test_signal(1000)).unwrap();
+
+        let profile = Profile::default_profile();
+        let frames = render_files(
+            &profile,
+            &[("mic".into(), input.clone())],
+            &[("Headphones".into(), output.clone())],
+        )
+        .unwrap();
+        assert_eq!(frames, 1000);
+        let audio = wav::read_wav(&output).unwrap();
+        assert_eq!(
+            (audio.sample_rate, audio.channels, audio.frames()),
+            (44_100, 2, 1000)
+        );
+
+        // Un canal inconnu, ou une entrée désignée comme bus de sortie
+        assert!(render_files(&profile, &[("Guitar".into(), input.clone())], &[]).is_err());
+        assert!(matches!(
+            render_files(&profile, &[], &[("Mic".into(), output.clone())]),
+            Err(TroubadourError::Mixer(MixerError::BusNotFound(0)))
+        ));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
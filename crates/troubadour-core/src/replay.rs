@@ -0,0 +1,282 @@
+//! Enregistrement rétroactif : les dernières secondes d'un bus, gardées
+//! en mémoire pour être sauvées après coup ("garde les 30 dernières
+//! secondes").
+//!
+//! ```text
+//!  bus joué ──► tap ──file bornée──► thread replay ──► ReplayRing
+//!  (callback)                        (copie les blocs)     │
+//!                         save_replay : copie de la fin ◄──┘──► WAV (thread recording)
+//! ```
+//!
+//! Le callback ne voit qu'un tap de plus : il ne touche jamais l'anneau.
+//! Le thread du replay vide le tap dans l'anneau, alloué une fois pour
+//! `seconds` de stéréo f32 (30 s à 48 kHz ≈ 11 Mo) ; les samples les
+//! plus anciens sont écrasés. Un changement de sample rate vide l'anneau :
+//! un fichier n'a qu'un rate.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::RecvTimeoutError;
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::recording::ReplayInfo;
+
+use crate::tap::TapReader;
+
+/// Plafond de la durée gardée : 5 min de stéréo à 192 kHz ≈ 440 Mo.
+pub const MAX_REPLAY_SECONDS: u32 = 300;
+
+/// Blocs d'avance du tap d'un replay : le thread lit en continu, la
+/// marge ne sert qu'aux ralentissements de la machine.
+pub const REPLAY_TAP_BLOCKS: usize = 64;
+
+/// Attente maximale du thread entre deux vérifications de l'arrêt.
+const REPLAY_POLL: Duration = Duration::from_millis(20);
+
+/// Dernières secondes d'un flux stéréo entrelacé, en mémoire bornée.
+#[derive(Debug)]
+pub struct ReplayRing {
+    samples: Vec<f32>,
+    /// Prochain sample écrit
+    write: usize,
+    /// Samples valides (au plus `samples.len()`)
+    len: usize,
+    sample_rate: u32,
+}
+
+impl ReplayRing {
+    /// Anneau de `seconds` à `sample_rate`, alloué d'un coup.
+    pub fn new(seconds: u32, sample_rate: u32) -> Self {
+        let frames = (seconds.clamp(1, MAX_REPLAY_SECONDS) as usize) * sample_rate.max(1) as usize;
+        Self {
+            samples: vec![0.0; frames * 2],
+            write: 0,
+            len: 0,
+            sample_rate,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Frames gardées.
+    pub fn frames(&self) -> usize {
+        self.len / 2
+    }
+
+    pub fn capacity_frames(&self) -> usize {
+        self.samples.len() / 2
+    }
+
+    /// Ajoute un bloc stéréo entrelacé ; écrase les plus anciens samples
+    /// une fois l'anneau plein. N'alloue jamais.
+    pub fn push(&mut self, stereo: &[f32]) {
+        let capacity = self.samples.len();
+        // Un bloc plus long que l'anneau : seule sa fin compte
+        let stereo = &stereo[stereo.len().saturating_sub(capacity)..];
+        let first = stereo.len().min(capacity - self.write);
+        self.samples[self.write..self.write + first].copy_from_slice(&stereo[..first]);
+        self.samples[..stereo.len() - first].copy_from_slice(&stereo[first..]);
+        self.write = (self.write + stereo.len()) % capacity;
+        self.len = (self.len + stereo.len()).min(capacity);
+    }
+
+    /// Les `frames` dernières frames (moins si l'anneau n'en a pas
+    /// autant), de la plus ancienne à la plus récente.
+    pub fn latest(&self, frames: usize) -> Vec<f32> {
+        let count = frames.saturating_mul(2).min(self.len);
+        let capacity = self.samples.len();
+        let start = (self.write + capacity - count) % capacity;
+        let first = count.min(capacity - start);
+        let mut out = Vec::with_capacity(count);
+        out.extend_from_slice(&self.samples[start..start + first]);
+        out.extend_from_slice(&self.samples[..count - first]);
+        out
+    }
+
+    /// Vide l'anneau et le réalloue pour `sample_rate`.
+    fn restart(&mut self, sample_rate: u32) {
+        let seconds = self.capacity_frames() / self.sample_rate.max(1) as usize;
+        *self = Self::new(seconds as u32, sample_rate);
+    }
+}
+
+/// Replay d'un bus : le tap, l'anneau, et le thread qui les relie.
+/// Droppé, il arrête son thread et ferme son tap.
+pub struct ReplayBuffer {
+    bus: ChannelId,
+    /// Id de son tap, caché de la liste des taps
+    tap_id: u32,
+    seconds: u32,
+    ring: Arc<Mutex<ReplayRing>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReplayBuffer {
+    /// Garde les `seconds` dernières secondes de `reader`, à
+    /// `sample_rate` (le rate des blocs reçus l'emporte ensuite).
+    pub fn start(reader: TapReader, seconds: u32, sample_rate: u32) -> TroubadourResult<Self> {
+        let (bus, tap_id) = (reader.bus(), reader.id());
+        let seconds = seconds.clamp(1, MAX_REPLAY_SECONDS);
+        let ring = Arc::new(Mutex::new(ReplayRing::new(seconds, sample_rate)));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let (ring, running) = (ring.clone(), running.clone());
+            std::thread::Builder::new()
+                .name(format!("replay-{}", bus.0))
+                .spawn(move || feed(&reader, &ring, &running))
+                .map_err(|e| TroubadourError::StreamError(format!("replay thread: {e}")))?
+        };
+        Ok(Self {
+            bus,
+            tap_id,
+            seconds,
+            ring,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn bus(&self) -> ChannelId {
+        self.bus
+    }
+
+    pub fn tap_id(&self) -> u32 {
+        self.tap_id
+    }
+
+    pub fn info(&self) -> ReplayInfo {
+        let ring = lock(&self.ring);
+        let rate = ring.sample_rate().max(1) as f32;
+        ReplayInfo {
+            bus: self.bus,
+            seconds: self.seconds,
+            buffered_seconds: ring.frames() as f32 / rate,
+            sample_rate: ring.sample_rate(),
+        }
+    }
+
+    /// Copie des `seconds` dernières secondes, avec leur sample rate.
+    pub fn latest(&self, seconds: f32) -> (Vec<f32>, u32) {
+        let ring = lock(&self.ring);
+        let frames = (seconds.max(0.0) * ring.sample_rate() as f32).round() as usize;
+        (ring.latest(frames), ring.sample_rate())
+    }
+}
+
+impl Drop for ReplayBuffer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Un anneau empoisonné (panique pendant une copie) reste lisible : au
+/// pire un bloc à moitié écrit.
+fn lock(ring: &Mutex<ReplayRing>) -> MutexGuard<'_, ReplayRing> {
+    ring.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn feed(reader: &TapReader, ring: &Mutex<ReplayRing>, running: &AtomicBool) {
+    while running.load(Ordering::Relaxed) {
+        match reader.read_timeout(REPLAY_POLL) {
+            Ok(block) => {
+                let mut ring = lock(ring);
+                if block.sample_rate != ring.sample_rate() {
+                    ring.restart(block.sample_rate);
+                }
+                ring.push(&block.samples);
+                drop(ring);
+                reader.recycle(block);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    use crate::tap::tap_channel;
+    use crate::wav;
+
+    /// Rampe stéréo : la frame `n` vaut `n` à gauche, `-n` à droite.
+    fn ramp(from: usize, frames: usize) -> Vec<f32> {
+        (from..from + frames)
+            .flat_map(|n| [n as f32, -(n as f32)])
+            .collect()
+    }
+
+    #[test]
+    fn ring_keeps_exactly_the_latest_samples() {
+        // 2 s à 10 Hz : 20 frames
+        let mut ring = ReplayRing::new(2, 10);
+        assert_eq!(ring.capacity_frames(), 20);
+        ring.push(&ramp(0, 7));
+        assert_eq!(ring.frames(), 7);
+        assert_eq!(ring.latest(100), ramp(0, 7));
+
+        // Blocs de 7 frames : l'écriture fait le tour en plein bloc
+        for block in 1..10 {
+            ring.push(&ramp(block * 7, 7));
+        }
+        assert_eq!(ring.frames(), 20);
+        assert_eq!(ring.latest(20), ramp(50, 20));
+        assert_eq!(ring.latest(3), ramp(67, 3));
+        assert_eq!(ring.latest(0), Vec::<f32>::new());
+
+        // Bloc plus long que l'anneau : sa fin seulement
+        ring.push(&ramp(1000, 45));
+        assert_eq!(ring.latest(20), ramp(1025, 20));
+    }
+
+    #[test]
+    fn saving_five_seconds_gives_the_end_of_the_ramp() {
+        let dir = std::env::temp_dir().join(format!("troubadour-replay-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // File assez longue pour toute la rampe : rien n'est jeté même
+        // si le thread prend du retard
+        let (mut writer, reader) = tap_channel(0, ChannelId(3), 256);
+        // 30 s à 100 Hz ; le premier bloc arrive à 1000 Hz : l'anneau
+        // repart au rate des blocs
+        let replay = ReplayBuffer::start(reader, 30, 100).unwrap();
+        let rate = 1000;
+        // 42 s de rampe en blocs de 250 frames
+        let total = 42 * rate;
+        for start in (0..total).step_by(250) {
+            writer.write(&ramp(start, 250), rate as u32);
+        }
+        // Jusqu'à ce que la dernière frame soit arrivée dans l'anneau
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while replay.latest(0.001).0.first() != Some(&((total - 1) as f32)) {
+            assert!(Instant::now() < deadline, "{:?}", replay.info());
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let info = replay.info();
+        assert_eq!((info.seconds, info.sample_rate), (30, 1000));
+        assert_eq!(info.buffered_seconds, 30.0);
+        assert_eq!(writer.info().blocks_dropped, 0);
+
+        let (samples, sample_rate) = replay.latest(5.0);
+        let path = dir.join("clip.wav");
+        wav::write_wav(&path, sample_rate, 2, &samples).unwrap();
+        let clip = wav::read_wav(&path).unwrap();
+        assert_eq!((clip.sample_rate, clip.channels), (1000, 2));
+        assert_eq!(clip.samples, ramp(total - 5 * rate, 5 * rate));
+
+        drop(replay);
+        // Le thread parti, le tap est fermé
+        assert!(writer.is_closed());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
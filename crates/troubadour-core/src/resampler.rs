@@ -1,3 +1,5 @@
+use std::collections::VecDeque;
+
 use rubato::{FftFixedInOut, Resampler as _};
 use troubadour_shared::error::{TroubadourError, TroubadourResult};
 
@@ -20,12 +22,20 @@ use troubadour_shared::error::{TroubadourError, TroubadourResult};
 ///
 /// On doit convertir dans les deux sens. C'est un coût CPU, mais
 /// c'est nécessaire car les deux libs ont des conventions différentes.
+///
+/// Les buffers planar sont alloués une fois, à la création : `process_into`
+/// tourne dans le callback audio et n'alloue rien.
 pub struct AudioResampler {
     resampler: FftFixedInOut<f32>,
     channels: usize,
     /// Nombre de frames en entrée attendu par rubato à chaque appel.
     /// Une "frame" = 1 sample par canal (ex: 1 frame stéréo = 2 samples).
     input_frames: usize,
+    /// Min/max du chunk d'entrée précédent (voir `process_into`).
+    prev_bounds: (f32, f32),
+    /// Entrée et sortie de rubato, un buffer par canal
+    planar_in: Vec<Vec<f32>>,
+    planar_out: Vec<Vec<f32>>,
 }
 
 impl AudioResampler {
@@ -58,11 +68,15 @@ impl AudioResampler {
                 .map_err(|e| TroubadourError::StreamError(format!("Resampler init failed: {e}")))?;
 
         let input_frames = resampler.input_frames_max();
+        let output_frames = resampler.output_frames_max();
 
         Ok(Self {
             resampler,
             channels,
             input_frames,
+            prev_bounds: (0.0, 0.0),
+            planar_in: vec![vec![0.0; input_frames]; channels],
+            planar_out: vec![vec![0.0; output_frames]; channels],
         })
     }
 
@@ -76,7 +90,17 @@ impl AudioResampler {
         self.resampler.output_frames_max()
     }
 
-    /// Convertit un buffer interleaved d'un sample rate à un autre.
+    /// Convertit un buffer interleaved d'un sample rate à un autre
+    /// (`process_into` dans un nouveau Vec).
+    pub fn process(&mut self, interleaved_input: &[f32]) -> TroubadourResult<Vec<f32>> {
+        let mut output = Vec::with_capacity(self.output_frames() * self.channels);
+        self.process_into(interleaved_input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Convertit un chunk interleaved de `input_frames_required` frames
+    /// et ajoute le résultat, interleaved, à `output`. N'alloue pas (sauf
+    /// si `output` doit grandir).
     ///
     /// # Le flux de données
     /// ```text
@@ -88,21 +112,49 @@ impl AudioResampler {
     /// # `&mut self` — pourquoi mutable ?
     /// rubato maintient un état interne (filtres FFT, buffers).
     /// Chaque appel modifie cet état. D'où le `&mut`.
-    pub fn process(&mut self, interleaved_input: &[f32]) -> TroubadourResult<Vec<f32>> {
-        let frames = interleaved_input.len() / self.channels;
-
+    ///
+    /// # Pas d'overshoot
+    /// Un resampler à base de sinc/FFT peut dépasser légèrement les
+    /// valeurs de son entrée (oscillations de Gibbs) : un signal à 0 dBFS
+    /// deviendrait un "inter-sample over" qui sature le DAC.
+    /// La sortie est donc bornée au min/max de l'entrée. Comme rubato
+    /// a une latence interne (la sortie correspond en partie au chunk
+    /// précédent), on prend l'union des bornes des deux derniers chunks.
+    pub fn process_into(
+        &mut self,
+        interleaved_input: &[f32],
+        output: &mut impl Extend<f32>,
+    ) -> TroubadourResult<()> {
         // Étape 1 : Deinterleave (interleaved → planar)
-        let planar_input = Self::deinterleave(interleaved_input, self.channels, frames);
+        Self::deinterleave_into(interleaved_input, &mut self.planar_in);
 
-        // Étape 2 : Resampling
-        // `process()` retourne un Vec<Vec<f32>> (un Vec par canal)
-        let planar_output = self
+        // Étape 2 : Resampling, dans les buffers de sortie pré-alloués
+        let (_, frames) = self
             .resampler
-            .process(&planar_input, None)
+            .process_into_buffer(&self.planar_in, &mut self.planar_out, None)
             .map_err(|e| TroubadourError::StreamError(format!("Resampling failed: {e}")))?;
 
-        // Étape 3 : Interleave (planar → interleaved)
-        Ok(Self::interleave(&planar_output))
+        // Étape 3 : Borner la sortie (anti-overshoot)
+        let bounds = interleaved_input
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &s| (lo.min(s), hi.max(s)));
+        let bounds = if interleaved_input.is_empty() {
+            (0.0, 0.0)
+        } else {
+            bounds
+        };
+        let lo = bounds.0.min(self.prev_bounds.0);
+        let hi = bounds.1.max(self.prev_bounds.1);
+        for channel in &mut self.planar_out {
+            for sample in &mut channel[..frames] {
+                *sample = sample.clamp(lo, hi);
+            }
+        }
+        self.prev_bounds = bounds;
+
+        // Étape 4 : Interleave (planar → interleaved)
+        Self::interleave_into(&self.planar_out, frames, output);
+        Ok(())
     }
 
     /// Vérifie si le resampling est nécessaire (rates différents).
@@ -110,7 +162,8 @@ impl AudioResampler {
         from_rate == to_rate
     }
 
-    /// Convertit un buffer interleaved en format planar.
+    /// Convertit un buffer interleaved en format planar, dans `planar`
+    /// (un buffer par canal, déjà à la bonne taille).
     ///
     /// # Algorithme
     /// ```text
@@ -123,53 +176,307 @@ impl AudioResampler {
     /// `chunks` car le compilateur sait que chaque chunk a
     /// exactement `channels` éléments → il peut optimiser les
     /// bounds checks.
-    fn deinterleave(interleaved: &[f32], channels: usize, frames: usize) -> Vec<Vec<f32>> {
-        let mut planar = vec![Vec::with_capacity(frames); channels];
-
-        for frame in interleaved.chunks_exact(channels) {
-            for (ch, &sample) in frame.iter().enumerate() {
-                planar[ch].push(sample);
+    fn deinterleave_into(interleaved: &[f32], planar: &mut [Vec<f32>]) {
+        let channels = planar.len();
+        for (i, frame) in interleaved.chunks_exact(channels).enumerate() {
+            for (channel, &sample) in planar.iter_mut().zip(frame) {
+                channel[i] = sample;
             }
         }
-
-        planar
     }
 
-    /// Convertit un buffer planar en format interleaved.
+    /// Ajoute les `frames` premières frames d'un buffer planar à `out`,
+    /// en format interleaved.
     ///
     /// # L'inverse de deinterleave
     /// ```text
     /// Input:  [[L0, L1, L2], [R0, R1, R2]]
     /// Output: [L0, R0, L1, R1, L2, R2]
     /// ```
-    fn interleave(planar: &[Vec<f32>]) -> Vec<f32> {
-        if planar.is_empty() {
-            return Vec::new();
+    fn interleave_into(planar: &[Vec<f32>], frames: usize, out: &mut impl Extend<f32>) {
+        out.extend((0..frames).flat_map(|i| planar.iter().map(move |channel| channel[i])));
+    }
+}
+
+/// Pont entre le callback d'entrée et le callback de sortie.
+///
+/// # Le problème
+/// Les deux callbacks cpal ne sont pas synchronisés : l'entrée peut
+/// livrer 441 frames quand la sortie en demande 512, et les deux devices
+/// peuvent tourner à des sample rates différents (44.1k vs 48k).
+/// Copier "un buffer d'entrée = un buffer de sortie" perd des samples
+/// (trop long) ou insère des trous (trop court).
+///
+/// # La solution : deux files
+/// - `pending` : samples d'entrée en attente d'un chunk complet pour rubato
+/// - `ready` : samples déjà convertis, en attente du device de sortie
+///
+/// `fill` écrit EXACTEMENT ce qui est disponible (jamais de vieux samples
+/// restés dans un buffer), complète par du silence, et borne la sortie
+/// à [-1.0, 1.0] : dernière protection avant le DAC.
+///
+/// Les deux files sont allouées à la création (`pending` : un chunk,
+/// `ready` : `max_ready`, plus une frame pour `repeat_frame`) et ne
+/// dépassent jamais leur taille : appelé depuis les callbacks, le pont
+/// n'alloue rien.
+///
+/// # Correction de dérive
+/// Deux horloges « à 48 kHz » diffèrent de quelques dizaines de ppm. Le
+/// ratio de rubato est fixe : `set_ratio_correction` ajoute un écart au
+/// ratio, appliqué à la lecture de `ready` par interpolation linéaire
+/// entre deux frames (`fill` consomme `1 + correction` frame par frame
+/// produite). À quelques centaines de ppm, l'interpolation est inaudible,
+/// contrairement à une frame jetée ou répétée.
+pub struct CallbackResampler {
+    /// `None` = même sample rate des deux côtés (passthrough)
+    inner: Option<AudioResampler>,
+    channels: usize,
+    pending: Vec<f32>,
+    ready: VecDeque<f32>,
+    /// Latence max tolérée dans `ready` (en samples) avant de jeter les plus vieux.
+    max_ready: usize,
+    /// Écart relatif au ratio nominal (1e-4 = 100 ppm plus vite)
+    correction: f64,
+    /// Position de lecture entre la première frame de `ready` et la
+    /// suivante (0.0 ≤ phase < 1.0)
+    phase: f64,
+}
+
+/// Nombre de chunks gardés au maximum dans la file de sortie.
+const MAX_READY_CHUNKS: usize = 8;
+
+/// Correction de ratio maximum (±0.2 %) : bien au-delà d'un écart
+/// d'horloge réel, bien en deçà d'un changement de hauteur audible.
+pub const MAX_RATIO_CORRECTION: f64 = 0.002;
+
+impl CallbackResampler {
+    pub fn new(
+        from_rate: u32,
+        to_rate: u32,
+        channels: usize,
+        chunk_size: usize,
+    ) -> TroubadourResult<Self> {
+        let channels = channels.max(1);
+        let inner = if AudioResampler::is_passthrough(from_rate, to_rate) {
+            None
+        } else {
+            Some(AudioResampler::new(
+                from_rate, to_rate, channels, chunk_size,
+            )?)
+        };
+        let chunk = inner
+            .as_ref()
+            .map_or(chunk_size, |r| r.output_frames().max(chunk_size));
+        let max_ready = chunk * channels * MAX_READY_CHUNKS;
+
+        let pending = inner
+            .as_ref()
+            .map_or(0, |r| r.input_frames_required() * channels);
+
+        Ok(Self {
+            inner,
+            channels,
+            pending: Vec::with_capacity(pending),
+            // Une frame de plus pour `repeat_frame`
+            ready: VecDeque::with_capacity(max_ready + channels),
+            max_ready,
+            correction: 0.0,
+            phase: 0.0,
+        })
+    }
+
+    /// Ajuste le ratio de conversion de `correction` (relatif, borné à
+    /// `MAX_RATIO_CORRECTION`) : positif, la file se vide plus vite.
+    pub fn set_ratio_correction(&mut self, correction: f64) {
+        self.correction = correction.clamp(-MAX_RATIO_CORRECTION, MAX_RATIO_CORRECTION);
+    }
+
+    pub fn ratio_correction(&self) -> f64 {
+        self.correction
+    }
+
+    /// `true` si aucune conversion de sample rate n'est faite.
+    pub fn is_passthrough(&self) -> bool {
+        self.inner.is_none()
+    }
+
+    /// Nombre de samples (interleaved) prêts pour la sortie.
+    pub fn available(&self) -> usize {
+        self.ready.len()
+    }
+
+    /// Nombre de frames complètes prêtes pour la sortie.
+    pub fn available_frames(&self) -> usize {
+        self.ready.len() / self.channels
+    }
+
+    /// Nombre de frames que la file de sortie garde au maximum.
+    pub fn capacity_frames(&self) -> usize {
+        self.max_ready / self.channels
+    }
+
+    /// Jette les `frames` plus anciennes frames prêtes (rattrapage de
+    /// latence). Retourne le nombre de frames réellement jetées.
+    pub fn skip_frames(&mut self, frames: usize) -> usize {
+        let frames = frames.min(self.available_frames());
+        self.ready.drain(..frames * self.channels);
+        frames
+    }
+
+    /// Répète la plus ancienne frame prête (gain d'une frame de latence).
+    /// Sans effet si rien n'est prêt.
+    pub fn repeat_frame(&mut self) -> bool {
+        if self.available_frames() == 0 {
+            return false;
+        }
+        // Chaque `push_front` décale les indices : le sample à recopier
+        // est toujours le dernier canal de la frame d'origine
+        for _ in 0..self.channels {
+            let sample = self.ready[self.channels - 1];
+            self.ready.push_front(sample);
         }
+        true
+    }
 
-        let frames = planar[0].len();
-        let channels = planar.len();
-        let mut interleaved = Vec::with_capacity(frames * channels);
+    /// Ajoute des samples d'entrée (interleaved) et convertit
+    /// tous les chunks complets.
+    ///
+    /// Le consommateur est trop lent → les frames les plus anciennes sont
+    /// jetées avant d'ajouter les nouvelles, plutôt que laisser la latence
+    /// (et la file) grandir indéfiniment.
+    pub fn push(&mut self, input: &[f32]) -> TroubadourResult<()> {
+        let (channels, max_ready) = (self.channels, self.max_ready);
+        match &mut self.inner {
+            None => {
+                // Plus long que la file : seule la fin compte, en frames entières
+                let skip = input.len().saturating_sub(max_ready).div_ceil(channels) * channels;
+                let input = &input[skip.min(input.len())..];
+                make_room(&mut self.ready, input.len(), max_ready, channels);
+                self.ready.extend(input);
+            }
+            Some(resampler) => {
+                let chunk = resampler.input_frames_required() * channels;
+                let produced = resampler.output_frames() * channels;
+                let mut input = input;
+                while !input.is_empty() {
+                    let take = (chunk - self.pending.len()).min(input.len());
+                    self.pending.extend_from_slice(&input[..take]);
+                    input = &input[take..];
+                    if self.pending.len() == chunk {
+                        make_room(&mut self.ready, produced, max_ready, channels);
+                        resampler.process_into(&self.pending, &mut self.ready)?;
+                        self.pending.clear();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 
-        for frame_idx in 0..frames {
-            for channel in planar {
-                interleaved.push(channel[frame_idx]);
+    /// Remplit `out` avec les samples disponibles, bornés à [-1.0, 1.0].
+    ///
+    /// Retourne le nombre de samples écrits ; le reste de `out` est mis
+    /// à zéro. On n'écrit que des frames complètes (jamais un L sans son R).
+    pub fn fill(&mut self, out: &mut [f32]) -> usize {
+        if self.correction == 0.0 && self.phase == 0.0 {
+            let n = self.ready.len().min(out.len());
+            let n = n - n % self.channels;
+
+            for (dst, src) in out.iter_mut().zip(self.ready.drain(..n)) {
+                *dst = src.clamp(-1.0, 1.0);
             }
+            out[n..].fill(0.0);
+            return n;
         }
 
-        interleaved
+        // Lecture corrigée : chaque frame de sortie interpole entre deux
+        // frames prêtes, puis avance de `1 + correction`
+        let channels = self.channels;
+        let step = 1.0 + self.correction;
+        let frames = self.ready.len() / channels;
+        // Première des deux frames lues ; celles dépassées partent en une fois
+        let mut base = 0;
+        let mut written = 0;
+        for frame in out.chunks_exact_mut(channels) {
+            if base + 2 > frames {
+                break;
+            }
+            let t = self.phase as f32;
+            let at = base * channels;
+            for (ch, dst) in frame.iter_mut().enumerate() {
+                let (a, b) = (self.ready[at + ch], self.ready[at + channels + ch]);
+                *dst = (a + (b - a) * t).clamp(-1.0, 1.0);
+            }
+            written += channels;
+            self.phase += step;
+            let whole = self.phase.floor();
+            self.phase -= whole;
+            base += whole as usize;
+        }
+        self.ready.drain(..base.min(frames) * channels);
+        out[written..].fill(0.0);
+        written
     }
 }
 
+/// Jette les plus anciennes frames de `ready` pour que `incoming`
+/// samples de plus tiennent dans `max_ready`.
+fn make_room(ready: &mut VecDeque<f32>, incoming: usize, max_ready: usize, channels: usize) {
+    let excess = (ready.len() + incoming).saturating_sub(max_ready);
+    let excess = (excess.div_ceil(channels) * channels).min(ready.len());
+    ready.drain(..excess);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alloc_count::count_allocations;
+
+    #[test]
+    fn skip_and_repeat_whole_frames() {
+        let mut bridge = CallbackResampler::new(48_000, 48_000, 2, 256).unwrap();
+        bridge.push(&[1.0, -1.0, 0.5, -0.5, 0.25, -0.25]).unwrap();
+        assert_eq!(bridge.available_frames(), 3);
+
+        assert!(bridge.repeat_frame());
+        let mut out = [0.0; 4];
+        assert_eq!(bridge.fill(&mut out), 4);
+        assert_eq!(out, [1.0, -1.0, 1.0, -1.0]);
+
+        assert!(bridge.repeat_frame());
+        assert_eq!(bridge.skip_frames(1), 1);
+        assert_eq!(bridge.fill(&mut out), 4);
+        assert_eq!(out, [0.5, -0.5, 0.25, -0.25]);
+
+        assert_eq!(bridge.skip_frames(1), 0);
+        assert!(!bridge.repeat_frame());
+    }
+
+    #[test]
+    fn ratio_correction_interpolates_between_frames() {
+        let mut bridge = CallbackResampler::new(48_000, 48_000, 1, 256).unwrap();
+        let ramp: Vec<f32> = (0..1000).map(|i| i as f32 / 1000.0).collect();
+        bridge.push(&ramp).unwrap();
+        bridge.set_ratio_correction(0.5);
+        assert_eq!(bridge.ratio_correction(), MAX_RATIO_CORRECTION);
+
+        // Une rampe relue un peu plus vite : pas de saut, juste une pente
+        // un peu plus forte, et un peu plus de frames consommées
+        let mut out = [0.0; 500];
+        assert_eq!(bridge.fill(&mut out), 500);
+        for pair in out.windows(2) {
+            let step = pair[1] - pair[0];
+            assert!((step - 0.001 * (1.0 + 0.002)).abs() < 1e-5, "step {step}");
+        }
+        assert_eq!(bridge.available_frames(), 1000 - 501);
+    }
 
     #[test]
     fn deinterleave_stereo() {
         // [L0, R0, L1, R1] → [[L0, L1], [R0, R1]]
         let interleaved = vec![1.0, 2.0, 3.0, 4.0];
-        let planar = AudioResampler::deinterleave(&interleaved, 2, 2);
+        let mut planar = vec![vec![0.0; 2]; 2];
+        AudioResampler::deinterleave_into(&interleaved, &mut planar);
 
         assert_eq!(planar.len(), 2); // 2 canaux
         assert_eq!(planar[0], vec![1.0, 3.0]); // canal gauche
@@ -179,7 +486,8 @@ mod tests {
     #[test]
     fn deinterleave_mono() {
         let interleaved = vec![1.0, 2.0, 3.0];
-        let planar = AudioResampler::deinterleave(&interleaved, 1, 3);
+        let mut planar = vec![vec![0.0; 3]; 1];
+        AudioResampler::deinterleave_into(&interleaved, &mut planar);
 
         assert_eq!(planar.len(), 1);
         assert_eq!(planar[0], vec![1.0, 2.0, 3.0]);
@@ -188,7 +496,8 @@ mod tests {
     #[test]
     fn interleave_stereo() {
         let planar = vec![vec![1.0, 3.0], vec![2.0, 4.0]];
-        let interleaved = AudioResampler::interleave(&planar);
+        let mut interleaved = Vec::new();
+        AudioResampler::interleave_into(&planar, 2, &mut interleaved);
 
         assert_eq!(interleaved, vec![1.0, 2.0, 3.0, 4.0]);
     }
@@ -196,7 +505,8 @@ mod tests {
     #[test]
     fn interleave_empty() {
         let planar: Vec<Vec<f32>> = vec![];
-        let interleaved = AudioResampler::interleave(&planar);
+        let mut interleaved = Vec::new();
+        AudioResampler::interleave_into(&planar, 0, &mut interleaved);
         assert!(interleaved.is_empty());
     }
 
@@ -206,8 +516,10 @@ mod tests {
         // C'est un test de "roundtrip" — très utile pour valider
         // que deux opérations inverses sont correctes.
         let original = vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6];
-        let planar = AudioResampler::deinterleave(&original, 2, 3);
-        let result = AudioResampler::interleave(&planar);
+        let mut planar = vec![vec![0.0; 3]; 2];
+        AudioResampler::deinterleave_into(&original, &mut planar);
+        let mut result = Vec::new();
+        AudioResampler::interleave_into(&planar, 3, &mut result);
 
         assert_eq!(original.len(), result.len());
         for (a, b) in original.iter().zip(result.iter()) {
@@ -304,4 +616,139 @@ mod tests {
             "Expected ~0.5 ratio for 96k→48k, got {ratio}"
         );
     }
+
+    #[test]
+    fn resample_output_stays_within_input_bounds() {
+        let mut resampler = AudioResampler::new(44100, 48000, 1, 256).unwrap();
+        let frames = resampler.input_frames_required();
+
+        // Signal carré à ±0.5 : le pire cas pour l'overshoot (Gibbs)
+        for _ in 0..4 {
+            let input: Vec<f32> = (0..frames)
+                .map(|i| if (i / 16) % 2 == 0 { 0.5 } else { -0.5 })
+                .collect();
+            let output = resampler.process(&input).unwrap();
+            for &s in &output {
+                assert!(s.abs() <= 0.5, "Overshoot: {s}");
+            }
+        }
+    }
+
+    #[test]
+    fn callback_passthrough_preserves_samples_in_order() {
+        let mut bridge = CallbackResampler::new(48000, 48000, 2, 256).unwrap();
+        assert!(bridge.is_passthrough());
+
+        let input: Vec<f32> = (0..20).map(|i| i as f32 / 100.0).collect();
+        bridge.push(&input).unwrap();
+
+        // Le device demande moins que disponible → exactement ce qu'il demande
+        let mut out = vec![9.0; 8];
+        assert_eq!(bridge.fill(&mut out), 8);
+        assert_eq!(out, input[..8]);
+
+        // Puis plus que disponible → le reste est du silence, pas du vieux contenu
+        let mut out = vec![9.0; 16];
+        assert_eq!(bridge.fill(&mut out), 12);
+        assert_eq!(out[..12], input[8..]);
+        assert!(out[12..].iter().all(|&s| s == 0.0));
+
+        // File vide → uniquement du silence
+        let mut out = vec![9.0; 4];
+        assert_eq!(bridge.fill(&mut out), 0);
+        assert!(out.iter().all(|&s| s == 0.0));
+    }
+
+    /// Pousse `seconds` de DC stéréo par blocs de 441 frames (taille
+    /// typique d'un callback), retourne le nombre total de samples produits.
+    fn drain_total(bridge: &mut CallbackResampler, from_rate: u32, seconds: f32) -> usize {
+        let block = vec![0.25_f32; 441 * 2];
+        let blocks = (from_rate as f32 * seconds / 441.0) as usize;
+        let mut total = 0;
+        let mut out = vec![0.0; 512 * 2];
+        for _ in 0..blocks {
+            bridge.push(&block).unwrap();
+            loop {
+                let n = bridge.fill(&mut out);
+                assert_eq!(n % 2, 0, "Only whole stereo frames");
+                assert!(out[n..].iter().all(|&s| s == 0.0), "Stale samples");
+                total += n;
+                if n < out.len() {
+                    break;
+                }
+            }
+        }
+        total
+    }
+
+    #[test]
+    fn callback_upsampling_length() {
+        let mut bridge = CallbackResampler::new(44100, 48000, 2, 256).unwrap();
+        assert!(!bridge.is_passthrough());
+
+        let produced = drain_total(&mut bridge, 44100, 1.0);
+        let consumed = (44100 / 441) * 441 * 2;
+        let expected = consumed as f64 * 48000.0 / 44100.0;
+        // À un chunk près (le dernier chunk partiel reste dans `pending`)
+        let tolerance = 2.0 * 1024.0;
+        assert!(
+            (produced as f64 - expected).abs() < tolerance,
+            "Expected ~{expected} samples, got {produced}"
+        );
+    }
+
+    #[test]
+    fn callback_downsampling_length() {
+        let mut bridge = CallbackResampler::new(48000, 44100, 2, 256).unwrap();
+
+        let produced = drain_total(&mut bridge, 48000, 1.0);
+        let consumed = (48000 / 441) * 441 * 2;
+        let expected = consumed as f64 * 44100.0 / 48000.0;
+        let tolerance = 2.0 * 1024.0;
+        assert!(
+            (produced as f64 - expected).abs() < tolerance,
+            "Expected ~{expected} samples, got {produced}"
+        );
+    }
+
+    #[test]
+    fn callback_output_is_clamped() {
+        let mut bridge = CallbackResampler::new(48000, 48000, 2, 256).unwrap();
+        bridge.push(&[1.5, -2.0, 0.5, -0.5]).unwrap();
+
+        let mut out = vec![0.0; 4];
+        bridge.fill(&mut out);
+        assert_eq!(out, vec![1.0, -1.0, 0.5, -0.5]);
+    }
+
+    #[test]
+    fn callback_resampling_does_not_allocate_once_built() {
+        let mut bridge = CallbackResampler::new(44_100, 48_000, 2, 256).unwrap();
+        // Blocs de taille quelconque, lecteur absent puis présent : la
+        // file de sortie se remplit jusqu'à sa limite
+        let block = vec![0.25_f32; 441 * 2];
+        let mut out = vec![0.0; 512 * 2];
+        let allocations = count_allocations(|| {
+            for _ in 0..200 {
+                bridge.push(&block).unwrap();
+            }
+            bridge.repeat_frame();
+            for _ in 0..200 {
+                bridge.push(&block[..300]).unwrap();
+                bridge.fill(&mut out);
+            }
+        });
+        assert_eq!(allocations, 0);
+        assert!(bridge.available() <= bridge.capacity_frames() * 2);
+    }
+
+    #[test]
+    fn callback_bounds_latency() {
+        let mut bridge = CallbackResampler::new(48000, 48000, 2, 256).unwrap();
+        // Personne ne lit → la file ne dépasse pas sa limite
+        for _ in 0..100 {
+            bridge.push(&[0.1; 512]).unwrap();
+        }
+        assert!(bridge.available() <= 256 * 2 * MAX_READY_CHUNKS);
+    }
 }
@@ -0,0 +1,204 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+use troubadour_shared::audio::{ChannelId, RouteActivity};
+use troubadour_shared::mixer::Route;
+
+/// Une mesure tous les N blocs : assez pour dire "ça passe / ça ne passe
+/// pas" (~20 fois par seconde à 256 frames / 48 kHz), sans payer la
+/// mesure à chaque callback.
+pub const ROUTE_METER_INTERVAL: u32 = 8;
+
+/// Activité de chaque route (canal → bus), pour répondre à la question
+/// "où est passé mon son ?" : le canal, la route ou le bus ?
+///
+/// # Une grille bornée plutôt qu'une HashMap
+/// Le callback audio écrit ici sans lock ni allocation : une case
+/// atomique par paire `(from, to)` d'ids inférieurs à la capacité (les
+/// autres ne sont pas suivies). Crête et horodatage sont écrits
+/// séparément : une lecture peut mélanger deux mesures voisines, sans
+/// conséquence pour un outil de diagnostic.
+///
+/// Le callback ne fait qu'écrire : le moteur lit la grille (les seules
+/// cases des routes existantes) et envoie l'activité à l'UI, hors du
+/// thread audio.
+///
+/// Coupé par défaut : c'est un surcoût dans le callback.
+pub struct RouteMeter {
+    enabled: AtomicBool,
+    /// Un bloc a été mesuré depuis la dernière lecture du moteur
+    measured: AtomicBool,
+    capacity: usize,
+    /// Dernière crête non nulle de chaque route (bits d'un `f32`)
+    peaks: Vec<AtomicU32>,
+    /// Quand cette crête a été vue (ms Unix, 0 = jamais)
+    last_seen_ms: Vec<AtomicU64>,
+}
+
+impl RouteMeter {
+    pub fn new(capacity: usize) -> Self {
+        let slots = capacity * capacity;
+        Self {
+            enabled: AtomicBool::new(false),
+            measured: AtomicBool::new(false),
+            capacity,
+            peaks: (0..slots).map(|_| AtomicU32::new(0)).collect(),
+            last_seen_ms: (0..slots).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Active ou coupe la mesure. Couper efface l'activité vue :
+    /// une route vue il y a une heure n'explique pas le silence actuel.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    fn slot(&self, from: ChannelId, to: ChannelId) -> Option<usize> {
+        (from.0 < self.capacity && to.0 < self.capacity).then(|| from.0 * self.capacity + to.0)
+    }
+
+    /// Crête (linéaire) de la contribution d'un bloc de `from` à `to`.
+    /// Un bloc silencieux ne compte pas : la route garde sa dernière
+    /// activité, avec son heure.
+    pub fn record(&self, from: ChannelId, to: ChannelId, peak: f32, now_ms: u64) {
+        if let Some(slot) = self.slot(from, to)
+            && peak > 0.0
+        {
+            self.peaks[slot].store(peak.to_bits(), Ordering::Relaxed);
+            self.last_seen_ms[slot].store(now_ms, Ordering::Relaxed);
+        }
+    }
+
+    /// Signale la fin d'un bloc mesuré (callback).
+    pub fn mark_measured(&self) {
+        self.measured.store(true, Ordering::Relaxed);
+    }
+
+    /// `true` si un bloc a été mesuré depuis le dernier appel (moteur).
+    pub fn take_measured(&self) -> bool {
+        self.measured.swap(false, Ordering::Relaxed)
+    }
+
+    /// Parmi `routes`, celles ayant déjà porté du signal, par `from`
+    /// puis `to`.
+    pub fn activity(&self, routes: &[Route]) -> Vec<RouteActivity> {
+        let mut activity: Vec<RouteActivity> = routes
+            .iter()
+            .filter_map(|route| {
+                let slot = self.slot(route.from, route.to)?;
+                let last_seen_ms = self.last_seen_ms[slot].load(Ordering::Relaxed);
+                (last_seen_ms > 0).then(|| RouteActivity {
+                    from: route.from,
+                    to: route.to,
+                    peak: f32::from_bits(self.peaks[slot].load(Ordering::Relaxed)),
+                    last_seen_ms,
+                })
+            })
+            .collect();
+        activity.sort_by_key(|a| (a.from.0, a.to.0));
+        activity
+    }
+
+    pub fn clear(&self) {
+        for (peak, seen) in self.peaks.iter().zip(&self.last_seen_ms) {
+            peak.store(0, Ordering::Relaxed);
+            seen.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Côté callback : décide quels blocs sont mesurés.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RouteSampler {
+    blocks: u32,
+}
+
+impl RouteSampler {
+    /// `true` si ce bloc doit être mesuré. Mesure coupée : retourne
+    /// aussitôt, sans même compter les blocs.
+    pub fn tick(&mut self, meter: &RouteMeter) -> bool {
+        if !meter.is_enabled() {
+            return false;
+        }
+        self.blocks += 1;
+        if self.blocks >= ROUTE_METER_INTERVAL {
+            self.blocks = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Blocs comptés depuis la dernière mesure.
+    pub fn pending_blocks(&self) -> u32 {
+        self.blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meter_keeps_last_activity_per_route() {
+        let meter = RouteMeter::new(8);
+        meter.set_enabled(true);
+        meter.record(ChannelId(0), ChannelId(3), 0.5, 1000);
+        meter.record(ChannelId(2), ChannelId(3), 0.2, 1000);
+        // Bloc silencieux : la dernière activité reste
+        meter.record(ChannelId(0), ChannelId(3), 0.0, 2000);
+        // Hors grille : ignoré, sans panique
+        meter.record(ChannelId(40), ChannelId(3), 1.0, 2000);
+        // Route supprimée depuis : plus listée
+        meter.record(ChannelId(1), ChannelId(4), 0.7, 1500);
+
+        let routes = [
+            Route::new(ChannelId(2), ChannelId(3)),
+            Route::new(ChannelId(40), ChannelId(3)),
+            Route::new(ChannelId(0), ChannelId(3)),
+            Route::new(ChannelId(1), ChannelId(3)),
+        ];
+        assert_eq!(
+            meter.activity(&routes),
+            vec![
+                RouteActivity {
+                    from: ChannelId(0),
+                    to: ChannelId(3),
+                    peak: 0.5,
+                    last_seen_ms: 1000,
+                },
+                RouteActivity {
+                    from: ChannelId(2),
+                    to: ChannelId(3),
+                    peak: 0.2,
+                    last_seen_ms: 1000,
+                },
+            ]
+        );
+
+        meter.set_enabled(false);
+        assert!(meter.activity(&routes).is_empty());
+    }
+
+    #[test]
+    fn sampler_measures_every_interval_only_when_enabled() {
+        let meter = RouteMeter::new(8);
+        let mut sampler = RouteSampler::default();
+        for _ in 0..100 {
+            assert!(!sampler.tick(&meter));
+        }
+        assert_eq!(sampler.pending_blocks(), 0);
+
+        meter.set_enabled(true);
+        let measured = (0..4 * ROUTE_METER_INTERVAL)
+            .filter(|_| sampler.tick(&meter))
+            .count();
+        assert_eq!(measured, 4);
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crossbeam_channel::{Receiver, Sender};
+use tracing::{debug, warn};
+use troubadour_shared::audio::ChannelId;
+
+/// Capacité de la file : largement plus que ce qu'un thread de contrôle
+/// à 30 fps laisse s'accumuler entre deux vidages.
+pub const RT_LOG_CAPACITY: usize = 1024;
+
+/// Événement notable du thread audio.
+///
+/// # Pourquoi pas `tracing` directement ?
+/// Un `debug!` dans le callback formate une `String` et passe par le
+/// subscriber, qui peut prendre un lock (écriture dans stderr, fichier).
+/// Activer les logs verbeux pour chercher des coupures en créait donc.
+/// Ici : un enum `Copy` (ids et nombres, jamais de `String`) poussé
+/// dans une file bornée sans lock ; le formatage se fait plus tard, sur
+/// le thread de contrôle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RtEvent {
+    /// Chaîne d'effets verrouillée par le thread de contrôle : le bloc
+    /// est passé sans effets
+    EffectsBypassed { channel: ChannelId, frames: u32 },
+    /// File vers la sortie pleine : un bloc audio perdu (coupure audible)
+    AudioBlockDropped { frames: u32 },
+    /// File d'événements vers l'UI pleine : un événement perdu
+    EventDropped,
+    /// Le device de sortie a demandé plus d'audio que disponible
+    OutputUnderrun { missing_frames: u32 },
+    /// Panic attrapé : étage d'effet sauté (`effect`) ou canal muet
+    ProcessingPanicked {
+        channel: ChannelId,
+        effect: Option<&'static str>,
+    },
+    /// Panic attrapé dans le rendu d'une sortie qui ne joue aucun bus
+    OutputPanicked,
+}
+
+impl RtEvent {
+    /// Formate l'événement dans `tracing`, au niveau qui lui correspond.
+    pub fn log(self) {
+        match self {
+            Self::EffectsBypassed { channel, frames } => {
+                debug!("Effects bypassed on {channel:?} for {frames} frames (chain busy)");
+            }
+            Self::AudioBlockDropped { frames } => {
+                warn!("Audio block of {frames} frames dropped (output queue full)");
+            }
+            Self::EventDropped => debug!("UI event dropped (event queue full)"),
+            Self::OutputUnderrun { missing_frames } => {
+                debug!("Output underrun: {missing_frames} frames of silence");
+            }
+            Self::ProcessingPanicked { channel, effect } => match effect {
+                Some(effect) => warn!("Effect '{effect}' panicked on {channel:?}, stage bypassed"),
+                None => warn!("Processing panicked on {channel:?}, channel silenced"),
+            },
+            Self::OutputPanicked => warn!("Output rendering panicked, device silenced"),
+        }
+    }
+}
+
+/// Côté écriture, cloné dans chaque callback audio.
+///
+/// `record` n'alloue pas et ne bloque jamais : file pleine = événement
+/// compté puis oublié.
+#[derive(Clone)]
+pub struct RtLogger {
+    tx: Sender<RtEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl RtLogger {
+    pub fn record(&self, event: RtEvent) {
+        if self.tx.try_send(event).is_err() {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// File d'événements temps réel, vidée par le thread de contrôle.
+pub struct RtLog {
+    logger: RtLogger,
+    rx: Receiver<RtEvent>,
+}
+
+impl RtLog {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, rx) = crossbeam_channel::bounded(capacity);
+        Self {
+            logger: RtLogger {
+                tx,
+                dropped: Arc::new(AtomicU64::new(0)),
+            },
+            rx,
+        }
+    }
+
+    /// Un écrivain pour un callback audio.
+    pub fn logger(&self) -> RtLogger {
+        self.logger.clone()
+    }
+
+    /// Vide la file en passant chaque événement à `f`. Retourne le
+    /// nombre d'événements perdus (file pleine) depuis le dernier vidage.
+    pub fn drain_with(&self, mut f: impl FnMut(RtEvent)) -> u64 {
+        while let Ok(event) = self.rx.try_recv() {
+            f(event);
+        }
+        self.logger.dropped.swap(0, Ordering::Relaxed)
+    }
+
+    /// Vide la file dans `tracing`.
+    pub fn drain(&self) {
+        let dropped = self.drain_with(RtEvent::log);
+        if dropped > 0 {
+            warn!("{dropped} real-time log events dropped (queue full)");
+        }
+    }
+}
+
+impl Default for RtLog {
+    fn default() -> Self {
+        Self::new(RT_LOG_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_drained_in_order_and_overflow_is_counted() {
+        let log = RtLog::new(2);
+        let logger = log.logger();
+        logger.record(RtEvent::AudioBlockDropped { frames: 256 });
+        logger.record(RtEvent::EventDropped);
+        logger.record(RtEvent::OutputUnderrun { missing_frames: 64 });
+
+        let mut drained = Vec::new();
+        let dropped = log.drain_with(|e| drained.push(e));
+        assert_eq!(
+            drained,
+            [
+                RtEvent::AudioBlockDropped { frames: 256 },
+                RtEvent::EventDropped
+            ]
+        );
+        assert_eq!(dropped, 1);
+        // Compteur remis à zéro par le vidage
+        assert_eq!(log.drain_with(|_| {}), 0);
+    }
+}
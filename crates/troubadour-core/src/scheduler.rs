@@ -0,0 +1,417 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use troubadour_shared::action::{Action, ActionSet, DailyTrigger, ScheduleConfig};
+use troubadour_shared::error::{MixerError, MixerResult};
+use troubadour_shared::mixer::{Route, RoutingSnapshot};
+
+use crate::mixer::Mixer;
+use crate::taper::VolumeDecibels;
+
+const MINUTES_PER_DAY: u64 = 24 * 60;
+
+/// Ce qui annule une action appliquée : une autre action, ou les routes
+/// d'avant un routage nommé (qu'aucune action de la config n'exprime).
+enum Undo {
+    Action(Action),
+    Routes(Vec<Route>),
+}
+
+/// Applique une action au mixer ; retourne ce qui l'annule.
+/// `snapshots` : les routages nommés (`Action::ApplyRoutingSnapshot`).
+fn apply_action(
+    mixer: &mut Mixer,
+    action: &Action,
+    snapshots: &BTreeMap<String, RoutingSnapshot>,
+) -> MixerResult<Undo> {
+    let config = |mixer: &Mixer, id: troubadour_shared::audio::ChannelId| {
+        mixer
+            .channel(id)
+            .cloned()
+            .ok_or(MixerError::ChannelNotFound(id.0))
+    };
+    match *action {
+        Action::SetMute { channel, muted } => {
+            let before = config(mixer, channel)?.muted;
+            mixer.set_mute(channel, muted);
+            Ok(Undo::Action(Action::SetMute {
+                channel,
+                muted: before,
+            }))
+        }
+        Action::SetSolo { channel, solo } => {
+            let before = config(mixer, channel)?.solo;
+            mixer.set_solo(channel, solo);
+            Ok(Undo::Action(Action::SetSolo {
+                channel,
+                solo: before,
+            }))
+        }
+        Action::SetVolume { channel, level } => {
+            let before = config(mixer, channel)?.volume;
+            mixer.set_volume(channel, level);
+            Ok(Undo::Action(Action::SetVolume {
+                channel,
+                level: before,
+            }))
+        }
+        Action::FadeBusVolume {
+            bus,
+            target_db,
+            duration_ms,
+        } => {
+            let before = config(mixer, bus)?.volume;
+            if !mixer.fade_bus_volume(bus, VolumeDecibels(target_db), duration_ms) {
+                return Err(MixerError::BusNotFound(bus.0));
+            }
+            // Annuler = reprendre le fader : pas de fondu retour
+            Ok(Undo::Action(Action::SetVolume {
+                channel: bus,
+                level: before,
+            }))
+        }
+        Action::SetRoute { from, to, enabled } => {
+            config(mixer, from)?;
+            config(mixer, to)?;
+            let before = mixer.has_route(from, to);
+            if enabled {
+                mixer.add_route(from, to);
+            } else {
+                mixer.remove_route(from, to);
+            }
+            Ok(Undo::Action(Action::SetRoute {
+                from,
+                to,
+                enabled: before,
+            }))
+        }
+        Action::SetEmergencyPassthrough { bus } => {
+            let before = mixer.emergency_passthrough();
+            mixer.set_emergency_passthrough(bus)?;
+            Ok(Undo::Action(Action::SetEmergencyPassthrough {
+                bus: before,
+            }))
+        }
+        Action::ApplyRoutingSnapshot { ref name } => {
+            let snapshot = snapshots.get(name).ok_or_else(|| {
+                MixerError::InvalidParameter(format!("routing snapshot '{name}' not found"))
+            })?;
+            let before = mixer.routes().to_vec();
+            mixer.replace_routes(&snapshot.routes)?;
+            Ok(Undo::Routes(before))
+        }
+    }
+}
+
+/// Applique un ensemble d'actions, tout ou rien.
+///
+/// # Retour arrière
+/// Chaque action réussie laisse son inverse. Si une action échoue, les
+/// inverses sont rejoués du dernier au premier : le mixer revient à son
+/// état d'avant l'ensemble, pas à un "BRB" à moitié appliqué (micro
+/// coupé mais musique toujours muette).
+pub fn apply_action_set(
+    mixer: &mut Mixer,
+    set: &ActionSet,
+    snapshots: &BTreeMap<String, RoutingSnapshot>,
+) -> MixerResult<()> {
+    let mut undo = Vec::with_capacity(set.actions.len());
+    for action in &set.actions {
+        match apply_action(mixer, action, snapshots) {
+            Ok(inverse) => undo.push(inverse),
+            Err(e) => {
+                for inverse in undo.into_iter().rev() {
+                    let _ = match inverse {
+                        Undo::Action(action) => apply_action(mixer, &action, snapshots).map(drop),
+                        Undo::Routes(routes) => mixer.replace_routes(&routes),
+                    };
+                }
+                return Err(e);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Déclencheurs quotidiens des ensembles d'actions.
+///
+/// # Horloge injectée
+/// Le scheduler ne lit jamais l'heure lui-même : `due` reçoit la minute
+/// locale courante. Le moteur lui passe l'horloge système, les tests une
+/// horloge simulée.
+///
+/// Une heure déclenche quand on la FRANCHIT entre deux vérifications :
+/// au premier appel rien ne part (démarrer à 22 h 40 ne rejoue pas le
+/// déclencheur de 22 h 30), et un thread bloqué quelques minutes
+/// rattrape les heures passées entre-temps, une fois chacune.
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    triggers: Vec<DailyTrigger>,
+    /// Décalage fixe ; `None` : celui du système à chaque minute
+    utc_offset_minutes: Option<i32>,
+    /// Dernière minute locale vérifiée (minutes depuis l'epoch)
+    last_minute: Option<u64>,
+}
+
+impl Scheduler {
+    /// Remplace les déclencheurs. La dernière minute vérifiée est
+    /// gardée : recharger la config ne rejoue ni ne saute rien.
+    pub fn configure(&mut self, config: &ScheduleConfig) {
+        self.triggers = config.triggers.clone();
+        self.utc_offset_minutes = config.utc_offset_minutes;
+    }
+
+    pub fn triggers(&self) -> &[DailyTrigger] {
+        &self.triggers
+    }
+
+    /// Retire les déclencheurs d'un ensemble supprimé ; retourne ceux
+    /// qui sont partis.
+    pub fn remove_triggers_of(&mut self, action_set: &str) -> Vec<DailyTrigger> {
+        let (removed, kept) = std::mem::take(&mut self.triggers)
+            .into_iter()
+            .partition(|t| t.action_set == action_set);
+        self.triggers = kept;
+        removed
+    }
+
+    /// Minute locale de `now`, en minutes depuis l'epoch.
+    ///
+    /// Sans décalage fixe, le fuseau du système est relu à chaque appel :
+    /// au passage à l'heure d'été, la minute locale saute d'une heure et
+    /// les déclencheurs de l'heure sautée partent quand même (rattrapage).
+    pub fn local_minute(&self, now: SystemTime) -> u64 {
+        let secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let offset = self
+            .utc_offset_minutes
+            .or_else(|| system_utc_offset_minutes(secs))
+            .unwrap_or(0);
+        (secs / 60).saturating_add_signed(i64::from(offset))
+    }
+
+    /// Ensembles d'actions dont l'heure a été franchie depuis le dernier
+    /// appel, dans l'ordre où leurs heures sont passées.
+    pub fn due(&mut self, local_minute: u64) -> Vec<String> {
+        let Some(last) = self.last_minute.replace(local_minute) else {
+            return Vec::new();
+        };
+        // Horloge revenue en arrière (réglage manuel) : on repart d'ici
+        if local_minute <= last {
+            return Vec::new();
+        }
+        let elapsed = local_minute - last;
+        let first = (last + 1) % MINUTES_PER_DAY;
+
+        let mut due: Vec<(u64, &str)> = self
+            .triggers
+            .iter()
+            .filter_map(|t| {
+                // Minutes entre la première minute franchie et l'heure
+                let at = u64::from(t.at.minute_of_day());
+                let offset = (at + MINUTES_PER_DAY - first) % MINUTES_PER_DAY;
+                (offset < elapsed).then_some((offset, t.action_set.as_str()))
+            })
+            .collect();
+        due.sort_by_key(|&(offset, _)| offset);
+        due.into_iter().map(|(_, name)| name.to_string()).collect()
+    }
+}
+
+/// Décalage sur UTC du fuseau du système à l'instant `secs` (secondes
+/// depuis l'epoch), en minutes ; `None` si le système ne sait pas.
+fn system_utc_offset_minutes(secs: u64) -> Option<i32> {
+    let time = libc::time_t::try_from(secs).ok()?;
+    // SAFETY: `tm` est une structure C sans invariant (zéro est valide),
+    // et les deux pointeurs sont valides pendant l'appel.
+    let local = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        #[cfg(unix)]
+        let ok = !libc::localtime_r(&time, &mut tm).is_null();
+        #[cfg(windows)]
+        let ok = libc::localtime_s(&mut tm, &time) == 0;
+        ok.then_some(tm)
+    }?;
+    // Heure locale relue comme si elle était UTC, moins l'instant réel
+    let days = days_from_civil(
+        i64::from(local.tm_year) + 1900,
+        i64::from(local.tm_mon) + 1,
+        i64::from(local.tm_mday),
+    );
+    let local_secs = days * 86_400
+        + i64::from(local.tm_hour) * 3600
+        + i64::from(local.tm_min) * 60
+        + i64::from(local.tm_sec);
+    i32::try_from((local_secs - i64::try_from(secs).ok()?) / 60).ok()
+}
+
+/// Jours entre le 1970-01-01 et une date du calendrier grégorien
+/// (algorithme "days from civil" de Howard Hinnant).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::action::TimeOfDay;
+    use troubadour_shared::audio::ChannelId;
+    use troubadour_shared::mixer::{MixerConfig, Route};
+
+    fn scheduler() -> Scheduler {
+        let trigger = |h, m, name: &str| DailyTrigger {
+            at: TimeOfDay::new(h, m).unwrap(),
+            action_set: name.into(),
+        };
+        let mut scheduler = Scheduler::default();
+        scheduler.configure(&ScheduleConfig {
+            triggers: vec![trigger(22, 30, "quiet"), trigger(7, 0, "awake")],
+            ..Default::default()
+        });
+        scheduler
+    }
+
+    /// Minute locale du jour `day` à `h`:`m`.
+    fn at(day: u64, h: u64, m: u64) -> u64 {
+        day * MINUTES_PER_DAY + h * 60 + m
+    }
+
+    #[test]
+    fn triggers_fire_once_when_their_time_is_crossed() {
+        let mut scheduler = scheduler();
+        // Premier passage : on ne rejoue pas ce qui est déjà passé
+        assert!(scheduler.due(at(3, 22, 40)).is_empty());
+        assert!(scheduler.due(at(3, 23, 0)).is_empty());
+        // Minuit franchi, puis 7 h
+        assert!(scheduler.due(at(4, 0, 1)).is_empty());
+        assert_eq!(scheduler.due(at(4, 7, 0)), vec!["awake"]);
+        assert!(scheduler.due(at(4, 7, 1)).is_empty());
+
+        // Un thread bloqué de 22 h 29 à 7 h 05 rattrape les deux, dans l'ordre
+        assert!(scheduler.due(at(4, 22, 29)).is_empty());
+        assert_eq!(scheduler.due(at(5, 7, 5)), vec!["quiet", "awake"]);
+
+        // Horloge remise en arrière : rien ne part
+        assert!(scheduler.due(at(5, 6, 0)).is_empty());
+        assert_eq!(scheduler.due(at(5, 7, 0)), vec!["awake"]);
+    }
+
+    #[test]
+    fn reloading_keeps_the_clock_position() {
+        let mut scheduler = scheduler();
+        scheduler.due(at(1, 22, 0));
+        scheduler.configure(&ScheduleConfig {
+            triggers: scheduler.triggers().to_vec(),
+            ..Default::default()
+        });
+        assert_eq!(scheduler.due(at(1, 22, 30)), vec!["quiet"]);
+    }
+
+    #[test]
+    fn local_minute_applies_the_utc_offset() {
+        let mut scheduler = Scheduler::default();
+        scheduler.configure(&ScheduleConfig {
+            utc_offset_minutes: Some(-90),
+            ..Default::default()
+        });
+        let now = UNIX_EPOCH + std::time::Duration::from_secs(at(2, 12, 0) * 60);
+        assert_eq!(scheduler.local_minute(now), at(2, 10, 30));
+    }
+
+    #[test]
+    fn system_offset_is_read_at_each_instant() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(2026, 10, 17), 20_743);
+
+        // Le fuseau du système, quel qu'il soit, donne un décalage
+        // plausible, et la minute locale le suit
+        let secs = 20_743 * 86_400 + 12 * 3600;
+        let offset = system_utc_offset_minutes(secs).unwrap();
+        assert!(offset.abs() <= 14 * 60, "{offset}");
+        let now = UNIX_EPOCH + std::time::Duration::from_secs(secs);
+        let local = Scheduler::default().local_minute(now);
+        assert_eq!(local as i64 - (secs / 60) as i64, i64::from(offset));
+    }
+
+    #[test]
+    fn removing_an_action_set_drops_its_triggers() {
+        let mut scheduler = scheduler();
+        let removed = scheduler.remove_triggers_of("quiet");
+        assert_eq!(removed.len(), 1);
+        assert_eq!(scheduler.triggers().len(), 1);
+        assert_eq!(scheduler.triggers()[0].action_set, "awake");
+        assert!(scheduler.remove_triggers_of("quiet").is_empty());
+    }
+
+    #[test]
+    fn failed_action_set_rolls_back_what_was_applied() {
+        let mut mixer = Mixer::from_config(MixerConfig::default_setup());
+        let set = ActionSet {
+            actions: vec![
+                Action::SetMute {
+                    channel: ChannelId(0),
+                    muted: true,
+                },
+                Action::SetRoute {
+                    from: ChannelId(1),
+                    to: ChannelId(4),
+                    enabled: true,
+                },
+                Action::FadeBusVolume {
+                    bus: ChannelId(1),
+                    target_db: -20.0,
+                    duration_ms: 500.0,
+                },
+            ],
+        };
+        assert_eq!(
+            apply_action_set(&mut mixer, &set, &BTreeMap::new()),
+            Err(MixerError::BusNotFound(1))
+        );
+        assert!(!mixer.channel(ChannelId(0)).unwrap().muted);
+        assert!(!mixer.has_route(ChannelId(1), ChannelId(4)));
+
+        // Sans l'action fautive, tout passe
+        let ok = ActionSet {
+            actions: set.actions[..2].to_vec(),
+        };
+        apply_action_set(&mut mixer, &ok, &BTreeMap::new()).unwrap();
+        assert!(mixer.channel(ChannelId(0)).unwrap().muted);
+        assert!(mixer.has_route(ChannelId(1), ChannelId(4)));
+    }
+
+    #[test]
+    fn routing_snapshot_action_is_rolled_back_with_its_set() {
+        let mut mixer = Mixer::from_config(MixerConfig::default_setup());
+        let snapshots = BTreeMap::from([(
+            "music".to_string(),
+            RoutingSnapshot {
+                routes: vec![Route::new(ChannelId(1), ChannelId(4))],
+            },
+        )]);
+        let before = mixer.routes().to_vec();
+        let mut set = ActionSet {
+            actions: vec![
+                Action::ApplyRoutingSnapshot {
+                    name: "music".into(),
+                },
+                Action::SetMute {
+                    channel: ChannelId(42),
+                    muted: true,
+                },
+            ],
+        };
+        assert!(apply_action_set(&mut mixer, &set, &snapshots).is_err());
+        assert_eq!(mixer.routes(), before);
+
+        set.actions.pop();
+        apply_action_set(&mut mixer, &set, &snapshots).unwrap();
+        assert_eq!(mixer.routes(), snapshots["music"].routes);
+    }
+}
@@ -0,0 +1,670 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{AST, CallFnOptions, Dynamic, EvalAltResult, Map, Scope};
+use tracing::{debug, info};
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::messages::Command;
+use troubadour_shared::mixer::Route;
+use troubadour_shared::script::{ScriptInfo, ScriptingConfig};
+
+use crate::levels::SharedLevels;
+use crate::mixer::Mixer;
+use crate::taper::VolumeDecibels;
+
+/// Extension des fichiers de script.
+pub const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Écart de volume (dB) en dessous duquel `set_volume` n'envoie rien :
+/// un script qui répète son réglage à chaque tick ne consomme pas son
+/// débit de commandes.
+const VOLUME_EPSILON_DB: f32 = 0.05;
+
+/// Un canal tel que le voit un script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptChannel {
+    pub id: ChannelId,
+    pub name: String,
+    /// Volume linéaire (1.0 = 0 dB)
+    pub volume: f32,
+    pub muted: bool,
+    /// `(rms, peak)` linéaires du dernier bloc (`None` : pas mesuré)
+    pub level: Option<(f32, f32)>,
+}
+
+/// Instantané du mixer lu par les scripts pendant un tick.
+///
+/// Copié une fois par tick : un script ne touche jamais le `Mixer`,
+/// il ne fait que proposer des `Command`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MixerView {
+    pub channels: Vec<ScriptChannel>,
+    pub routes: Vec<Route>,
+}
+
+impl MixerView {
+    pub fn from_mixer(mixer: &Mixer, levels: &SharedLevels) -> Self {
+        let channels = mixer
+            .inputs()
+            .into_iter()
+            .chain(mixer.outputs())
+            .map(|c| ScriptChannel {
+                id: c.id,
+                name: c.name.clone(),
+                volume: c.volume,
+                muted: c.muted,
+                level: levels.load(c.id),
+            })
+            .collect();
+        Self {
+            channels,
+            routes: mixer.routes().to_vec(),
+        }
+    }
+
+    /// Canal par son nom, sans tenir compte de la casse.
+    fn channel(&self, name: &str) -> Result<&ScriptChannel, Box<EvalAltResult>> {
+        self.channels
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("no channel named '{name}'").into())
+    }
+}
+
+/// L'objet `mixer` passé à `tick` : getters sur l'instantané, setters
+/// qui empilent des `Command` (rien n'est appliqué pendant le tick).
+#[derive(Clone)]
+struct MixerApi {
+    view: Arc<MixerView>,
+    /// Secondes depuis l'activation du script
+    time: f64,
+    commands: Arc<Mutex<Vec<Command>>>,
+}
+
+impl MixerApi {
+    fn push(&self, command: Command) {
+        self.commands
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(command);
+    }
+
+    fn level(&mut self, name: &str) -> Result<f64, Box<EvalAltResult>> {
+        let rms = self.view.channel(name)?.level.map_or(0.0, |(rms, _)| rms);
+        Ok(VolumeDecibels::from_linear(rms).0 as f64)
+    }
+
+    fn peak(&mut self, name: &str) -> Result<f64, Box<EvalAltResult>> {
+        let peak = self.view.channel(name)?.level.map_or(0.0, |(_, peak)| peak);
+        Ok(VolumeDecibels::from_linear(peak).0 as f64)
+    }
+
+    fn volume(&mut self, name: &str) -> Result<f64, Box<EvalAltResult>> {
+        let volume = self.view.channel(name)?.volume;
+        Ok(VolumeDecibels::from_linear(volume).0 as f64)
+    }
+
+    fn muted(&mut self, name: &str) -> Result<bool, Box<EvalAltResult>> {
+        Ok(self.view.channel(name)?.muted)
+    }
+
+    fn routed(&mut self, from: &str, to: &str) -> Result<bool, Box<EvalAltResult>> {
+        let route = Route::new(self.view.channel(from)?.id, self.view.channel(to)?.id);
+        Ok(self.view.routes.contains(&route))
+    }
+
+    fn set_volume(&mut self, name: &str, db: f64) -> Result<(), Box<EvalAltResult>> {
+        let channel = self.view.channel(name)?;
+        let db = db as f32;
+        let current = VolumeDecibels::from_linear(channel.volume).0;
+        if current == db || (current - db).abs() < VOLUME_EPSILON_DB {
+            return Ok(());
+        }
+        self.push(Command::SetVolume {
+            channel: channel.id,
+            level: VolumeDecibels(db).to_linear(),
+        });
+        Ok(())
+    }
+
+    fn set_mute(&mut self, name: &str, muted: bool) -> Result<(), Box<EvalAltResult>> {
+        let channel = self.view.channel(name)?;
+        if channel.muted != muted {
+            self.push(Command::SetMute {
+                channel: channel.id,
+                muted,
+            });
+        }
+        Ok(())
+    }
+
+    fn set_route(&mut self, from: &str, to: &str, enabled: bool) -> Result<(), Box<EvalAltResult>> {
+        let (from, to) = (self.view.channel(from)?.id, self.view.channel(to)?.id);
+        if self.view.routes.contains(&Route::new(from, to)) != enabled {
+            self.push(if enabled {
+                Command::AddRoute { from, to }
+            } else {
+                Command::RemoveRoute { from, to }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Un script actif.
+struct Script {
+    ast: AST,
+    /// `this` des fonctions du script : son état d'un tick à l'autre
+    state: Dynamic,
+    enabled_ms: u64,
+    /// Commandes encore permises (seau de jetons, rempli au débit permis)
+    tokens: f64,
+    refilled_ms: u64,
+}
+
+/// Ce qu'un tick des scripts demande au moteur.
+#[derive(Debug, Default)]
+pub struct ScriptOutput {
+    /// À appliquer par le chemin normal des commandes
+    pub commands: Vec<Command>,
+    /// `(script, message)` : erreurs à signaler à l'UI
+    pub errors: Vec<(String, String)>,
+}
+
+/// Scripts d'automatisation (Rhai), exécutés par le thread de contrôle.
+///
+/// Un script définit `fn tick(mixer)` (et, au besoin, `fn init()`,
+/// appelé à l'activation). Son état vit dans `this`, une table gardée
+/// d'un tick à l'autre :
+///
+/// ```rhai
+/// // Le Mic au-dessus de -10 dB pendant 5 s : musique baissée de 6 dB
+/// fn tick(mixer) {
+///     if mixer.level("Mic") > -10.0 {
+///         if this.loud_since == () { this.loud_since = mixer.time; }
+///         if mixer.time - this.loud_since >= 5.0 { mixer.set_volume("Music", -6.0); }
+///     } else {
+///         this.loud_since = ();
+///     }
+/// }
+/// ```
+///
+/// # Bac à sable
+/// - Jamais dans le callback audio : `tick` est appelé par
+///   `Engine::process_commands`, et ses commandes suivent ensuite le
+///   même chemin que celles de l'UI.
+/// - Pas de fichier ni de réseau : Rhai n'en expose aucun, `import` ne
+///   résout aucun module et `eval` est désactivé.
+/// - `max_operations` par appel : une boucle infinie est interrompue, et
+///   le script désactivé avec son erreur.
+/// - `max_commands_per_second` par script : le surplus est ignoré.
+pub struct ScriptHost {
+    engine: rhai::Engine,
+    dir: PathBuf,
+    config: ScriptingConfig,
+    scripts: BTreeMap<String, Script>,
+    /// Dernière erreur de chaque script (effacée à sa réactivation)
+    errors: BTreeMap<String, String>,
+    next_tick_ms: u64,
+}
+
+impl ScriptHost {
+    pub fn new(dir: PathBuf, config: &ScriptingConfig) -> Self {
+        let mut engine = rhai::Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(config.max_operations.max(1));
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(4096);
+        engine.set_max_array_size(1024);
+        engine.set_max_map_size(1024);
+        engine.on_print(|text| info!("script: {text}"));
+        engine.on_debug(|text, source, pos| debug!("script {source:?} {pos}: {text}"));
+
+        engine
+            .register_type_with_name::<MixerApi>("Mixer")
+            .register_get("time", |api: &mut MixerApi| api.time)
+            .register_fn("level", MixerApi::level)
+            .register_fn("peak", MixerApi::peak)
+            .register_fn("volume", MixerApi::volume)
+            .register_fn("muted", MixerApi::muted)
+            .register_fn("routed", MixerApi::routed)
+            .register_fn("set_volume", MixerApi::set_volume)
+            .register_fn("set_mute", MixerApi::set_mute)
+            .register_fn("set_route", MixerApi::set_route);
+
+        Self {
+            engine,
+            dir,
+            config: config.clone(),
+            scripts: BTreeMap::new(),
+            errors: BTreeMap::new(),
+            next_tick_ms: 0,
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Réglages en vigueur, `enabled` à jour (pour la sauvegarde).
+    pub fn config(&self) -> ScriptingConfig {
+        ScriptingConfig {
+            enabled: self.scripts.keys().cloned().collect(),
+            ..self.config.clone()
+        }
+    }
+
+    /// Scripts du dossier, plus ceux qui n'y sont pas (ou plus) mais
+    /// sont actifs ou ont échoué.
+    pub fn list(&self) -> Vec<ScriptInfo> {
+        let mut names: Vec<String> = std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+                    .filter_map(|p| Some(p.file_stem()?.to_str()?.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.extend(self.scripts.keys().cloned());
+        names.extend(self.errors.keys().cloned());
+        names.sort();
+        names.dedup();
+        names
+            .into_iter()
+            .map(|name| ScriptInfo {
+                enabled: self.scripts.contains_key(&name),
+                error: self.errors.get(&name).cloned(),
+                name,
+            })
+            .collect()
+    }
+
+    /// Lit et active `scripts/<name>.rhai` ; déjà actif, il est relu
+    /// (état remis à zéro).
+    pub fn enable(&mut self, name: &str, now_ms: u64) -> Result<(), String> {
+        let result = self
+            .load(name)
+            .and_then(|source| self.enable_source(name, &source, now_ms));
+        if let Err(e) = &result {
+            self.errors.insert(name.to_string(), e.clone());
+        }
+        result
+    }
+
+    fn load(&self, name: &str) -> Result<String, String> {
+        // Un nom, pas un chemin : rien hors du dossier des scripts
+        if name.is_empty() || Path::new(name).file_name() != Some(name.as_ref()) {
+            return Err(format!("invalid script name '{name}'"));
+        }
+        let path = self.dir.join(format!("{name}.{SCRIPT_EXTENSION}"));
+        std::fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Compile et active un script (`enable` sans le fichier).
+    pub fn enable_source(&mut self, name: &str, source: &str, now_ms: u64) -> Result<(), String> {
+        self.scripts.remove(name);
+        let ast = self.engine.compile(source).map_err(|e| e.to_string())?;
+        let has = |fn_name: &str, params: usize| {
+            ast.iter_functions()
+                .any(|f| f.name == fn_name && f.params.len() == params)
+        };
+        if !has("tick", 1) {
+            return Err("missing fn tick(mixer)".to_string());
+        }
+        let has_init = has("init", 0);
+        let mut script = Script {
+            state: Dynamic::from_map(Map::new()),
+            enabled_ms: now_ms,
+            tokens: self.config.max_commands_per_second as f64,
+            refilled_ms: now_ms,
+            ast,
+        };
+        if has_init {
+            let options = CallFnOptions::new()
+                .eval_ast(false)
+                .bind_this_ptr(&mut script.state);
+            let _: Dynamic = self
+                .engine
+                .call_fn_with_options(options, &mut Scope::new(), &script.ast, "init", ())
+                .map_err(|e| e.to_string())?;
+        }
+        self.errors.remove(name);
+        self.scripts.insert(name.to_string(), script);
+        info!("Script '{name}' enabled");
+        Ok(())
+    }
+
+    /// `false` : le script n'était pas actif.
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.scripts.remove(name).is_some()
+    }
+
+    /// Active les scripts de la config ; renvoie les erreurs
+    /// `(script, message)`.
+    pub fn enable_configured(&mut self, now_ms: u64) -> Vec<(String, String)> {
+        let names = self.config.enabled.clone();
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let e = self.enable(&name, now_ms).err()?;
+                Some((name, e))
+            })
+            .collect()
+    }
+
+    /// Un script est actif et son tick est dû (`tick_ms` depuis le
+    /// précédent) : l'instantané du mixer vaut la peine d'être copié.
+    pub fn is_due(&self, now_ms: u64) -> bool {
+        !self.scripts.is_empty() && now_ms >= self.next_tick_ms
+    }
+
+    /// Exécute les scripts si leur tick est dû. Un script qui lève une
+    /// erreur est désactivé.
+    pub fn tick(&mut self, view: MixerView, now_ms: u64) -> ScriptOutput {
+        let mut output = ScriptOutput::default();
+        if !self.is_due(now_ms) {
+            return output;
+        }
+        self.next_tick_ms = now_ms + self.config.tick_ms.max(1);
+
+        let view = Arc::new(view);
+        let rate = self.config.max_commands_per_second as f64;
+        let mut failed = Vec::new();
+        for (name, script) in &mut self.scripts {
+            let api = MixerApi {
+                view: view.clone(),
+                time: now_ms.saturating_sub(script.enabled_ms) as f64 / 1000.0,
+                commands: Arc::new(Mutex::new(Vec::new())),
+            };
+            let options = CallFnOptions::new()
+                .eval_ast(false)
+                .bind_this_ptr(&mut script.state);
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                options,
+                &mut Scope::new(),
+                &script.ast,
+                "tick",
+                (api.clone(),),
+            );
+            if let Err(e) = result {
+                failed.push((name.clone(), e.to_string()));
+                continue;
+            }
+
+            let elapsed = now_ms.saturating_sub(script.refilled_ms) as f64 / 1000.0;
+            script.tokens = (script.tokens + elapsed * rate).min(rate);
+            script.refilled_ms = now_ms;
+            let commands =
+                std::mem::take(&mut *api.commands.lock().unwrap_or_else(|e| e.into_inner()));
+            let allowed = (script.tokens.floor() as usize).min(commands.len());
+            script.tokens -= allowed as f64;
+            if allowed < commands.len() {
+                output.errors.push((
+                    name.clone(),
+                    format!(
+                        "rate limit reached: {} command(s) dropped",
+                        commands.len() - allowed
+                    ),
+                ));
+            }
+            output.commands.extend(commands.into_iter().take(allowed));
+        }
+        for (name, message) in failed {
+            self.scripts.remove(&name);
+            self.errors.insert(name.clone(), message.clone());
+            output.errors.push((name, message));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUCKING: &str = r#"
+        // Le Mic au-dessus de -10 dB pendant 5 s : musique à -6 dB
+        fn tick(mixer) {
+            if mixer.level("Mic") > -10.0 {
+                if this.loud_since == () { this.loud_since = mixer.time; }
+                if mixer.time - this.loud_since >= 5.0 { mixer.set_volume("Music", -6.0); }
+            } else {
+                this.loud_since = ();
+                mixer.set_volume("Music", 0.0);
+            }
+        }
+    "#;
+
+    fn config() -> ScriptingConfig {
+        ScriptingConfig {
+            tick_ms: 100,
+            ..ScriptingConfig::default()
+        }
+    }
+
+    fn channel(id: usize, name: &str, volume: f32, level: Option<f32>) -> ScriptChannel {
+        ScriptChannel {
+            id: ChannelId(id),
+            name: name.to_string(),
+            volume,
+            muted: false,
+            level: level.map(|l| (l, l)),
+        }
+    }
+
+    /// Mic à `mic` (linéaire), musique à `music` (linéaire), un bus.
+    fn view(mic: f32, music: f32) -> MixerView {
+        MixerView {
+            channels: vec![
+                channel(0, "Mic", 1.0, Some(mic)),
+                channel(1, "Music", music, None),
+                channel(5, "Stream", 1.0, None),
+            ],
+            routes: vec![Route::new(ChannelId(0), ChannelId(5))],
+        }
+    }
+
+    fn host(source: &str) -> ScriptHost {
+        let mut host = ScriptHost::new(PathBuf::from("/nonexistent"), &config());
+        host.enable_source("test", source, 0).unwrap();
+        host
+    }
+
+    #[test]
+    fn ducking_script_lowers_music_after_five_loud_seconds() {
+        let mut host = host(DUCKING);
+        // Mic à -6 dB : fort, mais pas encore depuis 5 s
+        for now in (0..5_000).step_by(100) {
+            let output = host.tick(view(0.5, 1.0), now);
+            assert!(output.commands.is_empty(), "at {now} ms");
+            assert!(output.errors.is_empty());
+        }
+        let output = host.tick(view(0.5, 1.0), 5_000);
+        assert_eq!(output.commands.len(), 1);
+        let Command::SetVolume { channel, level } = output.commands[0] else {
+            panic!("{:?}", output.commands);
+        };
+        assert_eq!(channel, ChannelId(1));
+        assert!((VolumeDecibels::from_linear(level).0 + 6.0).abs() < 0.01);
+
+        // Déjà à -6 dB : rien à renvoyer
+        let ducked = VolumeDecibels(-6.0).to_linear();
+        assert!(host.tick(view(0.5, ducked), 5_100).commands.is_empty());
+
+        // Le Mic se tait : la musique remonte
+        let output = host.tick(view(0.01, ducked), 5_200);
+        assert!(matches!(
+            output.commands[..],
+            [Command::SetVolume { channel: ChannelId(1), level }] if level == 1.0
+        ));
+    }
+
+    #[test]
+    fn ticks_follow_the_interval() {
+        let mut host = host(r#"fn tick(mixer) { mixer.set_mute("Music", true); }"#);
+        assert_eq!(host.tick(view(0.0, 1.0), 0).commands.len(), 1);
+        assert!(host.tick(view(0.0, 1.0), 50).commands.is_empty());
+        assert_eq!(host.tick(view(0.0, 1.0), 100).commands.len(), 1);
+    }
+
+    #[test]
+    fn routes_and_mutes_become_commands() {
+        let mut host = host(
+            r#"
+            fn tick(mixer) {
+                if mixer.routed("Mic", "Stream") && !mixer.muted("Mic") {
+                    mixer.set_route("Music", "Stream", true);
+                    mixer.set_route("Mic", "Stream", false);
+                    mixer.set_mute("Mic", true);
+                }
+            }
+            "#,
+        );
+        let output = host.tick(view(0.0, 1.0), 0);
+        assert_eq!(output.commands.len(), 3);
+        assert!(matches!(
+            output.commands[..],
+            [
+                Command::AddRoute {
+                    from: ChannelId(1),
+                    to: ChannelId(5)
+                },
+                Command::RemoveRoute {
+                    from: ChannelId(0),
+                    to: ChannelId(5)
+                },
+                Command::SetMute {
+                    channel: ChannelId(0),
+                    muted: true
+                },
+            ]
+        ));
+    }
+
+    #[test]
+    fn throwing_script_is_reported_and_disabled() {
+        let mut host = host(r#"fn tick(mixer) { mixer.set_mute("Nope", true); }"#);
+        let output = host.tick(view(0.0, 1.0), 0);
+        assert!(output.commands.is_empty());
+        assert_eq!(output.errors.len(), 1);
+        assert!(output.errors[0].1.contains("no channel named 'Nope'"));
+
+        let info = &host.list()[0];
+        assert_eq!(info.name, "test");
+        assert!(!info.enabled);
+        assert!(info.error.is_some());
+        assert!(host.config().enabled.is_empty());
+    }
+
+    #[test]
+    fn endless_loops_hit_the_operation_limit() {
+        let mut host = host("fn tick(mixer) { loop { } }");
+        let output = host.tick(view(0.0, 1.0), 0);
+        assert_eq!(output.errors.len(), 1);
+        assert!(output.errors[0].1.contains("Too many operations"));
+        assert!(!host.list()[0].enabled);
+    }
+
+    #[test]
+    fn commands_beyond_the_rate_limit_are_dropped() {
+        let mut host = ScriptHost::new(
+            PathBuf::new(),
+            &ScriptingConfig {
+                max_commands_per_second: 2,
+                ..config()
+            },
+        );
+        let source = r#"fn tick(mixer) {
+            mixer.set_mute("Mic", true);
+            mixer.set_mute("Music", true);
+            mixer.set_mute("Stream", true);
+        }"#;
+        host.enable_source("spam", source, 0).unwrap();
+
+        let output = host.tick(view(0.0, 1.0), 0);
+        assert_eq!(output.commands.len(), 2);
+        assert!(output.errors[0].1.contains("1 command(s) dropped"));
+        // 100 ms plus tard : 0.2 jeton, pas encore une commande entière
+        assert!(host.tick(view(0.0, 1.0), 100).commands.is_empty());
+        // Une seconde plus tard : le seau est de nouveau plein
+        assert_eq!(host.tick(view(0.0, 1.0), 1_100).commands.len(), 2);
+        // Un script limité reste actif
+        assert!(host.list()[0].enabled);
+    }
+
+    #[test]
+    fn scripts_cannot_reach_files_or_eval() {
+        let mut host = ScriptHost::new(PathBuf::new(), &config());
+        host.enable_source(
+            "import_tick",
+            r#"fn tick(mixer) { import "../config" as c; }"#,
+            0,
+        )
+        .unwrap();
+        let errors = host.tick(view(0.0, 1.0), 0).errors;
+        assert!(errors.iter().any(|(name, _)| name == "import_tick"));
+
+        let eval = host.enable_source("eval", r#"fn tick(mixer) { eval("1") }"#, 0);
+        assert!(eval.is_err());
+    }
+
+    #[test]
+    fn invalid_scripts_are_refused() {
+        let mut host = ScriptHost::new(PathBuf::new(), &config());
+        assert!(host.enable_source("empty", "let x = 1;", 0).is_err());
+        assert!(host.enable_source("syntax", "fn tick(mixer) {", 0).is_err());
+        assert!(host.enable("../config", 0).unwrap_err().contains("invalid"));
+    }
+
+    #[test]
+    fn init_seeds_the_state() {
+        let mut host = host(
+            r#"
+            fn init() { this.count = 0; }
+            fn tick(mixer) {
+                this.count += 1;
+                if this.count == 2 { mixer.set_mute("Mic", true); }
+            }
+            "#,
+        );
+        assert!(host.tick(view(0.0, 1.0), 0).commands.is_empty());
+        assert_eq!(host.tick(view(0.0, 1.0), 100).commands.len(), 1);
+    }
+
+    #[test]
+    fn list_reads_the_scripts_folder() {
+        let dir = std::env::temp_dir().join(format!("troubadour-scripts-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("duck.rhai"), DUCKING).unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let mut host = ScriptHost::new(
+            dir.clone(),
+            &ScriptingConfig {
+                enabled: vec!["duck".into(), "missing".into()],
+                ..config()
+            },
+        );
+        let errors = host.enable_configured(0);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "missing");
+
+        let list = host.list();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "duck");
+        assert!(list[0].enabled);
+        // Un script introuvable reste listé, avec son erreur
+        assert_eq!(list[1].name, "missing");
+        assert!(list[1].error.is_some());
+        assert_eq!(host.config().enabled, ["duck"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
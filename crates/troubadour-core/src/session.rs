@@ -0,0 +1,251 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant};
+
+use troubadour_shared::audio::{BusPeak, ChannelId, SessionStats};
+
+/// Maximums relevés par le callback audio, lisibles sans lock.
+///
+/// # Un `fetch_max` sur les bits d'un `f32`
+/// Pour des flottants positifs, l'ordre des bits (lus comme `u32`) est
+/// celui des valeurs : `fetch_max` garde donc le plus grand niveau sans
+/// boucle compare-and-swap, en une instruction atomique.
+pub struct SessionCounters {
+    peak_load: AtomicU32,
+    /// Crête de chaque bus, par id (au-delà : non suivi)
+    bus_peaks: Vec<AtomicU32>,
+}
+
+impl SessionCounters {
+    pub fn new(max_channels: usize) -> Self {
+        Self {
+            peak_load: AtomicU32::new(0),
+            bus_peaks: (0..max_channels).map(|_| AtomicU32::new(0)).collect(),
+        }
+    }
+
+    /// Charge d'un bloc : temps de traitement / durée audio du bloc.
+    pub fn record_load(&self, load: f32) {
+        if load > 0.0 {
+            self.peak_load.fetch_max(load.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Crête (linéaire) d'un bloc envoyé à un bus.
+    pub fn record_bus_peak(&self, bus: ChannelId, peak: f32) {
+        if let Some(slot) = self.bus_peaks.get(bus.0)
+            && peak > 0.0
+        {
+            slot.fetch_max(peak.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    pub fn peak_load(&self) -> f32 {
+        f32::from_bits(self.peak_load.load(Ordering::Relaxed))
+    }
+
+    /// Bus ayant reçu du signal, par id.
+    pub fn bus_peaks(&self) -> Vec<BusPeak> {
+        self.bus_peaks
+            .iter()
+            .enumerate()
+            .map(|(id, slot)| BusPeak {
+                bus: ChannelId(id),
+                peak: f32::from_bits(slot.load(Ordering::Relaxed)),
+            })
+            .filter(|p| p.peak > 0.0)
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        self.peak_load.store(0, Ordering::Relaxed);
+        for slot in &self.bus_peaks {
+            slot.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Statistiques de la session, côté thread de contrôle.
+///
+/// Le callback n'écrit que dans `SessionCounters` (atomiques) ; tout
+/// le reste (durée, underruns depuis la remise à zéro, reconnexions)
+/// est tenu ici, hors du chemin audio. Le temps de fonctionnement ne
+/// compte que les périodes où le moteur tourne.
+pub struct SessionTracker {
+    counters: Arc<SessionCounters>,
+    running_since: Option<Instant>,
+    /// Temps de fonctionnement des démarrages précédents
+    previous_uptime: Duration,
+    /// Underruns du moteur au début de la session
+    underruns_at_start: u64,
+    reconnects: u64,
+}
+
+impl SessionTracker {
+    pub fn new(max_channels: usize) -> Self {
+        Self {
+            counters: Arc::new(SessionCounters::new(max_channels)),
+            running_since: None,
+            previous_uptime: Duration::ZERO,
+            underruns_at_start: 0,
+            reconnects: 0,
+        }
+    }
+
+    /// Compteurs à confier au callback audio.
+    pub fn counters(&self) -> Arc<SessionCounters> {
+        self.counters.clone()
+    }
+
+    pub fn engine_started(&mut self, now: Instant) {
+        self.running_since.get_or_insert(now);
+    }
+
+    pub fn engine_stopped(&mut self, now: Instant) {
+        if let Some(since) = self.running_since.take() {
+            self.previous_uptime += now.saturating_duration_since(since);
+        }
+    }
+
+    /// Streams rouverts par le moteur : panne (watchdog), défaut du
+    /// système changé, device revenu.
+    pub fn record_reconnect(&mut self) {
+        self.reconnects += 1;
+    }
+
+    /// `underruns` : total du moteur depuis sa création.
+    pub fn stats(&self, now: Instant, underruns: u64) -> SessionStats {
+        let running = self
+            .running_since
+            .map_or(Duration::ZERO, |since| now.saturating_duration_since(since));
+        SessionStats {
+            uptime_seconds: (self.previous_uptime + running).as_secs(),
+            underruns: underruns.saturating_sub(self.underruns_at_start),
+            peak_dsp_load: self.counters.peak_load(),
+            bus_peaks: self.counters.bus_peaks(),
+            reconnects: self.reconnects,
+        }
+    }
+
+    /// Nouvelle session, à partir de maintenant.
+    pub fn reset(&mut self, now: Instant, underruns: u64) {
+        if self.running_since.is_some() {
+            self.running_since = Some(now);
+        }
+        self.previous_uptime = Duration::ZERO;
+        self.underruns_at_start = underruns;
+        self.reconnects = 0;
+        self.counters.reset();
+    }
+}
+
+/// Ligne de résumé pour le log, à l'arrêt du moteur.
+pub fn summary(stats: &SessionStats) -> String {
+    let (hours, rest) = (stats.uptime_seconds / 3600, stats.uptime_seconds % 3600);
+    let mut line = format!(
+        "Session: {hours}h{:02}m{:02}s, {} underrun(s), peak DSP load {:.0}%, {} reconnect(s)",
+        rest / 60,
+        rest % 60,
+        stats.underruns,
+        stats.peak_dsp_load * 100.0,
+        stats.reconnects
+    );
+    for peak in &stats.bus_peaks {
+        let db = 20.0 * peak.peak.log10();
+        line.push_str(&format!(", bus {} peak {db:.1} dBFS", peak.bus.0));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_keep_the_highest_values() {
+        let counters = SessionCounters::new(8);
+        for load in [0.12, 0.5, 0.31] {
+            counters.record_load(load);
+        }
+        counters.record_bus_peak(ChannelId(3), 0.25);
+        counters.record_bus_peak(ChannelId(3), 0.9);
+        counters.record_bus_peak(ChannelId(3), 0.4);
+        counters.record_bus_peak(ChannelId(4), 0.1);
+        // Au-delà de la capacité : ignoré, sans panique
+        counters.record_bus_peak(ChannelId(40), 1.0);
+
+        assert_eq!(counters.peak_load(), 0.5);
+        assert_eq!(
+            counters.bus_peaks(),
+            vec![
+                BusPeak {
+                    bus: ChannelId(3),
+                    peak: 0.9
+                },
+                BusPeak {
+                    bus: ChannelId(4),
+                    peak: 0.1
+                },
+            ]
+        );
+
+        counters.reset();
+        assert_eq!(counters.peak_load(), 0.0);
+        assert!(counters.bus_peaks().is_empty());
+    }
+
+    #[test]
+    fn uptime_counts_running_periods_only() {
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+        let mut tracker = SessionTracker::new(8);
+
+        tracker.engine_started(at(0));
+        tracker.engine_stopped(at(100));
+        // Arrêté 50 s, puis redémarré
+        tracker.engine_started(at(150));
+        tracker.record_reconnect();
+        tracker.counters().record_load(0.42);
+
+        let stats = tracker.stats(at(160), 7);
+        assert_eq!(stats.uptime_seconds, 110);
+        assert_eq!(stats.underruns, 7);
+        assert_eq!(stats.reconnects, 1);
+        assert_eq!(stats.peak_dsp_load, 0.42);
+    }
+
+    #[test]
+    fn reset_starts_a_new_session() {
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + Duration::from_secs(s);
+        let mut tracker = SessionTracker::new(8);
+        tracker.engine_started(at(0));
+        tracker.record_reconnect();
+        tracker.counters().record_bus_peak(ChannelId(3), 0.5);
+
+        tracker.reset(at(60), 12);
+        let stats = tracker.stats(at(90), 15);
+        assert_eq!(stats.uptime_seconds, 30);
+        assert_eq!(stats.underruns, 3);
+        assert_eq!(stats.reconnects, 0);
+        assert!(stats.bus_peaks.is_empty());
+    }
+
+    #[test]
+    fn summary_line_is_readable() {
+        let stats = SessionStats {
+            uptime_seconds: 3723,
+            underruns: 2,
+            peak_dsp_load: 0.35,
+            bus_peaks: vec![BusPeak {
+                bus: ChannelId(3),
+                peak: 0.5,
+            }],
+            reconnects: 1,
+        };
+        assert_eq!(
+            summary(&stats),
+            "Session: 1h02m03s, 2 underrun(s), peak DSP load 35%, 1 reconnect(s), bus 3 peak -6.0 dBFS"
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use troubadour_shared::audio::DeviceInfo;
+use troubadour_shared::machine::{ChannelDevice, MachineProfile, SetupProposal};
+use troubadour_shared::mixer::{ChannelKind, MixerConfig};
+
+/// Fragments de nom (en minuscules) des devices qui capturent le son
+/// de la machine plutôt qu'un micro : PulseAudio/PipeWire ("Monitor of…"),
+/// Windows ("Stereo Mix", "What U Hear"), VB-Cable, BlackHole (macOS)…
+const LOOPBACK_HINTS: &[&str] = &[
+    "monitor of",
+    ".monitor",
+    "loopback",
+    "stereo mix",
+    "what u hear",
+    "cable output",
+    "blackhole",
+    "soundflower",
+];
+
+/// `true` si le device d'entrée capture le son de la machine.
+pub fn is_loopback(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_HINTS.iter().any(|hint| name.contains(hint))
+}
+
+/// Devices visibles au premier lancement, tels que l'énumération les
+/// donne. Séparé de cpal pour que la proposition reste une fonction pure.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInventory {
+    pub inputs: Vec<DeviceInfo>,
+    pub outputs: Vec<DeviceInfo>,
+    pub default_input: Option<String>,
+    pub default_output: Option<String>,
+}
+
+/// Propose les devices d'un premier lancement.
+///
+/// # Les choix
+/// - la première entrée du mixer (le Mic) reçoit l'entrée par défaut,
+///   sauf si c'est un loopback : on prend alors le premier vrai micro ;
+/// - la deuxième entrée (le son du PC) reçoit un device de loopback,
+///   s'il en existe un ;
+/// - le premier bus (A1) reçoit la sortie par défaut, ou la première.
+///
+/// Rien n'est appliqué ici : `summary` explique chaque choix (et chaque
+/// absence) pour que l'utilisateur valide avant `ApplySetupProposal`.
+pub fn propose_initial_config(mixer: &MixerConfig, devices: &DeviceInventory) -> SetupProposal {
+    let mut channels: Vec<_> = mixer.channels.iter().collect();
+    channels.sort_by_key(|c| c.id.0);
+    let mut inputs = channels.iter().filter(|c| c.kind == ChannelKind::Input);
+    let (mic, system) = (inputs.next(), inputs.next());
+    let bus = channels.iter().find(|c| c.kind == ChannelKind::Output);
+
+    let microphone = devices
+        .default_input
+        .as_ref()
+        .filter(|name| !is_loopback(name) && devices.inputs.iter().any(|d| &d.name == *name))
+        .map(|name| (name.clone(), "default input"))
+        .or_else(|| {
+            devices
+                .inputs
+                .iter()
+                .find(|d| !is_loopback(&d.name))
+                .map(|d| (d.name.clone(), "first microphone"))
+        });
+    let loopback = devices
+        .inputs
+        .iter()
+        .find(|d| is_loopback(&d.name))
+        .map(|d| (d.name.clone(), "loopback device"));
+    let output = devices
+        .default_output
+        .as_ref()
+        .filter(|name| devices.outputs.iter().any(|d| &d.name == *name))
+        .map(|name| (name.clone(), "default output"))
+        .or_else(|| {
+            devices
+                .outputs
+                .first()
+                .map(|d| (d.name.clone(), "first output"))
+        });
+
+    let mut proposal = SetupProposal::default();
+    let choices = [
+        (mic, microphone, "no microphone found"),
+        (
+            system,
+            loopback,
+            "no loopback device, desktop audio stays off",
+        ),
+        (bus, output.clone(), "no output device found"),
+    ];
+    for (channel, choice, missing) in choices {
+        let Some(channel) = channel else {
+            continue;
+        };
+        match choice {
+            Some((device, reason)) => {
+                proposal
+                    .summary
+                    .push(format!("{} → {device} ({reason})", channel.name));
+                proposal.profile.channel_devices.push(ChannelDevice {
+                    channel: channel.id,
+                    device,
+                });
+            }
+            None => proposal
+                .summary
+                .push(format!("{}: {missing}", channel.name)),
+        }
+    }
+    proposal.profile.input_device = mic.and_then(|mic| {
+        proposal
+            .profile
+            .channel_devices
+            .iter()
+            .find(|d| d.channel == mic.id)
+            .map(|d| d.device.clone())
+    });
+    proposal.profile.output_device = output.map(|(name, _)| name);
+    proposal
+}
+
+/// Applique les devices proposés à un profil machine existant : le
+/// backend audio choisi reste celui de la machine.
+pub fn merge_into(profile: &mut MachineProfile, proposal: &SetupProposal) {
+    profile.input_device = proposal.profile.input_device.clone();
+    profile.output_device = proposal.profile.output_device.clone();
+    profile.channel_devices = proposal.profile.channel_devices.clone();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::audio::ChannelId;
+
+    fn device(name: &str, is_input: bool) -> DeviceInfo {
+        DeviceInfo::new(name, is_input, 2, Vec::new())
+    }
+
+    fn assigned(proposal: &SetupProposal, id: usize) -> Option<&str> {
+        proposal
+            .profile
+            .channel_devices
+            .iter()
+            .find(|d| d.channel == ChannelId(id))
+            .map(|d| d.device.as_str())
+    }
+
+    #[test]
+    fn typical_machine_gets_mic_loopback_and_output() {
+        let devices = DeviceInventory {
+            inputs: vec![
+                device("Monitor of Built-in Audio", true),
+                device("Blue Yeti", true),
+            ],
+            outputs: vec![device("Built-in Audio", false), device("HD 600", false)],
+            default_input: Some("Blue Yeti".into()),
+            default_output: Some("HD 600".into()),
+        };
+        let proposal = propose_initial_config(&MixerConfig::default_setup(), &devices);
+
+        assert_eq!(assigned(&proposal, 0), Some("Blue Yeti"));
+        assert_eq!(assigned(&proposal, 1), Some("Monitor of Built-in Audio"));
+        assert_eq!(assigned(&proposal, 2), None);
+        assert_eq!(assigned(&proposal, 3), Some("HD 600"));
+        assert_eq!(proposal.profile.input_device.as_deref(), Some("Blue Yeti"));
+        assert_eq!(proposal.profile.output_device.as_deref(), Some("HD 600"));
+        assert_eq!(
+            proposal.summary,
+            [
+                "Mic → Blue Yeti (default input)",
+                "Desktop → Monitor of Built-in Audio (loopback device)",
+                "Headphones → HD 600 (default output)",
+            ]
+        );
+    }
+
+    #[test]
+    fn machine_without_input_or_output_says_so() {
+        let devices = DeviceInventory {
+            outputs: vec![device("Speakers", false)],
+            ..DeviceInventory::default()
+        };
+        let proposal = propose_initial_config(&MixerConfig::default_setup(), &devices);
+        assert_eq!(assigned(&proposal, 0), None);
+        assert_eq!(proposal.profile.input_device, None);
+        // Pas de sortie par défaut : la première fait l'affaire
+        assert_eq!(assigned(&proposal, 3), Some("Speakers"));
+        assert!(proposal.summary[0].contains("no microphone"));
+
+        let devices = DeviceInventory {
+            inputs: vec![device("Blue Yeti", true)],
+            ..DeviceInventory::default()
+        };
+        let proposal = propose_initial_config(&MixerConfig::default_setup(), &devices);
+        assert_eq!(assigned(&proposal, 0), Some("Blue Yeti"));
+        assert_eq!(proposal.profile.output_device, None);
+        assert_eq!(proposal.summary[2], "Headphones: no output device found");
+    }
+
+    #[test]
+    fn duplex_headset_serves_mic_and_bus() {
+        // Un seul device, en entrée ET en sortie ; l'entrée par défaut
+        // du système est un loopback, qui ne doit pas devenir le Mic
+        let devices = DeviceInventory {
+            inputs: vec![device("Stereo Mix", true), device("USB Headset", true)],
+            outputs: vec![device("USB Headset", false)],
+            default_input: Some("Stereo Mix".into()),
+            default_output: Some("USB Headset".into()),
+        };
+        let proposal = propose_initial_config(&MixerConfig::default_setup(), &devices);
+        assert_eq!(assigned(&proposal, 0), Some("USB Headset"));
+        assert_eq!(assigned(&proposal, 1), Some("Stereo Mix"));
+        assert_eq!(assigned(&proposal, 3), Some("USB Headset"));
+        assert_eq!(proposal.summary[0], "Mic → USB Headset (first microphone)");
+    }
+
+    #[test]
+    fn unicode_and_bracketed_names_are_assigned_verbatim() {
+        let mic = "Яндекс микрофон";
+        let loopback = "Monitor of Scarlett 2i2 (3rd Gen) [USB]";
+        let output = "🎧 Casque / Bureau";
+        let devices = DeviceInventory {
+            inputs: vec![device(loopback, true), device(mic, true)],
+            outputs: vec![device(output, false)],
+            default_input: Some(mic.into()),
+            default_output: Some(output.into()),
+        };
+        let proposal = propose_initial_config(&MixerConfig::default_setup(), &devices);
+        assert_eq!(assigned(&proposal, 0), Some(mic));
+        assert_eq!(assigned(&proposal, 1), Some(loopback));
+        assert_eq!(assigned(&proposal, 3), Some(output));
+
+        // Le profil garde le nom brut, pas le repli ASCII de l'affichage
+        let mut profile = MachineProfile::default();
+        merge_into(&mut profile, &proposal);
+        let saved = toml::to_string(&profile).unwrap();
+        let restored: MachineProfile = toml::from_str(&saved).unwrap();
+        assert_eq!(restored.input_device.as_deref(), Some(mic));
+        assert_eq!(restored.output_device.as_deref(), Some(output));
+        for device in devices.inputs.iter().chain(&devices.outputs) {
+            assert!(
+                restored
+                    .channel_devices
+                    .iter()
+                    .any(|d| d.device == device.name)
+            );
+        }
+    }
+}
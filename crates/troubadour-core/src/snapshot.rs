@@ -0,0 +1,903 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use troubadour_shared::audio::{ChannelCount, ChannelId};
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::mixer::{ChannelKind, MeterScale, Route, SignalPresenceConfig};
+
+use crate::channel_map::ChannelSlice;
+use crate::command_queue::{ENGINE_QUEUE_CAPACITY, EffectsSwap, EngineCommand};
+use crate::dsp::ramp::BusFade;
+use crate::mixer::Mixer;
+
+/// Canaux et routes ajoutables sans que la copie du lecteur ne réalloue.
+const READER_HEADROOM: usize = 16;
+
+/// Sous-mix traversés au plus entre un canal et un bus : une config
+/// éditée à la main peut contenir une boucle que `add_route` refuse.
+const MAX_SUBMIX_DEPTH: usize = 8;
+
+/// Paramètres d'un canal tels que le callback audio les consomme.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuntimeChannel {
+    pub id: ChannelId,
+    /// Gain effectif gauche/droite (volume × pan, mute et solo inclus)
+    pub gain: (f32, f32),
+    /// Volume du fader, avant pan (gain d'un bus)
+    pub volume: f32,
+    pub muted: bool,
+    pub delay_ms: f32,
+    /// Conditionnement de l'entrée (voir `InputConditioner`)
+    pub phase_invert: bool,
+    pub dc_block: bool,
+    /// Canaux du device lus par ce canal
+    pub device_slice: ChannelSlice,
+    /// Détection de silence propre au canal (`None` = réglages globaux)
+    pub signal_presence: Option<SignalPresenceConfig>,
+    /// Pour un bus : `false` si un autre bus est en solo (device muet)
+    pub dispatches: bool,
+    /// Pour un bus : dernier fondu demandé (`None` = lissage simple)
+    pub fade: Option<BusFade>,
+    /// Retour pré-fader : bus destinataire et gain linéaire (0 si muted)
+    pub monitor: Option<(ChannelId, f32)>,
+    /// Le retour porte la différence sec − traité, pas le signal traité
+    pub effects_diff_monitor: bool,
+    /// Pour un bus : génération de sa mesure de sonie (`None` : pas mesuré)
+    pub loudness_meter: Option<u64>,
+    /// Pour un bus : crête vraie mesurée
+    pub true_peak_meter: bool,
+    /// Pour un bus : retard de sa sortie vers le device
+    pub output_delay_ms: f32,
+    /// Pour un bus : mono ou stéréo
+    pub channel_count: ChannelCount,
+    /// Sous-mix : transmet ce qu'il reçoit à ses propres routes
+    pub submix: bool,
+}
+
+/// Copie immuable et minimale de l'état du mixer pour le thread audio.
+///
+/// # Pourquoi une copie ?
+/// Le `Mixer` complet (noms, configs, HashMap...) appartient au thread
+/// de contrôle. Le callback audio n'a besoin que de quelques nombres :
+/// gains, mutes, routes. On les extrait dans une struct immuable,
+/// qu'on publie entière après chaque modification.
+///
+/// Immuable = pas de lock pour la lire : personne ne peut la modifier
+/// pendant que le callback la lit. On en publie une NOUVELLE à la place.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MixerRuntimeState {
+    /// Incrémenté à chaque publication (pour le debug et les tests)
+    pub version: u64,
+    /// Canaux triés par id
+    pub channels: Vec<RuntimeChannel>,
+    pub routes: Vec<Route>,
+    /// `true` si tous les canaux d'entrée sont muted
+    pub all_inputs_muted: bool,
+    /// Pour placer les niveaux du callback sur l'échelle des meters
+    pub meter_scale: MeterScale,
+    /// Passthrough d'urgence : bus de repli (voir `Mixer::
+    /// set_emergency_passthrough`)
+    pub passthrough_bus: Option<ChannelId>,
+}
+
+impl MixerRuntimeState {
+    /// Extrait l'état runtime d'un mixer.
+    pub fn from_mixer(mixer: &Mixer) -> Self {
+        let mut channels: Vec<RuntimeChannel> = mixer
+            .to_config()
+            .channels
+            .iter()
+            .map(|c| RuntimeChannel {
+                id: c.id,
+                gain: mixer.effective_gain(c.id),
+                volume: c.volume,
+                muted: c.muted,
+                delay_ms: c.delay_ms,
+                phase_invert: c.phase_invert,
+                dc_block: c.dc_block,
+                device_slice: ChannelSlice::from_config(c),
+                signal_presence: c.signal_presence,
+                dispatches: mixer.bus_dispatches(c.id),
+                fade: mixer.bus_fade(c.id),
+                monitor: c
+                    .monitor_bus
+                    .map(|bus| (bus, mixer.monitor_gain(c.id, bus))),
+                effects_diff_monitor: mixer.effects_diff_monitor(c.id),
+                loudness_meter: mixer.loudness_meter(c.id),
+                true_peak_meter: c.true_peak_meter,
+                output_delay_ms: c.output_delay_ms,
+                channel_count: c.channel_count,
+                submix: c.kind == ChannelKind::Submix,
+            })
+            .collect();
+        channels.sort_by_key(|c| c.id.0);
+
+        Self {
+            version: 0,
+            channels,
+            routes: mixer.routes().to_vec(),
+            // Passthrough : les mutes des entrées sont ignorés
+            all_inputs_muted: mixer.emergency_passthrough().is_none()
+                && mixer.inputs().iter().all(|ch| ch.muted),
+            meter_scale: mixer.meter_scale(),
+            passthrough_bus: mixer.emergency_passthrough(),
+        }
+    }
+
+    pub fn channel(&self, id: ChannelId) -> Option<&RuntimeChannel> {
+        self.channels.iter().find(|c| c.id == id)
+    }
+
+    /// Applique une commande. Un nouvel état complet remplace tout ; ses
+    /// buffers sont recopiés dans les nôtres (pas d'allocation tant que
+    /// la capacité suffit).
+    pub fn apply(&mut self, command: &EngineCommand) {
+        match command {
+            EngineCommand::SetGain {
+                channel,
+                gain,
+                volume,
+                fade,
+            } => {
+                if let Some(c) = self.channels.iter_mut().find(|c| c.id == *channel) {
+                    c.gain = *gain;
+                    c.volume = *volume;
+                    c.fade = *fade;
+                }
+                self.version += 1;
+            }
+            EngineCommand::StartRamp {
+                channel,
+                gain,
+                volume,
+                fade,
+            } => {
+                if let Some(c) = self.channels.iter_mut().find(|c| c.id == *channel) {
+                    c.gain = *gain;
+                    c.volume = *volume;
+                    c.fade = Some(*fade);
+                }
+                self.version += 1;
+            }
+            // Rien dans l'état : le lecteur la passe au pipeline
+            EngineCommand::SwapEffectsProcessor(_) => {}
+            EngineCommand::ReplaceRuntimeState(state) => {
+                self.version = state.version;
+                self.channels.clone_from(&state.channels);
+                self.routes.clone_from(&state.routes);
+                self.all_inputs_muted = state.all_inputs_muted;
+                self.meter_scale = state.meter_scale;
+                self.passthrough_bus = state.passthrough_bus;
+            }
+        }
+    }
+
+    /// Gain effectif d'un canal ((0, 0) s'il n'existe pas).
+    ///
+    /// En passthrough d'urgence, un canal routé vers le bus de repli
+    /// passe à gain unité : ni volume, ni pan, ni mute (vers tous ses
+    /// bus : un canal n'a qu'un gain).
+    pub fn gain(&self, id: ChannelId) -> (f32, f32) {
+        if self.is_passed_through(id) {
+            return (1.0, 1.0);
+        }
+        self.channel(id).map_or((0.0, 0.0), |c| c.gain)
+    }
+
+    /// Le passthrough d'urgence est-il actif ? (effets contournés)
+    pub fn passthrough(&self) -> bool {
+        self.passthrough_bus.is_some()
+    }
+
+    fn is_passed_through(&self, id: ChannelId) -> bool {
+        self.passthrough_bus
+            .is_some_and(|bus| self.routes.contains(&Route::new(id, bus)))
+    }
+
+    /// Gain cible d'un bus : son volume, ou 0 s'il est muted (unité
+    /// pour le bus de repli du passthrough d'urgence).
+    pub fn bus_gain(&self, id: ChannelId) -> f32 {
+        if self.passthrough_bus == Some(id) {
+            return 1.0;
+        }
+        self.channel(id)
+            .map_or(1.0, |c| if c.muted { 0.0 } else { c.volume })
+    }
+
+    pub fn bus_fade(&self, id: ChannelId) -> Option<BusFade> {
+        if self.passthrough_bus == Some(id) {
+            return None;
+        }
+        self.channel(id).and_then(|c| c.fade)
+    }
+
+    /// Gain du retour pré-fader d'un canal vers un bus (0 si aucun).
+    pub fn monitor_gain(&self, id: ChannelId, bus: ChannelId) -> f32 {
+        match self.channel(id).and_then(|c| c.monitor) {
+            Some((to, gain)) if to == bus => gain,
+            _ => 0.0,
+        }
+    }
+
+    /// Le retour de `id` porte la différence sec − traité.
+    pub fn effects_diff_monitor(&self, id: ChannelId) -> bool {
+        self.channel(id).is_some_and(|c| c.effects_diff_monitor)
+    }
+
+    /// Gain gauche/droite d'un canal dans un bus : le mix normal s'il y
+    /// est routé (post-fader), plus son retour pré-fader vers ce bus.
+    pub fn bus_send(&self, id: ChannelId, bus: ChannelId) -> (f32, f32) {
+        let (l, r) = self.routed_gain(id, bus);
+        let monitor = self.monitor_gain(id, bus);
+        (l + monitor, r + monitor)
+    }
+
+    /// Gain gauche/droite d'un canal dans un bus par ses routes : la
+    /// route directe, plus les chemins à travers des sous-mix, dont les
+    /// gains (volume × pan, mute) s'appliquent en chemin.
+    pub fn routed_gain(&self, id: ChannelId, bus: ChannelId) -> (f32, f32) {
+        let (l, r) = self.gain(id);
+        if l == 0.0 && r == 0.0 {
+            return (0.0, 0.0);
+        }
+        let (path_l, path_r) = self.path_gain(id, bus, MAX_SUBMIX_DEPTH);
+        (l * path_l, r * path_r)
+    }
+
+    /// Somme des chemins de `from` vers `bus` : 1 par route directe, le
+    /// gain du sous-mix traversé sinon.
+    fn path_gain(&self, from: ChannelId, bus: ChannelId, depth: usize) -> (f32, f32) {
+        let mut total = (0.0, 0.0);
+        for route in self.routes.iter().filter(|r| r.from == from) {
+            if route.to == bus {
+                total.0 += 1.0;
+                total.1 += 1.0;
+            } else if depth > 0 && self.channel(route.to).is_some_and(|c| c.submix) {
+                let (l, r) = self.gain(route.to);
+                let (path_l, path_r) = self.path_gain(route.to, bus, depth - 1);
+                total.0 += l * path_l;
+                total.1 += r * path_r;
+            }
+        }
+        total
+    }
+
+    /// Génération de la mesure de sonie d'un bus (`None` : pas mesuré).
+    pub fn loudness_meter(&self, id: ChannelId) -> Option<u64> {
+        self.channel(id).and_then(|c| c.loudness_meter)
+    }
+
+    /// La crête vraie d'un bus est-elle mesurée ?
+    pub fn true_peak_meter(&self, id: ChannelId) -> bool {
+        self.channel(id).is_some_and(|c| c.true_peak_meter)
+    }
+
+    /// Nombre de canaux d'un bus (stéréo s'il n'existe pas).
+    pub fn bus_channel_count(&self, id: ChannelId) -> ChannelCount {
+        self.channel(id)
+            .map_or_else(ChannelCount::default, |c| c.channel_count)
+    }
+
+    pub fn delay_ms(&self, id: ChannelId) -> f32 {
+        self.channel(id).map_or(0.0, |c| c.delay_ms)
+    }
+
+    /// Retard de la sortie d'un bus vers son device (0 s'il n'existe pas).
+    pub fn output_delay_ms(&self, id: ChannelId) -> f32 {
+        self.channel(id).map_or(0.0, |c| c.output_delay_ms)
+    }
+
+    /// `(phase_invert, dc_block)` d'une entrée.
+    pub fn input_options(&self, id: ChannelId) -> (bool, bool) {
+        self.channel(id)
+            .map_or((false, false), |c| (c.phase_invert, c.dc_block))
+    }
+
+    /// Étage de dispatch vers un device de sortie.
+    ///
+    /// `buffer` est le signal destiné au device qui joue `buses`. Si aucun
+    /// de ces bus n'a le droit de jouer (solo d'un autre bus), le buffer
+    /// est remplacé par du silence. À appeler APRÈS les meters : les
+    /// niveaux et l'enregistrement voient toujours l'audio réel.
+    pub fn dispatch_to_device(&self, buses: &[ChannelId], buffer: &mut [f32]) {
+        let audible = buses
+            .iter()
+            .any(|id| self.channel(*id).is_some_and(|c| c.dispatches));
+        if !audible {
+            buffer.fill(0.0);
+        }
+    }
+
+    /// Détection de silence propre à un canal, si surchargée.
+    pub fn signal_presence(&self, id: ChannelId) -> Option<SignalPresenceConfig> {
+        self.channel(id).and_then(|c| c.signal_presence)
+    }
+
+    /// Tranche de canaux du device lue par un canal (défaut : tout le device).
+    pub fn device_slice(&self, id: ChannelId) -> ChannelSlice {
+        self.channel(id)
+            .map_or_else(ChannelSlice::default, |c| c.device_slice)
+    }
+}
+
+/// Côté "publication" de l'état runtime : vit sur le thread de contrôle.
+///
+/// # Le design "state swap"
+/// Avant : des `Arc<Mutex<f32>>` lus en `try_lock` par le callback.
+/// Un déplacement de fader prenait le même lock que le callback, et
+/// un `try_lock` raté = un buffer avec les anciens paramètres.
+///
+/// Maintenant : le thread de contrôle construit un `MixerRuntimeState`
+/// complet et l'envoie dans un channel crossbeam (lock-free).
+/// Le callback récupère le plus récent avec `try_recv` : jamais d'attente,
+/// jamais de lock partagé avec l'UI.
+///
+/// # Les files de commandes
+/// Chaque lecteur a sa file d'`EngineCommand` (voir `command_queue`) :
+/// des états complets, mais aussi des changements fins (`SetGain`). Le
+/// lecteur les applique dans l'ordre à sa propre copie de l'état. Chaque
+/// commande part dans toutes les files : deux pipelines vivants (pendant
+/// une bascule) voient la même suite de commandes.
+/// - `publish` ne peut pas échouer : un état complet remplace tout ce qui
+///   le précède, donc file pleine → on jette les plus anciennes.
+/// - `send` refuse une commande quand une file est pleine, et ne la met
+///   alors dans aucune : perdre un `SetGain` suivi d'autres `SetGain`
+///   appliquerait le mauvais gain.
+///
+/// Sans lecteur (moteur arrêté), rien n'est mis en file : le prochain
+/// lecteur partira de `latest`, qui a déjà tout appliqué.
+///
+/// # Et la libération mémoire ?
+/// Désallouer dans le callback audio est interdit (free peut bloquer).
+/// Le lecteur renvoie les anciens snapshots par un second channel
+/// ("garbage"), vidé ici à chaque publication : c'est le thread de
+/// contrôle qui libère.
+#[derive(Clone)]
+pub struct SharedMixerState {
+    /// Dernier snapshot publié, pour initialiser les nouveaux lecteurs.
+    /// Ce Mutex n'est JAMAIS pris par le thread audio.
+    latest: Arc<Mutex<Arc<MixerRuntimeState>>>,
+    /// File de chaque lecteur vivant (un par pipeline ouvert). Pris
+    /// après `latest`, jamais par le thread audio.
+    queues: Arc<Mutex<Vec<ReaderQueue>>>,
+    garbage_tx: Sender<Arc<MixerRuntimeState>>,
+    garbage_rx: Receiver<Arc<MixerRuntimeState>>,
+}
+
+/// File d'un lecteur, vue du thread de contrôle.
+struct ReaderQueue {
+    tx: Sender<EngineCommand>,
+    /// Pour jeter les plus anciennes quand un état complet arrive
+    rx: Receiver<EngineCommand>,
+    /// Passe à `false` quand le lecteur est droppé
+    open: Arc<AtomicBool>,
+}
+
+impl SharedMixerState {
+    pub fn new() -> Self {
+        let (garbage_tx, garbage_rx) = crossbeam_channel::bounded(ENGINE_QUEUE_CAPACITY);
+        Self {
+            latest: Arc::new(Mutex::new(Arc::new(MixerRuntimeState::default()))),
+            queues: Arc::new(Mutex::new(Vec::new())),
+            garbage_tx,
+            garbage_rx,
+        }
+    }
+
+    /// Publie un nouveau snapshot extrait du mixer.
+    /// À appeler après CHAQUE modification du mixer.
+    pub fn update_from_mixer(&self, mixer: &Mixer) {
+        self.publish(MixerRuntimeState::from_mixer(mixer));
+    }
+
+    /// Publie un snapshot (la version est attribuée ici).
+    pub fn publish(&self, mut state: MixerRuntimeState) {
+        // Libérer les snapshots rendus par le thread audio
+        while self.garbage_rx.try_recv().is_ok() {}
+
+        let Ok(mut latest) = self.latest.lock() else {
+            return;
+        };
+        state.version = latest.version + 1;
+        let snapshot = Arc::new(state);
+        *latest = snapshot.clone();
+
+        // File pleine (lecteur lent) → jeter les plus anciennes : cet
+        // état les remplace toutes. Le dernier publié est celui qui reste.
+        for queue in self.open_queues().iter() {
+            let mut pending = EngineCommand::ReplaceRuntimeState(snapshot.clone());
+            while let Err(TrySendError::Full(back)) = queue.tx.try_send(pending) {
+                let _ = queue.rx.try_recv();
+                pending = back;
+            }
+        }
+    }
+
+    /// Envoie une commande au thread audio, sans jamais attendre.
+    ///
+    /// Une file pleine → `CommandQueueFull`, et la commande n'est
+    /// appliquée nulle part (ni ici, ni par aucun lecteur). La version
+    /// d'un état complet est attribuée ici, comme pour `publish`.
+    ///
+    /// Lock de l'état empoisonné → `LockPoisoned` : la commande n'est
+    /// pas partie, l'appelant doit le savoir.
+    pub fn send(&self, command: EngineCommand) -> TroubadourResult<()> {
+        while self.garbage_rx.try_recv().is_ok() {}
+
+        let mut latest = self
+            .latest
+            .lock()
+            .map_err(|_| TroubadourError::LockPoisoned("runtime state"))?;
+        let command = match command {
+            EngineCommand::ReplaceRuntimeState(state) => {
+                let mut state = Arc::unwrap_or_clone(state);
+                state.version = latest.version + 1;
+                EngineCommand::ReplaceRuntimeState(Arc::new(state))
+            }
+            command => command,
+        };
+
+        // Seul ce thread remplit les files (sous le lock) : une file qui
+        // n'est pas pleine maintenant ne le sera pas avant l'envoi
+        let queues = self.open_queues();
+        if let Some(full) = queues.iter().find(|q| q.tx.is_full()) {
+            return Err(TroubadourError::CommandQueueFull(full.tx.len()));
+        }
+        for queue in queues.iter() {
+            // Ni pleine, ni déconnectée (`queue.rx` la garde ouverte)
+            let _ = queue.tx.try_send(command.clone());
+        }
+        drop(queues);
+
+        match command {
+            EngineCommand::ReplaceRuntimeState(state) => *latest = state,
+            command => Arc::make_mut(&mut latest).apply(&command),
+        }
+        Ok(())
+    }
+
+    /// Commandes en attente devant le lecteur le plus en retard.
+    pub fn queue_depth(&self) -> usize {
+        self.open_queues()
+            .iter()
+            .map(|q| q.tx.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// `true` si au moins un pipeline lit les commandes.
+    pub fn has_readers(&self) -> bool {
+        !self.open_queues().is_empty()
+    }
+
+    /// `true` si `send` refuserait une commande maintenant.
+    pub fn is_queue_full(&self) -> bool {
+        self.open_queues().iter().any(|q| q.tx.is_full())
+    }
+
+    /// Files des lecteurs vivants ; celles des lecteurs droppés sont
+    /// retirées avec ce qui y traînait (jamais lu).
+    fn open_queues(&self) -> MutexGuard<'_, Vec<ReaderQueue>> {
+        let mut queues = self
+            .queues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        queues.retain(|q| q.open.load(Ordering::Acquire));
+        queues
+    }
+
+    /// Dernier snapshot publié (côté contrôle, pour l'UI et les tests).
+    pub fn snapshot(&self) -> Arc<MixerRuntimeState> {
+        self.latest
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    /// Crée un lecteur pour le callback audio, initialisé au dernier
+    /// snapshot, avec sa propre file. Les autres lecteurs gardent leurs
+    /// commandes en attente.
+    pub fn reader(&self) -> RuntimeStateReader {
+        // `latest` tenu jusqu'à l'inscription de la file : aucune commande
+        // ne peut tomber entre la copie et la première lecture
+        let latest = self
+            .latest
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        // Copie propre au lecteur, avec de la marge : un canal ou une
+        // route de plus ne réalloue pas dans le callback
+        let mut current = MixerRuntimeState::clone(&latest);
+        current.channels.reserve(READER_HEADROOM);
+        current.routes.reserve(READER_HEADROOM);
+
+        let (tx, rx) = crossbeam_channel::bounded(ENGINE_QUEUE_CAPACITY);
+        let open = Arc::new(AtomicBool::new(true));
+        self.open_queues().push(ReaderQueue {
+            tx,
+            rx: rx.clone(),
+            open: open.clone(),
+        });
+        drop(latest);
+        RuntimeStateReader {
+            current,
+            rx,
+            garbage_tx: self.garbage_tx.clone(),
+            effects_swap: None,
+            open,
+        }
+    }
+}
+
+impl Default for SharedMixerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Côté "lecture" : vit dans le callback audio.
+///
+/// Ne prend aucun lock, n'alloue rien, ne libère rien.
+pub struct RuntimeStateReader {
+    /// Copie de l'état, modifiée en place par les commandes
+    current: MixerRuntimeState,
+    rx: Receiver<EngineCommand>,
+    garbage_tx: Sender<Arc<MixerRuntimeState>>,
+    /// Dernière chaîne d'effets de rechange reçue, pas encore reprise
+    effects_swap: Option<Arc<EffectsSwap>>,
+    open: Arc<AtomicBool>,
+}
+
+impl RuntimeStateReader {
+    /// Vide la file de commandes, dans l'ordre, et retourne l'état obtenu.
+    /// À appeler en tête de chaque bloc.
+    pub fn current(&mut self) -> &MixerRuntimeState {
+        while let Ok(command) = self.rx.try_recv() {
+            self.current.apply(&command);
+            match command {
+                EngineCommand::ReplaceRuntimeState(state) => {
+                    // Rendre l'état au thread de contrôle pour qu'il le libère.
+                    // Si la poubelle est pleine, tant pis : libéré ici (rare).
+                    let _ = self.garbage_tx.try_send(state);
+                }
+                // Une rechange plus récente remplace la précédente (le
+                // thread de contrôle en garde une référence : pas de
+                // libération ici)
+                EngineCommand::SwapEffectsProcessor(swap) => self.effects_swap = Some(swap),
+                _ => {}
+            }
+        }
+        &self.current
+    }
+
+    /// `current`, avec la chaîne d'effets de rechange reçue depuis le
+    /// dernier appel, à installer par le pipeline.
+    pub fn current_with_effects_swap(&mut self) -> (&MixerRuntimeState, Option<Arc<EffectsSwap>>) {
+        self.current();
+        (&self.current, self.effects_swap.take())
+    }
+}
+
+impl Drop for RuntimeStateReader {
+    fn drop(&mut self) {
+        self.open.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use troubadour_shared::mixer::MixerConfig;
+
+    use crate::dsp::EffectsChain;
+    use crate::taper::VolumeDecibels;
+
+    fn setup() -> (Mixer, SharedMixerState) {
+        let mixer = Mixer::from_config(MixerConfig::default_setup());
+        let shared = SharedMixerState::new();
+        shared.update_from_mixer(&mixer);
+        (mixer, shared)
+    }
+
+    #[test]
+    fn snapshot_reflects_mixer() {
+        let (mut mixer, shared) = setup();
+        mixer.set_volume(ChannelId(0), 0.0);
+        mixer.set_delay(ChannelId(0), 50.0);
+        shared.update_from_mixer(&mixer);
+
+        let snapshot = shared.snapshot();
+        assert_eq!(snapshot.version, 2);
+        assert_eq!(snapshot.gain(ChannelId(0)), (0.0, 0.0));
+        assert_eq!(snapshot.delay_ms(ChannelId(0)), 50.0);
+        assert_eq!(snapshot.routes.len(), 3);
+        assert!(!snapshot.all_inputs_muted);
+    }
+
+    #[test]
+    fn emergency_passthrough_sums_routed_inputs_at_unity() {
+        let (mut mixer, shared) = setup();
+        mixer.set_volume(ChannelId(0), 0.2);
+        mixer.set_pan(ChannelId(1), -1.0);
+        mixer.set_mute(ChannelId(1), true);
+        mixer.set_volume(ChannelId(3), 0.1);
+        // Browser joue seulement sur Speakers
+        mixer.remove_route(ChannelId(2), ChannelId(3));
+        mixer.add_route(ChannelId(2), ChannelId(4));
+        mixer.set_volume(ChannelId(2), 0.5);
+        mixer.set_emergency_passthrough(Some(ChannelId(3))).unwrap();
+        shared.update_from_mixer(&mixer);
+        let state = shared.snapshot();
+
+        // Bus de repli : somme brute des entrées routées, fader à l'unité
+        for input in [ChannelId(0), ChannelId(1)] {
+            assert_eq!(state.bus_send(input, ChannelId(3)), (1.0, 1.0));
+        }
+        assert_eq!(state.bus_send(ChannelId(2), ChannelId(3)), (0.0, 0.0));
+        assert_eq!(state.bus_gain(ChannelId(3)), 1.0);
+        assert!(!state.all_inputs_muted);
+        // Un canal absent du bus de repli garde son mix
+        assert_eq!(
+            state.bus_send(ChannelId(2), ChannelId(4)),
+            mixer.effective_gain(ChannelId(2))
+        );
+
+        mixer.set_emergency_passthrough(None).unwrap();
+        shared.update_from_mixer(&mixer);
+        let state = shared.snapshot();
+        assert_eq!(state.gain(ChannelId(0)), mixer.effective_gain(ChannelId(0)));
+        assert_eq!(state.bus_gain(ChannelId(3)), 0.1);
+    }
+
+    #[test]
+    fn bus_solo_silences_other_devices_only() {
+        let (mut mixer, shared) = setup();
+        mixer.set_bus_solo(ChannelId(4), true).unwrap(); // A2 = retour stream
+        shared.update_from_mixer(&mixer);
+        let state = shared.snapshot();
+
+        // Même mix envoyé aux deux devices
+        let mix = vec![0.25_f32, -0.5, 0.75, -1.0];
+        let mut a1_device = mix.clone();
+        let mut a2_device = mix.clone();
+        state.dispatch_to_device(&[ChannelId(3)], &mut a1_device);
+        state.dispatch_to_device(&[ChannelId(4)], &mut a2_device);
+
+        assert!(a1_device.iter().all(|&s| s == 0.0));
+        assert_eq!(a2_device, mix);
+        // Les gains (donc les meters) des entrées ne bougent pas
+        assert!(state.gain(ChannelId(0)).0 > 0.0);
+
+        // Fin du solo : tout le monde rejoue
+        mixer.set_bus_solo(ChannelId(4), false).unwrap();
+        shared.update_from_mixer(&mixer);
+        let mut a1_device = mix.clone();
+        shared
+            .snapshot()
+            .dispatch_to_device(&[ChannelId(3)], &mut a1_device);
+        assert_eq!(a1_device, mix);
+    }
+
+    #[test]
+    fn reader_sees_latest_snapshot() {
+        let (mut mixer, shared) = setup();
+        let mut reader = shared.reader();
+        assert_eq!(reader.current().version, 1);
+
+        // Plus de publications que la file ne peut en contenir
+        for i in 0..(ENGINE_QUEUE_CAPACITY * 3) {
+            mixer.set_volume(ChannelId(0), i as f32 / 100.0);
+            shared.update_from_mixer(&mixer);
+        }
+        let state = reader.current();
+        assert_eq!(state.version, 1 + ENGINE_QUEUE_CAPACITY as u64 * 3);
+        assert_eq!(*state, *shared.snapshot());
+    }
+
+    fn gain_command(level: f32) -> EngineCommand {
+        EngineCommand::SetGain {
+            channel: ChannelId(0),
+            gain: (level, level),
+            volume: level,
+            fade: None,
+        }
+    }
+
+    #[test]
+    fn reader_applies_commands_in_order() {
+        let (mut mixer, shared) = setup();
+        let mut reader = shared.reader();
+
+        shared.send(gain_command(0.1)).unwrap();
+        mixer.set_mute(ChannelId(1), true);
+        shared.send(EngineCommand::state_of(&mixer)).unwrap();
+        shared.send(gain_command(0.3)).unwrap();
+        assert_eq!(shared.queue_depth(), 3);
+
+        // L'état complet arrive après le premier gain et l'écrase ; le
+        // second gain, envoyé après lui, survit
+        let state = reader.current();
+        assert_eq!(state.gain(ChannelId(0)), (0.3, 0.3));
+        assert!(state.channel(ChannelId(1)).unwrap().muted);
+        assert_eq!(state.version, 4);
+        assert_eq!(*state, *shared.snapshot());
+        assert_eq!(shared.queue_depth(), 0);
+    }
+
+    #[test]
+    fn every_reader_sees_every_command() {
+        let (_mixer, shared) = setup();
+        let mut first = shared.reader();
+        shared.send(gain_command(0.1)).unwrap();
+        // Un nouveau lecteur ne prive pas l'ancien de ses commandes
+        let mut second = shared.reader();
+        shared.send(gain_command(0.2)).unwrap();
+        assert_eq!(second.current().gain(ChannelId(0)), (0.2, 0.2));
+        assert_eq!(shared.queue_depth(), 2);
+        assert_eq!(first.current().gain(ChannelId(0)), (0.2, 0.2));
+        assert_eq!(shared.queue_depth(), 0);
+
+        // Une file pleine refuse la commande pour tous
+        for _ in 0..ENGINE_QUEUE_CAPACITY {
+            shared.send(gain_command(0.3)).unwrap();
+            second.current();
+        }
+        assert!(shared.send(gain_command(0.4)).is_err());
+        assert_eq!(second.current().gain(ChannelId(0)), (0.3, 0.3));
+        assert_eq!(first.current().gain(ChannelId(0)), (0.3, 0.3));
+
+        // Le lecteur droppé ne retient plus rien
+        drop(first);
+        shared.send(gain_command(0.5)).unwrap();
+        assert_eq!(shared.queue_depth(), 1);
+        assert_eq!(second.current().gain(ChannelId(0)), (0.5, 0.5));
+    }
+
+    #[test]
+    fn full_queue_refuses_commands() {
+        let (_mixer, shared) = setup();
+        let mut reader = shared.reader();
+        for i in 0..ENGINE_QUEUE_CAPACITY {
+            shared.send(gain_command(i as f32 / 100.0)).unwrap();
+        }
+        assert!(shared.is_queue_full());
+
+        let err = shared.send(gain_command(1.0)).unwrap_err();
+        assert!(matches!(
+            err,
+            TroubadourError::CommandQueueFull(ENGINE_QUEUE_CAPACITY)
+        ));
+        // Refusée : appliquée nulle part
+        let last = (ENGINE_QUEUE_CAPACITY - 1) as f32 / 100.0;
+        assert_eq!(shared.snapshot().gain(ChannelId(0)), (last, last));
+        assert_eq!(reader.current().gain(ChannelId(0)), (last, last));
+        shared.send(gain_command(1.0)).unwrap();
+    }
+
+    #[test]
+    fn ramps_and_effects_swaps_reach_the_reader() {
+        let (mut mixer, shared) = setup();
+        let mut reader = shared.reader();
+        assert!(shared.has_readers());
+
+        mixer.fade_bus_volume(ChannelId(3), VolumeDecibels(-12.0), 50.0);
+        let ramp = EngineCommand::ramp_of(&mixer, ChannelId(3)).unwrap();
+        assert!(EngineCommand::ramp_of(&mixer, ChannelId(4)).is_none());
+        shared.send(ramp).unwrap();
+        let swap = EffectsSwap::new(EffectsChain::new());
+        shared
+            .send(EngineCommand::SwapEffectsProcessor(swap.clone()))
+            .unwrap();
+
+        let (state, received) = reader.current_with_effects_swap();
+        let bus = state.channel(ChannelId(3)).unwrap();
+        assert_eq!(bus.fade, mixer.bus_fade(ChannelId(3)));
+        assert_eq!(bus.volume, mixer.channel(ChannelId(3)).unwrap().volume);
+
+        // Installée une seule fois ; l'ancienne chaîne reste dans la rechange
+        let mut live = EffectsChain::default_mic_chain();
+        received.unwrap().install(&mut live);
+        assert!(live.is_empty());
+        assert!(swap.is_installed());
+        swap.install(&mut live);
+        assert!(live.is_empty());
+        assert!(reader.current_with_effects_swap().1.is_none());
+    }
+
+    #[test]
+    fn poisoned_state_lock_is_reported_by_send() {
+        let (_mixer, shared) = setup();
+        let latest = shared.latest.clone();
+        let _ = std::thread::spawn(move || {
+            let _held = latest.lock().unwrap();
+            panic!("poisoning the state lock");
+        })
+        .join();
+        assert!(matches!(
+            shared.send(gain_command(0.5)),
+            Err(TroubadourError::LockPoisoned(_))
+        ));
+    }
+
+    #[test]
+    fn nothing_is_queued_without_a_reader() {
+        let (_mixer, shared) = setup();
+        let reader = shared.reader();
+        drop(reader);
+        for _ in 0..ENGINE_QUEUE_CAPACITY * 2 {
+            shared.send(gain_command(0.5)).unwrap();
+        }
+        assert_eq!(shared.queue_depth(), 0);
+        assert_eq!(shared.snapshot().gain(ChannelId(0)), (0.5, 0.5));
+    }
+
+    #[test]
+    fn new_reader_starts_from_latest() {
+        let (mut mixer, shared) = setup();
+        mixer.set_mute(ChannelId(0), true);
+        mixer.set_mute(ChannelId(1), true);
+        mixer.set_mute(ChannelId(2), true);
+        shared.update_from_mixer(&mixer);
+
+        let mut reader = shared.reader();
+        assert!(reader.current().all_inputs_muted);
+    }
+
+    #[test]
+    fn stress_concurrent_publish_and_read() {
+        let (mut mixer, shared) = setup();
+        let mut reader = shared.reader();
+        const UPDATES: usize = 20_000;
+
+        let writer = {
+            let shared = shared.clone();
+            std::thread::spawn(move || {
+                for i in 0..UPDATES {
+                    mixer.set_volume(ChannelId(0), (i % 200) as f32 / 100.0);
+                    shared.update_from_mixer(&mixer);
+                }
+                mixer.set_volume(ChannelId(0), 0.5);
+                shared.update_from_mixer(&mixer);
+            })
+        };
+
+        // "Callback audio" : lit le snapshot et fait un peu de travail
+        let mut worst = Duration::ZERO;
+        let mut last_version = 0;
+        while !writer.is_finished() {
+            let start = Instant::now();
+            let state = reader.current();
+            assert!(state.version >= last_version, "Snapshots went backwards");
+            last_version = state.version;
+            let (l, r) = state.gain(ChannelId(0));
+            let mut acc = 0.0_f32;
+            for i in 0..256 {
+                acc += (i as f32) * l - (i as f32) * r;
+            }
+            std::hint::black_box(acc);
+            worst = worst.max(start.elapsed());
+        }
+        writer.join().unwrap();
+
+        // Aucune attente sur un lock : une lecture + 256 multiplications
+        // prend des microsecondes. La borne est large pour absorber la
+        // préemption de l'OS sur une machine de CI chargée, mais un lecteur
+        // bloqué derrière 20 000 publications la dépasserait largement.
+        assert!(
+            worst < Duration::from_millis(50),
+            "Reader stalled for {worst:?}"
+        );
+
+        // Cohérence éventuelle : le lecteur finit sur le dernier état publié
+        let state = reader.current();
+        assert_eq!(state.version, UPDATES as u64 + 2);
+        let expected = (
+            0.5 * std::f32::consts::FRAC_PI_4.cos(),
+            0.5 * std::f32::consts::FRAC_PI_4.sin(),
+        );
+        let (l, r) = state.gain(ChannelId(0));
+        assert!((l - expected.0).abs() < 1e-6 && (r - expected.1).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,494 @@
+use std::collections::BTreeMap;
+
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::mixer::{ChannelKind, MixerConfig};
+
+use crate::device::SystemDefaults;
+use crate::engine::ActiveStream;
+
+/// Identifie un stream : un device, dans un sens.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StreamKey {
+    pub device_name: String,
+    pub is_input: bool,
+}
+
+impl StreamKey {
+    pub fn new(device_name: impl Into<String>, is_input: bool) -> Self {
+        Self {
+            device_name: device_name.into(),
+            is_input,
+        }
+    }
+}
+
+impl ActiveStream {
+    pub fn key(&self) -> StreamKey {
+        StreamKey::new(self.device_name.clone(), self.is_input)
+    }
+}
+
+/// Streams ouverts et touchés par un changement d'assignation.
+#[derive(Debug, Default, PartialEq)]
+pub struct StreamChanges {
+    pub opened: Vec<StreamKey>,
+    pub closed: Vec<StreamKey>,
+}
+
+impl StreamChanges {
+    pub fn is_empty(&self) -> bool {
+        self.opened.is_empty() && self.closed.is_empty()
+    }
+}
+
+/// Streams ouverts par le moteur, chacun avec les canaux qu'il sert.
+///
+/// # Pourquoi pas "tout fermer, tout rouvrir" ?
+/// Changer le device d'UN canal en recréant tous les streams coupe
+/// tous les autres canaux le temps de la réouverture (clic, trou de
+/// quelques centaines de ms). Ici, on calcule le plus petit changement :
+/// un stream n'est fermé que s'il ne sert plus aucun canal, et n'est
+/// ouvert que si aucun stream du device n'existe déjà. Les autres
+/// ne sont pas touchés (même objet, ni stop ni start).
+///
+/// Générique sur `S` (le handle qui garde le stream vivant : `cpal::Stream`
+/// dans le moteur) pour tester la logique sans device. Fermer un stream =
+/// dropper son handle.
+pub struct StreamSet<S> {
+    /// Infos et handles en parallèle (même index) : `infos()` reste une
+    /// slice pour le diagnostic et l'export du graphe.
+    infos: Vec<ActiveStream>,
+    handles: Vec<S>,
+}
+
+impl<S> StreamSet<S> {
+    pub fn new() -> Self {
+        Self {
+            infos: Vec::new(),
+            handles: Vec::new(),
+        }
+    }
+
+    /// Ajoute un stream ouvert.
+    pub fn push(&mut self, info: ActiveStream, handle: S) {
+        self.infos.push(info);
+        self.handles.push(handle);
+    }
+
+    pub fn infos(&self) -> &[ActiveStream] {
+        &self.infos
+    }
+
+    /// Handle du stream ouvert sur `key`.
+    pub fn handle(&self, key: &StreamKey) -> Option<&S> {
+        self.position(key).map(|i| &self.handles[i])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.infos.is_empty()
+    }
+
+    /// Ferme tous les streams.
+    pub fn clear(&mut self) {
+        self.infos.clear();
+        self.handles.clear();
+    }
+
+    /// Stream qui sert `channel` dans le sens donné.
+    pub fn serving(&self, channel: ChannelId, is_input: bool) -> Option<&ActiveStream> {
+        self.infos
+            .iter()
+            .find(|s| s.is_input == is_input && s.target_channels.contains(&channel))
+    }
+
+    fn position(&self, key: &StreamKey) -> Option<usize> {
+        self.infos
+            .iter()
+            .position(|s| s.is_input == key.is_input && s.device_name == key.device_name)
+    }
+
+    fn close(&mut self, index: usize) -> StreamKey {
+        let info = self.infos.remove(index);
+        drop(self.handles.remove(index));
+        info.key()
+    }
+
+    /// Aligne les streams sur les assignations voulues (`desired_streams`)
+    /// avec le plus petit changement : streams gardés (canaux servis mis
+    /// à jour), fermés s'ils ne servent plus, ouverts s'ils manquent.
+    ///
+    /// En cas d'erreur d'ouverture, les streams déjà traités restent
+    /// dans leur nouvel état ; ceux qui ne servent plus sont fermés
+    /// avant toute ouverture (libérer le device d'abord).
+    pub fn refresh<E>(
+        &mut self,
+        desired: &BTreeMap<StreamKey, Vec<ChannelId>>,
+        mut open: impl FnMut(&StreamKey) -> Result<Option<(ActiveStream, S)>, E>,
+    ) -> Result<StreamChanges, E> {
+        let mut changes = StreamChanges::default();
+
+        let mut i = 0;
+        while i < self.infos.len() {
+            match desired.get(&self.infos[i].key()) {
+                Some(channels) => {
+                    self.infos[i].target_channels = channels.clone();
+                    i += 1;
+                }
+                None => changes.closed.push(self.close(i)),
+            }
+        }
+
+        for (key, channels) in desired {
+            if self.position(key).is_some() {
+                continue;
+            }
+            if let Some((mut info, handle)) = open(key)? {
+                info.target_channels = channels.clone();
+                self.push(info, handle);
+                changes.opened.push(key.clone());
+            }
+        }
+        Ok(changes)
+    }
+}
+
+impl<S> Default for StreamSet<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Streams que demandent les assignations de devices du mixer :
+/// un par (device, sens), avec les canaux qu'il sert, triés par id.
+///
+/// C'est la topologie voulue d'un état complet du mixer : calculée
+/// après un remplacement en bloc (preset), elle ne mélange jamais
+/// l'ancienne config et la nouvelle.
+///
+/// Un canal qui suit le défaut du système rejoint le stream du device
+/// par défaut actuel (`defaults`) : quand celui-ci change, `refresh`
+/// ne rouvre que ce stream.
+pub fn desired_streams(
+    config: &MixerConfig,
+    defaults: &SystemDefaults,
+) -> BTreeMap<StreamKey, Vec<ChannelId>> {
+    let mut desired: BTreeMap<StreamKey, Vec<ChannelId>> = BTreeMap::new();
+    // Un sous-mix n'a pas de device : aucun stream
+    for channel in config
+        .channels
+        .iter()
+        .filter(|c| c.kind != ChannelKind::Submix)
+    {
+        let is_input = channel.kind == ChannelKind::Input;
+        if let Some(device) = defaults.resolve(&channel.device_name, is_input) {
+            let key = StreamKey::new(device, is_input);
+            desired.entry(key).or_default().push(channel.id);
+        }
+    }
+    for channels in desired.values_mut() {
+        channels.sort_by_key(|c| c.0);
+    }
+    desired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::convert::Infallible;
+    use std::rc::Rc;
+    use std::sync::Mutex;
+    use troubadour_shared::mixer::{ChannelConfig, DeviceAssignment};
+
+    use crate::device::DefaultDeviceSource;
+
+    /// Faux stream : journalise son ouverture et sa fermeture (drop).
+    struct MockStream {
+        id: usize,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Drop for MockStream {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push(format!("stop #{}", self.id));
+        }
+    }
+
+    struct MockDevices {
+        next_id: usize,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl MockDevices {
+        fn new() -> Self {
+            Self {
+                next_id: 1,
+                log: Rc::default(),
+            }
+        }
+
+        fn open(
+            &mut self,
+            key: &StreamKey,
+        ) -> Result<Option<(ActiveStream, MockStream)>, Infallible> {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.log
+                .borrow_mut()
+                .push(format!("start #{id} {}", key.device_name));
+            let info = ActiveStream {
+                device_name: key.device_name.clone(),
+                is_input: key.is_input,
+                channels: 2,
+                sample_rate: 48_000,
+                target_channels: Vec::new(),
+            };
+            Ok(Some((
+                info,
+                MockStream {
+                    id,
+                    log: self.log.clone(),
+                },
+            )))
+        }
+
+        fn take_log(&self) -> Vec<String> {
+            self.log.borrow_mut().drain(..).collect()
+        }
+    }
+
+    fn stream_id(set: &StreamSet<MockStream>, device: &str) -> Option<usize> {
+        set.handle(&StreamKey::new(device, true)).map(|s| s.id)
+    }
+
+    /// ch1 et ch2 partagent l'interface, ch3 est sur un micro USB.
+    fn session(devices: &mut MockDevices) -> (MixerConfig, StreamSet<MockStream>) {
+        let mut config = MixerConfig {
+            channels: vec![
+                ChannelConfig::input(1, "Guitar"),
+                ChannelConfig::input(2, "Vocals"),
+                ChannelConfig::input(3, "Talkback"),
+            ],
+            routes: vec![],
+            crossfades: vec![],
+            links: vec![],
+            bus_follows: vec![],
+        };
+        config.channels[0].device_name = DeviceAssignment::Specific("Interface".into());
+        config.channels[1].device_name = DeviceAssignment::Specific("Interface".into());
+        config.channels[2].device_name = DeviceAssignment::Specific("USB Mic".into());
+
+        let mut set = StreamSet::new();
+        set.refresh(&desired_streams(&config, &SystemDefaults::default()), |k| {
+            devices.open(k)
+        })
+        .unwrap();
+        (config, set)
+    }
+
+    fn refresh(
+        set: &mut StreamSet<MockStream>,
+        config: &MixerConfig,
+        devices: &mut MockDevices,
+    ) -> StreamChanges {
+        set.refresh(&desired_streams(config, &SystemDefaults::default()), |k| {
+            devices.open(k)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn shared_stream_closes_only_when_it_serves_nobody() {
+        let mut devices = MockDevices::new();
+        let (mut config, mut set) = session(&mut devices);
+        devices.take_log();
+
+        // ch3 rejoint l'interface : aucun stream ouvert, le micro USB se ferme
+        config.channels[2].device_name = DeviceAssignment::Specific("Interface".into());
+        let changes = refresh(&mut set, &config, &mut devices);
+        assert!(changes.opened.is_empty());
+        assert_eq!(devices.take_log(), ["stop #2"]);
+        assert_eq!(stream_id(&set, "Interface"), Some(1));
+        assert_eq!(
+            set.serving(ChannelId(3), true).unwrap().target_channels,
+            [ChannelId(1), ChannelId(2), ChannelId(3)]
+        );
+
+        // ch1 et ch2 sans device : l'interface sert encore ch3
+        config.channels[0].device_name = DeviceAssignment::None;
+        config.channels[1].device_name = DeviceAssignment::None;
+        refresh(&mut set, &config, &mut devices);
+        assert!(devices.take_log().is_empty());
+
+        config.channels[2].device_name = DeviceAssignment::None;
+        refresh(&mut set, &config, &mut devices);
+        assert_eq!(devices.take_log(), ["stop #1"]);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn refresh_applies_the_minimal_diff() {
+        let mut devices = MockDevices::new();
+        let (mut config, mut set) = session(&mut devices);
+        assert_eq!(
+            devices.take_log(),
+            ["start #1 Interface", "start #2 USB Mic"]
+        );
+
+        config.channels[2].device_name = DeviceAssignment::Specific("Headset".into());
+        let changes = set
+            .refresh(&desired_streams(&config, &SystemDefaults::default()), |k| {
+                devices.open(k)
+            })
+            .unwrap();
+
+        assert_eq!(changes.opened, [StreamKey::new("Headset", true)]);
+        assert_eq!(changes.closed, [StreamKey::new("USB Mic", true)]);
+        // Fermeture avant ouverture, l'interface n'est pas touchée
+        assert_eq!(devices.take_log(), ["stop #2", "start #3 Headset"]);
+        assert_eq!(stream_id(&set, "Interface"), Some(1));
+
+        // Rien n'a changé : rien à faire
+        let changes = set
+            .refresh(&desired_streams(&config, &SystemDefaults::default()), |k| {
+                devices.open(k)
+            })
+            .unwrap();
+        assert!(changes.is_empty());
+        assert!(devices.take_log().is_empty());
+    }
+
+    #[test]
+    fn preset_load_reconciles_once_from_the_whole_topology() {
+        let mut devices = MockDevices::new();
+        let mut config = MixerConfig::default_setup();
+        config.channels[0].device_name = DeviceAssignment::Specific("Interface".into());
+        config.channels[1].device_name = DeviceAssignment::Specific("Interface".into());
+        config.channels[3].device_name = DeviceAssignment::Specific("Speakers".into());
+        config.channels[4].device_name = DeviceAssignment::Specific("HD 600".into());
+        let mut set = StreamSet::new();
+        set.refresh(&desired_streams(&config, &SystemDefaults::default()), |k| {
+            devices.open(k)
+        })
+        .unwrap();
+        assert_eq!(
+            devices.take_log(),
+            ["start #1 HD 600", "start #2 Interface", "start #3 Speakers"]
+        );
+
+        // Le preset déplace ch1 sur le micro USB et A1 sur le casque
+        let mut preset = config.clone();
+        preset.channels[1].device_name = DeviceAssignment::Specific("USB Mic".into());
+        preset.channels[3].device_name = DeviceAssignment::Specific("Headset".into());
+        let topology = desired_streams(&preset, &SystemDefaults::default());
+        // Chaque ouverture voit déjà la topologie complète : jamais un
+        // device de l'ancienne config à côté d'un de la nouvelle
+        let changes = set
+            .refresh(&topology, |k| {
+                assert!(topology.contains_key(k));
+                devices.open(k)
+            })
+            .unwrap();
+
+        assert_eq!(changes.closed, [StreamKey::new("Speakers", false)]);
+        assert_eq!(
+            changes.opened,
+            [
+                StreamKey::new("Headset", false),
+                StreamKey::new("USB Mic", true)
+            ]
+        );
+        assert_eq!(
+            devices.take_log(),
+            ["stop #3", "start #4 Headset", "start #5 USB Mic"]
+        );
+        // L'interface garde ch0 sans être rouverte
+        assert_eq!(stream_id(&set, "Interface"), Some(2));
+        assert_eq!(
+            set.serving(ChannelId(0), true).unwrap().target_channels,
+            [ChannelId(0)]
+        );
+        let mut streams: Vec<_> = set.infos().iter().map(ActiveStream::key).collect();
+        streams.sort();
+        assert!(streams.iter().eq(topology.keys()));
+    }
+
+    #[test]
+    fn desired_streams_group_channels_by_device_and_direction() {
+        let mut config = MixerConfig::default_setup();
+        // Un sous-mix n'ouvre rien, même avec un device (config éditée)
+        config.channels.push(ChannelConfig::submix(5, "Stream mix"));
+        for channel in &mut config.channels {
+            channel.device_name = DeviceAssignment::Specific("Interface".into());
+        }
+        let desired = desired_streams(&config, &SystemDefaults::default());
+        assert_eq!(
+            desired[&StreamKey::new("Interface", true)],
+            [ChannelId(0), ChannelId(1), ChannelId(2)]
+        );
+        assert_eq!(
+            desired[&StreamKey::new("Interface", false)],
+            [ChannelId(3), ChannelId(4)]
+        );
+    }
+
+    /// Faux OS : son device de sortie par défaut change à la demande.
+    struct MockDefaults {
+        output: Mutex<String>,
+    }
+
+    impl DefaultDeviceSource for MockDefaults {
+        fn default_input_name(&self) -> Option<String> {
+            None
+        }
+
+        fn default_output_name(&self) -> Option<String> {
+            self.output.lock().ok().map(|name| name.clone())
+        }
+    }
+
+    #[test]
+    fn system_default_change_recreates_only_the_following_stream() {
+        let mut devices = MockDevices::new();
+        let os = MockDefaults {
+            output: Mutex::new("Speakers".into()),
+        };
+        let mut config = MixerConfig::default_setup();
+        config.channels[0].device_name = DeviceAssignment::Specific("Interface".into());
+        config.channels[3].device_name = DeviceAssignment::SystemDefault;
+        config.channels[4].device_name = DeviceAssignment::Specific("HD 600".into());
+
+        let mut set = StreamSet::new();
+        set.refresh(&desired_streams(&config, &os.system_defaults()), |k| {
+            devices.open(k)
+        })
+        .unwrap();
+        assert_eq!(
+            set.serving(ChannelId(3), false).unwrap().device_name,
+            "Speakers"
+        );
+        devices.take_log();
+
+        // L'utilisateur passe au casque dans les réglages de l'OS
+        *os.output.lock().unwrap() = "Headset".into();
+        let changes = set
+            .refresh(&desired_streams(&config, &os.system_defaults()), |k| {
+                devices.open(k)
+            })
+            .unwrap();
+
+        assert_eq!(changes.opened, [StreamKey::new("Headset", false)]);
+        assert_eq!(changes.closed, [StreamKey::new("Speakers", false)]);
+        assert_eq!(devices.take_log(), ["stop #3", "start #4 Headset"]);
+        assert_eq!(
+            set.serving(ChannelId(3), false).unwrap().device_name,
+            "Headset"
+        );
+        // Les streams des devices choisis explicitement n'ont pas bougé
+        assert_eq!(
+            set.handle(&StreamKey::new("HD 600", false)).map(|s| s.id),
+            Some(1)
+        );
+        assert_eq!(stream_id(&set, "Interface"), Some(2));
+    }
+}
@@ -0,0 +1,615 @@
+//! Copies d'un bus pour d'autres consommateurs : un visualiseur, un
+//! enregistreur, un script, ou un autre programme via TCP local.
+//!
+//! ```text
+//!  bus joué ──► TapWriter ──file bornée──► TapReader ──► consommateur
+//!  (callback)   copie le bloc,             dans le processus,
+//!               jette le plus ancien       ou TapServer ──TCP──► programme
+//!               si la file est pleine
+//! ```
+//!
+//! Un consommateur lent ne bloque jamais l'audio : quand sa file est
+//! pleine, le callback jette le bloc le plus ancien (compté) et pousse
+//! le nouveau. Les buffers lus reviennent au callback par une seconde
+//! file : une fois la file remplie, plus aucune allocation par bloc.
+//!
+//! # Format d'une trame TCP
+//! En-tête de 24 octets, little-endian, puis les samples f32 stéréo
+//! entrelacés :
+//!
+//! | octets | contenu                                   |
+//! |--------|-------------------------------------------|
+//! | 0..4   | `TTAP`                                    |
+//! | 4      | version (1)                               |
+//! | 5      | canaux (2)                                |
+//! | 6..8   | réservé (0)                               |
+//! | 8..16  | numéro de séquence du bloc (u64)          |
+//! | 16..20 | sample rate (u32)                         |
+//! | 20..24 | frames du bloc (u32)                      |
+//!
+//! Un trou dans les numéros de séquence : des blocs jetés faute d'avoir
+//! été lus à temps.
+
+use std::io::Write;
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TrySendError};
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+use troubadour_shared::tap::TapInfo;
+
+/// Début de chaque trame TCP.
+pub const FRAME_MAGIC: [u8; 4] = *b"TTAP";
+
+/// Version du format de trame.
+pub const FRAME_VERSION: u8 = 1;
+
+pub const FRAME_HEADER_LEN: usize = 24;
+
+/// Attente maximale du thread d'un `TapServer` entre deux vérifications
+/// de l'arrêt (et des connexions entrantes).
+const SERVER_POLL: Duration = Duration::from_millis(20);
+
+/// Un client TCP qui ne lit plus est lâché au bout de ce délai.
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Un bloc copié d'un bus, post-fader.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TapBlock {
+    /// Numéro du bloc dans le tap (0 au premier)
+    pub sequence: u64,
+    pub sample_rate: u32,
+    /// Stéréo entrelacé
+    pub samples: Vec<f32>,
+}
+
+#[derive(Default)]
+struct TapCounters {
+    written: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// Côté callback d'un tap.
+pub struct TapWriter {
+    id: u32,
+    bus: ChannelId,
+    capacity: usize,
+    blocks_tx: Sender<TapBlock>,
+    /// Même file que le lecteur : de quoi retirer le bloc le plus ancien
+    oldest_rx: Receiver<TapBlock>,
+    /// Buffers rendus par le lecteur
+    recycle_rx: Receiver<Vec<f32>>,
+    /// Buffer d'un bloc jeté, réutilisé pour le suivant
+    spare: Option<Vec<f32>>,
+    sequence: u64,
+    /// Blocs manqués par le callback (`Taps::miss_counter`) déjà comptés
+    misses_seen: u64,
+    /// Partagé avec le lecteur : seul propriétaire restant, le lecteur
+    /// a été droppé
+    counters: Arc<TapCounters>,
+}
+
+/// Côté consommateur d'un tap. Le tap se ferme quand il est droppé.
+pub struct TapReader {
+    id: u32,
+    bus: ChannelId,
+    blocks_rx: Receiver<TapBlock>,
+    recycle_tx: Sender<Vec<f32>>,
+    _counters: Arc<TapCounters>,
+}
+
+/// Crée un tap de `bus` gardant au plus `capacity` blocs non lus.
+pub fn tap_channel(id: u32, bus: ChannelId, capacity: usize) -> (TapWriter, TapReader) {
+    let capacity = capacity.max(1);
+    let (blocks_tx, blocks_rx) = crossbeam_channel::bounded(capacity);
+    // Un bloc de plus que la file : celui en cours de lecture
+    let (recycle_tx, recycle_rx) = crossbeam_channel::bounded(capacity + 1);
+    let counters = Arc::<TapCounters>::default();
+    let writer = TapWriter {
+        id,
+        bus,
+        capacity,
+        blocks_tx,
+        oldest_rx: blocks_rx.clone(),
+        recycle_rx,
+        spare: None,
+        sequence: 0,
+        misses_seen: 0,
+        counters: counters.clone(),
+    };
+    let reader = TapReader {
+        id,
+        bus,
+        blocks_rx,
+        recycle_tx,
+        _counters: counters,
+    };
+    (writer, reader)
+}
+
+impl TapWriter {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn bus(&self) -> ChannelId {
+        self.bus
+    }
+
+    /// Le lecteur a été droppé : plus rien à copier.
+    pub fn is_closed(&self) -> bool {
+        Arc::strong_count(&self.counters) == 1
+    }
+
+    /// Copie un bloc stéréo entrelacé dans le tap. Ne bloque jamais :
+    /// file pleine, le bloc le plus ancien est jeté.
+    ///
+    /// N'alloue que tant que le lecteur n'a pas rendu assez de buffers.
+    pub fn write(&mut self, stereo: &[f32], sample_rate: u32) {
+        if self.is_closed() {
+            return;
+        }
+        let mut samples = self
+            .spare
+            .take()
+            .or_else(|| self.recycle_rx.try_recv().ok())
+            .unwrap_or_else(|| Vec::with_capacity(stereo.len()));
+        samples.clear();
+        samples.extend_from_slice(stereo);
+        let mut block = TapBlock {
+            sequence: self.sequence,
+            sample_rate,
+            samples,
+        };
+        self.sequence += 1;
+        loop {
+            match self.blocks_tx.try_send(block) {
+                Ok(()) => break,
+                // `oldest_rx` garde la file ouverte : jamais déconnectée
+                Err(TrySendError::Disconnected(_)) => return,
+                Err(TrySendError::Full(rejected)) => {
+                    block = rejected;
+                    // Le lecteur a pu vider une place entre-temps :
+                    // rien à jeter, on réessaie
+                    if let Ok(oldest) = self.oldest_rx.try_recv() {
+                        self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                        self.spare = Some(oldest.samples);
+                    }
+                }
+            }
+        }
+        self.counters.written.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Compte `blocks` blocs que le callback n'a pas pu copier : le
+    /// lecteur voit le trou dans les numéros de séquence.
+    fn skip(&mut self, blocks: u64) {
+        if blocks == 0 || self.is_closed() {
+            return;
+        }
+        self.sequence += blocks;
+        self.counters.dropped.fetch_add(blocks, Ordering::Relaxed);
+    }
+
+    pub fn info(&self) -> TapInfo {
+        TapInfo {
+            id: self.id,
+            bus: self.bus,
+            capacity_blocks: self.capacity,
+            blocks_written: self.counters.written.load(Ordering::Relaxed),
+            blocks_dropped: self.counters.dropped.load(Ordering::Relaxed),
+            address: None,
+        }
+    }
+}
+
+impl TapReader {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn bus(&self) -> ChannelId {
+        self.bus
+    }
+
+    /// Bloc suivant, s'il y en a un.
+    pub fn try_read(&self) -> Option<TapBlock> {
+        self.blocks_rx.try_recv().ok()
+    }
+
+    /// Attend le bloc suivant. `Disconnected` : le tap a été fermé côté
+    /// moteur (et tous ses blocs lus).
+    pub fn read_timeout(&self, timeout: Duration) -> Result<TapBlock, RecvTimeoutError> {
+        self.blocks_rx.recv_timeout(timeout)
+    }
+
+    /// Rend le buffer d'un bloc lu au callback, qui le réutilise au
+    /// lieu d'allouer.
+    pub fn recycle(&self, block: TapBlock) {
+        let _ = self.recycle_tx.try_send(block.samples);
+    }
+}
+
+/// Taps ouverts, partagés moteur ↔ callback (le moteur ne prend le
+/// lock que pour en ouvrir, en fermer ou lire leurs compteurs).
+#[derive(Default)]
+pub struct Taps {
+    writers: Vec<TapWriter>,
+    next_id: u32,
+    /// Blocs jetés par les taps déjà fermés
+    retired_drops: u64,
+    /// Blocs que le callback n'a pas pu copier, lock pris par le moteur
+    misses: Arc<AtomicU64>,
+}
+
+impl Taps {
+    /// Ouvre un tap de `bus`.
+    pub fn open(&mut self, bus: ChannelId, capacity: usize) -> TapReader {
+        self.prune();
+        let id = self.next_id;
+        self.next_id += 1;
+        let (mut writer, reader) = tap_channel(id, bus, capacity);
+        writer.misses_seen = self.misses.load(Ordering::Relaxed);
+        self.writers.push(writer);
+        reader
+    }
+
+    /// Compteur que le callback incrémente quand `try_lock` échoue : le
+    /// bloc perdu est compté jeté par les taps du bus à la copie
+    /// suivante.
+    pub fn miss_counter(&self) -> Arc<AtomicU64> {
+        self.misses.clone()
+    }
+
+    /// Ferme le tap `id` : son lecteur voit la fin du flux une fois
+    /// les blocs restants lus. `false` : pas de tap `id`.
+    pub fn close(&mut self, id: u32) -> bool {
+        let Some(index) = self.writers.iter().position(|w| w.id == id) else {
+            return false;
+        };
+        let writer = self.writers.remove(index);
+        self.retired_drops += writer.counters.dropped.load(Ordering::Relaxed);
+        true
+    }
+
+    /// Oublie les taps dont le lecteur est parti.
+    fn prune(&mut self) {
+        let closed: Vec<u32> = self
+            .writers
+            .iter()
+            .filter(|w| w.is_closed())
+            .map(|w| w.id)
+            .collect();
+        for id in closed {
+            self.close(id);
+        }
+    }
+
+    /// Copie un bloc du bus `bus` dans chacun de ses taps. Appelé par le
+    /// callback : un tap fermé côté lecteur y est seulement sauté.
+    pub fn write(&mut self, bus: ChannelId, stereo: &[f32], sample_rate: u32) {
+        let misses = self.misses.load(Ordering::Relaxed);
+        for writer in &mut self.writers {
+            // Blocs manqués depuis la dernière copie : ceux du bus copié
+            let missed = misses - writer.misses_seen;
+            writer.misses_seen = misses;
+            if writer.bus == bus {
+                writer.skip(missed);
+                writer.write(stereo, sample_rate);
+            }
+        }
+    }
+
+    /// Aucun tap n'a de lecteur.
+    pub fn is_empty(&self) -> bool {
+        self.writers.iter().all(TapWriter::is_closed)
+    }
+
+    /// Taps ouverts, par id croissant.
+    pub fn infos(&mut self) -> Vec<TapInfo> {
+        self.prune();
+        self.writers.iter().map(TapWriter::info).collect()
+    }
+
+    /// Blocs jetés par tous les taps depuis le démarrage du moteur.
+    pub fn overflows(&self) -> u64 {
+        self.retired_drops
+            + self
+                .writers
+                .iter()
+                .map(|w| w.counters.dropped.load(Ordering::Relaxed))
+                .sum::<u64>()
+    }
+}
+
+/// En-tête d'une trame TCP décodée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub sequence: u64,
+    pub sample_rate: u32,
+    pub frames: u32,
+}
+
+/// Écrit la trame TCP d'un bloc dans `out` (vidé d'abord).
+pub fn encode_frame(out: &mut Vec<u8>, block: &TapBlock) {
+    out.clear();
+    out.extend_from_slice(&FRAME_MAGIC);
+    out.extend_from_slice(&[FRAME_VERSION, 2, 0, 0]);
+    out.extend_from_slice(&block.sequence.to_le_bytes());
+    out.extend_from_slice(&block.sample_rate.to_le_bytes());
+    out.extend_from_slice(&((block.samples.len() / 2) as u32).to_le_bytes());
+    for sample in &block.samples {
+        out.extend_from_slice(&sample.to_le_bytes());
+    }
+}
+
+/// Lit l'en-tête d'une trame ; `None` si ce n'est pas une trame de tap
+/// de cette version.
+pub fn decode_frame_header(header: &[u8; FRAME_HEADER_LEN]) -> Option<FrameHeader> {
+    if header[..4] != FRAME_MAGIC || header[4] != FRAME_VERSION || header[5] != 2 {
+        return None;
+    }
+    let u32_at = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+    Some(FrameHeader {
+        sequence: u64::from_le_bytes(header[8..16].try_into().unwrap()),
+        sample_rate: u32_at(16),
+        frames: u32_at(20),
+    })
+}
+
+fn tap_error(context: &str, e: impl std::fmt::Display) -> TroubadourError {
+    TroubadourError::Network(format!("tap {context}: {e}"))
+}
+
+/// Sert un tap en TCP sur `127.0.0.1`, à un client à la fois.
+///
+/// Sans client, les blocs sont lus et jetés : le suivant à se connecter
+/// reçoit l'audio courant, pas une file en retard. Un client qui ne lit
+/// plus est lâché après `CLIENT_WRITE_TIMEOUT` ; pendant ce temps, c'est
+/// la file du tap qui déborde, jamais le callback qui attend.
+pub struct TapServer {
+    id: u32,
+    address: SocketAddr,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl TapServer {
+    /// Écoute sur `127.0.0.1:port` (0 : port choisi par le système).
+    pub fn bind(reader: TapReader, port: u16) -> TroubadourResult<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| tap_error("bind", e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| tap_error("bind", e))?;
+        let address = listener.local_addr().map_err(|e| tap_error("bind", e))?;
+        let id = reader.id();
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let running = running.clone();
+            std::thread::Builder::new()
+                .name(format!("tap-{id}"))
+                .spawn(move || serve(listener, reader, &running))
+                .map_err(|e| tap_error("thread", e))?
+        };
+        Ok(Self {
+            id,
+            address,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+fn accept(listener: &TcpListener) -> Option<TcpStream> {
+    let (stream, _) = listener.accept().ok()?;
+    stream.set_nonblocking(false).ok()?;
+    stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT)).ok()?;
+    let _ = stream.set_nodelay(true);
+    Some(stream)
+}
+
+fn serve(listener: TcpListener, reader: TapReader, running: &AtomicBool) {
+    let mut client = None;
+    let mut frame = Vec::new();
+    while running.load(Ordering::Relaxed) {
+        if client.is_none() {
+            client = accept(&listener);
+        }
+        match reader.read_timeout(SERVER_POLL) {
+            Ok(block) => {
+                if let Some(stream) = client.as_mut() {
+                    encode_frame(&mut frame, &block);
+                    if stream.write_all(&frame).is_err() {
+                        client = None;
+                    }
+                }
+                reader.recycle(block);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
+impl Drop for TapServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::time::Instant;
+
+    fn block_of(sequence: u64) -> Vec<f32> {
+        (0..512)
+            .map(|n| sequence as f32 + n as f32 * 1e-3)
+            .collect()
+    }
+
+    #[test]
+    fn blocks_arrive_whole_and_in_order() {
+        let (mut writer, reader) = tap_channel(0, ChannelId(3), 4);
+        for round in 0..3 {
+            for sequence in round * 4..round * 4 + 4 {
+                writer.write(&block_of(sequence), 48_000);
+            }
+            for sequence in round * 4..round * 4 + 4 {
+                let block = reader.try_read().unwrap();
+                assert_eq!(block.sequence, sequence);
+                assert_eq!(block.sample_rate, 48_000);
+                assert_eq!(block.samples, block_of(sequence));
+                reader.recycle(block);
+            }
+        }
+        assert!(reader.try_read().is_none());
+        let info = writer.info();
+        assert_eq!((info.blocks_written, info.blocks_dropped), (12, 0));
+    }
+
+    #[test]
+    fn full_tap_drops_its_oldest_blocks() {
+        let (mut writer, reader) = tap_channel(0, ChannelId(3), 4);
+        for sequence in 0..10 {
+            writer.write(&block_of(sequence), 48_000);
+        }
+        let kept: Vec<u64> = std::iter::from_fn(|| reader.try_read())
+            .map(|block| {
+                assert_eq!(block.samples, block_of(block.sequence));
+                block.sequence
+            })
+            .collect();
+        assert_eq!(kept, [6, 7, 8, 9]);
+        assert_eq!(writer.info().blocks_dropped, 6);
+
+        drop(reader);
+        writer.write(&block_of(10), 48_000);
+        assert!(writer.is_closed());
+    }
+
+    #[test]
+    fn slow_consumer_never_stalls_the_writer() {
+        let (mut writer, reader) = tap_channel(0, ChannelId(3), 8);
+        let consumer = std::thread::spawn(move || {
+            let mut seen = Vec::new();
+            while let Ok(block) = reader.read_timeout(Duration::from_secs(1)) {
+                assert_eq!(block.samples, block_of(block.sequence));
+                seen.push(block.sequence);
+                reader.recycle(block);
+                // Bien plus lent que l'audio
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            seen
+        });
+
+        let started = Instant::now();
+        for sequence in 0..500 {
+            writer.write(&block_of(sequence), 48_000);
+        }
+        let writing = started.elapsed();
+        let info = writer.info();
+        drop(writer);
+        let seen = consumer.join().unwrap();
+
+        // 500 blocs en moins de temps qu'il n'en faut au lecteur pour 20
+        assert!(writing < Duration::from_millis(100), "{writing:?}");
+        assert!(seen.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(seen.last(), Some(&499));
+        assert_eq!(info.blocks_written, 500);
+        assert_eq!(info.blocks_dropped, 500 - seen.len() as u64);
+        assert!(info.blocks_dropped > 400);
+    }
+
+    #[test]
+    fn taps_only_copy_their_own_bus() {
+        let mut taps = Taps::default();
+        let headphones = taps.open(ChannelId(3), 4);
+        let stream = taps.open(ChannelId(4), 4);
+        taps.write(ChannelId(3), &[0.5; 8], 48_000);
+        assert_eq!(headphones.try_read().unwrap().samples, [0.5; 8]);
+        assert!(stream.try_read().is_none());
+
+        assert!(taps.close(stream.id()));
+        assert!(!taps.close(stream.id()));
+        assert!(stream.read_timeout(Duration::ZERO).is_err());
+
+        drop(headphones);
+        taps.write(ChannelId(3), &[0.5; 8], 48_000);
+        assert!(taps.infos().is_empty());
+    }
+
+    #[test]
+    fn blocks_missed_by_the_callback_leave_a_counted_gap() {
+        let mut taps = Taps::default();
+        let misses = taps.miss_counter();
+        let tap = taps.open(ChannelId(3), 8);
+        taps.write(ChannelId(3), &[0.1; 8], 48_000);
+        // Deux blocs perdus, lock tenu par le moteur
+        misses.fetch_add(2, Ordering::Relaxed);
+        taps.write(ChannelId(3), &[0.2; 8], 48_000);
+        assert_eq!(tap.try_read().unwrap().sequence, 0);
+        assert_eq!(tap.try_read().unwrap().sequence, 3);
+        let info = &taps.infos()[0];
+        assert_eq!((info.blocks_written, info.blocks_dropped), (2, 2));
+        assert_eq!(taps.overflows(), 2);
+
+        // Un tap ouvert après coup ne compte pas les pertes d'avant
+        let late = taps.open(ChannelId(3), 8);
+        taps.write(ChannelId(3), &[0.3; 8], 48_000);
+        assert_eq!(late.try_read().unwrap().sequence, 0);
+        assert_eq!(taps.infos()[1].blocks_dropped, 0);
+    }
+
+    #[test]
+    fn server_streams_framed_blocks_over_tcp() {
+        let mut taps = Taps::default();
+        let server = TapServer::bind(taps.open(ChannelId(3), 4), 0).unwrap();
+        let mut client = TcpStream::connect(server.address()).unwrap();
+
+        // Écrit jusqu'à ce que le serveur ait pris la connexion
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut sequence = 0;
+        client
+            .set_read_timeout(Some(Duration::from_millis(30)))
+            .unwrap();
+        while client.read_exact(&mut header).is_err() {
+            assert!(Instant::now() < deadline, "no frame received");
+            taps.write(ChannelId(3), &block_of(sequence), 44_100);
+            sequence += 1;
+        }
+        let header = decode_frame_header(&header).unwrap();
+        assert_eq!((header.sample_rate, header.frames), (44_100, 256));
+
+        let mut payload = vec![0u8; header.frames as usize * 2 * 4];
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        client.read_exact(&mut payload).unwrap();
+        let samples: Vec<f32> = payload
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect();
+        assert_eq!(samples, block_of(header.sequence));
+
+        drop(server);
+        assert!(decode_frame_header(&[0; FRAME_HEADER_LEN]).is_none());
+    }
+}
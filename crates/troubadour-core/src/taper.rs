@@ -0,0 +1,248 @@
+use std::fmt;
+
+use troubadour_shared::mixer::FaderTaper;
+
+/// Niveau le plus bas atteignable avec un fader non nul, en dB.
+/// En dessous (position 0), c'est le silence : -∞ dB.
+pub const FADER_MIN_DB: f32 = -60.0;
+
+/// Plancher du silence, en dB : à ce niveau ou en dessous, le gain est
+/// exactement 0. Couvre `-inf` (fader en butée, écrit `-inf` dans les
+/// fichiers TOML) et les sentinelles "très négatives" d'autres outils
+/// (-1000 dB...), bien en dessous de tout niveau audible (-60 dB, lui,
+/// reste audible sur une source forte).
+pub const SILENCE_FLOOR_DB: f32 = -144.0;
+
+/// Niveau en haut de course, en dB. +6 dB ≈ gain linéaire 2.0,
+/// le maximum accepté par `Mixer::set_volume`.
+pub const FADER_MAX_DB: f32 = 6.0;
+
+/// Courbe "audio" : points (position, dB) interpolés linéairement.
+///
+/// Inspirée des échelles de fader de console (IEC 60268-18) :
+/// 0 dB aux 3/4 de la course, et 35% de la course pour les 20 dB
+/// utiles (-20 → 0 dB), contre 30% en courbe linéaire en dB. Strictement croissante → inversible.
+const AUDIO_TAPER: [(f32, f32); 8] = [
+    (0.0, FADER_MIN_DB),
+    (0.05, -50.0),
+    (0.12, -40.0),
+    (0.25, -30.0),
+    (0.4, -20.0),
+    (0.55, -10.0),
+    (0.75, 0.0),
+    (1.0, FADER_MAX_DB),
+];
+
+/// Exposant de la courbe exponentielle (gain ∝ position⁴).
+const EXPONENTIAL_POWER: f32 = 4.0;
+
+/// Un niveau en décibels (0 dB = unity gain, -∞ = silence).
+///
+/// # Pourquoi un newtype ?
+/// Le mixer stocke des gains linéaires, l'UI affiche des dB, le fader
+/// donne une position 0 → 1. Trois `f32` faciles à confondre.
+/// `VolumeDecibels` rend la conversion explicite à chaque frontière.
+///
+/// # Une seule courbe pour tous les contrôles
+/// GUI, MIDI et OSC passent tous par `from_fader_position` : un même
+/// geste donne le même niveau, quelle que soit la surface de contrôle.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct VolumeDecibels(pub f32);
+
+impl VolumeDecibels {
+    pub const SILENCE: Self = Self(f32::NEG_INFINITY);
+
+    /// Position de fader (0.0 → 1.0) → niveau en dB selon la courbe.
+    pub fn from_fader_position(position: f32, taper: FaderTaper) -> Self {
+        let pos = position.clamp(0.0, 1.0);
+        if pos <= 0.0 {
+            return Self::SILENCE;
+        }
+        let db = match taper {
+            FaderTaper::LinearDb => FADER_MIN_DB + pos * (FADER_MAX_DB - FADER_MIN_DB),
+            FaderTaper::Audio => interpolate(&AUDIO_TAPER, pos, |(p, db)| (p, db)),
+            FaderTaper::Exponential => FADER_MAX_DB + 20.0 * EXPONENTIAL_POWER * pos.log10(),
+        };
+        Self(db)
+    }
+
+    /// Niveau en dB → position de fader (inverse de `from_fader_position`).
+    ///
+    /// Un niveau hors de la course (sous -60 dB en courbe linéaire,
+    /// au-dessus de +6 dB) est ramené à l'extrémité la plus proche.
+    pub fn to_fader_position(self, taper: FaderTaper) -> f32 {
+        let db = self.0.min(FADER_MAX_DB);
+        if db == f32::NEG_INFINITY {
+            return 0.0;
+        }
+        let pos = match taper {
+            FaderTaper::LinearDb => (db - FADER_MIN_DB) / (FADER_MAX_DB - FADER_MIN_DB),
+            FaderTaper::Audio => {
+                if db <= FADER_MIN_DB {
+                    return 0.0;
+                }
+                // Même table, axes inversés
+                interpolate(&AUDIO_TAPER, db, |(p, db)| (db, p))
+            }
+            FaderTaper::Exponential => {
+                10.0_f32.powf((db - FADER_MAX_DB) / (20.0 * EXPONENTIAL_POWER))
+            }
+        };
+        pos.clamp(0.0, 1.0)
+    }
+
+    /// Fader en butée (ou sous `SILENCE_FLOOR_DB`) : gain nul, pas
+    /// seulement très faible.
+    pub fn is_silent(self) -> bool {
+        self.0 <= SILENCE_FLOOR_DB
+    }
+
+    /// Gain linéaire → dB (0.0 → -∞).
+    pub fn from_linear(gain: f32) -> Self {
+        if gain <= 0.0 {
+            Self::SILENCE
+        } else {
+            Self(20.0 * gain.log10())
+        }
+    }
+
+    /// dB → gain linéaire (-∞, et tout le silence, → exactement 0.0).
+    pub fn to_linear(self) -> f32 {
+        if self.is_silent() {
+            0.0
+        } else {
+            10.0_f32.powf(self.0 / 20.0)
+        }
+    }
+}
+
+/// "-6.0 dB", "+3.0 dB", ou "-inf dB" pour le silence : la butée du
+/// fader se distingue de -60 dB.
+impl fmt::Display for VolumeDecibels {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_silent() {
+            write!(f, "-inf dB")
+        } else {
+            write!(f, "{:+.1} dB", self.0)
+        }
+    }
+}
+
+/// Interpolation linéaire par morceaux dans une table croissante.
+/// `axes` choisit quelle colonne est l'entrée et laquelle la sortie.
+fn interpolate(table: &[(f32, f32)], x: f32, axes: impl Fn((f32, f32)) -> (f32, f32)) -> f32 {
+    let mut prev = axes(table[0]);
+    if x <= prev.0 {
+        return prev.1;
+    }
+    for &point in &table[1..] {
+        let next = axes(point);
+        if x <= next.0 {
+            return prev.1 + (x - prev.0) / (next.0 - prev.0) * (next.1 - prev.1);
+        }
+        prev = next;
+    }
+    prev.1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TAPERS: [FaderTaper; 3] = [
+        FaderTaper::LinearDb,
+        FaderTaper::Audio,
+        FaderTaper::Exponential,
+    ];
+
+    #[test]
+    fn roundtrip_across_full_range() {
+        for taper in TAPERS {
+            for i in 0..=1000 {
+                let pos = i as f32 / 1000.0;
+                let db = VolumeDecibels::from_fader_position(pos, taper);
+                let back = db.to_fader_position(taper);
+                assert!(
+                    (back - pos).abs() < 1e-4,
+                    "{taper:?}: {pos} -> {db:?} -> {back}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tapers_are_strictly_monotonic() {
+        for taper in TAPERS {
+            let mut prev = VolumeDecibels::from_fader_position(0.0, taper);
+            for i in 1..=1000 {
+                let db = VolumeDecibels::from_fader_position(i as f32 / 1000.0, taper);
+                assert!(db > prev, "{taper:?} not increasing at step {i}");
+                prev = db;
+            }
+        }
+    }
+
+    #[test]
+    fn endpoints() {
+        for taper in TAPERS {
+            assert_eq!(
+                VolumeDecibels::from_fader_position(0.0, taper),
+                VolumeDecibels::SILENCE
+            );
+            let top = VolumeDecibels::from_fader_position(1.0, taper);
+            assert!((top.0 - FADER_MAX_DB).abs() < 1e-4, "{taper:?}: {top:?}");
+            assert!(top.to_linear() <= 2.0);
+        }
+    }
+
+    #[test]
+    fn audio_taper_gives_room_to_useful_range() {
+        // -20 → 0 dB : 35% de la course en "audio", 20/66 ≈ 30% en linéaire
+        let span = |taper| {
+            VolumeDecibels(0.0).to_fader_position(taper)
+                - VolumeDecibels(-20.0).to_fader_position(taper)
+        };
+        assert!((span(FaderTaper::Audio) - 0.35).abs() < 1e-6);
+        assert!(span(FaderTaper::Audio) > span(FaderTaper::LinearDb));
+        assert_eq!(
+            VolumeDecibels(0.0).to_fader_position(FaderTaper::Audio),
+            0.75
+        );
+    }
+
+    #[test]
+    fn linear_conversions() {
+        assert_eq!(VolumeDecibels::from_linear(0.0), VolumeDecibels::SILENCE);
+        assert_eq!(VolumeDecibels::SILENCE.to_linear(), 0.0);
+        assert!((VolumeDecibels::from_linear(1.0).0).abs() < 1e-6);
+        assert!((VolumeDecibels(-6.0).to_linear() - 0.501).abs() < 1e-3);
+        // Au-dessus de la course → haut du fader
+        assert_eq!(
+            VolumeDecibels(20.0).to_fader_position(FaderTaper::Audio),
+            1.0
+        );
+    }
+
+    #[test]
+    fn fader_bottom_is_true_silence_but_minus_60_is_not() {
+        for taper in TAPERS {
+            let bottom = VolumeDecibels::from_fader_position(0.0, taper);
+            assert!(bottom.is_silent());
+            assert_eq!(bottom.to_linear(), 0.0);
+            assert_eq!(VolumeDecibels::from_linear(bottom.to_linear()), bottom);
+        }
+        // Sentinelle très négative d'un ancien fichier : silence aussi
+        assert_eq!(VolumeDecibels(-1000.0).to_linear(), 0.0);
+        // -60 dB reste audible (presets existants)
+        let minus_60 = VolumeDecibels(FADER_MIN_DB);
+        assert!(!minus_60.is_silent());
+        assert!((minus_60.to_linear() - 0.001).abs() < 1e-6);
+    }
+
+    #[test]
+    fn display_tells_silence_from_minus_60() {
+        assert_eq!(VolumeDecibels::SILENCE.to_string(), "-inf dB");
+        assert_eq!(VolumeDecibels(-60.0).to_string(), "-60.0 dB");
+        assert_eq!(VolumeDecibels(3.0).to_string(), "+3.0 dB");
+    }
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+
+use tracing::warn;
+use troubadour_shared::dsp::{CompressorConfig, DeEsserConfig, EffectsPreset, NoiseGateConfig};
+use troubadour_shared::template::{BusAssignment, ChannelTemplate};
+
+/// Templates de canaux disponibles pour `AddChannelFromTemplate`.
+///
+/// Les templates intégrés (Mic, Music, Game, Chat) sont définis dans le
+/// code ; un fichier `<config>/templates/<nom>.toml` portant le même nom
+/// les remplace, un autre nom en ajoute un.
+pub struct ChannelTemplateRegistry {
+    templates: Vec<ChannelTemplate>,
+}
+
+impl ChannelTemplateRegistry {
+    /// Templates intégrés uniquement.
+    pub fn builtin() -> Self {
+        Self {
+            templates: vec![mic(), music(), game(), chat()],
+        }
+    }
+
+    /// Templates intégrés, surchargés par les fichiers d'un dossier.
+    pub fn load(dir: &Path) -> Self {
+        let mut registry = Self::builtin();
+        let (templates, errors) = ChannelTemplate::load_dir(dir);
+        for error in errors {
+            warn!("Ignoring channel template {error}");
+        }
+        for template in templates {
+            registry.insert(template);
+        }
+        registry
+    }
+
+    /// Ajoute un template, ou remplace celui du même nom (insensible à la casse).
+    pub fn insert(&mut self, template: ChannelTemplate) {
+        match self
+            .templates
+            .iter_mut()
+            .find(|t| t.name.eq_ignore_ascii_case(&template.name))
+        {
+            Some(existing) => *existing = template,
+            None => self.templates.push(template),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ChannelTemplate> {
+        self.templates
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Noms des templates : intégrés d'abord, puis ceux de l'utilisateur.
+    pub fn names(&self) -> Vec<String> {
+        self.templates.iter().map(|t| t.name.clone()).collect()
+    }
+}
+
+impl Default for ChannelTemplateRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Chaîne sans traitement : seul le limiter protège du clipping.
+fn clean_effects(name: &str) -> EffectsPreset {
+    EffectsPreset {
+        name: name.to_string(),
+        ..EffectsPreset::clean()
+    }
+}
+
+/// Micro : gate → compresseur → de-esser → limiter, vers tous les bus.
+fn mic() -> ChannelTemplate {
+    ChannelTemplate {
+        name: "Mic".to_string(),
+        volume: 1.0,
+        effects: EffectsPreset {
+            noise_gate: NoiseGateConfig {
+                threshold: 0.008,
+                enabled: true,
+                ..NoiseGateConfig::default()
+            },
+            compressor: CompressorConfig {
+                enabled: true,
+                ..CompressorConfig::default()
+            },
+            de_esser: DeEsserConfig {
+                enabled: true,
+                ..DeEsserConfig::default()
+            },
+            ..clean_effects("Mic")
+        },
+        buses: BusAssignment::All,
+    }
+}
+
+/// Musique de fond : pas de traitement, volume réduit pour laisser
+/// la voix devant.
+fn music() -> ChannelTemplate {
+    ChannelTemplate {
+        name: "Music".to_string(),
+        volume: 0.5,
+        effects: clean_effects("Music"),
+        buses: BusAssignment::All,
+    }
+}
+
+/// Son du jeu : pas de traitement, un peu sous le nominal.
+fn game() -> ChannelTemplate {
+    ChannelTemplate {
+        name: "Game".to_string(),
+        volume: 0.8,
+        effects: clean_effects("Game"),
+        buses: BusAssignment::All,
+    }
+}
+
+/// Voix des autres (Discord...) : compression douce pour égaliser les
+/// interlocuteurs, au casque seulement. Sur les enceintes, le micro
+/// les reprendrait (écho chez les autres).
+fn chat() -> ChannelTemplate {
+    ChannelTemplate {
+        name: "Chat".to_string(),
+        volume: 1.0,
+        effects: EffectsPreset {
+            compressor: CompressorConfig {
+                ratio: 2.0,
+                enabled: true,
+                ..CompressorConfig::default()
+            },
+            ..clean_effects("Chat")
+        },
+        buses: BusAssignment::Named(vec!["Headphones".to_string()]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtins_are_listed_in_order() {
+        assert_eq!(
+            ChannelTemplateRegistry::builtin().names(),
+            ["Mic", "Music", "Game", "Chat"]
+        );
+    }
+
+    #[test]
+    fn file_overrides_builtin_and_adds_new_templates() {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-template-registry-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let write = |name: &str, volume: f32| {
+            let template = ChannelTemplate { volume, ..game() };
+            let content = toml::to_string(&template).unwrap();
+            std::fs::write(dir.join(format!("{name}.toml")), content).unwrap();
+        };
+        write("Music", 0.2);
+        write("Podcast Guest", 0.9);
+
+        let registry = ChannelTemplateRegistry::load(&dir);
+        assert_eq!(registry.get("music").unwrap().volume, 0.2);
+        assert_eq!(registry.get("Podcast Guest").unwrap().volume, 0.9);
+        assert_eq!(registry.names().len(), 5);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,449 @@
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+
+use troubadour_shared::audio::StreamHealth;
+use troubadour_shared::config::WatchdogConfig;
+
+/// Intervalle entre deux vérifications des heartbeats.
+pub const CHECK_INTERVAL_MS: u64 = 1000;
+
+/// Millisecondes depuis le premier appel (horloge monotone du processus).
+///
+/// `SystemTime` peut reculer (NTP, changement d'heure) : un faux blocage
+/// serait détecté. `Instant` ne recule jamais, et le lire n'alloue ni
+/// ne bloque : utilisable depuis le callback audio.
+pub fn monotonic_ms() -> u64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_millis() as u64
+}
+
+/// Battement de cœur d'un callback audio : l'heure de son dernier passage.
+///
+/// # Pourquoi un simple `AtomicU64` ?
+/// Le callback ne peut ni prendre un lock ni envoyer un message à chaque
+/// bloc sans risque. Écrire un entier atomique ne coûte rien ; c'est le
+/// watchdog, sur le thread de contrôle, qui compare cette heure à la sienne.
+#[derive(Debug, Clone, Default)]
+pub struct Heartbeat(Arc<AtomicU64>);
+
+impl Heartbeat {
+    /// Appelé par le callback à chaque bloc.
+    pub fn beat(&self) {
+        self.beat_at(monotonic_ms());
+    }
+
+    /// `0` est réservé à "jamais battu".
+    pub fn beat_at(&self, now_ms: u64) {
+        self.0.store(now_ms.max(1), Ordering::Relaxed);
+    }
+
+    /// Heure du dernier battement (`None` : le callback n'a jamais tourné).
+    pub fn last(&self) -> Option<u64> {
+        match self.0.load(Ordering::Relaxed) {
+            0 => None,
+            ms => Some(ms),
+        }
+    }
+}
+
+/// Ce que le moteur doit faire après une vérification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchdogAction {
+    /// Le callback ne tourne plus depuis `silent_ms`
+    Stalled {
+        device_name: String,
+        is_input: bool,
+        silent_ms: u64,
+    },
+    /// Rouvrir le pipeline (tentative n° `attempt`)
+    Restart {
+        device_name: String,
+        is_input: bool,
+        attempt: u32,
+    },
+    /// Toutes les tentatives ont échoué : on n'insiste plus
+    GaveUp { device_name: String, is_input: bool },
+    /// Le callback tourne de nouveau
+    Recovered { device_name: String, is_input: bool },
+}
+
+struct WatchedStream {
+    device_name: String,
+    is_input: bool,
+    heartbeat: Heartbeat,
+    /// Début de la surveillance (ouverture, ou dernière réouverture) :
+    /// un stream qui n'a encore jamais tourné a droit au même délai.
+    since_ms: u64,
+    /// Heure du blocage détecté (`None` : stream sain)
+    stalled_at_ms: Option<u64>,
+    restart_attempts: u32,
+    next_restart_ms: u64,
+    gave_up: bool,
+}
+
+impl WatchedStream {
+    fn silent_ms(&self, now_ms: u64) -> u64 {
+        let last = self.heartbeat.last().unwrap_or(0).max(self.since_ms);
+        now_ms.saturating_sub(last)
+    }
+}
+
+/// Surveille les heartbeats des streams ouverts.
+///
+/// Appelé par `process_commands` ; la vérification elle-même n'a lieu
+/// qu'une fois par `CHECK_INTERVAL_MS`. L'horloge est passée en
+/// paramètre : les tests simulent des callbacks bloqués sans attendre.
+///
+/// # Réouvertures bornées
+/// Un stream bloqué est rouvert tout de suite, puis, s'il reste muet,
+/// après `restart_backoff_ms`, 2×, 4×… jusqu'à `max_restarts` tentatives.
+/// Un driver vraiment mort ne fait donc pas boucler le moteur.
+pub struct Watchdog {
+    config: WatchdogConfig,
+    streams: Vec<WatchedStream>,
+    next_check_ms: u64,
+    stalls: u64,
+    restarts: u64,
+}
+
+impl Watchdog {
+    pub fn new(config: WatchdogConfig) -> Self {
+        Self {
+            config,
+            streams: Vec::new(),
+            next_check_ms: 0,
+            stalls: 0,
+            restarts: 0,
+        }
+    }
+
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.config
+    }
+
+    /// Change les réglages sans perdre l'état des streams surveillés.
+    pub fn configure(&mut self, config: WatchdogConfig) {
+        self.config = config;
+    }
+
+    /// Remplace les streams surveillés par ceux du pipeline qui vient
+    /// d'être ouvert. Un stream déjà surveillé (même device, même sens)
+    /// garde son état et son compte de tentatives : c'est ainsi qu'une
+    /// réouverture reste bornée.
+    pub fn watch(&mut self, streams: Vec<(String, bool, Heartbeat)>, now_ms: u64) {
+        let mut previous = std::mem::take(&mut self.streams);
+        for (device_name, is_input, heartbeat) in streams {
+            let known = previous
+                .iter()
+                .position(|s| s.device_name == device_name && s.is_input == is_input)
+                .map(|i| previous.swap_remove(i));
+            let stream = match known {
+                Some(mut stream) => {
+                    stream.heartbeat = heartbeat;
+                    stream.since_ms = now_ms;
+                    stream
+                }
+                None => WatchedStream {
+                    device_name,
+                    is_input,
+                    heartbeat,
+                    since_ms: now_ms,
+                    stalled_at_ms: None,
+                    restart_attempts: 0,
+                    next_restart_ms: 0,
+                    gave_up: false,
+                },
+            };
+            self.streams.push(stream);
+        }
+    }
+
+    /// Un seul stream rouvert, dans le sens `is_input` : il remplace
+    /// celui de ce sens, l'autre stream garde sa surveillance en cours.
+    pub fn rewatch(
+        &mut self,
+        device_name: String,
+        is_input: bool,
+        heartbeat: Heartbeat,
+        now_ms: u64,
+    ) {
+        let (same, others): (Vec<_>, Vec<_>) = std::mem::take(&mut self.streams)
+            .into_iter()
+            .partition(|s| s.is_input == is_input);
+        self.streams = same;
+        self.watch(vec![(device_name, is_input, heartbeat)], now_ms);
+        self.streams.extend(others);
+        // L'entrée d'abord, comme à l'ouverture du pipeline
+        self.streams.sort_by_key(|s| !s.is_input);
+    }
+
+    /// Plus aucun stream ouvert (arrêt du moteur).
+    pub fn clear(&mut self) {
+        self.streams.clear();
+    }
+
+    /// Vérifie les heartbeats (au plus une fois par `CHECK_INTERVAL_MS`).
+    pub fn check(&mut self, now_ms: u64) -> Vec<WatchdogAction> {
+        let mut actions = Vec::new();
+        if !self.config.enabled || now_ms < self.next_check_ms {
+            return actions;
+        }
+        self.next_check_ms = now_ms + CHECK_INTERVAL_MS;
+
+        let config = self.config;
+        for stream in &mut self.streams {
+            let silent_ms = stream.silent_ms(now_ms);
+            let device_name = stream.device_name.clone();
+            let is_input = stream.is_input;
+
+            match stream.stalled_at_ms {
+                None if silent_ms > config.timeout_ms => {
+                    stream.stalled_at_ms = Some(now_ms);
+                    self.stalls += 1;
+                    actions.push(WatchdogAction::Stalled {
+                        device_name: device_name.clone(),
+                        is_input,
+                        silent_ms,
+                    });
+                }
+                None => continue,
+                Some(stalled_at) if stream.heartbeat.last().is_some_and(|l| l > stalled_at) => {
+                    stream.stalled_at_ms = None;
+                    stream.restart_attempts = 0;
+                    stream.next_restart_ms = 0;
+                    stream.gave_up = false;
+                    actions.push(WatchdogAction::Recovered {
+                        device_name,
+                        is_input,
+                    });
+                    continue;
+                }
+                // Rouvert récemment : on lui laisse le temps de démarrer
+                Some(_) if silent_ms <= config.timeout_ms => continue,
+                Some(_) => {}
+            }
+
+            if !config.auto_restart || stream.gave_up || now_ms < stream.next_restart_ms {
+                continue;
+            }
+            if stream.restart_attempts >= config.max_restarts {
+                stream.gave_up = true;
+                actions.push(WatchdogAction::GaveUp {
+                    device_name,
+                    is_input,
+                });
+                continue;
+            }
+            let backoff = config.restart_backoff_ms << stream.restart_attempts.min(16);
+            stream.restart_attempts += 1;
+            stream.next_restart_ms = now_ms + backoff;
+            self.restarts += 1;
+            actions.push(WatchdogAction::Restart {
+                device_name,
+                is_input,
+                attempt: stream.restart_attempts,
+            });
+        }
+        actions
+    }
+
+    /// Streams déclarés bloqués depuis la création du watchdog.
+    pub fn stall_count(&self) -> u64 {
+        self.stalls
+    }
+
+    /// Réouvertures demandées depuis la création du watchdog.
+    pub fn restart_count(&self) -> u64 {
+        self.restarts
+    }
+
+    /// État de chaque stream surveillé.
+    pub fn health(&self, now_ms: u64) -> Vec<StreamHealth> {
+        self.streams
+            .iter()
+            .map(|s| StreamHealth {
+                device_name: s.device_name.clone(),
+                is_input: s.is_input,
+                healthy: s.stalled_at_ms.is_none(),
+                silent_ms: s.silent_ms(now_ms),
+                restart_attempts: s.restart_attempts,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn watched(config: WatchdogConfig) -> (Watchdog, Heartbeat, Heartbeat) {
+        let mut watchdog = Watchdog::new(config);
+        let (input, output) = (Heartbeat::default(), Heartbeat::default());
+        watchdog.watch(
+            vec![
+                ("Blue Yeti".into(), true, input.clone()),
+                ("HD 600".into(), false, output.clone()),
+            ],
+            0,
+        );
+        (watchdog, input, output)
+    }
+
+    #[test]
+    fn silent_callback_is_reported_once_then_recovers() {
+        let config = WatchdogConfig {
+            auto_restart: false,
+            ..WatchdogConfig::default()
+        };
+        let (mut watchdog, input, output) = watched(config);
+
+        // Les deux callbacks tournent, puis l'entrée se fige à 1 s
+        for now in (0..=1000).step_by(10) {
+            input.beat_at(now);
+        }
+        let mut actions = Vec::new();
+        for now in (1000..=4000).step_by(1000) {
+            output.beat_at(now);
+            actions.extend(watchdog.check(now));
+        }
+        assert_eq!(
+            actions,
+            vec![WatchdogAction::Stalled {
+                device_name: "Blue Yeti".into(),
+                is_input: true,
+                silent_ms: 3000,
+            }]
+        );
+        let health = watchdog.health(4000);
+        assert!(!health[0].healthy && health[1].healthy);
+        assert_eq!(watchdog.stall_count(), 1);
+
+        input.beat_at(4500);
+        assert_eq!(
+            watchdog.check(5000),
+            vec![WatchdogAction::Recovered {
+                device_name: "Blue Yeti".into(),
+                is_input: true,
+            }]
+        );
+        assert!(watchdog.health(5000)[0].healthy);
+    }
+
+    #[test]
+    fn rewatch_replaces_one_direction_and_keeps_the_other() {
+        let config = WatchdogConfig {
+            auto_restart: false,
+            ..WatchdogConfig::default()
+        };
+        let (mut watchdog, input, _) = watched(config);
+        // La sortie se fige, puis seule elle est rouverte sur un autre device
+        input.beat_at(2500);
+        assert_eq!(watchdog.check(2500).len(), 1);
+        let output = Heartbeat::default();
+        watchdog.rewatch("Speakers".into(), false, output.clone(), 2500);
+
+        input.beat_at(4000);
+        output.beat_at(4000);
+        assert!(watchdog.check(4000).is_empty());
+        let health = watchdog.health(4000);
+        assert_eq!(
+            health
+                .iter()
+                .map(|h| h.device_name.as_str())
+                .collect::<Vec<_>>(),
+            ["Blue Yeti", "Speakers"]
+        );
+        assert!(health.iter().all(|h| h.healthy));
+    }
+
+    #[test]
+    fn checks_run_at_most_once_per_interval() {
+        let (mut watchdog, _, _) = watched(WatchdogConfig::default());
+        assert!(watchdog.check(1500).is_empty());
+        // Muet depuis 2,2 s, mais la vérification précédente date de 700 ms
+        assert!(watchdog.check(2200).is_empty());
+        assert_eq!(watchdog.check(2500).len(), 4); // 2 blocages + 2 réouvertures
+    }
+
+    #[test]
+    fn restarts_back_off_and_stop_after_max_attempts() {
+        let config = WatchdogConfig {
+            timeout_ms: 2000,
+            max_restarts: 3,
+            restart_backoff_ms: 1000,
+            ..WatchdogConfig::default()
+        };
+        let mut watchdog = Watchdog::new(config);
+        let mut restarts_at = Vec::new();
+        let mut gave_up_at = None;
+        let mut heartbeat = Heartbeat::default();
+        watchdog.watch(vec![("Blue Yeti".into(), true, heartbeat.clone())], 0);
+
+        for now in (1000..=40_000).step_by(1000) {
+            for action in watchdog.check(now) {
+                match action {
+                    WatchdogAction::Restart { attempt, .. } => {
+                        restarts_at.push((now, attempt));
+                        // Le moteur rouvre le pipeline : nouveau heartbeat,
+                        // qui ne bat jamais (driver mort)
+                        heartbeat = Heartbeat::default();
+                        watchdog.watch(vec![("Blue Yeti".into(), true, heartbeat.clone())], now);
+                    }
+                    WatchdogAction::GaveUp { .. } => gave_up_at = gave_up_at.or(Some(now)),
+                    _ => {}
+                }
+            }
+        }
+
+        // Blocage à 3 s : réouverture immédiate. Chaque stream rouvert a
+        // droit à son timeout (2 s) avant d'être jugé, et l'attente double
+        // (1 s, 2 s, 4 s) : l'abandon arrive 4 s après la 3e tentative
+        assert_eq!(restarts_at, vec![(3000, 1), (6000, 2), (9000, 3)]);
+        assert_eq!(gave_up_at, Some(13_000));
+        assert_eq!(watchdog.restart_count(), 3);
+        assert_eq!(watchdog.stall_count(), 1);
+        assert_eq!(watchdog.health(40_000)[0].restart_attempts, 3);
+    }
+
+    #[test]
+    fn recovery_resets_the_restart_budget() {
+        let (mut watchdog, _, output) = watched(WatchdogConfig::default());
+        output.beat_at(2500);
+        let actions = watchdog.check(2500);
+        assert!(matches!(
+            actions.as_slice(),
+            [
+                WatchdogAction::Stalled { is_input: true, .. },
+                WatchdogAction::Restart { attempt: 1, .. }
+            ]
+        ));
+
+        // La réouverture a marché
+        let restarted = Heartbeat::default();
+        watchdog.watch(
+            vec![
+                ("Blue Yeti".into(), true, restarted.clone()),
+                ("HD 600".into(), false, output.clone()),
+            ],
+            2600,
+        );
+        restarted.beat_at(2700);
+        output.beat_at(3500);
+        assert!(matches!(
+            watchdog.check(3500).as_slice(),
+            [WatchdogAction::Recovered { is_input: true, .. }]
+        ));
+        assert_eq!(watchdog.health(3500)[0].restart_attempts, 0);
+    }
+
+    #[test]
+    fn streams_no_longer_open_are_forgotten() {
+        let (mut watchdog, _, _) = watched(WatchdogConfig::default());
+        watchdog.watch(vec![("USB Mic".into(), true, Heartbeat::default())], 0);
+        let health = watchdog.health(0);
+        assert_eq!(health.len(), 1);
+        assert_eq!(health[0].device_name, "USB Mic");
+    }
+}
@@ -0,0 +1,402 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use troubadour_shared::error::{TroubadourError, TroubadourResult};
+
+/// Format PCM entier (16, 24 ou 32 bits)
+const FORMAT_PCM: u16 = 1;
+/// Format flottant IEEE (32 bits)
+const FORMAT_FLOAT: u16 = 3;
+/// `WAVE_FORMAT_EXTENSIBLE` : le vrai format est dans le sous-format
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Audio d'un fichier WAV, converti en `f32` entrelacé.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavAudio {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Samples entrelacés (L R L R… en stéréo), dans [-1, 1]
+    pub samples: Vec<f32>,
+}
+
+impl WavAudio {
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1) as usize
+    }
+
+    /// Moyenne des canaux de chaque frame (mono).
+    pub fn to_mono(&self) -> Vec<f32> {
+        let channels = self.channels.max(1) as usize;
+        self.samples
+            .chunks_exact(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect()
+    }
+}
+
+fn wav_error(path: &Path, message: impl std::fmt::Display) -> TroubadourError {
+    TroubadourError::AudioFile(format!("{}: {message}", path.display()))
+}
+
+/// Lit un fichier WAV (PCM 16/24/32 bits ou flottant 32 bits).
+///
+/// # Pourquoi pas une crate ?
+/// Le format RIFF est simple : une suite de chunks `id + taille + données`.
+/// Seuls `fmt ` (le format) et `data` (les samples) nous intéressent,
+/// les autres (`LIST`, `fact`…) sont sautés.
+pub fn read_wav(path: &Path) -> TroubadourResult<WavAudio> {
+    let bytes = std::fs::read(path).map_err(|e| wav_error(path, e))?;
+    parse_wav(&bytes).map_err(|e| wav_error(path, e))
+}
+
+fn parse_wav(bytes: &[u8]) -> Result<WavAudio, String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a RIFF/WAVE file".into());
+    }
+    let u16_at = |pos: usize| u16::from_le_bytes([bytes[pos], bytes[pos + 1]]);
+    let u32_at = |pos: usize| {
+        u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+    };
+
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32_at(pos + 4) as usize;
+        let body = pos + 8;
+        // Un fichier tronqué garde ce qui a été écrit
+        let end = (body + size).min(bytes.len());
+
+        match id {
+            b"fmt " if end - body >= 16 => {
+                let mut tag = u16_at(body);
+                if tag == FORMAT_EXTENSIBLE && end - body >= 26 {
+                    tag = u16_at(body + 24);
+                }
+                format = Some((tag, u16_at(body + 2), u32_at(body + 4), u16_at(body + 14)));
+            }
+            b"data" => {
+                let (tag, channels, sample_rate, bits) = format.ok_or("data chunk before fmt")?;
+                if channels == 0 {
+                    return Err("zero channels".into());
+                }
+                let samples = decode(&bytes[body..end], tag, bits)?;
+                return Ok(WavAudio {
+                    sample_rate,
+                    channels,
+                    samples,
+                });
+            }
+            _ => {}
+        }
+        // Les chunks sont alignés sur 2 octets
+        pos = body + size + (size & 1);
+    }
+    Err("no data chunk".into())
+}
+
+fn decode(data: &[u8], tag: u16, bits: u16) -> Result<Vec<f32>, String> {
+    let samples = match (tag, bits) {
+        (FORMAT_PCM, 16) => data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0)
+            .collect(),
+        (FORMAT_PCM, 24) => data
+            .chunks_exact(3)
+            // Les 3 octets en haut d'un i32 : le signe est conservé
+            .map(|b| i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2_147_483_648.0)
+            .collect(),
+        (FORMAT_PCM, 32) => data
+            .chunks_exact(4)
+            .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0)
+            .collect(),
+        (FORMAT_FLOAT, 32) => data
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        _ => return Err(format!("unsupported format {tag} with {bits} bits")),
+    };
+    Ok(samples)
+}
+
+/// Lecture d'un WAV par morceaux, pour les fichiers trop longs pour
+/// tenir en mémoire (un enregistrement de 3 h en stéréo 48 kHz : 4 Go
+/// en `f32`).
+///
+/// Mêmes formats que `read_wav` ; seul l'en-tête est lu à l'ouverture.
+pub struct WavReader {
+    path: PathBuf,
+    file: BufReader<File>,
+    sample_rate: u32,
+    channels: u16,
+    tag: u16,
+    bits: u16,
+    /// Octets de `data` pas encore lus
+    remaining: u64,
+    frames: u64,
+    /// Octets bruts du dernier morceau, réutilisés
+    bytes: Vec<u8>,
+}
+
+impl WavReader {
+    pub fn open(path: &Path) -> TroubadourResult<Self> {
+        let file = File::open(path).map_err(|e| wav_error(path, e))?;
+        let file_len = file.metadata().map_err(|e| wav_error(path, e))?.len();
+        let mut file = BufReader::new(file);
+        let read = |len: usize, file: &mut BufReader<File>| {
+            let mut bytes = vec![0; len];
+            file.read_exact(&mut bytes)
+                .map_err(|_| wav_error(path, "truncated header"))?;
+            Ok::<_, TroubadourError>(bytes)
+        };
+        let riff = read(12, &mut file)?;
+        if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+            return Err(wav_error(path, "not a RIFF/WAVE file"));
+        }
+
+        let mut format = None;
+        let mut pos = 12_u64;
+        loop {
+            let Ok(header) = read(8, &mut file) else {
+                return Err(wav_error(path, "no data chunk"));
+            };
+            let size = u64::from(u32::from_le_bytes([
+                header[4], header[5], header[6], header[7],
+            ]));
+            pos += 8;
+            match &header[0..4] {
+                b"fmt " if size >= 16 => {
+                    let body = read(size as usize, &mut file)?;
+                    let u16_at = |at: usize| u16::from_le_bytes([body[at], body[at + 1]]);
+                    let mut tag = u16_at(0);
+                    if tag == FORMAT_EXTENSIBLE && size >= 26 {
+                        tag = u16_at(24);
+                    }
+                    let rate = u32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+                    format = Some((tag, u16_at(2), rate, u16_at(14)));
+                    if size & 1 == 1 {
+                        read(1, &mut file)?;
+                    }
+                }
+                b"data" => {
+                    let (tag, channels, sample_rate, bits) =
+                        format.ok_or_else(|| wav_error(path, "data chunk before fmt"))?;
+                    if channels == 0 {
+                        return Err(wav_error(path, "zero channels"));
+                    }
+                    decode(&[], tag, bits).map_err(|e| wav_error(path, e))?;
+                    // Un fichier tronqué (ou en cours d'écriture) garde
+                    // ce qui a été écrit
+                    let remaining = size.min(file_len.saturating_sub(pos));
+                    let block_align = u64::from(channels) * u64::from(bits / 8);
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                        file,
+                        sample_rate,
+                        channels,
+                        tag,
+                        bits,
+                        remaining: remaining - remaining % block_align,
+                        frames: remaining / block_align,
+                        bytes: Vec::new(),
+                    });
+                }
+                _ => {
+                    // Les chunks sont alignés sur 2 octets
+                    let skip = size + (size & 1);
+                    file.seek_relative(skip as i64)
+                        .map_err(|e| wav_error(path, e))?;
+                }
+            }
+            pos += size + (size & 1);
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Frames du fichier (lues ou non).
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// Lit jusqu'à `frames` frames entrelacées dans `out` (remplacé).
+    /// Retourne le nombre de frames lues, 0 à la fin du fichier.
+    pub fn read_frames(&mut self, frames: usize, out: &mut Vec<f32>) -> TroubadourResult<usize> {
+        let block_align = self.channels as usize * (self.bits / 8) as usize;
+        let len = (frames as u64 * block_align as u64).min(self.remaining) as usize;
+        self.bytes.resize(len, 0);
+        self.file
+            .read_exact(&mut self.bytes)
+            .map_err(|e| wav_error(&self.path, e))?;
+        self.remaining -= len as u64;
+        *out = decode(&self.bytes, self.tag, self.bits).map_err(|e| wav_error(&self.path, e))?;
+        Ok(len / block_align)
+    }
+}
+
+/// Écriture d'un WAV flottant 32 bits par morceaux.
+///
+/// L'en-tête donne la taille des données, inconnue avant la fin :
+/// il est écrit à zéro, puis corrigé par `finish`. Un fichier jamais
+/// fini reste lisible ici (`data` vide ou tronqué).
+pub struct WavWriter {
+    path: PathBuf,
+    file: BufWriter<File>,
+    sample_rate: u32,
+    channels: u16,
+    samples: u64,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, sample_rate: u32, channels: u16) -> TroubadourResult<Self> {
+        let file = File::create(path).map_err(|e| wav_error(path, e))?;
+        let mut writer = Self {
+            path: path.to_path_buf(),
+            file: BufWriter::new(file),
+            sample_rate,
+            channels,
+            samples: 0,
+        };
+        let header = float_header(sample_rate, channels, 0);
+        writer
+            .file
+            .write_all(&header)
+            .map_err(|e| wav_error(path, e))?;
+        Ok(writer)
+    }
+
+    pub fn write(&mut self, samples: &[f32]) -> TroubadourResult<()> {
+        for sample in samples {
+            self.file
+                .write_all(&sample.to_le_bytes())
+                .map_err(|e| wav_error(&self.path, e))?;
+        }
+        self.samples += samples.len() as u64;
+        Ok(())
+    }
+
+    /// Corrige l'en-tête et ferme le fichier.
+    pub fn finish(mut self) -> TroubadourResult<()> {
+        let data_len = (self.samples * 4).min(u64::from(u32::MAX - 36)) as u32;
+        let header = float_header(self.sample_rate, self.channels, data_len);
+        self.file
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| self.file.write_all(&header))
+            .and_then(|_| self.file.flush())
+            .map_err(|e| wav_error(&self.path, e))
+    }
+}
+
+/// Écrit `samples` (entrelacés) en WAV flottant 32 bits : aucune perte
+/// par rapport au mix, et lisible par tous les éditeurs audio.
+pub fn write_wav(
+    path: &Path,
+    sample_rate: u32,
+    channels: u16,
+    samples: &[f32],
+) -> TroubadourResult<()> {
+    let mut bytes = float_header(sample_rate, channels, (samples.len() * 4) as u32);
+    bytes.reserve(samples.len() * 4);
+    for sample in samples {
+        bytes.extend_from_slice(&sample.to_le_bytes());
+    }
+    std::fs::write(path, bytes).map_err(|e| wav_error(path, e))
+}
+
+/// En-tête d'un WAV flottant 32 bits de `data_len` octets de données.
+fn float_header(sample_rate: u32, channels: u16, data_len: u32) -> Vec<u8> {
+    let block_align = channels * 4;
+    let mut bytes = Vec::with_capacity(44);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&FORMAT_FLOAT.to_le_bytes());
+    bytes.extend_from_slice(&channels.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    bytes.extend_from_slice(&block_align.to_le_bytes());
+    bytes.extend_from_slice(&32u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn float_wav_round_trips() {
+        let path = std::env::temp_dir().join(format!("troubadour-wav-{}.wav", std::process::id()));
+        let samples = vec![0.0, 0.5, -0.25, 1.0, -1.0, 0.125];
+        write_wav(&path, 44_100, 2, &samples).unwrap();
+
+        let audio = read_wav(&path).unwrap();
+        assert_eq!(audio.sample_rate, 44_100);
+        assert_eq!(audio.channels, 2);
+        assert_eq!(audio.samples, samples);
+        assert_eq!(audio.frames(), 3);
+        assert_eq!(audio.to_mono(), vec![0.25, 0.375, -0.4375]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn chunked_writer_and_reader_match_whole_file_io() {
+        let path =
+            std::env::temp_dir().join(format!("troubadour-wav-chunks-{}.wav", std::process::id()));
+        let samples: Vec<f32> = (0..1_000).map(|i| (i as f32 * 0.01).sin()).collect();
+        let mut writer = WavWriter::create(&path, 48_000, 2).unwrap();
+        for chunk in samples.chunks(300) {
+            writer.write(chunk).unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(read_wav(&path).unwrap().samples, samples);
+
+        let mut reader = WavReader::open(&path).unwrap();
+        assert_eq!((reader.sample_rate(), reader.channels()), (48_000, 2));
+        assert_eq!(reader.frames(), 500);
+        let (mut read, mut chunk) = (Vec::new(), Vec::new());
+        while reader.read_frames(128, &mut chunk).unwrap() > 0 {
+            read.extend_from_slice(&chunk);
+        }
+        assert_eq!(read, samples);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn pcm_formats_are_scaled_to_unit_range() {
+        // En-tête PCM 16 bits mono, précédé d'un chunk inconnu à sauter
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        bytes.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+        bytes.extend_from_slice(b"fmt \x10\0\0\0");
+        bytes.extend_from_slice(&[1, 0, 1, 0]);
+        bytes.extend_from_slice(&48_000u32.to_le_bytes());
+        bytes.extend_from_slice(&96_000u32.to_le_bytes());
+        bytes.extend_from_slice(&[2, 0, 16, 0]);
+        bytes.extend_from_slice(b"data\x06\0\0\0");
+        for sample in [16_384i16, -32_768, 0] {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        let audio = parse_wav(&bytes).unwrap();
+        assert_eq!(audio.samples, vec![0.5, -1.0, 0.0]);
+
+        // 24 bits : -0.5 = 0xC00000
+        assert_eq!(
+            decode(&[0x00, 0x00, 0xC0], FORMAT_PCM, 24).unwrap(),
+            vec![-0.5]
+        );
+        assert!(decode(&[0; 8], FORMAT_PCM, 8).is_err());
+        assert!(parse_wav(b"RIFF\0\0\0\0AVI ").is_err());
+    }
+}
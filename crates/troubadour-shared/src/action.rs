@@ -0,0 +1,251 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+use crate::mixer::{MixerConfig, RoutingSnapshot};
+
+/// Une action d'un ensemble : le sous-ensemble des `Command` qu'on peut
+/// écrire dans la config et rejouer d'un coup ("BRB", heures calmes).
+///
+/// ```toml
+/// [schedule.action_sets.brb]
+/// actions = [
+///     { action = "set_mute", channel = 0, muted = true },
+///     { action = "set_mute", channel = 5, muted = false },
+/// ]
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Action {
+    SetMute {
+        channel: ChannelId,
+        muted: bool,
+    },
+    SetSolo {
+        channel: ChannelId,
+        solo: bool,
+    },
+    /// Volume linéaire (1.0 = 0 dB), comme `Command::SetVolume`
+    SetVolume {
+        channel: ChannelId,
+        level: f32,
+    },
+    /// Fondu d'un bus (voir `Command::FadeBusVolume`) ; `target_db =
+    /// -inf` : fondu jusqu'au silence total
+    FadeBusVolume {
+        bus: ChannelId,
+        target_db: f32,
+        duration_ms: f32,
+    },
+    /// Connecte (`enabled`) ou déconnecte une route
+    SetRoute {
+        from: ChannelId,
+        to: ChannelId,
+        enabled: bool,
+    },
+    /// Passthrough d'urgence vers `bus`, ou coupé (`None`) : voir
+    /// `Command::SetEmergencyPassthrough`
+    SetEmergencyPassthrough {
+        bus: Option<ChannelId>,
+    },
+    /// Remplace les routes par un routage nommé (`[routing_snapshots]`)
+    ApplyRoutingSnapshot {
+        name: String,
+    },
+}
+
+impl Action {
+    /// Canaux dont l'action a besoin.
+    pub fn channels(&self) -> Vec<ChannelId> {
+        match *self {
+            Self::SetMute { channel, .. }
+            | Self::SetSolo { channel, .. }
+            | Self::SetVolume { channel, .. } => vec![channel],
+            Self::FadeBusVolume { bus, .. } => vec![bus],
+            Self::SetRoute { from, to, .. } => vec![from, to],
+            Self::SetEmergencyPassthrough { bus } => bus.into_iter().collect(),
+            // Vérifiés quand le routage est appliqué
+            Self::ApplyRoutingSnapshot { .. } => Vec::new(),
+        }
+    }
+}
+
+/// Actions appliquées ensemble, dans l'ordre : toutes ou aucune.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionSet {
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+impl ActionSet {
+    /// Premier canal référencé absent du mixer (`None` : tout existe).
+    ///
+    /// Vérifié à l'enregistrement : un ensemble qui vise un canal
+    /// supprimé échouerait sinon en plein stream, à 22 h, sans personne
+    /// pour lire l'erreur.
+    pub fn unknown_channel(&self, mixer: &MixerConfig) -> Option<ChannelId> {
+        self.actions
+            .iter()
+            .flat_map(Action::channels)
+            .find(|id| !mixer.channels.iter().any(|c| c.id == *id))
+    }
+
+    /// Premier routage nommé référencé absent de `snapshots` (`None` :
+    /// tous existent). Vérifié à l'enregistrement, comme les canaux.
+    pub fn unknown_routing_snapshot(
+        &self,
+        snapshots: &BTreeMap<String, RoutingSnapshot>,
+    ) -> Option<&str> {
+        self.actions.iter().find_map(|action| match action {
+            Action::ApplyRoutingSnapshot { name } if !snapshots.contains_key(name) => {
+                Some(name.as_str())
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Heure de la journée, écrite "HH:MM" dans la config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay {
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl TimeOfDay {
+    pub fn new(hour: u8, minute: u8) -> Option<Self> {
+        (hour < 24 && minute < 60).then_some(Self { hour, minute })
+    }
+
+    /// Minutes depuis minuit.
+    pub fn minute_of_day(self) -> u32 {
+        u32::from(self.hour) * 60 + u32::from(self.minute)
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.hour, self.minute)
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("invalid time '{value}', expected HH:MM");
+        let (hour, minute) = value.split_once(':').ok_or_else(invalid)?;
+        let hour = hour.trim().parse().map_err(|_| invalid())?;
+        let minute = minute.trim().parse().map_err(|_| invalid())?;
+        Self::new(hour, minute).ok_or_else(invalid)
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+/// Déclenchement quotidien d'un ensemble d'actions à heure fixe.
+///
+/// Une plage "heures calmes" = deux déclencheurs : un à l'entrée, un à
+/// la sortie.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DailyTrigger {
+    pub at: TimeOfDay,
+    pub action_set: String,
+}
+
+/// Section `[schedule]` de la config : ensembles d'actions nommés et
+/// leurs déclencheurs.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub action_sets: BTreeMap<String, ActionSet>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub triggers: Vec<DailyTrigger>,
+
+    /// Décalage fixe de l'heure locale sur UTC, en minutes. Absent :
+    /// fuseau du système, relu à chaque vérification (heure d'été
+    /// comprise). Un décalage fixe, lui, ne suit pas l'heure d'été.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utc_offset_minutes: Option<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mixer::ChannelConfig;
+
+    #[test]
+    fn schedule_roundtrips_through_toml() {
+        let toml_str = r#"
+            utc_offset_minutes = 120
+            triggers = [
+                { at = "22:30", action_set = "quiet" },
+                { at = "07:00", action_set = "awake" },
+            ]
+
+            [action_sets.quiet]
+            actions = [
+                { action = "set_mute", channel = 0, muted = true },
+                { action = "set_route", from = 1, to = 3, enabled = false },
+            ]
+        "#;
+        let schedule: ScheduleConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(schedule.triggers[0].at, TimeOfDay::new(22, 30).unwrap());
+        assert_eq!(schedule.action_sets["quiet"].actions.len(), 2);
+
+        let reloaded: ScheduleConfig =
+            toml::from_str(&toml::to_string(&schedule).unwrap()).unwrap();
+        assert_eq!(reloaded, schedule);
+
+        let bad =
+            toml::from_str::<ScheduleConfig>(r#"triggers = [{ at = "24:10", action_set = "x" }]"#);
+        assert!(bad.is_err());
+    }
+
+    #[test]
+    fn fade_to_silence_roundtrips_as_minus_inf() {
+        let set = ActionSet {
+            actions: vec![Action::FadeBusVolume {
+                bus: ChannelId(3),
+                target_db: f32::NEG_INFINITY,
+                duration_ms: 2000.0,
+            }],
+        };
+        let text = toml::to_string(&set).unwrap();
+        assert!(text.contains("target_db = -inf"), "{text}");
+        let reloaded: ActionSet = toml::from_str(&text).unwrap();
+        assert_eq!(reloaded, set);
+    }
+
+    #[test]
+    fn unknown_channels_are_found() {
+        let mixer = MixerConfig {
+            channels: vec![
+                ChannelConfig::input(0, "Mic"),
+                ChannelConfig::output(3, "A1"),
+            ],
+            ..Default::default()
+        };
+        let mut set = ActionSet {
+            actions: vec![Action::SetRoute {
+                from: ChannelId(0),
+                to: ChannelId(3),
+                enabled: true,
+            }],
+        };
+        assert_eq!(set.unknown_channel(&mixer), None);
+        set.actions.push(Action::SetMute {
+            channel: ChannelId(7),
+            muted: true,
+        });
+        assert_eq!(set.unknown_channel(&mixer), Some(ChannelId(7)));
+    }
+}
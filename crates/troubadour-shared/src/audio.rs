@@ -27,6 +27,9 @@ pub enum SampleRate {
 }
 
 impl SampleRate {
+    /// Tous les rates du moteur, du plus bas au plus haut.
+    pub const ALL: [Self; 4] = [Self::Hz44100, Self::Hz48000, Self::Hz96000, Self::Hz192000];
+
     /// Convertit l'enum en valeur numérique.
     ///
     /// # Pourquoi `self` et pas `&self` ?
@@ -88,6 +91,56 @@ impl BufferSize {
     }
 }
 
+/// Dither appliqué quand la sortie est convertie en entiers (i16).
+///
+/// Arrondir un f32 au pas de 16 bits crée une erreur corrélée au
+/// signal : sur du son très faible, elle s'entend comme de la
+/// distorsion. Ajouter un bruit aléatoire d'environ 1 LSB avant
+/// l'arrondi la transforme en un souffle constant, beaucoup moins gênant.
+/// Sans effet sur une sortie f32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DitherMode {
+    /// Arrondi simple
+    Off,
+    /// Bruit triangulaire (TPDF) : somme de deux bruits uniformes
+    #[default]
+    Tpdf,
+    /// TPDF + mise en forme du bruit (repoussé vers les aigus)
+    TpdfShaped,
+}
+
+/// Nombre de canaux transportés par un bus.
+///
+/// Un bus est stéréo par défaut ; un retour mono (IFB, oreillette d'un
+/// intervenant) reçoit la somme L+R. Un enum plutôt qu'un `u16` : chaque
+/// format a sa propre règle de mixage, et un futur 5.1 s'ajoutera ici.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelCount {
+    Mono,
+    #[default]
+    Stereo,
+}
+
+impl ChannelCount {
+    pub fn channels(self) -> u16 {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+        }
+    }
+}
+
+impl std::fmt::Display for ChannelCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Mono => "mono",
+            Self::Stereo => "stereo",
+        })
+    }
+}
+
 /// Identifie un périphérique audio du système.
 ///
 /// # `String` vs `&str`
@@ -95,16 +148,93 @@ impl BufferSize {
 /// doit vivre indépendamment — elle est envoyée entre threads via channels.
 /// `&str` est une référence → il faudrait une lifetime → complique tout.
 /// Règle : dans les structs qui voyagent, utilise `String`.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DeviceInfo {
-    /// Nom affiché par le système ("Realtek HD Audio", "Blue Yeti", etc.)
+    /// Nom donné par le système ("Realtek HD Audio", "Blue Yeti", etc.),
+    /// tel quel : c'est lui qui identifie le device (assignation, profils)
     pub name: String,
+    /// Repli ASCII de `name` pour les affichages qui ne savent pas rendre
+    /// l'UTF-8 (voir `ascii_display_name`). Jamais utilisé pour retrouver
+    /// le device.
+    #[serde(default)]
+    pub display_ascii: String,
     /// `true` = entrée (micro), `false` = sortie (casque/enceintes)
     pub is_input: bool,
     /// Nombre de canaux supportés (1 = mono, 2 = stéréo)
     pub channels: u16,
-    /// Sample rates supportés par ce device
+    /// Sample rates du moteur supportés par ce device
     pub supported_sample_rates: Vec<SampleRate>,
+    /// Rate de la config par défaut du device : celui auquel son stream
+    /// s'ouvre quand il ne supporte pas le rate demandé
+    #[serde(default)]
+    pub default_sample_rate: Option<u32>,
+}
+
+impl DeviceInfo {
+    pub fn new(
+        name: impl Into<String>,
+        is_input: bool,
+        channels: u16,
+        supported_sample_rates: Vec<SampleRate>,
+    ) -> Self {
+        let name = name.into();
+        Self {
+            display_ascii: ascii_display_name(&name),
+            name,
+            is_input,
+            channels,
+            supported_sample_rates,
+            default_sample_rate: None,
+        }
+    }
+
+    /// Tranches de `width` canaux proposées par le sélecteur de l'UI,
+    /// sous forme `(offset, count)`. Un device 8 canaux en largeur 2 donne
+    /// `(0, 2), (2, 2), (4, 2), (6, 2)` ; un canal impair restant forme
+    /// une tranche plus étroite.
+    pub fn channel_slices(&self, width: u16) -> Vec<(u16, u16)> {
+        let width = width.max(1);
+        (0..self.channels)
+            .step_by(width as usize)
+            .map(|offset| (offset, width.min(self.channels - offset)))
+            .collect()
+    }
+}
+
+/// Version ASCII prudente d'un nom de device, pour un affichage qui ne
+/// rend que l'ASCII : lettres, chiffres, espaces et `-_.,()[]+#` sont
+/// gardés, chaque suite d'autres caractères devient un seul `?`.
+///
+/// "Scarlett 2i2 (3rd Gen) [USB]" reste intact, "Micro Яндекс" devient
+/// "Micro ?" ; un nom sans rien de lisible ("Яндекс микрофон") donne
+/// "Audio device".
+pub fn ascii_display_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || " -_.,()[]+#".contains(c) {
+            out.push(c);
+        } else if !out.ends_with('?') {
+            out.push('?');
+        }
+    }
+    let out = out.trim();
+    if out.chars().all(|c| c == '?' || c == ' ') {
+        return String::from("Audio device");
+    }
+    out.to_string()
+}
+
+/// Libellé d'une tranche de canaux, numérotée à partir de 1 comme sur
+/// la façade des interfaces : `(2, 2)` → "Inputs 3-4", `(0, 1)` → "Input 1".
+/// `count = 0` (tout le device) → "All inputs".
+pub fn channel_slice_label(offset: u16, count: u16) -> String {
+    // En u32 : une tranche en bout de plage (offset 65535) reste lisible
+    let first = u32::from(offset) + 1;
+    match count {
+        0 => "All inputs".to_string(),
+        1 => format!("Input {first}"),
+        _ => format!("Inputs {first}-{}", u32::from(offset) + u32::from(count)),
+    }
 }
 
 /// Identifiant unique d'un canal dans le mixer.
@@ -121,6 +251,127 @@ pub struct DeviceInfo {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct ChannelId(pub usize);
 
+/// Santé du pipeline de sortie (réponse à `RequestAudioStats`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AudioStats {
+    /// Callbacks de sortie servis (en partie) en silence faute d'audio
+    pub underruns: u64,
+    /// Audio en attente devant la sortie au dernier callback, en frames
+    pub output_depth_frames: u32,
+    /// Réserve visée par le régulateur de latence, en frames
+    pub target_depth_frames: u32,
+    /// Frames jetées pour résorber une latence qui grimpe
+    pub frames_skipped: u64,
+    /// Frames répétées pour regagner de la marge
+    pub frames_inserted: u64,
+    /// Écart estimé entre l'horloge de l'entrée et celle de la sortie,
+    /// en ppm (positif : l'entrée va plus vite)
+    #[serde(default)]
+    pub clock_drift_ppm: f32,
+    /// Correction appliquée au ratio de conversion entrée → sortie, en ppm
+    #[serde(default)]
+    pub ratio_correction_ppm: f32,
+    /// Streams déclarés bloqués par le watchdog
+    pub stream_stalls: u64,
+    /// Réouvertures du pipeline lancées par le watchdog
+    pub stream_restarts: u64,
+    /// Commandes en attente dans la file vers le thread audio
+    pub command_queue_depth: u32,
+    /// Blocs jetés par les taps dont le lecteur n'a pas suivi
+    #[serde(default)]
+    pub tap_overflows: u64,
+    /// La boucle de contrôle est en veille : l'entrée ne livre que du
+    /// silence depuis un moment
+    #[serde(default)]
+    pub idle: bool,
+    /// Écoute d'un device en cours (`Command::StartDevicePreview`)
+    #[serde(default)]
+    pub device_preview: Option<DevicePreviewStatus>,
+    /// Canaux abonnés aux meters (`None` : pas d'abonnement, tout est
+    /// mesuré ; voir `Command::SetMeterSubscription`)
+    #[serde(default)]
+    pub meter_subscription: Option<u32>,
+    /// Envoi des meters suspendu (`Command::SetMetersPaused`)
+    #[serde(default)]
+    pub meters_paused: bool,
+    /// Niveaux envoyés à l'UI par seconde
+    #[serde(default)]
+    pub meter_levels_per_second: f32,
+}
+
+/// Écoute d'un device d'entrée avant son assignation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DevicePreviewStatus {
+    pub device: String,
+    /// Bus qui l'entend (`None` : aucun bus de sortie)
+    pub bus: Option<ChannelId>,
+    /// Avant l'arrêt automatique
+    pub remaining_seconds: u32,
+}
+
+/// Pourquoi une écoute de device s'est arrêtée.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewStopReason {
+    /// `Command::StopDevicePreview`
+    Requested,
+    /// Délai écoulé (`DevicePreviewConfig::timeout_seconds`)
+    TimedOut,
+    /// Moteur arrêté
+    EngineStopped,
+    /// Le device a été assigné à une entrée, qui l'ouvre à son tour
+    AssignedToChannel,
+    /// Une autre écoute a pris sa place
+    Replaced,
+}
+
+/// Statistiques de la session audio (réponse à `RequestSessionStats`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    /// Temps passé moteur démarré depuis le début de la session
+    pub uptime_seconds: u64,
+    /// Underruns de sortie depuis le début de la session
+    pub underruns: u64,
+    /// Pire charge DSP d'un bloc : temps de traitement / durée du bloc
+    /// (1.0 = le callback a pris tout le temps dont il disposait)
+    pub peak_dsp_load: f32,
+    /// Crête la plus haute de chaque bus joué
+    pub bus_peaks: Vec<BusPeak>,
+    /// Streams rouverts par le moteur (watchdog, changement de device)
+    pub reconnects: u64,
+}
+
+/// Crête la plus haute vue sur un bus (linéaire, 1.0 = 0 dBFS).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BusPeak {
+    pub bus: ChannelId,
+    pub peak: f32,
+}
+
+/// Dernière activité vue sur une route (canal → bus).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RouteActivity {
+    pub from: ChannelId,
+    pub to: ChannelId,
+    /// Crête de la contribution du canal au bus (linéaire, post-fader)
+    pub peak: f32,
+    /// Quand elle a été vue (ms Unix ; position dans le fichier pour un rendu)
+    pub last_seen_ms: u64,
+}
+
+/// État d'un stream surveillé par le watchdog (réponse à `RequestAudioStats`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamHealth {
+    pub device_name: String,
+    pub is_input: bool,
+    /// `false` : le callback n'a pas tourné depuis plus que le timeout
+    pub healthy: bool,
+    /// Temps écoulé depuis le dernier callback, en ms
+    pub silent_ms: u64,
+    /// Réouvertures tentées depuis le dernier blocage
+    pub restart_attempts: u32,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,15 +412,46 @@ mod tests {
 
     #[test]
     fn device_info_clone() {
-        let device = DeviceInfo {
-            name: String::from("Test Mic"),
-            is_input: true,
-            channels: 1,
-            supported_sample_rates: vec![SampleRate::Hz48000],
-        };
+        let device = DeviceInfo::new("Test Mic", true, 1, vec![SampleRate::Hz48000]);
         // Clone crée une copie profonde indépendante
         let cloned = device.clone();
         assert_eq!(cloned.name, "Test Mic");
         assert_eq!(cloned.channels, 1);
     }
+
+    #[test]
+    fn channel_slices_for_selector() {
+        let device = DeviceInfo::new("8-in Interface", true, 5, vec![]);
+        assert_eq!(device.channel_slices(2), vec![(0, 2), (2, 2), (4, 1)]);
+        assert_eq!(channel_slice_label(2, 2), "Inputs 3-4");
+        assert_eq!(channel_slice_label(4, 1), "Input 5");
+        assert_eq!(channel_slice_label(0, 0), "All inputs");
+        assert_eq!(channel_slice_label(u16::MAX, 1), "Input 65536");
+        assert_eq!(
+            channel_slice_label(u16::MAX, u16::MAX),
+            "Inputs 65536-131070"
+        );
+    }
+
+    #[test]
+    fn device_names_stay_raw_with_an_ascii_fallback() {
+        for (name, ascii) in [
+            (
+                "Scarlett 2i2 (3rd Gen) [USB]",
+                "Scarlett 2i2 (3rd Gen) [USB]",
+            ),
+            ("Яндекс микрофон", "Audio device"),
+            ("Micro Яндекс", "Micro ?"),
+            ("🎤 Podcast Mic", "? Podcast Mic"),
+            ("<b>Mic</b> \"USB\"", "?b?Mic?b? ?USB?"),
+        ] {
+            let device = DeviceInfo::new(name, true, 2, vec![]);
+            assert_eq!(device.name, name);
+            assert_eq!(device.display_ascii, ascii);
+
+            let json = serde_json::to_string(&device).unwrap();
+            let back: DeviceInfo = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, device);
+        }
+    }
 }
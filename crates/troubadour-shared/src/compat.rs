@@ -0,0 +1,92 @@
+//! Rapport de compatibilité d'un preset avec le matériel de la machine,
+//! établi avant de le charger (`troubadour_core::compat`).
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::SampleRate;
+
+/// Ce qu'est devenu un device du preset sur cette machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DeviceMatch {
+    /// Même nom, au caractère près
+    Exact { device: String },
+    /// Nom voisin (device renommé, autre système) ; `confidence` est la
+    /// ressemblance des noms, de 0 à 1
+    Fuzzy { device: String, confidence: f32 },
+    /// Plusieurs devices portent ce nom : le premier sera ouvert, pas
+    /// forcément le bon
+    Ambiguous { device: String, count: usize },
+    /// Aucun device ne correspond
+    Missing,
+}
+
+impl DeviceMatch {
+    /// Device qui serait ouvert.
+    pub fn device(&self) -> Option<&str> {
+        match self {
+            Self::Exact { device }
+            | Self::Fuzzy { device, .. }
+            | Self::Ambiguous { device, .. } => Some(device),
+            Self::Missing => None,
+        }
+    }
+}
+
+/// Un device référencé par le preset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCheck {
+    /// Qui le référence ("Mic", "profile input")
+    pub role: String,
+    /// Nom stocké dans le preset
+    pub wanted: String,
+    pub is_input: bool,
+    pub result: DeviceMatch,
+}
+
+impl DeviceCheck {
+    /// Une ligne pour l'utilisateur.
+    pub fn summary(&self) -> String {
+        let Self { role, wanted, .. } = self;
+        match &self.result {
+            DeviceMatch::Exact { .. } => format!("{role}: {wanted}"),
+            DeviceMatch::Fuzzy { device, confidence } => {
+                format!("{role}: {wanted} → {device} ({:.0} %)", confidence * 100.0)
+            }
+            DeviceMatch::Ambiguous { count, .. } => {
+                format!("{role}: {wanted} ({count} devices with this name)")
+            }
+            DeviceMatch::Missing => format!("{role}: {wanted} not found"),
+        }
+    }
+}
+
+/// Compatibilité d'un preset avec cette machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompatibilityReport {
+    pub preset: String,
+    /// Sample rate du moteur, contre lequel les devices sont vérifiés
+    pub sample_rate: SampleRate,
+    /// Un par device référencé, dans l'ordre des canaux
+    pub devices: Vec<DeviceCheck>,
+    /// Problèmes hors résolution : rate non supporté, trop de canaux
+    pub issues: Vec<String>,
+}
+
+impl CompatibilityReport {
+    /// Le preset se chargerait tel quel : chaque device est trouvé sous
+    /// son nom exact et rien d'autre n'est signalé.
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+            && self
+                .devices
+                .iter()
+                .all(|d| matches!(d.result, DeviceMatch::Exact { .. }))
+    }
+
+    /// Devices introuvables.
+    pub fn missing(&self) -> impl Iterator<Item = &DeviceCheck> {
+        self.devices
+            .iter()
+            .filter(|d| d.result == DeviceMatch::Missing)
+    }
+}
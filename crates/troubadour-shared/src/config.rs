@@ -1,6 +1,21 @@
 use serde::{Deserialize, Serialize};
 
-use crate::audio::{BufferSize, SampleRate};
+use std::collections::BTreeMap;
+
+use crate::action::ScheduleConfig;
+use crate::audio::{BufferSize, ChannelId, DitherMode, SampleRate};
+use crate::error::TroubadourError;
+use crate::machine::MachineProfile;
+use crate::mixer::{
+    DEFAULT_MAX_CHANNELS, FaderTaper, MeterScale, RoutingSnapshot, SignalPresenceConfig,
+};
+use crate::network::{NetworkReceiveConfig, NetworkSendConfig};
+use crate::preset::FavoritePresets;
+use crate::recovery::RecoverySnapshot;
+use crate::script::ScriptingConfig;
+use crate::tap::TapConfig;
+use crate::ui_state::UiState;
+use crate::unknown_keys::unknown_key_warnings;
 
 /// Configuration persistante de Troubadour.
 ///
@@ -13,6 +28,130 @@ use crate::audio::{BufferSize, SampleRate};
 pub struct AppConfig {
     #[serde(default)]
     pub audio: AudioConfig,
+
+    #[serde(default)]
+    pub journal: JournalConfig,
+
+    #[serde(default)]
+    pub controls: ControlsConfig,
+
+    /// Détection de silence par défaut (surchargeable par canal).
+    #[serde(default)]
+    pub signal_presence: SignalPresenceConfig,
+
+    #[serde(default)]
+    pub recovery: RecoveryConfig,
+
+    /// Instantanés pris avant de remplacer l'état (chargement d'un preset).
+    #[serde(default)]
+    pub auto_snapshots: AutoSnapshotConfig,
+
+    /// Surveillance des callbacks audio bloqués.
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
+
+    /// Historique des niveaux servi aux graphes de l'UI.
+    #[serde(default)]
+    pub level_history: LevelHistoryConfig,
+
+    /// Écoute d'un device avant son assignation.
+    #[serde(default)]
+    pub device_preview: DevicePreviewConfig,
+
+    /// Dernières secondes des bus gardées pour `SaveReplay`.
+    #[serde(default)]
+    pub replay: ReplayConfig,
+
+    /// Ensembles d'actions ("BRB", heures calmes) et leurs horaires.
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+
+    /// Scripts d'automatisation (feature `scripting` du moteur).
+    #[serde(default)]
+    pub scripting: ScriptingConfig,
+
+    /// Envoi d'un bus vers une autre machine (PCM sur UDP).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_send: Option<NetworkSendConfig>,
+
+    /// Flux reçu d'une autre machine, joué par un canal d'entrée.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network_receive: Option<NetworkReceiveConfig>,
+
+    /// Copies de bus servies en TCP local à d'autres programmes.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub taps: Vec<TapConfig>,
+
+    /// Devices propres à chaque machine, par hostname :
+    /// `[profiles."<hostname>"]`. `BTreeMap` pour un fichier trié
+    /// (diff stable quand la config est synchronisée).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, MachineProfile>,
+
+    /// Routages rappelés par leur nom (voir `RoutingSnapshot`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub routing_snapshots: BTreeMap<String, RoutingSnapshot>,
+
+    /// État de l'interface, sans schéma (voir `UiState`). Une table
+    /// invalide est ignorée au chargement, jamais bloquante.
+    #[serde(default, skip_serializing_if = "UiState::is_empty")]
+    pub ui: UiState,
+
+    /// Dossier des presets d'effets (défaut :
+    /// `EffectsPresetManager::default_dir()`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset_dir: Option<std::path::PathBuf>,
+
+    /// Presets d'effets favoris, dans l'ordre des touches `1` à `9`.
+    #[serde(default, skip_serializing_if = "FavoritePresets::is_empty")]
+    pub favorites: FavoritePresets,
+
+    /// Langue des noms d'usine des canaux et bus (voir `locale`) ;
+    /// `None` : anglais.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+}
+
+/// Config chargée au démarrage, avec ce qu'il faut proposer à l'utilisateur.
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: AppConfig,
+    /// Un instantané de secours plus récent que la config existe :
+    /// la session précédente s'est mal terminée. L'UI demande
+    /// "Restaurer la session non sauvée ?".
+    pub recovery_pending: bool,
+    /// Sections ignorées au chargement (table `[ui]` invalide), à
+    /// signaler à l'utilisateur
+    pub warnings: Vec<String>,
+}
+
+/// Réglages des surfaces de contrôle (GUI, MIDI, OSC).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ControlsConfig {
+    /// Courbe des faders de volume, partagée par tous les contrôles.
+    #[serde(default)]
+    pub fader_taper: FaderTaper,
+    /// Profil de surface de contrôle actif (MIDI/OSC), par son nom.
+    #[serde(default)]
+    pub control_profile: Option<String>,
+    /// Plancher des VU-meters en dBFS (-60 par défaut ; -48 pour une
+    /// échelle "broadcast").
+    #[serde(default)]
+    pub meter_min_db: Option<f32>,
+    /// Début de la zone d'alerte des meters en dBFS (-18 par défaut).
+    #[serde(default)]
+    pub meter_warn_db: Option<f32>,
+    /// Bus joué par le passthrough d'urgence (défaut : le premier bus
+    /// de sortie, en pratique le mix du stream).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passthrough_bus: Option<ChannelId>,
+}
+
+impl ControlsConfig {
+    /// Échelle des meters, surcharges comprises.
+    pub fn meter_scale(&self) -> MeterScale {
+        MeterScale::new(self.meter_min_db, self.meter_warn_db)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +176,62 @@ pub struct AudioConfig {
 
     #[serde(default)]
     pub output_device: Option<String>,
+
+    /// Dither de la conversion vers une sortie 16 bits.
+    #[serde(default)]
+    pub dither: DitherMode,
+
+    /// Audio gardé en réserve devant la sortie, en blocs du device.
+    /// Plus = plus de marge contre les à-coups, plus de latence.
+    #[serde(default = "AudioConfig::default_latency_blocks")]
+    pub target_output_latency_blocks: u32,
+
+    /// Nombre maximal de canaux du mixer (entrées et bus). Un preset
+    /// qui en contient plus est chargé sans les derniers.
+    #[serde(default = "AudioConfig::default_max_channels")]
+    pub max_channels: usize,
+
+    /// Blocs que des streams rouverts (changement de device, défaut du
+    /// système, stream bloqué) doivent livrer avant de remplacer les
+    /// anciens, qui jouent jusque-là. 0 : fermer d'abord, avec un trou.
+    #[serde(default = "AudioConfig::default_preroll_blocks")]
+    pub preroll_blocks: u32,
+
+    /// Secondes de silence en entrée avant que la boucle de contrôle
+    /// ne ralentisse à 10 Hz pour économiser le CPU. 0 : jamais.
+    #[serde(default = "AudioConfig::default_idle_after_seconds")]
+    pub idle_after_seconds: u32,
+
+    /// Crête d'entrée (dBFS) sous laquelle un bloc compte comme silence.
+    #[serde(default = "AudioConfig::default_idle_threshold_db")]
+    pub idle_threshold_db: f32,
+
+    /// Chronomètre chaque effet dès le lancement (lu par
+    /// `Command::RequestDspProfile`).
+    #[serde(default)]
+    pub dsp_profiling: bool,
+}
+
+impl AudioConfig {
+    fn default_latency_blocks() -> u32 {
+        2
+    }
+
+    fn default_max_channels() -> usize {
+        DEFAULT_MAX_CHANNELS
+    }
+
+    fn default_preroll_blocks() -> u32 {
+        4
+    }
+
+    fn default_idle_after_seconds() -> u32 {
+        30
+    }
+
+    fn default_idle_threshold_db() -> f32 {
+        -70.0
+    }
 }
 
 /// `Default` pour `AudioConfig` — valeurs par défaut sensées.
@@ -53,11 +248,334 @@ impl Default for AudioConfig {
             buffer_size: BufferSize::default(),
             input_device: None,
             output_device: None,
+            dither: DitherMode::default(),
+            target_output_latency_blocks: Self::default_latency_blocks(),
+            max_channels: Self::default_max_channels(),
+            preroll_blocks: Self::default_preroll_blocks(),
+            idle_after_seconds: Self::default_idle_after_seconds(),
+            idle_threshold_db: Self::default_idle_threshold_db(),
+            dsp_profiling: false,
+        }
+    }
+}
+
+/// Configuration du journal des modifications (audit trail).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfig {
+    /// Nombre maximum d'entrées gardées en mémoire.
+    /// Au-delà, les plus anciennes sont évincées.
+    #[serde(default = "JournalConfig::default_capacity")]
+    pub capacity: usize,
+
+    /// Écrit aussi chaque entrée dans `session.log` du dossier de config.
+    #[serde(default)]
+    pub log_to_file: bool,
+}
+
+impl JournalConfig {
+    fn default_capacity() -> usize {
+        500
+    }
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            capacity: Self::default_capacity(),
+            log_to_file: false,
+        }
+    }
+}
+
+/// Instantané de secours en cas de crash (`recovery.toml`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryConfig {
+    #[serde(default = "RecoveryConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// Intervalle minimum entre deux écritures, en secondes.
+    /// Un drag de fader ne déclenche donc pas une écriture par pixel.
+    #[serde(default = "RecoveryConfig::default_interval")]
+    pub interval_seconds: u64,
+}
+
+impl RecoveryConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_interval() -> u64 {
+        10
+    }
+}
+
+impl Default for RecoveryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            interval_seconds: Self::default_interval(),
+        }
+    }
+}
+
+/// Instantanés automatiques de l'état, pris avant chaque opération qui
+/// le remplace en bloc (`<config>/autosnapshots/`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AutoSnapshotConfig {
+    #[serde(default = "AutoSnapshotConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// Instantanés gardés ; au-delà, les plus anciens sont supprimés.
+    #[serde(default = "AutoSnapshotConfig::default_keep")]
+    pub keep: usize,
+
+    /// Un instantané qui ne peut pas s'écrire annule l'opération. Sinon
+    /// (défaut), l'échec est signalé et l'opération a lieu quand même.
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl AutoSnapshotConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_keep() -> usize {
+        10
+    }
+}
+
+impl Default for AutoSnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            keep: Self::default_keep(),
+            strict: false,
+        }
+    }
+}
+
+/// Watchdog des streams audio : détecte un callback qui ne tourne plus
+/// (driver bloqué, device débranché sans erreur) et peut rouvrir le pipeline.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    #[serde(default = "WatchdogConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// Silence d'un callback au-delà duquel son stream est déclaré bloqué.
+    #[serde(default = "WatchdogConfig::default_timeout")]
+    pub timeout_ms: u64,
+
+    /// Rouvre le pipeline quand un stream est bloqué.
+    #[serde(default = "WatchdogConfig::default_enabled")]
+    pub auto_restart: bool,
+
+    /// Tentatives de réouverture avant d'abandonner (remis à zéro dès que
+    /// le stream repart).
+    #[serde(default = "WatchdogConfig::default_max_restarts")]
+    pub max_restarts: u32,
+
+    /// Attente avant la 2e tentative, doublée à chaque nouvel échec.
+    #[serde(default = "WatchdogConfig::default_backoff")]
+    pub restart_backoff_ms: u64,
+}
+
+impl WatchdogConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_timeout() -> u64 {
+        2000
+    }
+
+    fn default_max_restarts() -> u32 {
+        3
+    }
+
+    fn default_backoff() -> u64 {
+        1000
+    }
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            timeout_ms: Self::default_timeout(),
+            auto_restart: Self::default_enabled(),
+            max_restarts: Self::default_max_restarts(),
+            restart_backoff_ms: Self::default_backoff(),
+        }
+    }
+}
+
+/// Historique des niveaux (`SetLevelHistory`) : une case de crête et
+/// de RMS toutes les `resolution_ms`, sur les `window_seconds` dernières
+/// secondes.
+///
+/// Chaque case prend 8 octets (deux `f32`) : 60 s à 100 ms font 600
+/// cases, soit ~4.8 Ko par canal suivi. Au pire (10 ms sur 600 s),
+/// ~480 Ko par canal.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelHistoryConfig {
+    /// Durée d'une case (bornée à 10–1000 ms)
+    #[serde(default = "LevelHistoryConfig::default_resolution")]
+    pub resolution_ms: u64,
+
+    /// Durée couverte (bornée à 1–600 s)
+    #[serde(default = "LevelHistoryConfig::default_window")]
+    pub window_seconds: u32,
+}
+
+impl LevelHistoryConfig {
+    fn default_resolution() -> u64 {
+        100
+    }
+
+    fn default_window() -> u32 {
+        60
+    }
+}
+
+impl Default for LevelHistoryConfig {
+    fn default() -> Self {
+        Self {
+            resolution_ms: Self::default_resolution(),
+            window_seconds: Self::default_window(),
+        }
+    }
+}
+
+/// Écoute d'un device d'entrée (`StartDevicePreview`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DevicePreviewConfig {
+    /// Arrêt automatique de l'écoute, en secondes
+    #[serde(default = "DevicePreviewConfig::default_timeout")]
+    pub timeout_seconds: u32,
+
+    /// Bus qui entend l'écoute, par son nom (`None` : le bus joué par
+    /// le device de sortie)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor_bus: Option<String>,
+}
+
+impl DevicePreviewConfig {
+    fn default_timeout() -> u32 {
+        60
+    }
+}
+
+impl Default for DevicePreviewConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: Self::default_timeout(),
+            monitor_bus: None,
+        }
+    }
+}
+
+/// Enregistrement rétroactif (`[replay]`) : les dernières secondes des
+/// bus listés restent en mémoire, prêtes à être sauvées après coup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    /// Durée gardée par bus (30 s de stéréo à 48 kHz ≈ 11 Mo)
+    #[serde(default = "ReplayConfig::default_seconds")]
+    pub seconds: u32,
+
+    /// Bus gardés dès le démarrage, par leur nom
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buses: Vec<String>,
+
+    /// Dossier des replays sauvés sans chemin (`None` :
+    /// `ReplayConfig::default_dir()`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<std::path::PathBuf>,
+}
+
+impl ReplayConfig {
+    fn default_seconds() -> u32 {
+        30
+    }
+
+    /// Dossier par défaut : `<config>/replays/`.
+    pub fn default_dir() -> std::path::PathBuf {
+        config_dir().join("replays")
+    }
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            seconds: Self::default_seconds(),
+            buses: Vec::new(),
+            dir: None,
         }
     }
 }
 
+/// Écrit un fichier de façon atomique : fichier temporaire voisin, puis `rename`.
+///
+/// # Pourquoi ?
+/// Un crash (ou une coupure de courant) pendant `std::fs::write` laisse
+/// un fichier tronqué : pire que pas de fichier du tout. Un `rename` dans
+/// le même dossier est atomique : on lit soit l'ancien contenu, soit le
+/// nouveau, jamais un mélange.
+pub fn write_atomic(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = path.with_file_name(tmp_name);
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Crée `dir` au besoin, puis y écrit et supprime un fichier témoin.
+///
+/// Les permissions ne disent pas tout (disque plein, montage en
+/// lecture seule, ACL Windows) : seul un vrai essai d'écriture fait foi.
+pub fn ensure_writable(dir: &std::path::Path) -> std::io::Result<()> {
+    let test_file = dir.join(".troubadour-write-test");
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&test_file, b"ok")?;
+    std::fs::remove_file(&test_file)
+}
+
+/// Dossier de configuration de Troubadour, selon l'OS.
+///
+/// - Windows : `%APPDATA%\troubadour`
+/// - macOS : `~/Library/Application Support/troubadour`
+/// - Linux : `$XDG_CONFIG_HOME/troubadour` ou `~/.config/troubadour`
+///
+/// # Pourquoi pas la crate `dirs` ?
+/// Trois variables d'environnement suffisent. Une dépendance de moins
+/// pour une lib partagée, c'est toujours ça de gagné.
+pub fn config_dir() -> std::path::PathBuf {
+    let env_dir = |key: &str| std::env::var_os(key).map(std::path::PathBuf::from);
+
+    let base = if cfg!(target_os = "windows") {
+        env_dir("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env_dir("HOME").map(|h| h.join("Library").join("Application Support"))
+    } else {
+        env_dir("XDG_CONFIG_HOME").or_else(|| env_dir("HOME").map(|h| h.join(".config")))
+    };
+
+    // Dernier recours : le dossier courant (mieux que paniquer).
+    base.unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join("troubadour")
+}
+
 impl AppConfig {
+    /// Emplacement par défaut du fichier de config : `<config>/config.toml`.
+    pub fn default_path() -> std::path::PathBuf {
+        config_dir().join("config.toml")
+    }
+
     /// Charge la config depuis un fichier TOML.
     ///
     /// # `Result` et l'opérateur `?`
@@ -77,10 +595,98 @@ impl AppConfig {
     /// sont compatibles (grâce au trait `From`).
     pub fn load(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
-        let config: Self = toml::from_str(&content)?;
+        let (config, _warnings) = Self::parse(&content)?;
         Ok(config)
     }
 
+    /// Lit une config TOML ; retourne aussi ce qui a été ignoré : clés
+    /// inconnues (fautes de frappe, voir `unknown_keys`) et `[ui]`
+    /// illisible.
+    ///
+    /// # `[ui]` à part
+    /// L'état de l'UI est lu APRÈS le reste : une table `[ui]`
+    /// invalide (trop grosse, trop imbriquée, pas une table) est
+    /// abandonnée avec un avertissement, et le mixer se charge quand
+    /// même. Une erreur dans les autres sections, elle, reste une erreur.
+    pub fn parse(content: &str) -> Result<(Self, Vec<String>), Box<dyn std::error::Error>> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        let ui = table.remove("ui");
+        let input = toml::Value::Table(table);
+        let mut config: Self = input.clone().try_into()?;
+        let mut warnings = unknown_key_warnings(&input, &config);
+        match ui {
+            Some(toml::Value::Table(ui)) => match UiState::from_table(ui) {
+                Ok(ui) => config.ui = ui,
+                Err(e) => warnings.push(format!("[ui] ignored: {e}")),
+            },
+            Some(_) => warnings.push("[ui] ignored: not a table".to_string()),
+            None => {}
+        }
+        Ok((config, warnings))
+    }
+
+    /// Comme `parse`, mais ce qui serait ignoré est une erreur
+    /// (validation avant déploiement : `troubadour config validate --strict`).
+    pub fn parse_strict(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (config, warnings) = Self::parse(content)?;
+        if !warnings.is_empty() {
+            return Err(TroubadourError::ConfigError(warnings.join("; ")).into());
+        }
+        Ok(config)
+    }
+
+    /// Charge la config (défauts si le fichier est absent) et signale
+    /// un instantané de secours laissé par une session interrompue.
+    pub fn load_with_recovery(
+        path: &std::path::Path,
+        recovery_path: &std::path::Path,
+    ) -> Result<LoadedConfig, Box<dyn std::error::Error>> {
+        let (config, warnings) = if path.exists() {
+            Self::parse(&std::fs::read_to_string(path)?)?
+        } else {
+            (Self::default(), Vec::new())
+        };
+        Ok(LoadedConfig {
+            config,
+            recovery_pending: RecoverySnapshot::is_pending(recovery_path, path),
+            warnings,
+        })
+    }
+
+    /// Profil de la machine `machine` (vide s'il n'existe pas encore).
+    pub fn machine_profile(&self, machine: &str) -> MachineProfile {
+        self.profiles.get(machine).cloned().unwrap_or_default()
+    }
+
+    /// Remplace le profil de `machine` et lui seul : les sections des
+    /// autres machines sont conservées telles quelles.
+    pub fn set_machine_profile(&mut self, machine: &str, profile: MachineProfile) {
+        self.profiles.insert(machine.to_string(), profile);
+    }
+
+    /// Migre une ancienne config "à plat" : les devices de `[audio]`
+    /// deviennent ceux du profil de `machine`, si celle-ci n'en a pas
+    /// encore. Retourne `true` si la config a changé.
+    ///
+    /// Les champs à plat sont vidés dans tous les cas : une autre
+    /// machine qui synchronise ce fichier ne doit pas hériter de
+    /// devices qu'elle n'a pas.
+    pub fn migrate_to_machine(&mut self, machine: &str) -> bool {
+        let input = self.audio.input_device.take();
+        let output = self.audio.output_device.take();
+        if input.is_none() && output.is_none() {
+            return false;
+        }
+        self.profiles
+            .entry(machine.to_string())
+            .or_insert_with(|| MachineProfile {
+                input_device: input,
+                output_device: output,
+                ..MachineProfile::default()
+            });
+        true
+    }
+
     /// Sauvegarde la config dans un fichier TOML.
     pub fn save(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
         let content = toml::to_string_pretty(self)?;
@@ -97,6 +703,7 @@ impl AppConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mixer::Route;
 
     #[test]
     fn default_config_values() {
@@ -105,6 +712,120 @@ mod tests {
         assert_eq!(config.audio.buffer_size, BufferSize::Samples256);
         assert!(config.audio.input_device.is_none());
         assert!(config.audio.output_device.is_none());
+        assert_eq!(config.journal.capacity, 500);
+        assert!(!config.journal.log_to_file);
+        assert_eq!(config.controls.fader_taper, FaderTaper::Audio);
+        assert_eq!(config.signal_presence.silence_seconds, 30.0);
+        assert!(config.recovery.enabled);
+        assert_eq!(config.recovery.interval_seconds, 10);
+        assert_eq!(config.watchdog, WatchdogConfig::default());
+        assert_eq!(config.watchdog.timeout_ms, 2000);
+    }
+
+    #[test]
+    fn write_atomic_replaces_content() {
+        let dir = std::env::temp_dir().join(format!("troubadour-atomic-{}", std::process::id()));
+        let path = dir.join("state.toml");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        // Pas de fichier temporaire qui traîne
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fader_taper_from_toml() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [controls]
+            fader_taper = "exponential"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.controls.fader_taper, FaderTaper::Exponential);
+    }
+
+    #[test]
+    fn flat_config_migrates_into_machine_profile() {
+        let mut config: AppConfig = toml::from_str(
+            r#"
+            [audio]
+            input_device = "Blue Yeti"
+            output_device = "HD 600"
+        "#,
+        )
+        .unwrap();
+
+        assert!(config.migrate_to_machine("desktop"));
+        assert!(config.audio.input_device.is_none());
+        assert!(config.audio.output_device.is_none());
+        let profile = config.machine_profile("desktop");
+        assert_eq!(profile.input_device.as_deref(), Some("Blue Yeti"));
+        assert_eq!(profile.output_device.as_deref(), Some("HD 600"));
+
+        // Déjà migrée : plus rien à faire
+        assert!(!config.migrate_to_machine("desktop"));
+    }
+
+    #[test]
+    fn migration_keeps_existing_machine_profile() {
+        let mut config: AppConfig = toml::from_str(
+            r#"
+            [audio]
+            input_device = "Old Mic"
+
+            [profiles.desktop]
+            input_device = "New Mic"
+        "#,
+        )
+        .unwrap();
+        config.migrate_to_machine("desktop");
+        assert_eq!(
+            config.machine_profile("desktop").input_device.as_deref(),
+            Some("New Mic")
+        );
+        assert!(config.audio.input_device.is_none());
+    }
+
+    #[test]
+    fn saving_a_machine_profile_leaves_others_untouched() {
+        let mut config: AppConfig = toml::from_str(
+            r#"
+            [profiles.laptop]
+            audio_host = "JACK"
+            output_device = "Built-in"
+
+            [profiles.desktop]
+            output_device = "HD 600"
+        "#,
+        )
+        .unwrap();
+        let laptop_before = config.machine_profile("laptop");
+
+        config.set_machine_profile(
+            "desktop",
+            MachineProfile {
+                output_device: Some("Speakers".into()),
+                ..MachineProfile::default()
+            },
+        );
+
+        let parsed: AppConfig = toml::from_str(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(parsed.machine_profile("laptop"), laptop_before);
+        assert_eq!(
+            parsed.machine_profile("desktop").output_device.as_deref(),
+            Some("Speakers")
+        );
+        assert_eq!(parsed.machine_profile("unknown"), MachineProfile::default());
+    }
+
+    #[test]
+    fn config_dir_ends_with_app_name() {
+        assert!(config_dir().ends_with("troubadour"));
     }
 
     #[test]
@@ -117,7 +838,15 @@ mod tests {
                 buffer_size: BufferSize::Samples128,
                 input_device: Some("Blue Yeti".to_string()),
                 output_device: Some("HD 600".to_string()),
+                dither: DitherMode::TpdfShaped,
+                target_output_latency_blocks: 3,
+                max_channels: 32,
+                preroll_blocks: 0,
+                idle_after_seconds: 0,
+                idle_threshold_db: -60.0,
+                dsp_profiling: true,
             },
+            ..AppConfig::default()
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -125,6 +854,10 @@ mod tests {
 
         assert_eq!(parsed.audio.sample_rate, SampleRate::Hz96000);
         assert_eq!(parsed.audio.buffer_size, BufferSize::Samples128);
+        assert_eq!(parsed.audio.dither, DitherMode::TpdfShaped);
+        assert_eq!(parsed.audio.target_output_latency_blocks, 3);
+        assert_eq!(parsed.audio.preroll_blocks, 0);
+        assert!(parsed.audio.dsp_profiling);
         assert_eq!(parsed.audio.input_device.as_deref(), Some("Blue Yeti"));
         assert_eq!(parsed.audio.output_device.as_deref(), Some("HD 600"));
     }
@@ -142,6 +875,8 @@ mod tests {
         assert_eq!(config.audio.sample_rate, SampleRate::Hz48000);
         assert_eq!(config.audio.buffer_size, BufferSize::Samples256); // défaut
         assert!(config.audio.input_device.is_none()); // défaut
+        assert_eq!(config.audio.target_output_latency_blocks, 2); // défaut
+        assert!(!config.audio.dsp_profiling); // défaut
     }
 
     #[test]
@@ -151,6 +886,133 @@ mod tests {
         assert_eq!(config.audio.sample_rate, SampleRate::Hz48000);
     }
 
+    #[test]
+    fn network_sections_are_optional() {
+        let config: AppConfig = toml::from_str("").unwrap();
+        assert!(config.network_send.is_none() && config.network_receive.is_none());
+        assert!(config.taps.is_empty());
+
+        let toml_str = r#"
+            [network_send]
+            bus = "Stream"
+            host = "192.168.1.20"
+            port = 9400
+            int16 = true
+
+            [network_receive]
+            channel = "Encoder return"
+            port = 9401
+
+            [[taps]]
+            bus = "Stream"
+            port = 9500
+        "#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        let send = config.network_send.unwrap();
+        assert_eq!((send.bus.as_str(), send.port), ("Stream", 9400));
+        assert!(send.int16 && send.enabled);
+        let receive = config.network_receive.unwrap();
+        assert_eq!(receive.bind, "0.0.0.0");
+        assert_eq!(receive.jitter_packets, 4);
+        let tap = &config.taps[0];
+        assert_eq!((tap.bus.as_str(), tap.port), ("Stream", 9500));
+        assert_eq!(tap.capacity_blocks, TapConfig::DEFAULT_CAPACITY_BLOCKS);
+        assert!(tap.enabled);
+    }
+
+    #[test]
+    fn ui_table_roundtrips_through_the_config_file() {
+        let config = AppConfig {
+            ui: UiState::from_json(
+                r#"{"panel": "routing", "meters": {"style": "rms"}, "collapsed": [2, 3]}"#,
+            )
+            .unwrap(),
+            ..AppConfig::default()
+        };
+        let text = toml::to_string_pretty(&config).unwrap();
+        let (parsed, warnings) = AppConfig::parse(&text).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(parsed.ui, config.ui);
+        // Sans état d'UI, pas de section vide dans le fichier
+        assert!(
+            !toml::to_string(&AppConfig::default())
+                .unwrap()
+                .contains("[ui]")
+        );
+    }
+
+    #[test]
+    fn favorites_keep_their_order_in_the_config_file() {
+        let (config, warnings) = AppConfig::parse("favorites = [\"Voice\", \"Music\"]\n").unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(config.favorites.names(), ["Voice", "Music"]);
+
+        let saved = toml::to_string_pretty(&config).unwrap();
+        let (parsed, _) = AppConfig::parse(&saved).unwrap();
+        assert_eq!(parsed.favorites, config.favorites);
+        assert!(
+            !toml::to_string(&AppConfig::default())
+                .unwrap()
+                .contains("favorites")
+        );
+    }
+
+    #[test]
+    fn routing_snapshots_roundtrip_through_the_config_file() {
+        let text = "[routing_snapshots.interview]\nroutes = [{ from = 0, to = 3 }, { from = 5, to = 4 }]\n\n[routing_snapshots.music]\n";
+        let (config, warnings) = AppConfig::parse(text).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        assert_eq!(
+            config.routing_snapshots["interview"].routes,
+            [
+                Route::new(ChannelId(0), ChannelId(3)),
+                Route::new(ChannelId(5), ChannelId(4))
+            ]
+        );
+        assert!(config.routing_snapshots["music"].routes.is_empty());
+
+        let saved = toml::to_string_pretty(&config).unwrap();
+        let (parsed, _) = AppConfig::parse(&saved).unwrap();
+        assert_eq!(parsed.routing_snapshots, config.routing_snapshots);
+        assert!(
+            !toml::to_string(&AppConfig::default())
+                .unwrap()
+                .contains("routing_snapshots")
+        );
+    }
+
+    #[test]
+    fn corrupt_ui_table_is_dropped_and_the_rest_loads() {
+        let deep = format!(
+            "[ui]\n{}",
+            (0..20)
+                .map(|i| format!("[ui{}]\nv = {i}\n", ".a".repeat(i + 1)))
+                .collect::<String>()
+        );
+        for ui in ["ui = 5\n".to_string(), deep] {
+            let text = format!("{ui}\n[journal]\ncapacity = 42\n");
+            let (config, warnings) = AppConfig::parse(&text).unwrap();
+            assert_eq!(config.journal.capacity, 42);
+            assert!(config.ui.is_empty());
+            assert_eq!(warnings.len(), 1, "{text}");
+            assert!(warnings[0].starts_with("[ui] ignored"));
+        }
+        // Une erreur ailleurs reste une erreur
+        assert!(AppConfig::parse("[journal]\ncapacity = \"many\"\n").is_err());
+    }
+
+    #[test]
+    fn unknown_keys_warn_and_fail_in_strict_mode() {
+        let text = "[journal]\ncapacity = 42\ncapacty = 7\n";
+        let (config, warnings) = AppConfig::parse(text).unwrap();
+        assert_eq!(config.journal.capacity, 42);
+        assert_eq!(warnings, ["unknown key journal.capacty ignored"]);
+
+        let strict = AppConfig::parse_strict(text).unwrap_err().to_string();
+        assert!(strict.contains("journal.capacty"), "{strict}");
+        assert!(AppConfig::parse_strict("[journal]\ncapacity = 42\n").is_ok());
+    }
+
     #[test]
     fn config_save_and_load() {
         // Test d'intégration : écrire sur disque puis relire.
@@ -166,7 +1028,15 @@ mod tests {
                 buffer_size: BufferSize::Samples64,
                 input_device: Some("Test Mic".to_string()),
                 output_device: None,
+                dither: DitherMode::Off,
+                target_output_latency_blocks: 2,
+                max_channels: DEFAULT_MAX_CHANNELS,
+                preroll_blocks: 4,
+                idle_after_seconds: 30,
+                idle_threshold_db: -70.0,
+                dsp_profiling: false,
             },
+            ..AppConfig::default()
         };
 
         config.save(&path).unwrap();
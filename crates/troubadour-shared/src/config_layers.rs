@@ -0,0 +1,621 @@
+//! Config effective, en couches : défauts < `config.toml` <
+//! environnement < ligne de commande.
+//!
+//! Pour un déploiement scripté (machine de régie, conteneur), changer
+//! le buffer ou le dossier des presets ne doit pas demander d'éditer
+//! `config.toml` :
+//!
+//! ```text
+//! TROUBADOUR_BUFFER_SIZE=128 troubadour tui --sample-rate 96000
+//! ```
+//!
+//! La fusion est une étape explicite (`resolve`) qui retient, pour
+//! chaque réglage, la couche qui l'a fourni : `troubadour config`
+//! l'affiche, l'UI la demande par `Command::RequestEffectiveConfig`.
+//!
+//! # Erreurs
+//! Une variable d'environnement invalide est un avertissement : elle a
+//! pu être posée il y a longtemps, ailleurs, et ne doit pas empêcher le
+//! stream de démarrer. La couche d'en dessous s'applique. Une option de
+//! ligne de commande invalide, elle, vient d'être tapée : erreur.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::machine::MachineProfile;
+
+/// Préfixe des variables d'environnement (`TROUBADOUR_SAMPLE_RATE`).
+pub const ENV_PREFIX: &str = "TROUBADOUR_";
+
+/// Couche qui a fourni une valeur, de la plus faible à la plus forte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigLayer {
+    Default,
+    File,
+    Env,
+    Cli,
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::File => "config file",
+            Self::Env => "environment",
+            Self::Cli => "command line",
+        })
+    }
+}
+
+/// Un réglage de la config effective et son origine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigEntry {
+    /// Clé TOML complète (`audio.sample_rate`)
+    pub key: String,
+    /// Valeur telle qu'écrite dans le fichier (`""` : pas de valeur)
+    pub value: String,
+    pub layer: ConfigLayer,
+}
+
+/// Où vit un réglage dans `config.toml`.
+#[derive(Clone, Copy)]
+enum Scope {
+    Root,
+    Section(&'static str),
+    /// `[profiles."<machine>"]` : devices et backend de cette machine
+    Machine,
+}
+
+/// Réglage surchargeable : où le lire, comment l'écrire. `get` et `set`
+/// reçoivent le nom de la machine (utile aux réglages `Scope::Machine`).
+struct Setting {
+    scope: Scope,
+    field: &'static str,
+    get: fn(&AppConfig, &str) -> String,
+    set: fn(&mut AppConfig, &str, &str) -> Result<(), String>,
+}
+
+impl Setting {
+    fn key(&self, machine: &str) -> String {
+        match self.scope {
+            Scope::Root => self.field.to_string(),
+            Scope::Section(section) => format!("{section}.{}", self.field),
+            Scope::Machine => format!("profiles.{machine}.{}", self.field),
+        }
+    }
+
+    /// `TROUBADOUR_SAMPLE_RATE`
+    fn env_var(&self) -> String {
+        format!("{ENV_PREFIX}{}", self.field.to_uppercase())
+    }
+
+    /// `--sample-rate`
+    fn flag(&self) -> String {
+        format!("--{}", self.field.replace('_', "-"))
+    }
+}
+
+/// Valeur d'un enum sérialisé en texte (`"48000"`, `"tpdf"`).
+fn parse_enum<T: DeserializeOwned>(raw: &str) -> Result<T, String> {
+    toml::Value::String(raw.to_string())
+        .try_into()
+        .map_err(|_| format!("invalid value {raw:?}"))
+}
+
+fn enum_text<T: Serialize>(value: &T) -> String {
+    match toml::Value::try_from(value) {
+        Ok(toml::Value::String(text)) => text,
+        _ => String::new(),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(raw: &str) -> Result<T, String> {
+    raw.trim()
+        .parse()
+        .map_err(|_| format!("invalid number {raw:?}"))
+}
+
+/// Nom de device ou de backend : vide = celui du système.
+fn parse_name(raw: &str) -> Option<String> {
+    Some(raw.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+fn machine_field(
+    config: &AppConfig,
+    machine: &str,
+    field: fn(&MachineProfile) -> &Option<String>,
+) -> String {
+    config
+        .profiles
+        .get(machine)
+        .and_then(|p| field(p).clone())
+        .unwrap_or_default()
+}
+
+fn machine_mut<'a>(config: &'a mut AppConfig, machine: &str) -> &'a mut MachineProfile {
+    config.profiles.entry(machine.to_string()).or_default()
+}
+
+/// Réglages surchargeables, dans l'ordre du rapport.
+const SETTINGS: [Setting; 12] = [
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "sample_rate",
+        get: |c, _| enum_text(&c.audio.sample_rate),
+        set: |c, _, raw| {
+            c.audio.sample_rate = parse_enum(raw)?;
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "buffer_size",
+        get: |c, _| enum_text(&c.audio.buffer_size),
+        set: |c, _, raw| {
+            c.audio.buffer_size = parse_enum(raw)?;
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "dither",
+        get: |c, _| enum_text(&c.audio.dither),
+        set: |c, _, raw| {
+            c.audio.dither = parse_enum(raw)?;
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "target_output_latency_blocks",
+        get: |c, _| c.audio.target_output_latency_blocks.to_string(),
+        set: |c, _, raw| {
+            c.audio.target_output_latency_blocks = parse_number(raw)?;
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "max_channels",
+        get: |c, _| c.audio.max_channels.to_string(),
+        set: |c, _, raw| match parse_number(raw)? {
+            0 => Err("at least one channel is needed".to_string()),
+            max => {
+                c.audio.max_channels = max;
+                Ok(())
+            }
+        },
+    },
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "preroll_blocks",
+        get: |c, _| c.audio.preroll_blocks.to_string(),
+        set: |c, _, raw| {
+            c.audio.preroll_blocks = parse_number(raw)?;
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "idle_after_seconds",
+        get: |c, _| c.audio.idle_after_seconds.to_string(),
+        set: |c, _, raw| {
+            c.audio.idle_after_seconds = parse_number(raw)?;
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Section("audio"),
+        field: "idle_threshold_db",
+        get: |c, _| c.audio.idle_threshold_db.to_string(),
+        set: |c, _, raw| {
+            c.audio.idle_threshold_db = parse_number(raw)?;
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Machine,
+        field: "audio_host",
+        get: |c, m| machine_field(c, m, |p| &p.audio_host),
+        set: |c, m, raw| {
+            machine_mut(c, m).audio_host = parse_name(raw);
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Machine,
+        field: "input_device",
+        get: |c, m| machine_field(c, m, |p| &p.input_device),
+        set: |c, m, raw| {
+            machine_mut(c, m).input_device = parse_name(raw);
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Machine,
+        field: "output_device",
+        get: |c, m| machine_field(c, m, |p| &p.output_device),
+        set: |c, m, raw| {
+            machine_mut(c, m).output_device = parse_name(raw);
+            Ok(())
+        },
+    },
+    Setting {
+        scope: Scope::Root,
+        field: "preset_dir",
+        get: |c, _| {
+            c.preset_dir
+                .as_ref()
+                .map_or_else(String::new, |d| d.display().to_string())
+        },
+        set: |c, _, raw| {
+            c.preset_dir = Some(raw.trim())
+                .filter(|d| !d.is_empty())
+                .map(PathBuf::from);
+            Ok(())
+        },
+    },
+];
+
+/// Options de ligne de commande reconnues (`--sample-rate`...).
+pub fn cli_flags() -> Vec<String> {
+    SETTINGS.iter().map(Setting::flag).collect()
+}
+
+/// Clés présentes dans un fichier de config (`audio.sample_rate`),
+/// pour attribuer les valeurs à la couche "fichier" même quand elles
+/// valent le défaut. Fichier illisible : aucune.
+pub fn file_keys(content: &str, machine: &str) -> HashSet<String> {
+    let Ok(table) = toml::from_str::<toml::Table>(content) else {
+        return HashSet::new();
+    };
+    let section = |name: &str| table.get(name).and_then(toml::Value::as_table);
+    SETTINGS
+        .iter()
+        .filter(|s| match s.scope {
+            Scope::Root => table.contains_key(s.field),
+            Scope::Section(name) => section(name).is_some_and(|t| t.contains_key(s.field)),
+            Scope::Machine => section("profiles")
+                .and_then(|p| p.get(machine))
+                .and_then(toml::Value::as_table)
+                .is_some_and(|t| t.contains_key(s.field)),
+        })
+        .map(|s| s.key(machine))
+        .collect()
+}
+
+/// Résultat de la fusion des couches.
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    pub config: AppConfig,
+    /// Chaque réglage surchargeable, avec sa couche
+    pub entries: Vec<ConfigEntry>,
+    /// Variables d'environnement ignorées
+    pub warnings: Vec<String>,
+    /// Config avant surcharges, pour `restore_file_values`
+    file: AppConfig,
+    machine: String,
+}
+
+impl EffectiveConfig {
+    pub fn layer(&self, key: &str) -> Option<ConfigLayer> {
+        self.entries.iter().find(|e| e.key == key).map(|e| e.layer)
+    }
+
+    /// Avant `AppConfig::save` : remet la valeur du fichier partout où
+    /// une surcharge est encore en place, pour qu'un
+    /// `TROUBADOUR_BUFFER_SIZE` ne finisse pas écrit dans `config.toml`.
+    /// Un réglage changé pendant la session est gardé.
+    pub fn restore_file_values(&self, config: &mut AppConfig) {
+        let machine = self.machine.as_str();
+        for (setting, entry) in SETTINGS.iter().zip(&self.entries) {
+            if entry.layer >= ConfigLayer::Env && (setting.get)(config, machine) == entry.value {
+                let _ = (setting.set)(config, machine, &(setting.get)(&self.file, machine));
+            }
+        }
+    }
+}
+
+/// Fusionne les couches au-dessus d'une config déjà lue.
+///
+/// - `file` : config lue (défauts compris), `in_file` ses clés présentes
+///   (`file_keys`) ;
+/// - `machine` : profil qui reçoit backend et devices surchargés ;
+/// - `env` : lecture d'une variable (`|k| std::env::var(k).ok()`, un
+///   tableau dans les tests) ;
+/// - `cli` : paires `(--option, valeur)`, dans l'ordre : la dernière
+///   l'emporte.
+///
+/// Erreur : option inconnue ou valeur invalide en ligne de commande.
+pub fn resolve(
+    file: AppConfig,
+    in_file: &HashSet<String>,
+    machine: &str,
+    env: impl Fn(&str) -> Option<String>,
+    cli: &[(String, String)],
+) -> Result<EffectiveConfig, String> {
+    if let Some((flag, _)) = cli
+        .iter()
+        .find(|(flag, _)| !SETTINGS.iter().any(|s| s.flag() == *flag))
+    {
+        return Err(format!("Unknown option: {flag}"));
+    }
+
+    let mut config = file.clone();
+    let mut entries = Vec::with_capacity(SETTINGS.len());
+    let mut warnings = Vec::new();
+    for setting in &SETTINGS {
+        let key = setting.key(machine);
+        let mut layer = if in_file.contains(&key) {
+            ConfigLayer::File
+        } else {
+            ConfigLayer::Default
+        };
+        let var = setting.env_var();
+        if let Some(raw) = env(&var) {
+            match (setting.set)(&mut config, machine, &raw) {
+                Ok(()) => layer = ConfigLayer::Env,
+                Err(e) => warnings.push(format!("{var} ignored: {e}")),
+            }
+        }
+        let flag = setting.flag();
+        for (_, raw) in cli.iter().filter(|(f, _)| *f == flag) {
+            (setting.set)(&mut config, machine, raw).map_err(|e| format!("{flag}: {e}"))?;
+            layer = ConfigLayer::Cli;
+        }
+        entries.push(ConfigEntry {
+            value: (setting.get)(&config, machine),
+            key,
+            layer,
+        });
+    }
+    Ok(EffectiveConfig {
+        config,
+        entries,
+        warnings,
+        file,
+        machine: machine.to_string(),
+    })
+}
+
+impl fmt::Display for EffectiveConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            let value = if entry.value.is_empty() {
+                "(none)"
+            } else {
+                &entry.value
+            };
+            writeln!(f, "{} = {value}  [{}]", entry.key, entry.layer)?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "warning: {warning}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{BufferSize, DitherMode, SampleRate};
+
+    const MACHINE: &str = "desk";
+
+    const FILE: &str = r#"
+preset_dir = "/srv/presets"
+
+[audio]
+sample_rate = "44100"
+buffer_size = "256"
+
+[profiles.desk]
+audio_host = "ALSA"
+"#;
+
+    fn file_config() -> (AppConfig, HashSet<String>) {
+        let (config, _) = AppConfig::parse(FILE).unwrap();
+        (config, file_keys(FILE, MACHINE))
+    }
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: Vec<(String, String)> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+    }
+
+    fn cli(args: &[(&str, &str)]) -> Vec<(String, String)> {
+        args.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn each_layer_overrides_the_one_below() {
+        let (config, keys) = file_config();
+        let effective = resolve(
+            config,
+            &keys,
+            MACHINE,
+            env(&[
+                ("TROUBADOUR_BUFFER_SIZE", "128"),
+                ("TROUBADOUR_SAMPLE_RATE", "48000"),
+                ("TROUBADOUR_DITHER", "off"),
+                ("TROUBADOUR_AUDIO_HOST", "JACK"),
+            ]),
+            &cli(&[("--sample-rate", "96000"), ("--max-channels", "12")]),
+        )
+        .unwrap();
+        let audio = &effective.config.audio;
+
+        // Défaut < fichier < environnement < ligne de commande
+        assert_eq!(audio.preroll_blocks, 4);
+        assert_eq!(
+            effective.layer("audio.preroll_blocks"),
+            Some(ConfigLayer::Default)
+        );
+        assert_eq!(
+            effective.config.preset_dir,
+            Some(PathBuf::from("/srv/presets"))
+        );
+        assert_eq!(effective.layer("preset_dir"), Some(ConfigLayer::File));
+        assert_eq!(audio.buffer_size, BufferSize::Samples128);
+        assert_eq!(audio.dither, DitherMode::Off);
+        assert_eq!(effective.layer("audio.buffer_size"), Some(ConfigLayer::Env));
+        assert_eq!(audio.sample_rate, SampleRate::Hz96000);
+        assert_eq!(effective.layer("audio.sample_rate"), Some(ConfigLayer::Cli));
+        assert_eq!(audio.max_channels, 12);
+        assert!(effective.warnings.is_empty());
+    }
+
+    #[test]
+    fn host_and_devices_override_the_machine_profile() {
+        let (config, keys) = file_config();
+        let effective = resolve(
+            config,
+            &keys,
+            MACHINE,
+            env(&[("TROUBADOUR_AUDIO_HOST", "JACK")]),
+            &cli(&[("--output-device", "Scarlett 2i2")]),
+        )
+        .unwrap();
+        let profile = effective.config.machine_profile(MACHINE);
+
+        assert_eq!(profile.audio_host.as_deref(), Some("JACK"));
+        assert_eq!(
+            effective.layer("profiles.desk.audio_host"),
+            Some(ConfigLayer::Env)
+        );
+        assert_eq!(profile.output_device.as_deref(), Some("Scarlett 2i2"));
+        assert_eq!(
+            effective.layer("profiles.desk.output_device"),
+            Some(ConfigLayer::Cli)
+        );
+        // Les autres machines ne sont pas touchées
+        assert_eq!(effective.config.profiles.len(), 1);
+    }
+
+    #[test]
+    fn value_equal_to_the_default_still_comes_from_the_file() {
+        let (config, keys) = file_config();
+        let effective = resolve(config, &keys, MACHINE, env(&[]), &[]).unwrap();
+        // "256" est aussi le défaut : le fichier le fixe quand même
+        assert_eq!(
+            effective.layer("audio.buffer_size"),
+            Some(ConfigLayer::File)
+        );
+        assert_eq!(
+            effective.layer("profiles.desk.audio_host"),
+            Some(ConfigLayer::File)
+        );
+        assert_eq!(effective.layer("audio.dither"), Some(ConfigLayer::Default));
+    }
+
+    #[test]
+    fn invalid_env_values_warn_and_fall_back() {
+        let (config, keys) = file_config();
+        let effective = resolve(
+            config,
+            &keys,
+            MACHINE,
+            env(&[
+                ("TROUBADOUR_SAMPLE_RATE", "fast"),
+                ("TROUBADOUR_MAX_CHANNELS", "0"),
+                ("TROUBADOUR_PRESET_DIR", "/tmp/p"),
+            ]),
+            &[],
+        )
+        .unwrap();
+
+        assert_eq!(effective.config.audio.sample_rate, SampleRate::Hz44100);
+        assert_eq!(
+            effective.layer("audio.sample_rate"),
+            Some(ConfigLayer::File)
+        );
+        assert_eq!(
+            effective.config.audio.max_channels,
+            AppConfig::default().audio.max_channels
+        );
+        assert_eq!(effective.warnings.len(), 2);
+        assert!(effective.warnings[0].starts_with("TROUBADOUR_SAMPLE_RATE ignored"));
+        // Les autres variables s'appliquent
+        assert_eq!(effective.config.preset_dir, Some(PathBuf::from("/tmp/p")));
+    }
+
+    #[test]
+    fn invalid_command_line_is_an_error() {
+        let (config, keys) = file_config();
+        assert!(
+            resolve(
+                config.clone(),
+                &keys,
+                MACHINE,
+                env(&[]),
+                &cli(&[("--rate", "1")])
+            )
+            .is_err()
+        );
+        let err = resolve(
+            config,
+            &keys,
+            MACHINE,
+            env(&[]),
+            &cli(&[("--buffer-size", "100")]),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("--buffer-size"));
+    }
+
+    #[test]
+    fn overrides_are_not_saved_back_to_the_file() {
+        let (config, keys) = file_config();
+        let effective = resolve(
+            config,
+            &keys,
+            MACHINE,
+            env(&[("TROUBADOUR_BUFFER_SIZE", "128")]),
+            &cli(&[("--sample-rate", "96000"), ("--audio-host", "JACK")]),
+        )
+        .unwrap();
+
+        let mut session = effective.config.clone();
+        // Changé pendant la session : gardé
+        session.audio.sample_rate = SampleRate::Hz48000;
+        effective.restore_file_values(&mut session);
+
+        assert_eq!(session.audio.buffer_size, BufferSize::Samples256);
+        assert_eq!(session.audio.sample_rate, SampleRate::Hz48000);
+        assert_eq!(
+            session.machine_profile(MACHINE).audio_host.as_deref(),
+            Some("ALSA")
+        );
+    }
+
+    #[test]
+    fn report_lists_every_setting_with_its_layer() {
+        let (config, keys) = file_config();
+        let effective = resolve(
+            config,
+            &keys,
+            MACHINE,
+            env(&[("TROUBADOUR_PRESET_DIR", "/tmp/p")]),
+            &cli(&[("--input-device", "")]),
+        )
+        .unwrap();
+        let report = effective.to_string();
+
+        assert_eq!(report.lines().count(), SETTINGS.len());
+        assert!(report.contains("audio.sample_rate = 44100  [config file]"));
+        assert!(report.contains("preset_dir = /tmp/p  [environment]"));
+        assert!(report.contains("profiles.desk.input_device = (none)  [command line]"));
+        assert!(report.contains("audio.preroll_blocks = 4  [default]"));
+    }
+}
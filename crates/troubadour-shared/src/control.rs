@@ -0,0 +1,423 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+use crate::config::{config_dir, write_atomic};
+use crate::error::{TroubadourError, TroubadourResult};
+use crate::messages::Command;
+use crate::preset::validate_preset_name;
+
+/// Contrôle physique ou réseau qui envoie une valeur.
+///
+/// En TOML : `source = { midi_cc = { channel = 1, cc = 7 } }`
+/// ou `source = { osc = "/troubadour/mic/volume" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlSource {
+    /// Control Change MIDI : canal 1 → 16 (numérotation des
+    /// contrôleurs, pas celle du protocole), numéro 0 → 127
+    MidiCc { channel: u8, cc: u8 },
+    /// Adresse OSC, commençant par `/`
+    Osc(String),
+}
+
+impl std::fmt::Display for ControlSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MidiCc { channel, cc } => write!(f, "MIDI ch {channel} CC {cc}"),
+            Self::Osc(address) => write!(f, "OSC {address}"),
+        }
+    }
+}
+
+/// Réglage du mixer piloté par un contrôle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlParam {
+    /// Fader de volume, selon la courbe configurée
+    Volume,
+    /// Pan : 0 → gauche, 0.5 → centre, 1 → droite
+    Pan,
+    /// Mute tant que la valeur est ≥ 0.5 (bouton enfoncé, interrupteur)
+    Mute,
+    /// Solo, même convention que le mute
+    Solo,
+    /// Applique un routage nommé (`[routing_snapshots]`) quand la valeur
+    /// passe à ≥ 0.5 ; le relâcher ne fait rien, le canal est ignoré.
+    /// En TOML : `param = { routing_snapshot = "BRB" }`
+    RoutingSnapshot(String),
+}
+
+/// Un contrôle relié à un réglage d'un canal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlBinding {
+    pub source: ControlSource,
+    pub channel: ChannelId,
+    pub param: ControlParam,
+}
+
+impl ControlBinding {
+    /// Commande du mixer pour une valeur normalisée (0.0 → 1.0 :
+    /// `valeur / 127` en MIDI, le float reçu en OSC) ; `None` quand la
+    /// valeur ne déclenche rien (bouton de routage relâché).
+    ///
+    /// Un contrôle passe par les mêmes commandes que l'UI : journal,
+    /// courbe de fader et validation restent ceux du moteur.
+    pub fn command(&self, value: f32) -> Option<Command> {
+        let value = value.clamp(0.0, 1.0);
+        let channel = self.channel;
+        let command = match &self.param {
+            ControlParam::Volume => Command::SetVolumeFader {
+                channel,
+                position: value,
+            },
+            ControlParam::Pan => Command::SetPan {
+                channel,
+                pan: value * 2.0 - 1.0,
+            },
+            ControlParam::Mute => Command::SetMute {
+                channel,
+                muted: value >= 0.5,
+            },
+            ControlParam::Solo => Command::SetSolo {
+                channel,
+                solo: value >= 0.5,
+            },
+            ControlParam::RoutingSnapshot(name) if value >= 0.5 => {
+                Command::ApplyRoutingSnapshot { name: name.clone() }
+            }
+            ControlParam::RoutingSnapshot(_) => return None,
+        };
+        Some(command)
+    }
+}
+
+/// Correspondance entre les contrôles d'une surface (MIDI, OSC) et le mixer.
+///
+/// # Un profil par contrôleur
+/// Un nanoKONTROL2 n'envoie pas les mêmes CC qu'une surface générique :
+/// chaque modèle a son profil, un seul est actif à la fois. Changer de
+/// profil remplace toutes les liaisons d'un coup, sans redémarrer.
+///
+/// Chaque profil est un fichier TOML `<dossier>/<nom>.toml` :
+///
+/// ```toml
+/// [[binding]]
+/// source = { midi_cc = { channel = 1, cc = 0 } }
+/// channel = 0
+/// param = "volume"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControlProfile {
+    pub name: String,
+    #[serde(default, rename = "binding")]
+    pub bindings: Vec<ControlBinding>,
+}
+
+/// Canaux couverts par les profils intégrés (8 faders).
+const BUILTIN_STRIPS: usize = 8;
+
+impl ControlProfile {
+    /// Surface générique à 8 faders : un canal MIDI par tranche, avec
+    /// les CC standards du protocole (7 = volume, 10 = pan), et les
+    /// adresses OSC `/troubadour/<n>/<réglage>`.
+    pub fn generic_8_fader() -> Self {
+        let mut bindings = Vec::new();
+        for strip in 0..BUILTIN_STRIPS {
+            let channel = ChannelId(strip);
+            for (cc, param, name) in [
+                (7, ControlParam::Volume, "volume"),
+                (10, ControlParam::Pan, "pan"),
+            ] {
+                bindings.push(ControlBinding {
+                    source: ControlSource::MidiCc {
+                        channel: strip as u8 + 1,
+                        cc,
+                    },
+                    channel,
+                    param: param.clone(),
+                });
+                bindings.push(ControlBinding {
+                    source: ControlSource::Osc(format!("/troubadour/{strip}/{name}")),
+                    channel,
+                    param,
+                });
+            }
+        }
+        Self {
+            name: "Generic 8-fader".to_string(),
+            bindings,
+        }
+    }
+
+    /// Korg nanoKONTROL2 en mode CC d'usine (canal 1) : faders 0-7,
+    /// potards 16-23, boutons S 32-39, M 48-55.
+    pub fn nano_kontrol2() -> Self {
+        let mut bindings = Vec::new();
+        for (first_cc, param) in [
+            (0, ControlParam::Volume),
+            (16, ControlParam::Pan),
+            (32, ControlParam::Solo),
+            (48, ControlParam::Mute),
+        ] {
+            for strip in 0..BUILTIN_STRIPS {
+                bindings.push(ControlBinding {
+                    source: ControlSource::MidiCc {
+                        channel: 1,
+                        cc: first_cc + strip as u8,
+                    },
+                    channel: ChannelId(strip),
+                    param: param.clone(),
+                });
+            }
+        }
+        Self {
+            name: "nanoKONTROL2".to_string(),
+            bindings,
+        }
+    }
+
+    /// Profils intégrés au binaire (exportables pour être modifiés).
+    pub fn builtin_profiles() -> Vec<Self> {
+        vec![Self::generic_8_fader(), Self::nano_kontrol2()]
+    }
+
+    /// Lit et valide un profil TOML. Un réglage inconnu
+    /// (`param = "eq"`) est refusé dès la lecture.
+    pub fn parse(content: &str) -> TroubadourResult<Self> {
+        let profile: Self = toml::from_str(content)
+            .map_err(|e| TroubadourError::InvalidControlProfile(e.to_string()))?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Vérifie les sources : plages MIDI, adresses OSC, et pas deux
+    /// liaisons pour le même contrôle (laquelle gagnerait ?).
+    pub fn validate(&self) -> TroubadourResult<()> {
+        let invalid = |message: String| {
+            Err(TroubadourError::InvalidControlProfile(format!(
+                "{}: {message}",
+                self.name
+            )))
+        };
+        let mut seen = HashSet::new();
+        for binding in &self.bindings {
+            match &binding.source {
+                ControlSource::MidiCc { channel, cc } => {
+                    if !(1..=16).contains(channel) {
+                        return invalid(format!("MIDI channel {channel} is not in 1-16"));
+                    }
+                    if *cc > 127 {
+                        return invalid(format!("MIDI CC {cc} is not in 0-127"));
+                    }
+                }
+                ControlSource::Osc(address) => {
+                    if !address.starts_with('/') || address.contains(char::is_whitespace) {
+                        return invalid(format!("invalid OSC address {address:?}"));
+                    }
+                }
+            }
+            if !seen.insert(&binding.source) {
+                return invalid(format!("{} is bound twice", binding.source));
+            }
+        }
+        Ok(())
+    }
+
+    /// Liaison d'un contrôle, s'il en a une dans ce profil.
+    pub fn binding_for(&self, source: &ControlSource) -> Option<&ControlBinding> {
+        self.bindings.iter().find(|b| &b.source == source)
+    }
+
+    pub fn to_toml(&self) -> TroubadourResult<String> {
+        toml::to_string_pretty(self).map_err(|e| TroubadourError::ConfigError(e.to_string()))
+    }
+}
+
+/// Bibliothèque des profils de surfaces de contrôle.
+///
+/// Les profils intégrés sont dans le code ; un fichier portant le même
+/// nom les remplace (c'est ainsi qu'on personnalise un profil exporté),
+/// un autre nom en ajoute un.
+pub struct ControlProfileManager {
+    dir: PathBuf,
+}
+
+impl ControlProfileManager {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `<config>/profiles/controls/` : à côté des profils du mixer,
+    /// dans un sous-dossier que ceux-ci ne lisent pas.
+    pub fn default_dir() -> PathBuf {
+        config_dir().join("profiles").join("controls")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Noms disponibles : intégrés d'abord, puis ceux du dossier (triés).
+    pub fn list(&self) -> Vec<String> {
+        let mut names: Vec<String> = ControlProfile::builtin_profiles()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        let mut saved: Vec<String> = std::fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|p| p.file_stem()?.to_str().map(str::to_string))
+            .filter(|name| validate_preset_name(name).is_ok())
+            .filter(|name| !names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+            .collect();
+        saved.sort();
+        names.extend(saved);
+        names
+    }
+
+    /// Charge un profil : le fichier s'il existe, sinon l'intégré
+    /// (insensible à la casse). Le nom du fichier fait foi.
+    pub fn load(&self, name: &str) -> TroubadourResult<ControlProfile> {
+        validate_preset_name(name)?;
+        let path = self.dir.join(format!("{name}.toml"));
+        if path.exists() {
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| TroubadourError::ConfigError(format!("{}: {e}", path.display())))?;
+            let mut profile = ControlProfile::parse(&content)?;
+            profile.name = name.to_string();
+            return Ok(profile);
+        }
+        ControlProfile::builtin_profiles()
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+            .ok_or_else(|| TroubadourError::ControlProfileNotFound(name.to_string()))
+    }
+
+    /// Sauve un profil (validé) dans le dossier, sous son nom.
+    pub fn save(&self, profile: &ControlProfile) -> TroubadourResult<()> {
+        validate_preset_name(&profile.name)?;
+        profile.validate()?;
+        let path = self.dir.join(format!("{}.toml", profile.name));
+        write_atomic(&path, profile.to_toml()?.as_bytes())
+            .map_err(|e| TroubadourError::ConfigError(format!("{}: {e}", path.display())))
+    }
+
+    /// Écrit un profil où l'utilisateur le demande, pour le modifier
+    /// puis le remettre dans le dossier des profils.
+    pub fn export(&self, name: &str, path: &Path) -> TroubadourResult<()> {
+        let profile = self.load(name)?;
+        write_atomic(path, profile.to_toml()?.as_bytes())
+            .map_err(|e| TroubadourError::ConfigError(format!("{}: {e}", path.display())))
+    }
+}
+
+impl Default for ControlProfileManager {
+    fn default() -> Self {
+        Self::new(Self::default_dir())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = include_str!("../fixtures/controls/desk.toml");
+    const DUPLICATE: &str = include_str!("../fixtures/controls/duplicate_binding.toml");
+    const UNKNOWN_TARGET: &str = include_str!("../fixtures/controls/unknown_target.toml");
+    const BAD_MIDI: &str = include_str!("../fixtures/controls/bad_midi_channel.toml");
+
+    fn temp_manager(tag: &str) -> (ControlProfileManager, PathBuf) {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-controls-{tag}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        (ControlProfileManager::new(&dir), dir)
+    }
+
+    #[test]
+    fn fixture_profile_parses() {
+        let profile = ControlProfile::parse(VALID).unwrap();
+        assert_eq!(profile.bindings.len(), 4);
+        let fader = ControlSource::MidiCc { channel: 1, cc: 7 };
+        assert!(matches!(
+            profile.binding_for(&fader).unwrap().command(0.5),
+            Some(Command::SetVolumeFader {
+                channel: ChannelId(0),
+                position: 0.5
+            })
+        ));
+        let mute = ControlSource::Osc("/desk/mic/mute".into());
+        assert!(matches!(
+            profile.binding_for(&mute).unwrap().command(1.0),
+            Some(Command::SetMute {
+                channel: ChannelId(0),
+                muted: true
+            })
+        ));
+        let brb = profile
+            .binding_for(&ControlSource::Osc("/desk/brb".into()))
+            .unwrap();
+        assert!(matches!(
+            brb.command(1.0),
+            Some(Command::ApplyRoutingSnapshot { name }) if name == "BRB"
+        ));
+        assert!(brb.command(0.0).is_none());
+    }
+
+    #[test]
+    fn invalid_fixtures_are_rejected() {
+        let error = ControlProfile::parse(DUPLICATE).unwrap_err().to_string();
+        assert!(error.contains("MIDI ch 1 CC 7 is bound twice"), "{error}");
+
+        let error = ControlProfile::parse(UNKNOWN_TARGET)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("unknown variant `eq`"), "{error}");
+
+        let error = ControlProfile::parse(BAD_MIDI).unwrap_err().to_string();
+        assert!(error.contains("MIDI channel 17"), "{error}");
+    }
+
+    #[test]
+    fn builtins_are_valid_and_round_trip() {
+        for profile in ControlProfile::builtin_profiles() {
+            profile.validate().unwrap();
+            let parsed = ControlProfile::parse(&profile.to_toml().unwrap()).unwrap();
+            assert_eq!(parsed, profile);
+        }
+        assert_eq!(ControlProfile::nano_kontrol2().bindings.len(), 32);
+    }
+
+    #[test]
+    fn exported_builtin_can_be_customized() {
+        let (manager, dir) = temp_manager("export");
+        assert_eq!(manager.list(), ["Generic 8-fader", "nanoKONTROL2"]);
+        assert!(matches!(
+            manager.load("Missing"),
+            Err(TroubadourError::ControlProfileNotFound(_))
+        ));
+
+        std::fs::create_dir_all(&dir).unwrap();
+        let exported = dir.join("nanoKONTROL2.toml");
+        manager.export("nanokontrol2", &exported).unwrap();
+        // Le fichier exporté remplace l'intégré du même nom
+        let mut profile = manager.load("nanoKONTROL2").unwrap();
+        assert_eq!(profile, ControlProfile::nano_kontrol2());
+        profile.bindings.truncate(8);
+        profile.name = "Faders only".into();
+        manager.save(&profile).unwrap();
+
+        assert_eq!(
+            manager.list(),
+            ["Generic 8-fader", "nanoKONTROL2", "Faders only"]
+        );
+        assert_eq!(manager.load("Faders only").unwrap().bindings.len(), 8);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
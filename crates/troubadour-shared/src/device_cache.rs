@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::{BufferSize, SampleRate};
+use crate::config::{config_dir, write_atomic};
+
+/// Réglages de stream appris, device par device (`device_cache.toml`).
+///
+/// Certains devices n'ouvrent de façon fiable qu'à un sample rate ou une
+/// taille de buffer précis. Plutôt que de le redécouvrir à chaque
+/// session, le moteur note ce qui a marché (et ce qui a échoué, avec
+/// l'erreur) à chaque ouverture de stream.
+///
+/// # Ordre de préférence
+/// ```text
+///  réglage explicite de l'utilisateur > cache du device > défaut du moteur
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCache {
+    /// Devices d'entrée, par nom
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub inputs: BTreeMap<String, DeviceCacheEntry>,
+    /// Devices de sortie, par nom
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub outputs: BTreeMap<String, DeviceCacheEntry>,
+}
+
+/// Ce qu'on sait d'un device.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceCacheEntry {
+    /// Derniers réglages qui ont ouvert le stream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<StreamSettings>,
+    /// Échecs, un par sample rate (le plus récent gagne)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<StreamFailure>,
+}
+
+/// Réglages d'un stream ouvert.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamSettings {
+    pub sample_rate: u32,
+    /// Frames par callback (`None` : taille choisie par le driver)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub buffer_frames: Option<u32>,
+    pub channels: u16,
+    /// Format des samples ("F32", "I16"...)
+    pub format: String,
+}
+
+/// Ouverture ratée : "ce device a échoué à 96 kHz la dernière fois".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamFailure {
+    pub sample_rate: u32,
+    pub error: String,
+}
+
+impl DeviceCache {
+    /// Emplacement par défaut : `<config>/device_cache.toml`.
+    pub fn default_path() -> PathBuf {
+        config_dir().join("device_cache.toml")
+    }
+
+    /// Charge le cache ; fichier absent = cache vide.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Écrit le cache (écriture atomique : jamais de fichier tronqué).
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let content = toml::to_string(self)?;
+        write_atomic(path, content.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn entry(&self, device: &str, is_input: bool) -> Option<&DeviceCacheEntry> {
+        self.devices(is_input).get(device)
+    }
+
+    fn devices(&self, is_input: bool) -> &BTreeMap<String, DeviceCacheEntry> {
+        if is_input {
+            &self.inputs
+        } else {
+            &self.outputs
+        }
+    }
+
+    fn entry_mut(&mut self, device: &str, is_input: bool) -> &mut DeviceCacheEntry {
+        let devices = if is_input {
+            &mut self.inputs
+        } else {
+            &mut self.outputs
+        };
+        devices.entry(device.to_string()).or_default()
+    }
+
+    /// Note une ouverture réussie ; l'échec éventuel à ce rate est oublié.
+    pub fn record_success(&mut self, device: &str, is_input: bool, settings: StreamSettings) {
+        let entry = self.entry_mut(device, is_input);
+        entry
+            .failures
+            .retain(|f| f.sample_rate != settings.sample_rate);
+        entry.last_success = Some(settings);
+    }
+
+    /// Note une ouverture ratée à `sample_rate`.
+    pub fn record_failure(&mut self, device: &str, is_input: bool, sample_rate: u32, error: &str) {
+        let entry = self.entry_mut(device, is_input);
+        entry.failures.retain(|f| f.sample_rate != sample_rate);
+        entry.failures.push(StreamFailure {
+            sample_rate,
+            error: error.to_string(),
+        });
+    }
+
+    /// Sample rate à demander au device.
+    ///
+    /// Le réglage explicite gagne toujours. Sinon le dernier rate qui a
+    /// marché, s'il est l'un de ceux du moteur (le pont resample ce qui
+    /// diffère de l'autre côté) ; sinon le défaut du moteur.
+    pub fn preferred_rate(
+        &self,
+        device: &str,
+        is_input: bool,
+        explicit: Option<SampleRate>,
+        engine_default: SampleRate,
+    ) -> u32 {
+        if let Some(rate) = explicit {
+            return rate.as_hz();
+        }
+        self.entry(device, is_input)
+            .and_then(|e| e.last_success.as_ref())
+            .map(|s| s.sample_rate)
+            .filter(|&hz| SampleRate::ALL.iter().any(|r| r.as_hz() == hz))
+            .unwrap_or(engine_default.as_hz())
+    }
+
+    /// Taille de buffer (en frames) à demander au device, dans le même
+    /// ordre que le sample rate : réglage explicite, sinon la taille du
+    /// dernier stream ouvert à ce réglage fixe, sinon le défaut du moteur.
+    pub fn preferred_buffer_frames(
+        &self,
+        device: &str,
+        is_input: bool,
+        explicit: Option<BufferSize>,
+        engine_default: BufferSize,
+    ) -> u32 {
+        if let Some(size) = explicit {
+            return size.as_frames();
+        }
+        self.entry(device, is_input)
+            .and_then(|e| e.last_success.as_ref())
+            .and_then(|s| s.buffer_frames)
+            .filter(|&frames| frames > 0)
+            .unwrap_or(engine_default.as_frames())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(sample_rate: u32) -> StreamSettings {
+        StreamSettings {
+            sample_rate,
+            buffer_frames: Some(256),
+            channels: 2,
+            format: "F32".into(),
+        }
+    }
+
+    #[test]
+    fn explicit_setting_beats_cache_which_beats_engine_default() {
+        let mut cache = DeviceCache::default();
+        let default = SampleRate::Hz48000;
+        assert_eq!(cache.preferred_rate("Yeti", true, None, default), 48_000);
+
+        cache.record_success("Yeti", true, settings(44_100));
+        assert_eq!(cache.preferred_rate("Yeti", true, None, default), 44_100);
+        assert_eq!(
+            cache.preferred_rate("Yeti", true, Some(SampleRate::Hz96000), default),
+            96_000
+        );
+        // Même nom, autre sens : rien d'appris
+        assert_eq!(cache.preferred_rate("Yeti", false, None, default), 48_000);
+
+        // Un rate que le moteur ne sait pas faire n'est pas repris
+        cache.record_success("Yeti", true, settings(22_050));
+        assert_eq!(cache.preferred_rate("Yeti", true, None, default), 48_000);
+    }
+
+    #[test]
+    fn buffer_size_follows_the_same_preference_order() {
+        let mut cache = DeviceCache::default();
+        let default = BufferSize::Samples256;
+        assert_eq!(
+            cache.preferred_buffer_frames("Yeti", true, None, default),
+            256
+        );
+
+        let mut learned = settings(48_000);
+        learned.buffer_frames = Some(512);
+        cache.record_success("Yeti", true, learned);
+        assert_eq!(
+            cache.preferred_buffer_frames("Yeti", true, None, default),
+            512
+        );
+        assert_eq!(
+            cache.preferred_buffer_frames("Yeti", true, Some(BufferSize::Samples64), default),
+            64
+        );
+
+        // Ouvert à la taille du driver : rien à reprendre
+        let mut driver = settings(48_000);
+        driver.buffer_frames = None;
+        cache.record_success("Yeti", true, driver);
+        assert_eq!(
+            cache.preferred_buffer_frames("Yeti", true, None, default),
+            256
+        );
+    }
+
+    #[test]
+    fn failures_are_kept_per_rate_until_a_success() {
+        let mut cache = DeviceCache::default();
+        cache.record_failure("Interface", false, 96_000, "unsupported");
+        cache.record_failure("Interface", false, 96_000, "device busy");
+        cache.record_failure("Interface", false, 44_100, "unsupported");
+        let entry = cache.entry("Interface", false).unwrap();
+        assert_eq!(entry.failures.len(), 2);
+        assert_eq!(entry.failures[0].error, "device busy");
+
+        cache.record_success("Interface", false, settings(96_000));
+        let entry = cache.entry("Interface", false).unwrap();
+        assert_eq!(entry.failures.len(), 1);
+        assert_eq!(entry.failures[0].sample_rate, 44_100);
+    }
+
+    #[test]
+    fn roundtrip_through_file() {
+        let dir = std::env::temp_dir().join(format!("troubadour-dcache-{}", std::process::id()));
+        let path = dir.join("device_cache.toml");
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(DeviceCache::load(&path).unwrap(), DeviceCache::default());
+
+        let mut cache = DeviceCache::default();
+        cache.record_success("Scarlett 2i2", true, settings(48_000));
+        cache.record_failure("HD 600", false, 192_000, "unsupported");
+        cache.save(&path).unwrap();
+        assert_eq!(DeviceCache::load(&path).unwrap(), cache);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::DeviceInfo;
+
+/// Résultat d'une sonde de diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeStatus {
+    Ok,
+    /// Fonctionne, mais quelque chose mérite l'attention (underruns...)
+    Warning,
+    Failed,
+    /// La sonde n'a pas répondu à temps (driver bloqué ?)
+    TimedOut,
+    /// Rien à tester (aucun device configuré, pas de fichier de config...)
+    Skipped,
+}
+
+/// Une vérification du diagnostic ("open_input:Blue Yeti", "config_parse"...).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub name: String,
+    pub status: ProbeStatus,
+    /// Message lisible (erreur, détail...)
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// Rapport complet du diagnostic "pas de son".
+///
+/// Tout ce qu'un support demande d'habitude, en une fois :
+/// devices vus par le système, streams ouvrables, config lisible,
+/// dossier de presets accessible, état du moteur.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DiagnosticsReport {
+    /// Horodatage en millisecondes depuis l'epoch Unix.
+    pub generated_at_ms: u64,
+    /// Version de Troubadour
+    pub version: String,
+    pub input_devices: Vec<DeviceInfo>,
+    pub output_devices: Vec<DeviceInfo>,
+    /// Devices référencés par des canaux mais absents du système
+    pub missing_devices: Vec<String>,
+    pub probes: Vec<ProbeResult>,
+}
+
+impl DiagnosticsReport {
+    /// Retourne la sonde portant ce nom.
+    pub fn probe(&self, name: &str) -> Option<&ProbeResult> {
+        self.probes.iter().find(|p| p.name == name)
+    }
+
+    /// `true` si aucune sonde n'a échoué ni expiré.
+    pub fn is_healthy(&self) -> bool {
+        self.probes
+            .iter()
+            .all(|p| !matches!(p.status, ProbeStatus::Failed | ProbeStatus::TimedOut))
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Écrit le rapport en JSON (à joindre à un ticket de support).
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(name: &str, status: ProbeStatus) -> ProbeResult {
+        ProbeResult {
+            name: name.into(),
+            status,
+            detail: String::new(),
+            duration_ms: 0,
+        }
+    }
+
+    #[test]
+    fn health_ignores_warnings_and_skips() {
+        let mut report = DiagnosticsReport {
+            probes: vec![
+                probe("a", ProbeStatus::Ok),
+                probe("b", ProbeStatus::Warning),
+                probe("c", ProbeStatus::Skipped),
+            ],
+            ..Default::default()
+        };
+        assert!(report.is_healthy());
+        report.probes.push(probe("d", ProbeStatus::TimedOut));
+        assert!(!report.is_healthy());
+        assert_eq!(report.probe("d").unwrap().status, ProbeStatus::TimedOut);
+    }
+
+    #[test]
+    fn json_roundtrip() {
+        let report = DiagnosticsReport {
+            version: "0.4.0".into(),
+            missing_devices: vec!["Old Headset".into()],
+            probes: vec![probe("config_parse", ProbeStatus::Failed)],
+            ..Default::default()
+        };
+        let json = report.to_json().unwrap();
+        assert!(json.contains("\"failed\""));
+        let parsed: DiagnosticsReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+}
@@ -0,0 +1,578 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+use crate::mixer::{ChannelConfig, ChannelKind, InputFallback, MixerConfig, Route};
+
+/// Réglage d'un canal comparé par `MixerConfig::diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelField {
+    Name,
+    Volume,
+    Muted,
+    Solo,
+    Pan,
+    DelayMs,
+    /// Device physique (affectation)
+    DeviceName,
+    /// Tranche du device : offset et nombre de canaux
+    DeviceChannels,
+    SignalPresence,
+    MonitorBus,
+    MonitorLevelDb,
+    /// Canaux d'un bus (mono / stéréo)
+    ChannelCount,
+    PhaseInvert,
+    DcBlock,
+    /// Tranche d'un bus dans les canaux de son device de sortie
+    OutputChannelOffset,
+    TruePeakMeter,
+    /// Repli d'une entrée quand son device manque
+    InputFallback,
+    OutputDelayMs,
+    /// Canaux sommés en premier par un bus
+    SumPriority,
+    /// Clé du nom d'usine (nom traduit tant qu'il n'est pas renommé)
+    DisplayKey,
+}
+
+impl ChannelField {
+    pub const ALL: [Self; 20] = [
+        Self::Name,
+        Self::Volume,
+        Self::Muted,
+        Self::Solo,
+        Self::Pan,
+        Self::DelayMs,
+        Self::DeviceName,
+        Self::DeviceChannels,
+        Self::SignalPresence,
+        Self::MonitorBus,
+        Self::MonitorLevelDb,
+        Self::ChannelCount,
+        Self::PhaseInvert,
+        Self::DcBlock,
+        Self::OutputChannelOffset,
+        Self::TruePeakMeter,
+        Self::InputFallback,
+        Self::OutputDelayMs,
+        Self::SumPriority,
+        Self::DisplayKey,
+    ];
+
+    /// Valeur lisible du champ (`none` pour une option vide).
+    ///
+    /// Les flottants passent par `Display` : la plus courte représentation
+    /// exacte (`0.5`, `1`), donc deux valeurs égales s'affichent pareil.
+    pub fn value(self, channel: &ChannelConfig) -> String {
+        fn optional<T: ToString>(value: Option<T>) -> String {
+            value.map_or_else(|| "none".to_string(), |v| v.to_string())
+        }
+        match self {
+            Self::Name => channel.name.clone(),
+            Self::Volume => channel.volume.to_string(),
+            Self::Muted => channel.muted.to_string(),
+            Self::Solo => channel.solo.to_string(),
+            Self::Pan => channel.pan.to_string(),
+            Self::DelayMs => channel.delay_ms.to_string(),
+            Self::DeviceName => channel.device_name.to_string(),
+            Self::DeviceChannels => format!(
+                "{}+{}",
+                channel.device_channel_offset, channel.device_channel_count
+            ),
+            Self::SignalPresence => optional(
+                channel
+                    .signal_presence
+                    .map(|p| format!("{} dB / {} s", p.threshold_db, p.silence_seconds)),
+            ),
+            Self::MonitorBus => optional(channel.monitor_bus.map(|b| b.0)),
+            Self::MonitorLevelDb => channel.monitor_level_db.to_string(),
+            Self::ChannelCount => channel.channel_count.to_string(),
+            Self::PhaseInvert => channel.phase_invert.to_string(),
+            Self::DcBlock => channel.dc_block.to_string(),
+            Self::OutputChannelOffset => optional(channel.output_channel_offset),
+            Self::TruePeakMeter => channel.true_peak_meter.to_string(),
+            Self::InputFallback => match &channel.input_fallback {
+                InputFallback::SystemDefault => "system_default".to_string(),
+                InputFallback::Silence => "silence".to_string(),
+                InputFallback::Devices(devices) => format!("devices: {}", devices.join(", ")),
+                InputFallback::File(path) => format!("file: {}", path.display()),
+            },
+            Self::OutputDelayMs => channel.output_delay_ms.to_string(),
+            Self::SumPriority => channel
+                .sum_priority
+                .iter()
+                .map(|id| id.0.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            Self::DisplayKey => optional(channel.display_key.as_deref()),
+        }
+    }
+
+    /// Copie ce champ de `from` vers `to`.
+    pub fn copy(self, from: &ChannelConfig, to: &mut ChannelConfig) {
+        match self {
+            Self::Name => to.name = from.name.clone(),
+            Self::Volume => to.volume = from.volume,
+            Self::Muted => to.muted = from.muted,
+            Self::Solo => to.solo = from.solo,
+            Self::Pan => to.pan = from.pan,
+            Self::DelayMs => to.delay_ms = from.delay_ms,
+            Self::DeviceName => to.device_name = from.device_name.clone(),
+            Self::DeviceChannels => {
+                to.device_channel_offset = from.device_channel_offset;
+                to.device_channel_count = from.device_channel_count;
+            }
+            Self::SignalPresence => to.signal_presence = from.signal_presence,
+            Self::MonitorBus => to.monitor_bus = from.monitor_bus,
+            Self::MonitorLevelDb => to.monitor_level_db = from.monitor_level_db,
+            Self::ChannelCount => to.channel_count = from.channel_count,
+            Self::PhaseInvert => to.phase_invert = from.phase_invert,
+            Self::DcBlock => to.dc_block = from.dc_block,
+            Self::OutputChannelOffset => to.output_channel_offset = from.output_channel_offset,
+            Self::TruePeakMeter => to.true_peak_meter = from.true_peak_meter,
+            Self::InputFallback => to.input_fallback = from.input_fallback.clone(),
+            Self::OutputDelayMs => to.output_delay_ms = from.output_delay_ms,
+            Self::SumPriority => to.sum_priority = from.sum_priority.clone(),
+            Self::DisplayKey => to.display_key = from.display_key.clone(),
+        }
+    }
+
+    fn differs(self, a: &ChannelConfig, b: &ChannelConfig) -> bool {
+        match self {
+            Self::Name => a.name != b.name,
+            Self::Volume => a.volume != b.volume,
+            Self::Muted => a.muted != b.muted,
+            Self::Solo => a.solo != b.solo,
+            Self::Pan => a.pan != b.pan,
+            Self::DelayMs => a.delay_ms != b.delay_ms,
+            Self::DeviceName => a.device_name != b.device_name,
+            Self::DeviceChannels => {
+                (a.device_channel_offset, a.device_channel_count)
+                    != (b.device_channel_offset, b.device_channel_count)
+            }
+            Self::SignalPresence => a.signal_presence != b.signal_presence,
+            Self::MonitorBus => a.monitor_bus != b.monitor_bus,
+            Self::MonitorLevelDb => a.monitor_level_db != b.monitor_level_db,
+            Self::ChannelCount => a.channel_count != b.channel_count,
+            Self::PhaseInvert => a.phase_invert != b.phase_invert,
+            Self::DcBlock => a.dc_block != b.dc_block,
+            Self::OutputChannelOffset => a.output_channel_offset != b.output_channel_offset,
+            Self::TruePeakMeter => a.true_peak_meter != b.true_peak_meter,
+            Self::InputFallback => a.input_fallback != b.input_fallback,
+            Self::OutputDelayMs => a.output_delay_ms != b.output_delay_ms,
+            Self::SumPriority => a.sum_priority != b.sum_priority,
+            Self::DisplayKey => a.display_key != b.display_key,
+        }
+    }
+}
+
+/// Une différence entre deux configs de mixer.
+///
+/// Chaque entrée se suffit à elle-même : l'UI peut en cocher une
+/// partie et ne renvoyer que celles-là (`apply_entries`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum DiffEntry {
+    /// Canal (entrée ou bus) présent seulement dans la nouvelle config
+    ChannelAdded {
+        channel: ChannelConfig,
+    },
+    /// Canal présent seulement dans la config actuelle
+    ChannelRemoved {
+        channel: ChannelConfig,
+    },
+    /// Réglage d'un canal présent des deux côtés
+    FieldChanged {
+        channel: ChannelId,
+        field: ChannelField,
+        before: String,
+        after: String,
+    },
+    RouteAdded {
+        route: Route,
+    },
+    RouteRemoved {
+        route: Route,
+    },
+}
+
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = |channel: &ChannelConfig| match channel.kind {
+            ChannelKind::Input => "channel",
+            ChannelKind::Output => "bus",
+            ChannelKind::Submix => "submix",
+        };
+        match self {
+            Self::ChannelAdded { channel } => {
+                write!(
+                    f,
+                    "+ {} {} \"{}\"",
+                    kind(channel),
+                    channel.id.0,
+                    channel.name
+                )
+            }
+            Self::ChannelRemoved { channel } => {
+                write!(
+                    f,
+                    "- {} {} \"{}\"",
+                    kind(channel),
+                    channel.id.0,
+                    channel.name
+                )
+            }
+            Self::FieldChanged {
+                channel,
+                field,
+                before,
+                after,
+            } => write!(f, "~ {} {field:?}: {before} -> {after}", channel.0),
+            Self::RouteAdded { route } => write!(f, "+ route {} -> {}", route.from.0, route.to.0),
+            Self::RouteRemoved { route } => {
+                write!(f, "- route {} -> {}", route.from.0, route.to.0)
+            }
+        }
+    }
+}
+
+/// Ce qui change en passant d'une config de mixer à une autre.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigDiff {
+    pub entries: Vec<DiffEntry>,
+}
+
+impl ConfigDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl MixerConfig {
+    /// Différences pour passer de `self` (config actuelle) à `other`.
+    ///
+    /// Ordre stable : canaux retirés, ajoutés puis modifiés (par id),
+    /// puis routes retirées et ajoutées. Le type d'un canal (entrée ou
+    /// bus) n'est pas un champ : s'il change, le canal est retiré puis
+    /// ajouté.
+    pub fn diff(&self, other: &MixerConfig) -> ConfigDiff {
+        let mut entries = Vec::new();
+        let sorted = |config: &MixerConfig| {
+            let mut channels: Vec<ChannelConfig> = config.channels.clone();
+            channels.sort_by_key(|c| c.id.0);
+            channels
+        };
+        let (before, after) = (sorted(self), sorted(other));
+        let same = |a: &ChannelConfig, b: &ChannelConfig| a.id == b.id && a.kind == b.kind;
+
+        for channel in &before {
+            if !after.iter().any(|c| same(c, channel)) {
+                entries.push(DiffEntry::ChannelRemoved {
+                    channel: channel.clone(),
+                });
+            }
+        }
+        for channel in &after {
+            if !before.iter().any(|c| same(c, channel)) {
+                entries.push(DiffEntry::ChannelAdded {
+                    channel: channel.clone(),
+                });
+            }
+        }
+        for old in &before {
+            let Some(new) = after.iter().find(|c| same(c, old)) else {
+                continue;
+            };
+            for field in ChannelField::ALL {
+                if field.differs(old, new) {
+                    entries.push(DiffEntry::FieldChanged {
+                        channel: old.id,
+                        field,
+                        before: field.value(old),
+                        after: field.value(new),
+                    });
+                }
+            }
+        }
+
+        for route in &self.routes {
+            if !other.routes.contains(route) {
+                entries.push(DiffEntry::RouteRemoved { route: *route });
+            }
+        }
+        for route in &other.routes {
+            if !self.routes.contains(route) {
+                entries.push(DiffEntry::RouteAdded { route: *route });
+            }
+        }
+        ConfigDiff { entries }
+    }
+
+    /// Applique une sélection d'entrées de `self.diff(target)`.
+    ///
+    /// Les entrées qui ne figurent plus dans ce diff (config modifiée
+    /// depuis l'affichage, sélection périmée) sont ignorées. Les
+    /// valeurs viennent de `target`, pas du texte des entrées. Une route
+    /// vers un canal absent est ignorée elle aussi. Retourne le nombre
+    /// d'entrées appliquées.
+    pub fn apply_entries(&mut self, target: &MixerConfig, selection: &[DiffEntry]) -> usize {
+        let current = self.diff(target);
+        let mut applied = 0;
+        for entry in current.entries.iter().filter(|e| selection.contains(e)) {
+            let done = match entry {
+                DiffEntry::ChannelRemoved { channel } => {
+                    self.channels.retain(|c| c.id != channel.id);
+                    self.routes
+                        .retain(|r| r.from != channel.id && r.to != channel.id);
+                    true
+                }
+                DiffEntry::ChannelAdded { channel } => {
+                    let free = self.channel(channel.id).is_none();
+                    if free {
+                        self.channels.push(channel.clone());
+                    }
+                    free
+                }
+                DiffEntry::FieldChanged { channel, field, .. } => {
+                    match (target.channel(*channel), self.channel_mut(*channel)) {
+                        (Some(from), Some(to)) => {
+                            field.copy(from, to);
+                            true
+                        }
+                        _ => false,
+                    }
+                }
+                DiffEntry::RouteRemoved { route } => {
+                    self.remove_route(route.from, route.to);
+                    true
+                }
+                DiffEntry::RouteAdded { route } => {
+                    // Une route vers un canal non retenu n'aurait pas de sens
+                    let linked =
+                        self.channel(route.from).is_some() && self.channel(route.to).is_some();
+                    if linked {
+                        self.add_route(route.from, route.to);
+                    }
+                    linked
+                }
+            };
+            applied += usize::from(done);
+        }
+        applied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mixer::DeviceAssignment;
+
+    /// Clés sérialisées couvertes par chaque champ comparé.
+    fn keys(field: ChannelField) -> &'static [&'static str] {
+        match field {
+            ChannelField::Name => &["name"],
+            ChannelField::Volume => &["volume"],
+            ChannelField::Muted => &["muted"],
+            ChannelField::Solo => &["solo"],
+            ChannelField::Pan => &["pan"],
+            ChannelField::DelayMs => &["delay_ms"],
+            ChannelField::DeviceName => &["device_name"],
+            ChannelField::DeviceChannels => &["device_channel_offset", "device_channel_count"],
+            ChannelField::SignalPresence => &["signal_presence"],
+            ChannelField::MonitorBus => &["monitor_bus"],
+            ChannelField::MonitorLevelDb => &["monitor_level_db"],
+            ChannelField::ChannelCount => &["channel_count"],
+            ChannelField::PhaseInvert => &["phase_invert"],
+            ChannelField::DcBlock => &["dc_block"],
+            ChannelField::OutputChannelOffset => &["output_channel_offset"],
+            ChannelField::TruePeakMeter => &["true_peak_meter"],
+            ChannelField::InputFallback => &["input_fallback"],
+            ChannelField::OutputDelayMs => &["output_delay_ms"],
+            ChannelField::SumPriority => &["sum_priority"],
+            ChannelField::DisplayKey => &["display_key"],
+        }
+    }
+
+    #[test]
+    fn every_persisted_channel_setting_is_compared() {
+        // Toutes les options remplies : rien n'est sauté à la sérialisation
+        let mut channel = ChannelConfig::output(3, "A1");
+        channel.display_key = Some("bus.a1".into());
+        channel.device_name = DeviceAssignment::Specific("HD 600".into());
+        channel.signal_presence = Some(Default::default());
+        channel.monitor_bus = Some(ChannelId(4));
+        channel.output_channel_offset = Some(2);
+        channel.input_fallback = InputFallback::Silence;
+        channel.sum_priority = vec![ChannelId(0)];
+
+        let serde_json::Value::Object(map) = serde_json::to_value(&channel).unwrap() else {
+            panic!("a channel serializes to an object");
+        };
+        // Identité du canal, pas un réglage : un autre id ou type est un
+        // autre canal pour le diff
+        let compared: Vec<&str> = ChannelField::ALL
+            .into_iter()
+            .flat_map(keys)
+            .copied()
+            .chain(["id", "kind"])
+            .collect();
+        for key in map.keys() {
+            assert!(
+                compared.contains(&key.as_str()),
+                "ChannelConfig::{key} is persisted but missing from ChannelField::ALL"
+            );
+        }
+    }
+
+    #[test]
+    fn later_channel_settings_are_diffed_and_applied() {
+        let mut current = MixerConfig::default_setup();
+        let mut preset = current.clone();
+        preset.channels[0].phase_invert = true;
+        preset.channels[3].output_delay_ms = 120.0;
+        preset.channels[3].sum_priority = vec![ChannelId(1), ChannelId(0)];
+
+        let diff = current.diff(&preset);
+        let fields: Vec<ChannelField> = diff
+            .entries
+            .iter()
+            .filter_map(|e| match e {
+                DiffEntry::FieldChanged { field, .. } => Some(*field),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ChannelField::PhaseInvert,
+                ChannelField::OutputDelayMs,
+                ChannelField::SumPriority,
+            ]
+        );
+        assert_eq!(current.apply_entries(&preset, &diff.entries), 3);
+        assert!(current.diff(&preset).is_empty());
+    }
+
+    #[test]
+    fn diff_against_itself_is_empty() {
+        let config = MixerConfig::default_setup();
+        assert!(config.diff(&config.clone()).is_empty());
+    }
+
+    #[test]
+    fn volume_change_and_removed_channel_reported_once() {
+        let current = MixerConfig::default_setup();
+        let mut preset = current.clone();
+        preset.channels[0].volume = 0.5;
+        preset.channels.retain(|c| c.id != ChannelId(2));
+        preset.routes.retain(|r| r.from != ChannelId(2));
+
+        let diff = current.diff(&preset);
+        let volume: Vec<&DiffEntry> = diff
+            .entries
+            .iter()
+            .filter(|e| matches!(e, DiffEntry::FieldChanged { .. }))
+            .collect();
+        assert_eq!(
+            volume,
+            [&DiffEntry::FieldChanged {
+                channel: ChannelId(0),
+                field: ChannelField::Volume,
+                before: "1".into(),
+                after: "0.5".into(),
+            }]
+        );
+        let removed: Vec<&DiffEntry> = diff
+            .entries
+            .iter()
+            .filter(|e| matches!(e, DiffEntry::ChannelRemoved { .. }))
+            .collect();
+        assert_eq!(removed.len(), 1);
+        assert!(
+            matches!(removed[0], DiffEntry::ChannelRemoved { channel } if channel.name == "Browser")
+        );
+        // Sa route disparaît avec lui ; rien d'autre ne change
+        assert_eq!(diff.len(), 3);
+        assert!(diff.entries.contains(&DiffEntry::RouteRemoved {
+            route: Route::new(ChannelId(2), ChannelId(3)),
+        }));
+    }
+
+    #[test]
+    fn added_bus_and_device_assignment_are_reported() {
+        let current = MixerConfig::default_setup();
+        let mut preset = current.clone();
+        preset.channels.push(ChannelConfig::output(5, "Stream"));
+        preset.add_route(ChannelId(0), ChannelId(5));
+        preset.channels[3].device_name = DeviceAssignment::Specific("HD 600".into());
+
+        let lines: Vec<String> = current
+            .diff(&preset)
+            .entries
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "+ bus 5 \"Stream\"",
+                "~ 3 DeviceName: none -> HD 600",
+                "+ route 0 -> 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn apply_entries_applies_only_the_selection() {
+        let mut current = MixerConfig::default_setup();
+        let mut preset = current.clone();
+        preset.channels[0].volume = 0.5;
+        preset.channels[1].muted = true;
+        preset.channels.push(ChannelConfig::output(5, "Stream"));
+        preset.add_route(ChannelId(0), ChannelId(5));
+
+        let diff = current.diff(&preset);
+        let volume = diff
+            .entries
+            .iter()
+            .find(|e| {
+                matches!(
+                    e,
+                    DiffEntry::FieldChanged {
+                        field: ChannelField::Volume,
+                        ..
+                    }
+                )
+            })
+            .unwrap()
+            .clone();
+        // La route seule : son bus n'est pas sélectionné, elle est ignorée
+        let route = DiffEntry::RouteAdded {
+            route: Route::new(ChannelId(0), ChannelId(5)),
+        };
+        let stale = DiffEntry::FieldChanged {
+            channel: ChannelId(1),
+            field: ChannelField::Pan,
+            before: "0".into(),
+            after: "1".into(),
+        };
+
+        assert_eq!(current.apply_entries(&preset, &[volume, route, stale]), 1);
+        assert_eq!(current.channels[0].volume, 0.5);
+        assert!(!current.channels[1].muted);
+        assert!(current.channel(ChannelId(5)).is_none());
+        assert!(!current.has_route(ChannelId(0), ChannelId(5)));
+
+        // Tout appliquer → plus aucune différence
+        let rest = current.diff(&preset).entries;
+        current.apply_entries(&preset, &rest);
+        assert!(current.diff(&preset).is_empty());
+    }
+}
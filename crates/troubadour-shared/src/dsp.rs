@@ -1,5 +1,159 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
+use crate::audio::ChannelId;
+
+/// Plage autorisée d'un paramètre d'effet, bornes incluses.
+///
+/// Ce sont les bornes que les processeurs du core appliquent eux-mêmes
+/// (`Compressor::set_attack`...) : une valeur hors plage n'y est jamais
+/// jouée telle quelle, elle est ramenée à la borne la plus proche.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ParamRange {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl ParamRange {
+    pub const fn new(min: f32, max: f32) -> Self {
+        Self { min, max }
+    }
+
+    /// `NaN` n'est dans aucune plage.
+    pub fn contains(&self, value: f32) -> bool {
+        (self.min..=self.max).contains(&value)
+    }
+
+    /// Ramène `value` dans la plage (`NaN` → `min`).
+    pub fn clamp(&self, value: f32) -> f32 {
+        if value.is_nan() {
+            self.min
+        } else {
+            value.clamp(self.min, self.max)
+        }
+    }
+}
+
+/// Un paramètre hors de sa plage : quel champ, quelle valeur demandée,
+/// quelle plage était permise. L'UI s'en sert pour surligner le
+/// contrôle fautif.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParamViolation {
+    /// Nom du champ : `attack` dans une config, `compressor.attack` ou
+    /// `eq.band1_q` dans un preset complet
+    pub field: String,
+    pub value: f32,
+    pub range: ParamRange,
+}
+
+impl fmt::Display for ParamViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} = {} (allowed {} to {})",
+            self.field, self.value, self.range.min, self.range.max
+        )
+    }
+}
+
+/// Que faire d'un paramètre hors plage ?
+///
+/// # Indulgent pour les fichiers, strict pour les commandes
+/// Un preset ou un profil édité à la main avec une valeur folle doit
+/// quand même se charger : `Clamp`, la valeur est ramenée à la borne.
+/// Une commande de l'UI, elle, est refusée en entier (`Reject`) avec la
+/// liste des champs fautifs : l'utilisateur voit ce qui n'a pas été
+/// appliqué au lieu d'un réglage discrètement différent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParamPolicy {
+    #[default]
+    Clamp,
+    Reject,
+}
+
+/// Paramètres continus d'une config, avec leur plage.
+type RangedParams<'a> = Vec<(String, &'a mut f32, ParamRange)>;
+
+fn violations(params: RangedParams<'_>) -> Vec<ParamViolation> {
+    params
+        .into_iter()
+        .filter(|(_, value, range)| !range.contains(**value))
+        .map(|(field, value, range)| ParamViolation {
+            field,
+            value: *value,
+            range,
+        })
+        .collect()
+}
+
+fn clamp_params(params: RangedParams<'_>) {
+    for (_, value, range) in params {
+        *value = range.clamp(*value);
+    }
+}
+
+fn values(params: RangedParams<'_>) -> Vec<(String, f32, ParamRange)> {
+    params
+        .into_iter()
+        .map(|(field, value, range)| (field, *value, range))
+        .collect()
+}
+
+/// Un effet de la chaîne, tel que `EffectsPreset` le manipule par son
+/// index : interrupteur et paramètres continus.
+trait EffectConfig {
+    fn enabled(&self) -> bool;
+    fn enabled_mut(&mut self) -> &mut bool;
+    fn ranged_params(&mut self) -> RangedParams<'_>;
+    /// `ranged_params` en lecture seule
+    fn values(&self) -> Vec<(String, f32, ParamRange)>;
+}
+
+/// Un effet dans l'ordre de la chaîne : son nom (clé des
+/// `insert_points`, préfixe des `ParamViolation`) et sa config.
+struct EffectDescriptor {
+    name: &'static str,
+    config: fn(&EffectsPreset) -> &dyn EffectConfig,
+    config_mut: fn(&mut EffectsPreset) -> &mut dyn EffectConfig,
+}
+
+/// Seule correspondance entre les index d'effets (0 gate, 1 AGC, 2 EQ,
+/// 3 compresseur, 4 de-esser, 5 limiter) et les champs du preset.
+const EFFECTS: [EffectDescriptor; 6] = [
+    EffectDescriptor {
+        name: "noise_gate",
+        config: |p| &p.noise_gate,
+        config_mut: |p| &mut p.noise_gate,
+    },
+    EffectDescriptor {
+        name: "auto_gain",
+        config: |p| &p.auto_gain,
+        config_mut: |p| &mut p.auto_gain,
+    },
+    EffectDescriptor {
+        name: "eq",
+        config: |p| &p.eq,
+        config_mut: |p| &mut p.eq,
+    },
+    EffectDescriptor {
+        name: "compressor",
+        config: |p| &p.compressor,
+        config_mut: |p| &mut p.compressor,
+    },
+    EffectDescriptor {
+        name: "de_esser",
+        config: |p| &p.de_esser,
+        config_mut: |p| &mut p.de_esser,
+    },
+    EffectDescriptor {
+        name: "limiter",
+        config: |p| &p.limiter,
+        config_mut: |p| &mut p.limiter,
+    },
+];
+
 /// Configuration sérialisable d'un noise gate.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NoiseGateConfig {
@@ -9,6 +163,44 @@ pub struct NoiseGateConfig {
     pub enabled: bool,
 }
 
+impl NoiseGateConfig {
+    pub const THRESHOLD: ParamRange = ParamRange::new(0.0, 1.0);
+    pub const ATTACK: ParamRange = ParamRange::new(0.001, 0.5);
+    pub const RELEASE: ParamRange = ParamRange::new(0.001, 0.5);
+
+    /// Champs hors plage (vide : config valide).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        violations(self.clone().ranged_params())
+    }
+
+    /// Ramène chaque champ dans sa plage.
+    pub fn clamp(&mut self) {
+        clamp_params(self.ranged_params());
+    }
+}
+
+impl EffectConfig for NoiseGateConfig {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.enabled
+    }
+
+    fn ranged_params(&mut self) -> RangedParams<'_> {
+        vec![
+            ("threshold".into(), &mut self.threshold, Self::THRESHOLD),
+            ("attack".into(), &mut self.attack, Self::ATTACK),
+            ("release".into(), &mut self.release, Self::RELEASE),
+        ]
+    }
+
+    fn values(&self) -> Vec<(String, f32, ParamRange)> {
+        values(self.clone().ranged_params())
+    }
+}
+
 impl Default for NoiseGateConfig {
     fn default() -> Self {
         Self {
@@ -25,17 +217,74 @@ impl Default for NoiseGateConfig {
 pub struct CompressorConfig {
     pub threshold: f32,
     pub ratio: f32,
+    /// Largeur du coude autour du seuil, en dB (0 : coude dur)
+    #[serde(default = "CompressorConfig::default_knee_db")]
+    pub knee_db: f32,
     pub attack: f32,
     pub release: f32,
     pub makeup_gain: f32,
     pub enabled: bool,
 }
 
+impl CompressorConfig {
+    pub const THRESHOLD: ParamRange = ParamRange::new(0.01, 1.0);
+    pub const RATIO: ParamRange = ParamRange::new(1.0, 20.0);
+    pub const KNEE_DB: ParamRange = ParamRange::new(0.0, 24.0);
+    pub const ATTACK: ParamRange = ParamRange::new(0.001, 0.5);
+    pub const RELEASE: ParamRange = ParamRange::new(0.001, 0.5);
+    pub const MAKEUP_GAIN: ParamRange = ParamRange::new(0.0, 4.0);
+
+    /// Champs hors plage (vide : config valide).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        violations(self.clone().ranged_params())
+    }
+
+    /// Ramène chaque champ dans sa plage.
+    pub fn clamp(&mut self) {
+        clamp_params(self.ranged_params());
+    }
+
+    /// Presets sauvés avant le réglage du coude : coude doux de 6 dB.
+    fn default_knee_db() -> f32 {
+        6.0
+    }
+}
+
+impl EffectConfig for CompressorConfig {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.enabled
+    }
+
+    fn ranged_params(&mut self) -> RangedParams<'_> {
+        vec![
+            ("threshold".into(), &mut self.threshold, Self::THRESHOLD),
+            ("ratio".into(), &mut self.ratio, Self::RATIO),
+            ("knee_db".into(), &mut self.knee_db, Self::KNEE_DB),
+            ("attack".into(), &mut self.attack, Self::ATTACK),
+            ("release".into(), &mut self.release, Self::RELEASE),
+            (
+                "makeup_gain".into(),
+                &mut self.makeup_gain,
+                Self::MAKEUP_GAIN,
+            ),
+        ]
+    }
+
+    fn values(&self) -> Vec<(String, f32, ParamRange)> {
+        values(self.clone().ranged_params())
+    }
+}
+
 impl Default for CompressorConfig {
     fn default() -> Self {
         Self {
             threshold: 0.4,
             ratio: 3.0,
+            knee_db: Self::default_knee_db(),
             attack: 0.005,
             release: 0.02,
             makeup_gain: 1.2,
@@ -44,6 +293,78 @@ impl Default for CompressorConfig {
     }
 }
 
+/// Configuration sérialisable d'un de-esser (compresseur dont le
+/// détecteur n'entend qu'une bande, celle des sifflantes).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeEsserConfig {
+    /// Centre de la bande détectée, en Hz
+    pub frequency: f32,
+    pub q: f32,
+    pub threshold: f32,
+    pub ratio: f32,
+    pub attack: f32,
+    pub release: f32,
+    pub enabled: bool,
+}
+
+impl DeEsserConfig {
+    pub const FREQUENCY: ParamRange = ParamRange::new(2000.0, 16_000.0);
+    pub const Q: ParamRange = ParamRange::new(0.5, 10.0);
+    pub const THRESHOLD: ParamRange = ParamRange::new(0.01, 1.0);
+    pub const RATIO: ParamRange = ParamRange::new(1.0, 20.0);
+    pub const ATTACK: ParamRange = ParamRange::new(0.001, 0.5);
+    pub const RELEASE: ParamRange = ParamRange::new(0.001, 0.5);
+
+    /// Champs hors plage (vide : config valide).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        violations(self.clone().ranged_params())
+    }
+
+    /// Ramène chaque champ dans sa plage.
+    pub fn clamp(&mut self) {
+        clamp_params(self.ranged_params());
+    }
+}
+
+impl EffectConfig for DeEsserConfig {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.enabled
+    }
+
+    fn ranged_params(&mut self) -> RangedParams<'_> {
+        vec![
+            ("frequency".into(), &mut self.frequency, Self::FREQUENCY),
+            ("q".into(), &mut self.q, Self::Q),
+            ("threshold".into(), &mut self.threshold, Self::THRESHOLD),
+            ("ratio".into(), &mut self.ratio, Self::RATIO),
+            ("attack".into(), &mut self.attack, Self::ATTACK),
+            ("release".into(), &mut self.release, Self::RELEASE),
+        ]
+    }
+
+    fn values(&self) -> Vec<(String, f32, ParamRange)> {
+        values(self.clone().ranged_params())
+    }
+}
+
+impl Default for DeEsserConfig {
+    fn default() -> Self {
+        Self {
+            frequency: 6000.0,
+            q: 2.0,
+            threshold: 0.1,
+            ratio: 4.0,
+            attack: 0.05,
+            release: 0.005,
+            enabled: false, // Off par defaut
+        }
+    }
+}
+
 /// Configuration sérialisable d'une bande EQ.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EqBandConfig {
@@ -54,6 +375,30 @@ pub struct EqBandConfig {
     pub enabled: bool,
 }
 
+impl EqBandConfig {
+    pub const FREQUENCY: ParamRange = ParamRange::new(20.0, 20_000.0);
+    pub const GAIN_DB: ParamRange = ParamRange::new(-12.0, 12.0);
+    pub const Q: ParamRange = ParamRange::new(0.1, 10.0);
+
+    fn ranged_params(&mut self) -> RangedParams<'_> {
+        vec![
+            ("frequency".into(), &mut self.frequency, Self::FREQUENCY),
+            ("gain_db".into(), &mut self.gain_db, Self::GAIN_DB),
+            ("q".into(), &mut self.q, Self::Q),
+        ]
+    }
+
+    /// Champs hors plage (vide : config valide).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        violations(self.clone().ranged_params())
+    }
+
+    /// Ramène chaque champ dans sa plage.
+    pub fn clamp(&mut self) {
+        clamp_params(self.ranged_params());
+    }
+}
+
 /// Configuration sérialisable d'un EQ paramétrique.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EqConfig {
@@ -61,6 +406,46 @@ pub struct EqConfig {
     pub enabled: bool,
 }
 
+impl EqConfig {
+    /// Champs hors plage (vide : config valide).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        violations(self.clone().ranged_params())
+    }
+
+    /// Ramène chaque champ dans sa plage.
+    pub fn clamp(&mut self) {
+        clamp_params(self.ranged_params());
+    }
+}
+
+impl EffectConfig for EqConfig {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.enabled
+    }
+
+    /// Les champs des bandes s'appellent `band<N>_<champ>`, comme dans
+    /// `EffectsPreset::param_mut`.
+    fn ranged_params(&mut self) -> RangedParams<'_> {
+        self.bands
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, band)| {
+                band.ranged_params()
+                    .into_iter()
+                    .map(move |(field, value, range)| (format!("band{i}_{field}"), value, range))
+            })
+            .collect()
+    }
+
+    fn values(&self) -> Vec<(String, f32, ParamRange)> {
+        values(self.clone().ranged_params())
+    }
+}
+
 impl Default for EqConfig {
     fn default() -> Self {
         Self {
@@ -100,6 +485,42 @@ pub struct LimiterConfig {
     pub enabled: bool,
 }
 
+impl LimiterConfig {
+    pub const CEILING: ParamRange = ParamRange::new(0.1, 1.0);
+    pub const RELEASE: ParamRange = ParamRange::new(0.001, 0.5);
+
+    /// Champs hors plage (vide : config valide).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        violations(self.clone().ranged_params())
+    }
+
+    /// Ramène chaque champ dans sa plage.
+    pub fn clamp(&mut self) {
+        clamp_params(self.ranged_params());
+    }
+}
+
+impl EffectConfig for LimiterConfig {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.enabled
+    }
+
+    fn ranged_params(&mut self) -> RangedParams<'_> {
+        vec![
+            ("ceiling".into(), &mut self.ceiling, Self::CEILING),
+            ("release".into(), &mut self.release, Self::RELEASE),
+        ]
+    }
+
+    fn values(&self) -> Vec<(String, f32, ParamRange)> {
+        values(self.clone().ranged_params())
+    }
+}
+
 impl Default for LimiterConfig {
     fn default() -> Self {
         Self {
@@ -110,14 +531,105 @@ impl Default for LimiterConfig {
     }
 }
 
+/// Configuration sérialisable d'un AGC (auto gain).
+///
+/// Contrairement aux autres configs (coefficients par sample),
+/// les durées sont en secondes et les niveaux en dBFS.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoGainConfig {
+    pub target_db: f32,
+    pub max_gain_db: f32,
+    pub freeze_threshold_db: f32,
+    pub window: f32,
+    pub rise_time: f32,
+    pub fall_time: f32,
+    pub enabled: bool,
+}
+
+impl AutoGainConfig {
+    pub const TARGET_DB: ParamRange = ParamRange::new(-40.0, 0.0);
+    pub const MAX_GAIN_DB: ParamRange = ParamRange::new(0.0, 30.0);
+    pub const FREEZE_THRESHOLD_DB: ParamRange = ParamRange::new(-90.0, -20.0);
+    pub const WINDOW: ParamRange = ParamRange::new(0.5, 10.0);
+    pub const RISE_TIME: ParamRange = ParamRange::new(0.5, 30.0);
+    pub const FALL_TIME: ParamRange = ParamRange::new(0.05, 10.0);
+
+    /// Champs hors plage (vide : config valide).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        violations(self.clone().ranged_params())
+    }
+
+    /// Ramène chaque champ dans sa plage.
+    pub fn clamp(&mut self) {
+        clamp_params(self.ranged_params());
+    }
+}
+
+impl EffectConfig for AutoGainConfig {
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn enabled_mut(&mut self) -> &mut bool {
+        &mut self.enabled
+    }
+
+    fn ranged_params(&mut self) -> RangedParams<'_> {
+        vec![
+            ("target_db".into(), &mut self.target_db, Self::TARGET_DB),
+            (
+                "max_gain_db".into(),
+                &mut self.max_gain_db,
+                Self::MAX_GAIN_DB,
+            ),
+            (
+                "freeze_threshold_db".into(),
+                &mut self.freeze_threshold_db,
+                Self::FREEZE_THRESHOLD_DB,
+            ),
+            ("window".into(), &mut self.window, Self::WINDOW),
+            ("rise_time".into(), &mut self.rise_time, Self::RISE_TIME),
+            ("fall_time".into(), &mut self.fall_time, Self::FALL_TIME),
+        ]
+    }
+
+    fn values(&self) -> Vec<(String, f32, ParamRange)> {
+        values(self.clone().ranged_params())
+    }
+}
+
+impl Default for AutoGainConfig {
+    fn default() -> Self {
+        Self {
+            target_db: -18.0,
+            max_gain_db: 12.0,
+            freeze_threshold_db: -50.0,
+            window: 3.0,
+            rise_time: 3.0,
+            fall_time: 0.5,
+            enabled: false, // Off par defaut
+        }
+    }
+}
+
 /// Preset complet d'une chaîne d'effets.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EffectsPreset {
     pub name: String,
     pub noise_gate: NoiseGateConfig,
+    /// `#[serde(default)]` : les presets sauvés avant l'AGC restent lisibles.
+    #[serde(default)]
+    pub auto_gain: AutoGainConfig,
     pub eq: EqConfig,
     pub compressor: CompressorConfig,
+    /// `#[serde(default)]` : les presets sauvés avant le de-esser restent lisibles.
+    #[serde(default)]
+    pub de_esser: DeEsserConfig,
     pub limiter: LimiterConfig,
+    /// Point d'insertion des effets sur un bus, par nom d'effet (voir
+    /// `EFFECT_NAMES`). Absent : avant le fader. Sans effet sur une entrée.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub insert_points: BTreeMap<String, InsertPoint>,
 }
 
 impl EffectsPreset {
@@ -126,9 +638,12 @@ impl EffectsPreset {
         Self {
             name: "Default".to_string(),
             noise_gate: NoiseGateConfig::default(),
+            auto_gain: AutoGainConfig::default(),
             eq: EqConfig::default(),
             compressor: CompressorConfig::default(),
+            de_esser: DeEsserConfig::default(),
             limiter: LimiterConfig::default(),
+            insert_points: BTreeMap::new(),
         }
     }
 
@@ -142,6 +657,7 @@ impl EffectsPreset {
                 release: 0.003,
                 enabled: true,
             },
+            auto_gain: AutoGainConfig::default(),
             eq: EqConfig {
                 bands: vec![
                     EqBandConfig {
@@ -171,12 +687,15 @@ impl EffectsPreset {
             compressor: CompressorConfig {
                 threshold: 0.25,
                 ratio: 5.0,
+                knee_db: 6.0,
                 attack: 0.005,
                 release: 0.03,
                 makeup_gain: 1.5,
                 enabled: true,
             },
+            de_esser: DeEsserConfig::default(),
             limiter: LimiterConfig::default(),
+            insert_points: BTreeMap::new(),
         }
     }
 
@@ -188,6 +707,7 @@ impl EffectsPreset {
                 enabled: false,
                 ..NoiseGateConfig::default()
             },
+            auto_gain: AutoGainConfig::default(),
             eq: EqConfig {
                 enabled: false,
                 ..EqConfig::default()
@@ -196,7 +716,9 @@ impl EffectsPreset {
                 enabled: false,
                 ..CompressorConfig::default()
             },
+            de_esser: DeEsserConfig::default(),
             limiter: LimiterConfig::default(),
+            insert_points: BTreeMap::new(),
         }
     }
 
@@ -204,6 +726,270 @@ impl EffectsPreset {
     pub fn builtin_presets() -> Vec<Self> {
         vec![Self::default_preset(), Self::streaming(), Self::clean()]
     }
+
+    /// Paramètre continu d'un effet, désigné par sa position dans la
+    /// chaîne (0 gate, 1 AGC, 2 EQ, 3 compresseur, 4 de-esser, 5 limiter) et le nom
+    /// de son champ. Les bandes d'EQ s'écrivent `band<N>_<champ>`
+    /// (`band2_gain_db`). `None` : effet ou paramètre inconnu.
+    ///
+    /// # Pourquoi des noms en texte ?
+    /// Rust n'a pas de réflexion : pour viser un champ depuis une
+    /// commande (automation, UI), chaque config énumère ses paramètres
+    /// (`EffectConfig::ranged_params`), et `EFFECTS` relie les index aux
+    /// configs.
+    pub fn param_mut(&mut self, effect_index: usize, name: &str) -> Option<&mut f32> {
+        (EFFECTS.get(effect_index)?.config_mut)(self)
+            .ranged_params()
+            .into_iter()
+            .find(|(field, _, _)| field == name)
+            .map(|(_, value, _)| value)
+    }
+}
+
+impl EffectsPreset {
+    /// Nom de chaque effet dans les champs de `ParamViolation`, dans
+    /// l'ordre de la chaîne.
+    pub const EFFECT_NAMES: [&str; EFFECTS.len()] = {
+        let mut names = [""; EFFECTS.len()];
+        let mut index = 0;
+        while index < EFFECTS.len() {
+            names[index] = EFFECTS[index].name;
+            index += 1;
+        }
+        names
+    };
+
+    /// Point d'insertion d'un effet (mêmes index que `param_mut`).
+    pub fn insert_point(&self, effect_index: usize) -> InsertPoint {
+        Self::EFFECT_NAMES
+            .get(effect_index)
+            .and_then(|name| self.insert_points.get(*name))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Place un effet avant ou après le fader du bus. `false` : effet
+    /// inconnu.
+    pub fn set_insert_point(&mut self, effect_index: usize, point: InsertPoint) -> bool {
+        let Some(name) = Self::EFFECT_NAMES.get(effect_index) else {
+            return false;
+        };
+        match point {
+            InsertPoint::PreFader => self.insert_points.remove(*name),
+            InsertPoint::PostFader => self.insert_points.insert(name.to_string(), point),
+        };
+        true
+    }
+
+    /// Noms des effets actifs, dans l'ordre de la chaîne.
+    pub fn enabled_effects(&self) -> Vec<&'static str> {
+        EFFECTS
+            .iter()
+            .filter(|effect| (effect.config)(self).enabled())
+            .map(|effect| effect.name)
+            .collect()
+    }
+
+    /// Les effets de la chaîne placés à `point`, ceux de l'autre côté
+    /// du fader coupés. `None` : aucun effet actif à ce point.
+    pub fn at_insert_point(&self, point: InsertPoint) -> Option<Self> {
+        let mut preset = self.clone();
+        let mut any = false;
+        for (index, effect) in EFFECTS.iter().enumerate() {
+            let at_point = self.insert_point(index) == point;
+            let enabled = (effect.config_mut)(&mut preset).enabled_mut();
+            *enabled &= at_point;
+            any |= *enabled;
+        }
+        any.then_some(preset)
+    }
+
+    /// Plage d'un paramètre (mêmes noms que `param_mut`).
+    pub fn param_range(&self, effect_index: usize, name: &str) -> Option<ParamRange> {
+        (EFFECTS.get(effect_index)?.config)(self)
+            .values()
+            .into_iter()
+            .find(|(field, _, _)| field == name)
+            .map(|(_, _, range)| range)
+    }
+
+    /// Champs hors plage de toute la chaîne, préfixés par le nom de
+    /// l'effet (`compressor.attack`).
+    pub fn validate(&self) -> Vec<ParamViolation> {
+        EFFECTS
+            .iter()
+            .flat_map(|effect| {
+                (effect.config)(self)
+                    .values()
+                    .into_iter()
+                    .filter(|(_, value, range)| !range.contains(*value))
+                    .map(|(field, value, range)| ParamViolation {
+                        field: format!("{}.{field}", effect.name),
+                        value,
+                        range,
+                    })
+            })
+            .collect()
+    }
+
+    /// Ramène chaque paramètre de la chaîne dans sa plage.
+    pub fn clamp(&mut self) {
+        for effect in &EFFECTS {
+            clamp_params((effect.config_mut)(self).ranged_params());
+        }
+    }
+
+    /// Applique la politique : `Clamp` corrige et accepte toujours,
+    /// `Reject` refuse le preset s'il a un seul champ hors plage.
+    pub fn checked(mut self, policy: ParamPolicy) -> Result<Self, Vec<ParamViolation>> {
+        match policy {
+            ParamPolicy::Clamp => {
+                self.clamp();
+                Ok(self)
+            }
+            ParamPolicy::Reject => {
+                let found = self.validate();
+                if found.is_empty() {
+                    Ok(self)
+                } else {
+                    Err(found)
+                }
+            }
+        }
+    }
+
+    /// Paramètres continus d'un effet, noms et valeurs, dans les noms
+    /// de `param_mut` (mêmes index d'effets que la chaîne).
+    pub fn params(&self, effect_index: usize) -> Vec<(String, f32)> {
+        EFFECTS.get(effect_index).map_or_else(Vec::new, |effect| {
+            (effect.config)(self)
+                .values()
+                .into_iter()
+                .map(|(field, value, _)| (field, value))
+                .collect()
+        })
+    }
+}
+
+/// Un étage de la chaîne d'effets d'un canal, vu de l'UI : réglages,
+/// latence ajoutée et ce qui s'y passe en ce moment.
+///
+/// # Crêtes par étage
+/// `input_peak` / `output_peak` sont les crêtes du dernier bloc audio
+/// avant et après l'étage : on voit où le niveau monte (EQ, makeup) et
+/// où il est écrasé. Mesurées seulement en métrologie détaillée
+/// (`Command::SetDetailedEffectMetering`), `None` sinon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectStageInfo {
+    /// Type de processeur ("NoiseGate", "Compressor"...)
+    pub name: String,
+    /// Paramètres continus (voir `EffectsPreset::params`)
+    pub params: Vec<(String, f32)>,
+    pub bypassed: bool,
+    /// L'étage a paniqué dans le callback : il est sauté jusqu'au
+    /// prochain chargement de la chaîne
+    pub poisoned: bool,
+    /// Retard ajouté par l'étage, en samples
+    pub latency_samples: u32,
+    /// Réduction de gain actuelle (0.0 à 1.0), pour les dynamiques
+    pub gain_reduction: Option<f32>,
+    pub input_peak: Option<f32>,
+    pub output_peak: Option<f32>,
+    /// Avant ou après le fader, pour les étages d'un bus (`None` : entrée)
+    pub insert_point: Option<InsertPoint>,
+}
+
+/// Place d'un effet de bus par rapport au fader du bus.
+///
+/// Avant le fader, un compresseur voit le signal entier quel que soit
+/// le volume ; après, il ne voit que ce qui sort du bus (baisser le
+/// fader le fait décrocher).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InsertPoint {
+    #[default]
+    PreFader,
+    PostFader,
+}
+
+/// Réponse en fréquence prédite d'une chaîne d'effets, calculée depuis
+/// ses coefficients sans traiter d'audio (courbe d'EQ de l'UI).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrequencyResponse {
+    /// Fréquences en Hz, espacées en log de 20 Hz à 20 kHz
+    pub frequencies: Vec<f32>,
+    /// Gain à chaque fréquence, en dB
+    pub gains_db: Vec<f32>,
+    /// Étages actifs dont la réponse dépend du signal (gate,
+    /// compresseur...) : comptés à 0 dB dans `gains_db`
+    pub nonlinear_stages: Vec<String>,
+}
+
+/// Temps CPU des effets qui tournent, mesuré dans le callback quand le
+/// profilage est activé (`Command::SetDspProfiling`).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DspProfile {
+    pub enabled: bool,
+    /// Durée audio d'un bloc, en µs : le temps dont dispose le callback
+    pub block_budget_us: f32,
+    /// Un étage par effet qui tourne, Mic puis bus joué
+    pub stages: Vec<EffectTiming>,
+}
+
+impl DspProfile {
+    /// Part du bloc prise par tous les effets, en %.
+    pub fn total_percent(&self) -> f32 {
+        self.stages.iter().map(|s| s.budget_percent).sum()
+    }
+
+    /// L'étage le plus coûteux.
+    pub fn heaviest(&self) -> Option<&EffectTiming> {
+        self.stages
+            .iter()
+            .max_by(|a, b| a.micros.total_cmp(&b.micros))
+    }
+}
+
+/// Temps moyen d'un effet d'un canal, par bloc.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectTiming {
+    pub channel: ChannelId,
+    /// Place dans la chaîne (mêmes index que `EffectsPreset::params`)
+    pub effect_index: usize,
+    pub name: String,
+    /// Moyenne glissante, en µs par bloc
+    pub micros: f32,
+    /// Part du bloc, en % de `block_budget_us`
+    pub budget_percent: f32,
+}
+
+impl EffectTiming {
+    pub fn new(
+        channel: ChannelId,
+        effect_index: usize,
+        name: &str,
+        micros: f32,
+        block_budget_us: f32,
+    ) -> Self {
+        let budget_percent = if block_budget_us > 0.0 {
+            100.0 * micros / block_budget_us
+        } else {
+            0.0
+        };
+        Self {
+            channel,
+            effect_index,
+            name: name.to_string(),
+            micros,
+            budget_percent,
+        }
+    }
+}
+
+/// Découpe `band2_gain_db` en `(2, "gain_db")`.
+pub fn parse_band_param(name: &str) -> Option<(usize, &str)> {
+    let (band, field) = name.strip_prefix("band")?.split_once('_')?;
+    Some((band.parse().ok()?, field))
 }
 
 #[cfg(test)]
@@ -250,4 +1036,166 @@ mod tests {
         assert_eq!(parsed.name, "Streaming");
         assert_eq!(parsed.eq.bands.len(), 3);
     }
+
+    #[test]
+    fn insert_points_persist_and_split_the_chain() {
+        let mut preset = EffectsPreset::default_preset();
+        assert!(!toml::to_string(&preset).unwrap().contains("insert_points"));
+        assert!(preset.set_insert_point(3, InsertPoint::PostFader));
+        assert!(!preset.set_insert_point(6, InsertPoint::PostFader));
+
+        let parsed: EffectsPreset =
+            toml::from_str(&toml::to_string_pretty(&preset).unwrap()).unwrap();
+        assert_eq!(parsed.insert_point(3), InsertPoint::PostFader);
+        assert_eq!(parsed.insert_point(5), InsertPoint::PreFader);
+
+        let pre = parsed.at_insert_point(InsertPoint::PreFader).unwrap();
+        assert!(!pre.compressor.enabled && pre.limiter.enabled);
+        let post = parsed.at_insert_point(InsertPoint::PostFader).unwrap();
+        assert!(post.compressor.enabled && !post.limiter.enabled);
+
+        preset.set_insert_point(3, InsertPoint::PreFader);
+        assert!(preset.insert_points.is_empty());
+        assert!(preset.at_insert_point(InsertPoint::PostFader).is_none());
+    }
+
+    #[test]
+    fn params_are_addressed_by_chain_position_and_name() {
+        let mut preset = EffectsPreset::streaming();
+        assert_eq!(preset.param_mut(3, "ratio").copied(), Some(5.0));
+        *preset.param_mut(2, "band2_gain_db").unwrap() = -12.0;
+        assert_eq!(preset.eq.bands[2].gain_db, -12.0);
+
+        assert!(preset.param_mut(3, "ceiling").is_none()); // pas un champ du compresseur
+        assert!(preset.param_mut(2, "band7_gain_db").is_none());
+        assert!(preset.param_mut(2, "bandx_q").is_none());
+        assert!(preset.param_mut(5, "ratio").is_none());
+        assert!(preset.param_mut(6, "ceiling").is_none());
+        *preset.param_mut(4, "frequency").unwrap() = 7500.0;
+        assert_eq!(preset.de_esser.frequency, 7500.0);
+        assert_eq!(preset.param_mut(5, "ceiling").copied(), Some(0.95));
+    }
+
+    /// Chaque paramètre de chaque effet : bornes acceptées, juste
+    /// au-delà refusé, `NaN` refusé.
+    #[test]
+    fn every_param_is_checked_at_its_bounds() {
+        let base = EffectsPreset::streaming();
+        assert!(base.validate().is_empty());
+        for (index, effect) in EffectsPreset::EFFECT_NAMES.iter().enumerate() {
+            let params = base.params(index);
+            assert!(!params.is_empty());
+            for (name, _) in params {
+                let range = base.param_range(index, &name).unwrap();
+                let step = (range.max - range.min) * 1e-3;
+                for (value, valid) in [
+                    (range.min, true),
+                    (range.max, true),
+                    (range.min - step, false),
+                    (range.max + step, false),
+                    (f32::NAN, false),
+                ] {
+                    let mut preset = base.clone();
+                    *preset.param_mut(index, &name).unwrap() = value;
+                    let found = preset.validate();
+                    if valid {
+                        assert!(found.is_empty(), "{effect}.{name} = {value}: {found:?}");
+                        continue;
+                    }
+                    assert_eq!(found.len(), 1, "{effect}.{name} = {value}");
+                    assert_eq!(found[0].field, format!("{effect}.{name}"));
+                    assert_eq!(found[0].range, range);
+
+                    preset.clamp();
+                    let clamped = *preset.param_mut(index, &name).unwrap();
+                    assert!(range.contains(clamped), "{effect}.{name} -> {clamped}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn config_validation_lists_each_field_out_of_range() {
+        let compressor = CompressorConfig {
+            attack: 5.0,
+            ratio: 0.5,
+            ..CompressorConfig::default()
+        };
+        let found = compressor.validate();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].field, "ratio");
+        assert_eq!(found[1].field, "attack");
+        assert_eq!(found[1].value, 5.0);
+        assert_eq!(found[1].range, CompressorConfig::ATTACK);
+        assert_eq!(found[1].to_string(), "attack = 5 (allowed 0.001 to 0.5)");
+
+        let mut eq = EqConfig::default();
+        eq.bands[1].q = 0.0;
+        assert_eq!(eq.validate()[0].field, "band1_q");
+        assert!(NoiseGateConfig::default().validate().is_empty());
+        assert!(LimiterConfig::default().validate().is_empty());
+        assert!(AutoGainConfig::default().validate().is_empty());
+        assert!(DeEsserConfig::default().validate().is_empty());
+    }
+
+    #[test]
+    fn policy_clamps_or_rejects() {
+        let mut preset = EffectsPreset::default_preset();
+        preset.noise_gate.attack = 5.0;
+        preset.limiter.ceiling = 0.0;
+
+        let rejected = preset.clone().checked(ParamPolicy::Reject).unwrap_err();
+        let fields: Vec<&str> = rejected.iter().map(|v| v.field.as_str()).collect();
+        assert_eq!(fields, ["noise_gate.attack", "limiter.ceiling"]);
+
+        let clamped = preset.checked(ParamPolicy::Clamp).unwrap();
+        assert_eq!(clamped.noise_gate.attack, 0.5);
+        assert_eq!(clamped.limiter.ceiling, 0.1);
+        assert!(clamped.checked(ParamPolicy::Reject).is_ok());
+    }
+
+    #[test]
+    fn preset_without_de_esser_still_parses() {
+        let preset = EffectsPreset::streaming();
+        let mut value = toml::Value::try_from(&preset).unwrap();
+        value.as_table_mut().unwrap().remove("de_esser");
+        let toml_str = toml::to_string(&value).unwrap();
+
+        let parsed: EffectsPreset = toml::from_str(&toml_str).unwrap();
+        assert!(!parsed.de_esser.enabled);
+        assert_eq!(parsed.de_esser.frequency, 6000.0);
+    }
+
+    #[test]
+    fn preset_without_auto_gain_still_parses() {
+        let preset = EffectsPreset::default_preset();
+        let mut value = toml::Value::try_from(&preset).unwrap();
+        value.as_table_mut().unwrap().remove("auto_gain");
+        let toml_str = toml::to_string(&value).unwrap();
+
+        let parsed: EffectsPreset = toml::from_str(&toml_str).unwrap();
+        assert!(!parsed.auto_gain.enabled);
+        assert_eq!(parsed.auto_gain.target_db, -18.0);
+    }
+
+    #[test]
+    fn effect_timings_are_shares_of_the_block() {
+        // 512 frames à 48 kHz : 10 667 µs
+        let budget = 512.0 / 48_000.0 * 1e6;
+        let profile = DspProfile {
+            enabled: true,
+            block_budget_us: budget,
+            stages: vec![
+                EffectTiming::new(ChannelId(0), 0, "NoiseGate", budget / 100.0, budget),
+                EffectTiming::new(ChannelId(0), 3, "Compressor", budget / 20.0, budget),
+            ],
+        };
+        assert!((profile.stages[1].budget_percent - 5.0).abs() < 1e-4);
+        assert!((profile.total_percent() - 6.0).abs() < 1e-4);
+        assert_eq!(profile.heaviest().unwrap().name, "Compressor");
+        assert_eq!(
+            EffectTiming::new(ChannelId(0), 0, "Limiter", 3.0, 0.0).budget_percent,
+            0.0
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use serde::{Deserialize, Serialize};
+
 /// Erreurs partagées de Troubadour.
 ///
 /// # `thiserror` vs erreurs manuelles
@@ -25,14 +27,157 @@ pub enum TroubadourError {
     #[error("Audio stream error: {0}")]
     StreamError(String),
 
+    /// Ouverture ou démarrage d'un stream refusé, cause classée pour que
+    /// l'UI dise quoi faire (`hint`). `message` : le texte brut de cpal.
+    #[error("Cannot open audio stream on {device}: {message}")]
+    StreamOpen {
+        device: String,
+        kind: StreamErrorKind,
+        message: String,
+    },
+
     #[error("Unsupported sample rate: {0} Hz")]
     UnsupportedSampleRate(u32),
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
+    #[error(transparent)]
+    Mixer(#[from] MixerError),
+
+    #[error("Invalid preset name: {0}")]
+    InvalidPresetName(String),
+
+    #[error("Preset not found: {0}")]
+    PresetNotFound(String),
+
+    #[error("Preset already exists: {0}")]
+    PresetExists(String),
+
+    /// Fichier de profil refusé avant lecture : extension, taille, pas
+    /// un fichier
+    #[error("Invalid preset file: {0}")]
+    InvalidPresetFile(String),
+
+    /// Fichier de profil lu mais illisible (TOML ou schéma)
+    #[error("Cannot parse preset file: {0}")]
+    PresetParse(String),
+
+    #[error("Channel template not found: {0}")]
+    TemplateNotFound(String),
+
+    #[error("Control profile not found: {0}")]
+    ControlProfileNotFound(String),
+
+    #[error("Invalid control profile: {0}")]
+    InvalidControlProfile(String),
+
+    #[error("Audio file error: {0}")]
+    AudioFile(String),
+
+    #[error("Engine command queue is full ({0} commands pending)")]
+    CommandQueueFull(usize),
+
+    #[error("Network audio error: {0}")]
+    Network(String),
+
+    #[error("Invalid UI state: {0}")]
+    InvalidUiState(String),
+
+    /// Un thread a paniqué en tenant ce lock : l'état qu'il protège
+    /// n'est plus fiable
+    #[error("Engine state lock poisoned: {0}")]
+    LockPoisoned(&'static str),
+}
+
+impl TroubadourError {
+    /// Ce que l'utilisateur peut faire, quand l'erreur le dit.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::StreamOpen { kind, .. } => kind.hint(),
+            _ => None,
+        }
+    }
+}
+
+/// Cause d'un échec d'ouverture de stream.
+///
+/// cpal ne donne que quelques variantes, et tout ce qui vient du backend
+/// (WASAPI, CoreAudio, ALSA) arrive en texte libre : le classement se
+/// fait sur la variante, puis sur des mots du message. Ce qui ne se
+/// reconnaît pas reste `Other`, avec le message brut dans l'erreur.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StreamErrorKind {
+    /// Un autre programme tient le device
+    DeviceInUse,
+    /// Format refusé ; `supported` : les sample rates du device (Hz)
+    FormatNotSupported {
+        requested: u32,
+        supported: Vec<u32>,
+    },
+    /// Mode exclusif refusé par le système ou pris par un autre programme
+    ExclusiveModeDenied,
+    /// Device débranché ou disparu pendant l'ouverture
+    DeviceDisconnected,
+    /// Accès au device refusé ; `input` : le stream refusé est une
+    /// entrée (permission micro) plutôt qu'une sortie (groupe audio)
+    PermissionDenied {
+        input: bool,
+    },
+    Other,
+}
+
+impl StreamErrorKind {
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::DeviceInUse => {
+                Some("Another application is using this device: close it or pick another device")
+            }
+            Self::FormatNotSupported { .. } => Some(
+                "The device does not accept this format: choose one of its supported sample rates",
+            ),
+            Self::ExclusiveModeDenied => Some(
+                "Another application may be using this device in exclusive mode, or exclusive mode is disabled in the system sound settings",
+            ),
+            Self::DeviceDisconnected => {
+                Some("The device was unplugged or disabled: reconnect it or pick another device")
+            }
+            Self::PermissionDenied { input: true } => Some(
+                "Access to the device was denied: allow microphone access for Troubadour in the system privacy settings",
+            ),
+            Self::PermissionDenied { input: false } => Some(
+                "Access to the output device was denied: check that your user may use audio devices (audio group, system sound permissions)",
+            ),
+            Self::Other => None,
+        }
+    }
+}
+
+/// Erreurs du mixer : canaux, bus, paramètres.
+///
+/// # Pourquoi un type à part ?
+/// Un canal introuvable n'est pas un device introuvable : réutiliser
+/// `DeviceNotFound` donnait des messages trompeurs ("Audio device not
+/// found: ch1"). Le mixer renvoie ses propres erreurs, que l'appelant
+/// peut matcher précisément ; `?` les convertit en `TroubadourError`
+/// grâce à `#[from]` (le `From` est généré par `thiserror`).
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum MixerError {
     #[error("Channel {0} not found")]
     ChannelNotFound(usize),
+
+    #[error("Channel {0} is not an output bus")]
+    BusNotFound(usize),
+
+    #[error("Channel {0} already exists")]
+    DuplicateId(usize),
+
+    #[error("Too many channels: the mixer is limited to {0}")]
+    TooManyChannels(usize),
+
+    #[error("Invalid parameter: {0}")]
+    InvalidParameter(String),
 }
 
 /// Type alias pour simplifier les signatures.
@@ -48,3 +193,40 @@ pub enum TroubadourError {
 /// fn do_thing() -> Result<(), TroubadourError> { ... }
 /// ```
 pub type TroubadourResult<T> = Result<T, TroubadourError>;
+
+/// `Result` des opérations du mixer.
+pub type MixerResult<T> = Result<T, MixerError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mixer_errors_convert_with_their_own_message() {
+        fn remove(id: usize) -> TroubadourResult<()> {
+            Err(MixerError::ChannelNotFound(id))?
+        }
+        let err = remove(1).unwrap_err();
+        assert!(matches!(
+            err,
+            TroubadourError::Mixer(MixerError::ChannelNotFound(1))
+        ));
+        assert_eq!(err.to_string(), "Channel 1 not found");
+    }
+
+    #[test]
+    fn stream_open_errors_carry_a_hint() {
+        let err = TroubadourError::StreamOpen {
+            device: "Yeti".into(),
+            kind: StreamErrorKind::ExclusiveModeDenied,
+            message: "AUDCLNT_E_EXCLUSIVE_MODE_NOT_ALLOWED".into(),
+        };
+        assert!(err.hint().unwrap().contains("exclusive mode"));
+        assert_eq!(
+            err.to_string(),
+            "Cannot open audio stream on Yeti: AUDCLNT_E_EXCLUSIVE_MODE_NOT_ALLOWED"
+        );
+        assert_eq!(StreamErrorKind::Other.hint(), None);
+        assert_eq!(TroubadourError::StreamError("x".into()).hint(), None);
+    }
+}
@@ -0,0 +1,300 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Format d'export du graphe de signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// JSON structuré (pour les outils / les tests)
+    #[default]
+    Json,
+    /// Graphviz DOT (`dot -Tsvg graph.dot > graph.svg`)
+    Dot,
+}
+
+/// `FromStr` permet d'écrire `"dot".parse::<GraphFormat>()`,
+/// pratique pour les arguments de ligne de commande.
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "dot" | "gv" => Ok(Self::Dot),
+            other => Err(format!(
+                "Unknown graph format: {other} (expected json or dot)"
+            )),
+        }
+    }
+}
+
+/// Type de nœud dans le graphe de signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    /// Canal d'entrée du mixer
+    Input,
+    /// Canal de sortie du mixer (bus)
+    Output,
+    /// Sous-mix (point de mix intermédiaire)
+    Submix,
+    /// Device audio physique de capture
+    InputDevice,
+    /// Device audio physique de lecture
+    OutputDevice,
+}
+
+/// Un nœud du graphe : canal du mixer ou device physique.
+///
+/// # Pourquoi des `Option` pour les propriétés ?
+/// Un device n'a pas de volume ni de mute, un canal n'a pas de
+/// "stream actif". Plutôt que deux structs, les champs non pertinents
+/// sont `None` et omis du JSON (`skip_serializing_if`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Identifiant stable dans le graphe ("ch0", "in:Blue Yeti"...)
+    pub id: String,
+    pub kind: NodeKind,
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pan: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub muted: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solo: Option<bool>,
+    /// Pour un device : `true` si le moteur a réellement ouvert un stream dessus.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active: Option<bool>,
+}
+
+impl GraphNode {
+    /// Crée un nœud device (sans propriétés de canal).
+    pub fn device(id: impl Into<String>, kind: NodeKind, label: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            kind,
+            label: label.into(),
+            volume: None,
+            pan: None,
+            muted: None,
+            solo: None,
+            active: Some(false),
+        }
+    }
+}
+
+/// Une arête du graphe : le signal circule de `from` vers `to`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    /// Gain appliqué sur ce chemin (linéaire).
+    pub gain: f32,
+    /// `false` si le signal ne passe pas (source muted, hors solo...).
+    pub enabled: bool,
+}
+
+/// Graphe complet du flux de signal (canaux, devices, routes).
+///
+/// # Données vs rendu
+/// Le graphe est une structure de données pure : elle se sérialise
+/// en JSON telle quelle, et `to_dot` n'est qu'un rendu texte de plus.
+/// Ajouter un format (Mermaid...) ne touche pas à la construction.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignalGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl SignalGraph {
+    /// Retourne le nœud avec cet identifiant.
+    pub fn node(&self, id: &str) -> Option<&GraphNode> {
+        self.nodes.iter().find(|n| n.id == id)
+    }
+
+    /// Ajoute un nœud s'il n'existe pas déjà ; retourne une référence mutable.
+    pub fn upsert_node(&mut self, node: GraphNode) -> &mut GraphNode {
+        match self.nodes.iter().position(|n| n.id == node.id) {
+            Some(idx) => &mut self.nodes[idx],
+            None => {
+                self.nodes.push(node);
+                self.nodes.last_mut().expect("just pushed")
+            }
+        }
+    }
+
+    /// Ajoute une arête si elle n'existe pas déjà (même `from` → `to`).
+    pub fn add_edge(&mut self, edge: GraphEdge) {
+        if !self
+            .edges
+            .iter()
+            .any(|e| e.from == edge.from && e.to == edge.to)
+        {
+            self.edges.push(edge);
+        }
+    }
+
+    /// Sérialise le graphe en JSON indenté.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Rend le graphe en texte Graphviz DOT.
+    ///
+    /// Canaux = boîtes, devices = ellipses ; les éléments coupés
+    /// (muted, stream inactif, route désactivée) sont en pointillés.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph troubadour {\n    rankdir=LR;\n");
+
+        for node in &self.nodes {
+            let shape = match node.kind {
+                NodeKind::Input | NodeKind::Output | NodeKind::Submix => "box",
+                NodeKind::InputDevice | NodeKind::OutputDevice => "ellipse",
+            };
+            let mut label = escape_dot(&node.label);
+            if let Some(volume) = node.volume {
+                let _ = write!(label, "\\nvol {volume:.2}");
+            }
+            if let Some(pan) = node.pan
+                && pan != 0.0
+            {
+                let _ = write!(label, " pan {pan:+.2}");
+            }
+            if node.muted == Some(true) {
+                label.push_str("\\nMUTED");
+            }
+            if node.solo == Some(true) {
+                label.push_str("\\nSOLO");
+            }
+            let dashed = node.muted == Some(true) || node.active == Some(false);
+            let style = if dashed { ", style=dashed" } else { "" };
+            let _ = writeln!(
+                out,
+                "    \"{}\" [label=\"{label}\", shape={shape}{style}];",
+                escape_dot(&node.id)
+            );
+        }
+
+        for edge in &self.edges {
+            let style = if edge.enabled { "" } else { ", style=dashed" };
+            let _ = writeln!(
+                out,
+                "    \"{}\" -> \"{}\" [label=\"{:.2}\"{style}];",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                edge.gain
+            );
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Rend le graphe dans le format demandé.
+    pub fn render(&self, format: GraphFormat) -> Result<String, serde_json::Error> {
+        match format {
+            GraphFormat::Json => self.to_json(),
+            GraphFormat::Dot => Ok(self.to_dot()),
+        }
+    }
+
+    /// Écrit le graphe dans un fichier.
+    pub fn save(&self, path: &Path, format: GraphFormat) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, self.render(format)?)?;
+        Ok(())
+    }
+}
+
+/// Échappe les guillemets et backslashes pour une chaîne DOT.
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> SignalGraph {
+        let mut graph = SignalGraph::default();
+        graph.upsert_node(GraphNode {
+            id: "ch0".into(),
+            kind: NodeKind::Input,
+            label: "Mic \"Yeti\"".into(),
+            volume: Some(0.8),
+            pan: Some(0.0),
+            muted: Some(false),
+            solo: Some(false),
+            active: None,
+        });
+        graph.upsert_node(GraphNode::device(
+            "out:Speakers",
+            NodeKind::OutputDevice,
+            "Speakers",
+        ));
+        graph.add_edge(GraphEdge {
+            from: "ch0".into(),
+            to: "out:Speakers".into(),
+            gain: 0.8,
+            enabled: true,
+        });
+        graph
+    }
+
+    #[test]
+    fn format_from_str() {
+        assert_eq!("json".parse::<GraphFormat>(), Ok(GraphFormat::Json));
+        assert_eq!("DOT".parse::<GraphFormat>(), Ok(GraphFormat::Dot));
+        assert!("svg".parse::<GraphFormat>().is_err());
+    }
+
+    #[test]
+    fn duplicate_nodes_and_edges_are_ignored() {
+        let mut graph = sample_graph();
+        graph.upsert_node(GraphNode::device(
+            "out:Speakers",
+            NodeKind::OutputDevice,
+            "Speakers",
+        ));
+        graph.add_edge(GraphEdge {
+            from: "ch0".into(),
+            to: "out:Speakers".into(),
+            gain: 1.0,
+            enabled: true,
+        });
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.edges.len(), 1);
+    }
+
+    #[test]
+    fn json_roundtrip_omits_irrelevant_fields() {
+        let graph = sample_graph();
+        let json = graph.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        // Un device n'a pas de volume
+        assert!(value["nodes"][1].get("volume").is_none());
+        assert_eq!(value["nodes"][1]["kind"], "output_device");
+
+        let parsed: SignalGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, graph);
+    }
+
+    #[test]
+    fn dot_output_escapes_and_styles() {
+        let dot = sample_graph().to_dot();
+        assert!(dot.starts_with("digraph troubadour {"));
+        assert!(dot.contains("Mic \\\"Yeti\\\""));
+        // Device sans stream actif → pointillés
+        assert!(dot.contains("\"out:Speakers\" [label=\"Speakers\", shape=ellipse, style=dashed]"));
+        assert!(dot.contains("\"ch0\" -> \"out:Speakers\" [label=\"0.80\"]"));
+    }
+}
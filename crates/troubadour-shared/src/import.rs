@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Format d'une configuration audio d'un autre logiciel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportFormat {
+    /// Collection de scènes OBS Studio (`basic/scenes/*.json`)
+    Obs,
+    /// Export des réglages VoiceMeeter (`.xml`)
+    VoiceMeeter,
+}
+
+impl ImportFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Obs => "OBS",
+            Self::VoiceMeeter => "VoiceMeeter",
+        }
+    }
+}
+
+/// Ce qu'a produit un import, et ce qui n'a pas pu être repris.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Nom du profil créé
+    pub preset: String,
+    /// Canaux créés (entrées et bus)
+    pub channels: usize,
+    /// Devices introuvables sur cette machine ("Mic/Aux: Blue Yeti") :
+    /// les canaux sont créés sans device
+    pub unresolved_devices: Vec<String>,
+    /// Éléments ignorés, avec la raison
+    pub skipped: Vec<String>,
+}
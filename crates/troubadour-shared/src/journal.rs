@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+
+/// Une entrée du journal des modifications du mixer.
+///
+/// # Pourquoi des `String` pour before/after ?
+/// Les valeurs modifiées ont des types différents (volume en f32,
+/// mute en bool, route en paire de canaux...). Un `String` déjà formaté
+/// suffit pour l'affichage et le fichier de log, sans multiplier
+/// les variantes d'enum.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEntry {
+    /// Horodatage en millisecondes depuis l'epoch Unix.
+    pub timestamp_ms: u64,
+    /// Nom de la commande ("SetVolume", "AddRoute"...)
+    pub action: String,
+    /// Canal concerné (`None` pour les commandes globales).
+    pub channel: Option<ChannelId>,
+    /// Valeur avant la modification (si elle est peu coûteuse à capturer).
+    pub before: Option<String>,
+    /// Valeur après la modification.
+    pub after: Option<String>,
+}
+
+impl ChangeEntry {
+    /// Formate l'entrée en une ligne de log lisible.
+    ///
+    /// Exemple : `1710000000000 SetVolume ch=0 0.80 -> 0.50`
+    pub fn to_log_line(&self) -> String {
+        let mut line = format!("{} {}", self.timestamp_ms, self.action);
+        if let Some(channel) = self.channel {
+            line.push_str(&format!(" ch={}", channel.0));
+        }
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => line.push_str(&format!(" {before} -> {after}")),
+            (None, Some(after)) => line.push_str(&format!(" {after}")),
+            (Some(before), None) => line.push_str(&format!(" {before} -> ?")),
+            (None, None) => {}
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_line_with_before_after() {
+        let entry = ChangeEntry {
+            timestamp_ms: 42,
+            action: "SetVolume".to_string(),
+            channel: Some(ChannelId(0)),
+            before: Some("0.80".to_string()),
+            after: Some("0.50".to_string()),
+        };
+        assert_eq!(entry.to_log_line(), "42 SetVolume ch=0 0.80 -> 0.50");
+    }
+
+    #[test]
+    fn log_line_global_command() {
+        let entry = ChangeEntry {
+            timestamp_ms: 7,
+            action: "Shutdown".to_string(),
+            channel: None,
+            before: None,
+            after: None,
+        };
+        assert_eq!(entry.to_log_line(), "7 Shutdown");
+    }
+}
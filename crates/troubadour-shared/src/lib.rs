@@ -2,10 +2,33 @@
 // En Rust, chaque fichier est un module. `mod audio` cherche
 // soit `audio.rs` soit `audio/mod.rs` dans le même dossier.
 // `pub` le rend accessible depuis l'extérieur de la crate.
+pub mod action;
 pub mod audio;
+pub mod compat;
 pub mod config;
+pub mod config_layers;
+pub mod control;
+pub mod device_cache;
+pub mod diagnostics;
+pub mod diff;
 pub mod dsp;
 pub mod error;
+pub mod graph;
+pub mod import;
+pub mod journal;
+pub mod locale;
+pub mod machine;
 pub mod messages;
+pub mod meter_frame;
 pub mod mixer;
+pub mod network;
+pub mod preset;
 pub mod profile;
+pub mod profile_json;
+pub mod recording;
+pub mod recovery;
+pub mod script;
+pub mod tap;
+pub mod template;
+pub mod ui_state;
+pub mod unknown_keys;
@@ -0,0 +1,168 @@
+//! Noms affichés des canaux et bus d'usine, selon la langue.
+//!
+//! Le moteur ne connaît que des ids et des clés (`channel.mic`,
+//! `bus.headphones`) : la config d'usine stocke la clé à côté du nom
+//! anglais (`ChannelConfig::display_key`). La traduction se fait ici,
+//! au moment de servir les canaux à l'interface.
+//!
+//! Règles de résolution (`Locale::display_name`) :
+//!
+//! - pas de clé : le nom stocké, tel quel (configs plus anciennes,
+//!   canaux créés par l'utilisateur) ;
+//! - nom vide ou encore égal au nom anglais d'usine : la clé, dans la
+//!   langue choisie, puis en anglais, puis le nom stocké ;
+//! - sinon le canal a été renommé : le nom de l'utilisateur gagne.
+//!
+//! Les tables sont du JSON embarqué dans le binaire (`locales/*.json`),
+//! une clé par nom ; une clé absente d'une langue retombe sur l'anglais.
+
+use std::collections::HashMap;
+
+use crate::mixer::ChannelConfig;
+
+/// Langue de repli, complète par construction.
+pub const FALLBACK_LOCALE: &str = "en";
+
+/// Langues embarquées : code et table JSON.
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../locales/en.json")),
+    ("fr", include_str!("../locales/fr.json")),
+];
+
+/// Codes des langues disponibles.
+pub fn available() -> Vec<&'static str> {
+    LOCALES.iter().map(|(code, _)| *code).collect()
+}
+
+/// Table `clé → texte` d'une langue embarquée. Une table illisible est
+/// un bug du binaire, vérifié par les tests.
+fn table(code: &str) -> Option<HashMap<String, String>> {
+    let (_, json) = LOCALES.iter().find(|(c, _)| *c == code)?;
+    Some(serde_json::from_str(json).expect("embedded locale table"))
+}
+
+/// Langue choisie, avec l'anglais en repli.
+#[derive(Debug, Clone)]
+pub struct Locale {
+    code: String,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Locale {
+    /// Charge la langue `code` ; `None` si elle n'est pas embarquée.
+    pub fn new(code: &str) -> Option<Self> {
+        Some(Self {
+            code: code.to_string(),
+            strings: table(code)?,
+            fallback: table(FALLBACK_LOCALE)?,
+        })
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// Texte de `key` : la langue choisie, puis l'anglais.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(String::as_str)
+    }
+
+    /// Nom à afficher pour `channel` (règles en tête de module).
+    pub fn display_name(&self, channel: &ChannelConfig) -> String {
+        let Some(key) = channel.display_key.as_deref() else {
+            return channel.name.clone();
+        };
+        let factory = channel.name.is_empty()
+            || self
+                .fallback
+                .get(key)
+                .is_some_and(|name| *name == channel.name);
+        match self.get(key) {
+            Some(text) if factory => text.to_string(),
+            _ => channel.name.clone(),
+        }
+    }
+
+    /// Copie de `channel` portant son nom affiché.
+    pub fn localized(&self, channel: &ChannelConfig) -> ChannelConfig {
+        ChannelConfig {
+            name: self.display_name(channel),
+            ..channel.clone()
+        }
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new(FALLBACK_LOCALE).expect("fallback locale is embedded")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mixer::MixerConfig;
+
+    #[test]
+    fn every_locale_names_every_factory_channel() {
+        let setup = MixerConfig::default_setup();
+        for code in available() {
+            let locale = Locale::new(code).unwrap();
+            for channel in &setup.channels {
+                let key = channel.display_key.as_deref().unwrap();
+                assert!(locale.strings.contains_key(key), "{code} lacks {key}");
+            }
+        }
+        // L'anglais redonne les noms stockés
+        let en = Locale::default();
+        for channel in &setup.channels {
+            assert_eq!(en.display_name(channel), channel.name);
+        }
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_english_then_to_the_name() {
+        let mut fr = Locale::new("fr").unwrap();
+        fr.strings.remove("channel.desktop");
+        let desktop = ChannelConfig::input(1, "Desktop").keyed("channel.desktop");
+        assert_eq!(fr.display_name(&desktop), "Desktop");
+
+        // Clé inconnue partout : le nom stocké
+        let unknown = ChannelConfig::input(7, "Game").keyed("channel.game");
+        assert_eq!(fr.get("channel.game"), None);
+        assert_eq!(fr.display_name(&unknown), "Game");
+        // Sans nom non plus : vide, jamais la clé brute
+        let blank = ChannelConfig::input(8, "").keyed("channel.game");
+        assert_eq!(fr.display_name(&blank), "");
+    }
+
+    #[test]
+    fn user_names_override_keys() {
+        let fr = Locale::new("fr").unwrap();
+        let factory = ChannelConfig::input(0, "Mic").keyed("channel.mic");
+        assert_eq!(fr.display_name(&factory), "Micro");
+        let unnamed = ChannelConfig::input(0, "").keyed("channel.mic");
+        assert_eq!(fr.display_name(&unnamed), "Micro");
+
+        let renamed = ChannelConfig::input(0, "Shure SM7B").keyed("channel.mic");
+        assert_eq!(fr.display_name(&renamed), "Shure SM7B");
+        // Config d'avant les clés : nom littéral
+        let legacy = ChannelConfig::input(0, "Mic");
+        assert_eq!(fr.display_name(&legacy), "Mic");
+    }
+
+    #[test]
+    fn unknown_locales_are_rejected() {
+        assert!(Locale::new("xx").is_none());
+        assert_eq!(Locale::default().code(), "en");
+        let localized = Locale::new("fr")
+            .unwrap()
+            .localized(&ChannelConfig::output(3, "Headphones").keyed("bus.headphones"));
+        assert_eq!(localized.name, "Casque");
+        assert_eq!(localized.display_key.as_deref(), Some("bus.headphones"));
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+use crate::mixer::{DeviceAssignment, MixerConfig};
+
+/// Nom de machine utilisé quand le hostname est introuvable.
+pub const DEFAULT_MACHINE: &str = "default";
+
+/// Affectations de devices propres à une machine.
+///
+/// # Pourquoi séparer ?
+/// Le dossier de config peut être synchronisé entre plusieurs machines
+/// (bureau, portable). Canaux, effets, routing et volumes sont les mêmes
+/// partout ; les noms de devices, eux, changent d'une machine à l'autre.
+/// Ils vivent donc dans `[profiles."<hostname>"]`, et chaque machine ne
+/// lit et n'écrit que sa propre section.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MachineProfile {
+    /// Backend audio cpal ("ALSA", "JACK", "WASAPI"...). `None` = celui du système.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audio_host: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_device: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_device: Option<String>,
+
+    /// Device de chaque canal (entrées et bus), par id de canal.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channel_devices: Vec<ChannelDevice>,
+}
+
+/// Devices proposés au premier lancement, avant validation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SetupProposal {
+    /// Devices d'entrée/sortie et device de chaque canal retenu
+    pub profile: MachineProfile,
+    /// Un choix (ou une absence) par ligne, pour l'utilisateur
+    pub summary: Vec<String>,
+}
+
+/// Device physique affecté à un canal sur une machine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelDevice {
+    pub channel: ChannelId,
+    pub device: String,
+}
+
+impl MachineProfile {
+    /// Applique les devices de la machine au mixer. Un canal absent du
+    /// profil garde le device du mixer partagé.
+    pub fn apply_to(&self, mixer: &mut MixerConfig) {
+        for assignment in &self.channel_devices {
+            if let Some(channel) = mixer
+                .channels
+                .iter_mut()
+                .find(|c| c.id == assignment.channel)
+            {
+                channel.device_name = DeviceAssignment::Specific(assignment.device.clone());
+            }
+        }
+    }
+
+    /// Relève les devices actuels des canaux du mixer (triés par id).
+    /// Un canal qui suit le défaut du système n'est lié à aucune machine :
+    /// il le garde dans le mixer partagé.
+    pub fn capture_from(&mut self, mixer: &MixerConfig) {
+        self.channel_devices = mixer
+            .channels
+            .iter()
+            .filter_map(|c| {
+                Some(ChannelDevice {
+                    channel: c.id,
+                    device: c.device_name.specific()?.to_string(),
+                })
+            })
+            .collect();
+        self.channel_devices.sort_by_key(|d| d.channel.0);
+    }
+}
+
+/// Nom de la machine courante, par ordre de priorité :
+/// 1. `override_name` (option `--profile` de la ligne de commande)
+/// 2. le hostname (`COMPUTERNAME` sous Windows, `HOSTNAME`, `/etc/hostname`)
+/// 3. `DEFAULT_MACHINE`
+pub fn machine_name(override_name: Option<&str>) -> String {
+    let hostname = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok());
+    resolve_machine_name(override_name, hostname.as_deref())
+}
+
+/// Partie pure de `machine_name` (testable sans dépendre de l'hôte).
+pub fn resolve_machine_name(override_name: Option<&str>, hostname: Option<&str>) -> String {
+    [override_name, hostname]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|name| !name.is_empty())
+        .unwrap_or(DEFAULT_MACHINE)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mixer::ChannelConfig;
+
+    #[test]
+    fn machine_name_precedence() {
+        assert_eq!(
+            resolve_machine_name(Some("laptop"), Some("desktop")),
+            "laptop"
+        );
+        assert_eq!(resolve_machine_name(None, Some("desktop\n")), "desktop");
+        assert_eq!(resolve_machine_name(Some("  "), Some("")), DEFAULT_MACHINE);
+    }
+
+    #[test]
+    fn apply_and_capture_channel_devices() {
+        let mut mixer = MixerConfig::default_setup();
+        mixer.channels[1].device_name = DeviceAssignment::Specific("Shared Device".into());
+
+        let profile = MachineProfile {
+            channel_devices: vec![ChannelDevice {
+                channel: ChannelId(0),
+                device: "Focusrite USB".into(),
+            }],
+            ..MachineProfile::default()
+        };
+        profile.apply_to(&mut mixer);
+        assert_eq!(
+            mixer.channels[0].device_name.specific(),
+            Some("Focusrite USB")
+        );
+        // Canal absent du profil : le device partagé reste
+        assert_eq!(
+            mixer.channels[1].device_name.specific(),
+            Some("Shared Device")
+        );
+
+        let mut captured = MachineProfile::default();
+        mixer.channels.push(ChannelConfig::output(9, "Stream"));
+        captured.capture_from(&mixer);
+        let ids: Vec<usize> = captured
+            .channel_devices
+            .iter()
+            .map(|d| d.channel.0)
+            .collect();
+        assert_eq!(ids, [0, 1]);
+    }
+}
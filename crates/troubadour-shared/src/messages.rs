@@ -1,5 +1,38 @@
-use crate::audio::{BufferSize, ChannelId, SampleRate};
-use crate::mixer::ChannelLevel;
+use std::path::PathBuf;
+
+use crate::action::ActionSet;
+use crate::audio::{
+    AudioStats, BufferSize, ChannelCount, ChannelId, DeviceInfo, PreviewStopReason, RouteActivity,
+    SampleRate, SessionStats, StreamHealth,
+};
+use crate::compat::CompatibilityReport;
+use crate::config_layers::ConfigEntry;
+use crate::control::ControlSource;
+use crate::device_cache::DeviceCache;
+use crate::diagnostics::DiagnosticsReport;
+use crate::diff::{ConfigDiff, DiffEntry};
+use crate::dsp::{
+    DspProfile, EffectStageInfo, EffectsPreset, FrequencyResponse, InsertPoint, ParamPolicy,
+    ParamViolation,
+};
+use crate::error::StreamErrorKind;
+use crate::graph::GraphFormat;
+use crate::import::{ImportFormat, ImportReport};
+use crate::journal::ChangeEntry;
+use crate::machine::{MachineProfile, SetupProposal};
+use crate::meter_frame::{MeterLayout, MeterTransport};
+use crate::mixer::{
+    AutoLevelResult, BusFollow, ChannelConfig, ChannelCountSummary, ChannelDependencies,
+    ChannelLevel, ChannelLink, ChannelQuery, Crossfade, CrossfadeCurve, DeviceAssignment,
+    FaderTaper, InputFallback, InputSource, LevelPoint, Loudness, MeterScale, SignalPresenceConfig,
+    SignalState,
+};
+use crate::network::{NetworkReceiveConfig, NetworkSendConfig, NetworkStats};
+use crate::preset::{FavoritePreset, PresetCacheStats, PresetSummary};
+use crate::recording::{NormalizeOutcome, NormalizeTarget, RecordingReport, ReplayInfo};
+use crate::recovery::AutoSnapshotInfo;
+use crate::script::ScriptInfo;
+use crate::tap::{TapConfig, TapInfo};
 
 /// Commandes envoyées de l'UI vers le moteur audio.
 ///
@@ -18,6 +51,81 @@ pub enum Command {
     /// Change le volume d'un canal (0.0 = silence, 1.0 = nominal, >1.0 = boost)
     SetVolume { channel: ChannelId, level: f32 },
 
+    /// Règle le volume depuis une position de fader (0.0 → 1.0),
+    /// convertie selon la courbe configurée (GUI, MIDI et OSC identiques)
+    SetVolumeFader { channel: ChannelId, position: f32 },
+
+    /// Demande la position de fader correspondant au volume d'un canal
+    RequestVolumeFader { channel: ChannelId },
+
+    /// Change la courbe des faders de volume
+    SetFaderTaper(FaderTaper),
+
+    /// Change le plancher et le seuil d'alerte des meters (dBFS,
+    /// `None` : valeur par défaut) ; répond par `MeterScale`
+    SetMeterScale {
+        min_db: Option<f32>,
+        warn_db: Option<f32>,
+    },
+
+    /// Demande l'échelle des meters
+    RequestMeterScale,
+
+    // === État de l'UI ===
+    /// Remplace l'état de l'UI gardé dans la config (objet JSON, voir
+    /// `UiState`). Trop gros ou trop imbriqué : `Event::Error`, rien ne
+    /// change.
+    SetUiState { json: String },
+
+    /// Demande l'état de l'UI → `Event::UiState`
+    RequestUiState,
+
+    /// Change la langue des noms d'usine (`fr`, `en`...) : les canaux
+    /// servis ensuite (`FindChannels`) portent les noms traduits.
+    /// Langue inconnue : `Event::Error`. → `Event::LocaleChanged`
+    SetLocale { locale: String },
+
+    // === Presets d'effets ===
+    /// Change le dossier des presets d'effets (refusé s'il n'est pas
+    /// inscriptible) → `Event::PresetDir`
+    SetPresetDir { path: PathBuf },
+
+    /// Demande le dossier des presets d'effets → `Event::PresetDir`
+    RequestPresetDir,
+
+    /// Demande la config effective et l'origine de chaque réglage
+    /// (fichier, environnement, ligne de commande) → `Event::EffectiveConfig`
+    RequestEffectiveConfig,
+
+    // === Audio réseau ===
+    /// Envoie un bus à une autre machine (remplace l'envoi en cours).
+    /// Moteur démarré, et seulement le bus joué par le device de sortie
+    EnableNetworkSend(NetworkSendConfig),
+
+    /// Arrête l'envoi réseau
+    DisableNetworkSend,
+
+    /// Joue un flux réseau sur un canal d'entrée (remplace la réception
+    /// en cours)
+    EnableNetworkReceive(NetworkReceiveConfig),
+
+    /// Arrête la réception réseau
+    DisableNetworkReceive,
+
+    /// Demande les compteurs du transport réseau
+    RequestNetworkStats,
+
+    // === Taps ===
+    /// Sert une copie d'un bus en TCP local → `Event::TapList`.
+    /// Moteur démarré, et seulement le bus joué par le device de sortie
+    CreateTap(TapConfig),
+
+    /// Ferme un tap → `Event::TapList`
+    DestroyTap { id: u32 },
+
+    /// Demande les taps ouverts → `Event::TapList`
+    ListTaps,
+
     /// Mute ou unmute un canal
     SetMute { channel: ChannelId, muted: bool },
 
@@ -27,6 +135,150 @@ pub enum Command {
     /// Change le pan stéréo d'un canal (-1.0 gauche, 0.0 centre, 1.0 droite)
     SetPan { channel: ChannelId, pan: f32 },
 
+    /// Change le retard d'un canal en ms (0 → 1000), pour le lip-sync
+    SetDelay { channel: ChannelId, delay_ms: f32 },
+
+    /// Inversion de polarité et bloqueur de DC d'une entrée, appliqués
+    /// avant les effets
+    SetChannelInputOptions {
+        channel: ChannelId,
+        phase_invert: bool,
+        dc_block: bool,
+    },
+
+    /// Choisit la tranche de canaux du device lue par un canal
+    /// (`count = 0` = tout le device). Seule l'entrée du pipeline (canal
+    /// 0) lit une tranche en direct : refusé pour les autres canaux
+    SetDeviceChannels {
+        channel: ChannelId,
+        offset: u16,
+        count: u16,
+    },
+
+    /// Assigne un device à un canal (aucun, un device nommé ou celui par
+    /// défaut du système) ; seuls les streams concernés par ce canal sont
+    /// rouverts
+    SetChannelDevice {
+        channel: ChannelId,
+        device: DeviceAssignment,
+    },
+
+    /// Règle le repli d'une entrée quand son device manque
+    SetInputFallback {
+        channel: ChannelId,
+        fallback: InputFallback,
+    },
+
+    /// Règle le retour pré-fader (monitoring direct) d'une entrée vers
+    /// un bus, en plus de son routing (`bus = None` = pas de retour)
+    SetChannelMonitor {
+        channel: ChannelId,
+        bus: Option<ChannelId>,
+        level_db: f32,
+    },
+
+    /// Surcharge la détection de silence d'un canal (`None` = réglages globaux)
+    SetSignalPresence {
+        channel: ChannelId,
+        config: Option<SignalPresenceConfig>,
+    },
+
+    /// Demande l'état de présence de signal d'un canal (une UI qui
+    /// démarre, sans attendre le prochain changement) → `Event::SignalState`
+    RequestSignalState { channel: ChannelId },
+
+    // === Canaux ===
+    /// Ajoute un canal nu (erreur si l'id est déjà pris)
+    AddChannel(ChannelConfig),
+
+    /// Crée un canal d'entrée depuis un template (Mic, Music, Game, Chat,
+    /// template utilisateur ou preset d'effets sauvé) : volume, effets et
+    /// routage vers les bus en une seule commande
+    AddChannelFromTemplate {
+        channel: ChannelId,
+        name: String,
+        template: String,
+    },
+
+    /// Ce que la suppression d'un canal retirerait (routes, crossfaders,
+    /// paire stéréo...), à confirmer avant `RemoveChannel`
+    /// → `Event::ChannelDependencies`
+    RequestChannelDependencies { channel: ChannelId },
+
+    /// Supprime un canal et tout ce qui le référence → `Event::ChannelRemoved`
+    RemoveChannel { channel: ChannelId },
+
+    /// Demande la liste des templates de canaux
+    ListChannelTemplates,
+
+    /// Cherche les canaux qui satisfont tous les critères
+    /// → `Event::ChannelsFound`
+    FindChannels(ChannelQuery),
+
+    /// Demande les totaux par état (badges) → `Event::ChannelCountSummary`
+    RequestChannelCountSummary,
+
+    // === Bus ===
+    /// Fondu du volume d'un bus vers `target_db` (`-inf` = silence) en
+    /// `duration_ms`. Un `SetVolume` sur ce bus pendant le fondu l'annule
+    FadeBusVolume {
+        bus: ChannelId,
+        target_db: f32,
+        duration_ms: f32,
+    },
+
+    /// Active/désactive le solo d'un bus de sortie (transitoire, non sauvé).
+    /// Les autres bus envoient du silence à leur device ; meters inchangés.
+    ToggleBusSolo { bus: ChannelId },
+
+    /// Change le nombre de canaux d'un bus (mono pour un retour IFB).
+    /// Refusé si le device du bus n'a pas assez de canaux.
+    SetBusChannelCount { bus: ChannelId, count: ChannelCount },
+
+    /// Place un bus sur les canaux `offset..` de son device de sortie
+    /// (`None` : stéréo recopié sur toutes les paires). Refusé si la
+    /// tranche chevauche un autre bus ou dépasse les canaux du device ;
+    /// la place apparaît dans `ChannelConfig::output_channel_offset`.
+    SetBusOutputMapping { bus: ChannelId, offset: Option<u16> },
+
+    /// Bouton panique : tous les effets contournés, gain unité pour
+    /// chaque canal routé vers le bus de repli (`ControlsConfig::
+    /// passthrough_bus`). Rien n'est modifié : désactiver rend le mix
+    /// d'avant tel quel.
+    SetEmergencyPassthrough { enabled: bool },
+
+    /// Active/coupe la mesure de sonie (LUFS, EBU R128) d'un bus ; les
+    /// valeurs arrivent dans `LevelUpdate` (transitoire, non sauvé)
+    SetLoudnessMeter { bus: ChannelId, enabled: bool },
+
+    /// Active/coupe la mesure de crête vraie (dBTP) d'un bus : la valeur
+    /// arrive dans `LevelUpdate` et décide de `clipped`. Sauvée avec le
+    /// bus (`ChannelConfig::true_peak_meter`).
+    SetTruePeakMeter { bus: ChannelId, enabled: bool },
+
+    /// Canaux qu'un bus somme en premier, dans cet ordre (vide : ordre
+    /// d'affichage). Sauvé avec le bus (`ChannelConfig::sum_priority`).
+    SetBusSumPriority {
+        bus: ChannelId,
+        priority: Vec<ChannelId>,
+    },
+
+    /// Écoute un device d'entrée sans l'assigner : stream temporaire
+    /// vers le bus d'écoute, à -12 dB, niveaux sous `PREVIEW_CHANNEL`.
+    /// Remplace l'écoute en cours → `Event::DevicePreviewStarted`
+    StartDevicePreview { device: String },
+
+    /// Arrête l'écoute en cours → `Event::DevicePreviewStopped`
+    StopDevicePreview,
+
+    /// Retarde la sortie d'un bus (0 → 500 ms) vers son device, après
+    /// les taps ; le changement passe par un fondu de 10 ms
+    SetOutputDelay { bus: ChannelId, delay_ms: f32 },
+
+    /// Demande la sonie d'un bus → `Event::Loudness` ; `reset` remet
+    /// ensuite la mesure à zéro (l'intégrée recommence)
+    RequestLoudness { bus: ChannelId, reset: bool },
+
     // === Routing ===
     /// Connecte une entrée à une sortie
     AddRoute { from: ChannelId, to: ChannelId },
@@ -34,6 +286,86 @@ pub enum Command {
     /// Déconnecte une route
     RemoveRoute { from: ChannelId, to: ChannelId },
 
+    // === Crossfaders ===
+    /// Crée un crossfader A/B entre deux entrées (au centre)
+    CreateCrossfade {
+        id: u32,
+        channel_a: ChannelId,
+        channel_b: ChannelId,
+        curve: CrossfadeCurve,
+    },
+
+    /// Déplace un crossfader (0.0 = tout A, 1.0 = tout B)
+    SetCrossfadePosition { id: u32, position: f32 },
+
+    /// Supprime un crossfader
+    RemoveCrossfade { id: u32 },
+
+    /// Demande la liste des crossfaders
+    ListCrossfades,
+
+    // === Liens stéréo ===
+    /// Lie deux entrées mono en une paire stéréo ; `left` fait foi.
+    /// Volume, mute, solo et effets suivent tout de suite ; la paire
+    /// n'est mixée en L/R que hors ligne (`render_offline`) : en direct,
+    /// le pipeline ne joue que son entrée, avec son propre pan
+    LinkChannels { left: ChannelId, right: ChannelId },
+
+    /// Défait le lien stéréo dont `channel` est l'un des côtés
+    UnlinkChannels { channel: ChannelId },
+
+    /// Demande la liste des liens stéréo
+    ListChannelLinks,
+
+    // === Suivi de volume entre bus ===
+    /// Fait suivre à un bus le volume d'un autre, à un écart fixe
+    /// (remplace un suivi existant du même suiveur) → `Event::BusFollowList`
+    FollowBus(BusFollow),
+
+    /// Défait le suivi de `follower`, qui garde son volume
+    UnfollowBus { follower: ChannelId },
+
+    /// Demande la liste des suivis de volume entre bus
+    ListBusFollows,
+
+    // === Ensembles d'actions ===
+    /// Enregistre (ou remplace) un ensemble d'actions nommé ; refusé s'il
+    /// vise un canal inconnu
+    SaveActionSet { name: String, set: ActionSet },
+
+    /// Supprime un ensemble d'actions
+    DeleteActionSet { name: String },
+
+    /// Applique un ensemble d'actions, tout ou rien ("BRB")
+    ExecuteActionSet { name: String },
+
+    /// Demande les noms des ensembles d'actions
+    ListActionSets,
+
+    // === Routages nommés ===
+    /// Enregistre (ou remplace) le routage actuel sous un nom
+    SaveRoutingSnapshot { name: String },
+
+    /// Remplace toutes les routes par celles d'un routage nommé, d'un
+    /// coup ; volumes, effets et devices ne bougent pas
+    ApplyRoutingSnapshot { name: String },
+
+    /// Supprime un routage nommé
+    DeleteRoutingSnapshot { name: String },
+
+    /// Demande les noms des routages → `Event::RoutingSnapshotList`
+    ListRoutingSnapshots,
+
+    // === Scripts ===
+    /// Demande les scripts du dossier `scripts/` → `Event::ScriptList`
+    ListScripts,
+
+    /// Compile et active un script (nom du fichier sans `.rhai`)
+    EnableScript { name: String },
+
+    /// Désactive un script ; son état est oublié
+    DisableScript { name: String },
+
     // === Devices ===
     /// Sélectionne le device d'entrée actif
     SetInputDevice { name: String },
@@ -50,6 +382,328 @@ pub enum Command {
     /// Demande la liste des devices disponibles
     RequestDeviceList,
 
+    /// Demande les réglages de stream appris par device
+    RequestDeviceCache,
+
+    /// Oublie les réglages de stream appris par device
+    ClearDeviceCache,
+
+    /// Active le profil de devices d'une machine (hostname ou nom libre,
+    /// créé vide s'il n'existe pas)
+    SelectMachineProfile { name: String },
+
+    /// Demande le profil machine actif
+    RequestMachineProfile,
+
+    /// Demande les devices proposés pour un premier lancement
+    /// → `Event::SetupProposal`
+    RequestSetupProposal,
+
+    /// Applique une proposition (éventuellement retouchée par l'utilisateur)
+    ApplySetupProposal(SetupProposal),
+
+    /// Demande la santé de la sortie (underruns, latence) → `Event::AudioStats`,
+    /// puis l'état des streams surveillés → `Event::StreamHealth`
+    RequestAudioStats,
+
+    /// Demande les statistiques de la session (durée, underruns, charge
+    /// DSP, crêtes des bus, reconnexions) → `Event::SessionStats`.
+    /// `reset` : la session repart de zéro après l'envoi.
+    RequestSessionStats { reset: bool },
+
+    /// Active la mesure d'activité des routes (coupée par défaut : elle
+    /// coûte dans le callback). Active, le moteur envoie un
+    /// `Event::RouteActivity` après chaque bloc mesuré (~20 fois par
+    /// seconde).
+    SetRouteMetering { enabled: bool },
+
+    /// Réglage guidé des niveaux : mesure la crête de chaque canal, l'un
+    /// après l'autre pendant `duration_ms` (l'utilisateur parle dans le
+    /// micro), puis règle son volume pour atteindre `target_peak_db`
+    /// → `Event::AutoLevelProgress`, `AutoLevelChannel`, `AutoLevelFinished`.
+    /// Moteur en marche, et seulement pour les canaux mesurés (entrée du
+    /// pipeline, bus joué)
+    AutoLevelChannels {
+        channels: Vec<ChannelId>,
+        target_peak_db: f32,
+        duration_ms: u64,
+    },
+
+    /// Abandonne le réglage guidé en cours
+    CancelAutoLevel,
+
+    /// Commence (ou arrête) l'historique des niveaux d'un canal, pour
+    /// un graphe défilant : coupé par défaut, il coûte de la mémoire
+    /// (voir `LevelHistoryConfig`)
+    SetLevelHistory { channel: ChannelId, enabled: bool },
+
+    /// Demande les `seconds` dernières secondes de l'historique d'un
+    /// canal → `Event::LevelHistory`
+    RequestLevelHistory { channel: ChannelId, seconds: u32 },
+
+    /// Niveaux en `LevelUpdate` (défaut) ou en trames binaires
+    /// (`Event::MeterFrame`, voir `meter_frame`)
+    SetMeterTransport(MeterTransport),
+
+    /// Renvoie le layout des trames binaires en cours (annonce perdue,
+    /// UI reconnectée) → `Event::MeterLayout`
+    RequestMeterLayout,
+
+    /// Canaux que l'UI affiche : seuls eux sont mesurés pour elle (liste
+    /// vide : aucun). `active: false` revient à tout mesurer.
+    SetMeterSubscription {
+        targets: Vec<ChannelId>,
+        active: bool,
+    },
+
+    /// Suspend l'envoi des meters (fenêtre réduite) ; les crêtes restent
+    /// tenues et partent à la reprise
+    SetMetersPaused { paused: bool },
+
+    /// Demande l'activité des routes → `Event::RouteActivity`
+    RequestRouteActivity,
+
+    // === Presets d'effets ===
+    /// Remplace la chaîne d'effets d'un canal
+    SetChannelEffects {
+        channel: ChannelId,
+        preset: EffectsPreset,
+    },
+
+    /// Place un effet d'un bus avant ou après son fader (même index
+    /// que `AutomateEffectParam`), gardé dans le preset du bus
+    SetEffectInsertPoint {
+        bus: ChannelId,
+        effect_index: usize,
+        point: InsertPoint,
+    },
+
+    /// Paramètres hors plage dans `SetChannelEffects` /
+    /// `AutomateEffectParam` : refusés avec `Event::EffectParamsRejected`
+    /// (`Reject`, par défaut) ou ramenés à la borne (`Clamp`)
+    SetEffectParamPolicy(ParamPolicy),
+
+    /// Fait glisser un paramètre d'effet vers `target` en `duration_ms`
+    /// (`effect_index` / `param` : voir `EffectsPreset::param_mut`)
+    AutomateEffectParam {
+        channel: ChannelId,
+        effect_index: usize,
+        param: String,
+        target: f32,
+        duration_ms: u32,
+    },
+
+    /// Fait entendre le détecteur d'un effet (gate, compresseur) à la
+    /// place de sa sortie, pour régler le seuil. Coupé à l'arrêt du moteur.
+    SetDetectorListen {
+        channel: ChannelId,
+        effect_index: usize,
+        listen: bool,
+    },
+
+    /// Fait entendre sur le retour d'une entrée la différence entre son
+    /// signal sec et sa sortie d'effets (test de nulle), à la place du
+    /// retour normal ; le mix principal ne change pas. Il faut un bus de
+    /// retour. Coupé à l'arrêt du moteur. → `Event::EffectsDiffMonitor`
+    SetEffectsDiffMonitor { channel: ChannelId, enabled: bool },
+
+    /// Demande la chaîne d'effets d'un canal, étage par étage
+    /// → `Event::ChannelEffects`
+    RequestChannelEffects { channel: ChannelId },
+
+    /// Demande la courbe prédite de la chaîne d'effets d'un canal sur
+    /// `points` fréquences (log, 20 Hz → 20 kHz), sans traiter d'audio
+    /// → `Event::EqResponse`
+    RequestEqResponse { channel: ChannelId, points: u32 },
+
+    /// Active la mesure des crêtes avant/après chaque effet du canal
+    /// (coûte deux comparaisons par étage et par sample)
+    SetDetailedEffectMetering { channel: ChannelId, enabled: bool },
+
+    /// Chronomètre chaque effet qui tourne (Mic et bus joué) : deux
+    /// lectures d'horloge par effet et par bloc
+    SetDspProfiling { enabled: bool },
+
+    /// Demande le temps moyen de chaque effet → `Event::DspProfile`
+    RequestDspProfile,
+
+    /// Sauve la chaîne d'effets actuelle d'un canal sous un nom.
+    /// Sans `overwrite`, un preset existant n'est pas remplacé :
+    /// `Event::EffectsPresetExists`, pour demander confirmation.
+    SaveEffectsPreset {
+        channel: ChannelId,
+        name: String,
+        overwrite: bool,
+    },
+
+    /// Applique un preset d'effets sauvé à un canal (sans redémarrer le moteur)
+    ApplyEffectsPreset { channel: ChannelId, name: String },
+
+    /// Supprime un preset d'effets sauvé
+    DeleteEffectsPreset { name: String },
+
+    /// Demande la liste des presets d'effets sauvés
+    ListEffectsPresets,
+
+    /// Demande le résumé de chaque preset (effets actifs, fichier
+    /// illisible...) → `Event::EffectsPresetSummaries`. Seuls les
+    /// fichiers modifiés depuis la demande précédente sont relus.
+    ListEffectsPresetSummaries,
+
+    /// Oublie les résumés gardés : la prochaine liste relit tous les
+    /// fichiers → `Event::PresetCacheStats`
+    RefreshPresetCache,
+
+    /// Demande les compteurs du cache des résumés → `Event::PresetCacheStats`
+    RequestPresetCacheStats,
+
+    /// Renomme un preset d'effets sauvé (son favori suit) ; un nom
+    /// déjà pris est refusé
+    RenameEffectsPreset { from: String, to: String },
+
+    /// Ajoute un preset d'effets aux favoris, à la place `position`
+    /// (`None` : en dernier ; déjà favori : déplacé) → `Event::FavoritePresets`
+    AddFavoritePreset {
+        name: String,
+        position: Option<usize>,
+    },
+
+    /// Retire un preset des favoris → `Event::FavoritePresets`
+    RemoveFavoritePreset { name: String },
+
+    /// Nouvel ordre des favoris, chacun une fois → `Event::FavoritePresets`
+    ReorderFavoritePresets { names: Vec<String> },
+
+    /// Demande les favoris, vérifiés sur le disque → `Event::FavoritePresets`
+    ListFavoritePresets,
+
+    /// Applique le favori `position` (0 = le premier) à un canal,
+    /// comme `ApplyEffectsPreset`
+    ApplyFavoritePreset { channel: ChannelId, position: usize },
+
+    // === Surfaces de contrôle (MIDI, OSC) ===
+    /// Demande les profils de surface disponibles → `Event::ControlProfiles`
+    ListControlProfiles,
+
+    /// Active un profil de surface : les liaisons changent aussitôt,
+    /// sans redémarrer le moteur
+    ActivateControlProfile { name: String },
+
+    /// Écrit un profil (intégré ou sauvé) dans un fichier, pour le modifier
+    ExportControlProfile { name: String, path: PathBuf },
+
+    /// Valeur (0.0 → 1.0) reçue d'un contrôle, traduite selon le profil actif
+    ControlInput { source: ControlSource, value: f32 },
+
+    // === Profils ===
+    /// Compare un profil (intégré ou sauvé) au mixer actuel, sans
+    /// rien appliquer
+    DiffPreset { name: String },
+
+    /// Vérifie les devices d'un profil contre ceux de cette machine,
+    /// sans l'appliquer → `Event::PresetCompatibility`
+    CheckPresetCompatibility { name: String },
+
+    /// Applique seulement les entrées choisies du diff d'un profil
+    ApplyPresetSelection {
+        name: String,
+        selection: Vec<DiffEntry>,
+    },
+
+    /// Crée un profil depuis une config OBS ou VoiceMeeter
+    ImportExternalConfig { path: PathBuf, format: ImportFormat },
+
+    /// Applique un fichier de profil pris n'importe où (glissé sur la
+    /// fenêtre) → `Event::PresetFileLoaded`
+    LoadPresetFile { path: PathBuf },
+
+    /// Recopie un fichier de profil dans le dossier des profils, sous
+    /// `name` (par défaut : le nom du fichier) → `Event::DroppedPresetSaved`
+    SaveDroppedPreset {
+        path: PathBuf,
+        name: Option<String>,
+        overwrite: bool,
+    },
+
+    /// Profil (intégré ou sauvé) écrit en JSON, pour un éditeur en
+    /// ligne → `Event::PresetJson`
+    ExportPresetJson { name: String },
+
+    /// Sauve un profil JSON dans le dossier des profils, sous `name`,
+    /// sans l'appliquer → `Event::PresetJsonImported`
+    ImportPresetJson {
+        name: String,
+        json: String,
+        overwrite: bool,
+    },
+
+    // === Journal ===
+    /// Demande les `limit` dernières entrées du journal des modifications
+    RequestChangeLog { limit: usize },
+
+    /// Vide le journal des modifications
+    ClearChangeLog,
+
+    // === Diagnostic ===
+    /// Lance le diagnostic complet ("pas de son") → `Event::Diagnostics`
+    RunDiagnostics,
+
+    /// Lance le diagnostic et écrit le rapport JSON dans un fichier
+    ExportDiagnostics { path: PathBuf },
+
+    /// Demande le graphe du flux de signal (canaux, routes, streams actifs)
+    ExportSignalGraph { format: GraphFormat },
+
+    // === Enregistrements ===
+    /// Mesure un fichier WAV (crêtes, RMS, sonie) dans un thread à part :
+    /// `Event::RecordingProgress` puis `Event::RecordingAnalyzed`
+    AnalyzeRecording { path: PathBuf },
+
+    /// Écrit dans `dst` une copie de `src` amenée à `target`, crête
+    /// vraie gardée sous -1 dBTP → `Event::RecordingNormalized`
+    NormalizeRecording {
+        src: PathBuf,
+        dst: PathBuf,
+        target: NormalizeTarget,
+    },
+
+    /// Garde en mémoire les `seconds` dernières secondes de `bus`
+    /// (`seconds: None` : la durée de la config) → `Event::ReplayStatus`
+    EnableReplay {
+        bus: ChannelId,
+        seconds: Option<u32>,
+    },
+
+    /// Libère la mémoire du replay de `bus` → `Event::ReplayStatus`
+    DisableReplay { bus: ChannelId },
+
+    /// Replays actifs et leur remplissage → `Event::ReplayStatus`
+    RequestReplayStatus,
+
+    /// Sauve en WAV les `seconds` dernières secondes (`None` : tout ce
+    /// qui est gardé) du replay de `bus`, ou de chaque replay (`None`).
+    /// Sans `path`, un fichier horodaté du dossier des replays ; un
+    /// chemin n'est accepté qu'avec un bus → `Event::ReplaySaved`
+    SaveReplay {
+        bus: Option<ChannelId>,
+        path: Option<PathBuf>,
+        seconds: Option<f32>,
+    },
+
+    // === Récupération après crash ===
+    /// Applique l'instantané de secours laissé par une session interrompue
+    RestoreRecoverySnapshot,
+
+    /// Supprime l'instantané de secours sans l'appliquer
+    DiscardRecoverySnapshot,
+
+    /// Liste les instantanés pris avant un chargement de preset
+    ListAutoSnapshots,
+
+    /// Revient à un instantané automatique (l'état actuel est lui-même
+    /// sauvé avant)
+    RestoreAutoSnapshot { id: u64 },
+
     /// Arrête le moteur audio proprement
     Shutdown,
 }
@@ -64,21 +718,407 @@ pub enum Event {
     /// Un seul Vec = 1 allocation. Pour du temps réel à 60fps, ça compte.
     LevelUpdate(Vec<ChannelLevel>),
 
-    /// Liste des devices audio disponibles sur le système
+    /// Un canal est devenu silencieux ou a retrouvé du signal
+    /// (pour l'automatisation : "le micro est muet depuis 30 s")
+    SignalStateChanged {
+        channel: ChannelId,
+        state: SignalState,
+        timestamp_ms: u64,
+    },
+
+    /// État de présence de signal d'un canal (réponse à `RequestSignalState`)
+    SignalState {
+        channel: ChannelId,
+        state: SignalState,
+    },
+
+    /// Le traitement d'un canal a paniqué dans le callback audio ; le
+    /// reste du mixer continue. `effect` : l'étage fautif, désormais
+    /// sauté (`None` : le canal entier est réduit au silence). Envoyé une
+    /// fois par étage ou canal mis à l'écart.
+    ProcessingPanicked {
+        channel: ChannelId,
+        effect: Option<String>,
+        message: String,
+    },
+
+    /// Passthrough d'urgence activé (bus de repli joué) ou coupé (`None`)
+    EmergencyPassthroughChanged { bus: Option<ChannelId> },
+
+    /// Un fondu de bus a atteint sa cible (pas envoyé s'il a été annulé)
+    BusFadeCompleted { bus: ChannelId },
+
+    /// Écoute d'un device démarrée, entendue sur `bus` s'il est joué
+    DevicePreviewStarted {
+        device: String,
+        bus: Option<ChannelId>,
+    },
+
+    /// Écoute d'un device arrêtée
+    DevicePreviewStopped {
+        device: String,
+        reason: PreviewStopReason,
+    },
+
+    /// Sonie d'un bus (`None` : meter non activé sur ce bus)
+    Loudness {
+        bus: ChannelId,
+        loudness: Option<Loudness>,
+    },
+
+    /// Nouvel état du solo d'un bus
+    BusSolo { bus: ChannelId, solo: bool },
+
+    /// Écoute de la différence sec − traité d'une entrée, activée ou coupée
+    EffectsDiffMonitor { channel: ChannelId, enabled: bool },
+
+    /// Position de fader d'un canal (réponse à `RequestVolumeFader`)
+    VolumeFader { channel: ChannelId, position: f32 },
+
+    /// Compteurs du transport réseau (paquets perdus, tampon anti-gigue)
+    NetworkStats(NetworkStats),
+
+    /// Taps ouverts, avec leurs blocs copiés et jetés
+    TapList(Vec<TapInfo>),
+
+    /// La boucle de contrôle passe en veille (entrée silencieuse) ou
+    /// en sort
+    IdleChanged { idle: bool },
+
+    /// Échelle des meters qui fait foi, avec ses graduations conseillées
+    /// en dB (du plancher au haut de l'échelle)
+    MeterScale { scale: MeterScale, ticks: Vec<f32> },
+
+    /// État de l'UI en JSON (`{}` si rien n'a été sauvé)
+    UiState { json: String },
+
+    /// Dossier des presets d'effets en usage
+    PresetDir { path: PathBuf },
+
+    /// Réglages surchargeables et couche qui les a fournis
+    EffectiveConfig { entries: Vec<ConfigEntry> },
+
+    /// Liste des devices audio disponibles sur le système, avec ceux par
+    /// défaut (vers lesquels se résolvent les canaux qui les suivent)
     DeviceList {
         inputs: Vec<String>,
         outputs: Vec<String>,
+        default_input: Option<String>,
+        default_output: Option<String>,
+    },
+
+    /// Détails des devices (nombre de canaux...), envoyé avec `DeviceList`
+    /// pour que le sélecteur puisse proposer "Inputs 3-4"
+    DeviceDetails {
+        inputs: Vec<DeviceInfo>,
+        outputs: Vec<DeviceInfo>,
     },
 
+    /// Réglages de stream appris par device : ce qui a marché, et ce qui
+    /// a échoué ("ce device a échoué à 96 kHz la dernière fois")
+    DeviceCache(DeviceCache),
+
     /// Un device a été branché ou débranché
     DeviceChanged,
 
+    /// Profil machine actif et machines connues (triées)
+    MachineProfile {
+        name: String,
+        profile: MachineProfile,
+        available: Vec<String>,
+    },
+
+    /// Devices proposés pour le premier lancement
+    SetupProposal(SetupProposal),
+
+    /// Noms des presets d'effets sauvés (triés)
+    EffectsPresetList(Vec<String>),
+
+    /// Résumés des presets d'effets, dans l'ordre de `EffectsPresetList`
+    EffectsPresetSummaries(Vec<PresetSummary>),
+
+    /// Compteurs du cache des résumés de presets
+    PresetCacheStats(PresetCacheStats),
+
+    /// Favoris dans leur ordre ; `missing` : plus de fichier à ce nom
+    FavoritePresets(Vec<FavoritePreset>),
+
+    /// Profils de surface disponibles, et celui qui est actif
+    ControlProfiles {
+        names: Vec<String>,
+        active: Option<String>,
+    },
+
+    /// Un profil de surface est actif (liaisons remplacées)
+    ControlProfileActivated { name: String },
+
+    /// Un profil de surface a été écrit sur disque
+    ControlProfileExported { name: String, path: PathBuf },
+
+    /// `SaveEffectsPreset` sans `overwrite` sur un nom déjà pris
+    EffectsPresetExists { name: String },
+
+    /// Un preset d'effets a été appliqué à un canal
+    EffectsPresetApplied { channel: ChannelId, name: String },
+
+    /// Réglages d'effets refusés : rien n'a été appliqué, chaque champ
+    /// fautif est listé avec sa plage
+    EffectParamsRejected {
+        channel: ChannelId,
+        violations: Vec<ParamViolation>,
+    },
+
+    /// Une automation de paramètre a atteint sa cible
+    EffectAutomationCompleted {
+        channel: ChannelId,
+        effect_index: usize,
+        param: String,
+        value: f32,
+    },
+
+    /// Chaîne d'effets d'un canal, dans l'ordre de traitement
+    ChannelEffects {
+        channel: ChannelId,
+        stages: Vec<EffectStageInfo>,
+    },
+
+    /// Courbe prédite de la chaîne d'effets d'un canal
+    EqResponse {
+        channel: ChannelId,
+        response: FrequencyResponse,
+    },
+
+    /// Temps CPU de chaque effet qui tourne
+    DspProfile(DspProfile),
+
+    /// Une automation a été annulée : le paramètre a été réglé à la main
+    EffectAutomationCancelled {
+        channel: ChannelId,
+        effect_index: usize,
+        param: String,
+    },
+
+    /// Noms des templates de canaux (intégrés d'abord)
+    ChannelTemplateList(Vec<String>),
+
+    /// Crossfaders du mixer, triés par id
+    CrossfadeList(Vec<Crossfade>),
+
+    /// Paires stéréo du mixer, triées par canal de gauche : un canal
+    /// présent ici est affiché lié à son partenaire
+    ChannelLinkList(Vec<ChannelLink>),
+
+    /// Suivis de volume entre bus, triés par suiveur
+    BusFollowList(Vec<BusFollow>),
+
+    /// Noms des ensembles d'actions, triés
+    ActionSetList(Vec<String>),
+
+    /// Noms des routages enregistrés, triés
+    RoutingSnapshotList(Vec<String>),
+
+    /// Un routage nommé remplace les routes
+    RoutingSnapshotApplied { name: String },
+
+    /// Un ensemble d'actions a été appliqué, à la demande ou par son
+    /// horaire (`scheduled`)
+    ActionSetExecuted { name: String, scheduled: bool },
+
+    /// Scripts du dossier `scripts/`, triés par nom
+    ScriptList(Vec<ScriptInfo>),
+
+    /// Un script a levé une erreur (ou dépassé ses limites) : il est
+    /// désactivé. Aussi envoyé, sans désactivation, quand ses commandes
+    /// dépassent le débit permis.
+    ScriptError { name: String, message: String },
+
+    /// Canaux trouvés par `FindChannels`, dans l'ordre d'affichage
+    ChannelsFound {
+        query: ChannelQuery,
+        channels: Vec<ChannelConfig>,
+    },
+
+    /// Totaux par état des canaux
+    ChannelCountSummary(ChannelCountSummary),
+
+    /// Langue des noms d'usine changée : l'UI redemande ses canaux
+    LocaleChanged { locale: String },
+
+    /// Un canal a été créé (`template` : celui utilisé, le cas échéant)
+    ChannelAdded {
+        channel: ChannelId,
+        template: Option<String>,
+    },
+
+    /// Ce que retirerait la suppression d'un canal
+    ChannelDependencies(ChannelDependencies),
+
+    /// Un canal a été supprimé, avec ce qui a été nettoyé
+    ChannelRemoved(ChannelDependencies),
+
+    /// Ce que changerait un profil (réponse à `DiffPreset`)
+    PresetDiff { name: String, diff: ConfigDiff },
+
+    /// Devices d'un profil résolus sur cette machine
+    /// (`CheckPresetCompatibility`)
+    PresetCompatibility(CompatibilityReport),
+
+    /// Entrées d'un diff de profil appliquées (`ApplyPresetSelection`)
+    PresetSelectionApplied { name: String, applied: usize },
+
+    /// Profil créé par `ImportExternalConfig`, et ce qui n'a pas été repris
+    ExternalConfigImported(ImportReport),
+
+    /// Fichier de profil appliqué (`LoadPresetFile`), et ce qui n'a pas
+    /// été repris
+    PresetFileLoaded { name: String, warnings: Vec<String> },
+
+    /// Preset chargé malgré des clés inconnues (faute de frappe, version
+    /// plus récente) : elles ont été ignorées
+    PresetWarnings { name: String, warnings: Vec<String> },
+
+    /// Fichier de profil recopié dans le dossier des profils, sous ce nom
+    DroppedPresetSaved { name: String },
+
+    /// Profil en JSON (réponse à `ExportPresetJson`)
+    PresetJson { name: String, json: String },
+
+    /// Profil JSON sauvé sous ce nom, avec ses clés inconnues, ignorées
+    PresetJsonImported { name: String, warnings: Vec<String> },
+
+    /// Entrées du journal des modifications (plus ancienne en premier)
+    ChangeLog(Vec<ChangeEntry>),
+
+    /// Graphe du flux de signal, déjà rendu dans le format demandé
+    SignalGraph {
+        format: GraphFormat,
+        content: String,
+    },
+
+    /// Rapport de diagnostic (boxé : bien plus gros que les autres variantes)
+    Diagnostics(Box<DiagnosticsReport>),
+
+    /// Rapport de diagnostic écrit sur disque
+    DiagnosticsExported { path: PathBuf },
+
+    /// Avancement d'une analyse ou d'une normalisation (0 → 1), par pas
+    /// d'au moins 1 %
+    RecordingProgress { path: PathBuf, progress: f32 },
+
+    /// Mesures d'un fichier (`Command::AnalyzeRecording`)
+    RecordingAnalyzed {
+        path: PathBuf,
+        report: RecordingReport,
+    },
+
+    /// Copie normalisée écrite dans `path`
+    RecordingNormalized {
+        path: PathBuf,
+        outcome: NormalizeOutcome,
+    },
+
+    /// Replays actifs, par bus
+    ReplayStatus(Vec<ReplayInfo>),
+
+    /// Fin d'un replay écrite dans `path`
+    ReplaySaved {
+        bus: ChannelId,
+        path: PathBuf,
+        seconds: f32,
+    },
+
+    /// L'instantané de secours a été appliqué (pris à `saved_at_ms`)
+    RecoveryRestored { saved_at_ms: u64 },
+
+    /// L'instantané de secours a été supprimé
+    RecoveryDiscarded,
+
+    /// Instantanés automatiques, du plus récent au plus ancien
+    AutoSnapshotList(Vec<AutoSnapshotInfo>),
+
+    /// Un instantané automatique a été restauré
+    AutoSnapshotRestored(AutoSnapshotInfo),
+
+    /// Sample rate réellement utilisé après `SetSampleRate`
+    /// (peut différer de la demande si le device ne la supporte pas)
+    SampleRateChanged { sample_rate: u32 },
+
+    /// Santé de la sortie (réponse à `RequestAudioStats`)
+    AudioStats(AudioStats),
+
+    /// Statistiques de la session (réponse à `RequestSessionStats`)
+    SessionStats(SessionStats),
+
+    /// Routes ayant porté du signal, avec leur dernière crête : envoyé
+    /// après les blocs mesurés (voir `SetRouteMetering`), ou réponse à
+    /// `RequestRouteActivity`
+    RouteActivity(Vec<RouteActivity>),
+
+    /// Canaux des trames binaires à venir, dans leur ordre : envoyé au
+    /// passage en binaire, à chaque nouveau pipeline, ou en réponse à
+    /// `RequestMeterLayout`
+    MeterLayout(MeterLayout),
+
+    /// Niveaux d'un tick en binaire (`SetMeterTransport`), à la place du
+    /// `LevelUpdate` : format dans `meter_frame`
+    MeterFrame(Vec<u8>),
+
+    /// Réglage guidé : la mesure du canal `index` (sur `total`) commence,
+    /// l'utilisateur doit parler dans son micro
+    AutoLevelProgress {
+        channel: ChannelId,
+        index: usize,
+        total: usize,
+    },
+
+    /// Réglage guidé : un canal mesuré, et réglé sauf silence ou montée
+    /// au-delà du plafond
+    AutoLevelChannel(AutoLevelResult),
+
+    /// Réglage guidé terminé : tous les canaux, dans l'ordre demandé
+    AutoLevelFinished(Vec<AutoLevelResult>),
+
+    /// Historique des niveaux d'un canal, du plus ancien au plus récent
+    LevelHistory {
+        channel: ChannelId,
+        points: Vec<LevelPoint>,
+    },
+
+    /// État de chaque stream ouvert selon le watchdog (après `AudioStats`)
+    StreamHealth(Vec<StreamHealth>),
+
+    /// Le callback d'un stream n'a pas tourné depuis `silent_ms`
+    /// (driver bloqué ?). Le pipeline est rouvert si `auto_restart`.
+    StreamStalled {
+        device_name: String,
+        is_input: bool,
+        silent_ms: u64,
+    },
+
+    /// Un stream déclaré bloqué tourne de nouveau
+    StreamRecovered { device_name: String, is_input: bool },
+
+    /// Une entrée joue son repli (`source`) faute de device, ou revient
+    /// sur son device (`source = None`)
+    InputFallback {
+        channel: ChannelId,
+        source: Option<InputSource>,
+    },
+
     /// Le moteur audio a démarré
     EngineStarted,
 
     /// Le moteur audio s'est arrêté
     EngineStopped,
 
+    /// Un stream n'a pas pu s'ouvrir ou démarrer : cause classée et
+    /// conseil à afficher
+    StreamFailed {
+        device_name: String,
+        kind: StreamErrorKind,
+        hint: Option<String>,
+    },
+
     /// Une erreur s'est produite dans le moteur
     Error(String),
 }
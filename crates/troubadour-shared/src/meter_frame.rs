@@ -0,0 +1,228 @@
+//! Meters en binaire : une alternative compacte à `Event::LevelUpdate`.
+//!
+//! À 30 images par seconde et pour des dizaines de canaux, passer les
+//! niveaux en JSON coûte au moteur comme à l'UI. En transport binaire
+//! (`MeterTransport::Binary`), la liste des canaux mesurés est annoncée
+//! une fois (`Event::MeterLayout`, sérialisable), puis chaque tick
+//! n'envoie que des nombres, dans l'ordre de cette liste
+//! (`Event::MeterFrame`) :
+//!
+//! ```text
+//! octet  taille  contenu
+//!    0      4    magic "TRMF"
+//!    4      1    version du format (`METER_FRAME_VERSION`)
+//!    5      1    réservé (0)
+//!    6      2    nombre de canaux n (u16 LE)
+//!    8      4    id du layout (u32 LE), celui de `MeterLayout::id`
+//!   12      4    numéro de trame (u32 LE, repart à 0 après u32::MAX)
+//!   16    8 × n  par canal, dans l'ordre du layout : rms, peak
+//!                (f32 LE, linéaires ; NaN = pas mesuré à ce tick)
+//! ```
+//!
+//! Un décodeur refuse une version qu'il ne connaît pas, et ignore les
+//! trames dont l'id de layout n'est pas celui annoncé en dernier (trames
+//! d'un pipeline remplacé, encore en route). Un layout perdu (file
+//! d'événements pleine) se redemande : `Command::RequestMeterLayout`.
+//!
+//! Une trame lue peut être rendue au moteur
+//! (`EngineChannels::meter_frame_tx`) : le callback la réécrit au lieu
+//! d'en allouer une nouvelle.
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+use crate::mixer::ChannelLevel;
+
+/// Début de chaque trame.
+pub const METER_FRAME_MAGIC: [u8; 4] = *b"TRMF";
+
+/// Version du format : change dès que la disposition change.
+pub const METER_FRAME_VERSION: u8 = 1;
+
+/// Taille de l'en-tête, avant les niveaux.
+pub const METER_FRAME_HEADER_BYTES: usize = 16;
+
+/// Forme des niveaux envoyés à l'UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MeterTransport {
+    /// `Event::LevelUpdate` (défaut)
+    #[default]
+    Json,
+    /// `Event::MeterLayout` une fois, puis `Event::MeterFrame`
+    Binary,
+}
+
+/// Canaux d'une trame binaire, dans l'ordre des niveaux.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeterLayout {
+    /// `METER_FRAME_VERSION` de l'encodeur
+    pub version: u8,
+    /// Repris dans chaque trame : change avec le pipeline
+    pub id: u32,
+    pub targets: Vec<ChannelId>,
+}
+
+impl MeterLayout {
+    pub fn new(id: u32, targets: Vec<ChannelId>) -> Self {
+        Self {
+            version: METER_FRAME_VERSION,
+            id,
+            targets,
+        }
+    }
+
+    /// Taille d'une trame de ce layout, en octets.
+    pub fn frame_len(&self) -> usize {
+        METER_FRAME_HEADER_BYTES + self.targets.len() * 8
+    }
+}
+
+/// Écrit les trames d'un layout (côté moteur).
+#[derive(Debug, Clone)]
+pub struct MeterFrameEncoder {
+    layout: MeterLayout,
+    sequence: u32,
+}
+
+impl MeterFrameEncoder {
+    /// Au plus `u16::MAX` canaux : le reste est ignoré.
+    pub fn new(mut layout: MeterLayout) -> Self {
+        layout.targets.truncate(usize::from(u16::MAX));
+        Self {
+            layout,
+            sequence: 0,
+        }
+    }
+
+    pub fn layout(&self) -> &MeterLayout {
+        &self.layout
+    }
+
+    /// Trame des niveaux du tick. Un canal du layout absent de `levels`
+    /// vaut NaN ; un canal hors layout est ignoré.
+    pub fn encode(&mut self, levels: &[ChannelLevel]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(self.layout.frame_len());
+        self.encode_into(levels, &mut frame);
+        frame
+    }
+
+    /// `encode` dans `frame`, vidée d'abord : une trame réutilisée
+    /// assez grande n'alloue pas.
+    pub fn encode_into(&mut self, levels: &[ChannelLevel], frame: &mut Vec<u8>) {
+        frame.clear();
+        frame.reserve(self.layout.frame_len());
+        frame.extend_from_slice(&METER_FRAME_MAGIC);
+        frame.push(METER_FRAME_VERSION);
+        frame.push(0);
+        frame.extend_from_slice(&(self.layout.targets.len() as u16).to_le_bytes());
+        frame.extend_from_slice(&self.layout.id.to_le_bytes());
+        frame.extend_from_slice(&self.sequence.to_le_bytes());
+        for target in &self.layout.targets {
+            let (rms, peak) = levels
+                .iter()
+                .find(|level| level.channel == *target)
+                .map_or((f32::NAN, f32::NAN), |level| (level.rms, level.peak));
+            frame.extend_from_slice(&rms.to_le_bytes());
+            frame.extend_from_slice(&peak.to_le_bytes());
+        }
+        self.sequence = self.sequence.wrapping_add(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Décodeur de référence, écrit d'après le tableau du module et non
+    /// d'après l'encodeur : celui qu'une UI reproduit.
+    #[derive(Debug, PartialEq)]
+    struct Decoded {
+        layout_id: u32,
+        sequence: u32,
+        levels: Vec<(f32, f32)>,
+    }
+
+    fn decode(frame: &[u8]) -> Result<Decoded, String> {
+        let u16_at = |at: usize| u16::from_le_bytes([frame[at], frame[at + 1]]);
+        let u32_at = |at: usize| u32::from_le_bytes(frame[at..at + 4].try_into().unwrap());
+        let f32_at = |at: usize| f32::from_le_bytes(frame[at..at + 4].try_into().unwrap());
+        if frame.len() < 16 || frame[..4] != *b"TRMF" {
+            return Err("not a meter frame".into());
+        }
+        if frame[4] != 1 {
+            return Err(format!("unknown version {}", frame[4]));
+        }
+        let count = usize::from(u16_at(6));
+        if frame.len() != 16 + 8 * count {
+            return Err(format!("{} bytes for {count} targets", frame.len()));
+        }
+        Ok(Decoded {
+            layout_id: u32_at(8),
+            sequence: u32_at(12),
+            levels: (0..count)
+                .map(|i| (f32_at(16 + 8 * i), f32_at(20 + 8 * i)))
+                .collect(),
+        })
+    }
+
+    fn level(channel: usize, rms: f32, peak: f32) -> ChannelLevel {
+        ChannelLevel {
+            channel: ChannelId(channel),
+            rms,
+            peak,
+            rms_position: 0.0,
+            peak_position: 0.0,
+            loudness: None,
+            true_peak: None,
+            clipped: false,
+        }
+    }
+
+    #[test]
+    fn frames_decode_in_layout_order() {
+        let layout = MeterLayout::new(7, vec![ChannelId(3), ChannelId(0), ChannelId(9)]);
+        let mut encoder = MeterFrameEncoder::new(layout.clone());
+        let levels = [
+            level(0, 0.25, 0.5),
+            level(3, 0.125, 1.5),
+            level(5, 1.0, 1.0),
+        ];
+
+        let frame = encoder.encode(&levels);
+        assert_eq!(frame.len(), layout.frame_len());
+        let decoded = decode(&frame).unwrap();
+        assert_eq!((decoded.layout_id, decoded.sequence), (7, 0));
+        assert_eq!(decoded.levels[..2], [(0.125, 1.5), (0.25, 0.5)]);
+        // Pas mesuré à ce tick
+        assert!(decoded.levels[2].0.is_nan() && decoded.levels[2].1.is_nan());
+
+        assert_eq!(decode(&encoder.encode(&levels)).unwrap().sequence, 1);
+
+        // Le layout voyage en JSON
+        let json = serde_json::to_string(&layout).unwrap();
+        assert_eq!(serde_json::from_str::<MeterLayout>(&json).unwrap(), layout);
+    }
+
+    #[test]
+    fn reused_frames_are_rewritten_in_place() {
+        let mut encoder = MeterFrameEncoder::new(MeterLayout::new(2, vec![ChannelId(0)]));
+        let mut frame = encoder.encode(&[level(0, 0.5, 0.5)]);
+        let buffer = frame.as_ptr();
+
+        encoder.encode_into(&[level(0, 0.25, 1.0)], &mut frame);
+        assert_eq!(frame.as_ptr(), buffer);
+        let decoded = decode(&frame).unwrap();
+        assert_eq!((decoded.sequence, decoded.levels[0]), (1, (0.25, 1.0)));
+    }
+
+    #[test]
+    fn other_versions_are_detected() {
+        let mut encoder = MeterFrameEncoder::new(MeterLayout::new(1, vec![ChannelId(0)]));
+        let mut frame = encoder.encode(&[]);
+        assert_eq!(frame[4], METER_FRAME_VERSION);
+        frame[4] = METER_FRAME_VERSION + 1;
+        assert!(decode(&frame).unwrap_err().contains("version"));
+        assert!(decode(b"{\"LevelUpdate\":[]}").is_err());
+    }
+}
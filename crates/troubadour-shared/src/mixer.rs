@@ -1,6 +1,15 @@
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
 
-use crate::audio::ChannelId;
+use crate::audio::{ChannelCount, ChannelId};
+
+/// Nombre maximal de canaux (entrées et bus) par défaut.
+///
+/// Chaque canal coûte du temps dans chaque callback : au-delà, un
+/// script qui ajoute des canaux en boucle ferait sauter le budget
+/// temps réel. Réglable par `audio.max_channels`.
+pub const DEFAULT_MAX_CHANNELS: usize = 64;
 
 /// Type de canal dans le mixer.
 ///
@@ -13,6 +22,10 @@ use crate::audio::ChannelId;
 pub enum ChannelKind {
     Input,
     Output,
+    /// Sous-mix : point de mix intermédiaire, sans device. Reçoit des
+    /// entrées (ou d'autres sous-mix) et s'envoie, avec son volume, son
+    /// pan et son mute, vers des bus ou d'autres sous-mix.
+    Submix,
 }
 
 /// Configuration d'un canal du mixer.
@@ -24,10 +37,17 @@ pub enum ChannelKind {
 /// `ChannelConfig` est la configuration persistante (sauvegardée en TOML).
 /// L'état runtime (niveau audio actuel, peak hold) vit dans le core
 /// et n'est PAS sérialisé — il change 60x par seconde.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChannelConfig {
     pub id: ChannelId,
     pub name: String,
+
+    /// Clé du nom d'usine dans les tables de langue (`channel.mic`),
+    /// résolue par `Locale::display_name`. `name` garde le nom anglais :
+    /// tant qu'il n'est pas renommé, c'est la clé qui s'affiche.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_key: Option<String>,
+
     pub kind: ChannelKind,
 
     /// Volume linéaire : 0.0 = silence, 1.0 = unity gain, >1.0 = boost
@@ -51,9 +71,85 @@ pub struct ChannelConfig {
     /// (-0.3). Un enum ne permettrait que des positions discrètes.
     pub pan: f32,
 
-    /// Nom du device audio physique associé (si applicable).
-    /// `None` = pas encore assigné.
-    pub device_name: Option<String>,
+    /// Retard appliqué au canal, en millisecondes (0 → 1000).
+    /// Sert à resynchroniser l'audio avec une vidéo en retard (lip-sync).
+    /// `#[serde(default)]` : les configs existantes restent lisibles.
+    #[serde(default)]
+    pub delay_ms: f32,
+
+    /// Polarité inversée (samples × -1), avant tout traitement : deux
+    /// micros face à face s'annulent sans elle.
+    #[serde(default)]
+    pub phase_invert: bool,
+
+    /// Bloqueur de DC (passe-haut à ~5 Hz) avant tout traitement, pour
+    /// les interfaces qui décalent le signal d'une tension constante.
+    #[serde(default)]
+    pub dc_block: bool,
+
+    /// Device audio physique associé : un device précis, celui par
+    /// défaut du système, ou aucun (pas encore assigné).
+    #[serde(default, skip_serializing_if = "DeviceAssignment::is_none")]
+    pub device_name: DeviceAssignment,
+
+    /// Premier canal du device lu par ce canal (0 = entrée 1).
+    /// Permet d'isoler "Inputs 3-4" sur une interface multicanal.
+    #[serde(default)]
+    pub device_channel_offset: u16,
+
+    /// Nombre de canaux du device lus à partir de l'offset.
+    /// 0 = tous les canaux du device (downmix complet).
+    #[serde(default)]
+    pub device_channel_count: u16,
+
+    /// Détection de silence propre à ce canal.
+    /// `None` = réglages globaux (`AppConfig::signal_presence`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal_presence: Option<SignalPresenceConfig>,
+
+    /// Bus de retour (monitoring direct) : reçoit le signal du canal
+    /// après effets mais AVANT le fader, en plus du routing normal.
+    /// Baisser le canal dans le mix ne l'enlève pas du casque de
+    /// l'artiste. `None` = pas de retour.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor_bus: Option<ChannelId>,
+
+    /// Niveau du retour en dB (0 = nominal, `-inf` = silence).
+    #[serde(default)]
+    pub monitor_level_db: f32,
+
+    /// Pour un bus : canaux transportés (stéréo par défaut, mono pour
+    /// un retour). Sans effet sur une entrée.
+    #[serde(default)]
+    pub channel_count: ChannelCount,
+
+    /// Pour un bus : premier canal du device de sortie où il est écrit
+    /// (2 = sorties 3-4). `None` : le stéréo est recopié sur toutes les
+    /// paires du device, comme avant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_channel_offset: Option<u16>,
+
+    /// Pour un bus : mesure de la crête vraie (suréchantillonnée x4),
+    /// qui remplace la crête des samples pour signaler l'écrêtage.
+    /// Coupée par défaut : elle coûte ~50 multiplications par sample.
+    #[serde(default)]
+    pub true_peak_meter: bool,
+
+    /// Pour un bus : retard de sa sortie en millisecondes (0 → 500),
+    /// pour un casque sans fil qui arrive après les autres bus. Appliqué
+    /// juste avant le device : les taps et l'envoi réseau ne le voient pas.
+    #[serde(default)]
+    pub output_delay_ms: f32,
+
+    /// Pour une entrée : quoi ouvrir quand son device manque au
+    /// démarrage (laptop sorti du dock, interface débranchée).
+    #[serde(default, skip_serializing_if = "InputFallback::is_system_default")]
+    pub input_fallback: InputFallback,
+
+    /// Pour un bus : canaux sommés en premier, dans cet ordre ; les
+    /// autres suivent dans l'ordre d'affichage (voir `sum_order`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sum_priority: Vec<ChannelId>,
 }
 
 impl ChannelConfig {
@@ -62,15 +158,54 @@ impl ChannelConfig {
         Self {
             id,
             name: name.into(),
+            display_key: None,
             kind,
             volume: 1.0,
             muted: false,
             solo: false,
             pan: 0.0,
-            device_name: None,
+            delay_ms: 0.0,
+            phase_invert: false,
+            dc_block: false,
+            device_name: DeviceAssignment::None,
+            device_channel_offset: 0,
+            device_channel_count: 0,
+            signal_presence: None,
+            monitor_bus: None,
+            monitor_level_db: 0.0,
+            channel_count: ChannelCount::default(),
+            output_channel_offset: None,
+            true_peak_meter: false,
+            output_delay_ms: 0.0,
+            input_fallback: InputFallback::SystemDefault,
+            sum_priority: Vec::new(),
         }
     }
 
+    /// Ordre dans lequel ce bus somme `sources` : les canaux de
+    /// `sum_priority` d'abord, puis les autres par id (l'ordre
+    /// d'affichage des entrées et sous-mix).
+    ///
+    /// # Pourquoi un ordre fixe ?
+    /// L'addition flottante n'est pas associative : `(a + b) + c` et
+    /// `a + (b + c)` diffèrent au dernier bit. Sommer dans l'ordre d'une
+    /// `HashMap` donnerait des bus différents d'un lancement à l'autre,
+    /// et des rendus de référence qui ne se reproduisent pas.
+    pub fn sum_order(&self, sources: &[ChannelId]) -> Vec<ChannelId> {
+        let mut rest: Vec<ChannelId> = sources
+            .iter()
+            .copied()
+            .filter(|id| !self.sum_priority.contains(id))
+            .collect();
+        rest.sort_by_key(|id| id.0);
+        self.sum_priority
+            .iter()
+            .copied()
+            .filter(|id| sources.contains(id))
+            .chain(rest)
+            .collect()
+    }
+
     /// Crée un canal d'entrée.
     pub fn input(id: usize, name: impl Into<String>) -> Self {
         Self::new(ChannelId(id), name, ChannelKind::Input)
@@ -80,6 +215,264 @@ impl ChannelConfig {
     pub fn output(id: usize, name: impl Into<String>) -> Self {
         Self::new(ChannelId(id), name, ChannelKind::Output)
     }
+
+    /// Crée un sous-mix.
+    pub fn submix(id: usize, name: impl Into<String>) -> Self {
+        Self::new(ChannelId(id), name, ChannelKind::Submix)
+    }
+
+    /// Rattache le canal à un nom d'usine traduisible (voir `locale`).
+    pub fn keyed(mut self, key: &str) -> Self {
+        self.display_key = Some(key.to_string());
+        self
+    }
+}
+
+/// Device assigné à un canal.
+///
+/// # Suivre le défaut du système
+/// `SystemDefault` ne désigne aucun device : il est résolu à l'ouverture
+/// des streams (le device par défaut de l'OS à cet instant), puis de
+/// nouveau quand l'OS change de défaut. Passer des enceintes au casque
+/// dans Windows emmène le bus avec lui.
+///
+/// # Format
+/// Étiqueté dans la config, pour que "défaut du système" ne se confonde
+/// jamais avec un device qui s'appellerait "default" :
+///
+/// ```toml
+/// device_name = { mode = "specific", device = "HD 600" }
+/// device_name = { mode = "system_default" }
+/// ```
+///
+/// Un simple nom (configs plus anciennes) se lit comme `Specific`.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(
+    tag = "mode",
+    content = "device",
+    rename_all = "snake_case",
+    from = "StoredAssignment"
+)]
+pub enum DeviceAssignment {
+    #[default]
+    None,
+    SystemDefault,
+    Specific(String),
+}
+
+impl DeviceAssignment {
+    pub fn is_none(&self) -> bool {
+        *self == Self::None
+    }
+
+    pub fn follows_system_default(&self) -> bool {
+        *self == Self::SystemDefault
+    }
+
+    /// Device choisi explicitement (`None` pour le défaut du système).
+    pub fn specific(&self) -> Option<&str> {
+        match self {
+            Self::Specific(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Device réel, avec `system_default` le défaut actuel de l'OS.
+    pub fn resolve<'a>(&'a self, system_default: Option<&'a str>) -> Option<&'a str> {
+        match self {
+            Self::None => None,
+            Self::SystemDefault => system_default,
+            Self::Specific(name) => Some(name),
+        }
+    }
+}
+
+impl From<Option<String>> for DeviceAssignment {
+    fn from(device: Option<String>) -> Self {
+        device.map_or(Self::None, Self::Specific)
+    }
+}
+
+impl std::fmt::Display for DeviceAssignment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::SystemDefault => f.write_str("system default"),
+            Self::Specific(name) => f.write_str(name),
+        }
+    }
+}
+
+/// Ce qu'on accepte à la lecture : la forme étiquetée ou un ancien nom.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredAssignment {
+    Name(String),
+    Tagged(TaggedAssignment),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "mode", content = "device", rename_all = "snake_case")]
+enum TaggedAssignment {
+    None,
+    SystemDefault,
+    Specific(String),
+}
+
+impl From<StoredAssignment> for DeviceAssignment {
+    fn from(stored: StoredAssignment) -> Self {
+        match stored {
+            StoredAssignment::Name(name) => Self::Specific(name),
+            StoredAssignment::Tagged(TaggedAssignment::None) => Self::None,
+            StoredAssignment::Tagged(TaggedAssignment::SystemDefault) => Self::SystemDefault,
+            StoredAssignment::Tagged(TaggedAssignment::Specific(name)) => Self::Specific(name),
+        }
+    }
+}
+
+/// Repli d'une entrée dont le device manque.
+///
+/// Résolu à l'ouverture des streams, quand le device de l'entrée n'est
+/// pas branché. Le moteur revient sur le device dès qu'il réapparaît.
+///
+/// ```toml
+/// input_fallback = "silence"
+/// input_fallback = { devices = ["USB Mic", "Built-in Mic"] }
+/// input_fallback = { file = "/home/me/hold-music.wav" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputFallback {
+    /// Le device par défaut du système (comportement historique)
+    #[default]
+    SystemDefault,
+    /// Aucun device : le canal reste muet
+    Silence,
+    /// Le premier device branché de la liste, dans l'ordre ; aucun : silence
+    Devices(Vec<String>),
+    /// Un fichier WAV joué en boucle à la place du device
+    File(PathBuf),
+}
+
+impl InputFallback {
+    pub fn is_system_default(&self) -> bool {
+        *self == Self::SystemDefault
+    }
+}
+
+/// Source réellement ouverte pour une entrée.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputSource {
+    Device(String),
+    File(PathBuf),
+    Silence,
+}
+
+impl InputSource {
+    /// Nom du device (`None` pour un fichier ou le silence).
+    pub fn device(&self) -> Option<&str> {
+        match self {
+            Self::Device(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for InputSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Device(name) => f.write_str(name),
+            Self::File(path) => write!(f, "file {}", path.display()),
+            Self::Silence => f.write_str("silence"),
+        }
+    }
+}
+
+/// Présence de signal sur un canal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalState {
+    /// Du signal passe (ou pas encore assez de silence pour conclure)
+    #[default]
+    Active,
+    /// Sous le seuil depuis au moins `silence_seconds`
+    Silent,
+}
+
+/// État du traitement d'un canal après un panic attrapé dans le callback
+/// audio (voir `Event::ProcessingPanicked`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelHealth {
+    #[default]
+    Ok,
+    /// Un effet a paniqué : il est sauté, le reste de la chaîne tourne
+    EffectBypassed,
+    /// Le traitement du canal a paniqué hors des effets : il ne joue
+    /// plus que du silence jusqu'à la réouverture du pipeline
+    Silenced,
+}
+
+/// Réglages de la détection de silence ("mon micro est-il mort ?").
+///
+/// # Hystérésis
+/// Un seul seuil ferait "clignoter" l'état quand le niveau oscille
+/// autour. On passe donc en `Silent` sous `threshold_db`, mais on ne
+/// revient en `Active` qu'au-dessus de `threshold_db + hysteresis_db`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SignalPresenceConfig {
+    /// Niveau RMS (dBFS) sous lequel le canal est considéré silencieux
+    #[serde(default = "SignalPresenceConfig::default_threshold_db")]
+    pub threshold_db: f32,
+    /// Durée de silence continue avant de signaler `Silent`, en secondes
+    #[serde(default = "SignalPresenceConfig::default_silence_seconds")]
+    pub silence_seconds: f32,
+    /// Marge au-dessus du seuil pour revenir en `Active`, en dB
+    #[serde(default = "SignalPresenceConfig::default_hysteresis_db")]
+    pub hysteresis_db: f32,
+}
+
+impl SignalPresenceConfig {
+    fn default_threshold_db() -> f32 {
+        -60.0
+    }
+
+    fn default_silence_seconds() -> f32 {
+        30.0
+    }
+
+    fn default_hysteresis_db() -> f32 {
+        6.0
+    }
+}
+
+impl Default for SignalPresenceConfig {
+    fn default() -> Self {
+        Self {
+            threshold_db: Self::default_threshold_db(),
+            silence_seconds: Self::default_silence_seconds(),
+            hysteresis_db: Self::default_hysteresis_db(),
+        }
+    }
+}
+
+/// Courbe de réponse d'un fader de volume (position → dB).
+///
+/// Un fader linéaire en gain concentre tout l'utile dans le dernier
+/// centimètre ; un fader linéaire en dB écrase la zone -20..0 dB.
+/// Les consoles utilisent une courbe "audio" qui donne plus de course
+/// aux niveaux qu'on règle vraiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FaderTaper {
+    /// dB proportionnels à la position (-60 → +6 dB)
+    LinearDb,
+    /// Courbe de console par morceaux (0 dB aux 3/4 de la course)
+    #[default]
+    Audio,
+    /// Gain proportionnel à position⁴
+    Exponential,
 }
 
 /// Une route audio : connecte une entrée à une sortie.
@@ -100,6 +493,259 @@ impl Route {
     }
 }
 
+/// Routage seul, rappelé par son nom : passer de "interview" (invité
+/// vers le stream) à "musique" (invité coupé, musique vers le stream)
+/// sans toucher aux volumes, aux effets ni aux devices.
+///
+/// Gardé dans la config, `[routing_snapshots.<nom>]` :
+///
+/// ```toml
+/// [routing_snapshots.interview]
+/// routes = [{ from = 0, to = 3 }, { from = 5, to = 4 }]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutingSnapshot {
+    #[serde(default)]
+    pub routes: Vec<Route>,
+}
+
+/// Courbe d'un crossfader (position → gains de A et de B).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossfadeCurve {
+    /// A = 1 - x, B = x : creux de -6 dB au centre
+    Linear,
+    /// A = cos, B = sin : la puissance totale reste constante, chaque
+    /// canal est à -3 dB au centre
+    #[default]
+    ConstantPower,
+}
+
+impl CrossfadeCurve {
+    /// Gains (A, B) pour une position de 0.0 (tout A) à 1.0 (tout B).
+    pub fn gains(self, position: f32) -> (f32, f32) {
+        let x = position.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => (1.0 - x, x),
+            Self::ConstantPower => {
+                let angle = x * std::f32::consts::FRAC_PI_2;
+                (angle.cos(), angle.sin())
+            }
+        }
+    }
+}
+
+/// Crossfader "A/B" façon DJ entre deux entrées.
+///
+/// Un seul contrôle fait passer de l'une à l'autre. Son gain se
+/// multiplie à celui des faders sans les toucher : le volume sauvé de
+/// chaque canal reste le sien, annuler un mouvement de fader ne
+/// déplace pas le crossfader.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Crossfade {
+    pub id: u32,
+    pub channel_a: ChannelId,
+    pub channel_b: ChannelId,
+    #[serde(default)]
+    pub curve: CrossfadeCurve,
+    /// 0.0 = tout A, 1.0 = tout B
+    #[serde(default = "Crossfade::centre")]
+    pub position: f32,
+}
+
+impl Crossfade {
+    /// Un crossfader neuf démarre au centre.
+    pub fn new(id: u32, channel_a: ChannelId, channel_b: ChannelId, curve: CrossfadeCurve) -> Self {
+        Self {
+            id,
+            channel_a,
+            channel_b,
+            curve,
+            position: Self::centre(),
+        }
+    }
+
+    fn centre() -> f32 {
+        0.5
+    }
+
+    /// Gain appliqué à `channel` (1.0 s'il n'est ni A ni B).
+    pub fn gain(&self, channel: ChannelId) -> f32 {
+        let (a, b) = self.curve.gains(self.position);
+        if channel == self.channel_a {
+            a
+        } else if channel == self.channel_b {
+            b
+        } else {
+            1.0
+        }
+    }
+
+    /// `true` si `channel` est l'un des deux côtés.
+    pub fn involves(&self, channel: ChannelId) -> bool {
+        self.channel_a == channel || self.channel_b == channel
+    }
+}
+
+/// Lien stéréo entre deux entrées mono voisines (micros L/R d'un
+/// couple, sorties d'une carte en deux canaux).
+///
+/// Le canal de gauche fait foi : volume, mute, solo et effets réglés
+/// sur l'un des deux s'appliquent aux deux. Au mixage, la paire devient
+/// une seule tranche stéréo (L = `left`, R = `right`) routée comme `left`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelLink {
+    pub left: ChannelId,
+    pub right: ChannelId,
+}
+
+impl ChannelLink {
+    pub fn new(left: ChannelId, right: ChannelId) -> Self {
+        Self { left, right }
+    }
+
+    /// `true` si `channel` est l'un des deux côtés.
+    pub fn involves(&self, channel: ChannelId) -> bool {
+        self.left == channel || self.right == channel
+    }
+
+    /// L'autre côté du lien (`None` si `channel` n'en fait pas partie).
+    pub fn partner(&self, channel: ChannelId) -> Option<ChannelId> {
+        if channel == self.left {
+            Some(self.right)
+        } else if channel == self.right {
+            Some(self.left)
+        } else {
+            None
+        }
+    }
+}
+
+/// Bus dont le volume suit celui d'un autre, à un écart fixe
+/// (retour casque toujours 3 dB au-dessus du mix stream).
+///
+/// Un changement du volume de `leader` donne à `follower` le volume du
+/// leader + `offset_db` (borné comme tout volume). Un bus ne suit qu'un
+/// leader ; un leader peut en suivre un autre, sans jamais boucler.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BusFollow {
+    pub follower: ChannelId,
+    pub leader: ChannelId,
+    pub offset_db: f32,
+    /// Ce que fait un réglage direct du volume du suiveur
+    #[serde(default)]
+    pub on_override: FollowOverride,
+}
+
+/// Réglage direct du volume d'un bus qui en suit un autre.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FollowOverride {
+    /// Le lien est défait : le bus garde le volume réglé
+    #[default]
+    Unlink,
+    /// Le lien reste, l'écart devient celui du nouveau volume
+    AdjustOffset,
+}
+
+/// Critères de recherche de canaux (`Command::FindChannels`).
+///
+/// Chaque critère renseigné restreint le résultat (ET logique) ;
+/// une requête vide (`ChannelQuery::default()`) retourne tous les canaux.
+/// Avec 20+ canaux, l'UI filtre côté moteur au lieu de tout rapatrier.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelQuery {
+    /// Morceau du nom, sans tenir compte de la casse ("MIC" trouve "Micro")
+    pub name: Option<String>,
+    pub kind: Option<ChannelKind>,
+    pub muted: Option<bool>,
+    /// Solo du canal pour une entrée, solo de bus pour une sortie
+    pub soloed: Option<bool>,
+    /// Entrées routées vers ce bus
+    pub bus: Option<ChannelId>,
+    /// Nom exact du device assigné
+    pub device: Option<String>,
+}
+
+/// Nombre de canaux par état, pour les badges de l'UI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelCountSummary {
+    pub total: usize,
+    pub inputs: usize,
+    pub outputs: usize,
+    pub submixes: usize,
+    pub muted: usize,
+    pub soloed: usize,
+    /// Canaux sans device assigné
+    pub unassigned: usize,
+}
+
+/// Tout ce qui référence un canal, et que sa suppression retire : à
+/// montrer avant de confirmer (`Event::ChannelDependencies`), puis ce
+/// qui a réellement été nettoyé (`Event::ChannelRemoved`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelDependencies {
+    pub channel: ChannelId,
+    /// Routes qui partent du canal ou y arrivent
+    pub routes: Vec<Route>,
+    /// Canaux dont le retour (`monitor_bus`) est ce bus
+    pub monitored_by: Vec<ChannelId>,
+    /// Crossfaders dont le canal est l'un des côtés
+    pub crossfades: Vec<Crossfade>,
+    /// Paire stéréo (le partenaire redevient mono)
+    pub links: Vec<ChannelLink>,
+    /// Suivis de volume où le canal est suiveur ou meneur
+    pub follows: Vec<BusFollow>,
+    /// Le bus est en solo
+    pub bus_solo: bool,
+    /// Le bus sert au passthrough d'urgence en cours
+    pub emergency_passthrough: bool,
+    /// Ensembles d'actions qui visent le canal : ces actions en sont
+    /// retirées, le reste de l'ensemble est gardé
+    pub action_sets: Vec<String>,
+    /// Le bus est le passthrough d'urgence configuré (`passthrough_bus`)
+    pub passthrough_bus: bool,
+    /// Taps ouverts sur le bus, fermés avec lui
+    pub taps: Vec<u32>,
+    /// Le bus a un replay, libéré avec lui
+    pub replay: bool,
+    /// Le bus part sur le réseau : l'envoi s'arrête
+    pub network_send: bool,
+    /// Le canal joue le flux réseau reçu : la réception s'arrête
+    pub network_receive: bool,
+    /// Le bus joue sur la sortie ouverte : le pipeline est rouvert sans lui
+    pub played: bool,
+    /// Le canal est l'entrée du pipeline (le Mic) : son stream reste
+    /// ouvert, mais plus rien ne l'écoute
+    pub pipeline_input: bool,
+}
+
+impl ChannelDependencies {
+    /// `true` si rien d'autre que le canal lui-même n'est touché.
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+            && self.monitored_by.is_empty()
+            && self.crossfades.is_empty()
+            && self.links.is_empty()
+            && self.follows.is_empty()
+            && !self.bus_solo
+            && !self.emergency_passthrough
+            && self.action_sets.is_empty()
+            && !self.passthrough_bus
+            && self.taps.is_empty()
+            && !self.replay
+            && !self.network_send
+            && !self.network_receive
+            && !self.played
+            && !self.pipeline_input
+    }
+}
+
+/// Cible des niveaux de l'écoute d'un device (`Command::StartDevicePreview`)
+/// dans `LevelUpdate` : aucun canal ne porte cet id.
+pub const PREVIEW_CHANNEL: ChannelId = ChannelId(usize::MAX);
+
 /// Niveau audio mesuré sur un canal (pour les VU-meters).
 ///
 /// # Peak vs RMS
@@ -111,11 +757,185 @@ impl Route {
 ///
 /// Les deux sont en valeur linéaire (0.0 → 1.0+).
 /// Conversion en dB : `20.0 * level.log10()`
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ChannelLevel {
     pub channel: ChannelId,
     pub rms: f32,
     pub peak: f32,
+    /// `rms` placé sur l'échelle des meters (0.0 = plancher, 1.0 = haut)
+    pub rms_position: f32,
+    /// `peak` placé sur l'échelle des meters
+    pub peak_position: f32,
+    /// Sonie du bus, si son loudness meter est activé
+    pub loudness: Option<Loudness>,
+    /// Crête vraie du bus (linéaire, entre les samples), si sa mesure
+    /// est activée (`ChannelConfig::true_peak_meter`)
+    pub true_peak: Option<f32>,
+    /// Le niveau a atteint `MeterScale::clip_db` : crête vraie si elle
+    /// est mesurée, crête des samples sinon
+    pub clipped: bool,
+}
+
+/// Une case de l'historique des niveaux d'un canal
+/// (`Event::LevelHistory`), en valeurs linéaires.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelPoint {
+    /// Début de la case (ms depuis l'epoch)
+    pub timestamp_ms: u64,
+    /// Crête la plus haute de la case
+    pub peak: f32,
+    /// RMS de la case (moyenne quadratique des RMS mesurés)
+    pub rms: f32,
+}
+
+/// Issue du réglage automatique du niveau d'un canal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoLevelStatus {
+    /// Volume corrigé pour amener la crête sur la cible
+    Applied,
+    /// Rien entendu pendant la mesure : volume inchangé
+    Silent,
+    /// Il faudrait monter de plus que le plafond de sécurité (micro
+    /// mal branché ?) : volume inchangé
+    BoostRefused,
+}
+
+/// Résultat du réglage automatique d'un canal
+/// (`Event::AutoLevelChannel`).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AutoLevelResult {
+    pub channel: ChannelId,
+    /// Crête la plus haute de la mesure, en dBFS (`None` : silence)
+    pub measured_peak_db: Option<f32>,
+    /// Correction demandée pour atteindre la cible, en dB
+    pub needed_gain_db: f32,
+    /// Correction réellement appliquée (volume borné à 2.0), en dB
+    pub applied_gain_db: f32,
+    /// Volume du canal après réglage
+    pub volume: f32,
+    pub status: AutoLevelStatus,
+}
+
+/// Plancher des meters par défaut, en dBFS : en dessous, un niveau
+/// s'affiche (et retombe) à zéro.
+pub const METER_MIN_DB: f32 = -60.0;
+
+/// Haut de l'échelle des meters, en dBFS.
+pub const METER_MAX_DB: f32 = 0.0;
+
+/// Début de la zone orange des meters par défaut, en dBFS.
+pub const METER_WARN_DB: f32 = -18.0;
+
+/// Niveau à partir duquel un meter signale l'écrêtage, en dBFS.
+pub const METER_CLIP_DB: f32 = 0.0;
+
+/// Plancher le plus bas accepté : en dessous, le bruit de quantification
+/// d'une sortie 24 bits n'a plus rien à montrer.
+const METER_LOWEST_DB: f32 = -144.0;
+
+/// Écart minimal entre le plancher et le haut de l'échelle.
+const METER_MIN_RANGE_DB: f32 = 6.0;
+
+/// Échelle des VU-meters, la même pour le moteur et toutes les UIs.
+///
+/// Le moteur s'en sert pour le plancher de ses niveaux (mesure et
+/// retombée) et pour placer chaque niveau entre 0.0 et 1.0 dans les
+/// `LevelUpdate` : une UI n'a plus à refaire la conversion dB → barre.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MeterScale {
+    pub min_db: f32,
+    pub max_db: f32,
+    pub warn_db: f32,
+    pub clip_db: f32,
+}
+
+impl Default for MeterScale {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl MeterScale {
+    /// Échelle par défaut, plancher et seuil d'alerte éventuellement
+    /// surchargés (`controls.meter_min_db`, `controls.meter_warn_db`).
+    ///
+    /// Le plancher est ramené entre -144 dB et 6 dB sous le haut de
+    /// l'échelle, le seuil d'alerte entre le plancher et l'écrêtage.
+    pub fn new(min_db: Option<f32>, warn_db: Option<f32>) -> Self {
+        let min_db = min_db
+            .filter(|db| db.is_finite())
+            .unwrap_or(METER_MIN_DB)
+            .clamp(METER_LOWEST_DB, METER_MAX_DB - METER_MIN_RANGE_DB);
+        let warn_db = warn_db
+            .filter(|db| db.is_finite())
+            .unwrap_or(METER_WARN_DB)
+            .clamp(min_db, METER_CLIP_DB);
+        Self {
+            min_db,
+            max_db: METER_MAX_DB,
+            warn_db,
+            clip_db: METER_CLIP_DB,
+        }
+    }
+
+    /// Plancher en gain linéaire.
+    pub fn floor(&self) -> f32 {
+        10.0_f32.powf(self.min_db / 20.0)
+    }
+
+    /// Position d'un niveau en dB sur l'échelle (0.0 → 1.0).
+    pub fn position_db(&self, db: f32) -> f32 {
+        ((db - self.min_db) / (self.max_db - self.min_db)).clamp(0.0, 1.0)
+    }
+
+    /// Position d'un niveau linéaire (0.0 pour le silence).
+    pub fn position(&self, level: f32) -> f32 {
+        if level <= 0.0 {
+            return 0.0;
+        }
+        self.position_db(20.0 * level.log10())
+    }
+
+    /// Un niveau linéaire atteint-il le seuil d'écrêtage ?
+    pub fn clips(&self, level: f32) -> bool {
+        level > 0.0 && 20.0 * level.log10() >= self.clip_db
+    }
+
+    /// Graduations conseillées, en dB, du plancher au haut de l'échelle.
+    ///
+    /// Un pas "rond" (3, 6, 10, 12 ou 20 dB) qui donne au plus dix
+    /// intervalles, compté depuis le haut ; le plancher est toujours
+    /// gradué.
+    pub fn ticks(&self) -> Vec<f32> {
+        let range = self.max_db - self.min_db;
+        let step = [3.0, 6.0, 10.0, 12.0, 20.0]
+            .into_iter()
+            .find(|step| range / step <= 10.0)
+            .unwrap_or(20.0);
+        let mut ticks: Vec<f32> = (0..)
+            .map(|i| self.max_db - i as f32 * step)
+            .take_while(|db| *db > self.min_db)
+            .collect();
+        ticks.push(self.min_db);
+        ticks.reverse();
+        ticks
+    }
+}
+
+/// Sonie EBU R128 d'un bus, en LUFS.
+///
+/// `None` tant que la fenêtre n'a pas vu assez d'audio (400 ms pour la
+/// momentanée, 3 s pour le court terme) ou pour un silence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Loudness {
+    /// Fenêtre de 400 ms
+    pub momentary: Option<f32>,
+    /// Fenêtre de 3 s
+    pub short_term: Option<f32>,
+    /// Depuis l'activation ou la dernière remise à zéro, blocs
+    /// silencieux et très calmes exclus
+    pub integrated: Option<f32>,
 }
 
 /// État complet du mixer, sérialisable pour la config.
@@ -123,6 +943,12 @@ pub struct ChannelLevel {
 pub struct MixerConfig {
     pub channels: Vec<ChannelConfig>,
     pub routes: Vec<Route>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub crossfades: Vec<Crossfade>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<ChannelLink>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bus_follows: Vec<BusFollow>,
 }
 
 impl MixerConfig {
@@ -130,11 +956,11 @@ impl MixerConfig {
     pub fn default_setup() -> Self {
         Self {
             channels: vec![
-                ChannelConfig::input(0, "Mic"),
-                ChannelConfig::input(1, "Desktop"),
-                ChannelConfig::input(2, "Browser"),
-                ChannelConfig::output(3, "Headphones"),
-                ChannelConfig::output(4, "Speakers"),
+                ChannelConfig::input(0, "Mic").keyed("channel.mic"),
+                ChannelConfig::input(1, "Desktop").keyed("channel.desktop"),
+                ChannelConfig::input(2, "Browser").keyed("channel.browser"),
+                ChannelConfig::output(3, "Headphones").keyed("bus.headphones"),
+                ChannelConfig::output(4, "Speakers").keyed("bus.speakers"),
             ],
             routes: vec![
                 // Par défaut : tout va dans les écouteurs
@@ -142,6 +968,9 @@ impl MixerConfig {
                 Route::new(ChannelId(1), ChannelId(3)), // Desktop → Headphones
                 Route::new(ChannelId(2), ChannelId(3)), // Browser → Headphones
             ],
+            crossfades: Vec::new(),
+            links: Vec::new(),
+            bus_follows: Vec::new(),
         }
     }
 
@@ -304,4 +1133,135 @@ mod tests {
         assert_eq!(r1, r2);
         assert_ne!(r1, r3);
     }
+
+    #[test]
+    fn channel_config_without_delay_still_parses() {
+        let toml_str = r#"
+            id = 0
+            name = "Mic"
+            kind = "Input"
+            volume = 1.0
+            muted = false
+            solo = false
+            pan = 0.0
+        "#;
+        let ch: ChannelConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(ch.delay_ms, 0.0);
+        assert_eq!(ch.channel_count, ChannelCount::Stereo);
+
+        let mono: ChannelConfig =
+            toml::from_str(&format!("{toml_str}\nchannel_count = \"mono\"")).unwrap();
+        assert_eq!(mono.channel_count, ChannelCount::Mono);
+    }
+
+    #[test]
+    fn buses_sum_their_priority_channels_first() {
+        let mut bus = ChannelConfig::output(3, "Headphones");
+        let sources = [ChannelId(2), ChannelId(0), ChannelId(5), ChannelId(1)];
+        assert_eq!(bus.sum_order(&sources), [0, 1, 2, 5].map(ChannelId));
+        // Un canal prioritaire absent des sources est ignoré
+        bus.sum_priority = vec![ChannelId(5), ChannelId(9), ChannelId(1)];
+        assert_eq!(bus.sum_order(&sources), [5, 1, 0, 2].map(ChannelId));
+
+        let parsed: ChannelConfig = toml::from_str(&toml::to_string(&bus).unwrap()).unwrap();
+        assert_eq!(parsed.sum_priority, bus.sum_priority);
+        let plain = toml::to_string(&ChannelConfig::output(3, "Headphones")).unwrap();
+        assert!(!plain.contains("sum_priority"));
+    }
+
+    #[test]
+    fn input_fallback_round_trips() {
+        for fallback in [
+            InputFallback::Silence,
+            InputFallback::Devices(vec!["USB Mic".into(), "Built-in Mic".into()]),
+            InputFallback::File(PathBuf::from("/tmp/hold.wav")),
+        ] {
+            let mut ch = ChannelConfig::input(0, "Mic");
+            ch.input_fallback = fallback.clone();
+            let parsed: ChannelConfig = toml::from_str(&toml::to_string(&ch).unwrap()).unwrap();
+            assert_eq!(parsed.input_fallback, fallback);
+        }
+        let plain = toml::to_string(&ChannelConfig::input(0, "Mic")).unwrap();
+        assert!(!plain.contains("input_fallback"));
+    }
+
+    #[test]
+    fn device_assignment_round_trips_and_reads_legacy_names() {
+        let legacy: ChannelConfig = toml::from_str(
+            r#"
+            id = 0
+            name = "Mic"
+            kind = "Input"
+            volume = 1.0
+            muted = false
+            solo = false
+            pan = 0.0
+            device_name = "Blue Yeti"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            legacy.device_name,
+            DeviceAssignment::Specific("Blue Yeti".into())
+        );
+
+        for assignment in [
+            DeviceAssignment::None,
+            DeviceAssignment::SystemDefault,
+            DeviceAssignment::Specific("HD 600".into()),
+        ] {
+            let mut ch = ChannelConfig::output(4, "A1");
+            ch.device_name = assignment.clone();
+            let parsed: ChannelConfig = toml::from_str(&toml::to_string(&ch).unwrap()).unwrap();
+            assert_eq!(parsed.device_name, assignment);
+        }
+
+        let follow = DeviceAssignment::SystemDefault;
+        assert_eq!(follow.resolve(Some("Speakers")), Some("Speakers"));
+        assert_eq!(DeviceAssignment::None.resolve(Some("Speakers")), None);
+    }
+
+    #[test]
+    fn meter_scale_places_levels_between_floor_and_top() {
+        let scale = MeterScale::default();
+        assert_eq!(scale.position(0.0), 0.0);
+        assert_eq!(scale.position(1.0), 1.0);
+        // Au-dessus de 0 dBFS ou sous le plancher : bornés
+        assert_eq!(scale.position(2.0), 1.0);
+        assert_eq!(scale.position_db(-90.0), 0.0);
+        assert!((scale.position_db(-30.0) - 0.5).abs() < 1e-6);
+        assert!((scale.floor() - 0.001).abs() < 1e-7);
+        // Écrêtage à partir de 0 dBFS, silence jamais
+        assert!(scale.clips(1.0) && scale.clips(1.4));
+        assert!(!scale.clips(0.99) && !scale.clips(0.0));
+
+        let broadcast = MeterScale::new(Some(-48.0), None);
+        assert!((broadcast.position_db(-24.0) - 0.5).abs() < 1e-6);
+        assert_eq!(broadcast.position_db(-48.0), 0.0);
+    }
+
+    #[test]
+    fn meter_scale_overrides_are_kept_in_range() {
+        // Plancher trop haut : 6 dB d'échelle au moins
+        assert_eq!(MeterScale::new(Some(0.0), None).min_db, -6.0);
+        assert_eq!(MeterScale::new(Some(-500.0), None).min_db, -144.0);
+        assert_eq!(MeterScale::new(Some(f32::NAN), None).min_db, METER_MIN_DB);
+        // L'alerte reste entre le plancher et l'écrêtage
+        assert_eq!(MeterScale::new(Some(-48.0), Some(-70.0)).warn_db, -48.0);
+        assert_eq!(MeterScale::new(None, Some(3.0)).warn_db, 0.0);
+    }
+
+    #[test]
+    fn meter_ticks_are_round_and_include_the_floor() {
+        let ticks = MeterScale::default().ticks();
+        assert_eq!(ticks.first(), Some(&-60.0));
+        assert_eq!(ticks.last(), Some(&0.0));
+        assert_eq!(ticks.len(), 11);
+        assert!(ticks.windows(2).all(|w| w[1] - w[0] == 6.0));
+
+        // -50 n'est pas un multiple du pas : gradué quand même
+        let ticks = MeterScale::new(Some(-50.0), None).ticks();
+        assert_eq!(&ticks[..2], &[-50.0, -48.0]);
+        assert_eq!(MeterScale::new(Some(-6.0), None).ticks(), [-6.0, -3.0, 0.0]);
+    }
 }
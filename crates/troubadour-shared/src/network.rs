@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+/// Envoi d'un bus vers une autre instance de Troubadour (`[network_send]`).
+///
+/// Pensé pour un réseau local (un second PC d'encodage) : PCM brut sur
+/// UDP, sans chiffrement ni correction d'erreur.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSendConfig {
+    /// Nom du bus envoyé (celui que joue le device de sortie)
+    pub bus: String,
+    pub host: String,
+    pub port: u16,
+    /// Samples en 16 bits au lieu de f32 : deux fois moins de débit,
+    /// bruit de quantification à -96 dB
+    #[serde(default)]
+    pub int16: bool,
+    /// Démarré avec le moteur
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+/// Réception d'un flux réseau comme entrée du mixer (`[network_receive]`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkReceiveConfig {
+    /// Nom du canal d'entrée qui joue le flux reçu
+    pub channel: String,
+    /// Adresse d'écoute ("0.0.0.0" : toutes les interfaces)
+    #[serde(default = "NetworkReceiveConfig::default_bind")]
+    pub bind: String,
+    pub port: u16,
+    /// Paquets gardés d'avance avant de jouer : plus = plus de marge
+    /// contre la gigue du réseau, plus de latence (~2.7 ms par paquet
+    /// à 48 kHz)
+    #[serde(default = "NetworkReceiveConfig::default_jitter_packets")]
+    pub jitter_packets: usize,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl NetworkReceiveConfig {
+    fn default_bind() -> String {
+        "0.0.0.0".to_string()
+    }
+
+    fn default_jitter_packets() -> usize {
+        4
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Compteurs du transport réseau (`Event::NetworkStats`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkStats {
+    /// Un envoi est configuré
+    pub sending: bool,
+    pub packets_sent: u64,
+    /// Paquets que le socket a refusés (buffer d'envoi plein, réseau absent)
+    pub send_errors: u64,
+    /// Une réception est configurée
+    pub receiving: bool,
+    pub packets_received: u64,
+    /// Paquets jamais arrivés, remplacés par du silence
+    pub packets_lost: u64,
+    /// Paquets arrivés trop tard (déjà remplacés par du silence) ou
+    /// illisibles (autre programme, autre version)
+    pub packets_dropped: u64,
+    /// Paquets en attente dans le tampon anti-gigue
+    pub jitter_depth: usize,
+    /// Tampon vide au moment de jouer : silence, puis remplissage
+    pub underruns: u64,
+    /// Sample rate annoncé par l'émetteur, `None` avant le premier paquet
+    #[serde(default)]
+    pub remote_sample_rate: Option<u32>,
+}
@@ -0,0 +1,1000 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{config_dir, write_atomic};
+use crate::dsp::EffectsPreset;
+use crate::error::{TroubadourError, TroubadourResult};
+use crate::unknown_keys::unknown_key_warnings;
+
+/// Longueur maximum d'un nom de preset.
+pub const MAX_PRESET_NAME_LEN: usize = 64;
+
+/// Ancien dossier des presets, relatif au dossier COURANT : lancée du
+/// bureau ou d'un terminal, l'application ne voyait pas les mêmes
+/// presets. Son contenu est recopié au démarrage (`migrate_from`).
+pub const LEGACY_PRESET_DIR: &str = "presets";
+
+/// Suffixe d'un preset migré dont le nom est déjà pris.
+const MIGRATED_SUFFIX: &str = "-migrated";
+
+/// Fichier de la bibliothèque qui liste les anciens dossiers déjà
+/// migrés (un chemin absolu par ligne).
+const MIGRATION_MARKER: &str = ".migrated-from";
+
+/// Bilan d'une migration de presets (`migrate_from`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PresetMigration {
+    /// Recopiés sous leur nom
+    pub copied: Vec<String>,
+    /// Nom déjà pris par un autre preset : (ancien nom, nouveau nom)
+    pub renamed: Vec<(String, String)>,
+    /// Déjà présents à l'identique
+    pub unchanged: usize,
+    /// Fichiers `.toml` qui ne sont pas des presets d'effets, laissés
+    pub skipped: Vec<String>,
+}
+
+impl PresetMigration {
+    /// Rien n'a été recopié.
+    pub fn is_empty(&self) -> bool {
+        self.copied.is_empty() && self.renamed.is_empty()
+    }
+}
+
+impl std::fmt::Display for PresetMigration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} preset(s) copied, {} renamed, {} already present",
+            self.copied.len(),
+            self.renamed.len(),
+            self.unchanged
+        )?;
+        for (from, to) in &self.renamed {
+            write!(f, "; \"{from}\" → \"{to}\"")?;
+        }
+        if !self.skipped.is_empty() {
+            write!(f, "; not presets: {}", self.skipped.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Un favori tel que listé : son preset a pu être supprimé ou renommé
+/// hors de l'application depuis.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FavoritePreset {
+    pub name: String,
+    /// Plus de fichier à ce nom dans la bibliothèque
+    pub missing: bool,
+}
+
+/// Résumé d'un preset pour le sélecteur, sans la chaîne complète.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetSummary {
+    pub name: String,
+    /// Effets actifs, dans l'ordre de la chaîne
+    pub effects: Vec<String>,
+    /// Paramètres hors plage (corrigés à l'application)
+    pub out_of_range: usize,
+    /// Fichier illisible : pourquoi (le reste est vide)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl PresetSummary {
+    /// Lit et résume le fichier `path`.
+    fn read(name: &str, path: &Path) -> Self {
+        match EffectsPresetManager::load_file(path) {
+            Ok((preset, _)) => Self {
+                name: name.to_string(),
+                effects: preset
+                    .enabled_effects()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect(),
+                out_of_range: preset.validate().len(),
+                error: None,
+            },
+            Err(e) => Self {
+                name: name.to_string(),
+                effects: Vec::new(),
+                out_of_range: 0,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+}
+
+/// Compteurs du cache des résumés, pour le diagnostic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetCacheStats {
+    /// Résumés gardés
+    pub entries: usize,
+    /// Résumés servis sans relire le fichier
+    pub hits: u64,
+    /// Fichiers lus et parsés
+    pub misses: u64,
+}
+
+/// Écart sous lequel un mtime ne prouve rien : un fichier réécrit dans
+/// la même seconde (systèmes de fichiers à mtime grossier) garde son
+/// mtime. Un résumé pris moins de `RACY_WINDOW` après la dernière
+/// écriture est relu à l'appel suivant.
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Résumé d'un fichier, valable tant que son mtime et sa taille restent.
+#[derive(Debug)]
+struct CachedSummary {
+    modified: SystemTime,
+    size: u64,
+    cached_at: SystemTime,
+    summary: PresetSummary,
+}
+
+impl CachedSummary {
+    fn is_fresh(&self, modified: SystemTime, size: u64) -> bool {
+        self.modified == modified
+            && self.size == size
+            && self
+                .cached_at
+                .duration_since(self.modified)
+                .is_ok_and(|age| age >= RACY_WINDOW)
+    }
+}
+
+/// Résumés déjà parsés, par chemin de fichier.
+#[derive(Debug, Default)]
+struct PresetCache {
+    entries: HashMap<PathBuf, CachedSummary>,
+    hits: u64,
+    misses: u64,
+}
+
+impl PresetCache {
+    /// Résumé de `path` : celui du cache si le fichier n'a pas bougé,
+    /// sinon relu. Le mtime est lu AVANT le fichier : une écriture
+    /// pendant la lecture change le mtime, et l'entrée sera relue.
+    fn summary(&mut self, name: &str, path: &Path, now: SystemTime) -> PresetSummary {
+        let stamp = std::fs::metadata(path)
+            .ok()
+            .and_then(|meta| Some((meta.modified().ok()?, meta.len())));
+        if let Some((modified, size)) = stamp
+            && let Some(entry) = self.entries.get(path)
+            && entry.is_fresh(modified, size)
+        {
+            self.hits += 1;
+            return entry.summary.clone();
+        }
+        self.misses += 1;
+        let summary = PresetSummary::read(name, path);
+        match stamp {
+            Some((modified, size)) => {
+                self.entries.insert(
+                    path.to_path_buf(),
+                    CachedSummary {
+                        modified,
+                        size,
+                        cached_at: now,
+                        summary: summary.clone(),
+                    },
+                );
+            }
+            None => {
+                self.entries.remove(path);
+            }
+        }
+        summary
+    }
+}
+
+/// Presets d'effets favoris, dans l'ordre choisi par l'utilisateur (le
+/// premier est rappelé par la touche `1`).
+///
+/// Un favori dont le fichier a disparu reste dans la liste, signalé
+/// par `EffectsPresetManager::favorites` : le fichier revient peut-être
+/// (dossier synchronisé), et la place choisie ne doit pas se perdre.
+/// Supprimer ou renommer un preset par l'application met la liste à
+/// jour (`preset_deleted`, `preset_renamed`).
+///
+/// En TOML, une simple liste : `favorites = ["Voix", "Podcast"]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FavoritePresets(Vec<String>);
+
+impl FavoritePresets {
+    /// Favoris dans cet ordre ; un nom répété ne compte qu'une fois.
+    pub fn new(names: impl IntoIterator<Item = String>) -> Self {
+        let mut favorites = Self::default();
+        for name in names {
+            if !favorites.contains(&name) {
+                favorites.0.push(name);
+            }
+        }
+        favorites
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.iter().any(|n| n == name)
+    }
+
+    /// Favori à la place `position` (0 = le premier).
+    pub fn get(&self, position: usize) -> Option<&str> {
+        self.0.get(position).map(String::as_str)
+    }
+
+    /// Ajoute `name` à la place `position` (`None` ou au-delà de la
+    /// fin : en dernier). Déjà favori : il est déplacé.
+    pub fn add(&mut self, name: &str, position: Option<usize>) {
+        self.remove(name);
+        let position = position.unwrap_or(self.0.len()).min(self.0.len());
+        self.0.insert(position, name.to_string());
+    }
+
+    /// Retire `name` ; `false` s'il n'était pas favori.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.0.len();
+        self.0.retain(|n| n != name);
+        self.0.len() != before
+    }
+
+    /// Remplace l'ordre : `order` doit reprendre chaque favori une et
+    /// une seule fois, sinon rien ne change.
+    pub fn reorder(&mut self, order: &[String]) -> TroubadourResult<()> {
+        let mut sorted = order.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        let mut current = self.0.clone();
+        current.sort();
+        if sorted.len() != order.len() || sorted != current {
+            return Err(TroubadourError::ConfigError(
+                "the new order must list every favorite preset exactly once".to_string(),
+            ));
+        }
+        self.0 = order.to_vec();
+        Ok(())
+    }
+
+    /// Le preset `name` a été supprimé : il quitte les favoris.
+    pub fn preset_deleted(&mut self, name: &str) {
+        self.remove(name);
+    }
+
+    /// Le preset `from` s'appelle désormais `to` : le favori garde sa place.
+    pub fn preset_renamed(&mut self, from: &str, to: &str) {
+        if let Some(entry) = self.0.iter_mut().find(|n| *n == from) {
+            *entry = to.to_string();
+        }
+    }
+}
+
+/// Vérifie qu'un nom de preset est utilisable comme nom de fichier.
+///
+/// # Pourquoi être aussi strict ?
+/// Le nom devient un chemin : `presets/effects/<nom>.toml`.
+/// Un nom comme `../../.bashrc` écrirait HORS du dossier des presets.
+/// On n'accepte donc que lettres, chiffres, espaces, `-` et `_`
+/// (pas de `/`, `\`, `.`, ni caractères de contrôle).
+/// Les lettres accentuées sont acceptées ("Voix grave", "Réglage été").
+pub fn validate_preset_name(name: &str) -> TroubadourResult<()> {
+    let invalid = |reason: &str| {
+        Err(TroubadourError::InvalidPresetName(format!(
+            "{name:?}: {reason}"
+        )))
+    };
+
+    if name.trim().is_empty() {
+        return invalid("name is empty");
+    }
+    if name.chars().count() > MAX_PRESET_NAME_LEN {
+        return invalid("name is too long");
+    }
+    if name != name.trim() {
+        return invalid("leading or trailing whitespace");
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || *c == ' ' || *c == '-' || *c == '_'))
+    {
+        return invalid(&format!("character {c:?} is not allowed"));
+    }
+    Ok(())
+}
+
+/// Tire un nom de preset valide d'un nom quelconque (nom de fichier
+/// déposé, saisie libre) : les caractères refusés par
+/// `validate_preset_name` deviennent `_`, les espaces en trop et la
+/// longueur sont rognés. `None` s'il ne reste rien d'utilisable.
+pub fn sanitize_preset_name(raw: &str) -> Option<String> {
+    let replaced: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_PRESET_NAME_LEN)
+        .collect();
+    let name = replaced.trim();
+    (!name.is_empty() && name.chars().any(|c| c != '_')).then(|| name.to_string())
+}
+
+/// Bibliothèque de presets d'effets, indépendante des profils complets.
+///
+/// # Profil vs preset d'effets
+/// Un `Profile` sauve TOUT le mixer. Un preset d'effets ne contient
+/// qu'une chaîne DSP ("Ma voix" = gate + EQ + compresseur) qu'on peut
+/// appliquer à n'importe quel canal.
+///
+/// Chaque preset est un petit fichier TOML : `<dossier>/<nom>.toml`.
+///
+/// # Cache des résumés
+/// `summaries` parse chaque fichier : avec quelques centaines de
+/// presets, le sélecteur rame. Les résumés sont gardés par fichier avec
+/// son mtime et sa taille, et relus dès que l'un des deux change, même
+/// si le fichier a été modifié hors de l'application. Le dossier est
+/// relu à chaque appel : un preset ajouté ou supprimé se voit toujours.
+pub struct EffectsPresetManager {
+    dir: PathBuf,
+    /// Derrière un `Mutex` : les lectures restent en `&self`
+    cache: Mutex<PresetCache>,
+}
+
+impl EffectsPresetManager {
+    /// Crée un gestionnaire sur un dossier donné (créé à la première sauvegarde).
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Dossier par défaut : `<config>/presets/effects/`.
+    pub fn default_dir() -> PathBuf {
+        config_dir().join("presets").join("effects")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Chemin du fichier d'un preset (après validation du nom).
+    pub fn path_for(&self, name: &str) -> TroubadourResult<PathBuf> {
+        validate_preset_name(name)?;
+        Ok(self.dir.join(format!("{name}.toml")))
+    }
+
+    /// Sauvegarde une chaîne d'effets sous un nom.
+    ///
+    /// # Écraser, seulement sur demande
+    /// Un preset du même nom n'est remplacé que si `overwrite` est vrai ;
+    /// sinon `PresetExists`, et l'UI demande confirmation. L'ancienne
+    /// version est gardée dans `<nom>.toml.bak` (ignoré par `list`).
+    /// L'écriture est atomique : un crash en pleine sauvegarde laisse
+    /// l'ancien preset intact.
+    pub fn save(
+        &self,
+        name: &str,
+        preset: &EffectsPreset,
+        overwrite: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.path_for(name)?;
+        if path.exists() {
+            if !overwrite {
+                return Err(TroubadourError::PresetExists(name.to_string()).into());
+            }
+            std::fs::copy(&path, self.dir.join(format!("{name}.toml.bak")))?;
+        }
+        let preset = EffectsPreset {
+            name: name.to_string(),
+            ..preset.clone()
+        };
+        write_atomic(&path, toml::to_string_pretty(&preset)?.as_bytes())?;
+        self.invalidate(&path);
+        Ok(())
+    }
+
+    /// Charge un preset par son nom ; retourne aussi ses clés inconnues.
+    pub fn load(
+        &self,
+        name: &str,
+    ) -> Result<(EffectsPreset, Vec<String>), Box<dyn std::error::Error>> {
+        let path = self.path_for(name)?;
+        if !path.exists() {
+            return Err(TroubadourError::PresetNotFound(name.to_string()).into());
+        }
+        let (mut preset, warnings) = Self::load_file(&path)?;
+        // Le nom du fichier fait foi (le fichier a pu être renommé à la main)
+        preset.name = name.to_string();
+        Ok((preset, warnings))
+    }
+
+    /// Lit un fichier de preset n'importe où (pas seulement dans la
+    /// bibliothèque), tel quel : paramètres hors plage compris. Retourne
+    /// aussi ses clés inconnues (fautes de frappe, voir `unknown_keys`),
+    /// ignorées au chargement.
+    pub fn load_file(
+        path: &Path,
+    ) -> Result<(EffectsPreset, Vec<String>), Box<dyn std::error::Error>> {
+        let input: toml::Value = toml::from_str(&std::fs::read_to_string(path)?)?;
+        let preset: EffectsPreset = input.clone().try_into()?;
+        let warnings = unknown_key_warnings(&input, &preset);
+        Ok((preset, warnings))
+    }
+
+    /// Supprime un preset.
+    pub fn delete(&self, name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let path = self.path_for(name)?;
+        if !path.exists() {
+            return Err(TroubadourError::PresetNotFound(name.to_string()).into());
+        }
+        std::fs::remove_file(&path)?;
+        self.invalidate(&path);
+        Ok(())
+    }
+
+    /// Renomme un preset. Le nouveau nom ne doit pas être pris : un
+    /// renommage n'écrase jamais un autre preset.
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let source = self.path_for(from)?;
+        let target = self.path_for(to)?;
+        if !source.exists() {
+            return Err(TroubadourError::PresetNotFound(from.to_string()).into());
+        }
+        if target.exists() {
+            return Err(TroubadourError::PresetExists(to.to_string()).into());
+        }
+        // `load` prend le nom du fichier : le renommer suffit
+        std::fs::rename(&source, target)?;
+        self.invalidate(&source);
+        Ok(())
+    }
+
+    /// Les favoris dans leur ordre, chacun vérifié sur le disque.
+    pub fn favorites(&self, favorites: &FavoritePresets) -> Vec<FavoritePreset> {
+        favorites
+            .names()
+            .iter()
+            .map(|name| FavoritePreset {
+                name: name.clone(),
+                missing: !self.path_for(name).is_ok_and(|path| path.exists()),
+            })
+            .collect()
+    }
+
+    /// Liste les noms des presets disponibles, triés alphabétiquement.
+    ///
+    /// Les fichiers dont le nom n'est pas un nom de preset valide
+    /// sont ignorés. Dossier absent = liste vide (pas une erreur).
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|p| p.file_stem()?.to_str().map(str::to_string))
+            .filter(|name| validate_preset_name(name).is_ok())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Résumés des presets, dans l'ordre de `list`. Seuls les fichiers
+    /// nouveaux ou modifiés depuis l'appel précédent sont parsés ; les
+    /// résumés des fichiers disparus sont oubliés.
+    pub fn summaries(&self) -> Vec<PresetSummary> {
+        let names = self.list();
+        let now = SystemTime::now();
+        let mut cache = self.lock_cache();
+        let paths: HashSet<PathBuf> = names
+            .iter()
+            .map(|name| self.dir.join(format!("{name}.toml")))
+            .collect();
+        let summaries = names
+            .iter()
+            .map(|name| cache.summary(name, &self.dir.join(format!("{name}.toml")), now))
+            .collect();
+        cache.entries.retain(|path, _| paths.contains(path));
+        summaries
+    }
+
+    /// Oublie le résumé d'un fichier (écrit par l'application, ou signalé
+    /// par une surveillance du dossier).
+    pub fn invalidate(&self, path: &Path) {
+        self.lock_cache().entries.remove(path);
+    }
+
+    /// Oublie tous les résumés : le prochain `summaries` relit tout.
+    /// Les compteurs sont gardés.
+    pub fn refresh_cache(&self) {
+        self.lock_cache().entries.clear();
+    }
+
+    pub fn cache_stats(&self) -> PresetCacheStats {
+        let cache = self.lock_cache();
+        PresetCacheStats {
+            entries: cache.entries.len(),
+            hits: cache.hits,
+            misses: cache.misses,
+        }
+    }
+
+    /// Un panic pendant une lecture ne laisse qu'un cache incomplet.
+    fn lock_cache(&self) -> std::sync::MutexGuard<'_, PresetCache> {
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Recopie les presets de `legacy` dans la bibliothèque.
+    ///
+    /// # Une seule fois par dossier
+    /// Le chemin de `legacy` est ensuite noté dans la bibliothèque
+    /// (`.migrated-from`) : aux démarrages suivants, ce dossier est
+    /// ignoré, même si son contenu a changé. Un preset déjà présent à
+    /// l'identique (sous son nom ou un nom migré) est sauté. Un nom pris
+    /// par un AUTRE preset n'est jamais écrasé : la copie prend le
+    /// suffixe `-migrated` (puis `-migrated-2`…).
+    ///
+    /// Seuls les fichiers qui se lisent comme des presets d'effets sont
+    /// recopiés (`skipped` : les autres `.toml`). Les fichiers de
+    /// `legacy` ne sont pas supprimés (le dossier est peut-être celui
+    /// d'une autre application). Dossier absent, déjà migré ou confondu
+    /// avec la bibliothèque : bilan vide.
+    pub fn migrate_from(
+        &self,
+        legacy: &Path,
+    ) -> Result<PresetMigration, Box<dyn std::error::Error>> {
+        let mut report = PresetMigration::default();
+        if !legacy.is_dir() || same_dir(legacy, &self.dir) {
+            return Ok(report);
+        }
+        let legacy_path = legacy.canonicalize()?.display().to_string();
+        let marker = self.dir.join(MIGRATION_MARKER);
+        let migrated = std::fs::read_to_string(&marker).unwrap_or_default();
+        if migrated.lines().any(|line| line == legacy_path) {
+            return Ok(report);
+        }
+        for name in Self::new(legacy).list() {
+            let source = legacy.join(format!("{name}.toml"));
+            if Self::load_file(&source).is_err() {
+                report.skipped.push(name);
+                continue;
+            }
+            let contents = std::fs::read(&source)?;
+            let candidates = std::iter::once(name.clone())
+                .chain(std::iter::once(format!("{name}{MIGRATED_SUFFIX}")))
+                .chain((2..).map(|n| format!("{name}{MIGRATED_SUFFIX}-{n}")));
+            for candidate in candidates {
+                // Nom trop long une fois suffixé : rien de mieux à tenter
+                let path = self.path_for(&candidate)?;
+                match std::fs::read(&path) {
+                    Ok(existing) if existing == contents => {
+                        report.unchanged += 1;
+                        break;
+                    }
+                    Ok(_) => continue,
+                    Err(_) => {
+                        write_atomic(&path, &contents)?;
+                        if candidate == name {
+                            report.copied.push(name.clone());
+                        } else {
+                            report.renamed.push((name.clone(), candidate));
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+        write_atomic(&marker, format!("{migrated}{legacy_path}\n").as_bytes())?;
+        Ok(report)
+    }
+}
+
+/// Les deux chemins désignent-ils le même dossier ?
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+impl Default for EffectsPresetManager {
+    fn default() -> Self {
+        Self::new(Self::default_dir())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_manager(tag: &str) -> (EffectsPresetManager, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "troubadour-fx-presets-{tag}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        (EffectsPresetManager::new(&dir), dir)
+    }
+
+    #[test]
+    fn valid_names() {
+        assert!(validate_preset_name("My Vocal Chain").is_ok());
+        assert!(validate_preset_name("voix-grave_2").is_ok());
+        assert!(validate_preset_name("Réglage été").is_ok());
+    }
+
+    #[test]
+    fn invalid_names() {
+        for name in [
+            "",
+            "   ",
+            "../evil",
+            "a/b",
+            "a\\b",
+            ".hidden",
+            "x.toml",
+            " padded",
+            "tab\there",
+        ] {
+            assert!(
+                validate_preset_name(name).is_err(),
+                "{name:?} should be rejected"
+            );
+        }
+        let long = "a".repeat(MAX_PRESET_NAME_LEN + 1);
+        assert!(validate_preset_name(&long).is_err());
+    }
+
+    #[test]
+    fn sanitized_names_are_valid() {
+        assert_eq!(
+            sanitize_preset_name("Late Show").as_deref(),
+            Some("Late Show")
+        );
+        assert_eq!(
+            sanitize_preset_name(" ../my.preset ").as_deref(),
+            Some("___my_preset")
+        );
+        let long = sanitize_preset_name(&"a".repeat(100)).unwrap();
+        assert!(validate_preset_name(&long).is_ok());
+        assert_eq!(sanitize_preset_name("   "), None);
+        assert_eq!(sanitize_preset_name("../"), None);
+    }
+
+    #[test]
+    fn save_load_roundtrip() {
+        let (manager, dir) = temp_manager("roundtrip");
+        let mut preset = EffectsPreset::streaming();
+        preset.compressor.ratio = 7.5;
+
+        manager.save("My Vocal Chain", &preset, false).unwrap();
+        let loaded = manager.load("My Vocal Chain").unwrap().0;
+
+        assert_eq!(loaded.name, "My Vocal Chain");
+        assert_eq!(loaded.compressor.ratio, 7.5);
+        assert!(loaded.noise_gate.enabled);
+
+        // Une faute de frappe ne casse pas le chargement, mais se voit
+        let path = dir.join("My Vocal Chain.toml");
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replacen("ratio =", "raito = 2.0\nratio =", 1)).unwrap();
+        let (loaded, warnings) = manager.load("My Vocal Chain").unwrap();
+        assert_eq!(loaded.compressor.ratio, 7.5);
+        assert_eq!(warnings, ["unknown key compressor.raito ignored"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn list_and_delete() {
+        let (manager, dir) = temp_manager("list");
+        assert!(manager.list().is_empty());
+
+        manager
+            .save("Zeta", &EffectsPreset::clean(), false)
+            .unwrap();
+        manager
+            .save("Alpha", &EffectsPreset::default_preset(), false)
+            .unwrap();
+        // Fichier parasite : ignoré
+        std::fs::write(dir.join("notes.txt"), "hello").unwrap();
+
+        assert_eq!(manager.list(), vec!["Alpha", "Zeta"]);
+
+        manager.delete("Alpha").unwrap();
+        assert_eq!(manager.list(), vec!["Zeta"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn overwrite_needs_the_flag_and_keeps_a_backup() {
+        let (manager, dir) = temp_manager("overwrite");
+        let mut tuned = EffectsPreset::streaming();
+        tuned.compressor.ratio = 7.5;
+        manager.save("Voice", &tuned, false).unwrap();
+        let original = std::fs::read_to_string(dir.join("Voice.toml")).unwrap();
+
+        let err = manager
+            .save("Voice", &EffectsPreset::clean(), false)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TroubadourError>(),
+            Some(TroubadourError::PresetExists(name)) if name == "Voice"
+        ));
+        assert_eq!(manager.load("Voice").unwrap().0.compressor.ratio, 7.5);
+
+        manager
+            .save("Voice", &EffectsPreset::clean(), true)
+            .unwrap();
+        assert!(!manager.load("Voice").unwrap().0.compressor.enabled);
+        let backup = std::fs::read_to_string(dir.join("Voice.toml.bak")).unwrap();
+        assert_eq!(backup, original);
+        // Ni la sauvegarde ni le temporaire ne sont des presets
+        assert_eq!(manager.list(), vec!["Voice"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn favorites_keep_their_order() {
+        let mut favorites = FavoritePresets::new(["Voice", "Music", "Voice"].map(String::from));
+        assert_eq!(favorites.names(), ["Voice", "Music"]);
+
+        favorites.add("Podcast", Some(0));
+        favorites.add("Night", None);
+        favorites.add("Late", Some(99));
+        assert_eq!(
+            favorites.names(),
+            ["Podcast", "Voice", "Music", "Night", "Late"]
+        );
+        // Déjà favori : déplacé, pas dupliqué
+        favorites.add("Late", Some(1));
+        assert_eq!(favorites.get(1), Some("Late"));
+        assert_eq!(favorites.names().len(), 5);
+
+        assert!(favorites.remove("Night"));
+        assert!(!favorites.remove("Night"));
+
+        let order = ["Music", "Voice", "Late", "Podcast"].map(String::from);
+        favorites.reorder(&order).unwrap();
+        assert_eq!(favorites.names(), order);
+        // Un favori oublié, inconnu ou répété : refusé, rien ne bouge
+        for bad in [
+            &["Music", "Voice", "Late"][..],
+            &["Music", "Voice", "Late", "Other"],
+            &["Music", "Voice", "Late", "Podcast", "Music"],
+        ] {
+            let bad: Vec<String> = bad.iter().map(|s| s.to_string()).collect();
+            assert!(favorites.reorder(&bad).is_err());
+        }
+        assert_eq!(favorites.names(), order);
+    }
+
+    #[test]
+    fn missing_favorites_are_flagged_not_dropped() {
+        let (manager, dir) = temp_manager("favorites");
+        manager
+            .save("Voice", &EffectsPreset::default_preset(), false)
+            .unwrap();
+        let favorites = FavoritePresets::new(["Gone", "Voice"].map(String::from));
+        let listed = manager.favorites(&favorites);
+        assert_eq!(
+            listed,
+            [
+                FavoritePreset {
+                    name: "Gone".to_string(),
+                    missing: true
+                },
+                FavoritePreset {
+                    name: "Voice".to_string(),
+                    missing: false
+                },
+            ]
+        );
+        assert_eq!(favorites.names().len(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rename_and_delete_keep_favorites_consistent() {
+        let (manager, dir) = temp_manager("rename");
+        manager
+            .save("Voice", &EffectsPreset::streaming(), false)
+            .unwrap();
+        manager
+            .save("Music", &EffectsPreset::clean(), false)
+            .unwrap();
+        let mut favorites = FavoritePresets::new(["Music", "Voice"].map(String::from));
+
+        manager.rename("Voice", "Live Voice").unwrap();
+        favorites.preset_renamed("Voice", "Live Voice");
+        assert_eq!(favorites.names(), ["Music", "Live Voice"]);
+        assert_eq!(manager.load("Live Voice").unwrap().0.name, "Live Voice");
+        assert!(manager.favorites(&favorites).iter().all(|f| !f.missing));
+
+        // Nom pris, source absente, nom invalide : rien ne bouge
+        assert!(manager.rename("Music", "Live Voice").is_err());
+        assert!(manager.rename("Nope", "Other").is_err());
+        assert!(manager.rename("Music", "../escape").is_err());
+        assert_eq!(manager.list(), ["Live Voice", "Music"]);
+
+        manager.delete("Music").unwrap();
+        favorites.preset_deleted("Music");
+        assert_eq!(favorites.names(), ["Live Voice"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// Recule le mtime d'un fichier, hors de la fenêtre des écritures
+    /// récentes.
+    fn age(path: &Path, seconds: u64) {
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn summaries_are_parsed_once_until_the_file_changes() {
+        let (manager, dir) = temp_manager("cache");
+        manager
+            .save("Clean", &EffectsPreset::clean(), false)
+            .unwrap();
+        manager
+            .save("Streaming", &EffectsPreset::streaming(), false)
+            .unwrap();
+        for name in ["Clean", "Streaming"] {
+            age(&dir.join(format!("{name}.toml")), 60);
+        }
+
+        let first = manager.summaries();
+        assert_eq!(first[1].name, "Streaming");
+        assert!(first[1].effects.contains(&"compressor".to_string()));
+        assert_eq!(manager.summaries(), first);
+        let stats = manager.cache_stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (2, 2, 2));
+
+        // Modifié hors de l'application : relu au prochain appel
+        let path = dir.join("Clean.toml");
+        let mut preset = EffectsPreset::clean();
+        preset.compressor.enabled = true;
+        std::fs::write(&path, toml::to_string_pretty(&preset).unwrap()).unwrap();
+        age(&path, 30);
+        let summaries = manager.summaries();
+        assert!(summaries[0].effects.contains(&"compressor".to_string()));
+        assert_eq!(manager.cache_stats().misses, 3);
+
+        // Fichier disparu : son résumé est oublié
+        std::fs::remove_file(dir.join("Streaming.toml")).unwrap();
+        assert_eq!(manager.summaries().len(), 1);
+        assert_eq!(manager.cache_stats().entries, 1);
+
+        // Vidage manuel : tout est relu
+        manager.refresh_cache();
+        manager.summaries();
+        let stats = manager.cache_stats();
+        assert_eq!((stats.entries, stats.misses), (1, 4));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fresh_writes_are_reread_even_with_the_same_stamp() {
+        let (manager, dir) = temp_manager("racy");
+        let mut preset = EffectsPreset::streaming();
+        preset.compressor.ratio = 7.5;
+        manager.save("Voice", &preset, false).unwrap();
+        let path = dir.join("Voice.toml");
+        assert_eq!(manager.summaries()[0].out_of_range, 0);
+
+        // Même taille, même mtime : seul le délai depuis l'écriture
+        // empêche de servir l'ancien résumé
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(text.contains("ratio = 7.5"));
+        std::fs::write(&path, text.replace("ratio = 7.5", "ratio = 0.5")).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified).unwrap();
+        assert_eq!(manager.summaries()[0].out_of_range, 1);
+        assert_eq!(manager.cache_stats().hits, 0);
+
+        // Un fichier illisible est résumé par son erreur
+        std::fs::write(dir.join("Broken.toml"), "not = [toml").unwrap();
+        let summaries = manager.summaries();
+        assert_eq!(summaries[0].name, "Broken");
+        assert!(summaries[0].error.is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_preset_is_an_error() {
+        let (manager, _dir) = temp_manager("missing");
+        assert!(manager.load("Nope").is_err());
+        assert!(manager.delete("Nope").is_err());
+    }
+
+    #[test]
+    fn unsafe_name_never_touches_disk() {
+        let (manager, dir) = temp_manager("unsafe");
+        assert!(
+            manager
+                .save("../escape", &EffectsPreset::default_preset(), true)
+                .is_err()
+        );
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn legacy_presets_are_copied_once_without_overwriting() {
+        let (manager, dir) = temp_manager("migrate");
+        let (legacy, legacy_dir) = temp_manager("legacy");
+        legacy
+            .save("Voice", &EffectsPreset::default_preset(), false)
+            .unwrap();
+        legacy
+            .save("Clean", &EffectsPreset::clean(), false)
+            .unwrap();
+        legacy.save("Same", &EffectsPreset::clean(), false).unwrap();
+        std::fs::write(legacy_dir.join("Notes.toml"), "todo = \"louder\"").unwrap();
+        // "Voice" existe déjà, avec un autre contenu ; "Same" à l'identique
+        manager
+            .save("Voice", &EffectsPreset::clean(), false)
+            .unwrap();
+        std::fs::copy(legacy_dir.join("Same.toml"), dir.join("Same.toml")).unwrap();
+
+        let report = manager.migrate_from(&legacy_dir).unwrap();
+        assert_eq!(report.copied, ["Clean"]);
+        assert_eq!(
+            report.renamed,
+            [("Voice".to_string(), "Voice-migrated".to_string())]
+        );
+        assert_eq!(report.unchanged, 1);
+        assert_eq!(report.skipped, ["Notes"]);
+        assert!(!dir.join("Notes.toml").exists());
+        // L'ancien "Voice" est intact, la copie garde ses réglages
+        assert!(!manager.load("Voice").unwrap().0.compressor.enabled);
+        assert!(manager.load("Voice-migrated").unwrap().0.compressor.enabled);
+
+        // Aux démarrages suivants, le dossier n'est plus relu, même modifié
+        legacy
+            .save("Voice", &EffectsPreset::streaming(), true)
+            .unwrap();
+        let again = manager.migrate_from(&legacy_dir).unwrap();
+        assert_eq!(again, PresetMigration::default());
+        assert!(!dir.join("Voice-migrated-2.toml").exists());
+
+        // Un autre ancien dossier : migré à son tour, suffixe numéroté
+        let (other, other_dir) = temp_manager("legacy-other");
+        other
+            .save("Voice", &EffectsPreset::streaming(), false)
+            .unwrap();
+        let third = manager.migrate_from(&other_dir).unwrap();
+        assert_eq!(
+            third.renamed,
+            [("Voice".to_string(), "Voice-migrated-2".to_string())]
+        );
+        assert!(manager.migrate_from(&other_dir).unwrap().is_empty());
+        // La bibliothèque elle-même n'est pas un ancien dossier
+        assert!(manager.migrate_from(&dir).unwrap().is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+        let _ = std::fs::remove_dir_all(&legacy_dir);
+        let _ = std::fs::remove_dir_all(&other_dir);
+    }
+}
@@ -1,7 +1,18 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
+use crate::config::{config_dir, write_atomic};
 use crate::dsp::EffectsPreset;
+use crate::error::{TroubadourError, TroubadourResult};
 use crate::mixer::MixerConfig;
+use crate::preset::validate_preset_name;
+use crate::unknown_keys::unknown_key_warnings;
+
+/// Taille maximum d'un fichier de profil ouvert depuis n'importe où
+/// (`Profile::load_dropped`) : un profil fait quelques Ko.
+pub const MAX_PROFILE_FILE_BYTES: u64 = 1024 * 1024;
 
 /// Profil complet de Troubadour.
 ///
@@ -20,6 +31,9 @@ pub struct Profile {
     pub name: String,
     pub mixer: MixerConfig,
     pub effects: EffectsPreset,
+    /// Chaîne d'effets des bus qui en ont une, par nom de bus
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bus_effects: BTreeMap<String, EffectsPreset>,
     pub input_device: Option<String>,
     pub output_device: Option<String>,
 }
@@ -31,6 +45,7 @@ impl Profile {
             name: "Default".to_string(),
             mixer: MixerConfig::default_setup(),
             effects: EffectsPreset::default_preset(),
+            bus_effects: BTreeMap::new(),
             input_device: None,
             output_device: None,
         }
@@ -42,6 +57,7 @@ impl Profile {
             name: "Gaming".to_string(),
             mixer: MixerConfig::default_setup(),
             effects: EffectsPreset::streaming(), // Bonne config pour gaming aussi
+            bus_effects: BTreeMap::new(),
             input_device: None,
             output_device: None,
         }
@@ -53,6 +69,7 @@ impl Profile {
             name: "Streaming".to_string(),
             mixer: MixerConfig::default_setup(),
             effects: EffectsPreset::streaming(),
+            bus_effects: BTreeMap::new(),
             input_device: None,
             output_device: None,
         }
@@ -64,6 +81,7 @@ impl Profile {
             name: "Music".to_string(),
             mixer: MixerConfig::default_setup(),
             effects: EffectsPreset::clean(),
+            bus_effects: BTreeMap::new(),
             input_device: None,
             output_device: None,
         }
@@ -75,6 +93,7 @@ impl Profile {
             name: "Meeting".to_string(),
             mixer: MixerConfig::default_setup(),
             effects: EffectsPreset::default_preset(),
+            bus_effects: BTreeMap::new(),
             input_device: None,
             output_device: None,
         }
@@ -91,21 +110,118 @@ impl Profile {
         ]
     }
 
-    /// Sauvegarde le profil dans un fichier TOML.
+    /// Dossier par défaut des profils sauvés : `<config>/profiles/`.
+    pub fn profiles_dir() -> PathBuf {
+        config_dir().join("profiles")
+    }
+
+    /// Noms des profils disponibles : intégrés d'abord, puis ceux sauvés
+    /// dans `dir` (triés, sans doublon avec les intégrés).
+    pub fn available_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = Self::builtin_profiles()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        let mut saved: Vec<String> = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .filter_map(|p| p.file_stem()?.to_str().map(str::to_string))
+            .filter(|name| validate_preset_name(name).is_ok())
+            .filter(|name| !names.iter().any(|n| n.eq_ignore_ascii_case(name)))
+            .collect();
+        saved.sort();
+        names.extend(saved);
+        names
+    }
+
+    /// Trouve un profil par son nom : intégré (insensible à la casse),
+    /// sinon `<dir>/<nom>.toml`. Retourne aussi ses clés inconnues
+    /// (aucune pour un profil intégré).
+    pub fn find(name: &str, dir: &Path) -> Result<(Self, Vec<String>), Box<dyn std::error::Error>> {
+        if let Some(profile) = Self::builtin_profiles()
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+        {
+            return Ok((profile, Vec::new()));
+        }
+        validate_preset_name(name)?;
+        let path = dir.join(format!("{name}.toml"));
+        if !path.exists() {
+            return Err(TroubadourError::PresetNotFound(name.to_string()).into());
+        }
+        Self::load(&path)
+    }
+
+    /// Sauvegarde le profil dans un fichier TOML (écriture atomique :
+    /// un profil n'est jamais laissé à moitié écrit).
     pub fn save(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
         let content = toml::to_string_pretty(self)?;
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(path, content)?;
+        write_atomic(path, content.as_bytes())?;
         Ok(())
     }
 
-    /// Charge un profil depuis un fichier TOML.
-    pub fn load(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Charge un profil depuis un fichier TOML ; retourne aussi ses clés
+    /// inconnues, à signaler (voir `parse`).
+    pub fn load(path: &std::path::Path) -> Result<(Self, Vec<String>), Box<dyn std::error::Error>> {
         let content = std::fs::read_to_string(path)?;
-        let profile: Self = toml::from_str(&content)?;
-        Ok(profile)
+        Ok(Self::parse(&content)?)
+    }
+
+    /// Lit un profil TOML ; retourne aussi ses clés inconnues (fautes de
+    /// frappe, voir `unknown_keys`), ignorées au chargement.
+    pub fn parse(content: &str) -> Result<(Self, Vec<String>), toml::de::Error> {
+        let input: toml::Value = toml::from_str(content)?;
+        let profile: Self = input.clone().try_into()?;
+        let warnings = unknown_key_warnings(&input, &profile);
+        Ok((profile, warnings))
+    }
+
+    /// Charge un profil hors du dossier des profils (fichier glissé sur
+    /// la fenêtre, chemin donné à la main).
+    ///
+    /// # Un fichier venu d'ailleurs
+    /// Le chemin est canonicalisé (un lien est suivi jusqu'à sa cible),
+    /// puis refusé s'il ne désigne pas un fichier `.toml` de moins de
+    /// `MAX_PROFILE_FILE_BYTES` : `InvalidPresetFile`, sans rien lire.
+    /// Un contenu illisible donne `PresetParse`. Sans nom, le profil
+    /// prend celui du fichier. Retourne aussi ses clés inconnues.
+    pub fn load_dropped(path: &Path) -> TroubadourResult<(Self, Vec<String>)> {
+        let invalid = |reason: String| TroubadourError::InvalidPresetFile(reason);
+        let path = path
+            .canonicalize()
+            .map_err(|e| invalid(format!("{}: {e}", path.display())))?;
+        let metadata =
+            std::fs::metadata(&path).map_err(|e| invalid(format!("{}: {e}", path.display())))?;
+        if !metadata.is_file() {
+            return Err(invalid(format!("{} is not a file", path.display())));
+        }
+        if !path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+        {
+            return Err(invalid(format!("{} is not a .toml file", path.display())));
+        }
+        if metadata.len() > MAX_PROFILE_FILE_BYTES {
+            return Err(invalid(format!(
+                "{} is larger than {} KB",
+                path.display(),
+                MAX_PROFILE_FILE_BYTES / 1024
+            )));
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| TroubadourError::PresetParse(format!("{}: {e}", path.display())))?;
+        let (mut profile, warnings) = Self::parse(&content)
+            .map_err(|e| TroubadourError::PresetParse(format!("{}: {e}", path.display())))?;
+        if profile.name.trim().is_empty() {
+            profile.name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+        }
+        Ok((profile, warnings))
     }
 }
 
@@ -120,6 +236,79 @@ mod tests {
         assert!(profile.input_device.is_none());
     }
 
+    #[test]
+    fn find_builtin_and_saved_profiles() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-profile-find-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            Profile::find("streaming", &dir).unwrap().0.name,
+            "Streaming"
+        );
+        assert!(Profile::find("Late Show", &dir).is_err());
+        assert!(Profile::find("../escape", &dir).is_err());
+
+        let mut custom = Profile::music();
+        custom.name = "Late Show".into();
+        custom.save(&dir.join("Late Show.toml")).unwrap();
+        assert_eq!(
+            Profile::find("Late Show", &dir).unwrap().0.name,
+            "Late Show"
+        );
+
+        // Une faute de frappe dans un profil sauvé est rapportée par `find`
+        let path = dir.join("Typo.toml");
+        let text = std::fs::read_to_string(dir.join("Late Show.toml")).unwrap();
+        std::fs::write(&path, format!("volumne = 3\n{text}")).unwrap();
+        let (typo, warnings) = Profile::find("Typo", &dir).unwrap();
+        assert_eq!(typo.name, "Late Show");
+        assert_eq!(warnings, ["unknown key volumne ignored"]);
+        std::fs::remove_file(&path).unwrap();
+
+        let names = Profile::available_names(&dir);
+        assert_eq!(names.len(), 6);
+        assert_eq!(names.last().map(String::as_str), Some("Late Show"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dropped_files_are_checked_before_parsing() {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-profile-drop-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let good = dir.join("Late Show.toml");
+        let mut profile = Profile::music();
+        profile.name = String::new();
+        profile.save(&good).unwrap();
+        assert_eq!(Profile::load_dropped(&good).unwrap().0.name, "Late Show");
+
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/presets");
+        for fixture in ["malformed.toml", "missing_mixer.toml"] {
+            assert!(matches!(
+                Profile::load_dropped(&fixtures.join(fixture)),
+                Err(TroubadourError::PresetParse(_))
+            ));
+        }
+
+        let oversized = dir.join("huge.toml");
+        let padding = "# padding\n".repeat(MAX_PROFILE_FILE_BYTES as usize / 10 + 1);
+        std::fs::write(&oversized, padding).unwrap();
+        let text = dir.join("notes.txt");
+        std::fs::copy(&good, &text).unwrap();
+        for path in [&oversized, &text, &dir, &dir.join("missing.toml")] {
+            assert!(matches!(
+                Profile::load_dropped(path),
+                Err(TroubadourError::InvalidPresetFile(_))
+            ));
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn builtin_profiles_count() {
         assert_eq!(Profile::builtin_profiles().len(), 5);
@@ -142,8 +331,9 @@ mod tests {
         let profile = Profile::gaming();
         profile.save(&path).unwrap();
 
-        let loaded = Profile::load(&path).unwrap();
+        let (loaded, warnings) = Profile::load(&path).unwrap();
         assert_eq!(loaded.name, "Gaming");
+        assert!(warnings.is_empty());
 
         let _ = std::fs::remove_dir_all(&dir);
     }
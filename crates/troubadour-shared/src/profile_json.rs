@@ -0,0 +1,308 @@
+//! Profils en JSON, pour les éditeurs de presets en ligne.
+//!
+//! Le TOML reste le format des fichiers ; le JSON n'en est qu'une autre
+//! écriture, versionnée :
+//!
+//! ```json
+//! { "version": 1, "profile": { "name": "Studio", "mixer": { ... } } }
+//! ```
+//!
+//! `profile` a exactement la forme du TOML (mêmes clés, mêmes champs
+//! omis). Au retour, le JSON repasse par le chargement d'un profil
+//! TOML : formes anciennes relues de la même façon, clés inconnues
+//! ignorées et signalées (`unknown_keys`), un `null` vaut un champ
+//! absent.
+//!
+//! JSON n'a pas de flottants infinis : ils s'écrivent `{"$f32": "-inf"}`
+//! (`"inf"`, `"-inf"` ou `"nan"`, comme en TOML) et redeviennent des
+//! nombres au retour. Un nom de canal `"inf"` reste une chaîne.
+
+use serde_json::{Map, Number, Value as Json};
+use toml::Value as Toml;
+
+use crate::error::{TroubadourError, TroubadourResult};
+use crate::profile::Profile;
+use crate::unknown_keys::unknown_key_warnings;
+
+/// Clé de l'objet qui porte un flottant non fini.
+const NON_FINITE_KEY: &str = "$f32";
+
+/// Version de la forme JSON : change dès que `profile` ne se relit plus
+/// comme avant.
+pub const PROFILE_JSON_VERSION: u64 = 1;
+
+/// Écrit un profil en JSON.
+pub fn profile_to_json(profile: &Profile) -> TroubadourResult<String> {
+    let value = Toml::try_from(profile).map_err(|e| parse_error(&e))?;
+    let mut root = Map::new();
+    root.insert("version".into(), PROFILE_JSON_VERSION.into());
+    root.insert("profile".into(), to_json(value));
+    serde_json::to_string_pretty(&Json::Object(root)).map_err(|e| parse_error(&e))
+}
+
+/// Profil TOML réécrit en JSON, avec les clés inconnues du TOML (qui
+/// ne sont pas reprises).
+pub fn preset_to_json(toml: &str) -> TroubadourResult<(String, Vec<String>)> {
+    let (profile, warnings) = Profile::parse(toml).map_err(|e| parse_error(&e))?;
+    Ok((profile_to_json(&profile)?, warnings))
+}
+
+/// Lit un profil JSON ; retourne aussi ses clés inconnues, ignorées.
+pub fn preset_from_json(json: &str) -> TroubadourResult<(Profile, Vec<String>)> {
+    let root: Json = serde_json::from_str(json).map_err(|e| parse_error(&e))?;
+    let Json::Object(mut root) = root else {
+        return Err(parse_error(&"expected an object"));
+    };
+    let version = root
+        .remove("version")
+        .ok_or_else(|| parse_error(&"missing version"))?;
+    match version.as_u64() {
+        Some(PROFILE_JSON_VERSION) => {}
+        Some(v) if v > PROFILE_JSON_VERSION => {
+            return Err(parse_error(&format!(
+                "version {v} is newer than this build ({PROFILE_JSON_VERSION})"
+            )));
+        }
+        _ => return Err(parse_error(&format!("invalid version {version}"))),
+    }
+    let profile = root
+        .remove("profile")
+        .ok_or_else(|| parse_error(&"missing profile"))?;
+    let input = match to_toml(profile, "profile")? {
+        Some(input @ Toml::Table(_)) => input,
+        _ => return Err(parse_error(&"profile is not an object")),
+    };
+    let profile: Profile = input.clone().try_into().map_err(|e| parse_error(&e))?;
+    let mut warnings: Vec<String> = root
+        .keys()
+        .map(|key| format!("unknown key {key} ignored"))
+        .collect();
+    warnings.extend(
+        unknown_key_warnings(&input, &profile)
+            .into_iter()
+            .map(|w| w.replacen("unknown key ", "unknown key profile.", 1)),
+    );
+    Ok((profile, warnings))
+}
+
+fn parse_error(e: &dyn std::fmt::Display) -> TroubadourError {
+    TroubadourError::PresetParse(e.to_string())
+}
+
+fn to_json(value: Toml) -> Json {
+    match value {
+        Toml::String(s) => Json::String(s),
+        Toml::Integer(i) => i.into(),
+        Toml::Float(f) => Number::from_f64(f).map_or_else(
+            || {
+                let text = match f {
+                    f if f.is_nan() => "nan",
+                    f if f > 0.0 => "inf",
+                    _ => "-inf",
+                };
+                let mut tagged = Map::new();
+                tagged.insert(NON_FINITE_KEY.into(), text.into());
+                Json::Object(tagged)
+            },
+            Json::Number,
+        ),
+        Toml::Boolean(b) => b.into(),
+        Toml::Datetime(d) => Json::String(d.to_string()),
+        Toml::Array(items) => Json::Array(items.into_iter().map(to_json).collect()),
+        Toml::Table(table) => {
+            Json::Object(table.into_iter().map(|(k, v)| (k, to_json(v))).collect())
+        }
+    }
+}
+
+/// `None` : `null`, le champ est omis.
+fn to_toml(value: Json, path: &str) -> TroubadourResult<Option<Toml>> {
+    Ok(Some(match value {
+        Json::Null => return Ok(None),
+        Json::Bool(b) => Toml::Boolean(b),
+        Json::Number(n) => match (n.as_i64(), n.as_f64()) {
+            (Some(i), _) => Toml::Integer(i),
+            (None, Some(f)) => Toml::Float(f),
+            (None, None) => return Err(parse_error(&format!("{path}: number out of range"))),
+        },
+        Json::String(s) => Toml::String(s),
+        Json::Array(items) => Toml::Array(
+            items
+                .into_iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    let path = format!("{path}[{i}]");
+                    to_toml(item, &path)?
+                        .ok_or_else(|| parse_error(&format!("{path}: null in a list")))
+                })
+                .collect::<TroubadourResult<_>>()?,
+        ),
+        Json::Object(map) if map.contains_key(NON_FINITE_KEY) => {
+            let text = map.get(NON_FINITE_KEY).and_then(Json::as_str);
+            match (map.len(), text) {
+                (1, Some("inf" | "+inf")) => Toml::Float(f64::INFINITY),
+                (1, Some("-inf")) => Toml::Float(f64::NEG_INFINITY),
+                (1, Some("nan")) => Toml::Float(f64::NAN),
+                _ => {
+                    return Err(parse_error(&format!(
+                        "{path}: expected {{\"{NON_FINITE_KEY}\": \"inf\" | \"-inf\" | \"nan\"}}"
+                    )));
+                }
+            }
+        }
+        Json::Object(map) => {
+            let mut table = toml::map::Map::new();
+            for (key, item) in map {
+                if let Some(item) = to_toml(item, &format!("{path}.{key}"))? {
+                    table.insert(key, item);
+                }
+            }
+            Toml::Table(table)
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{ChannelCount, ChannelId};
+    use crate::dsp::{EffectsPreset, InsertPoint};
+    use crate::mixer::{
+        BusFollow, ChannelLink, Crossfade, CrossfadeCurve, DeviceAssignment, InputFallback,
+        SignalPresenceConfig,
+    };
+
+    /// Un profil avec tous les champs optionnels remplis.
+    fn maximal() -> Profile {
+        let mut profile = Profile::streaming();
+        profile.name = "Everything".into();
+        profile.input_device = Some("Interface In".into());
+        profile.output_device = Some("Interface Out".into());
+        let mut bus_effects = EffectsPreset::clean();
+        bus_effects
+            .insert_points
+            .insert("compressor".into(), InsertPoint::PostFader);
+        profile.bus_effects.insert("Speakers".into(), bus_effects);
+
+        let mixer = &mut profile.mixer;
+        let mic = &mut mixer.channels[0];
+        mic.delay_ms = 12.5;
+        mic.phase_invert = true;
+        mic.dc_block = true;
+        mic.device_name = DeviceAssignment::Specific("Interface In".into());
+        mic.device_channel_offset = 2;
+        mic.device_channel_count = 1;
+        mic.signal_presence = Some(SignalPresenceConfig {
+            threshold_db: -50.0,
+            silence_seconds: 3.0,
+            hysteresis_db: 4.0,
+        });
+        mic.monitor_bus = Some(ChannelId(3));
+        mic.monitor_level_db = f32::NEG_INFINITY;
+        mic.input_fallback = InputFallback::Devices(vec!["Webcam".into()]);
+        mixer.channels[1].device_name = DeviceAssignment::SystemDefault;
+        mixer.channels[2].input_fallback = InputFallback::File("hold.wav".into());
+        let bus = &mut mixer.channels[3];
+        bus.channel_count = ChannelCount::Mono;
+        bus.output_channel_offset = Some(2);
+        bus.true_peak_meter = true;
+        mixer.crossfades.push(Crossfade::new(
+            1,
+            ChannelId(1),
+            ChannelId(2),
+            CrossfadeCurve::default(),
+        ));
+        mixer
+            .links
+            .push(ChannelLink::new(ChannelId(1), ChannelId(2)));
+        mixer.bus_follows.push(BusFollow {
+            follower: ChannelId(4),
+            leader: ChannelId(3),
+            offset_db: -6.0,
+            on_override: Default::default(),
+        });
+        profile
+    }
+
+    fn same(a: &Profile, b: &Profile) {
+        assert_eq!(Toml::try_from(a).unwrap(), Toml::try_from(b).unwrap());
+    }
+
+    #[test]
+    fn profiles_round_trip_through_json() {
+        for profile in Profile::builtin_profiles().into_iter().chain([maximal()]) {
+            let json = profile_to_json(&profile).unwrap();
+            let (back, warnings) = preset_from_json(&json).unwrap();
+            assert!(warnings.is_empty(), "{warnings:?}");
+            same(&profile, &back);
+        }
+
+        // TOML → JSON → TOML : le même profil, l'infini compris
+        let toml = toml::to_string_pretty(&maximal()).unwrap();
+        let (json, warnings) = preset_to_json(&toml).unwrap();
+        assert!(warnings.is_empty());
+        let parsed: Json = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["version"], PROFILE_JSON_VERSION);
+        assert_eq!(
+            parsed["profile"]["mixer"]["channels"][0]["monitor_level_db"],
+            serde_json::json!({ "$f32": "-inf" })
+        );
+        let (back, _) = preset_from_json(&json).unwrap();
+        let (reread, _) = Profile::parse(&toml::to_string_pretty(&back).unwrap()).unwrap();
+        same(&maximal(), &reread);
+    }
+
+    #[test]
+    fn json_follows_the_toml_loading_rules() {
+        let mut parsed: Json =
+            serde_json::from_str(&profile_to_json(&Profile::default_profile()).unwrap()).unwrap();
+        parsed["editor"] = "web".into();
+        parsed["profile"]["mixer"]["channels"][1]["volumne"] = 0.5.into();
+        // `null` : champ absent
+        parsed["profile"]["input_device"] = Json::Null;
+        let (profile, warnings) = preset_from_json(&parsed.to_string()).unwrap();
+        assert_eq!(
+            warnings,
+            [
+                "unknown key editor ignored",
+                "unknown key profile.mixer.channels[1].volumne ignored"
+            ]
+        );
+        assert_eq!(profile.input_device, None);
+
+        // Un nom de canal "inf" reste un nom, même avec un niveau infini
+        parsed["profile"]["mixer"]["channels"][1]["name"] = "inf".into();
+        parsed["profile"]["mixer"]["channels"][1]["monitor_level_db"] =
+            serde_json::json!({ "$f32": "-inf" });
+        let (profile, _) = preset_from_json(&parsed.to_string()).unwrap();
+        assert_eq!(profile.mixer.channels[1].name, "inf");
+        assert_eq!(
+            profile.mixer.channels[1].monitor_level_db,
+            f32::NEG_INFINITY
+        );
+        parsed["profile"]["mixer"]["channels"][1]["monitor_level_db"] =
+            serde_json::json!({ "$f32": "loud" });
+        let e = preset_from_json(&parsed.to_string()).unwrap_err();
+        assert!(
+            e.to_string().contains("channels[1].monitor_level_db"),
+            "{e}"
+        );
+        parsed["profile"]["mixer"]["channels"][1]["monitor_level_db"] = 0.0.into();
+
+        for (version, error) in [
+            (Json::Null, "invalid version"),
+            (2.into(), "newer than this build"),
+        ] {
+            parsed["version"] = version;
+            let e = preset_from_json(&parsed.to_string()).unwrap_err();
+            assert!(e.to_string().contains(error), "{e}");
+        }
+        parsed.as_object_mut().unwrap().remove("version");
+        assert!(
+            preset_from_json(&parsed.to_string())
+                .unwrap_err()
+                .to_string()
+                .contains("missing version")
+        );
+    }
+}
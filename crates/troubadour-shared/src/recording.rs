@@ -0,0 +1,100 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+
+/// Plafond de crête vraie d'une copie normalisée : le gain est réduit
+/// pour rester dessous, quelle que soit la cible demandée.
+///
+/// # Pourquoi -1 et pas 0 ?
+/// Un encodeur (AAC, Opus) reconstruit le signal entre les samples et
+/// peut dépasser la crête du fichier d'origine. 1 dB de marge est la
+/// recommandation EBU R128 pour la diffusion.
+pub const TRUE_PEAK_CEILING_DBTP: f32 = -1.0;
+
+/// Mesures d'un fichier enregistré (`analyze_recording`).
+///
+/// Les niveaux sont `None` pour un fichier silencieux : -∞ ne passe pas
+/// en JSON.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordingReport {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub frames: u64,
+    /// Plus grand sample, en dBFS
+    pub sample_peak_dbfs: Option<f32>,
+    /// Crête entre les samples (suréchantillonnage x4), en dBTP
+    pub true_peak_dbtp: Option<f32>,
+    /// Niveau moyen, tous canaux confondus, en dBFS
+    pub rms_dbfs: Option<f32>,
+    /// Sonie intégrée EBU R128, en LUFS (`None` : rien au-dessus de la
+    /// porte à -70 LUFS)
+    pub integrated_lufs: Option<f32>,
+}
+
+impl RecordingReport {
+    pub fn duration_secs(&self) -> f64 {
+        self.frames as f64 / f64::from(self.sample_rate.max(1))
+    }
+}
+
+impl fmt::Display for RecordingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = |value: Option<f32>, unit: &str| {
+            value.map_or_else(|| "silent".to_string(), |v| format!("{v:.1} {unit}"))
+        };
+        write!(
+            f,
+            "peak {}, true peak {}, RMS {}, integrated {} ({} Hz, {} ch, {:.2} s)",
+            level(self.sample_peak_dbfs, "dBFS"),
+            level(self.true_peak_dbtp, "dBTP"),
+            level(self.rms_dbfs, "dBFS"),
+            level(self.integrated_lufs, "LUFS"),
+            self.sample_rate,
+            self.channels,
+            self.duration_secs()
+        )
+    }
+}
+
+/// Niveau visé par `normalize_recording`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "value")]
+pub enum NormalizeTarget {
+    /// Sonie intégrée (-16 LUFS pour un podcast, -14 pour le streaming)
+    Lufs(f32),
+    /// Crête vraie, en dBTP
+    Peak(f32),
+}
+
+impl fmt::Display for NormalizeTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lufs(lufs) => write!(f, "{lufs} LUFS"),
+            Self::Peak(dbtp) => write!(f, "{dbtp} dBTP peak"),
+        }
+    }
+}
+
+/// Résultat d'une normalisation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NormalizeOutcome {
+    /// Gain appliqué à la copie
+    pub gain_db: f32,
+    /// Gain réduit pour garder la crête vraie sous
+    /// `TRUE_PEAK_CEILING_DBTP` : la cible n'est pas atteinte
+    pub limited: bool,
+}
+
+/// Replay d'un bus : ses dernières secondes gardées en mémoire
+/// (`Event::ReplayStatus`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayInfo {
+    pub bus: ChannelId,
+    /// Durée gardée au plus
+    pub seconds: u32,
+    /// Durée déjà en mémoire : `seconds` une fois l'anneau rempli
+    pub buffered_seconds: f32,
+    pub sample_rate: u32,
+}
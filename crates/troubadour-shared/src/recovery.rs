@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{config_dir, write_atomic};
+use crate::profile::Profile;
+use crate::ui_state::UiState;
+
+/// Instantané de secours de l'état non sauvé (mixer + effets, état de l'UI).
+///
+/// # Cycle de vie
+/// ```text
+///  modification ──► dirty ──(≤ 1 écriture / N s)──► recovery.toml
+///  arrêt propre ─────────────────────────────────► supprimé
+///  crash ────────────────────────────────────────► reste sur disque
+///  démarrage suivant : plus récent que config.toml ? → "Restaurer ?"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverySnapshot {
+    /// Horodatage en millisecondes depuis l'epoch Unix.
+    pub saved_at_ms: u64,
+    pub profile: Profile,
+    /// État de l'UI non encore sauvé dans la config
+    #[serde(default, skip_serializing_if = "UiState::is_empty")]
+    pub ui: UiState,
+}
+
+impl RecoverySnapshot {
+    /// Emplacement par défaut : `<config>/recovery.toml`.
+    pub fn default_path() -> PathBuf {
+        config_dir().join("recovery.toml")
+    }
+
+    /// Écrit l'instantané (écriture atomique : jamais de fichier tronqué).
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let content = toml::to_string(self)?;
+        write_atomic(path, content.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+
+    /// Supprime l'instantané. Absent = déjà supprimé, pas une erreur.
+    pub fn discard(path: &Path) -> std::io::Result<()> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// `true` si un instantané existe et est plus récent que la config
+    /// (ou s'il n'y a pas de config du tout).
+    ///
+    /// Un instantané plus ancien que la config a déjà été "rattrapé"
+    /// par une sauvegarde : le proposer ferait revenir en arrière.
+    pub fn is_pending(recovery_path: &Path, config_path: &Path) -> bool {
+        let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        match (modified(recovery_path), modified(config_path)) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(recovery), Some(config)) => recovery > config,
+        }
+    }
+}
+
+/// État sauvé automatiquement avant une opération qui le remplace
+/// (`<config>/autosnapshots/<id>.toml`), à restaurer si elle était une
+/// erreur.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoSnapshot {
+    /// Horodatage en millisecondes depuis l'epoch Unix.
+    pub taken_at_ms: u64,
+    /// Opération qui l'a déclenché ("ApplyProfile"...)
+    pub reason: String,
+    pub profile: Profile,
+}
+
+/// Un instantané automatique, sans son contenu (`Event::AutoSnapshotList`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoSnapshotInfo {
+    pub id: u64,
+    pub taken_at_ms: u64,
+    pub reason: String,
+}
+
+impl AutoSnapshot {
+    /// Dossier par défaut : `<config>/autosnapshots/`.
+    pub fn default_dir() -> PathBuf {
+        config_dir().join("autosnapshots")
+    }
+
+    /// Écriture atomique, comme `RecoverySnapshot::save`.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let content = toml::to_string(self)?;
+        write_atomic(path, content.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&content)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-recovery-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn set_mtime(path: &Path, time: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn roundtrip_and_discard() {
+        let dir = temp_dir("roundtrip");
+        let path = dir.join("recovery.toml");
+        let mut profile = Profile::default_profile();
+        profile.mixer.channels[0].volume = 0.42;
+
+        RecoverySnapshot {
+            saved_at_ms: 1234,
+            profile,
+            ui: UiState::default(),
+        }
+        .save(&path)
+        .unwrap();
+
+        let loaded = RecoverySnapshot::load(&path).unwrap();
+        assert_eq!(loaded.saved_at_ms, 1234);
+        assert_eq!(loaded.profile.mixer.channels[0].volume, 0.42);
+
+        RecoverySnapshot::discard(&path).unwrap();
+        assert!(!path.exists());
+        // Deuxième suppression : pas d'erreur
+        RecoverySnapshot::discard(&path).unwrap();
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn pending_only_when_newer_than_config() {
+        let dir = temp_dir("pending");
+        let recovery = dir.join("recovery.toml");
+        let config = dir.join("config.toml");
+
+        assert!(!RecoverySnapshot::is_pending(&recovery, &config));
+
+        std::fs::write(&recovery, "").unwrap();
+        assert!(RecoverySnapshot::is_pending(&recovery, &config));
+
+        let now = SystemTime::now();
+        std::fs::write(&config, "").unwrap();
+        set_mtime(&recovery, now - Duration::from_secs(60));
+        set_mtime(&config, now);
+        assert!(!RecoverySnapshot::is_pending(&recovery, &config));
+
+        set_mtime(&recovery, now + Duration::from_secs(60));
+        assert!(RecoverySnapshot::is_pending(&recovery, &config));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
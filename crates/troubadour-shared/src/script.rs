@@ -0,0 +1,92 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_dir;
+
+/// Scripts d'automatisation (`[scripting]`), avec la feature `scripting`
+/// du moteur.
+///
+/// ```toml
+/// [scripting]
+/// enabled = ["ducking"]     # scripts/ducking.rhai
+/// tick_ms = 100
+/// ```
+///
+/// Un script définit `fn tick(mixer)`, appelé à chaque tick par le
+/// thread de contrôle (jamais par le callback audio).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptingConfig {
+    /// Scripts actifs au démarrage, par nom de fichier sans `.rhai`
+    #[serde(default)]
+    pub enabled: Vec<String>,
+
+    /// Intervalle entre deux appels de `tick`.
+    #[serde(default = "ScriptingConfig::default_tick_ms")]
+    pub tick_ms: u64,
+
+    /// Opérations permises à un script par tick : au-delà, le script est
+    /// interrompu (boucle infinie) et désactivé.
+    #[serde(default = "ScriptingConfig::default_max_operations")]
+    pub max_operations: u64,
+
+    /// Commandes envoyées au mixer par un script, par seconde : les
+    /// suivantes sont ignorées.
+    #[serde(default = "ScriptingConfig::default_max_commands_per_second")]
+    pub max_commands_per_second: u32,
+}
+
+impl ScriptingConfig {
+    fn default_tick_ms() -> u64 {
+        100
+    }
+
+    fn default_max_operations() -> u64 {
+        10_000
+    }
+
+    fn default_max_commands_per_second() -> u32 {
+        20
+    }
+
+    /// Dossier des scripts (`scripts/` dans le dossier de config).
+    pub fn default_dir() -> PathBuf {
+        config_dir().join("scripts")
+    }
+}
+
+impl Default for ScriptingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Vec::new(),
+            tick_ms: Self::default_tick_ms(),
+            max_operations: Self::default_max_operations(),
+            max_commands_per_second: Self::default_max_commands_per_second(),
+        }
+    }
+}
+
+/// Un script du dossier `scripts/`, pour la liste de l'UI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScriptInfo {
+    /// Nom du fichier, sans `.rhai`
+    pub name: String,
+    pub enabled: bool,
+    /// Dernière erreur (compilation ou exécution) ; un script qui a
+    /// levé une erreur est désactivé
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_section_uses_defaults() {
+        let config: ScriptingConfig = toml::from_str("enabled = [\"ducking\"]").unwrap();
+        assert_eq!(config.enabled, ["ducking"]);
+        assert_eq!(config.tick_ms, 100);
+        assert_eq!(config.max_operations, 10_000);
+        assert_eq!(config.max_commands_per_second, 20);
+    }
+}
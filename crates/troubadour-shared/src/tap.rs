@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::audio::ChannelId;
+
+/// Copie d'un bus servie à un autre programme de la machine (`[[taps]]`).
+///
+/// Le flux part en TCP sur `127.0.0.1` uniquement : pas d'accès depuis
+/// le réseau, un visualiseur ou un encodeur local s'y connecte.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TapConfig {
+    /// Nom du bus copié (celui que joue le device de sortie)
+    pub bus: String,
+    /// Port d'écoute (0 : choisi par le système, voir `TapInfo::address`)
+    pub port: u16,
+    /// Blocs gardés pour un lecteur lent ; au-delà, les plus anciens
+    /// sont jetés
+    #[serde(default = "TapConfig::default_capacity_blocks")]
+    pub capacity_blocks: usize,
+    /// Ouvert au démarrage du moteur
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl TapConfig {
+    /// ~85 ms de marge en blocs de 256 frames à 48 kHz.
+    pub const DEFAULT_CAPACITY_BLOCKS: usize = 16;
+
+    fn default_capacity_blocks() -> usize {
+        Self::DEFAULT_CAPACITY_BLOCKS
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Un tap ouvert (`Event::TapList`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TapInfo {
+    pub id: u32,
+    pub bus: ChannelId,
+    pub capacity_blocks: usize,
+    /// Blocs copiés dans le tap depuis son ouverture
+    pub blocks_written: u64,
+    /// Blocs jetés avant d'être lus : le lecteur n'a pas suivi
+    pub blocks_dropped: u64,
+    /// Adresse TCP locale du flux (`None` : tap lu dans le processus)
+    pub address: Option<String>,
+}
@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::config_dir;
+use crate::dsp::EffectsPreset;
+use crate::preset::validate_preset_name;
+
+/// Bus de sortie auxquels un canal créé depuis un template est routé.
+///
+/// En TOML : `buses = "all"`, `buses = "none"`
+/// ou `buses = { named = ["Headphones"] }`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BusAssignment {
+    /// Tous les bus existants au moment de la création
+    #[default]
+    All,
+    /// Les bus portant ces noms (insensible à la casse), s'ils existent
+    Named(Vec<String>),
+    /// Aucun bus : le canal est créé non routé
+    None,
+}
+
+impl BusAssignment {
+    /// `true` si un bus de ce nom fait partie de l'affectation.
+    pub fn includes(&self, bus_name: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Named(names) => names.iter().any(|n| n.eq_ignore_ascii_case(bus_name)),
+            Self::None => false,
+        }
+    }
+}
+
+/// Modèle de canal d'entrée : volume, chaîne d'effets et routage
+/// appliqués en une fois à la création du canal.
+///
+/// # Template vs preset d'effets
+/// Un preset d'effets ne décrit que la chaîne DSP. Un template décrit
+/// un canal complet prêt à l'emploi ("Mic" = gate + compresseur,
+/// routé vers tous les bus).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChannelTemplate {
+    pub name: String,
+    /// Volume linéaire initial
+    #[serde(default = "unity")]
+    pub volume: f32,
+    pub effects: EffectsPreset,
+    #[serde(default)]
+    pub buses: BusAssignment,
+}
+
+fn unity() -> f32 {
+    1.0
+}
+
+impl ChannelTemplate {
+    /// Template minimal autour d'un preset d'effets sauvé :
+    /// volume nominal, routé vers tous les bus.
+    pub fn from_effects_preset(preset: EffectsPreset) -> Self {
+        Self {
+            name: preset.name.clone(),
+            volume: 1.0,
+            effects: preset,
+            buses: BusAssignment::All,
+        }
+    }
+
+    /// Dossier des templates utilisateur : `<config>/templates/`.
+    pub fn default_dir() -> PathBuf {
+        config_dir().join("templates")
+    }
+
+    /// Charge tous les templates d'un dossier (`<nom>.toml`).
+    ///
+    /// Comme pour les presets, le nom du fichier fait foi. Les fichiers
+    /// illisibles sont ignorés avec leur erreur : un template cassé ne
+    /// doit pas empêcher les autres de se charger. Dossier absent =
+    /// aucun template (pas une erreur).
+    pub fn load_dir(dir: &Path) -> (Vec<Self>, Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return (Vec::new(), Vec::new());
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        let mut templates = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if validate_preset_name(name).is_err() {
+                continue;
+            }
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| toml::from_str::<Self>(&content).map_err(|e| e.to_string()));
+            match parsed {
+                Ok(mut template) => {
+                    template.name = name.to_string();
+                    templates.push(template);
+                }
+                Err(e) => errors.push(format!("{}: {e}", path.display())),
+            }
+        }
+        (templates, errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bus_assignment_matches_names() {
+        assert!(BusAssignment::All.includes("Speakers"));
+        assert!(!BusAssignment::None.includes("Speakers"));
+        let named = BusAssignment::Named(vec!["headphones".into()]);
+        assert!(named.includes("Headphones"));
+        assert!(!named.includes("Speakers"));
+    }
+
+    #[test]
+    fn load_dir_reads_templates_and_reports_broken_files() {
+        let dir = std::env::temp_dir().join(format!("troubadour-templates-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let template = ChannelTemplate {
+            name: "ignored".into(),
+            volume: 0.5,
+            effects: EffectsPreset::clean(),
+            buses: BusAssignment::Named(vec!["Headphones".into()]),
+        };
+        std::fs::write(dir.join("Music.toml"), toml::to_string(&template).unwrap()).unwrap();
+        std::fs::write(dir.join("Broken.toml"), "volume = \"loud\"").unwrap();
+
+        let (templates, errors) = ChannelTemplate::load_dir(&dir);
+        assert_eq!(templates.len(), 1);
+        assert_eq!(templates[0].name, "Music");
+        assert_eq!(templates[0].volume, 0.5);
+        assert_eq!(templates[0].buses, template.buses);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("Broken.toml"));
+
+        assert!(ChannelTemplate::load_dir(&dir.join("missing")).0.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,164 @@
+//! État de l'interface gardé dans la config (`[ui]`) : panneau ouvert,
+//! style des meters, canaux repliés...
+//!
+//! # Pourquoi pas le stockage du navigateur ?
+//! Le `localStorage` reste sur la machine. Dans `config.toml`, l'état de
+//! l'UI suit le fichier : synchronisé, sauvegardé, copié avec le reste.
+//!
+//! # Sans schéma
+//! Le moteur ne lit pas cette table : l'UI y range ce qu'elle veut, en
+//! JSON, et la retrouve telle quelle. Seules la taille et la profondeur
+//! sont bornées, pour qu'une UI boguée ne puisse pas gonfler la config
+//! sans limite.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{TroubadourError, TroubadourResult};
+
+/// Taille maximum de l'état de l'UI, en JSON.
+pub const MAX_UI_STATE_BYTES: usize = 64 * 1024;
+
+/// Imbrication maximum (objets et listes).
+pub const MAX_UI_STATE_DEPTH: usize = 16;
+
+/// Table `[ui]`, gardée telle que l'UI l'a donnée.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UiState(toml::Table);
+
+impl UiState {
+    /// Lit l'état envoyé par l'UI : un objet JSON.
+    ///
+    /// `null` n'existe pas en TOML : une clé `null` est retirée (comme
+    /// un élément `null` d'une liste), le reste de l'état est gardé.
+    pub fn from_json(json: &str) -> TroubadourResult<Self> {
+        if json.len() > MAX_UI_STATE_BYTES {
+            return Err(invalid(format!(
+                "{} bytes, at most {MAX_UI_STATE_BYTES}",
+                json.len()
+            )));
+        }
+        let mut value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| invalid(e.to_string()))?;
+        drop_nulls(&mut value);
+        match toml::Value::try_from(value) {
+            Ok(toml::Value::Table(table)) => Self::from_table(table),
+            Ok(_) => Err(invalid("not a JSON object".to_string())),
+            Err(e) => Err(invalid(e.to_string())),
+        }
+    }
+
+    /// Vérifie une table lue dans la config.
+    pub fn from_table(table: toml::Table) -> TroubadourResult<Self> {
+        let depth = table.values().map(depth).max().unwrap_or(0) + 1;
+        if depth > MAX_UI_STATE_DEPTH {
+            return Err(invalid(format!(
+                "nested {depth} levels deep, at most {MAX_UI_STATE_DEPTH}"
+            )));
+        }
+        let state = Self(table);
+        let size = state.to_json().len();
+        if size > MAX_UI_STATE_BYTES {
+            return Err(invalid(format!(
+                "{size} bytes, at most {MAX_UI_STATE_BYTES}"
+            )));
+        }
+        Ok(state)
+    }
+
+    /// L'état en JSON (`{}` s'il est vide), pour l'UI. Mêmes valeurs
+    /// que reçues ; les clés des objets reviennent triées.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.0).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    pub fn table(&self) -> &toml::Table {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn invalid(reason: String) -> TroubadourError {
+    TroubadourError::InvalidUiState(reason)
+}
+
+/// Retire les `null` des objets et des listes, à toute profondeur.
+fn drop_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(drop_nulls);
+        }
+        serde_json::Value::Array(items) => {
+            items.retain(|v| !v.is_null());
+            items.iter_mut().for_each(drop_nulls);
+        }
+        _ => {}
+    }
+}
+
+/// Niveaux d'imbrication d'une valeur (1 pour un scalaire).
+fn depth(value: &toml::Value) -> usize {
+    match value {
+        toml::Value::Table(table) => table.values().map(depth).max().unwrap_or(0) + 1,
+        toml::Value::Array(items) => items.iter().map(depth).max().unwrap_or(0) + 1,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NESTED: &str = r#"{
+        "panel": "effects",
+        "meters": {"style": "peak", "hold_ms": 1500, "decay": 0.85},
+        "collapsed": [1, 4],
+        "strips": [{"id": 0, "pinned": true}, {"id": 2, "tags": ["voice", "live"]}]
+    }"#;
+
+    #[test]
+    fn nested_state_survives_a_config_file_roundtrip() {
+        let state = UiState::from_json(NESTED).unwrap();
+        let text = toml::to_string(&state).unwrap();
+        let parsed: UiState = toml::from_str(&text).unwrap();
+        assert_eq!(parsed, state);
+
+        let json: serde_json::Value = serde_json::from_str(&parsed.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::from_str::<serde_json::Value>(NESTED).unwrap()
+        );
+        assert_eq!(UiState::default().to_json(), "{}");
+    }
+
+    #[test]
+    fn oversized_deep_or_non_object_states_are_refused() {
+        let big = format!(r#"{{"blob": "{}"}}"#, "x".repeat(MAX_UI_STATE_BYTES));
+        assert!(UiState::from_json(&big).is_err());
+
+        let nest = |levels: usize| format!("{}1{}", r#"{"a":"#.repeat(levels), "}".repeat(levels));
+        assert!(UiState::from_json(&nest(MAX_UI_STATE_DEPTH - 1)).is_ok());
+        assert!(UiState::from_json(&nest(MAX_UI_STATE_DEPTH + 1)).is_err());
+
+        for json in ["[1, 2]", "42", "null", "{not json"] {
+            assert!(UiState::from_json(json).is_err(), "{json} accepted");
+        }
+    }
+
+    #[test]
+    fn null_keys_are_dropped_and_the_rest_is_kept() {
+        let state = UiState::from_json(
+            r#"{"panel": null, "meters": {"style": "peak", "hold_ms": null}, "collapsed": [1, null, 4]}"#,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&state.to_json()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"meters": {"style": "peak"}, "collapsed": [1, 4]})
+        );
+    }
+}
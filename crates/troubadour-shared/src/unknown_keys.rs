@@ -0,0 +1,186 @@
+//! Clés d'un fichier TOML que serde a ignorées sans rien dire.
+//!
+//! Une faute de frappe dans `config.toml` ou un profil (`volumne_db`)
+//! ne casse pas le chargement : le champ garde son défaut, et le
+//! réglage « ne marche pas ». On retrouve ces clés sans décrire le
+//! schéma à la main, en deux temps :
+//!
+//! 1. la valeur chargée est resérialisée : une clé du fichier absente de
+//!    ce qu'elle produit est suspecte ;
+//! 2. une clé suspecte peut encore être connue, mais omise à l'écriture
+//!    (`skip_serializing_if` : liste vide, device `none`...). On la
+//!    remplace par une date, puis par un booléen : aucun champ n'accepte
+//!    les deux, si le fichier se charge toujours, personne ne la lit.
+//!
+//! Les chemins suivent la forme du fichier : `mixer.channels[2].volumne_db`.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use toml::Value;
+
+/// Un pas dans le chemin d'une clé.
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Chemins des clés de `input` qu'aucun champ de `T` ne lit, dans
+/// l'ordre du fichier. `parsed` est `input` déjà chargé.
+pub fn unknown_keys<T: Serialize + DeserializeOwned>(input: &Value, parsed: &T) -> Vec<String> {
+    let Ok(known) = Value::try_from(parsed) else {
+        return Vec::new();
+    };
+    let mut suspects = Vec::new();
+    collect_suspects(input, &known, &mut Vec::new(), &mut suspects);
+    suspects
+        .into_iter()
+        .filter(|path| is_ignored::<T>(input, path))
+        .map(|path| format_path(&path))
+        .collect()
+}
+
+/// `unknown_keys`, en avertissements à afficher.
+pub fn unknown_key_warnings<T: Serialize + DeserializeOwned>(
+    input: &Value,
+    parsed: &T,
+) -> Vec<String> {
+    unknown_keys(input, parsed)
+        .into_iter()
+        .map(|key| format!("unknown key {key} ignored"))
+        .collect()
+}
+
+fn collect_suspects(
+    input: &Value,
+    known: &Value,
+    path: &mut Vec<Segment>,
+    out: &mut Vec<Vec<Segment>>,
+) {
+    match (input, known) {
+        (Value::Table(input), Value::Table(known)) => {
+            for (key, value) in input {
+                path.push(Segment::Key(key.clone()));
+                match known.get(key) {
+                    Some(known) => collect_suspects(value, known, path, out),
+                    None => out.push(path.clone()),
+                }
+                path.pop();
+            }
+        }
+        (Value::Array(input), Value::Array(known)) => {
+            for (i, (value, known)) in input.iter().zip(known).enumerate() {
+                path.push(Segment::Index(i));
+                collect_suspects(value, known, path, out);
+                path.pop();
+            }
+        }
+        // Types différents (ancienne forme relue puis réécrite) : rien
+        // à comparer en dessous
+        _ => {}
+    }
+}
+
+/// `true` si `input` se charge encore avec, à `path`, une date puis un
+/// booléen : un champ texte lit la date (`toml::Value` la rend comme une
+/// chaîne), un champ booléen le booléen, aucun les deux.
+fn is_ignored<T: DeserializeOwned>(input: &Value, path: &[Segment]) -> bool {
+    let Ok(date) = "1979-05-27T07:32:00Z".parse() else {
+        return false;
+    };
+    [Value::Datetime(date), Value::Boolean(true)]
+        .into_iter()
+        .all(|probe| {
+            let mut input = input.clone();
+            match value_at(&mut input, path) {
+                Some(value) => *value = probe,
+                None => return false,
+            }
+            T::deserialize(input).is_ok()
+        })
+}
+
+fn value_at<'a>(value: &'a mut Value, path: &[Segment]) -> Option<&'a mut Value> {
+    path.iter().try_fold(value, |value, segment| match segment {
+        Segment::Key(key) => value.as_table_mut()?.get_mut(key),
+        Segment::Index(i) => value.as_array_mut()?.get_mut(*i),
+    })
+}
+
+fn format_path(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(key);
+            }
+            Segment::Index(i) => out.push_str(&format!("[{i}]")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+    use crate::profile::Profile;
+
+    fn unknown<T: Serialize + DeserializeOwned>(text: &str) -> Vec<String> {
+        let input: Value = toml::from_str(text).unwrap();
+        let parsed: T = input.clone().try_into().unwrap();
+        unknown_keys(&input, &parsed)
+    }
+
+    fn fixture(path: &str) -> String {
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        std::fs::read_to_string(fixtures.join(path)).unwrap()
+    }
+
+    #[test]
+    fn typos_are_found_at_every_depth() {
+        assert_eq!(
+            unknown::<AppConfig>(&fixture("config/typos.toml")),
+            [
+                "audio.idle_after_secs",
+                "journal.max_entries",
+                "profiles.studio.outptu_device",
+                "sample_rate",
+                "taps[0].capcity_blocks",
+            ]
+        );
+        assert_eq!(
+            unknown::<Profile>(&fixture("presets/typos.toml")),
+            [
+                "effects.compressor.treshold",
+                "effects.eq.bands[1].gain",
+                "mixer.bus_follow",
+                "mixer.channels[2].volumne_db",
+                "output_devise",
+            ]
+        );
+    }
+
+    #[test]
+    fn known_keys_left_out_when_saving_are_not_reported() {
+        // Listes vides et `None` ne sont pas réécrits, mais sont lus
+        let text = r#"
+            taps = []
+
+            [profiles]
+        "#;
+        assert!(unknown::<AppConfig>(text).is_empty());
+        let mut profile = toml::Value::try_from(Profile::default_profile()).unwrap();
+        let channel = &mut profile["mixer"]["channels"].as_array_mut().unwrap()[0];
+        let none: Value = toml::from_str("mode = \"none\"").unwrap();
+        channel
+            .as_table_mut()
+            .unwrap()
+            .insert("device_name".into(), none);
+        let text = toml::to_string(&profile).unwrap();
+        assert_eq!(unknown::<Profile>(&text), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,613 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+use troubadour_core::mixer::Mixer;
+use troubadour_core::taper::VolumeDecibels;
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::locale::Locale;
+use troubadour_shared::messages::{Command, Event};
+use troubadour_shared::mixer::{ChannelConfig, FaderTaper, MeterScale};
+
+/// Pas du fader par appui sur ↑/↓ (50 crans sur toute la course).
+pub const FADER_STEP: f32 = 0.02;
+
+/// Chute des VU-meters entre deux frames sans nouveau niveau.
+/// ×0.85 à ~30 fps ≈ -20 dB/s : le meter retombe sans clignoter.
+const METER_DECAY: f32 = 0.85;
+
+/// Panneau qui a le focus clavier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    /// Canaux d'entrée (Mic, Desktop...)
+    Channels,
+    /// Bus de sortie (Speakers, Stream...)
+    Buses,
+}
+
+/// Ce que l'écran affiche par-dessus le mixer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    Mixer,
+    /// Sélecteur de preset ouvert, curseur sur `selected`
+    PresetPicker {
+        selected: usize,
+    },
+}
+
+/// Ce que la boucle principale doit faire après une touche.
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Envoyer une commande au moteur
+    Command(Command),
+    /// Charger le profil de ce nom
+    LoadPreset(String),
+    /// Appliquer le preset d'effets favori `n` (0 = touche `1`) à la
+    /// tranche sélectionnée
+    LoadFavorite(usize),
+    Quit,
+}
+
+/// Une tranche (canal ou bus) telle qu'affichée pendant UNE frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StripView {
+    pub id: ChannelId,
+    pub name: String,
+    /// Position du fader (0.0 → 1.0) selon la courbe active
+    pub fader: f32,
+    pub muted: bool,
+    /// Solo d'entrée pour un canal, solo de bus pour un bus
+    pub solo: bool,
+    /// Crête récente, linéaire (0.0 → 1.0+)
+    pub level: f32,
+}
+
+impl StripView {
+    /// Niveau du fader en dB, pour l'étiquette sous la tranche.
+    pub fn fader_db(&self, taper: FaderTaper) -> VolumeDecibels {
+        VolumeDecibels::from_fader_position(self.fader, taper)
+    }
+
+    /// Remplissage du VU-meter (0.0 → 1.0) sur l'échelle du moteur.
+    pub fn meter_fraction(&self, scale: MeterScale) -> f32 {
+        scale.position(self.level)
+    }
+}
+
+/// Photo du mixer prise une fois par frame.
+///
+/// # Pourquoi une photo ?
+/// Le rendu et la gestion du clavier lisent beaucoup (noms, faders,
+/// mute...). Tout copier ici en un seul passage évite de retourner
+/// au mixer pour chaque cellule dessinée : l'accès au mixer se fait
+/// une fois par frame, jamais pendant le dessin.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MixerView {
+    pub taper: FaderTaper,
+    /// Échelle des meters du moteur (plancher de `controls.meter_min_db`)
+    pub meter_scale: MeterScale,
+    pub channels: Vec<StripView>,
+    pub buses: Vec<StripView>,
+}
+
+impl MixerView {
+    pub fn capture(
+        mixer: &Mixer,
+        locale: &Locale,
+        taper: FaderTaper,
+        levels: &HashMap<ChannelId, f32>,
+    ) -> Self {
+        let strip = |config: &ChannelConfig, solo: bool| StripView {
+            id: config.id,
+            name: locale.display_name(config),
+            fader: VolumeDecibels::from_linear(config.volume).to_fader_position(taper),
+            muted: config.muted,
+            solo,
+            level: levels.get(&config.id).copied().unwrap_or(0.0),
+        };
+        // Le mixer stocke ses canaux dans une HashMap : on trie par id
+        // pour que les tranches ne changent pas de place d'une frame à l'autre.
+        let mut channels: Vec<StripView> = mixer
+            .inputs()
+            .into_iter()
+            .map(|c| strip(c, c.solo))
+            .collect();
+        let mut buses: Vec<StripView> = mixer
+            .outputs()
+            .into_iter()
+            .map(|c| strip(c, mixer.is_bus_soloed(c.id)))
+            .collect();
+        channels.sort_by_key(|s| s.id.0);
+        buses.sort_by_key(|s| s.id.0);
+        Self {
+            taper,
+            meter_scale: mixer.meter_scale(),
+            channels,
+            buses,
+        }
+    }
+
+    pub fn strips(&self, pane: Pane) -> &[StripView] {
+        match pane {
+            Pane::Channels => &self.channels,
+            Pane::Buses => &self.buses,
+        }
+    }
+
+    fn strips_mut(&mut self, pane: Pane) -> &mut Vec<StripView> {
+        match pane {
+            Pane::Channels => &mut self.channels,
+            Pane::Buses => &mut self.buses,
+        }
+    }
+}
+
+/// État de l'interface terminal, indépendant du terminal lui-même.
+///
+/// Tout passe par `handle_key` / `handle_event` : testable sans
+/// terminal ni moteur audio.
+pub struct TuiApp {
+    pub pane: Pane,
+    pub mode: Mode,
+    /// Tranche sélectionnée dans chaque panneau
+    selected_channel: usize,
+    selected_bus: usize,
+    /// Profils proposés par le sélecteur
+    pub presets: Vec<String>,
+    /// Crête la plus récente de chaque canal (linéaire)
+    levels: HashMap<ChannelId, f32>,
+    /// Dernier message affiché en bas de l'écran
+    pub status: String,
+    /// Une session interrompue a laissé un instantané : `r` / `d` actifs
+    pub recovery_pending: bool,
+    /// Passthrough d'urgence actif (confirmé par le moteur), basculé par `!`
+    pub passthrough: bool,
+}
+
+impl TuiApp {
+    pub fn new(presets: Vec<String>) -> Self {
+        Self {
+            pane: Pane::Channels,
+            mode: Mode::Mixer,
+            selected_channel: 0,
+            selected_bus: 0,
+            presets,
+            levels: HashMap::new(),
+            status: String::new(),
+            recovery_pending: false,
+            passthrough: false,
+        }
+    }
+
+    pub fn levels(&self) -> &HashMap<ChannelId, f32> {
+        &self.levels
+    }
+
+    /// Index de la tranche sélectionnée dans un panneau.
+    pub fn selected(&self, pane: Pane) -> usize {
+        match pane {
+            Pane::Channels => self.selected_channel,
+            Pane::Buses => self.selected_bus,
+        }
+    }
+
+    /// Tranche sélectionnée dans le panneau actif.
+    pub fn selected_strip(&self, view: &MixerView) -> Option<ChannelId> {
+        view.strips(self.pane)
+            .get(self.selected(self.pane))
+            .map(|strip| strip.id)
+    }
+
+    fn selected_mut(&mut self, pane: Pane) -> &mut usize {
+        match pane {
+            Pane::Channels => &mut self.selected_channel,
+            Pane::Buses => &mut self.selected_bus,
+        }
+    }
+
+    /// Intègre un événement du moteur.
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::LevelUpdate(levels) => {
+                for level in levels {
+                    let current = self.levels.entry(level.channel).or_insert(0.0);
+                    *current = current.max(level.peak);
+                }
+            }
+            Event::EffectsPresetApplied { channel, name } => {
+                self.status = format!("Effects preset \"{name}\" on channel {}", channel.0);
+            }
+            Event::RecoveryRestored { .. } => self.status = "Unsaved session restored".to_string(),
+            Event::RecoveryDiscarded => self.status = "Unsaved session discarded".to_string(),
+            Event::EmergencyPassthroughChanged { bus } => {
+                self.passthrough = bus.is_some();
+                self.status = match bus {
+                    Some(bus) => format!("EMERGENCY PASSTHROUGH on bus {} (! to leave)", bus.0),
+                    None => "Emergency passthrough off".to_string(),
+                };
+            }
+            Event::StreamFailed {
+                device_name,
+                hint: Some(hint),
+                ..
+            } => self.status = format!("Error: cannot open {device_name}. {hint}"),
+            Event::ReplaySaved { path, seconds, .. } => {
+                self.status = format!("Replay saved ({seconds:.0} s): {}", path.display());
+            }
+            Event::PresetWarnings { name, warnings } => {
+                self.status = format!("Preset \"{name}\": {}", warnings.join("; "));
+            }
+            Event::Error(message) => self.status = format!("Error: {message}"),
+            _ => {}
+        }
+    }
+
+    /// Fait retomber les meters (appelé une fois par frame).
+    pub fn decay_meters(&mut self) {
+        for level in self.levels.values_mut() {
+            *level *= METER_DECAY;
+        }
+    }
+
+    /// Traite une touche.
+    ///
+    /// `view` est mise à jour tout de suite (fader, mute, solo) : deux
+    /// appuis dans la même frame s'additionnent au lieu de partir
+    /// tous les deux de la même valeur.
+    pub fn handle_key(&mut self, key: KeyCode, view: &mut MixerView) -> Option<Action> {
+        if let Mode::PresetPicker { selected } = self.mode {
+            return self.handle_picker_key(key, selected);
+        }
+
+        // Garder la sélection dans les bornes (canal supprimé entre-temps)
+        let count = view.strips(self.pane).len();
+        let selected = self.selected_mut(self.pane);
+        *selected = (*selected).min(count.saturating_sub(1));
+        let index = *selected;
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => Some(Action::Quit),
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.pane = match self.pane {
+                    Pane::Channels => Pane::Buses,
+                    Pane::Buses => Pane::Channels,
+                };
+                None
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                *self.selected_mut(self.pane) = index.saturating_sub(1);
+                None
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                *self.selected_mut(self.pane) = (index + 1).min(count.saturating_sub(1));
+                None
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.nudge_fader(view, index, FADER_STEP),
+            KeyCode::Down | KeyCode::Char('j') => self.nudge_fader(view, index, -FADER_STEP),
+            KeyCode::Char('m') => {
+                let strip = view.strips_mut(self.pane).get_mut(index)?;
+                strip.muted = !strip.muted;
+                Some(Action::Command(Command::SetMute {
+                    channel: strip.id,
+                    muted: strip.muted,
+                }))
+            }
+            KeyCode::Char('s') => {
+                let pane = self.pane;
+                let strip = view.strips_mut(pane).get_mut(index)?;
+                strip.solo = !strip.solo;
+                Some(Action::Command(match pane {
+                    Pane::Channels => Command::SetSolo {
+                        channel: strip.id,
+                        solo: strip.solo,
+                    },
+                    Pane::Buses => Command::ToggleBusSolo { bus: strip.id },
+                }))
+            }
+            KeyCode::Char('r') | KeyCode::Char('d') if self.recovery_pending => {
+                self.recovery_pending = false;
+                Some(Action::Command(if key == KeyCode::Char('r') {
+                    Command::RestoreRecoverySnapshot
+                } else {
+                    Command::DiscardRecoverySnapshot
+                }))
+            }
+            KeyCode::Char('!') => Some(Action::Command(Command::SetEmergencyPassthrough {
+                enabled: !self.passthrough,
+            })),
+            // "Clip ça" : la fin de chaque replay, dans le dossier des replays
+            KeyCode::Char('c') => Some(Action::Command(Command::SaveReplay {
+                bus: None,
+                path: None,
+                seconds: None,
+            })),
+            // Favoris : `1` → le premier
+            KeyCode::Char(digit @ '1'..='9') => {
+                Some(Action::LoadFavorite(digit as usize - '1' as usize))
+            }
+            KeyCode::Char('p') => {
+                if self.presets.is_empty() {
+                    self.status = "No presets available".to_string();
+                } else {
+                    self.mode = Mode::PresetPicker { selected: 0 };
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn nudge_fader(&mut self, view: &mut MixerView, index: usize, delta: f32) -> Option<Action> {
+        let strip = view.strips_mut(self.pane).get_mut(index)?;
+        let position = (strip.fader + delta).clamp(0.0, 1.0);
+        if position == strip.fader {
+            return None;
+        }
+        strip.fader = position;
+        Some(Action::Command(Command::SetVolumeFader {
+            channel: strip.id,
+            position,
+        }))
+    }
+
+    fn handle_picker_key(&mut self, key: KeyCode, selected: usize) -> Option<Action> {
+        let last = self.presets.len().saturating_sub(1);
+        match key {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.mode = Mode::PresetPicker {
+                    selected: selected.saturating_sub(1),
+                };
+                None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.mode = Mode::PresetPicker {
+                    selected: (selected + 1).min(last),
+                };
+                None
+            }
+            KeyCode::Enter => {
+                self.mode = Mode::Mixer;
+                self.presets.get(selected).cloned().map(Action::LoadPreset)
+            }
+            KeyCode::Esc | KeyCode::Char('p') | KeyCode::Char('q') => {
+                self.mode = Mode::Mixer;
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::mixer::{ChannelLevel, MixerConfig};
+
+    fn setup() -> (TuiApp, MixerView) {
+        let mixer = Mixer::from_config(MixerConfig::default_setup());
+        let view = MixerView::capture(
+            &mixer,
+            &Locale::default(),
+            FaderTaper::Audio,
+            &HashMap::new(),
+        );
+        (TuiApp::new(vec!["Default".into(), "Gaming".into()]), view)
+    }
+
+    #[test]
+    fn capture_splits_channels_and_buses() {
+        let (_, view) = setup();
+        assert_eq!(view.channels.len(), 3);
+        assert_eq!(view.buses.len(), 2);
+        // Volume 1.0 = 0 dB = 3/4 de la course en courbe audio
+        assert!((view.channels[0].fader - 0.75).abs() < 1e-4);
+    }
+
+    #[test]
+    fn capture_translates_factory_names() {
+        let mixer = Mixer::from_config(MixerConfig::default_setup());
+        let french = Locale::new("fr").unwrap();
+        let view = MixerView::capture(&mixer, &french, FaderTaper::Audio, &HashMap::new());
+        assert_eq!(view.channels[0].name, "Micro");
+        assert_eq!(view.buses[1].name, "Enceintes");
+    }
+
+    #[test]
+    fn arrows_move_fader_and_accumulate_within_a_frame() {
+        let (mut app, mut view) = setup();
+        let start = view.channels[0].fader;
+
+        app.handle_key(KeyCode::Up, &mut view);
+        let action = app.handle_key(KeyCode::Up, &mut view);
+
+        let Some(Action::Command(Command::SetVolumeFader { channel, position })) = action else {
+            panic!("expected SetVolumeFader, got {action:?}");
+        };
+        assert_eq!(channel, ChannelId(0));
+        assert!((position - (start + 2.0 * FADER_STEP)).abs() < 1e-6);
+
+        // En butée : plus de commande
+        view.channels[0].fader = 1.0;
+        assert!(app.handle_key(KeyCode::Up, &mut view).is_none());
+    }
+
+    #[test]
+    fn mute_and_solo_target_the_focused_pane() {
+        let (mut app, mut view) = setup();
+        app.handle_key(KeyCode::Right, &mut view);
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('m'), &mut view),
+            Some(Action::Command(Command::SetMute {
+                channel: ChannelId(1),
+                muted: true,
+            }))
+        ));
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('s'), &mut view),
+            Some(Action::Command(Command::SetSolo {
+                channel: ChannelId(1),
+                solo: true,
+            }))
+        ));
+
+        // Les bus ont leur propre sélection et leur propre solo
+        app.handle_key(KeyCode::Tab, &mut view);
+        assert_eq!(app.pane, Pane::Buses);
+        assert_eq!(app.selected(Pane::Buses), 0);
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('s'), &mut view),
+            Some(Action::Command(Command::ToggleBusSolo {
+                bus: ChannelId(3)
+            }))
+        ));
+        // Sélection bornée au dernier bus
+        for _ in 0..5 {
+            app.handle_key(KeyCode::Right, &mut view);
+        }
+        assert_eq!(app.selected(Pane::Buses), 1);
+    }
+
+    #[test]
+    fn preset_picker_selects_and_cancels() {
+        let (mut app, mut view) = setup();
+        app.handle_key(KeyCode::Char('p'), &mut view);
+        assert_eq!(app.mode, Mode::PresetPicker { selected: 0 });
+
+        // Les flèches déplacent le curseur, pas le fader
+        assert!(app.handle_key(KeyCode::Down, &mut view).is_none());
+        assert!(matches!(
+            app.handle_key(KeyCode::Enter, &mut view),
+            Some(Action::LoadPreset(name)) if name == "Gaming"
+        ));
+        assert_eq!(app.mode, Mode::Mixer);
+
+        app.handle_key(KeyCode::Char('p'), &mut view);
+        assert!(app.handle_key(KeyCode::Esc, &mut view).is_none());
+        assert_eq!(app.mode, Mode::Mixer);
+        // Esc hors du sélecteur = quitter
+        assert!(matches!(
+            app.handle_key(KeyCode::Esc, &mut view),
+            Some(Action::Quit)
+        ));
+    }
+
+    #[test]
+    fn digits_load_favorites_on_the_selected_strip() {
+        let (mut app, mut view) = setup();
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('1'), &mut view),
+            Some(Action::LoadFavorite(0))
+        ));
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('9'), &mut view),
+            Some(Action::LoadFavorite(8))
+        ));
+        assert!(app.handle_key(KeyCode::Char('0'), &mut view).is_none());
+
+        app.handle_key(KeyCode::Tab, &mut view);
+        app.handle_key(KeyCode::Right, &mut view);
+        assert_eq!(app.selected_strip(&view), Some(ChannelId(4)));
+        app.handle_event(&Event::EffectsPresetApplied {
+            channel: ChannelId(4),
+            name: "Voice".into(),
+        });
+        assert!(app.status.contains("Voice"));
+    }
+
+    #[test]
+    fn recovery_keys_only_when_a_snapshot_is_pending() {
+        let (mut app, mut view) = setup();
+        assert!(app.handle_key(KeyCode::Char('r'), &mut view).is_none());
+
+        app.recovery_pending = true;
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('r'), &mut view),
+            Some(Action::Command(Command::RestoreRecoverySnapshot))
+        ));
+        // Une seule réponse possible
+        assert!(app.handle_key(KeyCode::Char('d'), &mut view).is_none());
+    }
+
+    #[test]
+    fn bang_toggles_passthrough_as_confirmed_by_the_engine() {
+        let (mut app, mut view) = setup();
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('!'), &mut view),
+            Some(Action::Command(Command::SetEmergencyPassthrough {
+                enabled: true
+            }))
+        ));
+        app.handle_event(&Event::EmergencyPassthroughChanged {
+            bus: Some(ChannelId(3)),
+        });
+        assert!(app.status.contains("EMERGENCY PASSTHROUGH"));
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('!'), &mut view),
+            Some(Action::Command(Command::SetEmergencyPassthrough {
+                enabled: false
+            }))
+        ));
+    }
+
+    #[test]
+    fn c_saves_the_replays() {
+        let (mut app, mut view) = setup();
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('c'), &mut view),
+            Some(Action::Command(Command::SaveReplay {
+                bus: None,
+                path: None,
+                seconds: None
+            }))
+        ));
+        app.handle_event(&Event::PresetWarnings {
+            name: "Show".into(),
+            warnings: vec!["unknown key volumne ignored".into()],
+        });
+        assert_eq!(app.status, "Preset \"Show\": unknown key volumne ignored");
+        app.handle_event(&Event::ReplaySaved {
+            bus: ChannelId(3),
+            path: "Replay Headphones.wav".into(),
+            seconds: 30.0,
+        });
+        assert_eq!(app.status, "Replay saved (30 s): Replay Headphones.wav");
+    }
+
+    #[test]
+    fn level_updates_feed_meters_and_decay() {
+        let (mut app, _) = setup();
+        app.handle_event(&Event::LevelUpdate(vec![ChannelLevel {
+            channel: ChannelId(0),
+            rms: 0.5,
+            peak: 1.0,
+            rms_position: 0.95,
+            peak_position: 1.0,
+            loudness: None,
+            true_peak: None,
+            clipped: true,
+        }]));
+
+        let mut mixer = Mixer::from_config(MixerConfig::default_setup());
+        let view = MixerView::capture(&mixer, &Locale::default(), FaderTaper::Audio, app.levels());
+        let scale = view.meter_scale;
+        assert_eq!(view.channels[0].meter_fraction(scale), 1.0);
+        assert_eq!(view.channels[1].meter_fraction(scale), 0.0);
+
+        // 15 frames de retombée : ≈ -21 dB, plus bas sur une échelle courte
+        for _ in 0..15 {
+            app.decay_meters();
+        }
+        let view = MixerView::capture(&mixer, &Locale::default(), FaderTaper::Audio, app.levels());
+        let full = view.channels[0].meter_fraction(view.meter_scale);
+        mixer.set_meter_scale(MeterScale::new(Some(-42.0), None));
+        let view = MixerView::capture(&mixer, &Locale::default(), FaderTaper::Audio, app.levels());
+        let short = view.channels[0].meter_fraction(view.meter_scale);
+        assert!((full - 0.65).abs() < 0.01, "{full}");
+        assert!((short - 0.5).abs() < 0.01, "{short}");
+
+        for _ in 0..100 {
+            app.decay_meters();
+        }
+        let view = MixerView::capture(&mixer, &Locale::default(), FaderTaper::Audio, app.levels());
+        assert_eq!(view.channels[0].meter_fraction(view.meter_scale), 0.0);
+    }
+}
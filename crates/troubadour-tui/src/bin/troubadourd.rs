@@ -0,0 +1,5 @@
+/// Service sans interface (systemd), voir `troubadour_tui::daemon`.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(troubadour_tui::daemon::run(&args));
+}
@@ -0,0 +1,1126 @@
+//! Réglages du mixer en ligne de commande, sans interface :
+//! `troubadour set-volume mic -6`, `troubadour route mic speakers on`...
+//!
+//! Chaque commande charge la dernière session (le mixer que le prochain
+//! lancement rechargera), applique le changement par les mêmes
+//! `Command` que l'interface, puis réécrit la session de façon atomique.
+//!
+//! ```text
+//!  args ──► CliCommand::parse ──► Command ──► Engine (arrêté)
+//!                                                 │
+//!  "Last Session.toml" ◄── Profile::save ◄─────────┘  (sauf --dry-run)
+//! ```
+//!
+//! `restore-snapshot` remplace la session par un instantané automatique
+//! (pris avant un chargement de preset), après avoir sauvé la session
+//! actuelle de la même façon : `snapshots` les liste.
+//!
+//! `check-preset` ne touche pas à la session : il liste les paramètres
+//! hors plage d'un preset d'effets, ceux que le moteur ramènerait à
+//! leur borne en le chargeant. `check-devices` non plus : il cherche
+//! les devices d'un profil parmi ceux de la machine, avant de le
+//! charger. `analyze` et `normalize` non plus : ils
+//! mesurent un enregistrement WAV et en écrivent une copie normalisée.
+//! `export-preset` écrit un profil en JSON sur la sortie standard ;
+//! `import-preset` sauve un profil JSON dans le dossier des profils,
+//! sans l'appliquer. `favorites` liste les presets d'effets favoris de
+//! la config, dans l'ordre de leurs touches. `dsp-profile` chronomètre
+//! les effets de la session sur une voix de synthèse, au rate et à la
+//! taille de bloc de la config, du plus coûteux au moins coûteux.
+//!
+//! # Codes de sortie
+//! `0` : appliqué (ou simulé), `1` : refusé (canal inconnu, volume hors
+//! plage, fichier illisible, preset hors plage, device introuvable),
+//! `2` : arguments invalides.
+
+use std::path::Path;
+
+use troubadour_core::engine::Engine;
+use troubadour_core::journal;
+use troubadour_core::recording;
+use troubadour_core::recovery::AutoSnapshots;
+use troubadour_core::render::{self, RenderSettings};
+use troubadour_core::taper::{FADER_MAX_DB, FADER_MIN_DB, VolumeDecibels};
+use troubadour_core::template::ChannelTemplateRegistry;
+use troubadour_shared::audio::ChannelId;
+use troubadour_shared::compat::{CompatibilityReport, DeviceCheck};
+use troubadour_shared::config::{AppConfig, AudioConfig};
+use troubadour_shared::diff::ConfigDiff;
+use troubadour_shared::messages::{Command, Event};
+use troubadour_shared::mixer::{ChannelConfig, ChannelKind, MixerConfig};
+use troubadour_shared::preset::EffectsPresetManager;
+use troubadour_shared::profile::Profile;
+use troubadour_shared::recording::NormalizeTarget;
+use troubadour_shared::recovery::AutoSnapshot;
+use troubadour_shared::template::ChannelTemplate;
+
+use crate::LAST_SESSION;
+
+/// Sous-commandes reconnues (pour l'aiguillage du binaire).
+pub const SUBCOMMANDS: [&str; 15] = [
+    "set-volume",
+    "mute",
+    "unmute",
+    "route",
+    "add-channel",
+    "check-preset",
+    "check-devices",
+    "analyze",
+    "normalize",
+    "snapshots",
+    "restore-snapshot",
+    "export-preset",
+    "import-preset",
+    "favorites",
+    "dsp-profile",
+];
+
+/// Blocs traités par `dsp-profile` : assez pour que les moyennes se
+/// posent (~2 s à 256 frames et 48 kHz).
+const DSP_PROFILE_BLOCKS: usize = 400;
+
+const USAGE: &str = "Usage:
+  troubadour set-volume <channel> <dB> [--dry-run]
+  troubadour mute|unmute <channel> [--dry-run]
+  troubadour route <channel> <bus> on|off [--dry-run]
+  troubadour add-channel <template> <name> [--dry-run]
+  troubadour check-preset <name|file.toml>
+  troubadour check-devices <preset>
+  troubadour analyze <file.wav>
+  troubadour normalize <in.wav> <out.wav> --lufs <target>|--peak <dBTP>
+  troubadour snapshots
+  troubadour restore-snapshot <id> [--dry-run]
+  troubadour export-preset <name>
+  troubadour import-preset <name> <file.json> [--overwrite]
+  troubadour favorites
+  troubadour dsp-profile
+
+<channel> / <bus>: id, name, or the start of a name (case-insensitive)
+<dB>: -60 to +6, or -inf for silence";
+
+/// Une commande de la ligne de commande, canaux encore désignés par
+/// l'utilisateur (résolus contre le mixer chargé).
+#[derive(Debug, Clone, PartialEq)]
+pub enum CliCommand {
+    SetVolume {
+        channel: String,
+        db: f32,
+    },
+    SetMute {
+        channel: String,
+        muted: bool,
+    },
+    Route {
+        from: String,
+        to: String,
+        enabled: bool,
+    },
+    AddChannel {
+        template: String,
+        name: String,
+    },
+    /// Preset d'effets de la bibliothèque, ou chemin d'un `.toml`
+    CheckPreset {
+        preset: String,
+    },
+    /// Devices d'un profil (intégré ou sauvé) résolus sur cette machine
+    CheckDevices {
+        preset: String,
+    },
+    /// Crêtes, RMS et sonie d'un WAV
+    Analyze {
+        file: String,
+    },
+    /// Copie de `src` amenée à la cible, crête vraie sous -1 dBTP
+    Normalize {
+        src: String,
+        dst: String,
+        target: NormalizeTarget,
+    },
+    /// Instantanés automatiques, du plus récent au plus ancien
+    Snapshots,
+    /// Session remplacée par l'instantané `id`
+    RestoreSnapshot {
+        id: u64,
+    },
+    /// Profil intégré ou sauvé, en JSON
+    ExportPreset {
+        name: String,
+    },
+    /// Profil JSON sauvé sous `name`
+    ImportPreset {
+        name: String,
+        file: String,
+        overwrite: bool,
+    },
+    /// Presets d'effets favoris, dans leur ordre
+    Favorites,
+    /// Temps CPU de chaque effet de la session
+    DspProfile,
+}
+
+/// `<sous-commande> <arguments>... [--dry-run]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CliInvocation {
+    pub command: CliCommand,
+    /// Affiche le diff sans rien écrire
+    pub dry_run: bool,
+}
+
+impl CliInvocation {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let dry_run = args.iter().any(|a| a == "--dry-run");
+        let args: Vec<&str> = args
+            .iter()
+            .map(String::as_str)
+            .filter(|a| *a != "--dry-run")
+            .collect();
+        let command = match args.as_slice() {
+            ["set-volume", channel, db] => CliCommand::SetVolume {
+                channel: channel.to_string(),
+                db: db
+                    .parse()
+                    .map_err(|_| format!("Invalid volume: {db} (dB)"))?,
+            },
+            ["mute", channel] | ["unmute", channel] => CliCommand::SetMute {
+                channel: channel.to_string(),
+                muted: args[0] == "mute",
+            },
+            ["route", from, to, state] => CliCommand::Route {
+                from: from.to_string(),
+                to: to.to_string(),
+                enabled: match *state {
+                    "on" => true,
+                    "off" => false,
+                    other => return Err(format!("Invalid route state: {other} (on|off)")),
+                },
+            },
+            ["add-channel", template, name] => CliCommand::AddChannel {
+                template: template.to_string(),
+                name: name.to_string(),
+            },
+            ["check-preset", preset] => CliCommand::CheckPreset {
+                preset: preset.to_string(),
+            },
+            ["check-devices", preset] => CliCommand::CheckDevices {
+                preset: preset.to_string(),
+            },
+            ["analyze", file] => CliCommand::Analyze {
+                file: file.to_string(),
+            },
+            ["normalize", src, dst, kind, value] => {
+                let value: f32 = value
+                    .parse()
+                    .map_err(|_| format!("Invalid target: {value}"))?;
+                CliCommand::Normalize {
+                    src: src.to_string(),
+                    dst: dst.to_string(),
+                    target: match *kind {
+                        "--lufs" => NormalizeTarget::Lufs(value),
+                        "--peak" => NormalizeTarget::Peak(value),
+                        other => return Err(format!("Invalid target: {other} (--lufs|--peak)")),
+                    },
+                }
+            }
+            ["snapshots"] => CliCommand::Snapshots,
+            ["restore-snapshot", id] => CliCommand::RestoreSnapshot {
+                id: id
+                    .parse()
+                    .map_err(|_| format!("Invalid snapshot id: {id}"))?,
+            },
+            ["export-preset", name] => CliCommand::ExportPreset {
+                name: name.to_string(),
+            },
+            ["favorites"] => CliCommand::Favorites,
+            ["dsp-profile"] => CliCommand::DspProfile,
+            ["import-preset", name, file] | ["import-preset", name, file, "--overwrite"] => {
+                CliCommand::ImportPreset {
+                    name: name.to_string(),
+                    file: file.to_string(),
+                    overwrite: args.len() == 4,
+                }
+            }
+            [other, ..] if SUBCOMMANDS.contains(other) => {
+                return Err(format!("Wrong arguments for {other}"));
+            }
+            [other, ..] => return Err(format!("Unknown command: {other}")),
+            [] => return Err("Missing command".to_string()),
+        };
+        Ok(Self { command, dry_run })
+    }
+}
+
+/// Canaux d'où part une route.
+const ROUTE_SOURCES: &[ChannelKind] = &[ChannelKind::Input, ChannelKind::Submix];
+/// Canaux où arrive une route.
+const ROUTE_DESTINATIONS: &[ChannelKind] = &[ChannelKind::Output, ChannelKind::Submix];
+
+/// Trouve un canal : par id, par nom exact, puis par début de nom
+/// (`mic` → "Mic", `guest` → "Guest Mic"), sans tenir compte de la
+/// casse. Un début de nom partagé par plusieurs canaux est refusé.
+///
+/// `kinds` restreint la recherche, vide = tous les canaux (une route va
+/// d'une entrée ou d'un sous-mix vers un bus ou un sous-mix).
+pub fn resolve_channel(
+    mixer: &MixerConfig,
+    ident: &str,
+    kinds: &[ChannelKind],
+) -> Result<ChannelId, String> {
+    let candidates: Vec<&ChannelConfig> = mixer
+        .channels
+        .iter()
+        .filter(|c| kinds.is_empty() || kinds.contains(&c.kind))
+        .collect();
+    let what = if kinds.contains(&ChannelKind::Output) {
+        "bus"
+    } else {
+        "channel"
+    };
+    if let Ok(id) = ident.parse::<usize>() {
+        return candidates
+            .iter()
+            .find(|c| c.id.0 == id)
+            .map(|c| c.id)
+            .ok_or_else(|| format!("Unknown {what}: {ident}"));
+    }
+    if let Some(exact) = candidates
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case(ident))
+    {
+        return Ok(exact.id);
+    }
+    let lower = ident.to_lowercase();
+    let matches: Vec<&&ChannelConfig> = candidates
+        .iter()
+        .filter(|c| c.name.to_lowercase().starts_with(&lower))
+        .collect();
+    match matches.as_slice() {
+        [one] => Ok(one.id),
+        [] => Err(format!("Unknown {what}: {ident}")),
+        many => {
+            let names: Vec<&str> = many.iter().map(|c| c.name.as_str()).collect();
+            Err(format!(
+                "Ambiguous {what} \"{ident}\": {}",
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// `troubadour <set-volume|mute|unmute|route|add-channel|check-preset> ...`
+///
+/// Retourne le code de sortie.
+pub fn run(args: &[String]) -> i32 {
+    let templates = ChannelTemplateRegistry::load(&ChannelTemplate::default_dir());
+    let config = AppConfig::load(&AppConfig::default_path())
+        .map(|config| config.auto_snapshots)
+        .unwrap_or_default();
+    let snapshots = AutoSnapshots::from_config(&config, AutoSnapshot::default_dir());
+    run_in(args, &Profile::profiles_dir(), &snapshots, templates)
+}
+
+/// `run` sur des dossiers donnés (les tests passent des dossiers
+/// temporaires).
+pub fn run_in(
+    args: &[String],
+    profiles_dir: &Path,
+    snapshots: &AutoSnapshots,
+    templates: ChannelTemplateRegistry,
+) -> i32 {
+    let invocation = match CliInvocation::parse(args) {
+        Ok(invocation) => invocation,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            return 2;
+        }
+    };
+    match execute(&invocation, profiles_dir, snapshots, templates) {
+        Ok(lines) => {
+            for line in lines {
+                println!("{line}");
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            1
+        }
+    }
+}
+
+/// Applique la commande à la session ; retourne les lignes à afficher.
+fn execute(
+    invocation: &CliInvocation,
+    profiles_dir: &Path,
+    snapshots: &AutoSnapshots,
+    templates: ChannelTemplateRegistry,
+) -> Result<Vec<String>, String> {
+    match invocation.command {
+        CliCommand::CheckPreset { ref preset } => return check_preset(preset),
+        CliCommand::CheckDevices { ref preset } => {
+            let config = AppConfig::load(&AppConfig::default_path()).unwrap_or_default();
+            return check_devices(preset, profiles_dir, &config.audio);
+        }
+        CliCommand::Snapshots => return Ok(list_snapshots(snapshots)),
+        CliCommand::Analyze { ref file } => {
+            let report = recording::analyze_recording(Path::new(file), |_| {})
+                .map_err(|e| format!("Analysis failed: {e}"))?;
+            return Ok(vec![format!("{file}: {report}")]);
+        }
+        CliCommand::Normalize {
+            ref src,
+            ref dst,
+            target,
+        } => return normalize(src, dst, target),
+        CliCommand::ExportPreset { ref name } => {
+            let mut engine = Engine::new().0;
+            engine.set_profiles_dir(profiles_dir.to_path_buf());
+            let json = engine
+                .export_profile_json(name)
+                .map_err(|e| format!("Cannot export preset {name}: {e}"))?;
+            return Ok(vec![json]);
+        }
+        CliCommand::ImportPreset {
+            ref name,
+            ref file,
+            overwrite,
+        } => return import_preset(name, file, overwrite, profiles_dir),
+        CliCommand::Favorites => {
+            let config = AppConfig::load(&AppConfig::default_path()).unwrap_or_default();
+            return Ok(list_favorites(&config));
+        }
+        _ => {}
+    }
+    let path = profiles_dir.join(format!("{LAST_SESSION}.toml"));
+    let mut session = if path.exists() {
+        let (session, warnings) =
+            Profile::load(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        for warning in warnings {
+            eprintln!("Warning: {}: {warning}", path.display());
+        }
+        session
+    } else {
+        Profile::default_profile()
+    };
+    if let CliCommand::RestoreSnapshot { id } = invocation.command {
+        return restore_snapshot(id, &session, &path, snapshots, invocation.dry_run);
+    }
+    if invocation.command == CliCommand::DspProfile {
+        let config = AppConfig::load(&AppConfig::default_path()).unwrap_or_default();
+        return Ok(dsp_profile(&session, &config.audio));
+    }
+    let before = session.mixer.clone();
+
+    // Validation propre à la ligne de commande, avant le moteur : les
+    // commandes de l'interface ignorent un canal inconnu sans erreur
+    let (command, changed) = match invocation.command {
+        CliCommand::SetVolume { ref channel, db } => {
+            // `-inf` : fader en butée, silence total
+            if db != f32::NEG_INFINITY && !(FADER_MIN_DB..=FADER_MAX_DB).contains(&db) {
+                return Err(format!(
+                    "Volume out of range: {db} dB (-inf, or {FADER_MIN_DB} to +{FADER_MAX_DB} dB)"
+                ));
+            }
+            let channel = resolve_channel(&before, channel, &[])?;
+            let level = VolumeDecibels(db).to_linear();
+            (Command::SetVolume { channel, level }, channel)
+        }
+        CliCommand::SetMute { ref channel, muted } => {
+            let channel = resolve_channel(&before, channel, &[])?;
+            (Command::SetMute { channel, muted }, channel)
+        }
+        CliCommand::Route {
+            ref from,
+            ref to,
+            enabled,
+        } => {
+            let from = resolve_channel(&before, from, ROUTE_SOURCES)?;
+            let to = resolve_channel(&before, to, ROUTE_DESTINATIONS)?;
+            let command = if enabled {
+                Command::AddRoute { from, to }
+            } else {
+                Command::RemoveRoute { from, to }
+            };
+            (command, from)
+        }
+        CliCommand::AddChannel {
+            ref template,
+            ref name,
+        } => {
+            let channel = ChannelId(
+                before
+                    .channels
+                    .iter()
+                    .map(|c| c.id.0 + 1)
+                    .max()
+                    .unwrap_or(0),
+            );
+            let command = Command::AddChannelFromTemplate {
+                channel,
+                name: name.clone(),
+                template: template.clone(),
+            };
+            (command, channel)
+        }
+        CliCommand::CheckPreset { .. }
+        | CliCommand::CheckDevices { .. }
+        | CliCommand::Analyze { .. }
+        | CliCommand::Normalize { .. }
+        | CliCommand::Snapshots
+        | CliCommand::RestoreSnapshot { .. }
+        | CliCommand::ExportPreset { .. }
+        | CliCommand::ImportPreset { .. }
+        | CliCommand::Favorites
+        | CliCommand::DspProfile => unreachable!("handled above"),
+    };
+
+    let (mut engine, channels) = Engine::new();
+    engine.set_channel_templates(templates);
+    engine.apply_profile(&session).map_err(|e| e.to_string())?;
+    let _ = channels.command_tx.send(command);
+    engine.process_commands();
+    if let Some(error) = channels.event_rx.try_iter().find_map(|e| match e {
+        Event::Error(message) => Some(message),
+        _ => None,
+    }) {
+        return Err(error);
+    }
+
+    let after = engine.mixer().to_config();
+    if invocation.dry_run {
+        return Ok(dry_run(&before.diff(&after), &path));
+    }
+
+    // Seul le mixer change : effets et devices de la session restent
+    // tels qu'ils ont été lus
+    let line = describe(&invocation.command, &after, changed);
+    session.mixer = after;
+    session
+        .save(&path)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(vec![line])
+}
+
+/// Ce qu'écrirait la commande, sans rien écrire.
+fn dry_run(diff: &ConfigDiff, path: &Path) -> Vec<String> {
+    let mut lines: Vec<String> = diff.entries.iter().map(ToString::to_string).collect();
+    if diff.is_empty() {
+        lines.push("No change".to_string());
+    }
+    lines.push(format!("Dry run: {} not written", path.display()));
+    lines
+}
+
+/// Une ligne par instantané : "1760000000000  ApplyProfile".
+fn list_snapshots(snapshots: &AutoSnapshots) -> Vec<String> {
+    let list = snapshots.list();
+    if list.is_empty() {
+        return vec!["No auto snapshots".to_string()];
+    }
+    list.iter()
+        .map(|info| format!("{}  {}", info.id, info.reason))
+        .collect()
+}
+
+/// Remplace la session par l'instantané `id`, après avoir sauvé la
+/// session actuelle comme le ferait le moteur (refusé si l'instantané
+/// ne s'écrit pas en mode strict).
+fn restore_snapshot(
+    id: u64,
+    session: &Profile,
+    path: &Path,
+    snapshots: &AutoSnapshots,
+    dry_run_only: bool,
+) -> Result<Vec<String>, String> {
+    let snapshot = snapshots
+        .load(id)
+        .map_err(|e| format!("Cannot read auto snapshot {id}: {e}"))?;
+    if dry_run_only {
+        return Ok(dry_run(&session.mixer.diff(&snapshot.profile.mixer), path));
+    }
+    let mut lines = Vec::new();
+    if let Err(e) = snapshots.take("RestoreAutoSnapshot", session, journal::now_ms()) {
+        let message = format!("Cannot save auto snapshot of the current session: {e}");
+        if snapshots.is_strict() {
+            return Err(message);
+        }
+        lines.push(format!("Warning: {message}"));
+    }
+    let restored = Profile {
+        name: LAST_SESSION.to_string(),
+        ..snapshot.profile
+    };
+    restored
+        .save(path)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    lines.push(format!("Restored auto snapshot {id} ({})", snapshot.reason));
+    Ok(lines)
+}
+
+/// Une ligne par favori : "1. Voice", "2. Night (missing)".
+fn list_favorites(config: &AppConfig) -> Vec<String> {
+    let manager = config
+        .preset_dir
+        .as_ref()
+        .map_or_else(EffectsPresetManager::default, EffectsPresetManager::new);
+    let favorites = manager.favorites(&config.favorites);
+    if favorites.is_empty() {
+        return vec!["No favorite presets".to_string()];
+    }
+    favorites
+        .iter()
+        .enumerate()
+        .map(|(i, favorite)| {
+            let missing = if favorite.missing { " (missing)" } else { "" };
+            format!("{}. {}{missing}", i + 1, favorite.name)
+        })
+        .collect()
+}
+
+/// Le budget d'un bloc, puis une ligne par effet ("Mic #3 Compressor:
+/// 41.2 µs (0.77 %)") du plus coûteux au moins coûteux, puis le total.
+fn dsp_profile(session: &Profile, audio: &AudioConfig) -> Vec<String> {
+    let settings = RenderSettings {
+        sample_rate: audio.sample_rate.as_hz(),
+        block_frames: audio.buffer_size.as_frames() as usize,
+    };
+    let mut profile = render::profile_effects(session, settings, DSP_PROFILE_BLOCKS);
+    profile.stages.sort_by(|a, b| b.micros.total_cmp(&a.micros));
+    let channel_name = |id: ChannelId| {
+        session
+            .mixer
+            .channels
+            .iter()
+            .find(|c| c.id == id)
+            .map_or_else(|| format!("channel {}", id.0), |c| c.name.clone())
+    };
+    let mut lines = vec![format!(
+        "Block budget: {:.0} µs ({} frames at {} Hz)",
+        profile.block_budget_us, settings.block_frames, settings.sample_rate
+    )];
+    lines.extend(profile.stages.iter().map(|stage| {
+        format!(
+            "{} #{} {}: {:.1} µs ({:.2} %)",
+            channel_name(stage.channel),
+            stage.effect_index,
+            stage.name,
+            stage.micros,
+            stage.budget_percent
+        )
+    }));
+    lines.push(format!(
+        "Total: {:.2} % of the block",
+        profile.total_percent()
+    ));
+    lines
+}
+
+/// Une ligne par paramètre hors plage (`compressor.attack = 5 (allowed
+/// 0.001 to 0.5)`) ; refusé s'il y en a au moins un.
+fn check_preset(preset: &str) -> Result<Vec<String>, String> {
+    let (loaded, warnings) = if preset.ends_with(".toml") {
+        EffectsPresetManager::load_file(Path::new(preset))
+    } else {
+        EffectsPresetManager::default().load(preset)
+    }
+    .map_err(|e| format!("Failed to read preset {preset}: {e}"))?;
+    let mut lines: Vec<String> = warnings.iter().map(|w| format!("Warning: {w}")).collect();
+    let violations = loaded.validate();
+    if violations.is_empty() {
+        lines.push(format!("{preset}: all parameters in range"));
+        return Ok(lines);
+    }
+    let lines: Vec<String> = lines
+        .into_iter()
+        .chain(violations.iter().map(ToString::to_string))
+        .collect();
+    Err(format!(
+        "{preset}: {} parameter(s) out of range\n{}",
+        lines.len(),
+        lines.join("\n")
+    ))
+}
+
+/// Devices du profil `preset` sur cette machine, au rate et au nombre
+/// de canaux de la config. Un device introuvable ou un problème de rate
+/// est un refus ; un device retrouvé sous un autre nom est signalé.
+fn check_devices(
+    preset: &str,
+    profiles_dir: &Path,
+    audio: &AudioConfig,
+) -> Result<Vec<String>, String> {
+    let mut engine = Engine::new().0;
+    engine.set_profiles_dir(profiles_dir.to_path_buf());
+    let _ = engine.set_sample_rate(audio.sample_rate);
+    engine.set_max_channels(audio.max_channels);
+    let report = engine
+        .check_preset_compatibility(preset)
+        .map_err(|e| format!("Cannot check preset {preset}: {e}"))?;
+    compatibility_lines(&report)
+}
+
+/// Lignes du rapport ; `Err` si le preset ne se chargerait pas entier.
+fn compatibility_lines(report: &CompatibilityReport) -> Result<Vec<String>, String> {
+    let mut lines: Vec<String> = report.devices.iter().map(DeviceCheck::summary).collect();
+    lines.extend(report.issues.iter().cloned());
+    let missing = report.missing().count();
+    if missing > 0 || !report.issues.is_empty() {
+        return Err(format!(
+            "{}: {missing} device(s) missing, {} issue(s)\n{}",
+            report.preset,
+            report.issues.len(),
+            lines.join("\n")
+        ));
+    }
+    if report.devices.is_empty() {
+        lines.push(format!("{}: no device to resolve", report.preset));
+    } else if report.is_clean() {
+        lines.push(format!("{}: all devices found", report.preset));
+    }
+    Ok(lines)
+}
+
+/// Sauve le profil JSON `file` sous `name` ; une ligne par clé
+/// inconnue, ignorée.
+fn import_preset(
+    name: &str,
+    file: &str,
+    overwrite: bool,
+    profiles_dir: &Path,
+) -> Result<Vec<String>, String> {
+    let json = std::fs::read_to_string(file).map_err(|e| format!("Failed to read {file}: {e}"))?;
+    let mut engine = Engine::new().0;
+    engine.set_profiles_dir(profiles_dir.to_path_buf());
+    let (name, warnings) = engine
+        .import_profile_json(name, &json, overwrite)
+        .map_err(|e| format!("Cannot import preset {name}: {e}"))?;
+    let mut lines: Vec<String> = warnings.iter().map(|w| format!("Warning: {w}")).collect();
+    lines.push(format!("Saved preset {name}"));
+    Ok(lines)
+}
+
+/// Écrit la copie normalisée ; signale une cible non atteinte.
+fn normalize(src: &str, dst: &str, target: NormalizeTarget) -> Result<Vec<String>, String> {
+    let outcome = recording::normalize_recording(Path::new(src), Path::new(dst), target, |_| {})
+        .map_err(|e| format!("Normalization failed: {e}"))?;
+    let mut lines = vec![format!("{dst}: {:+.1} dB applied", outcome.gain_db)];
+    if outcome.limited {
+        lines.push(format!(
+            "{target} not reached: gain limited to keep the true peak under -1 dBTP"
+        ));
+    }
+    Ok(lines)
+}
+
+/// Valeur obtenue, une ligne : "Mic: -6.0 dB", "Mic -> Speakers: on".
+fn describe(command: &CliCommand, mixer: &MixerConfig, channel: ChannelId) -> String {
+    let name = |id: ChannelId| {
+        mixer
+            .channels
+            .iter()
+            .find(|c| c.id == id)
+            .map_or(String::new(), |c| c.name.clone())
+    };
+    let config = mixer.channels.iter().find(|c| c.id == channel);
+    match command {
+        CliCommand::SetVolume { .. } => {
+            let volume = config.map_or(0.0, |c| c.volume);
+            format!("{}: {}", name(channel), VolumeDecibels::from_linear(volume))
+        }
+        CliCommand::SetMute { .. } => {
+            let muted = config.is_some_and(|c| c.muted);
+            let state = if muted { "muted" } else { "unmuted" };
+            format!("{}: {state}", name(channel))
+        }
+        CliCommand::Route { to, .. } => {
+            let to = resolve_channel(mixer, to, ROUTE_DESTINATIONS).unwrap_or(channel);
+            let state = if mixer.has_route(channel, to) {
+                "on"
+            } else {
+                "off"
+            };
+            format!("{} -> {}: {state}", name(channel), name(to))
+        }
+        CliCommand::AddChannel { .. } => {
+            format!("Added channel {} \"{}\"", channel.0, name(channel))
+        }
+        CliCommand::CheckPreset { .. }
+        | CliCommand::CheckDevices { .. }
+        | CliCommand::Analyze { .. }
+        | CliCommand::Normalize { .. }
+        | CliCommand::Snapshots
+        | CliCommand::RestoreSnapshot { .. }
+        | CliCommand::ExportPreset { .. }
+        | CliCommand::ImportPreset { .. }
+        | CliCommand::Favorites
+        | CliCommand::DspProfile => unreachable!("these leave the session alone"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::dsp::EffectsPreset;
+    use troubadour_shared::mixer::DeviceAssignment;
+    use troubadour_shared::preset::FavoritePresets;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Dossier de profils vide, propre à chaque test.
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("troubadour-cli-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn run_cli(dir: &Path, list: &[&str]) -> i32 {
+        run_in(
+            &args(list),
+            dir,
+            &snapshots(dir),
+            ChannelTemplateRegistry::builtin(),
+        )
+    }
+
+    fn snapshots(dir: &Path) -> AutoSnapshots {
+        let config = troubadour_shared::config::AutoSnapshotConfig::default();
+        AutoSnapshots::from_config(&config, dir.join("autosnapshots"))
+    }
+
+    fn session(dir: &Path) -> MixerConfig {
+        Profile::load(&dir.join(format!("{LAST_SESSION}.toml")))
+            .unwrap()
+            .0
+            .mixer
+    }
+
+    fn channel<'a>(mixer: &'a MixerConfig, name: &str) -> &'a ChannelConfig {
+        mixer.channels.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn parse_subcommands() {
+        assert_eq!(
+            CliInvocation::parse(&args(&["set-volume", "mic", "-6", "--dry-run"])).unwrap(),
+            CliInvocation {
+                command: CliCommand::SetVolume {
+                    channel: "mic".into(),
+                    db: -6.0,
+                },
+                dry_run: true,
+            }
+        );
+        assert_eq!(
+            CliInvocation::parse(&args(&["unmute", "2"]))
+                .unwrap()
+                .command,
+            CliCommand::SetMute {
+                channel: "2".into(),
+                muted: false,
+            }
+        );
+        assert!(CliInvocation::parse(&args(&["set-volume", "mic", "loud"])).is_err());
+        assert!(CliInvocation::parse(&args(&["route", "mic", "A1", "maybe"])).is_err());
+        assert!(CliInvocation::parse(&args(&["mute"])).is_err());
+        assert!(CliInvocation::parse(&args(&["bogus"])).is_err());
+    }
+
+    #[test]
+    fn channels_resolve_by_id_name_or_unique_prefix() {
+        let mut mixer = MixerConfig::default_setup();
+        mixer.channels.push(ChannelConfig::input(7, "Guest Mic"));
+        assert_eq!(resolve_channel(&mixer, "mic", &[]), Ok(ChannelId(0)));
+        assert_eq!(resolve_channel(&mixer, "guest", &[]), Ok(ChannelId(7)));
+        assert_eq!(resolve_channel(&mixer, "1", &[]), Ok(ChannelId(1)));
+        assert_eq!(
+            resolve_channel(&mixer, "speak", &[ChannelKind::Output]),
+            Ok(ChannelId(4))
+        );
+        // Un bus n'est pas une entrée
+        assert!(resolve_channel(&mixer, "speakers", &[ChannelKind::Input]).is_err());
+        assert!(resolve_channel(&mixer, "99", &[]).is_err());
+        assert_eq!(resolve_channel(&mixer, "d", &[]), Ok(ChannelId(1)));
+        mixer.channels.push(ChannelConfig::input(8, "Discord"));
+        assert!(
+            resolve_channel(&mixer, "d", &[])
+                .unwrap_err()
+                .contains("Ambiguous")
+        );
+    }
+
+    #[test]
+    fn each_subcommand_edits_the_saved_session() {
+        let dir = temp_dir("edits");
+
+        assert_eq!(run_cli(&dir, &["set-volume", "mic", "-6"]), 0);
+        let volume = channel(&session(&dir), "Mic").volume;
+        assert!((VolumeDecibels::from_linear(volume).0 + 6.0).abs() < 1e-3);
+        // Fader en butée : silence exact, relu tel quel
+        assert_eq!(run_cli(&dir, &["set-volume", "browser", "-inf"]), 0);
+        assert_eq!(channel(&session(&dir), "Browser").volume, 0.0);
+
+        assert_eq!(run_cli(&dir, &["mute", "desktop"]), 0);
+        assert!(channel(&session(&dir), "Desktop").muted);
+
+        assert_eq!(run_cli(&dir, &["route", "browser", "speakers", "on"]), 0);
+        assert!(session(&dir).has_route(ChannelId(2), ChannelId(4)));
+        assert_eq!(run_cli(&dir, &["route", "browser", "speakers", "off"]), 0);
+        assert!(!session(&dir).has_route(ChannelId(2), ChannelId(4)));
+
+        assert_eq!(run_cli(&dir, &["add-channel", "mic", "Guest Mic"]), 0);
+        let mixer = session(&dir);
+        let guest = channel(&mixer, "Guest Mic");
+        assert_eq!((guest.id, guest.kind), (ChannelId(5), ChannelKind::Input));
+        // Les réglages précédents sont toujours là
+        assert!(channel(&mixer, "Desktop").muted);
+        assert_eq!(run_cli(&dir, &["mute", "guest"]), 0);
+        assert!(channel(&session(&dir), "Guest Mic").muted);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn restore_snapshot_replaces_the_session_and_keeps_it_undoable() {
+        let dir = temp_dir("restore-snapshot");
+        assert_eq!(run_cli(&dir, &["snapshots"]), 0);
+        assert_eq!(run_cli(&dir, &["restore-snapshot", "42"]), 1);
+
+        let mut saved = Profile::default_profile();
+        saved.mixer.channels[0].muted = true;
+        let id = snapshots(&dir)
+            .take("ApplyProfile", &saved, 1_000)
+            .unwrap()
+            .id;
+
+        assert_eq!(
+            run_cli(&dir, &["restore-snapshot", &id.to_string(), "--dry-run"]),
+            0
+        );
+        assert!(!dir.join(format!("{LAST_SESSION}.toml")).exists());
+
+        assert_eq!(run_cli(&dir, &["restore-snapshot", &id.to_string()]), 0);
+        assert!(session(&dir).channels[0].muted);
+        // La session remplacée est elle-même un instantané
+        let list = snapshots(&dir).list();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].reason, "RestoreAutoSnapshot");
+        assert!(
+            !snapshots(&dir)
+                .load(list[0].id)
+                .unwrap()
+                .profile
+                .mixer
+                .channels[0]
+                .muted
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn favorites_are_listed_in_order_with_missing_ones_flagged() {
+        let dir = temp_dir("favorites");
+        EffectsPresetManager::new(&dir)
+            .save("Voice", &EffectsPreset::streaming(), false)
+            .unwrap();
+        let mut config = AppConfig {
+            preset_dir: Some(dir.clone()),
+            ..AppConfig::default()
+        };
+        assert_eq!(list_favorites(&config), ["No favorite presets"]);
+
+        config.favorites = FavoritePresets::new(["Night", "Voice"].map(String::from));
+        assert_eq!(list_favorites(&config), ["1. Night (missing)", "2. Voice"]);
+        assert_eq!(
+            CliInvocation::parse(&args(&["favorites"])).unwrap().command,
+            CliCommand::Favorites
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn dsp_profile_lists_each_effect_by_cost() {
+        let session = Profile::default_profile();
+        let lines = dsp_profile(&session, &AudioConfig::default());
+        assert_eq!(
+            lines.first().unwrap(),
+            "Block budget: 5333 µs (256 frames at 48000 Hz)"
+        );
+        assert!(lines.last().unwrap().starts_with("Total: "));
+        // Les six effets du Mic, chacun une fois
+        let effects = &lines[1..lines.len() - 1];
+        assert_eq!(effects.len(), 6);
+        assert!(effects.iter().all(|line| line.starts_with("Mic #")));
+        assert!(effects.iter().any(|line| line.contains("#3 Compressor: ")));
+        assert_eq!(
+            CliInvocation::parse(&args(&["dsp-profile"]))
+                .unwrap()
+                .command,
+            CliCommand::DspProfile
+        );
+    }
+
+    #[test]
+    fn check_preset_reports_out_of_range_parameters() {
+        let dir = temp_dir("check-preset");
+        std::fs::create_dir_all(&dir).unwrap();
+        let write = |name: &str, preset: &EffectsPreset| {
+            let path = dir.join(name);
+            let manager = EffectsPresetManager::new(&dir);
+            manager
+                .save(name.trim_end_matches(".toml"), preset, true)
+                .unwrap();
+            path.to_string_lossy().into_owned()
+        };
+        let good = write("good.toml", &EffectsPreset::streaming());
+        let mut slow = EffectsPreset::streaming();
+        slow.compressor.attack = 5.0;
+        let bad = write("bad.toml", &slow);
+
+        assert_eq!(
+            CliInvocation::parse(&args(&["check-preset", &bad]))
+                .unwrap()
+                .command,
+            CliCommand::CheckPreset {
+                preset: bad.clone()
+            }
+        );
+        assert_eq!(check_preset(&good).unwrap().len(), 1);
+        let error = check_preset(&bad).unwrap_err();
+        assert!(error.contains("compressor.attack = 5 (allowed 0.001 to 0.5)"));
+        assert_eq!(run_cli(&dir, &["check-preset", &bad]), 1);
+        assert_eq!(run_cli(&dir, &["check-preset", &good]), 0);
+        // La session n'a pas été créée
+        assert!(!dir.join(format!("{LAST_SESSION}.toml")).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn check_devices_refuses_presets_with_missing_devices() {
+        let dir = temp_dir("check-devices");
+        let mut elsewhere = Profile::default_profile();
+        elsewhere.name = "Elsewhere".into();
+        elsewhere.mixer.channels[0].device_name =
+            DeviceAssignment::Specific("Troubadour Test Mic 1195".into());
+        elsewhere.save(&dir.join("Elsewhere.toml")).unwrap();
+
+        assert_eq!(
+            CliInvocation::parse(&args(&["check-devices", "Elsewhere"]))
+                .unwrap()
+                .command,
+            CliCommand::CheckDevices {
+                preset: "Elsewhere".into()
+            }
+        );
+        let error = check_devices("Elsewhere", &dir, &AudioConfig::default()).unwrap_err();
+        assert!(
+            error.contains("Mic: Troubadour Test Mic 1195 not found"),
+            "{error}"
+        );
+        assert_eq!(run_cli(&dir, &["check-devices", "Elsewhere"]), 1);
+        // Le profil par défaut ne nomme aucun device
+        assert_eq!(
+            check_devices("Default", &dir, &AudioConfig::default()).unwrap(),
+            ["Default: no device to resolve"]
+        );
+        assert_eq!(run_cli(&dir, &["check-devices", "Nowhere"]), 1);
+        assert!(!dir.join(format!("{LAST_SESSION}.toml")).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn presets_export_and_import_as_json() {
+        let dir = temp_dir("preset-json");
+        let json = execute(
+            &CliInvocation::parse(&args(&["export-preset", "streaming"])).unwrap(),
+            &dir,
+            &snapshots(&dir),
+            ChannelTemplateRegistry::builtin(),
+        )
+        .unwrap()
+        .remove(0);
+        assert!(json.contains("\"version\": 1"));
+        let file = dir.join("streaming.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&file, &json).unwrap();
+        let file = file.to_string_lossy().into_owned();
+
+        assert_eq!(run_cli(&dir, &["import-preset", "Live", &file]), 0);
+        let saved = Profile::load(&dir.join("Live.toml")).unwrap().0;
+        assert_eq!(saved.name, "Live");
+        let names = |p: &Profile| -> Vec<String> {
+            p.mixer.channels.iter().map(|c| c.name.clone()).collect()
+        };
+        assert_eq!(names(&saved), names(&Profile::streaming()));
+        // Nom pris : seulement avec --overwrite
+        assert_eq!(run_cli(&dir, &["import-preset", "Live", &file]), 1);
+        assert_eq!(
+            run_cli(&dir, &["import-preset", "Live", &file, "--overwrite"]),
+            0
+        );
+        assert_eq!(run_cli(&dir, &["export-preset", "Nope"]), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn analyze_and_normalize_work_on_wav_files() {
+        let dir = temp_dir("normalize");
+        std::fs::create_dir_all(&dir).unwrap();
+        let src = dir.join("take.wav");
+        let dst = dir.join("take-normalized.wav");
+        let sine: Vec<f32> = (0..96_000)
+            .map(|n| 0.1 * (n as f32 * 1_000.0 * std::f32::consts::TAU / 48_000.0).sin())
+            .collect();
+        troubadour_core::wav::write_wav(&src, 48_000, 1, &sine).unwrap();
+        let (src, dst) = (src.to_string_lossy(), dst.to_string_lossy());
+
+        assert_eq!(
+            CliInvocation::parse(&args(&["normalize", &src, &dst, "--peak", "-3"]))
+                .unwrap()
+                .command,
+            CliCommand::Normalize {
+                src: src.to_string(),
+                dst: dst.to_string(),
+                target: NormalizeTarget::Peak(-3.0),
+            }
+        );
+        assert_eq!(run_cli(&dir, &["analyze", &src]), 0);
+        assert_eq!(run_cli(&dir, &["normalize", &src, &dst, "--peak", "-3"]), 0);
+        let report = recording::analyze_recording(Path::new(dst.as_ref()), |_| {}).unwrap();
+        assert!((report.true_peak_dbtp.unwrap() + 3.0).abs() < 0.05);
+
+        assert_eq!(run_cli(&dir, &["normalize", &src, &dst, "--rms", "-3"]), 2);
+        assert_eq!(run_cli(&dir, &["analyze", "missing.wav"]), 1);
+        assert!(!dir.join(format!("{LAST_SESSION}.toml")).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failures_exit_non_zero_without_writing() {
+        let dir = temp_dir("failures");
+        let path = dir.join(format!("{LAST_SESSION}.toml"));
+
+        assert_eq!(run_cli(&dir, &["mute", "nobody"]), 1);
+        assert_eq!(run_cli(&dir, &["set-volume", "mic", "12"]), 1);
+        assert_eq!(run_cli(&dir, &["set-volume", "mic", "-90"]), 1);
+        // Une route part d'une entrée
+        assert_eq!(run_cli(&dir, &["route", "speakers", "mic", "on"]), 1);
+        assert_eq!(run_cli(&dir, &["add-channel", "Kazoo", "Guest"]), 1);
+        assert_eq!(run_cli(&dir, &["set-volume", "mic"]), 2);
+        assert!(!path.exists());
+
+        // --dry-run : le diff est calculé, rien n'est écrit
+        assert_eq!(run_cli(&dir, &["mute", "mic", "--dry-run"]), 0);
+        assert!(!path.exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
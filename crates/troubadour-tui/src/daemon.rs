@@ -0,0 +1,430 @@
+//! Service sans interface : `troubadourd`.
+//!
+//! Pour un PC audio dédié, lancé par systemd : démarre le moteur depuis
+//! `config.toml` et la dernière session, traite les commandes jusqu'à
+//! SIGTERM / SIGINT, puis arrête les streams et sauve config et session,
+//! comme `troubadour tui` en quittant.
+//!
+//! ```text
+//!  config.toml ──► Startup ──► Engine ──► devices ? ──► start ──► serve ──► stop ──► save
+//!                                            │                     ▲
+//!                          --wait-for-devices └─ attente (backoff)  └─ SIGTERM / SIGINT
+//! ```
+//!
+//! Les logs partent sur stderr, sans couleurs ni horodatage : journald
+//! ajoute le sien (`RUST_LOG` règle le niveau, `info` par défaut).
+//!
+//! # Codes de sortie
+//! `0` : arrêt propre, `1` : démarrage impossible (config, devices
+//! absents, streams) ou échec de sauvegarde, `2` : arguments invalides.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use tracing::{error, info, warn};
+use troubadour_core::engine::{Engine, EngineChannels};
+use troubadour_shared::messages::Event;
+use troubadour_shared::profile::Profile;
+
+use crate::{Options, Startup, StartupError, initial_profile};
+
+/// Intervalle de la boucle de traitement : celui d'une frame de la TUI.
+const TICK: Duration = Duration::from_millis(33);
+
+/// Première attente des devices, doublée à chaque essai manqué...
+const FIRST_RETRY: Duration = Duration::from_secs(1);
+/// ... jusqu'à ce plafond.
+const MAX_RETRY: Duration = Duration::from_secs(30);
+
+const USAGE: &str = "Usage: troubadourd [--preset <name>] [--profile <machine>] [--wait-for-devices] [--<setting> <value>]...";
+
+/// Options de `troubadourd` : celles de `troubadour tui`, plus l'attente
+/// des devices.
+#[derive(Debug, Default, PartialEq)]
+pub struct DaemonOptions {
+    pub options: Options,
+    /// Devices absents au démarrage : attendre qu'ils arrivent au lieu
+    /// de refuser de démarrer
+    pub wait_for_devices: bool,
+}
+
+impl DaemonOptions {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let wait_for_devices = args.iter().any(|a| a == "--wait-for-devices");
+        let args: Vec<String> = args
+            .iter()
+            .filter(|a| *a != "--wait-for-devices")
+            .cloned()
+            .collect();
+        Ok(Self {
+            options: Options::parse(&args)?,
+            wait_for_devices,
+        })
+    }
+}
+
+/// Attente entre deux essais : doublée à chaque fois, plafonnée.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backoff {
+    next: Duration,
+    max: Duration,
+}
+
+impl Backoff {
+    pub fn new(first: Duration, max: Duration) -> Self {
+        Self { next: first, max }
+    }
+
+    /// Attente avant le prochain essai.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(FIRST_RETRY, MAX_RETRY)
+    }
+}
+
+/// Le moteur n'a pas démarré.
+#[derive(Debug, PartialEq)]
+pub enum DeviceWaitError {
+    /// Devices requis absents, sans `--wait-for-devices`
+    Missing(Vec<String>),
+    /// Arrêt demandé pendant l'attente
+    Interrupted,
+}
+
+/// Vérifie que les devices requis sont là (`Engine::missing_devices`).
+///
+/// Sans `backoff`, un device absent est une erreur immédiate. Avec, on
+/// réessaie après chaque attente (`sleep`), jusqu'à ce qu'ils soient tous
+/// là ou que `stop` soit levé. `sleep` est injecté pour les tests.
+pub fn wait_for_devices(
+    engine: &Engine,
+    mut backoff: Option<Backoff>,
+    stop: &AtomicBool,
+    mut sleep: impl FnMut(Duration),
+) -> Result<(), DeviceWaitError> {
+    loop {
+        if stop.load(Ordering::Relaxed) {
+            return Err(DeviceWaitError::Interrupted);
+        }
+        let missing = engine.missing_devices();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        let Some(backoff) = backoff.as_mut() else {
+            return Err(DeviceWaitError::Missing(missing));
+        };
+        let delay = backoff.next_delay();
+        warn!(
+            "Waiting for {}, next check in {}s",
+            missing.join(", "),
+            delay.as_secs_f32()
+        );
+        sleep(delay);
+    }
+}
+
+/// Fait tourner le moteur jusqu'à ce que `stop` soit levé : commandes
+/// traitées à chaque tick, événements vidés (erreurs dans les logs).
+/// Le tick s'allonge quand le moteur est en veille (`Engine::loop_tick`).
+pub fn serve(engine: &mut Engine, channels: &EngineChannels, stop: &AtomicBool, tick: Duration) {
+    while !stop.load(Ordering::Relaxed) {
+        engine.process_commands();
+        for event in channels.event_rx.try_iter() {
+            match event {
+                Event::Error(message) => warn!("{message}"),
+                Event::StreamFailed {
+                    device_name,
+                    hint: Some(hint),
+                    ..
+                } => warn!("Cannot open {device_name}: {hint}"),
+                Event::PresetWarnings { name, warnings } => {
+                    for warning in warnings {
+                        warn!("Preset \"{name}\": {warning}");
+                    }
+                }
+                Event::EngineStopped => info!("Audio engine stopped"),
+                _ => {}
+            }
+        }
+        std::thread::sleep(engine.loop_tick(tick));
+    }
+}
+
+/// Attente découpée en pas de `TICK` : un signal n'attend pas la fin
+/// d'une attente de 30 s.
+fn sleep_unless_stopped(stop: &AtomicBool, delay: Duration) {
+    let mut left = delay;
+    while !left.is_zero() && !stop.load(Ordering::Relaxed) {
+        let step = left.min(TICK);
+        std::thread::sleep(step);
+        left -= step;
+    }
+}
+
+/// `troubadourd [--preset <nom>] [--profile <machine>] [--wait-for-devices]`
+///
+/// Retourne le code de sortie.
+pub fn run(args: &[String]) -> i32 {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_ansi(false)
+        .without_time()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let daemon = match DaemonOptions::parse(args) {
+        Ok(daemon) => daemon,
+        Err(e) => {
+            error!("{e}");
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+
+    let stop = Arc::new(AtomicBool::new(false));
+    for signal in [signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT] {
+        if let Err(e) = signal_hook::flag::register(signal, stop.clone()) {
+            error!("Cannot handle signal {signal}: {e}");
+            return 1;
+        }
+    }
+
+    let mut startup = match Startup::load(&daemon.options) {
+        Ok(startup) => startup,
+        Err(StartupError::Config(e)) => {
+            error!("{e}");
+            return 1;
+        }
+        Err(StartupError::Options(e)) => {
+            error!("{e}");
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+    for warning in &startup.warnings {
+        warn!("{warning}");
+    }
+
+    let (mut engine, channels) = Engine::new();
+    startup.configure(&mut engine);
+    let profiles_dir = Profile::profiles_dir();
+    match initial_profile(&mut engine, daemon.options.preset.as_deref(), &profiles_dir) {
+        Ok(Some(name)) => info!("Loaded preset \"{name}\""),
+        Ok(None) => {}
+        Err(e) => {
+            error!("{e}");
+            return 1;
+        }
+    }
+    startup.configure_auto_snapshots(&mut engine);
+    if startup.first_run {
+        match startup.apply_first_run_setup(&mut engine) {
+            Ok(proposal) => info!("First run: {}", proposal.summary.join(", ")),
+            Err(e) => warn!("First run setup not applied: {e}"),
+        }
+    }
+    // Personne pour répondre à la question de la TUI : les changements
+    // non sauvés sont repris
+    if startup.recovery_pending {
+        match engine.restore_recovery_snapshot() {
+            Ok(_) => info!("Restored unsaved changes from the previous session"),
+            Err(e) => warn!("Cannot restore the recovery snapshot: {e}"),
+        }
+    }
+
+    let backoff = daemon.wait_for_devices.then(Backoff::default);
+    match wait_for_devices(&engine, backoff, &stop, |delay| {
+        sleep_unless_stopped(&stop, delay)
+    }) {
+        Ok(()) => {}
+        Err(DeviceWaitError::Missing(missing)) => {
+            error!(
+                "Required audio devices missing: {} (use --wait-for-devices to wait for them)",
+                missing.join(", ")
+            );
+            return 1;
+        }
+        // Arrêté avant d'avoir démarré : rien n'a changé, rien à sauver
+        Err(DeviceWaitError::Interrupted) => return 0,
+    }
+    if let Err(e) = engine.start() {
+        error!("Audio engine not started: {e}");
+        if let Some(hint) = e.hint() {
+            error!("{hint}");
+        }
+        return 1;
+    }
+    startup.configure_network(&mut engine);
+    info!("troubadourd running");
+
+    serve(&mut engine, &channels, &stop, TICK);
+
+    info!("Shutting down");
+    engine.stop();
+    let errors = startup.save(&mut engine, &profiles_dir);
+    for e in &errors {
+        error!("{e}");
+    }
+    i32::from(!errors.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use troubadour_core::device::{DefaultDeviceSource, SystemDefaults};
+    use troubadour_shared::audio::ChannelId;
+    use troubadour_shared::messages::Command;
+
+    use super::*;
+
+    /// Défauts du système que le test change à la main.
+    struct MockDefaults(Arc<Mutex<SystemDefaults>>);
+
+    impl DefaultDeviceSource for MockDefaults {
+        fn default_input_name(&self) -> Option<String> {
+            self.0.lock().unwrap().input.clone()
+        }
+
+        fn default_output_name(&self) -> Option<String> {
+            self.0.lock().unwrap().output.clone()
+        }
+    }
+
+    fn engine_without_devices() -> (Engine, EngineChannels, Arc<Mutex<SystemDefaults>>) {
+        let (mut engine, channels) = Engine::new();
+        let defaults = Arc::new(Mutex::new(SystemDefaults::default()));
+        engine.set_default_device_source(Box::new(MockDefaults(defaults.clone())));
+        (engine, channels, defaults)
+    }
+
+    fn plug_devices(defaults: &Mutex<SystemDefaults>) {
+        *defaults.lock().unwrap() = SystemDefaults {
+            input: Some("Interface In".into()),
+            output: Some("Interface Out".into()),
+        };
+    }
+
+    #[test]
+    fn parse_daemon_options() {
+        let args = ["--wait-for-devices", "--preset", "Stream"].map(String::from);
+        let daemon = DaemonOptions::parse(&args).unwrap();
+        assert!(daemon.wait_for_devices);
+        assert_eq!(daemon.options.preset.as_deref(), Some("Stream"));
+        assert!(!DaemonOptions::parse(&[]).unwrap().wait_for_devices);
+        assert!(DaemonOptions::parse(&["--bogus".to_string()]).is_err());
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_its_ceiling() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5]);
+    }
+
+    #[test]
+    fn missing_devices_refuse_to_start_without_waiting() {
+        let (engine, _channels, _defaults) = engine_without_devices();
+        let stop = AtomicBool::new(false);
+        let result = wait_for_devices(&engine, None, &stop, |_| panic!("must not wait"));
+        assert_eq!(
+            result,
+            Err(DeviceWaitError::Missing(vec![
+                "default input device".into(),
+                "default output device".into()
+            ]))
+        );
+    }
+
+    #[test]
+    fn waiting_retries_with_backoff_until_devices_appear() {
+        let (engine, _channels, defaults) = engine_without_devices();
+        let stop = AtomicBool::new(false);
+        let mut waits = Vec::new();
+        let result = wait_for_devices(&engine, Some(Backoff::default()), &stop, |delay| {
+            waits.push(delay.as_secs());
+            // Branchés pendant la troisième attente
+            if waits.len() == 3 {
+                plug_devices(&defaults);
+            }
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(waits, [1, 2, 4]);
+    }
+
+    #[test]
+    fn a_signal_ends_the_wait() {
+        let (engine, _channels, _defaults) = engine_without_devices();
+        let stop = AtomicBool::new(false);
+        let mut waits = 0;
+        let result = wait_for_devices(&engine, Some(Backoff::default()), &stop, |_| {
+            waits += 1;
+            stop.store(true, Ordering::Relaxed);
+        });
+        assert_eq!(result, Err(DeviceWaitError::Interrupted));
+        assert_eq!(waits, 1);
+    }
+
+    #[test]
+    fn serve_processes_commands_until_stopped_then_state_is_saved() {
+        let dir = std::env::temp_dir().join(format!("troubadourd-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut startup = Startup::load_from(
+            dir.join("config.toml"),
+            dir.join("recovery.toml"),
+            &Options::default(),
+        )
+        .unwrap();
+        assert!(startup.first_run);
+
+        let (mut engine, channels, _defaults) = engine_without_devices();
+        startup.configure(&mut engine);
+        channels
+            .command_tx
+            .send(Command::SetMute {
+                channel: ChannelId(1),
+                muted: true,
+            })
+            .unwrap();
+
+        // Le "signal" arrive d'un autre thread, pendant que le service tourne
+        let stop = Arc::new(AtomicBool::new(false));
+        let signal = {
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                stop.store(true, Ordering::Relaxed);
+            })
+        };
+        serve(&mut engine, &channels, &stop, Duration::from_millis(5));
+        signal.join().unwrap();
+        assert!(engine.mixer().channel(ChannelId(1)).unwrap().muted);
+
+        engine.stop();
+        assert!(startup.save(&mut engine, &dir).is_empty());
+        assert!(dir.join("config.toml").exists());
+        let session = Profile::load(&dir.join(format!("{}.toml", crate::LAST_SESSION)))
+            .unwrap()
+            .0;
+        assert!(
+            session
+                .mixer
+                .channels
+                .iter()
+                .any(|c| c.id == ChannelId(1) && c.muted)
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,735 @@
+//! Mixer en mode terminal : `troubadour tui`.
+//!
+//! Même moteur que l'interface graphique, piloté au clavier. Utile en
+//! SSH, sur une machine sans bureau, ou simplement pour garder le mixer
+//! dans un coin du terminal.
+//!
+//! ```text
+//!  clavier ──► TuiApp::handle_key ──► Command ──► Engine::process_commands
+//!                                                        │
+//!  écran  ◄── ui::render ◄── MixerView::capture ◄────────┤ (1×/frame)
+//!                                                        │
+//!  meters ◄── TuiApp::handle_event ◄── Event::LevelUpdate┘
+//! ```
+
+pub mod app;
+pub mod cli;
+pub mod daemon;
+pub mod ui;
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crossterm::event::{self, Event as TermEvent, KeyEventKind};
+use troubadour_core::engine::{Engine, EngineChannels};
+use troubadour_core::template::ChannelTemplateRegistry;
+use troubadour_shared::config::{AppConfig, LoadedConfig};
+use troubadour_shared::config_layers::{self, EffectiveConfig};
+use troubadour_shared::device_cache::DeviceCache;
+use troubadour_shared::locale::FALLBACK_LOCALE;
+use troubadour_shared::machine::{self, SetupProposal};
+use troubadour_shared::messages::Command;
+use troubadour_shared::preset::EffectsPresetManager;
+use troubadour_shared::profile::Profile;
+use troubadour_shared::recovery::{AutoSnapshot, RecoverySnapshot};
+use troubadour_shared::script::ScriptingConfig;
+use troubadour_shared::template::ChannelTemplate;
+
+use crate::app::{Action, MixerView, TuiApp};
+
+/// Durée d'une frame : ~30 fps, assez pour des meters fluides.
+const FRAME: Duration = Duration::from_millis(33);
+
+/// Profil écrit à la sortie et rechargé au lancement suivant.
+pub const LAST_SESSION: &str = "Last Session";
+
+/// Options de `troubadour tui`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    /// Profil à charger au démarrage (sinon la dernière session)
+    pub preset: Option<String>,
+    /// Profil machine à utiliser (sinon le hostname)
+    pub profile: Option<String>,
+    /// Réglages imposés pour ce lancement (`--sample-rate 96000`),
+    /// au-dessus de `config.toml` et de l'environnement
+    pub overrides: Vec<(String, String)>,
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--preset" => match args.next() {
+                    Some(name) => options.preset = Some(name.clone()),
+                    None => return Err("--preset requires a name".to_string()),
+                },
+                "--profile" => match args.next() {
+                    Some(name) => options.profile = Some(name.clone()),
+                    None => return Err("--profile requires a machine name".to_string()),
+                },
+                flag if config_layers::cli_flags().iter().any(|f| f == flag) => match args.next() {
+                    Some(value) => options.overrides.push((flag.to_string(), value.clone())),
+                    None => return Err(format!("{flag} requires a value")),
+                },
+                other => return Err(format!("Unknown argument: {other}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+const USAGE: &str =
+    "Usage: troubadour tui [--preset <name>] [--profile <machine>] [--<setting> <value>]...";
+
+/// `troubadour tui [--preset <nom>] [--profile <machine>] [--sample-rate <Hz>]...`
+///
+/// Charge la config (surchargée par `TROUBADOUR_*` et les options),
+/// démarre le moteur, affiche le mixer jusqu'à `q`, puis sauve la config
+/// (sans les surcharges) et la session. Retourne le code de sortie.
+pub fn run(args: &[String]) -> i32 {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+
+    let mut startup = match Startup::load(&options) {
+        Ok(startup) => startup,
+        Err(StartupError::Config(e)) => {
+            eprintln!("{e}");
+            return 1;
+        }
+        Err(StartupError::Options(e)) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+
+    let (mut engine, channels) = Engine::new();
+    startup.configure(&mut engine);
+
+    let profiles_dir = Profile::profiles_dir();
+    let mut app = TuiApp::new(Profile::available_names(&profiles_dir));
+    match initial_profile(&mut engine, options.preset.as_deref(), &profiles_dir) {
+        Ok(Some(name)) => app.status = format!("Loaded preset \"{name}\""),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{e}");
+            return 1;
+        }
+    }
+    startup.configure_auto_snapshots(&mut engine);
+
+    if startup.first_run {
+        // Devices choisis d'après ceux branchés, expliqués dans la barre
+        // d'état (modifiables ensuite canal par canal)
+        app.status = match startup.apply_first_run_setup(&mut engine) {
+            Ok(proposal) => format!("First run: {}", proposal.summary.join(", ")),
+            Err(e) => format!("Error: first run setup not applied: {e}"),
+        };
+    }
+
+    if let Some(warning) = startup.warnings.first() {
+        app.status = warning.clone();
+    }
+
+    if startup.recovery_pending {
+        app.recovery_pending = true;
+        app.status =
+            "Previous session ended unexpectedly: r to restore unsaved changes, d to discard"
+                .to_string();
+    }
+
+    if let Err(e) = engine.start() {
+        app.status = format!("Error: audio engine not started: {e}");
+    }
+    startup.configure_network(&mut engine);
+
+    let mut terminal = match ratatui::try_init() {
+        Ok(terminal) => terminal,
+        Err(e) => {
+            eprintln!("Failed to initialize terminal: {e}");
+            return 1;
+        }
+    };
+    let result = event_loop(
+        &mut terminal,
+        &mut engine,
+        &channels,
+        &mut app,
+        &profiles_dir,
+    );
+    ratatui::restore();
+    engine.stop();
+
+    let mut code = 0;
+    if let Err(e) = result {
+        eprintln!("Terminal error: {e}");
+        code = 1;
+    }
+    for error in startup.save(&mut engine, &profiles_dir) {
+        eprintln!("{error}");
+        code = 1;
+    }
+    code
+}
+
+/// Échec du chargement de la config au lancement.
+#[derive(Debug)]
+pub(crate) enum StartupError {
+    /// Fichier illisible ou invalide
+    Config(String),
+    /// Surcharge de la ligne de commande invalide
+    Options(String),
+}
+
+/// Config d'un lancement du moteur (`tui`, `troubadourd`) : lue,
+/// surchargée, puis réécrite à la sortie avec ce que le moteur a changé.
+pub(crate) struct Startup {
+    pub config_path: PathBuf,
+    pub recovery_path: PathBuf,
+    /// Pas encore de config : premier lancement
+    pub first_run: bool,
+    /// Un instantané de secours a survécu à un arrêt brutal
+    pub recovery_pending: bool,
+    pub warnings: Vec<String>,
+    pub machine: String,
+    pub effective: EffectiveConfig,
+    pub config: AppConfig,
+}
+
+impl Startup {
+    /// Config aux emplacements par défaut.
+    pub fn load(options: &Options) -> Result<Self, StartupError> {
+        Self::load_from(
+            AppConfig::default_path(),
+            RecoverySnapshot::default_path(),
+            options,
+        )
+    }
+
+    pub fn load_from(
+        config_path: PathBuf,
+        recovery_path: PathBuf,
+        options: &Options,
+    ) -> Result<Self, StartupError> {
+        let first_run = !config_path.exists();
+        let LoadedConfig {
+            config,
+            recovery_pending,
+            mut warnings,
+        } = AppConfig::load_with_recovery(&config_path, &recovery_path).map_err(|e| {
+            StartupError::Config(format!("Failed to read {}: {e}", config_path.display()))
+        })?;
+
+        let machine = machine::machine_name(options.profile.as_deref());
+        let effective = effective_config(config, &config_path, &machine, &options.overrides)
+            .map_err(StartupError::Options)?;
+        warnings.extend(effective.warnings.iter().cloned());
+        Ok(Self {
+            config: effective.config.clone(),
+            config_path,
+            recovery_path,
+            first_run,
+            recovery_pending,
+            warnings,
+            machine,
+            effective,
+        })
+    }
+
+    /// Applique la config au moteur (arrêté), avant le profil de départ.
+    pub fn configure(&self, engine: &mut Engine) {
+        let config = &self.config;
+        engine.configure_journal(&config.journal);
+        engine.configure_controls(&config.controls);
+        engine.configure_signal_presence(&config.signal_presence);
+        engine.configure_recovery(&config.recovery, self.recovery_path.clone());
+        if self.recovery_pending {
+            engine.hold_recovery_snapshot();
+        }
+        engine.configure_watchdog(&config.watchdog);
+        engine.configure_level_history(&config.level_history);
+        engine.configure_device_preview(&config.device_preview);
+        engine.configure_device_cache(DeviceCache::default_path());
+        engine.configure_presets(config.preset_dir.as_deref());
+        engine.configure_schedule(&config.schedule);
+        engine.configure_scripting(&config.scripting, ScriptingConfig::default_dir());
+        engine.set_channel_templates(ChannelTemplateRegistry::load(
+            &ChannelTemplate::default_dir(),
+        ));
+        // Moteur arrêté : le rate est seulement retenu pour `start`
+        let _ = engine.set_sample_rate(config.audio.sample_rate);
+        let _ = engine.set_buffer_size(config.audio.buffer_size);
+        engine.set_dither(config.audio.dither);
+        engine.set_target_output_latency_blocks(config.audio.target_output_latency_blocks);
+        engine.set_max_channels(config.audio.max_channels);
+        engine.set_preroll_blocks(config.audio.preroll_blocks);
+        engine.set_idle_detection(
+            config.audio.idle_after_seconds,
+            config.audio.idle_threshold_db,
+        );
+        engine.set_dsp_profiling(config.audio.dsp_profiling);
+        engine.configure_machine_profiles(config.profiles.clone(), &self.machine);
+        engine.configure_routing_snapshots(config.routing_snapshots.clone());
+        engine.configure_favorite_presets(config.favorites.clone());
+        engine.configure_ui_state(config.ui.clone());
+        // Langue inconnue (config d'une version plus récente) : anglais
+        if let Err(e) = engine.set_locale(config.locale.as_deref().unwrap_or(FALLBACK_LOCALE)) {
+            tracing::warn!("{e}, keeping {FALLBACK_LOCALE}");
+        }
+        engine.configure_effective_config(self.effective.entries.clone());
+    }
+
+    /// Flux réseau, taps et replays : bus et canal sont désignés par leur nom,
+    /// le profil de départ doit donc être chargé avant. Après `start` :
+    /// seul le bus joué par le pipeline démarré peut partir.
+    pub fn configure_network(&self, engine: &mut Engine) {
+        engine.configure_network(
+            self.config.network_send.as_ref(),
+            self.config.network_receive.as_ref(),
+        );
+        engine.configure_taps(&self.config.taps);
+        engine.configure_replay(&self.config.replay);
+    }
+
+    /// Instantanés automatiques : après le profil de départ, qui ne
+    /// remplace que l'état par défaut.
+    pub fn configure_auto_snapshots(&self, engine: &mut Engine) {
+        engine.configure_auto_snapshots(&self.config.auto_snapshots, AutoSnapshot::default_dir());
+    }
+
+    /// Premier lancement : applique la proposition de devices et écrit
+    /// la config tout de suite. Un arrêt brutal avant la sortie ne fait
+    /// ni reposer la question, ni perdre les devices acceptés.
+    pub fn apply_first_run_setup(&mut self, engine: &mut Engine) -> Result<SetupProposal, String> {
+        let proposal = engine.setup_proposal();
+        engine
+            .apply_setup_proposal(&proposal)
+            .map_err(|e| e.to_string())?;
+        self.save_config(engine)?;
+        self.first_run = false;
+        Ok(proposal)
+    }
+
+    /// Sauve la config (sans les surcharges) et la session, une fois le
+    /// moteur arrêté. Retourne les échecs, à afficher.
+    pub fn save(&mut self, engine: &mut Engine, profiles_dir: &Path) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Err(e) = self.save_config(engine) {
+            errors.push(e);
+        }
+        let session = engine.to_profile(LAST_SESSION);
+        match session.save(&profiles_dir.join(format!("{LAST_SESSION}.toml"))) {
+            // Session sauvée : l'instantané de secours n'a plus lieu d'être
+            Ok(()) => {
+                if let Err(e) = engine.discard_recovery_snapshot() {
+                    errors.push(format!("Failed to remove recovery snapshot: {e}"));
+                }
+            }
+            Err(e) => errors.push(format!("Failed to save session: {e}")),
+        }
+        errors
+    }
+
+    /// Config à jour de ce que le moteur a changé, écrite sans les
+    /// surcharges de l'environnement et des options.
+    fn save_config(&mut self, engine: &Engine) -> Result<(), String> {
+        let config = &mut self.config;
+        config.controls.fader_taper = engine.fader_taper();
+        config.controls.control_profile = engine.active_control_profile().map(str::to_string);
+        config.audio.sample_rate = engine.sample_rate();
+        config.audio.buffer_size = engine.buffer_size();
+        config.schedule.action_sets = engine.action_sets().clone();
+        config.schedule.triggers = engine.schedule_triggers().to_vec();
+        config.scripting = engine.scripting_config();
+        config.routing_snapshots = engine.routing_snapshots().clone();
+        config.favorites = engine.favorite_presets().clone();
+        config.ui = engine.ui_state().clone();
+        config.locale =
+            Some(engine.locale().code().to_string()).filter(|code| code != FALLBACK_LOCALE);
+        config.preset_dir = Some(engine.preset_dir().to_path_buf())
+            .filter(|dir| *dir != EffectsPresetManager::default_dir());
+        // Seule la section de cette machine change : les autres sont
+        // réécrites telles qu'elles ont été lues
+        if let Some(profile) = engine.machine_profiles().remove(&self.machine) {
+            config.set_machine_profile(&self.machine, profile);
+        }
+        self.effective.restore_file_values(config);
+        config
+            .save(&self.config_path)
+            .map_err(|e| format!("Failed to save {}: {e}", self.config_path.display()))
+    }
+}
+
+/// Config lue, migrée vers les profils machine, puis surchargée par
+/// l'environnement et les options. Erreur : option invalide.
+fn effective_config(
+    mut config: AppConfig,
+    config_path: &Path,
+    machine: &str,
+    overrides: &[(String, String)],
+) -> Result<EffectiveConfig, String> {
+    config.migrate_to_machine(machine);
+    let content = std::fs::read_to_string(config_path).unwrap_or_default();
+    config_layers::resolve(
+        config,
+        &config_layers::file_keys(&content, machine),
+        machine,
+        |var| std::env::var(var).ok(),
+        overrides,
+    )
+}
+
+/// `troubadour config [--profile <machine>] [--sample-rate <Hz>]...`
+///
+/// Affiche la config effective et la couche (défaut, fichier,
+/// environnement, ligne de commande) qui a fourni chaque réglage, sans
+/// démarrer le moteur ni rien écrire. `troubadour config validate` :
+/// voir `validate_config`.
+pub fn print_effective_config(args: &[String]) -> i32 {
+    const USAGE: &str = "Usage: troubadour config [--profile <machine>] [--<setting> <value>]...";
+
+    if args.first().map(String::as_str) == Some("validate") {
+        return validate_config(&args[1..]);
+    }
+
+    let options = match Options::parse(args) {
+        Ok(options) if options.preset.is_none() => options,
+        Ok(_) => {
+            eprintln!("{USAGE}");
+            return 2;
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+    let config_path = AppConfig::default_path();
+    let config =
+        match AppConfig::load_with_recovery(&config_path, &RecoverySnapshot::default_path()) {
+            Ok(loaded) => loaded.config,
+            Err(e) => {
+                eprintln!("Failed to read {}: {e}", config_path.display());
+                return 1;
+            }
+        };
+    let machine = machine::machine_name(options.profile.as_deref());
+    match effective_config(config, &config_path, &machine, &options.overrides) {
+        Ok(effective) => {
+            print!("{effective}");
+            0
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            2
+        }
+    }
+}
+
+/// `troubadour config validate [--strict] [<config.toml>]`
+///
+/// Relit la config (par défaut celle de l'application) comme au
+/// démarrage et affiche ce qui serait ignoré : clés inconnues, `[ui]`
+/// illisible. Avec `--strict` (intégration continue), ces
+/// avertissements font échouer la commande.
+pub fn validate_config(args: &[String]) -> i32 {
+    const USAGE: &str = "Usage: troubadour config validate [--strict] [<config.toml>]";
+
+    let strict = args.iter().any(|a| a == "--strict");
+    let rest: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--strict")
+        .collect();
+    let path = match rest.as_slice() {
+        [] => AppConfig::default_path(),
+        [path] if !path.starts_with("--") => PathBuf::from(path),
+        _ => {
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+    let warnings = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| AppConfig::parse(&content).map_err(|e| e.to_string()))
+    {
+        Ok((_, warnings)) => warnings,
+        Err(e) => {
+            eprintln!("Failed to read {}: {e}", path.display());
+            return 1;
+        }
+    };
+    for warning in &warnings {
+        eprintln!("{}: {warning}", path.display());
+    }
+    if warnings.is_empty() {
+        println!("{}: OK", path.display());
+        0
+    } else if strict {
+        1
+    } else {
+        0
+    }
+}
+
+/// Profil de départ : `--preset` s'il est donné, sinon la dernière
+/// session si elle existe, sinon le mixer par défaut du moteur.
+/// Retourne le nom du profil appliqué.
+pub(crate) fn initial_profile(
+    engine: &mut Engine,
+    preset: Option<&str>,
+    profiles_dir: &Path,
+) -> Result<Option<String>, String> {
+    let name = match preset {
+        Some(name) => name,
+        None if profiles_dir.join(format!("{LAST_SESSION}.toml")).exists() => LAST_SESSION,
+        None => return Ok(None),
+    };
+    apply_profile(engine, name, profiles_dir).map(Some)
+}
+
+/// Applique un profil au moteur ; retourne son nom.
+fn apply_profile(engine: &mut Engine, name: &str, profiles_dir: &Path) -> Result<String, String> {
+    let (profile, warnings) =
+        Profile::find(name, profiles_dir).map_err(|e| format!("Preset \"{name}\": {e}"))?;
+    engine
+        .apply_profile(&profile)
+        .map_err(|e| format!("Preset \"{name}\": {e}"))?;
+    engine.report_preset_warnings(&profile.name, warnings);
+    Ok(profile.name)
+}
+
+/// Applique un profil au moteur et l'annonce dans la barre d'état.
+fn load_profile(
+    engine: &mut Engine,
+    app: &mut TuiApp,
+    name: &str,
+    profiles_dir: &Path,
+) -> Result<(), String> {
+    let name = apply_profile(engine, name, profiles_dir)?;
+    app.status = format!("Loaded preset \"{name}\"");
+    Ok(())
+}
+
+fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    engine: &mut Engine,
+    channels: &EngineChannels,
+    app: &mut TuiApp,
+    profiles_dir: &Path,
+) -> std::io::Result<()> {
+    loop {
+        engine.process_commands();
+        while let Ok(event) = channels.event_rx.try_recv() {
+            app.handle_event(&event);
+        }
+
+        // Le seul accès au mixer de la frame
+        let mut view = MixerView::capture(
+            engine.mixer(),
+            engine.locale(),
+            engine.fader_taper(),
+            app.levels(),
+        );
+        app.decay_meters();
+        terminal.draw(|frame| ui::render(frame, app, &view))?;
+
+        // Attendre une touche au plus une frame, puis vider la file
+        let mut timeout = FRAME;
+        while event::poll(timeout)? {
+            timeout = Duration::ZERO;
+            let TermEvent::Key(key) = event::read()? else {
+                continue;
+            };
+            // Windows envoie aussi les relâchements de touche
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match app.handle_key(key.code, &mut view) {
+                Some(Action::Command(command)) => {
+                    let _ = channels.command_tx.try_send(command);
+                }
+                Some(Action::LoadPreset(name)) => {
+                    if let Err(e) = load_profile(engine, app, &name, profiles_dir) {
+                        app.status = format!("Error: {e}");
+                    }
+                    view = MixerView::capture(
+                        engine.mixer(),
+                        engine.locale(),
+                        engine.fader_taper(),
+                        app.levels(),
+                    );
+                }
+                Some(Action::LoadFavorite(position)) => {
+                    if let Some(channel) = app.selected_strip(&view) {
+                        let _ = channels
+                            .command_tx
+                            .try_send(Command::ApplyFavoritePreset { channel, position });
+                    }
+                }
+                Some(Action::Quit) => return Ok(()),
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use troubadour_shared::audio::ChannelId;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_options() {
+        assert_eq!(Options::parse(&[]).unwrap(), Options::default());
+        assert_eq!(
+            Options::parse(&args(&["--preset", "Streaming"]))
+                .unwrap()
+                .preset
+                .as_deref(),
+            Some("Streaming")
+        );
+        assert_eq!(
+            Options::parse(&args(&["--profile", "laptop", "--preset", "Gaming"])).unwrap(),
+            Options {
+                preset: Some("Gaming".into()),
+                profile: Some("laptop".into()),
+                ..Options::default()
+            }
+        );
+        assert_eq!(
+            Options::parse(&args(&["--buffer-size", "128", "--preset", "Gaming"]))
+                .unwrap()
+                .overrides,
+            vec![("--buffer-size".to_string(), "128".to_string())]
+        );
+        assert!(Options::parse(&args(&["--preset"])).is_err());
+        assert!(Options::parse(&args(&["--sample-rate"])).is_err());
+        assert!(Options::parse(&args(&["--profile"])).is_err());
+        assert!(Options::parse(&args(&["--bogus"])).is_err());
+    }
+
+    #[test]
+    fn initial_profile_does_not_overwrite_a_pending_recovery_snapshot() {
+        let dir = std::env::temp_dir().join(format!("troubadour-pending-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let profiles_dir = dir.join("profiles");
+        std::fs::create_dir_all(&profiles_dir).unwrap();
+        let config_path = dir.join("config.toml");
+        let recovery_path = dir.join("recovery.toml");
+        let mut config = AppConfig::default();
+        config.recovery.interval_seconds = 0;
+        config.preset_dir = Some(dir.join("presets"));
+        config.save(&config_path).unwrap();
+
+        // Crash de la session précédente : Music à -∞ non sauvé
+        let mut crashed = Profile::default_profile();
+        crashed.mixer.channels[1].volume = 0.0;
+        RecoverySnapshot {
+            saved_at_ms: 1,
+            profile: crashed,
+            ui: Default::default(),
+        }
+        .save(&recovery_path)
+        .unwrap();
+        let later = std::time::SystemTime::now() + Duration::from_secs(10);
+        std::fs::File::options()
+            .write(true)
+            .open(&recovery_path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        let pending = std::fs::read_to_string(&recovery_path).unwrap();
+
+        let mut show = Profile::default_profile();
+        show.name = "Show".to_string();
+        show.mixer.channels[1].volume = 0.5;
+        show.save(&profiles_dir.join("Show.toml")).unwrap();
+
+        let startup =
+            Startup::load_from(config_path, recovery_path.clone(), &Options::default()).unwrap();
+        assert!(startup.recovery_pending);
+        let (mut engine, channels) = Engine::new();
+        startup.configure(&mut engine);
+        let loaded = initial_profile(&mut engine, Some("Show"), &profiles_dir).unwrap();
+        assert_eq!(loaded.as_deref(), Some("Show"));
+
+        // Le profil de départ a modifié l'état, la question est posée :
+        // l'instantané reste celui du crash
+        engine.process_commands();
+        engine.process_commands();
+        assert_eq!(std::fs::read_to_string(&recovery_path).unwrap(), pending);
+
+        channels
+            .command_tx
+            .send(Command::RestoreRecoverySnapshot)
+            .unwrap();
+        engine.process_commands();
+        let music = engine.mixer().channel(ChannelId(1)).unwrap();
+        assert_eq!(music.volume, 0.0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn first_run_setup_is_written_right_away() {
+        let dir = std::env::temp_dir().join(format!("troubadour-first-run-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("config.toml");
+        let recovery_path = dir.join("recovery.toml");
+        let mut startup = Startup::load_from(
+            config_path.clone(),
+            recovery_path.clone(),
+            &Options::default(),
+        )
+        .unwrap();
+        assert!(startup.first_run);
+        let (mut engine, _channels) = Engine::new();
+        startup.configure(&mut engine);
+
+        startup.apply_first_run_setup(&mut engine).unwrap();
+        // Écrite sans attendre la sortie : pas de second "premier lancement"
+        assert!(config_path.exists());
+        let next = Startup::load_from(config_path, recovery_path, &Options::default()).unwrap();
+        assert!(!next.first_run);
+        assert!(next.config.profiles.contains_key(&next.machine));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn config_validate_fails_on_unknown_keys_only_when_strict() {
+        let dir = std::env::temp_dir().join(format!("troubadour-validate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let clean = dir.join("clean.toml");
+        let typo = dir.join("typo.toml");
+        std::fs::write(&clean, "[journal]\ncapacity = 42\n").unwrap();
+        std::fs::write(&typo, "[journal]\ncapacty = 42\n").unwrap();
+        let path = |p: &Path| p.display().to_string();
+
+        assert_eq!(validate_config(&args(&["--strict", &path(&clean)])), 0);
+        assert_eq!(validate_config(&args(&[&path(&typo)])), 0);
+        assert_eq!(validate_config(&args(&[&path(&typo), "--strict"])), 1);
+        assert_eq!(
+            validate_config(&args(&[&path(&dir.join("missing.toml"))])),
+            1
+        );
+        assert_eq!(validate_config(&args(&["a.toml", "b.toml"])), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,5 @@
+/// Binaire autonome, équivalent à `troubadour tui`.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(troubadour_tui::run(&args));
+}
@@ -0,0 +1,340 @@
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, List, ListItem, ListState, Paragraph, Widget, Wrap};
+
+use crate::app::{MixerView, Mode, Pane, StripView, TuiApp};
+
+/// Aide affichée en permanence en bas de l'écran ; passe sur deux
+/// lignes dans un terminal trop étroit.
+const HELP: &str = "←/→ select  ↑/↓ fader  m mute  s solo  tab pane  p,1-9 presets  c save replays  ! panic  q quit";
+
+/// Largeur d'une tranche (meter + fader + marges).
+const STRIP_WIDTH: u16 = 12;
+
+/// Dessine l'écran complet à partir de la photo du mixer.
+pub fn render(frame: &mut Frame, app: &TuiApp, view: &MixerView) {
+    let help_lines = if HELP.chars().count() > frame.area().width as usize {
+        2
+    } else {
+        1
+    };
+    let [main, status, help] = Layout::vertical([
+        Constraint::Min(8),
+        Constraint::Length(1),
+        Constraint::Length(help_lines),
+    ])
+    .areas(frame.area());
+
+    // Chaque panneau prend une largeur proportionnelle à son nombre de tranches
+    let weight = |n: usize| n.max(1) as u32;
+    let [channels, buses] = Layout::horizontal([
+        Constraint::Ratio(
+            weight(view.channels.len()),
+            weight(view.channels.len()) + weight(view.buses.len()),
+        ),
+        Constraint::Ratio(
+            weight(view.buses.len()),
+            weight(view.channels.len()) + weight(view.buses.len()),
+        ),
+    ])
+    .areas(main);
+
+    render_pane(frame, app, view, Pane::Channels, channels);
+    render_pane(frame, app, view, Pane::Buses, buses);
+
+    frame.render_widget(
+        Paragraph::new(app.status.as_str()).style(Style::new().fg(Color::Yellow)),
+        status,
+    );
+    frame.render_widget(
+        Paragraph::new(HELP)
+            .style(Style::new().fg(Color::DarkGray))
+            .wrap(Wrap { trim: true }),
+        help,
+    );
+
+    if let Mode::PresetPicker { selected } = app.mode {
+        render_picker(frame, app, selected, main);
+    }
+}
+
+fn render_pane(frame: &mut Frame, app: &TuiApp, view: &MixerView, pane: Pane, area: Rect) {
+    let focused = app.pane == pane;
+    let title = match pane {
+        Pane::Channels => " Channels ",
+        Pane::Buses => " Buses ",
+    };
+    let border = if focused {
+        Style::new().fg(Color::Cyan)
+    } else {
+        Style::new().fg(Color::DarkGray)
+    };
+    let block = Block::bordered().title(title).border_style(border);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let strips = view.strips(pane);
+    let areas =
+        Layout::horizontal(strips.iter().map(|_| Constraint::Length(STRIP_WIDTH))).split(inner);
+    for (index, (strip, strip_area)) in strips.iter().zip(areas.iter()).enumerate() {
+        let selected = focused && app.selected(pane) == index;
+        render_strip(frame, strip, view, selected, *strip_area);
+    }
+}
+
+/// Une tranche : meter et fader côte à côte, niveau et états dessous.
+///
+/// ```text
+/// ┌ Mic ─────┐
+/// │ █   │    │
+/// │ █   ━    │
+/// │ █   │    │
+/// │ -3.2 dB  │
+/// │  M  S    │
+/// └──────────┘
+/// ```
+fn render_strip(
+    frame: &mut Frame,
+    strip: &StripView,
+    view: &MixerView,
+    selected: bool,
+    area: Rect,
+) {
+    let title_style = if selected {
+        Style::new().add_modifier(Modifier::REVERSED | Modifier::BOLD)
+    } else {
+        Style::new()
+    };
+    let block = Block::bordered().title(Span::styled(strip.name.as_str(), title_style));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [gauges, label, flags] = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(inner);
+    let [_, meter, _, fader, _] = Layout::horizontal([
+        Constraint::Length(1),
+        Constraint::Length(2),
+        Constraint::Length(2),
+        Constraint::Length(1),
+        Constraint::Min(0),
+    ])
+    .areas(gauges);
+
+    frame.render_widget(
+        VerticalMeter::new(strip.meter_fraction(view.meter_scale)),
+        meter,
+    );
+    frame.render_widget(FaderTrack::new(strip.fader), fader);
+
+    let db_label = strip.fader_db(view.taper).to_string();
+    frame.render_widget(Paragraph::new(db_label).centered(), label);
+
+    let flag = |on: bool, text: &'static str, color: Color| {
+        if on {
+            Span::styled(text, Style::new().fg(Color::Black).bg(color))
+        } else {
+            Span::styled(text, Style::new().fg(Color::DarkGray))
+        }
+    };
+    let flags_line = Line::from(vec![
+        flag(strip.muted, " M ", Color::Red),
+        Span::raw(" "),
+        flag(strip.solo, " S ", Color::Yellow),
+    ]);
+    frame.render_widget(Paragraph::new(flags_line).centered(), flags);
+}
+
+fn render_picker(frame: &mut Frame, app: &TuiApp, selected: usize, area: Rect) {
+    let width = app
+        .presets
+        .iter()
+        .map(|name| name.chars().count() as u16)
+        .max()
+        .unwrap_or(0)
+        .max(16)
+        + 6;
+    let height = app.presets.len() as u16 + 2;
+    let popup = Rect {
+        x: area.x + area.width.saturating_sub(width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: width.min(area.width),
+        height: height.min(area.height),
+    };
+
+    let items: Vec<ListItem> = app
+        .presets
+        .iter()
+        .map(|name| ListItem::new(name.as_str()))
+        .collect();
+    let list = List::new(items)
+        .block(Block::bordered().title(" Load preset "))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .highlight_symbol("> ");
+    let mut state = ListState::default().with_selected(Some(selected));
+
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+}
+
+/// VU-meter vertical, rempli depuis le bas.
+///
+/// Vert jusqu'à -12 dB, jaune jusqu'à -3 dB, rouge au-dessus
+/// (sur l'échelle -60 → 0 dB, soit 80% et 95% de la hauteur).
+struct VerticalMeter {
+    fraction: f32,
+}
+
+impl VerticalMeter {
+    fn new(fraction: f32) -> Self {
+        Self {
+            fraction: fraction.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Widget for VerticalMeter {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 {
+            return;
+        }
+        let lit = (self.fraction * area.height as f32).round() as u16;
+        for row in 0..area.height {
+            // row 0 = bas du meter
+            let y = area.bottom() - 1 - row;
+            let height = (row + 1) as f32 / area.height as f32;
+            let color = if height > 0.95 {
+                Color::Red
+            } else if height > 0.8 {
+                Color::Yellow
+            } else {
+                Color::Green
+            };
+            let (symbol, style) = if row < lit {
+                ("█", Style::new().fg(color))
+            } else {
+                ("·", Style::new().fg(Color::DarkGray))
+            };
+            for x in area.left()..area.right() {
+                buf[(x, y)].set_symbol(symbol).set_style(style);
+            }
+        }
+    }
+}
+
+/// Rail de fader avec son curseur à la position courante.
+struct FaderTrack {
+    position: f32,
+}
+
+impl FaderTrack {
+    fn new(position: f32) -> Self {
+        Self {
+            position: position.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Widget for FaderTrack {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 {
+            return;
+        }
+        let knob_row = (self.position * (area.height - 1) as f32).round() as u16;
+        for row in 0..area.height {
+            let y = area.bottom() - 1 - row;
+            let (symbol, style) = if row == knob_row {
+                (
+                    "━",
+                    Style::new().fg(Color::White).add_modifier(Modifier::BOLD),
+                )
+            } else {
+                ("│", Style::new().fg(Color::DarkGray))
+            };
+            for x in area.left()..area.right() {
+                buf[(x, y)].set_symbol(symbol).set_style(style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use troubadour_core::mixer::Mixer;
+    use troubadour_shared::locale::Locale;
+    use troubadour_shared::mixer::{FaderTaper, MixerConfig};
+
+    use super::*;
+
+    fn draw(app: &TuiApp, view: &MixerView) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+        terminal.draw(|frame| render(frame, app, view)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn renders_channels_buses_and_help() {
+        let mixer = Mixer::from_config(MixerConfig::default_setup());
+        let view = MixerView::capture(
+            &mixer,
+            &Locale::default(),
+            FaderTaper::Audio,
+            &HashMap::new(),
+        );
+        let screen = draw(&TuiApp::new(Vec::new()), &view);
+
+        assert!(screen.contains("Channels"));
+        assert!(screen.contains("Buses"));
+        for strip in view.channels.iter().chain(&view.buses) {
+            assert!(screen.contains(&strip.name), "missing {}", strip.name);
+        }
+        assert!(screen.contains("+0.0 dB"));
+        assert!(screen.contains("c save replays"));
+        assert!(screen.contains("q quit"));
+    }
+
+    #[test]
+    fn picker_overlays_preset_names() {
+        let mixer = Mixer::from_config(MixerConfig::default_setup());
+        let view = MixerView::capture(
+            &mixer,
+            &Locale::default(),
+            FaderTaper::Audio,
+            &HashMap::new(),
+        );
+        let mut app = TuiApp::new(vec!["Streaming".into(), "Late Show".into()]);
+        app.mode = Mode::PresetPicker { selected: 1 };
+
+        let screen = draw(&app, &view);
+        assert!(screen.contains("Load preset"));
+        assert!(screen.contains("> Late Show"));
+    }
+
+    #[test]
+    fn meter_fills_from_the_bottom() {
+        let area = Rect::new(0, 0, 1, 10);
+        let mut buf = Buffer::empty(area);
+        VerticalMeter::new(0.5).render(area, &mut buf);
+        assert_eq!(buf[(0, 9)].symbol(), "█");
+        assert_eq!(buf[(0, 5)].symbol(), "█");
+        assert_eq!(buf[(0, 4)].symbol(), "·");
+    }
+}
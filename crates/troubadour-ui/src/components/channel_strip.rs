@@ -1,4 +1,5 @@
 use dioxus::prelude::*;
+use troubadour_core::taper::VolumeDecibels;
 
 use super::vu_meter::VuMeter;
 
@@ -21,6 +22,8 @@ pub struct ChannelStripProps {
 #[component]
 pub fn ChannelStrip(props: ChannelStripProps) -> Element {
     let volume_pct = (props.volume * 100.0) as i32;
+    // Fader en butée : "-inf dB", distinct de -60 dB encore audible
+    let volume_db = VolumeDecibels::from_linear(props.volume).to_string();
     let pan_pct = (props.pan * 100.0) as i32;
     let pan_display = if props.pan < -0.05 {
         format!("L{:.0}", props.pan.abs() * 100.0)
@@ -73,7 +76,7 @@ pub fn ChannelStrip(props: ChannelStripProps) -> Element {
 
             // Valeur du volume
             p { class: "text-[11px] font-mono text-zinc-500",
-                "{volume_pct}%"
+                "{volume_db}"
             }
 
             // Fader (input range)
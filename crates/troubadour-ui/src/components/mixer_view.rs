@@ -47,7 +47,7 @@ pub fn MixerView() -> Element {
                     let mut lvls = levels.write();
                     for cl in &channel_levels {
                         if let Some(entry) = lvls.iter_mut().find(|(id, _)| *id == cl.channel) {
-                            entry.1 = cl.rms;
+                            entry.1 = cl.rms_position;
                         }
                     }
                 }
@@ -85,14 +85,15 @@ pub fn MixerView() -> Element {
     let inputs_for_matrix: Vec<(ChannelId, String)> = config
         .channels
         .iter()
-        .filter(|c| c.kind == ChannelKind::Input)
-        .map(|c| (c.id, c.name.clone()))
+        // Un sous-mix est à la fois source et destination de routes
+        .filter(|c| matches!(c.kind, ChannelKind::Input | ChannelKind::Submix))
+        .map(|c| (c.id, crate::display_name(c)))
         .collect();
     let outputs_for_matrix: Vec<(ChannelId, String)> = config
         .channels
         .iter()
-        .filter(|c| c.kind == ChannelKind::Output)
-        .map(|c| (c.id, c.name.clone()))
+        .filter(|c| matches!(c.kind, ChannelKind::Output | ChannelKind::Submix))
+        .map(|c| (c.id, crate::display_name(c)))
         .collect();
     let routes_for_matrix: Vec<(ChannelId, ChannelId)> =
         config.routes.iter().map(|r| (r.from, r.to)).collect();
@@ -179,6 +180,19 @@ pub fn MixerView() -> Element {
                                 }
                             }
                         }
+                        // Submixes
+                        if channels_data.iter().any(|c| c.kind == ChannelKind::Submix) {
+                            div { class: "mb-6",
+                                h2 { class: "text-xs font-semibold text-zinc-500 mb-3 uppercase tracking-wider",
+                                    "Submixes"
+                                }
+                                div { class: "flex gap-3 overflow-x-auto pb-2",
+                                    for ch in channels_data.iter().filter(|c| c.kind == ChannelKind::Submix) {
+                                        { render_channel_strip(ch, &levels_data, true, mixer_config) }
+                                    }
+                                }
+                            }
+                        }
                         // Outputs
                         div { class: "mb-6",
                             h2 { class: "text-xs font-semibold text-zinc-500 mb-3 uppercase tracking-wider",
@@ -335,7 +349,7 @@ fn render_channel_strip(
     rsx! {
         ChannelStrip {
             key: "{ch_id:?}",
-            name: ch.name.clone(),
+            name: crate::display_name(ch),
             volume: ch.volume,
             muted: ch.muted,
             solo: ch.solo,
@@ -8,7 +8,7 @@ use dioxus::prelude::*;
 /// et éviter les re-renders inutiles (comme React.memo).
 #[derive(Props, Clone, PartialEq)]
 pub struct VuMeterProps {
-    /// Niveau RMS (0.0 → 1.0+)
+    /// Niveau RMS placé sur l'échelle des meters du moteur (0.0 → 1.0)
     pub level: f32,
     /// Afficher horizontalement (true) ou verticalement (false)
     #[props(default = false)]
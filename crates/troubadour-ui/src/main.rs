@@ -5,6 +5,25 @@ mod components;
 const TAILWIND_CSS: &str = include_str!("../assets/tailwind.css");
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Le mode terminal passe avant les logs : une ligne de log écrite
+    // sur stdout casserait l'affichage ratatui.
+    if args.first().map(String::as_str) == Some("tui") {
+        std::process::exit(troubadour_tui::run(&args[1..]));
+    }
+    // Config effective et origine de chaque réglage
+    if args.first().map(String::as_str) == Some("config") {
+        std::process::exit(troubadour_tui::print_effective_config(&args[1..]));
+    }
+    // Réglages du mixer en script : même sortie texte, pas de logs
+    if args
+        .first()
+        .is_some_and(|a| troubadour_tui::cli::SUBCOMMANDS.contains(&a.as_str()))
+    {
+        std::process::exit(troubadour_tui::cli::run(&args));
+    }
+
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::from_default_env()
@@ -12,10 +31,33 @@ fn main() {
         )
         .init();
 
+    // Sous-commandes CLI (pas d'interface graphique)
+    if args.first().map(String::as_str) == Some("export-graph") {
+        std::process::exit(export_graph_cli(&args[1..]));
+    }
+    if args.first().map(String::as_str) == Some("presets") {
+        std::process::exit(presets_cli(&args[1..]));
+    }
+    if args.first().map(String::as_str) == Some("render") {
+        std::process::exit(render_cli(&args[1..]));
+    }
+
     tracing::info!("Starting Troubadour...");
 
     let (mut engine, channels) = troubadour_core::engine::Engine::new();
 
+    // Noms d'usine des canaux dans la langue de la config
+    let config = troubadour_shared::config::AppConfig::load(
+        &troubadour_shared::config::AppConfig::default_path(),
+    )
+    .unwrap_or_default();
+    if let Some(code) = config.locale.as_deref()
+        && let Err(e) = engine.set_locale(code)
+    {
+        tracing::warn!("{e}, keeping {}", engine.locale().code());
+    }
+    let _ = LOCALE.set(engine.locale().clone());
+
     match engine.start() {
         Ok(()) => tracing::info!("Audio engine started"),
         Err(e) => tracing::error!("Failed to start audio engine: {e}"),
@@ -25,8 +67,8 @@ fn main() {
     // Pas de clonage du receiver — sinon crossbeam distribue les messages
     // et certaines commandes sont "volées" par le mauvais thread.
     //
-    // Ce thread possède un Mixer local qui synchronise vers le SharedMixerState.
-    // Le SharedMixerState est lu par le callback audio (try_lock).
+    // Ce thread possède un Mixer local qui publie un snapshot runtime
+    // après chaque modification. Le callback audio lit ce snapshot sans lock.
     let shared_mixer = engine.shared_mixer_state();
     // Créer un channel dédié pour les commandes du thread de traitement.
     // L'UI envoie sur `cmd_tx`, le thread lit sur `cmd_rx`.
@@ -90,6 +132,162 @@ fn main() {
         .launch(app);
 }
 
+/// `troubadour export-graph <fichier> [--format json|dot]`
+///
+/// Démarre le moteur pour que le graphe reflète les streams réellement
+/// ouverts, écrit le fichier, puis quitte. Sans `--format`, l'extension
+/// du fichier décide (`.dot`/`.gv` → DOT, sinon JSON).
+fn export_graph_cli(args: &[String]) -> i32 {
+    use troubadour_shared::graph::GraphFormat;
+
+    let Some(path) = args.first().map(std::path::PathBuf::from) else {
+        eprintln!("Usage: troubadour export-graph <file> [--format json|dot]");
+        return 2;
+    };
+
+    let format = match args.iter().position(|a| a == "--format") {
+        Some(idx) => match args.get(idx + 1).map(|f| f.parse::<GraphFormat>()) {
+            Some(Ok(format)) => format,
+            Some(Err(e)) => {
+                eprintln!("{e}");
+                return 2;
+            }
+            None => {
+                eprintln!("--format requires a value (json or dot)");
+                return 2;
+            }
+        },
+        None => path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| ext.parse().ok())
+            .unwrap_or_default(),
+    };
+
+    let (mut engine, _channels) = troubadour_core::engine::Engine::new();
+    if let Err(e) = engine.start() {
+        tracing::warn!("Engine not started, exporting configured graph only: {e}");
+    }
+
+    let graph = engine.export_signal_graph();
+    match graph.save(&path, format) {
+        Ok(()) => {
+            println!("Signal graph written to {}", path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to write {}: {e}", path.display());
+            1
+        }
+    }
+}
+
+/// `troubadour presets diff <nom>`
+///
+/// Affiche ce que changerait le profil `<nom>` par rapport à la
+/// dernière session sauvée (à défaut, le profil par défaut), une
+/// ligne par différence. Rien n'est appliqué.
+fn presets_cli(args: &[String]) -> i32 {
+    use troubadour_shared::profile::Profile;
+
+    let (Some("diff"), Some(name)) = (args.first().map(String::as_str), args.get(1)) else {
+        eprintln!("Usage: troubadour presets diff <name>");
+        return 2;
+    };
+
+    let dir = Profile::profiles_dir();
+    let current = Profile::find(troubadour_tui::LAST_SESSION, &dir)
+        .map(|(profile, _)| profile)
+        .unwrap_or_else(|_| Profile::default_profile());
+    let preset = match Profile::find(name, &dir) {
+        Ok((preset, warnings)) => {
+            for warning in warnings {
+                eprintln!("Warning: {warning}");
+            }
+            preset
+        }
+        Err(e) => {
+            eprintln!("Preset \"{name}\": {e}");
+            return 1;
+        }
+    };
+
+    let diff = current.mixer.diff(&preset.mixer);
+    if diff.is_empty() {
+        println!("\"{}\" matches \"{}\"", preset.name, current.name);
+    }
+    for entry in &diff.entries {
+        println!("{entry}");
+    }
+    0
+}
+
+/// `troubadour render --preset <nom> --input <canal>=<in.wav>... --out <bus>=<out.wav>...`
+///
+/// Joue le profil hors ligne sur des fichiers WAV, sans ouvrir de
+/// device : pour écouter l'effet d'un preset sur un enregistrement,
+/// ou comparer deux rendus. Les canaux sont désignés par nom ou par id.
+fn render_cli(args: &[String]) -> i32 {
+    use troubadour_shared::profile::Profile;
+
+    const USAGE: &str = "Usage: troubadour render --preset <name> --input <channel>=<file.wav>... --out <bus>=<file.wav>...";
+
+    let mut preset = None;
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let Some(value) = iter.next() else {
+            eprintln!("{flag} requires a value\n{USAGE}");
+            return 2;
+        };
+        let target = match flag.as_str() {
+            "--preset" => {
+                preset = Some(value.clone());
+                continue;
+            }
+            "--input" => &mut inputs,
+            "--out" => &mut outputs,
+            _ => {
+                eprintln!("Unknown option {flag}\n{USAGE}");
+                return 2;
+            }
+        };
+        let Some((channel, path)) = value.split_once('=') else {
+            eprintln!("Expected <channel>=<file.wav>, got \"{value}\"");
+            return 2;
+        };
+        target.push((channel.to_string(), std::path::PathBuf::from(path)));
+    }
+    let Some(preset) = preset.filter(|_| !inputs.is_empty() && !outputs.is_empty()) else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+
+    let profile = match Profile::find(&preset, &Profile::profiles_dir()) {
+        Ok((profile, warnings)) => {
+            for warning in warnings {
+                eprintln!("Warning: {warning}");
+            }
+            profile
+        }
+        Err(e) => {
+            eprintln!("Preset \"{preset}\": {e}");
+            return 1;
+        }
+    };
+    match troubadour_core::render::render_files(&profile, &inputs, &outputs) {
+        Ok(frames) => {
+            println!("Rendered {frames} frames with \"{}\"", profile.name);
+            0
+        }
+        Err(e) => {
+            eprintln!("Render failed: {e}");
+            1
+        }
+    }
+}
+
 // Sender dédié pour les commandes UI → thread de traitement
 static CMD_TX: std::sync::RwLock<
     Option<crossbeam_channel::Sender<troubadour_shared::messages::Command>>,
@@ -126,6 +324,17 @@ pub fn update_dsp(preset: &troubadour_shared::dsp::EffectsPreset) {
     }
 }
 
+// Langue des noms d'usine, fixée au démarrage
+static LOCALE: std::sync::OnceLock<troubadour_shared::locale::Locale> = std::sync::OnceLock::new();
+
+/// Nom affiché d'un canal (nom d'usine traduit, nom choisi tel quel).
+pub fn display_name(channel: &troubadour_shared::mixer::ChannelConfig) -> String {
+    match LOCALE.get() {
+        Some(locale) => locale.display_name(channel),
+        None => channel.name.clone(),
+    }
+}
+
 pub fn try_recv_event() -> Option<troubadour_shared::messages::Event> {
     if let Ok(guard) = EVENT_RX.read()
         && let Some(rx) = guard.as_ref()